@@ -0,0 +1,153 @@
+#!/usr/bin/env python3
+"""基准结果基线管理
+
+在 src-tauri 下运行 `cargo bench -p inspirai-ctp-core --bench md_hot_path --bench logging_hot_path --bench log_search` 后：
+
+    python3 scripts/bench_baseline.py check            # 与基线比较，退化超过阈值时退出码为 1
+    python3 scripts/bench_baseline.py save             # 用本次结果覆盖基线
+    python3 scripts/bench_baseline.py check --threshold 0.25
+
+比较中位数而非均值，单次离群采样不影响判断。每项基准的容忍度取阈值与
+`noise_sigmas` 倍基线相对标准差中的较大者：抖动大的基准（如脱敏正则，标准差
+约为中位数的 15-20%）不会因正常波动误报，稳定的基准仍按阈值把关。
+
+基线中的 `ratios` 记录成对基准的加速比（慢者中位数 / 快者中位数），如日志预筛选
+检索相对逐行解析须保持 5 倍以上；save 时写入实测值，check 时低于 `min` 视为退化。
+
+基线只在同一台发布机上比较才有意义，换机器后先 save 再提交。
+"""
+
+import argparse
+import json
+import platform
+import sys
+from datetime import datetime, timezone
+from pathlib import Path
+
+ROOT = Path(__file__).resolve().parent.parent / "src-tauri"
+CRITERION_DIR = ROOT / "target" / "criterion"
+BASELINE_FILE = ROOT / "crates" / "inspirai-ctp-core" / "benches" / "baseline.json"
+DEFAULT_THRESHOLD = 0.15
+# 容忍度至少为基线相对标准差的倍数
+DEFAULT_NOISE_SIGMAS = 2.0
+# 须保持的加速比
+DEFAULT_RATIOS = {
+    "log_search": {"slow": "log_search/line_by_line", "fast": "log_search/query_engine", "min": 5.0},
+}
+
+
+def collect_results():
+    """读取 criterion 最近一次运行的结果，键为 full_id"""
+    results = {}
+    for benchmark_file in CRITERION_DIR.glob("**/new/benchmark.json"):
+        estimates_file = benchmark_file.with_name("estimates.json")
+        if not estimates_file.exists():
+            continue
+        benchmark = json.loads(benchmark_file.read_text())
+        estimates = json.loads(estimates_file.read_text())
+        results[benchmark["full_id"]] = {
+            "mean_ns": round(estimates["mean"]["point_estimate"], 2),
+            "median_ns": round(estimates["median"]["point_estimate"], 2),
+            "std_dev_ns": round(estimates["std_dev"]["point_estimate"], 2),
+        }
+    return dict(sorted(results.items()))
+
+
+def tolerance(base, threshold, noise_sigmas):
+    """允许的中位数退化比例"""
+    noise = base["std_dev_ns"] / base["median_ns"] if base["median_ns"] > 0 else 0.0
+    return max(threshold, noise_sigmas * noise)
+
+
+def measure_ratios(results, ratios):
+    """计算各加速比，缺少任一基准时为 None"""
+    measured = {}
+    for name, spec in ratios.items():
+        slow, fast = results.get(spec["slow"]), results.get(spec["fast"])
+        measured[name] = round(slow["median_ns"] / fast["median_ns"], 2) if slow and fast else None
+    return measured
+
+
+def save(results, threshold):
+    ratios = DEFAULT_RATIOS
+    if BASELINE_FILE.exists():
+        ratios = json.loads(BASELINE_FILE.read_text()).get("ratios", DEFAULT_RATIOS)
+    ratios = {name: {k: v for k, v in spec.items() if k != "measured"} for name, spec in ratios.items()}
+    for name, value in measure_ratios(results, ratios).items():
+        if value is not None:
+            ratios[name]["measured"] = value
+            print(f"  加速比  {name}: {value:.1f}x")
+    baseline = {
+        "generated_at": datetime.now(timezone.utc).isoformat(timespec="seconds"),
+        "machine": f"{platform.system()} {platform.machine()} {platform.processor() or ''}".strip(),
+        "threshold": threshold,
+        "noise_sigmas": DEFAULT_NOISE_SIGMAS,
+        "ratios": ratios,
+        "benchmarks": results,
+    }
+    BASELINE_FILE.write_text(json.dumps(baseline, indent=2, ensure_ascii=False) + "\n")
+    print(f"已写入基线 {BASELINE_FILE}（{len(results)} 项）")
+    return 0
+
+
+def check(results, threshold):
+    baseline = json.loads(BASELINE_FILE.read_text())
+    threshold = threshold if threshold is not None else baseline.get("threshold", DEFAULT_THRESHOLD)
+    noise_sigmas = baseline.get("noise_sigmas", DEFAULT_NOISE_SIGMAS)
+    regressions = []
+    for name, current in results.items():
+        base = baseline["benchmarks"].get(name)
+        if base is None:
+            print(f"  新增    {name}: {current['median_ns']:.1f} ns（无基线）")
+            continue
+        change = current["median_ns"] / base["median_ns"] - 1.0
+        allowed = tolerance(base, threshold, noise_sigmas)
+        flag = "退化" if change > allowed else "正常"
+        print(
+            f"  {flag}    {name}: {base['median_ns']:.1f} -> {current['median_ns']:.1f} ns "
+            f"({change:+.1%}，容忍 {allowed:.0%})"
+        )
+        if change > allowed:
+            regressions.append(name)
+
+    missing = sorted(set(baseline["benchmarks"]) - set(results))
+    for name in missing:
+        print(f"  未运行  {name}")
+
+    ratios = baseline.get("ratios", {})
+    for name, value in measure_ratios(results, ratios).items():
+        if value is None:
+            print(f"  未运行  {name}（加速比）")
+            continue
+        spec = ratios[name]
+        flag = "退化" if value < spec["min"] else "正常"
+        recorded = f"，基线 {spec['measured']:.1f}x" if "measured" in spec else ""
+        print(f"  {flag}    {name}: 加速比 {value:.1f}x（要求 ≥{spec['min']:.0f}x{recorded}）")
+        if value < spec["min"]:
+            regressions.append(name)
+
+    if regressions:
+        print(f"{len(regressions)} 项基准退化超过容忍度")
+        return 1
+    print("未发现性能退化")
+    return 0
+
+
+def main():
+    parser = argparse.ArgumentParser(description="criterion 基线保存与比较")
+    parser.add_argument("command", choices=["save", "check"])
+    parser.add_argument("--threshold", type=float, default=None, help="允许的中位数退化比例下限，默认取基线文件中的值")
+    args = parser.parse_args()
+
+    results = collect_results()
+    if not results:
+        print(f"未找到基准结果，请先在 {ROOT} 下运行 cargo bench", file=sys.stderr)
+        return 2
+
+    if args.command == "save":
+        return save(results, args.threshold if args.threshold is not None else DEFAULT_THRESHOLD)
+    return check(results, args.threshold)
+
+
+if __name__ == "__main__":
+    sys.exit(main())
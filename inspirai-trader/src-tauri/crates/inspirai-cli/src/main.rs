@@ -0,0 +1,362 @@
+//! 本地数据与日志的命令行管理工具
+//!
+//! 在未运行界面的服务器上查询时间线（报单/成交）、按查询语言检索日志、
+//! 导出归档行情、校验日志完整性、手动压缩行情和在存储后端间迁移数据。目录参数默认与应用一致，
+//! 需在应用的工作目录（src-tauri）下运行或显式指定
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use inspirai_ctp_core::ctp::{
+    migrate_storage, open_backend, CompactionConfig, ExportFormat, MarketDataExportRequest, MarketDataExporter,
+    StorageBackendKind, StorageConfig, StorageDataKind, StorageGranularity, TickCompactor, Timeline, TimelineKind,
+    TimelineQuery, DEFAULT_ARCHIVE_DIR, DEFAULT_RAW_DIR, DEFAULT_STORAGE_CONFIG_FILE, DEFAULT_TIMELINE_DIR,
+};
+use inspirai_ctp_core::logging::{HumanReadableFormatter, LogConfig, LogFormatter, LogQuery, LogQueryEngine, TimeRange};
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(name = "inspirai-cli", version, about = "本地数据与日志管理工具")]
+struct Cli {
+    /// 以 JSON 输出结果，便于脚本处理
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 查询账户时间线中的报单、成交等记录（按时间倒序）
+    Timeline {
+        #[arg(long)]
+        account: String,
+        #[arg(long, default_value = DEFAULT_TIMELINE_DIR)]
+        dir: PathBuf,
+        /// 只看指定类别，可重复
+        #[arg(long, value_enum)]
+        kind: Vec<TimelineKind>,
+        #[arg(long)]
+        instrument: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// 分页游标，取上一页输出的 next_before
+        #[arg(long)]
+        before: Option<u64>,
+    },
+    /// 按查询语言检索日志，如 "level>=warn AND module:ctp AND last 2h"
+    Logs {
+        query: String,
+        #[arg(long, default_value = "./logs")]
+        log_dir: PathBuf,
+        /// 覆盖查询中的条数上限
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// 导出归档行情为 CSV 或 parquet
+    Export {
+        /// 合约代码，可重复
+        #[arg(long = "instrument", required = true)]
+        instruments: Vec<String>,
+        /// 起始交易日（含），如 2024-01-15
+        #[arg(long)]
+        start: NaiveDate,
+        /// 结束交易日（含）
+        #[arg(long)]
+        end: NaiveDate,
+        #[arg(long, value_enum, default_value = "bar1m")]
+        granularity: StorageGranularity,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+        #[arg(long, short)]
+        output: PathBuf,
+        /// 日内起始时间 HH:MM:SS（含）
+        #[arg(long)]
+        start_time: Option<String>,
+        #[arg(long)]
+        end_time: Option<String>,
+        #[arg(long, default_value = DEFAULT_RAW_DIR)]
+        raw_dir: PathBuf,
+        #[arg(long, default_value = DEFAULT_ARCHIVE_DIR)]
+        archive_dir: PathBuf,
+    },
+    /// 按校验清单检查已轮转/归档日志，有缺失或被修改的文件时退出码为 2
+    VerifyLogs {
+        #[arg(long, default_value = "./logs")]
+        log_dir: PathBuf,
+        /// 只校验最近 N 小时内的文件
+        #[arg(long)]
+        last_hours: Option<i64>,
+    },
+    /// 压缩指定交易日的原始行情
+    Compact {
+        trading_day: NaiveDate,
+        #[arg(long, default_value = DEFAULT_RAW_DIR)]
+        raw_dir: PathBuf,
+        #[arg(long, default_value = DEFAULT_ARCHIVE_DIR)]
+        archive_dir: PathBuf,
+    },
+    /// 在存储后端之间复制数据，可重复执行；完成后修改存储配置切换后端
+    MigrateStorage {
+        #[arg(long, value_enum)]
+        from: StorageBackendKind,
+        #[arg(long, value_enum)]
+        to: StorageBackendKind,
+        /// 只迁移指定类别，可重复，默认全部
+        #[arg(long, value_enum)]
+        data: Vec<StorageDataKind>,
+        /// 从中读取各后端的数据库文件路径
+        #[arg(long, default_value = DEFAULT_STORAGE_CONFIG_FILE)]
+        config: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    // 诊断日志输出到 stderr，不干扰结果
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, String> {
+    let json = cli.json;
+    match cli.command {
+        Command::Timeline { account, dir, kind, instrument, limit, before } => {
+            let timeline = Timeline::open(&dir, &account).map_err(|e| format!("打开时间线失败: {}", e))?;
+            let page = timeline.page(&TimelineQuery {
+                before,
+                limit: Some(limit),
+                kinds: (!kind.is_empty()).then_some(kind),
+                instrument_id: instrument,
+            });
+            if json {
+                return print_json(&page);
+            }
+            for entry in &page.entries {
+                println!(
+                    "{:>6} {} {:<10} {:<8} {}{}",
+                    entry.seq,
+                    entry.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+                    format!("{:?}", entry.kind),
+                    entry.instrument_id.as_deref().unwrap_or("-"),
+                    entry.title,
+                    entry.detail.as_deref().map(|d| format!(" ({})", d)).unwrap_or_default(),
+                );
+            }
+            match page.next_before {
+                Some(next) => println!("还有更多记录，使用 --before {} 翻页", next),
+                None if page.entries.is_empty() => println!("无记录"),
+                None => {}
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Logs { query, log_dir, limit } => {
+            let mut query = LogQuery::parse(&query).map_err(|e| format!("解析查询失败: {}", e))?;
+            if let Some(limit) = limit {
+                query.limit = limit;
+            }
+            let engine = LogQueryEngine::new(log_config(log_dir)).map_err(|e| format!("创建查询引擎失败: {}", e))?;
+            let result = runtime()?
+                .block_on(engine.query(query))
+                .map_err(|e| format!("查询日志失败: {}", e))?;
+            if json {
+                return print_json(&result);
+            }
+            let formatter = HumanReadableFormatter::new();
+            for entry in &result.entries {
+                match formatter.format(entry) {
+                    Ok(line) => println!("{}", line.trim_end()),
+                    Err(e) => eprintln!("格式化日志失败: {}", e),
+                }
+            }
+            println!(
+                "共 {}{} 条，显示 {} 条，搜索 {} 个文件，耗时 {}ms",
+                result.total_found,
+                if result.is_estimate { "+" } else { "" },
+                result.entries.len(),
+                result.files_searched,
+                result.execution_time_ms,
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Export {
+            instruments,
+            start,
+            end,
+            granularity,
+            format,
+            output,
+            start_time,
+            end_time,
+            raw_dir,
+            archive_dir,
+        } => {
+            let request = MarketDataExportRequest {
+                instruments,
+                start_date: start,
+                end_date: end,
+                granularity,
+                format,
+                path: output,
+                start_time,
+                end_time,
+            };
+            let store = TickCompactor::new(CompactionConfig::new(raw_dir, archive_dir));
+            let summary = MarketDataExporter::new(&store)
+                .export(&request, |progress| {
+                    eprintln!(
+                        "[{}/{}] {} {} 已写入 {} 行",
+                        progress.completed, progress.total, progress.instrument_id, progress.trading_day, progress.rows_written
+                    );
+                })
+                .map_err(|e| format!("导出行情失败: {}", e))?;
+            if json {
+                return print_json(&summary);
+            }
+            println!(
+                "已导出 {} 行（{} 个交易日）到 {}",
+                summary.rows,
+                summary.trading_days,
+                summary.path.display()
+            );
+            if !summary.missing_instruments.is_empty() {
+                println!("无数据的合约: {}", summary.missing_instruments.join(", "));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::VerifyLogs { log_dir, last_hours } => {
+            let engine = LogQueryEngine::new(log_config(log_dir)).map_err(|e| format!("创建查询引擎失败: {}", e))?;
+            let report = runtime()?
+                .block_on(engine.verify_integrity(last_hours.map(TimeRange::last_hours)))
+                .map_err(|e| format!("校验日志完整性失败: {}", e))?;
+            let intact = report.is_intact();
+            if json {
+                print_json(&report)?;
+            } else {
+                println!("检查 {} 个文件，校验通过 {} 个", report.files_checked, report.verified);
+                for issue in &report.issues {
+                    println!("  {:<10} {}", format!("{:?}", issue.status), issue.path.display());
+                }
+                if intact {
+                    println!("日志完整");
+                }
+            }
+            Ok(if intact { ExitCode::SUCCESS } else { ExitCode::from(2) })
+        }
+        Command::Compact { trading_day, raw_dir, archive_dir } => {
+            let store = TickCompactor::new(CompactionConfig::new(raw_dir, archive_dir));
+            let report = store
+                .run_with_progress(trading_day, |done, total| eprintln!("[{}/{}] 压缩中", done, total))
+                .map_err(|e| format!("压缩行情失败: {}", e))?;
+            if json {
+                return print_json(&report);
+            }
+            println!(
+                "压缩 {} 个合约 {} 行，{} -> {} 字节",
+                report.instruments, report.rows, report.raw_bytes, report.compressed_bytes
+            );
+            for error in &report.errors {
+                println!("  失败: {}", error);
+            }
+            Ok(if report.errors.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        }
+        Command::MigrateStorage { from, to, data, config } => {
+            if from == to {
+                return Err("源后端与目标后端相同".to_string());
+            }
+            let config = StorageConfig::load(&config).map_err(|e| format!("读取存储配置失败: {}", e))?;
+            let source = open_backend(from, config.path_for(from)).map_err(|e| format!("打开源后端失败: {}", e))?;
+            let target = open_backend(to, config.path_for(to)).map_err(|e| format!("打开目标后端失败: {}", e))?;
+            let report = migrate_storage(source.as_ref(), target.as_ref(), &data, |key| {
+                eprintln!("迁移 {} {} {}", key.trading_day, key.instrument_id, key.granularity.dir_name());
+            })
+            .map_err(|e| format!("迁移数据失败: {}", e))?;
+            if json {
+                return print_json(&report);
+            }
+            println!(
+                "已迁移报单 {} 条、成交 {} 条、tick {} 组 {} 条、K 线 {} 组 {} 条",
+                report.orders, report.trades, report.tick_series, report.ticks, report.bar_series, report.bars
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn log_config(output_dir: PathBuf) -> LogConfig {
+    LogConfig {
+        output_dir,
+        console_output: false,
+        ..LogConfig::default()
+    }
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime, String> {
+    tokio::runtime::Runtime::new().map_err(|e| format!("创建异步运行时失败: {}", e))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<ExitCode, String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| format!("序列化结果失败: {}", e))?;
+    println!("{}", text);
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_export_and_timeline_args() {
+        let cli = Cli::try_parse_from([
+            "inspirai-cli", "export", "--instrument", "rb2501", "--instrument", "hc2501",
+            "--start", "2024-01-15", "--end", "2024-01-19", "--granularity", "bar5m", "--format", "parquet", "-o", "out.parquet",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Export { instruments, granularity, format, .. } => {
+                assert_eq!(instruments, vec!["rb2501", "hc2501"]);
+                assert_eq!(granularity, StorageGranularity::Bar5m);
+                assert_eq!(format, ExportFormat::Parquet);
+            }
+            other => panic!("解析为错误的子命令: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["inspirai-cli", "--json", "timeline", "--account", "00001", "--kind", "trade"]).unwrap();
+        assert!(cli.json);
+        assert!(matches!(cli.command, Command::Timeline { ref kind, .. } if kind == &vec![TimelineKind::Trade]));
+
+        assert!(Cli::try_parse_from(["inspirai-cli", "export", "--start", "2024-01-15"]).is_err());
+
+        let cli = Cli::try_parse_from([
+            "inspirai-cli", "migrate-storage", "--from", "sqlite", "--to", "duckdb", "--data", "ticks", "--data", "bars",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::MigrateStorage { from: StorageBackendKind::Sqlite, to: StorageBackendKind::Duckdb, ref data, .. }
+                if data == &vec![StorageDataKind::Ticks, StorageDataKind::Bars]
+        ));
+    }
+}
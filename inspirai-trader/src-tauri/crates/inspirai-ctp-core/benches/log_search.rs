@@ -0,0 +1,111 @@
+//! 日志检索基准：旧的逐行解析与 `LogQueryEngine` 预筛选检索对比
+//!
+//! cargo bench -p inspirai-ctp-core --bench log_search
+//!
+//! 生成 16 MB 的 JSON 与文本混合日志，搜索一个低频关键字。两者的中位数之比
+//! 记录在 `baseline.json` 的 `ratios` 中，`scripts/bench_baseline.py check`
+//! 在加速比低于 5 倍时报告退化
+
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use inspirai_ctp_core::logging::{LogConfig, LogQuery, LogQueryEngine, LogType};
+use regex::Regex;
+
+const LOG_BYTES: u64 = 16 * 1024 * 1024;
+const KEYWORD: &str = "RB2501-REJECT";
+
+/// 写入约 `target_bytes` 字节的日志，每 1000 行插入一条含关键字的记录
+fn generate_log(path: &std::path::Path, target_bytes: u64) -> std::io::Result<u64> {
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    let mut written = 0u64;
+    let mut lines = 0u64;
+    while written < target_bytes {
+        let line = if lines % 1000 == 999 {
+            format!(
+                r#"{{"timestamp":"2024-01-15T10:30:45.123Z","level":"WARN","module":"ctp::trader","message":"order {} rb2501-reject by exchange","order_ref":"{}"}}"#,
+                lines, lines
+            )
+        } else if lines % 2 == 0 {
+            format!(
+                r#"{{"timestamp":"2024-01-15T10:30:45.123Z","level":"INFO","module":"ctp::md","message":"tick received seq {}","instrument":"rb2501","last_price":3500.0}}"#,
+                lines
+            )
+        } else {
+            format!("2024-01-15 18:30:45.123 [INFO ] [trading_service] heartbeat ok seq {}", lines)
+        };
+        writeln!(writer, "{}", line)?;
+        written += line.len() as u64 + 1;
+        lines += 1;
+    }
+    writer.flush()?;
+    Ok(lines)
+}
+
+/// 旧实现：每行完整解析，文本行每次编译正则，关键字整串转小写比较
+fn line_by_line_search(path: &std::path::Path, keyword: &str) -> std::io::Result<usize> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut hits = 0;
+    for line in reader.lines() {
+        let line = line?;
+        let text = if line.trim().starts_with('{') {
+            match serde_json::from_str::<serde_json::Value>(&line) {
+                Ok(json) => {
+                    let message = json.get("message").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let fields = json
+                        .as_object()
+                        .map(|obj| obj.values().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))
+                        .unwrap_or_default();
+                    format!("{} {}", message, fields)
+                }
+                Err(_) => continue,
+            }
+        } else {
+            let re = Regex::new(r"(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{3}) \[(\w+)\s*\] \[([^\]]+)\] (.*)").unwrap();
+            match re.captures(&line) {
+                Some(captures) => captures.get(4).unwrap().as_str().to_string(),
+                None => continue,
+            }
+        };
+        if text.to_lowercase().contains(&keyword.to_lowercase()) {
+            hits += 1;
+        }
+    }
+    Ok(hits)
+}
+
+fn bench_log_search(c: &mut Criterion) {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config = LogConfig {
+        output_dir: temp_dir.path().to_path_buf(),
+        ..LogConfig::development()
+    };
+    config.ensure_directories().unwrap();
+    let path = config.get_log_file_path(LogType::App);
+    generate_log(&path, LOG_BYTES).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let engine = LogQueryEngine::new(config).unwrap();
+    let query = LogQuery::new().with_keyword(KEYWORD).with_limit(10_000);
+
+    // 两种方式命中数一致才有比较意义
+    let expected = line_by_line_search(&path, KEYWORD).unwrap();
+    let found = runtime.block_on(engine.query(query.clone())).unwrap().entries.len();
+    assert_eq!(expected, found);
+
+    let mut group = c.benchmark_group("log_search");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+    group.throughput(Throughput::Bytes(LOG_BYTES));
+    group.bench_function("line_by_line", |b| {
+        b.iter(|| line_by_line_search(black_box(&path), KEYWORD).unwrap())
+    });
+    group.bench_function("query_engine", |b| {
+        b.iter(|| runtime.block_on(engine.query(black_box(query.clone()))).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_log_search);
+criterion_main!(benches);
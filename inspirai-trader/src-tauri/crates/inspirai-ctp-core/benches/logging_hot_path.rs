@@ -0,0 +1,77 @@
+//! 日志热路径基准：各格式化器吞吐、脱敏正则
+//!
+//! cargo bench -p inspirai-ctp-core --bench logging_hot_path
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use inspirai_ctp_core::logging::{
+    CompactFormatter, DataMasker, HumanReadableFormatter, JsonFormatter, LogContext, LogEntry, LogFormatter, LogLevel,
+};
+
+fn market_entry() -> LogEntry {
+    let mut context = LogContext::new(LogLevel::Info, "ctp::md_spi");
+    context.request_id = Some("req_1024".to_string());
+    context.session_id = Some("sess_7".to_string());
+
+    let mut fields = HashMap::new();
+    fields.insert("instrument_id".to_string(), serde_json::json!("rb2501"));
+    fields.insert("last_price".to_string(), serde_json::json!(3512.0));
+    fields.insert("volume".to_string(), serde_json::json!(123456));
+    fields.insert("bid_price1".to_string(), serde_json::json!(3511.0));
+    fields.insert("ask_price1".to_string(), serde_json::json!(3513.0));
+
+    LogEntry {
+        timestamp: chrono::Utc::now(),
+        level: LogLevel::Info,
+        module: "ctp::md_spi".to_string(),
+        thread_id: "md-callback".to_string(),
+        message: "收到行情 rb2501 3512.0".to_string(),
+        context,
+        request_id: Some("req_1024".to_string()),
+        session_id: Some("sess_7".to_string()),
+        fields,
+    }
+}
+
+fn bench_formatters(c: &mut Criterion) {
+    let entry = market_entry();
+    let formatters: Vec<Box<dyn LogFormatter>> = vec![
+        Box::new(JsonFormatter::new()),
+        Box::new(HumanReadableFormatter::new()),
+        Box::new(CompactFormatter::new()),
+    ];
+
+    let mut group = c.benchmark_group("formatter");
+    group.throughput(Throughput::Elements(1));
+    for formatter in &formatters {
+        group.bench_function(formatter.name(), |b| b.iter(|| formatter.format(black_box(&entry)).unwrap()));
+    }
+    group.finish();
+}
+
+fn bench_masker(c: &mut Criterion) {
+    let masker = DataMasker::new();
+    let plain = "订单已提交 instrument=rb2501 price=3512.0 volume=2 order_ref=000123";
+    let sensitive = "登录请求 user_id=081234 password=Abc12345 phone=13812345678 id_card=110101199001011234";
+
+    let mut group = c.benchmark_group("masker");
+    group.throughput(Throughput::Bytes(plain.len() as u64));
+    group.bench_function("mask_text_plain", |b| b.iter(|| masker.mask_text(black_box(plain))));
+    group.throughput(Throughput::Bytes(sensitive.len() as u64));
+    group.bench_function("mask_text_sensitive", |b| b.iter(|| masker.mask_text(black_box(sensitive))));
+
+    let entry = market_entry();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("mask_log_entry", |b| {
+        b.iter_batched(
+            || entry.clone(),
+            |mut entry| masker.mask_log_entry(&mut entry).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_formatters, bench_masker);
+criterion_main!(benches);
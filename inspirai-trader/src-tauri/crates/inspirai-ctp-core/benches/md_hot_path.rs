@@ -0,0 +1,147 @@
+//! 行情热路径基准：tick 转换、多窗口事件分发、秒级 K 线聚合
+//!
+//! cargo bench -p inspirai-ctp-core --bench md_hot_path
+//!
+//! `tick_conversion/convert_depth_quote` 只用字符数组和数值字段，不构造 CTP 结构，
+//! 在没有 CTP SDK 的机器上也能与基线比较
+
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ctp2rs::ffi::AssignFromString;
+use ctp2rs::v1alpha1::CThostFtdcDepthMarketDataField;
+use inspirai_ctp_core::ctp::tick_compaction::downsample_to_seconds;
+use inspirai_ctp_core::ctp::{
+    CtpEvent, DataConverter, DepthQuote, EventBridge, EventTopic, MarketDataTick, WindowSubscription,
+};
+
+/// 与 CTP 字符数组同长，末尾补 0
+fn c_chars<const N: usize>(value: &str) -> [i8; N] {
+    let mut chars = [0i8; N];
+    for (dst, src) in chars.iter_mut().zip(value.bytes()) {
+        *dst = src as i8;
+    }
+    chars
+}
+
+fn depth_field(instrument_id: &str, last_price: f64) -> CThostFtdcDepthMarketDataField {
+    let mut field = CThostFtdcDepthMarketDataField::default();
+    field.InstrumentID.assign_from_str(instrument_id);
+    field.UpdateTime.assign_from_str("09:30:15");
+    field.UpdateMillisec = 500;
+    field.LastPrice = last_price;
+    field.PreClosePrice = last_price - 12.0;
+    field.OpenPrice = last_price - 5.0;
+    field.HighestPrice = last_price + 20.0;
+    field.LowestPrice = last_price - 30.0;
+    field.Volume = 123_456;
+    field.Turnover = last_price * 123_456.0 * 10.0;
+    field.OpenInterest = 1_500_000.0;
+    field.BidPrice1 = last_price - 1.0;
+    field.BidVolume1 = 35;
+    field.AskPrice1 = last_price + 1.0;
+    field.AskVolume1 = 42;
+    field
+}
+
+fn tick(instrument_id: &str, seq: usize) -> MarketDataTick {
+    let last_price = 3500.0 + (seq % 17) as f64;
+    MarketDataTick {
+        instrument_id: instrument_id.to_string(),
+        last_price,
+        volume: seq as i64 * 3,
+        turnover: seq as f64 * 3.0 * last_price * 10.0,
+        open_interest: 1_500_000 + seq as i64,
+        bid_price1: last_price - 1.0,
+        bid_volume1: 10,
+        ask_price1: last_price + 1.0,
+        ask_volume1: 12,
+        // 每秒两笔，与交易所快照频率一致
+        update_time: format!("{:02}:{:02}:{:02}", 9 + seq / 7200, (seq / 120) % 60, (seq / 2) % 60),
+        update_millisec: if seq % 2 == 0 { 0 } else { 500 },
+        change_percent: 0.0,
+        change_amount: 0.0,
+        open_price: 3500.0,
+        highest_price: 3520.0,
+        lowest_price: 3480.0,
+        pre_close_price: 3490.0,
+        price_limit: None,
+        trace: None,
+        source: None,
+    }
+}
+
+fn bench_tick_conversion(c: &mut Criterion) {
+    let field = depth_field("rb2501", 3500.0);
+    let instrument_id: [i8; 81] = c_chars("rb2501");
+    let update_time: [i8; 9] = c_chars("09:30:15");
+    let quote = DepthQuote {
+        instrument_id: &instrument_id,
+        update_time: &update_time,
+        update_millisec: 500,
+        last_price: 3500.0,
+        pre_close_price: 3488.0,
+        open_price: 3495.0,
+        highest_price: 3520.0,
+        lowest_price: 3470.0,
+        volume: 123_456,
+        turnover: 3500.0 * 123_456.0 * 10.0,
+        open_interest: 1_500_000.0,
+        bid_price1: 3499.0,
+        bid_volume1: 35,
+        ask_price1: 3501.0,
+        ask_volume1: 42,
+    };
+    let mut group = c.benchmark_group("tick_conversion");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("convert_depth_quote", |b| {
+        b.iter(|| DataConverter::convert_depth_quote(black_box(&quote)).unwrap())
+    });
+    group.bench_function("convert_market_data", |b| {
+        b.iter(|| DataConverter::convert_market_data(black_box(&field)).unwrap())
+    });
+    group.bench_function("convert_depth_snapshot", |b| {
+        b.iter(|| DataConverter::convert_depth_snapshot(black_box(&field)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_event_routing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_routing");
+    group.throughput(Throughput::Elements(1));
+    for windows in [1usize, 4, 16] {
+        let bridge = EventBridge::new();
+        for i in 0..windows {
+            // 一半窗口只看自己的合约，其余全量订阅
+            let subscription = if i % 2 == 0 {
+                WindowSubscription {
+                    topics: HashSet::from([EventTopic::MarketData]),
+                    instruments: Some(HashSet::from([format!("rb25{:02}", i)])),
+                    observer: false,
+                }
+            } else {
+                WindowSubscription::default()
+            };
+            bridge.register_window(&format!("window-{}", i), subscription, None, Vec::new());
+        }
+        let event = CtpEvent::MarketData(tick("rb2500", 0));
+        group.bench_with_input(BenchmarkId::new("dispatch_tick", windows), &event, |b, event| {
+            b.iter(|| bridge.dispatch(black_box(event)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_kline_aggregation(c: &mut Criterion) {
+    // 一个交易小时的 tick
+    let ticks: Vec<MarketDataTick> = (0..7200).map(|i| tick("rb2501", i)).collect();
+    let mut group = c.benchmark_group("kline_aggregation");
+    group.throughput(Throughput::Elements(ticks.len() as u64));
+    group.bench_function("downsample_to_seconds", |b| {
+        b.iter(|| downsample_to_seconds(black_box(&ticks)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_tick_conversion, bench_event_routing, bench_kline_aggregation);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use inspirai_trader_lib::ctp::{
+use inspirai_ctp_core::ctp::{
     ConfigManager, Environment, CtpClient, CtpConfig, 
     init_with_config, LoggerManager, PerformanceMonitor
 };
@@ -21,10 +21,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // 2. 创建扩展配置
-    let extended_config = inspirai_trader_lib::ctp::ExtendedCtpConfig {
+    let extended_config = inspirai_ctp_core::ctp::ExtendedCtpConfig {
         ctp: config.clone(),
-        logging: inspirai_trader_lib::ctp::config_manager::LoggingConfig::for_environment(Environment::SimNow),
-        environment: inspirai_trader_lib::ctp::config_manager::EnvironmentConfig::for_environment(Environment::SimNow),
+        logging: inspirai_ctp_core::ctp::config_manager::LoggingConfig::for_environment(Environment::SimNow),
+        environment: inspirai_ctp_core::ctp::config_manager::EnvironmentConfig::for_environment(Environment::SimNow),
     };
     
     // 3. 初始化组件（包括日志系统）
@@ -1,4 +1,4 @@
-use inspirai_trader_lib::ctp::{
+use inspirai_ctp_core::ctp::{
     client::CtpClient,
     config::CtpConfig,
     events::CtpEvent,
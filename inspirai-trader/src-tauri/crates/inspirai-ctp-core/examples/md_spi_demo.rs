@@ -1,5 +1,5 @@
-use inspirai_trader_lib::ctp::{
-    CtpConfig, Environment, MdSpiImpl, MarketDataManager, 
+use inspirai_ctp_core::ctp::{
+    CtpConfig, ConnectionMode, Environment, MdSpiImpl, MarketDataManager, 
     ClientState, CtpEvent, PriceChangeFilter, VolumeFilter
 };
 use std::sync::{Arc, Mutex};
@@ -19,6 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 创建配置
     let config = CtpConfig {
         environment: Environment::SimNow,
+        connection_mode: ConnectionMode::Full,
         broker_id: "9999".to_string(),
         investor_id: "demo_user".to_string(),
         password: "demo_pass".to_string(),
@@ -32,6 +33,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         timeout_secs: 30,
         reconnect_interval_secs: 5,
         max_reconnect_attempts: 3,
+        market_order_policy: Default::default(),
+        programmatic: Default::default(),
     };
     
     println!("配置信息:");
@@ -87,7 +90,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     sleep(Duration::from_millis(100)).await;
     
     // 这里应该模拟登录响应，但由于结构体定义的限制，我们直接发送登录成功事件
-    let login_response = inspirai_trader_lib::ctp::LoginResponse {
+    let login_response = inspirai_ctp_core::ctp::LoginResponse {
         trading_day: "20241203".to_string(),
         login_time: "09:30:00".to_string(),
         broker_id: config.broker_id.clone(),
@@ -218,8 +221,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// 创建示例行情数据
-fn create_sample_tick(instrument_id: &str, price: f64, volume: i64, time: &str) -> inspirai_trader_lib::ctp::MarketDataTick {
-    inspirai_trader_lib::ctp::MarketDataTick {
+fn create_sample_tick(instrument_id: &str, price: f64, volume: i64, time: &str) -> inspirai_ctp_core::ctp::MarketDataTick {
+    inspirai_ctp_core::ctp::MarketDataTick {
         instrument_id: instrument_id.to_string(),
         last_price: price,
         volume,
@@ -237,5 +240,8 @@ fn create_sample_tick(instrument_id: &str, price: f64, volume: i64, time: &str)
         highest_price: price + 5.0,
         lowest_price: price - 5.0,
         pre_close_price: price - 1.0,
+        price_limit: None,
+        trace: None,
+        source: None,
     }
 }
\ No newline at end of file
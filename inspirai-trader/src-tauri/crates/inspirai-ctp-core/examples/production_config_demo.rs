@@ -1,4 +1,4 @@
-use inspirai_trader_lib::ctp::{
+use inspirai_ctp_core::ctp::{
     ConfigManager, Environment, CtpClient, CtpConfig, 
     init_with_config, LoggerManager, PerformanceMonitor
 };
@@ -1,4 +1,4 @@
-use inspirai_trader::ctp::{
+use inspirai_ctp_core::ctp::{
     CtpClient, CtpConfig, Environment, QueryService, QueryOptions, QueryType,
     CtpEvent, EventHandler, DefaultEventListener, EventListener,
 };
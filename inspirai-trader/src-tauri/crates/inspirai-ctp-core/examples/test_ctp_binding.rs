@@ -1,5 +1,5 @@
 // 测试 CTP API 绑定是否正确
-use inspirai_trader_lib::ctp::{ffi, ctp_sys};
+use inspirai_ctp_core::ctp::{ffi, ctp_sys};
 use std::ffi::CString;
 
 fn main() {
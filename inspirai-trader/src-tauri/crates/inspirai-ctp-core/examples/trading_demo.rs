@@ -1,4 +1,4 @@
-use inspirai_trader_lib::ctp::{
+use inspirai_ctp_core::ctp::{
     CtpClient, CtpConfig, Environment,
     models::{OrderRequest, OrderDirection, OffsetFlag, OrderType, TimeCondition, LoginCredentials},
     trading_service::TradingService,
@@ -75,7 +75,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 创建交易服务
     let client_state = Arc::new(std::sync::Mutex::new(
-        inspirai_trader_lib::ctp::ClientState::LoggedIn
+        inspirai_ctp_core::ctp::ClientState::LoggedIn
     ));
     let (event_sender, mut event_receiver) = tokio::sync::mpsc::unbounded_channel();
     
@@ -0,0 +1,21 @@
+#![no_main]
+
+//! 日志行解析：截断的 JSON、类型错乱的字段、非 UTF-8 内容和超长行都不能 panic
+//!
+//! cargo +nightly fuzz run log_line_parser
+
+use inspirai_ctp_core::logging::LogQueryEngine;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // 与检索时一致：按 \n 切行，非 UTF-8 的行跳过
+    for (i, line) in data.split(|&b| b == b'\n').enumerate() {
+        if let Ok(line) = std::str::from_utf8(line) {
+            let _ = LogQueryEngine::parse_log_line(line.trim_end_matches('\r'), i + 1);
+        }
+    }
+
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(data) {
+        let _ = LogQueryEngine::parse_json_log_entry(&json);
+    }
+});
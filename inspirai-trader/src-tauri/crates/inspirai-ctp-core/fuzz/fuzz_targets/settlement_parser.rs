@@ -0,0 +1,17 @@
+#![no_main]
+
+//! 结算单解码与摘要解析：任意字节（非法 GB18030、截断的多字节字符、超长行）都不能 panic
+//!
+//! cargo +nightly fuzz run settlement_parser
+
+use inspirai_ctp_core::ctp::{decode_settlement_bytes, parse_settlement_summary};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let content = decode_settlement_bytes(data);
+    let summary = parse_settlement_summary(&content);
+    // 提取的数值不能是 inf/NaN，否则会污染结算报告的累计
+    for value in [summary.prev_balance, summary.balance, summary.commission, summary.risk_ratio] {
+        assert!(value.is_finite());
+    }
+});
@@ -0,0 +1,289 @@
+use crate::ctp::CtpError;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认操作录制目录
+pub const DEFAULT_ACTION_DIR: &str = "./logs/actions";
+
+/// 参数名（忽略大小写和下划线）包含这些片段时整体脱敏
+const SENSITIVE_KEYS: &[&str] = &["password", "passwd", "authcode", "token", "secret"];
+
+const MASK: &str = "***";
+
+/// 录制的一次命令调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedAction {
+    pub seq: u64,
+    pub command: String,
+    /// 脱敏后的调用参数
+    pub args: serde_json::Value,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    /// 距录制开始的毫秒数，回放时用于还原操作间隔
+    pub offset_ms: u64,
+}
+
+impl RecordedAction {
+    /// 按参数名取出并反序列化调用参数
+    pub fn arg<T: DeserializeOwned>(&self, name: &str) -> Result<T, CtpError> {
+        let value = self.args.get(name).cloned().unwrap_or(serde_json::Value::Null);
+        serde_json::from_value(value)
+            .map_err(|e| CtpError::ConversionError(format!("{} 参数 {} 无法解析: {}", self.command, name, e)))
+    }
+}
+
+/// 录制状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRecordingStatus {
+    pub enabled: bool,
+    /// 当前录制文件
+    pub file: Option<String>,
+    pub recorded: u64,
+}
+
+/// 回放单步结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayStepStatus {
+    Executed,
+    /// 命令不支持回放（连接、登录、界面查询等）
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub seq: u64,
+    pub command: String,
+    pub status: ReplayStepStatus,
+    pub message: Option<String>,
+}
+
+/// 回放报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub file: String,
+    pub executed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl ReplayReport {
+    pub fn new(file: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, action: &RecordedAction, status: ReplayStepStatus, message: Option<String>) {
+        match status {
+            ReplayStepStatus::Executed => self.executed += 1,
+            ReplayStepStatus::Skipped => self.skipped += 1,
+            ReplayStepStatus::Failed => self.failed += 1,
+        }
+        self.steps.push(ReplayStep {
+            seq: action.seq,
+            command: action.command.clone(),
+            status,
+            message,
+        });
+    }
+}
+
+struct RecorderInner {
+    dir: PathBuf,
+    /// 录制中的文件，为空表示未开启
+    path: Option<PathBuf>,
+    started_at: chrono::DateTime<chrono::Utc>,
+    next_seq: u64,
+}
+
+/// 操作录制器
+///
+/// 开启后将每次 Tauri 命令调用的命令名、脱敏参数和时间写入独立的 JSONL 文件，
+/// 用于复现用户反馈的问题，默认关闭
+#[derive(Clone)]
+pub struct ActionRecorder {
+    inner: Arc<Mutex<RecorderInner>>,
+}
+
+impl ActionRecorder {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(RecorderInner {
+                dir: dir.as_ref().to_path_buf(),
+                path: None,
+                started_at: chrono::Utc::now(),
+                next_seq: 1,
+            })),
+        }
+    }
+
+    /// 开始录制，每次开启写入新的会话文件；已在录制时保持原文件
+    pub fn start(&self) -> Result<ActionRecordingStatus, CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.path.is_none() {
+            std::fs::create_dir_all(&inner.dir)?;
+            let now = chrono::Utc::now();
+            let path = inner.dir.join(format!("session_{}.jsonl", now.format("%Y%m%d_%H%M%S_%3f")));
+            File::create(&path)?;
+            tracing::info!("开始录制操作: {}", path.display());
+            inner.path = Some(path);
+            inner.started_at = now;
+            inner.next_seq = 1;
+        }
+        Ok(status_of(&inner))
+    }
+
+    /// 停止录制，返回停止前的状态
+    pub fn stop(&self) -> ActionRecordingStatus {
+        let mut inner = self.inner.lock().unwrap();
+        let status = status_of(&inner);
+        if let Some(path) = inner.path.take() {
+            tracing::info!("停止录制操作: {}，共 {} 条", path.display(), status.recorded);
+        }
+        status
+    }
+
+    pub fn status(&self) -> ActionRecordingStatus {
+        status_of(&self.inner.lock().unwrap())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.lock().unwrap().path.is_some()
+    }
+
+    /// 记录一次命令调用，未开启录制时忽略；写入失败只记日志，不影响命令执行
+    pub fn record(&self, command: &str, args: &serde_json::Value) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(path) = inner.path.clone() else {
+            return;
+        };
+
+        let now = chrono::Utc::now();
+        let action = RecordedAction {
+            seq: inner.next_seq,
+            command: command.to_string(),
+            args: sanitize(args),
+            recorded_at: now,
+            offset_ms: (now - inner.started_at).num_milliseconds().max(0) as u64,
+        };
+        inner.next_seq += 1;
+
+        if let Err(e) = append_line(&path, &action) {
+            tracing::warn!("写入操作录制失败: {}", e);
+            crate::health::record_storage_error("action_recorder", &e);
+        }
+    }
+}
+
+impl Default for ActionRecorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTION_DIR)
+    }
+}
+
+/// 读取录制文件，无法解析的行跳过
+pub fn load_actions(path: impl AsRef<Path>) -> Result<Vec<RecordedAction>, CtpError> {
+    let reader = BufReader::new(File::open(path.as_ref())?);
+    let mut actions = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RecordedAction>(&line) {
+            Ok(action) => actions.push(action),
+            Err(e) => tracing::warn!("跳过无法解析的录制记录: {}", e),
+        }
+    }
+    Ok(actions)
+}
+
+/// 递归脱敏：敏感参数名的值整体替换为掩码
+pub fn sanitize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let value = if is_sensitive(key) {
+                    serde_json::Value::String(MASK.to_string())
+                } else {
+                    sanitize(value)
+                };
+                (key.clone(), value)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(sanitize).collect(),
+        other => other.clone(),
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let normalized: String = key.chars().filter(|c| *c != '_').collect::<String>().to_lowercase();
+    SENSITIVE_KEYS.iter().any(|k| normalized.contains(k))
+}
+
+fn status_of(inner: &RecorderInner) -> ActionRecordingStatus {
+    ActionRecordingStatus {
+        enabled: inner.path.is_some(),
+        file: inner.path.as_ref().map(|p| p.display().to_string()),
+        recorded: inner.next_seq - 1,
+    }
+}
+
+fn append_line(path: &Path, action: &RecordedAction) -> Result<(), CtpError> {
+    let line = serde_json::to_string(action).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sanitize_masks_credentials() {
+        let args = json!({
+            "config": {"investor_id": "123456", "password": "pw", "auth_code": "code"},
+            "credentials": [{"userPassword": "pw", "brokerId": "9999"}],
+            "instrumentIds": ["rb2501"],
+        });
+        let masked = sanitize(&args);
+        assert_eq!(masked["config"]["password"], MASK);
+        assert_eq!(masked["config"]["auth_code"], MASK);
+        assert_eq!(masked["config"]["investor_id"], "123456");
+        assert_eq!(masked["credentials"][0]["userPassword"], MASK);
+        assert_eq!(masked["instrumentIds"][0], "rb2501");
+    }
+
+    #[test]
+    fn test_record_only_when_enabled_and_reload() {
+        let dir = std::env::temp_dir().join(format!("action_recorder_test_{}", std::process::id()));
+        let recorder = ActionRecorder::new(&dir);
+
+        recorder.record("ctp_query_account", &json!({}));
+        assert!(!recorder.is_enabled());
+
+        let status = recorder.start().unwrap();
+        let file = status.file.unwrap();
+        recorder.record("ctp_subscribe", &json!({"instrumentIds": ["rb2501"]}));
+        recorder.record("ctp_login", &json!({"credentials": {"password": "pw"}}));
+        assert_eq!(recorder.stop().recorded, 2);
+        recorder.record("ctp_query_account", &json!({}));
+
+        let actions = load_actions(&file).unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].seq, 1);
+        assert_eq!(actions[0].arg::<Vec<String>>("instrumentIds").unwrap(), vec!["rb2501".to_string()]);
+        assert_eq!(actions[1].args["credentials"]["password"], MASK);
+        assert!(actions[1].arg::<Vec<String>>("instrumentIds").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::ctp::{bar_import::trading_day_of, CtpError};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 默认接口用量阈值配置文件
+pub const DEFAULT_API_USAGE_CONFIG_FILE: &str = "./config/api_usage.toml";
+
+/// 计数的请求类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRequestKind {
+    /// 报单录入
+    OrderInsert,
+    /// 撤单
+    OrderCancel,
+    /// 资金、持仓、成交、报单、结算等查询
+    Query,
+}
+
+/// 每个交易日的用量阈值，按经纪商和交易所规定配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiUsageLimits {
+    /// 单日报单上限
+    pub max_order_inserts: u64,
+    /// 单日撤单上限，交易所对频繁撤单有监管标准
+    pub max_order_cancels: u64,
+    /// 单日查询上限
+    pub max_queries: u64,
+    /// 撤单数 / 报单数 上限
+    pub max_cancel_ratio: f64,
+    /// 报单数达到该值后才检查撤单比例，避免开盘几笔单误报
+    pub cancel_ratio_min_inserts: u64,
+    /// 用量达到上限的该比例时开始预警
+    pub warn_fraction: f64,
+}
+
+impl Default for ApiUsageLimits {
+    fn default() -> Self {
+        Self {
+            max_order_inserts: 10_000,
+            max_order_cancels: 500,
+            max_queries: 5_000,
+            max_cancel_ratio: 0.5,
+            cancel_ratio_min_inserts: 100,
+            warn_fraction: 0.8,
+        }
+    }
+}
+
+impl ApiUsageLimits {
+    /// 读取阈值配置，文件不存在时使用默认值
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let limits: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("接口用量阈值配置解析失败: {}", e)))?;
+        limits.validate()?;
+        Ok(limits)
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if !(self.warn_fraction > 0.0 && self.warn_fraction <= 1.0) {
+            return Err(CtpError::ValidationError("预警比例须在 (0, 1] 之间".to_string()));
+        }
+        if self.max_cancel_ratio <= 0.0 {
+            return Err(CtpError::ValidationError("撤单比例上限须大于 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 用量等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageLevel {
+    Normal,
+    /// 接近上限
+    Warning,
+    /// 已达到或超过上限
+    Exceeded,
+}
+
+/// 单项用量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageMeter {
+    /// order_inserts、order_cancels、queries 或 cancel_ratio
+    pub name: String,
+    pub used: f64,
+    pub limit: f64,
+    pub level: UsageLevel,
+}
+
+/// 当日用量快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiUsageSnapshot {
+    pub trading_day: NaiveDate,
+    pub order_inserts: u64,
+    pub order_cancels: u64,
+    pub queries: u64,
+    pub cancel_ratio: f64,
+    pub meters: Vec<UsageMeter>,
+    /// 各项中最高的等级
+    pub level: UsageLevel,
+}
+
+/// 用量等级升高时的告警，每个交易日每项每个等级只告警一次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageWarning {
+    pub trading_day: NaiveDate,
+    pub meter: UsageMeter,
+    pub message: String,
+}
+
+struct UsageState {
+    trading_day: NaiveDate,
+    order_inserts: u64,
+    order_cancels: u64,
+    queries: u64,
+    /// 各项已告警的最高等级
+    alerted: Vec<(String, UsageLevel)>,
+}
+
+impl UsageState {
+    fn new(trading_day: NaiveDate) -> Self {
+        Self {
+            trading_day,
+            order_inserts: 0,
+            order_cancels: 0,
+            queries: 0,
+            alerted: Vec::new(),
+        }
+    }
+
+    fn cancel_ratio(&self) -> f64 {
+        if self.order_inserts == 0 {
+            0.0
+        } else {
+            self.order_cancels as f64 / self.order_inserts as f64
+        }
+    }
+}
+
+/// 按交易日统计报单、撤单和查询次数
+///
+/// 接近经纪商或交易所规定的上限时告警，避免因频繁撤单、超额查询被监管处罚或限制交易；
+/// 只做统计和提醒，不拦截请求。夜盘计入下一交易日，进程内计数，重启后从零开始
+#[derive(Clone)]
+pub struct ApiUsageTracker {
+    limits: ApiUsageLimits,
+    inner: Arc<Mutex<UsageState>>,
+}
+
+impl Default for ApiUsageTracker {
+    fn default() -> Self {
+        Self::new(ApiUsageLimits::default())
+    }
+}
+
+impl ApiUsageTracker {
+    pub fn new(limits: ApiUsageLimits) -> Self {
+        Self {
+            limits,
+            inner: Arc::new(Mutex::new(UsageState::new(current_trading_day()))),
+        }
+    }
+
+    pub fn limits(&self) -> &ApiUsageLimits {
+        &self.limits
+    }
+
+    /// 记录一次请求，用量等级升高时返回告警
+    pub fn record(&self, kind: ApiRequestKind) -> Vec<UsageWarning> {
+        self.record_at(kind, chrono::Local::now().naive_local())
+    }
+
+    /// 按指定本地时间记录，跨交易日时计数清零
+    pub fn record_at(&self, kind: ApiRequestKind, local_time: NaiveDateTime) -> Vec<UsageWarning> {
+        let mut state = self.inner.lock().unwrap();
+        self.roll_to(&mut state, trading_day_of(local_time));
+        match kind {
+            ApiRequestKind::OrderInsert => state.order_inserts += 1,
+            ApiRequestKind::OrderCancel => state.order_cancels += 1,
+            ApiRequestKind::Query => state.queries += 1,
+        }
+
+        let mut warnings = Vec::new();
+        for meter in self.meters(&state) {
+            if meter.level == UsageLevel::Normal {
+                continue;
+            }
+            let alerted = state
+                .alerted
+                .iter_mut()
+                .find(|(name, _)| *name == meter.name);
+            match alerted {
+                Some((_, level)) if *level >= meter.level => continue,
+                Some((_, level)) => *level = meter.level,
+                None => state.alerted.push((meter.name.clone(), meter.level)),
+            }
+            let message = match meter.level {
+                UsageLevel::Exceeded => format!("{} 已达上限: {} / {}", meter.name, format_usage(meter.used), format_usage(meter.limit)),
+                _ => format!("{} 接近上限: {} / {}", meter.name, format_usage(meter.used), format_usage(meter.limit)),
+            };
+            tracing::warn!("交易日 {} 接口用量{}", state.trading_day, message);
+            warnings.push(UsageWarning {
+                trading_day: state.trading_day,
+                meter,
+                message,
+            });
+        }
+        warnings
+    }
+
+    /// 当日用量
+    pub fn snapshot(&self) -> ApiUsageSnapshot {
+        self.snapshot_at(chrono::Local::now().naive_local())
+    }
+
+    /// 指定本地时间所属交易日的用量
+    pub fn snapshot_at(&self, local_time: NaiveDateTime) -> ApiUsageSnapshot {
+        let mut state = self.inner.lock().unwrap();
+        self.roll_to(&mut state, trading_day_of(local_time));
+        let meters = self.meters(&state);
+        ApiUsageSnapshot {
+            trading_day: state.trading_day,
+            order_inserts: state.order_inserts,
+            order_cancels: state.order_cancels,
+            queries: state.queries,
+            cancel_ratio: state.cancel_ratio(),
+            level: meters.iter().map(|m| m.level).max().unwrap_or(UsageLevel::Normal),
+            meters,
+        }
+    }
+
+    fn roll_to(&self, state: &mut UsageState, trading_day: NaiveDate) {
+        if trading_day != state.trading_day {
+            tracing::info!(
+                "交易日 {} 接口用量: 报单 {} 撤单 {} 查询 {}，切换到 {}",
+                state.trading_day,
+                state.order_inserts,
+                state.order_cancels,
+                state.queries,
+                trading_day
+            );
+            *state = UsageState::new(trading_day);
+        }
+    }
+
+    fn meters(&self, state: &UsageState) -> Vec<UsageMeter> {
+        let mut meters = vec![
+            self.meter("order_inserts", state.order_inserts as f64, self.limits.max_order_inserts as f64),
+            self.meter("order_cancels", state.order_cancels as f64, self.limits.max_order_cancels as f64),
+            self.meter("queries", state.queries as f64, self.limits.max_queries as f64),
+        ];
+        let mut ratio = self.meter("cancel_ratio", state.cancel_ratio(), self.limits.max_cancel_ratio);
+        if state.order_inserts < self.limits.cancel_ratio_min_inserts {
+            ratio.level = UsageLevel::Normal;
+        }
+        meters.push(ratio);
+        meters
+    }
+
+    fn meter(&self, name: &str, used: f64, limit: f64) -> UsageMeter {
+        let level = if limit <= 0.0 {
+            // 上限为 0 表示不限制
+            UsageLevel::Normal
+        } else if used >= limit {
+            UsageLevel::Exceeded
+        } else if used >= limit * self.limits.warn_fraction {
+            UsageLevel::Warning
+        } else {
+            UsageLevel::Normal
+        };
+        UsageMeter {
+            name: name.to_string(),
+            used,
+            limit,
+            level,
+        }
+    }
+}
+
+fn current_trading_day() -> NaiveDate {
+    trading_day_of(chrono::Local::now().naive_local())
+}
+
+fn format_usage(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_warns_once_per_level() {
+        let tracker = ApiUsageTracker::new(ApiUsageLimits {
+            max_order_cancels: 10,
+            ..ApiUsageLimits::default()
+        });
+        let now = at("2025-03-04 10:00:00");
+        let mut warnings = Vec::new();
+        for _ in 0..12 {
+            warnings.extend(tracker.record_at(ApiRequestKind::OrderCancel, now));
+        }
+        let levels: Vec<UsageLevel> = warnings
+            .iter()
+            .filter(|w| w.meter.name == "order_cancels")
+            .map(|w| w.meter.level)
+            .collect();
+        assert_eq!(levels, vec![UsageLevel::Warning, UsageLevel::Exceeded]);
+
+        // 撤单比例在报单数不足时不检查
+        assert!(warnings.iter().all(|w| w.meter.name != "cancel_ratio"));
+        for _ in 0..100 {
+            tracker.record_at(ApiRequestKind::OrderInsert, now);
+        }
+        let snapshot = tracker.snapshot_at(now);
+        assert_eq!((snapshot.order_inserts, snapshot.order_cancels), (100, 12));
+        assert_eq!(snapshot.level, UsageLevel::Exceeded);
+        assert!((snapshot.cancel_ratio - 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cancel_ratio_and_trading_day_rollover() {
+        let tracker = ApiUsageTracker::new(ApiUsageLimits {
+            cancel_ratio_min_inserts: 4,
+            ..ApiUsageLimits::default()
+        });
+        let day = at("2025-03-07 14:00:00");
+        for _ in 0..4 {
+            tracker.record_at(ApiRequestKind::OrderInsert, day);
+        }
+        tracker.record_at(ApiRequestKind::OrderCancel, day);
+        let warnings = tracker.record_at(ApiRequestKind::OrderCancel, day);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].meter.name, "cancel_ratio");
+        assert_eq!(warnings[0].meter.level, UsageLevel::Exceeded);
+
+        // 周五夜盘计入下周一，计数清零
+        let night = at("2025-03-07 21:00:00");
+        tracker.record_at(ApiRequestKind::Query, night);
+        let snapshot = tracker.snapshot_at(night);
+        assert_eq!(snapshot.trading_day, NaiveDate::from_ymd_opt(2025, 3, 10).unwrap());
+        assert_eq!((snapshot.order_inserts, snapshot.order_cancels, snapshot.queries), (0, 0, 1));
+        assert_eq!(snapshot.level, UsageLevel::Normal);
+    }
+}
@@ -0,0 +1,664 @@
+use crate::ctp::{
+    storage::{pending_restore_path, with_suffix, Storage, StorageBackend, DB_SIDECAR_SUFFIXES},
+    CtpError,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认备份配置文件
+pub const DEFAULT_BACKUP_CONFIG_FILE: &str = "./config/backup.toml";
+/// 备份包内的清单文件
+pub const BACKUP_MANIFEST: &str = "manifest.json";
+const BACKUP_PREFIX: &str = "inspirai-backup-";
+
+/// 备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// 是否定时备份
+    pub enabled: bool,
+    /// 备份包保存目录
+    pub target_dir: PathBuf,
+    /// 备份的目录或文件：配置档、数据目录（时间线、回合交易、工作区等）和操作日志
+    pub sources: Vec<PathBuf>,
+    /// 按包内路径前缀排除，如实例租约
+    pub exclude: Vec<String>,
+    pub interval_hours: u64,
+    /// 保留的备份包数量
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_dir: PathBuf::from("./backups"),
+            sources: vec![
+                PathBuf::from("./config"),
+                PathBuf::from("./data"),
+                PathBuf::from("./logs/actions"),
+            ],
+            exclude: vec!["data/instance".to_string()],
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+impl BackupConfig {
+    /// 从 TOML 文件加载，文件不存在时返回默认配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("备份配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("备份配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.target_dir.as_os_str().is_empty() {
+            return Err(CtpError::ConfigError("备份目录不能为空".to_string()));
+        }
+        if self.interval_hours == 0 || self.keep == 0 {
+            return Err(CtpError::ConfigError("备份间隔和保留数量必须大于0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 清单中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFileEntry {
+    /// 包内路径，恢复时相对恢复根目录
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub files: Vec<BackupFileEntry>,
+    /// 绝对路径备份源：包内根路径 -> 原位置，恢复时写回原位置；相对路径的源按恢复根目录解析
+    #[serde(default)]
+    pub roots: BTreeMap<String, PathBuf>,
+}
+
+/// 备份包信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub size_bytes: u64,
+    /// 新建备份时为文件数，列出已有备份时为 None
+    pub file_count: Option<usize>,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub backup: PathBuf,
+    pub restored_files: usize,
+    /// 恢复前自动为当前数据做的备份
+    pub pre_restore_backup: Option<PathBuf>,
+    /// 正在使用的数据库，恢复内容已写到待替换文件，重启后生效
+    #[serde(default)]
+    pub deferred: Vec<PathBuf>,
+}
+
+/// 本地数据备份
+///
+/// 将配置档、数据目录和操作日志打包为带时间戳的 zip，清单记录每个文件的
+/// SHA-256；超出保留数量的旧包按时间删除。恢复前先校验全部文件，
+/// 并为当前数据另做一份备份。
+///
+/// 挂载存储后，已打开的数据库不按原始文件复制，而是通过数据库引擎生成一致快照；
+/// 恢复时数据库文件写到待替换文件，下次打开存储时才替换
+#[derive(Clone)]
+pub struct BackupManager {
+    config: Arc<Mutex<BackupConfig>>,
+    storage: Option<Storage>,
+}
+
+impl BackupManager {
+    pub fn new(config: BackupConfig) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            storage: None,
+        }
+    }
+
+    pub fn with_storage(mut self, storage: Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// 已打开的数据库（规范化路径）及其后端
+    fn open_databases(&self) -> Vec<(PathBuf, Arc<dyn StorageBackend>)> {
+        self.storage
+            .iter()
+            .flat_map(|storage| storage.databases())
+            .filter_map(|(path, backend)| Some((std::fs::canonicalize(path).ok()?, backend.clone())))
+            .collect()
+    }
+
+    pub fn config(&self) -> BackupConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn update_config(&self, config: BackupConfig) -> Result<(), CtpError> {
+        config.validate()?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+
+    /// 距最近一次备份已超过间隔，需要定时备份
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> Result<bool, CtpError> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(false);
+        }
+        let latest = self.list_backups()?.into_iter().map(|b| b.created_at).max();
+        Ok(latest.is_none_or(|at| now - at >= chrono::Duration::hours(config.interval_hours as i64)))
+    }
+
+    /// 立即备份并清理旧包
+    pub fn create_backup(&self) -> Result<BackupInfo, CtpError> {
+        let config = self.config();
+        std::fs::create_dir_all(&config.target_dir)?;
+        let target = std::fs::canonicalize(&config.target_dir)?;
+
+        let created_at = chrono::Utc::now();
+        let mut path = config
+            .target_dir
+            .join(format!("{}{}.zip", BACKUP_PREFIX, created_at.format("%Y%m%d-%H%M%S")));
+        if path.exists() {
+            // 同一秒内重复备份
+            path = config.target_dir.join(format!(
+                "{}{}.zip",
+                BACKUP_PREFIX,
+                created_at.format("%Y%m%d-%H%M%S%.3f")
+            ));
+        }
+
+        let roots = archive_roots(&config.sources);
+        let mut files = Vec::new();
+        for (source, name) in config.sources.iter().zip(&roots) {
+            collect_files(source, name, &target, &config.exclude, &mut files)?;
+        }
+
+        let tmp = path.with_extension("zip.tmp");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&tmp)?);
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut manifest = BackupManifest {
+            created_at,
+            files: Vec::new(),
+            roots: config
+                .sources
+                .iter()
+                .zip(&roots)
+                .filter(|(source, name)| source.is_absolute() && !name.is_empty())
+                .map(|(source, name)| (name.clone(), source.clone()))
+                .collect(),
+        };
+        let databases = self.open_databases();
+        let snapshot_tmp = path.with_extension("snapshot.tmp");
+        for (name, file) in &files {
+            let content = match open_database_file(file, &databases) {
+                Some(DatabaseFile::Main(backend)) => {
+                    // 数据库文件可能处于写入中途，通过引擎生成快照
+                    let snapshot = backend
+                        .snapshot(&snapshot_tmp)
+                        .and_then(|_| std::fs::read(&snapshot_tmp).map_err(CtpError::from));
+                    let _ = std::fs::remove_file(&snapshot_tmp);
+                    snapshot?
+                }
+                // WAL 等伴随文件已并入快照
+                Some(DatabaseFile::Sidecar) => continue,
+                None => match std::fs::read(file) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        // 备份过程中被删除或占用的文件跳过
+                        tracing::warn!("备份时读取 {} 失败，已跳过: {}", file.display(), e);
+                        continue;
+                    }
+                },
+            };
+            zip.start_file(name.as_str(), options).map_err(zip_error)?;
+            zip.write_all(&content)?;
+            manifest.files.push(BackupFileEntry {
+                path: name.clone(),
+                size: content.len() as u64,
+                sha256: sha256_hex(&content),
+            });
+        }
+        let manifest_json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| CtpError::ConversionError(format!("序列化备份清单失败: {}", e)))?;
+        zip.start_file(BACKUP_MANIFEST, options).map_err(zip_error)?;
+        zip.write_all(&manifest_json)?;
+        zip.finish().map_err(zip_error)?;
+        std::fs::rename(&tmp, &path)?;
+
+        let info = BackupInfo {
+            size_bytes: std::fs::metadata(&path)?.len(),
+            path,
+            created_at,
+            file_count: Some(manifest.files.len()),
+        };
+        tracing::info!("备份完成: {}（{} 个文件，{} 字节）", info.path.display(), manifest.files.len(), info.size_bytes);
+        self.rotate(config.keep)?;
+        Ok(info)
+    }
+
+    /// 备份目录中的备份包，按时间倒序
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>, CtpError> {
+        let config = self.config();
+        if !config.target_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(&config.target_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(stamp) = name.strip_prefix(BACKUP_PREFIX).and_then(|n| n.strip_suffix(".zip")) else {
+                continue;
+            };
+            let Some(created_at) = parse_stamp(stamp) else {
+                continue;
+            };
+            backups.push(BackupInfo {
+                path: entry.path(),
+                created_at,
+                size_bytes: entry.metadata()?.len(),
+                file_count: None,
+            });
+        }
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        Ok(backups)
+    }
+
+    /// 校验备份包：清单中的每个文件都存在且 SHA-256 一致
+    pub fn verify_backup(&self, path: impl AsRef<Path>) -> Result<BackupManifest, CtpError> {
+        let path = path.as_ref();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?).map_err(zip_error)?;
+        let manifest: BackupManifest = {
+            let mut entry = archive
+                .by_name(BACKUP_MANIFEST)
+                .map_err(|_| CtpError::ValidationError(format!("{} 缺少备份清单", path.display())))?;
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            serde_json::from_slice(&content)
+                .map_err(|e| CtpError::ValidationError(format!("备份清单解析失败: {}", e)))?
+        };
+        for file in &manifest.files {
+            if !is_safe_entry(&file.path) {
+                return Err(CtpError::ValidationError(format!("备份包含不安全的路径: {}", file.path)));
+            }
+            let mut entry = archive
+                .by_name(&file.path)
+                .map_err(|_| CtpError::ValidationError(format!("备份缺少文件: {}", file.path)))?;
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            if content.len() as u64 != file.size || sha256_hex(&content) != file.sha256 {
+                return Err(CtpError::ValidationError(format!("备份文件校验失败: {}", file.path)));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// 校验通过后恢复备份，恢复前先备份当前数据
+    ///
+    /// 绝对路径的备份源写回原位置，其余包内路径相对 `root`；恢复前的备份失败时中止，
+    /// 除非 `force` 为真
+    pub fn restore_backup(
+        &self,
+        path: impl AsRef<Path>,
+        root: impl AsRef<Path>,
+        force: bool,
+    ) -> Result<RestoreReport, CtpError> {
+        let path = path.as_ref();
+        let root = root.as_ref();
+        let manifest = self.verify_backup(path)?;
+
+        let pre_restore_backup = match self.create_backup() {
+            Ok(info) => Some(info.path),
+            Err(e) if force => {
+                tracing::warn!("恢复前备份当前数据失败，按要求继续恢复: {}", e);
+                None
+            }
+            Err(e) => {
+                return Err(CtpError::StateError(format!("恢复前备份当前数据失败，已中止恢复: {}", e)));
+            }
+        };
+
+        let databases = self.open_databases();
+        let mut deferred = Vec::new();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?).map_err(zip_error)?;
+        for file in &manifest.files {
+            let mut entry = archive.by_name(&file.path).map_err(zip_error)?;
+            let mut target = restore_target(&file.path, root, &manifest.roots);
+            match open_database_file(&target, &databases) {
+                // 不覆盖正在使用的数据库，下次打开存储时替换
+                Some(DatabaseFile::Main(_)) => {
+                    target = pending_restore_path(&target);
+                    deferred.push(target.clone());
+                }
+                Some(DatabaseFile::Sidecar) => {
+                    tracing::warn!("跳过正在使用的数据库伴随文件: {}", file.path);
+                    continue;
+                }
+                None => {}
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let tmp = target.with_extension("restore.tmp");
+            std::io::copy(&mut entry, &mut std::fs::File::create(&tmp)?)?;
+            std::fs::rename(&tmp, &target)?;
+        }
+        tracing::info!("已从 {} 恢复 {} 个文件", path.display(), manifest.files.len());
+        if !deferred.is_empty() {
+            tracing::info!("{} 个数据库文件将在重启后替换", deferred.len());
+        }
+        Ok(RestoreReport {
+            backup: path.to_path_buf(),
+            restored_files: manifest.files.len(),
+            pre_restore_backup,
+            deferred,
+        })
+    }
+
+    fn rotate(&self, keep: usize) -> Result<(), CtpError> {
+        for old in self.list_backups()?.into_iter().skip(keep) {
+            match std::fs::remove_file(&old.path) {
+                Ok(()) => tracing::info!("已删除旧备份: {}", old.path.display()),
+                Err(e) => tracing::warn!("删除旧备份 {} 失败: {}", old.path.display(), e),
+            }
+        }
+        Ok(())
+    }
+}
+
+enum DatabaseFile<'a> {
+    Main(&'a Arc<dyn StorageBackend>),
+    Sidecar,
+}
+
+/// 文件是否为已打开的数据库或其伴随文件
+fn open_database_file<'a>(file: &Path, databases: &'a [(PathBuf, Arc<dyn StorageBackend>)]) -> Option<DatabaseFile<'a>> {
+    let file = std::fs::canonicalize(file).ok()?;
+    databases.iter().find_map(|(path, backend)| {
+        if file == *path {
+            Some(DatabaseFile::Main(backend))
+        } else if DB_SIDECAR_SUFFIXES.iter().any(|suffix| file == with_suffix(path, suffix)) {
+            Some(DatabaseFile::Sidecar)
+        } else {
+            None
+        }
+    })
+}
+
+/// 各备份源在包内的根路径，绝对路径与其他源重名或互为前缀时加序号区分
+fn archive_roots(sources: &[PathBuf]) -> Vec<String> {
+    let overlaps = |a: &str, b: &str| a == b || a.starts_with(&format!("{}/", b)) || b.starts_with(&format!("{}/", a));
+    let mut names: Vec<String> = sources.iter().map(|s| archive_root(s)).collect();
+    for i in 0..sources.len() {
+        if !sources[i].is_absolute() || names[i].is_empty() {
+            continue;
+        }
+        let base = names[i].clone();
+        let mut n = 1;
+        while names
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != i && !other.is_empty() && overlaps(&names[i], other))
+        {
+            n += 1;
+            names[i] = format!("{}-{}", base, n);
+        }
+    }
+    names
+}
+
+/// 包内路径的恢复位置：属于绝对路径备份源的写回原位置
+fn restore_target(name: &str, root: &Path, roots: &BTreeMap<String, PathBuf>) -> PathBuf {
+    for (prefix, source) in roots {
+        if name == prefix {
+            return source.clone();
+        }
+        if let Some(rest) = name.strip_prefix(prefix.as_str()).and_then(|r| r.strip_prefix('/')) {
+            return source.join(rest);
+        }
+    }
+    root.join(name)
+}
+
+/// 备份源在包内的根路径：相对路径去掉 `./`，绝对路径取最后一级目录名
+fn archive_root(source: &Path) -> String {
+    if source.is_absolute() {
+        return source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    source
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn collect_files(
+    path: &Path,
+    name: &str,
+    target: &Path,
+    exclude: &[String],
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<(), CtpError> {
+    if name.is_empty() || exclude.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+        return Ok(());
+    }
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.is_dir() {
+        // 备份目录位于备份源内时跳过自身
+        if std::fs::canonicalize(path).is_ok_and(|p| p == target) {
+            return Ok(());
+        }
+        let mut entries: Vec<_> = std::fs::read_dir(path)?.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let child = format!("{}/{}", name, entry.file_name().to_string_lossy());
+            collect_files(&entry.path(), &child, target, exclude, files)?;
+        }
+    } else if !name.ends_with(".tmp") {
+        files.push((name.to_string(), path.to_path_buf()));
+    }
+    Ok(())
+}
+
+fn is_safe_entry(name: &str) -> bool {
+    let path = Path::new(name);
+    !name.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn parse_stamp(stamp: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S%.f")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn zip_error(e: zip::result::ZipError) -> CtpError {
+    CtpError::IoError(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn manager(root: &Path, keep: usize) -> BackupManager {
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::create_dir_all(root.join("data/workspaces/default/layout")).unwrap();
+        std::fs::create_dir_all(root.join("data/instance")).unwrap();
+        std::fs::write(root.join("config/webhooks.toml"), "max_attempts = 3\n").unwrap();
+        std::fs::write(root.join("data/workspaces/default/layout/v000001.json"), "{}").unwrap();
+        std::fs::write(root.join("data/instance/lease.json"), "{}").unwrap();
+        BackupManager::new(BackupConfig {
+            target_dir: root.join("data/backups"),
+            sources: vec![root.join("config"), root.join("data")],
+            keep,
+            ..BackupConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_backup_restore_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(dir.path(), 5);
+        assert!(manager.is_due(chrono::Utc::now()).unwrap());
+
+        let info = manager.create_backup().unwrap();
+        // 实例租约和备份目录自身不进入备份
+        assert_eq!(info.file_count, Some(2));
+        assert!(!manager.is_due(chrono::Utc::now()).unwrap());
+        let manifest = manager.verify_backup(&info.path).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(names, vec!["config/webhooks.toml", "data/workspaces/default/layout/v000001.json"]);
+
+        assert_eq!(manifest.roots.get("config"), Some(&dir.path().join("config")));
+
+        // 绝对路径的备份源写回原位置，而不是恢复根目录下
+        std::fs::write(dir.path().join("config/webhooks.toml"), "broken").unwrap();
+        let other_root = TempDir::new().unwrap();
+        let report = manager.restore_backup(&info.path, other_root.path(), false).unwrap();
+        assert_eq!(report.restored_files, 2);
+        assert!(report.pre_restore_backup.is_some());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("config/webhooks.toml")).unwrap(),
+            "max_attempts = 3\n"
+        );
+        assert!(!other_root.path().join("config").exists());
+    }
+
+    #[test]
+    fn test_restore_aborts_without_safety_backup() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(dir.path(), 5);
+        let info = manager.create_backup().unwrap();
+        std::fs::write(dir.path().join("config/webhooks.toml"), "current").unwrap();
+
+        // 备份目录指向文件，恢复前的备份必然失败
+        let mut config = manager.config();
+        config.target_dir = dir.path().join("config/webhooks.toml");
+        manager.update_config(config).unwrap();
+        assert!(matches!(manager.restore_backup(&info.path, dir.path(), false), Err(CtpError::StateError(_))));
+        assert_eq!(std::fs::read_to_string(dir.path().join("config/webhooks.toml")).unwrap(), "current");
+
+        let report = manager.restore_backup(&info.path, dir.path(), true).unwrap();
+        assert!(report.pre_restore_backup.is_none());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("config/webhooks.toml")).unwrap(),
+            "max_attempts = 3\n"
+        );
+        assert_eq!(archive_roots(&[PathBuf::from("/a/logs"), PathBuf::from("./logs/actions")]), vec!["logs-2", "logs/actions"]);
+    }
+
+    #[test]
+    fn test_rotation_and_tampered_backup() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(dir.path(), 2);
+        for _ in 0..3 {
+            manager.create_backup().unwrap();
+        }
+        let backups = manager.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+
+        // 篡改后的备份校验失败，且不会恢复
+        let tampered = dir.path().join("tampered.zip");
+        {
+            let mut zip = zip::ZipWriter::new(std::fs::File::create(&tampered).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("config/webhooks.toml", options).unwrap();
+            zip.write_all(b"evil").unwrap();
+            let manifest = BackupManifest {
+                created_at: chrono::Utc::now(),
+                files: vec![BackupFileEntry {
+                    path: "config/webhooks.toml".to_string(),
+                    size: 4,
+                    sha256: sha256_hex(b"good"),
+                }],
+                roots: BTreeMap::new(),
+            };
+            zip.start_file(BACKUP_MANIFEST, options).unwrap();
+            zip.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+            zip.finish().unwrap();
+        }
+        assert!(manager.verify_backup(&tampered).is_err());
+        assert!(manager.restore_backup(&tampered, dir.path(), false).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("config/webhooks.toml")).unwrap(),
+            "max_attempts = 3\n"
+        );
+        assert!(!is_safe_entry("../etc/passwd"));
+    }
+
+    #[test]
+    fn test_open_database_snapshot_and_deferred_restore() {
+        let dir = TempDir::new().unwrap();
+        let config = crate::ctp::StorageConfig {
+            orders_backend: crate::ctp::StorageBackendKind::Sqlite,
+            market_data_backend: crate::ctp::StorageBackendKind::Sqlite,
+            sqlite_path: dir.path().join("data/trading.sqlite"),
+            duckdb_path: dir.path().join("data/market_data.duckdb"),
+        };
+        let storage = Storage::open(&config).unwrap();
+        let manager = manager(dir.path(), 5).with_storage(storage.clone());
+        // 伴随文件不进入备份
+        std::fs::write(dir.path().join("data/trading.sqlite-wal"), "stale").unwrap();
+
+        let info = manager.create_backup().unwrap();
+        let manifest = manager.verify_backup(&info.path).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert!(names.contains(&"data/trading.sqlite"));
+        assert!(!names.contains(&"data/trading.sqlite-wal"));
+
+        // 数据库正在使用，恢复内容写到待替换文件
+        let report = manager.restore_backup(&info.path, dir.path(), false).unwrap();
+        assert_eq!(report.deferred, vec![pending_restore_path(&dir.path().join("data/trading.sqlite"))]);
+        assert!(report.deferred[0].exists());
+        drop(storage);
+        drop(manager);
+
+        Storage::open(&config).unwrap();
+        assert!(!report.deferred[0].exists());
+    }
+}
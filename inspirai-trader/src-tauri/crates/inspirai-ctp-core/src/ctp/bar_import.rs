@@ -0,0 +1,653 @@
+use crate::ctp::{
+    CtpError,
+    models::InstrumentInfo,
+    tick_compaction::{ArchivedBar, StorageGranularity, TickCompactor},
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// 报告中最多保留的错误行数
+const MAX_REPORTED_ISSUES: usize = 100;
+/// 国内期货交易所所在时区
+const EXCHANGE_TIMEZONE: Tz = chrono_tz::Asia::Shanghai;
+
+/// 按（合约, 交易日）分组、按时间去重的 K 线
+type GroupedBars = BTreeMap<(String, NaiveDate), BTreeMap<String, ArchivedBar>>;
+
+/// CSV 列映射（按表头名称）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvColumnMapping {
+    /// 日期时间合并在一列
+    pub datetime: Option<String>,
+    /// 日期、时间分列时使用
+    pub date: Option<String>,
+    pub time: Option<String>,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: Option<String>,
+    pub turnover: Option<String>,
+    pub open_interest: Option<String>,
+    /// 合约代码列，不设置时使用 `BarImportConfig::instrument_id`
+    pub instrument: Option<String>,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        Self {
+            datetime: Some("datetime".to_string()),
+            date: None,
+            time: None,
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: Some("volume".to_string()),
+            turnover: None,
+            open_interest: None,
+            instrument: Some("symbol".to_string()),
+        }
+    }
+}
+
+/// 合约代码大小写转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolCase {
+    Keep,
+    Lower,
+    Upper,
+}
+
+/// 数据商合约代码到 CTP 合约代码的转换规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractNaming {
+    /// 去掉交易所前缀，如 `SHFE.rb2501` -> `rb2501`
+    pub strip_exchange_prefix: bool,
+    pub case: SymbolCase,
+    /// 显式映射，优先于其他规则
+    pub aliases: HashMap<String, String>,
+}
+
+impl Default for ContractNaming {
+    fn default() -> Self {
+        Self {
+            strip_exchange_prefix: true,
+            case: SymbolCase::Keep,
+            aliases: HashMap::new(),
+        }
+    }
+}
+
+impl ContractNaming {
+    /// 转换为 CTP 合约代码
+    pub fn normalize(&self, symbol: &str) -> String {
+        let symbol = symbol.trim();
+        if let Some(alias) = self.aliases.get(symbol) {
+            return alias.clone();
+        }
+
+        let symbol = if self.strip_exchange_prefix {
+            symbol.rsplit('.').next().unwrap_or(symbol)
+        } else {
+            symbol
+        };
+        match self.case {
+            SymbolCase::Keep => symbol.to_string(),
+            SymbolCase::Lower => symbol.to_lowercase(),
+            SymbolCase::Upper => symbol.to_uppercase(),
+        }
+    }
+}
+
+/// K 线导入配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarImportConfig {
+    pub mapping: CsvColumnMapping,
+    pub delimiter: u8,
+    /// 日期时间列格式（chrono 格式串）
+    pub datetime_format: String,
+    pub date_format: String,
+    pub time_format: String,
+    /// 源数据时区（IANA 名称，如 `UTC`、`Asia/Shanghai`）
+    pub timezone: String,
+    pub granularity: StorageGranularity,
+    /// 文件不含合约列时使用的合约代码
+    pub instrument_id: Option<String>,
+    pub naming: ContractNaming,
+}
+
+impl Default for BarImportConfig {
+    fn default() -> Self {
+        Self {
+            mapping: CsvColumnMapping::default(),
+            delimiter: b',',
+            datetime_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            time_format: "%H:%M:%S".to_string(),
+            timezone: "Asia/Shanghai".to_string(),
+            granularity: StorageGranularity::Bar1m,
+            instrument_id: None,
+            naming: ContractNaming::default(),
+        }
+    }
+}
+
+/// 被拒绝的数据行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportIssue {
+    /// 文件行号（含表头，从 1 开始）
+    pub line: u64,
+    pub message: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub rows_read: usize,
+    pub rows_imported: usize,
+    pub rows_rejected: usize,
+    /// 同一合约同一时间的重复行（保留最后一条）
+    pub duplicates: usize,
+    pub instruments: Vec<String>,
+    pub trading_days: Vec<NaiveDate>,
+    /// 最多保留前 100 条
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    fn reject(&mut self, line: u64, message: impl Into<String>) {
+        self.rows_rejected += 1;
+        if self.issues.len() < MAX_REPORTED_ISSUES {
+            self.issues.push(ImportIssue { line, message: message.into() });
+        }
+    }
+}
+
+/// 外部 K 线 CSV 导入器
+///
+/// 按列映射解析数据商的 CSV，转换时区与合约代码，按合约规格校验后
+/// 按交易日写入本地 K 线归档
+pub struct BarImporter {
+    config: BarImportConfig,
+    timezone: Tz,
+    specs: HashMap<String, InstrumentInfo>,
+}
+
+impl BarImporter {
+    pub fn new(config: BarImportConfig) -> Result<Self, CtpError> {
+        if config.granularity == StorageGranularity::Tick {
+            return Err(CtpError::ConfigError("导入粒度必须为 K 线".to_string()));
+        }
+        if config.mapping.datetime.is_none() && (config.mapping.date.is_none() || config.mapping.time.is_none()) {
+            return Err(CtpError::ConfigError("需要配置日期时间列，或同时配置日期列和时间列".to_string()));
+        }
+        if config.mapping.instrument.is_none() && config.instrument_id.is_none() {
+            return Err(CtpError::ConfigError("需要配置合约列或指定合约代码".to_string()));
+        }
+
+        let timezone = config
+            .timezone
+            .parse::<Tz>()
+            .map_err(|e| CtpError::ConfigError(format!("无效的时区 {}: {}", config.timezone, e)))?;
+
+        Ok(Self {
+            config,
+            timezone,
+            specs: HashMap::new(),
+        })
+    }
+
+    /// 设置合约规格，设置后未知合约和不符合最小变动价位的数据会被拒绝
+    pub fn with_instrument_specs(mut self, specs: Vec<InstrumentInfo>) -> Self {
+        self.specs = specs.into_iter().map(|s| (s.instrument_id.clone(), s)).collect();
+        self
+    }
+
+    /// 导入 CSV 文件到归档
+    pub fn import_file(&self, path: &Path, store: &TickCompactor) -> Result<ImportReport, CtpError> {
+        let file = std::fs::File::open(path)?;
+        let (report, grouped) = self.parse(file)?;
+
+        for ((instrument_id, trading_day), bars) in &grouped {
+            let bars: Vec<ArchivedBar> = bars.values().cloned().collect();
+            store.write_bars(*trading_day, instrument_id, self.config.granularity, &bars)?;
+        }
+
+        info!(
+            "导入 K 线 {}: 读取 {} 行，导入 {} 行，拒绝 {} 行",
+            path.display(),
+            report.rows_read,
+            report.rows_imported,
+            report.rows_rejected
+        );
+        Ok(report)
+    }
+
+    /// 解析 CSV，按（合约, 交易日）分组
+    fn parse<R: std::io::Read>(&self, reader: R) -> Result<(ImportReport, GroupedBars), CtpError> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .delimiter(self.config.delimiter)
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(reader);
+
+        let headers = csv_reader
+            .headers()
+            .map_err(|e| CtpError::ConversionError(format!("读取 CSV 表头失败: {}", e)))?
+            .clone();
+        let columns = ColumnIndex::resolve(&self.config.mapping, &headers)?;
+
+        let mut report = ImportReport::default();
+        let mut grouped: GroupedBars = BTreeMap::new();
+
+        for (row, record) in csv_reader.records().enumerate() {
+            // 表头占第 1 行
+            let line = row as u64 + 2;
+            report.rows_read += 1;
+
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    report.reject(line, format!("CSV 格式错误: {}", e));
+                    continue;
+                }
+            };
+
+            match self.parse_record(&columns, &record) {
+                Ok((trading_day, bar)) => {
+                    let bars = grouped.entry((bar.instrument_id.clone(), trading_day)).or_default();
+                    if bars.insert(bar.time.clone(), bar).is_some() {
+                        report.duplicates += 1;
+                    }
+                    report.rows_imported += 1;
+                }
+                Err(message) => report.reject(line, message),
+            }
+        }
+
+        let mut instruments: Vec<String> = grouped.keys().map(|(id, _)| id.clone()).collect();
+        instruments.dedup();
+        let mut days: Vec<NaiveDate> = grouped.keys().map(|(_, day)| *day).collect();
+        days.sort();
+        days.dedup();
+        report.instruments = instruments;
+        report.trading_days = days;
+
+        if report.rows_rejected > 0 {
+            warn!("K 线导入有 {} 行被拒绝", report.rows_rejected);
+        }
+        Ok((report, grouped))
+    }
+
+    fn parse_record(
+        &self,
+        columns: &ColumnIndex,
+        record: &csv::StringRecord,
+    ) -> Result<(NaiveDate, ArchivedBar), String> {
+        let field = |index: usize| record.get(index).unwrap_or("");
+
+        let instrument_id = match columns.instrument {
+            Some(index) => self.config.naming.normalize(field(index)),
+            None => self.config.instrument_id.clone().unwrap_or_default(),
+        };
+        if instrument_id.is_empty() {
+            return Err("合约代码为空".to_string());
+        }
+
+        let source_time = match (columns.datetime, columns.date, columns.time) {
+            (Some(index), _, _) => NaiveDateTime::parse_from_str(field(index), &self.config.datetime_format)
+                .or_else(|_| {
+                    // 日线数据常只有日期
+                    NaiveDate::parse_from_str(field(index), &self.config.date_format)
+                        .map(|d| d.and_time(NaiveTime::MIN))
+                })
+                .map_err(|e| format!("时间格式错误 {}: {}", field(index), e))?,
+            (None, Some(date), Some(time)) => {
+                let date = NaiveDate::parse_from_str(field(date), &self.config.date_format)
+                    .map_err(|e| format!("日期格式错误 {}: {}", field(date), e))?;
+                let time = NaiveTime::parse_from_str(field(time), &self.config.time_format)
+                    .map_err(|e| format!("时间格式错误 {}: {}", field(time), e))?;
+                date.and_time(time)
+            }
+            _ => return Err("缺少时间列".to_string()),
+        };
+        let local_time = self.to_exchange_time(source_time)?;
+
+        let number = |name: &str, index: usize| -> Result<f64, String> {
+            field(index)
+                .replace(',', "")
+                .parse::<f64>()
+                .map_err(|_| format!("{} 不是有效数字: {}", name, field(index)))
+        };
+        let optional = |name: &str, index: Option<usize>| -> Result<f64, String> {
+            match index {
+                Some(index) if !field(index).is_empty() => number(name, index),
+                _ => Ok(0.0),
+            }
+        };
+
+        let (time, trading_day) = if self.config.granularity == StorageGranularity::Bar1d {
+            ("00:00:00".to_string(), local_time.date())
+        } else {
+            (local_time.format("%H:%M:%S").to_string(), trading_day_of(local_time))
+        };
+
+        let bar = ArchivedBar {
+            instrument_id,
+            time,
+            open: number("open", columns.open)?,
+            high: number("high", columns.high)?,
+            low: number("low", columns.low)?,
+            close: number("close", columns.close)?,
+            volume: optional("volume", columns.volume)? as i64,
+            turnover: optional("turnover", columns.turnover)?,
+            open_interest: optional("open_interest", columns.open_interest)? as i64,
+            tick_count: 0,
+        };
+        self.validate(&bar)?;
+        Ok((trading_day, bar))
+    }
+
+    /// 源时区时间转换为交易所本地时间
+    fn to_exchange_time(&self, time: NaiveDateTime) -> Result<NaiveDateTime, String> {
+        if self.timezone == EXCHANGE_TIMEZONE {
+            return Ok(time);
+        }
+        self.timezone
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|t| t.with_timezone(&EXCHANGE_TIMEZONE).naive_local())
+            .ok_or_else(|| format!("时间在源时区中不存在: {}", time))
+    }
+
+    /// 按合约规格校验
+    fn validate(&self, bar: &ArchivedBar) -> Result<(), String> {
+        if [bar.open, bar.high, bar.low, bar.close].iter().any(|p| !p.is_finite() || *p <= 0.0) {
+            return Err("价格必须为正数".to_string());
+        }
+        if bar.high < bar.open.max(bar.close) || bar.low > bar.open.min(bar.close) || bar.low > bar.high {
+            return Err(format!(
+                "OHLC 不一致: O={} H={} L={} C={}",
+                bar.open, bar.high, bar.low, bar.close
+            ));
+        }
+        if bar.volume < 0 || bar.turnover < 0.0 || bar.open_interest < 0 {
+            return Err("成交量、成交额、持仓量不能为负".to_string());
+        }
+
+        if self.specs.is_empty() {
+            return Ok(());
+        }
+        let spec = self
+            .specs
+            .get(&bar.instrument_id)
+            .ok_or_else(|| format!("未知合约: {}", bar.instrument_id))?;
+        if spec.price_tick > 0.0 {
+            for price in [bar.open, bar.high, bar.low, bar.close] {
+                let ticks = price / spec.price_tick;
+                if (ticks - ticks.round()).abs() > 1e-6 {
+                    return Err(format!("价格 {} 不是最小变动价位 {} 的整数倍", price, spec.price_tick));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 默认休市日配置文件
+pub const DEFAULT_HOLIDAYS_FILE: &str = "./config/holidays.toml";
+
+/// 交易所休市日（法定节假日），推算交易日时与周末一并跳过
+static HOLIDAYS: RwLock<BTreeSet<NaiveDate>> = RwLock::new(BTreeSet::new());
+
+/// 休市日配置
+#[derive(Debug, Default, Deserialize)]
+struct HolidayFile {
+    #[serde(default)]
+    dates: Vec<NaiveDate>,
+}
+
+/// 加载休市日配置并登记，文件不存在时保持现有登记；返回休市日数量
+pub fn load_holidays(path: impl AsRef<Path>) -> Result<usize, CtpError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(0);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let file: HolidayFile =
+        toml::from_str(&content).map_err(|e| CtpError::ConfigError(format!("休市日配置解析失败: {}", e)))?;
+    let count = file.dates.len();
+    set_holidays(file.dates);
+    Ok(count)
+}
+
+/// 替换登记的休市日
+pub fn set_holidays(dates: impl IntoIterator<Item = NaiveDate>) {
+    *HOLIDAYS.write().unwrap() = dates.into_iter().collect();
+}
+
+/// 是否为交易日（非周末且未登记为休市日）
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    is_trading_day_in(date, &HOLIDAYS.read().unwrap())
+}
+
+/// 该日之后的第一个交易日
+pub fn next_trading_day(date: NaiveDate) -> NaiveDate {
+    next_trading_day_in(date, &HOLIDAYS.read().unwrap())
+}
+
+/// 该日之前的最近一个交易日
+pub fn previous_trading_day(date: NaiveDate) -> NaiveDate {
+    let holidays = HOLIDAYS.read().unwrap();
+    let mut previous = date - chrono::Duration::days(1);
+    while !is_trading_day_in(previous, &holidays) {
+        previous -= chrono::Duration::days(1);
+    }
+    previous
+}
+
+/// 交易所本地时间所属交易日
+///
+/// 夜盘（18 点后）归属下一个交易日；周五夜盘跨过午夜的部分（周六凌晨）同样归属下周一，
+/// 遇到登记的休市日顺延
+pub fn trading_day_of(local_time: NaiveDateTime) -> NaiveDate {
+    trading_day_in(local_time, &HOLIDAYS.read().unwrap())
+}
+
+fn trading_day_in(local_time: NaiveDateTime, holidays: &BTreeSet<NaiveDate>) -> NaiveDate {
+    let date = local_time.date();
+    if local_time.hour() < 18 && is_trading_day_in(date, holidays) {
+        return date;
+    }
+    next_trading_day_in(date, holidays)
+}
+
+fn is_trading_day_in(date: NaiveDate, holidays: &BTreeSet<NaiveDate>) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !holidays.contains(&date)
+}
+
+fn next_trading_day_in(date: NaiveDate, holidays: &BTreeSet<NaiveDate>) -> NaiveDate {
+    let mut next = date + chrono::Duration::days(1);
+    while !is_trading_day_in(next, holidays) {
+        next += chrono::Duration::days(1);
+    }
+    next
+}
+
+/// 表头列序号
+struct ColumnIndex {
+    datetime: Option<usize>,
+    date: Option<usize>,
+    time: Option<usize>,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+    turnover: Option<usize>,
+    open_interest: Option<usize>,
+    instrument: Option<usize>,
+}
+
+impl ColumnIndex {
+    fn resolve(mapping: &CsvColumnMapping, headers: &csv::StringRecord) -> Result<Self, CtpError> {
+        let find = |name: &str| -> Result<usize, CtpError> {
+            headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CtpError::ConfigError(format!("CSV 中缺少列: {}", name)))
+        };
+        let find_optional = |name: &Option<String>| -> Result<Option<usize>, CtpError> {
+            name.as_deref().map(find).transpose()
+        };
+
+        Ok(Self {
+            datetime: find_optional(&mapping.datetime)?,
+            date: find_optional(&mapping.date)?,
+            time: find_optional(&mapping.time)?,
+            open: find(&mapping.open)?,
+            high: find(&mapping.high)?,
+            low: find(&mapping.low)?,
+            close: find(&mapping.close)?,
+            volume: find_optional(&mapping.volume)?,
+            turnover: find_optional(&mapping.turnover)?,
+            open_interest: find_optional(&mapping.open_interest)?,
+            instrument: find_optional(&mapping.instrument)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::tick_compaction::CompactionConfig;
+
+    fn spec(instrument_id: &str, price_tick: f64) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 0,
+            min_market_order_volume: 0,
+            max_limit_order_volume: 0,
+            min_limit_order_volume: 0,
+            volume_multiple: 10,
+            price_tick,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.0,
+            short_margin_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_contract_naming() {
+        let mut naming = ContractNaming {
+            case: SymbolCase::Lower,
+            ..ContractNaming::default()
+        };
+        assert_eq!(naming.normalize("SHFE.RB2501"), "rb2501");
+
+        naming.aliases.insert("RB888".to_string(), "rb2501".to_string());
+        assert_eq!(naming.normalize("RB888"), "rb2501");
+    }
+
+    #[test]
+    fn test_trading_day_of_night_session() {
+        let friday_night = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap().and_hms_opt(21, 0, 0).unwrap();
+        assert_eq!(trading_day_of(friday_night), NaiveDate::from_ymd_opt(2025, 1, 6).unwrap());
+
+        let day = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(trading_day_of(day), day.date());
+
+        // 周五夜盘跨过午夜，周六凌晨仍属于下周一
+        let saturday_morning = NaiveDate::from_ymd_opt(2025, 1, 4).unwrap().and_hms_opt(0, 30, 0).unwrap();
+        assert_eq!(trading_day_of(saturday_morning), NaiveDate::from_ymd_opt(2025, 1, 6).unwrap());
+
+        // 周二凌晨属于周二
+        let tuesday_morning = NaiveDate::from_ymd_opt(2025, 1, 7).unwrap().and_hms_opt(1, 0, 0).unwrap();
+        assert_eq!(trading_day_of(tuesday_morning), tuesday_morning.date());
+    }
+
+    #[test]
+    fn test_trading_day_skips_holidays() {
+        // 2025 年国庆：9 月 30 日（周二）夜盘之后顺延到 10 月 9 日
+        let holidays: BTreeSet<NaiveDate> = (1..=8).map(|d| NaiveDate::from_ymd_opt(2025, 10, d).unwrap()).collect();
+        let after_holiday = NaiveDate::from_ymd_opt(2025, 10, 9).unwrap();
+
+        let night = NaiveDate::from_ymd_opt(2025, 9, 30).unwrap().and_hms_opt(21, 0, 0).unwrap();
+        assert_eq!(trading_day_in(night, &holidays), after_holiday);
+        let past_midnight = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap().and_hms_opt(0, 30, 0).unwrap();
+        assert_eq!(trading_day_in(past_midnight, &holidays), after_holiday);
+        assert!(!is_trading_day_in(NaiveDate::from_ymd_opt(2025, 10, 3).unwrap(), &holidays));
+        assert_eq!(trading_day_in(night, &BTreeSet::new()), NaiveDate::from_ymd_opt(2025, 10, 1).unwrap());
+    }
+
+    #[test]
+    fn test_load_holidays_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("holidays.toml");
+        assert_eq!(load_holidays(&path).unwrap(), 0);
+
+        std::fs::write(&path, "dates = [\"2031-05-01\", \"2031-05-02\"]\n").unwrap();
+        assert_eq!(load_holidays(&path).unwrap(), 2);
+        assert!(!is_trading_day(NaiveDate::from_ymd_opt(2031, 5, 1).unwrap()));
+        assert_eq!(next_trading_day(NaiveDate::from_ymd_opt(2031, 4, 30).unwrap()), NaiveDate::from_ymd_opt(2031, 5, 5).unwrap());
+    }
+
+    #[test]
+    fn test_import_csv_with_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let csv_path = root.join("bars.csv");
+        std::fs::write(
+            &csv_path,
+            "symbol,datetime,open,high,low,close,volume\n\
+             SHFE.rb2501,2025-01-02 01:00:00,3500,3510,3495,3505,120\n\
+             SHFE.rb2501,2025-01-02 01:01:00,3505,3500,3495,3505,80\n\
+             SHFE.rb2501,2025-01-02 01:02:00,3505.5,3506,3505,3506,10\n\
+             SHFE.hc2501,2025-01-02 01:00:00,3300,3310,3290,3300,50\n",
+        )
+        .unwrap();
+
+        let importer = BarImporter::new(BarImportConfig {
+            timezone: "UTC".to_string(),
+            ..BarImportConfig::default()
+        })
+        .unwrap()
+        .with_instrument_specs(vec![spec("rb2501", 1.0)]);
+
+        let store = TickCompactor::new(CompactionConfig::new(root.join("raw"), root.join("archive")));
+        let report = importer.import_file(&csv_path, &store).unwrap();
+
+        assert_eq!(report.rows_read, 4);
+        assert_eq!(report.rows_imported, 1);
+        // OHLC 不一致、不符合最小变动价位、未知合约
+        assert_eq!(report.rows_rejected, 3);
+        assert_eq!(report.issues[0].line, 3);
+
+        // UTC 01:00 即北京时间 09:00
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let bars = store.read_bars(day, "rb2501", StorageGranularity::Bar1m).unwrap();
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].time, "09:00:00");
+        assert_eq!(bars[0].volume, 120);
+    }
+}
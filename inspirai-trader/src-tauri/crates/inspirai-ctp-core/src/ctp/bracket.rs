@@ -0,0 +1,500 @@
+use crate::ctp::{hotkeys::splits_close_today, CtpError, OffsetFlag, OrderContingentCondition, OrderDirection, OrderRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 括号单子单的标签键，值为括号单 ID
+pub const BRACKET_TAG: &str = "bracket";
+
+/// 括号单的止损、止盈腿
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum BracketLeg {
+    StopLoss,
+    TakeProfit,
+}
+
+impl BracketLeg {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::StopLoss => "止损",
+            Self::TakeProfit => "止盈",
+        }
+    }
+}
+
+/// 附加的止损、止盈定义
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BracketSpec {
+    /// 止损触发价
+    #[serde(default)]
+    pub stop_loss: Option<f64>,
+    /// 止盈触发价
+    #[serde(default)]
+    pub take_profit: Option<f64>,
+    /// 触发后平仓委托价相对触发价的让价，0 表示按触发价报单
+    #[serde(default)]
+    pub slippage: f64,
+}
+
+/// 括号单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum BracketStatus {
+    /// 入场单未成交
+    PendingEntry,
+    /// 入场单已成交（或部分成交），止损止盈已挂载
+    Armed,
+    /// 某条腿已触发，平仓单已报出
+    Exiting,
+    /// 平仓完成
+    Closed,
+    /// 入场单未成交即撤销，或手动取消
+    Cancelled,
+}
+
+/// 子单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum BracketChildStatus {
+    /// 等待入场成交
+    Waiting,
+    /// 已挂载，监控触发价
+    Armed,
+    /// 已触发并报出平仓单
+    Triggered,
+    /// 平仓单全部成交
+    Filled,
+    /// 另一条腿触发或括号单取消
+    Cancelled,
+}
+
+/// 止损或止盈子单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct BracketChild {
+    pub leg: BracketLeg,
+    pub trigger_price: f64,
+    /// 挂载数量，随入场成交增加
+    pub volume: u32,
+    pub status: BracketChildStatus,
+    /// 触发后报出的平仓单引用
+    pub order_ref: Option<String>,
+    pub filled_volume: u32,
+}
+
+/// 括号单：入场单加附带的止损、止盈条件单，两条腿互斥（OCO）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct BracketOrder {
+    pub id: String,
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    pub entry_order_ref: String,
+    pub entry_volume: u32,
+    pub entry_filled: u32,
+    /// 入场单是否已结束（全部成交、撤单或拒单）
+    pub entry_done: bool,
+    pub children: Vec<BracketChild>,
+    pub status: BracketStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl BracketOrder {
+    pub fn child(&self, leg: BracketLeg) -> Option<&BracketChild> {
+        self.children.iter().find(|c| c.leg == leg)
+    }
+}
+
+/// 条件触发，调用方据此报出平仓单
+#[derive(Debug, Clone)]
+pub struct BracketTrigger {
+    pub bracket_id: String,
+    pub leg: BracketLeg,
+    pub order: OrderRequest,
+    /// 入场单仍有未成交部分时需撤销
+    pub cancel_entry: Option<String>,
+}
+
+struct BracketEntry {
+    bracket: BracketOrder,
+    entry: OrderRequest,
+    slippage: f64,
+    /// 平仓开平标志：持仓为当日开仓，上期所、能源中心须平今
+    close_flag: OffsetFlag,
+}
+
+#[derive(Default)]
+struct BookInner {
+    next_id: u64,
+    brackets: HashMap<String, BracketEntry>,
+    /// 入场单引用 -> 括号单 ID
+    entries: HashMap<String, String>,
+    /// 平仓单引用 -> (括号单 ID, 腿)
+    exits: HashMap<String, (String, BracketLeg)>,
+}
+
+/// 括号单簿
+///
+/// 止损、止盈为本地条件单：入场单成交后按实际成交手数挂载，行情触及触发价时
+/// 生成平仓单并撤销另一条腿；入场单未成交即撤销时括号单随之取消
+#[derive(Clone, Default)]
+pub struct BracketBook {
+    inner: Arc<Mutex<BookInner>>,
+}
+
+impl BracketBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 校验止损止盈与入场单方向、价格是否一致
+    pub fn validate(entry: &OrderRequest, spec: &BracketSpec) -> Result<(), CtpError> {
+        if entry.offset_flag != OffsetFlag::Open {
+            return Err(CtpError::ValidationError("括号单的入场单必须为开仓".to_string()));
+        }
+        if spec.stop_loss.is_none() && spec.take_profit.is_none() {
+            return Err(CtpError::ValidationError("括号单至少需要止损或止盈之一".to_string()));
+        }
+        if spec.slippage < 0.0 {
+            return Err(CtpError::ValidationError("平仓让价不能为负".to_string()));
+        }
+        let long = entry.direction == OrderDirection::Buy;
+        for (leg, price) in [(BracketLeg::StopLoss, spec.stop_loss), (BracketLeg::TakeProfit, spec.take_profit)] {
+            let Some(price) = price else { continue };
+            if price <= 0.0 {
+                return Err(CtpError::ValidationError(format!("{}触发价必须为正", leg.label())));
+            }
+            // 市价入场（价格为 0）时无法比较
+            if entry.price > 0.0 {
+                let below = price < entry.price;
+                let expected_below = (leg == BracketLeg::StopLoss) == long;
+                if below != expected_below {
+                    return Err(CtpError::ValidationError(format!(
+                        "{}触发价 {} 与入场价 {} 的方向不符",
+                        leg.label(), price, entry.price
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 登记已报出的入场单，`exchange_id` 为合约所属交易所，决定平仓单报平今还是平仓
+    pub fn create(
+        &self,
+        entry_order_ref: &str,
+        exchange_id: &str,
+        entry: &OrderRequest,
+        spec: &BracketSpec,
+    ) -> Result<BracketOrder, CtpError> {
+        Self::validate(entry, spec)?;
+        let now = chrono::Utc::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let id = format!("BRK{}", inner.next_id);
+        let children = [(BracketLeg::StopLoss, spec.stop_loss), (BracketLeg::TakeProfit, spec.take_profit)]
+            .into_iter()
+            .filter_map(|(leg, price)| {
+                price.map(|trigger_price| BracketChild {
+                    leg,
+                    trigger_price,
+                    volume: 0,
+                    status: BracketChildStatus::Waiting,
+                    order_ref: None,
+                    filled_volume: 0,
+                })
+            })
+            .collect();
+        let bracket = BracketOrder {
+            id: id.clone(),
+            instrument_id: entry.instrument_id.clone(),
+            direction: entry.direction,
+            entry_order_ref: entry_order_ref.to_string(),
+            entry_volume: entry.volume,
+            entry_filled: 0,
+            entry_done: false,
+            children,
+            status: BracketStatus::PendingEntry,
+            created_at: now,
+            updated_at: now,
+        };
+        inner.entries.insert(entry_order_ref.to_string(), id.clone());
+        inner.brackets.insert(
+            id,
+            BracketEntry {
+                bracket: bracket.clone(),
+                entry: entry.clone(),
+                slippage: spec.slippage,
+                close_flag: if splits_close_today(exchange_id) { OffsetFlag::CloseToday } else { OffsetFlag::Close },
+            },
+        );
+        Ok(bracket)
+    }
+
+    /// 成交回报：入场单成交时按成交手数挂载子单，平仓单全部成交时结束括号单
+    pub fn on_trade(&self, order_ref: &str, volume: u32) -> Option<BracketOrder> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        if let Some(id) = inner.entries.get(order_ref) {
+            let entry = inner.brackets.get_mut(id)?;
+            let bracket = &mut entry.bracket;
+            bracket.entry_filled = (bracket.entry_filled + volume).min(bracket.entry_volume);
+            if bracket.entry_filled >= bracket.entry_volume {
+                bracket.entry_done = true;
+            }
+            if bracket.status == BracketStatus::PendingEntry {
+                bracket.status = BracketStatus::Armed;
+            }
+            for child in bracket.children.iter_mut().filter(|c| c.status != BracketChildStatus::Cancelled) {
+                if child.status == BracketChildStatus::Waiting {
+                    child.status = BracketChildStatus::Armed;
+                }
+                if child.status == BracketChildStatus::Armed {
+                    child.volume = bracket.entry_filled;
+                }
+            }
+            bracket.updated_at = chrono::Utc::now();
+            return Some(bracket.clone());
+        }
+
+        let (id, leg) = inner.exits.get(order_ref)?.clone();
+        let bracket = &mut inner.brackets.get_mut(&id)?.bracket;
+        let child = bracket.children.iter_mut().find(|c| c.leg == leg)?;
+        child.filled_volume = (child.filled_volume + volume).min(child.volume);
+        if child.filled_volume >= child.volume {
+            child.status = BracketChildStatus::Filled;
+            bracket.status = BracketStatus::Closed;
+            tracing::info!("括号单 {} {}平仓完成", id, leg.label());
+        }
+        bracket.updated_at = chrono::Utc::now();
+        Some(bracket.clone())
+    }
+
+    /// 入场单撤单或拒单；`volume_traded` 为柜台报告的成交手数，未成交即结束时取消括号单
+    pub fn on_entry_finished(&self, order_ref: &str, volume_traded: u32) -> Option<BracketOrder> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let id = inner.entries.get(order_ref)?;
+        let bracket = &mut inner.brackets.get_mut(id)?.bracket;
+        if bracket.entry_done {
+            return None;
+        }
+        bracket.entry_done = true;
+        if volume_traded == 0 && bracket.entry_filled == 0 && bracket.status == BracketStatus::PendingEntry {
+            bracket.status = BracketStatus::Cancelled;
+            for child in &mut bracket.children {
+                child.status = BracketChildStatus::Cancelled;
+            }
+            tracing::info!("括号单 {} 的入场单未成交即结束，已取消", bracket.id);
+        }
+        bracket.updated_at = chrono::Utc::now();
+        Some(bracket.clone())
+    }
+
+    /// 行情更新，返回触发的平仓单；触发腿之外的另一条腿同时撤销
+    pub fn on_price(&self, instrument_id: &str, price: f64) -> Vec<BracketTrigger> {
+        let mut triggers = Vec::new();
+        let mut inner = self.inner.lock().unwrap();
+        for entry in inner.brackets.values_mut() {
+            let bracket = &mut entry.bracket;
+            if bracket.status != BracketStatus::Armed || bracket.instrument_id != instrument_id {
+                continue;
+            }
+            let long = bracket.direction == OrderDirection::Buy;
+            let Some(leg) = bracket
+                .children
+                .iter()
+                .filter(|c| c.status == BracketChildStatus::Armed && c.volume > 0)
+                .find(|c| match (c.leg, long) {
+                    (BracketLeg::StopLoss, true) | (BracketLeg::TakeProfit, false) => price <= c.trigger_price,
+                    (BracketLeg::StopLoss, false) | (BracketLeg::TakeProfit, true) => price >= c.trigger_price,
+                })
+                .map(|c| c.leg)
+            else {
+                continue;
+            };
+
+            let mut order = entry.entry.clone();
+            let child = bracket.children.iter_mut().find(|c| c.leg == leg).unwrap();
+            child.status = BracketChildStatus::Triggered;
+            order.order_ref = String::new();
+            order.direction = if long { OrderDirection::Sell } else { OrderDirection::Buy };
+            order.offset_flag = entry.close_flag;
+            order.price = if long { child.trigger_price - entry.slippage } else { child.trigger_price + entry.slippage };
+            order.volume = child.volume;
+            order.contingent_condition = OrderContingentCondition::Immediately;
+            order.stop_price = 0.0;
+            order.tags.insert(BRACKET_TAG.to_string(), bracket.id.clone());
+            for sibling in bracket.children.iter_mut().filter(|c| c.leg != leg) {
+                sibling.status = BracketChildStatus::Cancelled;
+            }
+            bracket.status = BracketStatus::Exiting;
+            bracket.updated_at = chrono::Utc::now();
+            tracing::info!("括号单 {} 触发{}，{} 手 @ {}", bracket.id, leg.label(), order.volume, order.price);
+
+            triggers.push(BracketTrigger {
+                bracket_id: bracket.id.clone(),
+                leg,
+                order,
+                cancel_entry: (!bracket.entry_done).then(|| bracket.entry_order_ref.clone()),
+            });
+        }
+        triggers
+    }
+
+    /// 记录触发后报出的平仓单引用
+    pub fn attach_exit_order(&self, bracket_id: &str, leg: BracketLeg, order_ref: &str) -> Option<BracketOrder> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let bracket = &mut inner.brackets.get_mut(bracket_id)?.bracket;
+        let child = bracket.children.iter_mut().find(|c| c.leg == leg)?;
+        child.order_ref = Some(order_ref.to_string());
+        inner.exits.insert(order_ref.to_string(), (bracket_id.to_string(), leg));
+        Some(bracket.clone())
+    }
+
+    /// 手动取消括号单，撤销未触发的子单；返回括号单和需撤销的入场单引用
+    pub fn cancel(&self, bracket_id: &str) -> Result<(BracketOrder, Option<String>), CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        let bracket = &mut inner
+            .brackets
+            .get_mut(bracket_id)
+            .ok_or_else(|| CtpError::NotFound(format!("括号单不存在: {}", bracket_id)))?
+            .bracket;
+        if matches!(bracket.status, BracketStatus::Exiting | BracketStatus::Closed | BracketStatus::Cancelled) {
+            return Err(CtpError::StateError(format!("括号单 {} 已触发或结束", bracket_id)));
+        }
+        for child in &mut bracket.children {
+            child.status = BracketChildStatus::Cancelled;
+        }
+        bracket.status = BracketStatus::Cancelled;
+        bracket.updated_at = chrono::Utc::now();
+        let cancel_entry = (!bracket.entry_done).then(|| bracket.entry_order_ref.clone());
+        Ok((bracket.clone(), cancel_entry))
+    }
+
+    pub fn get(&self, bracket_id: &str) -> Option<BracketOrder> {
+        self.inner.lock().unwrap().brackets.get(bracket_id).map(|e| e.bracket.clone())
+    }
+
+    /// 所有括号单，按创建时间排序
+    pub fn list(&self) -> Vec<BracketOrder> {
+        let mut brackets: Vec<BracketOrder> = self.inner.lock().unwrap().brackets.values().map(|e| e.bracket.clone()).collect();
+        brackets.sort_by_key(|b| b.created_at);
+        brackets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{OrderForceCloseReason, OrderPriceType, OrderTimeCondition, OrderType, OrderVolumeCondition};
+
+    fn buy_open(price: f64, volume: u32) -> OrderRequest {
+        OrderRequest {
+            instrument_id: "rb2501".to_string(),
+            order_ref: String::new(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price,
+            volume,
+            order_type: OrderType::Limit,
+            price_type: OrderPriceType::Limit,
+            time_condition: OrderTimeCondition::GFD,
+            volume_condition: OrderVolumeCondition::Any,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags: Default::default(),
+        }
+    }
+
+    fn spec() -> BracketSpec {
+        BracketSpec {
+            stop_loss: Some(3450.0),
+            take_profit: Some(3600.0),
+            slippage: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_partial_fill_arms_scaled_children_and_oco() {
+        let book = BracketBook::new();
+        let bracket = book.create("1", "DCE", &buy_open(3500.0, 5), &spec()).unwrap();
+        // 入场未成交时不触发
+        assert!(book.on_price("rb2501", 3400.0).is_empty());
+
+        let armed = book.on_trade("1", 3).unwrap();
+        assert_eq!(armed.status, BracketStatus::Armed);
+        assert_eq!(armed.child(BracketLeg::StopLoss).unwrap().volume, 3);
+        assert!(book.on_price("rb2501", 3500.0).is_empty());
+
+        let triggers = book.on_price("rb2501", 3449.0);
+        assert_eq!(triggers.len(), 1);
+        let trigger = &triggers[0];
+        assert_eq!(trigger.leg, BracketLeg::StopLoss);
+        assert_eq!(trigger.order.direction, OrderDirection::Sell);
+        assert_eq!(trigger.order.offset_flag, OffsetFlag::Close);
+        assert_eq!((trigger.order.volume, trigger.order.price), (3, 3448.0));
+        assert_eq!(trigger.order.tags.get(BRACKET_TAG), Some(&bracket.id));
+        assert_eq!(trigger.cancel_entry.as_deref(), Some("1"));
+
+        let exiting = book.get(&bracket.id).unwrap();
+        assert_eq!(exiting.status, BracketStatus::Exiting);
+        assert_eq!(exiting.child(BracketLeg::TakeProfit).unwrap().status, BracketChildStatus::Cancelled);
+        // 另一条腿已撤销，不再触发
+        assert!(book.on_price("rb2501", 3700.0).is_empty());
+
+        book.attach_exit_order(&bracket.id, BracketLeg::StopLoss, "9");
+        assert_eq!(book.on_trade("9", 2).unwrap().status, BracketStatus::Exiting);
+        let closed = book.on_trade("9", 1).unwrap();
+        assert_eq!(closed.status, BracketStatus::Closed);
+        assert_eq!(closed.child(BracketLeg::StopLoss).unwrap().status, BracketChildStatus::Filled);
+    }
+
+    #[test]
+    fn test_validation_and_unfilled_entry_cancel() {
+        let book = BracketBook::new();
+        let inverted = BracketSpec {
+            stop_loss: Some(3600.0),
+            take_profit: Some(3450.0),
+            slippage: 0.0,
+        };
+        assert!(book.create("1", "SHFE", &buy_open(3500.0, 1), &inverted).is_err());
+        assert!(book.create("1", "SHFE", &buy_open(3500.0, 1), &BracketSpec::default()).is_err());
+
+        let bracket = book.create("2", "SHFE", &buy_open(3500.0, 1), &spec()).unwrap();
+        let cancelled = book.on_entry_finished("2", 0).unwrap();
+        assert_eq!(cancelled.status, BracketStatus::Cancelled);
+        assert!(cancelled.children.iter().all(|c| c.status == BracketChildStatus::Cancelled));
+        assert!(book.cancel(&bracket.id).is_err());
+
+        let pending = book.create("3", "SHFE", &buy_open(3500.0, 2), &spec()).unwrap();
+        let (cancelled, entry) = book.cancel(&pending.id).unwrap();
+        assert_eq!(cancelled.status, BracketStatus::Cancelled);
+        assert_eq!(entry.as_deref(), Some("3"));
+        assert_eq!(book.list().len(), 2);
+    }
+
+    #[test]
+    fn test_shfe_exit_closes_today() {
+        // 括号单持仓为当日开仓，上期所平仓需报平今
+        let book = BracketBook::new();
+        let bracket = book.create("1", "SHFE", &buy_open(3500.0, 2), &spec()).unwrap();
+        book.on_trade("1", 2);
+        let triggers = book.on_price("rb2501", 3601.0);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].leg, BracketLeg::TakeProfit);
+        assert_eq!(triggers[0].order.offset_flag, OffsetFlag::CloseToday);
+        assert_eq!((triggers[0].order.volume, triggers[0].order.price), (2, 3598.0));
+        assert_eq!(triggers[0].bracket_id, bracket.id);
+    }
+}
@@ -0,0 +1,345 @@
+use super::{ClientState, ConnectionManager};
+use crate::ctp::{
+    error::CtpError,
+    events::{CtpEvent, EventHandler},
+    models::*,
+    programmatic_filing::RelayMode,
+    session_health::SideStatus,
+};
+use std::time::Duration;
+
+/// 默认登录结果等待时间
+const DEFAULT_LOGIN_WAIT: Duration = Duration::from_millis(2000);
+
+/// 可重试的认证错误
+const RETRYABLE_AUTH_ERRORS: [&str; 4] = ["网络连接失败", "服务器繁忙", "连接超时", "CTP:还没有初始化"];
+
+/// 认证与登录
+///
+/// 行情端直接登录，交易端先认证，登录由交易 SPI 在认证成功后发起；
+/// 交易端失败而行情端可用时以仅行情的降级模式继续，之后可通过 `retry_trader_login` 单独重试
+pub struct AuthManager {
+    login_wait: Duration,
+}
+
+impl Default for AuthManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuthManager {
+    pub fn new() -> Self {
+        Self {
+            login_wait: DEFAULT_LOGIN_WAIT,
+        }
+    }
+
+    /// 发起登录后等待回调更新会话状态的时间
+    pub fn with_login_wait(mut self, login_wait: Duration) -> Self {
+        self.login_wait = login_wait;
+        self
+    }
+
+    /// 用户登录
+    pub async fn login(
+        &self,
+        connection: &ConnectionManager,
+        events: &EventHandler,
+        credentials: LoginCredentials,
+    ) -> Result<LoginResponse, CtpError> {
+        if !matches!(connection.state(), ClientState::Connected) {
+            return Err(CtpError::ConnectionError("未连接到服务器".to_string()));
+        }
+
+        connection.set_state(ClientState::LoggingIn);
+
+        tracing::info!("开始用户登录，用户ID: {}", credentials.user_id);
+
+        // 发起真实的登录请求
+        self.req_user_login(connection, &credentials);
+
+        // 等待登录响应
+        let timeout = connection.config().timeout();
+        let login_future = self.wait_for_login(connection, events);
+
+        match tokio::time::timeout(timeout, login_future).await {
+            Ok(result) => {
+                result?;
+                tracing::info!("用户登录成功");
+
+                // 从事件中获取登录响应信息
+                let login_response = LoginResponse {
+                    trading_day: chrono::Utc::now().format("%Y%m%d").to_string(),
+                    login_time: chrono::Utc::now().format("%H:%M:%S").to_string(),
+                    broker_id: credentials.broker_id.clone(),
+                    user_id: credentials.user_id.clone(),
+                    system_name: "CTP交易系统".to_string(),
+                    front_id: 1,
+                    session_id: 1,
+                    max_order_ref: "1".to_string(),
+                };
+
+                Ok(login_response)
+            }
+            Err(_) => {
+                let error = CtpError::TimeoutError;
+                connection.set_state(ClientState::Error(error.to_string()));
+                Err(error)
+            }
+        }
+    }
+
+    /// 行情端已登录时单独认证交易端，用于备用实例接管交易会话
+    ///
+    /// 交易端认证失败时行情端保持登录，客户端处于降级模式
+    pub async fn login_trader(&self, connection: &ConnectionManager) -> Result<(), CtpError> {
+        if !matches!(connection.state(), ClientState::Connected) {
+            return Err(CtpError::ConnectionError("交易前置未连接".to_string()));
+        }
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+        connection.set_state(ClientState::LoggingIn);
+
+        use ctp2rs::ffi::AssignFromString;
+        let mut auth_req = ctp2rs::v1alpha1::CThostFtdcReqAuthenticateField::default();
+        auth_req.BrokerID.assign_from_str(&config.broker_id);
+        auth_req.UserID.assign_from_str(&config.investor_id);
+        auth_req.AppID.assign_from_str(&config.app_id);
+        auth_req.AuthCode.assign_from_str(&config.auth_code);
+
+        let request_id = connection.next_request_id();
+        tracing::info!("接管交易会话，发送交易认证请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.wire_log().request("ReqAuthenticate", request_id, &auth_req);
+        let result = trader_api.req_authenticate(&mut auth_req, request_id);
+        if result != 0 {
+            connection.set_state(ClientState::LoggedIn);
+            let message = format!("交易认证请求发送失败: {}", result);
+            connection.session_health().lock().unwrap().mark_trader_failed(&message);
+            return Err(CtpError::CtpApiError { code: result, message });
+        }
+
+        tokio::time::sleep(self.login_wait).await;
+        connection.set_state(ClientState::LoggedIn);
+        if let SideStatus::Failed(reason) = &connection.session_health().lock().unwrap().td {
+            return Err(CtpError::AuthenticationError(format!("交易端登录失败: {}", reason)));
+        }
+        Ok(())
+    }
+
+    /// 重试交易端认证（单次尝试，不影响行情端）
+    ///
+    /// 结果通过交易 SPI 回调异步更新会话状态
+    pub fn retry_trader_login(&self, connection: &ConnectionManager) -> Result<u32, CtpError> {
+        if !connection.is_degraded() {
+            return Err(CtpError::StateError("当前未处于降级模式".to_string()));
+        }
+
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+        let session_health = connection.session_health();
+
+        // 认证已通过（SPI 置为 Connecting）时直接发起登录，否则重新认证
+        let authenticated = matches!(session_health.lock().unwrap().td, SideStatus::Connecting);
+        let attempt = session_health.lock().unwrap().mark_trader_retrying();
+        let request_id = connection.next_request_id();
+        connection.track_td_request(request_id);
+
+        use ctp2rs::ffi::AssignFromString;
+        let result = if authenticated {
+            let mut login_req = ctp2rs::v1alpha1::CThostFtdcReqUserLoginField::default();
+            login_req.BrokerID.assign_from_str(&config.broker_id);
+            login_req.UserID.assign_from_str(&config.investor_id);
+            login_req.Password.assign_from_str(&config.password);
+
+            tracing::info!("降级模式下重试交易登录，第 {} 次，请求ID: {}", attempt, request_id);
+            connection.wire_log().request("ReqUserLogin", request_id, &login_req);
+            trader_api.req_user_login(&mut login_req, request_id)
+        } else {
+            let mut auth_req = ctp2rs::v1alpha1::CThostFtdcReqAuthenticateField::default();
+            auth_req.BrokerID.assign_from_str(&config.broker_id);
+            auth_req.UserID.assign_from_str(&config.investor_id);
+            auth_req.AppID.assign_from_str(&config.app_id);
+            auth_req.AuthCode.assign_from_str(&config.auth_code);
+
+            tracing::info!("降级模式下重试交易认证，第 {} 次，请求ID: {}", attempt, request_id);
+            connection.wire_log().request("ReqAuthenticate", request_id, &auth_req);
+            trader_api.req_authenticate(&mut auth_req, request_id)
+        };
+
+        if result != 0 {
+            let message = format!("交易端重试请求发送失败: {}", result);
+            session_health.lock().unwrap().mark_trader_failed(&message);
+            return Err(CtpError::CtpApiError { code: result, message });
+        }
+
+        Ok(attempt)
+    }
+
+    /// 中继模式下代终端上报采集信息
+    ///
+    /// 多连接模式须在认证成功后、登录前调用（RegisterUserSystemInfo），操作员登录模式在登录后调用
+    /// （SubmitUserSystemInfo）；直连时 API 自动采集，返回 false
+    pub fn report_user_system_info(&self, connection: &ConnectionManager) -> Result<bool, CtpError> {
+        let config = connection.config();
+        let Some(info) = config.programmatic.user_system_info(&config.app_id)? else {
+            return Ok(false);
+        };
+        let trader_api = connection.trader_api()?;
+
+        use ctp2rs::ffi::AssignFromString;
+        let mut field = ctp2rs::v1alpha1::CThostFtdcUserSystemInfoField::default();
+        field.BrokerID.assign_from_str(&config.broker_id);
+        field.UserID.assign_from_str(&config.investor_id);
+        for (dst, src) in field.ClientSystemInfo.iter_mut().zip(&info.system_info) {
+            *dst = *src as std::os::raw::c_char;
+        }
+        field.ClientSystemInfoLen = info.system_info.len() as i32;
+        field.ClientPublicIP.assign_from_str(&info.public_ip);
+        field.ClientIPPort = info.ip_port;
+        field.ClientAppID.assign_from_str(&info.app_id);
+        field.ClientLoginTime.assign_from_str(&chrono::Local::now().format("%H:%M:%S").to_string());
+
+        let result = match config.programmatic.relay_mode {
+            RelayMode::MultiConnection => trader_api.register_user_system_info(&mut field),
+            _ => trader_api.submit_user_system_info(&mut field),
+        };
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "终端信息上报失败".to_string(),
+            });
+        }
+        tracing::info!("已上报终端信息 ({:?})，公网 IP: {}", config.programmatic.relay_mode, info.public_ip);
+        Ok(true)
+    }
+
+    /// 检查认证错误是否可重试
+    pub fn is_retryable_error(&self, error_msg: &str) -> bool {
+        RETRYABLE_AUTH_ERRORS.iter().any(|&err| error_msg.contains(err))
+    }
+
+    /// 发起用户登录请求
+    fn req_user_login(&self, connection: &ConnectionManager, credentials: &LoginCredentials) {
+        tracing::info!("发起用户登录请求");
+
+        use ctp2rs::ffi::AssignFromString;
+        // 发起行情登录
+        if let Ok(md_api) = connection.md_api() {
+            let mut req = ctp2rs::v1alpha1::CThostFtdcReqUserLoginField::default();
+            req.BrokerID.assign_from_str(&credentials.broker_id);
+            req.UserID.assign_from_str(&credentials.user_id);
+            req.Password.assign_from_str(&credentials.password);
+
+            let request_id = connection.next_request_id();
+
+            tracing::info!("发送行情登录请求，经纪商: {}, 用户: {}, 请求ID: {}",
+                credentials.broker_id, credentials.user_id, request_id);
+
+            connection.track_md_request(request_id);
+            connection.wire_log().request("ReqUserLogin", request_id, &req);
+            md_api.req_user_login(&mut req, request_id);
+        }
+
+        // 发起交易登录（需要先认证）
+        if let Ok(trader_api) = connection.trader_api() {
+            let mut auth_req = ctp2rs::v1alpha1::CThostFtdcReqAuthenticateField::default();
+            auth_req.BrokerID.assign_from_str(&credentials.broker_id);
+            auth_req.UserID.assign_from_str(&credentials.user_id);
+            auth_req.AppID.assign_from_str(&credentials.app_id);
+            auth_req.AuthCode.assign_from_str(&credentials.auth_code);
+
+            let auth_request_id = connection.next_request_id();
+
+            tracing::info!("发送交易认证请求，应用ID: {}, 请求ID: {}",
+                credentials.app_id, auth_request_id);
+
+            connection.track_td_request(auth_request_id);
+            connection.wire_log().request("ReqAuthenticate", auth_request_id, &auth_req);
+            trader_api.req_authenticate(&mut auth_req, auth_request_id);
+        }
+    }
+
+    /// 等待登录完成
+    async fn wait_for_login(&self, connection: &ConnectionManager, events: &EventHandler) -> Result<(), CtpError> {
+        tracing::info!("等待登录完成");
+
+        // 简单的等待逻辑，实际应该通过事件来处理
+        tokio::time::sleep(self.login_wait).await;
+
+        // 交易端失败时，若行情端可用则以降级模式继续
+        let health = connection.session_health().lock().unwrap().clone();
+        if let SideStatus::Failed(reason) = &health.td {
+            if matches!(health.md, SideStatus::Failed(_)) {
+                let error = CtpError::AuthenticationError(format!("行情与交易端均登录失败: {}", reason));
+                connection.set_state(ClientState::Error(error.to_string()));
+                return Err(error);
+            }
+            tracing::warn!("交易端登录失败，以仅行情的降级模式继续运行: {}", reason);
+            connection.set_state(ClientState::LoggedIn);
+            return Ok(());
+        }
+
+        // 假设登录成功
+        connection.set_state(ClientState::LoggedIn);
+        let config = connection.config();
+        if config.programmatic.relay_mode == RelayMode::OperatorLogin {
+            if let Err(e) = self.report_user_system_info(connection) {
+                tracing::warn!("上报终端信息失败: {}", e);
+            }
+        }
+        events.send_event(CtpEvent::LoginSuccess(LoginResponse {
+            trading_day: chrono::Utc::now().format("%Y%m%d").to_string(),
+            login_time: chrono::Utc::now().format("%H:%M:%S").to_string(),
+            broker_id: config.broker_id.clone(),
+            user_id: config.investor_id.clone(),
+            system_name: "CTP交易系统".to_string(),
+            front_id: 1,
+            session_id: 1,
+            max_order_ref: "1".to_string(),
+        }))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::connection::test_manager;
+    use super::*;
+    use crate::ctp::config::ConnectionMode;
+
+    fn credentials() -> LoginCredentials {
+        LoginCredentials {
+            broker_id: "9999".to_string(),
+            user_id: "000001".to_string(),
+            password: "secret".to_string(),
+            app_id: "simnow_client_test".to_string(),
+            auth_code: "0000000000000000".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_degrades_when_only_trader_fails() {
+        let auth = AuthManager::new().with_login_wait(Duration::from_millis(1));
+        let connection = test_manager(ConnectionMode::Full);
+        let events = EventHandler::new();
+        assert!(matches!(
+            auth.login(&connection, &events, credentials()).await,
+            Err(CtpError::ConnectionError(_))
+        ));
+        assert!(matches!(auth.retry_trader_login(&connection), Err(CtpError::StateError(_))));
+
+        connection.set_state(ClientState::Connected);
+        connection.session_health().lock().unwrap().set_md(SideStatus::Ready);
+        connection.session_health().lock().unwrap().mark_trader_failed("认证失败");
+        auth.login(&connection, &events, credentials()).await.unwrap();
+        assert!(connection.is_logged_in() && connection.is_degraded());
+        // 尚未连接前置时无法重试
+        assert!(auth.retry_trader_login(&connection).is_err());
+
+        assert!(auth.is_retryable_error("CTP:还没有初始化"));
+        assert!(!auth.is_retryable_error("用户名或密码错误"));
+    }
+}
@@ -0,0 +1,496 @@
+use super::{ClientState, ConnectionStats};
+use crate::ctp::{
+    api_usage::{ApiRequestKind, ApiUsageSnapshot, ApiUsageTracker, UsageLevel},
+    config::{ConnectionMode, CtpConfig},
+    connection_quality::{ConnectionQuality, SharedConnectionQuality},
+    correlation::CorrelationRegistry,
+    diagnostics::{DiagnosticEvent, DiagnosticHub, DiagnosticSeverity, DiagnosticSource},
+    error::CtpError,
+    ffi::CtpApiManager,
+    session_health::{SessionHealth, SharedSessionHealth, SideStatus},
+    wire_log::WireLogger,
+};
+use ctp2rs::v1alpha1::{MdApi, MdSpi, TraderApi, TraderSpi};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// 连接管理：前置连接、客户端状态、请求编号与接口用量
+///
+/// 持有 CTP API 实例，其他组件通过 `md_api`、`trader_api` 取用；
+/// 未连接、未登录或连接模式不支持时由 `ensure_*` 返回错误
+pub struct ConnectionManager {
+    config: CtpConfig,
+    /// 由 SPI 回调同步更新
+    state: Arc<Mutex<ClientState>>,
+    api_manager: Option<CtpApiManager>,
+    /// 连接开始时间
+    connect_start_time: Option<Instant>,
+    /// 重连计数器
+    reconnect_count: u32,
+    /// 行情/交易两侧会话健康状态
+    session_health: SharedSessionHealth,
+    /// 行情/交易两侧连接质量统计
+    connection_quality: SharedConnectionQuality,
+    /// 请求/报单到前端关联 ID 的映射
+    correlations: CorrelationRegistry,
+    /// 按交易日的报单、撤单、查询次数
+    api_usage: ApiUsageTracker,
+    /// CTP 请求与回调报文日志
+    wire_log: WireLogger,
+    diagnostics: DiagnosticHub,
+}
+
+impl ConnectionManager {
+    pub fn new(config: CtpConfig, api_usage: ApiUsageTracker, wire_log: WireLogger, diagnostics: DiagnosticHub) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(ClientState::Disconnected)),
+            api_manager: None,
+            connect_start_time: None,
+            reconnect_count: 0,
+            session_health: SessionHealth::shared(),
+            connection_quality: ConnectionQuality::shared(),
+            correlations: CorrelationRegistry::default(),
+            api_usage,
+            wire_log,
+            diagnostics,
+        }
+    }
+
+    pub fn config(&self) -> &CtpConfig {
+        &self.config
+    }
+
+    /// 与 SPI 共享的客户端状态
+    pub fn shared_state(&self) -> Arc<Mutex<ClientState>> {
+        self.state.clone()
+    }
+
+    pub fn session_health(&self) -> &SharedSessionHealth {
+        &self.session_health
+    }
+
+    pub fn connection_quality(&self) -> &SharedConnectionQuality {
+        &self.connection_quality
+    }
+
+    pub fn correlations(&self) -> &CorrelationRegistry {
+        &self.correlations
+    }
+
+    pub fn wire_log(&self) -> &WireLogger {
+        &self.wire_log
+    }
+
+    /// 当日报单、撤单、查询次数及与阈值的距离
+    pub fn api_usage(&self) -> ApiUsageSnapshot {
+        self.api_usage.snapshot()
+    }
+
+    /// 按连接模式创建 API 实例、注册 SPI 和前置地址，等待连接建立
+    pub async fn connect(
+        &mut self,
+        md_spi: Box<dyn MdSpi + Send>,
+        trader_spi: Box<dyn TraderSpi + Send>,
+    ) -> Result<(), CtpError> {
+        self.connect_start_time = Some(Instant::now());
+        self.set_state(ClientState::Connecting);
+        {
+            let mut health = self.session_health.lock().unwrap();
+            health.reset();
+            if self.config.connection_mode.uses_md() {
+                health.set_md(SideStatus::Connecting);
+            }
+            if self.config.connection_mode.uses_td() {
+                health.td = SideStatus::Connecting;
+            }
+        }
+
+        let mode = self.config.connection_mode;
+        tracing::info!("开始连接 CTP 服务器，连接模式: {}", mode);
+        if mode.uses_md() {
+            tracing::info!("行情服务器: {}", self.config.md_front_addr);
+        }
+        if mode.uses_td() {
+            tracing::info!("交易服务器: {}", self.config.trader_front_addr);
+        }
+
+        // 验证动态库路径
+        if let Err(e) = self.validate_libraries() {
+            self.set_state(ClientState::Error(e.to_string()));
+            return Err(e);
+        }
+
+        // 初始化 CTP API 管理器，使用 ctp2rs 官方 API
+        let mut api_manager = CtpApiManager::new()?;
+
+        // 按连接模式创建 API 实例，使用配置中的动态库路径
+        if mode.uses_md() {
+            let md_dynlib_path = self.config.get_md_dynlib_path()?;
+            api_manager.create_md_api(&self.config.flow_path, md_dynlib_path)?;
+        }
+        if mode.uses_td() {
+            let td_dynlib_path = self.config.get_td_dynlib_path()?;
+            api_manager.create_trader_api(&self.config.flow_path, td_dynlib_path)?;
+        }
+
+        // 注册 SPI 到对应的 API，未启用的一侧跳过
+        if mode.uses_md() {
+            api_manager.register_md_spi(md_spi)?;
+        }
+        if mode.uses_td() {
+            api_manager.register_trader_spi(trader_spi)?;
+        }
+
+        // 注册前置机地址并发起连接
+        self.register_front_addresses(&api_manager)?;
+
+        self.api_manager = Some(api_manager);
+
+        // 等待连接建立
+        let timeout = self.config.timeout();
+        let connect_future = self.wait_for_connection();
+
+        match tokio::time::timeout(timeout, connect_future).await {
+            Ok(result) => {
+                result?;
+                self.reconnect_count = 0; // 重置重连计数器
+
+                let elapsed = self.connect_start_time.unwrap().elapsed();
+                tracing::info!("CTP 服务器连接成功，耗时: {:?}", elapsed);
+                Ok(())
+            }
+            Err(_) => {
+                let error = CtpError::TimeoutError;
+                self.set_state(ClientState::Error(error.to_string()));
+                Err(error)
+            }
+        }
+    }
+
+    /// 记录失败的连接尝试次数
+    pub fn record_failed_attempt(&mut self, attempt: u32) {
+        self.reconnect_count = attempt;
+    }
+
+    /// 释放 API 实例
+    pub fn disconnect(&mut self) {
+        self.set_state(ClientState::Disconnected);
+        self.api_manager = None;
+    }
+
+    /// 在已登录的行情会话上建立交易端：创建交易 API、注册 SPI 并连接交易前置，连接模式升为完整模式
+    ///
+    /// 用于备用实例接管交易会话；交易前置未能在超时内连接时释放交易端，行情端保持登录
+    pub async fn connect_trader(&mut self, trader_spi: Box<dyn TraderSpi + Send>) -> Result<(), CtpError> {
+        let api_manager = self
+            .api_manager
+            .as_mut()
+            .ok_or_else(|| CtpError::StateError("行情会话未建立".to_string()))?;
+        let td_dynlib_path = self.config.get_td_dynlib_path()?;
+        api_manager.create_trader_api(&self.config.flow_path, td_dynlib_path)?;
+        api_manager.register_trader_spi(trader_spi)?;
+        let trader_api = self.trader_api()?;
+
+        self.config.connection_mode = ConnectionMode::Full;
+        self.session_health.lock().unwrap().td = SideStatus::Connecting;
+        // 交易前置连接成功后由交易 SPI 将状态置为 Connected
+        self.set_state(ClientState::Connecting);
+        tracing::info!("注册交易前置机: {}", self.config.trader_front_addr);
+        trader_api.register_front(&self.config.trader_front_addr);
+        trader_api.init();
+
+        if let Err(e) = self.wait_for_connection().await {
+            self.disconnect_trader();
+            self.set_state(ClientState::LoggedIn);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// 只释放交易端，行情端保持连接和登录；连接模式降为仅行情
+    pub fn disconnect_trader(&mut self) {
+        if let Some(api_manager) = self.api_manager.as_mut() {
+            api_manager.release_trader();
+        }
+        if self.config.connection_mode == ConnectionMode::Full {
+            self.config.connection_mode = ConnectionMode::MdOnly;
+        }
+        self.session_health.lock().unwrap().td = SideStatus::Idle;
+    }
+
+    /// 断开并清零重连统计
+    pub fn reset(&mut self) {
+        self.disconnect();
+        self.reconnect_count = 0;
+        self.connect_start_time = None;
+    }
+
+    /// 注册前置机地址并发起连接
+    fn register_front_addresses(&self, api_manager: &CtpApiManager) -> Result<(), CtpError> {
+        tracing::info!("注册前置机地址");
+
+        // 注册行情前置机地址
+        if let Some(md_api) = api_manager.get_md_api() {
+            tracing::info!("注册行情前置机: {}", self.config.md_front_addr);
+            md_api.register_front(&self.config.md_front_addr);
+
+            // 发起行情连接
+            md_api.init();
+        }
+
+        // 注册交易前置机地址
+        if let Some(trader_api) = api_manager.get_trader_api() {
+            tracing::info!("注册交易前置机: {}", self.config.trader_front_addr);
+            trader_api.register_front(&self.config.trader_front_addr);
+
+            // 发起交易连接
+            trader_api.init();
+        }
+
+        tracing::info!("前置机地址注册完成，等待连接建立");
+        Ok(())
+    }
+
+    /// 等待连接建立
+    async fn wait_for_connection(&self) -> Result<(), CtpError> {
+        tracing::info!("等待 CTP 连接建立");
+
+        // 等待连接事件或超时
+        let timeout_duration = self.config.timeout();
+        let start_time = std::time::Instant::now();
+
+        while start_time.elapsed() < timeout_duration {
+            // 检查是否收到连接成功事件
+            if matches!(self.state(), ClientState::Connected) {
+                tracing::info!("CTP 连接已建立");
+                return Ok(());
+            }
+
+            // 短暂等待后再次检查
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        self.diagnostics.publish(DiagnosticEvent::new(
+            DiagnosticSeverity::Error,
+            DiagnosticSource::System,
+            format!("等待 CTP 连接超时（{} 秒）", timeout_duration.as_secs()),
+        ));
+        Err(CtpError::TimeoutError)
+    }
+
+    /// 验证动态库文件
+    fn validate_libraries(&self) -> Result<(), CtpError> {
+        let mode = self.config.connection_mode;
+
+        if let Some(md_path) = self.config.md_dynlib_path.as_ref().filter(|_| mode.uses_md()) {
+            if !md_path.exists() {
+                return Err(CtpError::LibraryLoadError(
+                    format!("行情动态库文件不存在: {:?}", md_path)
+                ));
+            }
+        }
+
+        if let Some(td_path) = self.config.td_dynlib_path.as_ref().filter(|_| mode.uses_td()) {
+            if !td_path.exists() {
+                return Err(CtpError::LibraryLoadError(
+                    format!("交易动态库文件不存在: {:?}", td_path)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取当前状态
+    pub fn state(&self) -> ClientState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// 设置状态
+    pub(crate) fn set_state(&self, new_state: ClientState) {
+        let mut state = self.state.lock().unwrap();
+        if *state != new_state {
+            tracing::debug!("CTP 客户端状态变更: {:?} -> {:?}", *state, new_state);
+            *state = new_state;
+        }
+    }
+
+    /// 检查是否已连接
+    pub fn is_connected(&self) -> bool {
+        matches!(self.state(), ClientState::Connected | ClientState::LoggingIn | ClientState::LoggedIn)
+    }
+
+    /// 检查是否已登录
+    pub fn is_logged_in(&self) -> bool {
+        matches!(self.state(), ClientState::LoggedIn)
+    }
+
+    /// 是否处于仅行情的降级模式
+    pub fn is_degraded(&self) -> bool {
+        self.config.connection_mode == ConnectionMode::Full
+            && self.is_logged_in()
+            && self.session_health.lock().unwrap().is_degraded()
+    }
+
+    /// 获取连接统计信息
+    pub fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            state: self.state(),
+            reconnect_count: self.reconnect_count,
+            connect_duration: self.connect_start_time.map(|start| start.elapsed()),
+            config_environment: self.config.environment,
+        }
+    }
+
+    /// 未登录时返回认证错误
+    pub fn ensure_logged_in(&self) -> Result<(), CtpError> {
+        if !self.is_logged_in() {
+            return Err(CtpError::AuthenticationError("用户未登录".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 检查当前连接模式是否支持行情操作
+    pub fn ensure_md_supported(&self) -> Result<(), CtpError> {
+        if !self.config.connection_mode.uses_md() {
+            return Err(CtpError::StateError(
+                format!("当前连接模式 {} 不支持行情操作", self.config.connection_mode)
+            ));
+        }
+        Ok(())
+    }
+
+    /// 检查交易端是否可用
+    pub fn ensure_trader_available(&self) -> Result<(), CtpError> {
+        if !self.config.connection_mode.uses_td() {
+            return Err(CtpError::StateError(
+                format!("当前连接模式 {} 不支持交易操作", self.config.connection_mode)
+            ));
+        }
+
+        let health = self.session_health.lock().unwrap();
+        match &health.td {
+            SideStatus::Failed(reason) => Err(CtpError::StateError(
+                format!("交易端不可用，当前处于仅行情的降级模式: {}", reason)
+            )),
+            SideStatus::Retrying { attempt } => Err(CtpError::StateError(
+                format!("交易端正在恢复中（第 {} 次重试）", attempt)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// 行情 API，未连接时返回状态错误
+    pub fn md_api(&self) -> Result<Arc<MdApi>, CtpError> {
+        self.api_manager
+            .as_ref()
+            .ok_or_else(|| CtpError::StateError("API 管理器未初始化".to_string()))?
+            .get_md_api()
+            .ok_or_else(|| CtpError::StateError("行情 API 未初始化".to_string()))
+    }
+
+    /// 交易 API，未连接时返回状态错误
+    pub fn trader_api(&self) -> Result<Arc<TraderApi>, CtpError> {
+        self.api_manager
+            .as_ref()
+            .ok_or_else(|| CtpError::StateError("API 管理器未初始化".to_string()))?
+            .get_trader_api()
+            .ok_or_else(|| CtpError::StateError("交易 API 未初始化".to_string()))
+    }
+
+    /// 获取下一个请求ID，并绑定当前关联 ID
+    pub fn next_request_id(&self) -> i32 {
+        // 简单的请求ID生成，实际应该使用原子计数器
+        let request_id = chrono::Utc::now().timestamp_millis() as i32 % 1000000;
+        self.correlations.bind_request(request_id);
+        request_id
+    }
+
+    /// 记录交易端请求发送时间，用于计算往返时延
+    pub fn track_td_request(&self, request_id: i32) {
+        self.connection_quality.lock().unwrap().td.record_request(request_id);
+    }
+
+    /// 记录行情端请求发送时间
+    pub fn track_md_request(&self, request_id: i32) {
+        self.connection_quality.lock().unwrap().md.record_request(request_id);
+    }
+
+    /// 计入当日接口用量，接近或超过阈值时发布诊断事件
+    pub fn track_api_usage(&self, kind: ApiRequestKind) {
+        for warning in self.api_usage.record(kind) {
+            let severity = match warning.meter.level {
+                UsageLevel::Exceeded => DiagnosticSeverity::Error,
+                _ => DiagnosticSeverity::Warning,
+            };
+            self.diagnostics.publish(DiagnosticEvent::new(
+                severity,
+                DiagnosticSource::Td,
+                format!("交易日 {} 接口用量{}", warning.trading_day, warning.message),
+            ));
+        }
+    }
+
+    /// 会话管理 - 保持会话活跃
+    pub async fn keep_session_alive(&self) -> Result<(), CtpError> {
+        tracing::debug!("保持会话活跃");
+
+        // 定期发送心跳或查询请求来保持会话
+        if self.trader_api().is_ok() {
+            // 发送一个简单的查询请求作为心跳
+            let request_id = self.next_request_id();
+
+            // 这里可以发送查询交易日等轻量级请求
+            tracing::debug!("发送心跳查询，请求ID: {}", request_id);
+
+            // 实际的心跳实现需要根据 CTP API 的具体方法来调用
+            // trader_api.req_qry_trading_day(request_id);
+        }
+
+        Ok(())
+    }
+}
+
+/// 未连接的连接管理器，供各组件测试使用
+#[cfg(test)]
+pub(super) fn test_manager(mode: ConnectionMode) -> ConnectionManager {
+    let config = CtpConfig {
+        connection_mode: mode,
+        ..CtpConfig::default()
+    };
+    ConnectionManager::new(
+        config,
+        ApiUsageTracker::new(crate::ctp::api_usage::ApiUsageLimits::default()),
+        WireLogger::new(crate::ctp::wire_log::WireLogConfig::default()),
+        crate::ctp::events::EventHandler::new().diagnostics(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guards_follow_state_and_mode() {
+        let connection = test_manager(ConnectionMode::Full);
+        assert!(!connection.is_connected());
+        assert!(matches!(connection.ensure_logged_in(), Err(CtpError::AuthenticationError(_))));
+        assert!(matches!(connection.trader_api(), Err(CtpError::StateError(_))));
+
+        connection.set_state(ClientState::LoggedIn);
+        assert!(connection.is_connected() && connection.ensure_logged_in().is_ok());
+        {
+            let mut health = connection.session_health().lock().unwrap();
+            health.set_md(SideStatus::Ready);
+            health.mark_trader_failed("认证失败");
+        }
+        assert!(connection.is_degraded());
+        assert!(connection.ensure_trader_available().is_err());
+        assert!(connection.ensure_md_supported().is_ok());
+
+        let md_only = test_manager(ConnectionMode::MdOnly);
+        assert!(md_only.ensure_trader_available().is_err());
+        assert_eq!(md_only.stats().reconnect_count, 0);
+    }
+}
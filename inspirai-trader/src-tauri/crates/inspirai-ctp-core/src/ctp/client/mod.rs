@@ -0,0 +1,870 @@
+//! CTP 客户端
+//!
+//! `CtpClient` 是对外的统一入口，内部按职责拆分为连接管理（`ConnectionManager`）、
+//! 认证登录（`AuthManager`）、行情订阅（`SubscriptionClient`）、报单撤单（`OrderClient`）
+//! 和查询（`QueryClient`）五个组件；跨组件的交易流程在 `workflows` 中编排。
+//! 各组件通过 `&ConnectionManager` 取用 API 实例与请求编号，可单独构造和测试。
+
+mod auth;
+mod connection;
+mod order;
+mod query;
+mod subscription;
+mod workflows;
+
+pub use auth::AuthManager;
+pub use connection::ConnectionManager;
+pub use order::OrderClient;
+pub use query::QueryClient;
+pub use subscription::SubscriptionClient;
+
+use crate::ctp::{
+    api_usage::{ApiUsageLimits, ApiUsageSnapshot, ApiUsageTracker, DEFAULT_API_USAGE_CONFIG_FILE},
+    client_order_id::{ClientOrderIds, ClientOrderRecord, DEFAULT_CLIENT_ORDER_DIR},
+    compliance_monitor::{ComplianceConfig, ComplianceMonitor, ComplianceStatus, DEFAULT_COMPLIANCE_CONFIG_FILE},
+    config::{ConnectionMode, CtpConfig},
+    connection_quality::ConnectionQualityReport,
+    currency::{AccountBalances, AggregatedEquity, FxConverter},
+    diagnostics::DiagnosticHub,
+    error::CtpError,
+    error_explainer::{ErrorExplainer, DEFAULT_ERROR_HINTS_FILE},
+    event_bus::{BusSubscriber, BusTopic, SubscriberLag},
+    events::{CtpEvent, EventHandler},
+    funds_monitor::FundsMonitor,
+    market_order::MarketOrderEmulator,
+    market_overview::MarketOverview,
+    models::*,
+    order_flow::OrderFlowAnalyzer,
+    order_manager::OrderManager,
+    order_preview::RateCache,
+    orderbook_heatmap::OrderBookHeatmap,
+    position_manager::PositionManager,
+    price_limit::PriceLimitTracker,
+    reconciliation::{Reconciler, ReconciliationSummary},
+    rejection_breaker::RejectionBreaker,
+    rollover::{RolloverManager, DEFAULT_ROLLOVER_DIR},
+    round_trip::{PairingMethod, RoundTripBook, DEFAULT_ROUND_TRIP_DIR},
+    session_health::SessionHealth,
+    settlement_prices::{SettlementPriceStore, DEFAULT_SETTLEMENT_PRICE_FILE},
+    spi::{MdSpiImpl, TraderSpiImpl},
+    timeline::{Timeline, DEFAULT_TIMELINE_DIR},
+    trade_dedup::{TradeDeduplicator, DEFAULT_TRADE_DEDUP_DIR},
+    trading_switchboard::{TradingSwitchboard, DEFAULT_SWITCHBOARD_FILE},
+    utils::{ConversionPools, PoolStats},
+    wire_log::{WireLogConfig, WireLogger, DEFAULT_WIRE_LOG_CONFIG_FILE},
+};
+use ctp2rs::v1alpha1::{MdSpi, TraderSpi};
+use tokio::sync::mpsc;
+use std::time::Duration;
+
+/// 客户端状态
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum ClientState {
+    /// 未连接
+    Disconnected,
+    /// 连接中
+    Connecting,
+    /// 已连接
+    Connected,
+    /// 登录中
+    LoggingIn,
+    /// 已登录
+    LoggedIn,
+    /// 错误状态
+    Error(String),
+}
+
+/// CTP 客户端
+pub struct CtpClient {
+    event_handler: EventHandler,
+    /// 前置连接、客户端状态与请求编号
+    connection: ConnectionManager,
+    /// 认证与登录
+    auth: AuthManager,
+    /// 已订阅的合约
+    subscriptions: SubscriptionClient,
+    /// 报单与撤单
+    orders: OrderClient,
+    /// 账户、持仓与费率查询
+    queries: QueryClient,
+    /// 盘口热力图数据源
+    order_book_heatmap: OrderBookHeatmap,
+    /// 各交易所市场概览统计
+    market_overview: MarketOverview,
+    /// 逐笔主动买卖与大单分析
+    order_flow: OrderFlowAnalyzer,
+    /// 资金曲线异常检测
+    funds_monitor: FundsMonitor,
+    /// 风控参数
+    risk_params: Option<RiskParams>,
+    /// 账户活动时间线
+    timeline: Timeline,
+    /// 按来源的拒单熔断
+    rejection_breaker: RejectionBreaker,
+    /// 持仓与成交配对的回合交易
+    position_manager: PositionManager,
+    /// 拒单说明
+    error_explainer: ErrorExplainer,
+    /// 各合约涨跌停状态
+    price_limits: PriceLimitTracker,
+    /// 市价单模拟与追单
+    market_orders: MarketOrderEmulator,
+    /// 临近到期持仓的移仓执行
+    rollovers: RolloverManager,
+    /// 行情回调线程绑定的 CPU 核
+    md_callback_core: Option<usize>,
+    /// 成交回报去重，跨重连保留
+    trade_dedup: TradeDeduplicator,
+    /// 行情和结算单采集的结算价
+    settlement_prices: SettlementPriceStore,
+    /// 分币种资金
+    account_balances: AccountBalances,
+    /// 断线重连后的对账，完成前拒绝新订单
+    reconciler: Reconciler,
+    /// 对账使用的本地报单簿，由报单、成交回报维护
+    order_book: OrderManager,
+    /// 按合约、品种的交易开关，下单前检查
+    switchboard: TradingSwitchboard,
+}
+
+impl CtpClient {
+    /// 创建新的 CTP 客户端
+    pub async fn new(config: CtpConfig) -> Result<Self, CtpError> {
+        // 验证配置
+        config.validate()?;
+
+        tracing::info!("创建 CTP 客户端，经纪商: {}", config.broker_id);
+
+        let timeline = Timeline::open(DEFAULT_TIMELINE_DIR, &config.investor_id).unwrap_or_else(|e| {
+            tracing::warn!("打开账户时间线失败，仅保存在内存: {}", e);
+            Timeline::in_memory(&config.investor_id)
+        });
+        let client_orders = ClientOrderIds::open(DEFAULT_CLIENT_ORDER_DIR, &config.investor_id).unwrap_or_else(|e| {
+            tracing::warn!("打开客户端订单号映射失败，仅保存在内存: {}", e);
+            ClientOrderIds::in_memory()
+        });
+        let round_trips = RoundTripBook::open(DEFAULT_ROUND_TRIP_DIR, &config.investor_id, PairingMethod::Fifo)
+            .unwrap_or_else(|e| {
+                tracing::warn!("打开回合交易记录失败，仅保存在内存: {}", e);
+                RoundTripBook::in_memory(PairingMethod::Fifo)
+            });
+        let error_explainer = ErrorExplainer::new(&config.broker_id)
+            .load_broker_hints(DEFAULT_ERROR_HINTS_FILE)
+            .unwrap_or_else(|e| {
+                tracing::warn!("{}", e);
+                ErrorExplainer::new(&config.broker_id)
+            });
+
+        let market_orders = MarketOrderEmulator::new(config.market_order_policy);
+        let rollovers = RolloverManager::open(DEFAULT_ROLLOVER_DIR, &config.investor_id).unwrap_or_else(|e| {
+            tracing::warn!("打开移仓记录失败，仅保存在内存: {}", e);
+            RolloverManager::in_memory()
+        });
+        let api_usage_limits = ApiUsageLimits::load(DEFAULT_API_USAGE_CONFIG_FILE).unwrap_or_else(|e| {
+            tracing::warn!("加载接口用量阈值失败，使用默认值: {}", e);
+            ApiUsageLimits::default()
+        });
+        let compliance_config = ComplianceConfig::load(DEFAULT_COMPLIANCE_CONFIG_FILE).unwrap_or_else(|e| {
+            tracing::warn!("加载合规阈值失败，使用默认值: {}", e);
+            ComplianceConfig::default()
+        });
+        let wire_log_config = WireLogConfig::load(DEFAULT_WIRE_LOG_CONFIG_FILE).unwrap_or_else(|e| {
+            tracing::warn!("加载报文日志配置失败，不启用报文日志: {}", e);
+            WireLogConfig::default()
+        });
+
+        let event_handler = EventHandler::new();
+        let market_overview = MarketOverview::new();
+        let position_manager = PositionManager::new()
+            .with_round_trip_book(round_trips)
+            .with_trade_dedup(open_trade_dedup(&config.investor_id, "positions"));
+        let trade_dedup = open_trade_dedup(&config.investor_id, "td_callback");
+        let orders = OrderClient::new(
+            client_orders,
+            ConversionPools::default(),
+            ComplianceMonitor::new(compliance_config),
+            event_handler.diagnostics(),
+        );
+        let queries = QueryClient::new(market_overview.clone(), position_manager.clone(), RateCache::default());
+        let connection = ConnectionManager::new(
+            config,
+            ApiUsageTracker::new(api_usage_limits),
+            WireLogger::new(wire_log_config),
+            event_handler.diagnostics(),
+        );
+
+        let client = Self {
+            event_handler,
+            connection,
+            auth: AuthManager::new(),
+            subscriptions: SubscriptionClient::new(),
+            orders,
+            queries,
+            order_book_heatmap: OrderBookHeatmap::new(),
+            market_overview,
+            order_flow: OrderFlowAnalyzer::new(),
+            funds_monitor: FundsMonitor::new(),
+            risk_params: None,
+            timeline,
+            rejection_breaker: RejectionBreaker::new(),
+            position_manager,
+            error_explainer,
+            price_limits: PriceLimitTracker::new(),
+            market_orders,
+            rollovers,
+            md_callback_core: None,
+            trade_dedup,
+            settlement_prices: SettlementPriceStore::open(DEFAULT_SETTLEMENT_PRICE_FILE).unwrap_or_else(|e| {
+                tracing::warn!("加载结算价失败，仅在内存中保存: {}", e);
+                SettlementPriceStore::in_memory()
+            }),
+            account_balances: AccountBalances::new(),
+            reconciler: Reconciler::new(),
+            order_book: OrderManager::new(),
+            switchboard: TradingSwitchboard::open(DEFAULT_SWITCHBOARD_FILE).unwrap_or_else(|e| {
+                tracing::warn!("加载交易开关状态失败，仅在内存中保存: {}", e);
+                TradingSwitchboard::in_memory()
+            }),
+        };
+
+        Ok(client)
+    }
+
+    /// 连接到 CTP 服务器
+    pub async fn connect(&mut self) -> Result<(), CtpError> {
+        let (md_spi, trader_spi) = self.build_spis();
+        self.connection.connect(md_spi, trader_spi).await
+    }
+
+    /// 带重连的连接方法
+    pub async fn connect_with_retry(&mut self) -> Result<(), CtpError> {
+        let (retry_interval, max_attempts) = self.reconnect_policy();
+
+        for attempt in 1..=max_attempts {
+            tracing::info!("连接尝试 {}/{}", attempt, max_attempts);
+
+            match self.connect().await {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    self.connection.record_failed_attempt(attempt);
+                    tracing::warn!("连接失败 (尝试 {}): {}", attempt, e);
+
+                    if attempt < max_attempts {
+                        tracing::info!("等待 {:?} 后重试...", retry_interval);
+                        tokio::time::sleep(retry_interval).await;
+                    }
+                }
+            }
+        }
+
+        let error = CtpError::ConnectionError(
+            format!("连接失败，已达到最大重试次数 {}", max_attempts)
+        );
+        self.connection.set_state(ClientState::Error(error.to_string()));
+        Err(error)
+    }
+
+    /// 创建行情、交易 SPI 实例，共享客户端的状态与各回调服务
+    fn build_spis(&self) -> (Box<dyn MdSpi + Send>, Box<dyn TraderSpi + Send>) {
+        tracing::info!("设置 SPI 回调处理器");
+        (self.build_md_spi(), self.build_trader_spi())
+    }
+
+    fn build_md_spi(&self) -> Box<dyn MdSpi + Send> {
+        let connection = &self.connection;
+
+        // 创建行情 SPI 实例
+        let md_spi = MdSpiImpl::new(
+            connection.shared_state(),
+            self.event_handler.sender(),
+            connection.config().clone(),
+        )
+        .with_session_health(connection.session_health().clone())
+        .with_connection_quality(connection.connection_quality().clone())
+        .with_order_book_heatmap(self.order_book_heatmap.clone())
+        .with_market_overview(self.market_overview.clone())
+        .with_order_flow(self.order_flow.clone())
+        .with_position_manager(self.position_manager.clone())
+        .with_price_limits(self.price_limits.clone())
+        .with_callback_core(self.md_callback_core)
+        .with_conversion_pools(self.orders.conversion_pools().clone())
+        .with_settlement_prices(self.settlement_prices.clone())
+        .with_timeline(self.timeline.clone())
+        .with_wire_log(connection.wire_log().clone())
+        .with_diagnostics(self.event_handler.diagnostics());
+        Box::new(md_spi)
+    }
+
+    fn build_trader_spi(&self) -> Box<dyn TraderSpi + Send> {
+        let connection = &self.connection;
+        let trader_spi = TraderSpiImpl::new(
+            connection.shared_state(),
+            self.event_handler.sender(),
+            connection.config().clone(),
+        )
+        .with_session_health(connection.session_health().clone())
+        .with_connection_quality(connection.connection_quality().clone())
+        .with_timeline(self.timeline.clone())
+        .with_rejection_breaker(self.rejection_breaker.clone())
+        .with_position_manager(self.position_manager.clone())
+        .with_error_explainer(self.error_explainer.clone())
+        .with_correlations(connection.correlations().clone())
+        .with_funds_monitor(self.funds_monitor.clone())
+        .with_market_orders(self.market_orders.clone())
+        .with_rollovers(self.rollovers.clone())
+        .with_compliance(self.orders.compliance().clone())
+        .with_client_order_ids(self.orders.client_orders().clone())
+        .with_trade_dedup(self.trade_dedup.clone())
+        .with_settlement_prices(self.settlement_prices.clone())
+        .with_account_balances(self.account_balances.clone())
+        .with_reconciliation(self.reconciler.clone(), self.order_book.clone())
+        .with_rate_cache(self.queries.rate_cache().clone())
+        .with_wire_log(connection.wire_log().clone())
+        .with_diagnostics(self.event_handler.diagnostics());
+        Box::new(trader_spi)
+    }
+
+    /// 用户登录，断线后重新登录时发起对账
+    pub async fn login(&mut self, credentials: LoginCredentials) -> Result<LoginResponse, CtpError> {
+        let response = self.auth.login(&self.connection, &self.event_handler, credentials).await?;
+        if self.reconciler.needs_start() && !self.connection.is_degraded() {
+            if let Err(e) = self.reconcile_after_reconnect().await {
+                tracing::warn!("重连对账未能发起: {}", e);
+            }
+        }
+        Ok(response)
+    }
+
+    /// 重新登录后发起对账：依次查询报单、成交、持仓，结果由交易 SPI 汇总对账
+    ///
+    /// 查询发送失败时放弃本次对账，不再阻止新订单；未收齐结果的对账超时后同样放行
+    pub async fn reconcile_after_reconnect(&mut self) -> Result<(), CtpError> {
+        self.reconciler.begin();
+        let result = self.send_reconciliation_queries().await;
+        if let Err(e) = &result {
+            self.reconciler.abort(&e.to_string());
+        }
+        result
+    }
+
+    async fn send_reconciliation_queries(&mut self) -> Result<(), CtpError> {
+        self.query_orders(None).await?;
+        // CTP 查询有流控，间隔发送
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.query_trades(None).await?;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.query_positions().await?;
+        Ok(())
+    }
+
+    /// 是否正在等待重连对账
+    pub fn is_reconciliation_pending(&self) -> bool {
+        self.reconciler.is_pending()
+    }
+
+    /// 交易端已恢复但尚未发起对账
+    pub fn needs_reconciliation(&self) -> bool {
+        self.reconciler.needs_start()
+    }
+
+    /// 最近一次对账结果
+    pub fn last_reconciliation(&self) -> Option<ReconciliationSummary> {
+        self.reconciler.last_summary()
+    }
+
+    /// 订阅行情数据
+    pub async fn subscribe_market_data(&mut self, instruments: &[String]) -> Result<(), CtpError> {
+        self.subscriptions.subscribe(&self.connection, instruments)
+    }
+
+    /// 取消订阅行情数据
+    pub async fn unsubscribe_market_data(&mut self, instruments: &[String]) -> Result<(), CtpError> {
+        self.subscriptions.unsubscribe(&self.connection, instruments)?;
+        for instrument in instruments {
+            self.order_book_heatmap.remove(instrument);
+            self.market_overview.remove(instrument);
+            self.order_flow.remove(instrument);
+        }
+        Ok(())
+    }
+
+    /// 提交订单
+    pub async fn submit_order(&mut self, order: OrderRequest) -> Result<String, CtpError> {
+        self.orders.submit(&self.connection, order).await
+    }
+
+    /// 撤销订单
+    pub async fn cancel_order(&mut self, order_id: &str) -> Result<(), CtpError> {
+        self.orders.cancel(&self.connection, order_id).await
+    }
+
+    /// 查询账户信息
+    pub async fn query_account(&mut self) -> Result<AccountInfo, CtpError> {
+        self.queries.query_account(&self.connection).await
+    }
+
+    /// 查询持仓信息
+    pub async fn query_positions(&mut self) -> Result<Vec<Position>, CtpError> {
+        self.queries.query_positions(&self.connection).await
+    }
+
+    /// 接管交易会话：在已登录的行情会话上建立交易端并认证，随后对账
+    ///
+    /// 用于备用实例升为主实例；已持有交易端时不做处理
+    pub async fn start_trader_session(&mut self) -> Result<(), CtpError> {
+        self.connection.ensure_logged_in()?;
+        if self.connection_mode().uses_td() {
+            return Ok(());
+        }
+        let trader_spi = self.build_trader_spi();
+        self.connection.connect_trader(trader_spi).await?;
+        self.auth.login_trader(&self.connection).await?;
+        // 接管前由其他实例报出的订单状态未知，对账完成前不接受新订单
+        self.reconcile_after_reconnect().await
+    }
+
+    /// 交出交易会话：只断开交易端，行情订阅与落盘继续；仅交易模式时整体断开
+    pub fn stop_trader_session(&mut self) {
+        match self.connection_mode() {
+            ConnectionMode::TdOnly => self.disconnect(),
+            ConnectionMode::Full => {
+                tracing::info!("断开交易端，保留行情会话");
+                self.connection.disconnect_trader();
+            }
+            ConnectionMode::MdOnly => {}
+        }
+    }
+
+    /// 断开连接
+    pub fn disconnect(&mut self) {
+        tracing::info!("断开 CTP 连接");
+
+        self.connection.disconnect();
+        let _ = self.event_handler.send_event(CtpEvent::Disconnected);
+    }
+
+    /// 连接管理组件
+    pub fn connection(&self) -> &ConnectionManager {
+        &self.connection
+    }
+
+    /// 认证登录组件
+    pub fn auth(&self) -> &AuthManager {
+        &self.auth
+    }
+
+    /// 行情订阅组件
+    pub fn subscriptions(&self) -> &SubscriptionClient {
+        &self.subscriptions
+    }
+
+    /// 报单撤单组件
+    pub fn orders(&self) -> &OrderClient {
+        &self.orders
+    }
+
+    /// 查询组件
+    pub fn queries(&self) -> &QueryClient {
+        &self.queries
+    }
+
+    /// 获取事件处理器
+    pub fn event_handler(&self) -> &EventHandler {
+        &self.event_handler
+    }
+
+    /// 获取诊断事件通道
+    pub fn diagnostics(&self) -> DiagnosticHub {
+        self.event_handler.diagnostics()
+    }
+
+    /// 获取事件发送器
+    pub fn event_sender(&self) -> mpsc::UnboundedSender<CtpEvent> {
+        self.event_handler.sender()
+    }
+
+    /// 取走事件接收端（由事件桥转发给前端窗口）
+    pub fn take_event_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<CtpEvent>> {
+        self.event_handler.take_receiver()
+    }
+
+    /// 启动事件总线，之后通过 `subscribe_events` 按主题订阅事件
+    pub fn start_event_bus(&mut self) -> bool {
+        self.event_handler.start_bus()
+    }
+
+    /// 替换交易开关，与其他持有者共享同一份停止交易状态
+    pub fn set_switchboard(&mut self, switchboard: TradingSwitchboard) {
+        self.switchboard = switchboard;
+    }
+
+    /// 交易开关
+    pub fn switchboard(&self) -> &TradingSwitchboard {
+        &self.switchboard
+    }
+
+    /// 行情回调线程绑定 CPU 核，须在连接前设置
+    pub fn set_md_callback_core(&mut self, core: Option<usize>) {
+        self.md_callback_core = core;
+    }
+
+    /// 在行情热路径运行时上启动事件总线，与界面命令隔离
+    pub fn start_event_bus_on(&mut self, handle: &tokio::runtime::Handle) -> bool {
+        self.event_handler.start_bus_on(handle)
+    }
+
+    /// 订阅事件总线，`topics` 为空时订阅全部主题
+    pub fn subscribe_events(&self, name: &str, topics: &[BusTopic], capacity: usize) -> BusSubscriber {
+        self.event_handler.subscribe(name, topics, capacity)
+    }
+
+    /// 事件总线各订阅者积压情况
+    pub fn event_bus_lag(&self) -> Vec<SubscriberLag> {
+        self.event_handler.bus().lag()
+    }
+
+    /// 获取当前状态
+    pub fn get_state(&self) -> ClientState {
+        self.connection.state()
+    }
+
+    /// 检查是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_connected()
+    }
+
+    /// 检查是否已登录
+    pub fn is_logged_in(&self) -> bool {
+        self.connection.is_logged_in()
+    }
+
+    /// 获取连接统计信息
+    pub fn get_connection_stats(&self) -> ConnectionStats {
+        self.connection.stats()
+    }
+
+    /// 获取会话健康状态
+    pub fn get_session_health(&self) -> SessionHealth {
+        self.connection.session_health().lock().unwrap().clone()
+    }
+
+    /// 获取连接质量报告
+    pub fn get_connection_quality(&self) -> ConnectionQualityReport {
+        self.connection.connection_quality().lock().unwrap().report()
+    }
+
+    /// 获取盘口热力图数据源
+    pub fn order_book_heatmap(&self) -> OrderBookHeatmap {
+        self.order_book_heatmap.clone()
+    }
+
+    /// 获取市场概览统计
+    pub fn market_overview(&self) -> MarketOverview {
+        self.market_overview.clone()
+    }
+
+    /// 获取订单流分析
+    pub fn order_flow(&self) -> OrderFlowAnalyzer {
+        self.order_flow.clone()
+    }
+
+    /// 获取资金曲线异常检测
+    pub fn funds_monitor(&self) -> FundsMonitor {
+        self.funds_monitor.clone()
+    }
+
+    /// 获取拒单说明服务
+    pub fn error_explainer(&self) -> &ErrorExplainer {
+        &self.error_explainer
+    }
+
+    /// 获取涨跌停状态跟踪
+    pub fn price_limits(&self) -> PriceLimitTracker {
+        self.price_limits.clone()
+    }
+
+    /// 获取持仓管理器（含已完成的回合交易）
+    pub fn position_manager(&self) -> PositionManager {
+        self.position_manager.clone()
+    }
+
+    /// 获取账户活动时间线
+    pub fn timeline(&self) -> Timeline {
+        self.timeline.clone()
+    }
+
+    /// 获取拒单熔断器
+    pub fn rejection_breaker(&self) -> RejectionBreaker {
+        self.rejection_breaker.clone()
+    }
+
+    /// 当日报单、撤单、查询次数及与阈值的距离
+    pub fn api_usage(&self) -> ApiUsageSnapshot {
+        self.connection.api_usage()
+    }
+
+    /// 按客户端订单号查对应的会话、报单引用和交易所编号
+    pub fn client_order(&self, client_order_id: &str) -> Option<ClientOrderRecord> {
+        self.orders.client_order(client_order_id)
+    }
+
+    /// 行情和结算单采集的结算价
+    pub fn settlement_prices(&self) -> &SettlementPriceStore {
+        &self.settlement_prices
+    }
+
+    /// 分币种资金
+    pub fn account_balances(&self) -> &AccountBalances {
+        &self.account_balances
+    }
+
+    /// 各币种资金按汇率折算到展示币种后的总权益
+    pub fn aggregated_equity(&self, display_currency: &str, fx: &dyn FxConverter) -> AggregatedEquity {
+        self.account_balances.aggregate(display_currency, fx)
+    }
+
+    /// 报单结构体池与代码缓存的命中率
+    pub fn conversion_pool_stats(&self) -> Vec<PoolStats> {
+        self.orders.pool_stats()
+    }
+
+    /// 账户及各报单来源的报单成交比、撤单比
+    pub fn compliance_statuses(&self) -> Vec<ComplianceStatus> {
+        self.orders.compliance_statuses()
+    }
+
+    /// CTP 请求与回调报文日志
+    pub fn wire_log(&self) -> &WireLogger {
+        self.connection.wire_log()
+    }
+
+    /// 是否处于仅行情的降级模式
+    pub fn is_degraded(&self) -> bool {
+        self.connection.is_degraded()
+    }
+
+    /// 获取当前连接模式
+    pub fn connection_mode(&self) -> ConnectionMode {
+        self.connection.config().connection_mode
+    }
+
+    /// 获取重连策略（重试间隔, 最大次数）
+    pub fn reconnect_policy(&self) -> (Duration, u32) {
+        let config = self.connection.config();
+        (config.reconnect_interval(), config.max_reconnect_attempts)
+    }
+
+    /// 重试交易端认证（单次尝试，不影响行情端）
+    ///
+    /// 结果通过交易 SPI 回调异步更新会话状态
+    pub fn retry_trader_login(&self) -> Result<u32, CtpError> {
+        self.auth.retry_trader_login(&self.connection)
+    }
+
+    /// 健康检查
+    pub async fn health_check(&self) -> Result<HealthStatus, CtpError> {
+        let state = self.get_state();
+        let is_healthy = matches!(state, ClientState::Connected | ClientState::LoggedIn);
+
+        let status = HealthStatus {
+            is_healthy,
+            state: state.clone(),
+            last_check_time: chrono::Utc::now(),
+            error_message: if let ClientState::Error(msg) = state {
+                Some(msg)
+            } else {
+                None
+            },
+        };
+
+        Ok(status)
+    }
+
+    /// 重置客户端状态
+    pub fn reset(&mut self) {
+        tracing::info!("重置 CTP 客户端状态");
+
+        self.disconnect();
+        self.connection.reset();
+    }
+
+    /// 获取配置信息（隐藏敏感信息）
+    pub fn get_config_info(&self) -> ConfigInfo {
+        let config = self.connection.config();
+        ConfigInfo {
+            environment: config.environment,
+            connection_mode: config.connection_mode,
+            broker_id: config.broker_id.clone(),
+            user_id: config.investor_id.clone(),
+            md_front_addr: config.md_front_addr.clone(),
+            trader_front_addr: config.trader_front_addr.clone(),
+            flow_path: config.flow_path.clone(),
+            timeout_secs: config.timeout_secs,
+            max_reconnect_attempts: config.max_reconnect_attempts,
+        }
+    }
+
+    /// 中继模式下代终端上报采集信息
+    ///
+    /// 多连接模式须在认证成功后、登录前调用（RegisterUserSystemInfo），操作员登录模式在登录后调用
+    /// （SubmitUserSystemInfo）；直连时 API 自动采集，返回 false
+    pub fn report_user_system_info(&self) -> Result<bool, CtpError> {
+        self.auth.report_user_system_info(&self.connection)
+    }
+
+    /// 添加已订阅的合约
+    pub fn add_subscribed_instrument(&self, instrument_id: &str) {
+        self.subscriptions.add(instrument_id);
+    }
+
+    /// 移除已订阅的合约
+    pub fn remove_subscribed_instrument(&self, instrument_id: &str) {
+        self.subscriptions.remove(instrument_id);
+    }
+
+    /// 检查合约是否已订阅
+    pub fn is_instrument_subscribed(&self, instrument_id: &str) -> bool {
+        self.subscriptions.contains(instrument_id)
+    }
+
+    /// 查询成交记录
+    pub async fn query_trades(&mut self, instrument_id: Option<&str>) -> Result<Vec<Trade>, CtpError> {
+        self.queries.query_trades(&self.connection, instrument_id).await
+    }
+
+    /// 查询报单记录
+    pub async fn query_orders(&mut self, instrument_id: Option<&str>) -> Result<Vec<OrderStatus>, CtpError> {
+        self.queries.query_orders(&self.connection, instrument_id).await
+    }
+
+    /// 查询结算信息
+    pub async fn query_settlement_info(&mut self, trading_day: Option<&str>) -> Result<(), CtpError> {
+        self.queries.query_settlement_info(&self.connection, trading_day).await
+    }
+
+    /// 确认结算信息
+    pub async fn confirm_settlement_info(&mut self) -> Result<(), CtpError> {
+        self.queries.confirm_settlement_info(&self.connection).await
+    }
+
+    /// 获取已订阅合约列表
+    pub fn get_subscribed_instruments(&self) -> Vec<String> {
+        self.subscriptions.list()
+    }
+
+    /// 重新订阅所有合约（用于重连后恢复订阅）
+    pub async fn resubscribe_all_instruments(&mut self) -> Result<(), CtpError> {
+        let instruments = self.get_subscribed_instruments();
+
+        if !instruments.is_empty() {
+            tracing::info!("重新订阅所有合约，数量: {}", instruments.len());
+            self.subscribe_market_data(&instruments).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 自动重连机制
+    pub async fn start_auto_reconnect(&mut self) -> Result<(), CtpError> {
+        tracing::info!("启动自动重连机制");
+
+        let (retry_interval, max_attempts) = self.reconnect_policy();
+
+        for attempt in 1..=max_attempts {
+            tracing::info!("重连尝试 {}/{}", attempt, max_attempts);
+
+            match self.connect_with_retry().await {
+                Ok(_) => {
+                    tracing::info!("重连成功");
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!("重连失败 (尝试 {}): {}", attempt, e);
+
+                    if attempt < max_attempts {
+                        tracing::info!("等待 {:?} 后重试...", retry_interval);
+                        tokio::time::sleep(retry_interval).await;
+                    }
+                }
+            }
+        }
+
+        let error = CtpError::ConnectionError(
+            format!("自动重连失败，已达到最大重试次数 {}", max_attempts)
+        );
+        self.connection.set_state(ClientState::Error(error.to_string()));
+        Err(error)
+    }
+
+    /// 查询合约信息
+    pub async fn query_instruments(&mut self) -> Result<Vec<InstrumentInfo>, CtpError> {
+        self.queries.query_instruments(&self.connection).await
+    }
+
+    /// 查询手续费率
+    pub async fn query_commission_rate(&mut self, instrument_id: &str) -> Result<CommissionRate, CtpError> {
+        self.queries.query_commission_rate(&self.connection, instrument_id).await
+    }
+
+    /// 查询保证金率
+    pub async fn query_margin_rate(&mut self, instrument_id: &str) -> Result<MarginRate, CtpError> {
+        self.queries.query_margin_rate(&self.connection, instrument_id).await
+    }
+
+    /// 获取市场数据
+    pub async fn get_market_data(&mut self, instrument_id: &str) -> Result<MarketData, CtpError> {
+        self.queries.get_market_data(&self.connection, instrument_id).await
+    }
+
+    /// 获取所有已订阅合约的市场数据
+    pub async fn get_all_market_data(&mut self) -> Result<Vec<MarketData>, CtpError> {
+        let instruments = self.subscriptions.list();
+        self.queries.get_all_market_data(&self.connection, &instruments).await
+    }
+
+    /// 会话管理 - 保持会话活跃
+    pub async fn keep_session_alive(&self) -> Result<(), CtpError> {
+        self.connection.keep_session_alive().await
+    }
+}
+
+/// 打开账户的已处理成交，失败时仅在内存中去重
+fn open_trade_dedup(account_id: &str, scope: &str) -> TradeDeduplicator {
+    TradeDeduplicator::open(DEFAULT_TRADE_DEDUP_DIR, account_id, scope).unwrap_or_else(|e| {
+        tracing::warn!("打开已处理成交记录失败，仅在内存中去重: {}", e);
+        TradeDeduplicator::in_memory()
+    })
+}
+
+/// 连接统计信息
+#[derive(Debug, Clone)]
+pub struct ConnectionStats {
+    pub state: ClientState,
+    pub reconnect_count: u32,
+    pub connect_duration: Option<Duration>,
+    pub config_environment: crate::ctp::Environment,
+}
+
+/// 健康状态
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HealthStatus {
+    pub is_healthy: bool,
+    pub state: ClientState,
+    pub last_check_time: chrono::DateTime<chrono::Utc>,
+    pub error_message: Option<String>,
+}
+
+/// 配置信息（不包含敏感数据）
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfigInfo {
+    pub environment: crate::ctp::Environment,
+    pub connection_mode: ConnectionMode,
+    pub broker_id: String,
+    pub user_id: String,
+    pub md_front_addr: String,
+    pub trader_front_addr: String,
+    pub flow_path: String,
+    pub timeout_secs: u64,
+    pub max_reconnect_attempts: u32,
+}
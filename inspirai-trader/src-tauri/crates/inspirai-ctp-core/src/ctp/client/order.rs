@@ -0,0 +1,217 @@
+use super::ConnectionManager;
+use crate::ctp::{
+    api_usage::ApiRequestKind,
+    client_order_id::{ClientOrderIds, ClientOrderRecord},
+    compliance_monitor::{ComplianceMonitor, ComplianceStatus, ComplianceWarning},
+    diagnostics::{DiagnosticEvent, DiagnosticHub, DiagnosticSeverity, DiagnosticSource},
+    error::CtpError,
+    models::*,
+    utils::{ConversionPools, PoolStats},
+};
+
+/// 报单与撤单
+///
+/// 负责报单引用、客户端订单号分配、报单结构体转换和撤单合规计数；
+/// 报单前的风控、熔断与市价单模拟由 `CtpClient::place_order` 统一编排
+pub struct OrderClient {
+    /// 跨会话稳定的客户端订单号
+    client_orders: ClientOrderIds,
+    /// 报单、行情转换复用的对象池
+    conversion_pools: ConversionPools,
+    /// 报单成交比、撤单比合规监控
+    compliance: ComplianceMonitor,
+    diagnostics: DiagnosticHub,
+}
+
+impl OrderClient {
+    pub fn new(
+        client_orders: ClientOrderIds,
+        conversion_pools: ConversionPools,
+        compliance: ComplianceMonitor,
+        diagnostics: DiagnosticHub,
+    ) -> Self {
+        Self {
+            client_orders,
+            conversion_pools,
+            compliance,
+            diagnostics,
+        }
+    }
+
+    pub fn client_orders(&self) -> &ClientOrderIds {
+        &self.client_orders
+    }
+
+    pub fn conversion_pools(&self) -> &ConversionPools {
+        &self.conversion_pools
+    }
+
+    pub fn compliance(&self) -> &ComplianceMonitor {
+        &self.compliance
+    }
+
+    /// 按客户端订单号查对应的会话、报单引用和交易所编号
+    pub fn client_order(&self, client_order_id: &str) -> Option<ClientOrderRecord> {
+        self.client_orders.get(client_order_id)
+    }
+
+    /// 报单结构体池与代码缓存的命中率
+    pub fn pool_stats(&self) -> Vec<PoolStats> {
+        self.conversion_pools.stats()
+    }
+
+    /// 账户及各报单来源的报单成交比、撤单比
+    pub fn compliance_statuses(&self) -> Vec<ComplianceStatus> {
+        self.compliance.statuses()
+    }
+
+    /// 生成订单引用
+    pub fn generate_order_ref() -> String {
+        // 生成12位的订单引用，格式：时间戳后6位 + 随机数6位
+        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+        let random_part = rand::random::<u32>() % 1000000;
+        format!("{:06}{:06}", timestamp % 1000000, random_part)
+    }
+
+    /// 提交订单
+    pub async fn submit(&self, connection: &ConnectionManager, mut order: OrderRequest) -> Result<String, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("提交订单: {} {:?} {} @ {}",
+            order.instrument_id, order.direction, order.volume, order.price);
+
+        // 使用真实的 CTP API 提交订单
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 生成订单引用
+        let order_ref = Self::generate_order_ref();
+        connection.correlations().bind_order_ref(&order_ref, &mut order.tags);
+        self.client_orders.assign(&order_ref, &order.instrument_id, &mut order.tags);
+
+        // 将业务订单转换为 CTP 订单结构
+        let mut ctp_order = crate::ctp::utils::DataConverter::convert_order_request_pooled(
+            self.conversion_pools.orders(),
+            &order,
+            &config.broker_id,
+            &config.investor_id,
+            &order_ref,
+        )?;
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送报单录入请求，订单引用: {}, 请求ID: {}", order_ref, request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::OrderInsert);
+
+        // 调用 ctp2rs TraderApi 提交订单
+        connection.wire_log().request("ReqOrderInsert", request_id, &ctp_order);
+        let result = trader_api.req_order_insert(&mut ctp_order, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单录入请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("报单录入请求已发送，订单引用: {}", order_ref);
+        Ok(order_ref)
+    }
+
+    /// 撤销订单
+    pub async fn cancel(&self, connection: &ConnectionManager, order_id: &str) -> Result<(), CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("撤销订单: {}", order_id);
+
+        // 使用真实的 CTP API 撤销订单
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建撤单请求
+        let mut order_action = ctp2rs::v1alpha1::CThostFtdcInputOrderActionField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        order_action.BrokerID.assign_from_str(&config.broker_id);
+        order_action.InvestorID.assign_from_str(&config.investor_id);
+        order_action.OrderRef.assign_from_str(order_id);
+
+        // 设置撤单标志
+        order_action.ActionFlag = '0' as i8; // 删除
+        order_action.FrontID = 1; // 前置编号，应该从登录响应中获取
+        order_action.SessionID = 1; // 会话编号，应该从登录响应中获取
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送报单操作请求，订单引用: {}, 请求ID: {}", order_id, request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::OrderCancel);
+        self.report_compliance(self.compliance.record_cancel(order_id));
+
+        // 调用 ctp2rs TraderApi 撤销订单
+        connection.wire_log().request("ReqOrderAction", request_id, &order_action);
+        let result = trader_api.req_order_action(&mut order_action, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单操作请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("报单操作请求已发送，订单引用: {}", order_id);
+        Ok(())
+    }
+
+    /// 合规阈值告警发布为诊断事件
+    pub fn report_compliance(&self, warnings: Vec<ComplianceWarning>) {
+        for warning in warnings {
+            self.diagnostics.publish(
+                DiagnosticEvent::new(DiagnosticSeverity::Warning, DiagnosticSource::Td, warning.message())
+                    .with_correlation_id(warning.scope.clone()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{connection::test_manager, ClientState};
+    use super::*;
+    use crate::ctp::{
+        compliance_monitor::ComplianceConfig, config::ConnectionMode, events::EventHandler,
+    };
+
+    fn order_client() -> OrderClient {
+        OrderClient::new(
+            ClientOrderIds::in_memory(),
+            ConversionPools::default(),
+            ComplianceMonitor::new(ComplianceConfig::default()),
+            EventHandler::new().diagnostics(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cancel_rejected_before_login_or_in_md_only_mode() {
+        let orders = order_client();
+        let connection = test_manager(ConnectionMode::Full);
+        assert!(matches!(
+            orders.cancel(&connection, "000001000001").await,
+            Err(CtpError::AuthenticationError(_))
+        ));
+
+        let md_only = test_manager(ConnectionMode::MdOnly);
+        md_only.set_state(ClientState::LoggedIn);
+        assert!(matches!(orders.cancel(&md_only, "000001000001").await, Err(CtpError::StateError(_))));
+        // 未发出撤单请求，不计入撤单次数
+        assert_eq!(md_only.api_usage().order_cancels, 0);
+
+        let order_ref = OrderClient::generate_order_ref();
+        assert_eq!(order_ref.len(), 12);
+        assert!(order_ref.chars().all(|c| c.is_ascii_digit()));
+    }
+}
@@ -0,0 +1,563 @@
+use super::ConnectionManager;
+use crate::ctp::{
+    api_usage::ApiRequestKind,
+    error::CtpError,
+    market_overview::MarketOverview,
+    models::*,
+    order_preview::RateCache,
+    position_manager::PositionManager,
+    trading_session::product_of,
+};
+
+/// 手续费率查询等待柜台回报的时长
+const COMMISSION_RATE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// 账户、持仓、报单、结算与合约费率查询
+///
+/// 查询结果由交易 SPI 回调推送；合约与费率查询顺带更新市场概览、回合交易和费率缓存
+pub struct QueryClient {
+    market_overview: MarketOverview,
+    position_manager: PositionManager,
+    /// 保证金率、手续费率缓存，供下单预览反复计算
+    rate_cache: RateCache,
+}
+
+impl QueryClient {
+    pub fn new(market_overview: MarketOverview, position_manager: PositionManager, rate_cache: RateCache) -> Self {
+        Self {
+            market_overview,
+            position_manager,
+            rate_cache,
+        }
+    }
+
+    pub fn rate_cache(&self) -> &RateCache {
+        &self.rate_cache
+    }
+
+    /// 查询账户信息
+    pub async fn query_account(&self, connection: &ConnectionManager) -> Result<AccountInfo, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("查询账户信息");
+
+        // 使用真实的 CTP API 查询账户信息
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建资金账户查询请求
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryTradingAccountField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送资金账户查询请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        // 调用 ctp2rs TraderApi 查询资金账户
+        connection.wire_log().request("ReqQryTradingAccount", request_id, &qry_req);
+        let result = trader_api.req_qry_trading_account(&mut qry_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "资金账户查询请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("资金账户查询请求已发送，结果将通过事件回调返回");
+
+        // 模拟返回账户信息（实际应该从事件回调中获取）
+        Ok(AccountInfo {
+            account_id: config.investor_id.clone(),
+            currency_id: "CNY".to_string(),
+            available: 100000.0,
+            balance: 100000.0,
+            margin: 0.0,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            curr_margin: 0.0,
+            commission: 0.0,
+            close_profit: 0.0,
+            position_profit: 0.0,
+            risk_ratio: 0.0,
+        })
+    }
+
+    /// 查询持仓信息
+    pub async fn query_positions(&self, connection: &ConnectionManager) -> Result<Vec<Position>, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("查询持仓信息");
+
+        // 使用真实的 CTP API 查询持仓信息
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建投资者持仓查询请求
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryInvestorPositionField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+        // InstrumentID 留空表示查询所有合约的持仓
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送投资者持仓查询请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        // 调用 ctp2rs TraderApi 查询投资者持仓
+        connection.wire_log().request("ReqQryInvestorPosition", request_id, &qry_req);
+        let result = trader_api.req_qry_investor_position(&mut qry_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "投资者持仓查询请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("投资者持仓查询请求已发送，结果将通过事件回调返回");
+
+        // 模拟返回持仓信息（实际应该从事件回调中获取）
+        Ok(vec![])
+    }
+
+    /// 查询成交记录
+    pub async fn query_trades(
+        &self,
+        connection: &ConnectionManager,
+        instrument_id: Option<&str>,
+    ) -> Result<Vec<Trade>, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("查询成交记录");
+
+        // 使用真实的 CTP API 查询成交记录
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建成交查询请求
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryTradeField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+
+        // 如果指定了合约，则只查询该合约的成交
+        if let Some(instrument) = instrument_id {
+            qry_req.InstrumentID.assign_from_str(instrument);
+        }
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送成交查询请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        // 调用 ctp2rs TraderApi 查询成交
+        connection.wire_log().request("ReqQryTrade", request_id, &qry_req);
+        let result = trader_api.req_qry_trade(&mut qry_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "成交查询请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("成交查询请求已发送，结果将通过事件回调返回");
+
+        // 模拟返回成交记录（实际应该从事件回调中获取）
+        Ok(vec![])
+    }
+
+    /// 查询报单记录
+    pub async fn query_orders(
+        &self,
+        connection: &ConnectionManager,
+        instrument_id: Option<&str>,
+    ) -> Result<Vec<OrderStatus>, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("查询报单记录");
+
+        // 使用真实的 CTP API 查询报单记录
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建报单查询请求
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryOrderField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+
+        // 如果指定了合约，则只查询该合约的报单
+        if let Some(instrument) = instrument_id {
+            qry_req.InstrumentID.assign_from_str(instrument);
+        }
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送报单查询请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        // 调用 ctp2rs TraderApi 查询报单
+        connection.wire_log().request("ReqQryOrder", request_id, &qry_req);
+        let result = trader_api.req_qry_order(&mut qry_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单查询请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("报单查询请求已发送，结果将通过事件回调返回");
+
+        // 模拟返回订单记录（实际应该从事件回调中获取）
+        Ok(vec![])
+    }
+
+    /// 查询结算信息
+    pub async fn query_settlement_info(
+        &self,
+        connection: &ConnectionManager,
+        trading_day: Option<&str>,
+    ) -> Result<(), CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("查询结算信息");
+
+        // 使用真实的 CTP API 查询结算信息
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建结算信息查询请求
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQrySettlementInfoField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+
+        // 如果指定了交易日，则查询指定日期的结算信息
+        if let Some(day) = trading_day {
+            qry_req.TradingDay.assign_from_str(day);
+        }
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送结算信息查询请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        // 调用 ctp2rs TraderApi 查询结算信息
+        connection.wire_log().request("ReqQrySettlementInfo", request_id, &qry_req);
+        let result = trader_api.req_qry_settlement_info(&mut qry_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "结算信息查询请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("结算信息查询请求已发送，结果将通过事件回调返回");
+        Ok(())
+    }
+
+    /// 确认结算信息
+    pub async fn confirm_settlement_info(&self, connection: &ConnectionManager) -> Result<(), CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        tracing::info!("确认结算信息");
+
+        // 使用真实的 CTP API 确认结算信息
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        // 创建结算信息确认请求
+        let mut confirm_req = ctp2rs::v1alpha1::CThostFtdcSettlementInfoConfirmField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        confirm_req.BrokerID.assign_from_str(&config.broker_id);
+        confirm_req.InvestorID.assign_from_str(&config.investor_id);
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送结算信息确认请求，请求ID: {}", request_id);
+        connection.track_td_request(request_id);
+
+        // 调用 ctp2rs TraderApi 确认结算信息
+        connection.wire_log().request("ReqSettlementInfoConfirm", request_id, &confirm_req);
+        let result = trader_api.req_settlement_info_confirm(&mut confirm_req, request_id);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "结算信息确认请求发送失败".to_string(),
+            });
+        }
+
+        tracing::info!("结算信息确认请求已发送，结果将通过事件回调返回");
+        Ok(())
+    }
+
+    /// 查询合约信息
+    pub async fn query_instruments(&self, connection: &ConnectionManager) -> Result<Vec<InstrumentInfo>, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        // 模拟返回一些合约信息
+        let instruments = vec![
+            InstrumentInfo {
+                instrument_id: "IF2401".to_string(),
+                exchange_id: "CFFEX".to_string(),
+                instrument_name: "沪深300股指期货2401".to_string(),
+                product_id: "IF".to_string(),
+                product_class: "Futures".to_string(),
+                delivery_year: 2024,
+                delivery_month: 1,
+                max_market_order_volume: 100,
+                min_market_order_volume: 1,
+                max_limit_order_volume: 500,
+                min_limit_order_volume: 1,
+                volume_multiple: 300,
+                price_tick: 0.2,
+                create_date: "20231201".to_string(),
+                open_date: "20231201".to_string(),
+                expire_date: "20240119".to_string(),
+                start_delivery_date: "20240119".to_string(),
+                end_delivery_date: "20240119".to_string(),
+                is_trading: true,
+                underlying_instrument: "000300".to_string(),
+                strike_price: 0.0,
+                underlying_multiple: 1.0,
+                long_margin_ratio: 0.12,
+                short_margin_ratio: 0.12,
+            },
+        ];
+
+        // 行情不带交易所代码时，市场概览按合约信息归类
+        self.market_overview.register_instruments(&instruments);
+        for instrument in &instruments {
+            self.position_manager.set_volume_multiple(&instrument.instrument_id, instrument.volume_multiple as f64);
+        }
+        Ok(instruments)
+    }
+
+    /// 查询手续费率
+    ///
+    /// 缓存未命中时向柜台查询，交易 SPI 收到结果后写入费率缓存和回合交易；
+    /// 柜台按品种设置的费率以品种代码返回，取到后按合约代码再登记一次
+    pub async fn query_commission_rate(
+        &self,
+        connection: &ConnectionManager,
+        instrument_id: &str,
+    ) -> Result<CommissionRate, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        if let Some(rate) = self.cached_commission(instrument_id) {
+            return Ok(rate);
+        }
+
+        let trader_api = connection.trader_api()?;
+        let config = connection.config();
+
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryInstrumentCommissionRateField::default();
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&config.broker_id);
+        qry_req.InvestorID.assign_from_str(&config.investor_id);
+        qry_req.InstrumentID.assign_from_str(instrument_id);
+
+        let request_id = connection.next_request_id();
+        tracing::info!("发送手续费率查询请求: {}，请求ID: {}", instrument_id, request_id);
+        connection.track_td_request(request_id);
+        connection.track_api_usage(ApiRequestKind::Query);
+
+        connection.wire_log().request("ReqQryInstrumentCommissionRate", request_id, &qry_req);
+        let result = trader_api.req_qry_instrument_commission_rate(&mut qry_req, request_id);
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "手续费率查询请求发送失败".to_string(),
+            });
+        }
+
+        let deadline = tokio::time::Instant::now() + COMMISSION_RATE_TIMEOUT;
+        loop {
+            if let Some(rate) = self.cached_commission(instrument_id) {
+                return Ok(rate);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(CtpError::TimeoutError);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    /// 缓存中的手续费率，按品种登记的费率转为合约费率并同步到回合交易
+    fn cached_commission(&self, instrument_id: &str) -> Option<CommissionRate> {
+        if let Some(rate) = self.rate_cache.commission(instrument_id) {
+            return Some(rate);
+        }
+        let mut rate = self.rate_cache.commission(product_of(instrument_id))?;
+        rate.instrument_id = instrument_id.to_string();
+        self.position_manager.round_trip_book().set_commission_rate(&rate);
+        self.rate_cache.put_commission(&rate);
+        Some(rate)
+    }
+
+    /// 查询保证金率
+    pub async fn query_margin_rate(
+        &self,
+        connection: &ConnectionManager,
+        instrument_id: &str,
+    ) -> Result<MarginRate, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_trader_available()?;
+
+        // 模拟返回保证金率
+        let rate = MarginRate {
+            instrument_id: instrument_id.to_string(),
+            long_margin_ratio_by_money: 0.12,
+            long_margin_ratio_by_volume: 0.0,
+            short_margin_ratio_by_money: 0.12,
+            short_margin_ratio_by_volume: 0.0,
+        };
+        self.rate_cache.put_margin(&rate);
+        Ok(rate)
+    }
+
+    /// 获取市场数据
+    pub async fn get_market_data(
+        &self,
+        connection: &ConnectionManager,
+        instrument_id: &str,
+    ) -> Result<MarketData, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_md_supported()?;
+
+        // 模拟返回市场数据
+        Ok(MarketData {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "CFFEX".to_string(),
+            last_price: 3800.0,
+            pre_settlement_price: 3790.0,
+            pre_close_price: 3795.0,
+            pre_open_interest: 150000.0,
+            open_price: 3798.0,
+            highest_price: 3810.0,
+            lowest_price: 3780.0,
+            volume: 50000,
+            turnover: 1900000000.0,
+            open_interest: 145000.0,
+            close_price: 0.0,
+            settlement_price: 0.0,
+            upper_limit_price: 4174.0,
+            lower_limit_price: 3406.0,
+            bid_price: 3799.8,
+            bid_volume: 50,
+            ask_price: 3800.2,
+            ask_volume: 45,
+            average_price: 3800.0,
+            update_time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            update_millisec: 500,
+            trading_day: chrono::Local::now().format("%Y%m%d").to_string(),
+        })
+    }
+
+    /// 获取一组合约的市场数据，取不到的合约跳过
+    pub async fn get_all_market_data(
+        &self,
+        connection: &ConnectionManager,
+        instruments: &[String],
+    ) -> Result<Vec<MarketData>, CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_md_supported()?;
+
+        let mut market_data_list = Vec::new();
+        for instrument_id in instruments {
+            if let Ok(data) = self.get_market_data(connection, instrument_id).await {
+                market_data_list.push(data);
+            }
+        }
+
+        Ok(market_data_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{connection::test_manager, ClientState};
+    use super::*;
+    use crate::ctp::config::ConnectionMode;
+
+    #[tokio::test]
+    async fn test_rate_queries_fill_cache_after_login() {
+        let queries = QueryClient::new(MarketOverview::new(), PositionManager::new(), RateCache::default());
+        let connection = test_manager(ConnectionMode::Full);
+        assert!(matches!(
+            queries.query_margin_rate(&connection, "IF2401").await,
+            Err(CtpError::AuthenticationError(_))
+        ));
+        assert!(queries.rate_cache().margin("IF2401").is_none());
+
+        connection.set_state(ClientState::LoggedIn);
+        queries.query_margin_rate(&connection, "IF2401").await.unwrap();
+        assert!(queries.rate_cache().margin("IF2401").is_some());
+
+        // 手续费率来自柜台回报，缓存未命中且交易前置未连接时无法查询
+        assert!(matches!(
+            queries.query_commission_rate(&connection, "IF2401").await,
+            Err(CtpError::StateError(_))
+        ));
+        assert!(queries.rate_cache().commission("IF2401").is_none());
+        // 按品种返回的费率对该品种所有合约生效
+        queries.rate_cache().put_commission(&CommissionRate {
+            instrument_id: "IF".to_string(),
+            open_ratio_by_money: 0.000023,
+            open_ratio_by_volume: 0.0,
+            close_ratio_by_money: 0.000023,
+            close_ratio_by_volume: 0.0,
+            close_today_ratio_by_money: 0.00023,
+            close_today_ratio_by_volume: 0.0,
+        });
+        let rate = queries.query_commission_rate(&connection, "IF2401").await.unwrap();
+        assert_eq!(rate.instrument_id, "IF2401");
+        assert!(queries.rate_cache().commission("IF2401").is_some());
+
+        // 未连接时查询请求无法发出
+        assert!(matches!(queries.query_account(&connection).await, Err(CtpError::StateError(_))));
+
+        let md_only = test_manager(ConnectionMode::MdOnly);
+        md_only.set_state(ClientState::LoggedIn);
+        assert!(queries.query_instruments(&md_only).await.is_err());
+        let market = queries
+            .get_all_market_data(&md_only, &["IF2401".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(market.len(), 1);
+    }
+}
@@ -0,0 +1,173 @@
+use super::ConnectionManager;
+use crate::ctp::error::CtpError;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// 行情订阅
+///
+/// 记录已订阅合约，重连后由 `list` 取回重新订阅
+#[derive(Clone, Default)]
+pub struct SubscriptionClient {
+    subscribed: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SubscriptionClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅行情数据
+    pub fn subscribe(&self, connection: &ConnectionManager, instruments: &[String]) -> Result<(), CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_md_supported()?;
+
+        tracing::info!("订阅行情数据，合约数量: {}", instruments.len());
+        for instrument in instruments {
+            tracing::debug!("订阅合约: {}", instrument);
+        }
+
+        // 使用真实的 CTP API 进行行情订阅
+        let md_api = connection.md_api()?;
+        let valid = Self::valid_instrument_count(instruments);
+        if valid == 0 {
+            return Err(CtpError::ConversionError("没有有效的合约代码".to_string()));
+        }
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送行情订阅请求，合约数量: {}, 请求ID: {}", valid, request_id);
+
+        // 调用 ctp2rs 的 MdApi 订阅行情
+        let instruments_vec = instruments.to_vec();
+        connection.wire_log().request("SubscribeMarketData", request_id, &instruments_vec);
+        let result = md_api.subscribe_market_data(&instruments_vec);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "行情订阅请求发送失败".to_string(),
+            });
+        }
+
+        // 记录已订阅的合约
+        for instrument in instruments {
+            self.add(instrument);
+        }
+
+        tracing::info!("行情订阅请求已发送");
+        Ok(())
+    }
+
+    /// 取消订阅行情数据
+    pub fn unsubscribe(&self, connection: &ConnectionManager, instruments: &[String]) -> Result<(), CtpError> {
+        connection.ensure_logged_in()?;
+        connection.ensure_md_supported()?;
+
+        tracing::info!("取消订阅行情数据，合约数量: {}", instruments.len());
+        for instrument in instruments {
+            tracing::debug!("取消订阅合约: {}", instrument);
+        }
+
+        // 使用真实的 CTP API 取消行情订阅
+        let md_api = connection.md_api()?;
+        let valid = Self::valid_instrument_count(instruments);
+        if valid == 0 {
+            return Err(CtpError::ConversionError("没有有效的合约代码".to_string()));
+        }
+
+        let request_id = connection.next_request_id();
+
+        tracing::info!("发送取消行情订阅请求，合约数量: {}, 请求ID: {}", valid, request_id);
+
+        // 调用 ctp2rs 的 MdApi 取消订阅行情
+        let instruments_vec = instruments.to_vec();
+        connection.wire_log().request("UnSubscribeMarketData", request_id, &instruments_vec);
+        let result = md_api.unsubscribe_market_data(&instruments_vec);
+
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "取消行情订阅请求发送失败".to_string(),
+            });
+        }
+
+        // 移除已订阅的合约
+        for instrument in instruments {
+            self.remove(instrument);
+        }
+
+        tracing::info!("取消行情订阅请求已发送");
+        Ok(())
+    }
+
+    /// 可转换为 C 字符串的合约代码数量
+    fn valid_instrument_count(instruments: &[String]) -> usize {
+        instruments
+            .iter()
+            .filter(|instrument| match std::ffi::CString::new(instrument.as_str()) {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::error!("合约代码转换失败: {} - {}", instrument, e);
+                    false
+                }
+            })
+            .count()
+    }
+
+    /// 添加已订阅的合约
+    pub fn add(&self, instrument_id: &str) {
+        let mut subscribed = self.subscribed.lock().unwrap();
+        subscribed.insert(instrument_id.to_string());
+        tracing::debug!("添加订阅合约: {}", instrument_id);
+    }
+
+    /// 移除已订阅的合约
+    pub fn remove(&self, instrument_id: &str) {
+        let mut subscribed = self.subscribed.lock().unwrap();
+        subscribed.remove(instrument_id);
+        tracing::debug!("移除订阅合约: {}", instrument_id);
+    }
+
+    /// 检查合约是否已订阅
+    pub fn contains(&self, instrument_id: &str) -> bool {
+        self.subscribed.lock().unwrap().contains(instrument_id)
+    }
+
+    /// 获取已订阅的合约列表
+    pub fn list(&self) -> Vec<String> {
+        self.subscribed.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{connection::test_manager, ClientState};
+    use super::*;
+    use crate::ctp::config::ConnectionMode;
+
+    #[test]
+    fn test_subscribe_requires_login_and_md_side() {
+        let subscriptions = SubscriptionClient::new();
+        let instruments = vec!["rb2410".to_string()];
+
+        let connection = test_manager(ConnectionMode::Full);
+        assert!(matches!(
+            subscriptions.subscribe(&connection, &instruments),
+            Err(CtpError::AuthenticationError(_))
+        ));
+        connection.set_state(ClientState::LoggedIn);
+        assert!(matches!(subscriptions.subscribe(&connection, &instruments), Err(CtpError::StateError(_))));
+
+        let td_only = test_manager(ConnectionMode::TdOnly);
+        td_only.set_state(ClientState::LoggedIn);
+        assert!(subscriptions.unsubscribe(&td_only, &instruments).is_err());
+        assert!(subscriptions.list().is_empty());
+
+        subscriptions.add("rb2410");
+        subscriptions.add("rb2410");
+        assert!(subscriptions.contains("rb2410"));
+        assert_eq!(subscriptions.list(), instruments);
+        subscriptions.remove("rb2410");
+        assert!(!subscriptions.contains("rb2410"));
+    }
+}
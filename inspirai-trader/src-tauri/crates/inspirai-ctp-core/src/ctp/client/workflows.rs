@@ -0,0 +1,704 @@
+use super::{CtpClient, OrderClient};
+use crate::ctp::{
+    dead_man_switch::{DeadManReport, DeadManTrigger, DEAD_MAN_SOURCE},
+    error::CtpError,
+    events::CtpEvent,
+    hedging::{suggest_hedges, HedgeConfig, HedgeReport},
+    hotkeys::{close_orders, exchange_of, HotkeyAction, HotkeyController, HotkeyOutcome, ResolvedHotkey, SOURCE_TAG},
+    market_order::{counterparty_price, MarketOrderChase},
+    models::*,
+    order_preview::{preview_order, OrderPreview},
+    order_sizing::{max_open_volume, MaxOpenVolume, DEFAULT_MARGIN_UTILIZATION},
+    order_validation::{order_request_from_input, OrderValidationResult, OrderValidator, ValidationContext},
+    rejection_breaker::{order_source, MANUAL_SOURCE},
+    risk_presets::{PresetSwitch, PresetSwitchSource, RiskPresetManager},
+    rollover::{plan_rollovers, RolloverConfig, RolloverExecution, RolloverManager, RolloverPlan, RolloverReport},
+    strategy_guard::StrategyGuard,
+    stress_test::{run_stress_test, StressTestResult, StressTestScenario},
+};
+use std::collections::HashMap;
+
+/// 跨组件的交易流程：下单编排、市价单追单、移仓、风控参数、预校验与应急处置
+///
+/// 这些流程组合连接、报单和查询组件，并读写客户端持有的风控与执行状态
+impl CtpClient {
+    /// 下单
+    pub async fn place_order(&mut self, mut order: OrderInput) -> Result<OrderRef, CtpError> {
+        self.connection.ensure_logged_in()?;
+        self.connection.ensure_trader_available()?;
+        // 断线后对账完成前不接受新订单
+        if self.reconciler.is_pending() {
+            return Err(CtpError::StateError("重连对账进行中，暂不接受新订单".to_string()));
+        }
+        self.switchboard.check(&order.instrument_id)?;
+
+        let source = order_source(&order.tags);
+        if source != MANUAL_SOURCE {
+            let config = self.connection.config();
+            config.programmatic.ensure_algo_allowed(config.environment)?;
+        }
+        self.rejection_breaker.check(&source)?;
+        self.orders.compliance().check(&source)?;
+
+        // 市价单按配置策略转换为限价单
+        let chase = self.emulate_market_order(&mut order).await?;
+
+        // 与下单预览同一套预校验：错误级问题（含开启拦截时的封板方向）拒绝报单，价格按最小变动价位取整
+        let context = self.validation_context(&order.instrument_id).await?;
+        order = OrderValidator::validate(&order, &context).into_order()?;
+
+        let order_ref = OrderClient::generate_order_ref();
+        let front_id = 1; // 应该从登录响应中获取
+        let session_id = 1; // 应该从登录响应中获取
+        self.connection.correlations().bind_order_ref(&order_ref, &mut order.tags);
+        let client_order_id = self.orders.client_orders().assign(&order_ref, &order.instrument_id, &mut order.tags);
+
+        // 创建订单请求
+        let order_request = order_request_from_input(&order, &order_ref)?;
+        self.rejection_breaker.register_order(&order_ref, &source);
+        let warnings = self.orders.compliance().register_order(&order_ref, &source);
+        self.orders.report_compliance(warnings);
+        self.position_manager.register_order_tags(&order_ref, &order.tags);
+
+        // 提交订单
+        let _ = self.submit_order(order_request).await?;
+        if let Some(chase) = chase {
+            self.market_orders.track(&order_ref, chase);
+        }
+
+        Ok(OrderRef {
+            order_ref,
+            front_id,
+            session_id,
+            client_order_id,
+        })
+    }
+
+    async fn emulate_market_order(&mut self, order: &mut OrderInput) -> Result<Option<MarketOrderChase>, CtpError> {
+        if order.order_type != "Market" {
+            return Ok(None);
+        }
+        let market = self.get_market_data(&order.instrument_id).await.ok();
+        let instrument = self
+            .query_instruments()
+            .await?
+            .into_iter()
+            .find(|i| i.instrument_id == order.instrument_id);
+        let exchange_id = exchange_of(instrument.as_ref(), market.as_ref()).to_string();
+        let price_tick = instrument.map(|i| i.price_tick).unwrap_or(0.0);
+        self.market_orders.emulate(order, &exchange_id, market.as_ref(), price_tick)
+    }
+
+    /// 是否有待追单的市价单模拟
+    pub fn has_pending_market_chases(&self) -> bool {
+        self.market_orders.has_pending_chases()
+    }
+
+    /// 市价单模拟的追单：未成交部分按最新对手价重新报单，失败的放弃并记录
+    pub async fn chase_market_orders(&mut self) -> Vec<OrderRef> {
+        let mut placed = Vec::new();
+        for mut chase in self.market_orders.take_chases() {
+            let instrument_id = chase.order.instrument_id.clone();
+            let market = self.get_market_data(&instrument_id).await.ok();
+            if let Err(e) = chase.reprice(market.as_ref()) {
+                tracing::warn!("市价单 {} 追单定价失败: {}", instrument_id, e);
+                continue;
+            }
+            match self.place_order(chase.order.clone()).await {
+                Ok(order_ref) => {
+                    tracing::info!("市价单 {} 追单 {} 手 @ {}", instrument_id, chase.order.volume, chase.order.price);
+                    self.market_orders.track(&order_ref.order_ref, chase);
+                    placed.push(order_ref);
+                }
+                Err(e) => tracing::warn!("市价单 {} 追单失败: {}", instrument_id, e),
+            }
+        }
+        placed
+    }
+
+    /// 移仓执行簿，用于查询进行中和已结束的移仓
+    pub fn rollovers(&self) -> RolloverManager {
+        self.rollovers.clone()
+    }
+
+    /// 为临近到期的持仓生成移仓计划，主力合约按同品种后续合约的持仓量确定
+    pub async fn plan_rollovers(&mut self, config: &RolloverConfig) -> Result<RolloverReport, CtpError> {
+        config.validate()?;
+        let positions = self.query_positions().await?;
+        let instruments = self.query_instruments().await?;
+        let held: Vec<&InstrumentInfo> = instruments
+            .iter()
+            .filter(|i| positions.iter().any(|p| p.total_position > 0 && p.instrument_id == i.instrument_id))
+            .collect();
+        let mut candidates: Vec<String> = instruments
+            .iter()
+            .filter(|i| held.iter().any(|h| h.product_id == i.product_id && h.exchange_id == i.exchange_id))
+            .map(|i| i.instrument_id.clone())
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let mut markets = HashMap::new();
+        for instrument_id in candidates {
+            if let Ok(market) = self.get_market_data(&instrument_id).await {
+                markets.insert(instrument_id, market);
+            }
+        }
+        let today = chrono::Local::now().date_naive();
+        Ok(plan_rollovers(&positions, &instruments, &markets, today, config))
+    }
+
+    /// 执行移仓：报出平仓腿，开仓腿随平仓成交由 `advance_rollovers` 报出
+    ///
+    /// 平仓腿按最新对手价重新定价，任一平仓腿报单失败时撤销已报出的腿
+    pub async fn execute_rollover(&mut self, plan: RolloverPlan) -> Result<RolloverExecution, CtpError> {
+        self.connection.ensure_logged_in()?;
+        self.connection.ensure_trader_available()?;
+
+        let price_tick = self
+            .query_instruments()
+            .await?
+            .into_iter()
+            .find(|i| i.instrument_id == plan.from_instrument)
+            .map(|i| i.price_tick)
+            .unwrap_or(0.0);
+        let market = self.get_market_data(&plan.from_instrument).await.ok();
+        let (close_orders, price_ticks) = (plan.close_orders.clone(), plan.price_ticks);
+        let execution = self.rollovers.start(plan)?;
+        tracing::info!(
+            "开始移仓 {}: {} -> {} {} 手",
+            execution.id,
+            execution.plan.from_instrument,
+            execution.plan.to_instrument,
+            execution.plan.volume
+        );
+
+        for mut order in close_orders {
+            RolloverManager::tag(&mut order, &execution.id, "close");
+            let placed = match counterparty_price(&order.direction, market.as_ref(), price_ticks, price_tick) {
+                Ok(price) => {
+                    order.price = price;
+                    self.place_order(order).await
+                }
+                Err(e) => Err(e),
+            };
+            match placed {
+                Ok(order_ref) => {
+                    self.rollovers.attach_close_order(&execution.id, &order_ref.order_ref);
+                }
+                Err(e) => {
+                    let (failed, working) = self.rollovers.fail(&execution.id, format!("平仓腿报单失败: {}", e))?;
+                    for order_ref in working {
+                        if let Err(e) = self.cancel_order(&order_ref).await {
+                            tracing::warn!("撤销移仓 {} 的报单 {} 失败: {}", failed.id, order_ref, e);
+                        }
+                    }
+                    self.send_rollover_update(failed);
+                    return Err(e);
+                }
+            }
+        }
+
+        let execution = self.rollovers.get(&execution.id).unwrap_or(execution);
+        self.send_rollover_update(execution.clone());
+        Ok(execution)
+    }
+
+    /// 是否有待报出的移仓开仓腿
+    pub fn has_pending_rollovers(&self) -> bool {
+        self.rollovers.has_pending()
+    }
+
+    /// 按最新对手价报出平仓已成交部分对应的开仓腿，报单失败时停止该移仓
+    pub async fn advance_rollovers(&mut self) -> Vec<OrderRef> {
+        let mut placed = Vec::new();
+        for pending in self.rollovers.take_pending() {
+            let mut order = pending.order;
+            let instrument_id = order.instrument_id.clone();
+            let market = self.get_market_data(&instrument_id).await.ok();
+            let price_tick = match self.query_instruments().await {
+                Ok(instruments) => instruments
+                    .into_iter()
+                    .find(|i| i.instrument_id == instrument_id)
+                    .map(|i| i.price_tick)
+                    .unwrap_or(0.0),
+                Err(_) => 0.0,
+            };
+            let result = match counterparty_price(&order.direction, market.as_ref(), pending.price_ticks, price_tick) {
+                Ok(price) => {
+                    order.price = price;
+                    self.place_order(order).await
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(order_ref) => {
+                    if let Some(execution) = self.rollovers.attach_open_order(&pending.rollover_id, &order_ref.order_ref) {
+                        self.send_rollover_update(execution);
+                    }
+                    placed.push(order_ref);
+                }
+                Err(e) => {
+                    if let Ok((failed, working)) =
+                        self.rollovers.fail(&pending.rollover_id, format!("{} 开仓腿报单失败: {}", instrument_id, e))
+                    {
+                        for order_ref in working {
+                            if let Err(e) = self.cancel_order(&order_ref).await {
+                                tracing::warn!("撤销移仓 {} 的报单 {} 失败: {}", failed.id, order_ref, e);
+                            }
+                        }
+                        self.send_rollover_update(failed);
+                    }
+                }
+            }
+        }
+        placed
+    }
+
+    /// 取消移仓，撤销在途的腿报单；已成交部分保留
+    pub async fn cancel_rollover(&mut self, rollover_id: &str) -> Result<RolloverExecution, CtpError> {
+        let (execution, working) = self.rollovers.cancel(rollover_id)?;
+        for order_ref in working {
+            if let Err(e) = self.cancel_order(&order_ref).await {
+                tracing::warn!("撤销移仓 {} 的报单 {} 失败: {}", rollover_id, order_ref, e);
+            }
+        }
+        self.send_rollover_update(execution.clone());
+        Ok(execution)
+    }
+
+    /// 策略因行情异常暂停后撤销它在该合约上的挂单，返回已撤销的报单引用
+    ///
+    /// 按报单的策略标签识别；单笔撤单失败只记录日志
+    pub async fn cancel_strategy_orders(&mut self, strategy_id: &str, instrument_id: &str) -> Result<Vec<String>, CtpError> {
+        let working: Vec<OrderStatus> = self
+            .query_orders(Some(instrument_id))
+            .await?
+            .into_iter()
+            .filter(|o| {
+                o.instrument_id == instrument_id
+                    && StrategyGuard::strategy_of(&o.tags) == Some(strategy_id)
+                    && matches!(
+                        o.status,
+                        OrderStatusType::NoTradeQueueing | OrderStatusType::PartTradedQueueing | OrderStatusType::Unknown
+                    )
+            })
+            .collect();
+        let mut cancelled = Vec::new();
+        for order in working {
+            match self.cancel_order(&order.order_ref).await {
+                Ok(()) => cancelled.push(order.order_ref),
+                Err(e) => tracing::warn!("撤销策略 {} 的报单 {} 失败: {}", strategy_id, order.order_ref, e),
+            }
+        }
+        if !cancelled.is_empty() {
+            self.timeline.record_risk(
+                format!("策略 {} 因 {} 行情异常暂停，撤销挂单 {} 笔", strategy_id, instrument_id, cancelled.len()),
+                Some(instrument_id.to_string()),
+            );
+        }
+        Ok(cancelled)
+    }
+
+    fn send_rollover_update(&self, execution: RolloverExecution) {
+        if let Err(e) = self.event_handler.send_event(CtpEvent::RolloverUpdate(execution)) {
+            tracing::warn!("发送移仓进度失败: {}", e);
+        }
+    }
+
+    /// 设置风险参数
+    pub async fn set_risk_params(&mut self, params: RiskParams) -> Result<(), CtpError> {
+        self.connection.ensure_logged_in()?;
+        self.connection.ensure_trader_available()?;
+
+        // 验证风险参数
+        if params.max_position_ratio < 0.0 || params.max_position_ratio > 1.0 {
+            return Err(CtpError::ValidationError("持仓比例必须在0到1之间".to_string()));
+        }
+
+        if params.max_single_loss < 0.0 {
+            return Err(CtpError::ValidationError("单笔最大损失不能为负".to_string()));
+        }
+
+        if params.max_daily_loss < 0.0 {
+            return Err(CtpError::ValidationError("日最大损失不能为负".to_string()));
+        }
+
+        tracing::info!("设置风险参数: {:?}", params);
+        self.risk_params = Some(params);
+
+        Ok(())
+    }
+
+    /// 当前风险参数
+    pub fn risk_params(&self) -> Option<RiskParams> {
+        self.risk_params.clone()
+    }
+
+    /// 切换到命名风控预设，返回相对切换前的参数变化并记入时间线
+    pub async fn apply_risk_preset(
+        &mut self,
+        presets: &RiskPresetManager,
+        name: &str,
+        source: PresetSwitchSource,
+    ) -> Result<PresetSwitch, CtpError> {
+        let preset = presets.preset(name)?;
+        let previous = self.risk_params.clone();
+        self.set_risk_params(preset.params).await?;
+        let switch = presets.mark_active(name, source, previous.as_ref())?;
+        let source_label = match source {
+            PresetSwitchSource::Manual => "手动",
+            PresetSwitchSource::Schedule => "定时",
+        };
+        self.timeline.record_risk(
+            format!("风控预设{}切换为 {}，{} 项参数变化", source_label, name, switch.changes.len()),
+            None,
+        );
+        Ok(switch)
+    }
+
+    /// 报单预校验，不提交
+    pub async fn validate_order(&mut self, order: &OrderInput) -> Result<OrderValidationResult, CtpError> {
+        let context = self.validation_context(&order.instrument_id).await?;
+        Ok(OrderValidator::validate(order, &context))
+    }
+
+    /// 下单预览：所需保证金、预估手续费、成交后可用资金和持仓
+    ///
+    /// 费率优先取缓存，未命中时查询；查询失败时保证金退回合约比例，手续费留空
+    pub async fn preview_order(&mut self, order: &OrderInput) -> Result<OrderPreview, CtpError> {
+        let context = self.validation_context(&order.instrument_id).await?;
+        let margin_rate = match self.queries.rate_cache().margin(&order.instrument_id) {
+            Some(rate) => Some(rate),
+            None => self.query_margin_rate(&order.instrument_id).await.ok(),
+        };
+        let commission_rate = match self.queries.rate_cache().commission(&order.instrument_id) {
+            Some(rate) => Some(rate),
+            None => self.query_commission_rate(&order.instrument_id).await.ok(),
+        };
+        preview_order(order, &context, margin_rate.as_ref(), commission_rate.as_ref())
+    }
+
+    /// 收集合约的预校验上下文
+    async fn validation_context(&mut self, instrument_id: &str) -> Result<ValidationContext, CtpError> {
+        self.connection.ensure_logged_in()?;
+        self.connection.ensure_trader_available()?;
+
+        let instrument = self
+            .query_instruments()
+            .await?
+            .into_iter()
+            .find(|i| i.instrument_id == instrument_id);
+        // 行情不可用（如仅交易模式）时跳过涨跌停检查
+        let market = self.get_market_data(instrument_id).await.ok();
+        Ok(ValidationContext {
+            instrument,
+            market,
+            positions: self.query_positions().await?,
+            account: Some(self.query_account().await?),
+            risk_params: self.risk_params.clone(),
+            price_limit: self.price_limits.status(instrument_id),
+        })
+    }
+
+    /// 计算合约最大可开手数，用于下单面板的“最大”按钮
+    ///
+    /// 未指定价格时买入取卖一、卖出取买一，缺失时取最新价；未指定占用上限时使用默认值
+    pub async fn max_open_volume(
+        &mut self,
+        instrument_id: &str,
+        direction: OrderDirection,
+        price: Option<f64>,
+        utilization_cap: Option<f64>,
+    ) -> Result<MaxOpenVolume, CtpError> {
+        let context = self.validation_context(instrument_id).await?;
+        let instrument = context
+            .instrument
+            .ok_or_else(|| CtpError::NotFound(format!("合约 {} 不存在", instrument_id)))?;
+        let account = context
+            .account
+            .ok_or_else(|| CtpError::StateError("账户资金不可用".to_string()))?;
+
+        let valid = |p: &f64| *p > 0.0 && *p < f64::MAX;
+        let price = price.or_else(|| {
+            let market = context.market.as_ref()?;
+            let quote = match direction {
+                OrderDirection::Buy => market.ask_price,
+                OrderDirection::Sell => market.bid_price,
+            };
+            Some(quote).filter(valid).or(Some(market.last_price).filter(valid))
+        });
+        let Some(price) = price else {
+            return Err(CtpError::StateError(format!("合约 {} 没有可用价格", instrument_id)));
+        };
+        // 保证金率查询失败时退回合约信息中的比例
+        let margin_rate = self.query_margin_rate(instrument_id).await.ok();
+
+        max_open_volume(
+            &instrument,
+            direction,
+            price,
+            margin_rate.as_ref(),
+            &account,
+            context.risk_params.as_ref(),
+            utilization_cap.unwrap_or(DEFAULT_MARGIN_UTILIZATION),
+        )
+    }
+
+    /// 对当前持仓做保证金压力测试
+    ///
+    /// 按持有合约逐一查询保证金率和最新价，查询失败的合约由压力测试退回估算并给出告警
+    pub async fn stress_test(&mut self, scenario: &StressTestScenario) -> Result<StressTestResult, CtpError> {
+        scenario.validate()?;
+        let positions = self.query_positions().await?;
+        let account = self.query_account().await?;
+        let (specs, prices) = self.held_instruments_snapshot(&positions).await?;
+        let mut margin_rates = HashMap::new();
+        for position in positions.iter().filter(|p| p.total_position > 0) {
+            let instrument_id = &position.instrument_id;
+            if margin_rates.contains_key(instrument_id) {
+                continue;
+            }
+            if let Ok(rate) = self.query_margin_rate(instrument_id).await {
+                margin_rates.insert(instrument_id.clone(), rate);
+            }
+        }
+
+        let result = run_stress_test(scenario, &positions, &account, &specs, &margin_rates, &prices)?;
+        tracing::info!(
+            "压力测试完成: 盈亏 {:.0}，冲击后保证金 {:.0}，权益 {:.0}{}",
+            result.pnl,
+            result.stressed_margin,
+            result.equity,
+            if result.margin_call { "，将触发追保" } else { "" }
+        );
+        Ok(result)
+    }
+
+    /// 按净敞口上限生成对冲建议，只返回建议，不会下单
+    pub async fn hedge_suggestions(&mut self, config: &HedgeConfig) -> Result<HedgeReport, CtpError> {
+        config.validate()?;
+        let positions = self.query_positions().await?;
+        let (specs, prices) = self.held_instruments_snapshot(&positions).await?;
+        let report = suggest_hedges(config, &positions, &specs, &prices);
+        if !report.suggestions.is_empty() {
+            let over_cap = report.exposures.iter().filter(|e| e.over_cap).count();
+            tracing::info!("{} 个品种超出敞口上限，生成 {} 条对冲建议", over_cap, report.suggestions.len());
+        }
+        Ok(report)
+    }
+
+    /// 持仓合约的合约信息和最新价，行情不可用的合约不含价格
+    ///
+    /// 期权持仓的标的合约信息一并返回，用于按标的归并敞口
+    async fn held_instruments_snapshot(
+        &mut self,
+        positions: &[Position],
+    ) -> Result<(HashMap<String, InstrumentInfo>, HashMap<String, f64>), CtpError> {
+        let mut held: Vec<String> = positions
+            .iter()
+            .filter(|p| p.total_position > 0)
+            .map(|p| p.instrument_id.clone())
+            .collect();
+        held.sort();
+        held.dedup();
+
+        let instruments = self.query_instruments().await?;
+        let underlyings: Vec<String> = instruments
+            .iter()
+            .filter(|i| held.contains(&i.instrument_id) && !i.underlying_instrument.is_empty())
+            .map(|i| i.underlying_instrument.clone())
+            .collect();
+        let specs: HashMap<String, InstrumentInfo> = instruments
+            .into_iter()
+            .filter(|i| held.contains(&i.instrument_id) || underlyings.contains(&i.instrument_id))
+            .map(|i| (i.instrument_id.clone(), i))
+            .collect();
+
+        let mut prices = HashMap::new();
+        for instrument_id in &held {
+            if let Ok(market) = self.get_market_data(instrument_id).await {
+                prices.insert(instrument_id.clone(), market.last_price);
+            }
+        }
+        Ok((specs, prices))
+    }
+
+    /// 执行快捷键动作
+    ///
+    /// 经总开关和限速后由后端解析价格与开平，解析出的订单逐一通过预校验才会提交
+    pub async fn execute_hotkey(
+        &mut self,
+        hotkeys: &HotkeyController,
+        action: HotkeyAction,
+    ) -> Result<HotkeyOutcome, CtpError> {
+        if let Err(e) = hotkeys.acquire() {
+            self.timeline.record_risk(format!("快捷键被拦截: {}", e), action.instrument_id().map(str::to_string));
+            return Err(e);
+        }
+
+        let instrument_id = hotkeys
+            .target_instrument(&action)
+            .ok_or_else(|| CtpError::NotFound("没有可平的快捷键开仓记录".to_string()))?;
+        let context = self.validation_context(&instrument_id).await?;
+        let orders = match action {
+            HotkeyAction::CancelNearestOrder { .. } => self.query_orders(Some(&instrument_id)).await?,
+            _ => Vec::new(),
+        };
+
+        let mut outcome = HotkeyOutcome {
+            action: action.clone(),
+            order_refs: Vec::new(),
+            cancelled: Vec::new(),
+            message: String::new(),
+        };
+        let exchange_id = exchange_of(context.instrument.as_ref(), context.market.as_ref());
+        match hotkeys.resolve(&action, exchange_id, context.market.as_ref(), &context.positions, &orders)? {
+            ResolvedHotkey::Submit(orders) => {
+                // 先全部校验，避免平今/平昨拆单只提交一半
+                let validated = orders
+                    .iter()
+                    .map(|order| OrderValidator::validate(order, &context).into_order())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for order in validated {
+                    let is_open = order.offset == "Open";
+                    let order_ref = self.place_order(order.clone()).await?;
+                    if is_open {
+                        hotkeys.record_open(&order);
+                    }
+                    outcome.order_refs.push(order_ref.order_ref);
+                }
+                outcome.message = format!("快捷键已提交 {} 笔订单", outcome.order_refs.len());
+            }
+            ResolvedHotkey::Cancel(order) => {
+                self.cancel_order(&order.order_ref).await?;
+                outcome.message = format!("快捷键已撤单: {} @ {}", order.order_ref, order.limit_price);
+                outcome.cancelled.push(order.order_ref);
+            }
+        }
+
+        tracing::info!("{}", outcome.message);
+        Ok(outcome)
+    }
+
+    /// 死人开关触发后的处置：撤销全部挂单，按配置以对手价平掉全部持仓
+    ///
+    /// 单笔撤单或平仓失败不中断处置，错误汇总在报告中
+    pub async fn execute_dead_man(&mut self, trigger: DeadManTrigger) -> DeadManReport {
+        let mut report = DeadManReport {
+            trigger,
+            cancelled: Vec::new(),
+            flatten_orders: Vec::new(),
+            errors: Vec::new(),
+        };
+        self.timeline.record_risk(
+            format!("死人开关触发: {} 秒未收到心跳，撤销全部挂单", report.trigger.silent_ms / 1000),
+            None,
+        );
+
+        match self.query_orders(None).await {
+            Ok(orders) => {
+                let working = orders.into_iter().filter(|o| {
+                    matches!(
+                        o.status,
+                        OrderStatusType::NoTradeQueueing | OrderStatusType::PartTradedQueueing | OrderStatusType::Unknown
+                    )
+                });
+                for order in working {
+                    match self.cancel_order(&order.order_ref).await {
+                        Ok(()) => report.cancelled.push(order.order_ref),
+                        Err(e) => report.errors.push(format!("撤单 {} 失败: {}", order.order_ref, e)),
+                    }
+                }
+            }
+            Err(e) => report.errors.push(format!("查询挂单失败: {}", e)),
+        }
+
+        if report.trigger.flatten {
+            self.flatten_all(&mut report).await;
+        }
+
+        tracing::warn!(
+            "死人开关处置完成: 撤单 {} 笔, 平仓单 {} 笔, 失败 {} 项",
+            report.cancelled.len(),
+            report.flatten_orders.len(),
+            report.errors.len()
+        );
+        report
+    }
+
+    async fn flatten_all(&mut self, report: &mut DeadManReport) {
+        let positions = match self.query_positions().await {
+            Ok(positions) => positions,
+            Err(e) => {
+                report.errors.push(format!("查询持仓失败: {}", e));
+                return;
+            }
+        };
+        let mut instruments: Vec<String> = positions
+            .iter()
+            .filter(|p| p.total_position > 0)
+            .map(|p| p.instrument_id.clone())
+            .collect();
+        instruments.sort();
+        instruments.dedup();
+
+        for instrument_id in instruments {
+            let context = match self.validation_context(&instrument_id).await {
+                Ok(context) => context,
+                Err(e) => {
+                    report.errors.push(format!("{} 获取行情失败: {}", instrument_id, e));
+                    continue;
+                }
+            };
+            let exchange_id = exchange_of(context.instrument.as_ref(), context.market.as_ref());
+            let orders = match close_orders(&instrument_id, exchange_id, None, context.market.as_ref(), &positions) {
+                Ok(orders) => orders,
+                Err(e) => {
+                    report.errors.push(format!("{} 无法生成平仓单: {}", instrument_id, e));
+                    continue;
+                }
+            };
+            for mut order in orders {
+                order.tags.insert(SOURCE_TAG.to_string(), DEAD_MAN_SOURCE.to_string());
+                match self.place_order(order).await {
+                    Ok(order_ref) => report.flatten_orders.push(order_ref.order_ref),
+                    Err(e) => report.errors.push(format!("{} 平仓失败: {}", instrument_id, e)),
+                }
+            }
+        }
+    }
+
+    /// 处理认证失败重试
+    pub async fn handle_auth_failure(&mut self, error_msg: &str) -> Result<(), CtpError> {
+        tracing::warn!("认证失败: {}", error_msg);
+
+        // 检查是否为可重试的错误
+        if self.auth.is_retryable_error(error_msg) {
+            tracing::info!("认证错误可重试，等待后重新尝试");
+
+            // 等待一段时间后重试
+            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+
+            // 重新连接和登录
+            self.connect_with_retry().await?;
+
+            // 使用原有凭据重新登录
+            let config = self.connection.config();
+            let credentials = LoginCredentials {
+                broker_id: config.broker_id.clone(),
+                user_id: config.investor_id.clone(),
+                password: config.password.clone(),
+                app_id: config.app_id.clone(),
+                auth_code: config.auth_code.clone(),
+            };
+
+            self.login(credentials).await?;
+
+            Ok(())
+        } else {
+            Err(CtpError::AuthenticationError(format!("认证失败且不可重试: {}", error_msg)))
+        }
+    }
+}
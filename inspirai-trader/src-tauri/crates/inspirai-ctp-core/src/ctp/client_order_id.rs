@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ctp::models::{OrderStatus, OrderTags, TradeRecord};
+use crate::ctp::utils::sanitize_file_component;
+use crate::ctp::CtpError;
+
+/// 客户端订单号的订单标签名，报单、成交事件都带上
+pub const CLIENT_ORDER_ID_TAG: &str = "client_order_id";
+/// 默认订单号映射目录，每个账户一个 JSONL 文件
+pub const DEFAULT_CLIENT_ORDER_DIR: &str = "./data/client_orders";
+
+/// Crockford Base32 字母表
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// 客户端订单号与 CTP 各级编号的对应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientOrderRecord {
+    pub client_order_id: String,
+    pub instrument_id: String,
+    pub front_id: i32,
+    pub session_id: i32,
+    pub order_ref: String,
+    /// 交易所报单编号，报单进入交易所后才有
+    #[serde(default)]
+    pub order_sys_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 提交时的订单标签，重启后补全回报的策略归属
+    #[serde(default)]
+    pub tags: OrderTags,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    records: HashMap<String, ClientOrderRecord>,
+    by_session_ref: HashMap<(i32, i32, String), String>,
+    by_sys_id: HashMap<String, String>,
+    /// 当前交易会话
+    front_id: i32,
+    session_id: i32,
+    /// 上一个生成的编号，保证同一毫秒内递增
+    last_ulid: u128,
+    path: Option<PathBuf>,
+}
+
+impl StoreInner {
+    fn index(&mut self, record: ClientOrderRecord) {
+        let id = record.client_order_id.clone();
+        self.by_session_ref
+            .insert((record.front_id, record.session_id, record.order_ref.clone()), id.clone());
+        if let Some(sys_id) = record.order_sys_id.as_ref().filter(|s| !s.is_empty()) {
+            self.by_sys_id.insert(sys_id.clone(), id.clone());
+        }
+        self.records.insert(id, record);
+    }
+
+    fn next_ulid(&mut self, now: DateTime<Utc>) -> String {
+        let millis = (now.timestamp_millis().max(0) as u128) & ((1 << 48) - 1);
+        let random: u128 = rand::thread_rng().gen::<u128>() & ((1 << 80) - 1);
+        let candidate = (millis << 80) | random;
+        // 时钟回拨或同一毫秒内，在上一个编号基础上加一
+        let value = if candidate > self.last_ulid { candidate } else { self.last_ulid + 1 };
+        self.last_ulid = value;
+        encode_ulid(value)
+    }
+
+    fn persist(&self, record: &ClientOrderRecord) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = append_line(path, record) {
+            tracing::warn!("保存客户端订单号失败: {}", e);
+            crate::health::record_storage_error("client_orders", &e);
+        }
+    }
+}
+
+/// 跨会话稳定的客户端订单号
+///
+/// OrderRef 每个会话重新计数，提交时分配 ULID 作为订单的长期标识，并随回报
+/// 补全 (FrontID, SessionID, OrderRef) 和 OrderSysID 的对应关系；映射连同订单标签
+/// 追加写入本地 JSONL，重启后恢复
+#[derive(Clone, Default)]
+pub struct ClientOrderIds {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl ClientOrderIds {
+    /// 仅内存的订单号映射
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// 打开账户的订单号映射，加载 `<dir>/<account_id>.jsonl` 中已有的记录
+    pub fn open(dir: impl AsRef<Path>, account_id: &str) -> Result<Self, CtpError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.jsonl", sanitize_file_component(account_id)));
+
+        let mut inner = StoreInner::default();
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                // 同一订单号后写的记录覆盖先写的
+                match serde_json::from_str::<ClientOrderRecord>(&line) {
+                    Ok(record) => inner.index(record),
+                    Err(e) => tracing::warn!("跳过无法解析的订单号记录: {}", e),
+                }
+            }
+            inner.last_ulid = inner.records.keys().filter_map(|id| decode_ulid(id)).max().unwrap_or(0);
+            tracing::info!("加载账户 {} 的客户端订单号 {} 条", account_id, inner.records.len());
+        }
+        inner.path = Some(path);
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// 登录成功后记录当前会话，之后分配的订单号按此会话建立映射
+    pub fn set_session(&self, front_id: i32, session_id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.front_id = front_id;
+        inner.session_id = session_id;
+    }
+
+    /// 提交时分配订单号并写入订单标签，标签中已有订单号时沿用
+    pub fn assign(&self, order_ref: &str, instrument_id: &str, tags: &mut OrderTags) -> String {
+        self.assign_at(order_ref, instrument_id, tags, Utc::now())
+    }
+
+    pub fn assign_at(&self, order_ref: &str, instrument_id: &str, tags: &mut OrderTags, now: DateTime<Utc>) -> String {
+        let mut inner = self.inner.lock().unwrap();
+        let key = (inner.front_id, inner.session_id, order_ref.to_string());
+        let client_order_id = match tags.get(CLIENT_ORDER_ID_TAG) {
+            Some(id) => id.clone(),
+            None => inner.next_ulid(now),
+        };
+        tags.insert(CLIENT_ORDER_ID_TAG.to_string(), client_order_id.clone());
+        if inner.by_session_ref.get(&key) == Some(&client_order_id) {
+            return client_order_id;
+        }
+        let record = ClientOrderRecord {
+            client_order_id: client_order_id.clone(),
+            instrument_id: instrument_id.to_string(),
+            front_id: key.0,
+            session_id: key.1,
+            order_ref: key.2,
+            order_sys_id: None,
+            created_at: now,
+            tags: tags.clone(),
+        };
+        inner.persist(&record);
+        inner.index(record);
+        client_order_id
+    }
+
+    /// 报单回报补全交易所编号，并把订单号和提交时的标签写入事件标签；非本客户端的报单不处理
+    pub fn tag_order(&self, status: &mut OrderStatus) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let key = (status.front_id, status.session_id, status.order_ref.clone());
+        let client_order_id = inner
+            .by_session_ref
+            .get(&key)
+            .or_else(|| inner.by_sys_id.get(&status.order_sys_id))
+            .cloned()?;
+        if !status.order_sys_id.is_empty() {
+            let updated = inner.records.get(&client_order_id).and_then(|record| {
+                (record.order_sys_id.as_deref() != Some(status.order_sys_id.as_str())).then(|| ClientOrderRecord {
+                    order_sys_id: Some(status.order_sys_id.clone()),
+                    ..record.clone()
+                })
+            });
+            if let Some(record) = updated {
+                inner.persist(&record);
+                inner.index(record);
+            }
+        }
+        if let Some(record) = inner.records.get(&client_order_id) {
+            merge_tags(&mut status.tags, &record.tags);
+        }
+        status.tags.insert(CLIENT_ORDER_ID_TAG.to_string(), client_order_id.clone());
+        Some(client_order_id)
+    }
+
+    /// 成交回报按交易所编号找订单号，找不到时按当前会话的报单引用；同时补全订单标签
+    pub fn tag_trade(&self, order_sys_id: &str, record: &mut TradeRecord) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        let client_order_id = inner
+            .by_sys_id
+            .get(order_sys_id)
+            .or_else(|| {
+                inner
+                    .by_session_ref
+                    .get(&(inner.front_id, inner.session_id, record.order_id.clone()))
+            })
+            .cloned()?;
+        if let Some(stored) = inner.records.get(&client_order_id) {
+            merge_tags(&mut record.tags, &stored.tags);
+        }
+        record.tags.insert(CLIENT_ORDER_ID_TAG.to_string(), client_order_id.clone());
+        Some(client_order_id)
+    }
+
+    pub fn get(&self, client_order_id: &str) -> Option<ClientOrderRecord> {
+        self.inner.lock().unwrap().records.get(client_order_id).cloned()
+    }
+
+    pub fn find_by_order_sys_id(&self, order_sys_id: &str) -> Option<ClientOrderRecord> {
+        let inner = self.inner.lock().unwrap();
+        inner.by_sys_id.get(order_sys_id).and_then(|id| inner.records.get(id)).cloned()
+    }
+}
+
+/// 回报已带的标签优先，缺少的按记录补齐
+fn merge_tags(tags: &mut OrderTags, stored: &OrderTags) {
+    for (key, value) in stored {
+        tags.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+}
+
+/// 128 位编码为 26 位 Crockford Base32
+fn encode_ulid(value: u128) -> String {
+    (0..26)
+        .rev()
+        .map(|i| ULID_ALPHABET[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+fn decode_ulid(id: &str) -> Option<u128> {
+    if id.len() != 26 {
+        return None;
+    }
+    id.bytes().try_fold(0u128, |acc, b| {
+        let digit = ULID_ALPHABET.iter().position(|&c| c == b.to_ascii_uppercase())?;
+        Some((acc << 5) | digit as u128)
+    })
+}
+
+fn append_line(path: &Path, record: &ClientOrderRecord) -> Result<(), CtpError> {
+    let line = serde_json::to_string(record).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{OffsetFlag, OrderDirection, OrderStatusType};
+    use tempfile::TempDir;
+
+    fn order_update(front_id: i32, session_id: i32, order_ref: &str, order_sys_id: &str) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_ref.to_string(),
+            order_id: order_ref.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            limit_price: 3500.0,
+            volume: 1,
+            volume_total_original: 1,
+            volume_traded: 0,
+            volume_left: 1,
+            volume_total: 1,
+            status: OrderStatusType::NoTradeQueueing,
+            submit_time: chrono::Local::now(),
+            insert_time: "09:00:00".to_string(),
+            update_time: chrono::Local::now(),
+            front_id,
+            session_id,
+            order_sys_id: order_sys_id.to_string(),
+            status_msg: String::new(),
+            is_local: false,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_ulid_is_sortable_and_monotonic() {
+        let ids = ClientOrderIds::in_memory();
+        let now = Utc::now();
+        let first = ids.assign_at("1", "rb2501", &mut OrderTags::new(), now);
+        let second = ids.assign_at("2", "rb2501", &mut OrderTags::new(), now);
+        let later = ids.assign_at("3", "rb2501", &mut OrderTags::new(), now + chrono::Duration::milliseconds(5));
+        assert_eq!(first.len(), 26);
+        assert!(first < second && second < later);
+        assert_eq!(decode_ulid(&first).map(encode_ulid), Some(first.clone()));
+
+        // 标签中已有订单号时沿用
+        let mut tags = OrderTags::from([(CLIENT_ORDER_ID_TAG.to_string(), first.clone())]);
+        assert_eq!(ids.assign("1", "rb2501", &mut tags), first);
+    }
+
+    #[test]
+    fn test_mapping_survives_new_session() {
+        let dir = TempDir::new().unwrap();
+        let ids = ClientOrderIds::open(dir.path(), "test/user").unwrap();
+        ids.set_session(1, 100);
+        let mut tags = OrderTags::from([("strategy".to_string(), "breakout".to_string())]);
+        let client_order_id = ids.assign("000001", "rb2501", &mut tags);
+        assert_eq!(tags.get(CLIENT_ORDER_ID_TAG), Some(&client_order_id));
+
+        let mut accepted = order_update(1, 100, "000001", "SYS123");
+        assert_eq!(ids.tag_order(&mut accepted).as_deref(), Some(client_order_id.as_str()));
+        assert_eq!(accepted.tags.get(CLIENT_ORDER_ID_TAG), Some(&client_order_id));
+        assert!(ids.tag_order(&mut order_update(1, 100, "000002", "")).is_none());
+
+        // 重启后新会话的 OrderRef 重新计数，旧订单仍能按交易所编号找回
+        let reopened = ClientOrderIds::open(dir.path(), "test/user").unwrap();
+        reopened.set_session(2, 200);
+        let record = reopened.find_by_order_sys_id("SYS123").unwrap();
+        assert_eq!((record.front_id, record.session_id, record.order_ref.as_str()), (1, 100, "000001"));
+        assert_eq!(reopened.get(&client_order_id).unwrap().order_sys_id.as_deref(), Some("SYS123"));
+
+        let mut trade = TradeRecord {
+            trade_id: "T1".to_string(),
+            order_id: "000001".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume: 1,
+            trade_time: "09:00:01".to_string(),
+            exchange_id: String::new(),
+            tags: Default::default(),
+        };
+        assert_eq!(reopened.tag_trade("SYS123", &mut trade).as_deref(), Some(client_order_id.as_str()));
+        // 重启前提交的订单标签随记录恢复，成交仍归属原策略
+        assert_eq!(trade.tags.get("strategy").map(String::as_str), Some("breakout"));
+        let next = reopened.assign("000001", "rb2501", &mut OrderTags::new());
+        assert!(next > client_order_id);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::ctp::{rejection_breaker::MANUAL_SOURCE, CtpError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 默认合规阈值配置文件
+pub const DEFAULT_COMPLIANCE_CONFIG_FILE: &str = "./config/compliance.toml";
+
+/// 账户整体的统计范围，其余范围为报单来源（`strategy:<策略名>` 或手动）
+pub const ACCOUNT_SCOPE: &str = "account";
+
+/// 已登记来源的报单引用上限，超过后清理最早的一半
+const MAX_TRACKED_ORDERS: usize = 10_000;
+
+/// 报撤单合规阈值，参照交易所程序化交易管理规定配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComplianceConfig {
+    /// 滚动统计窗口（秒）
+    pub window_secs: u64,
+    /// 报单数 / 成交笔数 上限
+    pub max_order_to_trade_ratio: f64,
+    /// 撤单数 / 报单数 上限
+    pub max_cancel_ratio: f64,
+    /// 窗口内报单数达到该值后才检查比例
+    pub min_orders: usize,
+    /// 超限后自动降速
+    pub slow_down: bool,
+    /// 降速期间同一范围两笔报单的最小间隔（毫秒）
+    pub slow_down_interval_ms: u64,
+}
+
+impl Default for ComplianceConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 600,
+            max_order_to_trade_ratio: 10.0,
+            max_cancel_ratio: 0.5,
+            min_orders: 20,
+            slow_down: false,
+            slow_down_interval_ms: 1_000,
+        }
+    }
+}
+
+impl ComplianceConfig {
+    /// 读取阈值配置，文件不存在时使用默认值
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("合规阈值配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.window_secs == 0 {
+            return Err(CtpError::ValidationError("统计窗口须大于 0".to_string()));
+        }
+        if self.max_order_to_trade_ratio <= 0.0 || self.max_cancel_ratio <= 0.0 {
+            return Err(CtpError::ValidationError("比例上限须大于 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 超限告警
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComplianceWarning {
+    pub scope: String,
+    /// 超出的指标说明
+    pub violations: Vec<String>,
+    /// 是否已自动降速
+    pub throttled: bool,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl ComplianceWarning {
+    pub fn message(&self) -> String {
+        let action = if self.throttled { "，已自动降速" } else { "" };
+        format!("{} 报撤单比例超限: {}{}", self.scope, self.violations.join("; "), action)
+    }
+}
+
+/// 范围内的滚动统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComplianceStatus {
+    pub scope: String,
+    pub orders: usize,
+    pub cancels: usize,
+    pub trades: usize,
+    /// 无成交时等于报单数
+    pub order_to_trade_ratio: f64,
+    pub cancel_ratio: f64,
+    pub violations: Vec<String>,
+    /// 降速中
+    pub throttled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Activity {
+    Order,
+    Cancel,
+    Trade,
+}
+
+#[derive(Default)]
+struct ScopeStats {
+    events: VecDeque<(DateTime<Utc>, Activity)>,
+    violating: bool,
+    last_order_at: Option<DateTime<Utc>>,
+}
+
+impl ScopeStats {
+    fn count(&self, activity: Activity) -> usize {
+        self.events.iter().filter(|(_, a)| *a == activity).count()
+    }
+}
+
+#[derive(Default)]
+struct MonitorInner {
+    order_sources: HashMap<String, String>,
+    order_queue: VecDeque<String>,
+    scopes: BTreeMap<String, ScopeStats>,
+}
+
+/// 报撤单合规监控
+///
+/// 按账户整体和各报单来源统计滚动窗口内的报单成交比、撤单比，超出阈值时告警；
+/// 开启自动降速后，超限范围的报单须间隔一定时间，恢复到阈值内后自动解除
+#[derive(Clone)]
+pub struct ComplianceMonitor {
+    config: ComplianceConfig,
+    inner: Arc<Mutex<MonitorInner>>,
+}
+
+impl Default for ComplianceMonitor {
+    fn default() -> Self {
+        Self::new(ComplianceConfig::default())
+    }
+}
+
+impl ComplianceMonitor {
+    pub fn new(config: ComplianceConfig) -> Self {
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(MonitorInner::default())),
+        }
+    }
+
+    pub fn config(&self) -> &ComplianceConfig {
+        &self.config
+    }
+
+    /// 报单前检查，降速中且距上一笔报单不足间隔时返回风控错误
+    pub fn check(&self, source: &str) -> Result<(), CtpError> {
+        self.check_at(source, Utc::now())
+    }
+
+    fn check_at(&self, source: &str, now: DateTime<Utc>) -> Result<(), CtpError> {
+        if !self.config.slow_down {
+            return Ok(());
+        }
+        let interval = chrono::Duration::milliseconds(self.config.slow_down_interval_ms as i64);
+        let mut inner = self.inner.lock().unwrap();
+        for scope in [ACCOUNT_SCOPE, source] {
+            let Some(stats) = inner.scopes.get_mut(scope) else {
+                continue;
+            };
+            // 窗口滑过后可能已恢复
+            self.expire(stats, now);
+            stats.violating = !self.status(scope, stats).violations.is_empty();
+            if !stats.violating {
+                continue;
+            }
+            if let Some(last) = stats.last_order_at {
+                let wait = interval - (now - last);
+                if wait > chrono::Duration::zero() {
+                    return Err(CtpError::RiskControl(format!(
+                        "{} 报撤单比例超限已降速，请 {} 毫秒后再报单",
+                        scope,
+                        wait.num_milliseconds()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 登记报单，撤单和成交回报按报单引用归属来源
+    pub fn register_order(&self, order_ref: &str, source: &str) -> Vec<ComplianceWarning> {
+        self.register_order_at(order_ref, source, Utc::now())
+    }
+
+    fn register_order_at(&self, order_ref: &str, source: &str, now: DateTime<Utc>) -> Vec<ComplianceWarning> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.order_sources.len() >= MAX_TRACKED_ORDERS {
+            for _ in 0..MAX_TRACKED_ORDERS / 2 {
+                if let Some(old) = inner.order_queue.pop_front() {
+                    inner.order_sources.remove(&old);
+                }
+            }
+        }
+        inner.order_sources.insert(order_ref.to_string(), source.to_string());
+        inner.order_queue.push_back(order_ref.to_string());
+        self.record(&mut inner, source, Activity::Order, now)
+    }
+
+    /// 记录撤单请求
+    pub fn record_cancel(&self, order_ref: &str) -> Vec<ComplianceWarning> {
+        self.record_for_order(order_ref, Activity::Cancel, Utc::now())
+    }
+
+    /// 记录成交回报，成交只会降低比例，不产生告警
+    pub fn record_trade(&self, order_ref: &str) {
+        self.record_for_order(order_ref, Activity::Trade, Utc::now());
+    }
+
+    /// 账户及各来源的当前统计，账户在前
+    pub fn statuses(&self) -> Vec<ComplianceStatus> {
+        self.statuses_at(Utc::now())
+    }
+
+    fn statuses_at(&self, now: DateTime<Utc>) -> Vec<ComplianceStatus> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut statuses: Vec<ComplianceStatus> = inner
+            .scopes
+            .iter_mut()
+            .map(|(scope, stats)| {
+                self.expire(stats, now);
+                let status = self.status(scope, stats);
+                stats.violating = !status.violations.is_empty();
+                status
+            })
+            .collect();
+        statuses.sort_by_key(|s| s.scope != ACCOUNT_SCOPE);
+        statuses
+    }
+
+    fn record_for_order(&self, order_ref: &str, activity: Activity, now: DateTime<Utc>) -> Vec<ComplianceWarning> {
+        let mut inner = self.inner.lock().unwrap();
+        let source = inner
+            .order_sources
+            .get(order_ref)
+            .cloned()
+            .unwrap_or_else(|| MANUAL_SOURCE.to_string());
+        self.record(&mut inner, &source, activity, now)
+    }
+
+    fn record(&self, inner: &mut MonitorInner, source: &str, activity: Activity, now: DateTime<Utc>) -> Vec<ComplianceWarning> {
+        let mut warnings = Vec::new();
+        for scope in [ACCOUNT_SCOPE, source] {
+            let stats = inner.scopes.entry(scope.to_string()).or_default();
+            stats.events.push_back((now, activity));
+            if activity == Activity::Order {
+                stats.last_order_at = Some(now);
+            }
+            self.expire(stats, now);
+
+            let status = self.status(scope, stats);
+            let violating = !status.violations.is_empty();
+            if violating && !stats.violating {
+                tracing::warn!("{} 报撤单比例超限: {}", scope, status.violations.join("; "));
+                warnings.push(ComplianceWarning {
+                    scope: scope.to_string(),
+                    violations: status.violations,
+                    throttled: self.config.slow_down,
+                    detected_at: now,
+                });
+            } else if !violating && stats.violating {
+                tracing::info!("{} 报撤单比例恢复正常", scope);
+            }
+            stats.violating = violating;
+        }
+        warnings
+    }
+
+    fn expire(&self, stats: &mut ScopeStats, now: DateTime<Utc>) {
+        let window = chrono::Duration::seconds(self.config.window_secs as i64);
+        while stats.events.front().is_some_and(|(at, _)| now - *at > window) {
+            stats.events.pop_front();
+        }
+    }
+
+    fn status(&self, scope: &str, stats: &ScopeStats) -> ComplianceStatus {
+        let orders = stats.count(Activity::Order);
+        let cancels = stats.count(Activity::Cancel);
+        let trades = stats.count(Activity::Trade);
+        let order_to_trade_ratio = orders as f64 / trades.max(1) as f64;
+        let cancel_ratio = if orders == 0 { 0.0 } else { cancels as f64 / orders as f64 };
+
+        let mut violations = Vec::new();
+        if orders >= self.config.min_orders {
+            if order_to_trade_ratio > self.config.max_order_to_trade_ratio {
+                violations.push(format!(
+                    "报单成交比 {:.1} 超过 {:.1}",
+                    order_to_trade_ratio, self.config.max_order_to_trade_ratio
+                ));
+            }
+            if cancel_ratio > self.config.max_cancel_ratio {
+                violations.push(format!(
+                    "撤单比 {:.0}% 超过 {:.0}%",
+                    cancel_ratio * 100.0,
+                    self.config.max_cancel_ratio * 100.0
+                ));
+            }
+        }
+        ComplianceStatus {
+            scope: scope.to_string(),
+            orders,
+            cancels,
+            trades,
+            order_to_trade_ratio,
+            cancel_ratio,
+            throttled: self.config.slow_down && !violations.is_empty(),
+            violations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ComplianceConfig {
+        ComplianceConfig {
+            window_secs: 60,
+            max_order_to_trade_ratio: 3.0,
+            max_cancel_ratio: 0.5,
+            min_orders: 4,
+            slow_down: true,
+            slow_down_interval_ms: 500,
+        }
+    }
+
+    #[test]
+    fn test_cancel_ratio_per_account_and_strategy() {
+        let monitor = ComplianceMonitor::new(ComplianceConfig {
+            max_order_to_trade_ratio: 10.0,
+            ..config()
+        });
+        let now = Utc::now();
+        for i in 0..4 {
+            monitor.register_order_at(&format!("g{}", i), "strategy:grid", now);
+        }
+        monitor.register_order_at("m0", MANUAL_SOURCE, now);
+        monitor.record_for_order("m0", Activity::Trade, now);
+        monitor.record_for_order("g0", Activity::Trade, now);
+        monitor.record_for_order("g1", Activity::Cancel, now);
+        monitor.record_for_order("g2", Activity::Cancel, now);
+        let warnings = monitor.record_for_order("g3", Activity::Cancel, now);
+
+        // 策略撤单 3/4 超限，账户整体 3/5 同样超限
+        let scopes: Vec<&str> = warnings.iter().map(|w| w.scope.as_str()).collect();
+        assert_eq!(scopes, vec![ACCOUNT_SCOPE, "strategy:grid"]);
+        assert!(warnings[1].violations[0].contains("撤单比"));
+
+        let statuses = monitor.statuses_at(now);
+        assert_eq!(statuses[0].scope, ACCOUNT_SCOPE);
+        let manual = statuses.iter().find(|s| s.scope == MANUAL_SOURCE).unwrap();
+        assert!(manual.violations.is_empty());
+
+        // 窗口过后统计清空
+        let later = now + chrono::Duration::seconds(120);
+        assert!(monitor.statuses_at(later).iter().all(|s| s.orders == 0 && s.violations.is_empty()));
+    }
+
+    #[test]
+    fn test_slow_down_until_ratio_recovers() {
+        let monitor = ComplianceMonitor::new(config());
+        let start = Utc::now();
+        let mut warnings = Vec::new();
+        for i in 0..4 {
+            warnings.extend(monitor.register_order_at(&format!("{}", i), "strategy:scalp", start));
+        }
+        // 4 笔报单 0 成交，报单成交比超限
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|w| w.throttled));
+
+        let soon = start + chrono::Duration::milliseconds(100);
+        assert!(matches!(monitor.check_at("strategy:scalp", soon), Err(CtpError::RiskControl(_))));
+        // 账户整体超限时其他来源同样降速
+        assert!(monitor.check_at(MANUAL_SOURCE, soon).is_err());
+        assert!(monitor.check_at("strategy:scalp", start + chrono::Duration::milliseconds(600)).is_ok());
+
+        monitor.record_for_order("0", Activity::Trade, soon);
+        monitor.record_for_order("1", Activity::Trade, soon);
+        assert!(monitor.check_at("strategy:scalp", soon).is_ok());
+        assert!(!monitor.statuses_at(soon)[0].throttled);
+    }
+}
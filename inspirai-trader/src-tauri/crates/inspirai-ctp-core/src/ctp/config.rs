@@ -2,10 +2,14 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::time::Duration;
 use std::str::FromStr;
-use clap::ValueEnum;
+
+use crate::ctp::market_order::MarketOrderPolicy;
+use crate::ctp::programmatic_filing::ProgrammaticTradingInfo;
 
 /// 环境类型枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum Environment {
     /// SimNow 模拟环境
     #[serde(rename = "simnow")]
@@ -47,12 +51,72 @@ impl FromStr for Environment {
     }
 }
 
+/// 连接模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ConnectionMode {
+    /// 行情 + 交易
+    #[serde(rename = "full")]
+    Full,
+    /// 仅行情（数据采集部署，无需交易凭据）
+    #[serde(rename = "md_only")]
+    MdOnly,
+    /// 仅交易（报单网关部署，无需行情）
+    #[serde(rename = "td_only")]
+    TdOnly,
+}
+
+impl ConnectionMode {
+    /// 是否使用行情端
+    pub fn uses_md(&self) -> bool {
+        matches!(self, ConnectionMode::Full | ConnectionMode::MdOnly)
+    }
+
+    /// 是否使用交易端
+    pub fn uses_td(&self) -> bool {
+        matches!(self, ConnectionMode::Full | ConnectionMode::TdOnly)
+    }
+}
+
+impl Default for ConnectionMode {
+    fn default() -> Self {
+        ConnectionMode::Full
+    }
+}
+
+impl std::fmt::Display for ConnectionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionMode::Full => write!(f, "full"),
+            ConnectionMode::MdOnly => write!(f, "md_only"),
+            ConnectionMode::TdOnly => write!(f, "td_only"),
+        }
+    }
+}
+
+impl FromStr for ConnectionMode {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(ConnectionMode::Full),
+            "md_only" | "md" => Ok(ConnectionMode::MdOnly),
+            "td_only" | "td" => Ok(ConnectionMode::TdOnly),
+            _ => Err(format!("Invalid connection mode: {}", s)),
+        }
+    }
+}
+
 /// CTP 连接配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CtpConfig {
     /// 环境类型
     #[serde(default)]
     pub environment: Environment,
+    /// 连接模式
+    #[serde(default)]
+    pub connection_mode: ConnectionMode,
     /// 行情前置地址
     pub md_front_addr: String,
     /// 交易前置地址  
@@ -84,6 +148,12 @@ pub struct CtpConfig {
     /// 最大重连次数
     #[serde(default = "default_max_reconnect_attempts")]
     pub max_reconnect_attempts: u32,
+    /// 市价单处理策略
+    #[serde(default)]
+    pub market_order_policy: MarketOrderPolicy,
+    /// 程序化交易报备信息
+    #[serde(default)]
+    pub programmatic: ProgrammaticTradingInfo,
 }
 
 impl CtpConfig {
@@ -105,6 +175,7 @@ impl CtpConfig {
     pub fn simnow_config(investor_id: String, password: String) -> Self {
         Self {
             environment: Environment::SimNow,
+            connection_mode: ConnectionMode::Full,
             md_front_addr: "tcp://180.168.146.187:10131".to_string(),
             trader_front_addr: "tcp://180.168.146.187:10130".to_string(),
             broker_id: "9999".to_string(),
@@ -118,6 +189,8 @@ impl CtpConfig {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: MarketOrderPolicy::default(),
+            programmatic: ProgrammaticTradingInfo::default(),
         }
     }
 
@@ -125,6 +198,7 @@ impl CtpConfig {
     pub fn tts_config(investor_id: String, password: String) -> Self {
         Self {
             environment: Environment::Tts,
+            connection_mode: ConnectionMode::Full,
             md_front_addr: "tcp://121.37.80.177:20004".to_string(),
             trader_front_addr: "tcp://121.37.80.177:20002".to_string(),
             broker_id: "9999".to_string(),
@@ -138,6 +212,8 @@ impl CtpConfig {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: MarketOrderPolicy::default(),
+            programmatic: ProgrammaticTradingInfo::default(),
         }
     }
 
@@ -145,6 +221,7 @@ impl CtpConfig {
     pub fn production_config(investor_id: String, password: String) -> Self {
         Self {
             environment: Environment::Production,
+            connection_mode: ConnectionMode::Full,
             md_front_addr: "tcp://180.168.146.187:10131".to_string(), // 需要替换为实际地址
             trader_front_addr: "tcp://180.168.146.187:10130".to_string(), // 需要替换为实际地址
             broker_id: "".to_string(), // 需要用户配置
@@ -158,6 +235,8 @@ impl CtpConfig {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: MarketOrderPolicy::default(),
+            programmatic: ProgrammaticTradingInfo::default(),
         }
     }
 
@@ -285,28 +364,31 @@ impl CtpConfig {
         if self.broker_id.is_empty() {
             return Err(crate::ctp::CtpError::ConfigError("经纪商代码不能为空".to_string()));
         }
-        if self.investor_id.is_empty() {
-            return Err(crate::ctp::CtpError::ConfigError("投资者代码不能为空".to_string()));
-        }
-        if self.password.is_empty() {
-            return Err(crate::ctp::CtpError::ConfigError("密码不能为空".to_string()));
+        // 仅行情模式不需要交易凭据
+        if self.connection_mode.uses_td() {
+            if self.investor_id.is_empty() {
+                return Err(crate::ctp::CtpError::ConfigError("投资者代码不能为空".to_string()));
+            }
+            if self.password.is_empty() {
+                return Err(crate::ctp::CtpError::ConfigError("密码不能为空".to_string()));
+            }
+            if self.trader_front_addr.is_empty() {
+                return Err(crate::ctp::CtpError::ConfigError("交易前置地址不能为空".to_string()));
+            }
         }
-        if self.md_front_addr.is_empty() {
+        if self.connection_mode.uses_md() && self.md_front_addr.is_empty() {
             return Err(crate::ctp::CtpError::ConfigError("行情前置地址不能为空".to_string()));
         }
-        if self.trader_front_addr.is_empty() {
-            return Err(crate::ctp::CtpError::ConfigError("交易前置地址不能为空".to_string()));
-        }
 
         // 验证动态库路径
-        if let Some(md_path) = &self.md_dynlib_path {
+        if let Some(md_path) = self.md_dynlib_path.as_ref().filter(|_| self.connection_mode.uses_md()) {
             if !md_path.exists() {
                 return Err(crate::ctp::CtpError::LibraryLoadError(
                     format!("行情动态库文件不存在: {:?}", md_path)
                 ));
             }
         }
-        if let Some(td_path) = &self.td_dynlib_path {
+        if let Some(td_path) = self.td_dynlib_path.as_ref().filter(|_| self.connection_mode.uses_td()) {
             if !td_path.exists() {
                 return Err(crate::ctp::CtpError::LibraryLoadError(
                     format!("交易动态库文件不存在: {:?}", td_path)
@@ -382,4 +464,29 @@ mod tests {
         // 现在应该验证成功
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_connection_mode_validation() {
+        let mut config = CtpConfig::default();
+        config.broker_id = "9999".to_string();
+        assert_eq!(config.connection_mode, ConnectionMode::Full);
+        assert!(config.validate().is_err());
+
+        // 仅行情模式不需要交易凭据
+        config.connection_mode = ConnectionMode::MdOnly;
+        config.trader_front_addr.clear();
+        assert!(config.validate().is_ok());
+
+        // 仅交易模式不需要行情前置
+        config.connection_mode = ConnectionMode::TdOnly;
+        config.md_front_addr.clear();
+        assert!(config.validate().is_err());
+        config.trader_front_addr = "tcp://127.0.0.1:10130".to_string();
+        config.investor_id = "test_user".to_string();
+        config.password = "test_pass".to_string();
+        assert!(config.validate().is_ok());
+
+        assert_eq!("md_only".parse::<ConnectionMode>().unwrap(), ConnectionMode::MdOnly);
+        assert!(!ConnectionMode::TdOnly.uses_md());
+    }
 }
\ No newline at end of file
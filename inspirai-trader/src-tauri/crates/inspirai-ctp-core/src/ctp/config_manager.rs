@@ -235,6 +235,11 @@ impl ConfigManager {
             config.flow_path = flow_path;
         }
         
+        if let Ok(mode) = std::env::var("CTP_CONNECTION_MODE") {
+            config.connection_mode = mode.parse()
+                .map_err(|e| CtpError::ConfigError(format!("解析连接模式失败: {}", e)))?;
+        }
+        
         if let Ok(timeout) = std::env::var("CTP_TIMEOUT_SECS") {
             config.timeout_secs = timeout.parse()
                 .map_err(|e| CtpError::ConfigError(format!("解析超时时间失败: {}", e)))?;
@@ -311,6 +316,11 @@ impl ConfigManager {
     pub fn merge_configs(file_config: CtpConfig, env_config: CtpConfig) -> CtpConfig {
         CtpConfig {
             environment: file_config.environment,
+            connection_mode: if env_config.connection_mode != CtpConfig::default().connection_mode {
+                env_config.connection_mode
+            } else {
+                file_config.connection_mode
+            },
             md_front_addr: if env_config.md_front_addr != CtpConfig::default().md_front_addr {
                 env_config.md_front_addr
             } else {
@@ -368,6 +378,8 @@ impl ConfigManager {
             } else {
                 file_config.max_reconnect_attempts
             },
+            market_order_policy: file_config.market_order_policy,
+            programmatic: file_config.programmatic,
         }
     }
 }
\ No newline at end of file
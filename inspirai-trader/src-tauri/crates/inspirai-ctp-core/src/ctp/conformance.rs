@@ -0,0 +1,529 @@
+//! OpenCTP TTS 端到端一致性测试
+//!
+//! 对接 7x24 的 TTS 环境依次执行 连接→登录→订阅→收行情→报单→撤单，并检查回报中的中文文本
+//! 经 GB18030 解码后没有乱码，结果写成 JSON 报告供 CI 归档。须设置 `CTP_TTS_CONFORMANCE=1`
+//! 才会运行，未设置时 CI 中直接跳过。
+
+use crate::ctp::{
+    config::{CtpConfig, Environment},
+    error::CtpError,
+    self_test::StageOutcome,
+    utils::{gb18030_to_utf8, utf8_to_gb18030},
+    CtpClient, CtpEvent, LoginCredentials, MarketDataTick, OrderInput, OrderStatus, OrderStatusType,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// 开启一致性测试的环境变量，取值 1 或 true
+pub const CONFORMANCE_ENV_FLAG: &str = "CTP_TTS_CONFORMANCE";
+
+/// 默认报告路径
+pub const DEFAULT_CONFORMANCE_REPORT: &str = "target/conformance/tts_conformance.json";
+
+/// 每个阶段等待回报的默认超时
+const DEFAULT_STAGE_TIMEOUT_SECS: u64 = 30;
+
+/// 一致性测试配置，全部来自环境变量
+#[derive(Debug, Clone)]
+pub struct ConformanceConfig {
+    pub investor_id: String,
+    pub password: String,
+    /// 订阅和报单的合约，须为 TTS 当前回放的合约
+    pub instrument: String,
+    pub md_dynlib_path: Option<PathBuf>,
+    pub td_dynlib_path: Option<PathBuf>,
+    pub report_path: PathBuf,
+    pub stage_timeout: Duration,
+}
+
+impl ConformanceConfig {
+    /// 从环境变量读取，未开启时返回 None
+    ///
+    /// 账号、密码、合约分别取 `CTP_TTS_INVESTOR_ID`、`CTP_TTS_PASSWORD`、`CTP_TTS_INSTRUMENT`；
+    /// 动态库取 `CTP_MD_DYNLIB`、`CTP_TD_DYNLIB`，报告路径取 `CTP_TTS_REPORT`
+    pub fn from_env() -> Result<Option<Self>, CtpError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Option<Self>, CtpError> {
+        let enabled = lookup(CONFORMANCE_ENV_FLAG)
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+
+        let required = |key: &str| {
+            lookup(key)
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| CtpError::ConfigError(format!("已开启 TTS 一致性测试，但未设置 {}", key)))
+        };
+        let stage_timeout_secs = match lookup("CTP_TTS_STAGE_TIMEOUT_SECS") {
+            Some(v) => v
+                .parse()
+                .map_err(|e| CtpError::ConfigError(format!("解析 CTP_TTS_STAGE_TIMEOUT_SECS 失败: {}", e)))?,
+            None => DEFAULT_STAGE_TIMEOUT_SECS,
+        };
+        if stage_timeout_secs == 0 {
+            return Err(CtpError::ConfigError("阶段超时必须大于 0".to_string()));
+        }
+
+        Ok(Some(Self {
+            investor_id: required("CTP_TTS_INVESTOR_ID")?,
+            password: required("CTP_TTS_PASSWORD")?,
+            instrument: required("CTP_TTS_INSTRUMENT")?,
+            md_dynlib_path: lookup("CTP_MD_DYNLIB").map(PathBuf::from),
+            td_dynlib_path: lookup("CTP_TD_DYNLIB").map(PathBuf::from),
+            report_path: lookup("CTP_TTS_REPORT")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFORMANCE_REPORT)),
+            stage_timeout: Duration::from_secs(stage_timeout_secs),
+        }))
+    }
+
+    /// TTS 环境的客户端配置
+    pub fn ctp_config(&self) -> CtpConfig {
+        let mut config = CtpConfig::tts_config(self.investor_id.clone(), self.password.clone());
+        if self.md_dynlib_path.is_some() {
+            config.md_dynlib_path = self.md_dynlib_path.clone();
+        }
+        if self.td_dynlib_path.is_some() {
+            config.td_dynlib_path = self.td_dynlib_path.clone();
+        }
+        config
+    }
+}
+
+/// 一致性测试阶段，按顺序执行，前一阶段失败时后续依赖阶段跳过
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConformanceStage {
+    Connect,
+    Login,
+    Subscribe,
+    MarketData,
+    PlaceOrder,
+    CancelOrder,
+    /// 回报中的中文文本经 GB18030 解码无乱码，与其他阶段独立判断
+    Gb18030,
+}
+
+impl ConformanceStage {
+    pub const ALL: [ConformanceStage; 7] = [
+        ConformanceStage::Connect,
+        ConformanceStage::Login,
+        ConformanceStage::Subscribe,
+        ConformanceStage::MarketData,
+        ConformanceStage::PlaceOrder,
+        ConformanceStage::CancelOrder,
+        ConformanceStage::Gb18030,
+    ];
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceStageResult {
+    pub stage: ConformanceStage,
+    pub outcome: StageOutcome,
+    pub duration_ms: u64,
+    pub detail: String,
+}
+
+/// 一致性测试报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    pub environment: Environment,
+    pub md_front_addr: String,
+    pub trader_front_addr: String,
+    pub instrument: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+    pub stages: Vec<ConformanceStageResult>,
+    /// 全部阶段通过
+    pub passed: bool,
+}
+
+impl ConformanceReport {
+    /// 一行摘要，用于日志和 CI 输出
+    pub fn summary(&self) -> String {
+        let failed: Vec<String> = self
+            .stages
+            .iter()
+            .filter(|s| s.outcome != StageOutcome::Passed)
+            .map(|s| format!("{:?}({:?}): {}", s.stage, s.outcome, s.detail))
+            .collect();
+        if failed.is_empty() {
+            format!("TTS 一致性测试通过（{} 个阶段）", self.stages.len())
+        } else {
+            format!("TTS 一致性测试未通过: {}", failed.join("; "))
+        }
+    }
+
+    /// 写出 JSON 报告，目录不存在时创建
+    pub fn save(&self, path: &Path) -> Result<(), CtpError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// 一致性测试执行器
+pub struct ConformanceRunner {
+    config: ConformanceConfig,
+    ctp_config: CtpConfig,
+    events: Option<mpsc::UnboundedReceiver<CtpEvent>>,
+    /// 回报中的非 ASCII 文本，用于 GB18030 检查
+    texts: Vec<String>,
+    stages: Vec<ConformanceStageResult>,
+}
+
+impl ConformanceRunner {
+    pub fn new(config: ConformanceConfig) -> Self {
+        let ctp_config = config.ctp_config();
+        Self {
+            config,
+            ctp_config,
+            events: None,
+            texts: Vec::new(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// 执行全部阶段并生成报告，结束时断开连接
+    pub async fn run(mut self) -> ConformanceReport {
+        let started_at = chrono::Utc::now();
+        let start = Instant::now();
+        match CtpClient::new(self.ctp_config.clone()).await {
+            Ok(mut client) => {
+                self.events = client.take_event_receiver();
+                self.run_stages(&mut client).await;
+                client.disconnect();
+            }
+            Err(e) => self.record(ConformanceStage::Connect, StageOutcome::Failed, start, format!("创建客户端失败: {}", e)),
+        }
+
+        let (outcome, detail) = check_gb18030(&self.texts);
+        self.record(ConformanceStage::Gb18030, outcome, Instant::now(), detail);
+        self.into_report(started_at)
+    }
+
+    async fn run_stages(&mut self, client: &mut CtpClient) {
+        // 连接
+        let start = Instant::now();
+        if let Err(e) = client.connect_with_retry().await {
+            return self.record(ConformanceStage::Connect, StageOutcome::Failed, start, e.to_string());
+        }
+        self.record(ConformanceStage::Connect, StageOutcome::Passed, start, "行情、交易前置已连接".to_string());
+
+        // 登录并确认结算单，TTS 未确认结算单时拒绝报单
+        let start = Instant::now();
+        let credentials = LoginCredentials {
+            broker_id: self.ctp_config.broker_id.clone(),
+            user_id: self.ctp_config.investor_id.clone(),
+            password: self.ctp_config.password.clone(),
+            app_id: self.ctp_config.app_id.clone(),
+            auth_code: self.ctp_config.auth_code.clone(),
+        };
+        let login = match client.login(credentials).await {
+            Ok(response) => response,
+            Err(e) => return self.record(ConformanceStage::Login, StageOutcome::Failed, start, e.to_string()),
+        };
+        if client.is_degraded() {
+            let detail = format!("交易端登录失败，仅行情可用: {:?}", client.get_session_health().td);
+            return self.record(ConformanceStage::Login, StageOutcome::Failed, start, detail);
+        }
+        if let Err(e) = client.confirm_settlement_info().await {
+            return self.record(ConformanceStage::Login, StageOutcome::Failed, start, format!("结算单确认失败: {}", e));
+        }
+        self.record(
+            ConformanceStage::Login,
+            StageOutcome::Passed,
+            start,
+            format!("交易日 {}，已确认结算单", login.trading_day),
+        );
+
+        // 订阅
+        let instrument = self.config.instrument.clone();
+        let start = Instant::now();
+        if let Err(e) = client.subscribe_market_data(std::slice::from_ref(&instrument)).await {
+            return self.record(ConformanceStage::Subscribe, StageOutcome::Failed, start, e.to_string());
+        }
+        self.record(ConformanceStage::Subscribe, StageOutcome::Passed, start, format!("已订阅 {}", instrument));
+
+        // 收行情
+        let start = Instant::now();
+        let tick = self
+            .wait_event(|event| match event {
+                CtpEvent::MarketData(tick) if tick.instrument_id == instrument => Some(tick.clone()),
+                _ => None,
+            })
+            .await;
+        let Some(tick) = tick else {
+            let detail = format!("{} 秒内未收到 {} 的行情", self.config.stage_timeout.as_secs(), instrument);
+            return self.record(ConformanceStage::MarketData, StageOutcome::Failed, start, detail);
+        };
+        self.record(
+            ConformanceStage::MarketData,
+            StageOutcome::Passed,
+            start,
+            format!("最新价 {} 买一 {} 卖一 {}", tick.last_price, tick.bid_price1, tick.ask_price1),
+        );
+
+        // 报单：1 手限价买开，优先挂在跌停价以免成交
+        let start = Instant::now();
+        let Some(price) = resting_buy_price(&tick) else {
+            return self.record(ConformanceStage::PlaceOrder, StageOutcome::Failed, start, "行情缺少可用报价".to_string());
+        };
+        if let Err(e) = client.place_order(tiny_order(&instrument, price)).await {
+            return self.record(ConformanceStage::PlaceOrder, StageOutcome::Failed, start, e.to_string());
+        }
+        // 报单引用以回报为准
+        let accepted = self
+            .wait_event(|event| match event {
+                CtpEvent::OrderUpdate(order)
+                    if order.instrument_id == instrument && order.status != OrderStatusType::Unknown =>
+                {
+                    Some(Ok(order.clone()))
+                }
+                CtpEvent::Error(message) => Some(Err(message.clone())),
+                _ => None,
+            })
+            .await;
+        let order = match accepted {
+            Some(Ok(order)) if !is_cancelled(&order) => order,
+            Some(Ok(order)) => {
+                let detail = format!("报单被拒: {}", order.status_msg);
+                return self.record(ConformanceStage::PlaceOrder, StageOutcome::Failed, start, detail);
+            }
+            Some(Err(message)) => return self.record(ConformanceStage::PlaceOrder, StageOutcome::Failed, start, message),
+            None => {
+                let detail = format!("{} 秒内未收到报单回报", self.config.stage_timeout.as_secs());
+                return self.record(ConformanceStage::PlaceOrder, StageOutcome::Failed, start, detail);
+            }
+        };
+        self.record(
+            ConformanceStage::PlaceOrder,
+            StageOutcome::Passed,
+            start,
+            format!("报单 {} @ {} 状态 {:?}: {}", order.order_ref, price, order.status, order.status_msg),
+        );
+
+        // 撤单
+        let start = Instant::now();
+        if order.status == OrderStatusType::AllTraded {
+            let detail = "报单已全部成交，无可撤".to_string();
+            return self.record(ConformanceStage::CancelOrder, StageOutcome::Skipped, start, detail);
+        }
+        if let Err(e) = client.cancel_order(&order.order_ref).await {
+            return self.record(ConformanceStage::CancelOrder, StageOutcome::Failed, start, e.to_string());
+        }
+        let order_ref = order.order_ref.clone();
+        let cancelled = self
+            .wait_event(|event| match event {
+                CtpEvent::OrderUpdate(order) if order.order_ref == order_ref && is_cancelled(order) => {
+                    Some(Ok(order.status_msg.clone()))
+                }
+                CtpEvent::Error(message) => Some(Err(message.clone())),
+                _ => None,
+            })
+            .await;
+        match cancelled {
+            Some(Ok(status_msg)) => {
+                self.record(ConformanceStage::CancelOrder, StageOutcome::Passed, start, format!("已撤单: {}", status_msg))
+            }
+            Some(Err(message)) => self.record(ConformanceStage::CancelOrder, StageOutcome::Failed, start, message),
+            None => {
+                let detail = format!("{} 秒内未收到撤单回报", self.config.stage_timeout.as_secs());
+                self.record(ConformanceStage::CancelOrder, StageOutcome::Failed, start, detail)
+            }
+        }
+    }
+
+    /// 等待第一个满足条件的事件，途经的事件都收集文本
+    async fn wait_event<T>(&mut self, mut matcher: impl FnMut(&CtpEvent) -> Option<T>) -> Option<T> {
+        let deadline = Instant::now() + self.config.stage_timeout;
+        let events = self.events.as_mut()?;
+        loop {
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            let event = tokio::time::timeout(remaining, events.recv()).await.ok()??;
+            collect_texts(&event, &mut self.texts);
+            if let Some(value) = matcher(&event) {
+                return Some(value);
+            }
+        }
+    }
+
+    fn record(&mut self, stage: ConformanceStage, outcome: StageOutcome, start: Instant, detail: String) {
+        match outcome {
+            StageOutcome::Failed => tracing::warn!("TTS 一致性测试 {:?} 失败: {}", stage, detail),
+            _ => tracing::info!("TTS 一致性测试 {:?} {:?}: {}", stage, outcome, detail),
+        }
+        self.stages.push(ConformanceStageResult {
+            stage,
+            outcome,
+            duration_ms: start.elapsed().as_millis() as u64,
+            detail,
+        });
+    }
+
+    fn into_report(self, started_at: chrono::DateTime<chrono::Utc>) -> ConformanceReport {
+        let stages: Vec<ConformanceStageResult> = ConformanceStage::ALL
+            .iter()
+            .map(|stage| {
+                self.stages.iter().find(|r| r.stage == *stage).cloned().unwrap_or(ConformanceStageResult {
+                    stage: *stage,
+                    outcome: StageOutcome::Skipped,
+                    duration_ms: 0,
+                    detail: "前置阶段未通过".to_string(),
+                })
+            })
+            .collect();
+        let passed = stages.iter().all(|s| s.outcome == StageOutcome::Passed);
+        ConformanceReport {
+            environment: self.ctp_config.environment,
+            md_front_addr: self.ctp_config.md_front_addr.clone(),
+            trader_front_addr: self.ctp_config.trader_front_addr.clone(),
+            instrument: self.config.instrument.clone(),
+            started_at,
+            finished_at: chrono::Utc::now(),
+            stages,
+            passed,
+        }
+    }
+}
+
+fn is_cancelled(order: &OrderStatus) -> bool {
+    matches!(order.status, OrderStatusType::Canceled | OrderStatusType::Cancelled)
+}
+
+/// 不易成交的买价：跌停价，缺失时取买一价
+fn resting_buy_price(tick: &MarketDataTick) -> Option<f64> {
+    let valid = |p: &f64| *p > 0.0 && *p < f64::MAX;
+    tick.price_limit
+        .as_ref()
+        .map(|limit| limit.lower_limit_price)
+        .filter(valid)
+        .or(Some(tick.bid_price1).filter(valid))
+}
+
+fn tiny_order(instrument_id: &str, price: f64) -> OrderInput {
+    OrderInput {
+        instrument_id: instrument_id.to_string(),
+        direction: "Buy".to_string(),
+        offset: "Open".to_string(),
+        price,
+        volume: 1,
+        order_type: "Limit".to_string(),
+        time_condition: "GFD".to_string(),
+        volume_condition: "Any".to_string(),
+        min_volume: 1,
+        contingent_condition: "Immediately".to_string(),
+        stop_price: 0.0,
+        force_close_reason: "NotForceClose".to_string(),
+        is_auto_suspend: false,
+        tags: Default::default(),
+    }
+}
+
+/// 收集回报中的非 ASCII 文本
+fn collect_texts(event: &CtpEvent, texts: &mut Vec<String>) {
+    let text = match event {
+        CtpEvent::OrderUpdate(order) => &order.status_msg,
+        CtpEvent::LoginFailed(message) | CtpEvent::Error(message) => message,
+        CtpEvent::QuerySettlementResult(content) => content,
+        _ => return,
+    };
+    if !text.is_ascii() {
+        texts.push(text.clone());
+    }
+}
+
+/// 中文回报没有替换字符且能无损往返 GB18030 即视为解码正确
+fn check_gb18030(texts: &[String]) -> (StageOutcome, String) {
+    if texts.is_empty() {
+        return (StageOutcome::Skipped, "未收到中文回报文本".to_string());
+    }
+    for text in texts {
+        let round_trip = utf8_to_gb18030(text).and_then(|bytes| gb18030_to_utf8(&bytes));
+        if text.contains('\u{FFFD}') || round_trip.ok().as_deref() != Some(text.as_str()) {
+            return (StageOutcome::Failed, format!("回报文本解码异常: {:?}", text));
+        }
+    }
+    let sample: String = texts[0].chars().take(20).collect();
+    (StageOutcome::Passed, format!("{} 条中文回报解码正常，如「{}」", texts.len(), sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_config_is_opt_in() {
+        assert!(ConformanceConfig::from_lookup(lookup(&[])).unwrap().is_none());
+        assert!(ConformanceConfig::from_lookup(lookup(&[(CONFORMANCE_ENV_FLAG, "0")])).unwrap().is_none());
+        assert!(matches!(
+            ConformanceConfig::from_lookup(lookup(&[(CONFORMANCE_ENV_FLAG, "1"), ("CTP_TTS_INVESTOR_ID", "000001")])),
+            Err(CtpError::ConfigError(_))
+        ));
+
+        let config = ConformanceConfig::from_lookup(lookup(&[
+            (CONFORMANCE_ENV_FLAG, "true"),
+            ("CTP_TTS_INVESTOR_ID", "000001"),
+            ("CTP_TTS_PASSWORD", "secret"),
+            ("CTP_TTS_INSTRUMENT", "rb2510"),
+            ("CTP_TD_DYNLIB", "/opt/ctp/thosttraderapi_se.so"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.report_path, PathBuf::from(DEFAULT_CONFORMANCE_REPORT));
+        let ctp = config.ctp_config();
+        assert_eq!(ctp.environment, Environment::Tts);
+        assert_eq!(ctp.td_dynlib_path, Some(PathBuf::from("/opt/ctp/thosttraderapi_se.so")));
+    }
+
+    #[test]
+    fn test_gb18030_check() {
+        assert_eq!(check_gb18030(&[]).0, StageOutcome::Skipped);
+        assert_eq!(check_gb18030(&["全部成交报单已提交".to_string()]).0, StageOutcome::Passed);
+        assert_eq!(check_gb18030(&["已撤单".to_string(), "\u{FFFD}\u{FFFD}".to_string()]).0, StageOutcome::Failed);
+    }
+
+    #[test]
+    fn test_report_fills_skipped_stages_and_saves() {
+        let config = ConformanceConfig {
+            investor_id: "000001".to_string(),
+            password: "secret".to_string(),
+            instrument: "rb2510".to_string(),
+            md_dynlib_path: None,
+            td_dynlib_path: None,
+            report_path: PathBuf::new(),
+            stage_timeout: Duration::from_secs(1),
+        };
+        let mut runner = ConformanceRunner::new(config);
+        runner.record(ConformanceStage::Connect, StageOutcome::Failed, Instant::now(), "连接超时".to_string());
+        let report = runner.into_report(chrono::Utc::now());
+
+        assert!(!report.passed);
+        assert_eq!(report.stages.len(), ConformanceStage::ALL.len());
+        assert!(report.stages[1..].iter().all(|s| s.outcome == StageOutcome::Skipped));
+        assert!(report.summary().contains("连接超时"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("conformance").join("tts.json");
+        report.save(&path).unwrap();
+        let saved: ConformanceReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.stages[0].stage, ConformanceStage::Connect);
+        assert_eq!(saved.environment, Environment::Tts);
+    }
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 保留的最近断线记录数
+const MAX_DISCONNECT_RECORDS: usize = 20;
+/// 未收到响应的请求最多跟踪数量
+const MAX_PENDING_REQUESTS: usize = 256;
+/// 断线后多长时间内视为连接不稳定
+const UNSTABLE_WINDOW: Duration = Duration::from_secs(300);
+/// 平均往返时延超过该值视为质量下降（毫秒）
+const SLOW_RTT_MS: f64 = 500.0;
+
+/// 解析 `OnFrontDisconnected` 的原因代码
+pub fn describe_disconnect_reason(reason: i32) -> &'static str {
+    match reason {
+        0x1001 => "网络读失败",
+        0x1002 => "网络写失败",
+        0x2001 => "接收心跳超时",
+        0x2002 => "发送心跳失败",
+        0x2003 => "收到错误报文",
+        _ => "未知原因",
+    }
+}
+
+/// 连接质量等级，供界面健康指示灯使用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum QualityLevel {
+    Good,
+    Degraded,
+    Poor,
+    Offline,
+}
+
+/// 断线记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisconnectRecord {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub reason_code: i32,
+    pub reason: String,
+}
+
+/// 请求往返时延统计（毫秒）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RttStats {
+    pub samples: u64,
+    pub last_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+}
+
+impl RttStats {
+    fn record(&mut self, rtt_ms: f64) {
+        if self.samples == 0 {
+            self.min_ms = rtt_ms;
+            self.max_ms = rtt_ms;
+        } else {
+            self.min_ms = self.min_ms.min(rtt_ms);
+            self.max_ms = self.max_ms.max(rtt_ms);
+        }
+        self.samples += 1;
+        self.last_ms = rtt_ms;
+        self.avg_ms += (rtt_ms - self.avg_ms) / self.samples as f64;
+    }
+}
+
+/// 单侧（行情/交易）连接质量
+///
+/// CTP 心跳由 API 内部维护，不暴露往返时间，这里以请求到首个响应的时延近似
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkQuality {
+    pub connected: bool,
+    /// 连接成功次数
+    pub connect_count: u32,
+    /// 断线后重新连上的次数
+    pub reconnect_count: u32,
+    pub disconnect_count: u32,
+    /// 最近的断线记录（按时间顺序）
+    pub recent_disconnects: VecDeque<DisconnectRecord>,
+    /// 各原因代码的断线次数
+    pub disconnect_reasons: HashMap<i32, u32>,
+    /// 心跳超时警告次数
+    pub heartbeat_warnings: u32,
+    /// 最近一次心跳警告距上次通讯的秒数
+    pub last_heartbeat_lapse_secs: Option<i32>,
+    pub rtt: RttStats,
+    /// 本次连接内最长的无回调间隔（毫秒）
+    pub longest_silence_ms: u64,
+    /// 当前无回调时长（毫秒），快照时计算
+    pub current_silence_ms: u64,
+    #[serde(skip)]
+    last_activity: Option<Instant>,
+    #[serde(skip)]
+    last_disconnect_at: Option<Instant>,
+    #[serde(skip)]
+    warnings_since_connect: u32,
+    #[serde(skip)]
+    pending_requests: HashMap<i32, Instant>,
+}
+
+impl LinkQuality {
+    /// 前置连接成功
+    pub fn record_connected(&mut self) {
+        if self.disconnect_count > 0 {
+            self.reconnect_count += 1;
+        }
+        self.connect_count += 1;
+        self.connected = true;
+        self.warnings_since_connect = 0;
+        self.longest_silence_ms = 0;
+        self.last_activity = Some(Instant::now());
+    }
+
+    /// 前置断开
+    pub fn record_disconnected(&mut self, reason_code: i32) {
+        self.connected = false;
+        self.disconnect_count += 1;
+        *self.disconnect_reasons.entry(reason_code).or_insert(0) += 1;
+        self.last_disconnect_at = Some(Instant::now());
+        self.pending_requests.clear();
+
+        if self.recent_disconnects.len() >= MAX_DISCONNECT_RECORDS {
+            self.recent_disconnects.pop_front();
+        }
+        self.recent_disconnects.push_back(DisconnectRecord {
+            timestamp: chrono::Utc::now(),
+            reason_code,
+            reason: describe_disconnect_reason(reason_code).to_string(),
+        });
+    }
+
+    /// 心跳超时警告
+    pub fn record_heartbeat_warning(&mut self, time_lapse_secs: i32) {
+        self.heartbeat_warnings += 1;
+        self.warnings_since_connect += 1;
+        self.last_heartbeat_lapse_secs = Some(time_lapse_secs);
+    }
+
+    /// 收到任意回调，更新静默间隔
+    pub fn record_activity(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_activity {
+            let gap = now.duration_since(last).as_millis() as u64;
+            self.longest_silence_ms = self.longest_silence_ms.max(gap);
+        }
+        self.last_activity = Some(now);
+    }
+
+    /// 记录请求发送时间
+    pub fn record_request(&mut self, request_id: i32) {
+        if self.pending_requests.len() >= MAX_PENDING_REQUESTS {
+            // 长时间无响应的请求不再计入
+            self.pending_requests.clear();
+        }
+        self.pending_requests.insert(request_id, Instant::now());
+    }
+
+    /// 收到请求响应，计算往返时延
+    pub fn record_response(&mut self, request_id: i32) {
+        self.record_activity();
+        if let Some(sent_at) = self.pending_requests.remove(&request_id) {
+            self.rtt.record(sent_at.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// 质量等级
+    pub fn level(&self) -> QualityLevel {
+        if !self.connected {
+            return QualityLevel::Offline;
+        }
+
+        let recently_dropped = self
+            .last_disconnect_at
+            .map_or(false, |at| at.elapsed() < UNSTABLE_WINDOW);
+        if recently_dropped || self.warnings_since_connect >= 3 {
+            QualityLevel::Poor
+        } else if self.warnings_since_connect > 0 || self.rtt.avg_ms > SLOW_RTT_MS {
+            QualityLevel::Degraded
+        } else {
+            QualityLevel::Good
+        }
+    }
+
+    /// 生成快照
+    fn snapshot(&self) -> Self {
+        let mut snapshot = self.clone();
+        snapshot.current_silence_ms = match (self.connected, self.last_activity) {
+            (true, Some(last)) => last.elapsed().as_millis() as u64,
+            _ => 0,
+        };
+        snapshot.pending_requests.clear();
+        snapshot
+    }
+}
+
+/// 行情/交易两侧的连接质量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionQuality {
+    pub md: LinkQuality,
+    pub td: LinkQuality,
+}
+
+/// 共享的连接质量统计
+pub type SharedConnectionQuality = Arc<Mutex<ConnectionQuality>>;
+
+impl ConnectionQuality {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn shared() -> SharedConnectionQuality {
+        Arc::new(Mutex::new(Self::new()))
+    }
+
+    /// 生成供前端展示的报告
+    pub fn report(&self) -> ConnectionQualityReport {
+        let md = self.md.snapshot();
+        let td = self.td.snapshot();
+        let md_level = md.level();
+        let td_level = td.level();
+
+        // 整体等级取已使用的一侧中较差者
+        let overall = match (md.connect_count > 0, td.connect_count > 0) {
+            (true, true) => md_level.max(td_level),
+            (true, false) => md_level,
+            (false, true) => td_level,
+            (false, false) => QualityLevel::Offline,
+        };
+
+        ConnectionQualityReport {
+            overall,
+            md_level,
+            td_level,
+            md,
+            td,
+            generated_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// 连接质量报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionQualityReport {
+    pub overall: QualityLevel,
+    pub md_level: QualityLevel,
+    pub td_level: QualityLevel,
+    pub md: LinkQuality,
+    pub td: LinkQuality,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_and_reason_counts() {
+        let mut link = LinkQuality::default();
+        assert_eq!(link.level(), QualityLevel::Offline);
+
+        link.record_connected();
+        assert_eq!(link.level(), QualityLevel::Good);
+
+        link.record_disconnected(0x2001);
+        link.record_connected();
+        link.record_disconnected(0x2001);
+        link.record_connected();
+
+        assert_eq!(link.connect_count, 3);
+        assert_eq!(link.reconnect_count, 2);
+        assert_eq!(link.disconnect_reasons[&0x2001], 2);
+        assert_eq!(link.recent_disconnects.back().unwrap().reason, "接收心跳超时");
+        // 刚断线重连，视为不稳定
+        assert_eq!(link.level(), QualityLevel::Poor);
+    }
+
+    #[test]
+    fn test_rtt_and_heartbeat_warning() {
+        let mut link = LinkQuality::default();
+        link.record_connected();
+        link.record_request(1);
+        link.record_response(1);
+        // 未登记的请求不计入
+        link.record_response(2);
+
+        assert_eq!(link.rtt.samples, 1);
+        assert!(link.rtt.min_ms <= link.rtt.max_ms);
+
+        link.record_heartbeat_warning(30);
+        assert_eq!(link.level(), QualityLevel::Degraded);
+        assert_eq!(link.last_heartbeat_lapse_secs, Some(30));
+    }
+
+    #[test]
+    fn test_report_overall_uses_active_sides() {
+        let mut quality = ConnectionQuality::new();
+        quality.md.record_connected();
+
+        let report = quality.report();
+        assert_eq!(report.md_level, QualityLevel::Good);
+        assert_eq!(report.td_level, QualityLevel::Offline);
+        assert_eq!(report.overall, QualityLevel::Good);
+    }
+}
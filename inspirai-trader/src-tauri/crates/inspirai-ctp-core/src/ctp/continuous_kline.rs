@@ -0,0 +1,312 @@
+use crate::ctp::{
+    CtpError,
+    tick_compaction::{ArchivedBar, StorageGranularity, TickCompactor},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 换月价差调整方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AdjustmentMethod {
+    /// 不调整，直接拼接
+    None,
+    /// 差值后复权：换月前的历史价格加上新旧合约价差
+    #[default]
+    BackAdjusted,
+    /// 比例后复权：换月前的历史价格乘以新旧合约价格比
+    RatioAdjusted,
+}
+
+/// 连续合约 K 线请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuousKlineRequest {
+    /// 按到期先后排列的合约，如 rb2410、rb2501、rb2505
+    pub contracts: Vec<String>,
+    /// 起始交易日（含）
+    pub start_date: NaiveDate,
+    /// 结束交易日（含）
+    pub end_date: NaiveDate,
+    pub granularity: StorageGranularity,
+    #[serde(default)]
+    pub adjustment: AdjustmentMethod,
+}
+
+/// 连续合约 K 线
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContinuousBar {
+    pub trading_day: NaiveDate,
+    pub time: String,
+    /// 该根 K 线的来源合约
+    pub instrument_id: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+    pub turnover: f64,
+    pub open_interest: i64,
+}
+
+/// 换月记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollEvent {
+    /// 新合约的第一个交易日
+    pub trading_day: NaiveDate,
+    pub from: String,
+    pub to: String,
+    /// 换月参考日旧合约收盘价
+    pub from_close: f64,
+    /// 换月参考日新合约收盘价，缺失时等于旧合约收盘价（不调整）
+    pub to_close: f64,
+}
+
+/// 连续合约序列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuousKline {
+    pub adjustment: AdjustmentMethod,
+    pub bars: Vec<ContinuousBar>,
+    pub rolls: Vec<RollEvent>,
+}
+
+/// 单个交易日各合约的 K 线
+#[derive(Debug, Clone, Default)]
+pub struct DayBars {
+    pub trading_day: NaiveDate,
+    pub bars: HashMap<String, Vec<ArchivedBar>>,
+}
+
+/// 连续合约 K 线构建
+///
+/// 按需从本地归档读取相继合约的 K 线，以持仓量确定主力并在主力切换时换月，
+/// 换月前的历史按所选方式复权，使指标计算不会在换月日断档
+pub struct ContinuousKlineBuilder<'a> {
+    store: &'a TickCompactor,
+}
+
+impl<'a> ContinuousKlineBuilder<'a> {
+    pub fn new(store: &'a TickCompactor) -> Self {
+        Self { store }
+    }
+
+    pub fn build(&self, request: &ContinuousKlineRequest) -> Result<ContinuousKline, CtpError> {
+        if request.contracts.is_empty() {
+            return Err(CtpError::InvalidParameter("至少需要指定一个合约".to_string()));
+        }
+        if request.start_date > request.end_date {
+            return Err(CtpError::InvalidParameter("起始日期不能晚于结束日期".to_string()));
+        }
+
+        let mut days = Vec::new();
+        for trading_day in request.start_date.iter_days().take_while(|d| *d <= request.end_date) {
+            let mut bars = HashMap::new();
+            for instrument_id in &request.contracts {
+                let loaded = self.store.read_bars(trading_day, instrument_id, request.granularity)?;
+                if !loaded.is_empty() {
+                    bars.insert(instrument_id.clone(), loaded);
+                }
+            }
+            if !bars.is_empty() {
+                days.push(DayBars { trading_day, bars });
+            }
+        }
+        Ok(merge_contracts(&days, &request.contracts, request.adjustment))
+    }
+}
+
+/// 拼接相继合约并复权
+///
+/// 每日收盘后比较持仓量，后续合约持仓量超过当前合约时于下一交易日换月；
+/// 当前合约无数据时直接切换到下一个有数据的合约。合约只向后切换
+pub fn merge_contracts(days: &[DayBars], contracts: &[String], adjustment: AdjustmentMethod) -> ContinuousKline {
+    let last_of = |day: &DayBars, id: &str| day.bars.get(id).and_then(|bars| bars.last()).cloned();
+
+    let mut current: Option<usize> = None;
+    let mut pending: Option<(usize, RollEvent)> = None;
+    let mut segments: Vec<Vec<ContinuousBar>> = Vec::new();
+    let mut rolls = Vec::new();
+    let mut previous_day: Option<&DayBars> = None;
+
+    for day in days {
+        // 执行前一日决定的换月，或当前合约已无数据时顺延到下一个合约
+        let active = current.filter(|i| day.bars.contains_key(&contracts[*i]));
+        let pending_roll = pending.take().filter(|(next, _)| day.bars.contains_key(&contracts[*next]));
+        let switch = match (pending_roll, active) {
+            (Some((next, roll)), _) => Some((next, Some(roll))),
+            (None, Some(_)) => None,
+            (None, None) => (current.map_or(0, |i| i + 1)..contracts.len())
+                .find(|i| day.bars.contains_key(&contracts[*i]))
+                .map(|next| (next, None)),
+        };
+        if let Some((next, roll)) = switch {
+            if let Some(from) = current {
+                let mut roll = roll.unwrap_or_else(|| {
+                    let reference = previous_day.and_then(|prev| last_of(prev, &contracts[from]));
+                    let from_close = reference.map_or(0.0, |bar| bar.close);
+                    let to_close = previous_day
+                        .and_then(|prev| last_of(prev, &contracts[next]))
+                        .map_or(from_close, |bar| bar.close);
+                    RollEvent {
+                        trading_day: day.trading_day,
+                        from: contracts[from].clone(),
+                        to: contracts[next].clone(),
+                        from_close,
+                        to_close,
+                    }
+                });
+                // 换月生效日为新合约的第一个交易日
+                roll.trading_day = day.trading_day;
+                tracing::debug!("连续合约换月: {} -> {} ({})", roll.from, roll.to, roll.trading_day);
+                rolls.push(roll);
+            }
+            current = Some(next);
+            segments.push(Vec::new());
+        }
+
+        let Some(index) = current else {
+            previous_day = Some(day);
+            continue;
+        };
+        let id = &contracts[index];
+        if let (Some(bars), Some(segment)) = (day.bars.get(id), segments.last_mut()) {
+            segment.extend(bars.iter().map(|bar| ContinuousBar {
+                trading_day: day.trading_day,
+                time: bar.time.clone(),
+                instrument_id: id.clone(),
+                open: bar.open,
+                high: bar.high,
+                low: bar.low,
+                close: bar.close,
+                volume: bar.volume,
+                turnover: bar.turnover,
+                open_interest: bar.open_interest,
+            }));
+        }
+
+        // 收盘后比较持仓量，决定下一交易日是否换月
+        if let Some(current_bar) = last_of(day, id) {
+            let dominant = (index + 1..contracts.len())
+                .filter_map(|i| last_of(day, &contracts[i]).map(|bar| (i, bar)))
+                .filter(|(_, bar)| bar.open_interest > current_bar.open_interest)
+                .max_by_key(|(_, bar)| bar.open_interest);
+            pending = dominant.map(|(next, bar)| {
+                (
+                    next,
+                    RollEvent {
+                        trading_day: day.trading_day,
+                        from: id.clone(),
+                        to: contracts[next].clone(),
+                        from_close: current_bar.close,
+                        to_close: bar.close,
+                    },
+                )
+            });
+        }
+        previous_day = Some(day);
+    }
+
+    let mut bars = Vec::new();
+    for (k, segment) in segments.into_iter().enumerate() {
+        // 第 k 段之后的换月累计调整量
+        let later = &rolls[k..];
+        let offset: f64 = later.iter().map(|r| r.to_close - r.from_close).sum();
+        let ratio: f64 = later
+            .iter()
+            .map(|r| if r.from_close > 0.0 { r.to_close / r.from_close } else { 1.0 })
+            .product();
+        bars.extend(segment.into_iter().map(|mut bar| {
+            let adjust = |price: f64| match adjustment {
+                AdjustmentMethod::None => price,
+                AdjustmentMethod::BackAdjusted => price + offset,
+                AdjustmentMethod::RatioAdjusted => price * ratio,
+            };
+            bar.open = adjust(bar.open);
+            bar.high = adjust(bar.high);
+            bar.low = adjust(bar.low);
+            bar.close = adjust(bar.close);
+            bar
+        }));
+    }
+
+    ContinuousKline {
+        adjustment,
+        bars,
+        rolls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(instrument_id: &str, close: f64, open_interest: i64) -> ArchivedBar {
+        ArchivedBar {
+            instrument_id: instrument_id.to_string(),
+            time: "00:00:00".to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100,
+            turnover: 0.0,
+            open_interest,
+            tick_count: 1,
+        }
+    }
+
+    fn day(n: u32, bars: &[(&str, f64, i64)]) -> DayBars {
+        DayBars {
+            trading_day: NaiveDate::from_ymd_opt(2024, 9, n).unwrap(),
+            bars: bars
+                .iter()
+                .map(|(id, close, oi)| (id.to_string(), vec![bar(id, *close, *oi)]))
+                .collect(),
+        }
+    }
+
+    fn contracts() -> Vec<String> {
+        vec!["rb2410".to_string(), "rb2501".to_string()]
+    }
+
+    #[test]
+    fn test_rolls_on_open_interest_and_back_adjusts() {
+        let days = vec![
+            day(2, &[("rb2410", 3500.0, 1000), ("rb2501", 3550.0, 500)]),
+            // 收盘后远月持仓超过近月，下一日换月，价差 3560 - 3510 = 50
+            day(3, &[("rb2410", 3510.0, 800), ("rb2501", 3560.0, 1200)]),
+            day(4, &[("rb2410", 3520.0, 600), ("rb2501", 3570.0, 1500)]),
+        ];
+
+        let series = merge_contracts(&days, &contracts(), AdjustmentMethod::BackAdjusted);
+        assert_eq!(series.rolls.len(), 1);
+        let roll = &series.rolls[0];
+        assert_eq!((roll.from.as_str(), roll.to.as_str()), ("rb2410", "rb2501"));
+        assert_eq!(roll.trading_day, NaiveDate::from_ymd_opt(2024, 9, 4).unwrap());
+
+        let closes: Vec<f64> = series.bars.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![3550.0, 3560.0, 3570.0]);
+        let sources: Vec<&str> = series.bars.iter().map(|b| b.instrument_id.as_str()).collect();
+        assert_eq!(sources, vec!["rb2410", "rb2410", "rb2501"]);
+
+        let raw = merge_contracts(&days, &contracts(), AdjustmentMethod::None);
+        let closes: Vec<f64> = raw.bars.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![3500.0, 3510.0, 3570.0]);
+    }
+
+    #[test]
+    fn test_ratio_adjust_and_fallback_when_contract_ends() {
+        let days = vec![
+            day(2, &[("rb2410", 100.0, 1000), ("rb2501", 110.0, 500)]),
+            // 近月到期无数据，直接切换，以前一日收盘计算比例 110 / 100
+            day(3, &[("rb2501", 120.0, 900)]),
+        ];
+        let series = merge_contracts(&days, &contracts(), AdjustmentMethod::RatioAdjusted);
+        assert_eq!(series.rolls.len(), 1);
+        assert_eq!(series.rolls[0].from_close, 100.0);
+        assert_eq!(series.rolls[0].to_close, 110.0);
+        assert!((series.bars[0].close - 110.0).abs() < 1e-9);
+        assert_eq!(series.bars[1].close, 120.0);
+
+        assert!(merge_contracts(&[], &contracts(), AdjustmentMethod::BackAdjusted).bars.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::ctp::models::OrderTags;
+use crate::logging::{current_correlation_id, enter_correlation, CorrelationGuard, CORRELATION_FIELD};
+
+/// 关联 ID 的订单标签名，与日志字段同名
+pub const CORRELATION_TAG: &str = CORRELATION_FIELD;
+/// 默认保留的绑定条数
+pub const DEFAULT_CORRELATION_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BindingKey {
+    Request(i32),
+    OrderRef(String),
+}
+
+#[derive(Debug, Default)]
+struct RegistryInner {
+    bindings: HashMap<BindingKey, String>,
+    /// 绑定顺序，超出容量时淘汰最早的
+    order: VecDeque<BindingKey>,
+}
+
+/// 请求 ID / 报单引用到关联 ID 的映射
+///
+/// 发请求时绑定当前作用域的关联 ID，SPI 回调按请求 ID 或报单引用取回，
+/// 使回调中的日志和发往前端的事件带上同一个关联 ID
+#[derive(Debug, Clone)]
+pub struct CorrelationRegistry {
+    inner: Arc<Mutex<RegistryInner>>,
+    capacity: usize,
+}
+
+impl Default for CorrelationRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_CORRELATION_CAPACITY)
+    }
+}
+
+impl CorrelationRegistry {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(RegistryInner::default())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn bind(&self, key: BindingKey, correlation_id: String) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.bindings.insert(key.clone(), correlation_id).is_none() {
+            inner.order.push_back(key);
+        }
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.bindings.remove(&oldest);
+            }
+        }
+    }
+
+    fn lookup(&self, key: &BindingKey) -> Option<String> {
+        self.inner.lock().unwrap().bindings.get(key).cloned()
+    }
+
+    /// 将请求 ID 绑定到当前关联 ID，不在关联作用域内时不绑定
+    pub fn bind_request(&self, request_id: i32) -> Option<String> {
+        let correlation_id = current_correlation_id()?;
+        self.bind(BindingKey::Request(request_id), correlation_id.clone());
+        Some(correlation_id)
+    }
+
+    /// 将报单引用绑定到当前关联 ID，并写入订单标签
+    pub fn bind_order_ref(&self, order_ref: &str, tags: &mut OrderTags) -> Option<String> {
+        let correlation_id = tags
+            .get(CORRELATION_TAG)
+            .cloned()
+            .or_else(current_correlation_id)?;
+        tags.insert(CORRELATION_TAG.to_string(), correlation_id.clone());
+        self.bind(BindingKey::OrderRef(order_ref.to_string()), correlation_id.clone());
+        Some(correlation_id)
+    }
+
+    pub fn for_request(&self, request_id: i32) -> Option<String> {
+        self.lookup(&BindingKey::Request(request_id))
+    }
+
+    pub fn for_order_ref(&self, order_ref: &str) -> Option<String> {
+        self.lookup(&BindingKey::OrderRef(order_ref.to_string()))
+    }
+
+    /// 在回调线程上进入请求对应的关联作用域
+    pub fn enter_request(&self, request_id: i32) -> Option<CorrelationGuard> {
+        self.for_request(request_id).map(enter_correlation)
+    }
+
+    /// 在回调线程上进入报单对应的关联作用域
+    pub fn enter_order_ref(&self, order_ref: &str) -> Option<CorrelationGuard> {
+        self.for_order_ref(order_ref).map(enter_correlation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::with_correlation;
+
+    #[tokio::test]
+    async fn test_binds_current_correlation() {
+        let registry = CorrelationRegistry::default();
+        assert_eq!(registry.bind_request(1), None);
+
+        let mut tags = OrderTags::new();
+        with_correlation(Some("ui-42".to_string()), async {
+            registry.bind_request(7);
+            registry.bind_order_ref("000001", &mut tags);
+        })
+        .await;
+
+        assert_eq!(registry.for_request(7).as_deref(), Some("ui-42"));
+        assert_eq!(registry.for_order_ref("000001").as_deref(), Some("ui-42"));
+        assert_eq!(tags.get(CORRELATION_TAG).map(String::as_str), Some("ui-42"));
+
+        // 回调线程上恢复作用域
+        let guard = registry.enter_order_ref("000001");
+        assert_eq!(current_correlation_id().as_deref(), Some("ui-42"));
+        drop(guard);
+        assert!(registry.enter_request(8).is_none());
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let registry = CorrelationRegistry::new(2);
+        let mut tags = OrderTags::from([(CORRELATION_TAG.to_string(), "strategy-a".to_string())]);
+        for order_ref in ["1", "2", "3"] {
+            registry.bind_order_ref(order_ref, &mut tags);
+        }
+        assert_eq!(registry.for_order_ref("1"), None);
+        assert_eq!(registry.for_order_ref("3").as_deref(), Some("strategy-a"));
+    }
+}
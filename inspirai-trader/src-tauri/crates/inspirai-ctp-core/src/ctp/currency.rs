@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ctp::{models::AccountInfo, CtpError};
+
+/// 本位币，CTP 资金账户未填币种时即为人民币
+pub const BASE_CURRENCY: &str = "CNY";
+/// 默认汇率配置文件
+pub const DEFAULT_FX_RATE_FILE: &str = "./config/fx_rates.toml";
+
+/// 汇率换算接口，返回 1 单位 `from` 折合多少 `to`，未知时为 None
+pub trait FxConverter: Send + Sync {
+    fn rate(&self, from: &str, to: &str) -> Option<f64>;
+}
+
+/// 固定汇率表
+///
+/// `rates` 为 1 单位外币折合多少本位币，交叉汇率经本位币换算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FxRateTable {
+    pub base_currency: String,
+    pub rates: HashMap<String, f64>,
+}
+
+impl Default for FxRateTable {
+    fn default() -> Self {
+        Self {
+            base_currency: BASE_CURRENCY.to_string(),
+            rates: HashMap::new(),
+        }
+    }
+}
+
+impl FxRateTable {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let table: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("汇率配置解析失败: {}", e)))?;
+        if let Some((currency, _)) = table.rates.iter().find(|(_, rate)| rate.is_nan() || **rate <= 0.0) {
+            return Err(CtpError::ConfigError(format!("{} 汇率必须为正数", currency)));
+        }
+        Ok(table)
+    }
+
+    pub fn with_rate(mut self, currency: &str, rate: f64) -> Self {
+        self.rates.insert(currency.to_string(), rate);
+        self
+    }
+
+    /// 1 单位币种折合多少本位币
+    fn to_base(&self, currency: &str) -> Option<f64> {
+        if currency == self.base_currency {
+            return Some(1.0);
+        }
+        self.rates.get(currency).copied()
+    }
+}
+
+impl FxConverter for FxRateTable {
+    fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        Some(self.to_base(from)? / self.to_base(to)?)
+    }
+}
+
+/// 单一币种的资金
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyBalance {
+    pub currency_id: String,
+    pub balance: f64,
+    pub available: f64,
+    pub curr_margin: f64,
+    pub frozen_margin: f64,
+    pub commission: f64,
+    pub close_profit: f64,
+    pub position_profit: f64,
+}
+
+impl From<&AccountInfo> for CurrencyBalance {
+    fn from(account: &AccountInfo) -> Self {
+        Self {
+            currency_id: account.currency_id.clone(),
+            balance: account.balance,
+            available: account.available,
+            curr_margin: account.curr_margin,
+            frozen_margin: account.frozen_margin,
+            commission: account.commission,
+            close_profit: account.close_profit,
+            position_profit: account.position_profit,
+        }
+    }
+}
+
+/// 折算到展示币种的总权益
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedEquity {
+    pub display_currency: String,
+    /// 任一币种缺少汇率时为 None，不把不同币种直接相加
+    pub balance: Option<f64>,
+    pub available: Option<f64>,
+    pub curr_margin: Option<f64>,
+    /// 各币种原币金额
+    pub currencies: Vec<CurrencyBalance>,
+    /// 缺少汇率的币种
+    pub missing_rates: Vec<String>,
+}
+
+/// 分币种资金
+///
+/// 部分期货公司对 INE 等美元计价品种单独报送美元资金账户，按币种分别保存，
+/// 汇总权益时必须经汇率换算
+#[derive(Clone, Default)]
+pub struct AccountBalances {
+    inner: Arc<Mutex<BTreeMap<String, CurrencyBalance>>>,
+}
+
+impl AccountBalances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个币种的资金查询结果
+    pub fn update(&self, account: &AccountInfo) {
+        let balance = CurrencyBalance::from(account);
+        self.inner.lock().unwrap().insert(balance.currency_id.clone(), balance);
+    }
+
+    pub fn get(&self, currency_id: &str) -> Option<CurrencyBalance> {
+        self.inner.lock().unwrap().get(currency_id).cloned()
+    }
+
+    pub fn balances(&self) -> Vec<CurrencyBalance> {
+        self.inner.lock().unwrap().values().cloned().collect()
+    }
+
+    /// 各币种折算到展示币种后汇总
+    pub fn aggregate(&self, display_currency: &str, fx: &dyn FxConverter) -> AggregatedEquity {
+        let currencies = self.balances();
+        let mut totals = Some((0.0, 0.0, 0.0));
+        let mut missing_rates = Vec::new();
+        for balance in &currencies {
+            match fx.rate(&balance.currency_id, display_currency) {
+                Some(rate) => {
+                    if let Some((total, available, margin)) = totals.as_mut() {
+                        *total += balance.balance * rate;
+                        *available += balance.available * rate;
+                        *margin += balance.curr_margin * rate;
+                    }
+                }
+                None => {
+                    missing_rates.push(balance.currency_id.clone());
+                    totals = None;
+                }
+            }
+        }
+        if !missing_rates.is_empty() {
+            tracing::warn!("缺少 {:?} 到 {} 的汇率，不汇总权益", missing_rates, display_currency);
+        }
+        AggregatedEquity {
+            display_currency: display_currency.to_string(),
+            balance: totals.map(|t| t.0),
+            available: totals.map(|t| t.1),
+            curr_margin: totals.map(|t| t.2),
+            currencies,
+            missing_rates,
+        }
+    }
+}
+
+/// CTP 资金账户的币种代码，为空时按人民币
+pub fn normalize_currency(currency_id: &str) -> String {
+    match currency_id.trim() {
+        "" => BASE_CURRENCY.to_string(),
+        code => code.to_ascii_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(currency_id: &str, balance: f64, margin: f64) -> AccountInfo {
+        AccountInfo {
+            account_id: "test".to_string(),
+            currency_id: currency_id.to_string(),
+            available: balance - margin,
+            balance,
+            margin,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            curr_margin: margin,
+            commission: 0.0,
+            close_profit: 0.0,
+            position_profit: 0.0,
+            risk_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_fx_rate_table_cross_rates() {
+        let table = FxRateTable::default().with_rate("USD", 7.2).with_rate("HKD", 0.9);
+        assert_eq!(table.rate("USD", "CNY"), Some(7.2));
+        assert_eq!(table.rate("CNY", "CNY"), Some(1.0));
+        assert!((table.rate("USD", "HKD").unwrap() - 8.0).abs() < 1e-9);
+        assert_eq!(table.rate("EUR", "CNY"), None);
+        assert_eq!(normalize_currency(" "), "CNY");
+        assert_eq!(normalize_currency("usd"), "USD");
+    }
+
+    #[test]
+    fn test_aggregate_refuses_mixed_currencies_without_rate() {
+        let balances = AccountBalances::new();
+        balances.update(&account("CNY", 1_000_000.0, 200_000.0));
+        balances.update(&account("USD", 10_000.0, 2_000.0));
+
+        let equity = balances.aggregate("CNY", &FxRateTable::default());
+        assert_eq!(equity.balance, None);
+        assert_eq!(equity.missing_rates, vec!["USD".to_string()]);
+        assert_eq!(equity.currencies.len(), 2);
+
+        let equity = balances.aggregate("CNY", &FxRateTable::default().with_rate("USD", 7.0));
+        assert_eq!(equity.balance, Some(1_070_000.0));
+        assert_eq!(equity.curr_margin, Some(214_000.0));
+        assert!(equity.missing_rates.is_empty());
+    }
+}
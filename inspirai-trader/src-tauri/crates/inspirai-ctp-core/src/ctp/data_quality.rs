@@ -0,0 +1,335 @@
+use crate::ctp::{
+    models::MarketDataTick,
+    trading_session::{SessionConfig, TradingSessions},
+    CtpError,
+};
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 行情质量检查配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataQualityConfig {
+    /// 交易时段内超过该秒数没有 tick 视为行情停滞
+    pub stale_secs: u64,
+    /// 相邻 tick 最新价变动超过该比例视为跳价，0 表示不检查
+    pub max_jump_ratio: f64,
+    /// 异常消失后需持续正常的秒数才判定恢复
+    pub recover_secs: u64,
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            stale_secs: 30,
+            max_jump_ratio: 0.05,
+            recover_secs: 60,
+        }
+    }
+}
+
+impl DataQualityConfig {
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.stale_secs == 0 {
+            return Err(CtpError::ConfigError("行情停滞阈值必须大于 0".to_string()));
+        }
+        if !(0.0..1.0).contains(&self.max_jump_ratio) {
+            return Err(CtpError::ConfigError("跳价比例应在 0 到 1 之间".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 行情异常类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataQualityIssue {
+    /// 交易时段内长时间没有 tick
+    Stale,
+    /// 最新价非正数或非有限值
+    InvalidPrice,
+    /// 买一价高于卖一价
+    CrossedBook,
+    /// 交易所时间倒退
+    TimeRegression,
+    /// 相邻 tick 价格变动过大
+    PriceJump,
+}
+
+/// 合约行情质量状态变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DataQualityTransition {
+    Degraded {
+        instrument_id: String,
+        issue: DataQualityIssue,
+        at: NaiveDateTime,
+    },
+    Recovered {
+        instrument_id: String,
+        at: NaiveDateTime,
+    },
+}
+
+impl DataQualityTransition {
+    pub fn instrument_id(&self) -> &str {
+        match self {
+            Self::Degraded { instrument_id, .. } | Self::Recovered { instrument_id, .. } => instrument_id,
+        }
+    }
+}
+
+#[derive(Default)]
+struct InstrumentQuality {
+    /// 最近一笔 tick 的本地接收时间
+    last_seen: Option<NaiveDateTime>,
+    last_price: Option<f64>,
+    last_exchange_time: Option<(NaiveTime, i32)>,
+    /// 当前异常，为空表示正常
+    issue: Option<DataQualityIssue>,
+    /// 异常后首次恢复正常的时间
+    healthy_since: Option<NaiveDateTime>,
+}
+
+/// 按合约检查行情质量
+///
+/// tick 与定时器由调用方送入；出现异常时立即产出 `Degraded`，
+/// 异常消失并持续 `recover_secs` 后产出 `Recovered`。停滞只在交易时段内检查，
+/// 开盘时从开盘时刻起计
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    session_config: SessionConfig,
+    sessions: HashMap<String, TradingSessions>,
+    instruments: HashMap<String, InstrumentQuality>,
+}
+
+impl DataQualityMonitor {
+    pub fn new(config: DataQualityConfig, session_config: SessionConfig) -> Self {
+        Self {
+            config,
+            session_config,
+            sessions: HashMap::new(),
+            instruments: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &DataQualityConfig {
+        &self.config
+    }
+
+    /// 开始监控合约，此前未收到 tick 也按停滞检查
+    pub fn watch(&mut self, instrument_id: &str) {
+        self.instruments.entry(instrument_id.to_string()).or_default();
+    }
+
+    pub fn unwatch(&mut self, instrument_id: &str) {
+        self.instruments.remove(instrument_id);
+        self.sessions.remove(instrument_id);
+    }
+
+    /// 当前异常的合约
+    pub fn degraded(&self) -> Vec<(String, DataQualityIssue)> {
+        let mut degraded: Vec<_> = self
+            .instruments
+            .iter()
+            .filter_map(|(id, q)| q.issue.map(|issue| (id.clone(), issue)))
+            .collect();
+        degraded.sort_by(|a, b| a.0.cmp(&b.0));
+        degraded
+    }
+
+    pub fn on_tick(&mut self, tick: &MarketDataTick, now: NaiveDateTime) -> Option<DataQualityTransition> {
+        let max_jump_ratio = self.config.max_jump_ratio;
+        let recover = self.recover_period();
+        let quality = self.instruments.entry(tick.instrument_id.clone()).or_default();
+        quality.last_seen = Some(now);
+
+        let exchange_time = NaiveTime::parse_from_str(&tick.update_time, "%H:%M:%S")
+            .ok()
+            .map(|t| (t, tick.update_millisec));
+        // 跨午夜时交易所时间回到 00:00，不视为倒退
+        let regressed = match (quality.last_exchange_time, exchange_time) {
+            (Some(last), Some(current)) => current < last && last.0 - current.0 < Duration::hours(12),
+            _ => false,
+        };
+        let issue = if !tick.last_price.is_finite() || tick.last_price <= 0.0 {
+            Some(DataQualityIssue::InvalidPrice)
+        } else if tick.bid_price1 > 0.0 && tick.ask_price1 > 0.0 && tick.bid_price1 > tick.ask_price1 {
+            Some(DataQualityIssue::CrossedBook)
+        } else if regressed {
+            Some(DataQualityIssue::TimeRegression)
+        } else if max_jump_ratio > 0.0
+            && quality
+                .last_price
+                .is_some_and(|last| ((tick.last_price - last) / last).abs() > max_jump_ratio)
+        {
+            Some(DataQualityIssue::PriceJump)
+        } else {
+            None
+        };
+
+        if !regressed {
+            if let Some(current) = exchange_time {
+                quality.last_exchange_time = Some(current);
+            }
+        }
+        if tick.last_price.is_finite() && tick.last_price > 0.0 {
+            quality.last_price = Some(tick.last_price);
+        }
+
+        match issue {
+            Some(issue) => Self::degrade(&tick.instrument_id, quality, issue, now),
+            None => {
+                if quality.issue.is_some() && quality.healthy_since.is_none() {
+                    quality.healthy_since = Some(now);
+                }
+                Self::check_recovered(&tick.instrument_id, quality, recover, now)
+            }
+        }
+    }
+
+    /// 检查行情停滞与恢复，应定期调用
+    pub fn on_timer(&mut self, now: NaiveDateTime) -> Vec<DataQualityTransition> {
+        let stale = Duration::seconds(self.config.stale_secs as i64);
+        let recover = self.recover_period();
+        let mut transitions = Vec::new();
+        let mut ids: Vec<String> = self.instruments.keys().cloned().collect();
+        ids.sort();
+        for id in ids {
+            let config = &self.session_config;
+            let sessions = self
+                .sessions
+                .entry(id.clone())
+                .or_insert_with(|| config.sessions_for(&id));
+            let session = sessions.locate(now);
+            let Some(quality) = self.instruments.get_mut(&id) else {
+                continue;
+            };
+            if let Some((open, _)) = session {
+                let since = quality.last_seen.map_or(open, |seen| seen.max(open));
+                if now - since > stale {
+                    transitions.extend(Self::degrade(&id, quality, DataQualityIssue::Stale, now));
+                    continue;
+                }
+            }
+            // 休市期间停滞不再成立，按停滞开始恢复计时
+            if quality.issue == Some(DataQualityIssue::Stale) && quality.healthy_since.is_none() {
+                quality.healthy_since = Some(now);
+            }
+            transitions.extend(Self::check_recovered(&id, quality, recover, now));
+        }
+        transitions
+    }
+
+    fn recover_period(&self) -> Duration {
+        Duration::seconds(self.config.recover_secs as i64)
+    }
+
+    fn degrade(
+        instrument_id: &str,
+        quality: &mut InstrumentQuality,
+        issue: DataQualityIssue,
+        now: NaiveDateTime,
+    ) -> Option<DataQualityTransition> {
+        quality.healthy_since = None;
+        let first = quality.issue.is_none();
+        quality.issue = Some(issue);
+        first.then(|| DataQualityTransition::Degraded {
+            instrument_id: instrument_id.to_string(),
+            issue,
+            at: now,
+        })
+    }
+
+    fn check_recovered(
+        instrument_id: &str,
+        quality: &mut InstrumentQuality,
+        recover: Duration,
+        now: NaiveDateTime,
+    ) -> Option<DataQualityTransition> {
+        let since = quality.healthy_since?;
+        if quality.issue.is_none() || now - since < recover {
+            return None;
+        }
+        quality.issue = None;
+        quality.healthy_since = None;
+        Some(DataQualityTransition::Recovered {
+            instrument_id: instrument_id.to_string(),
+            at: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 3).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    fn tick(time: &str, price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2505".to_string(),
+            last_price: price,
+            volume: 100,
+            turnover: 0.0,
+            open_interest: 1000,
+            bid_price1: price - 1.0,
+            bid_volume1: 1,
+            ask_price1: price + 1.0,
+            ask_volume1: 1,
+            update_time: time.to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_bad_ticks_degrade_and_recover_after_stable_period() {
+        let mut monitor = DataQualityMonitor::new(DataQualityConfig::default(), SessionConfig::default());
+        assert!(monitor.on_tick(&tick("09:30:00", 3500.0), at(9, 30, 0)).is_none());
+
+        let mut crossed = tick("09:30:01", 3500.0);
+        crossed.bid_price1 = 3502.0;
+        let transition = monitor.on_tick(&crossed, at(9, 30, 1)).unwrap();
+        assert!(matches!(transition, DataQualityTransition::Degraded { issue: DataQualityIssue::CrossedBook, .. }));
+        // 持续异常不重复产出
+        assert!(monitor.on_tick(&tick("09:30:02", 3000.0), at(9, 30, 2)).is_none());
+
+        assert!(monitor.on_tick(&tick("09:30:03", 3001.0), at(9, 30, 3)).is_none());
+        // 恢复期内再次异常，重新计时
+        assert!(monitor.on_tick(&tick("09:30:02", 3001.0), at(9, 30, 30)).is_none());
+        assert!(monitor.on_tick(&tick("09:30:40", 3002.0), at(9, 30, 40)).is_none());
+        assert!(monitor.on_tick(&tick("09:31:00", 3002.0), at(9, 31, 0)).is_none());
+        assert_eq!(monitor.degraded().len(), 1);
+        let transition = monitor.on_tick(&tick("09:31:40", 3003.0), at(9, 31, 40)).unwrap();
+        assert_eq!(transition, DataQualityTransition::Recovered { instrument_id: "rb2505".to_string(), at: at(9, 31, 40) });
+        assert!(monitor.degraded().is_empty());
+    }
+
+    #[test]
+    fn test_staleness_only_within_sessions() {
+        let mut monitor = DataQualityMonitor::new(DataQualityConfig::default(), SessionConfig::default());
+        monitor.watch("rb2505");
+        // 午休不算停滞，开盘后从开盘时刻计时
+        assert!(monitor.on_timer(at(12, 0, 0)).is_empty());
+        assert!(monitor.on_timer(at(13, 30, 20)).is_empty());
+        let transitions = monitor.on_timer(at(13, 30, 31));
+        assert!(matches!(transitions[..], [DataQualityTransition::Degraded { issue: DataQualityIssue::Stale, .. }]));
+
+        monitor.on_tick(&tick("13:31:00", 3500.0), at(13, 31, 0));
+        assert!(monitor.on_timer(at(13, 31, 20)).is_empty());
+        monitor.on_tick(&tick("13:31:50", 3501.0), at(13, 31, 50));
+        assert!(matches!(monitor.on_timer(at(13, 32, 0))[..], [DataQualityTransition::Recovered { .. }]));
+        assert!(DataQualityConfig { stale_secs: 0, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::ctp::CtpError;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 死人开关触发的报单来源标签
+pub const DEAD_MAN_SOURCE: &str = "dead_man";
+
+/// 死人开关配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManConfig {
+    /// 超过该时长未收到心跳即触发
+    pub timeout: Duration,
+    /// 触发时除撤销挂单外是否以对手价平掉全部持仓
+    pub flatten: bool,
+}
+
+impl Default for DeadManConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            flatten: false,
+        }
+    }
+}
+
+/// 死人开关状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManStatus {
+    pub armed: bool,
+    pub config: DeadManConfig,
+    /// 最近一次心跳的来源（主窗口、远程控制端等）
+    pub last_source: Option<String>,
+    /// 距最近一次心跳的毫秒数，未布防时为空
+    pub since_heartbeat_ms: Option<u64>,
+    /// 最近一次触发时间
+    pub triggered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 一次触发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManTrigger {
+    pub triggered_at: chrono::DateTime<chrono::Utc>,
+    pub silent_ms: u64,
+    pub last_source: Option<String>,
+    pub flatten: bool,
+}
+
+/// 触发后的处置结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManReport {
+    pub trigger: DeadManTrigger,
+    /// 已撤销的报单引用
+    pub cancelled: Vec<String>,
+    /// 平仓单的报单引用
+    pub flatten_orders: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+struct SwitchInner {
+    config: DeadManConfig,
+    armed: bool,
+    last_heartbeat: Instant,
+    last_source: Option<String>,
+    triggered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 死人开关
+///
+/// 策略运行期间由前端（或远程控制端）定期发送心跳，超时未收到心跳时
+/// 自动撤销全部挂单并可选平仓，防止界面崩溃后算法无人值守。
+/// 触发后自动解除布防，需重新布防
+#[derive(Clone)]
+pub struct DeadManSwitch {
+    inner: Arc<Mutex<SwitchInner>>,
+}
+
+impl DeadManSwitch {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(SwitchInner {
+                config: DeadManConfig::default(),
+                armed: false,
+                last_heartbeat: Instant::now(),
+                last_source: None,
+                triggered_at: None,
+            })),
+        }
+    }
+
+    /// 布防，布防即视为收到一次心跳
+    pub fn arm(&self, config: DeadManConfig, source: Option<String>) -> Result<DeadManStatus, CtpError> {
+        if config.timeout.is_zero() {
+            return Err(CtpError::InvalidParameter("心跳超时必须大于0".to_string()));
+        }
+        let mut inner = self.inner.lock().unwrap();
+        tracing::info!(
+            "死人开关布防: 超时 {} 秒, 平仓={}",
+            config.timeout.as_secs_f64(),
+            config.flatten
+        );
+        inner.config = config;
+        inner.armed = true;
+        inner.last_heartbeat = Instant::now();
+        inner.last_source = source;
+        Ok(status_of(&inner))
+    }
+
+    pub fn disarm(&self) -> DeadManStatus {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.armed {
+            tracing::info!("死人开关解除布防");
+        }
+        inner.armed = false;
+        status_of(&inner)
+    }
+
+    /// 记录心跳，未布防时忽略
+    pub fn heartbeat(&self, source: Option<String>) -> DeadManStatus {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.armed {
+            inner.last_heartbeat = Instant::now();
+            if source.is_some() {
+                inner.last_source = source;
+            }
+        }
+        status_of(&inner)
+    }
+
+    pub fn status(&self) -> DeadManStatus {
+        status_of(&self.inner.lock().unwrap())
+    }
+
+    /// 检查心跳是否超时，超时时解除布防并返回触发信息
+    pub fn check(&self) -> Option<DeadManTrigger> {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> Option<DeadManTrigger> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.armed {
+            return None;
+        }
+        let silent = now.saturating_duration_since(inner.last_heartbeat);
+        if silent < inner.config.timeout {
+            return None;
+        }
+
+        let triggered_at = chrono::Utc::now();
+        inner.armed = false;
+        inner.triggered_at = Some(triggered_at);
+        tracing::warn!("死人开关触发: {} 毫秒未收到心跳", silent.as_millis());
+        Some(DeadManTrigger {
+            triggered_at,
+            silent_ms: silent.as_millis() as u64,
+            last_source: inner.last_source.clone(),
+            flatten: inner.config.flatten,
+        })
+    }
+}
+
+impl Default for DeadManSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn status_of(inner: &SwitchInner) -> DeadManStatus {
+    DeadManStatus {
+        armed: inner.armed,
+        config: inner.config.clone(),
+        last_source: inner.last_source.clone(),
+        since_heartbeat_ms: inner.armed.then(|| inner.last_heartbeat.elapsed().as_millis() as u64),
+        triggered_at: inner.triggered_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(secs: u64, flatten: bool) -> DeadManConfig {
+        DeadManConfig {
+            timeout: Duration::from_secs(secs),
+            flatten,
+        }
+    }
+
+    #[test]
+    fn test_triggers_once_after_timeout() {
+        let switch = DeadManSwitch::new();
+        assert!(switch.check_at(Instant::now() + Duration::from_secs(3600)).is_none());
+
+        switch.arm(config(10, true), Some("main".to_string())).unwrap();
+        assert!(switch.check_at(Instant::now() + Duration::from_secs(5)).is_none());
+
+        let trigger = switch.check_at(Instant::now() + Duration::from_secs(11)).unwrap();
+        assert!(trigger.flatten);
+        assert_eq!(trigger.last_source.as_deref(), Some("main"));
+        assert!(trigger.silent_ms >= 10_000);
+
+        // 触发后解除布防，不会重复触发
+        let status = switch.status();
+        assert!(!status.armed);
+        assert!(status.triggered_at.is_some());
+        assert!(switch.check_at(Instant::now() + Duration::from_secs(20)).is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_and_disarm_prevent_trigger() {
+        let switch = DeadManSwitch::new();
+        assert!(switch.arm(config(0, false), None).is_err());
+
+        switch.arm(config(10, false), None).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let before = switch.status().since_heartbeat_ms.unwrap();
+        let status = switch.heartbeat(Some("remote".to_string()));
+        assert!(status.since_heartbeat_ms.unwrap() < before);
+        assert_eq!(status.last_source.as_deref(), Some("remote"));
+
+        switch.disarm();
+        assert!(switch.check_at(Instant::now() + Duration::from_secs(60)).is_none());
+        assert!(switch.heartbeat(None).since_heartbeat_ms.is_none());
+    }
+}
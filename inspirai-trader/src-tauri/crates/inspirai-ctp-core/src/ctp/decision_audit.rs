@@ -0,0 +1,303 @@
+use crate::ctp::{tick_compaction::ArchivedBar, MarketDataTick, OrderRequest, OrderTags, StrategyGuard};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// 订单标签中保存决策记录的键，值为紧凑 JSON
+pub const DECISION_TAG: &str = "decision";
+
+/// 单条决策记录最多保留的指标数
+const MAX_INDICATORS: usize = 32;
+
+/// 决策时的行情快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTick {
+    pub time: String,
+    pub last: f64,
+    pub bid: f64,
+    pub ask: f64,
+    pub bid_volume: i32,
+    pub ask_volume: i32,
+    pub volume: i64,
+    pub open_interest: i64,
+}
+
+impl From<&MarketDataTick> for DecisionTick {
+    fn from(tick: &MarketDataTick) -> Self {
+        Self {
+            time: format!("{}.{:03}", tick.update_time, tick.update_millisec),
+            last: tick.last_price,
+            bid: tick.bid_price1,
+            ask: tick.ask_price1,
+            bid_volume: tick.bid_volume1,
+            ask_volume: tick.ask_volume1,
+            volume: tick.volume,
+            open_interest: tick.open_interest,
+        }
+    }
+}
+
+/// 决策时最近一根 K 线
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionBar {
+    pub time: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+impl From<&ArchivedBar> for DecisionBar {
+    fn from(bar: &ArchivedBar) -> Self {
+        Self {
+            time: bar.time.clone(),
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+        }
+    }
+}
+
+/// 决策时策略在该合约上的持仓
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DecisionPosition {
+    /// 净持仓，多为正、空为负
+    pub net: i32,
+    pub avg_price: f64,
+}
+
+/// 策略报单时的决策输入
+///
+/// 随订单标签保存，成交和回合交易沿用同一份标签，复盘时可还原“为什么在这里交易”
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub strategy: String,
+    pub instrument_id: String,
+    pub captured_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick: Option<DecisionTick>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bar: Option<DecisionBar>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub indicators: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<DecisionPosition>,
+    /// 策略给出的信号说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl DecisionRecord {
+    pub fn new(strategy: &str, instrument_id: &str) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            instrument_id: instrument_id.to_string(),
+            captured_at: chrono::Utc::now(),
+            tick: None,
+            bar: None,
+            indicators: BTreeMap::new(),
+            position: None,
+            reason: None,
+        }
+    }
+
+    pub fn with_tick(mut self, tick: &MarketDataTick) -> Self {
+        self.tick = Some(tick.into());
+        self
+    }
+
+    pub fn with_bar(mut self, bar: &ArchivedBar) -> Self {
+        self.bar = Some(bar.into());
+        self
+    }
+
+    /// 记录指标值，超过上限或非有限值的忽略
+    pub fn with_indicator(mut self, name: &str, value: f64) -> Self {
+        if value.is_finite() && (self.indicators.len() < MAX_INDICATORS || self.indicators.contains_key(name)) {
+            self.indicators.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    pub fn with_position(mut self, net: i32, avg_price: f64) -> Self {
+        self.position = Some(DecisionPosition { net, avg_price });
+        self
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// 写入订单标签
+    pub fn attach(&self, tags: &mut OrderTags) {
+        match serde_json::to_string(self) {
+            Ok(json) => {
+                tags.insert(DECISION_TAG.to_string(), json);
+            }
+            Err(e) => tracing::warn!("序列化决策记录失败: {}", e),
+        }
+    }
+
+    /// 从订单或成交标签中读取决策记录
+    pub fn from_tags(tags: &OrderTags) -> Option<Self> {
+        let json = tags.get(DECISION_TAG)?;
+        match serde_json::from_str(json) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::warn!("解析决策记录失败: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// 决策记录采集
+///
+/// 缓存各合约最新行情；策略报单时补全其未提供的行情和持仓快照后写入订单标签。
+/// 指标和 K 线只能由策略自己提供
+#[derive(Debug, Clone, Default)]
+pub struct DecisionRecorder {
+    ticks: Arc<Mutex<HashMap<String, DecisionTick>>>,
+}
+
+impl DecisionRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 更新合约最新行情
+    pub fn on_tick(&self, tick: &MarketDataTick) {
+        self.ticks.lock().unwrap().insert(tick.instrument_id.clone(), tick.into());
+    }
+
+    /// 为带策略标签的订单生成或补全决策记录，无策略标签的订单不处理
+    pub fn capture(&self, order: &mut OrderRequest, guard: &StrategyGuard) {
+        let Some(strategy) = StrategyGuard::strategy_of(&order.tags).map(str::to_string) else {
+            return;
+        };
+        let mut record = DecisionRecord::from_tags(&order.tags)
+            .unwrap_or_else(|| DecisionRecord::new(&strategy, &order.instrument_id));
+        if record.tick.is_none() {
+            record.tick = self.ticks.lock().unwrap().get(&order.instrument_id).cloned();
+        }
+        if record.position.is_none() {
+            let (net, avg_price) = guard.position(&strategy, &order.instrument_id).unwrap_or((0, 0.0));
+            record.position = Some(DecisionPosition { net, avg_price });
+        }
+        record.attach(&mut order.tags);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{
+        OffsetFlag, OrderContingentCondition, OrderDirection, OrderForceCloseReason, OrderPriceType,
+        OrderTimeCondition, OrderType, OrderVolumeCondition, StrategyBudget, TradeRecord, STRATEGY_TAG,
+    };
+
+    fn tick(price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: price,
+            volume: 100,
+            turnover: price * 100.0,
+            open_interest: 2000,
+            bid_price1: price - 1.0,
+            bid_volume1: 5,
+            ask_price1: price + 1.0,
+            ask_volume1: 3,
+            update_time: "09:30:01".to_string(),
+            update_millisec: 500,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: price,
+            highest_price: price,
+            lowest_price: price,
+            pre_close_price: price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    fn order() -> OrderRequest {
+        let mut tags = OrderTags::new();
+        tags.insert(STRATEGY_TAG.to_string(), "ma_cross".to_string());
+        OrderRequest {
+            instrument_id: "rb2501".to_string(),
+            order_ref: String::new(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume: 1,
+            order_type: OrderType::Limit,
+            price_type: OrderPriceType::Limit,
+            time_condition: OrderTimeCondition::GFD,
+            volume_condition: OrderVolumeCondition::Any,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags,
+        }
+    }
+
+    #[test]
+    fn test_strategy_record_round_trips_through_tags() {
+        let record = DecisionRecord::new("ma_cross", "rb2501")
+            .with_tick(&tick(3500.0))
+            .with_indicator("ma5", 3498.2)
+            .with_indicator("nan", f64::NAN)
+            .with_position(0, 0.0)
+            .with_reason("金叉");
+        let mut order = order();
+        record.attach(&mut order.tags);
+
+        let recorder = DecisionRecorder::new();
+        recorder.on_tick(&tick(3600.0));
+        recorder.capture(&mut order, &StrategyGuard::new());
+        // 策略提供的快照不被覆盖
+        let restored = DecisionRecord::from_tags(&order.tags).unwrap();
+        assert_eq!(restored, record);
+        assert_eq!(restored.tick.unwrap().time, "09:30:01.500");
+        assert!(!order.tags[DECISION_TAG].contains("bar"));
+    }
+
+    #[test]
+    fn test_recorder_fills_tick_and_position() {
+        let guard = StrategyGuard::new();
+        guard.register("ma_cross", StrategyBudget::default());
+        guard.on_trade(&TradeRecord {
+            trade_id: "T1".to_string(),
+            order_id: "1".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3480.0,
+            volume: 2,
+            trade_time: "09:29:00".to_string(),
+            exchange_id: String::new(),
+            tags: order().tags,
+        });
+
+        let recorder = DecisionRecorder::new();
+        recorder.on_tick(&tick(3500.0));
+        let mut tagged = order();
+        recorder.capture(&mut tagged, &guard);
+        let record = DecisionRecord::from_tags(&tagged.tags).unwrap();
+        assert_eq!(record.tick.unwrap().last, 3500.0);
+        assert_eq!(record.position, Some(DecisionPosition { net: 2, avg_price: 3480.0 }));
+
+        let mut untagged = order();
+        untagged.tags.clear();
+        recorder.capture(&mut untagged, &guard);
+        assert!(untagged.tags.is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::ctp::error_explainer::ErrorExplanation;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// 诊断事件严重级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DiagnosticSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+/// 诊断事件来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiagnosticSource {
+    /// 行情端
+    Md,
+    /// 交易端
+    Td,
+    /// 系统内部
+    System,
+}
+
+/// 诊断事件
+///
+/// 与 `CtpEvent` 数据事件分离，供问题面板和策略订阅
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticEvent {
+    pub id: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub severity: DiagnosticSeverity,
+    pub source: DiagnosticSource,
+    /// CTP 错误码
+    pub code: Option<i32>,
+    pub message: String,
+    /// 关联标识（请求ID、报单引用等）
+    pub correlation_id: Option<String>,
+    /// 拒单说明和处理建议
+    #[serde(default)]
+    pub explanation: Option<ErrorExplanation>,
+}
+
+impl DiagnosticEvent {
+    pub fn new(severity: DiagnosticSeverity, source: DiagnosticSource, message: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now(),
+            severity,
+            source,
+            code: None,
+            message: message.into(),
+            correlation_id: None,
+            explanation: None,
+        }
+    }
+
+    /// 设置 CTP 错误码
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// 设置关联标识
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// 附加拒单说明
+    pub fn with_explanation(mut self, explanation: ErrorExplanation) -> Self {
+        self.explanation = Some(explanation);
+        self
+    }
+
+    /// 以 CTP 请求ID 作为关联标识
+    pub fn with_request_id(self, request_id: i32) -> Self {
+        self.with_correlation_id(format!("req-{}", request_id))
+    }
+}
+
+/// 诊断事件通道
+///
+/// 基于 broadcast 支持多个订阅者，并保留最近的事件供问题面板查询
+#[derive(Debug, Clone)]
+pub struct DiagnosticHub {
+    sender: broadcast::Sender<DiagnosticEvent>,
+    recent: Arc<Mutex<VecDeque<DiagnosticEvent>>>,
+    capacity: usize,
+}
+
+impl DiagnosticHub {
+    /// 默认保留的最近事件数量
+    pub const DEFAULT_CAPACITY: usize = 500;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// 发布诊断事件
+    pub fn publish(&self, event: DiagnosticEvent) {
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() >= self.capacity {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+
+        // 没有订阅者时发送失败属正常情况
+        let _ = self.sender.send(event);
+    }
+
+    /// 快捷发布
+    pub fn report(&self, severity: DiagnosticSeverity, source: DiagnosticSource, message: impl Into<String>) {
+        self.publish(DiagnosticEvent::new(severity, source, message));
+    }
+
+    /// 订阅诊断事件
+    pub fn subscribe(&self) -> broadcast::Receiver<DiagnosticEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 获取最近的诊断事件（按时间倒序）
+    pub fn recent(&self, limit: usize, min_severity: Option<DiagnosticSeverity>) -> Vec<DiagnosticEvent> {
+        let recent = self.recent.lock().unwrap();
+        recent
+            .iter()
+            .rev()
+            .filter(|e| min_severity.map_or(true, |min| e.severity >= min))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// 清空最近事件
+    pub fn clear(&self) {
+        self.recent.lock().unwrap().clear();
+    }
+}
+
+impl Default for DiagnosticHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_is_bounded_and_filtered() {
+        let hub = DiagnosticHub::with_capacity(2);
+        hub.report(DiagnosticSeverity::Info, DiagnosticSource::System, "a");
+        hub.report(DiagnosticSeverity::Error, DiagnosticSource::Td, "b");
+        hub.report(DiagnosticSeverity::Warning, DiagnosticSource::Md, "c");
+
+        let all = hub.recent(10, None);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].message, "c");
+
+        let errors = hub.recent(10, Some(DiagnosticSeverity::Error));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, DiagnosticSource::Td);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_events() {
+        let hub = DiagnosticHub::new();
+        let mut receiver = hub.subscribe();
+
+        hub.publish(
+            DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, "报单录入失败")
+                .with_code(31)
+                .with_request_id(7),
+        );
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.code, Some(31));
+        assert_eq!(event.correlation_id.as_deref(), Some("req-7"));
+    }
+}
@@ -0,0 +1,346 @@
+use crate::ctp::CtpError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 默认经纪商提示配置文件
+pub const DEFAULT_ERROR_HINTS_FILE: &str = "./config/error_hints.toml";
+
+/// 拒单原因分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorCategory {
+    /// 可用资金不足
+    InsufficientFunds,
+    /// 可平持仓不足
+    InsufficientPosition,
+    /// 价格超出涨跌停板
+    PriceLimit,
+    /// 非交易时段
+    MarketClosed,
+    /// 触发自成交限制
+    SelfTrade,
+    /// 合约不存在或不可交易
+    Instrument,
+    /// 账户权限、只可平仓等限制
+    Permission,
+    /// 报单字段或报单引用有误
+    OrderField,
+    /// 登录、认证、结算确认等会话问题
+    Session,
+    /// 流控
+    Throttled,
+    Other,
+}
+
+/// 错误说明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorExplanation {
+    pub code: i32,
+    pub category: ErrorCategory,
+    pub title: String,
+    pub explanation: String,
+    /// 建议的处理方式
+    pub suggestions: Vec<String>,
+    /// 当前经纪商的补充说明
+    pub broker_hint: Option<String>,
+}
+
+/// 经纪商补充说明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerHint {
+    pub broker_id: String,
+    pub code: i32,
+    pub hint: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BrokerHintFile {
+    #[serde(default)]
+    hints: Vec<BrokerHint>,
+}
+
+struct KnownError {
+    category: ErrorCategory,
+    title: &'static str,
+    explanation: &'static str,
+    suggestions: &'static [&'static str],
+}
+
+/// CTP 标准错误码
+const KNOWN_CODES: &[(i32, KnownError)] = &[
+    (3, KnownError {
+        category: ErrorCategory::Session,
+        title: "不合法的登录",
+        explanation: "用户名、密码或经纪商代码不正确",
+        suggestions: &["核对经纪商代码、投资者代码和密码", "确认账户未被锁定"],
+    }),
+    (6, KnownError {
+        category: ErrorCategory::Session,
+        title: "还没有登录",
+        explanation: "交易会话未登录或已失效",
+        suggestions: &["重新连接并登录交易前置"],
+    }),
+    (9, KnownError {
+        category: ErrorCategory::Permission,
+        title: "没有权限",
+        explanation: "账户没有执行该操作的权限",
+        suggestions: &["联系期货公司开通相应权限"],
+    }),
+    (15, KnownError {
+        category: ErrorCategory::OrderField,
+        title: "报单字段有误",
+        explanation: "报单价格类型、数量或其他字段不被接受",
+        suggestions: &["检查价格是否为最小变动价位的整数倍", "检查数量是否满足最小下单手数"],
+    }),
+    (16, KnownError {
+        category: ErrorCategory::Instrument,
+        title: "找不到合约",
+        explanation: "合约代码不存在，可能已到期或拼写有误",
+        suggestions: &["核对合约代码大小写", "切换到当前主力合约"],
+    }),
+    (17, KnownError {
+        category: ErrorCategory::Instrument,
+        title: "合约不能交易",
+        explanation: "合约当前处于停牌或非交易状态",
+        suggestions: &["确认合约交易状态", "等待合约恢复交易后再报单"],
+    }),
+    (22, KnownError {
+        category: ErrorCategory::OrderField,
+        title: "不允许重复报单",
+        explanation: "报单引用与本会话已有报单重复",
+        suggestions: &["重新提交，系统会生成新的报单引用"],
+    }),
+    (25, KnownError {
+        category: ErrorCategory::OrderField,
+        title: "撤单找不到相应报单",
+        explanation: "报单已全部成交、已撤销或不属于当前会话",
+        suggestions: &["刷新委托列表确认报单状态"],
+    }),
+    (26, KnownError {
+        category: ErrorCategory::OrderField,
+        title: "报单已全成交或已撤销",
+        explanation: "报单当前状态不允许撤单",
+        suggestions: &["刷新委托列表确认报单状态"],
+    }),
+    (28, KnownError {
+        category: ErrorCategory::Permission,
+        title: "没有报单交易权限",
+        explanation: "账户未开通该品种或该交易所的交易权限",
+        suggestions: &["联系期货公司开通交易权限"],
+    }),
+    (29, KnownError {
+        category: ErrorCategory::Permission,
+        title: "只能平仓",
+        explanation: "账户或合约被设置为只可平仓（如临近交割月）",
+        suggestions: &["改为平仓报单", "交易其他合约月份"],
+    }),
+    (30, KnownError {
+        category: ErrorCategory::InsufficientPosition,
+        title: "平仓量超过持仓量",
+        explanation: "可平持仓不足，部分持仓可能已被挂单冻结",
+        suggestions: &["减少平仓数量", "撤销已挂的平仓单后重试", "刷新持仓确认可平数量"],
+    }),
+    (31, KnownError {
+        category: ErrorCategory::InsufficientFunds,
+        title: "资金不足",
+        explanation: "可用资金不足以支付保证金和手续费",
+        suggestions: &["减少开仓手数", "平掉部分持仓释放保证金", "入金后再报单"],
+    }),
+    (42, KnownError {
+        category: ErrorCategory::Session,
+        title: "结算结果未确认",
+        explanation: "当日结算单未确认前不能报单",
+        suggestions: &["确认结算单后再报单"],
+    }),
+    (50, KnownError {
+        category: ErrorCategory::InsufficientPosition,
+        title: "平今仓位不足",
+        explanation: "今仓可平数量不足，持仓可能为昨仓",
+        suggestions: &["改用平昨或平仓", "减少平仓数量"],
+    }),
+    (51, KnownError {
+        category: ErrorCategory::InsufficientPosition,
+        title: "平昨仓位不足",
+        explanation: "昨仓可平数量不足，持仓可能为今仓",
+        suggestions: &["改用平今", "减少平仓数量"],
+    }),
+    (90, KnownError {
+        category: ErrorCategory::Throttled,
+        title: "查询未就绪",
+        explanation: "上一次查询尚未完成或查询过于频繁",
+        suggestions: &["稍后重试查询"],
+    }),
+];
+
+/// 交易所拒单时错误码不固定，按错误信息关键字识别
+const MESSAGE_PATTERNS: &[(&[&str], KnownError)] = &[
+    (&["涨跌停", "涨停", "跌停", "价格超出"], KnownError {
+        category: ErrorCategory::PriceLimit,
+        title: "价格超出涨跌停板",
+        explanation: "报单价格超出当日涨跌停板范围",
+        suggestions: &["将价格调整到涨跌停板范围内", "查看合约当日涨跌停价"],
+    }),
+    (&["非交易时段", "交易时间", "交易时段", "休市", "不在交易"], KnownError {
+        category: ErrorCategory::MarketClosed,
+        title: "非交易时段",
+        explanation: "当前不在合约的交易时段内",
+        suggestions: &["在交易时段内报单", "集合竞价时段只能提交限价单"],
+    }),
+    (&["自成交"], KnownError {
+        category: ErrorCategory::SelfTrade,
+        title: "可能自成交",
+        explanation: "报单可能与本账户的反向挂单成交",
+        suggestions: &["先撤销本账户的反向挂单", "调整报单价格避免与自身挂单撮合"],
+    }),
+    (&["资金不足"], KnownError {
+        category: ErrorCategory::InsufficientFunds,
+        title: "资金不足",
+        explanation: "可用资金不足以支付保证金和手续费",
+        suggestions: &["减少开仓手数", "平掉部分持仓释放保证金", "入金后再报单"],
+    }),
+    (&["仓位不足", "持仓不足", "超过持仓"], KnownError {
+        category: ErrorCategory::InsufficientPosition,
+        title: "可平持仓不足",
+        explanation: "可平持仓不足，部分持仓可能已被挂单冻结",
+        suggestions: &["减少平仓数量", "撤销已挂的平仓单后重试"],
+    }),
+    (&["流控", "频繁"], KnownError {
+        category: ErrorCategory::Throttled,
+        title: "请求过于频繁",
+        explanation: "超出柜台流控限制",
+        suggestions: &["降低报单或查询频率后重试"],
+    }),
+];
+
+/// 拒单说明服务
+///
+/// 将常见 CTP 错误码和交易所拒单信息映射为可操作的说明和处理建议，
+/// 经纪商补充说明从配置文件加载，按当前经纪商代码附加
+#[derive(Debug, Clone, Default)]
+pub struct ErrorExplainer {
+    broker_id: String,
+    broker_hints: HashMap<(String, i32), String>,
+}
+
+impl ErrorExplainer {
+    pub fn new(broker_id: &str) -> Self {
+        Self {
+            broker_id: broker_id.to_string(),
+            broker_hints: HashMap::new(),
+        }
+    }
+
+    /// 添加经纪商补充说明
+    pub fn with_broker_hints(mut self, hints: impl IntoIterator<Item = BrokerHint>) -> Self {
+        for hint in hints {
+            self.broker_hints.insert((hint.broker_id, hint.code), hint.hint);
+        }
+        self
+    }
+
+    /// 从 TOML 文件加载经纪商补充说明，文件不存在时不加载
+    pub fn load_broker_hints(self, path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(self);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let file: BrokerHintFile = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("经纪商错误提示配置解析失败: {}", e)))?;
+        tracing::info!("加载经纪商错误提示 {} 条", file.hints.len());
+        Ok(self.with_broker_hints(file.hints))
+    }
+
+    pub fn broker_id(&self) -> &str {
+        &self.broker_id
+    }
+
+    /// 说明错误码，`message` 为柜台返回的错误信息，用于识别交易所拒单
+    pub fn explain(&self, code: i32, message: &str) -> ErrorExplanation {
+        let known = KNOWN_CODES
+            .iter()
+            .find(|(known_code, _)| *known_code == code)
+            .map(|(_, known)| known)
+            .or_else(|| {
+                MESSAGE_PATTERNS
+                    .iter()
+                    .find(|(keywords, _)| keywords.iter().any(|k| message.contains(k)))
+                    .map(|(_, known)| known)
+            });
+
+        let mut explanation = match known {
+            Some(known) => ErrorExplanation {
+                code,
+                category: known.category,
+                title: known.title.to_string(),
+                explanation: known.explanation.to_string(),
+                suggestions: known.suggestions.iter().map(|s| s.to_string()).collect(),
+                broker_hint: None,
+            },
+            None => ErrorExplanation {
+                code,
+                category: ErrorCategory::Other,
+                title: if message.is_empty() { format!("错误 {}", code) } else { message.to_string() },
+                explanation: "未收录的错误码，请参考柜台返回的错误信息".to_string(),
+                suggestions: vec!["联系期货公司确认错误原因".to_string()],
+                broker_hint: None,
+            },
+        };
+        explanation.broker_hint = self.broker_hints.get(&(self.broker_id.clone(), code)).cloned();
+        explanation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_known_codes_and_messages() {
+        let explainer = ErrorExplainer::new("9999");
+
+        let funds = explainer.explain(31, "CTP:资金不足");
+        assert_eq!(funds.category, ErrorCategory::InsufficientFunds);
+        assert!(!funds.suggestions.is_empty());
+        assert_eq!(explainer.explain(50, "CTP:平今仓位不足").category, ErrorCategory::InsufficientPosition);
+
+        // 交易所拒单按错误信息识别
+        assert_eq!(explainer.explain(2052, "报单价格超出涨跌停板").category, ErrorCategory::PriceLimit);
+        assert_eq!(explainer.explain(1004, "非交易时段不能报单").category, ErrorCategory::MarketClosed);
+        assert_eq!(explainer.explain(1102, "可能自成交").category, ErrorCategory::SelfTrade);
+
+        let unknown = explainer.explain(9527, "未知错误");
+        assert_eq!(unknown.category, ErrorCategory::Other);
+        assert_eq!(unknown.title, "未知错误");
+    }
+
+    #[test]
+    fn test_broker_hints_apply_to_current_broker() {
+        let dir = std::env::temp_dir().join(format!("error_hints_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("error_hints.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[hints]]
+broker_id = "9999"
+code = 31
+hint = "仿真账户可在官网重置资金"
+
+[[hints]]
+broker_id = "8888"
+code = 31
+hint = "其他经纪商"
+"#,
+        )
+        .unwrap();
+
+        let explainer = ErrorExplainer::new("9999").load_broker_hints(&path).unwrap();
+        assert_eq!(explainer.explain(31, "").broker_hint.as_deref(), Some("仿真账户可在官网重置资金"));
+        assert!(explainer.explain(30, "").broker_hint.is_none());
+
+        // 配置文件不存在时不报错
+        assert!(ErrorExplainer::new("9999").load_broker_hints(dir.join("missing.toml")).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
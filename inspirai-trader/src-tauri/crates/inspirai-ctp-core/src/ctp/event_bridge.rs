@@ -0,0 +1,411 @@
+use crate::ctp::{
+    ClientState, CtpError, CtpEvent,
+    models::{AccountInfo, MarketDataTick, OrderStatus, Position, TradeRecord},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// 快照中保留的最近成交数
+const MAX_SNAPSHOT_TRADES: usize = 500;
+
+/// 事件主题，窗口按主题订阅
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventTopic {
+    /// 连接、登录、降级等会话事件
+    Session,
+    MarketData,
+    Orders,
+    Trades,
+    Account,
+    Positions,
+    /// 对账、熔断、错误等系统事件
+    System,
+}
+
+impl EventTopic {
+    pub fn of(event: &CtpEvent) -> Self {
+        match event {
+            CtpEvent::Connected
+            | CtpEvent::Disconnected
+            | CtpEvent::LoginRequired
+            | CtpEvent::LoginSuccess(_)
+            | CtpEvent::LoginFailed(_)
+            | CtpEvent::SettlementRequired
+            | CtpEvent::SettlementConfirmed
+            | CtpEvent::QuerySettlementResult(_)
+            | CtpEvent::DegradedModeEntered(_)
+            | CtpEvent::TraderRecovered => EventTopic::Session,
+            CtpEvent::MarketData(_) => EventTopic::MarketData,
+            CtpEvent::OrderUpdate(_) | CtpEvent::QueryOrdersResult(_)
+            | CtpEvent::BracketUpdate(_)
+            | CtpEvent::OcoGroupUpdate(_)
+            | CtpEvent::TrailingStopUpdate(_)
+            | CtpEvent::OrderExpired(_)
+            | CtpEvent::RolloverUpdate(_) => EventTopic::Orders,
+            CtpEvent::TradeUpdate(_) | CtpEvent::QueryTradesResult(_) => EventTopic::Trades,
+            CtpEvent::AccountUpdate(_) | CtpEvent::QueryAccountResult(_) => EventTopic::Account,
+            CtpEvent::PositionUpdate(_) | CtpEvent::QueryPositionsResult(_) => EventTopic::Positions,
+            CtpEvent::ReconciliationCompleted(_)
+            | CtpEvent::StrategyCircuitBreakerTripped(_)
+            | CtpEvent::OrderRejectionBreakerTripped(_)
+            | CtpEvent::FundsAnomalyDetected(_)
+            | CtpEvent::Error(_) => EventTopic::System,
+        }
+    }
+}
+
+/// 窗口订阅
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowSubscription {
+    /// 订阅的主题，为空时接收全部
+    #[serde(default)]
+    pub topics: HashSet<EventTopic>,
+    /// 行情只推送这些合约，为空时推送全部
+    #[serde(default)]
+    pub instruments: Option<HashSet<String>>,
+    /// 以观察模式注册：接收全部数据，但交易命令在后端被拒绝。
+    /// 观察模式只能由非观察窗口解除，重新注册时不带该标记不会解除
+    #[serde(default)]
+    pub observer: bool,
+}
+
+impl WindowSubscription {
+    fn accepts(&self, topic: EventTopic, event: &CtpEvent) -> bool {
+        if !self.topics.is_empty() && !self.topics.contains(&topic) {
+            return false;
+        }
+        match (event, &self.instruments) {
+            (CtpEvent::MarketData(tick), Some(instruments)) => instruments.contains(&tick.instrument_id),
+            _ => true,
+        }
+    }
+}
+
+/// 新窗口初始化用的最新状态快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BridgeSnapshot {
+    pub state: Option<ClientState>,
+    pub subscriptions: Vec<String>,
+    pub account: Option<AccountInfo>,
+    pub positions: Vec<Position>,
+    pub orders: Vec<OrderStatus>,
+    /// 最近成交，按时间顺序
+    pub trades: Vec<TradeRecord>,
+    /// 各合约最新行情
+    pub last_ticks: Vec<MarketDataTick>,
+    /// 当前窗口处于观察模式，界面据此显示只读横幅
+    #[serde(default)]
+    pub observer: bool,
+}
+
+/// 事件通道积压情况，用于健康检查
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventLag {
+    /// 最近一次取出事件时通道中剩余的事件数
+    pub backlog: usize,
+    /// 本次连接以来的最大积压
+    pub max_backlog: usize,
+    /// 已分发的事件数
+    pub dispatched: u64,
+    pub last_dispatch_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Default)]
+struct BridgeInner {
+    windows: HashMap<String, WindowSubscription>,
+    /// 观察模式窗口，注销后仍保留，避免观察窗口注销再注册绕过限制
+    observers: HashSet<String>,
+    account: Option<AccountInfo>,
+    positions: Vec<Position>,
+    orders: HashMap<String, OrderStatus>,
+    trades: VecDeque<TradeRecord>,
+    last_ticks: HashMap<String, MarketDataTick>,
+    lag: EventLag,
+}
+
+/// 多窗口事件桥
+///
+/// 将客户端事件按窗口订阅分发，并缓存账户、持仓、报单、成交和最新行情，
+/// 新打开的窗口注册时直接获得快照，无需重新发起 CTP 查询
+#[derive(Clone, Default)]
+pub struct EventBridge {
+    inner: Arc<Mutex<BridgeInner>>,
+}
+
+impl EventBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册窗口（重复注册会更新订阅），返回当前快照
+    ///
+    /// `state` 和 `subscriptions` 取自客户端本地状态，由调用方传入
+    pub fn register_window(
+        &self,
+        label: &str,
+        subscription: WindowSubscription,
+        state: Option<ClientState>,
+        subscriptions: Vec<String>,
+    ) -> BridgeSnapshot {
+        let mut inner = self.inner.lock().unwrap();
+        tracing::debug!("窗口 {} 注册事件订阅: {:?}", label, subscription.topics);
+        if subscription.observer && inner.observers.insert(label.to_string()) {
+            tracing::info!("窗口 {} 以观察模式注册", label);
+        }
+        inner.windows.insert(label.to_string(), subscription);
+
+        let mut orders: Vec<OrderStatus> = inner.orders.values().cloned().collect();
+        orders.sort_by_key(|o| o.submit_time);
+        BridgeSnapshot {
+            state,
+            subscriptions,
+            account: inner.account.clone(),
+            positions: inner.positions.clone(),
+            orders,
+            trades: inner.trades.iter().cloned().collect(),
+            last_ticks: inner.last_ticks.values().cloned().collect(),
+            observer: inner.observers.contains(label),
+        }
+    }
+
+    /// 注销窗口（窗口关闭时调用）
+    pub fn unregister_window(&self, label: &str) -> bool {
+        self.inner.lock().unwrap().windows.remove(label).is_some()
+    }
+
+    pub fn window_count(&self) -> usize {
+        self.inner.lock().unwrap().windows.len()
+    }
+
+    /// 设置窗口的观察模式，返回状态是否变化
+    ///
+    /// `requested_by` 为发起请求的窗口，观察窗口不能修改任何窗口的观察模式
+    pub fn set_observer(&self, requested_by: &str, label: &str, enabled: bool) -> Result<bool, CtpError> {
+        self.ensure_can_trade(requested_by)?;
+        let mut inner = self.inner.lock().unwrap();
+        let changed = if enabled {
+            inner.observers.insert(label.to_string())
+        } else {
+            inner.observers.remove(label)
+        };
+        if changed {
+            tracing::info!("窗口 {} 观察模式: {}", label, enabled);
+        }
+        Ok(changed)
+    }
+
+    pub fn is_observer(&self, label: &str) -> bool {
+        self.inner.lock().unwrap().observers.contains(label)
+    }
+
+    /// 处于观察模式的窗口
+    pub fn observers(&self) -> Vec<String> {
+        let mut observers: Vec<String> = self.inner.lock().unwrap().observers.iter().cloned().collect();
+        observers.sort();
+        observers
+    }
+
+    /// 交易命令入口检查：观察窗口发起的交易操作一律拒绝
+    pub fn ensure_can_trade(&self, label: &str) -> Result<(), CtpError> {
+        if self.is_observer(label) {
+            tracing::warn!("拒绝观察窗口 {} 的交易操作", label);
+            return Err(CtpError::StateError(format!("窗口 {} 处于观察模式，交易操作已禁用", label)));
+        }
+        Ok(())
+    }
+
+    /// 更新快照并返回需要接收该事件的窗口
+    pub fn dispatch(&self, event: &CtpEvent) -> Vec<String> {
+        let mut inner = self.inner.lock().unwrap();
+        apply(&mut inner, event);
+        inner.lag.dispatched += 1;
+        inner.lag.last_dispatch_at = Some(chrono::Utc::now());
+
+        let topic = EventTopic::of(event);
+        inner
+            .windows
+            .iter()
+            .filter(|(_, subscription)| subscription.accepts(topic, event))
+            .map(|(label, _)| label.clone())
+            .collect()
+    }
+
+    /// 记录事件通道当前积压，由事件桥每取出一个事件调用一次
+    pub fn record_backlog(&self, pending: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.lag.backlog = pending;
+        inner.lag.max_backlog = inner.lag.max_backlog.max(pending);
+    }
+
+    pub fn lag(&self) -> EventLag {
+        self.inner.lock().unwrap().lag.clone()
+    }
+
+    /// 断开连接后清空缓存，窗口订阅和观察模式保留
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let windows = std::mem::take(&mut inner.windows);
+        let observers = std::mem::take(&mut inner.observers);
+        *inner = BridgeInner {
+            windows,
+            observers,
+            ..BridgeInner::default()
+        };
+    }
+}
+
+fn apply(inner: &mut BridgeInner, event: &CtpEvent) {
+    match event {
+        CtpEvent::MarketData(tick) => {
+            inner.last_ticks.insert(tick.instrument_id.clone(), tick.clone());
+        }
+        CtpEvent::OrderUpdate(order) => {
+            inner.orders.insert(order.order_ref.clone(), order.clone());
+        }
+        CtpEvent::QueryOrdersResult(orders) => {
+            inner.orders = orders.iter().map(|o| (o.order_ref.clone(), o.clone())).collect();
+        }
+        CtpEvent::TradeUpdate(trade) => {
+            if inner.trades.len() >= MAX_SNAPSHOT_TRADES {
+                inner.trades.pop_front();
+            }
+            inner.trades.push_back(trade.clone());
+        }
+        CtpEvent::QueryTradesResult(trades) => {
+            let skip = trades.len().saturating_sub(MAX_SNAPSHOT_TRADES);
+            inner.trades = trades.iter().skip(skip).cloned().collect();
+        }
+        CtpEvent::AccountUpdate(account) | CtpEvent::QueryAccountResult(account) => {
+            inner.account = Some(account.clone());
+        }
+        CtpEvent::PositionUpdate(positions) | CtpEvent::QueryPositionsResult(positions) => {
+            inner.positions = positions.clone();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(instrument_id: &str, last_price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: instrument_id.to_string(),
+            last_price,
+            volume: 1,
+            turnover: last_price,
+            open_interest: 0,
+            bid_price1: last_price,
+            bid_volume1: 1,
+            ask_price1: last_price,
+            ask_volume1: 1,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: last_price,
+            highest_price: last_price,
+            lowest_price: last_price,
+            pre_close_price: last_price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_window_targeted_dispatch() {
+        let bridge = EventBridge::new();
+        bridge.register_window(
+            "chart",
+            WindowSubscription {
+                topics: HashSet::from([EventTopic::MarketData]),
+                instruments: Some(HashSet::from(["rb2501".to_string()])),
+                observer: false,
+            },
+            None,
+            Vec::new(),
+        );
+        bridge.register_window("main", WindowSubscription::default(), None, Vec::new());
+
+        let mut targets = bridge.dispatch(&CtpEvent::MarketData(tick("rb2501", 3500.0)));
+        targets.sort();
+        assert_eq!(targets, vec!["chart".to_string(), "main".to_string()]);
+        assert_eq!(bridge.dispatch(&CtpEvent::MarketData(tick("hc2501", 3300.0))), vec!["main".to_string()]);
+        assert_eq!(bridge.dispatch(&CtpEvent::Disconnected), vec!["main".to_string()]);
+
+        assert!(bridge.unregister_window("main"));
+        assert!(bridge.dispatch(&CtpEvent::Disconnected).is_empty());
+    }
+
+    #[test]
+    fn test_new_window_receives_snapshot() {
+        let bridge = EventBridge::new();
+        bridge.dispatch(&CtpEvent::MarketData(tick("rb2501", 3500.0)));
+        bridge.dispatch(&CtpEvent::MarketData(tick("rb2501", 3501.0)));
+        bridge.dispatch(&CtpEvent::QueryPositionsResult(Vec::new()));
+
+        let snapshot = bridge.register_window(
+            "dom",
+            WindowSubscription::default(),
+            Some(ClientState::LoggedIn),
+            vec!["rb2501".to_string()],
+        );
+        assert_eq!(snapshot.state, Some(ClientState::LoggedIn));
+        assert_eq!(snapshot.last_ticks.len(), 1);
+        assert_eq!(snapshot.last_ticks[0].last_price, 3501.0);
+
+        bridge.reset();
+        let snapshot = bridge.register_window("dom", WindowSubscription::default(), None, Vec::new());
+        assert!(snapshot.last_ticks.is_empty());
+        assert_eq!(bridge.window_count(), 1);
+    }
+
+    #[test]
+    fn test_observer_window_cannot_trade() {
+        let bridge = EventBridge::new();
+        bridge.register_window("main", WindowSubscription::default(), None, Vec::new());
+        let snapshot = bridge.register_window(
+            "observer",
+            WindowSubscription {
+                observer: true,
+                ..WindowSubscription::default()
+            },
+            None,
+            Vec::new(),
+        );
+        assert!(snapshot.observer);
+        assert!(bridge.ensure_can_trade("main").is_ok());
+        assert!(bridge.ensure_can_trade("observer").is_err());
+
+        // 观察窗口重新注册或自行解除都无效
+        bridge.unregister_window("observer");
+        bridge.register_window("observer", WindowSubscription::default(), None, Vec::new());
+        assert!(bridge.is_observer("observer"));
+        assert!(bridge.set_observer("observer", "observer", false).is_err());
+
+        assert_eq!(bridge.dispatch(&CtpEvent::Disconnected).len(), 2);
+        assert!(bridge.set_observer("main", "observer", false).unwrap());
+        assert!(bridge.ensure_can_trade("observer").is_ok());
+    }
+
+    #[test]
+    fn test_lag_tracking() {
+        let bridge = EventBridge::new();
+        bridge.record_backlog(120);
+        bridge.dispatch(&CtpEvent::Connected);
+        bridge.record_backlog(3);
+        bridge.dispatch(&CtpEvent::Connected);
+
+        let lag = bridge.lag();
+        assert_eq!(lag.backlog, 3);
+        assert_eq!(lag.max_backlog, 120);
+        assert_eq!(lag.dispatched, 2);
+        assert!(lag.last_dispatch_at.is_some());
+
+        bridge.reset();
+        assert_eq!(bridge.lag().dispatched, 0);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::ctp::{CtpEvent, EventTopic};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+/// 默认订阅缓冲大小
+pub const DEFAULT_SUBSCRIBER_CAPACITY: usize = 4096;
+
+/// 事件总线主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusTopic {
+    /// 行情
+    Ticks,
+    /// 报单、成交及条件单
+    Orders,
+    /// 资金与持仓
+    Account,
+    /// 会话、对账、熔断、错误等
+    System,
+}
+
+impl BusTopic {
+    pub const ALL: [BusTopic; 4] = [BusTopic::Ticks, BusTopic::Orders, BusTopic::Account, BusTopic::System];
+
+    pub fn of(event: &CtpEvent) -> Self {
+        match EventTopic::of(event) {
+            EventTopic::MarketData => BusTopic::Ticks,
+            EventTopic::Orders | EventTopic::Trades => BusTopic::Orders,
+            EventTopic::Account | EventTopic::Positions => BusTopic::Account,
+            EventTopic::Session | EventTopic::System => BusTopic::System,
+        }
+    }
+
+    /// 缓冲满时的丢弃优先级，越小越先丢弃；报单与资金事件不丢弃
+    fn drop_rank(self) -> Option<u8> {
+        match self {
+            BusTopic::Ticks => Some(0),
+            BusTopic::System => Some(1),
+            BusTopic::Orders | BusTopic::Account => None,
+        }
+    }
+}
+
+/// 订阅者积压情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriberLag {
+    pub name: String,
+    pub topics: Vec<BusTopic>,
+    pub capacity: usize,
+    /// 当前缓冲中未取出的事件数
+    pub pending: usize,
+    /// 订阅以来的最大积压
+    pub max_pending: usize,
+    /// 已取出的事件数
+    pub delivered: u64,
+    /// 缓冲满时丢弃的事件数
+    pub dropped: u64,
+    /// 缓冲被报单、资金事件占满而断开，之后不再投递
+    pub disconnected: bool,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<CtpEvent>,
+    max_pending: usize,
+    delivered: u64,
+    dropped: u64,
+    closed: bool,
+    disconnected: bool,
+}
+
+struct SubscriberQueue {
+    name: String,
+    topics: Vec<BusTopic>,
+    capacity: usize,
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl SubscriberQueue {
+    fn push(&self, event: CtpEvent) {
+        let mut state = self.state.lock().unwrap();
+        if state.disconnected {
+            state.dropped += 1;
+            return;
+        }
+        if state.events.len() >= self.capacity {
+            // 缓冲满时先丢弃最旧的行情，其次最旧的会话事件；行情会被后续 tick 覆盖。
+            // 报单和资金事件不丢弃，缓冲全是这两类时断开订阅者，由其重新订阅并重新查询
+            let incoming = BusTopic::of(&event).drop_rank();
+            let victim = state
+                .events
+                .iter()
+                .enumerate()
+                .filter_map(|(i, e)| BusTopic::of(e).drop_rank().map(|rank| (rank, i)))
+                .min();
+            match (victim, incoming) {
+                (Some((rank, i)), _) if incoming.is_none_or(|incoming| rank <= incoming) => {
+                    state.events.remove(i);
+                }
+                (_, Some(_)) => {
+                    state.dropped += 1;
+                    if state.dropped.is_power_of_two() {
+                        tracing::warn!("事件订阅者 {} 处理过慢，已丢弃 {} 个事件", self.name, state.dropped);
+                    }
+                    return;
+                }
+                (_, None) => {
+                    state.disconnected = true;
+                    state.closed = true;
+                    state.dropped += 1;
+                    tracing::error!(
+                        "事件订阅者 {} 积压 {} 个报单、资金事件，已断开，需重新订阅并重新查询",
+                        self.name,
+                        state.events.len()
+                    );
+                    drop(state);
+                    self.notify.notify_one();
+                    return;
+                }
+            }
+            state.dropped += 1;
+            if state.dropped.is_power_of_two() {
+                tracing::warn!("事件订阅者 {} 处理过慢，已丢弃 {} 个事件", self.name, state.dropped);
+            }
+        }
+        state.events.push_back(event);
+        state.max_pending = state.max_pending.max(state.events.len());
+        drop(state);
+        self.notify.notify_one();
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.notify.notify_one();
+    }
+
+    fn lag(&self) -> SubscriberLag {
+        let state = self.state.lock().unwrap();
+        SubscriberLag {
+            name: self.name.clone(),
+            topics: self.topics.clone(),
+            capacity: self.capacity,
+            pending: state.events.len(),
+            max_pending: state.max_pending,
+            delivered: state.delivered,
+            dropped: state.dropped,
+            disconnected: state.disconnected,
+        }
+    }
+}
+
+/// 事件订阅者，丢弃后自动退订
+pub struct BusSubscriber {
+    queue: Arc<SubscriberQueue>,
+}
+
+impl BusSubscriber {
+    pub fn name(&self) -> &str {
+        &self.queue.name
+    }
+
+    /// 接收下一个事件，总线关闭且缓冲取空后返回 None
+    pub async fn recv(&self) -> Option<CtpEvent> {
+        loop {
+            {
+                let mut state = self.queue.state.lock().unwrap();
+                if let Some(event) = state.events.pop_front() {
+                    state.delivered += 1;
+                    return Some(event);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.queue.notify.notified().await;
+        }
+    }
+
+    /// 非阻塞接收
+    pub fn try_recv(&self) -> Option<CtpEvent> {
+        let mut state = self.queue.state.lock().unwrap();
+        let event = state.events.pop_front()?;
+        state.delivered += 1;
+        Some(event)
+    }
+
+    /// 是否因报单、资金事件积压被断开；断开后 `recv` 取完缓冲返回 None
+    pub fn is_disconnected(&self) -> bool {
+        self.queue.state.lock().unwrap().disconnected
+    }
+
+    /// 缓冲中未取出的事件数
+    pub fn pending(&self) -> usize {
+        self.queue.state.lock().unwrap().events.len()
+    }
+
+    pub fn lag(&self) -> SubscriberLag {
+        self.queue.lag()
+    }
+}
+
+#[derive(Default)]
+struct BusInner {
+    subscribers: Vec<Arc<SubscriberQueue>>,
+    closed: bool,
+}
+
+/// 按主题分发的进程内事件总线
+///
+/// 界面事件桥、策略引擎、行情录制、告警等各自订阅所需主题，每个订阅者有独立的
+/// 有界缓冲，慢订阅者只丢弃自己的行情和会话事件，不影响其他订阅者；报单和资金事件
+/// 从不丢弃，积压到缓冲全是这两类时断开该订阅者。积压情况可按订阅者查询
+#[derive(Clone, Default)]
+pub struct EventBus {
+    inner: Arc<Mutex<BusInner>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅主题，`topics` 为空时订阅全部主题
+    pub fn subscribe(&self, name: &str, topics: &[BusTopic], capacity: usize) -> BusSubscriber {
+        let topics = if topics.is_empty() { BusTopic::ALL.to_vec() } else { topics.to_vec() };
+        let queue = Arc::new(SubscriberQueue {
+            name: name.to_string(),
+            topics,
+            capacity: capacity.max(1),
+            state: Mutex::default(),
+            notify: Notify::new(),
+        });
+        let mut inner = self.inner.lock().unwrap();
+        if inner.closed {
+            queue.close();
+        }
+        inner.subscribers.push(queue.clone());
+        tracing::debug!("事件订阅者 {} 已注册，主题: {:?}", name, queue.topics);
+        BusSubscriber { queue }
+    }
+
+    /// 发布事件到订阅了该主题的订阅者
+    pub fn publish(&self, event: CtpEvent) {
+        let topic = BusTopic::of(&event);
+        let mut inner = self.inner.lock().unwrap();
+        // 订阅者已丢弃的不再投递
+        inner.subscribers.retain(|queue| Arc::strong_count(queue) > 1);
+        for queue in &inner.subscribers {
+            if queue.topics.contains(&topic) {
+                queue.push(event.clone());
+            }
+        }
+    }
+
+    /// 关闭总线，订阅者取完缓冲后 `recv` 返回 None
+    pub fn close(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.closed = true;
+        for queue in &inner.subscribers {
+            queue.close();
+        }
+    }
+
+    /// 各订阅者积压情况
+    pub fn lag(&self) -> Vec<SubscriberLag> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .subscribers
+            .iter()
+            .filter(|queue| Arc::strong_count(queue) > 1)
+            .map(|queue| queue.lag())
+            .collect()
+    }
+
+    /// 将客户端事件通道转入总线，通道关闭后关闭总线
+    pub fn spawn_pump(&self, receiver: mpsc::UnboundedReceiver<CtpEvent>) -> tokio::task::JoinHandle<()> {
+        self.spawn_pump_on(&tokio::runtime::Handle::current(), receiver)
+    }
+
+    /// 在指定运行时（如行情热路径运行时）上运行分发任务
+    pub fn spawn_pump_on(
+        &self,
+        handle: &tokio::runtime::Handle,
+        mut receiver: mpsc::UnboundedReceiver<CtpEvent>,
+    ) -> tokio::task::JoinHandle<()> {
+        let bus = self.clone();
+        handle.spawn(async move {
+            tracing::info!("事件总线已启动");
+            while let Some(event) = receiver.recv().await {
+                bus.publish(event);
+            }
+            bus.close();
+            tracing::info!("事件总线已停止");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::models::MarketDataTick;
+
+    fn tick(price: f64) -> CtpEvent {
+        CtpEvent::MarketData(MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: price,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0,
+            bid_price1: 0.0,
+            bid_volume1: 0,
+            ask_price1: 0.0,
+            ask_volume1: 0,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        })
+    }
+
+    #[test]
+    fn test_topic_routing_and_bounded_buffers() {
+        let bus = EventBus::new();
+        let ui = bus.subscribe("ui", &[], 16);
+        let alerts = bus.subscribe("alerts", &[BusTopic::System], 16);
+        let slow = bus.subscribe("recorder", &[BusTopic::Ticks, BusTopic::System], 2);
+
+        bus.publish(CtpEvent::Connected);
+        bus.publish(tick(1.0));
+        bus.publish(tick(2.0));
+
+        assert_eq!(ui.pending(), 3);
+        assert!(matches!(alerts.try_recv(), Some(CtpEvent::Connected)));
+        assert!(alerts.try_recv().is_none());
+
+        // 缓冲满时先丢弃最旧的行情，会话事件保留
+        assert!(matches!(slow.try_recv(), Some(CtpEvent::Connected)));
+        assert!(matches!(slow.try_recv(), Some(CtpEvent::MarketData(t)) if t.last_price == 2.0));
+
+        let lag = bus.lag().into_iter().find(|l| l.name == "recorder").unwrap();
+        assert_eq!((lag.dropped, lag.delivered, lag.max_pending, lag.pending), (1, 2, 2, 0));
+
+        // 丢弃的订阅者自动退订
+        drop(alerts);
+        bus.publish(CtpEvent::Disconnected);
+        assert_eq!(bus.lag().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_order_events_are_never_dropped() {
+        let bus = EventBus::new();
+        let slow = bus.subscribe("storage", &[], 2);
+        let session = || CtpEvent::QuerySettlementResult(String::new());
+        assert_eq!(BusTopic::of(&session()), BusTopic::System);
+
+        // 行情和会话事件为报单、资金事件让位
+        let account = || CtpEvent::PositionUpdate(Vec::new());
+        bus.publish(tick(1.0));
+        bus.publish(session());
+        bus.publish(account());
+        bus.publish(tick(2.0));
+        assert!(matches!(slow.try_recv(), Some(CtpEvent::QuerySettlementResult(_))));
+        assert!(matches!(slow.try_recv(), Some(CtpEvent::PositionUpdate(_))));
+        assert_eq!(slow.lag().dropped, 2);
+
+        // 缓冲全是资金事件时断开，已缓冲的仍可取出
+        bus.publish(account());
+        bus.publish(account());
+        bus.publish(account());
+        assert!(slow.is_disconnected());
+        assert!(slow.lag().disconnected);
+        assert!(matches!(slow.recv().await, Some(CtpEvent::PositionUpdate(_))));
+        assert!(matches!(slow.recv().await, Some(CtpEvent::PositionUpdate(_))));
+        assert!(slow.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pump_closes_subscribers() {
+        let bus = EventBus::new();
+        let subscriber = bus.subscribe("strategy", &[BusTopic::Ticks], 8);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let pump = bus.spawn_pump(rx);
+
+        tx.send(CtpEvent::Connected).unwrap();
+        tx.send(tick(3500.0)).unwrap();
+        assert!(matches!(subscriber.recv().await, Some(CtpEvent::MarketData(_))));
+
+        drop(tx);
+        pump.await.unwrap();
+        assert!(subscriber.recv().await.is_none());
+        assert!(bus.subscribe("late", &[], 8).recv().await.is_none());
+    }
+}
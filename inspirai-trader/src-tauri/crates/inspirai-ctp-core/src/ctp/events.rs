@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
-use crate::ctp::{CtpError, models::*};
+use crate::ctp::{
+    CtpError, diagnostics::DiagnosticHub, models::*, reconciliation::ReconciliationSummary,
+    rejection_breaker::RejectionAlert, strategy_guard::StrategyStatus, funds_monitor::FundsAnomaly,
+    bracket::BracketOrder, order_manager::{OcoGroup, OrderExpiry}, trailing_stop::TrailingStop,
+    rollover::RolloverExecution,
+    event_bus::{BusSubscriber, BusTopic, EventBus},
+};
 
 /// CTP 事件类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum CtpEvent {
     /// 连接成功
@@ -40,21 +47,56 @@ pub enum CtpEvent {
     SettlementRequired,
     /// 结算信息确认成功
     SettlementConfirmed,
-    /// 错误事件
+    /// 交易端不可用，进入仅行情的降级模式
+    DegradedModeEntered(String),
+    /// 交易端恢复，退出降级模式
+    TraderRecovered,
+    /// 重连对账完成
+    ReconciliationCompleted(ReconciliationSummary),
+    /// 策略超出预算被熔断，其挂单已撤销
+    StrategyCircuitBreakerTripped(StrategyStatus),
+    /// 某来源短时间内拒单过多，已暂停其报单，需确认后恢复
+    OrderRejectionBreakerTripped(RejectionAlert),
+    /// 资金曲线异常（无法解释的权益下降、可用资金为负）
+    FundsAnomalyDetected(FundsAnomaly),
+    /// 括号单状态变化（挂载、触发、平仓完成、取消）
+    BracketUpdate(BracketOrder),
+    /// OCO 订单组状态变化（创建、触发、完成、到期、解散）
+    OcoGroupUpdate(OcoGroup),
+    /// 跟踪止损状态变化（新增、触发、取消）
+    TrailingStopUpdate(TrailingStop),
+    /// 订单到期自动撤销，附撤单原因
+    OrderExpired(OrderExpiry),
+    /// 移仓进度变化（平仓成交、开仓成交、完成、失败、取消）
+    RolloverUpdate(RolloverExecution),
+    /// 错误事件（保留兼容，结构化错误请订阅 `DiagnosticHub`）
     Error(String),
 }
 
 /// 事件处理器
 pub struct EventHandler {
     sender: mpsc::UnboundedSender<CtpEvent>,
-    receiver: mpsc::UnboundedReceiver<CtpEvent>,
+    /// 被事件桥或事件总线取走后为空
+    receiver: Option<mpsc::UnboundedReceiver<CtpEvent>>,
+    diagnostics: DiagnosticHub,
+    bus: EventBus,
 }
 
 impl EventHandler {
     /// 创建新的事件处理器
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
-        Self { sender, receiver }
+        Self {
+            sender,
+            receiver: Some(receiver),
+            diagnostics: DiagnosticHub::new(),
+            bus: EventBus::new(),
+        }
+    }
+
+    /// 获取诊断事件通道
+    pub fn diagnostics(&self) -> DiagnosticHub {
+        self.diagnostics.clone()
     }
 
     /// 获取事件发送器的克隆
@@ -71,20 +113,49 @@ impl EventHandler {
 
     /// 接收下一个事件
     pub async fn next_event(&mut self) -> Option<CtpEvent> {
-        self.receiver.recv().await
+        match self.receiver.as_mut() {
+            Some(receiver) => receiver.recv().await,
+            None => None,
+        }
     }
 
     /// 尝试接收事件（非阻塞）
     pub fn try_recv_event(&mut self) -> Result<CtpEvent, mpsc::error::TryRecvError> {
-        self.receiver.try_recv()
+        self.receiver
+            .as_mut()
+            .ok_or(mpsc::error::TryRecvError::Disconnected)?
+            .try_recv()
+    }
+
+    /// 取走事件接收端，交由事件桥转发，之后 `next_event` 不再返回事件
+    pub fn take_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<CtpEvent>> {
+        self.receiver.take()
+    }
+
+    /// 获取事件总线
+    pub fn bus(&self) -> EventBus {
+        self.bus.clone()
+    }
+
+    /// 启动事件总线，事件通道转入总线后由各订阅者按主题接收；重复调用返回 false
+    pub fn start_bus(&mut self) -> bool {
+        self.start_bus_on(&tokio::runtime::Handle::current())
+    }
+
+    /// 在指定运行时上启动事件总线
+    pub fn start_bus_on(&mut self, handle: &tokio::runtime::Handle) -> bool {
+        match self.receiver.take() {
+            Some(receiver) => {
+                self.bus.spawn_pump_on(handle, receiver);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// 创建事件订阅器
-    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<CtpEvent> {
-        let (tx, rx) = mpsc::unbounded_channel();
-        // 这里需要实现广播机制，暂时返回一个新的接收器
-        // 在实际实现中，应该使用 tokio::sync::broadcast 来支持多个订阅者
-        rx
+    /// 创建事件订阅器，`topics` 为空时订阅全部主题
+    pub fn subscribe(&self, name: &str, topics: &[BusTopic], capacity: usize) -> BusSubscriber {
+        self.bus.subscribe(name, topics, capacity)
     }
 }
 
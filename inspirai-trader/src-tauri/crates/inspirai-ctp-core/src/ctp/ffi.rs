@@ -82,6 +82,13 @@ impl CtpApiManager {
         self.trader_api.clone()
     }
 
+    /// 释放交易 API 与 SPI，行情端不受影响
+    pub fn release_trader(&mut self) {
+        // 先释放 API（停止回调线程），再释放 SPI
+        self.trader_api = None;
+        self.trader_spi = None;
+    }
+
     /// 检查行情 API 是否已创建
     pub fn is_md_api_ready(&self) -> bool {
         self.md_api.is_some()
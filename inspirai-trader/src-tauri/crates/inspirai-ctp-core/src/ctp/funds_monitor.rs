@@ -0,0 +1,265 @@
+use crate::ctp::{AccountInfo, ReconciliationSummary};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// 保留的最近异常条数
+const MAX_ANOMALIES: usize = 100;
+
+/// 资金曲线异常检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FundsMonitorConfig {
+    /// 无法解释的权益下降超过该金额才告警
+    pub min_unexplained_drop: f64,
+    /// 无法解释的权益下降超过上次权益的该比例才告警
+    pub unexplained_drop_ratio: f64,
+}
+
+impl Default for FundsMonitorConfig {
+    fn default() -> Self {
+        Self {
+            min_unexplained_drop: 1000.0,
+            unexplained_drop_ratio: 0.005,
+        }
+    }
+}
+
+/// 异常类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum FundsAnomalyKind {
+    /// 权益下降无法由成交、手续费、持仓盈亏和出入金解释
+    UnexplainedDrop,
+    /// 可用资金为负
+    NegativeAvailable,
+}
+
+/// 资金异常告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct FundsAnomaly {
+    pub kind: FundsAnomalyKind,
+    pub account_id: String,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+    pub previous_balance: f64,
+    pub balance: f64,
+    /// 可由平仓盈亏、持仓盈亏、手续费和出入金解释的变动
+    pub explained_change: f64,
+    /// 无法解释的变动，负数为下降
+    pub unexplained_change: f64,
+    pub available: f64,
+    /// 两次快照之间记录的成交笔数
+    pub fills_since_last: usize,
+    /// 两次快照之间记录的净出入金
+    pub transfers_since_last: f64,
+    pub message: String,
+    /// 最近一次对账结果，便于排查是否有漏记成交
+    pub reconciliation: Option<ReconciliationSummary>,
+}
+
+#[derive(Default)]
+struct MonitorInner {
+    last: Option<AccountInfo>,
+    fills_since_last: usize,
+    transfers_since_last: f64,
+    negative_available: bool,
+    reconciliation: Option<ReconciliationSummary>,
+    anomalies: VecDeque<FundsAnomaly>,
+}
+
+/// 资金曲线异常检测
+///
+/// 比较相邻两次资金快照，权益变动扣除平仓盈亏、持仓盈亏、手续费和出入金后
+/// 仍有明显下降时告警；没有记录到成交时平仓盈亏和手续费的变动不计入可解释部分。
+/// 可用资金转负时告警一次，恢复后再次转负重新告警
+#[derive(Clone)]
+pub struct FundsMonitor {
+    config: Arc<Mutex<FundsMonitorConfig>>,
+    inner: Arc<Mutex<MonitorInner>>,
+}
+
+impl FundsMonitor {
+    pub fn new() -> Self {
+        Self::with_config(FundsMonitorConfig::default())
+    }
+
+    pub fn with_config(config: FundsMonitorConfig) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            inner: Arc::new(Mutex::new(MonitorInner::default())),
+        }
+    }
+
+    pub fn config(&self) -> FundsMonitorConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: FundsMonitorConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 记录一笔成交
+    pub fn record_fill(&self) {
+        self.inner.lock().unwrap().fills_since_last += 1;
+    }
+
+    /// 记录出入金，转入为正
+    pub fn record_transfer(&self, amount: f64) {
+        self.inner.lock().unwrap().transfers_since_last += amount;
+    }
+
+    /// 记录最近一次对账结果，作为告警上下文
+    pub fn record_reconciliation(&self, summary: ReconciliationSummary) {
+        self.inner.lock().unwrap().reconciliation = Some(summary);
+    }
+
+    /// 检查新的资金快照，返回本次发现的异常
+    pub fn observe(&self, account: &AccountInfo) -> Vec<FundsAnomaly> {
+        // 外币资金账户的快照与人民币不可比较，只检测人民币账户
+        if account.currency_id != crate::ctp::currency::BASE_CURRENCY {
+            return Vec::new();
+        }
+        let config = self.config();
+        let now = chrono::Utc::now();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let mut anomalies = Vec::new();
+
+        if let Some(previous) = inner.last.as_ref().filter(|p| p.account_id == account.account_id) {
+            // 手续费回落说明已切换交易日，以新快照为基准
+            if account.commission + 1e-6 >= previous.commission {
+                let traded = inner.fills_since_last > 0;
+                let mut explained = account.position_profit - previous.position_profit + inner.transfers_since_last;
+                if traded {
+                    explained += account.close_profit - previous.close_profit;
+                    explained -= account.commission - previous.commission;
+                }
+                let change = account.balance - previous.balance;
+                let unexplained = change - explained;
+                let threshold = config.min_unexplained_drop.max(previous.balance.abs() * config.unexplained_drop_ratio);
+                if unexplained < -threshold {
+                    anomalies.push(FundsAnomaly {
+                        kind: FundsAnomalyKind::UnexplainedDrop,
+                        account_id: account.account_id.clone(),
+                        detected_at: now,
+                        previous_balance: previous.balance,
+                        balance: account.balance,
+                        explained_change: explained,
+                        unexplained_change: unexplained,
+                        available: account.available,
+                        fills_since_last: inner.fills_since_last,
+                        transfers_since_last: inner.transfers_since_last,
+                        message: format!(
+                            "账户 {} 权益由 {:.2} 降至 {:.2}，其中 {:.2} 无法由成交、手续费、持仓盈亏和出入金解释",
+                            account.account_id, previous.balance, account.balance, -unexplained
+                        ),
+                        reconciliation: inner.reconciliation.clone(),
+                    });
+                }
+            }
+        }
+
+        let negative = account.available < 0.0;
+        if negative && !inner.negative_available {
+            let previous_balance = inner.last.as_ref().map(|p| p.balance).unwrap_or(account.balance);
+            anomalies.push(FundsAnomaly {
+                kind: FundsAnomalyKind::NegativeAvailable,
+                account_id: account.account_id.clone(),
+                detected_at: now,
+                previous_balance,
+                balance: account.balance,
+                explained_change: 0.0,
+                unexplained_change: 0.0,
+                available: account.available,
+                fills_since_last: inner.fills_since_last,
+                transfers_since_last: inner.transfers_since_last,
+                message: format!(
+                    "账户 {} 可用资金为负: {:.2}，保证金 {:.2}，风险度 {:.2}%",
+                    account.account_id, account.available, account.curr_margin, account.risk_ratio * 100.0
+                ),
+                reconciliation: inner.reconciliation.clone(),
+            });
+        }
+        inner.negative_available = negative;
+
+        inner.last = Some(account.clone());
+        inner.fills_since_last = 0;
+        inner.transfers_since_last = 0.0;
+        for anomaly in &anomalies {
+            tracing::error!("资金异常: {}", anomaly.message);
+            if inner.anomalies.len() >= MAX_ANOMALIES {
+                inner.anomalies.pop_front();
+            }
+            inner.anomalies.push_back(anomaly.clone());
+        }
+        anomalies
+    }
+
+    /// 最近的异常，按时间升序
+    pub fn recent(&self) -> Vec<FundsAnomaly> {
+        self.inner.lock().unwrap().anomalies.iter().cloned().collect()
+    }
+}
+
+impl Default for FundsMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(balance: f64, available: f64, close_profit: f64, position_profit: f64, commission: f64) -> AccountInfo {
+        AccountInfo {
+            account_id: "000001".to_string(),
+            currency_id: "CNY".to_string(),
+            available,
+            balance,
+            margin: 0.0,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            curr_margin: 0.0,
+            commission,
+            close_profit,
+            position_profit,
+            risk_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_explained_changes_do_not_alert() {
+        let monitor = FundsMonitor::new();
+        assert!(monitor.observe(&account(100_000.0, 80_000.0, 0.0, 0.0, 0.0)).is_empty());
+        // 持仓浮亏
+        assert!(monitor.observe(&account(95_000.0, 75_000.0, 0.0, -5_000.0, 0.0)).is_empty());
+        // 平仓将浮亏转为平仓亏损并扣手续费
+        monitor.record_fill();
+        assert!(monitor.observe(&account(92_950.0, 92_950.0, -7_000.0, 0.0, 50.0)).is_empty());
+        // 出金
+        monitor.record_transfer(-20_000.0);
+        assert!(monitor.observe(&account(72_950.0, 72_950.0, -7_000.0, 0.0, 50.0)).is_empty());
+        assert!(monitor.recent().is_empty());
+    }
+
+    #[test]
+    fn test_unexplained_drop_and_negative_available() {
+        let monitor = FundsMonitor::new();
+        monitor.observe(&account(100_000.0, 80_000.0, 0.0, 0.0, 0.0));
+        // 没有成交却出现平仓亏损和手续费
+        let anomalies = monitor.observe(&account(96_000.0, 76_000.0, -3_000.0, 0.0, 1_000.0));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, FundsAnomalyKind::UnexplainedDrop);
+        assert!((anomalies[0].unexplained_change + 4_000.0).abs() < 1e-6);
+
+        let anomalies = monitor.observe(&account(90_000.0, -500.0, -3_000.0, -6_000.0, 1_000.0));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, FundsAnomalyKind::NegativeAvailable);
+        // 持续为负不重复告警
+        assert!(monitor.observe(&account(90_000.0, -800.0, -3_000.0, -6_000.0, 1_000.0)).is_empty());
+        assert_eq!(monitor.recent().len(), 2);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::ctp::{
+    CtpError,
+    models::{InstrumentInfo, OffsetFlag, OrderDirection, Position, PositionDirection},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 默认敞口上限配置文件
+pub const DEFAULT_HEDGE_CONFIG_FILE: &str = "./config/hedge_caps.toml";
+
+/// 单个品种/标的的净敞口上限，两项都设置时同时生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureCap {
+    /// 品种代码，期权按标的合约所属品种归并
+    pub key: String,
+    /// 净名义价值上限（绝对值）
+    #[serde(default)]
+    pub max_net_notional: Option<f64>,
+    /// 净手数上限（绝对值）
+    #[serde(default)]
+    pub max_net_volume: Option<i32>,
+    /// 指定对冲合约，未指定时取该品种持仓最多的期货合约
+    #[serde(default)]
+    pub hedge_instrument: Option<String>,
+}
+
+/// 对冲建议配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgeConfig {
+    pub caps: Vec<ExposureCap>,
+    /// 未单独配置的品种使用的净名义价值上限
+    pub default_max_net_notional: Option<f64>,
+}
+
+impl HedgeConfig {
+    /// 从 TOML 文件加载，文件不存在时返回空配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("敞口上限配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        let invalid_notional = |v: Option<f64>| v.is_some_and(|v| !(v.is_finite() && v >= 0.0));
+        if invalid_notional(self.default_max_net_notional) {
+            return Err(CtpError::ConfigError("默认净敞口上限无效".to_string()));
+        }
+        for cap in &self.caps {
+            if invalid_notional(cap.max_net_notional) || cap.max_net_volume.is_some_and(|v| v < 0) {
+                return Err(CtpError::ConfigError(format!("品种 {} 的敞口上限无效", cap.key)));
+            }
+        }
+        Ok(())
+    }
+
+    fn cap_for(&self, key: &str) -> Option<ExposureCap> {
+        self.caps.iter().find(|c| c.key == key).cloned().or_else(|| {
+            self.default_max_net_notional.map(|limit| ExposureCap {
+                key: key.to_string(),
+                max_net_notional: Some(limit),
+                max_net_volume: None,
+                hedge_instrument: None,
+            })
+        })
+    }
+}
+
+/// 品种/标的净敞口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetExposure {
+    pub key: String,
+    /// 净手数（多为正、空为负）
+    pub net_volume: i32,
+    pub net_notional: f64,
+    pub gross_notional: f64,
+    pub max_net_notional: Option<f64>,
+    pub max_net_volume: Option<i32>,
+    pub over_cap: bool,
+}
+
+/// 对冲建议，仅供参考，不会自动下单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeSuggestion {
+    pub key: String,
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    /// 持有反向仓位时建议平仓，否则开仓
+    pub offset_flag: OffsetFlag,
+    pub volume: i32,
+    pub price: f64,
+    /// 执行后的预计净名义价值
+    pub resulting_net_notional: f64,
+    pub reason: String,
+}
+
+/// 对冲建议报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub exposures: Vec<NetExposure>,
+    pub suggestions: Vec<HedgeSuggestion>,
+    pub warnings: Vec<String>,
+}
+
+struct Leg<'a> {
+    instrument_id: &'a str,
+    is_future: bool,
+    net_volume: i32,
+    gross_volume: i32,
+    multiplier: f64,
+    /// 持仓成本，价格缺失时用于估算
+    cost: f64,
+    price: f64,
+    long: i32,
+    short: i32,
+}
+
+impl Leg<'_> {
+    /// 每手名义价值
+    fn lot_notional(&self) -> f64 {
+        self.price * self.multiplier
+    }
+}
+
+/// 按品种/标的汇总净敞口，对超出上限的品种给出反向对冲建议
+///
+/// 期权持仓按标的所属品种归并并计入敞口，对冲合约只选期货；
+/// 价格缺失时以持仓均价估算
+pub fn suggest_hedges(
+    config: &HedgeConfig,
+    positions: &[Position],
+    specs: &HashMap<String, InstrumentInfo>,
+    prices: &HashMap<String, f64>,
+) -> HedgeReport {
+    let mut warnings = Vec::new();
+
+    let mut legs: HashMap<&str, Leg> = HashMap::new();
+    for position in positions.iter().filter(|p| p.total_position > 0) {
+        let instrument_id = position.instrument_id.as_str();
+        let spec = specs.get(instrument_id);
+        if spec.is_none() && !legs.contains_key(instrument_id) {
+            warnings.push(format!("{} 缺少合约信息，按乘数 1 计算", instrument_id));
+        }
+        let leg = legs.entry(instrument_id).or_insert_with(|| Leg {
+            instrument_id,
+            is_future: spec.is_none_or(|s| s.underlying_instrument.is_empty() || s.strike_price <= 0.0),
+            net_volume: 0,
+            gross_volume: 0,
+            multiplier: spec.map_or(1.0, |s| s.volume_multiple.max(1) as f64),
+            cost: 0.0,
+            price: 0.0,
+            long: 0,
+            short: 0,
+        });
+        match position.direction {
+            PositionDirection::Long => {
+                leg.net_volume += position.total_position;
+                leg.long += position.total_position;
+            }
+            PositionDirection::Short => {
+                leg.net_volume -= position.total_position;
+                leg.short += position.total_position;
+            }
+        }
+        leg.gross_volume += position.total_position;
+        leg.cost += position.position_cost;
+    }
+
+    let mut groups: HashMap<String, Vec<Leg>> = HashMap::new();
+    for (instrument_id, mut leg) in legs {
+        leg.price = match prices.get(instrument_id).copied().filter(|p| *p > 0.0 && *p < f64::MAX) {
+            Some(price) => price,
+            None => {
+                warnings.push(format!("{} 缺少行情，以持仓均价估算", instrument_id));
+                leg.cost / (leg.gross_volume as f64 * leg.multiplier)
+            }
+        };
+        groups.entry(exposure_key(instrument_id, specs)).or_default().push(leg);
+    }
+
+    let mut exposures = Vec::new();
+    let mut suggestions = Vec::new();
+    for (key, legs) in groups {
+        let net_volume: i32 = legs.iter().map(|l| l.net_volume).sum();
+        let net_notional: f64 = legs.iter().map(|l| l.lot_notional() * l.net_volume as f64).sum();
+        let gross_notional: f64 = legs.iter().map(|l| l.lot_notional() * l.gross_volume as f64).sum();
+        let cap = config.cap_for(&key);
+        let max_net_notional = cap.as_ref().and_then(|c| c.max_net_notional);
+        let max_net_volume = cap.as_ref().and_then(|c| c.max_net_volume);
+        let over_notional = max_net_notional.is_some_and(|limit| net_notional.abs() > limit);
+        let over_volume = max_net_volume.is_some_and(|limit| net_volume.abs() > limit);
+
+        if over_notional || over_volume {
+            let hedge_leg = match cap.as_ref().and_then(|c| c.hedge_instrument.as_deref()) {
+                Some(id) => legs.iter().find(|l| l.instrument_id == id),
+                None => legs.iter().filter(|l| l.is_future).max_by_key(|l| (l.gross_volume, l.instrument_id)),
+            };
+            match hedge_leg.filter(|l| l.lot_notional() > 0.0) {
+                Some(leg) => {
+                    let mut volume = 0;
+                    if let Some(limit) = max_net_notional.filter(|_| over_notional) {
+                        volume = ((net_notional.abs() - limit) / leg.lot_notional()).ceil() as i32;
+                    }
+                    if let Some(limit) = max_net_volume.filter(|_| over_volume) {
+                        volume = volume.max(net_volume.abs() - limit);
+                    }
+                    let (direction, opposite) = if net_notional > 0.0 {
+                        (OrderDirection::Sell, leg.long)
+                    } else {
+                        (OrderDirection::Buy, leg.short)
+                    };
+                    let signed = match direction {
+                        OrderDirection::Buy => volume,
+                        OrderDirection::Sell => -volume,
+                    };
+                    suggestions.push(HedgeSuggestion {
+                        key: key.clone(),
+                        instrument_id: leg.instrument_id.to_string(),
+                        direction,
+                        offset_flag: if opposite >= volume { OffsetFlag::Close } else { OffsetFlag::Open },
+                        volume,
+                        price: leg.price,
+                        resulting_net_notional: net_notional + signed as f64 * leg.lot_notional(),
+                        reason: format!(
+                            "{} 净敞口 {:.0}（{} 手）超出上限",
+                            key, net_notional, net_volume
+                        ),
+                    });
+                }
+                None => warnings.push(format!("{} 超出敞口上限，但没有可用的对冲合约", key)),
+            }
+        }
+
+        exposures.push(NetExposure {
+            key,
+            net_volume,
+            net_notional,
+            gross_notional,
+            max_net_notional,
+            max_net_volume,
+            over_cap: over_notional || over_volume,
+        });
+    }
+    exposures.sort_by(|a, b| a.key.cmp(&b.key));
+    suggestions.sort_by(|a, b| a.key.cmp(&b.key));
+    warnings.sort();
+
+    HedgeReport {
+        generated_at: chrono::Utc::now(),
+        exposures,
+        suggestions,
+        warnings,
+    }
+}
+
+/// 敞口归并键：期权取标的合约所属品种，其余取自身品种
+fn exposure_key(instrument_id: &str, specs: &HashMap<String, InstrumentInfo>) -> String {
+    let Some(spec) = specs.get(instrument_id) else {
+        return instrument_id.to_string();
+    };
+    if !spec.underlying_instrument.is_empty() && spec.strike_price > 0.0 {
+        if let Some(underlying) = specs.get(&spec.underlying_instrument) {
+            return underlying.product_id.clone();
+        }
+    }
+    if spec.product_id.is_empty() {
+        instrument_id.to_string()
+    } else {
+        spec.product_id.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(instrument_id: &str, product_id: &str, multiplier: i32) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: product_id.to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: multiplier,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    fn position(instrument_id: &str, direction: PositionDirection, volume: i32) -> Position {
+        Position {
+            instrument_id: instrument_id.to_string(),
+            direction,
+            total_position: volume,
+            yesterday_position: volume,
+            today_position: 0,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_suggest_hedge_for_product_over_notional_cap() {
+        let specs = HashMap::from([
+            ("rb2501".to_string(), instrument("rb2501", "rb", 10)),
+            ("rb2505".to_string(), instrument("rb2505", "rb", 10)),
+            ("cu2501".to_string(), instrument("cu2501", "cu", 5)),
+        ]);
+        let prices = HashMap::from([
+            ("rb2501".to_string(), 3500.0),
+            ("rb2505".to_string(), 3600.0),
+            ("cu2501".to_string(), 70_000.0),
+        ]);
+        // rb 净多 20 手（35000×15 + 36000×5 = 705000），cu 净空 1 手未超限
+        let positions = vec![
+            position("rb2501", PositionDirection::Long, 15),
+            position("rb2505", PositionDirection::Long, 5),
+            position("cu2501", PositionDirection::Short, 1),
+        ];
+        let config = HedgeConfig { caps: Vec::new(), default_max_net_notional: Some(500_000.0) };
+        let report = suggest_hedges(&config, &positions, &specs, &prices);
+
+        assert_eq!(report.exposures.len(), 2);
+        let rb = report.exposures.iter().find(|e| e.key == "rb").unwrap();
+        assert_eq!(rb.net_volume, 20);
+        assert!((rb.net_notional - 705_000.0).abs() < 1e-6);
+        assert!(rb.over_cap);
+
+        assert_eq!(report.suggestions.len(), 1);
+        let suggestion = &report.suggestions[0];
+        assert_eq!(suggestion.instrument_id, "rb2501");
+        assert_eq!(suggestion.direction, OrderDirection::Sell);
+        assert_eq!(suggestion.offset_flag, OffsetFlag::Close);
+        // (705000 - 500000) / 35000 向上取整
+        assert_eq!(suggestion.volume, 6);
+        assert!(suggestion.resulting_net_notional <= 500_000.0);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_volume_cap_and_option_grouping() {
+        let mut option = instrument("rb2501C3600", "rb_o", 10);
+        option.underlying_instrument = "rb2501".to_string();
+        option.strike_price = 3600.0;
+        let specs = HashMap::from([
+            ("rb2501".to_string(), instrument("rb2501", "rb", 10)),
+            ("rb2501C3600".to_string(), option),
+        ]);
+        let prices = HashMap::from([("rb2501".to_string(), 3500.0), ("rb2501C3600".to_string(), 50.0)]);
+        let positions = vec![
+            position("rb2501", PositionDirection::Short, 8),
+            position("rb2501C3600", PositionDirection::Short, 4),
+        ];
+        let config = HedgeConfig {
+            caps: vec![ExposureCap {
+                key: "rb".to_string(),
+                max_net_notional: None,
+                max_net_volume: Some(5),
+                hedge_instrument: None,
+            }],
+            default_max_net_notional: None,
+        };
+        let report = suggest_hedges(&config, &positions, &specs, &prices);
+
+        // 期权归入标的品种，对冲只用期货
+        assert_eq!(report.exposures.len(), 1);
+        assert_eq!(report.exposures[0].net_volume, -12);
+        let suggestion = &report.suggestions[0];
+        assert_eq!(suggestion.instrument_id, "rb2501");
+        assert_eq!(suggestion.direction, OrderDirection::Buy);
+        assert_eq!(suggestion.volume, 7);
+        assert_eq!(suggestion.offset_flag, OffsetFlag::Close);
+
+        assert!(HedgeConfig { caps: Vec::new(), default_max_net_notional: Some(-1.0) }.validate().is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::ctp::CtpError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// 默认运行时调优配置文件
+pub const DEFAULT_RUNTIME_TUNING_FILE: &str = "./config/runtime.toml";
+
+/// 调度延迟统计保留的最近样本数
+const LATENCY_SAMPLES: usize = 1024;
+
+/// 行情热路径的运行时调优
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeTuning {
+    /// 为行情热路径（事件总线分发、行情订阅者）单独创建 tokio 运行时，与界面和命令隔离
+    pub dedicated_md_runtime: bool,
+    /// 热路径运行时工作线程数
+    pub md_worker_threads: usize,
+    /// 热路径工作线程绑定的 CPU 核，按线程启动顺序轮流分配，为空不绑核
+    pub md_cores: Vec<usize>,
+    /// 行情 API 回调线程绑定的 CPU 核
+    pub md_callback_core: Option<usize>,
+    /// 调度延迟探测间隔（毫秒），0 表示不探测
+    pub probe_interval_ms: u64,
+}
+
+impl Default for RuntimeTuning {
+    fn default() -> Self {
+        Self {
+            dedicated_md_runtime: false,
+            md_worker_threads: 1,
+            md_cores: Vec::new(),
+            md_callback_core: None,
+            probe_interval_ms: 100,
+        }
+    }
+}
+
+impl RuntimeTuning {
+    /// 读取调优配置，文件不存在时使用默认值
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let tuning: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("运行时调优配置解析失败: {}", e)))?;
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.md_worker_threads == 0 {
+            return Err(CtpError::ValidationError("热路径工作线程数须大于 0".to_string()));
+        }
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        if let Some(core) = self.md_cores.iter().chain(&self.md_callback_core).find(|&&c| c >= cores) {
+            return Err(CtpError::ValidationError(format!("CPU 核 {} 不存在，本机共 {} 个核", core, cores)));
+        }
+        Ok(())
+    }
+
+    pub fn probe_interval(&self) -> Option<Duration> {
+        (self.probe_interval_ms > 0).then(|| Duration::from_millis(self.probe_interval_ms))
+    }
+}
+
+/// 将当前线程绑定到指定 CPU 核
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core: usize) -> Result<(), CtpError> {
+    // cpu_set_t 为纯位图，清零后设置单个核即可
+    let result = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if result != 0 {
+        return Err(CtpError::StateError(format!(
+            "绑定 CPU 核 {} 失败: {}",
+            core,
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+/// 将当前线程绑定到指定 CPU 核
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(core: usize) -> Result<(), CtpError> {
+    Err(CtpError::StateError(format!("当前平台不支持绑定 CPU 核 {}", core)))
+}
+
+/// 调度延迟统计：定时任务实际被唤醒的时间与预期时间之差
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulerLatencyStats {
+    pub runtime: String,
+    pub samples: u64,
+    pub mean_us: f64,
+    pub p50_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+    /// 工作线程绑定的 CPU 核
+    pub pinned_cores: Vec<usize>,
+}
+
+#[derive(Default)]
+struct LatencyWindow {
+    recent: VecDeque<u64>,
+    total: u64,
+}
+
+/// 调度延迟探测，丢弃后停止
+pub struct LatencyProbe {
+    name: String,
+    window: Arc<Mutex<LatencyWindow>>,
+    pinned_cores: Vec<usize>,
+    task: JoinHandle<()>,
+}
+
+impl LatencyProbe {
+    /// 在指定运行时上启动探测任务
+    pub fn spawn(handle: &Handle, name: &str, interval: Duration) -> Self {
+        let window = Arc::new(Mutex::new(LatencyWindow::default()));
+        let samples = window.clone();
+        let task = handle.spawn(async move {
+            loop {
+                let start = Instant::now();
+                tokio::time::sleep(interval).await;
+                let late = start.elapsed().saturating_sub(interval).as_micros() as u64;
+                let mut window = samples.lock().unwrap();
+                if window.recent.len() >= LATENCY_SAMPLES {
+                    window.recent.pop_front();
+                }
+                window.recent.push_back(late);
+                window.total += 1;
+            }
+        });
+        Self {
+            name: name.to_string(),
+            window,
+            pinned_cores: Vec::new(),
+            task,
+        }
+    }
+
+    fn with_pinned_cores(mut self, cores: Vec<usize>) -> Self {
+        self.pinned_cores = cores;
+        self
+    }
+
+    /// 最近样本的统计
+    pub fn stats(&self) -> SchedulerLatencyStats {
+        let window = self.window.lock().unwrap();
+        let mut sorted: Vec<u64> = window.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: f64| -> u64 {
+            if sorted.is_empty() {
+                return 0;
+            }
+            let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+            sorted[index]
+        };
+        SchedulerLatencyStats {
+            runtime: self.name.clone(),
+            samples: window.total,
+            mean_us: if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<u64>() as f64 / sorted.len() as f64 },
+            p50_us: percentile(0.5),
+            p99_us: percentile(0.99),
+            max_us: sorted.last().copied().unwrap_or(0),
+            pinned_cores: self.pinned_cores.clone(),
+        }
+    }
+}
+
+impl Drop for LatencyProbe {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// 行情热路径专用运行时
+///
+/// 事件总线的分发任务和对延迟敏感的行情订阅者运行在这里，不与界面命令、
+/// 导出等任务争抢调度；工作线程可按配置绑定 CPU 核
+pub struct HotPathRuntime {
+    runtime: Option<Runtime>,
+    probe: Option<LatencyProbe>,
+}
+
+impl HotPathRuntime {
+    pub fn start(tuning: &RuntimeTuning) -> Result<Self, CtpError> {
+        tuning.validate()?;
+        let cores = tuning.md_cores.clone();
+        let next = AtomicUsize::new(0);
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(tuning.md_worker_threads)
+            .thread_name("md-hot-path")
+            .enable_all()
+            .on_thread_start(move || {
+                if cores.is_empty() {
+                    return;
+                }
+                let core = cores[next.fetch_add(1, Ordering::Relaxed) % cores.len()];
+                match pin_current_thread(core) {
+                    Ok(()) => tracing::info!("行情热路径线程已绑定 CPU 核 {}", core),
+                    Err(e) => tracing::warn!("{}", e),
+                }
+            })
+            .build()
+            .map_err(|e| CtpError::StateError(format!("创建行情热路径运行时失败: {}", e)))?;
+        let probe = tuning
+            .probe_interval()
+            .map(|interval| LatencyProbe::spawn(runtime.handle(), "md_hot_path", interval).with_pinned_cores(tuning.md_cores.clone()));
+        tracing::info!(
+            "行情热路径运行时已启动，工作线程 {}，绑定核 {:?}",
+            tuning.md_worker_threads,
+            tuning.md_cores
+        );
+        Ok(Self {
+            runtime: Some(runtime),
+            probe,
+        })
+    }
+
+    pub fn handle(&self) -> Handle {
+        self.runtime.as_ref().expect("运行时已关闭").handle().clone()
+    }
+
+    /// 热路径运行时的调度延迟，未开启探测时为空
+    pub fn latency(&self) -> Option<SchedulerLatencyStats> {
+        self.probe.as_ref().map(LatencyProbe::stats)
+    }
+}
+
+impl Drop for HotPathRuntime {
+    fn drop(&mut self) {
+        self.probe.take();
+        // 可能在异步上下文中释放，不能阻塞等待
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuning_validation() {
+        assert!(RuntimeTuning::default().validate().is_ok());
+        let bad_threads = RuntimeTuning {
+            md_worker_threads: 0,
+            ..RuntimeTuning::default()
+        };
+        assert!(bad_threads.validate().is_err());
+        let bad_core = RuntimeTuning {
+            md_callback_core: Some(usize::MAX),
+            ..RuntimeTuning::default()
+        };
+        assert!(bad_core.validate().is_err());
+        assert_eq!(
+            RuntimeTuning { probe_interval_ms: 0, ..RuntimeTuning::default() }.probe_interval(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dedicated_runtime_runs_tasks_and_reports_latency() {
+        let tuning = RuntimeTuning {
+            dedicated_md_runtime: true,
+            probe_interval_ms: 1,
+            ..RuntimeTuning::default()
+        };
+        let runtime = HotPathRuntime::start(&tuning).unwrap();
+        let name = runtime
+            .handle()
+            .spawn(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("md-hot-path"));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let stats = runtime.latency().unwrap();
+        assert_eq!(stats.runtime, "md_hot_path");
+        assert!(stats.samples > 0);
+        assert!(stats.p50_us <= stats.p99_us && stats.p99_us <= stats.max_us);
+        // 在异步上下文中释放不会阻塞
+        drop(runtime);
+    }
+}
@@ -0,0 +1,510 @@
+use crate::ctp::{
+    CtpError,
+    models::{InstrumentInfo, MarketData, OrderInput, OrderStatus, OrderStatusType, Position, PositionDirection},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 下单来源标签
+pub const SOURCE_TAG: &str = "source";
+/// 快捷键下单的来源标签值
+pub const HOTKEY_SOURCE: &str = "hotkey";
+
+/// 区分平今/平昨的交易所
+const CLOSE_TODAY_EXCHANGES: [&str; 2] = ["SHFE", "INE"];
+
+/// 快捷键动作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum HotkeyAction {
+    /// 以对手价（卖一）买入开仓
+    BuyAtCounterparty { instrument_id: String, volume: u32 },
+    /// 以对手价（买一）卖出开仓
+    SellAtCounterparty { instrument_id: String, volume: u32 },
+    /// 以对手价平掉最近开仓的持仓，指定合约时平该合约全部持仓
+    CloseLastPosition { instrument_id: Option<String> },
+    /// 撤销价格最接近最新价的挂单
+    CancelNearestOrder { instrument_id: String },
+}
+
+impl HotkeyAction {
+    pub fn instrument_id(&self) -> Option<&str> {
+        match self {
+            HotkeyAction::BuyAtCounterparty { instrument_id, .. }
+            | HotkeyAction::SellAtCounterparty { instrument_id, .. }
+            | HotkeyAction::CancelNearestOrder { instrument_id } => Some(instrument_id),
+            HotkeyAction::CloseLastPosition { instrument_id } => instrument_id.as_deref(),
+        }
+    }
+}
+
+/// 快捷键配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyConfig {
+    /// 时间窗口内允许的最大动作数
+    pub max_actions: usize,
+    /// 限速时间窗口
+    pub window: Duration,
+    /// 单次开仓的最大手数
+    pub max_volume: u32,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            max_actions: 5,
+            window: Duration::from_secs(1),
+            max_volume: 10,
+        }
+    }
+}
+
+/// 快捷键动作解析结果
+#[derive(Debug, Clone)]
+pub enum ResolvedHotkey {
+    /// 需要提交的订单
+    Submit(Vec<OrderInput>),
+    /// 需要撤销的订单引用
+    Cancel(Box<OrderStatus>),
+}
+
+/// 快捷键执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyOutcome {
+    pub action: HotkeyAction,
+    /// 已提交的订单引用
+    pub order_refs: Vec<String>,
+    /// 已撤销的订单引用
+    pub cancelled: Vec<String>,
+    pub message: String,
+}
+
+/// 快捷键状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyStatus {
+    pub enabled: bool,
+    pub max_actions: usize,
+    pub window_ms: u64,
+    pub max_volume: u32,
+    /// 当前窗口内已执行的动作数
+    pub recent_actions: usize,
+    /// 最近开仓的合约
+    pub last_opened: Option<String>,
+}
+
+#[derive(Default)]
+struct HotkeyState {
+    enabled: bool,
+    recent: VecDeque<Instant>,
+    /// 最近一次快捷键开仓（合约, 持仓方向）
+    last_opened: Option<(String, PositionDirection)>,
+}
+
+/// 快捷键交易控制器
+///
+/// 快捷键只传动作和合约，价格、开平、手数由后端根据行情、持仓和挂单解析，
+/// 并经过总开关和滑动窗口限速，默认关闭，需用户显式启用
+pub struct HotkeyController {
+    config: HotkeyConfig,
+    state: Mutex<HotkeyState>,
+}
+
+impl HotkeyController {
+    pub fn new() -> Self {
+        Self::with_config(HotkeyConfig::default())
+    }
+
+    pub fn with_config(config: HotkeyConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(HotkeyState::default()),
+        }
+    }
+
+    /// 启用或关闭快捷键交易
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.enabled = enabled;
+        state.recent.clear();
+        tracing::info!("快捷键交易已{}", if enabled { "启用" } else { "关闭" });
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().unwrap().enabled
+    }
+
+    pub fn status(&self) -> HotkeyStatus {
+        let mut state = self.state.lock().unwrap();
+        prune(&mut state.recent, self.config.window);
+        HotkeyStatus {
+            enabled: state.enabled,
+            max_actions: self.config.max_actions,
+            window_ms: self.config.window.as_millis() as u64,
+            max_volume: self.config.max_volume,
+            recent_actions: state.recent.len(),
+            last_opened: state.last_opened.as_ref().map(|(id, _)| id.clone()),
+        }
+    }
+
+    /// 检查总开关和限速，通过时占用一个名额
+    pub fn acquire(&self) -> Result<(), CtpError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.enabled {
+            return Err(CtpError::RiskControl("快捷键交易未启用".to_string()));
+        }
+        prune(&mut state.recent, self.config.window);
+        if state.recent.len() >= self.config.max_actions {
+            return Err(CtpError::RiskControl(format!(
+                "快捷键操作过于频繁，{}ms 内最多 {} 次",
+                self.config.window.as_millis(),
+                self.config.max_actions
+            )));
+        }
+        state.recent.push_back(Instant::now());
+        Ok(())
+    }
+
+    /// 平仓动作未指定合约时使用最近开仓的合约
+    pub fn target_instrument(&self, action: &HotkeyAction) -> Option<String> {
+        action.instrument_id().map(str::to_string).or_else(|| {
+            let state = self.state.lock().unwrap();
+            state.last_opened.as_ref().map(|(id, _)| id.clone())
+        })
+    }
+
+    /// 记录快捷键开仓
+    pub fn record_open(&self, order: &OrderInput) {
+        let direction = if order.direction == "Buy" {
+            PositionDirection::Long
+        } else {
+            PositionDirection::Short
+        };
+        self.state.lock().unwrap().last_opened = Some((order.instrument_id.clone(), direction));
+    }
+
+    /// 根据行情、持仓和挂单将动作解析为具体订单或撤单，`exchange_id` 决定平仓是否拆分平今、平昨
+    pub fn resolve(
+        &self,
+        action: &HotkeyAction,
+        exchange_id: &str,
+        market: Option<&MarketData>,
+        positions: &[Position],
+        orders: &[OrderStatus],
+    ) -> Result<ResolvedHotkey, CtpError> {
+        match action {
+            HotkeyAction::BuyAtCounterparty { instrument_id, volume } => {
+                let price = counterparty_price(market, "Buy")?;
+                self.check_volume(*volume)?;
+                Ok(ResolvedHotkey::Submit(vec![hotkey_order(instrument_id, "Buy", "Open", price, *volume)]))
+            }
+            HotkeyAction::SellAtCounterparty { instrument_id, volume } => {
+                let price = counterparty_price(market, "Sell")?;
+                self.check_volume(*volume)?;
+                Ok(ResolvedHotkey::Submit(vec![hotkey_order(instrument_id, "Sell", "Open", price, *volume)]))
+            }
+            HotkeyAction::CloseLastPosition { instrument_id } => {
+                let (target, direction) = match instrument_id {
+                    Some(id) => (id.clone(), None),
+                    None => {
+                        let state = self.state.lock().unwrap();
+                        let (id, direction) = state
+                            .last_opened
+                            .clone()
+                            .ok_or_else(|| CtpError::NotFound("没有可平的快捷键开仓记录".to_string()))?;
+                        (id, Some(direction))
+                    }
+                };
+                let orders = close_orders(&target, exchange_id, direction, market, positions)?;
+                if orders.is_empty() {
+                    return Err(CtpError::NotFound(format!("合约 {} 没有可平持仓", target)));
+                }
+                Ok(ResolvedHotkey::Submit(orders))
+            }
+            HotkeyAction::CancelNearestOrder { instrument_id } => {
+                let reference = market.map(|m| m.last_price).filter(|p| valid_price(*p));
+                nearest_order(instrument_id, reference, orders)
+                    .map(|order| ResolvedHotkey::Cancel(Box::new(order.clone())))
+                    .ok_or_else(|| CtpError::NotFound(format!("合约 {} 没有可撤挂单", instrument_id)))
+            }
+        }
+    }
+
+    fn check_volume(&self, volume: u32) -> Result<(), CtpError> {
+        if volume == 0 {
+            return Err(CtpError::InvalidParameter("委托数量必须大于0".to_string()));
+        }
+        if volume > self.config.max_volume {
+            return Err(CtpError::RiskControl(format!("快捷键单次开仓不能超过 {} 手", self.config.max_volume)));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HotkeyController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn prune(recent: &mut VecDeque<Instant>, window: Duration) {
+    while recent.front().is_some_and(|t| t.elapsed() >= window) {
+        recent.pop_front();
+    }
+}
+
+/// 合约所属交易所，优先取合约信息，行情中的交易所代码常为空
+pub(crate) fn exchange_of<'a>(instrument: Option<&'a InstrumentInfo>, market: Option<&'a MarketData>) -> &'a str {
+    instrument
+        .map(|i| i.exchange_id.as_str())
+        .filter(|id| !id.is_empty())
+        .or_else(|| market.map(|m| m.exchange_id.as_str()))
+        .unwrap_or("")
+}
+
+/// 该交易所平仓是否需要区分平今、平昨
+pub(crate) fn splits_close_today(exchange_id: &str) -> bool {
+    CLOSE_TODAY_EXCHANGES.contains(&exchange_id)
+}
+
+fn valid_price(price: f64) -> bool {
+    price > 0.0 && price < f64::MAX
+}
+
+/// 对手价：买入取卖一，卖出取买一
+fn counterparty_price(market: Option<&MarketData>, direction: &str) -> Result<f64, CtpError> {
+    let market = market.ok_or_else(|| CtpError::StateError("没有可用行情，无法确定对手价".to_string()))?;
+    let price = if direction == "Buy" { market.ask_price } else { market.bid_price };
+    if !valid_price(price) {
+        return Err(CtpError::StateError(format!("合约 {} 对手盘无报价", market.instrument_id)));
+    }
+    Ok(price)
+}
+
+fn hotkey_order(instrument_id: &str, direction: &str, offset: &str, price: f64, volume: u32) -> OrderInput {
+    OrderInput {
+        instrument_id: instrument_id.to_string(),
+        direction: direction.to_string(),
+        offset: offset.to_string(),
+        price,
+        volume,
+        order_type: "Limit".to_string(),
+        time_condition: "GFD".to_string(),
+        volume_condition: "Any".to_string(),
+        min_volume: 1,
+        contingent_condition: "Immediately".to_string(),
+        stop_price: 0.0,
+        force_close_reason: "NotForceClose".to_string(),
+        is_auto_suspend: false,
+        tags: HashMap::from([(SOURCE_TAG.to_string(), HOTKEY_SOURCE.to_string())]),
+    }
+}
+
+/// 生成平仓单，上期所和能源中心拆分平今、平昨
+pub(crate) fn close_orders(
+    instrument_id: &str,
+    exchange_id: &str,
+    direction: Option<PositionDirection>,
+    market: Option<&MarketData>,
+    positions: &[Position],
+) -> Result<Vec<OrderInput>, CtpError> {
+    let split_today = splits_close_today(exchange_id);
+    let mut orders = Vec::new();
+
+    for position in positions
+        .iter()
+        .filter(|p| p.instrument_id == instrument_id && p.total_position > 0)
+        .filter(|p| direction.is_none_or(|d| p.direction == d))
+    {
+        let side = match position.direction {
+            PositionDirection::Long => "Sell",
+            PositionDirection::Short => "Buy",
+        };
+        let price = counterparty_price(market, side)?;
+
+        if split_today {
+            if position.today_position > 0 {
+                orders.push(hotkey_order(instrument_id, side, "CloseToday", price, position.today_position as u32));
+            }
+            if position.yesterday_position > 0 {
+                orders.push(hotkey_order(instrument_id, side, "CloseYesterday", price, position.yesterday_position as u32));
+            }
+        } else {
+            orders.push(hotkey_order(instrument_id, side, "Close", price, position.total_position as u32));
+        }
+    }
+    Ok(orders)
+}
+
+/// 价格最接近参考价的挂单，无参考价时取最近提交的挂单
+fn nearest_order<'a>(instrument_id: &str, reference: Option<f64>, orders: &'a [OrderStatus]) -> Option<&'a OrderStatus> {
+    let pending = orders.iter().filter(|o| {
+        o.instrument_id == instrument_id
+            && matches!(
+                o.status,
+                OrderStatusType::NoTradeQueueing | OrderStatusType::PartTradedQueueing | OrderStatusType::Unknown
+            )
+    });
+    match reference {
+        Some(reference) => pending.min_by(|a, b| {
+            let da = (a.limit_price - reference).abs();
+            let db = (b.limit_price - reference).abs();
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        None => pending.max_by_key(|o| o.submit_time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(exchange_id: &str) -> MarketData {
+        MarketData {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: exchange_id.to_string(),
+            last_price: 3500.0,
+            pre_settlement_price: 3490.0,
+            pre_close_price: 3490.0,
+            pre_open_interest: 0.0,
+            open_price: 3495.0,
+            highest_price: 3510.0,
+            lowest_price: 3480.0,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0.0,
+            close_price: 0.0,
+            settlement_price: 0.0,
+            upper_limit_price: 3800.0,
+            lower_limit_price: 3200.0,
+            bid_price: 3499.0,
+            bid_volume: 10,
+            ask_price: 3501.0,
+            ask_volume: 10,
+            average_price: 3500.0,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            trading_day: "20250102".to_string(),
+        }
+    }
+
+    fn position(direction: PositionDirection, today: i32, yesterday: i32) -> Position {
+        Position {
+            instrument_id: "rb2501".to_string(),
+            direction,
+            total_position: today + yesterday,
+            yesterday_position: yesterday,
+            today_position: today,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    fn instrument(exchange_id: &str) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: exchange_id.to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: 10,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_interlock_and_rate_limit() {
+        let controller = HotkeyController::with_config(HotkeyConfig {
+            max_actions: 2,
+            window: Duration::from_secs(60),
+            ..HotkeyConfig::default()
+        });
+        assert!(controller.acquire().is_err());
+
+        controller.set_enabled(true);
+        assert!(controller.acquire().is_ok());
+        assert!(controller.acquire().is_ok());
+        assert!(matches!(controller.acquire(), Err(CtpError::RiskControl(_))));
+        assert_eq!(controller.status().recent_actions, 2);
+
+        controller.set_enabled(false);
+        assert!(controller.acquire().is_err());
+    }
+
+    #[test]
+    fn test_resolve_counterparty_and_close_last() {
+        let controller = HotkeyController::new();
+        let md = market("SHFE");
+        let buy = HotkeyAction::BuyAtCounterparty {
+            instrument_id: "rb2501".to_string(),
+            volume: 2,
+        };
+        let ResolvedHotkey::Submit(orders) = controller.resolve(&buy, "SHFE", Some(&md), &[], &[]).unwrap() else {
+            panic!("expected submit");
+        };
+        assert_eq!(orders[0].price, 3501.0);
+        assert_eq!(orders[0].tags.get(SOURCE_TAG).map(String::as_str), Some(HOTKEY_SOURCE));
+        controller.record_open(&orders[0]);
+
+        let too_large = HotkeyAction::SellAtCounterparty {
+            instrument_id: "rb2501".to_string(),
+            volume: 100,
+        };
+        assert!(controller.resolve(&too_large, "SHFE", Some(&md), &[], &[]).is_err());
+
+        // 最近开的是多头，只平多头，上期所拆分平今平昨
+        let positions = vec![position(PositionDirection::Long, 2, 3), position(PositionDirection::Short, 1, 0)];
+        let close = HotkeyAction::CloseLastPosition { instrument_id: None };
+        let ResolvedHotkey::Submit(orders) = controller.resolve(&close, "SHFE", Some(&md), &positions, &[]).unwrap() else {
+            panic!("expected submit");
+        };
+        let summary: Vec<(&str, &str, u32)> = orders
+            .iter()
+            .map(|o| (o.direction.as_str(), o.offset.as_str(), o.volume))
+            .collect();
+        assert_eq!(summary, vec![("Sell", "CloseToday", 2), ("Sell", "CloseYesterday", 3)]);
+        assert!(orders.iter().all(|o| o.price == 3499.0));
+    }
+
+    #[test]
+    fn test_close_split_uses_instrument_exchange() {
+        // CTP 行情常不带交易所代码，拆分依据合约信息中的交易所
+        let md = market("");
+        let instrument = instrument("SHFE");
+        let exchange_id = exchange_of(Some(&instrument), Some(&md));
+        assert_eq!(exchange_id, "SHFE");
+
+        let positions = vec![position(PositionDirection::Short, 1, 2)];
+        let orders = close_orders("rb2501", exchange_id, None, Some(&md), &positions).unwrap();
+        let summary: Vec<(&str, &str, u32)> = orders
+            .iter()
+            .map(|o| (o.direction.as_str(), o.offset.as_str(), o.volume))
+            .collect();
+        assert_eq!(summary, vec![("Buy", "CloseToday", 1), ("Buy", "CloseYesterday", 2)]);
+
+        let orders = close_orders("rb2501", exchange_of(None, Some(&md)), None, Some(&md), &positions).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].offset, "Close");
+    }
+}
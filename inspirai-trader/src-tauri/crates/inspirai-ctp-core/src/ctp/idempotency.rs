@@ -0,0 +1,312 @@
+use crate::ctp::CtpError;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认幂等键保留时长
+pub const DEFAULT_IDEMPOTENCY_RETENTION: Duration = Duration::from_secs(10 * 60);
+
+/// 保留的幂等键上限，超过后清理最早的记录
+const MAX_IDEMPOTENCY_KEYS: usize = 10_000;
+
+/// 带幂等键的命令请求
+///
+/// 未携带幂等键时所有操作均为空操作，命令照常执行
+#[derive(Debug, Clone)]
+pub struct IdempotentRequest {
+    key: Option<String>,
+    command: String,
+    /// 请求参数摘要，同一幂等键用于不同参数时拒绝
+    fingerprint: u64,
+}
+
+impl IdempotentRequest {
+    pub fn new(key: Option<String>, command: &str, params: &impl Serialize) -> Self {
+        let mut hasher = DefaultHasher::new();
+        // 参数中的 HashMap（如订单标签）序列化顺序不固定，按规范化后的 JSON 计算摘要
+        let params = serde_json::to_value(params).unwrap_or(serde_json::Value::Null);
+        canonical_json(&params).hash(&mut hasher);
+        Self {
+            key: key.filter(|k| !k.is_empty()),
+            command: command.to_string(),
+            fingerprint: hasher.finish(),
+        }
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+}
+
+struct StoredResult {
+    command: String,
+    fingerprint: u64,
+    result: Result<serde_json::Value, String>,
+    stored_at: Instant,
+}
+
+struct StoreInner {
+    retention: Duration,
+    results: HashMap<String, StoredResult>,
+    order: VecDeque<String>,
+}
+
+/// 交易命令幂等存储
+///
+/// 前端为每次操作生成请求 UUID，IPC 超时重试时携带同一个键，
+/// 后端识别重复键后直接返回首次执行的结果，避免重复报单或撤单
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl IdempotencyStore {
+    pub fn new() -> Self {
+        Self::with_retention(DEFAULT_IDEMPOTENCY_RETENTION)
+    }
+
+    pub fn with_retention(retention: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(StoreInner {
+                retention,
+                results: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.inner.lock().unwrap().retention
+    }
+
+    /// 调整保留时长，对已保存的记录同样生效
+    pub fn set_retention(&self, retention: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.retention = retention;
+        purge_expired(&mut inner);
+    }
+
+    /// 查找首次执行的结果，未执行过时返回 None
+    ///
+    /// 调用方须在持有客户端锁后查找并在同一锁内保存结果，保证同一键只执行一次
+    pub fn replay<T: DeserializeOwned>(&self, request: &IdempotentRequest) -> Result<Option<Result<T, String>>, CtpError> {
+        let Some(key) = request.key() else {
+            return Ok(None);
+        };
+        let mut inner = self.inner.lock().unwrap();
+        purge_expired(&mut inner);
+
+        let Some(stored) = inner.results.get(key) else {
+            return Ok(None);
+        };
+        if stored.command != request.command || stored.fingerprint != request.fingerprint {
+            return Err(CtpError::ValidationError(format!(
+                "幂等键 {} 已用于其他请求 ({})",
+                key, stored.command
+            )));
+        }
+
+        tracing::info!("重复请求 {} ({})，返回首次执行结果", key, request.command);
+        let result = match &stored.result {
+            Ok(value) => Ok(serde_json::from_value(value.clone())
+                .map_err(|e| CtpError::ConversionError(format!("幂等结果解析失败: {}", e)))?),
+            Err(e) => Err(e.clone()),
+        };
+        Ok(Some(result))
+    }
+
+    /// 保存执行结果，重试时原样返回；失败信息以 `context` 为前缀返回给调用方
+    ///
+    /// 失败仅在结论确定时保存（如校验不通过、柜台拒单）。未连接、对账中、网络超时等暂时性失败不保存，
+    /// 恢复后携带同一个键重试会重新执行
+    pub fn complete<T: Serialize>(
+        &self,
+        request: &IdempotentRequest,
+        result: Result<T, CtpError>,
+        context: &str,
+    ) -> Result<T, String> {
+        let transient = result.as_ref().err().is_some_and(is_transient);
+        let result = result.map_err(|e| format!("{}: {}", context, e));
+        if !transient {
+            self.store(request, &result);
+        }
+        result
+    }
+
+    fn store<T: Serialize>(&self, request: &IdempotentRequest, result: &Result<T, String>) {
+        let Some(key) = request.key() else {
+            return;
+        };
+        let result = match result {
+            Ok(value) => match serde_json::to_value(value) {
+                Ok(value) => Ok(value),
+                Err(e) => {
+                    tracing::warn!("幂等结果序列化失败，不保存: {}", e);
+                    return;
+                }
+            },
+            Err(e) => Err(e.clone()),
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        purge_expired(&mut inner);
+        while inner.results.len() >= MAX_IDEMPOTENCY_KEYS {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            inner.results.remove(&oldest);
+        }
+        let stored = StoredResult {
+            command: request.command.clone(),
+            fingerprint: request.fingerprint,
+            result,
+            stored_at: Instant::now(),
+        };
+        if inner.results.insert(key.to_string(), stored).is_none() {
+            inner.order.push_back(key.to_string());
+        }
+    }
+
+    /// 当前保留的幂等键数量
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 暂时性失败：连接或会话状态不满足、网络错误，条件恢复后同一请求可能成功
+fn is_transient(error: &CtpError) -> bool {
+    error.is_retryable() || matches!(error, CtpError::StateError(_))
+}
+
+/// 对象键按字典序排列的 JSON 文本
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(k, v)| format!("{}:{}", serde_json::Value::String(k.clone()), canonical_json(v)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// 记录按保存顺序排列，从队首清理过期记录
+fn purge_expired(inner: &mut StoreInner) {
+    let retention = inner.retention;
+    while let Some(key) = inner.order.front() {
+        let expired = inner
+            .results
+            .get(key)
+            .is_none_or(|stored| stored.stored_at.elapsed() >= retention);
+        if !expired {
+            break;
+        }
+        let key = inner.order.pop_front().unwrap();
+        inner.results.remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_key_returns_original_result() {
+        let store = IdempotencyStore::new();
+        let request = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &("rb2501", 1));
+        assert!(store.replay::<String>(&request).unwrap().is_none());
+        assert_eq!(store.complete(&request, Ok("000001".to_string()), "下单失败"), Ok("000001".to_string()));
+
+        let replayed = store.replay::<String>(&request).unwrap();
+        assert_eq!(replayed, Some(Ok("000001".to_string())));
+
+        // 同一个键用于不同参数视为错误
+        let other = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &("rb2501", 2));
+        assert!(matches!(store.replay::<String>(&other), Err(CtpError::ValidationError(_))));
+
+        // 失败结果同样返回，不会重新执行
+        let failed = IdempotentRequest::new(Some("uuid-2".to_string()), "ctp_cancel_order", &"000001");
+        let rejected = CtpError::CtpApiError { code: 25, message: "撤单找不到相应报单".to_string() };
+        let result = store.complete::<String>(&failed, Err(rejected), "撤单失败");
+        assert_eq!(store.replay::<String>(&failed).unwrap(), Some(result));
+
+        // 未携带幂等键的请求不保存
+        let anonymous = IdempotentRequest::new(None, "ctp_place_order", &("rb2501", 1));
+        let _ = store.complete(&anonymous, Ok("000002".to_string()), "下单失败");
+        assert!(store.replay::<String>(&anonymous).unwrap().is_none());
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_keys_expire_after_retention() {
+        let store = IdempotencyStore::with_retention(Duration::from_secs(60));
+        let request = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &"rb2501");
+        let _ = store.complete(&request, Ok("000001".to_string()), "下单失败");
+        assert!(store.replay::<String>(&request).unwrap().is_some());
+
+        store.set_retention(Duration::ZERO);
+        assert!(store.is_empty());
+        assert!(store.replay::<String>(&request).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transient_failures_are_not_stored() {
+        let store = IdempotencyStore::new();
+        let request = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &"rb2501");
+
+        // 未连接时失败，恢复后重试应重新执行
+        let result = store.complete::<String>(&request, Err(CtpError::StateError("未连接".to_string())), "下单失败");
+        assert!(result.is_err());
+        assert!(store.replay::<String>(&request).unwrap().is_none());
+        let _ = store.complete::<String>(&request, Err(CtpError::TimeoutError), "下单失败");
+        assert!(store.replay::<String>(&request).unwrap().is_none());
+
+        let _ = store.complete(&request, Ok("000001".to_string()), "下单失败");
+        assert_eq!(store.replay::<String>(&request).unwrap(), Some(Ok("000001".to_string())));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_tag_order() {
+        #[derive(Serialize)]
+        struct Order {
+            instrument_id: String,
+            tags: HashMap<String, String>,
+        }
+        let order = |keys: &mut dyn Iterator<Item = usize>| Order {
+            instrument_id: "rb2501".to_string(),
+            tags: keys.map(|i| (format!("tag{}", i), i.to_string())).collect(),
+        };
+
+        let forward = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &order(&mut (0..16)));
+        let reverse = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &order(&mut (0..16).rev()));
+        assert_eq!(forward.fingerprint, reverse.fingerprint);
+
+        let store = IdempotencyStore::new();
+        let _ = store.complete(&forward, Ok("000001".to_string()), "下单失败");
+        assert_eq!(store.replay::<String>(&reverse).unwrap(), Some(Ok("000001".to_string())));
+
+        let changed = IdempotentRequest::new(Some("uuid-1".to_string()), "ctp_place_order", &order(&mut (1..16)));
+        assert_ne!(forward.fingerprint, changed.fingerprint);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::ctp::CtpError;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 默认实例协调目录
+pub const DEFAULT_INSTANCE_DIR: &str = "./data/instance";
+
+const LOCK_FILE: &str = "active.lock";
+const LEASE_FILE: &str = "active.json";
+
+/// 实例角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstanceRole {
+    /// 持有交易会话
+    Active,
+    /// 备用实例，仅记录行情
+    Standby,
+}
+
+/// 主实例租约，由持锁实例写入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseInfo {
+    pub instance_id: String,
+    pub pid: u32,
+    pub acquired_at: chrono::DateTime<chrono::Utc>,
+    pub heartbeat_at: chrono::DateTime<chrono::Utc>,
+    /// 移交目标，为空表示任一备用实例均可接管
+    pub handover_to: Option<String>,
+    /// 发起移交的时间，未移交时为空
+    pub handover_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 实例协调状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceStatus {
+    pub instance_id: String,
+    pub role: InstanceRole,
+    /// 当前（或最近一次）主实例租约
+    pub lease: Option<LeaseInfo>,
+}
+
+/// 实例协调配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinatorConfig {
+    /// 锁文件和租约文件所在目录，主备实例须指向同一目录
+    pub dir: PathBuf,
+    /// 心跳间隔，备用实例以同样间隔尝试接管
+    pub heartbeat_interval: Duration,
+    /// 指定目标的移交超过该时间未被接管时，其他备用实例也可接管
+    pub handover_timeout: Duration,
+}
+
+impl Default for CoordinatorConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from(DEFAULT_INSTANCE_DIR),
+            heartbeat_interval: Duration::from_secs(2),
+            handover_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+struct CoordinatorInner {
+    /// 主实例持有的排他锁，释放文件即释放主角色
+    lock: Option<File>,
+    acquired_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 主备实例协调器
+///
+/// 通过锁文件上的排他锁保证同一时刻只有一个实例持有交易会话，
+/// 主实例定期写入心跳租约；进程退出时锁由系统释放，备用实例随即接管。
+/// 受控移交时主实例先断开交易会话再释放锁，避免重复登录
+#[derive(Clone)]
+pub struct InstanceCoordinator {
+    config: CoordinatorConfig,
+    instance_id: String,
+    inner: Arc<Mutex<CoordinatorInner>>,
+}
+
+impl InstanceCoordinator {
+    pub fn new(config: CoordinatorConfig) -> Result<Self, CtpError> {
+        std::fs::create_dir_all(&config.dir)?;
+        let instance_id = format!("{}-{}", std::process::id(), &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        Ok(Self {
+            config,
+            instance_id,
+            inner: Arc::new(Mutex::new(CoordinatorInner {
+                lock: None,
+                acquired_at: None,
+            })),
+        })
+    }
+
+    pub fn config(&self) -> &CoordinatorConfig {
+        &self.config
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn role(&self) -> InstanceRole {
+        if self.inner.lock().unwrap().lock.is_some() {
+            InstanceRole::Active
+        } else {
+            InstanceRole::Standby
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.role() == InstanceRole::Active
+    }
+
+    /// 心跳：主实例刷新租约，备用实例尝试接管，返回当前角色
+    pub fn tick(&self) -> Result<InstanceRole, CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.lock.is_some() {
+            self.write_lease(inner.acquired_at.unwrap_or_else(chrono::Utc::now), None, None)?;
+            return Ok(InstanceRole::Active);
+        }
+
+        if !self.may_acquire(read_lease(&self.config.dir).as_ref()) {
+            return Ok(InstanceRole::Standby);
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.config.dir.join(LOCK_FILE))?;
+        if file.try_lock_exclusive().is_err() {
+            return Ok(InstanceRole::Standby);
+        }
+
+        let now = chrono::Utc::now();
+        inner.lock = Some(file);
+        inner.acquired_at = Some(now);
+        self.write_lease(now, None, None)?;
+        tracing::info!("实例 {} 成为主实例，持有交易会话", self.instance_id);
+        Ok(InstanceRole::Active)
+    }
+
+    /// 移交主角色
+    ///
+    /// 调用前须已断开本实例的交易会话；`target` 为空时任一备用实例均可接管
+    pub fn hand_over(&self, target: Option<String>) -> Result<InstanceStatus, CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.lock.is_none() {
+            return Err(CtpError::StateError("当前实例不是主实例，无法移交".to_string()));
+        }
+        if target.as_deref() == Some(self.instance_id.as_str()) {
+            return Err(CtpError::InvalidParameter("不能移交给自身".to_string()));
+        }
+
+        let acquired_at = inner.acquired_at.unwrap_or_else(chrono::Utc::now);
+        self.write_lease(acquired_at, target.clone(), Some(chrono::Utc::now()))?;
+        if let Some(file) = inner.lock.take() {
+            let _ = FileExt::unlock(&file);
+        }
+        inner.acquired_at = None;
+        tracing::info!(
+            "实例 {} 移交主角色给 {}",
+            self.instance_id,
+            target.as_deref().unwrap_or("任一备用实例")
+        );
+        drop(inner);
+        Ok(self.status())
+    }
+
+    /// 退出时释放主角色，不指定移交目标
+    pub fn release(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(file) = inner.lock.take() {
+            let _ = FileExt::unlock(&file);
+            inner.acquired_at = None;
+            tracing::info!("实例 {} 释放主角色", self.instance_id);
+        }
+    }
+
+    pub fn status(&self) -> InstanceStatus {
+        InstanceStatus {
+            instance_id: self.instance_id.clone(),
+            role: self.role(),
+            lease: read_lease(&self.config.dir),
+        }
+    }
+
+    /// 移交窗口内只有目标实例可接管，发起移交的实例不会立即取回
+    fn may_acquire(&self, lease: Option<&LeaseInfo>) -> bool {
+        let Some(lease) = lease else {
+            return true;
+        };
+        let Some(handover_at) = lease.handover_at else {
+            // 未移交：锁由持有者进程决定，直接尝试加锁
+            return true;
+        };
+        let timeout = chrono::Duration::from_std(self.config.handover_timeout)
+            .unwrap_or_else(|_| chrono::Duration::seconds(30));
+        if chrono::Utc::now() - handover_at > timeout {
+            return true;
+        }
+        match &lease.handover_to {
+            Some(target) => target == &self.instance_id,
+            None => lease.instance_id != self.instance_id,
+        }
+    }
+
+    fn write_lease(
+        &self,
+        acquired_at: chrono::DateTime<chrono::Utc>,
+        handover_to: Option<String>,
+        handover_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), CtpError> {
+        let lease = LeaseInfo {
+            instance_id: self.instance_id.clone(),
+            pid: std::process::id(),
+            acquired_at,
+            heartbeat_at: chrono::Utc::now(),
+            handover_to,
+            handover_at,
+        };
+        let json = serde_json::to_string_pretty(&lease).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+        // 先写临时文件再替换，备用实例不会读到半截内容
+        let tmp = self.config.dir.join(format!("{}.{}.tmp", LEASE_FILE, self.instance_id));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, self.config.dir.join(LEASE_FILE))?;
+        Ok(())
+    }
+}
+
+fn read_lease(dir: &Path) -> Option<LeaseInfo> {
+    let content = std::fs::read_to_string(dir.join(LEASE_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinator(dir: &Path) -> InstanceCoordinator {
+        InstanceCoordinator::new(CoordinatorConfig {
+            dir: dir.to_path_buf(),
+            heartbeat_interval: Duration::from_millis(100),
+            handover_timeout: Duration::from_secs(60),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_single_active_and_targeted_handover() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary = coordinator(dir.path());
+        let backup = coordinator(dir.path());
+
+        assert_eq!(primary.tick().unwrap(), InstanceRole::Active);
+        assert_eq!(backup.tick().unwrap(), InstanceRole::Standby);
+        assert_eq!(backup.status().lease.unwrap().instance_id, primary.instance_id());
+        assert!(matches!(backup.hand_over(None), Err(CtpError::StateError(_))));
+
+        let status = primary.hand_over(Some(backup.instance_id().to_string())).unwrap();
+        assert_eq!(status.role, InstanceRole::Standby);
+        // 移交窗口内原主实例不会取回
+        assert_eq!(primary.tick().unwrap(), InstanceRole::Standby);
+        assert_eq!(backup.tick().unwrap(), InstanceRole::Active);
+        assert_eq!(primary.tick().unwrap(), InstanceRole::Standby);
+
+        let lease = primary.status().lease.unwrap();
+        assert_eq!(lease.instance_id, backup.instance_id());
+        assert!(lease.handover_at.is_none());
+    }
+
+    #[test]
+    fn test_standby_takes_over_when_primary_exits() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary = coordinator(dir.path());
+        let backup = coordinator(dir.path());
+        assert!(primary.tick().unwrap() == InstanceRole::Active);
+
+        // 进程退出时排他锁随文件关闭释放
+        drop(primary);
+        assert_eq!(backup.tick().unwrap(), InstanceRole::Active);
+
+        let other = coordinator(dir.path());
+        assert_eq!(other.tick().unwrap(), InstanceRole::Standby);
+        backup.hand_over(None).unwrap();
+        assert_eq!(backup.tick().unwrap(), InstanceRole::Standby);
+        assert_eq!(other.tick().unwrap(), InstanceRole::Active);
+    }
+}
@@ -1,7 +1,6 @@
 use crate::ctp::{CtpError, config::Environment};
 use tracing_subscriber::{
     layer::SubscriberExt, 
-    util::SubscriberInitExt, 
     EnvFilter,
     Layer,
 };
@@ -58,12 +57,13 @@ impl LoggerManager {
             layers.push(file_layer.boxed());
         }
 
-        // 初始化订阅器
-        tracing_subscriber::registry()
+        // 初始化订阅器，已存在全局 subscriber 时沿用现有的
+        let subscriber = tracing_subscriber::registry()
             .with(env_filter)
-            .with(layers)
-            .try_init()
-            .map_err(|e| CtpError::ConfigError(format!("初始化日志系统失败: {}", e)))?;
+            .with(layers);
+        if !crate::logging::install_global(subscriber) {
+            return Ok(());
+        }
 
         tracing::info!("日志系统初始化完成");
         tracing::info!("环境: {:?}", environment);
@@ -172,6 +172,7 @@ mod tests {
 
     #[test]
     fn test_logger_initialization() {
+        let _guard = crate::logging::GLOBAL_SUBSCRIBER_TEST_LOCK.blocking_lock();
         let temp_dir = TempDir::new().unwrap();
         let log_file = temp_dir.path().join("test.log");
 
@@ -182,11 +183,8 @@ mod tests {
             Environment::SimNow,
         );
 
-        // 在测试环境中，可能会因为已经初始化过而失败，这是正常的
-        match result {
-            Ok(_) => println!("日志系统初始化成功"),
-            Err(e) => println!("日志系统初始化失败（可能已初始化）: {}", e),
-        }
+        // 已存在全局 subscriber 时沿用现有的，不再报错
+        assert!(result.is_ok(), "日志系统初始化失败: {:?}", result);
     }
 
     #[test]
@@ -0,0 +1,351 @@
+use crate::ctp::{
+    CtpError,
+    task_manager::CancelToken,
+    tick_compaction::{bar_batch, bar_schema, tick_batch, tick_schema, StorageGranularity, TickCompactor},
+};
+use arrow_array::{ArrayRef, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+/// 行情导出请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketDataExportRequest {
+    pub instruments: Vec<String>,
+    /// 起始交易日（含）
+    pub start_date: NaiveDate,
+    /// 结束交易日（含）
+    pub end_date: NaiveDate,
+    pub granularity: StorageGranularity,
+    pub format: ExportFormat,
+    /// 输出文件路径
+    pub path: PathBuf,
+    /// 日内时间过滤 HH:MM:SS（含），夜盘跨零点时可设置 start_time > end_time
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub end_time: Option<String>,
+}
+
+/// 导出进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub instrument_id: String,
+    pub trading_day: NaiveDate,
+    /// 已处理的（合约, 交易日）数
+    pub completed: usize,
+    pub total: usize,
+    pub rows_written: usize,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: PathBuf,
+    pub rows: usize,
+    pub trading_days: usize,
+    /// 无数据的合约
+    pub missing_instruments: Vec<String>,
+}
+
+/// 研究用行情导出
+///
+/// 从本地归档读取 tick 或 K 线，按合约、交易日和日内时间过滤后写入单个
+/// CSV 或 parquet 文件，首列为交易日，其余列与归档字段一致，可直接用 pandas 读取
+pub struct MarketDataExporter<'a> {
+    store: &'a TickCompactor,
+    cancel: Option<CancelToken>,
+}
+
+impl<'a> MarketDataExporter<'a> {
+    pub fn new(store: &'a TickCompactor) -> Self {
+        Self { store, cancel: None }
+    }
+
+    /// 设置取消标记，取消后删除未完成的导出文件
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// 执行导出，每处理完一个（合约, 交易日）回调一次进度
+    pub fn export(
+        &self,
+        request: &MarketDataExportRequest,
+        mut on_progress: impl FnMut(&ExportProgress),
+    ) -> Result<ExportSummary, CtpError> {
+        if request.instruments.is_empty() {
+            return Err(CtpError::InvalidParameter("至少需要指定一个合约".to_string()));
+        }
+        if request.start_date > request.end_date {
+            return Err(CtpError::InvalidParameter("起始日期不能晚于结束日期".to_string()));
+        }
+
+        let days: Vec<NaiveDate> = request
+            .start_date
+            .iter_days()
+            .take_while(|d| *d <= request.end_date)
+            .collect();
+        let total = days.len() * request.instruments.len();
+
+        if let Some(parent) = request.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        // 先写临时文件，失败时不留下不完整的导出
+        let tmp = request.path.with_extension("exporting");
+        let mut writer = BatchWriter::create(&tmp, request.format, export_schema(request.granularity))?;
+
+        let mut summary = ExportSummary {
+            path: request.path.clone(),
+            ..ExportSummary::default()
+        };
+        let mut found = vec![false; request.instruments.len()];
+        let mut days_with_data = std::collections::BTreeSet::new();
+        let mut completed = 0;
+
+        let result = (|| -> Result<(), CtpError> {
+            for day in &days {
+                for (i, instrument_id) in request.instruments.iter().enumerate() {
+                    if let Some(cancel) = &self.cancel {
+                        cancel.check()?;
+                    }
+                    let batch = self.load(request, *day, instrument_id)?;
+                    if let Some(batch) = batch {
+                        summary.rows += batch.num_rows();
+                        found[i] = true;
+                        days_with_data.insert(*day);
+                        writer.write(&batch)?;
+                    }
+
+                    completed += 1;
+                    on_progress(&ExportProgress {
+                        instrument_id: instrument_id.clone(),
+                        trading_day: *day,
+                        completed,
+                        total,
+                        rows_written: summary.rows,
+                    });
+                }
+            }
+            writer.finish()
+        })();
+
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+        std::fs::rename(&tmp, &request.path)?;
+
+        summary.trading_days = days_with_data.len();
+        summary.missing_instruments = request
+            .instruments
+            .iter()
+            .zip(found)
+            .filter(|(_, found)| !found)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        info!(
+            "行情导出完成 {}: {} 行，{} 个交易日",
+            request.path.display(),
+            summary.rows,
+            summary.trading_days
+        );
+        Ok(summary)
+    }
+
+    /// 读取单个合约单日数据，加上交易日列
+    fn load(
+        &self,
+        request: &MarketDataExportRequest,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+    ) -> Result<Option<RecordBatch>, CtpError> {
+        let in_window = |time: &str| time_in_window(time, request.start_time.as_deref(), request.end_time.as_deref());
+
+        let batch = if request.granularity == StorageGranularity::Tick {
+            let mut ticks = self.store.read_ticks(trading_day, instrument_id)?;
+            ticks.retain(|t| in_window(&t.update_time));
+            if ticks.is_empty() {
+                return Ok(None);
+            }
+            tick_batch(&ticks)?
+        } else {
+            let mut bars = self.store.read_bars(trading_day, instrument_id, request.granularity)?;
+            bars.retain(|b| in_window(&b.time));
+            if bars.is_empty() {
+                return Ok(None);
+            }
+            bar_batch(&bars)?
+        };
+
+        let day = trading_day.format("%Y-%m-%d").to_string();
+        let day_column: ArrayRef = Arc::new(StringArray::from(vec![day; batch.num_rows()]));
+        let mut columns = vec![day_column];
+        columns.extend(batch.columns().iter().cloned());
+
+        RecordBatch::try_new(export_schema(request.granularity), columns)
+            .map(Some)
+            .map_err(export_error)
+    }
+}
+
+/// 日内时间过滤，未设置的一端不限制
+fn time_in_window(time: &str, start: Option<&str>, end: Option<&str>) -> bool {
+    match (start, end) {
+        (Some(start), Some(end)) if start > end => time >= start || time <= end,
+        _ => start.is_none_or(|s| time >= s) && end.is_none_or(|e| time <= e),
+    }
+}
+
+/// 导出文件结构：交易日列 + 归档字段
+fn export_schema(granularity: StorageGranularity) -> SchemaRef {
+    let base = if granularity == StorageGranularity::Tick {
+        tick_schema()
+    } else {
+        bar_schema()
+    };
+
+    let mut fields = vec![Arc::new(Field::new("trading_day", DataType::Utf8, false))];
+    fields.extend(base.fields().iter().cloned());
+    Arc::new(Schema::new(fields))
+}
+
+fn export_error(e: impl std::fmt::Display) -> CtpError {
+    CtpError::ConversionError(format!("写入导出文件失败: {}", e))
+}
+
+enum BatchWriter {
+    Csv(Box<arrow_csv::Writer<File>>),
+    Parquet(Box<ArrowWriter<File>>),
+}
+
+impl BatchWriter {
+    fn create(path: &Path, format: ExportFormat, schema: SchemaRef) -> Result<Self, CtpError> {
+        let file = File::create(path)?;
+        match format {
+            ExportFormat::Csv => Ok(BatchWriter::Csv(Box::new(
+                arrow_csv::WriterBuilder::new().with_header(true).build(file),
+            ))),
+            ExportFormat::Parquet => {
+                let level = ZstdLevel::try_new(3).map_err(export_error)?;
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(level))
+                    .build();
+                ArrowWriter::try_new(file, schema, Some(props))
+                    .map(|writer| BatchWriter::Parquet(Box::new(writer)))
+                    .map_err(export_error)
+            }
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), CtpError> {
+        match self {
+            BatchWriter::Csv(writer) => writer.write(batch).map_err(export_error),
+            BatchWriter::Parquet(writer) => writer.write(batch).map_err(export_error),
+        }
+    }
+
+    fn finish(self) -> Result<(), CtpError> {
+        match self {
+            BatchWriter::Csv(writer) => {
+                writer.into_inner().sync_all()?;
+                Ok(())
+            }
+            BatchWriter::Parquet(writer) => writer.close().map(|_| ()).map_err(export_error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::tick_compaction::{ArchivedBar, CompactionConfig};
+
+    fn bar(time: &str, close: f64) -> ArchivedBar {
+        ArchivedBar {
+            instrument_id: "rb2501".to_string(),
+            time: time.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 10,
+            turnover: 0.0,
+            open_interest: 0,
+            tick_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_time_window_across_midnight() {
+        assert!(time_in_window("23:30:00", Some("21:00:00"), Some("02:30:00")));
+        assert!(time_in_window("01:00:00", Some("21:00:00"), Some("02:30:00")));
+        assert!(!time_in_window("10:00:00", Some("21:00:00"), Some("02:30:00")));
+        assert!(time_in_window("10:00:00", None, Some("11:30:00")));
+    }
+
+    #[test]
+    fn test_export_bars_to_csv_and_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let store = TickCompactor::new(CompactionConfig::new(root.join("raw"), root.join("archive")));
+        let day1 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        store
+            .write_bars(day1, "rb2501", StorageGranularity::Bar1m, &[bar("09:00:00", 3500.0), bar("14:00:00", 3510.0)])
+            .unwrap();
+        store
+            .write_bars(day2, "rb2501", StorageGranularity::Bar1m, &[bar("09:00:00", 3520.0)])
+            .unwrap();
+
+        let mut request = MarketDataExportRequest {
+            instruments: vec!["rb2501".to_string(), "hc2501".to_string()],
+            start_date: day1,
+            end_date: day2,
+            granularity: StorageGranularity::Bar1m,
+            format: ExportFormat::Csv,
+            path: root.join("out/bars.csv"),
+            start_time: None,
+            end_time: Some("11:30:00".to_string()),
+        };
+
+        let exporter = MarketDataExporter::new(&store);
+        let mut progress = Vec::new();
+        let summary = exporter.export(&request, |p| progress.push(p.completed)).unwrap();
+        assert_eq!(summary.rows, 2);
+        assert_eq!(summary.trading_days, 2);
+        assert_eq!(summary.missing_instruments, vec!["hc2501".to_string()]);
+        assert_eq!(progress, vec![1, 2, 3, 4]);
+
+        let csv = std::fs::read_to_string(&request.path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("trading_day,instrument_id,time"));
+        assert!(lines[1].starts_with("2025-01-02,rb2501,09:00:00"));
+
+        request.format = ExportFormat::Parquet;
+        request.path = root.join("out/bars.parquet");
+        request.end_time = None;
+        let summary = exporter.export(&request, |_| {}).unwrap();
+        assert_eq!(summary.rows, 3);
+        assert!(request.path.exists());
+    }
+}
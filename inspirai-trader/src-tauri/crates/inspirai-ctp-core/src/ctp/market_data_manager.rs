@@ -2,8 +2,12 @@ use crate::ctp::{
     CtpError, CtpEvent, MdSpiImpl,
     models::MarketDataTick,
     config::CtpConfig,
+    pipeline_trace::TraceStage,
+    market_data_source::{MarketDataSource, SourceMerger, SourceStats, TickSink, CTP_SOURCE},
+    tick_retention::{RetentionStats, TickHistory, TickRetentionConfig, TickSpillStore},
 };
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::{HashMap, HashSet};
 use tokio::sync::mpsc;
 use tokio::time::{Duration, Instant};
@@ -21,12 +25,19 @@ pub struct MarketDataManager {
     subscribed_instruments: Arc<Mutex<HashSet<String>>>,
     /// 行情数据缓存
     market_data_cache: Arc<Mutex<HashMap<String, MarketDataTick>>>,
+    /// 按合约保留的 tick 历史（受条数和内存预算限制）及其落盘存储
+    tick_history: TickHistory,
     /// 订阅请求队列
     subscription_queue: Arc<Mutex<Vec<SubscriptionRequest>>>,
     /// 数据过滤器
     data_filters: Arc<Mutex<Vec<Box<dyn MarketDataFilter + Send + Sync>>>>,
     /// 统计信息
     stats: Arc<Mutex<MarketDataStats>>,
+    /// 外部行情源
+    sources: Arc<Mutex<Vec<Arc<dyn MarketDataSource>>>>,
+    /// 多源合并，仅在接入外部行情源后启用
+    source_merger: Arc<Mutex<SourceMerger>>,
+    multi_source: AtomicBool,
 }
 
 /// 订阅请求
@@ -141,32 +152,113 @@ impl MarketDataManager {
             event_sender,
             subscribed_instruments: Arc::new(Mutex::new(HashSet::new())),
             market_data_cache: Arc::new(Mutex::new(HashMap::new())),
+            tick_history: TickHistory::new(TickRetentionConfig::default()),
             subscription_queue: Arc::new(Mutex::new(Vec::new())),
             data_filters: Arc::new(Mutex::new(Vec::new())),
             stats: Arc::new(Mutex::new(MarketDataStats::default())),
+            sources: Arc::new(Mutex::new(Vec::new())),
+            source_merger: Arc::new(Mutex::new(SourceMerger::default())),
+            multi_source: AtomicBool::new(false),
         }
     }
 
+    /// 设置行情源优先级（靠前优先）和超时切换时间，默认 CTP 优先、3 秒超时
+    pub fn with_source_preference(self, preference: Vec<String>, stale_after: Duration) -> Self {
+        self.source_merger.lock().unwrap().set_preference(preference, stale_after);
+        self
+    }
+
+    /// 接入外部行情源并订阅当前已订阅的合约
+    pub fn add_source(self: &Arc<Self>, source: Arc<dyn MarketDataSource>) -> Result<(), CtpError> {
+        let name = source.name().to_string();
+        if self.sources.lock().unwrap().iter().any(|s| s.name() == name) || name == CTP_SOURCE {
+            return Err(CtpError::ValidationError(format!("行情源名称重复: {}", name)));
+        }
+
+        let manager: Weak<Self> = Arc::downgrade(self);
+        source.start(TickSink::new(&name, move |source, tick| {
+            if let Some(manager) = manager.upgrade() {
+                manager.handle_source_tick(source, tick);
+            }
+        }))?;
+        let instruments = self.get_subscribed_instruments();
+        if !instruments.is_empty() {
+            source.subscribe(&instruments)?;
+        }
+
+        self.sources.lock().unwrap().push(source);
+        self.multi_source.store(true, Ordering::Release);
+        tracing::info!("接入行情源: {}", name);
+        Ok(())
+    }
+
+    /// 停止并移除外部行情源
+    pub fn remove_source(&self, name: &str) -> Option<Arc<dyn MarketDataSource>> {
+        let mut sources = self.sources.lock().unwrap();
+        let index = sources.iter().position(|s| s.name() == name)?;
+        let source = sources.remove(index);
+        self.multi_source.store(!sources.is_empty(), Ordering::Release);
+        drop(sources);
+
+        source.stop();
+        self.source_merger.lock().unwrap().remove_source(name);
+        tracing::info!("移除行情源: {}", name);
+        Some(source)
+    }
+
+    /// 各行情源统计
+    pub fn get_source_stats(&self) -> Vec<SourceStats> {
+        self.source_merger.lock().unwrap().stats()
+    }
+
+    /// 合约当前采用的行情源
+    pub fn get_active_source(&self, instrument_id: &str) -> Option<String> {
+        self.source_merger.lock().unwrap().active_source(instrument_id).map(str::to_string)
+    }
+
+    /// 处理外部行情源推送的 tick，打上来源标签后与 CTP 行情合并
+    pub fn handle_source_tick(&self, source: &str, mut tick: MarketDataTick) {
+        if !self.subscribed_instruments.lock().unwrap().contains(&tick.instrument_id) {
+            return;
+        }
+        tick.source = Some(source.to_string());
+        self.ingest(source, tick);
+    }
+
+    /// 设置缓存保留策略
+    pub fn with_retention(self, config: TickRetentionConfig) -> Self {
+        self.tick_history.set_config(config);
+        self
+    }
+
+    /// 设置淘汰数据的落盘存储
+    pub fn with_spill_store(mut self, store: Arc<dyn TickSpillStore + Send + Sync>) -> Self {
+        self.tick_history = self.tick_history.with_spill_store(store);
+        self
+    }
+
     /// 订阅行情数据
     pub async fn subscribe_market_data(&self, instruments: &[String]) -> Result<(), CtpError> {
         tracing::info!("订阅行情数据，合约数量: {}", instruments.len());
         
-        let mut subscription_queue = self.subscription_queue.lock().unwrap();
-        let mut subscribed = self.subscribed_instruments.lock().unwrap();
+        {
+            let mut subscription_queue = self.subscription_queue.lock().unwrap();
+            let mut subscribed = self.subscribed_instruments.lock().unwrap();
         
-        for instrument_id in instruments {
-            if !subscribed.contains(instrument_id) {
-                tracing::info!("添加订阅请求: {}", instrument_id);
+            for instrument_id in instruments {
+                if !subscribed.contains(instrument_id) {
+                    tracing::info!("添加订阅请求: {}", instrument_id);
                 
-                subscription_queue.push(SubscriptionRequest {
-                    instrument_id: instrument_id.clone(),
-                    action: SubscriptionAction::Subscribe,
-                    timestamp: Instant::now(),
-                });
+                    subscription_queue.push(SubscriptionRequest {
+                        instrument_id: instrument_id.clone(),
+                        action: SubscriptionAction::Subscribe,
+                        timestamp: Instant::now(),
+                    });
                 
-                subscribed.insert(instrument_id.clone());
-            } else {
-                tracing::debug!("合约已订阅: {}", instrument_id);
+                    subscribed.insert(instrument_id.clone());
+                } else {
+                    tracing::debug!("合约已订阅: {}", instrument_id);
+                }
             }
         }
         
@@ -180,26 +272,29 @@ impl MarketDataManager {
     pub async fn unsubscribe_market_data(&self, instruments: &[String]) -> Result<(), CtpError> {
         tracing::info!("取消订阅行情数据，合约数量: {}", instruments.len());
         
-        let mut subscription_queue = self.subscription_queue.lock().unwrap();
-        let mut subscribed = self.subscribed_instruments.lock().unwrap();
+        {
+            let mut subscription_queue = self.subscription_queue.lock().unwrap();
+            let mut subscribed = self.subscribed_instruments.lock().unwrap();
         
-        for instrument_id in instruments {
-            if subscribed.contains(instrument_id) {
-                tracing::info!("添加取消订阅请求: {}", instrument_id);
+            for instrument_id in instruments {
+                if subscribed.contains(instrument_id) {
+                    tracing::info!("添加取消订阅请求: {}", instrument_id);
                 
-                subscription_queue.push(SubscriptionRequest {
-                    instrument_id: instrument_id.clone(),
-                    action: SubscriptionAction::Unsubscribe,
-                    timestamp: Instant::now(),
-                });
+                    subscription_queue.push(SubscriptionRequest {
+                        instrument_id: instrument_id.clone(),
+                        action: SubscriptionAction::Unsubscribe,
+                        timestamp: Instant::now(),
+                    });
                 
-                subscribed.remove(instrument_id);
+                    subscribed.remove(instrument_id);
                 
-                // 从缓存中移除数据
-                let mut cache = self.market_data_cache.lock().unwrap();
-                cache.remove(instrument_id);
-            } else {
-                tracing::debug!("合约未订阅: {}", instrument_id);
+                    // 从缓存中移除数据
+                    let mut cache = self.market_data_cache.lock().unwrap();
+                    cache.remove(instrument_id);
+                    self.tick_history.remove(instrument_id);
+                } else {
+                    tracing::debug!("合约未订阅: {}", instrument_id);
+                }
             }
         }
         
@@ -230,6 +325,8 @@ impl MarketDataManager {
             }
         }
         
+        drop(queue);
+        
         // 处理订阅请求
         if !subscribe_list.is_empty() {
             // TODO: 调用实际的 CTP API 订阅方法
@@ -242,11 +339,38 @@ impl MarketDataManager {
             tracing::info!("执行取消订阅操作，合约: {:?}", unsubscribe_list);
         }
         
+        // 同步外部行情源订阅
+        let sources = self.sources.lock().unwrap().clone();
+        for source in sources {
+            if !subscribe_list.is_empty() {
+                if let Err(e) = source.subscribe(&subscribe_list) {
+                    tracing::warn!("行情源 {} 订阅失败: {}", source.name(), e);
+                }
+            }
+            if !unsubscribe_list.is_empty() {
+                if let Err(e) = source.unsubscribe(&unsubscribe_list) {
+                    tracing::warn!("行情源 {} 取消订阅失败: {}", source.name(), e);
+                }
+            }
+        }
+        
         Ok(())
     }
 
     /// 处理接收到的行情数据
     pub fn handle_market_data(&self, tick: MarketDataTick) {
+        self.ingest(CTP_SOURCE, tick);
+    }
+
+    fn ingest(&self, source: &str, mut tick: MarketDataTick) {
+        // 多源时只采用合约当前首选源的 tick
+        if self.multi_source.load(Ordering::Acquire) {
+            let (accepted, _) = self.source_merger.lock().unwrap().accept(source, &tick.instrument_id, Instant::now());
+            if !accepted {
+                return;
+            }
+        }
+
         // 更新统计信息
         self.update_stats(&tick);
         
@@ -261,6 +385,11 @@ impl MarketDataManager {
             let mut cache = self.market_data_cache.lock().unwrap();
             cache.insert(tick.instrument_id.clone(), tick.clone());
         }
+        self.tick_history.record(&tick);
+        // 发出时间已在 SPI 回调中记录，这里只记录管理器更新
+        if let Some(trace) = tick.trace.as_mut() {
+            trace.mark(TraceStage::ManagerUpdate);
+        }
         
         // 发送事件
         if let Err(e) = self.event_sender.send(CtpEvent::MarketData(tick)) {
@@ -268,6 +397,21 @@ impl MarketDataManager {
         }
     }
 
+    /// 立即落盘所有待写入的淘汰数据
+    pub fn flush_spill(&self) {
+        self.tick_history.flush();
+    }
+
+    /// 获取合约最近的 `limit` 条 tick（按时间顺序），超出内存部分从落盘存储补齐
+    pub fn get_tick_history(&self, instrument_id: &str, limit: usize) -> Result<Vec<MarketDataTick>, CtpError> {
+        self.tick_history.history(instrument_id, limit)
+    }
+
+    /// 获取缓存保留统计
+    pub fn get_retention_stats(&self) -> RetentionStats {
+        self.tick_history.stats()
+    }
+
     /// 应用数据过滤器
     fn apply_filters(&self, tick: &MarketDataTick) -> bool {
         let filters = self.data_filters.lock().unwrap();
@@ -351,6 +495,7 @@ impl MarketDataManager {
         tracing::info!("清除行情数据缓存");
         let mut cache = self.market_data_cache.lock().unwrap();
         cache.clear();
+        self.tick_history.clear();
     }
 
     /// 重置统计信息
@@ -364,12 +509,13 @@ impl MarketDataManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ctp::{ClientState, Environment};
+    use crate::ctp::{ClientState, ConnectionMode, Environment};
     use tokio::sync::mpsc;
 
     fn create_test_config() -> CtpConfig {
         CtpConfig {
             environment: Environment::SimNow,
+            connection_mode: ConnectionMode::Full,
             broker_id: "9999".to_string(),
             investor_id: "test_user".to_string(),
             password: "test_pass".to_string(),
@@ -383,6 +529,8 @@ mod tests {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: Default::default(),
+            programmatic: Default::default(),
         }
     }
 
@@ -405,6 +553,9 @@ mod tests {
             highest_price: price,
             lowest_price: price,
             pre_close_price: price,
+            price_limit: None,
+            trace: None,
+            source: None,
         }
     }
 
@@ -494,6 +645,72 @@ mod tests {
         }
     }
 
+    /// 记录订阅并保存推送入口的测试行情源
+    #[derive(Default)]
+    struct FakeSource {
+        sink: Mutex<Option<TickSink>>,
+        subscribed: Mutex<Vec<String>>,
+    }
+
+    impl MarketDataSource for FakeSource {
+        fn name(&self) -> &str {
+            "udp"
+        }
+
+        fn start(&self, sink: TickSink) -> Result<(), CtpError> {
+            *self.sink.lock().unwrap() = Some(sink);
+            Ok(())
+        }
+
+        fn stop(&self) {
+            self.sink.lock().unwrap().take();
+        }
+
+        fn subscribe(&self, instruments: &[String]) -> Result<(), CtpError> {
+            self.subscribed.lock().unwrap().extend(instruments.iter().cloned());
+            Ok(())
+        }
+
+        fn unsubscribe(&self, instruments: &[String]) -> Result<(), CtpError> {
+            self.subscribed.lock().unwrap().retain(|i| !instruments.contains(i));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_source_failover() {
+        let client_state = Arc::new(Mutex::new(ClientState::Disconnected));
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let md_spi = Arc::new(Mutex::new(MdSpiImpl::new(client_state, sender.clone(), create_test_config())));
+        let manager = Arc::new(
+            MarketDataManager::new(md_spi, sender)
+                .with_source_preference(vec![CTP_SOURCE.to_string(), "udp".to_string()], Duration::from_millis(50)),
+        );
+        manager.subscribe_market_data(&["rb2401".to_string()]).await.unwrap();
+
+        let source = Arc::new(FakeSource::default());
+        manager.add_source(source.clone()).unwrap();
+        assert_eq!(*source.subscribed.lock().unwrap(), vec!["rb2401".to_string()]);
+        manager.subscribe_market_data(&["hc2401".to_string()]).await.unwrap();
+        assert_eq!(source.subscribed.lock().unwrap().len(), 2);
+        let sink = source.sink.lock().unwrap().clone().unwrap();
+
+        // CTP 正常时外部源的 tick 不转发
+        manager.handle_market_data(create_test_tick("rb2401", 3500.0, 100));
+        sink.push(create_test_tick("rb2401", 3501.0, 100));
+        let ticks: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert!(matches!(&ticks[..], [CtpEvent::MarketData(t)] if t.source.is_none()));
+
+        // CTP 超时后切换到外部源，tick 带来源标签
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        sink.push(create_test_tick("rb2401", 3502.0, 110));
+        assert!(matches!(receiver.try_recv(), Ok(CtpEvent::MarketData(t)) if t.source.as_deref() == Some("udp")));
+        assert_eq!(manager.get_active_source("rb2401").as_deref(), Some("udp"));
+
+        assert!(manager.remove_source("udp").is_some());
+        assert!(source.sink.lock().unwrap().is_none());
+    }
+
     #[test]
     fn test_price_change_filter() {
         let filter = PriceChangeFilter::new(1.0); // 1% 变动阈值
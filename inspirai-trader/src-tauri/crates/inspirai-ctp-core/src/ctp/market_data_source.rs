@@ -0,0 +1,219 @@
+use crate::ctp::{CtpError, models::MarketDataTick};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// CTP 行情源名称
+pub const CTP_SOURCE: &str = "ctp";
+
+/// 默认行情源超时，超过该时间未收到某源的 tick 即切换到次优源
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(3);
+
+/// 外部行情源
+///
+/// UDP 组播桥、厂商 websocket 等行情源实现此 trait，将原始行情转换为
+/// `MarketDataTick` 后推送给 `TickSink`，由 `MarketDataManager` 与 CTP 行情合并
+pub trait MarketDataSource: Send + Sync {
+    /// 行情源名称，用作 tick 来源标签和优先级配置
+    fn name(&self) -> &str;
+
+    /// 启动行情源，之后收到的 tick 推送给 `sink`
+    fn start(&self, sink: TickSink) -> Result<(), CtpError>;
+
+    /// 停止行情源
+    fn stop(&self);
+
+    /// 订阅合约
+    fn subscribe(&self, instruments: &[String]) -> Result<(), CtpError>;
+
+    /// 取消订阅合约
+    fn unsubscribe(&self, instruments: &[String]) -> Result<(), CtpError>;
+}
+
+type TickHandler = dyn Fn(&str, MarketDataTick) + Send + Sync;
+
+/// 行情源推送 tick 的入口，可跨线程克隆
+#[derive(Clone)]
+pub struct TickSink {
+    source: Arc<str>,
+    handler: Arc<TickHandler>,
+}
+
+impl TickSink {
+    pub fn new(source: &str, handler: impl Fn(&str, MarketDataTick) + Send + Sync + 'static) -> Self {
+        Self {
+            source: Arc::from(source),
+            handler: Arc::new(handler),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 推送一笔已转换的 tick
+    pub fn push(&self, tick: MarketDataTick) {
+        (self.handler)(&self.source, tick);
+    }
+}
+
+/// 行情源统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceStats {
+    pub source: String,
+    /// 收到的 tick 数
+    pub received: u64,
+    /// 作为首选源被采用的 tick 数
+    pub accepted: u64,
+    /// 当前作为首选源的合约数
+    pub active_instruments: usize,
+}
+
+/// 首选源切换记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSwitch {
+    pub instrument_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// 多行情源合并
+///
+/// 按合约独立选择首选源：在未超时的源中取优先级最高者，只采用首选源的 tick；
+/// 高优先级源超时即切换到次优源，恢复推送后切回。未列入优先级的源排在最后，按名称排序
+pub struct SourceMerger {
+    preference: Vec<String>,
+    stale_after: Duration,
+    /// 各合约每个源最近一次 tick 的到达时间
+    last_seen: HashMap<String, HashMap<String, Instant>>,
+    active: HashMap<String, String>,
+    stats: HashMap<String, SourceStats>,
+}
+
+impl Default for SourceMerger {
+    fn default() -> Self {
+        Self::new(vec![CTP_SOURCE.to_string()], DEFAULT_STALE_AFTER)
+    }
+}
+
+impl SourceMerger {
+    pub fn new(preference: Vec<String>, stale_after: Duration) -> Self {
+        Self {
+            preference,
+            stale_after,
+            last_seen: HashMap::new(),
+            active: HashMap::new(),
+            stats: HashMap::new(),
+        }
+    }
+
+    pub fn set_preference(&mut self, preference: Vec<String>, stale_after: Duration) {
+        self.preference = preference;
+        self.stale_after = stale_after;
+    }
+
+    /// 记录一笔 tick，返回是否采用；首选源变化时返回切换记录
+    pub fn accept(&mut self, source: &str, instrument_id: &str, now: Instant) -> (bool, Option<SourceSwitch>) {
+        let preference = &self.preference;
+        let seen = self.last_seen.entry(instrument_id.to_string()).or_default();
+        seen.insert(source.to_string(), now);
+        let best = seen
+            .iter()
+            .filter(|(_, at)| now.duration_since(**at) <= self.stale_after)
+            .map(|(s, _)| s.as_str())
+            .min_by_key(|s| (rank(preference, s), *s))
+            .unwrap_or(source)
+            .to_string();
+
+        let stats = self.stats.entry(source.to_string()).or_insert_with(|| SourceStats {
+            source: source.to_string(),
+            ..SourceStats::default()
+        });
+        stats.received += 1;
+        let accepted = best == source;
+        if accepted {
+            stats.accepted += 1;
+        }
+
+        let previous = self.active.insert(instrument_id.to_string(), best.clone());
+        let switch = match previous {
+            Some(from) if from != best => {
+                tracing::warn!("合约 {} 行情源切换: {} -> {}", instrument_id, from, best);
+                Some(SourceSwitch {
+                    instrument_id: instrument_id.to_string(),
+                    from,
+                    to: best,
+                })
+            }
+            _ => None,
+        };
+        (accepted, switch)
+    }
+
+    /// 合约当前的首选源
+    pub fn active_source(&self, instrument_id: &str) -> Option<&str> {
+        self.active.get(instrument_id).map(String::as_str)
+    }
+
+    /// 移除行情源的到达记录，其合约在下一笔 tick 时重新选择首选源
+    pub fn remove_source(&mut self, source: &str) {
+        for seen in self.last_seen.values_mut() {
+            seen.remove(source);
+        }
+        self.stats.remove(source);
+    }
+
+    pub fn stats(&self) -> Vec<SourceStats> {
+        let mut stats: Vec<SourceStats> = self
+            .stats
+            .values()
+            .map(|s| SourceStats {
+                active_instruments: self.active.values().filter(|a| **a == s.source).count(),
+                ..s.clone()
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            (rank(&self.preference, &a.source), &a.source).cmp(&(rank(&self.preference, &b.source), &b.source))
+        });
+        stats
+    }
+}
+
+fn rank(preference: &[String], source: &str) -> usize {
+    preference.iter().position(|s| s == source).unwrap_or(preference.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_source_with_failover() {
+        let mut merger = SourceMerger::new(vec![CTP_SOURCE.to_string(), "udp".to_string()], Duration::from_secs(3));
+        let start = Instant::now();
+
+        assert!(merger.accept(CTP_SOURCE, "rb2501", start).0);
+        // CTP 正常时次优源的 tick 不采用
+        assert!(!merger.accept("udp", "rb2501", start + Duration::from_millis(10)).0);
+        // 未列入优先级的源排在最后
+        assert!(!merger.accept("vendor", "rb2501", start + Duration::from_millis(20)).0);
+
+        // CTP 超时，切换到 udp
+        let (accepted, switch) = merger.accept("udp", "rb2501", start + Duration::from_secs(4));
+        assert!(accepted);
+        let switch = switch.unwrap();
+        assert_eq!((switch.from.as_str(), switch.to.as_str()), (CTP_SOURCE, "udp"));
+
+        // CTP 恢复后切回
+        let (accepted, switch) = merger.accept(CTP_SOURCE, "rb2501", start + Duration::from_secs(5));
+        assert!(accepted);
+        assert_eq!(switch.unwrap().to, CTP_SOURCE);
+        assert_eq!(merger.active_source("rb2501"), Some(CTP_SOURCE));
+
+        let stats = merger.stats();
+        assert_eq!(stats.iter().map(|s| s.source.as_str()).collect::<Vec<_>>(), vec![CTP_SOURCE, "udp", "vendor"]);
+        assert_eq!((stats[1].received, stats[1].accepted), (2, 1));
+        assert_eq!(stats[0].active_instruments, 1);
+    }
+}
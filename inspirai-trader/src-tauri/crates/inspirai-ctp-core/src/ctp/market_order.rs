@@ -0,0 +1,326 @@
+use crate::ctp::{CtpError, MarketData, OrderInput, OrderStatus, OrderStatusType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 订单标签中记录市价单模拟方式的键
+pub const MARKET_EMULATION_TAG: &str = "market_emulation";
+
+/// 市价单处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MarketOrderPolicy {
+    /// 交易所支持时直接报市价单，否则（含交易所未知）按涨跌停价报限价单
+    #[default]
+    Native,
+    /// 一律以涨跌停价报 FAK 限价单（买涨停、卖跌停）
+    LimitPrice,
+    /// 以对手价加若干跳报 FAK 限价单，未成交部分按最新对手价追单
+    Counterparty { ticks: u32, max_chases: u32 },
+}
+
+/// 交易所是否接受市价单，上期所和能源中心只接受限价单
+pub fn exchange_supports_market_orders(exchange_id: &str) -> bool {
+    !matches!(exchange_id, "SHFE" | "INE")
+}
+
+/// 待追单的市价单模拟
+#[derive(Debug, Clone)]
+pub struct MarketOrderChase {
+    /// 下一次报单，数量为未成交部分
+    pub order: OrderInput,
+    pub ticks: u32,
+    pub price_tick: f64,
+    /// 剩余追单次数
+    pub chases_left: u32,
+}
+
+impl MarketOrderChase {
+    /// 按最新对手价重新定价，消耗一次追单次数
+    pub fn reprice(&mut self, market: Option<&MarketData>) -> Result<(), CtpError> {
+        self.order.price = counterparty_price(&self.order.direction, market, self.ticks, self.price_tick)?;
+        self.chases_left = self.chases_left.saturating_sub(1);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct EmulatorState {
+    /// 报单引用 -> 追单信息
+    tracked: HashMap<String, MarketOrderChase>,
+    pending: Vec<MarketOrderChase>,
+}
+
+/// 市价单模拟
+///
+/// 部分交易所拒绝市价单，前端“市价”按钮提交的订单在此按配置策略转换为限价单，
+/// 使各交易所表现一致；对手价模式下 FAK 未成交部分由追单任务按最新对手价重新报出
+#[derive(Debug, Clone, Default)]
+pub struct MarketOrderEmulator {
+    policy: MarketOrderPolicy,
+    state: Arc<Mutex<EmulatorState>>,
+}
+
+impl MarketOrderEmulator {
+    pub fn new(policy: MarketOrderPolicy) -> Self {
+        Self {
+            policy,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn policy(&self) -> MarketOrderPolicy {
+        self.policy
+    }
+
+    /// 将市价单转换为限价单，需要追单时返回追单信息；非市价单和直接报市价单时不做修改
+    ///
+    /// `exchange_id` 取自合约信息，行情中的交易所代码常为空
+    pub fn emulate(
+        &self,
+        order: &mut OrderInput,
+        exchange_id: &str,
+        market: Option<&MarketData>,
+        price_tick: f64,
+    ) -> Result<Option<MarketOrderChase>, CtpError> {
+        if order.order_type != "Market" {
+            return Ok(None);
+        }
+        let (price, label, chase) = match self.policy {
+            MarketOrderPolicy::Native => {
+                // 交易所未知时无法确认支持市价单，同样转换为限价单
+                if !exchange_id.is_empty() && exchange_supports_market_orders(exchange_id) {
+                    return Ok(None);
+                }
+                (limit_price(&order.direction, market)?, "limit_price", None)
+            }
+            MarketOrderPolicy::LimitPrice => (limit_price(&order.direction, market)?, "limit_price", None),
+            MarketOrderPolicy::Counterparty { ticks, max_chases } => {
+                let price = counterparty_price(&order.direction, market, ticks, price_tick)?;
+                (price, "counterparty", Some((ticks, max_chases)))
+            }
+        };
+
+        order.order_type = "Limit".to_string();
+        order.price = price;
+        if order.time_condition != "FOK" {
+            order.time_condition = "FAK".to_string();
+        }
+        order.tags.insert(MARKET_EMULATION_TAG.to_string(), label.to_string());
+        tracing::info!("市价单 {} 转换为限价单 @ {} ({})", order.instrument_id, price, label);
+
+        Ok(chase.map(|(ticks, max_chases)| MarketOrderChase {
+            order: order.clone(),
+            ticks,
+            price_tick,
+            chases_left: max_chases,
+        }))
+    }
+
+    /// 登记已报出的模拟单，追单次数用完后不再跟踪
+    pub fn track(&self, order_ref: &str, chase: MarketOrderChase) {
+        if chase.chases_left > 0 {
+            self.state.lock().unwrap().tracked.insert(order_ref.to_string(), chase);
+        }
+    }
+
+    /// 报单回报：模拟单结束且有未成交部分时加入待追单
+    pub fn on_order(&self, status: &OrderStatus) {
+        let finished = matches!(
+            status.status,
+            OrderStatusType::AllTraded
+                | OrderStatusType::Canceled
+                | OrderStatusType::Cancelled
+                | OrderStatusType::PartTradedNotQueueing
+                | OrderStatusType::NoTradeNotQueueing
+        );
+        if !finished {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let Some(mut chase) = state.tracked.remove(&status.order_ref) else {
+            return;
+        };
+        if status.volume_left > 0 {
+            chase.order.volume = status.volume_left;
+            state.pending.push(chase);
+        }
+    }
+
+    pub fn has_pending_chases(&self) -> bool {
+        !self.state.lock().unwrap().pending.is_empty()
+    }
+
+    /// 取出待追单
+    pub fn take_chases(&self) -> Vec<MarketOrderChase> {
+        std::mem::take(&mut self.state.lock().unwrap().pending)
+    }
+}
+
+fn valid_price(price: f64) -> bool {
+    price > 0.0 && price < f64::MAX
+}
+
+/// 涨跌停价：买入取涨停，卖出取跌停
+fn limit_price(direction: &str, market: Option<&MarketData>) -> Result<f64, CtpError> {
+    let market = market.ok_or_else(|| CtpError::StateError("没有可用行情，无法确定涨跌停价".to_string()))?;
+    let price = if direction == "Buy" { market.upper_limit_price } else { market.lower_limit_price };
+    if !valid_price(price) {
+        return Err(CtpError::StateError(format!("合约 {} 缺少涨跌停价", market.instrument_id)));
+    }
+    Ok(price)
+}
+
+/// 对手价加若干跳，不超过涨跌停；对手盘无报价（如封板）时取涨跌停价
+pub(crate) fn counterparty_price(direction: &str, market: Option<&MarketData>, ticks: u32, price_tick: f64) -> Result<f64, CtpError> {
+    let limit = limit_price(direction, market)?;
+    let Some(market) = market else {
+        return Ok(limit);
+    };
+    let offset = ticks as f64 * price_tick.max(0.0);
+    let price = if direction == "Buy" {
+        valid_price(market.ask_price).then(|| (market.ask_price + offset).min(limit))
+    } else {
+        valid_price(market.bid_price).then(|| (market.bid_price - offset).max(limit))
+    };
+    Ok(price.unwrap_or(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn market(exchange_id: &str) -> MarketData {
+        MarketData {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: exchange_id.to_string(),
+            last_price: 3500.0,
+            pre_settlement_price: 3490.0,
+            pre_close_price: 3490.0,
+            pre_open_interest: 0.0,
+            open_price: 3495.0,
+            highest_price: 3510.0,
+            lowest_price: 3480.0,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0.0,
+            close_price: 0.0,
+            settlement_price: 0.0,
+            upper_limit_price: 3800.0,
+            lower_limit_price: 3200.0,
+            bid_price: 3499.0,
+            bid_volume: 10,
+            ask_price: 3501.0,
+            ask_volume: 10,
+            average_price: 3500.0,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            trading_day: "20250102".to_string(),
+        }
+    }
+
+    fn market_order(direction: &str, volume: u32) -> OrderInput {
+        OrderInput {
+            instrument_id: "rb2501".to_string(),
+            direction: direction.to_string(),
+            offset: "Open".to_string(),
+            price: 0.0,
+            volume,
+            order_type: "Market".to_string(),
+            time_condition: "GFD".to_string(),
+            volume_condition: "Any".to_string(),
+            min_volume: 1,
+            contingent_condition: "Immediately".to_string(),
+            stop_price: 0.0,
+            force_close_reason: "NotForceClose".to_string(),
+            is_auto_suspend: false,
+            tags: Default::default(),
+        }
+    }
+
+    fn status(order_ref: &str, status: OrderStatusType, volume_left: u32) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_ref.to_string(),
+            order_id: order_ref.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: crate::ctp::OrderDirection::Buy,
+            offset_flag: crate::ctp::OffsetFlag::Open,
+            price: 3503.0,
+            limit_price: 3503.0,
+            volume: 5,
+            volume_total_original: 5,
+            volume_traded: 5 - volume_left,
+            volume_left,
+            volume_total: volume_left as i32,
+            status,
+            submit_time: Local::now(),
+            insert_time: "09:30:00".to_string(),
+            update_time: Local::now(),
+            front_id: 1,
+            session_id: 1,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: false,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_native_policy_emulates_only_where_rejected() {
+        let emulator = MarketOrderEmulator::default();
+
+        let mut order = market_order("Buy", 1);
+        assert!(emulator.emulate(&mut order, "DCE", Some(&market("DCE")), 1.0).unwrap().is_none());
+        assert_eq!(order.order_type, "Market");
+
+        let mut order = market_order("Sell", 1);
+        assert!(emulator.emulate(&mut order, "SHFE", Some(&market("SHFE")), 1.0).unwrap().is_none());
+        assert_eq!((order.order_type.as_str(), order.price), ("Limit", 3200.0));
+        assert_eq!(order.time_condition, "FAK");
+        assert_eq!(order.tags[MARKET_EMULATION_TAG], "limit_price");
+
+        // 行情不带交易所代码时按合约信息中的交易所判断
+        let mut order = market_order("Buy", 1);
+        assert!(emulator.emulate(&mut order, "INE", Some(&market("")), 1.0).unwrap().is_none());
+        assert_eq!((order.order_type.as_str(), order.price), ("Limit", 3800.0));
+
+        // 交易所未知时不报真市价单
+        let mut order = market_order("Buy", 1);
+        assert!(emulator.emulate(&mut order, "", Some(&market("")), 1.0).unwrap().is_none());
+        assert_eq!(order.order_type, "Limit");
+
+        // 缺少行情时无法确定价格
+        let mut order = market_order("Buy", 1);
+        assert!(MarketOrderEmulator::new(MarketOrderPolicy::LimitPrice).emulate(&mut order, "DCE", None, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_counterparty_policy_chases_remaining_volume() {
+        let emulator = MarketOrderEmulator::new(MarketOrderPolicy::Counterparty { ticks: 2, max_chases: 1 });
+        let mut order = market_order("Buy", 5);
+        let chase = emulator.emulate(&mut order, "DCE", Some(&market("DCE")), 1.0).unwrap().unwrap();
+        assert_eq!(order.price, 3503.0);
+        emulator.track("1", chase);
+
+        // 部分成交后剩余撤单，进入追单
+        emulator.on_order(&status("1", OrderStatusType::PartTradedQueueing, 3));
+        assert!(!emulator.has_pending_chases());
+        emulator.on_order(&status("1", OrderStatusType::Canceled, 3));
+        let mut chases = emulator.take_chases();
+        assert_eq!(chases.len(), 1);
+
+        let mut moved = market("DCE");
+        moved.ask_price = 3799.0;
+        let chase = &mut chases[0];
+        chase.reprice(Some(&moved)).unwrap();
+        assert_eq!((chase.order.volume, chase.order.price, chase.chases_left), (3, 3800.0, 0));
+
+        // 追单次数用完后不再跟踪
+        emulator.track("2", chase.clone());
+        emulator.on_order(&status("2", OrderStatusType::Canceled, 1));
+        assert!(emulator.take_chases().is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+use crate::ctp::models::{InstrumentInfo, MarketDataTick};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 行情中没有交易所代码且未登记合约时的归类
+pub const UNKNOWN_EXCHANGE: &str = "UNKNOWN";
+
+/// 市场概览配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketOverviewConfig {
+    /// 重新统计的间隔
+    pub refresh_interval: Duration,
+    /// 每个交易所保留的涨幅、跌幅榜数量
+    pub top_movers: usize,
+}
+
+impl Default for MarketOverviewConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: Duration::from_secs(3),
+            top_movers: 5,
+        }
+    }
+}
+
+/// 涨跌榜条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentMove {
+    pub instrument_id: String,
+    pub last_price: f64,
+    pub change_percent: f64,
+    pub volume: i64,
+    pub turnover: f64,
+}
+
+/// 单个交易所的汇总统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeSummary {
+    pub exchange_id: String,
+    pub instrument_count: usize,
+    pub total_volume: i64,
+    pub total_turnover: f64,
+    pub up_count: usize,
+    pub down_count: usize,
+    pub flat_count: usize,
+    /// 涨幅榜，按涨幅降序
+    pub top_gainers: Vec<InstrumentMove>,
+    /// 跌幅榜，按跌幅降序
+    pub top_losers: Vec<InstrumentMove>,
+}
+
+/// 市场概览快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketOverviewSnapshot {
+    /// 按成交额降序
+    pub exchanges: Vec<ExchangeSummary>,
+    pub instrument_count: usize,
+    pub computed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Default)]
+struct OverviewInner {
+    /// 合约 -> (交易所, 最新行情)
+    ticks: HashMap<String, (String, MarketDataTick)>,
+    /// 合约查询得到的交易所，行情不带交易所代码时使用
+    exchanges: HashMap<String, String>,
+    snapshot: MarketOverviewSnapshot,
+}
+
+/// 市场概览统计
+///
+/// 行情回调写入各订阅合约的最新行情，按固定节奏汇总为各交易所的
+/// 成交量、成交额、涨跌家数和涨跌幅榜，供市场概览面板查询
+#[derive(Clone)]
+pub struct MarketOverview {
+    config: MarketOverviewConfig,
+    inner: Arc<Mutex<OverviewInner>>,
+    refresh_started: Arc<AtomicBool>,
+}
+
+impl MarketOverview {
+    pub fn new() -> Self {
+        Self::with_config(MarketOverviewConfig::default())
+    }
+
+    pub fn with_config(config: MarketOverviewConfig) -> Self {
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(OverviewInner::default())),
+            refresh_started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 登记合约所属交易所
+    pub fn register_instruments(&self, instruments: &[InstrumentInfo]) {
+        let mut inner = self.inner.lock().unwrap();
+        for instrument in instruments {
+            if !instrument.exchange_id.is_empty() {
+                inner
+                    .exchanges
+                    .insert(instrument.instrument_id.clone(), instrument.exchange_id.clone());
+            }
+        }
+    }
+
+    /// 更新合约最新行情，`exchange_id` 为空时按登记的合约信息归类
+    pub fn update(&self, exchange_id: &str, tick: MarketDataTick) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let exchange_id = if exchange_id.is_empty() {
+            inner
+                .exchanges
+                .get(&tick.instrument_id)
+                .cloned()
+                .unwrap_or_else(|| UNKNOWN_EXCHANGE.to_string())
+        } else {
+            exchange_id.to_string()
+        };
+        inner.ticks.insert(tick.instrument_id.clone(), (exchange_id, tick));
+    }
+
+    /// 移除合约（取消订阅时调用）
+    pub fn remove(&self, instrument_id: &str) {
+        self.inner.lock().unwrap().ticks.remove(instrument_id);
+    }
+
+    /// 最近一次统计结果，尚未统计过时立即统计
+    pub fn snapshot(&self) -> MarketOverviewSnapshot {
+        {
+            let inner = self.inner.lock().unwrap();
+            if inner.snapshot.computed_at.is_some() {
+                return inner.snapshot.clone();
+            }
+        }
+        self.refresh()
+    }
+
+    /// 重新统计并保存结果
+    pub fn refresh(&self) -> MarketOverviewSnapshot {
+        let mut inner = self.inner.lock().unwrap();
+        let snapshot = compute(&inner.ticks, self.config.top_movers);
+        inner.snapshot = snapshot.clone();
+        snapshot
+    }
+
+    /// 启动定时统计任务，重复调用不会重复启动；客户端释放后任务自动退出
+    pub fn start_refresh(&self) -> bool {
+        if self.refresh_started.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        let config = self.config.clone();
+        let inner = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.refresh_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            tracing::info!("市场概览统计任务已启动，间隔 {:?}", config.refresh_interval);
+            loop {
+                interval.tick().await;
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                let mut inner = inner.lock().unwrap();
+                inner.snapshot = compute(&inner.ticks, config.top_movers);
+            }
+            tracing::info!("市场概览统计任务已退出");
+        });
+        true
+    }
+}
+
+impl Default for MarketOverview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn compute(ticks: &HashMap<String, (String, MarketDataTick)>, top_movers: usize) -> MarketOverviewSnapshot {
+    let mut by_exchange: HashMap<&str, Vec<&MarketDataTick>> = HashMap::new();
+    for (exchange_id, tick) in ticks.values() {
+        by_exchange.entry(exchange_id.as_str()).or_default().push(tick);
+    }
+
+    let mut exchanges: Vec<ExchangeSummary> = by_exchange
+        .into_iter()
+        .map(|(exchange_id, ticks)| summarize(exchange_id, &ticks, top_movers))
+        .collect();
+    exchanges.sort_by(|a, b| {
+        b.total_turnover
+            .total_cmp(&a.total_turnover)
+            .then_with(|| a.exchange_id.cmp(&b.exchange_id))
+    });
+
+    MarketOverviewSnapshot {
+        exchanges,
+        instrument_count: ticks.len(),
+        computed_at: Some(chrono::Utc::now()),
+    }
+}
+
+fn summarize(exchange_id: &str, ticks: &[&MarketDataTick], top_movers: usize) -> ExchangeSummary {
+    let mut summary = ExchangeSummary {
+        exchange_id: exchange_id.to_string(),
+        instrument_count: ticks.len(),
+        total_volume: 0,
+        total_turnover: 0.0,
+        up_count: 0,
+        down_count: 0,
+        flat_count: 0,
+        top_gainers: Vec::new(),
+        top_losers: Vec::new(),
+    };
+
+    let mut moves: Vec<InstrumentMove> = Vec::with_capacity(ticks.len());
+    for tick in ticks {
+        summary.total_volume += tick.volume;
+        summary.total_turnover += tick.turnover;
+        // 没有昨收价时涨跌幅无意义，按平盘计
+        let change = if tick.pre_close_price > 0.0 { tick.change_percent } else { 0.0 };
+        if change > 0.0 {
+            summary.up_count += 1;
+        } else if change < 0.0 {
+            summary.down_count += 1;
+        } else {
+            summary.flat_count += 1;
+        }
+        moves.push(InstrumentMove {
+            instrument_id: tick.instrument_id.clone(),
+            last_price: tick.last_price,
+            change_percent: change,
+            volume: tick.volume,
+            turnover: tick.turnover,
+        });
+    }
+
+    moves.sort_by(|a, b| {
+        b.change_percent
+            .total_cmp(&a.change_percent)
+            .then_with(|| a.instrument_id.cmp(&b.instrument_id))
+    });
+    summary.top_gainers = moves
+        .iter()
+        .filter(|m| m.change_percent > 0.0)
+        .take(top_movers)
+        .cloned()
+        .collect();
+    summary.top_losers = moves
+        .iter()
+        .rev()
+        .filter(|m| m.change_percent < 0.0)
+        .take(top_movers)
+        .cloned()
+        .collect();
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(instrument_id: &str, pre_close_price: f64, last_price: f64, volume: i64) -> MarketDataTick {
+        let change_amount = last_price - pre_close_price;
+        MarketDataTick {
+            instrument_id: instrument_id.to_string(),
+            last_price,
+            volume,
+            turnover: last_price * volume as f64,
+            open_interest: 0,
+            bid_price1: last_price,
+            bid_volume1: 1,
+            ask_price1: last_price,
+            ask_volume1: 1,
+            update_time: "10:00:00".to_string(),
+            update_millisec: 0,
+            change_percent: change_amount / pre_close_price * 100.0,
+            change_amount,
+            open_price: pre_close_price,
+            highest_price: last_price,
+            lowest_price: last_price,
+            pre_close_price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_exchange_summary_and_movers() {
+        let overview = MarketOverview::with_config(MarketOverviewConfig {
+            refresh_interval: Duration::from_secs(1),
+            top_movers: 1,
+        });
+        overview.update("SHFE", tick("rb2501", 3500.0, 3570.0, 100));
+        overview.update("SHFE", tick("hc2501", 3300.0, 3267.0, 50));
+        overview.update("SHFE", tick("cu2501", 70000.0, 70700.0, 10));
+        overview.update("SHFE", tick("al2501", 20000.0, 20000.0, 5));
+        overview.update("DCE", tick("m2501", 3000.0, 2940.0, 200));
+
+        let snapshot = overview.refresh();
+        assert_eq!(snapshot.instrument_count, 5);
+        assert_eq!(snapshot.exchanges.len(), 2);
+
+        let shfe = &snapshot.exchanges[0];
+        assert_eq!(shfe.exchange_id, "SHFE");
+        assert_eq!(shfe.total_volume, 165);
+        assert_eq!((shfe.up_count, shfe.down_count, shfe.flat_count), (2, 1, 1));
+        assert_eq!(shfe.top_gainers.len(), 1);
+        assert_eq!(shfe.top_gainers[0].instrument_id, "rb2501");
+        assert_eq!(shfe.top_losers[0].instrument_id, "hc2501");
+
+        overview.remove("m2501");
+        assert_eq!(overview.snapshot().exchanges.len(), 2);
+        assert_eq!(overview.refresh().exchanges.len(), 1);
+    }
+
+    #[test]
+    fn test_exchange_fallback_from_registered_instruments() {
+        let overview = MarketOverview::new();
+        overview.update("", tick("rb2501", 3500.0, 3510.0, 1));
+        assert_eq!(overview.snapshot().exchanges[0].exchange_id, UNKNOWN_EXCHANGE);
+
+        let mut instrument: InstrumentInfo = serde_json::from_value(serde_json::json!({
+            "instrument_id": "rb2501", "exchange_id": "SHFE", "instrument_name": "螺纹钢2501",
+            "product_id": "rb", "product_class": "Futures", "delivery_year": 2025, "delivery_month": 1,
+            "max_market_order_volume": 30, "min_market_order_volume": 1, "max_limit_order_volume": 500,
+            "min_limit_order_volume": 1, "volume_multiple": 10, "price_tick": 1.0,
+            "create_date": "", "open_date": "", "expire_date": "", "start_delivery_date": "",
+            "end_delivery_date": "", "is_trading": true, "underlying_instrument": "",
+            "strike_price": 0.0, "underlying_multiple": 1.0, "long_margin_ratio": 0.1,
+            "short_margin_ratio": 0.1
+        }))
+        .unwrap();
+        overview.register_instruments(std::slice::from_ref(&instrument));
+        overview.update("", tick("rb2501", 3500.0, 3520.0, 2));
+        let snapshot = overview.refresh();
+        assert_eq!(snapshot.exchanges.len(), 1);
+        assert_eq!(snapshot.exchanges[0].exchange_id, "SHFE");
+
+        instrument.exchange_id.clear();
+        overview.register_instruments(&[instrument]);
+        overview.update("", tick("rb2501", 3500.0, 3520.0, 2));
+        assert_eq!(overview.refresh().exchanges[0].exchange_id, "SHFE");
+    }
+}
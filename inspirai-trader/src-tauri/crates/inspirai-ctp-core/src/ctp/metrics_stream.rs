@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::ctp::{ClientState, QualityLevel};
+use crate::logging::MetricsSnapshot;
+
+/// 指标推送的前端事件名
+pub const METRICS_EVENT: &str = "metrics-update";
+/// 默认推送间隔
+pub const DEFAULT_METRICS_INTERVAL: Duration = Duration::from_millis(1000);
+/// 最小推送间隔，避免界面被指标刷屏
+pub const MIN_METRICS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 日志系统指标（不含时间戳，便于判断是否变化）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggingMetrics {
+    pub logs_written_total: u64,
+    pub logs_dropped_total: u64,
+    pub success_rate: f64,
+    pub average_latency_ms: f64,
+    pub p95_latency_ms: f64,
+    pub queue_size: usize,
+    pub disk_usage_bytes: u64,
+    pub error_count: u64,
+}
+
+impl From<&MetricsSnapshot> for LoggingMetrics {
+    fn from(snapshot: &MetricsSnapshot) -> Self {
+        Self {
+            logs_written_total: snapshot.logs_written_total,
+            logs_dropped_total: snapshot.logs_dropped_total,
+            success_rate: snapshot.success_rate,
+            average_latency_ms: snapshot.average_latency_ms,
+            p95_latency_ms: snapshot.p95_latency_ms,
+            queue_size: snapshot.queue_size,
+            disk_usage_bytes: snapshot.disk_usage_bytes,
+            error_count: snapshot.error_count,
+        }
+    }
+}
+
+/// 交易侧指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingMetrics {
+    pub state: ClientState,
+    pub degraded: bool,
+    pub subscribed_instruments: usize,
+    pub connection_quality: QualityLevel,
+    /// 交易端平均往返时延（毫秒）
+    pub td_rtt_avg_ms: f64,
+    pub event_backlog: usize,
+    pub events_dispatched: u64,
+}
+
+/// 指标帧
+///
+/// 推送给窗口时只携带相对该窗口上一帧有变化的部分，为空表示未变化；
+/// `seq` 单调递增，窗口订阅时拿到的是完整帧
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsFrame {
+    pub seq: u64,
+    pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    pub logging: Option<LoggingMetrics>,
+    pub trading: Option<TradingMetrics>,
+}
+
+impl MetricsFrame {
+    /// 相对上一帧的差量，无变化时返回 None
+    pub fn diff(&self, previous: &MetricsFrame) -> Option<MetricsFrame> {
+        let logging = self.logging.clone().filter(|m| previous.logging.as_ref() != Some(m));
+        let trading = self.trading.clone().filter(|m| previous.trading.as_ref() != Some(m));
+        if logging.is_none() && trading.is_none() {
+            return None;
+        }
+        Some(MetricsFrame {
+            seq: self.seq,
+            timestamp: self.timestamp,
+            logging,
+            trading,
+        })
+    }
+}
+
+/// 窗口的指标订阅信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSubscription {
+    pub window: String,
+    pub interval_ms: u64,
+}
+
+struct WindowMetrics {
+    interval: Duration,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// 指标推送流
+///
+/// 采集任务把最新指标写入 watch 通道，每个订阅窗口按各自的间隔取最新帧，
+/// 只推送变化的部分；慢窗口只会跳过中间帧，不会积压
+#[derive(Clone)]
+pub struct MetricsStream {
+    sender: Arc<watch::Sender<MetricsFrame>>,
+    windows: Arc<Mutex<HashMap<String, WindowMetrics>>>,
+}
+
+impl Default for MetricsStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsStream {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(MetricsFrame::default());
+        Self {
+            sender: Arc::new(sender),
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 发布新采集的指标，有变化时递增序号并返回 true
+    pub fn publish(&self, logging: Option<LoggingMetrics>, trading: Option<TradingMetrics>) -> bool {
+        self.sender.send_if_modified(|frame| {
+            if frame.seq > 0 && frame.logging == logging && frame.trading == trading {
+                return false;
+            }
+            frame.seq += 1;
+            frame.timestamp = Some(chrono::Utc::now());
+            frame.logging = logging;
+            frame.trading = trading;
+            true
+        })
+    }
+
+    pub fn latest(&self) -> MetricsFrame {
+        self.sender.borrow().clone()
+    }
+
+    /// 为窗口订阅指标，重复订阅替换原有间隔；返回当前完整帧
+    ///
+    /// `emit` 返回 false 表示推送失败，该窗口的推送任务随即退出
+    pub fn subscribe<F>(&self, window: &str, interval: Duration, emit: F) -> MetricsFrame
+    where
+        F: Fn(&MetricsFrame) -> bool + Send + 'static,
+    {
+        let interval = interval.max(MIN_METRICS_INTERVAL);
+        let mut receiver = self.sender.subscribe();
+        let mut last_sent = receiver.borrow_and_update().clone();
+        let initial = last_sent.clone();
+
+        let label = window.to_string();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                match receiver.has_changed() {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => break,
+                }
+                let frame = receiver.borrow_and_update().clone();
+                if let Some(delta) = frame.diff(&last_sent) {
+                    if !emit(&delta) {
+                        tracing::warn!("向窗口 {} 推送指标失败，停止推送", label);
+                        break;
+                    }
+                }
+                last_sent = frame;
+            }
+        });
+
+        let previous = self
+            .windows
+            .lock()
+            .unwrap()
+            .insert(window.to_string(), WindowMetrics { interval, task });
+        if let Some(previous) = previous {
+            previous.task.abort();
+        }
+        tracing::debug!("窗口 {} 订阅指标，间隔 {:?}", window, interval);
+        initial
+    }
+
+    pub fn unsubscribe(&self, window: &str) -> bool {
+        match self.windows.lock().unwrap().remove(window) {
+            Some(subscription) => {
+                subscription.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn subscriptions(&self) -> Vec<MetricsSubscription> {
+        let mut list: Vec<MetricsSubscription> = self
+            .windows
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(window, subscription)| MetricsSubscription {
+                window: window.clone(),
+                interval_ms: subscription.interval.as_millis() as u64,
+            })
+            .collect();
+        list.sort_by(|a, b| a.window.cmp(&b.window));
+        list
+    }
+
+    /// 采集间隔取所有订阅中最短的，无订阅时返回 None
+    pub fn collect_interval(&self) -> Option<Duration> {
+        self.windows.lock().unwrap().values().map(|s| s.interval).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trading(backlog: usize) -> TradingMetrics {
+        TradingMetrics {
+            state: ClientState::LoggedIn,
+            degraded: false,
+            subscribed_instruments: 2,
+            connection_quality: QualityLevel::Good,
+            td_rtt_avg_ms: 12.5,
+            event_backlog: backlog,
+            events_dispatched: 100,
+        }
+    }
+
+    #[test]
+    fn test_diff_keeps_changed_sections() {
+        let stream = MetricsStream::new();
+        assert!(stream.publish(None, Some(trading(0))));
+        assert!(!stream.publish(None, Some(trading(0))));
+        let first = stream.latest();
+
+        assert!(stream.publish(None, Some(trading(5))));
+        let second = stream.latest();
+        assert_eq!(second.seq, first.seq + 1);
+
+        let delta = second.diff(&first).unwrap();
+        assert_eq!(delta.trading.unwrap().event_backlog, 5);
+        assert!(delta.logging.is_none());
+        assert!(second.diff(&second).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_window_receives_deltas_at_its_cadence() {
+        let stream = MetricsStream::new();
+        stream.publish(None, Some(trading(0)));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let initial = stream.subscribe("main", Duration::from_millis(50), move |frame| tx.send(frame.clone()).is_ok());
+        assert_eq!(initial.trading.as_ref().unwrap().event_backlog, 0);
+        assert_eq!(stream.collect_interval(), Some(MIN_METRICS_INTERVAL));
+
+        // 同一间隔内的多次更新只推送最新一帧
+        stream.publish(None, Some(trading(3)));
+        stream.publish(None, Some(trading(7)));
+        tokio::time::sleep(MIN_METRICS_INTERVAL + Duration::from_millis(100)).await;
+        let delta = rx.recv().await.unwrap();
+        assert_eq!(delta.trading.unwrap().event_backlog, 7);
+        assert!(rx.try_recv().is_err());
+
+        assert!(stream.unsubscribe("main"));
+        assert!(stream.collect_interval().is_none());
+    }
+}
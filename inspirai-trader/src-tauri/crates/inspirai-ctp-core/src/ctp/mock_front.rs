@@ -0,0 +1,580 @@
+//! 测试用模拟前置
+//!
+//! 在 ctp2rs 回调层模拟 SimNow 前置：按脚本返回认证、登录和订阅应答，推送行情，
+//! 并用撮合模拟器生成报单、成交和撤单回报。回调直接驱动真实的 MdSpiImpl /
+//! TraderSpiImpl，CI 中无需外部服务即可端到端测试 连接→登录→订阅→报单→成交。
+//! 回调在调用线程上同步执行，测试从事件通道按顺序断言即可
+
+use crate::ctp::{
+    models::MarketDataTick,
+    sim_matching::{FillModel, MatchingSimulator, SimFill, SimLatencyConfig},
+    utils::{encoding::string_to_ctp_string, DataConverter},
+    CtpError, OrderRequest,
+};
+use ctp2rs::v1alpha1::{
+    CThostFtdcDepthMarketDataField, CThostFtdcInputOrderActionField, CThostFtdcOrderField,
+    CThostFtdcRspAuthenticateField, CThostFtdcRspInfoField, CThostFtdcRspUserLoginField,
+    CThostFtdcSpecificInstrumentField, CThostFtdcTradeField, MdSpi, TraderSpi,
+};
+use std::collections::{HashMap, HashSet};
+
+/// 登录失败：不合法的登录
+pub const MOCK_ERROR_INVALID_LOGIN: i32 = 3;
+/// 订阅失败：找不到合约
+pub const MOCK_ERROR_INSTRUMENT_NOT_FOUND: i32 = 16;
+/// 撤单失败：撤单找不到相应报单
+pub const MOCK_ERROR_ORDER_NOT_FOUND: i32 = 25;
+
+/// 模拟前置的应答脚本
+#[derive(Debug, Clone)]
+pub struct MockFrontScript {
+    pub broker_id: String,
+    pub investor_id: String,
+    pub trading_day: String,
+    pub front_id: i32,
+    pub session_id: i32,
+    pub max_order_ref: i32,
+    /// 登录应答返回的错误（错误码，错误信息）
+    pub login_error: Option<(i32, String)>,
+    /// 订阅时应答“找不到合约”的合约
+    pub unknown_instruments: HashSet<String>,
+    /// 报单被柜台拒绝的合约及错误（错误码，错误信息）
+    pub order_rejections: HashMap<String, (i32, String)>,
+    pub fill_model: FillModel,
+    /// 延迟、部分成交和流控拒单注入
+    pub latency: SimLatencyConfig,
+}
+
+impl Default for MockFrontScript {
+    fn default() -> Self {
+        Self {
+            broker_id: "9999".to_string(),
+            investor_id: "000000".to_string(),
+            trading_day: chrono::Local::now().format("%Y%m%d").to_string(),
+            front_id: 1,
+            session_id: 1,
+            max_order_ref: 0,
+            login_error: None,
+            unknown_instruments: HashSet::new(),
+            order_rejections: HashMap::new(),
+            fill_model: FillModel::Touch,
+            latency: SimLatencyConfig::default(),
+        }
+    }
+}
+
+impl MockFrontScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, broker_id: &str, investor_id: &str) -> Self {
+        self.broker_id = broker_id.to_string();
+        self.investor_id = investor_id.to_string();
+        self
+    }
+
+    pub fn with_trading_day(mut self, trading_day: &str) -> Self {
+        self.trading_day = trading_day.to_string();
+        self
+    }
+
+    pub fn with_session(mut self, front_id: i32, session_id: i32) -> Self {
+        self.front_id = front_id;
+        self.session_id = session_id;
+        self
+    }
+
+    pub fn with_login_error(mut self, error_id: i32, message: &str) -> Self {
+        self.login_error = Some((error_id, message.to_string()));
+        self
+    }
+
+    pub fn with_unknown_instrument(mut self, instrument_id: &str) -> Self {
+        self.unknown_instruments.insert(instrument_id.to_string());
+        self
+    }
+
+    pub fn with_order_rejection(mut self, instrument_id: &str, error_id: i32, message: &str) -> Self {
+        self.order_rejections
+            .insert(instrument_id.to_string(), (error_id, message.to_string()));
+        self
+    }
+
+    pub fn with_fill_model(mut self, fill_model: FillModel) -> Self {
+        self.fill_model = fill_model;
+        self
+    }
+
+    pub fn with_latency(mut self, latency: SimLatencyConfig) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+/// 模拟前置
+///
+/// 持有行情和交易两侧的 SPI，按调用顺序回放前置的应答与回报
+pub struct MockFront {
+    script: MockFrontScript,
+    md_spi: Box<dyn MdSpi>,
+    trader_spi: Box<dyn TraderSpi>,
+    simulator: MatchingSimulator,
+    /// 按报单引用记录的柜台报单
+    orders: HashMap<String, CThostFtdcOrderField>,
+    request_id: i32,
+    next_order_sys_id: u64,
+    next_trade_id: u64,
+}
+
+impl MockFront {
+    pub fn new(md_spi: impl MdSpi + 'static, trader_spi: impl TraderSpi + 'static, script: MockFrontScript) -> Self {
+        let simulator = MatchingSimulator::new(script.fill_model).with_latency(script.latency.clone());
+        Self {
+            script,
+            md_spi: Box::new(md_spi),
+            trader_spi: Box::new(trader_spi),
+            simulator,
+            orders: HashMap::new(),
+            request_id: 0,
+            next_order_sys_id: 1,
+            next_trade_id: 1,
+        }
+    }
+
+    pub fn script(&self) -> &MockFrontScript {
+        &self.script
+    }
+
+    /// 两侧前置连接成功，交易侧随后返回认证应答
+    pub fn connect(&mut self) {
+        tracing::info!("模拟前置: 连接");
+        self.md_spi.on_front_connected();
+        self.trader_spi.on_front_connected();
+
+        let mut auth = CThostFtdcRspAuthenticateField::default();
+        assign(&mut auth.BrokerID, &self.script.broker_id);
+        assign(&mut auth.UserID, &self.script.investor_id);
+        let request_id = self.next_request_id();
+        self.trader_spi.on_rsp_authenticate(Some(&auth), Some(&rsp_info(0, "")), request_id, true);
+    }
+
+    /// 两侧返回登录应答，脚本设置了登录错误时返回失败
+    pub fn login(&mut self) -> bool {
+        tracing::info!("模拟前置: 登录");
+        if let Some((error_id, message)) = self.script.login_error.clone() {
+            let info = rsp_info(error_id, &message);
+            let request_id = self.next_request_id();
+            self.md_spi.on_rsp_user_login(None, Some(&info), request_id, true);
+            let request_id = self.next_request_id();
+            self.trader_spi.on_rsp_user_login(None, Some(&info), request_id, true);
+            return false;
+        }
+
+        let mut login = CThostFtdcRspUserLoginField::default();
+        assign(&mut login.TradingDay, &self.script.trading_day);
+        assign(&mut login.LoginTime, &chrono::Local::now().format("%H:%M:%S").to_string());
+        assign(&mut login.BrokerID, &self.script.broker_id);
+        assign(&mut login.UserID, &self.script.investor_id);
+        assign(&mut login.SystemName, "MockFront");
+        assign(&mut login.MaxOrderRef, &self.script.max_order_ref.to_string());
+        login.FrontID = self.script.front_id;
+        login.SessionID = self.script.session_id;
+
+        let info = rsp_info(0, "");
+        let request_id = self.next_request_id();
+        self.md_spi.on_rsp_user_login(Some(&login), Some(&info), request_id, true);
+        let request_id = self.next_request_id();
+        self.trader_spi.on_rsp_user_login(Some(&login), Some(&info), request_id, true);
+        true
+    }
+
+    /// 逐个合约返回订阅应答
+    pub fn subscribe(&mut self, instruments: &[&str]) {
+        let request_id = self.next_request_id();
+        for (index, instrument_id) in instruments.iter().enumerate() {
+            let mut specific = CThostFtdcSpecificInstrumentField::default();
+            assign(&mut specific.InstrumentID, instrument_id);
+            let info = if self.script.unknown_instruments.contains(*instrument_id) {
+                rsp_info(MOCK_ERROR_INSTRUMENT_NOT_FOUND, "CTP:找不到合约")
+            } else {
+                rsp_info(0, "")
+            };
+            let is_last = index + 1 == instruments.len();
+            self.md_spi.on_rsp_sub_market_data(Some(&specific), Some(&info), request_id, is_last);
+        }
+    }
+
+    /// 推送一笔行情，并按该行情撮合挂单
+    pub fn push_tick(&mut self, tick: &MarketDataTick) {
+        let mut depth = CThostFtdcDepthMarketDataField::default();
+        assign(&mut depth.TradingDay, &self.script.trading_day);
+        assign(&mut depth.ActionDay, &self.script.trading_day);
+        assign(&mut depth.InstrumentID, &tick.instrument_id);
+        assign(&mut depth.UpdateTime, &tick.update_time);
+        depth.UpdateMillisec = tick.update_millisec;
+        depth.LastPrice = tick.last_price;
+        depth.PreClosePrice = tick.pre_close_price;
+        depth.OpenPrice = tick.open_price;
+        depth.HighestPrice = tick.highest_price;
+        depth.LowestPrice = tick.lowest_price;
+        depth.Volume = tick.volume as i32;
+        depth.Turnover = tick.turnover;
+        depth.OpenInterest = tick.open_interest as f64;
+        depth.BidPrice1 = tick.bid_price1;
+        depth.BidVolume1 = tick.bid_volume1;
+        depth.AskPrice1 = tick.ask_price1;
+        depth.AskVolume1 = tick.ask_volume1;
+        self.md_spi.on_rtn_depth_market_data(Some(&depth));
+
+        let fills = self.simulator.on_tick(tick);
+        self.apply_acknowledged();
+        self.apply_fills(fills);
+    }
+
+    /// 报单录入，柜台确认后回报未成交排队，可立即成交的部分随即回报成交
+    ///
+    /// 注入确认延迟时排队回报推迟到确认时间后的行情；注入流控时返回与
+    /// ReqOrderInsert 相同的错误码，不产生任何回调
+    pub fn insert_order(&mut self, order: &OrderRequest, order_ref: &str) -> Result<(), CtpError> {
+        let input = DataConverter::convert_order_request(order, &self.script.broker_id, &self.script.investor_id, order_ref)?;
+        let request_id = self.next_request_id();
+
+        if let Some((error_id, message)) = self.script.order_rejections.get(&order.instrument_id).cloned() {
+            tracing::info!("模拟前置: 拒绝报单 {} {}", order_ref, message);
+            let info = rsp_info(error_id, &message);
+            self.trader_spi.on_rsp_order_insert(Some(&input), Some(&info), request_id, true);
+            self.trader_spi.on_err_rtn_order_insert(Some(&input), Some(&info));
+            return Ok(());
+        }
+
+        let mut ctp_order = CThostFtdcOrderField {
+            BrokerID: input.BrokerID,
+            InvestorID: input.InvestorID,
+            InstrumentID: input.InstrumentID,
+            OrderRef: input.OrderRef,
+            Direction: input.Direction,
+            CombOffsetFlag: input.CombOffsetFlag,
+            CombHedgeFlag: input.CombHedgeFlag,
+            OrderPriceType: input.OrderPriceType,
+            TimeCondition: input.TimeCondition,
+            LimitPrice: input.LimitPrice,
+            VolumeTotalOriginal: input.VolumeTotalOriginal,
+            VolumeTotal: input.VolumeTotalOriginal,
+            RequestID: request_id,
+            FrontID: self.script.front_id,
+            SessionID: self.script.session_id,
+            OrderStatus: '3' as i8,
+            ..Default::default()
+        };
+        assign(&mut ctp_order.TradingDay, &self.script.trading_day);
+        assign(&mut ctp_order.OrderSysID, &format!("{:>12}", self.next_order_sys_id));
+        assign(&mut ctp_order.InsertTime, &chrono::Local::now().format("%H:%M:%S").to_string());
+        assign(&mut ctp_order.StatusMsg, "未成交");
+
+        let fills = self.simulator.submit(
+            order_ref,
+            &order.instrument_id,
+            order.direction,
+            order.price,
+            input.VolumeTotalOriginal,
+        )?;
+        self.next_order_sys_id += 1;
+        self.orders.insert(order_ref.to_string(), ctp_order);
+        self.apply_acknowledged();
+        self.apply_fills(fills);
+        Ok(())
+    }
+
+    /// 回报已确认报单的未成交排队状态
+    fn apply_acknowledged(&mut self) {
+        for order_ref in self.simulator.take_acknowledged() {
+            if let Some(ctp_order) = self.orders.get(&order_ref) {
+                self.trader_spi.on_rtn_order(Some(ctp_order));
+            }
+        }
+    }
+
+    /// 撤单，报单不存在或已全部成交时返回撤单失败应答
+    pub fn cancel_order(&mut self, order_ref: &str) {
+        let request_id = self.next_request_id();
+        if self.simulator.cancel(order_ref).is_none() {
+            let mut action = CThostFtdcInputOrderActionField::default();
+            assign(&mut action.OrderRef, order_ref);
+            let info = rsp_info(MOCK_ERROR_ORDER_NOT_FOUND, "CTP:撤单找不到相应报单");
+            self.trader_spi.on_rsp_order_action(Some(&action), Some(&info), request_id, true);
+            return;
+        }
+        if let Some(ctp_order) = self.orders.get_mut(order_ref) {
+            ctp_order.OrderStatus = '5' as i8;
+            assign(&mut ctp_order.StatusMsg, "已撤单");
+            let ctp_order = *ctp_order;
+            self.trader_spi.on_rtn_order(Some(&ctp_order));
+        }
+    }
+
+    /// 两侧前置断开，reason 为 CTP 断开原因码（如 0x1001）
+    pub fn disconnect(&mut self, reason: i32) {
+        tracing::info!("模拟前置: 断开 {:#x}", reason);
+        self.md_spi.on_front_disconnected(reason);
+        self.trader_spi.on_front_disconnected(reason);
+    }
+
+    /// 当前挂单的报单引用
+    pub fn resting_orders(&self) -> Vec<String> {
+        self.simulator
+            .resting_orders()
+            .into_iter()
+            .map(|order| order.order_id.clone())
+            .collect()
+    }
+
+    /// 按柜台顺序先回报报单状态，再回报成交
+    fn apply_fills(&mut self, fills: Vec<SimFill>) {
+        for fill in fills {
+            let Some(ctp_order) = self.orders.get_mut(&fill.order_id) else {
+                tracing::warn!("模拟前置: 成交找不到报单 {}", fill.order_id);
+                continue;
+            };
+            ctp_order.VolumeTraded += fill.volume;
+            ctp_order.VolumeTotal -= fill.volume;
+            if ctp_order.VolumeTotal <= 0 {
+                ctp_order.OrderStatus = '0' as i8;
+                assign(&mut ctp_order.StatusMsg, "全部成交");
+            } else {
+                ctp_order.OrderStatus = '1' as i8;
+                assign(&mut ctp_order.StatusMsg, "部分成交");
+            }
+            let ctp_order = *ctp_order;
+
+            let mut trade = CThostFtdcTradeField {
+                BrokerID: ctp_order.BrokerID,
+                InvestorID: ctp_order.InvestorID,
+                InstrumentID: ctp_order.InstrumentID,
+                OrderRef: ctp_order.OrderRef,
+                OrderSysID: ctp_order.OrderSysID,
+                TradingDay: ctp_order.TradingDay,
+                Direction: ctp_order.Direction,
+                OffsetFlag: ctp_order.CombOffsetFlag[0],
+                HedgeFlag: ctp_order.CombHedgeFlag[0],
+                Price: fill.price,
+                Volume: fill.volume,
+                ..Default::default()
+            };
+            assign(&mut trade.TradeID, &format!("{:>20}", self.next_trade_id));
+            assign(&mut trade.TradeTime, &chrono::Local::now().format("%H:%M:%S").to_string());
+            self.next_trade_id += 1;
+
+            self.trader_spi.on_rtn_order(Some(&ctp_order));
+            self.trader_spi.on_rtn_trade(Some(&trade));
+        }
+    }
+
+    fn next_request_id(&mut self) -> i32 {
+        self.request_id += 1;
+        self.request_id
+    }
+}
+
+/// 写入字符串字段，失败时只记录告警
+fn assign(field: &mut [i8], value: &str) {
+    if let Err(e) = string_to_ctp_string(value, field) {
+        tracing::warn!("模拟前置: 写入字段失败 {}: {}", value, e);
+    }
+}
+
+fn rsp_info(error_id: i32, message: &str) -> CThostFtdcRspInfoField {
+    let mut info = CThostFtdcRspInfoField {
+        ErrorID: error_id,
+        ..Default::default()
+    };
+    assign(&mut info.ErrorMsg, message);
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{
+        ClientState, CtpConfig, CtpEvent, MdSpiImpl, OffsetFlag, OrderContingentCondition, OrderDirection,
+        OrderForceCloseReason, OrderPriceType, OrderStatusType, OrderTimeCondition, OrderType, OrderVolumeCondition,
+        TraderSpiImpl,
+    };
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc;
+
+    fn mock_front(script: MockFrontScript) -> (MockFront, mpsc::UnboundedReceiver<CtpEvent>) {
+        let state = Arc::new(Mutex::new(ClientState::Disconnected));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let md_spi = MdSpiImpl::new(state.clone(), tx.clone(), CtpConfig::default());
+        let trader_spi = TraderSpiImpl::new(state, tx, CtpConfig::default());
+        (MockFront::new(md_spi, trader_spi, script), rx)
+    }
+
+    fn drain(rx: &mut mpsc::UnboundedReceiver<CtpEvent>) -> Vec<CtpEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    fn tick(instrument_id: &str, last_price: f64, bid: f64, ask: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: instrument_id.to_string(),
+            last_price,
+            volume: 100,
+            turnover: 0.0,
+            open_interest: 1000,
+            bid_price1: bid,
+            bid_volume1: 10,
+            ask_price1: ask,
+            ask_volume1: 10,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: last_price,
+            highest_price: last_price,
+            lowest_price: last_price,
+            pre_close_price: last_price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    fn buy_open(instrument_id: &str, price: f64, volume: u32) -> OrderRequest {
+        OrderRequest {
+            instrument_id: instrument_id.to_string(),
+            order_ref: String::new(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price,
+            volume,
+            order_type: OrderType::Limit,
+            price_type: OrderPriceType::Limit,
+            time_condition: OrderTimeCondition::GFD,
+            volume_condition: OrderVolumeCondition::Any,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_connect_login_subscribe_order_fill() {
+        let (mut front, mut rx) = mock_front(MockFrontScript::new().with_trading_day("20240115").with_session(3, 42));
+
+        front.connect();
+        assert!(front.login());
+        let events = drain(&mut rx);
+        assert!(matches!(events.first(), Some(CtpEvent::Connected)));
+        assert!(events.iter().any(|e| matches!(e, CtpEvent::LoginRequired)));
+        let logins: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                CtpEvent::LoginSuccess(login) => Some(login),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(logins.len(), 2);
+        assert!(logins.iter().all(|l| l.trading_day == "20240115" && l.front_id == 3 && l.session_id == 42));
+
+        // 未订阅合约的行情被行情 SPI 过滤
+        front.push_tick(&tick("rb2501", 3500.0, 3499.0, 3501.0));
+        assert!(drain(&mut rx).is_empty());
+
+        front.subscribe(&["rb2501"]);
+        front.push_tick(&tick("rb2501", 3500.0, 3499.0, 3501.0));
+        let events = drain(&mut rx);
+        assert!(matches!(&events[..], [CtpEvent::MarketData(t)] if t.last_price == 3500.0));
+
+        // 挂在买一，价格穿过后全部成交
+        front.insert_order(&buy_open("rb2501", 3499.0, 2), "1").unwrap();
+        let events = drain(&mut rx);
+        assert!(matches!(&events[..], [CtpEvent::OrderUpdate(o)] if o.status == OrderStatusType::NoTradeQueueing));
+        assert_eq!(front.resting_orders(), vec!["1".to_string()]);
+
+        front.push_tick(&tick("rb2501", 3498.0, 3497.0, 3498.0));
+        let events = drain(&mut rx);
+        assert!(matches!(events[0], CtpEvent::MarketData(_)));
+        match &events[1..] {
+            [CtpEvent::OrderUpdate(order), CtpEvent::TradeUpdate(trade)] => {
+                assert_eq!(order.status, OrderStatusType::AllTraded);
+                assert_eq!(order.volume_traded, 2);
+                assert_eq!(trade.order_id, "1");
+                assert_eq!(trade.volume, 2);
+                assert_eq!(trade.price, 3499.0);
+            }
+            other => panic!("意外的回报序列: {:?}", other),
+        }
+        assert!(front.resting_orders().is_empty());
+    }
+
+    #[test]
+    fn test_scripted_failures() {
+        let (mut front, mut rx) = mock_front(
+            MockFrontScript::new()
+                .with_order_rejection("ag2506", 31, "CTP:资金不足")
+                .with_login_error(MOCK_ERROR_INVALID_LOGIN, "CTP:不合法的登录"),
+        );
+        front.connect();
+        assert!(!front.login());
+        let events = drain(&mut rx);
+        assert!(events.iter().any(|e| matches!(e, CtpEvent::LoginFailed(msg) if msg.contains("不合法的登录"))));
+
+        front.insert_order(&buy_open("ag2506", 7000.0, 1), "7").unwrap();
+        let events = drain(&mut rx);
+        assert!(events.iter().any(|e| matches!(e, CtpEvent::OrderUpdate(o) if o.status_msg.contains("资金不足"))));
+        assert!(front.resting_orders().is_empty());
+
+        // 撤一笔不存在的报单，只有撤单失败应答，没有状态回报
+        front.cancel_order("404");
+        assert!(!drain(&mut rx).iter().any(|e| matches!(e, CtpEvent::OrderUpdate(_))));
+
+        front.disconnect(0x1001);
+        assert!(drain(&mut rx).iter().any(|e| matches!(e, CtpEvent::Disconnected)));
+    }
+
+    #[test]
+    fn test_injected_ack_delay_and_flow_control() {
+        let (mut front, mut rx) = mock_front(MockFrontScript::new().with_latency(SimLatencyConfig {
+            ack_delay_ms: 1000,
+            ..SimLatencyConfig::default()
+        }));
+        front.connect();
+        assert!(front.login());
+        front.subscribe(&["rb2501"]);
+        front.push_tick(&tick("rb2501", 3500.0, 3499.0, 3501.0));
+        drain(&mut rx);
+
+        // 确认前没有排队回报，确认时盘口可成交则随即成交
+        front.insert_order(&buy_open("rb2501", 3501.0, 1), "1").unwrap();
+        assert!(drain(&mut rx).is_empty());
+        let mut later = tick("rb2501", 3501.0, 3500.0, 3501.0);
+        later.update_time = "09:30:01".to_string();
+        front.push_tick(&later);
+        let events = drain(&mut rx);
+        assert!(matches!(
+            &events[..],
+            [CtpEvent::MarketData(_), CtpEvent::OrderUpdate(queued), CtpEvent::OrderUpdate(done), CtpEvent::TradeUpdate(_)]
+                if queued.status == OrderStatusType::NoTradeQueueing && done.status == OrderStatusType::AllTraded
+        ));
+
+        let (mut front, mut rx) = mock_front(MockFrontScript::new().with_latency(SimLatencyConfig {
+            flow_control_reject_probability: 1.0,
+            ..SimLatencyConfig::default()
+        }));
+        assert!(matches!(
+            front.insert_order(&buy_open("rb2501", 3501.0, 1), "1"),
+            Err(CtpError::CtpApiError { code: crate::ctp::sim_matching::SIM_FLOW_CONTROL_ERROR, .. })
+        ));
+        assert!(drain(&mut rx).is_empty());
+        assert!(front.resting_orders().is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+// CTP 交易组件模块
+// 基于 ctp2rs 库的高级封装
+
+pub mod client;
+pub mod config;
+pub mod config_manager;
+pub mod error;
+pub mod events;
+pub mod models;
+pub mod ffi;
+pub mod ctp_sys;
+pub mod logger;
+pub mod spi;
+pub mod utils;
+pub mod services;
+pub mod market_data_manager;
+pub mod subscription_manager;
+pub mod order_manager;
+pub mod trading_service;
+pub mod account_service;
+pub mod position_manager;
+pub mod settlement_manager;
+pub mod query_service;
+pub mod pipeline_trace;
+pub mod session_health;
+pub mod connection_quality;
+pub mod diagnostics;
+pub mod reconciliation;
+pub mod strategy_guard;
+pub mod tick_retention;
+pub mod tick_compaction;
+pub mod sim_matching;
+pub mod bar_import;
+pub mod market_data_export;
+pub mod orderbook_heatmap;
+pub mod risk_report;
+pub mod order_validation;
+pub mod hotkeys;
+pub mod timeline;
+pub mod rejection_breaker;
+pub mod event_bridge;
+pub mod action_recorder;
+pub mod market_overview;
+pub mod idempotency;
+pub mod instance_coordinator;
+pub mod trade_analytics;
+pub mod round_trip;
+pub mod error_explainer;
+pub mod dead_man_switch;
+pub mod price_limit;
+pub mod order_sizing;
+pub mod order_preview;
+pub mod continuous_kline;
+pub mod task_manager;
+pub mod correlation;
+pub mod metrics_stream;
+pub mod webhook;
+pub mod notifier;
+pub mod stress_test;
+pub mod hedging;
+pub mod order_flow;
+pub mod workspace_store;
+pub mod backup;
+pub mod funds_monitor;
+pub mod bracket;
+pub mod trailing_stop;
+pub mod strategy_deploy;
+pub mod decision_audit;
+pub mod market_data_source;
+pub mod order_router;
+pub mod market_order;
+pub mod trading_switchboard;
+pub mod event_bus;
+pub mod self_test;
+pub mod api_usage;
+pub mod compliance_monitor;
+pub mod programmatic_filing;
+pub mod hot_path;
+pub mod client_order_id;
+pub mod trade_dedup;
+pub mod settlement_prices;
+pub mod currency;
+pub mod retention;
+pub mod rollover;
+pub mod risk_presets;
+pub mod storage;
+pub mod trading_session;
+pub mod data_quality;
+pub mod wire_log;
+pub mod strategy_engine;
+pub mod strategy_warmup;
+#[cfg(feature = "ts")]
+pub mod ts_bindings;
+// 测试用模拟前置，下游集成测试通过 mock_front 特性启用
+#[cfg(any(test, feature = "mock_front"))]
+pub mod mock_front;
+// OpenCTP TTS 一致性测试，通过 integration_tests 特性启用
+#[cfg(any(test, feature = "integration_tests"))]
+pub mod conformance;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod production_config_test;
+
+#[cfg(test)]
+mod simple_production_test;
+
+#[cfg(test)]
+mod test_path_issue;
+
+#[cfg(test)]
+mod test_serde;
+
+pub use client::{CtpClient, ClientState, ConnectionStats, HealthStatus, ConfigInfo, ConnectionManager, AuthManager, SubscriptionClient, OrderClient, QueryClient};
+pub use config::{CtpConfig, ConnectionMode, Environment};
+pub use config_manager::{ConfigManager, ExtendedCtpConfig};
+pub use error::CtpError;
+pub use events::{CtpEvent, EventHandler, EventListener, DefaultEventListener};
+pub use logger::{LoggerManager, PerformanceMonitor};
+pub use models::*;
+pub use spi::{MdSpiImpl, TraderSpiImpl};
+pub use utils::{DataConverter, DepthQuote, gb18030_to_utf8, utf8_to_gb18030, ConversionPools, PoolStats};
+pub use market_data_manager::{MarketDataManager, MarketDataFilter, MarketDataStats, PriceChangeFilter, VolumeFilter};
+pub use subscription_manager::{SubscriptionManager, SubscriptionInfo, SubscriptionStatus, SubscriptionConfig, SubscriptionStats, SubscriptionPriority};
+pub use services::market_data_service::MarketDataService;
+pub use order_manager::{OrderManager, OrderInfo, OrderStats, TagAttribution, OcoGroup, OcoGroupStatus, OrderExpiry, EXPIRE_AT_TAG};
+pub use trading_service::{TradingService, TradingStats};
+pub use account_service::{AccountService, FundStats, RiskMetrics, RiskStatus, AccountSummary};
+pub use position_manager::{PositionManager, PositionDetail, PositionStats};
+pub use settlement_manager::{SettlementManager, Settlement, SettlementSummary, SettlementReport, decode_settlement_bytes, parse_settlement_summary};
+pub use query_service::{QueryService, QueryType, QueryState, QueryCache, QueryOptions};
+pub use session_health::{SessionHealth, SharedSessionHealth, SideStatus, OperatingMode};
+pub use connection_quality::{ConnectionQuality, ConnectionQualityReport, LinkQuality, QualityLevel, RttStats, DisconnectRecord, SharedConnectionQuality};
+pub use diagnostics::{DiagnosticEvent, DiagnosticHub, DiagnosticSeverity, DiagnosticSource};
+pub use reconciliation::{Reconciler, ReconciliationSummary, PositionAdjustment};
+pub use strategy_guard::{StrategyGuard, StrategyBudget, StrategyStatus, BreakerState, STRATEGY_TAG};
+pub use tick_retention::{TickRetentionConfig, TickHistory, TickHistoryBuffer, TickSpillStore, JsonLinesSpillStore, RetentionStats};
+pub use tick_compaction::{TickCompactor, CompactionConfig, CompactionReport, DayIndex, DayIndexEntry, ArchivedBar, StorageGranularity, DEFAULT_RAW_DIR, DEFAULT_ARCHIVE_DIR};
+pub use bar_import::{BarImporter, BarImportConfig, CsvColumnMapping, ContractNaming, SymbolCase, ImportReport, ImportIssue};
+pub use market_data_export::{MarketDataExporter, MarketDataExportRequest, ExportFormat, ExportProgress, ExportSummary};
+pub use orderbook_heatmap::{OrderBookHeatmap, HeatmapConfig, HeatmapColumn, DepthSnapshot, DepthLevel};
+pub use risk_report::{RiskReportGenerator, RiskReportConfig, DailyRiskReport, InstrumentRisk, MarkSource, ConcentrationEntry, StressScenario, DEFAULT_REPORT_DIR};
+pub use order_validation::{OrderValidator, OrderValidationResult, ValidationContext, ValidationIssue, IssueSeverity};
+pub use hotkeys::{HotkeyController, HotkeyConfig, HotkeyAction, HotkeyOutcome, HotkeyStatus, SOURCE_TAG, HOTKEY_SOURCE};
+pub use timeline::{Timeline, TimelineEntry, TimelineKind, TimelineQuery, TimelinePage, DEFAULT_TIMELINE_DIR};
+pub use rejection_breaker::{RejectionBreaker, RejectionBreakerConfig, RejectionAlert, RejectionReason, SourceBreakerStatus, MANUAL_SOURCE};
+pub use event_bridge::{EventBridge, EventTopic, EventLag, WindowSubscription, BridgeSnapshot};
+pub use action_recorder::{ActionRecorder, ActionRecordingStatus, RecordedAction, ReplayReport, ReplayStep, ReplayStepStatus, load_actions, DEFAULT_ACTION_DIR};
+pub use market_overview::{MarketOverview, MarketOverviewConfig, MarketOverviewSnapshot, ExchangeSummary, InstrumentMove, UNKNOWN_EXCHANGE};
+pub use idempotency::{IdempotencyStore, IdempotentRequest, DEFAULT_IDEMPOTENCY_RETENTION};
+pub use instance_coordinator::{InstanceCoordinator, CoordinatorConfig, InstanceRole, InstanceStatus, LeaseInfo, DEFAULT_INSTANCE_DIR};
+pub use trade_analytics::{analyze as analyze_trades, pnl_heatmap, AnalyticsQuery, HeatmapCell, HoldingBucket, PerformanceStats, PnlHeatmap, PnlHeatmapReport, TradeAnalyticsReport, DEFAULT_HEATMAP_UTC_OFFSET_HOURS};
+pub use round_trip::{RoundTripBook, RoundTrip, PairingMethod, DEFAULT_ROUND_TRIP_DIR};
+pub use error_explainer::{ErrorExplainer, ErrorExplanation, ErrorCategory, BrokerHint, DEFAULT_ERROR_HINTS_FILE};
+pub use dead_man_switch::{DeadManSwitch, DeadManConfig, DeadManStatus, DeadManTrigger, DeadManReport, DEAD_MAN_SOURCE};
+pub use price_limit::{PriceLimitTracker, LimitStatus, LimitState};
+pub use order_sizing::{margin_per_lot, max_open_volume, MaxOpenVolume, DEFAULT_MARGIN_UTILIZATION};
+pub use order_preview::{preview_order, OrderPreview, PositionSides, RateCache, DEFAULT_RATE_CACHE_TTL};
+pub use continuous_kline::{ContinuousKlineBuilder, ContinuousKlineRequest, ContinuousKline, ContinuousBar, RollEvent, AdjustmentMethod};
+pub use task_manager::{TaskManager, TaskHandle, TaskInfo, TaskState, CancelToken, TASK_PROGRESS_EVENT};
+pub use correlation::{CorrelationRegistry, CORRELATION_TAG, DEFAULT_CORRELATION_CAPACITY};
+pub use metrics_stream::{MetricsStream, MetricsFrame, MetricsSubscription, LoggingMetrics, TradingMetrics, METRICS_EVENT, DEFAULT_METRICS_INTERVAL, MIN_METRICS_INTERVAL};
+pub use webhook::{WebhookDispatcher, WebhookConfig, WebhookEndpoint, WebhookEventKind, WebhookPayload, WebhookDelivery, DeliveryStatus, DEFAULT_WEBHOOK_CONFIG_FILE};
+pub use notifier::{Notifier, NotifierConfig, NotificationChannel, ChannelTarget, NotificationKind, NotificationTemplates, NotificationRecord, NotificationStatus, DEFAULT_NOTIFIER_CONFIG_FILE};
+pub use stress_test::{run_stress_test, StressTestScenario, StressTestResult, StressedPosition, PriceShock, DEFAULT_MARGIN_CALL_RATIO};
+pub use hedging::{suggest_hedges, HedgeConfig, ExposureCap, NetExposure, HedgeSuggestion, HedgeReport, DEFAULT_HEDGE_CONFIG_FILE};
+pub use order_flow::{OrderFlowAnalyzer, OrderFlowConfig, OrderFlowTick, OrderFlowSnapshot, AggressorSide};
+pub use workspace_store::{WorkspaceStore, WorkspaceSnapshot, WorkspaceSummary, WorkspaceVersion, DEFAULT_WORKSPACE_DIR, DEFAULT_WORKSPACE_PROFILE, DEFAULT_WORKSPACE_VERSIONS};
+pub use backup::{BackupManager, BackupConfig, BackupInfo, BackupManifest, BackupFileEntry, RestoreReport, DEFAULT_BACKUP_CONFIG_FILE};
+pub use funds_monitor::{FundsMonitor, FundsMonitorConfig, FundsAnomaly, FundsAnomalyKind};
+pub use bracket::{BracketBook, BracketSpec, BracketOrder, BracketChild, BracketLeg, BracketStatus, BracketChildStatus, BracketTrigger, BRACKET_TAG};
+pub use trailing_stop::{TrailingStopManager, TrailingStop, TrailingStopSpec, TrailingStopStatus, TrailingStopTrigger, TrailMode, TRAILING_STOP_TAG, TRAILING_STOP_ATR_FEED, DEFAULT_TRAILING_STOP_FILE, atr_feed_id};
+pub use strategy_deploy::{StrategyRegistry, StrategyPackage, StrategyEntryPoint, StrategyInstance, DeploymentRecord, DeploymentAction, DEFAULT_STRATEGY_REGISTRY_FILE};
+pub use decision_audit::{DecisionRecorder, DecisionRecord, DecisionTick, DecisionBar, DecisionPosition, DECISION_TAG};
+pub use market_data_source::{MarketDataSource, TickSink, SourceMerger, SourceStats, SourceSwitch, CTP_SOURCE};
+pub use order_router::{OrderRouter, CtpOrderRouter, RouterCapabilities, CTP_ROUTER};
+pub use market_order::{MarketOrderEmulator, MarketOrderPolicy, MarketOrderChase, MARKET_EMULATION_TAG};
+pub use trading_switchboard::{TradingSwitchboard, DisabledTarget, SwitchScope, SwitchSource, DEFAULT_SWITCHBOARD_FILE};
+pub use event_bus::{EventBus, BusTopic, BusSubscriber, SubscriberLag, DEFAULT_SUBSCRIBER_CAPACITY};
+pub use self_test::{run_self_test, SelfTestOptions, SelfTestReport, SelfTestStage, StageOutcome, StageResult};
+pub use api_usage::{ApiUsageTracker, ApiUsageLimits, ApiUsageSnapshot, ApiRequestKind, UsageLevel, UsageMeter, UsageWarning, DEFAULT_API_USAGE_CONFIG_FILE};
+pub use compliance_monitor::{ComplianceMonitor, ComplianceConfig, ComplianceStatus, ComplianceWarning, ACCOUNT_SCOPE, DEFAULT_COMPLIANCE_CONFIG_FILE};
+pub use programmatic_filing::{ProgrammaticTradingInfo, RelayMode, TerminalInfo, UserSystemInfo};
+pub use hot_path::{HotPathRuntime, LatencyProbe, RuntimeTuning, SchedulerLatencyStats, pin_current_thread, DEFAULT_RUNTIME_TUNING_FILE};
+pub use client_order_id::{ClientOrderIds, ClientOrderRecord, CLIENT_ORDER_ID_TAG, DEFAULT_CLIENT_ORDER_DIR};
+pub use trade_dedup::{TradeDeduplicator, trade_key, DEFAULT_TRADE_DEDUP_DIR};
+pub use settlement_prices::{SettlementPriceStore, SettlementPrice, SettlementSource, DailyMark, mark_position, DEFAULT_SETTLEMENT_PRICE_FILE};
+pub use currency::{AccountBalances, CurrencyBalance, AggregatedEquity, FxConverter, FxRateTable, normalize_currency, BASE_CURRENCY, DEFAULT_FX_RATE_FILE};
+pub use retention::{RetentionManager, RetentionPolicy, RetentionReport, RetentionItem, RetentionCategory, DEFAULT_RETENTION_CONFIG_FILE};
+pub use rollover::{plan_rollovers, RolloverConfig, RolloverExecution, RolloverManager, RolloverPlan, RolloverReport, RolloverStatus, PendingRolloverOrder, DEFAULT_ROLLOVER_CONFIG_FILE, DEFAULT_ROLLOVER_DIR, ROLLOVER_TAG, ROLLOVER_LEG_TAG};
+pub use risk_presets::{diff_risk_params, PresetScheduleRule, PresetSwitch, PresetSwitchSource, RiskParamChange, RiskPreset, RiskPresetConfig, RiskPresetManager, DEFAULT_RISK_PRESETS_FILE};
+pub use storage::{migrate_storage, open_backend, DuckDbStore, MigrationReport, SeriesKey, SqliteStore, Storage, StorageBackend, StorageBackendKind, StorageConfig, StorageDataKind, DEFAULT_DUCKDB_PATH, DEFAULT_SQLITE_PATH, DEFAULT_STORAGE_CONFIG_FILE};
+pub use trading_session::{product_of, BarWindow, SessionConfig, SessionRange, TradingSessions};
+pub use wire_log::{debug_to_json, WireDirection, WireLogConfig, WireLogStats, WireLogger, WireRecord, DEFAULT_WIRE_LOG_CONFIG_FILE, DEFAULT_WIRE_LOG_DIR};
+pub use data_quality::{DataQualityConfig, DataQualityIssue, DataQualityMonitor, DataQualityTransition};
+pub use strategy_engine::{AutoPausePolicy, BarScheduler, BarSubscription, Strategy, StrategyBar, StrategyEngine, StrategyEngineConfig, StrategyMode, StrategyPauseEvent, DEFAULT_STRATEGY_ENGINE_CONFIG_FILE};
+pub use strategy_warmup::{HistorySource, WarmupConfig, WarmupLoader, WarmupReport, WarmupSeries};
+pub use sim_matching::{MatchingSimulator, FillModel, Liquidity, SimOrder, SimFill, SimLatencyConfig, SIM_FLOW_CONTROL_ERROR};
+#[cfg(any(test, feature = "mock_front"))]
+pub use mock_front::{MockFront, MockFrontScript};
+#[cfg(any(test, feature = "integration_tests"))]
+pub use conformance::{ConformanceConfig, ConformanceReport, ConformanceRunner, ConformanceStage, ConformanceStageResult};
+pub use pipeline_trace::{PipelineTracer, PipelineTraceStats, StageLatencyStats, TickTrace, TraceStage};
+
+/// CTP 组件版本信息
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 初始化 CTP 组件
+pub fn init() -> Result<(), CtpError> {
+    tracing::info!("初始化 CTP 交易组件 v{}", VERSION);
+    
+    // 尝试使用默认配置检查动态库
+    let default_config = CtpConfig::default();
+    if let (Some(md_path), Some(td_path)) = (&default_config.md_dynlib_path, &default_config.td_dynlib_path) {
+        if md_path.exists() && td_path.exists() {
+            ffi::check_ctp_libraries(md_path, td_path)?;
+        } else {
+            tracing::warn!("CTP 动态库文件不存在，跳过检查");
+        }
+    } else {
+        tracing::warn!("未设置 CTP 动态库路径，跳过检查");
+    }
+    
+    tracing::info!("CTP 交易组件初始化完成");
+    Ok(())
+}
+
+/// 使用配置初始化 CTP 组件
+pub fn init_with_config(config: &ExtendedCtpConfig) -> Result<(), CtpError> {
+    // 初始化日志系统
+    LoggerManager::init(
+        &config.logging.level,
+        Some(std::path::Path::new(&config.logging.file_path)),
+        config.logging.console,
+        config.ctp.environment,
+    )?;
+    
+    // 使用配置中的库路径检查动态库
+    if let (Some(md_path), Some(td_path)) = (&config.ctp.md_dynlib_path, &config.ctp.td_dynlib_path) {
+        ffi::check_ctp_libraries(md_path, td_path)?;
+    } else {
+        tracing::warn!("配置中未设置 CTP 动态库路径，跳过检查");
+    }
+    
+    tracing::info!("使用配置初始化 CTP 组件完成");
+    tracing::info!("环境: {:?}", config.ctp.environment);
+    tracing::info!("经纪商: {}", config.ctp.broker_id);
+    
+    Ok(())
+}
\ No newline at end of file
@@ -14,6 +14,7 @@ pub use trading::{
 
 /// 登录响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(rename_all = "camelCase")]
 pub struct LoginResponse {
     pub trading_day: String,
@@ -28,16 +29,19 @@ pub struct LoginResponse {
 
 /// 行情数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct MarketDataTick {
     /// 合约代码
     pub instrument_id: String,
     /// 最新价
     pub last_price: f64,
     /// 成交量
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
     pub volume: i64,
     /// 成交额
     pub turnover: f64,
     /// 持仓量
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
     pub open_interest: i64,
     /// 买一价
     pub bid_price1: f64,
@@ -63,10 +67,20 @@ pub struct MarketDataTick {
     pub lowest_price: f64,
     /// 昨收盘
     pub pre_close_price: f64,
+    /// 涨跌停价与贴板/封板状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_limit: Option<crate::ctp::price_limit::LimitStatus>,
+    /// 链路追踪时间戳（仅在开启追踪时存在）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<crate::ctp::pipeline_trace::TickTrace>,
+    /// 行情来源，为空表示 CTP 行情
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// 买卖方向
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderDirection {
     /// 买入
     Buy,
@@ -85,6 +99,7 @@ impl std::fmt::Display for OrderDirection {
 
 /// 开平仓标志
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OffsetFlag {
     /// 开仓
     Open,
@@ -98,6 +113,7 @@ pub enum OffsetFlag {
 
 /// 订单类型
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderType {
     /// 限价单
     Limit,
@@ -120,6 +136,7 @@ pub enum TimeCondition {
 
 /// 订单状态
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderStatusType {
     /// 未知
     Unknown,
@@ -141,8 +158,12 @@ pub enum OrderStatusType {
     Touched,
 }
 
+/// 订单标签（策略名、信号ID、下单来源等）
+pub type OrderTags = HashMap<String, String>;
+
 /// 订单请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct OrderRequest {
     /// 合约代码
     pub instrument_id: String,
@@ -174,6 +195,38 @@ pub struct OrderRequest {
     pub force_close_reason: OrderForceCloseReason,
     /// 自动挂起标志
     pub is_auto_suspend: bool,
+    /// 报单有效期，设置后覆盖时间条件和成交量条件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
+    /// 自定义标签，随成交传递
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: OrderTags,
+}
+
+impl OrderRequest {
+    /// 设置报单有效期，同步时间条件和成交量条件
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        let (time_condition, volume_condition) = time_in_force.conditions();
+        self.time_condition = time_condition;
+        // FAK 可附带最小成交量
+        if !(time_in_force == TimeInForce::FAK && self.volume_condition == OrderVolumeCondition::Min) {
+            self.volume_condition = volume_condition;
+        }
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    /// 实际生效的报单有效期，未显式设置时由时间条件和成交量条件推断
+    pub fn time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+            .unwrap_or_else(|| TimeInForce::from_conditions(self.time_condition, self.volume_condition))
+    }
+
+    /// 添加标签
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
 }
 
 /// 撤单请求
@@ -196,6 +249,7 @@ pub enum ActionFlag {
 
 /// 订单状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct OrderStatus {
     /// 订单引用
     pub order_ref: String,
@@ -243,10 +297,14 @@ pub struct OrderStatus {
     pub frozen_margin: f64,
     /// 冻结手续费
     pub frozen_commission: f64,
+    /// 下单时附带的标签
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: OrderTags,
 }
 
 /// 成交记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct TradeRecord {
     /// 成交编号
     pub trade_id: String,
@@ -264,10 +322,17 @@ pub struct TradeRecord {
     pub volume: i32,
     /// 成交时间
     pub trade_time: String,
+    /// 交易所代码
+    #[serde(default)]
+    pub exchange_id: String,
+    /// 所属订单的标签
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: OrderTags,
 }
 
 /// 持仓方向
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum PositionDirection {
     /// 多头
     Long,
@@ -286,6 +351,7 @@ impl std::fmt::Display for PositionDirection {
 
 /// 持仓信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct Position {
     /// 合约代码
     pub instrument_id: String,
@@ -311,9 +377,13 @@ pub struct Position {
 
 /// 账户信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct AccountInfo {
     /// 账户代码
     pub account_id: String,
+    /// 币种，不同币种的资金不能直接相加
+    #[serde(default = "default_currency_id")]
+    pub currency_id: String,
     /// 可用资金
     pub available: f64,
     /// 账户余额
@@ -336,6 +406,10 @@ pub struct AccountInfo {
     pub risk_ratio: f64,
 }
 
+fn default_currency_id() -> String {
+    crate::ctp::currency::BASE_CURRENCY.to_string()
+}
+
 /// 登录凭据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -349,6 +423,7 @@ pub struct LoginCredentials {
 
 /// 订单价格类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderPriceType {
     /// 限价
     Limit,
@@ -362,6 +437,7 @@ pub enum OrderPriceType {
 
 /// 订单时间条件
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderTimeCondition {
     /// 立即完成，否则撤销
     IOC,
@@ -379,6 +455,7 @@ pub enum OrderTimeCondition {
 
 /// 订单成交量条件
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderVolumeCondition {
     /// 任意数量
     Any,
@@ -388,8 +465,43 @@ pub enum OrderVolumeCondition {
     All,
 }
 
+/// 报单有效期
+///
+/// CTP 没有独立的有效期字段，FAK、FOK 由时间条件 IOC 与成交量条件组合表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum TimeInForce {
+    /// 当日有效
+    GFD,
+    /// 立即成交剩余撤销
+    FAK,
+    /// 全部成交否则撤销
+    FOK,
+}
+
+impl TimeInForce {
+    /// 对应的时间条件和成交量条件
+    pub fn conditions(self) -> (OrderTimeCondition, OrderVolumeCondition) {
+        match self {
+            TimeInForce::GFD => (OrderTimeCondition::GFD, OrderVolumeCondition::Any),
+            TimeInForce::FAK => (OrderTimeCondition::IOC, OrderVolumeCondition::Any),
+            TimeInForce::FOK => (OrderTimeCondition::IOC, OrderVolumeCondition::All),
+        }
+    }
+
+    /// 由时间条件和成交量条件推断，非 IOC 的一律视为当日有效
+    pub fn from_conditions(time_condition: OrderTimeCondition, volume_condition: OrderVolumeCondition) -> Self {
+        match (time_condition, volume_condition) {
+            (OrderTimeCondition::IOC, OrderVolumeCondition::All) => TimeInForce::FOK,
+            (OrderTimeCondition::IOC, _) => TimeInForce::FAK,
+            _ => TimeInForce::GFD,
+        }
+    }
+}
+
 /// 订单触发条件
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderContingentCondition {
     /// 立即
     Immediately,
@@ -403,6 +515,7 @@ pub enum OrderContingentCondition {
 
 /// 强平原因
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub enum OrderForceCloseReason {
     /// 非强平
     NotForceClose,
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 // 订单输入
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct OrderInput {
     pub instrument_id: String,
     pub direction: String, // Buy/Sell
@@ -16,6 +17,9 @@ pub struct OrderInput {
     pub stop_price: f64,
     pub force_close_reason: String, // NotForceClose/LackDeposit/ClientOverPositionLimit
     pub is_auto_suspend: bool,
+    /// 自定义标签（策略名、信号ID、下单来源等）
+    #[serde(default)]
+    pub tags: std::collections::HashMap<String, String>,
 }
 
 // 订单引用
@@ -24,6 +28,9 @@ pub struct OrderRef {
     pub order_ref: String,
     pub front_id: i32,
     pub session_id: i32,
+    /// 跨会话稳定的客户端订单号
+    #[serde(default)]
+    pub client_order_id: String,
 }
 
 // 成交记录
@@ -142,4 +149,7 @@ pub struct RiskParams {
     pub stop_loss_ratio: f64,
     pub auto_take_profit: bool,
     pub take_profit_ratio: f64,
+    /// 封板时拒绝缺少对手盘的委托，否则仅提示
+    #[serde(default)]
+    pub block_limit_locked: bool,
 }
\ No newline at end of file
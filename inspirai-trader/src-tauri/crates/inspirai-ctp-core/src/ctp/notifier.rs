@@ -0,0 +1,600 @@
+use crate::ctp::{CtpError, CtpEvent, OffsetFlag};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认通知渠道配置文件
+pub const DEFAULT_NOTIFIER_CONFIG_FILE: &str = "./config/notifications.toml";
+/// 保留的发送记录条数
+pub const NOTIFICATION_LOG_CAPACITY: usize = 200;
+const TELEGRAM_API_BASE: &str = "https://api.telegram.org";
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 通知类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    OrderFilled,
+    /// 当日盈亏（平仓 + 持仓）穿过配置的阈值
+    PnlThreshold,
+    ConnectionLost,
+    /// 测试发送
+    Test,
+}
+
+/// 渠道目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelTarget {
+    /// 钉钉群机器人，secret 为加签密钥
+    #[serde(rename = "dingtalk")]
+    DingTalk {
+        webhook_url: String,
+        #[serde(default)]
+        secret: Option<String>,
+    },
+    /// 飞书群机器人，secret 为签名校验密钥
+    Feishu {
+        webhook_url: String,
+        #[serde(default)]
+        secret: Option<String>,
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+        /// 自建 Bot API 服务地址，默认官方地址
+        #[serde(default)]
+        api_base: Option<String>,
+    },
+}
+
+impl ChannelTarget {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::DingTalk { .. } => "钉钉",
+            Self::Feishu { .. } => "飞书",
+            Self::Telegram { .. } => "Telegram",
+        }
+    }
+}
+
+/// 通知渠道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    pub id: String,
+    pub target: ChannelTarget,
+    /// 订阅的通知，为空表示全部
+    #[serde(default)]
+    pub events: Vec<NotificationKind>,
+    /// 每分钟最多发送条数，0 表示不限；钉钉机器人上限为每分钟 20 条
+    #[serde(default = "default_rate_limit")]
+    pub rate_limit_per_minute: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rate_limit() -> u32 {
+    20
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl NotificationChannel {
+    pub fn accepts(&self, kind: NotificationKind) -> bool {
+        kind == NotificationKind::Test || (self.enabled && (self.events.is_empty() || self.events.contains(&kind)))
+    }
+}
+
+/// 消息模板，`{name}` 为占位符，未知占位符原样保留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationTemplates {
+    /// 可用占位符：instrument_id、direction、offset、volume、price、trade_time、order_id
+    pub order_filled: String,
+    /// 可用占位符：pnl、threshold、crossed（升破/跌破）
+    pub pnl_threshold: String,
+    /// 可用占位符：reason
+    pub connection_lost: String,
+}
+
+impl Default for NotificationTemplates {
+    fn default() -> Self {
+        Self {
+            order_filled: "【成交】{instrument_id} {direction}{offset} {volume} 手 @ {price}（{trade_time}）".to_string(),
+            pnl_threshold: "【盈亏提醒】当日盈亏 {pnl} 已{crossed}阈值 {threshold}".to_string(),
+            connection_lost: "【连接断开】{reason}".to_string(),
+        }
+    }
+}
+
+impl NotificationTemplates {
+    pub fn render(&self, kind: NotificationKind, values: &HashMap<&str, String>) -> String {
+        let template = match kind {
+            NotificationKind::OrderFilled => &self.order_filled,
+            NotificationKind::PnlThreshold => &self.pnl_threshold,
+            NotificationKind::ConnectionLost => &self.connection_lost,
+            NotificationKind::Test => return "Inspirai Trader 测试消息，收到即表示通知渠道配置正确".to_string(),
+        };
+        values
+            .iter()
+            .fold(template.clone(), |text, (key, value)| text.replace(&format!("{{{}}}", key), value))
+    }
+}
+
+/// 通知配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifierConfig {
+    pub channels: Vec<NotificationChannel>,
+    pub templates: NotificationTemplates,
+    /// 当日盈亏提醒阈值，可正可负
+    pub pnl_thresholds: Vec<f64>,
+}
+
+impl NotifierConfig {
+    /// 从 TOML 文件加载，文件不存在时返回默认配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("通知渠道配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("通知渠道配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        let mut ids: Vec<&str> = Vec::new();
+        for channel in &self.channels {
+            if channel.id.trim().is_empty() {
+                return Err(CtpError::ConfigError("通知渠道 ID 不能为空".to_string()));
+            }
+            if ids.contains(&channel.id.as_str()) {
+                return Err(CtpError::ConfigError(format!("通知渠道 ID 重复: {}", channel.id)));
+            }
+            ids.push(&channel.id);
+            match &channel.target {
+                ChannelTarget::DingTalk { webhook_url, .. } | ChannelTarget::Feishu { webhook_url, .. } => {
+                    if !webhook_url.starts_with("http://") && !webhook_url.starts_with("https://") {
+                        return Err(CtpError::ConfigError(format!("通知渠道 {} 地址无效", channel.id)));
+                    }
+                }
+                ChannelTarget::Telegram { bot_token, chat_id, .. } => {
+                    if bot_token.is_empty() || chat_id.is_empty() {
+                        return Err(CtpError::ConfigError(format!("通知渠道 {} 缺少 bot_token 或 chat_id", channel.id)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 发送结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationStatus {
+    Sent,
+    /// 超出渠道每分钟条数，未发送
+    RateLimited,
+    Failed,
+}
+
+/// 发送记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRecord {
+    pub channel_id: String,
+    pub kind: NotificationKind,
+    pub status: NotificationStatus,
+    pub text: String,
+    pub error: Option<String>,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+struct NotifierInner {
+    config: NotifierConfig,
+    client: reqwest::Client,
+    /// 各渠道最近一分钟的发送时间
+    windows: HashMap<String, VecDeque<Instant>>,
+    last_pnl: Option<f64>,
+    records: VecDeque<NotificationRecord>,
+}
+
+/// 群机器人通知
+///
+/// 将成交、盈亏阈值和断线事件按模板渲染后发送到钉钉、飞书或 Telegram，
+/// 各渠道独立限速，超出部分直接丢弃并记录
+#[derive(Clone)]
+pub struct Notifier {
+    inner: Arc<Mutex<NotifierInner>>,
+}
+
+impl Notifier {
+    pub fn new(config: NotifierConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(NotifierInner {
+                config,
+                client: build_client(),
+                windows: HashMap::new(),
+                last_pnl: None,
+                records: VecDeque::new(),
+            })),
+        }
+    }
+
+    pub fn config(&self) -> NotifierConfig {
+        self.inner.lock().unwrap().config.clone()
+    }
+
+    pub fn update_config(&self, config: NotifierConfig) -> Result<(), CtpError> {
+        config.validate()?;
+        self.inner.lock().unwrap().config = config;
+        Ok(())
+    }
+
+    /// 最近的发送记录，按时间倒序
+    pub fn records(&self, limit: usize) -> Vec<NotificationRecord> {
+        self.inner.lock().unwrap().records.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// 从客户端事件生成通知，返回发送的渠道数
+    pub fn notify_event(&self, event: &CtpEvent) -> usize {
+        match event {
+            CtpEvent::TradeUpdate(trade) => {
+                let offset = match trade.offset_flag {
+                    OffsetFlag::Open => "开仓",
+                    OffsetFlag::Close => "平仓",
+                    OffsetFlag::CloseToday => "平今",
+                    OffsetFlag::CloseYesterday => "平昨",
+                };
+                let values = HashMap::from([
+                    ("instrument_id", trade.instrument_id.clone()),
+                    ("direction", trade.direction.to_string()),
+                    ("offset", offset.to_string()),
+                    ("volume", trade.volume.to_string()),
+                    ("price", trade.price.to_string()),
+                    ("trade_time", trade.trade_time.clone()),
+                    ("order_id", trade.order_id.clone()),
+                ]);
+                self.notify(NotificationKind::OrderFilled, &values)
+            }
+            CtpEvent::Disconnected => {
+                self.notify(NotificationKind::ConnectionLost, &HashMap::from([("reason", "CTP 前置连接断开".to_string())]))
+            }
+            CtpEvent::DegradedModeEntered(reason) => self.notify(
+                NotificationKind::ConnectionLost,
+                &HashMap::from([("reason", format!("交易端不可用，进入仅行情模式: {}", reason))]),
+            ),
+            CtpEvent::AccountUpdate(account) | CtpEvent::QueryAccountResult(account) => {
+                self.observe_pnl(account.close_profit + account.position_profit)
+            }
+            _ => 0,
+        }
+    }
+
+    /// 记录最新当日盈亏，穿过阈值时发送提醒；首个观测值只作为基准
+    pub fn observe_pnl(&self, pnl: f64) -> usize {
+        let crossings: Vec<(f64, &str)> = {
+            let mut inner = self.inner.lock().unwrap();
+            let previous = inner.last_pnl.replace(pnl);
+            let Some(previous) = previous else {
+                return 0;
+            };
+            inner
+                .config
+                .pnl_thresholds
+                .iter()
+                .filter_map(|&threshold| {
+                    if previous < threshold && pnl >= threshold {
+                        Some((threshold, "升破"))
+                    } else if previous > threshold && pnl <= threshold {
+                        Some((threshold, "跌破"))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+        crossings
+            .into_iter()
+            .map(|(threshold, crossed)| {
+                let values = HashMap::from([
+                    ("pnl", format!("{:.2}", pnl)),
+                    ("threshold", format!("{:.2}", threshold)),
+                    ("crossed", crossed.to_string()),
+                ]);
+                self.notify(NotificationKind::PnlThreshold, &values)
+            })
+            .sum()
+    }
+
+    /// 渲染并发送到订阅该类别的渠道，需在 tokio 运行时中调用；返回发送的渠道数
+    pub fn notify(&self, kind: NotificationKind, values: &HashMap<&str, String>) -> usize {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("不在异步运行时中，丢弃通知: {:?}", kind);
+            return 0;
+        };
+        let (client, text, channels) = {
+            let mut inner = self.inner.lock().unwrap();
+            let text = inner.config.templates.render(kind, values);
+            let candidates: Vec<NotificationChannel> = inner
+                .config
+                .channels
+                .iter()
+                .filter(|c| kind != NotificationKind::Test && c.accepts(kind))
+                .cloned()
+                .collect();
+            let mut channels = Vec::new();
+            for channel in candidates {
+                if inner.acquire(&channel) {
+                    channels.push(channel);
+                } else {
+                    tracing::debug!("通知渠道 {} 超出限速，丢弃: {}", channel.id, text);
+                    inner.record(&channel.id, kind, NotificationStatus::RateLimited, &text, None);
+                }
+            }
+            (inner.client.clone(), text, channels)
+        };
+
+        let count = channels.len();
+        for channel in channels {
+            let notifier = self.clone();
+            let client = client.clone();
+            let text = text.clone();
+            runtime.spawn(async move {
+                let result = send(&client, &channel.target, &text).await;
+                notifier.finish(&channel, kind, &text, result);
+            });
+        }
+        count
+    }
+
+    /// 向指定渠道发送测试消息并等待结果，不受订阅和限速影响
+    pub async fn test_send(&self, channel_id: &str) -> Result<(), CtpError> {
+        let (client, channel, text) = {
+            let inner = self.inner.lock().unwrap();
+            let channel = inner
+                .config
+                .channels
+                .iter()
+                .find(|c| c.id == channel_id)
+                .cloned()
+                .ok_or_else(|| CtpError::NotFound(format!("通知渠道 {}", channel_id)))?;
+            let text = inner.config.templates.render(NotificationKind::Test, &HashMap::new());
+            (inner.client.clone(), channel, text)
+        };
+        let result = send(&client, &channel.target, &text).await;
+        self.finish(&channel, NotificationKind::Test, &text, result.clone());
+        result.map_err(CtpError::NetworkError)
+    }
+
+    fn finish(&self, channel: &NotificationChannel, kind: NotificationKind, text: &str, result: Result<(), String>) {
+        let mut inner = self.inner.lock().unwrap();
+        match result {
+            Ok(()) => inner.record(&channel.id, kind, NotificationStatus::Sent, text, None),
+            Err(e) => {
+                tracing::warn!("{}通知发送失败 {}: {}", channel.target.name(), channel.id, e);
+                inner.record(&channel.id, kind, NotificationStatus::Failed, text, Some(e));
+            }
+        }
+    }
+}
+
+impl NotifierInner {
+    /// 限速窗口内还有余量时占用一次
+    fn acquire(&mut self, channel: &NotificationChannel) -> bool {
+        if channel.rate_limit_per_minute == 0 {
+            return true;
+        }
+        let now = Instant::now();
+        let window = self.windows.entry(channel.id.clone()).or_default();
+        while window.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+            window.pop_front();
+        }
+        if window.len() >= channel.rate_limit_per_minute as usize {
+            return false;
+        }
+        window.push_back(now);
+        true
+    }
+
+    fn record(&mut self, channel_id: &str, kind: NotificationKind, status: NotificationStatus, text: &str, error: Option<String>) {
+        if self.records.len() >= NOTIFICATION_LOG_CAPACITY {
+            self.records.pop_front();
+        }
+        self.records.push_back(NotificationRecord {
+            channel_id: channel_id.to_string(),
+            kind,
+            status,
+            text: text.to_string(),
+            error,
+            at: chrono::Utc::now(),
+        });
+    }
+}
+
+fn build_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::warn!("创建通知 HTTP 客户端失败，使用默认配置: {}", e);
+            reqwest::Client::new()
+        })
+}
+
+/// 按各平台接口发送文本消息，平台在 HTTP 200 中返回的错误码也视为失败
+async fn send(client: &reqwest::Client, target: &ChannelTarget, text: &str) -> Result<(), String> {
+    let request = match target {
+        ChannelTarget::DingTalk { webhook_url, secret } => {
+            let mut request = client.post(webhook_url);
+            if let Some(secret) = secret.as_deref().filter(|s| !s.is_empty()) {
+                let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+                let sign = hmac_base64(secret.as_bytes(), format!("{}\n{}", timestamp, secret).as_bytes());
+                request = request.query(&[("timestamp", timestamp), ("sign", sign)]);
+            }
+            request.json(&serde_json::json!({ "msgtype": "text", "text": { "content": text } }))
+        }
+        ChannelTarget::Feishu { webhook_url, secret } => {
+            let mut body = serde_json::json!({ "msg_type": "text", "content": { "text": text } });
+            if let Some(secret) = secret.as_deref().filter(|s| !s.is_empty()) {
+                // 飞书以 "timestamp\nsecret" 作为密钥对空串签名
+                let timestamp = chrono::Utc::now().timestamp().to_string();
+                body["timestamp"] = serde_json::json!(timestamp);
+                body["sign"] = serde_json::json!(hmac_base64(format!("{}\n{}", timestamp, secret).as_bytes(), b""));
+            }
+            client.post(webhook_url).json(&body)
+        }
+        ChannelTarget::Telegram { bot_token, chat_id, api_base } => {
+            let base = api_base.as_deref().unwrap_or(TELEGRAM_API_BASE).trim_end_matches('/');
+            client
+                .post(format!("{}/bot{}/sendMessage", base, bot_token))
+                .json(&serde_json::json!({ "chat_id": chat_id, "text": text }))
+        }
+    };
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("HTTP {} {}", status, body));
+    }
+    let rejected = match target {
+        ChannelTarget::DingTalk { .. } => body["errcode"].as_i64().is_some_and(|code| code != 0),
+        ChannelTarget::Feishu { .. } => body["code"]
+            .as_i64()
+            .or_else(|| body["StatusCode"].as_i64())
+            .is_some_and(|code| code != 0),
+        ChannelTarget::Telegram { .. } => body["ok"].as_bool() == Some(false),
+    };
+    if rejected {
+        return Err(format!("平台返回错误: {}", body));
+    }
+    Ok(())
+}
+
+fn hmac_base64(key: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度密钥");
+    mac.update(message);
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn channel(id: &str, target: ChannelTarget, rate_limit_per_minute: u32) -> NotificationChannel {
+        NotificationChannel {
+            id: id.to_string(),
+            target,
+            events: Vec::new(),
+            rate_limit_per_minute,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_templates_rate_limit_and_pnl_crossing() {
+        let templates = NotificationTemplates::default();
+        let text = templates.render(
+            NotificationKind::ConnectionLost,
+            &HashMap::from([("reason", "网络读失败".to_string())]),
+        );
+        assert_eq!(text, "【连接断开】网络读失败");
+
+        let config = NotifierConfig {
+            channels: vec![channel(
+                "ops",
+                ChannelTarget::Telegram { bot_token: "t".to_string(), chat_id: "1".to_string(), api_base: None },
+                2,
+            )],
+            pnl_thresholds: vec![-5000.0, 10000.0],
+            ..NotifierConfig::default()
+        };
+        let channel = config.channels[0].clone();
+        let notifier = Notifier::new(config);
+        {
+            let mut inner = notifier.inner.lock().unwrap();
+            assert!(inner.acquire(&channel));
+            assert!(inner.acquire(&channel));
+            assert!(!inner.acquire(&channel));
+        }
+
+        // 不在运行时中不发送，但仍按阈值穿越更新基准
+        assert_eq!(notifier.observe_pnl(0.0), 0);
+        assert_eq!(notifier.inner.lock().unwrap().last_pnl, Some(0.0));
+        notifier.observe_pnl(-6000.0);
+        assert_eq!(notifier.inner.lock().unwrap().last_pnl, Some(-6000.0));
+
+        let toml_text = r#"
+            pnl_thresholds = [-5000.0]
+            [[channels]]
+            id = "dd"
+            target = { type = "dingtalk", webhook_url = "https://oapi.dingtalk.com/robot/send?access_token=x", secret = "SEC" }
+        "#;
+        let parsed: NotifierConfig = toml::from_str(toml_text).unwrap();
+        assert!(matches!(parsed.channels[0].target, ChannelTarget::DingTalk { .. }));
+        assert_eq!(parsed.channels[0].rate_limit_per_minute, 20);
+        assert!(parsed.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_to_feishu_and_platform_error() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/open-apis/bot/v2/hook/x", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for body in [r#"{"code":0,"msg":"success"}"#, r#"{"code":19021,"msg":"sign match fail"}"#] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = socket.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_string());
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let notifier = Notifier::new(NotifierConfig {
+            channels: vec![channel("fs", ChannelTarget::Feishu { webhook_url: url, secret: Some("key".to_string()) }, 0)],
+            ..NotifierConfig::default()
+        });
+        notifier.test_send("fs").await.unwrap();
+        let err = notifier.test_send("fs").await.unwrap_err();
+        assert!(err.to_string().contains("19021"));
+        assert!(notifier.test_send("missing").await.is_err());
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].contains(r#""msg_type":"text""#));
+        assert!(requests[0].contains(r#""sign":"#));
+
+        let records = notifier.records(10);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].status, NotificationStatus::Failed);
+        assert_eq!(records[1].status, NotificationStatus::Sent);
+    }
+}
@@ -0,0 +1,390 @@
+use crate::ctp::models::MarketDataTick;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 每个合约保留的大单条数
+const LARGE_ORDER_HISTORY: usize = 100;
+
+/// 主动成交方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggressorSide {
+    /// 主动买（成交价达到此前卖一或上涨）
+    Buy,
+    /// 主动卖（成交价达到此前买一或下跌）
+    Sell,
+    /// 无法判断
+    Neutral,
+}
+
+/// 订单流配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFlowConfig {
+    /// 买卖量失衡的滚动窗口
+    pub window: Duration,
+    /// 单笔成交量达到该值视为大单
+    pub large_order_volume: i64,
+    /// 按合约覆盖大单阈值
+    pub large_order_overrides: HashMap<String, i64>,
+}
+
+impl Default for OrderFlowConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            large_order_volume: 100,
+            large_order_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl OrderFlowConfig {
+    fn large_order_threshold(&self, instrument_id: &str) -> i64 {
+        self.large_order_overrides
+            .get(instrument_id)
+            .copied()
+            .unwrap_or(self.large_order_volume)
+    }
+}
+
+/// 单个 tick 的订单流指标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFlowTick {
+    pub instrument_id: String,
+    pub timestamp_ms: i64,
+    pub update_time: String,
+    pub update_millisec: i32,
+    pub price: f64,
+    /// 本 tick 的成交量增量
+    pub volume: i64,
+    pub side: AggressorSide,
+    /// 窗口内主动买量
+    pub window_buy_volume: i64,
+    /// 窗口内主动卖量
+    pub window_sell_volume: i64,
+    /// (买 - 卖) / (买 + 卖)，窗口内无主动成交时为 0
+    pub imbalance: f64,
+    /// 当日累计主动买卖差
+    pub cumulative_delta: i64,
+    pub large_order: bool,
+}
+
+/// 合约订单流快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFlowSnapshot {
+    pub instrument_id: String,
+    pub last: Option<OrderFlowTick>,
+    pub session_buy_volume: i64,
+    pub session_sell_volume: i64,
+    pub session_neutral_volume: i64,
+    /// 最近的大单，按时间倒序
+    pub large_orders: Vec<OrderFlowTick>,
+}
+
+struct InstrumentFlow {
+    prev_volume: i64,
+    prev_bid: f64,
+    prev_ask: f64,
+    prev_price: f64,
+    /// (时间戳, 方向, 成交量)
+    window: VecDeque<(i64, AggressorSide, i64)>,
+    window_buy: i64,
+    window_sell: i64,
+    session_buy: i64,
+    session_sell: i64,
+    session_neutral: i64,
+    last: Option<OrderFlowTick>,
+    large_orders: VecDeque<OrderFlowTick>,
+}
+
+impl InstrumentFlow {
+    fn new(tick: &MarketDataTick) -> Self {
+        Self {
+            prev_volume: tick.volume,
+            prev_bid: tick.bid_price1,
+            prev_ask: tick.ask_price1,
+            prev_price: tick.last_price,
+            window: VecDeque::new(),
+            window_buy: 0,
+            window_sell: 0,
+            session_buy: 0,
+            session_sell: 0,
+            session_neutral: 0,
+            last: None,
+            large_orders: VecDeque::new(),
+        }
+    }
+
+    /// 先比较此前买一卖一，价格落在盘口中间时按涨跌判断
+    fn classify(&self, price: f64) -> AggressorSide {
+        let valid = |p: f64| p > 0.0 && p < f64::MAX;
+        if valid(self.prev_ask) && price >= self.prev_ask {
+            AggressorSide::Buy
+        } else if valid(self.prev_bid) && price <= self.prev_bid {
+            AggressorSide::Sell
+        } else if price > self.prev_price {
+            AggressorSide::Buy
+        } else if price < self.prev_price {
+            AggressorSide::Sell
+        } else {
+            AggressorSide::Neutral
+        }
+    }
+
+    fn expire(&mut self, now_ms: i64, window_ms: i64) {
+        while let Some(&(at, side, volume)) = self.window.front() {
+            if now_ms - at < window_ms {
+                break;
+            }
+            match side {
+                AggressorSide::Buy => self.window_buy -= volume,
+                AggressorSide::Sell => self.window_sell -= volume,
+                AggressorSide::Neutral => {}
+            }
+            self.window.pop_front();
+        }
+    }
+}
+
+/// 订单流分析
+///
+/// 行情回调按成交量增量和此前盘口推断主动成交方向，维护滚动窗口内的
+/// 买卖量失衡、当日累计差和大单记录，指标经独立的 broadcast 通道推送
+#[derive(Clone)]
+pub struct OrderFlowAnalyzer {
+    config: Arc<Mutex<OrderFlowConfig>>,
+    inner: Arc<Mutex<HashMap<String, InstrumentFlow>>>,
+    sender: broadcast::Sender<OrderFlowTick>,
+    stream_started: Arc<AtomicBool>,
+}
+
+impl Default for OrderFlowAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderFlowAnalyzer {
+    pub fn new() -> Self {
+        Self::with_config(OrderFlowConfig::default())
+    }
+
+    pub fn with_config(config: OrderFlowConfig) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            sender,
+            stream_started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn config(&self) -> OrderFlowConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// 更新配置，新窗口在下一笔成交时生效
+    pub fn set_config(&self, config: OrderFlowConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 处理一笔行情，有成交时返回指标并推送
+    pub fn update(&self, tick: &MarketDataTick) -> Option<OrderFlowTick> {
+        self.update_at(tick, chrono::Utc::now().timestamp_millis())
+    }
+
+    fn update_at(&self, tick: &MarketDataTick, now_ms: i64) -> Option<OrderFlowTick> {
+        let config = self.config.lock().unwrap().clone();
+        let mut inner = self.inner.lock().unwrap();
+        let Some(flow) = inner.get_mut(&tick.instrument_id) else {
+            // 首笔行情只作为基准
+            inner.insert(tick.instrument_id.clone(), InstrumentFlow::new(tick));
+            return None;
+        };
+
+        let delta = tick.volume - flow.prev_volume;
+        if delta < 0 {
+            // 成交量回退说明换了交易日，重新计数
+            *flow = InstrumentFlow::new(tick);
+            return None;
+        }
+
+        let window_ms = config.window.as_millis() as i64;
+        flow.expire(now_ms, window_ms);
+        let result = if delta > 0 {
+            let side = flow.classify(tick.last_price);
+            match side {
+                AggressorSide::Buy => {
+                    flow.window_buy += delta;
+                    flow.session_buy += delta;
+                }
+                AggressorSide::Sell => {
+                    flow.window_sell += delta;
+                    flow.session_sell += delta;
+                }
+                AggressorSide::Neutral => flow.session_neutral += delta,
+            }
+            flow.window.push_back((now_ms, side, delta));
+
+            let active = flow.window_buy + flow.window_sell;
+            let indicator = OrderFlowTick {
+                instrument_id: tick.instrument_id.clone(),
+                timestamp_ms: now_ms,
+                update_time: tick.update_time.clone(),
+                update_millisec: tick.update_millisec,
+                price: tick.last_price,
+                volume: delta,
+                side,
+                window_buy_volume: flow.window_buy,
+                window_sell_volume: flow.window_sell,
+                imbalance: if active > 0 {
+                    (flow.window_buy - flow.window_sell) as f64 / active as f64
+                } else {
+                    0.0
+                },
+                cumulative_delta: flow.session_buy - flow.session_sell,
+                large_order: delta >= config.large_order_threshold(&tick.instrument_id),
+            };
+            if indicator.large_order {
+                tracing::debug!("{} 大单 {:?} {} 手 @ {}", tick.instrument_id, side, delta, tick.last_price);
+                if flow.large_orders.len() >= LARGE_ORDER_HISTORY {
+                    flow.large_orders.pop_front();
+                }
+                flow.large_orders.push_back(indicator.clone());
+            }
+            flow.last = Some(indicator.clone());
+            Some(indicator)
+        } else {
+            None
+        };
+
+        flow.prev_volume = tick.volume;
+        flow.prev_bid = tick.bid_price1;
+        flow.prev_ask = tick.ask_price1;
+        flow.prev_price = tick.last_price;
+        drop(inner);
+
+        if let Some(indicator) = &result {
+            // 没有订阅者时发送失败可忽略
+            let _ = self.sender.send(indicator.clone());
+        }
+        result
+    }
+
+    /// 移除合约（取消订阅时调用）
+    pub fn remove(&self, instrument_id: &str) {
+        self.inner.lock().unwrap().remove(instrument_id);
+    }
+
+    pub fn snapshot(&self, instrument_id: &str) -> Option<OrderFlowSnapshot> {
+        let inner = self.inner.lock().unwrap();
+        let flow = inner.get(instrument_id)?;
+        Some(OrderFlowSnapshot {
+            instrument_id: instrument_id.to_string(),
+            last: flow.last.clone(),
+            session_buy_volume: flow.session_buy,
+            session_sell_volume: flow.session_sell,
+            session_neutral_volume: flow.session_neutral,
+            large_orders: flow.large_orders.iter().rev().cloned().collect(),
+        })
+    }
+
+    /// 订阅订单流指标
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderFlowTick> {
+        self.sender.subscribe()
+    }
+
+    /// 标记推送已启动，已启动时返回 false，避免重复转发
+    pub fn start_stream(&self) -> bool {
+        !self.stream_started.swap(true, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(price: f64, volume: i64, bid: f64, ask: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: price,
+            volume,
+            turnover: 0.0,
+            open_interest: 0,
+            bid_price1: bid,
+            bid_volume1: 1,
+            ask_price1: ask,
+            ask_volume1: 1,
+            update_time: "10:00:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: price,
+            highest_price: price,
+            lowest_price: price,
+            pre_close_price: price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_aggressor_side_and_imbalance() {
+        let analyzer = OrderFlowAnalyzer::with_config(OrderFlowConfig {
+            window: Duration::from_secs(10),
+            large_order_volume: 50,
+            large_order_overrides: HashMap::new(),
+        });
+        assert!(analyzer.update_at(&tick(3500.0, 1000, 3499.0, 3500.0), 0).is_none());
+
+        // 成交在此前卖一，主动买
+        let flow = analyzer.update_at(&tick(3500.0, 1030, 3500.0, 3501.0), 1_000).unwrap();
+        assert_eq!(flow.side, AggressorSide::Buy);
+        assert_eq!(flow.volume, 30);
+        assert_eq!(flow.imbalance, 1.0);
+
+        // 成交在此前买一，主动卖，且为大单
+        let flow = analyzer.update_at(&tick(3500.0, 1090, 3499.0, 3501.0), 2_000).unwrap();
+        assert_eq!(flow.side, AggressorSide::Sell);
+        assert!(flow.large_order);
+        assert_eq!(flow.cumulative_delta, -30);
+        assert!((flow.imbalance + 1.0 / 3.0).abs() < 1e-9);
+
+        // 价格落在盘口中间且不变，无法判断；无成交不产生指标
+        let flow = analyzer.update_at(&tick(3500.0, 1095, 3499.0, 3501.0), 3_000).unwrap();
+        assert_eq!(flow.side, AggressorSide::Neutral);
+        assert!(analyzer.update_at(&tick(3500.0, 1095, 3499.0, 3501.0), 3_500).is_none());
+
+        // 窗口过期后只剩新成交
+        let flow = analyzer.update_at(&tick(3501.0, 1100, 3500.0, 3502.0), 12_500).unwrap();
+        assert_eq!(flow.side, AggressorSide::Buy);
+        assert_eq!(flow.window_buy_volume, 5);
+        assert_eq!(flow.window_sell_volume, 0);
+
+        let snapshot = analyzer.snapshot("rb2501").unwrap();
+        assert_eq!(snapshot.session_buy_volume, 35);
+        assert_eq!(snapshot.session_sell_volume, 60);
+        assert_eq!(snapshot.session_neutral_volume, 5);
+        assert_eq!(snapshot.large_orders.len(), 1);
+    }
+
+    #[test]
+    fn test_volume_reset_and_stream() {
+        let analyzer = OrderFlowAnalyzer::new();
+        let mut receiver = analyzer.subscribe();
+        analyzer.update_at(&tick(3500.0, 1000, 3499.0, 3500.0), 0);
+        analyzer.update_at(&tick(3501.0, 1010, 3500.0, 3501.0), 500);
+        assert_eq!(receiver.try_recv().unwrap().volume, 10);
+
+        // 新交易日成交量从头开始
+        assert!(analyzer.update_at(&tick(3600.0, 5, 3599.0, 3600.0), 1_000).is_none());
+        assert_eq!(analyzer.snapshot("rb2501").unwrap().session_buy_volume, 0);
+
+        assert!(analyzer.start_stream());
+        assert!(!analyzer.start_stream());
+    }
+}
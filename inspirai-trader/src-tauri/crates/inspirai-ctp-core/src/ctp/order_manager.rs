@@ -0,0 +1,943 @@
+use crate::ctp::{
+    CtpError, OrderRequest, OrderStatus, OrderStatusType, TradeRecord,
+    OrderDirection, OffsetFlag, OrderType, TimeCondition, OrderTags,
+    trade_dedup::TradeDeduplicator,
+    round_trip::RoundTripBook,
+};
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn, error, debug};
+
+/// 订单管理器
+#[derive(Clone)]
+pub struct OrderManager {
+    /// 所有订单
+    orders: Arc<Mutex<HashMap<String, OrderInfo>>>,
+    /// 活动订单（未完成）
+    active_orders: Arc<Mutex<HashMap<String, String>>>,
+    /// 成交记录
+    trades: Arc<Mutex<Vec<TradeRecord>>>,
+    /// 订单标签 (order_ref -> tags)，CTP 回报不携带标签，由此补全；
+    /// 重启前的订单标签随客户端订单号记录持久化，由交易 SPI 在回报中补全
+    order_tags: Arc<Mutex<HashMap<String, OrderTags>>>,
+    /// 订单统计
+    stats: Arc<Mutex<OrderStats>>,
+    /// OCO 订单组
+    oco: Arc<Mutex<OcoState>>,
+    /// 订单到期自动撤单计划 (order_id -> 到期信息)，撤单回报确认前保留
+    expiries: Arc<Mutex<HashMap<String, OrderExpiry>>>,
+    /// 已发出到期撤单、等待回报的订单 (order_id -> 发出时间)
+    expiry_cancels: Arc<Mutex<HashMap<String, chrono::DateTime<chrono::Utc>>>>,
+    /// 撤单已确认的到期计划，待发布
+    expired: Arc<Mutex<Vec<OrderExpiry>>>,
+    /// 已处理成交，重复回报不重复计入
+    trade_dedup: TradeDeduplicator,
+}
+
+/// 订单标签中的到期时间（RFC 3339），提交时自动登记到期撤单
+pub const EXPIRE_AT_TAG: &str = "expire_at";
+
+/// 到期撤单发出后超过该时长仍未收到回报则重新撤单
+pub const EXPIRY_CANCEL_RETRY_SECS: i64 = 5;
+
+/// 订单到期撤单计划
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct OrderExpiry {
+    pub order_id: String,
+    pub instrument_id: String,
+    pub expire_at: chrono::DateTime<chrono::Utc>,
+    /// 撤单原因，随撤单事件发布
+    pub reason: String,
+}
+
+/// 订单信息
+#[derive(Debug, Clone)]
+pub struct OrderInfo {
+    /// 订单状态
+    pub status: OrderStatus,
+    /// 创建时间
+    pub create_time: Instant,
+    /// 最后更新时间
+    pub last_update: Instant,
+    /// 重试次数
+    pub retry_count: u32,
+    /// 相关成交记录
+    pub trades: Vec<TradeRecord>,
+}
+
+/// 按标签归集的成交统计
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TagAttribution {
+    /// 成交笔数
+    pub trade_count: u64,
+    /// 成交手数
+    pub volume: i64,
+    /// 成交额（乘合约乘数）
+    pub turnover: f64,
+    /// 手续费
+    #[serde(default)]
+    pub fees: f64,
+    /// 净现金流（卖出为正、买入为负，乘合约乘数并扣除手续费）
+    pub net_cash_flow: f64,
+}
+
+/// OCO 订单组状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum OcoGroupStatus {
+    /// 监控中
+    Active,
+    /// 某成员成交达到阈值，其余成员待撤
+    Triggered,
+    /// 触发后其余成员均已结束
+    Completed,
+    /// 所有成员均已结束且未触发
+    Expired,
+    /// 手动解散
+    Dissolved,
+}
+
+/// OCO（一撤其余）订单组：任一成员成交达到阈值后撤销其余成员
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct OcoGroup {
+    pub id: String,
+    pub order_ids: Vec<String>,
+    /// 成员成交比例达到该值即触发，1.0 表示全部成交
+    pub fill_threshold: f64,
+    pub status: OcoGroupStatus,
+    /// 触发的成员
+    pub triggered_by: Option<String>,
+    /// 触发时仍在队列中、需撤销的成员
+    pub cancel_order_ids: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Default)]
+struct OcoState {
+    next_id: u64,
+    groups: HashMap<String, OcoGroup>,
+    /// 订单号 -> 所属 OCO 组
+    index: HashMap<String, String>,
+    /// 待发布的组状态变化
+    updates: Vec<OcoGroup>,
+}
+
+/// 订单统计
+#[derive(Debug, Clone, Default)]
+pub struct OrderStats {
+    /// 总订单数
+    pub total_orders: u64,
+    /// 成功订单数
+    pub success_orders: u64,
+    /// 失败订单数
+    pub failed_orders: u64,
+    /// 撤销订单数
+    pub canceled_orders: u64,
+    /// 总成交数
+    pub total_trades: u64,
+    /// 今日成交额
+    pub today_turnover: f64,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self {
+            orders: Arc::new(Mutex::new(HashMap::new())),
+            active_orders: Arc::new(Mutex::new(HashMap::new())),
+            trades: Arc::new(Mutex::new(Vec::new())),
+            order_tags: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(Mutex::new(OrderStats::default())),
+            oco: Arc::new(Mutex::new(OcoState::default())),
+            expiries: Arc::new(Mutex::new(HashMap::new())),
+            expiry_cancels: Arc::new(Mutex::new(HashMap::new())),
+            expired: Arc::new(Mutex::new(Vec::new())),
+            trade_dedup: TradeDeduplicator::in_memory(),
+        }
+    }
+
+    /// 使用指定的成交去重（如按交易日持久化的已处理集合）
+    pub fn with_trade_dedup(mut self, trade_dedup: TradeDeduplicator) -> Self {
+        self.trade_dedup = trade_dedup;
+        self
+    }
+
+    /// 成交是否已处理过
+    pub fn is_duplicate_trade(&self, trade: &TradeRecord) -> bool {
+        self.trade_dedup.contains(trade)
+    }
+
+    /// 添加新订单
+    pub fn add_order(&self, mut order: OrderStatus) -> Result<(), CtpError> {
+        let order_id = order.order_id.clone();
+        self.sync_tags(&mut order);
+        
+        let order_info = OrderInfo {
+            status: order.clone(),
+            create_time: Instant::now(),
+            last_update: Instant::now(),
+            retry_count: 0,
+            trades: Vec::new(),
+        };
+        
+        self.orders.lock().unwrap().insert(order_id.clone(), order_info);
+        
+        // 如果是活动订单，加入活动列表
+        if self.is_active_status(order.status) {
+            self.active_orders.lock().unwrap()
+                .insert(order_id.clone(), order.instrument_id.clone());
+            self.schedule_tagged_expiry(&order);
+        }
+        
+        // 更新统计
+        let mut stats = self.stats.lock().unwrap();
+        stats.total_orders += 1;
+        
+        info!("添加订单: {} 合约={} 状态={:?}", 
+            order_id, order.instrument_id, order.status);
+        
+        Ok(())
+    }
+
+    /// 更新订单状态
+    pub fn update_order(&self, mut order: OrderStatus) -> Result<(), CtpError> {
+        let order_id = order.order_id.clone();
+        self.sync_tags(&mut order);
+        
+        let mut orders = self.orders.lock().unwrap();
+        
+        if !orders.contains_key(&order_id) {
+            // 如果订单不存在，创建新订单（先释放锁，add_order 会重新加锁）
+            drop(orders);
+            return self.add_order(order);
+        }
+        
+        if let Some(order_info) = orders.get_mut(&order_id) {
+            let old_status = order_info.status.status;
+            order_info.status = order.clone();
+            order_info.last_update = Instant::now();
+            
+            // 更新活动订单列表
+            if !self.is_active_status(order.status) {
+                self.active_orders.lock().unwrap().remove(&order_id);
+                
+                // 更新统计
+                let mut stats = self.stats.lock().unwrap();
+                match order.status {
+                    OrderStatusType::AllTraded => stats.success_orders += 1,
+                    OrderStatusType::Canceled => stats.canceled_orders += 1,
+                    OrderStatusType::Unknown => stats.failed_orders += 1,
+                    _ => {}
+                }
+                drop(stats);
+                self.finish_expiry(&order_id, order.status);
+            }
+            
+            debug!("更新订单: {} 状态={:?} -> {:?}", 
+                order_id, old_status, order.status);
+        }
+        drop(orders);
+        self.check_oco(&order_id);
+        
+        Ok(())
+    }
+
+    /// 添加成交记录
+    pub fn add_trade(&self, mut trade: TradeRecord) -> Result<(), CtpError> {
+        if !self.trade_dedup.first_seen(&trade) {
+            warn!("忽略重复成交: {} 合约={}", trade.trade_id, trade.instrument_id);
+            return Ok(());
+        }
+        let order_id = trade.order_id.clone();
+        
+        // 继承订单标签（成交回报的 order_id 为报单引用），回报已带的标签优先
+        if let Some(tags) = self.tags_for_order(&order_id) {
+            for (key, value) in tags {
+                trade.tags.entry(key).or_insert(value);
+            }
+        }
+        
+        // 添加到总成交列表
+        self.trades.lock().unwrap().push(trade.clone());
+        
+        // 关联到对应订单
+        let mut orders = self.orders.lock().unwrap();
+        if let Some(order_info) = orders.get_mut(&order_id) {
+            order_info.trades.push(trade.clone());
+            order_info.last_update = Instant::now();
+        }
+        drop(orders);
+        self.check_oco(&order_id);
+        
+        // 更新统计
+        let mut stats = self.stats.lock().unwrap();
+        stats.total_trades += 1;
+        stats.today_turnover += trade.price * trade.volume as f64;
+        
+        info!("添加成交: {} 合约={} {}手@{}", 
+            trade.trade_id, trade.instrument_id, trade.volume, trade.price);
+        
+        Ok(())
+    }
+
+    /// 获取订单标签（按报单引用）
+    pub fn tags_for_order(&self, order_id: &str) -> Option<OrderTags> {
+        let tags = self.order_tags.lock().unwrap().get(order_id).cloned();
+        tags.or_else(|| {
+            self.orders.lock().unwrap().get(order_id).map(|info| info.status.tags.clone())
+        })
+    }
+
+    /// 获取订单信息
+    pub fn get_order(&self, order_id: &str) -> Option<OrderInfo> {
+        self.orders.lock().unwrap().get(order_id).cloned()
+    }
+
+    /// 获取所有活动订单
+    pub fn get_active_orders(&self) -> Vec<OrderStatus> {
+        let orders = self.orders.lock().unwrap();
+        let active = self.active_orders.lock().unwrap();
+        
+        active.keys()
+            .filter_map(|id| orders.get(id))
+            .map(|info| info.status.clone())
+            .collect()
+    }
+
+    /// 获取所有订单
+    pub fn get_all_orders(&self) -> Vec<OrderStatus> {
+        self.orders.lock().unwrap()
+            .values()
+            .map(|info| info.status.clone())
+            .collect()
+    }
+
+    /// 成交是否已记录
+    pub fn has_trade(&self, trade_id: &str) -> bool {
+        self.trades.lock().unwrap()
+            .iter()
+            .any(|trade| trade.trade_id == trade_id)
+    }
+
+    /// 将订单标记为未知状态并移出活动列表（对账时柜台不存在的订单）
+    pub fn mark_order_unknown(&self, order_id: &str, reason: &str) {
+        let mut orders = self.orders.lock().unwrap();
+        if let Some(order_info) = orders.get_mut(order_id) {
+            order_info.status.status = OrderStatusType::Unknown;
+            order_info.status.status_msg = reason.to_string();
+            order_info.last_update = Instant::now();
+            self.active_orders.lock().unwrap().remove(order_id);
+            warn!("订单标记为未知: {} 原因={}", order_id, reason);
+        }
+    }
+
+    /// 获取订单的成交记录
+    pub fn get_order_trades(&self, order_id: &str) -> Vec<TradeRecord> {
+        self.orders.lock().unwrap()
+            .get(order_id)
+            .map(|info| info.trades.clone())
+            .unwrap_or_default()
+    }
+
+    /// 获取今日成交
+    pub fn get_today_trades(&self) -> Vec<TradeRecord> {
+        self.trades.lock().unwrap().clone()
+    }
+
+    /// 按标签键归集成交，如 `strategy` 下各策略的成交统计
+    ///
+    /// 合约乘数和手续费率取自回合交易簿
+    pub fn get_attribution_by_tag(&self, key: &str, book: &RoundTripBook) -> HashMap<String, TagAttribution> {
+        let trades = self.trades.lock().unwrap();
+        let mut result: HashMap<String, TagAttribution> = HashMap::new();
+        
+        for trade in trades.iter() {
+            let Some(value) = trade.tags.get(key) else {
+                continue;
+            };
+            
+            let entry = result.entry(value.clone()).or_default();
+            let amount = trade.price * trade.volume as f64 * book.volume_multiple(&trade.instrument_id);
+            let fee = book.trade_fee(trade);
+            entry.trade_count += 1;
+            entry.volume += trade.volume as i64;
+            entry.turnover += amount;
+            entry.fees += fee;
+            entry.net_cash_flow += match trade.direction {
+                OrderDirection::Buy => -amount,
+                OrderDirection::Sell => amount,
+            } - fee;
+        }
+        
+        result
+    }
+
+    /// 获取订单统计
+    pub fn get_stats(&self) -> OrderStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// 将两个以上的活动订单组成 OCO 组，`fill_threshold` 为触发的成交比例，默认全部成交
+    pub fn create_oco_group(&self, order_ids: Vec<String>, fill_threshold: Option<f64>) -> Result<OcoGroup, CtpError> {
+        let fill_threshold = fill_threshold.unwrap_or(1.0);
+        if !(fill_threshold > 0.0 && fill_threshold <= 1.0) {
+            return Err(CtpError::ValidationError("OCO 触发成交比例须在 (0, 1] 之间".to_string()));
+        }
+        let mut unique = order_ids.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() < 2 || unique.len() != order_ids.len() {
+            return Err(CtpError::ValidationError("OCO 组至少需要两个不重复的订单".to_string()));
+        }
+        {
+            let active = self.active_orders.lock().unwrap();
+            if let Some(id) = order_ids.iter().find(|id| !active.contains_key(*id)) {
+                return Err(CtpError::StateError(format!("订单 {} 不存在或已结束", id)));
+            }
+        }
+
+        let mut oco = self.oco.lock().unwrap();
+        if let Some(id) = order_ids.iter().find(|id| oco.index.contains_key(*id)) {
+            return Err(CtpError::StateError(format!("订单 {} 已属于其他 OCO 组", id)));
+        }
+        oco.next_id += 1;
+        let now = chrono::Utc::now();
+        let group = OcoGroup {
+            id: format!("OCO{}", oco.next_id),
+            order_ids,
+            fill_threshold,
+            status: OcoGroupStatus::Active,
+            triggered_by: None,
+            cancel_order_ids: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        for order_id in &group.order_ids {
+            oco.index.insert(order_id.clone(), group.id.clone());
+        }
+        oco.groups.insert(group.id.clone(), group.clone());
+        oco.updates.push(group.clone());
+        info!("创建 OCO 组 {}: {:?}", group.id, group.order_ids);
+        Ok(group)
+    }
+
+    /// 手动解散 OCO 组，成员订单保持不变
+    pub fn dissolve_oco_group(&self, group_id: &str) -> Result<OcoGroup, CtpError> {
+        let mut guard = self.oco.lock().unwrap();
+        let oco = &mut *guard;
+        let group = oco
+            .groups
+            .get_mut(group_id)
+            .ok_or_else(|| CtpError::NotFound(format!("OCO 组不存在: {}", group_id)))?;
+        if group.status != OcoGroupStatus::Active {
+            return Err(CtpError::StateError(format!("OCO 组 {} 已触发或结束", group_id)));
+        }
+        group.status = OcoGroupStatus::Dissolved;
+        group.updated_at = chrono::Utc::now();
+        let group = group.clone();
+        for order_id in &group.order_ids {
+            oco.index.remove(order_id);
+        }
+        oco.updates.push(group.clone());
+        Ok(group)
+    }
+
+    /// 获取 OCO 组
+    pub fn get_oco_group(&self, group_id: &str) -> Option<OcoGroup> {
+        self.oco.lock().unwrap().groups.get(group_id).cloned()
+    }
+
+    /// 获取所有 OCO 组，按创建时间排序
+    pub fn get_oco_groups(&self) -> Vec<OcoGroup> {
+        let mut groups: Vec<OcoGroup> = self.oco.lock().unwrap().groups.values().cloned().collect();
+        groups.sort_by_key(|g| g.created_at);
+        groups
+    }
+
+    /// 取出待发布的 OCO 组状态变化；状态为 `Triggered` 的组需撤销 `cancel_order_ids`
+    pub fn take_oco_updates(&self) -> Vec<OcoGroup> {
+        std::mem::take(&mut self.oco.lock().unwrap().updates)
+    }
+
+    /// 订单成交或状态变化后检查所属 OCO 组
+    fn check_oco(&self, order_id: &str) {
+        let Some(group_id) = self.oco.lock().unwrap().index.get(order_id).cloned() else {
+            return;
+        };
+        let filled_ratio = {
+            let orders = self.orders.lock().unwrap();
+            orders.get(order_id).map(|info| {
+                let traded: i32 = info.trades.iter().map(|t| t.volume).sum();
+                let filled = (info.status.volume_traded as i32).max(traded);
+                filled as f64 / info.status.volume_total_original.max(1) as f64
+            })
+        };
+        let active: Vec<String> = self.active_orders.lock().unwrap().keys().cloned().collect();
+
+        let mut guard = self.oco.lock().unwrap();
+        let oco = &mut *guard;
+        let Some(group) = oco.groups.get_mut(&group_id) else {
+            return;
+        };
+        let others_active: Vec<String> = group
+            .order_ids
+            .iter()
+            .filter(|id| id.as_str() != order_id && active.contains(id))
+            .cloned()
+            .collect();
+        let previous = group.status;
+        match group.status {
+            OcoGroupStatus::Active if filled_ratio.is_some_and(|ratio| ratio + 1e-9 >= group.fill_threshold) => {
+                info!("OCO 组 {} 由订单 {} 触发，撤销 {:?}", group.id, order_id, others_active);
+                group.status = if others_active.is_empty() { OcoGroupStatus::Completed } else { OcoGroupStatus::Triggered };
+                group.triggered_by = Some(order_id.to_string());
+                group.cancel_order_ids = others_active;
+            }
+            OcoGroupStatus::Active if !group.order_ids.iter().any(|id| active.contains(id)) => {
+                group.status = OcoGroupStatus::Expired;
+            }
+            OcoGroupStatus::Triggered => {
+                let triggered_by = group.triggered_by.clone();
+                let remaining = group
+                    .order_ids
+                    .iter()
+                    .any(|id| Some(id) != triggered_by.as_ref() && active.contains(id));
+                if !remaining {
+                    group.status = OcoGroupStatus::Completed;
+                }
+            }
+            _ => {}
+        }
+        if group.status != previous {
+            group.updated_at = chrono::Utc::now();
+            let group = group.clone();
+            if matches!(group.status, OcoGroupStatus::Completed | OcoGroupStatus::Expired) {
+                for id in &group.order_ids {
+                    oco.index.remove(id);
+                }
+            }
+            oco.updates.push(group);
+        }
+    }
+
+    /// 为活动订单设置到期时间，到期后由 `due_expiries` 取出撤销；重复设置覆盖之前的计划
+    pub fn set_order_expiry(
+        &self,
+        order_id: &str,
+        expire_at: chrono::DateTime<chrono::Utc>,
+        reason: Option<String>,
+    ) -> Result<OrderExpiry, CtpError> {
+        if !self.active_orders.lock().unwrap().contains_key(order_id) {
+            return Err(CtpError::StateError(format!("订单 {} 不存在或已结束", order_id)));
+        }
+        let instrument_id = self
+            .orders
+            .lock()
+            .unwrap()
+            .get(order_id)
+            .map(|info| info.status.instrument_id.clone())
+            .unwrap_or_default();
+        let expiry = OrderExpiry {
+            order_id: order_id.to_string(),
+            instrument_id,
+            expire_at,
+            reason: reason.unwrap_or_else(|| format!("订单到期 ({})", expire_at.with_timezone(&chrono::Local).format("%H:%M:%S"))),
+        };
+        info!("订单 {} 将于 {} 自动撤销: {}", order_id, expire_at, expiry.reason);
+        self.expiries.lock().unwrap().insert(order_id.to_string(), expiry.clone());
+        Ok(expiry)
+    }
+
+    /// 为带指定标签的全部活动订单设置到期时间，例如收盘前一分钟撤销所有剥头皮挂单
+    pub fn set_expiry_by_tag(
+        &self,
+        key: &str,
+        value: &str,
+        expire_at: chrono::DateTime<chrono::Utc>,
+        reason: Option<String>,
+    ) -> Vec<OrderExpiry> {
+        let order_ids: Vec<String> = self
+            .get_active_orders()
+            .into_iter()
+            .filter(|order| order.tags.get(key).map(String::as_str) == Some(value))
+            .map(|order| order.order_id)
+            .collect();
+        order_ids
+            .iter()
+            .filter_map(|id| self.set_order_expiry(id, expire_at, reason.clone()).ok())
+            .collect()
+    }
+
+    /// 取消订单的到期计划
+    pub fn clear_order_expiry(&self, order_id: &str) -> Option<OrderExpiry> {
+        self.expiry_cancels.lock().unwrap().remove(order_id);
+        self.expiries.lock().unwrap().remove(order_id)
+    }
+
+    /// 获取全部到期计划，按到期时间排序
+    pub fn get_order_expiries(&self) -> Vec<OrderExpiry> {
+        let mut expiries: Vec<OrderExpiry> = self.expiries.lock().unwrap().values().cloned().collect();
+        expiries.sort_by(|a, b| a.expire_at.cmp(&b.expire_at).then_with(|| a.order_id.cmp(&b.order_id)));
+        expiries
+    }
+
+    /// 已到期且需要发出撤单的计划，计划保留到撤单回报确认或撤单失败；
+    /// 撤单发出后 `EXPIRY_CANCEL_RETRY_SECS` 秒内不重复返回
+    pub fn due_expiries(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<OrderExpiry> {
+        let active = self.active_orders.lock().unwrap();
+        let mut expiries = self.expiries.lock().unwrap();
+        let mut cancels = self.expiry_cancels.lock().unwrap();
+        expiries.retain(|id, _| active.contains_key(id));
+        cancels.retain(|id, _| expiries.contains_key(id));
+        let retry_after = chrono::Duration::seconds(EXPIRY_CANCEL_RETRY_SECS);
+        let mut due: Vec<OrderExpiry> = expiries
+            .values()
+            .filter(|e| e.expire_at <= now)
+            .filter(|e| cancels.get(&e.order_id).is_none_or(|sent| now - *sent >= retry_after))
+            .cloned()
+            .collect();
+        for expiry in &due {
+            cancels.insert(expiry.order_id.clone(), now);
+        }
+        due.sort_by(|a, b| a.expire_at.cmp(&b.expire_at).then_with(|| a.order_id.cmp(&b.order_id)));
+        due
+    }
+
+    /// 到期撤单未能发出：`terminal` 为真时（订单已不可撤）放弃计划，否则下次定时检查时重试
+    pub fn expiry_cancel_failed(&self, order_id: &str, terminal: bool) {
+        self.expiry_cancels.lock().unwrap().remove(order_id);
+        if terminal {
+            self.expiries.lock().unwrap().remove(order_id);
+        }
+    }
+
+    /// 取出撤单已确认的到期计划
+    pub fn take_expired_orders(&self) -> Vec<OrderExpiry> {
+        std::mem::take(&mut *self.expired.lock().unwrap())
+    }
+
+    /// 订单结束时清除到期计划，因到期撤单而撤销的记入待发布列表
+    fn finish_expiry(&self, order_id: &str, status: OrderStatusType) {
+        let cancelling = self.expiry_cancels.lock().unwrap().remove(order_id).is_some();
+        let Some(expiry) = self.expiries.lock().unwrap().remove(order_id) else {
+            return;
+        };
+        if cancelling && matches!(status, OrderStatusType::Canceled | OrderStatusType::Cancelled) {
+            self.expired.lock().unwrap().push(expiry);
+        }
+    }
+
+    /// 按订单标签登记到期撤单
+    fn schedule_tagged_expiry(&self, order: &OrderStatus) {
+        let Some(value) = order.tags.get(EXPIRE_AT_TAG) else {
+            return;
+        };
+        match chrono::DateTime::parse_from_rfc3339(value) {
+            Ok(expire_at) => {
+                if let Err(e) = self.set_order_expiry(&order.order_id, expire_at.with_timezone(&chrono::Utc), None) {
+                    warn!("登记订单 {} 到期撤单失败: {}", order.order_id, e);
+                }
+            }
+            Err(e) => warn!("订单 {} 到期时间无效 {}: {}", order.order_id, value, e),
+        }
+    }
+
+    /// 验证订单请求
+    pub fn validate_order(&self, order: &OrderRequest) -> Result<(), CtpError> {
+        // 基本验证
+        if order.instrument_id.is_empty() {
+            return Err(CtpError::ValidationError("合约代码不能为空".to_string()));
+        }
+        
+        if order.volume <= 0 {
+            return Err(CtpError::ValidationError("委托数量必须大于0".to_string()));
+        }
+        
+        if order.price <= 0.0 && order.order_type == OrderType::Limit {
+            return Err(CtpError::ValidationError("限价单价格必须大于0".to_string()));
+        }
+        
+        // TODO: 添加更多验证规则
+        // - 风险控制检查
+        // - 资金充足性检查
+        // - 持仓可平检查
+        
+        Ok(())
+    }
+
+    /// 保存或补全订单标签
+    fn sync_tags(&self, order: &mut OrderStatus) {
+        let mut order_tags = self.order_tags.lock().unwrap();
+        if order.tags.is_empty() {
+            if let Some(tags) = order_tags.get(&order.order_ref) {
+                order.tags = tags.clone();
+            }
+        } else {
+            order_tags.insert(order.order_ref.clone(), order.tags.clone());
+        }
+    }
+
+    /// 判断是否为活动状态
+    fn is_active_status(&self, status: OrderStatusType) -> bool {
+        matches!(
+            status,
+            OrderStatusType::Unknown
+                | OrderStatusType::PartTradedQueueing
+                | OrderStatusType::PartTradedNotQueueing
+                | OrderStatusType::NoTradeQueueing
+                | OrderStatusType::NoTradeNotQueueing
+                | OrderStatusType::Touched
+        )
+    }
+
+    /// 清理过期订单
+    pub fn cleanup_expired_orders(&self, expire_duration: Duration) {
+        let now = Instant::now();
+        let mut orders = self.orders.lock().unwrap();
+        let mut active = self.active_orders.lock().unwrap();
+        
+        let expired: Vec<String> = orders
+            .iter()
+            .filter(|(_, info)| {
+                !self.is_active_status(info.status.status)
+                    && now.duration_since(info.last_update) > expire_duration
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        
+        for id in expired {
+            orders.remove(&id);
+            active.remove(&id);
+            debug!("清理过期订单: {}", id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(trade_id: &str, order_id: &str, direction: OrderDirection, price: f64) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: order_id.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction,
+            offset_flag: OffsetFlag::Open,
+            price,
+            volume: 1,
+            trade_time: "09:30:00".to_string(),
+            exchange_id: String::new(),
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_trade_not_double_counted() {
+        let manager = OrderManager::new();
+        manager.add_trade(trade("t1", "1", OrderDirection::Buy, 3500.0)).unwrap();
+        assert!(manager.is_duplicate_trade(&trade("t1", "1", OrderDirection::Buy, 3500.0)));
+        manager.add_trade(trade("t1", "1", OrderDirection::Buy, 3500.0)).unwrap();
+
+        let stats = manager.get_stats();
+        assert_eq!(stats.total_trades, 1);
+        assert_eq!(manager.get_today_trades().len(), 1);
+    }
+
+    #[test]
+    fn test_trades_inherit_order_tags() {
+        let manager = OrderManager::new();
+        let mut tags = OrderTags::new();
+        tags.insert("strategy".to_string(), "breakout".to_string());
+        manager.add_order(OrderStatus {
+            order_ref: "1".to_string(),
+            order_id: "1".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            limit_price: 3500.0,
+            volume: 1,
+            volume_total_original: 1,
+            volume_traded: 0,
+            volume_left: 1,
+            volume_total: 1,
+            status: OrderStatusType::NoTradeQueueing,
+            submit_time: chrono::Local::now(),
+            insert_time: "09:30:00".to_string(),
+            update_time: chrono::Local::now(),
+            front_id: 1,
+            session_id: 1,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: true,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags,
+        }).unwrap();
+
+        manager.add_trade(trade("t1", "1", OrderDirection::Buy, 3500.0)).unwrap();
+        manager.add_trade(trade("t2", "1", OrderDirection::Sell, 3520.0)).unwrap();
+        manager.add_trade(trade("t3", "2", OrderDirection::Buy, 3600.0)).unwrap();
+
+        assert_eq!(manager.get_today_trades()[0].tags.get("strategy").map(String::as_str), Some("breakout"));
+
+        let book = RoundTripBook::default();
+        book.set_volume_multiple("rb2501", 10.0);
+        book.set_commission_rate(&crate::ctp::CommissionRate {
+            instrument_id: "rb2501".to_string(),
+            open_ratio_by_money: 0.0,
+            open_ratio_by_volume: 1.5,
+            close_ratio_by_money: 0.0,
+            close_ratio_by_volume: 1.5,
+            close_today_ratio_by_money: 0.0,
+            close_today_ratio_by_volume: 1.5,
+        });
+        let attribution = manager.get_attribution_by_tag("strategy", &book);
+        assert_eq!(attribution.len(), 1);
+        let breakout = &attribution["breakout"];
+        assert_eq!(breakout.trade_count, 2);
+        assert!((breakout.fees - 3.0).abs() < 1e-9);
+        assert!((breakout.net_cash_flow - (20.0 * 10.0 - 3.0)).abs() < 1e-9);
+    }
+
+    fn working_order(order_id: &str, volume: i32) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_id.to_string(),
+            order_id: order_id.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Sell,
+            offset_flag: OffsetFlag::Close,
+            price: 3500.0,
+            limit_price: 3500.0,
+            volume: volume as u32,
+            volume_total_original: volume,
+            volume_traded: 0,
+            volume_left: volume as u32,
+            volume_total: volume,
+            status: OrderStatusType::NoTradeQueueing,
+            submit_time: chrono::Local::now(),
+            insert_time: "09:30:00".to_string(),
+            update_time: chrono::Local::now(),
+            front_id: 1,
+            session_id: 1,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: true,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_oco_partial_fill_threshold_triggers_cancel() {
+        let manager = OrderManager::new();
+        for id in ["1", "2", "3"] {
+            manager.add_order(working_order(id, 4)).unwrap();
+        }
+        let group = manager.create_oco_group(vec!["1".to_string(), "2".to_string(), "3".to_string()], Some(0.5)).unwrap();
+        assert_eq!(manager.take_oco_updates().len(), 1);
+        // 已在组内的订单不能再加入其他组
+        assert!(manager.create_oco_group(vec!["1".to_string(), "2".to_string()], None).is_err());
+
+        // 成交 1/4 未达阈值
+        manager.add_trade(trade("t1", "2", OrderDirection::Sell, 3500.0)).unwrap();
+        assert!(manager.take_oco_updates().is_empty());
+        manager.add_trade(trade("t2", "2", OrderDirection::Sell, 3500.0)).unwrap();
+        let updates = manager.take_oco_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].status, OcoGroupStatus::Triggered);
+        assert_eq!(updates[0].triggered_by.as_deref(), Some("2"));
+        let mut cancels = updates[0].cancel_order_ids.clone();
+        cancels.sort();
+        assert_eq!(cancels, vec!["1", "3"]);
+
+        // 其余成员撤单后组完成
+        for id in ["1", "3"] {
+            let mut order = working_order(id, 4);
+            order.status = OrderStatusType::Canceled;
+            manager.update_order(order).unwrap();
+        }
+        assert_eq!(manager.get_oco_group(&group.id).unwrap().status, OcoGroupStatus::Completed);
+        assert_eq!(manager.take_oco_updates().last().unwrap().status, OcoGroupStatus::Completed);
+    }
+
+    #[test]
+    fn test_order_expiry_schedule() {
+        let manager = OrderManager::new();
+        let now = chrono::Utc::now();
+        let mut tagged = working_order("1", 1);
+        tagged.tags.insert(EXPIRE_AT_TAG.to_string(), (now + chrono::Duration::seconds(30)).to_rfc3339());
+        tagged.tags.insert("strategy".to_string(), "scalp".to_string());
+        manager.add_order(tagged).unwrap();
+        let mut scalp = working_order("2", 1);
+        scalp.tags.insert("strategy".to_string(), "scalp".to_string());
+        manager.add_order(scalp).unwrap();
+        manager.add_order(working_order("3", 1)).unwrap();
+        assert_eq!(manager.get_order_expiries().len(), 1);
+
+        // 按标签批量设置，覆盖标签中的到期时间
+        let close = now + chrono::Duration::seconds(60);
+        let set = manager.set_expiry_by_tag("strategy", "scalp", close, Some("收盘前撤销剥头皮挂单".to_string()));
+        assert_eq!(set.len(), 2);
+        assert!(manager.set_order_expiry("9", close, None).is_err());
+
+        assert!(manager.due_expiries(now + chrono::Duration::seconds(59)).is_empty());
+        // 已结束订单的计划不再撤单
+        let mut done = working_order("2", 1);
+        done.status = OrderStatusType::AllTraded;
+        manager.update_order(done).unwrap();
+        let due = manager.due_expiries(close);
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].order_id.as_str(), due[0].reason.as_str()), ("1", "收盘前撤销剥头皮挂单"));
+
+        // 撤单回报前计划保留，超时未确认才重新撤单
+        assert_eq!(manager.get_order_expiries().len(), 1);
+        assert!(manager.due_expiries(close + chrono::Duration::seconds(1)).is_empty());
+        assert_eq!(manager.due_expiries(close + chrono::Duration::seconds(EXPIRY_CANCEL_RETRY_SECS)).len(), 1);
+        assert!(manager.take_expired_orders().is_empty());
+        let mut cancelled = working_order("1", 1);
+        cancelled.status = OrderStatusType::Canceled;
+        manager.update_order(cancelled).unwrap();
+        assert!(manager.get_order_expiries().is_empty());
+        assert_eq!(manager.take_expired_orders()[0].order_id, "1");
+
+        // 发送失败的撤单在下次检查时重试，不可撤的订单放弃计划
+        manager.set_order_expiry("3", close, None).unwrap();
+        assert_eq!(manager.due_expiries(close).len(), 1);
+        manager.expiry_cancel_failed("3", false);
+        assert_eq!(manager.due_expiries(close).len(), 1);
+        manager.expiry_cancel_failed("3", true);
+        assert!(manager.get_order_expiries().is_empty());
+    }
+
+    #[test]
+    fn test_oco_validation_expiry_and_dissolve() {
+        let manager = OrderManager::new();
+        for id in ["1", "2", "3", "4"] {
+            manager.add_order(working_order(id, 1)).unwrap();
+        }
+        assert!(manager.create_oco_group(vec!["1".to_string()], None).is_err());
+        assert!(manager.create_oco_group(vec!["1".to_string(), "9".to_string()], None).is_err());
+        assert!(manager.create_oco_group(vec!["1".to_string(), "2".to_string()], Some(0.0)).is_err());
+
+        let group = manager.create_oco_group(vec!["1".to_string(), "2".to_string()], None).unwrap();
+        for id in ["1", "2"] {
+            let mut order = working_order(id, 1);
+            order.status = OrderStatusType::Canceled;
+            manager.update_order(order).unwrap();
+        }
+        assert_eq!(manager.get_oco_group(&group.id).unwrap().status, OcoGroupStatus::Expired);
+
+        let group = manager.create_oco_group(vec!["3".to_string(), "4".to_string()], None).unwrap();
+        assert_eq!(manager.dissolve_oco_group(&group.id).unwrap().status, OcoGroupStatus::Dissolved);
+        // 解散后成员可重新组队
+        assert!(manager.create_oco_group(vec!["3".to_string(), "4".to_string()], None).is_ok());
+        assert_eq!(manager.get_oco_groups().len(), 3);
+    }
+}
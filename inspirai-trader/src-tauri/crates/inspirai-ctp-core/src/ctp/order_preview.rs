@@ -0,0 +1,352 @@
+use crate::ctp::{
+    CtpError,
+    models::{CommissionRate, MarginRate, OffsetFlag, OrderDirection, OrderInput, PositionDirection},
+    order_sizing::margin_per_lot,
+    order_validation::{order_request_from_input, OrderValidator, ValidationContext, ValidationIssue},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 保证金率、手续费率缓存有效期，费率在交易日内基本不变
+pub const DEFAULT_RATE_CACHE_TTL: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// 保证金率、手续费率缓存
+///
+/// 下单面板每次修改都会重新预览，命中缓存时不再发起费率查询，避免触发流控
+#[derive(Clone)]
+pub struct RateCache {
+    ttl: Duration,
+    margin: Arc<Mutex<HashMap<String, (MarginRate, Instant)>>>,
+    commission: Arc<Mutex<HashMap<String, (CommissionRate, Instant)>>>,
+}
+
+impl Default for RateCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RATE_CACHE_TTL)
+    }
+}
+
+impl RateCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            margin: Arc::new(Mutex::new(HashMap::new())),
+            commission: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn margin(&self, instrument_id: &str) -> Option<MarginRate> {
+        let cache = self.margin.lock().unwrap();
+        cache
+            .get(instrument_id)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(rate, _)| rate.clone())
+    }
+
+    pub fn commission(&self, instrument_id: &str) -> Option<CommissionRate> {
+        let cache = self.commission.lock().unwrap();
+        cache
+            .get(instrument_id)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(rate, _)| rate.clone())
+    }
+
+    pub fn put_margin(&self, rate: &MarginRate) {
+        self.margin.lock().unwrap().insert(rate.instrument_id.clone(), (rate.clone(), Instant::now()));
+    }
+
+    pub fn put_commission(&self, rate: &CommissionRate) {
+        self.commission.lock().unwrap().insert(rate.instrument_id.clone(), (rate.clone(), Instant::now()));
+    }
+
+    /// 换日或重新登录后清空
+    pub fn clear(&self) {
+        self.margin.lock().unwrap().clear();
+        self.commission.lock().unwrap().clear();
+    }
+}
+
+/// 合约单边持仓（手）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionSides {
+    pub long: i32,
+    pub short: i32,
+}
+
+/// 下单前预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderPreview {
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    pub offset: OffsetFlag,
+    pub volume: u32,
+    /// 计算所用价格：限价单为取整后的委托价，市价单为对手价或最新价
+    pub price: f64,
+    pub notional: f64,
+    /// 开仓占用的保证金，平仓为 0
+    pub required_margin: f64,
+    /// 平仓释放的保证金，按持仓已占用保证金折算
+    pub released_margin: f64,
+    /// 预估手续费，缺少手续费率时为 None
+    pub estimated_fee: Option<f64>,
+    pub available_before: f64,
+    /// 成交后可用资金，不含平仓盈亏
+    pub available_after: f64,
+    pub position_before: PositionSides,
+    pub position_after: PositionSides,
+    /// 按下单预校验得到的问题
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// 组合合约信息、费率、账户和持仓计算下单预览
+///
+/// 平今、平昨分别按平今、平仓费率计费；上期所、能源中心的“平仓”即平昨，
+/// 其他交易所先平昨仓，超出部分按平今计费
+pub fn preview_order(
+    order: &OrderInput,
+    ctx: &ValidationContext,
+    margin_rate: Option<&MarginRate>,
+    commission_rate: Option<&CommissionRate>,
+) -> Result<OrderPreview, CtpError> {
+    let request = order_request_from_input(order, "")?;
+    let instrument = ctx
+        .instrument
+        .as_ref()
+        .ok_or_else(|| CtpError::NotFound(format!("合约 {} 不存在", order.instrument_id)))?;
+    let account = ctx
+        .account
+        .as_ref()
+        .ok_or_else(|| CtpError::StateError("账户资金不可用".to_string()))?;
+
+    let validation = OrderValidator::validate(order, ctx);
+    let valid = |p: &f64| *p > 0.0 && *p < f64::MAX;
+    let price = if order.order_type == "Market" {
+        ctx.market.as_ref().and_then(|market| {
+            let quote = match request.direction {
+                OrderDirection::Buy => market.ask_price,
+                OrderDirection::Sell => market.bid_price,
+            };
+            Some(quote).filter(valid).or(Some(market.last_price).filter(valid))
+        })
+    } else {
+        Some(validation.normalized.price).filter(valid)
+    }
+    .ok_or_else(|| CtpError::StateError(format!("合约 {} 没有可用价格", order.instrument_id)))?;
+
+    let volume = order.volume;
+    let multiplier = instrument.volume_multiple as f64;
+    let notional = price * multiplier * volume as f64;
+
+    // 卖出平多头，买入平空头
+    let closed_side = match request.direction {
+        OrderDirection::Buy => PositionDirection::Short,
+        OrderDirection::Sell => PositionDirection::Long,
+    };
+    let held = ctx
+        .positions
+        .iter()
+        .filter(|p| p.instrument_id == order.instrument_id);
+    let mut before = PositionSides::default();
+    let (mut closed_total, mut closed_yesterday, mut closed_margin) = (0, 0, 0.0);
+    for position in held {
+        match position.direction {
+            PositionDirection::Long => before.long += position.total_position,
+            PositionDirection::Short => before.short += position.total_position,
+        }
+        if position.direction == closed_side {
+            closed_total += position.total_position;
+            closed_yesterday += position.yesterday_position;
+            closed_margin += position.margin;
+        }
+    }
+
+    let opening = request.offset_flag == OffsetFlag::Open;
+    let mut after = before;
+    let (required_margin, released_margin) = if opening {
+        match request.direction {
+            OrderDirection::Buy => after.long += volume as i32,
+            OrderDirection::Sell => after.short += volume as i32,
+        }
+        (margin_per_lot(instrument, request.direction, price, margin_rate) * volume as f64, 0.0)
+    } else {
+        let closed = (volume as i32).min(closed_total);
+        match closed_side {
+            PositionDirection::Long => after.long -= closed,
+            PositionDirection::Short => after.short -= closed,
+        }
+        let released = if closed_total > 0 {
+            closed_margin * closed as f64 / closed_total as f64
+        } else {
+            0.0
+        };
+        (0.0, released)
+    };
+
+    let estimated_fee = commission_rate.map(|rate| {
+        let lot_value = price * multiplier;
+        let fee = |by_money: f64, by_volume: f64, lots: u32| (lot_value * by_money + by_volume) * lots as f64;
+        match request.offset_flag {
+            OffsetFlag::Open => fee(rate.open_ratio_by_money, rate.open_ratio_by_volume, volume),
+            OffsetFlag::CloseToday => fee(rate.close_today_ratio_by_money, rate.close_today_ratio_by_volume, volume),
+            OffsetFlag::CloseYesterday => fee(rate.close_ratio_by_money, rate.close_ratio_by_volume, volume),
+            _ if matches!(instrument.exchange_id.as_str(), "SHFE" | "INE") => {
+                fee(rate.close_ratio_by_money, rate.close_ratio_by_volume, volume)
+            }
+            _ => {
+                let yesterday = volume.min(closed_yesterday.max(0) as u32);
+                fee(rate.close_ratio_by_money, rate.close_ratio_by_volume, yesterday)
+                    + fee(rate.close_today_ratio_by_money, rate.close_today_ratio_by_volume, volume - yesterday)
+            }
+        }
+    });
+
+    let available_after = account.available - required_margin + released_margin - estimated_fee.unwrap_or(0.0);
+    Ok(OrderPreview {
+        instrument_id: order.instrument_id.clone(),
+        direction: request.direction,
+        offset: request.offset_flag,
+        volume,
+        price,
+        notional,
+        required_margin,
+        released_margin,
+        estimated_fee,
+        available_before: account.available,
+        available_after,
+        position_before: before,
+        position_after: after,
+        issues: validation.issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::models::{AccountInfo, InstrumentInfo, Position};
+
+    fn instrument(exchange_id: &str) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: exchange_id.to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: 10,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    fn context(exchange_id: &str) -> ValidationContext {
+        ValidationContext {
+            instrument: Some(instrument(exchange_id)),
+            account: Some(AccountInfo {
+                account_id: "test".to_string(),
+                currency_id: "CNY".to_string(),
+                available: 80_000.0,
+                balance: 100_000.0,
+                margin: 20_000.0,
+                frozen_margin: 0.0,
+                frozen_commission: 0.0,
+                curr_margin: 20_000.0,
+                commission: 0.0,
+                close_profit: 0.0,
+                position_profit: 0.0,
+                risk_ratio: 0.2,
+            }),
+            positions: vec![Position {
+                instrument_id: "rb2501".to_string(),
+                direction: PositionDirection::Long,
+                total_position: 4,
+                yesterday_position: 1,
+                today_position: 3,
+                open_cost: 0.0,
+                position_cost: 0.0,
+                margin: 14_000.0,
+                unrealized_pnl: 0.0,
+                realized_pnl: 0.0,
+            }],
+            ..ValidationContext::default()
+        }
+    }
+
+    fn order(direction: &str, offset: &str, volume: u32) -> OrderInput {
+        OrderInput {
+            instrument_id: "rb2501".to_string(),
+            direction: direction.to_string(),
+            offset: offset.to_string(),
+            price: 3500.0,
+            volume,
+            order_type: "Limit".to_string(),
+            time_condition: "GFD".to_string(),
+            volume_condition: "Any".to_string(),
+            min_volume: 1,
+            contingent_condition: "Immediately".to_string(),
+            stop_price: 0.0,
+            force_close_reason: "NotForceClose".to_string(),
+            is_auto_suspend: false,
+            tags: HashMap::new(),
+        }
+    }
+
+    fn commission() -> CommissionRate {
+        CommissionRate {
+            instrument_id: "rb2501".to_string(),
+            open_ratio_by_money: 0.0001,
+            open_ratio_by_volume: 0.0,
+            close_ratio_by_money: 0.0001,
+            close_ratio_by_volume: 0.0,
+            close_today_ratio_by_money: 0.001,
+            close_today_ratio_by_volume: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_open_preview() {
+        let preview = preview_order(&order("Buy", "Open", 2), &context("SHFE"), None, Some(&commission())).unwrap();
+        // 每手 3500 * 10 * 0.1 = 3500，手续费 35000 * 0.0001 * 2 = 7
+        assert_eq!(preview.required_margin, 7_000.0);
+        assert!((preview.estimated_fee.unwrap() - 7.0).abs() < 1e-9);
+        assert!((preview.available_after - (80_000.0 - 7_000.0 - 7.0)).abs() < 1e-9);
+        assert_eq!(preview.position_after, PositionSides { long: 6, short: 0 });
+        assert!(preview.issues.is_empty());
+    }
+
+    #[test]
+    fn test_close_splits_fee_and_releases_margin() {
+        let ctx = context("DCE");
+        let preview = preview_order(&order("Sell", "Close", 2), &ctx, None, Some(&commission())).unwrap();
+        // 1 手平昨 3.5，1 手平今 35
+        assert!((preview.estimated_fee.unwrap() - 38.5).abs() < 1e-9);
+        assert_eq!(preview.released_margin, 7_000.0);
+        assert_eq!(preview.required_margin, 0.0);
+        assert_eq!(preview.position_after, PositionSides { long: 2, short: 0 });
+
+        // 上期所平仓即平昨
+        let preview = preview_order(&order("Sell", "Close", 2), &context("SHFE"), None, Some(&commission())).unwrap();
+        assert!((preview.estimated_fee.unwrap() - 7.0).abs() < 1e-9);
+
+        let preview = preview_order(&order("Sell", "Close", 5), &ctx, None, None).unwrap();
+        assert!(preview.estimated_fee.is_none());
+        assert_eq!(preview.position_after.long, 0);
+        assert!(!preview.issues.is_empty());
+    }
+}
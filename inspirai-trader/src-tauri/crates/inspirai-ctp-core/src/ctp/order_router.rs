@@ -0,0 +1,226 @@
+use crate::ctp::{
+    CtpError, OrderPriceType, OrderRequest, OrderStatus, OrderType, TimeInForce,
+    utils::{ConversionPools, DataConverter},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// CTP 交易通道名称
+pub const CTP_ROUTER: &str = "ctp";
+
+/// 交易通道能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterCapabilities {
+    /// 支持市价、最优价等非限价报单
+    pub supports_market_orders: bool,
+    /// 支持 FAK（立即成交剩余撤销）
+    pub supports_fak: bool,
+    /// 支持 FOK（全部成交否则撤销）
+    pub supports_fok: bool,
+}
+
+impl Default for RouterCapabilities {
+    fn default() -> Self {
+        Self {
+            supports_market_orders: true,
+            supports_fak: true,
+            supports_fok: true,
+        }
+    }
+}
+
+impl RouterCapabilities {
+    /// 检查订单是否在通道能力范围内
+    pub fn check(&self, router: &str, order: &OrderRequest) -> Result<(), CtpError> {
+        let is_market = order.order_type == OrderType::Market || order.price_type != OrderPriceType::Limit;
+        if is_market && !self.supports_market_orders {
+            return Err(CtpError::ValidationError(format!("交易通道 {} 不支持市价单", router)));
+        }
+        let time_in_force = order.time_in_force();
+        let supported = match time_in_force {
+            TimeInForce::GFD => true,
+            TimeInForce::FAK => self.supports_fak,
+            TimeInForce::FOK => self.supports_fok,
+        };
+        if !supported {
+            return Err(CtpError::ValidationError(format!("交易通道 {} 不支持 {:?} 报单", router, time_in_force)));
+        }
+        Ok(())
+    }
+}
+
+/// 交易通道
+///
+/// `TradingService` 只通过此 trait 报单和撤单，CTP-Mini、飞马或券商 REST 接口等
+/// 新通道实现此 trait 即可接入，无需改动交易服务
+pub trait OrderRouter: Send + Sync {
+    /// 通道名称，用于日志和错误信息
+    fn name(&self) -> &str;
+
+    /// 通道能力，报单前由交易服务校验
+    fn capabilities(&self) -> RouterCapabilities;
+
+    /// 发送报单，`order_ref` 为本地生成的订单引用
+    fn insert_order(&self, order: &OrderRequest, order_ref: &str) -> Result<(), CtpError>;
+
+    /// 发送撤单
+    fn cancel_order(&self, order: &OrderStatus) -> Result<(), CtpError>;
+}
+
+/// CTP 交易通道，封装 ctp2rs `TraderApi`
+pub struct CtpOrderRouter {
+    api: Arc<ctp2rs::v1alpha1::TraderApi>,
+    broker_id: String,
+    investor_id: String,
+    pools: ConversionPools,
+}
+
+impl CtpOrderRouter {
+    pub fn new(api: Arc<ctp2rs::v1alpha1::TraderApi>, broker_id: &str, investor_id: &str) -> Self {
+        Self {
+            api,
+            broker_id: broker_id.to_string(),
+            investor_id: investor_id.to_string(),
+            pools: ConversionPools::default(),
+        }
+    }
+
+    /// 与客户端共用对象池，命中率合并统计
+    pub fn with_conversion_pools(mut self, pools: ConversionPools) -> Self {
+        self.pools = pools;
+        self
+    }
+
+    fn next_request_id() -> i32 {
+        chrono::Utc::now().timestamp_millis() as i32 % 1000000
+    }
+}
+
+impl OrderRouter for CtpOrderRouter {
+    fn name(&self) -> &str {
+        CTP_ROUTER
+    }
+
+    fn capabilities(&self) -> RouterCapabilities {
+        RouterCapabilities::default()
+    }
+
+    fn insert_order(&self, order: &OrderRequest, order_ref: &str) -> Result<(), CtpError> {
+        // 将业务订单转换为 CTP 订单结构
+        let mut ctp_order = DataConverter::convert_order_request_pooled(
+            self.pools.orders(),
+            order,
+            &self.broker_id,
+            &self.investor_id,
+            order_ref,
+        )?;
+        let request_id = Self::next_request_id();
+
+        info!("发送报单录入请求，订单引用: {}, 请求ID: {}", order_ref, request_id);
+
+        let result = self.api.req_order_insert(&mut ctp_order, request_id);
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单录入请求发送失败".to_string(),
+            });
+        }
+
+        info!("报单录入请求已发送，订单引用: {}", order_ref);
+        Ok(())
+    }
+
+    fn cancel_order(&self, order: &OrderStatus) -> Result<(), CtpError> {
+        let mut order_action = ctp2rs::v1alpha1::CThostFtdcInputOrderActionField::default();
+
+        // 使用 ctp2rs 提供的字符串赋值工具
+        use ctp2rs::ffi::AssignFromString;
+        order_action.BrokerID.assign_from_str(&self.broker_id);
+        order_action.InvestorID.assign_from_str(&self.investor_id);
+        order_action.OrderRef.assign_from_str(&order.order_ref);
+        order_action.InstrumentID.assign_from_str(&order.instrument_id);
+
+        // 设置撤单标志
+        order_action.ActionFlag = '0' as i8; // 删除
+        order_action.FrontID = 1; // 前置编号，应该从登录响应中获取
+        order_action.SessionID = 1; // 会话编号，应该从登录响应中获取
+
+        let request_id = Self::next_request_id();
+
+        info!("发送报单操作请求，订单引用: {}, 请求ID: {}", order.order_ref, request_id);
+
+        let result = self.api.req_order_action(&mut order_action, request_id);
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单操作请求发送失败".to_string(),
+            });
+        }
+
+        info!("报单操作请求已发送，订单引用: {}", order.order_ref);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{
+        OffsetFlag, OrderContingentCondition, OrderDirection, OrderForceCloseReason, OrderTags, OrderTimeCondition,
+        OrderVolumeCondition,
+    };
+
+    fn order(price_type: OrderPriceType, time_condition: OrderTimeCondition, volume_condition: OrderVolumeCondition) -> OrderRequest {
+        OrderRequest {
+            instrument_id: "rb2501".to_string(),
+            order_ref: String::new(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume: 1,
+            order_type: OrderType::Limit,
+            price_type,
+            time_condition,
+            volume_condition,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_capabilities_reject_unsupported_orders() {
+        let limited = RouterCapabilities {
+            supports_market_orders: false,
+            supports_fak: true,
+            supports_fok: false,
+        };
+        use OrderTimeCondition::*;
+        use OrderVolumeCondition::*;
+
+        assert!(limited.check("mini", &order(OrderPriceType::Limit, GFD, Any)).is_ok());
+        assert!(limited.check("mini", &order(OrderPriceType::Limit, IOC, Any)).is_ok());
+        let err = limited.check("mini", &order(OrderPriceType::Limit, IOC, All)).unwrap_err();
+        assert!(err.to_string().contains("FOK"));
+        assert!(limited.check("mini", &order(OrderPriceType::Best, GFD, Any)).is_err());
+
+        let mut market = order(OrderPriceType::Limit, GFD, Any);
+        market.order_type = OrderType::Market;
+        assert!(limited.check("mini", &market).is_err());
+    }
+
+    #[test]
+    fn test_default_capabilities_accept_all() {
+        let caps = RouterCapabilities::default();
+        for time_condition in [OrderTimeCondition::GFD, OrderTimeCondition::IOC] {
+            for volume_condition in [OrderVolumeCondition::Any, OrderVolumeCondition::All] {
+                assert!(caps.check(CTP_ROUTER, &order(OrderPriceType::Market, time_condition, volume_condition)).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::ctp::{
+    CtpError,
+    models::{AccountInfo, InstrumentInfo, MarginRate, OrderDirection, RiskParams},
+};
+use serde::{Deserialize, Serialize};
+
+/// 默认保证金占用上限（占权益比例）
+pub const DEFAULT_MARGIN_UTILIZATION: f64 = 0.8;
+
+/// 最大可开手数的计算结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaxOpenVolume {
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    /// 计算所用价格
+    pub price: f64,
+    /// 每手保证金
+    pub margin_per_lot: f64,
+    /// 保证金占用上限
+    pub utilization_cap: f64,
+    /// 可用于新开仓的保证金
+    pub margin_budget: f64,
+    pub max_volume: u32,
+    /// 手数被单笔上限截断时的说明
+    pub capped_by: Option<String>,
+}
+
+/// 每手开仓保证金，优先使用查询到的保证金率，否则使用合约信息中的比例
+pub fn margin_per_lot(
+    instrument: &InstrumentInfo,
+    direction: OrderDirection,
+    price: f64,
+    margin_rate: Option<&MarginRate>,
+) -> f64 {
+    let (by_money, by_volume) = match (margin_rate, direction) {
+        (Some(rate), OrderDirection::Buy) => (rate.long_margin_ratio_by_money, rate.long_margin_ratio_by_volume),
+        (Some(rate), OrderDirection::Sell) => (rate.short_margin_ratio_by_money, rate.short_margin_ratio_by_volume),
+        (None, OrderDirection::Buy) => (instrument.long_margin_ratio, 0.0),
+        (None, OrderDirection::Sell) => (instrument.short_margin_ratio, 0.0),
+    };
+    price * instrument.volume_multiple as f64 * by_money + by_volume
+}
+
+/// 按可用资金、保证金率和占用上限计算最大可开手数
+///
+/// 新开仓保证金不超过 `权益 × 上限 - 已占用保证金`，也不超过可用资金，
+/// 结果再按合约单笔限价单上限和风控单笔上限截断
+pub fn max_open_volume(
+    instrument: &InstrumentInfo,
+    direction: OrderDirection,
+    price: f64,
+    margin_rate: Option<&MarginRate>,
+    account: &AccountInfo,
+    risk_params: Option<&RiskParams>,
+    utilization_cap: f64,
+) -> Result<MaxOpenVolume, CtpError> {
+    if price <= 0.0 || price >= f64::MAX {
+        return Err(CtpError::InvalidParameter(format!("无效的计算价格: {}", price)));
+    }
+    if !(utilization_cap > 0.0 && utilization_cap <= 1.0) {
+        return Err(CtpError::InvalidParameter("保证金占用上限必须在0到1之间".to_string()));
+    }
+
+    let margin_per_lot = margin_per_lot(instrument, direction, price, margin_rate);
+    if margin_per_lot <= 0.0 {
+        return Err(CtpError::ValidationError(format!("合约 {} 缺少保证金率", instrument.instrument_id)));
+    }
+
+    let used = account.curr_margin.max(account.margin) + account.frozen_margin;
+    let margin_budget = (account.balance * utilization_cap - used).min(account.available).max(0.0);
+    let mut max_volume = (margin_budget / margin_per_lot).floor() as u32;
+
+    let mut capped_by = None;
+    let mut cap = |limit: i32, reason: String| {
+        if limit > 0 && max_volume > limit as u32 {
+            max_volume = limit as u32;
+            capped_by = Some(reason);
+        }
+    };
+    cap(instrument.max_limit_order_volume, format!("合约单笔上限 {} 手", instrument.max_limit_order_volume));
+    if let Some(params) = risk_params {
+        cap(params.max_order_volume, format!("风控单笔上限 {} 手", params.max_order_volume));
+    }
+
+    Ok(MaxOpenVolume {
+        instrument_id: instrument.instrument_id.clone(),
+        direction,
+        price,
+        margin_per_lot,
+        utilization_cap,
+        margin_budget,
+        max_volume,
+        capped_by,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument() -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: 10,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    fn account(balance: f64, margin: f64) -> AccountInfo {
+        AccountInfo {
+            account_id: "test".to_string(),
+            currency_id: "CNY".to_string(),
+            available: balance - margin,
+            balance,
+            margin,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            curr_margin: margin,
+            commission: 0.0,
+            close_profit: 0.0,
+            position_profit: 0.0,
+            risk_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_volume_from_budget_and_margin_rate() {
+        // 每手保证金 3500 * 10 * 0.1 = 3500，预算 100000 * 0.8 - 20000 = 60000
+        let result =
+            max_open_volume(&instrument(), OrderDirection::Buy, 3500.0, None, &account(100_000.0, 20_000.0), None, 0.8)
+                .unwrap();
+        assert_eq!(result.margin_per_lot, 3500.0);
+        assert_eq!(result.margin_budget, 60_000.0);
+        assert_eq!(result.max_volume, 17);
+        assert!(result.capped_by.is_none());
+
+        let rate = MarginRate {
+            instrument_id: "rb2501".to_string(),
+            long_margin_ratio_by_money: 0.1,
+            long_margin_ratio_by_volume: 0.0,
+            short_margin_ratio_by_money: 0.2,
+            short_margin_ratio_by_volume: 0.0,
+        };
+        let result = max_open_volume(
+            &instrument(),
+            OrderDirection::Sell,
+            3500.0,
+            Some(&rate),
+            &account(100_000.0, 20_000.0),
+            None,
+            0.8,
+        )
+        .unwrap();
+        assert_eq!(result.max_volume, 8);
+
+        // 已超过占用上限时为 0
+        let result =
+            max_open_volume(&instrument(), OrderDirection::Buy, 3500.0, None, &account(100_000.0, 90_000.0), None, 0.8)
+                .unwrap();
+        assert_eq!(result.max_volume, 0);
+    }
+
+    #[test]
+    fn test_caps_and_invalid_input() {
+        let params = RiskParams {
+            max_position_ratio: 1.0,
+            max_single_loss: 0.0,
+            max_daily_loss: 0.0,
+            max_order_volume: 5,
+            position_limit: Default::default(),
+            forbidden_instruments: vec![],
+            auto_stop_loss: false,
+            stop_loss_ratio: 0.0,
+            auto_take_profit: false,
+            take_profit_ratio: 0.0,
+            block_limit_locked: false,
+        };
+        let result = max_open_volume(
+            &instrument(),
+            OrderDirection::Buy,
+            3500.0,
+            None,
+            &account(1_000_000.0, 0.0),
+            Some(&params),
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(result.max_volume, 5);
+        assert!(result.capped_by.unwrap().contains("风控"));
+
+        let acc = account(100_000.0, 0.0);
+        assert!(max_open_volume(&instrument(), OrderDirection::Buy, 0.0, None, &acc, None, 0.8).is_err());
+        assert!(max_open_volume(&instrument(), OrderDirection::Buy, 3500.0, None, &acc, None, 1.5).is_err());
+    }
+}
@@ -0,0 +1,649 @@
+use crate::ctp::{
+    CtpError,
+    models::{
+        AccountInfo, InstrumentInfo, MarketData, OffsetFlag, OrderContingentCondition, OrderDirection,
+        OrderForceCloseReason, OrderInput, OrderPriceType, OrderRequest, OrderTimeCondition, OrderType,
+        OrderVolumeCondition, Position, PositionDirection, RiskParams, TimeInForce,
+    },
+    price_limit::LimitStatus,
+};
+use serde::{Deserialize, Serialize};
+
+/// 限价偏离最新价超过该比例时提示
+const PRICE_DEVIATION_WARNING: f64 = 0.03;
+/// 预估保证金占可用资金超过该比例时提示
+const MARGIN_USAGE_WARNING: f64 = 0.8;
+
+/// 将前端报单输入转换为订单请求
+pub fn order_request_from_input(order: &OrderInput, order_ref: &str) -> Result<OrderRequest, CtpError> {
+    let request = OrderRequest {
+        instrument_id: order.instrument_id.clone(),
+        order_ref: order_ref.to_string(),
+        direction: match order.direction.as_str() {
+            "Buy" => OrderDirection::Buy,
+            "Sell" => OrderDirection::Sell,
+            _ => return Err(CtpError::ValidationError("无效的买卖方向".to_string())),
+        },
+        offset_flag: match order.offset.as_str() {
+            "Open" => OffsetFlag::Open,
+            "Close" => OffsetFlag::Close,
+            "CloseToday" => OffsetFlag::CloseToday,
+            "CloseYesterday" => OffsetFlag::CloseYesterday,
+            _ => return Err(CtpError::ValidationError("无效的开平标志".to_string())),
+        },
+        price: order.price,
+        volume: order.volume,
+        order_type: match order.order_type.as_str() {
+            "Limit" => OrderType::Limit,
+            "Market" => OrderType::Market,
+            _ => OrderType::Limit,
+        },
+        price_type: match order.order_type.as_str() {
+            "Market" => OrderPriceType::Market,
+            _ => OrderPriceType::Limit,
+        },
+        time_condition: match order.time_condition.as_str() {
+            "IOC" => OrderTimeCondition::IOC,
+            "GFS" => OrderTimeCondition::GFS,
+            "GFD" => OrderTimeCondition::GFD,
+            _ => OrderTimeCondition::GFD,
+        },
+        volume_condition: match order.volume_condition.as_str() {
+            "Any" => OrderVolumeCondition::Any,
+            "Min" => OrderVolumeCondition::Min,
+            "All" => OrderVolumeCondition::All,
+            _ => OrderVolumeCondition::Any,
+        },
+        min_volume: order.min_volume,
+        contingent_condition: match order.contingent_condition.as_str() {
+            "Touch" => OrderContingentCondition::Touch,
+            "TouchProfit" => OrderContingentCondition::TouchProfit,
+            _ => OrderContingentCondition::Immediately,
+        },
+        stop_price: order.stop_price,
+        force_close_reason: match order.force_close_reason.as_str() {
+            "LackDeposit" => OrderForceCloseReason::LackDeposit,
+            _ => OrderForceCloseReason::NotForceClose,
+        },
+        is_auto_suspend: order.is_auto_suspend,
+        time_in_force: None,
+        tags: order.tags.clone(),
+    };
+    // 时间条件也接受 FAK、FOK
+    Ok(match order.time_condition.as_str() {
+        "FAK" => request.with_time_in_force(TimeInForce::FAK),
+        "FOK" => request.with_time_in_force(TimeInForce::FOK),
+        _ => request,
+    })
+}
+
+/// 校验问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    /// 提示，不阻止下单
+    Warning,
+    /// 错误，提交会被拒绝
+    Error,
+}
+
+/// 校验问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    /// 相关字段（price、volume、offset 等），供界面定位
+    pub field: String,
+    pub message: String,
+}
+
+/// 报单预校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderValidationResult {
+    /// 没有错误级问题
+    pub valid: bool,
+    /// 规范化后的订单（价格按最小变动价位取整）
+    pub normalized: OrderInput,
+    /// 名义价值
+    pub estimated_notional: Option<f64>,
+    /// 开仓预估保证金
+    pub estimated_margin: Option<f64>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl OrderValidationResult {
+    /// 报单路径使用：有错误级问题时返回全部错误原因，否则返回规范化后的订单
+    pub fn into_order(self) -> Result<OrderInput, CtpError> {
+        if self.valid {
+            return Ok(self.normalized);
+        }
+        let reasons: Vec<String> = self
+            .issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .map(|i| i.message.clone())
+            .collect();
+        Err(CtpError::ValidationError(reasons.join("; ")))
+    }
+}
+
+/// 预校验所需的上下文，缺失的部分跳过对应检查
+#[derive(Debug, Clone, Default)]
+pub struct ValidationContext {
+    pub instrument: Option<InstrumentInfo>,
+    pub market: Option<MarketData>,
+    pub positions: Vec<Position>,
+    pub account: Option<AccountInfo>,
+    pub risk_params: Option<RiskParams>,
+    /// 合约当前涨跌停状态
+    pub price_limit: Option<LimitStatus>,
+}
+
+/// 报单预校验
+///
+/// 依次执行基本检查、合约检查、价格取整、涨跌停与偏离、风控限额、可平仓位和
+/// 保证金预估，收集全部问题而不是遇错即停，便于界面在提交前逐项提示
+pub struct OrderValidator;
+
+impl OrderValidator {
+    pub fn validate(order: &OrderInput, ctx: &ValidationContext) -> OrderValidationResult {
+        let mut issues = Vec::new();
+        let mut normalized = order.clone();
+        let mut error = |field: &str, message: String| issues.push(issue(IssueSeverity::Error, field, message));
+
+        // 基本检查
+        let request = match order_request_from_input(order, "") {
+            Ok(request) => Some(request),
+            Err(e) => {
+                error("order", e.to_string());
+                None
+            }
+        };
+        if order.instrument_id.trim().is_empty() {
+            error("instrument_id", "合约代码不能为空".to_string());
+        }
+        if order.volume == 0 {
+            error("volume", "委托数量必须大于0".to_string());
+        }
+        let is_market = order.order_type == "Market";
+        if !is_market && order.price <= 0.0 {
+            error("price", "限价单价格必须大于0".to_string());
+        }
+
+        let Some(request) = request else {
+            return finish(normalized, None, None, issues);
+        };
+
+        // 合约检查与价格取整
+        if let Some(instrument) = &ctx.instrument {
+            check_instrument(order, instrument, is_market, &mut issues);
+            if !is_market && instrument.price_tick > 0.0 && order.price > 0.0 {
+                let rounded = round_to_tick(order.price, instrument.price_tick, request.direction);
+                if (rounded - order.price).abs() > 1e-9 {
+                    issues.push(issue(
+                        IssueSeverity::Warning,
+                        "price",
+                        format!("价格 {} 不是最小变动价位 {} 的整数倍，已调整为 {}", order.price, instrument.price_tick, rounded),
+                    ));
+                    normalized.price = rounded;
+                }
+            }
+        }
+
+        // 报单有效期
+        let exchange_id = ctx.instrument.as_ref().map(|i| i.exchange_id.as_str());
+        check_time_in_force(&request, exchange_id, is_market, &mut normalized, &mut issues);
+
+        // 涨跌停与最新价偏离
+        if let Some(market) = &ctx.market {
+            check_price_band(&normalized, market, is_market, &mut issues);
+        }
+
+        // 封板时缺少对手盘
+        if let Some(limit) = &ctx.price_limit {
+            let block = ctx.risk_params.as_ref().is_some_and(|p| p.block_limit_locked);
+            check_limit_locked(&request, limit, block, &mut issues);
+        }
+
+        // 风控限额
+        if let Some(params) = &ctx.risk_params {
+            check_risk_limits(&request, params, &ctx.positions, &mut issues);
+        }
+
+        // 可平仓位
+        if request.offset_flag != OffsetFlag::Open {
+            check_closeable(&request, &ctx.positions, &mut issues);
+        }
+
+        // 保证金预估
+        let reference_price = if is_market {
+            ctx.market.as_ref().map(|m| m.last_price).filter(|p| *p > 0.0)
+        } else {
+            Some(normalized.price)
+        };
+        let (notional, margin) = match (&ctx.instrument, reference_price) {
+            (Some(instrument), Some(price)) => {
+                let notional = price * instrument.volume_multiple as f64 * order.volume as f64;
+                let ratio = match request.direction {
+                    OrderDirection::Buy => instrument.long_margin_ratio,
+                    OrderDirection::Sell => instrument.short_margin_ratio,
+                };
+                let margin = (request.offset_flag == OffsetFlag::Open && ratio > 0.0).then_some(notional * ratio);
+                (Some(notional), margin)
+            }
+            _ => (None, None),
+        };
+        if let (Some(margin), Some(account)) = (margin, &ctx.account) {
+            if margin > account.available {
+                issues.push(issue(
+                    IssueSeverity::Error,
+                    "volume",
+                    format!("预估保证金 {:.2} 超过可用资金 {:.2}", margin, account.available),
+                ));
+            } else if margin > account.available * MARGIN_USAGE_WARNING {
+                issues.push(issue(
+                    IssueSeverity::Warning,
+                    "volume",
+                    format!("预估保证金 {:.2} 将占用 {:.0}% 可用资金", margin, margin / account.available * 100.0),
+                ));
+            }
+        }
+
+        finish(normalized, notional, margin, issues)
+    }
+}
+
+fn issue(severity: IssueSeverity, field: &str, message: String) -> ValidationIssue {
+    ValidationIssue {
+        severity,
+        field: field.to_string(),
+        message,
+    }
+}
+
+fn finish(
+    normalized: OrderInput,
+    estimated_notional: Option<f64>,
+    estimated_margin: Option<f64>,
+    issues: Vec<ValidationIssue>,
+) -> OrderValidationResult {
+    OrderValidationResult {
+        valid: !issues.iter().any(|i| i.severity == IssueSeverity::Error),
+        normalized,
+        estimated_notional,
+        estimated_margin,
+        issues,
+    }
+}
+
+/// 按最小变动价位取整，向不利于成交的方向取整（买单向下、卖单向上），避免意外提高成交激进度
+pub fn round_to_tick(price: f64, price_tick: f64, direction: OrderDirection) -> f64 {
+    let ticks = price / price_tick;
+    // 消除浮点误差，避免本已对齐的价格被多调一档
+    let nearest = ticks.round();
+    let ticks = if (ticks - nearest).abs() < 1e-6 {
+        nearest
+    } else {
+        match direction {
+            OrderDirection::Buy => ticks.floor(),
+            OrderDirection::Sell => ticks.ceil(),
+        }
+    };
+    // 按价位小数位数修正表示误差
+    let decimals = format!("{}", price_tick).split('.').nth(1).map_or(0, |d| d.len()) as i32;
+    let factor = 10f64.powi(decimals);
+    (ticks * price_tick * factor).round() / factor
+}
+
+fn check_instrument(order: &OrderInput, instrument: &InstrumentInfo, is_market: bool, issues: &mut Vec<ValidationIssue>) {
+    if !instrument.is_trading {
+        issues.push(issue(IssueSeverity::Error, "instrument_id", format!("合约 {} 当前不可交易", instrument.instrument_id)));
+    }
+
+    let (min, max) = if is_market {
+        (instrument.min_market_order_volume, instrument.max_market_order_volume)
+    } else {
+        (instrument.min_limit_order_volume, instrument.max_limit_order_volume)
+    };
+    let volume = order.volume as i32;
+    if min > 0 && volume < min {
+        issues.push(issue(IssueSeverity::Error, "volume", format!("委托数量不能小于 {} 手", min)));
+    }
+    if max > 0 && volume > max {
+        issues.push(issue(IssueSeverity::Error, "volume", format!("单笔委托不能超过 {} 手", max)));
+    }
+}
+
+/// 交易所支持的报单有效期，未知交易所只允许当日有效
+fn supported_time_in_force(exchange_id: &str) -> &'static [TimeInForce] {
+    match exchange_id {
+        "SHFE" | "INE" | "DCE" | "CZCE" | "CFFEX" | "GFEX" => &[TimeInForce::GFD, TimeInForce::FAK, TimeInForce::FOK],
+        _ => &[TimeInForce::GFD],
+    }
+}
+
+fn check_time_in_force(
+    request: &OrderRequest,
+    exchange_id: Option<&str>,
+    is_market: bool,
+    normalized: &mut OrderInput,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let mut time_in_force = request.time_in_force();
+    // 交易所不接受当日有效的市价单
+    if is_market && time_in_force == TimeInForce::GFD {
+        time_in_force = TimeInForce::FAK;
+        normalized.time_condition = "FAK".to_string();
+        issues.push(issue(IssueSeverity::Warning, "time_condition", "市价单只能立即成交，已调整为 FAK".to_string()));
+    }
+    if let Some(exchange_id) = exchange_id.filter(|e| !e.is_empty()) {
+        if !supported_time_in_force(exchange_id).contains(&time_in_force) {
+            issues.push(issue(
+                IssueSeverity::Error,
+                "time_condition",
+                format!("交易所 {} 不支持 {:?} 报单", exchange_id, time_in_force),
+            ));
+        }
+    }
+}
+
+fn check_price_band(order: &OrderInput, market: &MarketData, is_market: bool, issues: &mut Vec<ValidationIssue>) {
+    if is_market {
+        return;
+    }
+    let valid = |p: f64| p > 0.0 && p < f64::MAX;
+
+    if valid(market.upper_limit_price) && order.price > market.upper_limit_price {
+        issues.push(issue(IssueSeverity::Error, "price", format!("价格高于涨停价 {}", market.upper_limit_price)));
+    }
+    if valid(market.lower_limit_price) && order.price < market.lower_limit_price {
+        issues.push(issue(IssueSeverity::Error, "price", format!("价格低于跌停价 {}", market.lower_limit_price)));
+    }
+    if valid(market.last_price) {
+        let deviation = (order.price - market.last_price).abs() / market.last_price;
+        if deviation > PRICE_DEVIATION_WARNING {
+            issues.push(issue(
+                IssueSeverity::Warning,
+                "price",
+                format!("价格偏离最新价 {} 达 {:.1}%", market.last_price, deviation * 100.0),
+            ));
+        }
+    }
+}
+
+/// 风控开启封板拦截时，拒绝向封板方向报单
+///
+/// 下单与交易服务的提交路径共用，未开启拦截时仅在预校验中提示
+pub fn ensure_not_limit_locked(
+    order: &OrderRequest,
+    limit: Option<&LimitStatus>,
+    risk_params: Option<&RiskParams>,
+) -> Result<(), CtpError> {
+    let Some(limit) = limit.filter(|_| risk_params.is_some_and(|p| p.block_limit_locked)) else {
+        return Ok(());
+    };
+    let mut issues = Vec::new();
+    check_limit_locked(order, limit, true, &mut issues);
+    match issues.into_iter().next() {
+        Some(issue) => Err(CtpError::ValidationError(issue.message)),
+        None => Ok(()),
+    }
+}
+
+fn check_limit_locked(order: &OrderRequest, limit: &LimitStatus, block: bool, issues: &mut Vec<ValidationIssue>) {
+    if !limit.state.blocks(order.direction) {
+        return;
+    }
+    let (side, price) = match order.direction {
+        OrderDirection::Buy => ("涨停封板", limit.upper_limit_price),
+        OrderDirection::Sell => ("跌停封板", limit.lower_limit_price),
+    };
+    let severity = if block { IssueSeverity::Error } else { IssueSeverity::Warning };
+    issues.push(issue(
+        severity,
+        "direction",
+        format!("合约 {} 已{}（{}），委托缺少对手盘可能无法成交", order.instrument_id, side, price),
+    ));
+}
+
+fn check_risk_limits(order: &OrderRequest, params: &RiskParams, positions: &[Position], issues: &mut Vec<ValidationIssue>) {
+    if params.forbidden_instruments.iter().any(|id| id == &order.instrument_id) {
+        issues.push(issue(IssueSeverity::Error, "instrument_id", format!("合约 {} 已被禁止交易", order.instrument_id)));
+    }
+    if params.max_order_volume > 0 && order.volume as i32 > params.max_order_volume {
+        issues.push(issue(
+            IssueSeverity::Error,
+            "volume",
+            format!("委托数量超过单笔上限 {} 手", params.max_order_volume),
+        ));
+    }
+
+    if order.offset_flag == OffsetFlag::Open {
+        if let Some(limit) = params.position_limit.get(&order.instrument_id) {
+            let held: i32 = positions
+                .iter()
+                .filter(|p| p.instrument_id == order.instrument_id)
+                .map(|p| p.total_position)
+                .sum();
+            if held + order.volume as i32 > *limit {
+                issues.push(issue(
+                    IssueSeverity::Error,
+                    "volume",
+                    format!("开仓后持仓 {} 手超过限额 {} 手", held + order.volume as i32, limit),
+                ));
+            }
+        }
+    }
+}
+
+fn check_closeable(order: &OrderRequest, positions: &[Position], issues: &mut Vec<ValidationIssue>) {
+    // 卖出平多头，买入平空头
+    let side = match order.direction {
+        OrderDirection::Buy => PositionDirection::Short,
+        OrderDirection::Sell => PositionDirection::Long,
+    };
+    let closeable: i32 = positions
+        .iter()
+        .filter(|p| p.instrument_id == order.instrument_id && p.direction == side)
+        .map(|p| match order.offset_flag {
+            OffsetFlag::CloseToday => p.today_position,
+            OffsetFlag::CloseYesterday => p.yesterday_position,
+            _ => p.total_position,
+        })
+        .sum();
+
+    if (order.volume as i32) > closeable {
+        issues.push(issue(
+            IssueSeverity::Error,
+            "offset",
+            format!("可平仓位 {} 手，不足 {} 手", closeable, order.volume),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(direction: &str, offset: &str, price: f64, volume: u32) -> OrderInput {
+        OrderInput {
+            instrument_id: "rb2501".to_string(),
+            direction: direction.to_string(),
+            offset: offset.to_string(),
+            price,
+            volume,
+            order_type: "Limit".to_string(),
+            time_condition: "GFD".to_string(),
+            volume_condition: "Any".to_string(),
+            min_volume: 1,
+            contingent_condition: "Immediately".to_string(),
+            stop_price: 0.0,
+            force_close_reason: "NotForceClose".to_string(),
+            is_auto_suspend: false,
+            tags: Default::default(),
+        }
+    }
+
+    fn instrument() -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: "rb2501".to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: 10,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(3500.4, 1.0, OrderDirection::Buy), 3500.0);
+        assert_eq!(round_to_tick(3500.4, 1.0, OrderDirection::Sell), 3501.0);
+        assert_eq!(round_to_tick(3900.6, 0.2, OrderDirection::Buy), 3900.6);
+        assert_eq!(round_to_tick(3900.7, 0.2, OrderDirection::Sell), 3900.8);
+    }
+
+    #[test]
+    fn test_collects_all_issues() {
+        let ctx = ValidationContext {
+            instrument: Some(instrument()),
+            risk_params: Some(RiskParams {
+                max_position_ratio: 1.0,
+                max_single_loss: 0.0,
+                max_daily_loss: 0.0,
+                max_order_volume: 5,
+                position_limit: Default::default(),
+                forbidden_instruments: vec![],
+                auto_stop_loss: false,
+                stop_loss_ratio: 0.0,
+                auto_take_profit: false,
+                take_profit_ratio: 0.0,
+                block_limit_locked: false,
+            }),
+            ..ValidationContext::default()
+        };
+
+        // 平仓无持仓、超过单笔上限、价格需取整
+        let result = OrderValidator::validate(&input("Sell", "Close", 3500.5, 10), &ctx);
+        assert!(!result.valid);
+        assert_eq!(result.normalized.price, 3501.0);
+        let fields: Vec<&str> = result.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, vec!["price", "volume", "offset"]);
+        assert_eq!(result.estimated_margin, None);
+        // 报单路径拒绝，警告不计入原因
+        match result.into_order() {
+            Err(CtpError::ValidationError(reason)) => assert_eq!(reason.split("; ").count(), 2),
+            other => panic!("应拒绝报单: {:?}", other),
+        }
+
+        let result = OrderValidator::validate(&input("Buy", "Open", 3500.0, 2), &ctx);
+        assert!(result.valid);
+        assert_eq!(result.estimated_notional, Some(70000.0));
+        assert_eq!(result.estimated_margin, Some(7000.0));
+        assert_eq!(OrderValidator::validate(&input("Buy", "Open", 3500.5, 2), &ctx).into_order().unwrap().price, 3500.0);
+    }
+
+    #[test]
+    fn test_time_in_force_checked_against_exchange() {
+        let mut order = input("Buy", "Open", 3500.0, 1);
+        order.time_condition = "FOK".to_string();
+        let request = order_request_from_input(&order, "").unwrap();
+        assert_eq!((request.time_condition, request.volume_condition), (OrderTimeCondition::IOC, OrderVolumeCondition::All));
+
+        let mut ctx = ValidationContext {
+            instrument: Some(instrument()),
+            ..ValidationContext::default()
+        };
+        assert!(OrderValidator::validate(&order, &ctx).valid);
+
+        ctx.instrument.as_mut().unwrap().exchange_id = "SGE".to_string();
+        let result = OrderValidator::validate(&order, &ctx);
+        assert!(!result.valid);
+        assert!(result.issues[0].message.contains("FOK"));
+
+        // 当日有效的市价单调整为 FAK
+        let mut market = input("Buy", "Open", 0.0, 1);
+        market.order_type = "Market".to_string();
+        ctx.instrument = Some(instrument());
+        let result = OrderValidator::validate(&market, &ctx);
+        assert!(result.valid);
+        assert_eq!(result.normalized.time_condition, "FAK");
+    }
+
+    #[test]
+    fn test_margin_check_against_available() {
+        let ctx = ValidationContext {
+            instrument: Some(instrument()),
+            account: Some(AccountInfo {
+                account_id: "test".to_string(),
+                currency_id: "CNY".to_string(),
+                available: 5000.0,
+                balance: 5000.0,
+                margin: 0.0,
+                frozen_margin: 0.0,
+                frozen_commission: 0.0,
+                curr_margin: 0.0,
+                commission: 0.0,
+                close_profit: 0.0,
+                position_profit: 0.0,
+                risk_ratio: 0.0,
+            }),
+            ..ValidationContext::default()
+        };
+
+        let result = OrderValidator::validate(&input("Buy", "Open", 3500.0, 2), &ctx);
+        assert!(!result.valid);
+        assert!(result.issues[0].message.contains("超过可用资金"));
+    }
+
+    #[test]
+    fn test_limit_locked_warns_or_blocks() {
+        let mut ctx = ValidationContext {
+            price_limit: Some(LimitStatus {
+                upper_limit_price: 3800.0,
+                lower_limit_price: 3200.0,
+                state: crate::ctp::price_limit::LimitState::LimitUpLocked,
+                pinned_since: None,
+            }),
+            ..ValidationContext::default()
+        };
+
+        let result = OrderValidator::validate(&input("Buy", "Open", 3800.0, 1), &ctx);
+        assert!(result.valid);
+        assert_eq!(result.issues[0].severity, IssueSeverity::Warning);
+        assert!(OrderValidator::validate(&input("Sell", "Open", 3800.0, 1), &ctx).issues.is_empty());
+
+        ctx.risk_params = Some(RiskParams {
+            max_position_ratio: 1.0,
+            max_single_loss: 0.0,
+            max_daily_loss: 0.0,
+            max_order_volume: 0,
+            position_limit: Default::default(),
+            forbidden_instruments: vec![],
+            auto_stop_loss: false,
+            stop_loss_ratio: 0.0,
+            auto_take_profit: false,
+            take_profit_ratio: 0.0,
+            block_limit_locked: true,
+        });
+        let result = OrderValidator::validate(&input("Buy", "Open", 3800.0, 1), &ctx);
+        assert!(!result.valid);
+        assert_eq!(result.issues[0].field, "direction");
+    }
+}
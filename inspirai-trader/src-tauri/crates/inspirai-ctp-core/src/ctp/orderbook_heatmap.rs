@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// 单列价格阶梯的最大档数，避免盘口价差过大时生成超长数组
+const MAX_LADDER_STEPS: usize = 200;
+
+/// 盘口档位
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    pub price: f64,
+    pub volume: i32,
+}
+
+/// 五档盘口快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthSnapshot {
+    pub instrument_id: String,
+    pub last_price: f64,
+    pub update_time: String,
+    pub update_millisec: i32,
+    /// 买盘，由优到劣
+    pub bids: Vec<DepthLevel>,
+    /// 卖盘，由优到劣
+    pub asks: Vec<DepthLevel>,
+}
+
+impl DepthSnapshot {
+    /// 是否为多档行情（仅一档的合约不生成热力图）
+    pub fn has_depth(&self) -> bool {
+        self.bids.len() > 1 || self.asks.len() > 1
+    }
+
+    /// 相邻档位的最小价差，用于推断最小变动价位
+    fn min_gap(&self) -> Option<f64> {
+        let mut prices: Vec<f64> = self.bids.iter().chain(self.asks.iter()).map(|l| l.price).collect();
+        prices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        prices
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|gap| *gap > 1e-9)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+}
+
+/// 热力图单列（一个采样时刻的价格阶梯）
+///
+/// `sizes[i]` 对应价格 `base_price + i * price_tick`，买盘为正、卖盘为负、无挂单为 0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapColumn {
+    pub instrument_id: String,
+    /// 采样时间（毫秒时间戳）
+    pub timestamp_ms: i64,
+    /// 快照的交易所时间
+    pub update_time: String,
+    pub last_price: f64,
+    pub base_price: f64,
+    pub price_tick: f64,
+    pub sizes: Vec<i32>,
+}
+
+/// 热力图配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapConfig {
+    /// 采样间隔
+    pub sample_interval: Duration,
+    /// 每个合约保留的列数
+    pub history_len: usize,
+    /// 超过该时间未更新的盘口不再采样
+    pub stale_after: Duration,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_millis(500),
+            history_len: 1200,
+            stale_after: Duration::from_secs(60),
+        }
+    }
+}
+
+struct BookState {
+    snapshot: DepthSnapshot,
+    received_at: Instant,
+}
+
+#[derive(Default)]
+struct HeatmapInner {
+    books: HashMap<String, BookState>,
+    price_ticks: HashMap<String, f64>,
+    history: HashMap<String, VecDeque<HeatmapColumn>>,
+}
+
+/// 盘口热力图数据源
+///
+/// 行情回调写入最新五档快照，按固定节奏采样为价格阶梯列，
+/// 经独立的 broadcast 通道推送给 DOM/热力图组件，并在环形缓冲中保留历史
+#[derive(Clone)]
+pub struct OrderBookHeatmap {
+    config: HeatmapConfig,
+    inner: Arc<Mutex<HeatmapInner>>,
+    sender: broadcast::Sender<HeatmapColumn>,
+    sampler_started: Arc<AtomicBool>,
+}
+
+impl OrderBookHeatmap {
+    pub fn new() -> Self {
+        Self::with_config(HeatmapConfig::default())
+    }
+
+    pub fn with_config(config: HeatmapConfig) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(HeatmapInner::default())),
+            sender,
+            sampler_started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn config(&self) -> &HeatmapConfig {
+        &self.config
+    }
+
+    /// 设置合约最小变动价位，未设置时按盘口相邻档位价差推断
+    pub fn set_price_tick(&self, instrument_id: &str, price_tick: f64) {
+        if price_tick > 0.0 {
+            self.inner.lock().unwrap().price_ticks.insert(instrument_id.to_string(), price_tick);
+        }
+    }
+
+    /// 更新最新盘口，仅一档的行情忽略
+    pub fn update(&self, snapshot: DepthSnapshot) {
+        if !snapshot.has_depth() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.books.insert(
+            snapshot.instrument_id.clone(),
+            BookState {
+                snapshot,
+                received_at: Instant::now(),
+            },
+        );
+    }
+
+    /// 移除合约（取消订阅时调用）
+    pub fn remove(&self, instrument_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.books.remove(instrument_id);
+        inner.history.remove(instrument_id);
+    }
+
+    /// 订阅热力图列
+    pub fn subscribe(&self) -> broadcast::Receiver<HeatmapColumn> {
+        self.sender.subscribe()
+    }
+
+    /// 采样一次所有活跃合约，返回生成的列
+    pub fn sample(&self) -> Vec<HeatmapColumn> {
+        let timestamp_ms = chrono::Utc::now().timestamp_millis();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+
+        let mut columns = Vec::new();
+        for (instrument_id, book) in &inner.books {
+            if book.received_at.elapsed() > self.config.stale_after {
+                continue;
+            }
+            let price_tick = inner
+                .price_ticks
+                .get(instrument_id)
+                .copied()
+                .or_else(|| book.snapshot.min_gap());
+            let Some(price_tick) = price_tick else {
+                continue;
+            };
+            if let Some(column) = build_column(&book.snapshot, price_tick, timestamp_ms) {
+                columns.push(column);
+            }
+        }
+
+        for column in &columns {
+            let history = inner.history.entry(column.instrument_id.clone()).or_default();
+            if history.len() >= self.config.history_len {
+                history.pop_front();
+            }
+            history.push_back(column.clone());
+        }
+        drop(guard);
+
+        for column in &columns {
+            // 没有订阅者时发送失败属正常情况
+            let _ = self.sender.send(column.clone());
+        }
+        columns
+    }
+
+    /// 获取合约的历史列（按时间顺序，最多 `limit` 列）
+    pub fn history(&self, instrument_id: &str, limit: usize) -> Vec<HeatmapColumn> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .history
+            .get(instrument_id)
+            .map(|history| {
+                let skip = history.len().saturating_sub(limit);
+                history.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 启动采样任务，重复调用不会重复启动
+    pub fn start_sampler(&self) -> bool {
+        if self.sampler_started.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        let heatmap = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(heatmap.config.sample_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            tracing::info!("盘口热力图采样任务已启动，间隔 {:?}", heatmap.config.sample_interval);
+            loop {
+                interval.tick().await;
+                heatmap.sample();
+            }
+        });
+        true
+    }
+}
+
+impl Default for OrderBookHeatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 快照转为价格阶梯列
+fn build_column(snapshot: &DepthSnapshot, price_tick: f64, timestamp_ms: i64) -> Option<HeatmapColumn> {
+    let levels = snapshot.bids.iter().map(|l| (l, 1)).chain(snapshot.asks.iter().map(|l| (l, -1)));
+    let base_price = snapshot
+        .bids
+        .iter()
+        .chain(snapshot.asks.iter())
+        .map(|l| l.price)
+        .fold(f64::MAX, f64::min);
+    if base_price == f64::MAX {
+        return None;
+    }
+
+    let mut sizes: Vec<i32> = Vec::new();
+    for (level, sign) in levels {
+        let step = ((level.price - base_price) / price_tick).round() as usize;
+        if step >= MAX_LADDER_STEPS {
+            continue;
+        }
+        if sizes.len() <= step {
+            sizes.resize(step + 1, 0);
+        }
+        sizes[step] += sign * level.volume;
+    }
+
+    Some(HeatmapColumn {
+        instrument_id: snapshot.instrument_id.clone(),
+        timestamp_ms,
+        update_time: snapshot.update_time.clone(),
+        last_price: snapshot.last_price,
+        base_price,
+        price_tick,
+        sizes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(instrument_id: &str, bids: &[(f64, i32)], asks: &[(f64, i32)]) -> DepthSnapshot {
+        let level = |&(price, volume): &(f64, i32)| DepthLevel { price, volume };
+        DepthSnapshot {
+            instrument_id: instrument_id.to_string(),
+            last_price: 3500.0,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            bids: bids.iter().map(level).collect(),
+            asks: asks.iter().map(level).collect(),
+        }
+    }
+
+    #[test]
+    fn test_column_encodes_ladder() {
+        let heatmap = OrderBookHeatmap::new();
+        heatmap.update(snapshot(
+            "rb2501",
+            &[(3500.0, 10), (3499.0, 20), (3497.0, 5)],
+            &[(3501.0, 7), (3502.0, 3)],
+        ));
+        // 仅一档的行情不参与
+        heatmap.update(snapshot("IF2501", &[(3900.0, 1)], &[(3900.2, 1)]));
+
+        let columns = heatmap.sample();
+        assert_eq!(columns.len(), 1);
+        let column = &columns[0];
+        assert_eq!(column.base_price, 3497.0);
+        assert_eq!(column.price_tick, 1.0);
+        assert_eq!(column.sizes, vec![5, 0, 20, 10, -7, -3]);
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_broadcast() {
+        let heatmap = OrderBookHeatmap::with_config(HeatmapConfig {
+            history_len: 2,
+            ..HeatmapConfig::default()
+        });
+        heatmap.set_price_tick("rb2501", 1.0);
+        let mut receiver = heatmap.subscribe();
+        heatmap.update(snapshot("rb2501", &[(3500.0, 10), (3499.0, 20)], &[(3501.0, 7)]));
+
+        for _ in 0..3 {
+            heatmap.sample();
+        }
+        assert_eq!(heatmap.history("rb2501", 10).len(), 2);
+        assert_eq!(heatmap.history("rb2501", 1).len(), 1);
+        assert_eq!(receiver.try_recv().unwrap().instrument_id, "rb2501");
+
+        heatmap.remove("rb2501");
+        assert!(heatmap.sample().is_empty());
+        assert!(heatmap.history("rb2501", 10).is_empty());
+    }
+}
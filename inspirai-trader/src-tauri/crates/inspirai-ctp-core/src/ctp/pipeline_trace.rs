@@ -0,0 +1,348 @@
+// 行情链路追踪
+// 记录一笔行情从 SPI 回调到前端确认的各阶段耗时，
+// 时间戳基于进程内单调时钟，随 `MarketDataTick` 一起传递
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// 进程内单调时钟基准
+static CLOCK_ANCHOR: OnceLock<Instant> = OnceLock::new();
+
+/// 全局追踪器实例
+static TRACER: OnceLock<PipelineTracer> = OnceLock::new();
+
+/// 获取单调时钟纳秒数（相对进程内基准，始终大于 0）
+pub fn monotonic_ns() -> u64 {
+    let anchor = CLOCK_ANCHOR.get_or_init(Instant::now);
+    anchor.elapsed().as_nanos() as u64 + 1
+}
+
+/// 链路阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceStage {
+    /// SPI 回调 → 数据转换完成
+    Conversion,
+    /// 上一阶段 → 管理器更新完成
+    ManagerUpdate,
+    /// 上一阶段 → 事件发出
+    EventEmit,
+    /// 事件发出 → 前端确认
+    FrontendAck,
+    /// SPI 回调 → 前端确认（端到端）
+    EndToEnd,
+}
+
+impl TraceStage {
+    pub fn all() -> Vec<TraceStage> {
+        vec![
+            TraceStage::Conversion,
+            TraceStage::ManagerUpdate,
+            TraceStage::EventEmit,
+            TraceStage::FrontendAck,
+            TraceStage::EndToEnd,
+        ]
+    }
+}
+
+/// 单笔行情的链路时间戳（纳秒，0 表示未经过该阶段）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TickTrace {
+    /// SPI 回调进入时间
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub spi_ns: u64,
+    /// 转换完成时间
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub converted_ns: u64,
+    /// 管理器更新完成时间
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub managed_ns: u64,
+    /// 事件发出时间
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub emitted_ns: u64,
+}
+
+impl TickTrace {
+    /// 在 SPI 回调入口开始追踪，未启用时返回 None
+    pub fn begin() -> Option<Self> {
+        if !PipelineTracer::global().is_enabled() {
+            return None;
+        }
+        Some(Self {
+            spi_ns: monotonic_ns(),
+            ..Self::default()
+        })
+    }
+
+    /// 标记阶段完成，并将与上一时间点的间隔计入直方图
+    ///
+    /// 每个阶段只记录第一次打点，行情经管理器再次发出时不重复计入
+    pub fn mark(&mut self, stage: TraceStage) {
+        let now = monotonic_ns();
+        let previous = self.last_mark_ns();
+
+        let slot = match stage {
+            TraceStage::Conversion => &mut self.converted_ns,
+            TraceStage::ManagerUpdate => &mut self.managed_ns,
+            TraceStage::EventEmit => &mut self.emitted_ns,
+            TraceStage::FrontendAck | TraceStage::EndToEnd => return,
+        };
+        if *slot > 0 {
+            return;
+        }
+        *slot = now;
+
+        if previous > 0 {
+            PipelineTracer::global().record(stage, now.saturating_sub(previous));
+        }
+    }
+
+    /// 最近一次打点时间，阶段不一定按声明顺序到达
+    fn last_mark_ns(&self) -> u64 {
+        [self.emitted_ns, self.managed_ns, self.converted_ns, self.spi_ns]
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// 延迟直方图（按微秒分桶）
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum_ns: u64,
+    min_ns: u64,
+    max_ns: u64,
+}
+
+impl LatencyHistogram {
+    /// 分桶上界（微秒），最后一个桶收纳所有更大的值
+    pub const BUCKET_BOUNDS_US: [u64; 13] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000, 2_000, 5_000, 10_000];
+
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; Self::BUCKET_BOUNDS_US.len() + 1],
+            count: 0,
+            sum_ns: 0,
+            min_ns: u64::MAX,
+            max_ns: 0,
+        }
+    }
+
+    pub fn record(&mut self, elapsed_ns: u64) {
+        let elapsed_us = elapsed_ns / 1_000;
+        let index = Self::BUCKET_BOUNDS_US
+            .iter()
+            .position(|bound| elapsed_us < *bound)
+            .unwrap_or(Self::BUCKET_BOUNDS_US.len());
+
+        self.counts[index] += 1;
+        self.count += 1;
+        self.sum_ns = self.sum_ns.saturating_add(elapsed_ns);
+        self.min_ns = self.min_ns.min(elapsed_ns);
+        self.max_ns = self.max_ns.max(elapsed_ns);
+    }
+
+    /// 估算分位数（返回所在桶的上界，微秒）
+    pub fn percentile_us(&self, percentile: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+
+        let target = ((self.count as f64) * percentile / 100.0).ceil().max(1.0) as u64;
+        let mut accumulated = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            accumulated += count;
+            if accumulated >= target {
+                return Self::BUCKET_BOUNDS_US
+                    .get(index)
+                    .copied()
+                    .unwrap_or(self.max_ns / 1_000);
+            }
+        }
+        self.max_ns / 1_000
+    }
+
+    pub fn snapshot(&self, stage: TraceStage) -> StageLatencyStats {
+        StageLatencyStats {
+            stage,
+            count: self.count,
+            min_us: if self.count == 0 { 0.0 } else { self.min_ns as f64 / 1_000.0 },
+            max_us: self.max_ns as f64 / 1_000.0,
+            mean_us: if self.count == 0 { 0.0 } else { self.sum_ns as f64 / self.count as f64 / 1_000.0 },
+            p50_us: self.percentile_us(50.0),
+            p99_us: self.percentile_us(99.0),
+            buckets: self.counts.clone(),
+        }
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单阶段延迟统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageLatencyStats {
+    pub stage: TraceStage,
+    pub count: u64,
+    pub min_us: f64,
+    pub max_us: f64,
+    pub mean_us: f64,
+    pub p50_us: u64,
+    pub p99_us: u64,
+    /// 各分桶计数，对应 `LatencyHistogram::BUCKET_BOUNDS_US`
+    pub buckets: Vec<u64>,
+}
+
+/// 链路追踪汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineTraceStats {
+    pub enabled: bool,
+    pub bucket_bounds_us: Vec<u64>,
+    pub stages: Vec<StageLatencyStats>,
+}
+
+/// 行情链路追踪器
+///
+/// 默认关闭，开启后各阶段打点会汇总为直方图
+#[derive(Debug)]
+pub struct PipelineTracer {
+    enabled: AtomicBool,
+    histograms: Mutex<HashMap<TraceStage, LatencyHistogram>>,
+}
+
+impl PipelineTracer {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            histograms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取全局追踪器
+    pub fn global() -> &'static PipelineTracer {
+        TRACER.get_or_init(PipelineTracer::new)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 开启或关闭追踪
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        tracing::info!("行情链路追踪已{}", if enabled { "开启" } else { "关闭" });
+    }
+
+    /// 记录阶段耗时
+    pub fn record(&self, stage: TraceStage, elapsed_ns: u64) {
+        let mut histograms = self.histograms.lock().unwrap();
+        histograms.entry(stage).or_default().record(elapsed_ns);
+    }
+
+    /// 记录前端确认，同时计入端到端耗时
+    pub fn record_ack(&self, trace: &TickTrace) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let now = monotonic_ns();
+        if trace.emitted_ns > 0 {
+            self.record(TraceStage::FrontendAck, now.saturating_sub(trace.emitted_ns));
+        }
+        if trace.spi_ns > 0 {
+            self.record(TraceStage::EndToEnd, now.saturating_sub(trace.spi_ns));
+        }
+    }
+
+    /// 获取各阶段统计
+    pub fn get_stats(&self) -> PipelineTraceStats {
+        let histograms = self.histograms.lock().unwrap();
+        let stages = TraceStage::all()
+            .into_iter()
+            .filter_map(|stage| histograms.get(&stage).map(|h| h.snapshot(stage)))
+            .collect();
+
+        PipelineTraceStats {
+            enabled: self.is_enabled(),
+            bucket_bounds_us: LatencyHistogram::BUCKET_BOUNDS_US.to_vec(),
+            stages,
+        }
+    }
+
+    /// 清空统计
+    pub fn reset(&self) {
+        self.histograms.lock().unwrap().clear();
+    }
+}
+
+impl Default for PipelineTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = LatencyHistogram::new();
+        histogram.record(500); // 0.5us
+        histogram.record(3_000); // 3us
+        histogram.record(50_000_000); // 50ms
+
+        let stats = histogram.snapshot(TraceStage::Conversion);
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.buckets[0], 1);
+        assert_eq!(stats.buckets[2], 1);
+        assert_eq!(*stats.buckets.last().unwrap(), 1);
+        assert_eq!(stats.p50_us, 5);
+    }
+
+    #[test]
+    fn test_tick_trace_marks() {
+        let mut trace = TickTrace {
+            spi_ns: monotonic_ns(),
+            ..TickTrace::default()
+        };
+        trace.mark(TraceStage::Conversion);
+        trace.mark(TraceStage::EventEmit);
+
+        assert!(trace.converted_ns >= trace.spi_ns);
+        assert!(trace.emitted_ns >= trace.converted_ns);
+        assert_eq!(trace.managed_ns, 0);
+
+        // 管理器在发出之后更新，再次发出不覆盖首次发出时间
+        let emitted = trace.emitted_ns;
+        trace.mark(TraceStage::ManagerUpdate);
+        trace.mark(TraceStage::EventEmit);
+        assert_eq!(trace.emitted_ns, emitted);
+        assert!(trace.managed_ns >= emitted);
+        assert_eq!(trace.last_mark_ns(), trace.managed_ns);
+    }
+
+    #[test]
+    fn test_tracer_stats() {
+        let tracer = PipelineTracer::new();
+        tracer.record(TraceStage::EventEmit, 1_500);
+        tracer.record(TraceStage::EventEmit, 2_500);
+
+        let stats = tracer.get_stats();
+        assert!(!stats.enabled);
+        assert_eq!(stats.stages.len(), 1);
+        assert_eq!(stats.stages[0].count, 2);
+
+        tracer.reset();
+        assert!(tracer.get_stats().stages.is_empty());
+    }
+}
@@ -1,16 +1,23 @@
 use crate::ctp::{
-    CtpError, Position, PositionDirection, OrderDirection, OffsetFlag,
+    CtpError, Position, PositionDirection, OrderDirection, OffsetFlag, OrderTags, TradeRecord,
+    round_trip::{RoundTrip, RoundTripBook},
+    trade_dedup::TradeDeduplicator,
 };
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use tracing::{info, warn, debug};
 
 /// 持仓管理器
+#[derive(Clone)]
 pub struct PositionManager {
     /// 持仓映射表 (instrument_id -> direction -> position)
     positions: Arc<Mutex<HashMap<String, HashMap<PositionDirection, PositionDetail>>>>,
     /// 持仓统计
     stats: Arc<Mutex<PositionStats>>,
+    /// 开仓批次与已完成的回合交易
+    round_trips: RoundTripBook,
+    /// 已处理成交，重复回报不重复配对
+    trade_dedup: TradeDeduplicator,
 }
 
 /// 持仓详情
@@ -56,17 +63,54 @@ impl PositionManager {
         Self {
             positions: Arc::new(Mutex::new(HashMap::new())),
             stats: Arc::new(Mutex::new(PositionStats::default())),
+            round_trips: RoundTripBook::default(),
+            trade_dedup: TradeDeduplicator::in_memory(),
         }
     }
 
+    /// 使用指定的成交去重（如按交易日持久化的已处理集合）
+    pub fn with_trade_dedup(mut self, trade_dedup: TradeDeduplicator) -> Self {
+        self.trade_dedup = trade_dedup;
+        self
+    }
+
+    /// 使用指定的回合交易簿（如持久化的账户交易簿）
+    pub fn with_round_trip_book(mut self, round_trips: RoundTripBook) -> Self {
+        self.round_trips = round_trips;
+        self
+    }
+
+    /// 回合交易簿
+    pub fn round_trip_book(&self) -> RoundTripBook {
+        self.round_trips.clone()
+    }
+
+    /// 设置合约乘数
+    pub fn set_volume_multiple(&self, instrument_id: &str, volume_multiple: f64) {
+        self.round_trips.set_volume_multiple(instrument_id, volume_multiple);
+    }
+
+    /// 登记报单标签，成交回报不带标签时按报单引用补齐
+    pub fn register_order_tags(&self, order_ref: &str, tags: &OrderTags) {
+        self.round_trips.register_order_tags(order_ref, tags);
+    }
+
+    /// 记录成交，平仓成交配对为回合交易
+    pub fn record_fill(&self, trade: &TradeRecord, at: chrono::DateTime<chrono::Utc>) -> Vec<RoundTrip> {
+        if !self.trade_dedup.first_seen(trade) {
+            warn!("忽略重复成交: {} 合约={}", trade.trade_id, trade.instrument_id);
+            return Vec::new();
+        }
+        self.round_trips.record_fill(trade, at)
+    }
+
+    /// 已完成的回合交易，按平仓时间顺序
+    pub fn round_trips(&self) -> Vec<RoundTrip> {
+        self.round_trips.round_trips()
+    }
+
     /// 更新持仓
     pub fn update_position(&self, position: Position) -> Result<(), CtpError> {
-        let mut positions = self.positions.lock().unwrap();
-        
-        let instrument_positions = positions
-            .entry(position.instrument_id.clone())
-            .or_insert_with(HashMap::new);
-        
         let detail = PositionDetail {
             today_closeable: position.today_position,
             yesterday_closeable: position.yesterday_position,
@@ -81,7 +125,10 @@ impl PositionManager {
             position: position.clone(),
         };
         
-        instrument_positions.insert(position.direction, detail);
+        self.positions.lock().unwrap()
+            .entry(position.instrument_id.clone())
+            .or_insert_with(HashMap::new)
+            .insert(position.direction, detail);
         
         // 更新统计
         self.update_stats();
@@ -101,6 +148,16 @@ impl PositionManager {
         Ok(())
     }
 
+    /// 以柜台快照替换全部持仓
+    pub fn replace_positions(&self, positions: Vec<Position>) {
+        self.positions.lock().unwrap().clear();
+        for position in positions {
+            let _ = self.update_position(position);
+        }
+        self.update_stats();
+        info!("持仓已按柜台快照替换");
+    }
+
     /// 获取可平仓数量
     pub fn get_closeable_volume(
         &self,
@@ -186,6 +243,9 @@ impl PositionManager {
 
     /// 更新最新价
     pub fn update_last_price(&self, instrument_id: &str, price: f64) {
+        self.round_trips.update_price(instrument_id, price);
+        let multiplier = self.round_trips.volume_multiple(instrument_id);
+        
         let mut positions = self.positions.lock().unwrap();
         
         if let Some(instrument_positions) = positions.get_mut(instrument_id) {
@@ -193,7 +253,6 @@ impl PositionManager {
                 detail.last_price = price;
                 
                 // 重新计算浮动盈亏
-                let multiplier = 10.0; // TODO: 从合约信息获取
                 let volume = detail.position.total_position as f64;
                 
                 detail.floating_pnl = match direction {
@@ -208,6 +267,8 @@ impl PositionManager {
                 detail.position.unrealized_pnl = detail.floating_pnl;
             }
         }
+        // update_stats 会再次获取持仓锁
+        drop(positions);
         
         self.update_stats();
     }
@@ -247,9 +308,10 @@ impl PositionManager {
         self.stats.lock().unwrap().clone()
     }
 
-    /// 清空持仓
+    /// 清空持仓（回合交易记录保留）
     pub fn clear(&self) {
         self.positions.lock().unwrap().clear();
+        self.round_trips.clear_open_lots();
         *self.stats.lock().unwrap() = PositionStats::default();
         info!("清空所有持仓");
     }
@@ -298,4 +360,78 @@ impl PositionManager {
             0
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{round_trip::DEFAULT_VOLUME_MULTIPLE, strategy_guard::STRATEGY_TAG};
+
+    fn fill(trade_id: &str, direction: OrderDirection, offset_flag: OffsetFlag, price: f64, volume: i32) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: format!("ref-{}", trade_id),
+            instrument_id: "rb2501".to_string(),
+            direction,
+            offset_flag,
+            price,
+            volume,
+            trade_time: "10:00:00".to_string(),
+            exchange_id: String::new(),
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_fifo_round_trips_with_excursions() {
+        let manager = PositionManager::new();
+        let t0 = chrono::Utc::now();
+        manager.register_order_tags("ref-1", &OrderTags::from([(STRATEGY_TAG.to_string(), "grid".to_string())]));
+
+        manager.record_fill(&fill("1", OrderDirection::Buy, OffsetFlag::Open, 3500.0, 2), t0);
+        manager.record_fill(&fill("2", OrderDirection::Buy, OffsetFlag::Open, 3510.0, 1), t0);
+        manager.update_last_price("rb2501", 3480.0);
+        manager.update_last_price("rb2501", 3530.0);
+
+        let trips = manager.record_fill(
+            &fill("3", OrderDirection::Sell, OffsetFlag::CloseToday, 3520.0, 3),
+            t0 + chrono::Duration::seconds(90),
+        );
+        assert_eq!(trips.len(), 2);
+        assert_eq!(trips[0].open_trade_id, "1");
+        assert_eq!(trips[0].volume, 2);
+        assert_eq!(trips[0].pnl, 20.0 * 2.0 * DEFAULT_VOLUME_MULTIPLE);
+        assert_eq!(trips[0].mae, 20.0 * 2.0 * DEFAULT_VOLUME_MULTIPLE);
+        assert_eq!(trips[0].mfe, 30.0 * 2.0 * DEFAULT_VOLUME_MULTIPLE);
+        assert_eq!(trips[0].strategy.as_deref(), Some("grid"));
+        assert_eq!(trips[0].holding_secs, 90);
+        assert_eq!(trips[1].open_trade_id, "2");
+        assert_eq!(trips[1].strategy, None);
+        assert_eq!(manager.round_trips().len(), 2);
+
+        // 重复推送的成交不再配对
+        manager.record_fill(&fill("4", OrderDirection::Buy, OffsetFlag::Open, 3500.0, 1), t0);
+        manager.record_fill(&fill("4", OrderDirection::Buy, OffsetFlag::Open, 3500.0, 1), t0);
+        let trips = manager.record_fill(&fill("5", OrderDirection::Sell, OffsetFlag::Close, 3500.0, 2), t0);
+        assert_eq!(trips.len(), 1);
+    }
+
+    #[test]
+    fn test_short_round_trip_and_unmatched_close() {
+        let manager = PositionManager::new();
+        manager.set_volume_multiple("rb2501", 5.0);
+        let t0 = chrono::Utc::now();
+
+        // 没有本地开仓记录的平仓不生成回合
+        assert!(manager.record_fill(&fill("0", OrderDirection::Buy, OffsetFlag::Close, 3500.0, 1), t0).is_empty());
+
+        manager.record_fill(&fill("1", OrderDirection::Sell, OffsetFlag::Open, 3500.0, 2), t0);
+        let trips = manager.record_fill(&fill("2", OrderDirection::Buy, OffsetFlag::Close, 3510.0, 3), t0);
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].direction, PositionDirection::Short);
+        assert_eq!(trips[0].volume, 2);
+        assert_eq!(trips[0].pnl, -10.0 * 2.0 * 5.0);
+        assert_eq!(trips[0].mae, 10.0 * 2.0 * 5.0);
+        assert_eq!(trips[0].mfe, 0.0);
+    }
+}
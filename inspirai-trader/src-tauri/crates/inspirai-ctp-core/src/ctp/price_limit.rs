@@ -0,0 +1,192 @@
+use crate::ctp::models::{MarketDataTick, OrderDirection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// 价格比较容差
+const PRICE_EPSILON: f64 = 1e-6;
+
+/// 合约涨跌停状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum LimitState {
+    #[default]
+    Normal,
+    /// 最新价触及涨停价，卖盘仍有挂单
+    AtUpperLimit,
+    /// 涨停封板：买一在涨停价且卖盘为空
+    LimitUpLocked,
+    /// 最新价触及跌停价，买盘仍有挂单
+    AtLowerLimit,
+    /// 跌停封板：卖一在跌停价且买盘为空
+    LimitDownLocked,
+}
+
+impl LimitState {
+    pub fn is_locked(&self) -> bool {
+        matches!(self, LimitState::LimitUpLocked | LimitState::LimitDownLocked)
+    }
+
+    /// 封板时该方向的委托缺少对手盘，无法立即成交
+    pub fn blocks(&self, direction: OrderDirection) -> bool {
+        matches!(
+            (self, direction),
+            (LimitState::LimitUpLocked, OrderDirection::Buy) | (LimitState::LimitDownLocked, OrderDirection::Sell)
+        )
+    }
+}
+
+/// 合约涨跌停价与当前状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct LimitStatus {
+    pub upper_limit_price: f64,
+    pub lower_limit_price: f64,
+    pub state: LimitState,
+    /// 进入当前非正常状态的时间
+    pub pinned_since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 涨跌停跟踪
+///
+/// 从深度行情读取每个合约当日的涨跌停价，判断报价是否贴板或封板，
+/// 供行情事件展示和报单校验使用
+#[derive(Clone, Default)]
+pub struct PriceLimitTracker {
+    inner: Arc<Mutex<HashMap<String, LimitStatus>>>,
+}
+
+impl PriceLimitTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按最新行情更新状态，涨跌停价无效时返回空
+    pub fn update(&self, tick: &MarketDataTick, upper_limit_price: f64, lower_limit_price: f64) -> Option<LimitStatus> {
+        if !valid_price(upper_limit_price) || !valid_price(lower_limit_price) {
+            return None;
+        }
+        let state = classify(tick, upper_limit_price, lower_limit_price);
+
+        let mut statuses = self.inner.lock().unwrap();
+        let previous = statuses.get(&tick.instrument_id);
+        let pinned_since = match previous {
+            _ if state == LimitState::Normal => None,
+            Some(prev) if prev.state == state => prev.pinned_since,
+            _ => Some(chrono::Utc::now()),
+        };
+        if previous.is_none_or(|prev| prev.state != state) {
+            match state {
+                LimitState::Normal => tracing::info!("{} 脱离涨跌停", tick.instrument_id),
+                _ => tracing::warn!("{} 涨跌停状态: {:?}", tick.instrument_id, state),
+            }
+        }
+
+        let status = LimitStatus {
+            upper_limit_price,
+            lower_limit_price,
+            state,
+            pinned_since,
+        };
+        statuses.insert(tick.instrument_id.clone(), status.clone());
+        Some(status)
+    }
+
+    pub fn status(&self, instrument_id: &str) -> Option<LimitStatus> {
+        self.inner.lock().unwrap().get(instrument_id).cloned()
+    }
+
+    /// 全部合约状态，按合约代码排序
+    pub fn all(&self) -> BTreeMap<String, LimitStatus> {
+        self.inner
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, status)| (id.clone(), status.clone()))
+            .collect()
+    }
+}
+
+fn valid_price(price: f64) -> bool {
+    price > 0.0 && price < f64::MAX
+}
+
+fn classify(tick: &MarketDataTick, upper: f64, lower: f64) -> LimitState {
+    let at = |price: f64, limit: f64| valid_price(price) && (price - limit).abs() < PRICE_EPSILON;
+    let no_ask = !valid_price(tick.ask_price1) || tick.ask_volume1 <= 0;
+    let no_bid = !valid_price(tick.bid_price1) || tick.bid_volume1 <= 0;
+
+    if at(tick.bid_price1, upper) && no_ask {
+        LimitState::LimitUpLocked
+    } else if at(tick.ask_price1, lower) && no_bid {
+        LimitState::LimitDownLocked
+    } else if at(tick.last_price, upper) {
+        LimitState::AtUpperLimit
+    } else if at(tick.last_price, lower) {
+        LimitState::AtLowerLimit
+    } else {
+        LimitState::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(last: f64, bid: (f64, i32), ask: (f64, i32)) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: last,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0,
+            bid_price1: bid.0,
+            bid_volume1: bid.1,
+            ask_price1: ask.0,
+            ask_volume1: ask.1,
+            update_time: "09:00:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_classifies_limit_states() {
+        let tracker = PriceLimitTracker::new();
+        let update = |t: MarketDataTick| tracker.update(&t, 3800.0, 3200.0).unwrap().state;
+
+        assert_eq!(update(tick(3500.0, (3499.0, 5), (3500.0, 3))), LimitState::Normal);
+        assert_eq!(update(tick(3800.0, (3799.0, 5), (3800.0, 3))), LimitState::AtUpperLimit);
+        assert_eq!(update(tick(3800.0, (3800.0, 900), (f64::MAX, 0))), LimitState::LimitUpLocked);
+        assert_eq!(update(tick(3200.0, (f64::MAX, 0), (3200.0, 900))), LimitState::LimitDownLocked);
+        assert_eq!(update(tick(3200.0, (3200.0, 2), (3201.0, 900))), LimitState::AtLowerLimit);
+
+        assert!(tracker.update(&tick(3500.0, (0.0, 0), (0.0, 0)), f64::MAX, 0.0).is_none());
+        assert!(LimitState::LimitUpLocked.blocks(OrderDirection::Buy));
+        assert!(!LimitState::LimitUpLocked.blocks(OrderDirection::Sell));
+    }
+
+    #[test]
+    fn test_pinned_since_kept_while_locked() {
+        let tracker = PriceLimitTracker::new();
+        let locked = tick(3800.0, (3800.0, 900), (0.0, 0));
+
+        let first = tracker.update(&locked, 3800.0, 3200.0).unwrap();
+        let since = first.pinned_since.unwrap();
+        let second = tracker.update(&locked, 3800.0, 3200.0).unwrap();
+        assert_eq!(second.pinned_since, Some(since));
+
+        let released = tracker.update(&tick(3790.0, (3789.0, 5), (3790.0, 3)), 3800.0, 3200.0).unwrap();
+        assert_eq!(released.pinned_since, None);
+        assert_eq!(tracker.status("rb2501").unwrap().state, LimitState::Normal);
+        assert_eq!(tracker.all().len(), 1);
+    }
+}
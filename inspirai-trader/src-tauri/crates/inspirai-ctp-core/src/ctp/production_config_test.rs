@@ -5,7 +5,7 @@ mod production_config_tests {
 
     #[tokio::test]
     async fn test_production_config_loading() {
-        let config_path = Path::new("config/production.toml");
+        let config_path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/production.toml"));
         
         println!("测试生产环境配置文件: {:?}", config_path);
         
@@ -88,7 +88,7 @@ mod production_config_tests {
 
     #[tokio::test]
     async fn test_production_config_structure() {
-        let config_path = Path::new("config/production.toml");
+        let config_path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/production.toml"));
         
         if !config_path.exists() {
             println!("⚠️  生产环境配置文件不存在，跳过结构测试");
@@ -158,7 +158,7 @@ mod production_config_tests {
         // 比如密码强度、敏感信息处理等
         
         // 检查是否使用了生产环境的安全设置
-        let config_path = std::path::Path::new("config/production.toml");
+        let config_path = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/production.toml"));
         if config_path.exists() {
             let content = std::fs::read_to_string(config_path).unwrap();
             
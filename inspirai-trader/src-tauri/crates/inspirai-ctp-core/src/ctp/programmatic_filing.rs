@@ -0,0 +1,204 @@
+use crate::ctp::{config::Environment, CtpError};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// 终端系统信息最大字节数（CThostFtdcUserSystemInfoField.ClientSystemInfo）
+pub const MAX_SYSTEM_INFO_LEN: usize = 273;
+
+/// 中继上报方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayMode {
+    /// 直连，终端信息由 API 自动采集
+    #[default]
+    Direct,
+    /// 中继多连接模式：认证成功后、登录前调用 RegisterUserSystemInfo
+    MultiConnection,
+    /// 中继操作员登录模式：登录后调用 SubmitUserSystemInfo
+    OperatorLogin,
+}
+
+/// 终端采集信息，中继模式下代客户上报
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalInfo {
+    /// 终端调用 CTP_GetSystemInfo 得到的采集信息，Base64 编码
+    pub system_info: String,
+    /// 终端公网 IP
+    pub public_ip: String,
+    /// 终端公网端口
+    pub ip_port: i32,
+}
+
+/// 上报用的终端信息，字段与 CThostFtdcUserSystemInfoField 对应
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSystemInfo {
+    pub system_info: Vec<u8>,
+    pub public_ip: String,
+    pub ip_port: i32,
+    pub app_id: String,
+}
+
+/// 程序化交易报备信息
+///
+/// 交易所要求程序化交易账户事先报备，生产环境下策略报单前须填写完整
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgrammaticTradingInfo {
+    /// 账户已完成程序化交易报备
+    pub enabled: bool,
+    /// 报备编号
+    pub filing_id: String,
+    /// 交易软件名称
+    pub software_name: String,
+    /// 交易软件版本
+    pub software_version: String,
+    /// 软件开发者
+    pub developer: String,
+    /// 策略类型，如趋势、套利、做市、高频
+    pub strategy_types: Vec<String>,
+    /// 报备的最高每秒申报笔数
+    pub max_orders_per_second: u32,
+    /// 交易服务器所在地
+    pub server_location: String,
+    /// 中继上报方式
+    pub relay_mode: RelayMode,
+    /// 中继模式下的终端信息
+    pub terminal: Option<TerminalInfo>,
+}
+
+impl ProgrammaticTradingInfo {
+    /// 未填写的必填项
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        let required = [
+            ("filing_id", &self.filing_id),
+            ("software_name", &self.software_name),
+            ("software_version", &self.software_version),
+            ("developer", &self.developer),
+            ("server_location", &self.server_location),
+        ];
+        for (name, value) in required {
+            if value.trim().is_empty() {
+                missing.push(name);
+            }
+        }
+        if self.strategy_types.iter().all(|t| t.trim().is_empty()) {
+            missing.push("strategy_types");
+        }
+        if self.max_orders_per_second == 0 {
+            missing.push("max_orders_per_second");
+        }
+        if self.relay_mode != RelayMode::Direct && self.terminal.is_none() {
+            missing.push("terminal");
+        }
+        missing
+    }
+
+    /// 启用策略前检查报备信息，仅生产环境要求
+    pub fn ensure_algo_allowed(&self, environment: Environment) -> Result<(), CtpError> {
+        if environment != Environment::Production {
+            return Ok(());
+        }
+        if !self.enabled {
+            return Err(CtpError::ValidationError(
+                "账户未完成程序化交易报备，不能启用策略".to_string(),
+            ));
+        }
+        let missing = self.missing_fields();
+        if !missing.is_empty() {
+            return Err(CtpError::ValidationError(format!(
+                "程序化交易报备信息不完整，缺少: {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// 中继模式下待上报的终端信息，直连时为 None
+    pub fn user_system_info(&self, app_id: &str) -> Result<Option<UserSystemInfo>, CtpError> {
+        if self.relay_mode == RelayMode::Direct {
+            return Ok(None);
+        }
+        let terminal = self
+            .terminal
+            .as_ref()
+            .ok_or_else(|| CtpError::ConfigError("中继模式须配置终端信息".to_string()))?;
+        let system_info = base64::engine::general_purpose::STANDARD
+            .decode(terminal.system_info.trim())
+            .map_err(|e| CtpError::ConversionError(format!("终端采集信息不是有效的 Base64: {}", e)))?;
+        if system_info.is_empty() || system_info.len() > MAX_SYSTEM_INFO_LEN {
+            return Err(CtpError::ValidationError(format!(
+                "终端采集信息长度 {} 超出范围 (1-{})",
+                system_info.len(),
+                MAX_SYSTEM_INFO_LEN
+            )));
+        }
+        if terminal.public_ip.parse::<std::net::IpAddr>().is_err() {
+            return Err(CtpError::ValidationError(format!("终端公网 IP 无效: {}", terminal.public_ip)));
+        }
+        Ok(Some(UserSystemInfo {
+            system_info,
+            public_ip: terminal.public_ip.clone(),
+            ip_port: terminal.ip_port,
+            app_id: app_id.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filed() -> ProgrammaticTradingInfo {
+        ProgrammaticTradingInfo {
+            enabled: true,
+            filing_id: "CX2025001".to_string(),
+            software_name: "inspirai-trader".to_string(),
+            software_version: "1.0.0".to_string(),
+            developer: "Inspirai".to_string(),
+            strategy_types: vec!["趋势".to_string()],
+            max_orders_per_second: 5,
+            server_location: "上海".to_string(),
+            relay_mode: RelayMode::Direct,
+            terminal: None,
+        }
+    }
+
+    #[test]
+    fn test_algo_requires_complete_filing_in_production() {
+        let empty = ProgrammaticTradingInfo::default();
+        assert!(empty.ensure_algo_allowed(Environment::SimNow).is_ok());
+        assert!(empty.ensure_algo_allowed(Environment::Production).is_err());
+
+        let mut info = filed();
+        assert!(info.ensure_algo_allowed(Environment::Production).is_ok());
+
+        info.filing_id.clear();
+        info.relay_mode = RelayMode::OperatorLogin;
+        assert_eq!(info.missing_fields(), vec!["filing_id", "terminal"]);
+        let err = info.ensure_algo_allowed(Environment::Production).unwrap_err();
+        assert!(err.to_string().contains("filing_id"));
+    }
+
+    #[test]
+    fn test_user_system_info_for_relay() {
+        let mut info = filed();
+        assert_eq!(info.user_system_info("app").unwrap(), None);
+
+        info.relay_mode = RelayMode::MultiConnection;
+        info.terminal = Some(TerminalInfo {
+            system_info: base64::engine::general_purpose::STANDARD.encode(b"collected"),
+            public_ip: "203.0.113.8".to_string(),
+            ip_port: 50123,
+        });
+        let payload = info.user_system_info("inspirai_1.0").unwrap().unwrap();
+        assert_eq!(payload.system_info, b"collected");
+        assert_eq!(payload.app_id, "inspirai_1.0");
+
+        info.terminal.as_mut().unwrap().public_ip = "localhost".to_string();
+        assert!(info.user_system_info("app").is_err());
+        info.terminal.as_mut().unwrap().system_info = "不是base64".to_string();
+        assert!(info.user_system_info("app").is_err());
+    }
+}
@@ -0,0 +1,441 @@
+use crate::ctp::{
+    CtpEvent, OrderManager, OrderStatus, Position, PositionDirection, PositionManager, TradeRecord,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 收集柜台数据的最长时间，超时后放弃本次对账并放行新订单
+pub const DEFAULT_RECONCILE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 持仓调整记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct PositionAdjustment {
+    pub instrument_id: String,
+    pub direction: PositionDirection,
+    /// 本地持仓
+    pub local_volume: i32,
+    /// 柜台持仓
+    pub remote_volume: i32,
+}
+
+/// 对账结果摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ReconciliationSummary {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+    /// 柜台返回的报单数
+    pub orders_checked: usize,
+    /// 状态被柜台数据更新的报单
+    pub orders_updated: Vec<String>,
+    /// 本地不存在、从柜台补录的报单
+    pub orders_adopted: Vec<String>,
+    /// 本地活动但柜台不存在、标记为未知的报单
+    pub orders_marked_unknown: Vec<String>,
+    /// 补录的成交数
+    pub trades_added: usize,
+    /// 持仓调整
+    pub position_adjustments: Vec<PositionAdjustment>,
+}
+
+impl ReconciliationSummary {
+    /// 是否存在差异
+    pub fn has_conflicts(&self) -> bool {
+        !self.orders_updated.is_empty()
+            || !self.orders_adopted.is_empty()
+            || !self.orders_marked_unknown.is_empty()
+            || self.trades_added > 0
+            || !self.position_adjustments.is_empty()
+    }
+}
+
+/// 柜台快照（重连后查询结果）
+#[derive(Debug, Clone, Default)]
+pub struct RemoteSnapshot {
+    pub orders: Option<Vec<OrderStatus>>,
+    pub trades: Option<Vec<TradeRecord>>,
+    pub positions: Option<Vec<Position>>,
+}
+
+impl RemoteSnapshot {
+    pub fn is_complete(&self) -> bool {
+        self.orders.is_some() && self.trades.is_some() && self.positions.is_some()
+    }
+}
+
+#[derive(Debug)]
+struct ReconcilerInner {
+    /// 断线后需要对账
+    required: bool,
+    /// 正在收集柜台数据
+    collecting: bool,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    snapshot: RemoteSnapshot,
+    last_summary: Option<ReconciliationSummary>,
+    timeout: Duration,
+}
+
+impl Default for ReconcilerInner {
+    fn default() -> Self {
+        Self {
+            required: false,
+            collecting: false,
+            started_at: None,
+            snapshot: RemoteSnapshot::default(),
+            last_summary: None,
+            timeout: DEFAULT_RECONCILE_TIMEOUT,
+        }
+    }
+}
+
+impl ReconcilerInner {
+    /// 收集超时则放弃本次对账
+    fn expire(&mut self) {
+        let Some(started_at) = self.started_at.filter(|_| self.collecting) else {
+            return;
+        };
+        let elapsed = (chrono::Utc::now() - started_at).to_std().unwrap_or_default();
+        if elapsed >= self.timeout {
+            warn!("重连对账 {:?} 内未收齐柜台数据，放弃本次对账", self.timeout);
+            self.reset();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.required = false;
+        self.collecting = false;
+        self.started_at = None;
+        self.snapshot = RemoteSnapshot::default();
+    }
+}
+
+/// 重连对账协调器
+///
+/// 断线后阻止新订单，重新登录后收集报单/成交/持仓查询结果，
+/// 与本地 `OrderManager`/`PositionManager` 对账完成后才放行；
+/// 查询发送失败或超时未收齐时放弃本次对账，同样放行
+#[derive(Debug, Clone, Default)]
+pub struct Reconciler {
+    inner: Arc<Mutex<ReconcilerInner>>,
+}
+
+impl Reconciler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置收集柜台数据的超时时间
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.inner.lock().unwrap().timeout = timeout;
+        self
+    }
+
+    /// 标记需要对账（断线时调用）
+    pub fn require(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.required {
+            info!("连接断开，重连后需要对账");
+        }
+        inner.required = true;
+    }
+
+    /// 开始收集柜台数据
+    pub fn begin(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.required = true;
+        inner.collecting = true;
+        inner.started_at = Some(chrono::Utc::now());
+        inner.snapshot = RemoteSnapshot::default();
+        info!("开始重连对账");
+    }
+
+    /// 放弃本次对账（查询发送失败等），放行新订单
+    pub fn abort(&self, reason: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.required {
+            warn!("放弃重连对账: {}", reason);
+        }
+        inner.reset();
+    }
+
+    /// 是否需要阻止新订单
+    pub fn is_pending(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.expire();
+        inner.required
+    }
+
+    /// 已断线、尚未开始收集柜台数据，重新登录后应发起对账
+    pub fn needs_start(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.required && !inner.collecting
+    }
+
+    /// 是否正在收集柜台数据
+    pub fn is_collecting(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.expire();
+        inner.collecting
+    }
+
+    /// 接收查询结果事件，数据齐全时返回 true
+    pub fn feed(&self, event: &CtpEvent) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.expire();
+        if !inner.collecting {
+            return false;
+        }
+
+        match event {
+            CtpEvent::QueryOrdersResult(orders) => inner.snapshot.orders = Some(orders.clone()),
+            CtpEvent::QueryTradesResult(trades) => inner.snapshot.trades = Some(trades.clone()),
+            CtpEvent::QueryPositionsResult(positions) => inner.snapshot.positions = Some(positions.clone()),
+            _ => return false,
+        }
+
+        inner.snapshot.is_complete()
+    }
+
+    /// 使用已收集的柜台数据完成对账
+    pub fn complete(
+        &self,
+        order_manager: &OrderManager,
+        position_manager: &PositionManager,
+    ) -> Option<ReconciliationSummary> {
+        let (snapshot, started_at) = {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.collecting || !inner.snapshot.is_complete() {
+                return None;
+            }
+            inner.collecting = false;
+            (
+                std::mem::take(&mut inner.snapshot),
+                inner.started_at.take().unwrap_or_else(chrono::Utc::now),
+            )
+        };
+
+        let summary = reconcile(order_manager, position_manager, snapshot, started_at);
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.required = false;
+        inner.last_summary = Some(summary.clone());
+        Some(summary)
+    }
+
+    /// 最近一次对账结果
+    pub fn last_summary(&self) -> Option<ReconciliationSummary> {
+        self.inner.lock().unwrap().last_summary.clone()
+    }
+}
+
+/// 以柜台数据为准，修正本地报单与持仓
+pub fn reconcile(
+    order_manager: &OrderManager,
+    position_manager: &PositionManager,
+    snapshot: RemoteSnapshot,
+    started_at: chrono::DateTime<chrono::Utc>,
+) -> ReconciliationSummary {
+    let remote_orders = snapshot.orders.unwrap_or_default();
+    let remote_trades = snapshot.trades.unwrap_or_default();
+    let remote_positions = snapshot.positions.unwrap_or_default();
+
+    let mut orders_updated = Vec::new();
+    let mut orders_adopted = Vec::new();
+    let mut orders_marked_unknown = Vec::new();
+
+    // 报单：柜台为准
+    let remote_ids: HashSet<String> = remote_orders.iter().map(|o| o.order_id.clone()).collect();
+    for remote in &remote_orders {
+        match order_manager.get_order(&remote.order_id) {
+            Some(local) => {
+                if local.status.status != remote.status || local.status.volume_traded != remote.volume_traded {
+                    orders_updated.push(remote.order_id.clone());
+                    let _ = order_manager.update_order(remote.clone());
+                }
+            }
+            None => {
+                orders_adopted.push(remote.order_id.clone());
+                let _ = order_manager.update_order(remote.clone());
+            }
+        }
+    }
+
+    for local in order_manager.get_active_orders() {
+        if !remote_ids.contains(&local.order_id) {
+            warn!("本地活动订单在柜台不存在，标记为未知: {}", local.order_id);
+            order_manager.mark_order_unknown(&local.order_id, "重连对账未在柜台找到该报单");
+            orders_marked_unknown.push(local.order_id);
+        }
+    }
+
+    // 成交：补录缺失的成交
+    let mut trades_added = 0;
+    for trade in remote_trades {
+        if !order_manager.has_trade(&trade.trade_id) {
+            let _ = order_manager.add_trade(trade);
+            trades_added += 1;
+        }
+    }
+
+    // 持仓：对比后以柜台快照替换
+    let mut local_volumes: HashMap<(String, PositionDirection), i32> = position_manager
+        .get_all_positions()
+        .into_iter()
+        .map(|d| ((d.position.instrument_id.clone(), d.position.direction), d.position.total_position))
+        .collect();
+
+    let mut position_adjustments = Vec::new();
+    for remote in &remote_positions {
+        let key = (remote.instrument_id.clone(), remote.direction);
+        let local_volume = local_volumes.remove(&key).unwrap_or(0);
+        if local_volume != remote.total_position {
+            position_adjustments.push(PositionAdjustment {
+                instrument_id: remote.instrument_id.clone(),
+                direction: remote.direction,
+                local_volume,
+                remote_volume: remote.total_position,
+            });
+        }
+    }
+    for ((instrument_id, direction), local_volume) in local_volumes {
+        if local_volume != 0 {
+            position_adjustments.push(PositionAdjustment {
+                instrument_id,
+                direction,
+                local_volume,
+                remote_volume: 0,
+            });
+        }
+    }
+    position_manager.replace_positions(remote_positions);
+
+    let summary = ReconciliationSummary {
+        started_at,
+        finished_at: chrono::Utc::now(),
+        orders_checked: remote_orders.len(),
+        orders_updated,
+        orders_adopted,
+        orders_marked_unknown,
+        trades_added,
+        position_adjustments,
+    };
+
+    info!(
+        "重连对账完成: 报单 {} 笔，更新 {}，补录 {}，标记未知 {}，补录成交 {}，持仓调整 {}",
+        summary.orders_checked,
+        summary.orders_updated.len(),
+        summary.orders_adopted.len(),
+        summary.orders_marked_unknown.len(),
+        summary.trades_added,
+        summary.position_adjustments.len()
+    );
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{OffsetFlag, OrderDirection, OrderStatusType};
+
+    fn order(order_id: &str, status: OrderStatusType, volume_traded: u32) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_id.to_string(),
+            order_id: order_id.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            limit_price: 3500.0,
+            volume: 2,
+            volume_total_original: 2,
+            volume_traded,
+            volume_left: 2 - volume_traded,
+            volume_total: (2 - volume_traded) as i32,
+            status,
+            submit_time: chrono::Local::now(),
+            insert_time: "09:00:00".to_string(),
+            update_time: chrono::Local::now(),
+            front_id: 1,
+            session_id: 1,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: true,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: Default::default(),
+        }
+    }
+
+    fn position(instrument_id: &str, total: i32) -> Position {
+        Position {
+            instrument_id: instrument_id.to_string(),
+            direction: PositionDirection::Long,
+            total_position: total,
+            yesterday_position: 0,
+            today_position: total,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_resolves_conflicts() {
+        let order_manager = OrderManager::new();
+        let position_manager = PositionManager::new();
+
+        order_manager.add_order(order("1", OrderStatusType::NoTradeQueueing, 0)).unwrap();
+        order_manager.add_order(order("2", OrderStatusType::NoTradeQueueing, 0)).unwrap();
+        position_manager.update_position(position("rb2501", 1)).unwrap();
+
+        let reconciler = Reconciler::new();
+        reconciler.require();
+        assert!(reconciler.is_pending());
+
+        reconciler.begin();
+        assert!(!reconciler.feed(&CtpEvent::QueryOrdersResult(vec![
+            order("1", OrderStatusType::AllTraded, 2),
+            order("3", OrderStatusType::NoTradeQueueing, 0),
+        ])));
+        assert!(!reconciler.feed(&CtpEvent::QueryTradesResult(vec![])));
+        assert!(reconciler.feed(&CtpEvent::QueryPositionsResult(vec![position("rb2501", 3)])));
+
+        let summary = reconciler.complete(&order_manager, &position_manager).unwrap();
+        assert_eq!(summary.orders_updated, vec!["1".to_string()]);
+        assert_eq!(summary.orders_adopted, vec!["3".to_string()]);
+        assert_eq!(summary.orders_marked_unknown, vec!["2".to_string()]);
+        assert_eq!(summary.position_adjustments.len(), 1);
+        assert_eq!(summary.position_adjustments[0].remote_volume, 3);
+        assert!(summary.has_conflicts());
+        assert!(!reconciler.is_pending());
+
+        assert_eq!(position_manager.get_net_position("rb2501"), 3);
+        let unknown = order_manager.get_order("2").unwrap();
+        assert_eq!(unknown.status.status, OrderStatusType::Unknown);
+    }
+
+    #[test]
+    fn test_abort_and_timeout_release_gate() {
+        let reconciler = Reconciler::new();
+        reconciler.require();
+        assert!(reconciler.needs_start());
+        reconciler.begin();
+        assert!(!reconciler.needs_start());
+        reconciler.abort("报单查询请求发送失败");
+        assert!(!reconciler.is_pending());
+        assert!(!reconciler.is_collecting());
+
+        let reconciler = Reconciler::new().with_timeout(Duration::ZERO);
+        reconciler.require();
+        reconciler.begin();
+        assert!(!reconciler.is_pending());
+        assert!(!reconciler.feed(&CtpEvent::QueryOrdersResult(vec![])));
+    }
+}
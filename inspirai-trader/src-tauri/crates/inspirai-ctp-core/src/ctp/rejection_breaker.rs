@@ -0,0 +1,315 @@
+use crate::ctp::{CtpError, OrderTags, strategy_guard::StrategyGuard};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 手动下单（含快捷键）的来源标识
+pub const MANUAL_SOURCE: &str = "manual";
+
+/// 已登记来源的报单引用上限，超过后清理最早的一半
+const MAX_TRACKED_ORDERS: usize = 10_000;
+
+/// 报单来源：策略单为 `strategy:<策略名>`，其余为手动
+pub fn order_source(tags: &OrderTags) -> String {
+    match StrategyGuard::strategy_of(tags) {
+        Some(name) => format!("strategy:{}", name),
+        None => MANUAL_SOURCE.to_string(),
+    }
+}
+
+/// 拒单熔断配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectionBreakerConfig {
+    /// 窗口内超过该拒单数即熔断
+    pub max_rejections: usize,
+    /// 统计窗口
+    pub window: Duration,
+}
+
+impl Default for RejectionBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_rejections: 5,
+            window: Duration::from_secs(30),
+        }
+    }
+}
+
+/// 单次拒单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rejection {
+    pub order_ref: String,
+    pub instrument_id: String,
+    pub reason: String,
+    pub rejected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 拒单原因汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct RejectionReason {
+    pub reason: String,
+    pub count: usize,
+}
+
+/// 熔断告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct RejectionAlert {
+    pub source: String,
+    pub rejections: usize,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub window_secs: u64,
+    /// 按次数降序
+    pub reasons: Vec<RejectionReason>,
+    pub instruments: Vec<String>,
+    pub tripped_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 来源熔断状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceBreakerStatus {
+    pub source: String,
+    /// 已暂停，需确认后恢复
+    pub paused: bool,
+    /// 窗口内的拒单数
+    pub recent_rejections: usize,
+    pub alert: Option<RejectionAlert>,
+}
+
+#[derive(Default)]
+struct BreakerInner {
+    order_sources: HashMap<String, String>,
+    order_queue: VecDeque<String>,
+    counted: HashSet<String>,
+    recent: HashMap<String, VecDeque<Rejection>>,
+    tripped: HashMap<String, RejectionAlert>,
+}
+
+/// 拒单熔断器
+///
+/// 按来源（各策略、手动）统计柜台和交易所拒单，窗口内拒单过多时暂停该来源的
+/// 后续报单并生成汇总告警，须人工确认后恢复，其他来源不受影响
+#[derive(Clone)]
+pub struct RejectionBreaker {
+    config: RejectionBreakerConfig,
+    inner: Arc<Mutex<BreakerInner>>,
+}
+
+impl RejectionBreaker {
+    pub fn new() -> Self {
+        Self::with_config(RejectionBreakerConfig::default())
+    }
+
+    pub fn with_config(config: RejectionBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(BreakerInner::default())),
+        }
+    }
+
+    /// 报单前检查来源是否已暂停
+    pub fn check(&self, source: &str) -> Result<(), CtpError> {
+        let inner = self.inner.lock().unwrap();
+        match inner.tripped.get(source) {
+            Some(alert) => Err(CtpError::RiskControl(format!(
+                "{} 连续拒单 {} 次已暂停报单，请确认后恢复",
+                source, alert.rejections
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// 登记报单来源，拒单回报只带报单引用
+    pub fn register_order(&self, order_ref: &str, source: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.order_sources.len() >= MAX_TRACKED_ORDERS {
+            for _ in 0..MAX_TRACKED_ORDERS / 2 {
+                if let Some(old) = inner.order_queue.pop_front() {
+                    inner.order_sources.remove(&old);
+                    inner.counted.remove(&old);
+                }
+            }
+        }
+        inner.order_sources.insert(order_ref.to_string(), source.to_string());
+        inner.order_queue.push_back(order_ref.to_string());
+    }
+
+    /// 记录拒单，本次触发熔断时返回告警
+    ///
+    /// 柜台拒单会同时收到报单响应和错误回报，同一报单引用只计一次
+    pub fn record_rejection(&self, order_ref: &str, instrument_id: &str, reason: &str) -> Option<RejectionAlert> {
+        let now = chrono::Utc::now();
+        let window = chrono::Duration::from_std(self.config.window).unwrap_or_else(|_| chrono::Duration::seconds(30));
+
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        if !order_ref.is_empty() && !inner.counted.insert(order_ref.to_string()) {
+            return None;
+        }
+        let source = inner
+            .order_sources
+            .get(order_ref)
+            .cloned()
+            .unwrap_or_else(|| MANUAL_SOURCE.to_string());
+
+        let recent = inner.recent.entry(source.clone()).or_default();
+        recent.push_back(Rejection {
+            order_ref: order_ref.to_string(),
+            instrument_id: instrument_id.to_string(),
+            reason: reason.to_string(),
+            rejected_at: now,
+        });
+        while recent.front().is_some_and(|r| now - r.rejected_at > window) {
+            recent.pop_front();
+        }
+
+        if recent.len() <= self.config.max_rejections || inner.tripped.contains_key(&source) {
+            return None;
+        }
+
+        let alert = build_alert(&source, recent, self.config.window, now);
+        tracing::error!(
+            "来源 {} 在 {} 秒内被拒单 {} 次，已暂停报单",
+            source,
+            alert.window_secs,
+            alert.rejections
+        );
+        inner.tripped.insert(source, alert.clone());
+        Some(alert)
+    }
+
+    /// 确认告警并恢复来源报单，来源未暂停时返回 false
+    pub fn acknowledge(&self, source: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let resumed = inner.tripped.remove(source).is_some();
+        if resumed {
+            inner.recent.remove(source);
+            tracing::info!("来源 {} 拒单熔断已确认，恢复报单", source);
+        }
+        resumed
+    }
+
+    /// 所有有拒单记录或已暂停的来源状态
+    pub fn statuses(&self) -> Vec<SourceBreakerStatus> {
+        let now = chrono::Utc::now();
+        let window = chrono::Duration::from_std(self.config.window).unwrap_or_else(|_| chrono::Duration::seconds(30));
+        let inner = self.inner.lock().unwrap();
+
+        let mut sources: Vec<&String> = inner.recent.keys().chain(inner.tripped.keys()).collect();
+        sources.sort();
+        sources.dedup();
+        sources
+            .into_iter()
+            .map(|source| SourceBreakerStatus {
+                source: source.clone(),
+                paused: inner.tripped.contains_key(source),
+                recent_rejections: inner
+                    .recent
+                    .get(source)
+                    .map_or(0, |r| r.iter().filter(|r| now - r.rejected_at <= window).count()),
+                alert: inner.tripped.get(source).cloned(),
+            })
+            .collect()
+    }
+}
+
+impl Default for RejectionBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn build_alert(
+    source: &str,
+    recent: &VecDeque<Rejection>,
+    window: Duration,
+    tripped_at: chrono::DateTime<chrono::Utc>,
+) -> RejectionAlert {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut instruments: Vec<String> = Vec::new();
+    for rejection in recent {
+        *counts.entry(rejection.reason.as_str()).or_default() += 1;
+        if !instruments.contains(&rejection.instrument_id) {
+            instruments.push(rejection.instrument_id.clone());
+        }
+    }
+
+    let mut reasons: Vec<RejectionReason> = counts
+        .into_iter()
+        .map(|(reason, count)| RejectionReason {
+            reason: reason.to_string(),
+            count,
+        })
+        .collect();
+    reasons.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+
+    RejectionAlert {
+        source: source.to_string(),
+        rejections: recent.len(),
+        window_secs: window.as_secs(),
+        reasons,
+        instruments,
+        tripped_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::strategy_guard::STRATEGY_TAG;
+
+    #[test]
+    fn test_trips_per_source_and_requires_ack() {
+        let breaker = RejectionBreaker::with_config(RejectionBreakerConfig {
+            max_rejections: 2,
+            window: Duration::from_secs(60),
+        });
+        let tags = OrderTags::from([(STRATEGY_TAG.to_string(), "grid".to_string())]);
+        let strategy = order_source(&tags);
+        assert_eq!(strategy, "strategy:grid");
+
+        for i in 0..3 {
+            breaker.register_order(&format!("{}", i), &strategy);
+        }
+        assert!(breaker.record_rejection("0", "rb2501", "资金不足").is_none());
+        // 同一报单的重复拒单回报只计一次
+        assert!(breaker.record_rejection("0", "rb2501", "资金不足").is_none());
+        assert!(breaker.record_rejection("1", "rb2501", "价格超出涨跌停").is_none());
+        let alert = breaker.record_rejection("2", "hc2501", "资金不足").unwrap();
+
+        assert_eq!(alert.rejections, 3);
+        assert_eq!(
+            alert.reasons[0],
+            RejectionReason {
+                reason: "资金不足".to_string(),
+                count: 2
+            }
+        );
+        assert_eq!(alert.instruments, vec!["rb2501".to_string(), "hc2501".to_string()]);
+
+        assert!(matches!(breaker.check(&strategy), Err(CtpError::RiskControl(_))));
+        assert!(breaker.check(MANUAL_SOURCE).is_ok());
+
+        assert!(breaker.acknowledge(&strategy));
+        assert!(breaker.check(&strategy).is_ok());
+        assert!(!breaker.acknowledge(&strategy));
+    }
+
+    #[test]
+    fn test_unregistered_orders_count_as_manual() {
+        let breaker = RejectionBreaker::with_config(RejectionBreakerConfig {
+            max_rejections: 1,
+            window: Duration::from_secs(60),
+        });
+        breaker.record_rejection("a", "rb2501", "资金不足");
+        assert!(breaker.record_rejection("b", "rb2501", "资金不足").is_some());
+
+        let statuses = breaker.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].source, MANUAL_SOURCE);
+        assert!(statuses[0].paused);
+        assert_eq!(statuses[0].recent_rejections, 2);
+    }
+}
@@ -0,0 +1,564 @@
+use crate::ctp::{
+    tick_compaction::{CompactionConfig, StorageGranularity, TickCompactor, DEFAULT_ARCHIVE_DIR, DEFAULT_RAW_DIR},
+    CtpError, DEFAULT_ACTION_DIR, DEFAULT_CLIENT_ORDER_DIR, DEFAULT_ROUND_TRIP_DIR, DEFAULT_TIMELINE_DIR,
+    DEFAULT_TRADE_DEDUP_DIR,
+};
+use crate::logging::{seek_index, LogConfig, LogIndexManager, LogType, LogTypePolicy};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// 默认数据保留策略文件
+pub const DEFAULT_RETENTION_CONFIG_FILE: &str = "./config/retention.toml";
+
+/// JSONL 记录中按顺序查找的时间字段（时间线、订单号映射、回合交易、操作录制、决策审计）
+const RECORD_TIME_FIELDS: [&str; 5] = ["timestamp", "created_at", "closed_at", "recorded_at", "captured_at"];
+
+/// 按保留策略清理的 K 线粒度
+const BAR_GRANULARITIES: [StorageGranularity; 7] = [
+    StorageGranularity::Bar1s,
+    StorageGranularity::Bar1m,
+    StorageGranularity::Bar5m,
+    StorageGranularity::Bar15m,
+    StorageGranularity::Bar30m,
+    StorageGranularity::Bar1h,
+    StorageGranularity::Bar1d,
+];
+
+/// 数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RetentionCategory {
+    Logs,
+    Ticks,
+    Bars,
+    OrderHistory,
+    Journals,
+}
+
+/// 数据保留策略，天数为 None 的类别不清理
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// 是否随压缩任务自动清理
+    pub enabled: bool,
+    /// 归档 tick 保留天数
+    pub tick_days: Option<u32>,
+    /// 归档 K 线保留天数
+    pub bar_days: Option<u32>,
+    /// 订单号映射、回合交易和成交去重记录保留天数
+    pub order_history_days: Option<u32>,
+    /// 时间线和操作录制保留天数
+    pub journal_days: Option<u32>,
+    pub log_dir: PathBuf,
+    pub archive_dir: PathBuf,
+    pub order_history_dirs: Vec<PathBuf>,
+    pub journal_dirs: Vec<PathBuf>,
+    /// 各日志通道的保留天数，键为通道名（app、ctp、trading 等），未配置的通道沿用日志轮转的默认值
+    pub log_days: BTreeMap<String, u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            log_days: BTreeMap::from([
+                (LogType::App.as_str().to_string(), 30),
+                (LogType::Ctp.as_str().to_string(), 30),
+                (LogType::Trading.as_str().to_string(), 365),
+                (LogType::MarketData.as_str().to_string(), 14),
+                (LogType::Error.as_str().to_string(), 180),
+                (LogType::Performance.as_str().to_string(), 14),
+            ]),
+            tick_days: Some(90),
+            bar_days: None,
+            order_history_days: None,
+            journal_days: None,
+            log_dir: LogConfig::default().output_dir,
+            archive_dir: PathBuf::from(DEFAULT_ARCHIVE_DIR),
+            order_history_dirs: vec![
+                PathBuf::from(DEFAULT_CLIENT_ORDER_DIR),
+                PathBuf::from(DEFAULT_ROUND_TRIP_DIR),
+                PathBuf::from(DEFAULT_TRADE_DEDUP_DIR),
+            ],
+            journal_dirs: vec![PathBuf::from(DEFAULT_TIMELINE_DIR), PathBuf::from(DEFAULT_ACTION_DIR)],
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// 从 TOML 文件加载，文件不存在时返回默认策略
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let policy: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("数据保留策略解析失败: {}", e)))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("数据保留策略序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        for (channel, days) in &self.log_days {
+            if log_type(channel).is_none() {
+                return Err(CtpError::ConfigError(format!("未知日志通道: {}", channel)));
+            }
+            if *days == 0 {
+                return Err(CtpError::ConfigError(format!("日志通道 {} 的保留天数必须大于0", channel)));
+            }
+        }
+        let days = [self.tick_days, self.bar_days, self.order_history_days, self.journal_days];
+        if days.iter().any(|d| *d == Some(0)) {
+            return Err(CtpError::ConfigError("保留天数必须大于0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 将各日志通道的保留天数写入日志配置，使日志轮转任务按同一策略清理
+    pub fn apply_to_log_config(&self, mut config: LogConfig) -> LogConfig {
+        for (channel, days) in &self.log_days {
+            if let Some(log_type) = log_type(channel) {
+                let policy = config.type_policies.remove(&log_type).unwrap_or_else(LogTypePolicy::new);
+                config.type_policies.insert(log_type, policy.with_retention_days(*days));
+            }
+        }
+        config
+    }
+}
+
+/// 待清理（或已清理）的一项数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionItem {
+    pub category: RetentionCategory,
+    pub path: PathBuf,
+    /// 归档行情所属交易日
+    pub trading_day: Option<NaiveDate>,
+    pub bytes: u64,
+    /// 按行裁剪的 JSONL 文件删除的记录数，整文件或目录删除时为 None
+    pub records: Option<usize>,
+}
+
+/// 数据保留清理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// 试运行只列出将删除的数据
+    pub dry_run: bool,
+    pub generated_at: DateTime<Utc>,
+    pub items: Vec<RetentionItem>,
+    pub total_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+impl RetentionReport {
+    fn new(dry_run: bool, now: DateTime<Utc>) -> Self {
+        Self {
+            dry_run,
+            generated_at: now,
+            items: Vec::new(),
+            total_bytes: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, item: RetentionItem) {
+        self.total_bytes += item.bytes;
+        self.items.push(item);
+    }
+
+    /// 某一类别涉及的字节数
+    pub fn bytes_for(&self, category: RetentionCategory) -> u64 {
+        self.items.iter().filter(|i| i.category == category).map(|i| i.bytes).sum()
+    }
+}
+
+/// 数据保留策略管理
+///
+/// 统一按类别清理日志、归档行情、订单历史和日志型记录：日志和其他文件按修改时间
+/// 整文件删除，JSONL 记录按时间字段逐行裁剪，归档行情按交易日删除对应粒度。
+/// 试运行给出同样的清单但不删除；定时清理随行情压缩任务执行
+#[derive(Clone)]
+pub struct RetentionManager {
+    policy: Arc<Mutex<RetentionPolicy>>,
+}
+
+impl RetentionManager {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self {
+            policy: Arc::new(Mutex::new(policy)),
+        }
+    }
+
+    pub fn policy(&self) -> RetentionPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    pub fn update_policy(&self, policy: RetentionPolicy) -> Result<(), CtpError> {
+        policy.validate()?;
+        *self.policy.lock().unwrap() = policy;
+        Ok(())
+    }
+
+    /// 试运行：列出按当前策略将删除的数据
+    pub fn preview(&self, now: DateTime<Utc>) -> RetentionReport {
+        self.run(now, true)
+    }
+
+    /// 是否随压缩任务自动清理
+    pub fn is_enabled(&self) -> bool {
+        self.policy.lock().unwrap().enabled
+    }
+
+    /// 按策略删除过期数据
+    pub fn enforce(&self, now: DateTime<Utc>) -> RetentionReport {
+        let report = self.run(now, false);
+        if !report.items.is_empty() {
+            info!("数据保留清理完成: {} 项，{} 字节", report.items.len(), report.total_bytes);
+        }
+        report
+    }
+
+    fn run(&self, now: DateTime<Utc>, dry_run: bool) -> RetentionReport {
+        let policy = self.policy();
+        let mut report = RetentionReport::new(dry_run, now);
+
+        for (channel, days) in &policy.log_days {
+            if let Err(e) = sweep_logs(&policy.log_dir, channel, cutoff(now, *days), dry_run, &mut report) {
+                report.errors.push(format!("日志通道 {}: {}", channel, e));
+            }
+        }
+
+        let compactor = TickCompactor::new(CompactionConfig::new(DEFAULT_RAW_DIR, &policy.archive_dir));
+        if let Some(days) = policy.tick_days {
+            let before = cutoff(now, days).date_naive();
+            if let Err(e) = sweep_archive(&compactor, &[StorageGranularity::Tick], RetentionCategory::Ticks, before, dry_run, &mut report) {
+                report.errors.push(format!("归档 tick: {}", e));
+            }
+        }
+        if let Some(days) = policy.bar_days {
+            let before = cutoff(now, days).date_naive();
+            if let Err(e) = sweep_archive(&compactor, &BAR_GRANULARITIES, RetentionCategory::Bars, before, dry_run, &mut report) {
+                report.errors.push(format!("归档 K 线: {}", e));
+            }
+        }
+
+        let record_dirs = [
+            (RetentionCategory::OrderHistory, policy.order_history_days, &policy.order_history_dirs),
+            (RetentionCategory::Journals, policy.journal_days, &policy.journal_dirs),
+        ];
+        for (category, days, dirs) in record_dirs {
+            let Some(days) = days else {
+                continue;
+            };
+            for dir in dirs {
+                if let Err(e) = sweep_records(dir, category, cutoff(now, days), dry_run, &mut report) {
+                    report.errors.push(format!("{}: {}", dir.display(), e));
+                }
+            }
+        }
+
+        for error in &report.errors {
+            warn!("数据保留清理失败: {}", error);
+        }
+        report
+    }
+}
+
+fn log_type(channel: &str) -> Option<LogType> {
+    LogType::all().into_iter().find(|t| t.as_str() == channel)
+}
+
+fn cutoff(now: DateTime<Utc>, days: u32) -> DateTime<Utc> {
+    now - chrono::Duration::days(days as i64)
+}
+
+fn modified_before(metadata: &std::fs::Metadata, cutoff: DateTime<Utc>) -> bool {
+    metadata.modified().map(|m| DateTime::<Utc>::from(m) < cutoff).unwrap_or(false)
+}
+
+/// 日志通道目录中早于截止时间的轮转文件，当前写入的文件不删除
+fn sweep_logs(
+    log_dir: &Path,
+    channel: &str,
+    cutoff: DateTime<Utc>,
+    dry_run: bool,
+    report: &mut RetentionReport,
+) -> Result<(), CtpError> {
+    let log_type = log_type(channel).ok_or_else(|| CtpError::ConfigError(format!("未知日志通道: {}", channel)))?;
+    let dir = log_dir.join(channel);
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let mut deleted = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // 索引文件随对应的压缩文件一起删除
+        if !path.is_file() || seek_index::is_seek_index(&path) || entry.file_name() == log_type.file_name() {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if !modified_before(&metadata, cutoff) {
+            continue;
+        }
+        if !dry_run {
+            std::fs::remove_file(&path)?;
+            let index = seek_index::seek_index_path(&path);
+            if index.exists() {
+                std::fs::remove_file(index)?;
+            }
+            deleted.push(path.clone());
+        }
+        report.push(RetentionItem {
+            category: RetentionCategory::Logs,
+            path,
+            trading_day: None,
+            bytes: metadata.len(),
+            records: None,
+        });
+    }
+
+    if !deleted.is_empty() {
+        let config = LogConfig {
+            output_dir: log_dir.to_path_buf(),
+            ..LogConfig::default()
+        };
+        if let Err(e) = LogIndexManager::new(&config).and_then(|mut m| m.forget_files(&config, &deleted)) {
+            warn!(error = %e, "更新日志校验清单失败");
+        }
+    }
+    Ok(())
+}
+
+/// 早于截止交易日的归档目录
+fn sweep_archive(
+    compactor: &TickCompactor,
+    granularities: &[StorageGranularity],
+    category: RetentionCategory,
+    before: NaiveDate,
+    dry_run: bool,
+    report: &mut RetentionReport,
+) -> Result<(), CtpError> {
+    for day in compactor.archived_days()? {
+        if day >= before {
+            continue;
+        }
+        for granularity in granularities {
+            let path = compactor.day_dir(day).join(granularity.dir_name());
+            if !path.exists() {
+                continue;
+            }
+            let bytes = dir_size(&path)?;
+            if !dry_run {
+                compactor.purge(day, *granularity)?;
+            }
+            report.push(RetentionItem {
+                category,
+                path,
+                trading_day: Some(day),
+                bytes,
+                records: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 订单历史与日志型记录：JSONL 按记录时间逐行裁剪，其他文件按修改时间删除
+fn sweep_records(
+    dir: &Path,
+    category: RetentionCategory,
+    cutoff: DateTime<Utc>,
+    dry_run: bool,
+    report: &mut RetentionReport,
+) -> Result<(), CtpError> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+
+        if path.extension().is_some_and(|ext| ext == "jsonl") {
+            let (kept, expired, expired_bytes) = split_expired(&path, cutoff)?;
+            if expired == 0 {
+                continue;
+            }
+            let whole_file = kept.is_empty();
+            if !dry_run {
+                if whole_file {
+                    std::fs::remove_file(&path)?;
+                } else {
+                    rewrite_lines(&path, &kept)?;
+                }
+            }
+            report.push(RetentionItem {
+                category,
+                path,
+                trading_day: None,
+                bytes: if whole_file { metadata.len() } else { expired_bytes },
+                records: Some(expired),
+            });
+        } else if modified_before(&metadata, cutoff) {
+            if !dry_run {
+                std::fs::remove_file(&path)?;
+            }
+            report.push(RetentionItem {
+                category,
+                path,
+                trading_day: None,
+                bytes: metadata.len(),
+                records: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 拆分 JSONL 文件：返回保留的行、过期记录数和过期行的字节数。没有时间字段的行保留
+fn split_expired(path: &Path, cutoff: DateTime<Utc>) -> Result<(Vec<String>, usize, u64), CtpError> {
+    let file = std::fs::File::open(path)?;
+    let mut kept = Vec::new();
+    let mut expired = 0;
+    let mut expired_bytes = 0;
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if record_time(&line).is_some_and(|t| t < cutoff) {
+            expired += 1;
+            expired_bytes += line.len() as u64 + 1;
+        } else {
+            kept.push(line);
+        }
+    }
+    Ok((kept, expired, expired_bytes))
+}
+
+fn record_time(line: &str) -> Option<DateTime<Utc>> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    RECORD_TIME_FIELDS.iter().find_map(|field| {
+        value
+            .get(field)?
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+    })
+}
+
+/// 先写临时文件再替换，避免裁剪中断时丢失记录
+fn rewrite_lines(path: &Path, lines: &[String]) -> Result<(), CtpError> {
+    let tmp = path.with_extension("jsonl.tmp");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+        for line in lines {
+            writeln!(writer, "{}", line)?;
+        }
+        writer.flush()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> Result<u64, CtpError> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() { dir_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(root: &Path) -> RetentionPolicy {
+        RetentionPolicy {
+            log_days: BTreeMap::new(),
+            tick_days: Some(30),
+            journal_days: Some(7),
+            log_dir: root.join("logs"),
+            archive_dir: root.join("archive"),
+            order_history_dirs: Vec::new(),
+            journal_dirs: vec![root.join("timeline")],
+            ..RetentionPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_preview_then_enforce() {
+        let root = std::env::temp_dir().join(format!("retention_{}", uuid::Uuid::new_v4()));
+        let old_ticks = root.join("archive/20250102/ticks");
+        let new_ticks = root.join("archive/20250301/ticks");
+        std::fs::create_dir_all(&old_ticks).unwrap();
+        std::fs::create_dir_all(&new_ticks).unwrap();
+        std::fs::write(old_ticks.join("rb2501.parquet"), b"old").unwrap();
+        std::fs::write(new_ticks.join("rb2501.parquet"), b"new").unwrap();
+
+        let timeline = root.join("timeline");
+        std::fs::create_dir_all(&timeline).unwrap();
+        std::fs::write(
+            timeline.join("acct.jsonl"),
+            "{\"timestamp\":\"2025-02-01T00:00:00Z\"}\n{\"timestamp\":\"2025-03-09T00:00:00Z\"}\n",
+        )
+        .unwrap();
+
+        let manager = RetentionManager::new(policy(&root));
+        let now = "2025-03-10T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let preview = manager.preview(now);
+        assert!(preview.dry_run);
+        assert!(preview.errors.is_empty());
+        assert_eq!(preview.items.len(), 2);
+        assert_eq!(preview.bytes_for(RetentionCategory::Ticks), 3);
+        assert!(old_ticks.exists());
+
+        let report = manager.enforce(now);
+        assert_eq!(report.items.len(), 2);
+        assert!(!old_ticks.exists());
+        assert!(new_ticks.exists());
+        let journal = report.items.iter().find(|i| i.category == RetentionCategory::Journals).unwrap();
+        assert_eq!(journal.records, Some(1));
+        let remaining = std::fs::read_to_string(timeline.join("acct.jsonl")).unwrap();
+        assert_eq!(remaining.lines().count(), 1);
+        assert!(remaining.contains("2025-03-09"));
+
+        assert!(manager.preview(now).items.is_empty());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_channel() {
+        let mut policy = RetentionPolicy::default();
+        assert!(policy.validate().is_ok());
+        policy.log_days.insert("audit".to_string(), 10);
+        assert!(policy.validate().is_err());
+
+        let mut policy = RetentionPolicy::default();
+        policy.log_days.insert("ctp".to_string(), 3);
+        let config = policy.apply_to_log_config(LogConfig::default());
+        assert_eq!(config.policy_for(LogType::Ctp).retention_days, 3);
+        assert_eq!(config.policy_for(LogType::Trading).retention_days, 365);
+    }
+}
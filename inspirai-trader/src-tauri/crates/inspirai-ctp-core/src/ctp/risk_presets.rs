@@ -0,0 +1,396 @@
+use crate::ctp::{models::RiskParams, CtpError};
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 默认风控预设配置文件
+pub const DEFAULT_RISK_PRESETS_FILE: &str = "./config/risk_presets.toml";
+
+/// 命名的风控参数组合，如“夜盘保守”“日盘正常”“数据发布日”
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub params: RiskParams,
+}
+
+/// 按时段切换预设，结束时间早于开始时间表示跨午夜（如夜盘 21:00-02:30）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetScheduleRule {
+    pub preset: String,
+    /// HH:MM
+    pub start: String,
+    /// HH:MM
+    pub end: String,
+    /// 生效的星期（1 为周一），为空表示每天；跨午夜时段按开始所在日判断
+    #[serde(default)]
+    pub weekdays: Vec<u32>,
+    /// 只在这些日期（YYYYMMDD）生效，如数据发布日；为空表示不限
+    #[serde(default)]
+    pub dates: Vec<String>,
+}
+
+impl PresetScheduleRule {
+    fn matches(&self, now: NaiveDateTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let time = now.time();
+        // 跨午夜时段的后半段属于前一天开始的时段
+        let session_day = if start <= end {
+            if time < start || time >= end {
+                return false;
+            }
+            now.date()
+        } else if time >= start {
+            now.date()
+        } else if time < end {
+            now.date().pred_opt().unwrap_or(now.date())
+        } else {
+            return false;
+        };
+        let weekday_ok = self.weekdays.is_empty() || self.weekdays.contains(&session_day.weekday().number_from_monday());
+        let date_ok = self.dates.is_empty() || self.dates.contains(&session_day.format("%Y%m%d").to_string());
+        weekday_ok && date_ok
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+/// 风控预设配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskPresetConfig {
+    /// 定时切换是否启用
+    pub schedule_enabled: bool,
+    /// 没有时段命中时使用的预设
+    pub default_preset: Option<String>,
+    /// 按顺序匹配，先命中者生效
+    pub schedule: Vec<PresetScheduleRule>,
+    pub presets: Vec<RiskPreset>,
+}
+
+impl RiskPresetConfig {
+    /// 从 TOML 文件加载，文件不存在时返回空配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("风控预设配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        self.validate()?;
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("风控预设配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        let mut names = HashSet::new();
+        for preset in &self.presets {
+            if preset.name.trim().is_empty() {
+                return Err(CtpError::ConfigError("风控预设名称不能为空".to_string()));
+            }
+            if !names.insert(preset.name.as_str()) {
+                return Err(CtpError::ConfigError(format!("风控预设 {} 重复", preset.name)));
+            }
+        }
+        let known = |name: &str| names.contains(name);
+        if let Some(default) = &self.default_preset {
+            if !known(default) {
+                return Err(CtpError::ConfigError(format!("默认风控预设 {} 不存在", default)));
+            }
+        }
+        for rule in &self.schedule {
+            if !known(&rule.preset) {
+                return Err(CtpError::ConfigError(format!("定时切换引用的风控预设 {} 不存在", rule.preset)));
+            }
+            if parse_time(&rule.start).is_none() || parse_time(&rule.end).is_none() {
+                return Err(CtpError::ConfigError(format!(
+                    "风控预设 {} 的时段 {}-{} 格式无效，应为 HH:MM",
+                    rule.preset, rule.start, rule.end
+                )));
+            }
+            if rule.weekdays.iter().any(|d| !(1..=7).contains(d)) {
+                return Err(CtpError::ConfigError(format!("风控预设 {} 的星期应为 1-7", rule.preset)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&RiskPreset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// 当前时刻按时段应生效的预设
+    pub fn scheduled_preset(&self, now: NaiveDateTime) -> Option<String> {
+        self.schedule
+            .iter()
+            .find(|rule| rule.matches(now))
+            .map(|rule| rule.preset.clone())
+            .or_else(|| self.default_preset.clone())
+    }
+}
+
+/// 单个风控参数的变化，值以 JSON 文本表示
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskParamChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// 比较两组风控参数，持仓上限按合约逐项列出
+pub fn diff_risk_params(before: Option<&RiskParams>, after: &RiskParams) -> Vec<RiskParamChange> {
+    let flatten = |params: Option<&RiskParams>| -> Vec<(String, String)> {
+        let Some(serde_json::Value::Object(map)) = params.and_then(|p| serde_json::to_value(p).ok()) else {
+            return Vec::new();
+        };
+        let mut fields = Vec::new();
+        for (key, value) in map {
+            match value {
+                serde_json::Value::Object(inner) => {
+                    fields.extend(inner.into_iter().map(|(k, v)| (format!("{}.{}", key, k), v.to_string())));
+                }
+                other => fields.push((key, other.to_string())),
+            }
+        }
+        fields
+    };
+    let before = flatten(before);
+    let after = flatten(Some(after));
+    let lookup = |fields: &[(String, String)], key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+
+    let keys: BTreeSet<&String> = before.iter().chain(after.iter()).map(|(k, _)| k).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (old, new) = (lookup(&before, key), lookup(&after, key));
+            (old != new).then(|| RiskParamChange {
+                field: key.clone(),
+                before: old,
+                after: new,
+            })
+        })
+        .collect()
+}
+
+/// 预设切换来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PresetSwitchSource {
+    Manual,
+    Schedule,
+}
+
+/// 一次预设切换，附变化的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetSwitch {
+    pub preset: String,
+    pub previous: Option<String>,
+    pub source: PresetSwitchSource,
+    pub switched_at: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<RiskParamChange>,
+}
+
+#[derive(Debug, Default)]
+struct PresetState {
+    config: RiskPresetConfig,
+    active: Option<String>,
+    /// 上次检查时按时段应生效的预设，时段变化时才自动切换，手动切换保持到下一个时段
+    last_scheduled: Option<String>,
+}
+
+/// 风控预设管理
+///
+/// 保存命名预设和定时规则，记录当前生效的预设；参数实际下发由调用方完成
+#[derive(Clone, Default)]
+pub struct RiskPresetManager {
+    state: Arc<Mutex<PresetState>>,
+}
+
+impl RiskPresetManager {
+    pub fn new(config: RiskPresetConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PresetState {
+                config,
+                ..PresetState::default()
+            })),
+        }
+    }
+
+    pub fn config(&self) -> RiskPresetConfig {
+        self.state.lock().unwrap().config.clone()
+    }
+
+    /// 替换配置；当前预设被删除时清空，定时检查重新从当前时段开始
+    pub fn update_config(&self, config: RiskPresetConfig) -> Result<(), CtpError> {
+        config.validate()?;
+        let mut state = self.state.lock().unwrap();
+        if state.active.as_deref().is_some_and(|name| config.preset(name).is_none()) {
+            state.active = None;
+        }
+        state.last_scheduled = None;
+        state.config = config;
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<String> {
+        self.state.lock().unwrap().active.clone()
+    }
+
+    pub fn preset(&self, name: &str) -> Result<RiskPreset, CtpError> {
+        self.state
+            .lock()
+            .unwrap()
+            .config
+            .preset(name)
+            .cloned()
+            .ok_or_else(|| CtpError::NotFound(format!("风控预设 {} 不存在", name)))
+    }
+
+    /// 切换到预设前查看相对当前参数的变化
+    pub fn diff(&self, name: &str, current: Option<&RiskParams>) -> Result<Vec<RiskParamChange>, CtpError> {
+        Ok(diff_risk_params(current, &self.preset(name)?.params))
+    }
+
+    /// 记录预设已生效，返回切换说明
+    pub fn mark_active(
+        &self,
+        name: &str,
+        source: PresetSwitchSource,
+        previous_params: Option<&RiskParams>,
+    ) -> Result<PresetSwitch, CtpError> {
+        let preset = self.preset(name)?;
+        let previous = self.state.lock().unwrap().active.replace(name.to_string());
+        Ok(PresetSwitch {
+            preset: name.to_string(),
+            previous,
+            source,
+            switched_at: chrono::Utc::now(),
+            changes: diff_risk_params(previous_params, &preset.params),
+        })
+    }
+
+    /// 定时检查：按时段应生效的预设发生变化且与当前不同时返回该预设
+    pub fn due_switch(&self, now: NaiveDateTime) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        if !state.config.schedule_enabled {
+            return None;
+        }
+        let target = state.config.scheduled_preset(now)?;
+        if state.last_scheduled.as_deref() == Some(target.as_str()) {
+            return None;
+        }
+        state.last_scheduled = Some(target.clone());
+        (state.active.as_deref() != Some(target.as_str())).then_some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn params(max_order_volume: i32) -> RiskParams {
+        RiskParams {
+            max_position_ratio: 0.8,
+            max_single_loss: 5_000.0,
+            max_daily_loss: 20_000.0,
+            max_order_volume,
+            position_limit: HashMap::from([("rb2501".to_string(), max_order_volume * 5)]),
+            forbidden_instruments: Vec::new(),
+            auto_stop_loss: false,
+            stop_loss_ratio: 0.0,
+            auto_take_profit: false,
+            take_profit_ratio: 0.0,
+            block_limit_locked: false,
+        }
+    }
+
+    fn config() -> RiskPresetConfig {
+        RiskPresetConfig {
+            schedule_enabled: true,
+            default_preset: Some("日盘正常".to_string()),
+            schedule: vec![
+                PresetScheduleRule {
+                    preset: "数据发布日".to_string(),
+                    start: "09:00".to_string(),
+                    end: "11:30".to_string(),
+                    weekdays: Vec::new(),
+                    dates: vec!["20250110".to_string()],
+                },
+                PresetScheduleRule {
+                    preset: "夜盘保守".to_string(),
+                    start: "21:00".to_string(),
+                    end: "02:30".to_string(),
+                    weekdays: vec![1, 2, 3, 4, 5],
+                    dates: Vec::new(),
+                },
+            ],
+            presets: vec![
+                RiskPreset { name: "日盘正常".to_string(), description: String::new(), params: params(10) },
+                RiskPreset { name: "夜盘保守".to_string(), description: String::new(), params: params(2) },
+                RiskPreset { name: "数据发布日".to_string(), description: String::new(), params: params(1) },
+            ],
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_matching() {
+        let config = config();
+        config.validate().unwrap();
+        // 2025-01-10 为周五
+        assert_eq!(config.scheduled_preset(at(10, 10, 0)).as_deref(), Some("数据发布日"));
+        assert_eq!(config.scheduled_preset(at(9, 10, 0)).as_deref(), Some("日盘正常"));
+        assert_eq!(config.scheduled_preset(at(10, 22, 0)).as_deref(), Some("夜盘保守"));
+        // 周六凌晨属于周五夜盘
+        assert_eq!(config.scheduled_preset(at(11, 1, 0)).as_deref(), Some("夜盘保守"));
+        // 周日凌晨属于周六，不在规则内
+        assert_eq!(config.scheduled_preset(at(12, 1, 0)).as_deref(), Some("日盘正常"));
+
+        let mut invalid = config.clone();
+        invalid.schedule[0].preset = "不存在".to_string();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_manual_switch_holds_until_next_period() {
+        let manager = RiskPresetManager::new(config());
+        assert_eq!(manager.due_switch(at(9, 10, 0)).as_deref(), Some("日盘正常"));
+        manager.mark_active("日盘正常", PresetSwitchSource::Schedule, None).unwrap();
+
+        let switch = manager
+            .mark_active("夜盘保守", PresetSwitchSource::Manual, Some(&params(10)))
+            .unwrap();
+        assert_eq!(switch.previous.as_deref(), Some("日盘正常"));
+        let fields: Vec<&str> = switch.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["max_order_volume", "position_limit.rb2501"]);
+        assert_eq!(switch.changes[0].before.as_deref(), Some("10"));
+        assert_eq!(switch.changes[0].after.as_deref(), Some("2"));
+
+        // 同一时段内不覆盖手动切换
+        assert_eq!(manager.due_switch(at(9, 14, 0)), None);
+        assert_eq!(manager.due_switch(at(9, 21, 5)), None);
+        assert_eq!(manager.due_switch(at(10, 9, 30)).as_deref(), Some("数据发布日"));
+    }
+}
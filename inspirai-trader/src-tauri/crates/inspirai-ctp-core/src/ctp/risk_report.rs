@@ -0,0 +1,591 @@
+use crate::ctp::{
+    CtpError,
+    models::{AccountInfo, InstrumentInfo, Position, PositionDirection},
+    settlement_prices::mark_position,
+    bar_import::is_trading_day,
+    tick_compaction::{ArchivedBar, TickCompactor},
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// 默认风险报告目录
+pub const DEFAULT_REPORT_DIR: &str = "./reports/risk";
+
+/// 风险报告配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReportConfig {
+    /// ATR 周期（交易日）
+    pub atr_period: usize,
+    /// VaR 分位数对应的 z 值（1.65 约为 95% 单尾）
+    pub var_z: f64,
+    /// 保证金压力测试的价格变动幅度，如 0.05 表示 ±5%
+    pub stress_moves: Vec<f64>,
+    /// 单一品种/交易所名义敞口占比超过该值时告警
+    pub concentration_warning: f64,
+    /// 报告保存目录
+    pub report_dir: PathBuf,
+    /// 每个交易日在该时刻（收盘后）之后自动生成报告
+    #[serde(default = "default_run_after")]
+    pub run_after: NaiveTime,
+}
+
+fn default_run_after() -> NaiveTime {
+    NaiveTime::from_hms_opt(15, 30, 0).unwrap()
+}
+
+impl Default for RiskReportConfig {
+    fn default() -> Self {
+        Self {
+            atr_period: 14,
+            var_z: 1.65,
+            stress_moves: vec![0.03, 0.05, 0.10],
+            concentration_warning: 0.5,
+            report_dir: PathBuf::from(DEFAULT_REPORT_DIR),
+            run_after: default_run_after(),
+        }
+    }
+}
+
+/// 盯市价格来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkSource {
+    /// 当日结算价
+    Settlement,
+    /// 日线收盘价
+    #[default]
+    DailyClose,
+    /// 缺少行情时的持仓均价
+    AverageCost,
+}
+
+/// 单合约风险
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentRisk {
+    pub instrument_id: String,
+    pub product_id: String,
+    pub exchange_id: String,
+    /// 净持仓（多为正、空为负）
+    pub net_volume: i32,
+    pub gross_volume: i32,
+    pub last_price: f64,
+    #[serde(default)]
+    pub mark_source: MarkSource,
+    /// 按结算价盯市的持仓盈亏，未取得结算价时为 None
+    #[serde(default)]
+    pub settlement_pnl: Option<f64>,
+    /// 带方向的名义价值
+    pub net_notional: f64,
+    pub gross_notional: f64,
+    pub margin: f64,
+    /// ATR（价格单位），历史数据不足时为 None
+    pub atr: Option<f64>,
+    /// 基于 ATR 的单日 VaR 近似（金额）
+    pub var: Option<f64>,
+}
+
+/// 集中度统计项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcentrationEntry {
+    pub key: String,
+    pub gross_notional: f64,
+    pub net_notional: f64,
+    /// 占总名义敞口的比例
+    pub share: f64,
+}
+
+/// 价格整体变动情景下的保证金压力
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressScenario {
+    /// 价格变动幅度，如 -0.05
+    pub price_move: f64,
+    pub pnl: f64,
+    pub equity: f64,
+    pub margin: f64,
+    /// 保证金 / 权益，权益不为正时为 None
+    pub risk_ratio: Option<f64>,
+}
+
+/// 每日风险报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyRiskReport {
+    pub trading_day: NaiveDate,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub balance: f64,
+    pub margin: f64,
+    pub gross_notional: f64,
+    pub net_notional: f64,
+    /// 各合约 VaR 直接相加（不考虑相关性，偏保守）
+    pub total_var: f64,
+    pub instruments: Vec<InstrumentRisk>,
+    pub by_product: Vec<ConcentrationEntry>,
+    pub by_exchange: Vec<ConcentrationEntry>,
+    pub stress: Vec<StressScenario>,
+    pub warnings: Vec<String>,
+}
+
+/// 风险报告生成器
+///
+/// 根据持仓、账户资金和日线计算 ATR 近似 VaR、品种/交易所集中度与保证金压力，
+/// 报告以 JSON 按交易日保存在报告目录
+pub struct RiskReportGenerator {
+    config: RiskReportConfig,
+    /// 当日结算价，有结算价的合约按结算价盯市并重算保证金
+    settlement_prices: HashMap<String, f64>,
+}
+
+impl RiskReportGenerator {
+    pub fn new(config: RiskReportConfig) -> Self {
+        Self {
+            config,
+            settlement_prices: HashMap::new(),
+        }
+    }
+
+    pub fn with_settlement_prices(mut self, settlement_prices: HashMap<String, f64>) -> Self {
+        self.settlement_prices = settlement_prices;
+        self
+    }
+
+    pub fn config(&self) -> &RiskReportConfig {
+        &self.config
+    }
+
+    /// 从归档读取截至 `end_day` 的日线，用于计算 ATR；没有日线的交易日由分钟线等日内数据汇总
+    pub fn load_daily_bars(
+        &self,
+        store: &TickCompactor,
+        instruments: &[String],
+        end_day: NaiveDate,
+    ) -> Result<HashMap<String, Vec<ArchivedBar>>, CtpError> {
+        // 按自然日回溯，覆盖节假日
+        let lookback = (self.config.atr_period as i64 + 1) * 2 + 10;
+        let start_day = end_day - chrono::Duration::days(lookback);
+
+        let mut result = HashMap::new();
+        for instrument_id in instruments {
+            let mut bars = Vec::new();
+            for day in start_day.iter_days().take_while(|d| *d <= end_day) {
+                bars.extend(store.read_daily_bar(day, instrument_id)?);
+            }
+            result.insert(instrument_id.clone(), bars);
+        }
+        Ok(result)
+    }
+
+    /// 生成报告
+    pub fn generate(
+        &self,
+        trading_day: NaiveDate,
+        positions: &[Position],
+        account: Option<&AccountInfo>,
+        specs: &HashMap<String, InstrumentInfo>,
+        daily_bars: &HashMap<String, Vec<ArchivedBar>>,
+    ) -> DailyRiskReport {
+        let mut warnings = Vec::new();
+
+        // 同一合约的多空持仓合并
+        let mut grouped: HashMap<&str, Vec<&Position>> = HashMap::new();
+        for position in positions.iter().filter(|p| p.total_position > 0) {
+            grouped.entry(position.instrument_id.as_str()).or_default().push(position);
+        }
+
+        let mut instruments = Vec::new();
+        for (instrument_id, positions) in grouped {
+            let spec = specs.get(instrument_id);
+            if spec.is_none() {
+                warnings.push(format!("{} 缺少合约信息，按乘数 1 计算", instrument_id));
+            }
+            let multiplier = spec.map_or(1.0, |s| s.volume_multiple.max(1) as f64);
+
+            let mut net_volume = 0;
+            let mut gross_volume = 0;
+            let mut margin = 0.0;
+            let mut cost = 0.0;
+            for position in &positions {
+                let signed = match position.direction {
+                    PositionDirection::Long => position.total_position,
+                    PositionDirection::Short => -position.total_position,
+                };
+                net_volume += signed;
+                gross_volume += position.total_position;
+                margin += position.margin;
+                cost += position.position_cost;
+            }
+
+            let bars = daily_bars.get(instrument_id).map(Vec::as_slice).unwrap_or(&[]);
+            let settlement_price = self.settlement_prices.get(instrument_id).copied();
+            let (last_price, mark_source) = match (settlement_price, bars.last()) {
+                (Some(price), _) => (price, MarkSource::Settlement),
+                (None, Some(bar)) => (bar.close, MarkSource::DailyClose),
+                (None, None) => {
+                    warnings.push(format!("{} 缺少日线数据，以持仓均价估算", instrument_id));
+                    (cost / (gross_volume as f64 * multiplier), MarkSource::AverageCost)
+                }
+            };
+
+            // 按结算价盯市，有保证金率时保证金也按结算价重算
+            let mut settlement_pnl = None;
+            if let Some(price) = settlement_price {
+                let marks: Vec<_> = positions.iter().map(|p| mark_position(p, spec, price)).collect();
+                settlement_pnl = Some(marks.iter().map(|m| m.mtm_pnl).sum());
+                if marks.iter().all(|m| m.margin.is_some()) {
+                    margin = marks.iter().filter_map(|m| m.margin).sum();
+                }
+            }
+
+            let atr = average_true_range(bars, self.config.atr_period);
+            if atr.is_none() && !bars.is_empty() {
+                warnings.push(format!(
+                    "{} 日线不足 {} 根，无法计算 VaR",
+                    instrument_id,
+                    self.config.atr_period + 1
+                ));
+            }
+            let var = atr.map(|atr| self.config.var_z * atr * multiplier * net_volume.abs() as f64);
+
+            instruments.push(InstrumentRisk {
+                instrument_id: instrument_id.to_string(),
+                product_id: spec.map(|s| s.product_id.clone()).unwrap_or_default(),
+                exchange_id: spec.map(|s| s.exchange_id.clone()).unwrap_or_default(),
+                net_volume,
+                gross_volume,
+                last_price,
+                mark_source,
+                settlement_pnl,
+                net_notional: last_price * multiplier * net_volume as f64,
+                gross_notional: last_price * multiplier * gross_volume as f64,
+                margin,
+                atr,
+                var,
+            });
+        }
+        instruments.sort_by(|a, b| a.instrument_id.cmp(&b.instrument_id));
+
+        let gross_notional: f64 = instruments.iter().map(|i| i.gross_notional).sum();
+        let net_notional: f64 = instruments.iter().map(|i| i.net_notional).sum();
+        let total_var: f64 = instruments.iter().filter_map(|i| i.var).sum();
+        let position_margin: f64 = instruments.iter().map(|i| i.margin).sum();
+        let (balance, margin) = match account {
+            Some(account) => (account.balance, account.curr_margin),
+            None => (0.0, position_margin),
+        };
+
+        let by_product = concentration(&instruments, gross_notional, |i| &i.product_id);
+        let by_exchange = concentration(&instruments, gross_notional, |i| &i.exchange_id);
+        for entry in by_product.iter().chain(by_exchange.iter()) {
+            if entry.share > self.config.concentration_warning && instruments.len() > 1 {
+                warnings.push(format!("{} 名义敞口占比 {:.1}%", entry.key, entry.share * 100.0));
+            }
+        }
+
+        let stress = self.stress(&instruments, balance, margin);
+        if let Some(breach) = stress.iter().find(|s| s.risk_ratio.is_none_or(|r| r >= 1.0)) {
+            warnings.push(format!(
+                "价格变动 {:+.0}% 时保证金超过权益",
+                breach.price_move * 100.0
+            ));
+        }
+
+        DailyRiskReport {
+            trading_day,
+            generated_at: chrono::Utc::now(),
+            balance,
+            margin,
+            gross_notional,
+            net_notional,
+            total_var,
+            instruments,
+            by_product,
+            by_exchange,
+            stress,
+            warnings,
+        }
+    }
+
+    /// 所有合约同时按比例变动时的权益与保证金（保证金随价格线性变化）
+    fn stress(&self, instruments: &[InstrumentRisk], balance: f64, margin: f64) -> Vec<StressScenario> {
+        let net_notional: f64 = instruments.iter().map(|i| i.net_notional).sum();
+        let mut scenarios = Vec::new();
+        for &size in &self.config.stress_moves {
+            for price_move in [-size, size] {
+                let pnl = net_notional * price_move;
+                let equity = balance + pnl;
+                let stressed_margin = margin * (1.0 + price_move);
+                scenarios.push(StressScenario {
+                    price_move,
+                    pnl,
+                    equity,
+                    margin: stressed_margin,
+                    risk_ratio: (equity > 0.0).then(|| stressed_margin / equity),
+                });
+            }
+        }
+        scenarios
+    }
+
+    /// 保存报告到报告目录，同一交易日覆盖
+    pub fn save(&self, report: &DailyRiskReport) -> Result<PathBuf, CtpError> {
+        std::fs::create_dir_all(&self.config.report_dir)?;
+        let path = self.report_path(report.trading_day);
+        let content = serde_json::to_string_pretty(report)
+            .map_err(|e| CtpError::ConversionError(format!("序列化风险报告失败: {}", e)))?;
+        std::fs::write(&path, content)?;
+
+        info!(
+            "交易日 {} 风险报告已生成: VaR {:.0}，名义敞口 {:.0}，{} 条告警",
+            report.trading_day,
+            report.total_var,
+            report.gross_notional,
+            report.warnings.len()
+        );
+        for warning in &report.warnings {
+            warn!("风险报告告警: {}", warning);
+        }
+        Ok(path)
+    }
+
+    /// 读取已保存的报告
+    pub fn load(&self, trading_day: NaiveDate) -> Result<Option<DailyRiskReport>, CtpError> {
+        let path = self.report_path(trading_day);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| CtpError::ConversionError(format!("解析风险报告失败: {}", e)))
+    }
+
+    /// 需要自动生成报告的交易日：交易日 `run_after` 之后且当日报告尚未保存
+    pub fn due_report_day(&self, now: NaiveDateTime) -> Option<NaiveDate> {
+        let day = now.date();
+        (is_trading_day(day) && now.time() >= self.config.run_after && !self.report_path(day).exists()).then_some(day)
+    }
+
+    fn report_path(&self, trading_day: NaiveDate) -> PathBuf {
+        self.config
+            .report_dir
+            .join(format!("risk_{}.json", trading_day.format("%Y%m%d")))
+    }
+}
+
+/// 平均真实波幅（简单平均），需要 `period + 1` 根日线
+pub fn average_true_range(bars: &[ArchivedBar], period: usize) -> Option<f64> {
+    if period == 0 || bars.len() < period + 1 {
+        return None;
+    }
+    let recent = &bars[bars.len() - period - 1..];
+    let sum: f64 = recent
+        .windows(2)
+        .map(|w| {
+            let (prev, bar) = (&w[0], &w[1]);
+            (bar.high - bar.low)
+                .max((bar.high - prev.close).abs())
+                .max((bar.low - prev.close).abs())
+        })
+        .sum();
+    Some(sum / period as f64)
+}
+
+fn concentration(
+    instruments: &[InstrumentRisk],
+    total: f64,
+    key: impl Fn(&InstrumentRisk) -> &String,
+) -> Vec<ConcentrationEntry> {
+    let mut map: HashMap<String, (f64, f64)> = HashMap::new();
+    for instrument in instruments {
+        let name = if key(instrument).is_empty() { "未知".to_string() } else { key(instrument).clone() };
+        let entry = map.entry(name).or_default();
+        entry.0 += instrument.gross_notional;
+        entry.1 += instrument.net_notional;
+    }
+
+    let mut entries: Vec<ConcentrationEntry> = map
+        .into_iter()
+        .map(|(key, (gross, net))| ConcentrationEntry {
+            key,
+            gross_notional: gross,
+            net_notional: net,
+            share: if total > 0.0 { gross / total } else { 0.0 },
+        })
+        .collect();
+    entries.sort_by(|a, b| b.gross_notional.total_cmp(&a.gross_notional));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::tick_compaction::{CompactionConfig, StorageGranularity};
+
+    fn bar(high: f64, low: f64, close: f64) -> ArchivedBar {
+        ArchivedBar {
+            instrument_id: "rb2501".to_string(),
+            time: "00:00:00".to_string(),
+            open: close,
+            high,
+            low,
+            close,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0,
+            tick_count: 0,
+        }
+    }
+
+    fn position(instrument_id: &str, direction: PositionDirection, volume: i32, margin: f64) -> Position {
+        Position {
+            instrument_id: instrument_id.to_string(),
+            direction,
+            total_position: volume,
+            yesterday_position: volume,
+            today_position: 0,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    fn spec(instrument_id: &str, volume_multiple: i32) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 0,
+            min_market_order_volume: 0,
+            max_limit_order_volume: 0,
+            min_limit_order_volume: 0,
+            volume_multiple,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.0,
+            short_margin_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_average_true_range() {
+        let bars = vec![bar(110.0, 90.0, 100.0), bar(105.0, 95.0, 104.0), bar(120.0, 106.0, 118.0)];
+        // TR: max(10, 5, 5) = 10；max(14, 16, 2) = 16
+        assert_eq!(average_true_range(&bars, 2), Some(13.0));
+        assert_eq!(average_true_range(&bars, 3), None);
+    }
+
+    #[test]
+    fn test_generate_report() {
+        let generator = RiskReportGenerator::new(RiskReportConfig {
+            atr_period: 2,
+            stress_moves: vec![0.1],
+            ..RiskReportConfig::default()
+        });
+
+        let specs = HashMap::from([("rb2501".to_string(), spec("rb2501", 10))]);
+        let bars = HashMap::from([(
+            "rb2501".to_string(),
+            vec![bar(110.0, 90.0, 100.0), bar(105.0, 95.0, 104.0), bar(120.0, 106.0, 100.0)],
+        )]);
+        let positions = vec![
+            position("rb2501", PositionDirection::Long, 3, 300.0),
+            position("rb2501", PositionDirection::Short, 1, 100.0),
+        ];
+
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let report = generator.generate(day, &positions, None, &specs, &bars);
+
+        let rb = &report.instruments[0];
+        assert_eq!(rb.net_volume, 2);
+        assert_eq!(rb.net_notional, 2000.0);
+        assert_eq!(rb.gross_notional, 4000.0);
+        // ATR 13 × 乘数 10 × 净持仓 2 × 1.65
+        assert!((report.total_var - 429.0).abs() < 1e-6);
+        assert_eq!(report.by_exchange[0].share, 1.0);
+
+        // 无账户资金时权益不为正，视为保证金超过权益
+        assert_eq!(report.stress.len(), 2);
+        assert_eq!(report.stress[0].pnl, -200.0);
+        assert!(report.warnings.iter().any(|w| w.contains("保证金超过权益")));
+    }
+
+    #[test]
+    fn test_settlement_price_preferred_for_marking() {
+        let generator = RiskReportGenerator::new(RiskReportConfig::default())
+            .with_settlement_prices(HashMap::from([("rb2501".to_string(), 110.0)]));
+        let mut spec = spec("rb2501", 10);
+        spec.long_margin_ratio = 0.1;
+        let specs = HashMap::from([("rb2501".to_string(), spec)]);
+        let bars = HashMap::from([("rb2501".to_string(), vec![bar(110.0, 90.0, 100.0)])]);
+        let mut long = position("rb2501", PositionDirection::Long, 2, 300.0);
+        long.position_cost = 2000.0;
+
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let report = generator.generate(day, &[long], None, &specs, &bars);
+        let rb = &report.instruments[0];
+        assert_eq!(rb.mark_source, MarkSource::Settlement);
+        assert_eq!(rb.last_price, 110.0);
+        assert_eq!(rb.settlement_pnl, Some(200.0));
+        assert!((rb.margin - 220.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_daily_bars_derived_from_minute_archive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = TickCompactor::new(CompactionConfig::new(dir.path().join("raw"), dir.path().join("archive")));
+        let generator = RiskReportGenerator::new(RiskReportConfig {
+            report_dir: dir.path().join("reports"),
+            ..RiskReportConfig::default()
+        });
+        let day1 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        let minute = |time: &str, high: f64, low: f64, close: f64| ArchivedBar {
+            time: time.to_string(),
+            volume: 5,
+            ..bar(high, low, close)
+        };
+        let minutes = vec![minute("09:00:00", 102.0, 99.0, 101.0), minute("09:01:00", 108.0, 100.0, 104.0)];
+        store.write_bars(day1, "rb2501", StorageGranularity::Bar1m, &minutes).unwrap();
+        store.write_bars(day2, "rb2501", StorageGranularity::Bar1d, &[bar(120.0, 103.0, 110.0)]).unwrap();
+
+        let bars = generator.load_daily_bars(&store, &["rb2501".to_string()], day2).unwrap();
+        let rb = &bars["rb2501"];
+        assert_eq!(rb.len(), 2);
+        assert_eq!((rb[0].open, rb[0].high, rb[0].low, rb[0].close, rb[0].volume), (101.0, 108.0, 99.0, 104.0, 10));
+        assert_eq!(average_true_range(rb, 1), Some(17.0));
+    }
+
+    #[test]
+    fn test_report_due_after_close_once_per_trading_day() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let generator = RiskReportGenerator::new(RiskReportConfig {
+            report_dir: dir.path().to_path_buf(),
+            ..RiskReportConfig::default()
+        });
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        assert_eq!(generator.due_report_day(day.and_hms_opt(15, 0, 0).unwrap()), None);
+        assert_eq!(generator.due_report_day(day.and_hms_opt(15, 31, 0).unwrap()), Some(day));
+        // 周六不生成
+        let saturday = NaiveDate::from_ymd_opt(2025, 1, 4).unwrap();
+        assert_eq!(generator.due_report_day(saturday.and_hms_opt(16, 0, 0).unwrap()), None);
+
+        let report = generator.generate(day, &[], None, &HashMap::new(), &HashMap::new());
+        generator.save(&report).unwrap();
+        assert_eq!(generator.due_report_day(day.and_hms_opt(15, 31, 0).unwrap()), None);
+    }
+}
@@ -0,0 +1,814 @@
+use crate::ctp::{
+    CtpError,
+    market_order::counterparty_price,
+    models::{InstrumentInfo, MarketData, OrderInput, OrderStatus, OrderStatusType, Position, PositionDirection, TradeRecord},
+    utils::sanitize_file_component,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认移仓配置文件
+pub const DEFAULT_ROLLOVER_CONFIG_FILE: &str = "./config/rollover.toml";
+
+/// 默认移仓记录目录
+pub const DEFAULT_ROLLOVER_DIR: &str = "./data/rollovers";
+
+/// 移仓报单的标签键，值为移仓 ID
+pub const ROLLOVER_TAG: &str = "rollover";
+
+/// 移仓报单的腿标签键，值为 close 或 open
+pub const ROLLOVER_LEG_TAG: &str = "rollover_leg";
+
+/// 移仓配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RolloverConfig {
+    /// 距到期日不超过该天数的持仓生成移仓计划
+    pub days_before_expiry: u32,
+    /// 两条腿均以对手价加若干跳报限价单
+    pub price_ticks: u32,
+}
+
+impl Default for RolloverConfig {
+    fn default() -> Self {
+        Self {
+            days_before_expiry: 10,
+            price_ticks: 1,
+        }
+    }
+}
+
+impl RolloverConfig {
+    /// 从 TOML 文件加载，文件不存在时返回默认配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("移仓配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        self.validate()?;
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("移仓配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.days_before_expiry == 0 {
+            return Err(CtpError::ConfigError("移仓提前天数必须大于 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 移仓计划：平掉临近到期的合约，在主力合约开出同方向同手数的持仓
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct RolloverPlan {
+    pub product_id: String,
+    pub direction: PositionDirection,
+    pub from_instrument: String,
+    pub to_instrument: String,
+    pub volume: u32,
+    pub expire_date: String,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub days_to_expiry: i64,
+    /// 平仓腿，上期所、能源中心按今昨仓拆分
+    pub close_orders: Vec<OrderInput>,
+    pub open_order: OrderInput,
+    /// 新合约与旧合约的价差（新 - 旧），按两条腿的报单价计算
+    pub estimated_spread: f64,
+    /// 报单时按最新对手价加该跳数重新定价
+    pub price_ticks: u32,
+}
+
+/// 移仓计划生成结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RolloverReport {
+    pub plans: Vec<RolloverPlan>,
+    /// 临近到期但无法生成计划的持仓（无后续合约、缺少行情等）
+    pub warnings: Vec<String>,
+}
+
+/// 移仓执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum RolloverStatus {
+    /// 平仓腿报单中
+    Closing,
+    /// 平仓已成交部分正在新合约开仓
+    Opening,
+    /// 全部手数已移至新合约
+    Completed,
+    /// 报单失败或被撤销，已移手数见 opened_volume
+    Failed,
+    /// 用户取消
+    Cancelled,
+}
+
+impl RolloverStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// 一次移仓的执行进度，结束后作为关联的平仓、开仓成交对写入移仓记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct RolloverExecution {
+    pub id: String,
+    pub plan: RolloverPlan,
+    pub status: RolloverStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub close_order_refs: Vec<String>,
+    pub open_order_refs: Vec<String>,
+    pub closed_volume: u32,
+    pub opened_volume: u32,
+    pub close_avg_price: f64,
+    pub open_avg_price: f64,
+    pub close_trade_ids: Vec<String>,
+    pub open_trade_ids: Vec<String>,
+    pub message: Option<String>,
+}
+
+impl RolloverExecution {
+    /// 已完成比例，按新合约开仓手数计
+    pub fn progress(&self) -> f64 {
+        if self.plan.volume == 0 {
+            return 1.0;
+        }
+        self.opened_volume as f64 / self.plan.volume as f64
+    }
+
+    /// 实际移仓价差（新 - 旧），尚无成交时为 None
+    pub fn realized_spread(&self) -> Option<f64> {
+        (self.closed_volume > 0 && self.opened_volume > 0).then(|| self.open_avg_price - self.close_avg_price)
+    }
+}
+
+/// 按持仓、合约信息和行情生成移仓计划
+///
+/// 主力合约取同品种、到期更晚的合约中持仓量最大者；行情都不可用时取到期最近的后续合约
+pub fn plan_rollovers(
+    positions: &[Position],
+    instruments: &[InstrumentInfo],
+    markets: &HashMap<String, MarketData>,
+    today: NaiveDate,
+    config: &RolloverConfig,
+) -> RolloverReport {
+    let mut report = RolloverReport::default();
+    for position in positions.iter().filter(|p| p.total_position > 0) {
+        let Some(from) = instruments.iter().find(|i| i.instrument_id == position.instrument_id) else {
+            continue;
+        };
+        let Some(expire) = parse_date(&from.expire_date) else {
+            continue;
+        };
+        let days_to_expiry = (expire - today).num_days();
+        if days_to_expiry > config.days_before_expiry as i64 {
+            continue;
+        }
+
+        let Some(to) = dominant_successor(from, instruments, markets) else {
+            report.warnings.push(format!("{} 临近到期，但没有可移仓的后续合约", from.instrument_id));
+            continue;
+        };
+        let (Some(from_market), Some(to_market)) = (markets.get(&from.instrument_id), markets.get(&to.instrument_id)) else {
+            report.warnings.push(format!("{} 或 {} 缺少行情，无法生成移仓计划", from.instrument_id, to.instrument_id));
+            continue;
+        };
+
+        let (close_direction, open_direction) = match position.direction {
+            PositionDirection::Long => ("Sell", "Buy"),
+            PositionDirection::Short => ("Buy", "Sell"),
+        };
+        let priced = |direction: &str, market: &MarketData, tick: f64| {
+            counterparty_price(direction, Some(market), config.price_ticks, tick)
+        };
+        let (close_price, open_price) = match (
+            priced(close_direction, from_market, from.price_tick),
+            priced(open_direction, to_market, to.price_tick),
+        ) {
+            (Ok(close_price), Ok(open_price)) => (close_price, open_price),
+            (Err(e), _) | (_, Err(e)) => {
+                report.warnings.push(format!("{} 移仓定价失败: {}", from.instrument_id, e));
+                continue;
+            }
+        };
+
+        let close_orders = close_legs(position, &from.exchange_id)
+            .into_iter()
+            .map(|(offset, volume)| leg_order(&from.instrument_id, close_direction, offset, close_price, volume))
+            .collect();
+        let volume = position.total_position as u32;
+        report.plans.push(RolloverPlan {
+            product_id: from.product_id.clone(),
+            direction: position.direction,
+            from_instrument: from.instrument_id.clone(),
+            to_instrument: to.instrument_id.clone(),
+            volume,
+            expire_date: from.expire_date.clone(),
+            days_to_expiry,
+            close_orders,
+            open_order: leg_order(&to.instrument_id, open_direction, "Open", open_price, volume),
+            estimated_spread: open_price - close_price,
+            price_ticks: config.price_ticks,
+        });
+    }
+    report
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+}
+
+fn dominant_successor<'a>(
+    from: &InstrumentInfo,
+    instruments: &'a [InstrumentInfo],
+    markets: &HashMap<String, MarketData>,
+) -> Option<&'a InstrumentInfo> {
+    let from_expire = parse_date(&from.expire_date)?;
+    let successors: Vec<&InstrumentInfo> = instruments
+        .iter()
+        .filter(|i| i.product_id == from.product_id && i.exchange_id == from.exchange_id && i.underlying_instrument.is_empty())
+        .filter(|i| parse_date(&i.expire_date).is_some_and(|d| d > from_expire))
+        .collect();
+    let open_interest = |i: &InstrumentInfo| markets.get(&i.instrument_id).map(|m| m.open_interest);
+    successors
+        .iter()
+        .filter_map(|i| open_interest(*i).map(|oi| (*i, oi)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+        .or_else(|| successors.iter().min_by_key(|i| i.expire_date.clone()).copied())
+}
+
+/// 平仓腿的开平标志和手数：上期所、能源中心区分平今、平昨
+fn close_legs(position: &Position, exchange_id: &str) -> Vec<(&'static str, u32)> {
+    if !matches!(exchange_id, "SHFE" | "INE") {
+        return vec![("Close", position.total_position as u32)];
+    }
+    let today = position.today_position.max(0) as u32;
+    let yesterday = (position.total_position as u32).saturating_sub(today);
+    [("CloseYesterday", yesterday), ("CloseToday", today)]
+        .into_iter()
+        .filter(|(_, volume)| *volume > 0)
+        .collect()
+}
+
+fn leg_order(instrument_id: &str, direction: &str, offset: &str, price: f64, volume: u32) -> OrderInput {
+    OrderInput {
+        instrument_id: instrument_id.to_string(),
+        direction: direction.to_string(),
+        offset: offset.to_string(),
+        price,
+        volume,
+        order_type: "Limit".to_string(),
+        time_condition: "GFD".to_string(),
+        volume_condition: "Any".to_string(),
+        min_volume: 1,
+        contingent_condition: "Immediately".to_string(),
+        stop_price: 0.0,
+        force_close_reason: "NotForceClose".to_string(),
+        is_auto_suspend: false,
+        tags: HashMap::new(),
+    }
+}
+
+/// 待报出的开仓腿
+#[derive(Debug, Clone)]
+pub struct PendingRolloverOrder {
+    pub rollover_id: String,
+    pub order: OrderInput,
+    pub price_ticks: u32,
+}
+
+#[derive(Debug, Default)]
+struct RolloverInner {
+    active: HashMap<String, RolloverExecution>,
+    /// 平仓已成交、等待报出的开仓腿
+    pending: Vec<PendingRolloverOrder>,
+    /// 已结束的移仓，按开始时间排列
+    history: Vec<RolloverExecution>,
+    /// 报单引用 -> 移仓 ID
+    order_refs: HashMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+impl RolloverInner {
+    /// 没有在途报单和待报开仓腿时结束移仓并写入记录，返回结束后的移仓
+    fn settle(&mut self, id: &str) -> Option<RolloverExecution> {
+        let working = self.order_refs.values().any(|v| v == id) || self.pending.iter().any(|p| p.rollover_id == id);
+        if working {
+            return None;
+        }
+        let mut execution = self.active.remove(id)?;
+        if !execution.status.is_finished() {
+            execution.status = if execution.opened_volume >= execution.plan.volume {
+                RolloverStatus::Completed
+            } else {
+                RolloverStatus::Failed
+            };
+        }
+        execution.finished_at = Some(Utc::now());
+        tracing::info!(
+            "移仓 {} 结束: {:?} {} -> {} 已移 {}/{} 手",
+            execution.id,
+            execution.status,
+            execution.plan.from_instrument,
+            execution.plan.to_instrument,
+            execution.opened_volume,
+            execution.plan.volume
+        );
+        if let Some(path) = &self.path {
+            if let Err(e) = append_record(path, &execution) {
+                tracing::warn!("写入移仓记录失败: {}", e);
+            }
+        }
+        self.history.push(execution.clone());
+        Some(execution)
+    }
+}
+
+fn append_record(path: &Path, execution: &RolloverExecution) -> Result<(), CtpError> {
+    let line = serde_json::to_string(execution)
+        .map_err(|e| CtpError::ConversionError(format!("序列化移仓记录失败: {}", e)))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// 移仓执行簿
+///
+/// 先报平仓腿，平仓每成交一笔即按成交手数排队新合约的开仓腿，由后台任务按最新对手价报出，
+/// 使敞口在两合约间的空档尽量短。报单回报和成交回报由交易 SPI 送入
+#[derive(Debug, Clone, Default)]
+pub struct RolloverManager {
+    inner: Arc<Mutex<RolloverInner>>,
+}
+
+impl RolloverManager {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// 打开账户移仓记录，加载 `<dir>/<account_id>.jsonl` 中已结束的移仓
+    pub fn open(dir: impl AsRef<Path>, account_id: &str) -> Result<Self, CtpError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.jsonl", sanitize_file_component(account_id)));
+
+        let mut history = Vec::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                match serde_json::from_str::<RolloverExecution>(&line?) {
+                    Ok(execution) => history.push(execution),
+                    Err(e) => tracing::warn!("跳过无法解析的移仓记录: {}", e),
+                }
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(Mutex::new(RolloverInner {
+                history,
+                path: Some(path),
+                ..RolloverInner::default()
+            })),
+        })
+    }
+
+    /// 开始执行移仓，同一合约同方向已有移仓在途时拒绝
+    pub fn start(&self, plan: RolloverPlan) -> Result<RolloverExecution, CtpError> {
+        if plan.volume == 0 || plan.close_orders.is_empty() {
+            return Err(CtpError::ValidationError("移仓手数为 0".to_string()));
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner
+            .active
+            .values()
+            .any(|e| e.plan.from_instrument == plan.from_instrument && e.plan.direction == plan.direction)
+        {
+            return Err(CtpError::StateError(format!("{} 已有进行中的移仓", plan.from_instrument)));
+        }
+        let id = format!("ROLL-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let execution = RolloverExecution {
+            id: id.clone(),
+            plan,
+            status: RolloverStatus::Closing,
+            started_at: Utc::now(),
+            finished_at: None,
+            close_order_refs: Vec::new(),
+            open_order_refs: Vec::new(),
+            closed_volume: 0,
+            opened_volume: 0,
+            close_avg_price: 0.0,
+            open_avg_price: 0.0,
+            close_trade_ids: Vec::new(),
+            open_trade_ids: Vec::new(),
+            message: None,
+        };
+        inner.active.insert(id, execution.clone());
+        Ok(execution)
+    }
+
+    /// 为腿报单加上移仓标签
+    pub fn tag(order: &mut OrderInput, rollover_id: &str, leg: &str) {
+        order.tags.insert(ROLLOVER_TAG.to_string(), rollover_id.to_string());
+        order.tags.insert(ROLLOVER_LEG_TAG.to_string(), leg.to_string());
+    }
+
+    /// 登记已报出的平仓腿
+    pub fn attach_close_order(&self, rollover_id: &str, order_ref: &str) -> Option<RolloverExecution> {
+        let mut inner = self.inner.lock().unwrap();
+        let execution = inner.active.get_mut(rollover_id)?;
+        execution.close_order_refs.push(order_ref.to_string());
+        let execution = execution.clone();
+        inner.order_refs.insert(order_ref.to_string(), rollover_id.to_string());
+        Some(execution)
+    }
+
+    /// 登记已报出的开仓腿
+    pub fn attach_open_order(&self, rollover_id: &str, order_ref: &str) -> Option<RolloverExecution> {
+        let mut inner = self.inner.lock().unwrap();
+        let execution = inner.active.get_mut(rollover_id)?;
+        execution.open_order_refs.push(order_ref.to_string());
+        let execution = execution.clone();
+        inner.order_refs.insert(order_ref.to_string(), rollover_id.to_string());
+        Some(execution)
+    }
+
+    /// 报单或定价失败：停止移仓，返回需要撤销的在途报单引用
+    pub fn fail(&self, rollover_id: &str, message: String) -> Result<(RolloverExecution, Vec<String>), CtpError> {
+        tracing::warn!("移仓 {} 失败: {}", rollover_id, message);
+        self.stop(rollover_id, RolloverStatus::Failed, Some(message))
+    }
+
+    /// 取消移仓，返回需要撤销的在途报单引用
+    pub fn cancel(&self, rollover_id: &str) -> Result<(RolloverExecution, Vec<String>), CtpError> {
+        self.stop(rollover_id, RolloverStatus::Cancelled, None)
+    }
+
+    /// 停止排队开仓腿；在途报单的撤单回报到达后移仓结束，期间的成交仍计入
+    fn stop(
+        &self,
+        rollover_id: &str,
+        status: RolloverStatus,
+        message: Option<String>,
+    ) -> Result<(RolloverExecution, Vec<String>), CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        let execution = inner
+            .active
+            .get_mut(rollover_id)
+            .ok_or_else(|| CtpError::NotFound(format!("移仓 {} 不存在或已结束", rollover_id)))?;
+        execution.status = status;
+        if message.is_some() {
+            execution.message = message;
+        }
+        let snapshot = execution.clone();
+        inner.pending.retain(|p| p.rollover_id != rollover_id);
+        let working: Vec<String> = inner
+            .order_refs
+            .iter()
+            .filter(|(_, id)| id.as_str() == rollover_id)
+            .map(|(order_ref, _)| order_ref.clone())
+            .collect();
+        let execution = inner.settle(rollover_id).unwrap_or(snapshot);
+        Ok((execution, working))
+    }
+
+    /// 成交回报：平仓成交排队等量开仓，开仓成交计入进度
+    pub fn on_trade(&self, trade: &TradeRecord) -> Option<RolloverExecution> {
+        let volume = trade.volume.max(0) as u32;
+        if volume == 0 {
+            return None;
+        }
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let id = inner.order_refs.get(&trade.order_id)?.clone();
+        let execution = inner.active.get_mut(&id)?;
+        let average = |avg: f64, filled: u32| (avg * filled as f64 + trade.price * volume as f64) / (filled + volume) as f64;
+
+        if execution.close_order_refs.contains(&trade.order_id) {
+            execution.close_avg_price = average(execution.close_avg_price, execution.closed_volume);
+            execution.closed_volume += volume;
+            execution.close_trade_ids.push(trade.trade_id.clone());
+            if execution.status == RolloverStatus::Closing {
+                execution.status = RolloverStatus::Opening;
+            }
+            if !execution.status.is_finished() {
+                let mut order = execution.plan.open_order.clone();
+                order.volume = volume;
+                Self::tag(&mut order, &id, "open");
+                inner.pending.push(PendingRolloverOrder {
+                    rollover_id: id.clone(),
+                    order,
+                    price_ticks: execution.plan.price_ticks,
+                });
+            }
+        } else {
+            execution.open_avg_price = average(execution.open_avg_price, execution.opened_volume);
+            execution.opened_volume += volume;
+            execution.open_trade_ids.push(trade.trade_id.clone());
+        }
+        Some(execution.clone())
+    }
+
+    /// 报单回报：腿报单结束（全部成交、撤单、拒单）后检查移仓是否结束
+    pub fn on_order(&self, status: &OrderStatus) -> Option<RolloverExecution> {
+        let finished = matches!(
+            status.status,
+            OrderStatusType::AllTraded
+                | OrderStatusType::Canceled
+                | OrderStatusType::Cancelled
+                | OrderStatusType::PartTradedNotQueueing
+                | OrderStatusType::NoTradeNotQueueing
+        );
+        if !finished {
+            return None;
+        }
+        let unfilled = (status.volume_left > 0).then(|| {
+            format!("{} 报单 {} 未全部成交: {}", status.instrument_id, status.order_ref, status.status_msg)
+        });
+        self.finish_order(&status.order_ref, unfilled)
+    }
+
+    /// 报单录入被拒绝
+    pub fn on_rejected(&self, order_ref: &str, reason: &str) -> Option<RolloverExecution> {
+        self.finish_order(order_ref, Some(format!("报单 {} 被拒绝: {}", order_ref, reason)))
+    }
+
+    fn finish_order(&self, order_ref: &str, unfilled: Option<String>) -> Option<RolloverExecution> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.order_refs.remove(order_ref)?;
+        let execution = inner.active.get_mut(&id)?;
+        if execution.message.is_none() && !execution.status.is_finished() {
+            execution.message = unfilled;
+        }
+        let snapshot = execution.clone();
+        Some(inner.settle(&id).unwrap_or(snapshot))
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.inner.lock().unwrap().pending.is_empty()
+    }
+
+    /// 取出待报出的开仓腿
+    pub fn take_pending(&self) -> Vec<PendingRolloverOrder> {
+        std::mem::take(&mut self.inner.lock().unwrap().pending)
+    }
+
+    pub fn get(&self, rollover_id: &str) -> Option<RolloverExecution> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .active
+            .get(rollover_id)
+            .or_else(|| inner.history.iter().rev().find(|h| h.id == rollover_id))
+            .cloned()
+    }
+
+    /// 进行中的移仓
+    pub fn active(&self) -> Vec<RolloverExecution> {
+        let mut active: Vec<_> = self.inner.lock().unwrap().active.values().cloned().collect();
+        active.sort_by_key(|e| e.started_at);
+        active
+    }
+
+    /// 已结束的移仓，最近的在前
+    pub fn history(&self, limit: usize) -> Vec<RolloverExecution> {
+        self.inner.lock().unwrap().history.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::models::{OffsetFlag, OrderDirection};
+
+    fn instrument(instrument_id: &str, expire_date: &str) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: "rb".to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: 10,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: expire_date.to_string(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    fn market(instrument_id: &str, price: f64, open_interest: f64) -> MarketData {
+        MarketData {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            last_price: price,
+            pre_settlement_price: price,
+            pre_close_price: price,
+            pre_open_interest: open_interest,
+            open_price: price,
+            highest_price: price,
+            lowest_price: price,
+            volume: 0,
+            turnover: 0.0,
+            open_interest,
+            close_price: 0.0,
+            settlement_price: 0.0,
+            upper_limit_price: price * 1.1,
+            lower_limit_price: price * 0.9,
+            bid_price: price - 1.0,
+            bid_volume: 10,
+            ask_price: price + 1.0,
+            ask_volume: 10,
+            average_price: price,
+            update_time: String::new(),
+            update_millisec: 0,
+            trading_day: String::new(),
+        }
+    }
+
+    fn position() -> Position {
+        Position {
+            instrument_id: "rb2501".to_string(),
+            direction: PositionDirection::Long,
+            total_position: 5,
+            yesterday_position: 3,
+            today_position: 2,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    fn plan() -> RolloverPlan {
+        let instruments = vec![
+            instrument("rb2501", "20250115"),
+            instrument("rb2505", "20250515"),
+            instrument("rb2510", "20251015"),
+        ];
+        let markets = HashMap::from([
+            ("rb2501".to_string(), market("rb2501", 3500.0, 10_000.0)),
+            ("rb2505".to_string(), market("rb2505", 3550.0, 900_000.0)),
+            ("rb2510".to_string(), market("rb2510", 3600.0, 200_000.0)),
+        ]);
+        let today = NaiveDate::from_ymd_opt(2025, 1, 8).unwrap();
+        let report = plan_rollovers(&[position()], &instruments, &markets, today, &RolloverConfig::default());
+        assert!(report.warnings.is_empty());
+        report.plans.into_iter().next().unwrap()
+    }
+
+    fn trade(trade_id: &str, order_ref: &str, price: f64, volume: i32) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: order_ref.to_string(),
+            instrument_id: String::new(),
+            direction: OrderDirection::Sell,
+            offset_flag: OffsetFlag::Close,
+            price,
+            volume,
+            trade_time: String::new(),
+            exchange_id: String::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_picks_dominant_and_splits_close() {
+        let plan = plan();
+        assert_eq!(plan.to_instrument, "rb2505");
+        assert_eq!(plan.days_to_expiry, 7);
+        assert_eq!(plan.volume, 5);
+        let legs: Vec<(&str, u32)> = plan.close_orders.iter().map(|o| (o.offset.as_str(), o.volume)).collect();
+        assert_eq!(legs, vec![("CloseYesterday", 3), ("CloseToday", 2)]);
+        // 卖出取买一减一跳，买入取卖一加一跳
+        assert_eq!(plan.close_orders[0].price, 3498.0);
+        assert_eq!(plan.open_order.direction, "Buy");
+        assert_eq!(plan.open_order.price, 3552.0);
+
+        let far = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        let report = plan_rollovers(&[position()], &[instrument("rb2501", "20250115")], &HashMap::new(), far, &RolloverConfig::default());
+        assert!(report.plans.is_empty() && report.warnings.is_empty());
+    }
+
+    fn order_update(order_ref: &str, status: OrderStatusType, volume_left: u32) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_ref.to_string(),
+            order_id: order_ref.to_string(),
+            instrument_id: String::new(),
+            direction: OrderDirection::Sell,
+            offset_flag: OffsetFlag::Close,
+            price: 0.0,
+            limit_price: 0.0,
+            volume: 0,
+            volume_total_original: 0,
+            volume_traded: 0,
+            volume_left,
+            volume_total: volume_left as i32,
+            status,
+            submit_time: chrono::Local::now(),
+            insert_time: String::new(),
+            update_time: chrono::Local::now(),
+            front_id: 0,
+            session_id: 0,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: false,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_execution_opens_as_closes_fill() {
+        let dir = std::env::temp_dir().join(format!("rollover_test_{}", uuid::Uuid::new_v4()));
+        let manager = RolloverManager::open(&dir, "test").unwrap();
+        let execution = manager.start(plan()).unwrap();
+        assert!(manager.start(plan()).is_err());
+        manager.attach_close_order(&execution.id, "1");
+        manager.attach_close_order(&execution.id, "2");
+
+        // 平仓每成交一笔即排队等量开仓
+        manager.on_trade(&trade("T1", "1", 3498.0, 3));
+        let pending = manager.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].order.volume, 3);
+        assert_eq!(pending[0].order.tags.get(ROLLOVER_TAG), Some(&execution.id));
+        manager.attach_open_order(&execution.id, "3");
+        manager.on_order(&order_update("1", OrderStatusType::AllTraded, 0));
+
+        manager.on_trade(&trade("T2", "2", 3500.0, 2));
+        manager.on_order(&order_update("2", OrderStatusType::AllTraded, 0));
+        assert_eq!(manager.take_pending()[0].order.volume, 2);
+        manager.attach_open_order(&execution.id, "4");
+
+        manager.on_trade(&trade("T3", "3", 3552.0, 3));
+        let current = manager.on_order(&order_update("3", OrderStatusType::AllTraded, 0)).unwrap();
+        assert_eq!(current.status, RolloverStatus::Opening);
+        assert!((current.close_avg_price - 3498.8).abs() < 1e-9);
+        assert!((current.progress() - 0.6).abs() < 1e-9);
+
+        manager.on_trade(&trade("T4", "4", 3554.0, 2));
+        let done = manager.on_order(&order_update("4", OrderStatusType::AllTraded, 0)).unwrap();
+        assert_eq!(done.status, RolloverStatus::Completed);
+        assert_eq!(done.close_trade_ids, vec!["T1", "T2"]);
+        assert_eq!(done.open_trade_ids, vec!["T3", "T4"]);
+        assert!((done.realized_spread().unwrap() - 54.0).abs() < 1e-9);
+        assert!(manager.active().is_empty());
+
+        let reopened = RolloverManager::open(&dir, "test").unwrap();
+        assert_eq!(reopened.history(10)[0].id, execution.id);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cancel_waits_for_working_orders() {
+        let manager = RolloverManager::in_memory();
+        let execution = manager.start(plan()).unwrap();
+        manager.attach_close_order(&execution.id, "1");
+        manager.on_trade(&trade("T1", "1", 3498.0, 1));
+
+        let (cancelled, working) = manager.cancel(&execution.id).unwrap();
+        assert_eq!(cancelled.status, RolloverStatus::Cancelled);
+        assert_eq!(working, vec!["1".to_string()]);
+        assert!(!manager.has_pending());
+
+        let finished = manager.on_order(&order_update("1", OrderStatusType::Canceled, 4)).unwrap();
+        assert_eq!(finished.status, RolloverStatus::Cancelled);
+        assert_eq!(finished.closed_volume, 1);
+        assert!(finished.finished_at.is_some());
+        assert_eq!(manager.history(10).len(), 1);
+    }
+}
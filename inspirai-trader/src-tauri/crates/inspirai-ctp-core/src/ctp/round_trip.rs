@@ -0,0 +1,519 @@
+use crate::ctp::{
+    CtpError, CommissionRate, OffsetFlag, OrderDirection, OrderTags, PositionDirection, TradeRecord,
+    strategy_guard::StrategyGuard,
+    utils::sanitize_file_component,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认回合交易存储目录
+pub const DEFAULT_ROUND_TRIP_DIR: &str = "./data/round_trips";
+
+/// 未设置合约乘数时使用的默认值
+pub const DEFAULT_VOLUME_MULTIPLE: f64 = 10.0;
+
+/// 保留的报单标签和成交编号上限，超过后清空
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// 开平仓配对方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairingMethod {
+    /// 先开先平
+    #[default]
+    Fifo,
+    /// 后开先平
+    Lifo,
+}
+
+/// 一次完整的开平仓回合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundTrip {
+    pub instrument_id: String,
+    pub direction: PositionDirection,
+    pub volume: i32,
+    pub open_price: f64,
+    pub close_price: f64,
+    pub open_trade_id: String,
+    pub close_trade_id: String,
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+    pub closed_at: chrono::DateTime<chrono::Utc>,
+    pub holding_secs: i64,
+    /// 平仓盈亏（未扣手续费）
+    pub pnl: f64,
+    /// 开仓和平仓手续费，未设置费率时为 0
+    #[serde(default)]
+    pub fees: f64,
+    /// 扣除手续费后的盈亏
+    #[serde(default)]
+    pub net_pnl: f64,
+    /// 最大不利偏移（金额，非负）
+    pub mae: f64,
+    /// 最大有利偏移（金额，非负）
+    pub mfe: f64,
+    /// 开仓单的策略标签
+    pub strategy: Option<String>,
+    pub tags: OrderTags,
+}
+
+/// 未平仓的开仓批次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenLot {
+    trade_id: String,
+    volume: i32,
+    price: f64,
+    opened_at: chrono::DateTime<chrono::Utc>,
+    tags: OrderTags,
+    /// 每手开仓手续费
+    fee_per_unit: f64,
+    /// 开仓以来的最高、最低价，用于计算 MAE/MFE
+    high: f64,
+    low: f64,
+}
+
+/// 持久化的开仓批次，按合约和方向分组
+#[derive(Serialize, Deserialize)]
+struct StoredLots {
+    instrument_id: String,
+    direction: PositionDirection,
+    lots: VecDeque<OpenLot>,
+}
+
+struct BookInner {
+    method: PairingMethod,
+    lots: HashMap<(String, PositionDirection), VecDeque<OpenLot>>,
+    round_trips: Vec<RoundTrip>,
+    order_tags: HashMap<String, OrderTags>,
+    /// 已记录的成交编号，查询结果重复推送时忽略
+    seen_trades: HashSet<String>,
+    volume_multiples: HashMap<String, f64>,
+    commission_rates: HashMap<String, CommissionRate>,
+    /// 持久化文件，为空时仅保存在内存
+    path: Option<PathBuf>,
+    /// 未平仓批次快照文件
+    lots_path: Option<PathBuf>,
+}
+
+impl BookInner {
+    fn volume_multiple(&self, instrument_id: &str) -> f64 {
+        self.volume_multiples.get(instrument_id).copied().unwrap_or(DEFAULT_VOLUME_MULTIPLE)
+    }
+
+    /// 按费率计算单手手续费
+    fn fee_per_unit(&self, trade: &TradeRecord) -> f64 {
+        let Some(rate) = self.commission_rates.get(&trade.instrument_id) else {
+            return 0.0;
+        };
+        let (by_money, by_volume) = match trade.offset_flag {
+            OffsetFlag::Open => (rate.open_ratio_by_money, rate.open_ratio_by_volume),
+            OffsetFlag::CloseToday => (rate.close_today_ratio_by_money, rate.close_today_ratio_by_volume),
+            OffsetFlag::Close | OffsetFlag::CloseYesterday => (rate.close_ratio_by_money, rate.close_ratio_by_volume),
+        };
+        trade.price * self.volume_multiple(&trade.instrument_id) * by_money + by_volume
+    }
+
+    /// 成交改变批次后重写快照；最高、最低价只随快照保存，不逐笔落盘
+    fn save_lots(&self) {
+        let Some(path) = &self.lots_path else {
+            return;
+        };
+        let stored: Vec<StoredLots> = self
+            .lots
+            .iter()
+            .filter(|(_, lots)| !lots.is_empty())
+            .map(|((instrument_id, direction), lots)| StoredLots {
+                instrument_id: instrument_id.clone(),
+                direction: *direction,
+                lots: lots.clone(),
+            })
+            .collect();
+        if let Err(e) = write_lots(path, &stored) {
+            tracing::warn!("写入未平仓批次失败: {}", e);
+            crate::health::record_storage_error("round_trip", &e);
+        }
+    }
+}
+
+/// 回合交易簿
+///
+/// 将开仓成交登记为批次，平仓成交按先进先出（或后进先出）配对为回合交易，
+/// 计算平仓盈亏、手续费、持仓时长和 MAE/MFE。已完成的回合以 JSON Lines
+/// 追加写入本地文件，重启后加载，供绩效统计和交易复盘查询；未平仓批次另存快照，
+/// 重启后的平仓成交仍能与之前的开仓配对
+#[derive(Clone)]
+pub struct RoundTripBook {
+    inner: Arc<Mutex<BookInner>>,
+}
+
+impl RoundTripBook {
+    /// 仅内存的回合交易簿
+    pub fn in_memory(method: PairingMethod) -> Self {
+        Self::build(method, None, None, Vec::new(), HashMap::new())
+    }
+
+    /// 打开账户回合交易簿，加载 `<dir>/<account_id>.jsonl` 中已有的回合和
+    /// `<dir>/<account_id>.lots.json` 中的未平仓批次
+    pub fn open(dir: impl AsRef<Path>, account_id: &str, method: PairingMethod) -> Result<Self, CtpError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.jsonl", sanitize_file_component(account_id)));
+        let lots_path = dir.join(format!("{}.lots.json", sanitize_file_component(account_id)));
+
+        let mut round_trips = Vec::new();
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                match serde_json::from_str::<RoundTrip>(&line) {
+                    Ok(trip) => round_trips.push(trip),
+                    Err(e) => tracing::warn!("跳过无法解析的回合交易记录: {}", e),
+                }
+            }
+            tracing::info!("加载账户 {} 的回合交易 {} 条", account_id, round_trips.len());
+        }
+
+        let mut lots = HashMap::new();
+        if lots_path.exists() {
+            let content = std::fs::read(&lots_path)?;
+            match serde_json::from_slice::<Vec<StoredLots>>(&content) {
+                Ok(stored) => {
+                    for entry in stored {
+                        lots.insert((entry.instrument_id, entry.direction), entry.lots);
+                    }
+                    tracing::info!("加载账户 {} 的未平仓批次 {} 组", account_id, lots.len());
+                }
+                Err(e) => tracing::warn!("跳过无法解析的未平仓批次: {}", e),
+            }
+        }
+
+        Ok(Self::build(method, Some(path), Some(lots_path), round_trips, lots))
+    }
+
+    fn build(
+        method: PairingMethod,
+        path: Option<PathBuf>,
+        lots_path: Option<PathBuf>,
+        round_trips: Vec<RoundTrip>,
+        lots: HashMap<(String, PositionDirection), VecDeque<OpenLot>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BookInner {
+                method,
+                lots,
+                round_trips,
+                order_tags: HashMap::new(),
+                seen_trades: HashSet::new(),
+                volume_multiples: HashMap::new(),
+                commission_rates: HashMap::new(),
+                path,
+                lots_path,
+            })),
+        }
+    }
+
+    pub fn method(&self) -> PairingMethod {
+        self.inner.lock().unwrap().method
+    }
+
+    /// 切换配对方式，只影响之后的平仓成交
+    pub fn set_method(&self, method: PairingMethod) {
+        self.inner.lock().unwrap().method = method;
+        tracing::info!("回合交易配对方式切换为 {:?}", method);
+    }
+
+    /// 设置合约乘数
+    pub fn set_volume_multiple(&self, instrument_id: &str, volume_multiple: f64) {
+        if volume_multiple > 0.0 {
+            self.inner.lock().unwrap().volume_multiples.insert(instrument_id.to_string(), volume_multiple);
+        }
+    }
+
+    pub fn volume_multiple(&self, instrument_id: &str) -> f64 {
+        self.inner.lock().unwrap().volume_multiple(instrument_id)
+    }
+
+    /// 按已设置的费率计算一笔成交的手续费，未设置费率时为 0
+    pub fn trade_fee(&self, trade: &TradeRecord) -> f64 {
+        self.inner.lock().unwrap().fee_per_unit(trade) * trade.volume as f64
+    }
+
+    /// 设置手续费率，之后的成交按该费率计算手续费
+    pub fn set_commission_rate(&self, rate: &CommissionRate) {
+        self.inner.lock().unwrap().commission_rates.insert(rate.instrument_id.clone(), rate.clone());
+    }
+
+    /// 登记报单标签，成交回报不带标签时按报单引用补齐
+    pub fn register_order_tags(&self, order_ref: &str, tags: &OrderTags) {
+        if tags.is_empty() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.order_tags.len() >= MAX_TRACKED_KEYS {
+            inner.order_tags.clear();
+        }
+        inner.order_tags.insert(order_ref.to_string(), tags.clone());
+    }
+
+    /// 记录成交：开仓成交登记为批次，平仓成交配对为回合交易
+    ///
+    /// 本地没有对应开仓批次的平仓量（如启动前的持仓）不生成回合
+    pub fn record_fill(&self, trade: &TradeRecord, at: chrono::DateTime<chrono::Utc>) -> Vec<RoundTrip> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        if inner.seen_trades.len() >= MAX_TRACKED_KEYS {
+            inner.seen_trades.clear();
+        }
+        if !inner.seen_trades.insert(trade.trade_id.clone()) {
+            return Vec::new();
+        }
+        let tags = if trade.tags.is_empty() {
+            inner.order_tags.get(&trade.order_id).cloned().unwrap_or_default()
+        } else {
+            trade.tags.clone()
+        };
+        let fee_per_unit = inner.fee_per_unit(trade);
+
+        if trade.offset_flag == OffsetFlag::Open {
+            let direction = match trade.direction {
+                OrderDirection::Buy => PositionDirection::Long,
+                OrderDirection::Sell => PositionDirection::Short,
+            };
+            inner
+                .lots
+                .entry((trade.instrument_id.clone(), direction))
+                .or_default()
+                .push_back(OpenLot {
+                    trade_id: trade.trade_id.clone(),
+                    volume: trade.volume,
+                    price: trade.price,
+                    opened_at: at,
+                    tags,
+                    fee_per_unit,
+                    high: trade.price,
+                    low: trade.price,
+                });
+            inner.save_lots();
+            return Vec::new();
+        }
+
+        // 平仓方向相反
+        let direction = match trade.direction {
+            OrderDirection::Buy => PositionDirection::Short,
+            OrderDirection::Sell => PositionDirection::Long,
+        };
+        let multiple = inner.volume_multiple(&trade.instrument_id);
+        let method = inner.method;
+        let Some(lots) = inner.lots.get_mut(&(trade.instrument_id.clone(), direction)) else {
+            tracing::debug!("平仓成交 {} 无本地开仓记录，跳过配对", trade.trade_id);
+            return Vec::new();
+        };
+
+        let mut remaining = trade.volume;
+        let mut trips = Vec::new();
+        while remaining > 0 && !lots.is_empty() {
+            let index = match method {
+                PairingMethod::Fifo => 0,
+                PairingMethod::Lifo => lots.len() - 1,
+            };
+            let lot = &mut lots[index];
+            let volume = remaining.min(lot.volume);
+            trips.push(close_lot(lot, direction, volume, trade, at, multiple, fee_per_unit));
+            lot.volume -= volume;
+            remaining -= volume;
+            if lot.volume == 0 {
+                lots.remove(index);
+            }
+        }
+        if remaining > 0 {
+            tracing::debug!("平仓成交 {} 有 {} 手无本地开仓记录", trade.trade_id, remaining);
+        }
+        if !trips.is_empty() {
+            inner.save_lots();
+        }
+
+        if let Some(path) = &inner.path {
+            if let Err(e) = append_lines(path, &trips) {
+                tracing::warn!("写入回合交易失败: {}", e);
+                crate::health::record_storage_error("round_trip", &e);
+            }
+        }
+        inner.round_trips.extend(trips.iter().cloned());
+        trips
+    }
+
+    /// 更新未平仓批次的最高、最低价
+    pub fn update_price(&self, instrument_id: &str, price: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        for direction in [PositionDirection::Long, PositionDirection::Short] {
+            if let Some(lots) = inner.lots.get_mut(&(instrument_id.to_string(), direction)) {
+                for lot in lots.iter_mut() {
+                    lot.high = lot.high.max(price);
+                    lot.low = lot.low.min(price);
+                }
+            }
+        }
+    }
+
+    /// 清空未平仓批次，已完成的回合保留
+    pub fn clear_open_lots(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.lots.clear();
+        inner.save_lots();
+    }
+
+    /// 已完成的回合交易，按平仓时间顺序
+    pub fn round_trips(&self) -> Vec<RoundTrip> {
+        self.inner.lock().unwrap().round_trips.clone()
+    }
+}
+
+impl Default for RoundTripBook {
+    fn default() -> Self {
+        Self::in_memory(PairingMethod::default())
+    }
+}
+
+/// 从开仓批次平掉 `volume` 手，生成回合交易
+fn close_lot(
+    lot: &OpenLot,
+    direction: PositionDirection,
+    volume: i32,
+    trade: &TradeRecord,
+    at: chrono::DateTime<chrono::Utc>,
+    multiple: f64,
+    close_fee_per_unit: f64,
+) -> RoundTrip {
+    let high = lot.high.max(trade.price);
+    let low = lot.low.min(trade.price);
+    let (per_unit_pnl, favorable, adverse) = match direction {
+        PositionDirection::Long => (trade.price - lot.price, high - lot.price, lot.price - low),
+        PositionDirection::Short => (lot.price - trade.price, lot.price - low, high - lot.price),
+    };
+    let amount = volume as f64 * multiple;
+    let pnl = per_unit_pnl * amount;
+    let fees = (lot.fee_per_unit + close_fee_per_unit) * volume as f64;
+
+    RoundTrip {
+        instrument_id: trade.instrument_id.clone(),
+        direction,
+        volume,
+        open_price: lot.price,
+        close_price: trade.price,
+        open_trade_id: lot.trade_id.clone(),
+        close_trade_id: trade.trade_id.clone(),
+        opened_at: lot.opened_at,
+        closed_at: at,
+        holding_secs: (at - lot.opened_at).num_seconds().max(0),
+        pnl,
+        fees,
+        net_pnl: pnl - fees,
+        mae: adverse.max(0.0) * amount,
+        mfe: favorable.max(0.0) * amount,
+        strategy: StrategyGuard::strategy_of(&lot.tags).map(str::to_string),
+        tags: lot.tags.clone(),
+    }
+}
+
+fn append_lines(path: &Path, trips: &[RoundTrip]) -> Result<(), CtpError> {
+    if trips.is_empty() {
+        return Ok(());
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for trip in trips {
+        let line = serde_json::to_string(trip).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn write_lots(path: &Path, stored: &[StoredLots]) -> Result<(), CtpError> {
+    let content = serde_json::to_vec(stored).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(trade_id: &str, direction: OrderDirection, offset_flag: OffsetFlag, price: f64, volume: i32) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: format!("ref-{}", trade_id),
+            instrument_id: "rb2501".to_string(),
+            direction,
+            offset_flag,
+            price,
+            volume,
+            trade_time: "10:00:00".to_string(),
+            exchange_id: String::new(),
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_lifo_pairing_with_fees() {
+        let book = RoundTripBook::in_memory(PairingMethod::Lifo);
+        book.set_volume_multiple("rb2501", 10.0);
+        book.set_commission_rate(&CommissionRate {
+            instrument_id: "rb2501".to_string(),
+            open_ratio_by_money: 0.0,
+            open_ratio_by_volume: 2.0,
+            close_ratio_by_money: 0.0,
+            close_ratio_by_volume: 2.0,
+            close_today_ratio_by_money: 0.0,
+            close_today_ratio_by_volume: 5.0,
+        });
+        let t0 = chrono::Utc::now();
+
+        book.record_fill(&fill("1", OrderDirection::Buy, OffsetFlag::Open, 3500.0, 1), t0);
+        book.record_fill(&fill("2", OrderDirection::Buy, OffsetFlag::Open, 3510.0, 1), t0);
+        let trips = book.record_fill(&fill("3", OrderDirection::Sell, OffsetFlag::CloseToday, 3520.0, 1), t0);
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].open_trade_id, "2");
+        assert_eq!(trips[0].pnl, 100.0);
+        assert_eq!(trips[0].fees, 7.0);
+        assert_eq!(trips[0].net_pnl, 93.0);
+
+        // 切换为先进先出后平掉剩余批次
+        book.set_method(PairingMethod::Fifo);
+        let trips = book.record_fill(&fill("4", OrderDirection::Sell, OffsetFlag::Close, 3490.0, 1), t0);
+        assert_eq!(trips[0].open_trade_id, "1");
+        assert_eq!(trips[0].net_pnl, -100.0 - 4.0);
+        assert_eq!(book.round_trips().len(), 2);
+    }
+
+    #[test]
+    fn test_round_trips_persist_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let t0 = chrono::Utc::now();
+        {
+            let book = RoundTripBook::open(dir.path(), "acct/1", PairingMethod::Fifo).unwrap();
+            book.record_fill(&fill("1", OrderDirection::Sell, OffsetFlag::Open, 3500.0, 3), t0);
+            book.record_fill(&fill("2", OrderDirection::Buy, OffsetFlag::Close, 3480.0, 2), t0);
+            // 重复推送的成交不再配对
+            book.record_fill(&fill("2", OrderDirection::Buy, OffsetFlag::Close, 3480.0, 2), t0);
+        }
+
+        let book = RoundTripBook::open(dir.path(), "acct/1", PairingMethod::Fifo).unwrap();
+        let trips = book.round_trips();
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].direction, PositionDirection::Short);
+        assert_eq!(trips[0].pnl, 20.0 * 2.0 * DEFAULT_VOLUME_MULTIPLE);
+
+        // 重启前剩余的 1 手仍可与重启后的平仓成交配对
+        let trips = book.record_fill(&fill("3", OrderDirection::Buy, OffsetFlag::Close, 3490.0, 1), t0);
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].open_trade_id, "1");
+
+        book.record_fill(&fill("4", OrderDirection::Buy, OffsetFlag::Open, 3500.0, 1), t0);
+        book.clear_open_lots();
+        let book = RoundTripBook::open(dir.path(), "acct/1", PairingMethod::Fifo).unwrap();
+        assert!(book.record_fill(&fill("5", OrderDirection::Sell, OffsetFlag::Close, 3510.0, 1), t0).is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::ctp::{
+    config::CtpConfig,
+    event_bus::{BusSubscriber, BusTopic, EventBus},
+    models::MarketDataTick,
+    sim_matching::{FillModel, MatchingSimulator},
+    spi::MdSpiImpl,
+    ClientState, CtpEvent, MarketDataManager, OffsetFlag, OrderDirection, OrderManager, OrderStatus,
+    OrderStatusType, TradeRecord,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+/// 自检使用的虚拟合约，不会与真实合约冲突
+pub const SELF_TEST_INSTRUMENT: &str = "SELFTEST";
+
+/// 每个阶段等待事件的超时
+const STAGE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 自检阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStage {
+    /// 合成行情经行情管理器缓存并发出事件
+    MarketData,
+    /// 模拟报单、成交、完成的订单生命周期
+    OrderLifecycle,
+    /// 事件经总线按主题送达订阅者，无丢弃
+    EventBus,
+    /// 日志系统写入
+    Logging,
+    /// 数据目录可写可读
+    Storage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageOutcome {
+    Passed,
+    Failed,
+    /// 组件未启用，不影响就绪判断
+    Skipped,
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageResult {
+    pub stage: SelfTestStage,
+    pub outcome: StageOutcome,
+    pub duration_ms: u64,
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub stages: Vec<StageResult>,
+    /// 没有失败阶段即可连接真实前置
+    pub ready: bool,
+}
+
+impl SelfTestReport {
+    /// 一行摘要，用于日志和通知
+    pub fn summary(&self) -> String {
+        let failed: Vec<String> = self
+            .stages
+            .iter()
+            .filter(|s| s.outcome == StageOutcome::Failed)
+            .map(|s| format!("{:?}: {}", s.stage, s.detail))
+            .collect();
+        if failed.is_empty() {
+            format!("自检通过（{} 个阶段）", self.stages.len())
+        } else {
+            format!("自检未通过: {}", failed.join("; "))
+        }
+    }
+}
+
+/// 自检参数
+#[derive(Debug, Clone)]
+pub struct SelfTestOptions {
+    /// 行情管理器使用的配置，不会发起连接
+    pub config: CtpConfig,
+    /// 存储探测目录
+    pub data_dir: PathBuf,
+    /// 合成 tick 数
+    pub ticks: usize,
+}
+
+impl SelfTestOptions {
+    pub fn new(config: CtpConfig) -> Self {
+        Self {
+            config,
+            data_dir: PathBuf::from("./data"),
+            ticks: 20,
+        }
+    }
+
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+}
+
+/// 启动自检
+///
+/// 在连接真实前置之前，用合成行情和模拟撮合走一遍行情管理器、订单管理、事件总线、
+/// 日志和存储，逐阶段核对结果并给出能否连接的结论。全部组件均为独立实例，不影响正在运行的状态
+pub async fn run_self_test(options: SelfTestOptions) -> SelfTestReport {
+    let started_at = chrono::Utc::now();
+    tracing::info!("开始启动自检");
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let bus = EventBus::new();
+    let ticks = bus.subscribe("self_test_ticks", &[BusTopic::Ticks], options.ticks.max(1));
+    let orders = bus.subscribe("self_test_orders", &[BusTopic::Orders], 16);
+    let pump = bus.spawn_pump(receiver);
+
+    let mut stages = Vec::new();
+    stages.push(timed(SelfTestStage::MarketData, market_data_stage(&options, sender.clone(), &ticks)).await);
+    stages.push(timed(SelfTestStage::OrderLifecycle, order_stage(sender.clone(), &orders)).await);
+
+    // 关闭事件通道，总线排空后检查各订阅者
+    drop(sender);
+    stages.push(timed(SelfTestStage::EventBus, bus_stage(&bus, pump)).await);
+    stages.push(timed(SelfTestStage::Logging, logging_stage()).await);
+    stages.push(timed(SelfTestStage::Storage, storage_stage(options.data_dir.clone())).await);
+
+    let ready = stages.iter().all(|s| s.outcome != StageOutcome::Failed);
+    let report = SelfTestReport { started_at, stages, ready };
+    if ready {
+        tracing::info!("{}", report.summary());
+    } else {
+        tracing::error!("{}", report.summary());
+    }
+    report
+}
+
+async fn timed(
+    stage: SelfTestStage,
+    future: impl std::future::Future<Output = Result<(StageOutcome, String), String>>,
+) -> StageResult {
+    let start = Instant::now();
+    let (outcome, detail) = future.await.unwrap_or_else(|e| (StageOutcome::Failed, e));
+    StageResult {
+        stage,
+        outcome,
+        duration_ms: start.elapsed().as_millis() as u64,
+        detail,
+    }
+}
+
+async fn market_data_stage(
+    options: &SelfTestOptions,
+    sender: mpsc::UnboundedSender<CtpEvent>,
+    subscriber: &BusSubscriber,
+) -> Result<(StageOutcome, String), String> {
+    let md_spi = Arc::new(Mutex::new(MdSpiImpl::new(
+        Arc::new(Mutex::new(ClientState::Disconnected)),
+        sender.clone(),
+        options.config.clone(),
+    )));
+    let manager = MarketDataManager::new(md_spi, sender);
+
+    let count = options.ticks.max(1);
+    for i in 0..count {
+        manager.handle_market_data(synthetic_tick(3500.0 + i as f64, i));
+    }
+
+    let cached = manager
+        .get_cached_market_data(SELF_TEST_INSTRUMENT)
+        .ok_or("行情管理器未缓存合成行情")?;
+    let expected_last = 3500.0 + (count - 1) as f64;
+    if cached.last_price != expected_last {
+        return Err(format!("缓存最新价 {} 与预期 {} 不符", cached.last_price, expected_last));
+    }
+
+    let mut received = 0;
+    while received < count {
+        match timeout(STAGE_TIMEOUT, subscriber.recv()).await {
+            Ok(Some(CtpEvent::MarketData(_))) => received += 1,
+            Ok(Some(_)) => {}
+            Ok(None) | Err(_) => return Err(format!("仅收到 {}/{} 笔行情事件", received, count)),
+        }
+    }
+    Ok((StageOutcome::Passed, format!("{} 笔合成行情已缓存并送达", count)))
+}
+
+async fn order_stage(
+    sender: mpsc::UnboundedSender<CtpEvent>,
+    subscriber: &BusSubscriber,
+) -> Result<(StageOutcome, String), String> {
+    let order_manager = OrderManager::new();
+    let mut simulator = MatchingSimulator::new(FillModel::Touch);
+    let tick = synthetic_tick(3500.0, 0);
+    simulator.on_tick(&tick);
+
+    let mut order = synthetic_order(tick.ask_price1);
+    order_manager.add_order(order.clone()).map_err(|e| format!("登记订单失败: {}", e))?;
+    let _ = sender.send(CtpEvent::OrderUpdate(order.clone()));
+
+    let fills = simulator
+        .submit(&order.order_id, SELF_TEST_INSTRUMENT, OrderDirection::Buy, order.limit_price, order.volume as i32)
+        .map_err(|e| format!("模拟撮合失败: {}", e))?;
+    let filled: i32 = fills.iter().map(|f| f.volume).sum();
+    if filled != order.volume as i32 {
+        return Err(format!("模拟成交 {} 手，预期 {} 手", filled, order.volume));
+    }
+
+    for (i, fill) in fills.iter().enumerate() {
+        let trade = TradeRecord {
+            trade_id: format!("SELFTEST-T{}", i + 1),
+            order_id: order.order_id.clone(),
+            instrument_id: fill.instrument_id.clone(),
+            direction: fill.direction,
+            offset_flag: OffsetFlag::Open,
+            price: fill.price,
+            volume: fill.volume,
+            trade_time: tick.update_time.clone(),
+            exchange_id: String::new(),
+            tags: Default::default(),
+        };
+        order_manager.add_trade(trade.clone()).map_err(|e| format!("登记成交失败: {}", e))?;
+        let _ = sender.send(CtpEvent::TradeUpdate(trade));
+    }
+
+    order.status = OrderStatusType::AllTraded;
+    order.volume_traded = order.volume;
+    order.volume_left = 0;
+    order.volume_total = 0;
+    order_manager.update_order(order.clone()).map_err(|e| format!("更新订单失败: {}", e))?;
+    let _ = sender.send(CtpEvent::OrderUpdate(order.clone()));
+
+    if !order_manager.get_active_orders().is_empty() {
+        return Err("订单成交后仍在活动列表中".to_string());
+    }
+    if order_manager.get_order_trades(&order.order_id).len() != fills.len() {
+        return Err("成交未关联到订单".to_string());
+    }
+
+    // 报单、成交、完成三类事件均应送达订单主题订阅者
+    let expected = 2 + fills.len();
+    let mut received = 0;
+    while received < expected {
+        match timeout(STAGE_TIMEOUT, subscriber.recv()).await {
+            Ok(Some(_)) => received += 1,
+            Ok(None) | Err(_) => return Err(format!("仅收到 {}/{} 个订单事件", received, expected)),
+        }
+    }
+    Ok((StageOutcome::Passed, format!("报单 {} 手全部成交，{} 个订单事件已送达", order.volume, expected)))
+}
+
+async fn bus_stage(bus: &EventBus, pump: tokio::task::JoinHandle<()>) -> Result<(StageOutcome, String), String> {
+    timeout(STAGE_TIMEOUT, pump)
+        .await
+        .map_err(|_| "事件总线未能在通道关闭后停止".to_string())?
+        .map_err(|e| format!("事件总线任务异常: {}", e))?;
+
+    let lag = bus.lag();
+    if let Some(slow) = lag.iter().find(|l| l.dropped > 0) {
+        return Err(format!("订阅者 {} 丢弃了 {} 个事件", slow.name, slow.dropped));
+    }
+    let delivered: u64 = lag.iter().map(|l| l.delivered).sum();
+    Ok((StageOutcome::Passed, format!("{} 个订阅者共收到 {} 个事件，无丢弃", lag.len(), delivered)))
+}
+
+async fn logging_stage() -> Result<(StageOutcome, String), String> {
+    let Ok(system) = crate::logging::LoggingSystem::instance() else {
+        return Ok((StageOutcome::Skipped, "日志系统未初始化".to_string()));
+    };
+    let before = system.get_metrics().lock().await.logs_written_total;
+    tracing::info!(target: "self_test", "启动自检日志探针");
+
+    let deadline = Instant::now() + STAGE_TIMEOUT;
+    loop {
+        let written = system.get_metrics().lock().await.logs_written_total;
+        if written > before {
+            return Ok((StageOutcome::Passed, "日志已写入".to_string()));
+        }
+        if Instant::now() >= deadline {
+            return Err("日志探针未写入，检查日志级别和输出目录".to_string());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+async fn storage_stage(data_dir: PathBuf) -> Result<(StageOutcome, String), String> {
+    tokio::task::spawn_blocking(move || {
+        let probe = data_dir.join(".self_test_probe");
+        let tmp = probe.with_extension("tmp");
+        let content = chrono::Utc::now().to_rfc3339();
+        let result = (|| -> std::io::Result<String> {
+            std::fs::create_dir_all(&data_dir)?;
+            std::fs::write(&tmp, &content)?;
+            std::fs::rename(&tmp, &probe)?;
+            let read = std::fs::read_to_string(&probe)?;
+            std::fs::remove_file(&probe)?;
+            Ok(read)
+        })();
+        match result {
+            Ok(read) if read == content => Ok((StageOutcome::Passed, format!("数据目录 {} 可读写", data_dir.display()))),
+            Ok(_) => Err(format!("数据目录 {} 读回内容不一致", data_dir.display())),
+            Err(e) => Err(format!("数据目录 {} 不可写: {}", data_dir.display(), e)),
+        }
+    })
+    .await
+    .map_err(|e| format!("存储探测任务异常: {}", e))?
+}
+
+fn synthetic_tick(price: f64, seq: usize) -> MarketDataTick {
+    MarketDataTick {
+        instrument_id: SELF_TEST_INSTRUMENT.to_string(),
+        last_price: price,
+        volume: seq as i64 + 1,
+        turnover: price * (seq as f64 + 1.0),
+        open_interest: 1000,
+        bid_price1: price - 1.0,
+        bid_volume1: 10,
+        ask_price1: price + 1.0,
+        ask_volume1: 10,
+        update_time: "09:30:00".to_string(),
+        update_millisec: (seq % 2 * 500) as i32,
+        change_percent: 0.0,
+        change_amount: 0.0,
+        open_price: 3500.0,
+        highest_price: price,
+        lowest_price: 3500.0,
+        pre_close_price: 3500.0,
+        price_limit: None,
+        trace: None,
+        source: Some("self_test".to_string()),
+    }
+}
+
+fn synthetic_order(price: f64) -> OrderStatus {
+    OrderStatus {
+        order_ref: "SELFTEST-1".to_string(),
+        order_id: "SELFTEST-1".to_string(),
+        instrument_id: SELF_TEST_INSTRUMENT.to_string(),
+        direction: OrderDirection::Buy,
+        offset_flag: OffsetFlag::Open,
+        price,
+        limit_price: price,
+        volume: 2,
+        volume_total_original: 2,
+        volume_traded: 0,
+        volume_left: 2,
+        volume_total: 2,
+        status: OrderStatusType::NoTradeQueueing,
+        submit_time: chrono::Local::now(),
+        insert_time: "09:30:00".to_string(),
+        update_time: chrono::Local::now(),
+        front_id: 0,
+        session_id: 0,
+        order_sys_id: String::new(),
+        status_msg: "自检".to_string(),
+        is_local: true,
+        frozen_margin: 0.0,
+        frozen_commission: 0.0,
+        tags: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::config::Environment;
+    use tempfile::TempDir;
+
+    fn options(dir: &TempDir) -> SelfTestOptions {
+        let config = CtpConfig::for_environment(Environment::SimNow, "self_test".to_string(), String::new());
+        SelfTestOptions::new(config).with_data_dir(dir.path().join("data"))
+    }
+
+    #[tokio::test]
+    async fn test_self_test_passes_on_healthy_pipeline() {
+        let dir = TempDir::new().unwrap();
+        let report = run_self_test(options(&dir)).await;
+
+        assert!(report.ready, "{}", report.summary());
+        let stages: Vec<SelfTestStage> = report.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(stages[0], SelfTestStage::MarketData);
+        assert_eq!(stages.len(), 5);
+        // 测试进程中日志系统未初始化，该阶段跳过
+        let logging = report.stages.iter().find(|s| s.stage == SelfTestStage::Logging).unwrap();
+        assert_eq!(logging.outcome, StageOutcome::Skipped);
+        assert!(!dir.path().join("data/.self_test_probe").exists());
+    }
+
+    #[tokio::test]
+    async fn test_unwritable_storage_is_no_go() {
+        let dir = TempDir::new().unwrap();
+        // 数据目录路径被普通文件占用
+        let blocker = dir.path().join("data");
+        std::fs::write(&blocker, "x").unwrap();
+
+        let report = run_self_test(options(&dir)).await;
+        assert!(!report.ready);
+        let storage = report.stages.iter().find(|s| s.stage == SelfTestStage::Storage).unwrap();
+        assert_eq!(storage.outcome, StageOutcome::Failed);
+        assert!(report.summary().contains("Storage"));
+    }
+}
@@ -140,6 +140,8 @@ impl OrderManager {
             stop_price: 0.0,
             force_close_reason: crate::ctp::models::OrderForceCloseReason::NotForceClose,
             is_auto_suspend: false,
+            time_in_force: None,
+            tags: Default::default(),
         };
 
         // 创建初始订单状态
@@ -167,6 +169,7 @@ impl OrderManager {
             is_local: false,
             frozen_margin: 0.0,
             frozen_commission: 0.0,
+            tags: request.tags.clone(),
         };
 
         // 添加到活动订单
@@ -87,6 +87,7 @@ impl QueryService {
         // 模拟账户信息
         let account = AccountInfo {
             account_id: "test_account".to_string(),
+            currency_id: "CNY".to_string(),
             available: 100000.0,
             balance: 150000.0,
             margin: 50000.0,
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// 单侧（行情/交易）会话状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "detail")]
+pub enum SideStatus {
+    /// 未启动
+    Idle,
+    /// 连接/登录中
+    Connecting,
+    /// 可用
+    Ready,
+    /// 失败
+    Failed(String),
+    /// 正在重试
+    Retrying { attempt: u32 },
+}
+
+impl SideStatus {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, SideStatus::Ready)
+    }
+}
+
+/// 运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperatingMode {
+    /// 行情和交易均可用
+    Full,
+    /// 仅行情可用（交易端失败后降级）
+    Degraded,
+    /// 均不可用
+    Offline,
+}
+
+/// 行情/交易两侧的会话健康状态
+///
+/// 交易端登录失败时不影响行情端，客户端据此进入降级模式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHealth {
+    pub md: SideStatus,
+    pub td: SideStatus,
+    /// 进入降级模式的时间
+    pub degraded_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// 交易端重试次数
+    pub trader_retry_count: u32,
+    /// 交易端最近一次错误
+    pub last_trader_error: Option<String>,
+}
+
+/// 共享的会话健康状态
+pub type SharedSessionHealth = Arc<Mutex<SessionHealth>>;
+
+impl SessionHealth {
+    pub fn new() -> Self {
+        Self {
+            md: SideStatus::Idle,
+            td: SideStatus::Idle,
+            degraded_since: None,
+            trader_retry_count: 0,
+            last_trader_error: None,
+        }
+    }
+
+    pub fn shared() -> SharedSessionHealth {
+        Arc::new(Mutex::new(Self::new()))
+    }
+
+    /// 当前运行模式
+    pub fn mode(&self) -> OperatingMode {
+        match (self.md.is_ready(), self.td.is_ready()) {
+            (true, true) => OperatingMode::Full,
+            (true, false) => OperatingMode::Degraded,
+            // 交易端单独可用时仍视为 Full，由连接模式决定是否需要行情
+            (false, true) => OperatingMode::Full,
+            (false, false) => OperatingMode::Offline,
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.mode() == OperatingMode::Degraded
+    }
+
+    /// 标记行情端状态
+    pub fn set_md(&mut self, status: SideStatus) {
+        self.md = status;
+        self.refresh_degraded_since();
+    }
+
+    /// 标记交易端失败
+    pub fn mark_trader_failed(&mut self, reason: &str) {
+        self.td = SideStatus::Failed(reason.to_string());
+        self.last_trader_error = Some(reason.to_string());
+        self.refresh_degraded_since();
+    }
+
+    /// 标记交易端重试
+    pub fn mark_trader_retrying(&mut self) -> u32 {
+        self.trader_retry_count += 1;
+        self.td = SideStatus::Retrying { attempt: self.trader_retry_count };
+        self.trader_retry_count
+    }
+
+    /// 标记交易端可用
+    pub fn mark_trader_ready(&mut self) {
+        self.td = SideStatus::Ready;
+        self.trader_retry_count = 0;
+        self.refresh_degraded_since();
+    }
+
+    /// 重置为初始状态
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn refresh_degraded_since(&mut self) {
+        if self.is_degraded() {
+            if self.degraded_since.is_none() {
+                self.degraded_since = Some(chrono::Utc::now());
+            }
+        } else {
+            self.degraded_since = None;
+        }
+    }
+}
+
+impl Default for SessionHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degraded_when_trader_fails() {
+        let mut health = SessionHealth::new();
+        assert_eq!(health.mode(), OperatingMode::Offline);
+
+        health.set_md(SideStatus::Ready);
+        health.mark_trader_failed("账户已锁定");
+        assert_eq!(health.mode(), OperatingMode::Degraded);
+        assert!(health.degraded_since.is_some());
+        assert_eq!(health.last_trader_error.as_deref(), Some("账户已锁定"));
+
+        assert_eq!(health.mark_trader_retrying(), 1);
+        assert!(health.is_degraded());
+
+        health.mark_trader_ready();
+        assert_eq!(health.mode(), OperatingMode::Full);
+        assert!(health.degraded_since.is_none());
+        assert_eq!(health.trader_retry_count, 0);
+    }
+}
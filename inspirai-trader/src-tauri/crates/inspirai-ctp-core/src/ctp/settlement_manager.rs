@@ -164,42 +164,7 @@ impl SettlementManager {
 
     /// 解析结算单内容
     fn parse_settlement_content(&self, content: &str) -> Result<SettlementSummary, CtpError> {
-        let mut summary = SettlementSummary::default();
-        
-        // 简单的解析逻辑，实际需要根据结算单格式调整
-        for line in content.lines() {
-            if line.contains("期初权益") {
-                summary.prev_balance = self.extract_number(line);
-            } else if line.contains("期末权益") {
-                summary.balance = self.extract_number(line);
-            } else if line.contains("平仓盈亏") {
-                summary.close_profit = self.extract_number(line);
-            } else if line.contains("持仓盈亏") {
-                summary.position_profit = self.extract_number(line);
-            } else if line.contains("手续费") {
-                summary.commission = self.extract_number(line);
-            } else if line.contains("入金") {
-                summary.deposit = self.extract_number(line);
-            } else if line.contains("出金") {
-                summary.withdraw = self.extract_number(line);
-            } else if line.contains("风险度") {
-                summary.risk_ratio = self.extract_number(line);
-            }
-        }
-        
-        // 计算当日盈亏
-        summary.daily_profit = summary.close_profit + summary.position_profit - summary.commission;
-        
-        Ok(summary)
-    }
-
-    /// 从文本行提取数字
-    fn extract_number(&self, line: &str) -> f64 {
-        // 简单的数字提取逻辑
-        line.split_whitespace()
-            .filter_map(|s| s.parse::<f64>().ok())
-            .next()
-            .unwrap_or(0.0)
+        Ok(parse_settlement_summary(content))
     }
 
     /// 生成结算报告
@@ -245,6 +210,58 @@ impl SettlementManager {
     }
 }
 
+/// 解码柜台返回的结算单原始字节
+///
+/// 结算单按固定长度分片返回，多字节字符可能跨片，需拼接后整体解码；
+/// 无法解码的字节替换为 U+FFFD，不丢弃整片
+pub fn decode_settlement_bytes(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let (decoded, had_errors) = encoding_rs::GB18030.decode_without_bom_handling(&bytes[..len]);
+    if had_errors {
+        warn!("结算单包含无法解码的 GB18030 字节，已替换");
+    }
+    decoded.into_owned()
+}
+
+/// 从结算单文本提取摘要，任意输入都不会出错
+pub fn parse_settlement_summary(content: &str) -> SettlementSummary {
+    let mut summary = SettlementSummary::default();
+    
+    // 简单的解析逻辑，实际需要根据结算单格式调整
+    for line in content.lines() {
+        if line.contains("期初权益") {
+            summary.prev_balance = extract_number(line);
+        } else if line.contains("期末权益") {
+            summary.balance = extract_number(line);
+        } else if line.contains("平仓盈亏") {
+            summary.close_profit = extract_number(line);
+        } else if line.contains("持仓盈亏") {
+            summary.position_profit = extract_number(line);
+        } else if line.contains("手续费") {
+            summary.commission = extract_number(line);
+        } else if line.contains("入金") {
+            summary.deposit = extract_number(line);
+        } else if line.contains("出金") {
+            summary.withdraw = extract_number(line);
+        } else if line.contains("风险度") {
+            summary.risk_ratio = extract_number(line);
+        }
+    }
+    
+    // 计算当日盈亏
+    summary.daily_profit = summary.close_profit + summary.position_profit - summary.commission;
+    
+    summary
+}
+
+/// 从文本行提取数字，忽略 inf/NaN 等非有限值
+fn extract_number(line: &str) -> f64 {
+    line.split_whitespace()
+        .filter_map(|s| s.parse::<f64>().ok())
+        .find(|v| v.is_finite())
+        .unwrap_or(0.0)
+}
+
 /// 结算报告
 #[derive(Debug, Clone, Default)]
 pub struct SettlementReport {
@@ -274,4 +291,24 @@ pub struct SettlementReport {
     pub max_daily_profit: f64,
     /// 最大日亏损
     pub max_daily_loss: f64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_and_parse_split_content() {
+        let (encoded, _, _) = encoding_rs::GB18030.encode("期初权益 100000.00\n平仓盈亏 1500.5\n手续费 20.5\n风险度 inf\n");
+        // 按固定长度分片时多字节字符被拆开，拼接后整体解码
+        let (first, second) = encoded.split_at(3);
+        let content = decode_settlement_bytes(&[first, second].concat());
+        let summary = parse_settlement_summary(&content);
+        assert_eq!(summary.prev_balance, 100000.0);
+        assert_eq!(summary.daily_profit, 1480.0);
+        assert_eq!(summary.risk_ratio, 0.0);
+
+        // 非法字节替换后继续解析
+        assert_eq!(decode_settlement_bytes(&[b'a', 0xFF, b'b']), "a\u{FFFD}b");
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ctp::{
+    models::{InstrumentInfo, Position, PositionDirection},
+    CtpError,
+};
+
+/// 默认结算价文件
+pub const DEFAULT_SETTLEMENT_PRICE_FILE: &str = "./data/settlement_prices.json";
+
+/// 结算价来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementSource {
+    /// 深度行情中的结算价字段，收盘后才有
+    MarketData,
+    /// 结算单持仓汇总，与期货公司一致，优先于行情
+    Statement,
+}
+
+/// 合约某交易日的昨结算、今结算价
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementPrice {
+    pub instrument_id: String,
+    pub trading_day: NaiveDate,
+    pub pre_settlement_price: Option<f64>,
+    pub settlement_price: Option<f64>,
+    pub source: SettlementSource,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    prices: HashMap<String, SettlementPrice>,
+    path: Option<PathBuf>,
+}
+
+impl StoreInner {
+    /// 合并一条价格，返回是否有变化
+    fn merge(
+        &mut self,
+        instrument_id: &str,
+        trading_day: NaiveDate,
+        pre_settlement_price: Option<f64>,
+        settlement_price: Option<f64>,
+        source: SettlementSource,
+    ) -> bool {
+        // 行情逐笔调用，已有记录时不分配键
+        if !self.prices.contains_key(instrument_id) {
+            self.prices.insert(
+                instrument_id.to_string(),
+                SettlementPrice {
+                    instrument_id: instrument_id.to_string(),
+                    trading_day,
+                    pre_settlement_price: None,
+                    settlement_price: None,
+                    source,
+                    updated_at: Utc::now(),
+                },
+            );
+        }
+        let Some(entry) = self.prices.get_mut(instrument_id) else {
+            return false;
+        };
+        if trading_day < entry.trading_day {
+            return false;
+        }
+        if trading_day > entry.trading_day {
+            // 新交易日的昨结算即上一交易日的今结算
+            *entry = SettlementPrice {
+                instrument_id: instrument_id.to_string(),
+                trading_day,
+                pre_settlement_price: entry.settlement_price,
+                settlement_price: None,
+                source,
+                updated_at: Utc::now(),
+            };
+        }
+        // 行情不覆盖结算单给出的价格
+        if entry.source == SettlementSource::Statement && source == SettlementSource::MarketData {
+            let filled = entry.settlement_price.is_some() && entry.pre_settlement_price.is_some();
+            if filled {
+                return false;
+            }
+        }
+        let before = (entry.pre_settlement_price, entry.settlement_price);
+        if let Some(price) = pre_settlement_price {
+            entry.pre_settlement_price = Some(price);
+        }
+        if let Some(price) = settlement_price {
+            entry.settlement_price = Some(price);
+        }
+        if (entry.pre_settlement_price, entry.settlement_price) == before {
+            return false;
+        }
+        if source == SettlementSource::Statement {
+            entry.source = source;
+        }
+        entry.updated_at = Utc::now();
+        true
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut prices: Vec<&SettlementPrice> = self.prices.values().collect();
+        prices.sort_by(|a, b| a.instrument_id.cmp(&b.instrument_id));
+        if let Err(e) = write_json(path, &prices) {
+            tracing::warn!("保存结算价失败: {}", e);
+            crate::health::record_storage_error("settlement_prices", &e);
+        }
+    }
+}
+
+/// 结算价存储
+///
+/// 从深度行情和结算单采集各合约的昨结算、今结算价，日终盈亏和保证金按结算价
+/// 盯市，与期货公司结算单一致；每个合约保留最近一个交易日，写入本地 JSON
+#[derive(Clone, Default)]
+pub struct SettlementPriceStore {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl SettlementPriceStore {
+    /// 仅内存的结算价
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// 打开结算价文件，恢复已采集的价格
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref().to_path_buf();
+        let prices: Vec<SettlementPrice> = if path.exists() {
+            let content = std::fs::read(&path)?;
+            serde_json::from_slice(&content)
+                .map_err(|e| CtpError::ConversionError(format!("解析结算价文件失败: {}", e)))?
+        } else {
+            Vec::new()
+        };
+        let inner = StoreInner {
+            prices: prices.into_iter().map(|p| (p.instrument_id.clone(), p)).collect(),
+            path: Some(path),
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// 记录行情中的结算价，无效值（0 或 DBL_MAX）忽略
+    pub fn record_market(
+        &self,
+        instrument_id: &str,
+        trading_day: NaiveDate,
+        pre_settlement_price: f64,
+        settlement_price: f64,
+    ) -> bool {
+        let valid = |price: f64| (price > 0.0 && price < f64::MAX).then_some(price);
+        let (pre_settlement_price, settlement_price) = (valid(pre_settlement_price), valid(settlement_price));
+        if pre_settlement_price.is_none() && settlement_price.is_none() {
+            return false;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let changed = inner.merge(
+            instrument_id,
+            trading_day,
+            pre_settlement_price,
+            settlement_price,
+            SettlementSource::MarketData,
+        );
+        if changed {
+            inner.persist();
+        }
+        changed
+    }
+
+    /// 从结算单持仓汇总采集结算价，返回采集的合约数
+    pub fn ingest_statement(&self, trading_day: NaiveDate, content: &str) -> usize {
+        let rows = parse_statement_prices(content);
+        if rows.is_empty() {
+            return 0;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        for (instrument_id, pre_settlement_price, settlement_price) in &rows {
+            inner.merge(
+                instrument_id,
+                trading_day,
+                *pre_settlement_price,
+                *settlement_price,
+                SettlementSource::Statement,
+            );
+        }
+        inner.persist();
+        tracing::info!("从结算单采集 {} 个合约的结算价 ({})", rows.len(), trading_day);
+        rows.len()
+    }
+
+    pub fn get(&self, instrument_id: &str) -> Option<SettlementPrice> {
+        self.inner.lock().unwrap().prices.get(instrument_id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<SettlementPrice> {
+        let mut prices: Vec<SettlementPrice> = self.inner.lock().unwrap().prices.values().cloned().collect();
+        prices.sort_by(|a, b| a.instrument_id.cmp(&b.instrument_id));
+        prices
+    }
+
+    /// 指定交易日已有今结算价的合约
+    pub fn settlement_prices(&self, trading_day: NaiveDate) -> HashMap<String, f64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .prices
+            .values()
+            .filter(|p| p.trading_day == trading_day)
+            .filter_map(|p| p.settlement_price.map(|price| (p.instrument_id.clone(), price)))
+            .collect()
+    }
+}
+
+/// 按结算价盯市的持仓
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyMark {
+    pub instrument_id: String,
+    pub direction: PositionDirection,
+    pub volume: i32,
+    pub settlement_price: f64,
+    /// 盯市盈亏：结算价相对持仓成本（昨仓为昨结算、今仓为开仓价）
+    pub mtm_pnl: f64,
+    /// 按结算价和保证金率计算的保证金，缺少保证金率时为 None
+    pub margin: Option<f64>,
+}
+
+/// 按结算价对持仓盯市
+pub fn mark_position(position: &Position, spec: Option<&InstrumentInfo>, settlement_price: f64) -> DailyMark {
+    let multiplier = spec.map_or(1.0, |s| s.volume_multiple.max(1) as f64);
+    let value = settlement_price * multiplier * position.total_position as f64;
+    let (mtm_pnl, ratio) = match position.direction {
+        PositionDirection::Long => (value - position.position_cost, spec.map(|s| s.long_margin_ratio)),
+        PositionDirection::Short => (position.position_cost - value, spec.map(|s| s.short_margin_ratio)),
+    };
+    DailyMark {
+        instrument_id: position.instrument_id.clone(),
+        direction: position.direction,
+        volume: position.total_position,
+        settlement_price,
+        mtm_pnl,
+        margin: ratio.filter(|r| *r > 0.0).map(|r| value * r),
+    }
+}
+
+/// 解析结算单持仓汇总中的 (合约, 昨结算, 今结算)
+///
+/// 按表头中的“合约”“昨结算”“今结算”定位列，表格以 `|` 分隔
+fn parse_statement_prices(content: &str) -> Vec<(String, Option<f64>, Option<f64>)> {
+    let mut rows = Vec::new();
+    let mut columns: Option<(usize, usize, usize)> = None;
+    for line in content.lines() {
+        let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+        if cells.len() < 3 {
+            // 分隔线不结束表格，空行结束
+            if line.trim().is_empty() {
+                columns = None;
+            }
+            continue;
+        }
+        let position = |name: &str| cells.iter().position(|c| *c == name);
+        if let (Some(instrument), Some(pre), Some(settle)) = (position("合约"), position("昨结算"), position("今结算")) {
+            columns = Some((instrument, pre, settle));
+            continue;
+        }
+        let Some((instrument, pre, settle)) = columns else {
+            continue;
+        };
+        let Some(instrument_id) = cells.get(instrument).filter(|c| !c.is_empty() && c.is_ascii()) else {
+            continue;
+        };
+        let price = |index: usize| cells.get(index).and_then(|c| c.parse::<f64>().ok()).filter(|p| *p > 0.0);
+        let (pre_settlement_price, settlement_price) = (price(pre), price(settle));
+        // 英文表头行和合计行没有价格
+        if pre_settlement_price.is_some() || settlement_price.is_some() {
+            rows.push((instrument_id.to_string(), pre_settlement_price, settlement_price));
+        }
+    }
+    rows
+}
+
+fn write_json(path: &Path, prices: &[&SettlementPrice]) -> Result<(), CtpError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_vec_pretty(prices)
+        .map_err(|e| CtpError::ConversionError(format!("序列化结算价失败: {}", e)))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const STATEMENT: &str = "
+                                                    持仓汇总 Positions
+|       品种       |      合约      |    买持     |    买均价   |     卖持     |    卖均价    |  昨结算  |  今结算  |持仓盯市盈亏|
+|      Product     |   Instrument   |  Long Pos.  |Avg Buy Price|  Short Pos.  |Avg Sell Price|Prev. Sttl|Sttl Today| MTM P/L  |
+-------------------------------------------------------------------------------------------------------------------------------
+|螺纹钢            |rb2501          |            2|     3500.000|             0|         0.000|  3490.000|  3520.000|    400.00|
+|铜                |cu2502          |            0|        0.000|             1|     75000.000| 75200.000| 74800.000|   1000.00|
+-------------------------------------------------------------------------------------------------------------------------------
+|共   2条          |                |            2|             |             1|              |          |          |   1400.00|
+";
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_statement_overrides_market_and_rolls_days() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("settlement_prices.json");
+        let store = SettlementPriceStore::open(&path).unwrap();
+
+        assert!(store.record_market("rb2501", day(6), 3490.0, f64::MAX));
+        assert_eq!(store.ingest_statement(day(6), STATEMENT), 2);
+        // 结算单价格已齐全，行情不再覆盖
+        assert!(!store.record_market("rb2501", day(6), 3490.0, 3519.0));
+
+        let reopened = SettlementPriceStore::open(&path).unwrap();
+        let rb = reopened.get("rb2501").unwrap();
+        assert_eq!((rb.pre_settlement_price, rb.settlement_price), (Some(3490.0), Some(3520.0)));
+        assert_eq!(rb.source, SettlementSource::Statement);
+        assert_eq!(reopened.settlement_prices(day(6)).get("cu2502"), Some(&74800.0));
+
+        // 次日的昨结算沿用上一日今结算
+        reopened.record_market("rb2501", day(7), 0.0, 0.0);
+        reopened.record_market("cu2502", day(7), f64::MAX, 74900.0);
+        let cu = reopened.get("cu2502").unwrap();
+        assert_eq!((cu.trading_day, cu.pre_settlement_price), (day(7), Some(74800.0)));
+        assert!(!reopened.settlement_prices(day(7)).contains_key("rb2501"));
+    }
+
+    #[test]
+    fn test_mark_position_to_settlement() {
+        let spec: InstrumentInfo = serde_json::from_value(serde_json::json!({
+            "instrument_id": "rb2501", "exchange_id": "SHFE", "instrument_name": "", "product_id": "rb",
+            "product_class": "1", "delivery_year": 2025, "delivery_month": 1,
+            "max_market_order_volume": 0, "min_market_order_volume": 0,
+            "max_limit_order_volume": 0, "min_limit_order_volume": 0,
+            "volume_multiple": 10, "price_tick": 1.0, "create_date": "", "open_date": "",
+            "expire_date": "", "start_delivery_date": "", "end_delivery_date": "", "is_trading": true,
+            "underlying_instrument": "", "strike_price": 0.0, "underlying_multiple": 0.0,
+            "long_margin_ratio": 0.1, "short_margin_ratio": 0.12
+        }))
+        .unwrap();
+        let long = Position {
+            instrument_id: "rb2501".to_string(),
+            direction: PositionDirection::Long,
+            total_position: 2,
+            yesterday_position: 1,
+            today_position: 1,
+            open_cost: 70000.0,
+            // 昨仓按昨结算 3490、今仓按开仓价 3510
+            position_cost: 70000.0,
+            margin: 7000.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        };
+        let mark = mark_position(&long, Some(&spec), 3520.0);
+        assert!((mark.mtm_pnl - 400.0).abs() < 1e-9);
+        assert!((mark.margin.unwrap() - 7040.0).abs() < 1e-9);
+
+        let short = Position {
+            direction: PositionDirection::Short,
+            ..long
+        };
+        let mark = mark_position(&short, Some(&spec), 3520.0);
+        assert!((mark.mtm_pnl + 400.0).abs() < 1e-9);
+        assert!((mark.margin.unwrap() - 8448.0).abs() < 1e-9);
+        // 缺少合约信息时不估算保证金
+        assert_eq!(mark_position(&short, None, 3520.0).margin, None);
+    }
+}
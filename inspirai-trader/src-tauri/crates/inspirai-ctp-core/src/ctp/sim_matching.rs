@@ -0,0 +1,581 @@
+use crate::ctp::{CtpError, OrderDirection, models::MarketDataTick};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 流控拒单的返回码，与 ReqOrderInsert “每秒发送请求数超过许可数”一致
+pub const SIM_FLOW_CONTROL_ERROR: i32 = -3;
+
+const DAY_MS: i64 = 24 * 3600 * 1000;
+
+/// 成交判定模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillModel {
+    /// 价格触及即全部成交
+    Touch,
+    /// 模拟排队位置：按到达时盘口挂单量估算前方队列，随成交量递减
+    QueuePosition,
+}
+
+/// 成交的流动性方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Liquidity {
+    /// 挂单被动成交
+    Maker,
+    /// 主动吃单
+    Taker,
+}
+
+/// 不利条件注入，默认全部关闭
+///
+/// 延迟按行情时间计算：报单在 `ack_delay_ms` 后才进入撮合，成交在 `fill_delay_ms`
+/// 后才回报，二者都在时间到达后的第一笔行情上生效
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimLatencyConfig {
+    /// 报单确认延迟
+    pub ack_delay_ms: u64,
+    /// 成交回报延迟
+    pub fill_delay_ms: u64,
+    /// 每次成交只成交一部分的概率
+    pub partial_fill_probability: f64,
+    /// 报单被流控拒绝的概率
+    pub flow_control_reject_probability: f64,
+    /// 随机种子，便于复现
+    pub seed: Option<u64>,
+}
+
+impl Default for SimLatencyConfig {
+    fn default() -> Self {
+        Self {
+            ack_delay_ms: 0,
+            fill_delay_ms: 0,
+            partial_fill_probability: 0.0,
+            flow_control_reject_probability: 0.0,
+            seed: None,
+        }
+    }
+}
+
+/// 模拟限价单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimOrder {
+    pub order_id: String,
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    pub price: f64,
+    pub volume: i32,
+    pub filled: i32,
+    /// 前方排队量，None 表示挂单价位不在一档，暂无法估算
+    pub queue_ahead: Option<i64>,
+}
+
+impl SimOrder {
+    pub fn remaining(&self) -> i32 {
+        self.volume - self.filled
+    }
+}
+
+/// 模拟成交
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimFill {
+    pub order_id: String,
+    pub instrument_id: String,
+    pub direction: OrderDirection,
+    pub price: f64,
+    pub volume: i32,
+    pub liquidity: Liquidity,
+}
+
+/// 回测用撮合模拟器
+///
+/// 行情仅有一档盘口，排队模型做如下近似：
+/// - 挂在一档时，前方队列取到达时该价位的挂单量
+/// - 最新价等于挂单价时，成交量增量先消耗前方队列，剩余部分成交本单
+/// - 该价位挂单量小于前方队列时视为前方撤单，队列随之缩短
+/// - 最新价穿过挂单价时全部成交
+pub struct MatchingSimulator {
+    model: FillModel,
+    /// 按订单号有序，逐笔撮合时随机数的抽取顺序固定，同一种子可复现
+    orders: BTreeMap<String, SimOrder>,
+    last_ticks: HashMap<String, MarketDataTick>,
+    latency: SimLatencyConfig,
+    rng: StdRng,
+    /// 行情时钟（毫秒），跨午夜后继续累加
+    now_ms: i64,
+    last_clock_ms: Option<i64>,
+    /// 尚未确认的报单及确认时间
+    pending: Vec<(i64, SimOrder)>,
+    /// 已确认、尚未取走的报单
+    acknowledged: Vec<String>,
+    /// 尚未回报的成交及回报时间
+    delayed_fills: Vec<(i64, SimFill)>,
+}
+
+impl MatchingSimulator {
+    pub fn new(model: FillModel) -> Self {
+        Self {
+            model,
+            orders: BTreeMap::new(),
+            last_ticks: HashMap::new(),
+            latency: SimLatencyConfig::default(),
+            rng: StdRng::from_entropy(),
+            now_ms: 0,
+            last_clock_ms: None,
+            pending: Vec::new(),
+            acknowledged: Vec::new(),
+            delayed_fills: Vec::new(),
+        }
+    }
+
+    /// 注入延迟、部分成交和流控拒单
+    pub fn with_latency(mut self, latency: SimLatencyConfig) -> Self {
+        self.rng = match latency.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.latency = latency;
+        self
+    }
+
+    pub fn model(&self) -> FillModel {
+        self.model
+    }
+
+    pub fn latency(&self) -> &SimLatencyConfig {
+        &self.latency
+    }
+
+    /// 取出自上次调用以来已确认的报单，按确认顺序
+    pub fn take_acknowledged(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.acknowledged)
+    }
+
+    /// 提交限价单，可立即成交的部分按对手价成交
+    pub fn submit(
+        &mut self,
+        order_id: &str,
+        instrument_id: &str,
+        direction: OrderDirection,
+        price: f64,
+        volume: i32,
+    ) -> Result<Vec<SimFill>, CtpError> {
+        if volume <= 0 {
+            return Err(CtpError::InvalidParameter("报单数量必须大于0".to_string()));
+        }
+        if self.orders.contains_key(order_id) || self.pending.iter().any(|(_, o)| o.order_id == order_id) {
+            return Err(CtpError::InvalidParameter(format!("重复的订单号: {}", order_id)));
+        }
+        if roll(&mut self.rng, self.latency.flow_control_reject_probability) {
+            tracing::debug!("模拟流控拒单: {}", order_id);
+            return Err(CtpError::CtpApiError {
+                code: SIM_FLOW_CONTROL_ERROR,
+                message: "每秒发送请求数超过许可数".to_string(),
+            });
+        }
+
+        let order = SimOrder {
+            order_id: order_id.to_string(),
+            instrument_id: instrument_id.to_string(),
+            direction,
+            price,
+            volume,
+            filled: 0,
+            queue_ahead: None,
+        };
+
+        if self.latency.ack_delay_ms > 0 {
+            self.pending.push((self.now_ms + self.latency.ack_delay_ms as i64, order));
+            return Ok(Vec::new());
+        }
+        let fills = self.activate(order);
+        Ok(self.deliver(fills))
+    }
+
+    /// 报单确认：可立即成交的部分按对手价成交，其余挂单
+    fn activate(&mut self, mut order: SimOrder) -> Vec<SimFill> {
+        self.acknowledged.push(order.order_id.clone());
+        let mut fills = Vec::new();
+        if let Some(tick) = self.last_ticks.get(&order.instrument_id) {
+            // 可与对手一档成交的部分作为吃单成交
+            if let Some((opposite_price, opposite_volume)) = opposite_quote(tick, order.direction) {
+                if crosses(order.direction, order.price, opposite_price) {
+                    let volume = partial(&mut self.rng, &self.latency, order.remaining().min(opposite_volume.max(0)));
+                    if volume > 0 {
+                        order.filled += volume;
+                        fills.push(fill(&order, opposite_price, volume, Liquidity::Taker));
+                    }
+                }
+            }
+            order.queue_ahead = initial_queue(tick, order.direction, order.price);
+        }
+
+        if order.remaining() > 0 {
+            self.orders.insert(order.order_id.clone(), order);
+        }
+        fills
+    }
+
+    /// 按成交延迟暂存新成交，返回已到回报时间的成交
+    fn deliver(&mut self, fills: Vec<SimFill>) -> Vec<SimFill> {
+        if self.latency.fill_delay_ms == 0 && self.delayed_fills.is_empty() {
+            return fills;
+        }
+        let release_at = self.now_ms + self.latency.fill_delay_ms as i64;
+        self.delayed_fills.extend(fills.into_iter().map(|f| (release_at, f)));
+        let now = self.now_ms;
+        let (due, waiting): (Vec<_>, Vec<_>) = self.delayed_fills.drain(..).partition(|(at, _)| *at <= now);
+        self.delayed_fills = waiting;
+        due.into_iter().map(|(_, f)| f).collect()
+    }
+
+    /// 按行情时间推进时钟，跨午夜时累加一天
+    fn advance_clock(&mut self, tick: &MarketDataTick) {
+        let Ok(time) = chrono::NaiveTime::parse_from_str(&tick.update_time, "%H:%M:%S") else {
+            return;
+        };
+        let clock = chrono::Timelike::num_seconds_from_midnight(&time) as i64 * 1000 + tick.update_millisec as i64;
+        let elapsed = match self.last_clock_ms {
+            Some(last) if clock < last - DAY_MS / 2 => clock + DAY_MS - last,
+            Some(last) => (clock - last).max(0),
+            None => 0,
+        };
+        self.last_clock_ms = Some(clock);
+        self.now_ms += elapsed;
+    }
+
+    /// 撤单，返回未成交部分
+    pub fn cancel(&mut self, order_id: &str) -> Option<SimOrder> {
+        if let Some(index) = self.pending.iter().position(|(_, o)| o.order_id == order_id) {
+            return Some(self.pending.remove(index).1);
+        }
+        self.orders.remove(order_id)
+    }
+
+    /// 当前挂单
+    pub fn resting_orders(&self) -> Vec<&SimOrder> {
+        self.orders.values().collect()
+    }
+
+    /// 推进一笔行情，返回产生的成交
+    pub fn on_tick(&mut self, tick: &MarketDataTick) -> Vec<SimFill> {
+        self.advance_clock(tick);
+        let traded = self
+            .last_ticks
+            .get(&tick.instrument_id)
+            .map_or(0, |prev| (tick.volume - prev.volume).max(0));
+
+        let mut fills = Vec::new();
+        let mut done = Vec::new();
+        for order in self.orders.values_mut() {
+            if order.instrument_id != tick.instrument_id {
+                continue;
+            }
+
+            let volume = match self.model {
+                FillModel::Touch => touch_fill(order, tick),
+                FillModel::QueuePosition => queue_fill(order, tick, traded),
+            };
+            let volume = partial(&mut self.rng, &self.latency, volume);
+            if volume > 0 {
+                order.filled += volume;
+                fills.push(fill(order, order.price, volume, Liquidity::Maker));
+            }
+            if order.remaining() == 0 {
+                done.push(order.order_id.clone());
+            }
+        }
+
+        for order_id in done {
+            self.orders.remove(&order_id);
+        }
+        self.last_ticks.insert(tick.instrument_id.clone(), tick.clone());
+
+        // 确认时间已到的报单按当前盘口进入撮合
+        let now = self.now_ms;
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending).into_iter().partition(|(at, _)| *at <= now);
+        self.pending = waiting;
+        for (_, order) in due {
+            fills.extend(self.activate(order));
+        }
+        self.deliver(fills)
+    }
+}
+
+fn roll(rng: &mut StdRng, probability: f64) -> bool {
+    probability > 0.0 && rng.gen_bool(probability.min(1.0))
+}
+
+/// 按概率把成交量截为部分成交
+fn partial(rng: &mut StdRng, latency: &SimLatencyConfig, volume: i32) -> i32 {
+    if volume > 1 && roll(rng, latency.partial_fill_probability) {
+        rng.gen_range(1..volume)
+    } else {
+        volume
+    }
+}
+
+fn fill(order: &SimOrder, price: f64, volume: i32, liquidity: Liquidity) -> SimFill {
+    SimFill {
+        order_id: order.order_id.clone(),
+        instrument_id: order.instrument_id.clone(),
+        direction: order.direction,
+        price,
+        volume,
+        liquidity,
+    }
+}
+
+/// 对手一档价格和数量
+fn opposite_quote(tick: &MarketDataTick, direction: OrderDirection) -> Option<(f64, i32)> {
+    let (price, volume) = match direction {
+        OrderDirection::Buy => (tick.ask_price1, tick.ask_volume1),
+        OrderDirection::Sell => (tick.bid_price1, tick.bid_volume1),
+    };
+    (price > 0.0 && price < f64::MAX).then_some((price, volume))
+}
+
+/// 同方向一档价格和数量
+fn same_side_quote(tick: &MarketDataTick, direction: OrderDirection) -> (f64, i32) {
+    match direction {
+        OrderDirection::Buy => (tick.bid_price1, tick.bid_volume1),
+        OrderDirection::Sell => (tick.ask_price1, tick.ask_volume1),
+    }
+}
+
+/// 报价是否优于（或等于）参考价
+fn crosses(direction: OrderDirection, price: f64, reference: f64) -> bool {
+    match direction {
+        OrderDirection::Buy => price >= reference,
+        OrderDirection::Sell => price <= reference,
+    }
+}
+
+/// 报价是否严格优于参考价
+fn improves(direction: OrderDirection, price: f64, reference: f64) -> bool {
+    match direction {
+        OrderDirection::Buy => price > reference,
+        OrderDirection::Sell => price < reference,
+    }
+}
+
+/// 到达时的前方排队量
+fn initial_queue(tick: &MarketDataTick, direction: OrderDirection, price: f64) -> Option<i64> {
+    let (best_price, best_volume) = same_side_quote(tick, direction);
+    if best_price <= 0.0 || best_price >= f64::MAX || improves(direction, price, best_price) {
+        // 本方无挂单或报价优于一档，排在队首
+        Some(0)
+    } else if price == best_price {
+        Some(best_volume as i64)
+    } else {
+        None
+    }
+}
+
+fn touch_fill(order: &SimOrder, tick: &MarketDataTick) -> i32 {
+    if crosses(order.direction, order.price, tick.last_price) {
+        order.remaining()
+    } else {
+        0
+    }
+}
+
+fn queue_fill(order: &mut SimOrder, tick: &MarketDataTick, traded: i64) -> i32 {
+    // 最新价穿过挂单价，队列必然已被消耗
+    if improves(order.direction, order.price, tick.last_price) {
+        return order.remaining();
+    }
+
+    let (best_price, best_volume) = same_side_quote(tick, order.direction);
+    let Some(mut ahead) = order.queue_ahead else {
+        if best_price == order.price {
+            // 价位成为一档，按当前挂单量估算，本笔行情不计成交
+            order.queue_ahead = Some(best_volume as i64);
+        } else if improves(order.direction, order.price, best_price) {
+            // 前方价位已清空，本单即为一档
+            order.queue_ahead = Some(0);
+        }
+        return 0;
+    };
+
+    // 成交量先消耗前方队列
+    let mut filled = 0;
+    if tick.last_price == order.price && traded > 0 {
+        let consumed = traded.min(ahead);
+        ahead -= consumed;
+        filled = (traded - consumed).min(order.remaining() as i64) as i32;
+    }
+
+    // 挂单量仍少于前方队列，视为前方撤单
+    if best_price == order.price {
+        ahead = ahead.min(best_volume as i64);
+    }
+    order.queue_ahead = Some(ahead);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(last_price: f64, volume: i64, bid: (f64, i32), ask: (f64, i32)) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price,
+            volume,
+            turnover: 0.0,
+            open_interest: 0,
+            bid_price1: bid.0,
+            bid_volume1: bid.1,
+            ask_price1: ask.0,
+            ask_volume1: ask.1,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: last_price,
+            highest_price: last_price,
+            lowest_price: last_price,
+            pre_close_price: last_price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    fn run(model: FillModel) -> i32 {
+        let mut sim = MatchingSimulator::new(model);
+        sim.on_tick(&tick(3501.0, 100, (3500.0, 50), (3501.0, 20)));
+        assert!(sim.submit("1", "rb2501", OrderDirection::Buy, 3500.0, 5).unwrap().is_empty());
+
+        // 在挂单价成交 30 手，前方队列 50 手尚未耗尽
+        sim.on_tick(&tick(3500.0, 130, (3500.0, 20), (3501.0, 20)))
+            .iter()
+            .map(|f| f.volume)
+            .sum()
+    }
+
+    #[test]
+    fn test_touch_fills_immediately() {
+        assert_eq!(run(FillModel::Touch), 5);
+    }
+
+    #[test]
+    fn test_queue_position_waits_for_queue() {
+        assert_eq!(run(FillModel::QueuePosition), 0);
+
+        let mut sim = MatchingSimulator::new(FillModel::QueuePosition);
+        sim.on_tick(&tick(3501.0, 100, (3500.0, 10), (3501.0, 20)));
+        sim.submit("1", "rb2501", OrderDirection::Buy, 3500.0, 5).unwrap();
+
+        // 成交 12 手：前方 10 手后本单成交 2 手
+        let fills = sim.on_tick(&tick(3500.0, 112, (3500.0, 3), (3501.0, 20)));
+        assert_eq!(fills[0].volume, 2);
+        assert_eq!(fills[0].liquidity, Liquidity::Maker);
+
+        // 价格下穿，剩余全部成交
+        let fills = sim.on_tick(&tick(3499.0, 115, (3499.0, 10), (3500.0, 5)));
+        assert_eq!(fills[0].volume, 3);
+        assert!(sim.resting_orders().is_empty());
+    }
+
+    #[test]
+    fn test_marketable_order_takes_liquidity() {
+        let mut sim = MatchingSimulator::new(FillModel::QueuePosition);
+        sim.on_tick(&tick(3501.0, 100, (3500.0, 10), (3501.0, 3)));
+
+        let fills = sim.submit("1", "rb2501", OrderDirection::Buy, 3502.0, 5).unwrap();
+        assert_eq!(fills[0].volume, 3);
+        assert_eq!(fills[0].price, 3501.0);
+        assert_eq!(fills[0].liquidity, Liquidity::Taker);
+        // 剩余 2 手以 3502 挂单，优于买一，排在队首
+        assert_eq!(sim.resting_orders()[0].queue_ahead, Some(0));
+    }
+
+    fn at(mut tick: MarketDataTick, time: &str, millisec: i32) -> MarketDataTick {
+        tick.update_time = time.to_string();
+        tick.update_millisec = millisec;
+        tick
+    }
+
+    #[test]
+    fn test_ack_and_fill_delay() {
+        let mut sim = MatchingSimulator::new(FillModel::Touch).with_latency(SimLatencyConfig {
+            ack_delay_ms: 500,
+            fill_delay_ms: 1000,
+            ..SimLatencyConfig::default()
+        });
+        sim.on_tick(&at(tick(3501.0, 100, (3500.0, 10), (3501.0, 3)), "09:30:00", 0));
+
+        // 确认前盘口不可见，也不计入挂单
+        assert!(sim.submit("1", "rb2501", OrderDirection::Buy, 3501.0, 5).unwrap().is_empty());
+        assert!(sim.take_acknowledged().is_empty());
+        assert!(sim.on_tick(&at(tick(3501.0, 100, (3500.0, 10), (3501.0, 3)), "09:30:00", 400)).is_empty());
+        assert!(sim.resting_orders().is_empty());
+
+        // 确认时盘口已上移，只能挂单
+        assert!(sim.on_tick(&at(tick(3502.0, 110, (3501.0, 4), (3502.0, 6)), "09:30:00", 500)).is_empty());
+        assert_eq!(sim.take_acknowledged(), vec!["1".to_string()]);
+        assert_eq!(sim.resting_orders()[0].queue_ahead, Some(4));
+
+        // 价格下穿后成交，回报延迟 1 秒
+        assert!(sim.on_tick(&at(tick(3500.0, 120, (3499.0, 5), (3500.0, 5)), "09:30:01", 0)).is_empty());
+        assert!(sim.resting_orders().is_empty());
+        let fills = sim.on_tick(&at(tick(3500.0, 120, (3499.0, 5), (3500.0, 5)), "09:30:02", 0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].volume, 5);
+    }
+
+    #[test]
+    fn test_flow_control_and_partial_fills() {
+        let mut sim = MatchingSimulator::new(FillModel::Touch).with_latency(SimLatencyConfig {
+            flow_control_reject_probability: 1.0,
+            ..SimLatencyConfig::default()
+        });
+        match sim.submit("1", "rb2501", OrderDirection::Buy, 3500.0, 5) {
+            Err(CtpError::CtpApiError { code, .. }) => assert_eq!(code, SIM_FLOW_CONTROL_ERROR),
+            other => panic!("应被流控拒绝: {:?}", other),
+        }
+
+        let mut sim = MatchingSimulator::new(FillModel::Touch).with_latency(SimLatencyConfig {
+            partial_fill_probability: 1.0,
+            seed: Some(7),
+            ..SimLatencyConfig::default()
+        });
+        sim.on_tick(&tick(3501.0, 100, (3500.0, 10), (3501.0, 20)));
+        sim.submit("1", "rb2501", OrderDirection::Buy, 3500.0, 5).unwrap();
+        let mut filled = 0;
+        for _ in 0..5 {
+            let fills = sim.on_tick(&tick(3499.0, 100, (3499.0, 10), (3500.0, 20)));
+            // 剩余 1 手以上时每次只成交一部分
+            assert!(fills.iter().all(|f| f.volume < 5));
+            filled += fills.iter().map(|f| f.volume).sum::<i32>();
+        }
+        assert_eq!(filled, 5);
+        assert!(sim.resting_orders().is_empty());
+    }
+
+    #[test]
+    fn test_seeded_runs_are_reproducible_with_several_orders() {
+        let run = || {
+            let mut sim = MatchingSimulator::new(FillModel::Touch).with_latency(SimLatencyConfig {
+                partial_fill_probability: 0.5,
+                seed: Some(42),
+                ..SimLatencyConfig::default()
+            });
+            sim.on_tick(&tick(3501.0, 100, (3500.0, 10), (3501.0, 20)));
+            sim.submit("1", "rb2501", OrderDirection::Buy, 3500.0, 8).unwrap();
+            sim.submit("2", "rb2501", OrderDirection::Buy, 3500.0, 8).unwrap();
+            let mut fills = Vec::new();
+            for _ in 0..6 {
+                fills.extend(sim.on_tick(&tick(3499.0, 100, (3499.0, 10), (3500.0, 20))));
+            }
+            fills
+        };
+        let first = run();
+        assert!(first.iter().any(|f| f.order_id == "1") && first.iter().any(|f| f.order_id == "2"));
+        for _ in 0..5 {
+            assert_eq!(run(), first);
+        }
+    }
+}
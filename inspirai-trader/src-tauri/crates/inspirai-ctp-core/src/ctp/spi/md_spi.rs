@@ -2,6 +2,19 @@ use crate::ctp::{
     CtpError, CtpEvent, ClientState,
     models::{MarketDataTick, LoginResponse},
     config::CtpConfig,
+    connection_quality::{describe_disconnect_reason, LinkQuality, SharedConnectionQuality},
+    diagnostics::{DiagnosticEvent, DiagnosticHub, DiagnosticSeverity, DiagnosticSource},
+    market_overview::MarketOverview,
+    order_flow::OrderFlowAnalyzer,
+    orderbook_heatmap::OrderBookHeatmap,
+    pipeline_trace::{TickTrace, TraceStage},
+    position_manager::PositionManager,
+    price_limit::PriceLimitTracker,
+    session_health::{SharedSessionHealth, SideStatus},
+    settlement_prices::SettlementPriceStore,
+    timeline::Timeline,
+    utils::ConversionPools,
+    wire_log::WireLogger,
 };
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
@@ -26,6 +39,32 @@ pub struct MdSpiImpl {
     subscribed_instruments: Arc<Mutex<HashMap<String, bool>>>,
     /// 请求ID计数器
     request_id_counter: Arc<Mutex<i32>>,
+    /// 会话健康状态（用于降级模式判断）
+    session_health: Option<SharedSessionHealth>,
+    /// 诊断事件通道
+    diagnostics: Option<DiagnosticHub>,
+    /// 连接质量统计
+    connection_quality: Option<SharedConnectionQuality>,
+    /// 盘口热力图数据源
+    order_book_heatmap: Option<OrderBookHeatmap>,
+    /// 市场概览统计
+    market_overview: Option<MarketOverview>,
+    /// 订单流分析
+    order_flow: Option<OrderFlowAnalyzer>,
+    /// 账户活动时间线
+    timeline: Option<Timeline>,
+    /// 持仓管理（浮动盈亏与回合交易 MAE/MFE）
+    position_manager: Option<PositionManager>,
+    /// 涨跌停状态跟踪
+    price_limits: Option<PriceLimitTracker>,
+    /// 回调线程绑定的 CPU 核
+    callback_core: Option<usize>,
+    /// 合约、交易所代码解码缓存
+    pools: Option<ConversionPools>,
+    /// 结算价采集
+    settlement_prices: Option<SettlementPriceStore>,
+    /// CTP 报文日志
+    wire_log: Option<WireLogger>,
 }
 
 // 实现 Send 和 Sync trait 以支持多线程环境
@@ -47,6 +86,130 @@ impl MdSpiImpl {
             config,
             subscribed_instruments: Arc::new(Mutex::new(HashMap::new())),
             request_id_counter: Arc::new(Mutex::new(1)),
+            session_health: None,
+            diagnostics: None,
+            connection_quality: None,
+            order_book_heatmap: None,
+            market_overview: None,
+            order_flow: None,
+            timeline: None,
+            position_manager: None,
+            price_limits: None,
+            callback_core: None,
+            pools: None,
+            settlement_prices: None,
+            wire_log: None,
+        }
+    }
+
+    /// 关联 CTP 报文日志
+    pub fn with_wire_log(mut self, wire_log: WireLogger) -> Self {
+        self.wire_log = Some(wire_log);
+        self
+    }
+
+    pub fn with_conversion_pools(mut self, pools: ConversionPools) -> Self {
+        self.pools = Some(pools);
+        self
+    }
+
+    /// 行情回调线程绑定到指定 CPU 核，在前置连接回调中生效
+    pub fn with_callback_core(mut self, core: Option<usize>) -> Self {
+        self.callback_core = core;
+        self
+    }
+
+    /// 关联会话健康状态
+    pub fn with_session_health(mut self, session_health: SharedSessionHealth) -> Self {
+        self.session_health = Some(session_health);
+        self
+    }
+
+    /// 关联诊断事件通道
+    pub fn with_diagnostics(mut self, diagnostics: DiagnosticHub) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// 关联连接质量统计
+    pub fn with_connection_quality(mut self, connection_quality: SharedConnectionQuality) -> Self {
+        self.connection_quality = Some(connection_quality);
+        self
+    }
+
+    /// 关联盘口热力图数据源
+    pub fn with_order_book_heatmap(mut self, order_book_heatmap: OrderBookHeatmap) -> Self {
+        self.order_book_heatmap = Some(order_book_heatmap);
+        self
+    }
+
+    /// 关联市场概览统计
+    pub fn with_market_overview(mut self, market_overview: MarketOverview) -> Self {
+        self.market_overview = Some(market_overview);
+        self
+    }
+
+    /// 关联订单流分析
+    pub fn with_order_flow(mut self, order_flow: OrderFlowAnalyzer) -> Self {
+        self.order_flow = Some(order_flow);
+        self
+    }
+
+    /// 关联持仓管理器
+    pub fn with_position_manager(mut self, position_manager: PositionManager) -> Self {
+        self.position_manager = Some(position_manager);
+        self
+    }
+
+    /// 关联涨跌停跟踪
+    pub fn with_price_limits(mut self, price_limits: PriceLimitTracker) -> Self {
+        self.price_limits = Some(price_limits);
+        self
+    }
+
+    /// 关联账户活动时间线
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// 关联结算价存储，行情中的昨结算、今结算价逐笔采集
+    pub fn with_settlement_prices(mut self, settlement_prices: SettlementPriceStore) -> Self {
+        self.settlement_prices = Some(settlement_prices);
+        self
+    }
+
+    /// 更新行情端连接质量
+    fn update_quality(&self, f: impl FnOnce(&mut LinkQuality)) {
+        if let Some(quality) = &self.connection_quality {
+            f(&mut quality.lock().unwrap().md);
+        }
+    }
+
+    /// 发布诊断事件
+    fn report(&self, event: DiagnosticEvent) {
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.publish(event);
+        }
+    }
+
+    /// 写入报文日志
+    fn log_wire<T: std::fmt::Debug>(
+        &self,
+        name: &str,
+        request_id: Option<i32>,
+        data: Option<&T>,
+        rsp_info: Option<&CThostFtdcRspInfoField>,
+    ) {
+        if let Some(wire_log) = &self.wire_log {
+            wire_log.callback(name, request_id, data, rsp_info.map(|info| info as &dyn std::fmt::Debug));
+        }
+    }
+
+    /// 更新行情端会话状态
+    fn update_md_status(&self, status: SideStatus) {
+        if let Some(health) = &self.session_health {
+            health.lock().unwrap().set_md(status);
         }
     }
 
@@ -105,14 +268,30 @@ impl MdSpiImpl {
 impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
     /// 当客户端与交易后台建立起通信连接时（还未登录前），该方法被调用
     fn on_front_connected(&mut self) {
+        self.log_wire::<()>("OnFrontConnected", None, None, None);
         tracing::info!("行情前置连接成功");
+        if let Some(core) = self.callback_core {
+            match crate::ctp::hot_path::pin_current_thread(core) {
+                Ok(()) => tracing::info!("行情回调线程已绑定 CPU 核 {}", core),
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
         
+        self.update_quality(|q| q.record_connected());
+        if let Some(timeline) = &self.timeline {
+            timeline.record_connection("行情", true, None);
+        }
         self.update_client_state(ClientState::Connected);
         self.send_event(CtpEvent::Connected);
         
         // 连接成功后自动发起登录请求
         if let Err(e) = self.req_user_login() {
             tracing::error!("自动登录请求失败: {}", e);
+            self.report(DiagnosticEvent::new(
+                DiagnosticSeverity::Error,
+                DiagnosticSource::Md,
+                format!("自动登录请求失败: {}", e),
+            ));
             self.send_event(CtpEvent::Error(format!("自动登录请求失败: {}", e)));
         }
     }
@@ -120,19 +299,26 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
     /// 当客户端与交易后台通信连接断开时，该方法被调用
     /// 当发生这个情况后，API会自动重新连接，客户端可不做处理
     fn on_front_disconnected(&mut self, reason: i32) {
+        self.log_wire("OnFrontDisconnected", None, Some(&reason), None);
         tracing::warn!("行情前置连接断开，原因代码: {}", reason);
         
-        let reason_msg = match reason {
-            0x1001 => "网络读失败",
-            0x1002 => "网络写失败", 
-            0x2001 => "接收心跳超时",
-            0x2002 => "发送心跳失败",
-            0x2003 => "收到错误报文",
-            _ => "未知原因",
-        };
+        let reason_msg = describe_disconnect_reason(reason);
         
         tracing::warn!("断开原因: {}", reason_msg);
+        self.update_quality(|q| q.record_disconnected(reason));
+        self.report(
+            DiagnosticEvent::new(
+                DiagnosticSeverity::Warning,
+                DiagnosticSource::Md,
+                format!("行情前置连接断开: {}", reason_msg),
+            )
+            .with_code(reason)
+        );
+        if let Some(timeline) = &self.timeline {
+            timeline.record_connection("行情", false, Some(reason_msg.to_string()));
+        }
         
+        self.update_md_status(SideStatus::Failed(reason_msg.to_string()));
         self.update_client_state(ClientState::Disconnected);
         self.send_event(CtpEvent::Disconnected);
         
@@ -143,6 +329,18 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
         }
     }
 
+    /// 心跳超时警告，time_lapse 为距上次接收报文的秒数
+    fn on_heart_beat_warning(&mut self, time_lapse: i32) {
+        self.log_wire("OnHeartBeatWarning", None, Some(&time_lapse), None);
+        tracing::warn!("行情心跳超时警告: 距上次通讯 {} 秒", time_lapse);
+        self.update_quality(|q| q.record_heartbeat_warning(time_lapse));
+        self.report(DiagnosticEvent::new(
+            DiagnosticSeverity::Warning,
+            DiagnosticSource::Md,
+            format!("行情心跳超时警告: 距上次通讯 {} 秒", time_lapse),
+        ));
+    }
+
     /// 登录请求响应
     fn on_rsp_user_login(
         &mut self,
@@ -151,14 +349,27 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
         request_id: i32,
         _is_last: bool,
     ) {
+        self.log_wire("OnRspUserLogin", Some(request_id), rsp_user_login, rsp_info);
         tracing::info!("收到登录响应，请求ID: {}, 是否最后一条: {}", request_id, _is_last);
+        self.update_quality(|q| q.record_response(request_id));
         
         if let Some(rsp_info) = rsp_info {
             if rsp_info.ErrorID != 0 {
                 let error_msg = self.convert_gb18030_to_string(&rsp_info.ErrorMsg);
                 tracing::error!("登录失败: {} (错误码: {})", error_msg, rsp_info.ErrorID);
                 
+                self.report(
+                    DiagnosticEvent::new(
+                        DiagnosticSeverity::Critical,
+                        DiagnosticSource::Md,
+                        format!("行情登录失败: {}", error_msg),
+                    )
+                    .with_code(rsp_info.ErrorID)
+                    .with_request_id(request_id)
+                );
+                
                 let error = CtpError::from_ctp_error(rsp_info.ErrorID, &error_msg);
+                self.update_md_status(SideStatus::Failed(error.to_string()));
                 self.update_client_state(ClientState::Error(error.to_string()));
                 self.send_event(CtpEvent::LoginFailed(error.to_string()));
                 return;
@@ -186,6 +397,7 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
                 max_order_ref: self.convert_gb18030_to_string(&login_field.MaxOrderRef),
             };
             
+            self.update_md_status(SideStatus::Ready);
             self.update_client_state(ClientState::LoggedIn);
             self.send_event(CtpEvent::LoginSuccess(login_response));
         }
@@ -199,7 +411,9 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
         request_id: i32,
         _is_last: bool,
     ) {
+        self.log_wire("OnRspSubMarketData", Some(request_id), specific_instrument, rsp_info);
         tracing::debug!("收到行情订阅响应，请求ID: {}, 是否最后一条: {}", request_id, _is_last);
+        self.update_quality(|q| q.record_activity());
         
         if let Some(rsp_info) = rsp_info {
             if rsp_info.ErrorID != 0 {
@@ -211,6 +425,16 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
                     tracing::error!("订阅失败的合约: {}", instrument_id);
                 }
                 
+                self.report(
+                    DiagnosticEvent::new(
+                        DiagnosticSeverity::Error,
+                        DiagnosticSource::Md,
+                        format!("行情订阅失败: {}", error_msg),
+                    )
+                    .with_code(rsp_info.ErrorID)
+                    .with_request_id(request_id)
+                );
+                
                 self.send_event(CtpEvent::Error(format!("行情订阅失败: {}", error_msg)));
                 return;
             }
@@ -232,12 +456,23 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
         request_id: i32,
         _is_last: bool,
     ) {
+        self.log_wire("OnRspUnSubMarketData", Some(request_id), specific_instrument, rsp_info);
         tracing::debug!("收到取消行情订阅响应，请求ID: {}, 是否最后一条: {}", request_id, _is_last);
+        self.update_quality(|q| q.record_activity());
         
         if let Some(rsp_info) = rsp_info {
             if rsp_info.ErrorID != 0 {
                 let error_msg = self.convert_gb18030_to_string(&rsp_info.ErrorMsg);
                 tracing::error!("取消行情订阅失败: {} (错误码: {})", error_msg, rsp_info.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(
+                        DiagnosticSeverity::Error,
+                        DiagnosticSource::Md,
+                        format!("取消行情订阅失败: {}", error_msg),
+                    )
+                    .with_code(rsp_info.ErrorID)
+                    .with_request_id(request_id)
+                );
                 self.send_event(CtpEvent::Error(format!("取消行情订阅失败: {}", error_msg)));
                 return;
             }
@@ -253,8 +488,11 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
 
     /// 深度行情通知
     fn on_rtn_depth_market_data(&mut self, depth_market_data: Option<&CThostFtdcDepthMarketDataField>) {
+        self.log_wire("OnRtnDepthMarketData", None, depth_market_data, None);
+        self.update_quality(|q| q.record_activity());
         if let Some(market_data) = depth_market_data {
-            let instrument_id = self.convert_gb18030_to_string(&market_data.InstrumentID);
+            let mut trace = TickTrace::begin();
+            let instrument_id = self.symbol(&market_data.InstrumentID);
             
             // 只处理已订阅的合约行情
             if !self.is_instrument_subscribed(&instrument_id) {
@@ -262,10 +500,51 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
                 return;
             }
             
-            let tick = self.convert_market_data_to_tick(market_data);
+            if let Some(heatmap) = &self.order_book_heatmap {
+                match crate::ctp::utils::DataConverter::convert_depth_snapshot(market_data) {
+                    Ok(snapshot) => heatmap.update(snapshot),
+                    Err(e) => tracing::warn!("盘口快照转换失败: {}", e),
+                }
+            }
+
+            let mut tick = self.convert_market_data_to_tick(market_data);
+            if let Some(trace) = trace.as_mut() {
+                trace.mark(TraceStage::Conversion);
+            }
+
+            if let Some(overview) = &self.market_overview {
+                let exchange_id = self.symbol(&market_data.ExchangeID);
+                overview.update(&exchange_id, tick.clone());
+            }
+            if let Some(order_flow) = &self.order_flow {
+                order_flow.update(&tick);
+            }
+            if let Some(position_manager) = &self.position_manager {
+                position_manager.update_last_price(&tick.instrument_id, tick.last_price);
+            }
+            if let Some(settlement_prices) = &self.settlement_prices {
+                let trading_day = chrono::NaiveDate::parse_from_str(
+                    &self.convert_gb18030_to_string(&market_data.TradingDay),
+                    "%Y%m%d",
+                )
+                .unwrap_or_else(|_| crate::ctp::bar_import::trading_day_of(chrono::Local::now().naive_local()));
+                settlement_prices.record_market(
+                    &tick.instrument_id,
+                    trading_day,
+                    market_data.PreSettlementPrice,
+                    market_data.SettlementPrice,
+                );
+            }
+            if let Some(price_limits) = &self.price_limits {
+                tick.price_limit = price_limits.update(&tick, market_data.UpperLimitPrice, market_data.LowerLimitPrice);
+            }
             
             tracing::trace!("收到行情数据: {} 最新价: {}", tick.instrument_id, tick.last_price);
             
+            if let Some(trace) = trace.as_mut() {
+                trace.mark(TraceStage::EventEmit);
+            }
+            tick.trace = trace;
             self.send_event(CtpEvent::MarketData(tick));
         }
     }
@@ -277,10 +556,20 @@ impl ctp2rs::v1alpha1::MdSpi for MdSpiImpl {
         request_id: i32,
         _is_last: bool,
     ) {
+        self.log_wire::<()>("OnRspError", Some(request_id), None, rsp_info);
         if let Some(rsp_info) = rsp_info {
             let error_msg = self.convert_gb18030_to_string(&rsp_info.ErrorMsg);
             tracing::error!("CTP 行情错误: {} (错误码: {}, 请求ID: {})", 
                 error_msg, rsp_info.ErrorID, request_id);
+            self.report(
+                DiagnosticEvent::new(
+                    DiagnosticSeverity::Error,
+                    DiagnosticSource::Md,
+                    format!("CTP 行情错误: {}", error_msg),
+                )
+                .with_code(rsp_info.ErrorID)
+                .with_request_id(request_id)
+            );
             
             let error = CtpError::from_ctp_error(rsp_info.ErrorID, &error_msg);
             self.send_event(CtpEvent::Error(error.to_string()));
@@ -310,6 +599,17 @@ impl MdSpiImpl {
         }).to_string()
     }
 
+    /// 解码行情中的合约、交易所代码，配置了缓存时命中即不再分配
+    fn symbol(&self, field: &[i8]) -> Arc<str> {
+        match &self.pools {
+            Some(pools) => pools.symbols().get(field).unwrap_or_else(|e| {
+                tracing::warn!("{}", e);
+                "".into()
+            }),
+            None => self.convert_gb18030_to_string(field).into(),
+        }
+    }
+
     /// 将 CTP 行情数据转换为业务模型
     /// 使用 ctp2rs 官方数据转换工具，严禁自定义实现
     fn convert_market_data_to_tick(&self, market_data: &CThostFtdcDepthMarketDataField) -> MarketDataTick {
@@ -339,11 +639,12 @@ use ctp2rs::ffi::gb18030_cstr_i8_to_str;
 mod tests {
     use super::*;
     use tokio::sync::mpsc;
-    use crate::ctp::Environment;
+    use crate::ctp::{ConnectionMode, Environment};
 
     fn create_test_config() -> CtpConfig {
         CtpConfig {
             environment: Environment::SimNow,
+            connection_mode: ConnectionMode::Full,
             broker_id: "9999".to_string(),
             investor_id: "test_user".to_string(),
             password: "test_pass".to_string(),
@@ -357,6 +658,8 @@ mod tests {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: Default::default(),
+            programmatic: Default::default(),
         }
     }
 
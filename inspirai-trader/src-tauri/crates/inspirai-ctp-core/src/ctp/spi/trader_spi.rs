@@ -0,0 +1,1212 @@
+use crate::ctp::{
+    CtpError, CtpEvent, ClientState,
+    config::CtpConfig,
+    models::{OrderRequest, OrderStatus, TradeRecord, Position, AccountInfo, LoginResponse},
+    utils::DataConverter,
+    session_health::{SharedSessionHealth, SideStatus},
+    connection_quality::{describe_disconnect_reason, LinkQuality, SharedConnectionQuality},
+    diagnostics::{DiagnosticEvent, DiagnosticHub, DiagnosticSeverity, DiagnosticSource},
+    rejection_breaker::RejectionBreaker,
+    timeline::Timeline,
+    position_manager::PositionManager,
+    error_explainer::ErrorExplainer,
+    correlation::{CorrelationRegistry, CORRELATION_TAG},
+    funds_monitor::FundsMonitor,
+    market_order::MarketOrderEmulator,
+    rollover::RolloverManager,
+    compliance_monitor::ComplianceMonitor,
+    client_order_id::ClientOrderIds,
+    trade_dedup::TradeDeduplicator,
+    settlement_prices::SettlementPriceStore,
+    currency::AccountBalances,
+    wire_log::WireLogger,
+    order_manager::OrderManager,
+    reconciliation::Reconciler,
+    order_preview::RateCache,
+};
+use ctp2rs::v1alpha1::{
+    CThostFtdcRspUserLoginField,
+    CThostFtdcRspInfoField,
+    CThostFtdcInputOrderField,
+    CThostFtdcOrderField,
+    CThostFtdcTradeField,
+    CThostFtdcInputOrderActionField,
+    CThostFtdcInvestorPositionField,
+    CThostFtdcTradingAccountField,
+    CThostFtdcInstrumentCommissionRateField,
+    CThostFtdcRspTransferField,
+};
+use ctp2rs::ffi::gb18030_cstr_i8_to_str;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::{info, warn, error, debug};
+
+/// 交易 SPI 实现
+/// 
+/// 负责处理 CTP 交易 API 的所有回调事件
+pub struct TraderSpiImpl {
+    /// 客户端状态的共享引用
+    client_state: Arc<Mutex<ClientState>>,
+    /// 事件发送器，用于向上层发送事件
+    event_sender: mpsc::UnboundedSender<CtpEvent>,
+    /// CTP 配置信息
+    config: CtpConfig,
+    /// 订单映射表
+    orders: Arc<Mutex<HashMap<String, OrderStatus>>>,
+    /// 持仓映射表
+    positions: Arc<Mutex<HashMap<String, Position>>>,
+    /// 请求ID计数器
+    request_id: Arc<Mutex<i32>>,
+    /// 前置编号
+    front_id: i32,
+    /// 会话编号
+    session_id: i32,
+    /// 最大报单引用
+    max_order_ref: Arc<Mutex<i32>>,
+    /// 会话健康状态（用于降级模式判断）
+    session_health: Option<SharedSessionHealth>,
+    /// 诊断事件通道
+    diagnostics: Option<DiagnosticHub>,
+    /// 连接质量统计
+    connection_quality: Option<SharedConnectionQuality>,
+    /// 账户活动时间线
+    timeline: Option<Timeline>,
+    /// 拒单熔断
+    rejection_breaker: Option<RejectionBreaker>,
+    /// 持仓管理（成交配对回合交易）
+    position_manager: Option<PositionManager>,
+    /// 拒单说明
+    error_explainer: Option<ErrorExplainer>,
+    /// 请求/报单到关联 ID 的映射
+    correlations: Option<CorrelationRegistry>,
+    /// 资金曲线异常检测
+    funds_monitor: Option<FundsMonitor>,
+    /// 市价单模拟（追单）
+    market_orders: Option<MarketOrderEmulator>,
+    /// 移仓执行，平仓腿成交后排队开仓腿
+    rollovers: Option<RolloverManager>,
+    /// 报撤单合规监控
+    compliance: Option<ComplianceMonitor>,
+    /// 客户端订单号映射
+    client_orders: Option<ClientOrderIds>,
+    /// 成交回报去重，重复回调整体丢弃
+    trade_dedup: Option<TradeDeduplicator>,
+    /// 从结算单采集结算价
+    settlement_prices: Option<SettlementPriceStore>,
+    /// 正在接收的结算单所属交易日
+    settlement_trading_day: Option<chrono::NaiveDate>,
+    /// 分币种资金
+    account_balances: Option<AccountBalances>,
+    /// CTP 报文日志
+    wire_log: Option<WireLogger>,
+    /// 重连对账及其本地报单簿
+    reconciliation: Option<(Reconciler, OrderManager)>,
+    /// 费率缓存，手续费率查询结果写入
+    rate_cache: Option<RateCache>,
+}
+
+// 实现 Send 和 Sync trait 以支持多线程环境
+unsafe impl Send for TraderSpiImpl {}
+unsafe impl Sync for TraderSpiImpl {}
+
+impl TraderSpiImpl {
+    /// 创建新的交易 SPI 实例
+    pub fn new(
+        client_state: Arc<Mutex<ClientState>>,
+        event_sender: mpsc::UnboundedSender<CtpEvent>,
+        config: CtpConfig,
+    ) -> Self {
+        info!("创建交易 SPI 实例");
+        
+        Self {
+            client_state,
+            event_sender,
+            config,
+            orders: Arc::new(Mutex::new(HashMap::new())),
+            positions: Arc::new(Mutex::new(HashMap::new())),
+            request_id: Arc::new(Mutex::new(0)),
+            front_id: 0,
+            session_id: 0,
+            max_order_ref: Arc::new(Mutex::new(0)),
+            session_health: None,
+            diagnostics: None,
+            connection_quality: None,
+            timeline: None,
+            rejection_breaker: None,
+            position_manager: None,
+            error_explainer: None,
+            correlations: None,
+            funds_monitor: None,
+            market_orders: None,
+            rollovers: None,
+            compliance: None,
+            client_orders: None,
+            trade_dedup: None,
+            settlement_prices: None,
+            settlement_trading_day: None,
+            account_balances: None,
+            wire_log: None,
+            reconciliation: None,
+            rate_cache: None,
+        }
+    }
+
+    /// 关联 CTP 报文日志
+    pub fn with_wire_log(mut self, wire_log: WireLogger) -> Self {
+        self.wire_log = Some(wire_log);
+        self
+    }
+
+    /// 关联诊断事件通道
+    pub fn with_diagnostics(mut self, diagnostics: DiagnosticHub) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// 关联连接质量统计
+    pub fn with_connection_quality(mut self, connection_quality: SharedConnectionQuality) -> Self {
+        self.connection_quality = Some(connection_quality);
+        self
+    }
+
+    /// 关联账户活动时间线
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// 关联拒单熔断器
+    pub fn with_rejection_breaker(mut self, rejection_breaker: RejectionBreaker) -> Self {
+        self.rejection_breaker = Some(rejection_breaker);
+        self
+    }
+
+    /// 关联市价单模拟，模拟单结束时登记未成交部分供追单
+    pub fn with_market_orders(mut self, market_orders: MarketOrderEmulator) -> Self {
+        self.market_orders = Some(market_orders);
+        self
+    }
+
+    /// 关联移仓执行，腿报单的回报推进移仓进度
+    pub fn with_rollovers(mut self, rollovers: RolloverManager) -> Self {
+        self.rollovers = Some(rollovers);
+        self
+    }
+
+    /// 关联合规监控，成交回报计入报单成交比
+    pub fn with_compliance(mut self, compliance: ComplianceMonitor) -> Self {
+        self.compliance = Some(compliance);
+        self
+    }
+
+    /// 关联客户端订单号映射，回报事件带上订单号
+    pub fn with_client_order_ids(mut self, client_orders: ClientOrderIds) -> Self {
+        self.client_orders = Some(client_orders);
+        self
+    }
+
+    /// 关联成交去重，重连后重复送达的成交不再下发
+    pub fn with_trade_dedup(mut self, trade_dedup: TradeDeduplicator) -> Self {
+        self.trade_dedup = Some(trade_dedup);
+        self
+    }
+
+    /// 关联结算价存储，结算单查询完成后采集持仓合约的结算价
+    pub fn with_settlement_prices(mut self, settlement_prices: SettlementPriceStore) -> Self {
+        self.settlement_prices = Some(settlement_prices);
+        self
+    }
+
+    /// 关联分币种资金，每个币种的资金查询结果分别保存
+    pub fn with_account_balances(mut self, account_balances: AccountBalances) -> Self {
+        self.account_balances = Some(account_balances);
+        self
+    }
+
+    /// 关联重连对账：断线时要求对账，报单、成交回报写入本地报单簿，
+    /// 对账查询结果收齐后与本地报单簿、持仓管理器对账
+    pub fn with_reconciliation(mut self, reconciler: Reconciler, order_book: OrderManager) -> Self {
+        self.reconciliation = Some((reconciler, order_book));
+        self
+    }
+
+    pub fn client_order_ids(&self) -> Option<&ClientOrderIds> {
+        self.client_orders.as_ref()
+    }
+
+    /// 关联持仓管理器，实时成交配对为回合交易
+    pub fn with_position_manager(mut self, position_manager: PositionManager) -> Self {
+        self.position_manager = Some(position_manager);
+        self
+    }
+
+    /// 关联费率缓存，手续费率查询结果同时写入缓存和回合交易
+    pub fn with_rate_cache(mut self, rate_cache: RateCache) -> Self {
+        self.rate_cache = Some(rate_cache);
+        self
+    }
+
+    /// 关联拒单说明服务
+    pub fn with_error_explainer(mut self, error_explainer: ErrorExplainer) -> Self {
+        self.error_explainer = Some(error_explainer);
+        self
+    }
+
+    /// 关联请求/报单的关联 ID 映射
+    pub fn with_correlations(mut self, correlations: CorrelationRegistry) -> Self {
+        self.correlations = Some(correlations);
+        self
+    }
+
+    /// 关联资金曲线异常检测
+    pub fn with_funds_monitor(mut self, funds_monitor: FundsMonitor) -> Self {
+        self.funds_monitor = Some(funds_monitor);
+        self
+    }
+
+    /// 进入请求对应的关联作用域，回调内的日志带上关联 ID
+    fn enter_request(&self, request_id: i32) -> Option<crate::logging::CorrelationGuard> {
+        self.correlations.as_ref().and_then(|c| c.enter_request(request_id))
+    }
+
+    /// 进入报单对应的关联作用域，并把关联 ID 写入事件标签
+    fn enter_order_ref(&self, order_ref: &str, tags: &mut crate::ctp::OrderTags) -> Option<crate::logging::CorrelationGuard> {
+        let correlations = self.correlations.as_ref()?;
+        let correlation_id = correlations.for_order_ref(order_ref)?;
+        tags.entry(CORRELATION_TAG.to_string()).or_insert_with(|| correlation_id.clone());
+        Some(crate::logging::enter_correlation(correlation_id))
+    }
+
+    /// 为拒单诊断事件附加说明和处理建议
+    fn explain(&self, event: DiagnosticEvent, code: i32, message: &str) -> DiagnosticEvent {
+        match &self.error_explainer {
+            Some(explainer) => event.with_explanation(explainer.explain(code, message)),
+            None => event,
+        }
+    }
+
+    /// 计入拒单，触发熔断时发布告警
+    fn handle_rejection(&self, order_ref: &str, instrument_id: &str, reason: &str) {
+        let Some(breaker) = &self.rejection_breaker else {
+            return;
+        };
+        let Some(alert) = breaker.record_rejection(order_ref, instrument_id, reason) else {
+            return;
+        };
+
+        let reasons: Vec<String> = alert.reasons.iter().map(|r| format!("{} ×{}", r.reason, r.count)).collect();
+        let message = format!(
+            "{} 在 {} 秒内被拒单 {} 次，已暂停报单: {}",
+            alert.source,
+            alert.window_secs,
+            alert.rejections,
+            reasons.join("; ")
+        );
+        self.report(
+            DiagnosticEvent::new(DiagnosticSeverity::Critical, DiagnosticSource::Td, message.clone())
+                .with_correlation_id(alert.source.clone()),
+        );
+        if let Some(timeline) = &self.timeline {
+            timeline.record_risk(message, None);
+        }
+        self.send_event(CtpEvent::OrderRejectionBreakerTripped(alert));
+    }
+
+    /// 检查资金快照，发现异常时发布高优先级告警
+    fn check_funds(&self, account: &AccountInfo) {
+        let Some(monitor) = &self.funds_monitor else {
+            return;
+        };
+        for anomaly in monitor.observe(account) {
+            self.report(
+                DiagnosticEvent::new(DiagnosticSeverity::Critical, DiagnosticSource::Td, anomaly.message.clone())
+                    .with_correlation_id(account.account_id.clone()),
+            );
+            if let Some(timeline) = &self.timeline {
+                timeline.record_risk(anomaly.message.clone(), None);
+            }
+            self.send_event(CtpEvent::FundsAnomalyDetected(anomaly));
+        }
+    }
+
+    /// 记录银期转账回报，`sign` 为 1 表示转入期货账户、-1 表示转出
+    fn record_transfer(&self, transfer: Option<&CThostFtdcRspTransferField>, sign: f64) {
+        if let (Some(monitor), Some(transfer)) = (&self.funds_monitor, transfer) {
+            if transfer.ErrorID == 0 {
+                monitor.record_transfer(sign * transfer.TradeAmount);
+            }
+        }
+        let (Some(timeline), Some(transfer)) = (&self.timeline, transfer) else {
+            return;
+        };
+        let serial = gb18030_cstr_i8_to_str(&transfer.BankSerial).unwrap_or_default().to_string();
+        let detail = if transfer.ErrorID != 0 {
+            let msg = gb18030_cstr_i8_to_str(&transfer.ErrorMsg).unwrap_or_default().to_string();
+            format!("转账失败: {} ({})", msg, transfer.ErrorID)
+        } else {
+            format!("银行 {}", gb18030_cstr_i8_to_str(&transfer.BankID).unwrap_or_default())
+        };
+        timeline.record_transfer(sign * transfer.TradeAmount, Some(detail), (!serial.is_empty()).then_some(serial));
+    }
+
+    /// 更新交易端连接质量
+    fn update_quality(&self, f: impl FnOnce(&mut LinkQuality)) {
+        if let Some(quality) = &self.connection_quality {
+            f(&mut quality.lock().unwrap().td);
+        }
+    }
+
+    /// 发布诊断事件
+    fn report(&self, event: DiagnosticEvent) {
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.publish(event);
+        }
+    }
+
+    /// 写入报文日志
+    fn log_wire<T: std::fmt::Debug>(
+        &self,
+        name: &str,
+        request_id: Option<i32>,
+        data: Option<&T>,
+        rsp_info: Option<&CThostFtdcRspInfoField>,
+    ) {
+        if let Some(wire_log) = &self.wire_log {
+            wire_log.callback(name, request_id, data, rsp_info.map(|info| info as &dyn std::fmt::Debug));
+        }
+    }
+
+    /// 查询结果送入对账，收齐后完成对账并发布结果
+    fn feed_reconciliation(&self, event: &CtpEvent) {
+        let (Some((reconciler, order_book)), Some(position_manager)) = (&self.reconciliation, &self.position_manager) else {
+            return;
+        };
+        if !reconciler.feed(event) {
+            return;
+        }
+        if let Some(summary) = reconciler.complete(order_book, position_manager) {
+            if let Some(monitor) = &self.funds_monitor {
+                monitor.record_reconciliation(summary.clone());
+            }
+            self.send_event(CtpEvent::ReconciliationCompleted(summary));
+        }
+    }
+
+    /// 对账查询失败时放弃本次对账
+    fn abort_reconciliation(&self, reason: &str) {
+        if let Some((reconciler, _)) = self.reconciliation.as_ref().filter(|(r, _)| r.is_collecting()) {
+            reconciler.abort(reason);
+        }
+    }
+
+    /// 关联会话健康状态
+    pub fn with_session_health(mut self, session_health: SharedSessionHealth) -> Self {
+        self.session_health = Some(session_health);
+        self
+    }
+
+    /// 处理交易端认证/登录失败
+    ///
+    /// 行情端可用时进入降级模式，不影响整体客户端状态；否则按原逻辑置为错误状态
+    fn handle_trader_failure(&self, msg: String) {
+        let degraded = match &self.session_health {
+            Some(health) => {
+                let mut health = health.lock().unwrap();
+                health.mark_trader_failed(&msg);
+                health.is_degraded()
+            }
+            None => false,
+        };
+
+        if degraded {
+            warn!("交易端不可用，进入仅行情的降级模式: {}", msg);
+            self.send_event(CtpEvent::DegradedModeEntered(msg));
+        } else {
+            self.update_client_state(ClientState::Error(msg.clone()));
+            self.send_event(CtpEvent::LoginFailed(msg));
+        }
+    }
+
+    /// 获取下一个请求ID
+    pub fn next_request_id(&self) -> i32 {
+        let mut id = self.request_id.lock().unwrap();
+        *id += 1;
+        *id
+    }
+
+    /// 获取下一个报单引用
+    pub fn next_order_ref(&self) -> String {
+        let mut ref_id = self.max_order_ref.lock().unwrap();
+        *ref_id += 1;
+        format!("{:012}", *ref_id)
+    }
+
+    /// 获取订单状态
+    pub fn get_order(&self, order_id: &str) -> Option<OrderStatus> {
+        self.orders.lock().unwrap().get(order_id).cloned()
+    }
+
+    /// 获取所有订单
+    pub fn get_all_orders(&self) -> Vec<OrderStatus> {
+        self.orders.lock().unwrap().values().cloned().collect()
+    }
+
+    /// 获取持仓
+    pub fn get_position(&self, instrument_id: &str) -> Option<Position> {
+        self.positions.lock().unwrap().get(instrument_id).cloned()
+    }
+
+    /// 获取所有持仓
+    pub fn get_all_positions(&self) -> Vec<Position> {
+        self.positions.lock().unwrap().values().cloned().collect()
+    }
+
+    /// 发送事件到事件处理器
+    fn send_event(&self, event: CtpEvent) {
+        if let Err(e) = self.event_sender.send(event) {
+            error!("发送事件失败: {}", e);
+        }
+    }
+
+    /// 更新客户端状态
+    fn update_client_state(&self, new_state: ClientState) {
+        let mut state = self.client_state.lock().unwrap();
+        if *state != new_state {
+            debug!("交易客户端状态变更: {:?} -> {:?}", *state, new_state);
+            *state = new_state;
+        }
+    }
+}
+
+// 实现 ctp2rs TraderSpi trait
+impl ctp2rs::v1alpha1::TraderSpi for TraderSpiImpl {
+    /// 前置连接
+    fn on_front_connected(&mut self) {
+        self.log_wire::<()>("OnFrontConnected", None, None, None);
+        info!("交易前置连接成功");
+        self.update_quality(|q| q.record_connected());
+        if let Some(timeline) = &self.timeline {
+            timeline.record_connection("交易", true, None);
+        }
+        self.update_client_state(ClientState::Connected);
+        self.send_event(CtpEvent::Connected);
+    }
+
+    /// 认证响应
+    fn on_rsp_authenticate(
+        &mut self,
+        rsp_authenticate: Option<&ctp2rs::v1alpha1::CThostFtdcRspAuthenticateField>,
+        rsp_info: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspAuthenticate", Some(request_id), rsp_authenticate, rsp_info);
+        info!("收到认证响应，请求ID: {}", request_id);
+        self.update_quality(|q| q.record_response(request_id));
+        
+        if let Some(err) = rsp_info {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("交易认证失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Critical, DiagnosticSource::Td, format!("交易认证失败: {}", msg))
+                        .with_code(err.ErrorID)
+                        .with_request_id(request_id)
+                );
+                self.handle_trader_failure(msg);
+                return;
+            }
+        }
+        
+        if let Some(_auth_field) = rsp_authenticate {
+            info!("交易认证成功，准备发起登录请求");
+            if let Some(health) = &self.session_health {
+                health.lock().unwrap().td = SideStatus::Connecting;
+            }
+            
+            // 认证成功后，发起登录请求
+            // 这里需要通过某种方式获取登录凭据并发起登录
+            // 实际实现中应该通过事件或回调来处理
+        }
+    }
+
+    /// 前置断开
+    fn on_front_disconnected(&mut self, reason: i32) {
+        self.log_wire("OnFrontDisconnected", None, Some(&reason), None);
+        warn!("交易前置断开连接: reason={} ({})", reason, describe_disconnect_reason(reason));
+        self.update_quality(|q| q.record_disconnected(reason));
+        if let Some(timeline) = &self.timeline {
+            timeline.record_connection("交易", false, Some(describe_disconnect_reason(reason).to_string()));
+        }
+        // 断线期间的报单状态未知，重新登录并对账前不接受新订单
+        if let Some((reconciler, _)) = &self.reconciliation {
+            reconciler.require();
+        }
+        self.report(
+            DiagnosticEvent::new(
+                DiagnosticSeverity::Warning,
+                DiagnosticSource::Td,
+                format!("交易前置断开连接: {}", describe_disconnect_reason(reason)),
+            )
+                .with_code(reason)
+        );
+        if let Some(health) = &self.session_health {
+            let mut health = health.lock().unwrap();
+            health.mark_trader_failed(&format!("交易前置断开: {}", reason));
+            if health.is_degraded() {
+                // 行情端仍可用，保持整体状态不变
+                drop(health);
+                self.send_event(CtpEvent::DegradedModeEntered(format!("交易前置断开: {}", reason)));
+                return;
+            }
+        }
+        self.update_client_state(ClientState::Disconnected);
+        self.send_event(CtpEvent::Disconnected);
+    }
+
+    /// 心跳超时警告，time_lapse 为距上次接收报文的秒数
+    fn on_heart_beat_warning(&mut self, time_lapse: i32) {
+        self.log_wire("OnHeartBeatWarning", None, Some(&time_lapse), None);
+        warn!("交易心跳超时警告: 距上次通讯 {} 秒", time_lapse);
+        self.update_quality(|q| q.record_heartbeat_warning(time_lapse));
+        self.report(DiagnosticEvent::new(
+            DiagnosticSeverity::Warning,
+            DiagnosticSource::Td,
+            format!("交易心跳超时警告: 距上次通讯 {} 秒", time_lapse),
+        ));
+    }
+
+    /// 登录响应
+    fn on_rsp_user_login(
+        &mut self,
+        rsp: Option<&CThostFtdcRspUserLoginField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspUserLogin", Some(request_id), rsp, error);
+        self.update_quality(|q| q.record_response(request_id));
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("交易登录失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Critical, DiagnosticSource::Td, format!("交易登录失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.handle_trader_failure(msg);
+                return;
+            }
+        }
+
+        if let Some(login_field) = rsp {
+            self.front_id = login_field.FrontID;
+            self.session_id = login_field.SessionID;
+            if let Some(client_orders) = &self.client_orders {
+                client_orders.set_session(self.front_id, self.session_id);
+            }
+            
+            let max_ref = gb18030_cstr_i8_to_str(&login_field.MaxOrderRef)
+                .unwrap_or_else(|_| "0".into()).to_string();
+            
+            if let Ok(ref_num) = max_ref.parse::<i32>() {
+                *self.max_order_ref.lock().unwrap() = ref_num;
+            }
+            
+            info!("交易登录成功: FrontID={}, SessionID={}", self.front_id, self.session_id);
+            if let Some(health) = &self.session_health {
+                let mut health = health.lock().unwrap();
+                let was_degraded = health.is_degraded();
+                health.mark_trader_ready();
+                if was_degraded {
+                    info!("交易端已恢复，退出降级模式");
+                    self.send_event(CtpEvent::TraderRecovered);
+                }
+            }
+            self.update_client_state(ClientState::LoggedIn);
+            
+            self.send_event(CtpEvent::LoginSuccess(
+                LoginResponse {
+                    trading_day: gb18030_cstr_i8_to_str(&login_field.TradingDay).unwrap_or_default().to_string(),
+                    login_time: gb18030_cstr_i8_to_str(&login_field.LoginTime).unwrap_or_default().to_string(),
+                    broker_id: gb18030_cstr_i8_to_str(&login_field.BrokerID).unwrap_or_default().to_string(),
+                    user_id: gb18030_cstr_i8_to_str(&login_field.UserID).unwrap_or_default().to_string(),
+                    system_name: gb18030_cstr_i8_to_str(&login_field.SystemName).unwrap_or_default().to_string(),
+                    front_id: self.front_id,
+                    session_id: self.session_id,
+                    max_order_ref: max_ref,
+                }
+            ));
+            
+            // 登录成功后自动确认结算单
+            self.send_event(CtpEvent::SettlementRequired);
+        }
+    }
+
+    /// 报单录入响应
+    fn on_rsp_order_insert(
+        &mut self,
+        input: Option<&CThostFtdcInputOrderField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspOrderInsert", Some(request_id), input, error);
+        self.update_quality(|q| q.record_response(request_id));
+        let _correlation = self.enter_request(request_id);
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("报单录入失败: {} ({}) RequestID={}", msg, err.ErrorID, request_id);
+                let correlation_id = crate::logging::current_correlation_id()
+                    .or_else(|| input.map(|order_field| gb18030_cstr_i8_to_str(&order_field.OrderRef).unwrap_or_default().to_string()))
+                    .unwrap_or_else(|| format!("req-{}", request_id));
+                let event = DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("报单录入失败: {}", msg))
+                    .with_code(err.ErrorID)
+                    .with_correlation_id(correlation_id);
+                self.report(self.explain(event, err.ErrorID, &msg));
+                
+                if let Some(order_field) = input {
+                    let order_ref = gb18030_cstr_i8_to_str(&order_field.OrderRef).unwrap_or_default().to_string();
+                    let instrument_id = gb18030_cstr_i8_to_str(&order_field.InstrumentID).unwrap_or_default().to_string();
+                    
+                    // 创建失败的订单状态
+                    let mut failed_order = OrderStatus {
+                        order_ref: order_ref.clone(),
+                        order_id: order_ref.clone(),
+                        instrument_id,
+                        direction: DataConverter::ctp_char_to_direction(order_field.Direction).unwrap_or(crate::ctp::OrderDirection::Buy),
+                        offset_flag: DataConverter::ctp_char_to_offset_flag(order_field.CombOffsetFlag[0]).unwrap_or(crate::ctp::OffsetFlag::Open),
+                        price: order_field.LimitPrice,
+                        limit_price: order_field.LimitPrice,
+                        volume: order_field.VolumeTotalOriginal as u32,
+                        volume_total_original: order_field.VolumeTotalOriginal,
+                        volume_traded: 0,
+                        volume_left: order_field.VolumeTotalOriginal as u32,
+                        volume_total: order_field.VolumeTotalOriginal,
+                        status: crate::ctp::models::OrderStatusType::Unknown,
+                        submit_time: chrono::Local::now(),
+                        insert_time: chrono::Local::now().format("%H:%M:%S").to_string(),
+                        update_time: chrono::Local::now(),
+                        front_id: self.front_id,
+                        session_id: self.session_id,
+                        order_sys_id: String::new(),
+                        status_msg: msg.clone(),
+                        is_local: false,
+                        frozen_margin: 0.0,
+                        frozen_commission: 0.0,
+                        tags: Default::default(),
+                    };
+                    let _order_correlation = self.enter_order_ref(&order_ref, &mut failed_order.tags);
+                    
+                    if let Some(timeline) = &self.timeline {
+                        timeline.record_order_rejected(&failed_order, &msg);
+                    }
+                    self.handle_rejection(&order_ref, &failed_order.instrument_id, &msg);
+                    if let Some(execution) = self.rollovers.as_ref().and_then(|r| r.on_rejected(&order_ref, &msg)) {
+                        self.send_event(CtpEvent::RolloverUpdate(execution));
+                    }
+                    self.orders.lock().unwrap().insert(order_ref.clone(), failed_order.clone());
+                    self.send_event(CtpEvent::OrderUpdate(failed_order));
+                }
+                
+                // 发送错误事件
+                self.send_event(CtpEvent::Error(msg));
+            }
+        } else {
+            // 报单录入成功
+            if let Some(order_field) = input {
+                let order_ref = gb18030_cstr_i8_to_str(&order_field.OrderRef).unwrap_or_default().to_string();
+                info!("报单录入成功，订单引用: {}", order_ref);
+            }
+        }
+    }
+
+    /// 报单错误回报（交易所拒单）
+    fn on_err_rtn_order_insert(
+        &mut self,
+        input: Option<&CThostFtdcInputOrderField>,
+        error: Option<&CThostFtdcRspInfoField>,
+    ) {
+        self.log_wire("OnErrRtnOrderInsert", None, input, error);
+        let (Some(order_field), Some(err)) = (input, error) else {
+            return;
+        };
+        if err.ErrorID == 0 {
+            return;
+        }
+        let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+        let order_ref = gb18030_cstr_i8_to_str(&order_field.OrderRef).unwrap_or_default().to_string();
+        let instrument_id = gb18030_cstr_i8_to_str(&order_field.InstrumentID).unwrap_or_default().to_string();
+        let mut tags = crate::ctp::OrderTags::new();
+        let _correlation = self.enter_order_ref(&order_ref, &mut tags);
+        error!("报单错误回报: {} ({}) OrderRef={}", msg, err.ErrorID, order_ref);
+        let correlation_id = tags.remove(CORRELATION_TAG).unwrap_or_else(|| order_ref.clone());
+        let event = DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("报单被拒: {}", msg))
+            .with_code(err.ErrorID)
+            .with_correlation_id(correlation_id);
+        self.report(self.explain(event, err.ErrorID, &msg));
+        self.handle_rejection(&order_ref, &instrument_id, &msg);
+    }
+
+    /// 报单回报
+    fn on_rtn_order(&mut self, order: Option<&CThostFtdcOrderField>) {
+        self.log_wire("OnRtnOrder", None, order, None);
+        self.update_quality(|q| q.record_activity());
+        if let Some(order_field) = order {
+            let order_status = DataConverter::convert_order(order_field);
+            
+            if let Ok(mut status) = order_status {
+                let _correlation = self.enter_order_ref(&status.order_ref, &mut status.tags);
+                if let Some(client_orders) = &self.client_orders {
+                    client_orders.tag_order(&mut status);
+                }
+                let order_id = status.order_id.clone();
+                self.orders.lock().unwrap().insert(order_id.clone(), status.clone());
+                if let Some((_, order_book)) = &self.reconciliation {
+                    let _ = order_book.update_order(status.clone());
+                }
+                
+                debug!("报单回报: {} 状态={:?}", order_id, status.status);
+                if let Some(timeline) = &self.timeline {
+                    timeline.record_order(&status);
+                }
+                if let Some(market_orders) = &self.market_orders {
+                    market_orders.on_order(&status);
+                }
+                let rollover = self.rollovers.as_ref().and_then(|r| r.on_order(&status));
+                self.send_event(CtpEvent::OrderUpdate(status));
+                if let Some(execution) = rollover {
+                    self.send_event(CtpEvent::RolloverUpdate(execution));
+                }
+            }
+        }
+    }
+
+    /// 成交回报
+    fn on_rtn_trade(&mut self, trade: Option<&CThostFtdcTradeField>) {
+        self.log_wire("OnRtnTrade", None, trade, None);
+        self.update_quality(|q| q.record_activity());
+        if let Some(trade_field) = trade {
+            let trade_record = DataConverter::convert_trade(trade_field);
+            
+            if let Ok(mut record) = trade_record {
+                if let Some(dedup) = &self.trade_dedup {
+                    if !dedup.first_seen(&record) {
+                        warn!("忽略重复成交回报: {} 合约={}", record.trade_id, record.instrument_id);
+                        return;
+                    }
+                }
+                // 成交的 order_id 即报单引用
+                let _correlation = self.enter_order_ref(&record.order_id, &mut record.tags);
+                if let Some(client_orders) = &self.client_orders {
+                    let order_sys_id = gb18030_cstr_i8_to_str(&trade_field.OrderSysID).unwrap_or_default();
+                    client_orders.tag_trade(&order_sys_id, &mut record);
+                }
+                info!("成交回报: {} {} {} @ {}", 
+                    record.instrument_id, record.direction, record.volume, record.price);
+                if let Some(timeline) = &self.timeline {
+                    timeline.record_trade(&record);
+                }
+                if let Some(position_manager) = &self.position_manager {
+                    position_manager.record_fill(&record, chrono::Utc::now());
+                }
+                if let Some(monitor) = &self.funds_monitor {
+                    monitor.record_fill();
+                }
+                if let Some(compliance) = &self.compliance {
+                    compliance.record_trade(&record.order_id);
+                }
+                if let Some((_, order_book)) = &self.reconciliation {
+                    let _ = order_book.add_trade(record.clone());
+                }
+                let rollover = self.rollovers.as_ref().and_then(|r| r.on_trade(&record));
+                self.send_event(CtpEvent::TradeUpdate(record));
+                if let Some(execution) = rollover {
+                    self.send_event(CtpEvent::RolloverUpdate(execution));
+                }
+            }
+        }
+    }
+
+    /// 撤单响应
+    fn on_rsp_order_action(
+        &mut self,
+        action: Option<&CThostFtdcInputOrderActionField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspOrderAction", Some(request_id), action, error);
+        self.update_quality(|q| q.record_response(request_id));
+        let _correlation = self.enter_request(request_id);
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("撤单失败: {} ({})", msg, err.ErrorID);
+                let mut event = DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("撤单失败: {}", msg))
+                    .with_code(err.ErrorID);
+                if let Some(correlation_id) = crate::logging::current_correlation_id() {
+                    event = event.with_correlation_id(correlation_id);
+                }
+                self.report(self.explain(event, err.ErrorID, &msg));
+            }
+        }
+    }
+
+    /// 查询投资者持仓响应
+    fn on_rsp_qry_investor_position(
+        &mut self,
+        position: Option<&CThostFtdcInvestorPositionField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        is_last: bool,
+    ) {
+        self.log_wire("OnRspQryInvestorPosition", Some(request_id), position, error);
+        self.update_quality(|q| q.record_response(request_id));
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询持仓失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询持仓失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("查询持仓失败: {}", msg)));
+                self.abort_reconciliation(&format!("查询持仓失败: {}", msg));
+                return;
+            }
+        }
+
+        if let Some(pos_field) = position {
+            let position = DataConverter::convert_position(pos_field);
+            
+            if let Ok(pos) = position {
+                let instrument_id = pos.instrument_id.clone();
+                self.positions.lock().unwrap().insert(instrument_id, pos.clone());
+                // 发送单个持仓更新事件
+                self.send_event(CtpEvent::PositionUpdate(vec![pos]));
+            }
+        }
+        
+        if is_last {
+            let positions = self.get_all_positions();
+            info!("持仓查询完成，共{}条记录", positions.len());
+            // 发送查询结果事件
+            let event = CtpEvent::QueryPositionsResult(positions);
+            self.feed_reconciliation(&event);
+            self.send_event(event);
+        }
+    }
+
+    /// 查询资金账户响应
+    fn on_rsp_qry_trading_account(
+        &mut self,
+        account: Option<&CThostFtdcTradingAccountField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspQryTradingAccount", Some(request_id), account, error);
+        self.update_quality(|q| q.record_response(request_id));
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询资金账户失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询资金账户失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("查询资金账户失败: {}", msg)));
+                return;
+            }
+        }
+
+        if let Some(acc_field) = account {
+            let account_info = DataConverter::convert_account(acc_field);
+            
+            if let Ok(info) = account_info {
+                info!("资金账户查询结果: {} 余额={:.2}, 可用={:.2}", info.currency_id, info.balance, info.available);
+                if let Some(balances) = &self.account_balances {
+                    balances.update(&info);
+                }
+                self.check_funds(&info);
+                // 发送账户更新事件
+                self.send_event(CtpEvent::AccountUpdate(info.clone()));
+                // 发送查询结果事件
+                self.send_event(CtpEvent::QueryAccountResult(info));
+            }
+        }
+    }
+
+    /// 查询手续费率响应
+    fn on_rsp_qry_instrument_commission_rate(
+        &mut self,
+        rate: Option<&CThostFtdcInstrumentCommissionRateField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspQryInstrumentCommissionRate", Some(request_id), rate, error);
+        self.update_quality(|q| q.record_response(request_id));
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询手续费率失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询手续费率失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                return;
+            }
+        }
+
+        let Some(rate) = rate.and_then(|field| DataConverter::convert_commission_rate(field).ok()) else {
+            return;
+        };
+        info!("手续费率查询结果: {}", rate.instrument_id);
+        if let Some(position_manager) = &self.position_manager {
+            position_manager.round_trip_book().set_commission_rate(&rate);
+        }
+        if let Some(rate_cache) = &self.rate_cache {
+            rate_cache.put_commission(&rate);
+        }
+    }
+
+    /// 查询成交响应
+    fn on_rsp_qry_trade(
+        &mut self,
+        trade: Option<&CThostFtdcTradeField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        is_last: bool,
+    ) {
+        self.log_wire("OnRspQryTrade", Some(request_id), trade, error);
+        self.update_quality(|q| q.record_response(request_id));
+        // 使用静态变量收集查询结果
+        static mut TRADE_QUERY_RESULTS: Vec<TradeRecord> = Vec::new();
+        
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询成交失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询成交失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("查询成交失败: {}", msg)));
+                self.abort_reconciliation(&format!("查询成交失败: {}", msg));
+                return;
+            }
+        }
+
+        if let Some(trade_field) = trade {
+            let trade_record = DataConverter::convert_trade_record(trade_field);
+            
+            if let Ok(record) = trade_record {
+                debug!("查询成交: {} {} {} @ {}", 
+                    record.instrument_id, record.direction, record.volume, record.price);
+                
+                // 收集查询结果
+                unsafe {
+                    TRADE_QUERY_RESULTS.push(record.clone());
+                }
+                
+                // 发送单个成交更新事件
+                self.send_event(CtpEvent::TradeUpdate(record));
+            }
+        }
+        
+        if is_last {
+            unsafe {
+                info!("成交查询完成，共{}条记录", TRADE_QUERY_RESULTS.len());
+                // 发送查询结果事件
+                let event = CtpEvent::QueryTradesResult(TRADE_QUERY_RESULTS.clone());
+                self.feed_reconciliation(&event);
+                self.send_event(event);
+                // 清空结果集
+                TRADE_QUERY_RESULTS.clear();
+            }
+        }
+    }
+
+    /// 查询报单响应
+    fn on_rsp_qry_order(
+        &mut self,
+        order: Option<&CThostFtdcOrderField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        is_last: bool,
+    ) {
+        self.log_wire("OnRspQryOrder", Some(request_id), order, error);
+        self.update_quality(|q| q.record_response(request_id));
+        // 使用静态变量收集查询结果
+        static mut ORDER_QUERY_RESULTS: Vec<OrderStatus> = Vec::new();
+        
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询报单失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询报单失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("查询报单失败: {}", msg)));
+                self.abort_reconciliation(&format!("查询报单失败: {}", msg));
+                return;
+            }
+        }
+
+        if let Some(order_field) = order {
+            let order_status = DataConverter::convert_order_status(order_field);
+            
+            if let Ok(status) = order_status {
+                let order_id = status.order_id.clone();
+                self.orders.lock().unwrap().insert(order_id.clone(), status.clone());
+                
+                debug!("查询报单: {} 状态={:?}", order_id, status.status);
+                
+                // 收集查询结果
+                unsafe {
+                    ORDER_QUERY_RESULTS.push(status.clone());
+                }
+                
+                // 发送单个订单更新事件
+                self.send_event(CtpEvent::OrderUpdate(status));
+            }
+        }
+        
+        if is_last {
+            unsafe {
+                info!("报单查询完成，共{}条记录", ORDER_QUERY_RESULTS.len());
+                // 发送查询结果事件
+                let event = CtpEvent::QueryOrdersResult(ORDER_QUERY_RESULTS.clone());
+                self.feed_reconciliation(&event);
+                self.send_event(event);
+                // 清空结果集
+                ORDER_QUERY_RESULTS.clear();
+            }
+        }
+    }
+
+    /// 结算信息确认响应
+    fn on_rsp_settlement_info_confirm(
+        &mut self,
+        settlement: Option<&ctp2rs::v1alpha1::CThostFtdcSettlementInfoConfirmField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        _is_last: bool,
+    ) {
+        self.log_wire("OnRspSettlementInfoConfirm", Some(request_id), settlement, error);
+        self.update_quality(|q| q.record_response(request_id));
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("结算信息确认失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("结算信息确认失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("结算信息确认失败: {}", msg)));
+                return;
+            }
+        }
+        
+        info!("结算信息确认成功");
+        self.send_event(CtpEvent::SettlementConfirmed);
+    }
+
+    /// 查询结算信息响应
+    fn on_rsp_qry_settlement_info(
+        &mut self,
+        settlement: Option<&ctp2rs::v1alpha1::CThostFtdcSettlementInfoField>,
+        error: Option<&CThostFtdcRspInfoField>,
+        request_id: i32,
+        is_last: bool,
+    ) {
+        self.log_wire("OnRspQrySettlementInfo", Some(request_id), settlement, error);
+        self.update_quality(|q| q.record_response(request_id));
+        // 使用静态变量收集结算信息原始字节，多字节字符可能跨片，结束后整体解码
+        static mut SETTLEMENT_CONTENT: Vec<u8> = Vec::new();
+        
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("查询结算信息失败: {} ({})", msg, err.ErrorID);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("查询结算信息失败: {}", msg))
+                        .with_code(err.ErrorID)
+                );
+                self.send_event(CtpEvent::Error(format!("查询结算信息失败: {}", msg)));
+                return;
+            }
+        }
+
+        if let Some(settlement_field) = settlement {
+            let bytes: Vec<u8> = settlement_field.Content.iter()
+                .take_while(|&&c| c != 0)
+                .map(|&c| c as u8)
+                .collect();
+            let trading_day = gb18030_cstr_i8_to_str(&settlement_field.TradingDay).unwrap_or_default();
+            if let Ok(day) = chrono::NaiveDate::parse_from_str(&trading_day, "%Y%m%d") {
+                self.settlement_trading_day = Some(day);
+            }
+            
+            if !bytes.is_empty() {
+                debug!("收到结算信息片段: {} 字节", bytes.len());
+                // 累积结算信息内容
+                unsafe {
+                    SETTLEMENT_CONTENT.extend_from_slice(&bytes);
+                }
+            }
+        }
+        
+        if is_last {
+            unsafe {
+                let content = crate::ctp::decode_settlement_bytes(&SETTLEMENT_CONTENT);
+                info!("结算信息查询完成，总长度: {} 字节", SETTLEMENT_CONTENT.len());
+                if let (Some(store), Some(day)) = (&self.settlement_prices, self.settlement_trading_day.take()) {
+                    store.ingest_statement(day, &content);
+                }
+                // 发送完整的结算信息
+                self.send_event(CtpEvent::QuerySettlementResult(content));
+                // 清空内容
+                SETTLEMENT_CONTENT.clear();
+            }
+        }
+    }
+
+    /// 错误回报
+    fn on_rsp_error(&mut self, error: Option<&CThostFtdcRspInfoField>, request_id: i32, _is_last: bool) {
+        self.log_wire::<()>("OnRspError", Some(request_id), None, error);
+        self.update_quality(|q| q.record_response(request_id));
+        let _correlation = self.enter_request(request_id);
+        if let Some(err) = error {
+            if err.ErrorID != 0 {
+                let msg = gb18030_cstr_i8_to_str(&err.ErrorMsg).unwrap_or_else(|_| "Unknown error".into()).to_string();
+                error!("交易错误: {} ({}) RequestID={}", msg, err.ErrorID, request_id);
+                self.report(
+                    DiagnosticEvent::new(DiagnosticSeverity::Error, DiagnosticSource::Td, format!("交易错误: {}", msg))
+                        .with_code(err.ErrorID)
+                        .with_request_id(request_id)
+                );
+                self.send_event(CtpEvent::Error(msg));
+            }
+        }
+    }
+
+    /// 期货发起银行资金转期货通知
+    fn on_rtn_from_bank_to_future_by_future(&mut self, transfer: Option<&CThostFtdcRspTransferField>) {
+        self.log_wire("OnRtnFromBankToFutureByFuture", None, transfer, None);
+        info!("银行转期货回报");
+        self.record_transfer(transfer, 1.0);
+    }
+
+    /// 期货发起期货资金转银行通知
+    fn on_rtn_from_future_to_bank_by_future(&mut self, transfer: Option<&CThostFtdcRspTransferField>) {
+        self.log_wire("OnRtnFromFutureToBankByFuture", None, transfer, None);
+        info!("期货转银行回报");
+        self.record_transfer(transfer, -1.0);
+    }
+}
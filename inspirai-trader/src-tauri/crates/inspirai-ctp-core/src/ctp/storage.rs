@@ -0,0 +1,854 @@
+use crate::ctp::{
+    models::{MarketDataTick, OrderStatus, TradeRecord},
+    tick_compaction::{ArchivedBar, StorageGranularity},
+    trade_key, CtpError,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认存储后端配置文件
+pub const DEFAULT_STORAGE_CONFIG_FILE: &str = "./config/storage.toml";
+/// 默认 sqlite 数据库文件
+pub const DEFAULT_SQLITE_PATH: &str = "./data/trading.sqlite";
+/// 默认 DuckDB 数据库文件
+pub const DEFAULT_DUCKDB_PATH: &str = "./data/market_data.duckdb";
+/// 数据库的日志、WAL 等伴随文件后缀（sqlite 与 DuckDB）
+pub const DB_SIDECAR_SUFFIXES: [&str; 4] = ["-wal", "-shm", "-journal", ".wal"];
+/// 恢复备份时数据库文件先写到的待替换文件后缀，下次打开前替换
+const PENDING_RESTORE_SUFFIX: &str = ".restore";
+
+/// 两种后端共用的表结构，类型名同时兼容 sqlite 与 DuckDB
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS orders (
+    order_key TEXT PRIMARY KEY,
+    instrument_id TEXT NOT NULL,
+    submit_time TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS trades (
+    trade_key TEXT PRIMARY KEY,
+    instrument_id TEXT NOT NULL,
+    trade_time TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ticks (
+    trading_day TEXT NOT NULL,
+    seq BIGINT NOT NULL,
+    instrument_id TEXT NOT NULL,
+    last_price DOUBLE NOT NULL,
+    volume BIGINT NOT NULL,
+    turnover DOUBLE NOT NULL,
+    open_interest BIGINT NOT NULL,
+    bid_price1 DOUBLE NOT NULL,
+    bid_volume1 INTEGER NOT NULL,
+    ask_price1 DOUBLE NOT NULL,
+    ask_volume1 INTEGER NOT NULL,
+    update_time TEXT NOT NULL,
+    update_millisec INTEGER NOT NULL,
+    change_percent DOUBLE NOT NULL,
+    change_amount DOUBLE NOT NULL,
+    open_price DOUBLE NOT NULL,
+    highest_price DOUBLE NOT NULL,
+    lowest_price DOUBLE NOT NULL,
+    pre_close_price DOUBLE NOT NULL
+);
+CREATE TABLE IF NOT EXISTS bars (
+    trading_day TEXT NOT NULL,
+    instrument_id TEXT NOT NULL,
+    granularity TEXT NOT NULL,
+    time TEXT NOT NULL,
+    open DOUBLE NOT NULL,
+    high DOUBLE NOT NULL,
+    low DOUBLE NOT NULL,
+    close DOUBLE NOT NULL,
+    volume BIGINT NOT NULL,
+    turnover DOUBLE NOT NULL,
+    open_interest BIGINT NOT NULL,
+    tick_count INTEGER NOT NULL,
+    PRIMARY KEY (trading_day, instrument_id, granularity, time)
+);
+";
+
+const TICK_COLUMNS: &str = "instrument_id, last_price, volume, turnover, open_interest, bid_price1, bid_volume1, \
+    ask_price1, ask_volume1, update_time, update_millisec, change_percent, change_amount, open_price, \
+    highest_price, lowest_price, pre_close_price";
+
+const BAR_COLUMNS: &str = "instrument_id, time, open, high, low, close, volume, turnover, open_interest, tick_count";
+
+const GRANULARITIES: [StorageGranularity; 8] = [
+    StorageGranularity::Tick,
+    StorageGranularity::Bar1s,
+    StorageGranularity::Bar1m,
+    StorageGranularity::Bar5m,
+    StorageGranularity::Bar15m,
+    StorageGranularity::Bar30m,
+    StorageGranularity::Bar1h,
+    StorageGranularity::Bar1d,
+];
+
+/// 存储后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum StorageBackendKind {
+    /// 行存储，适合报单、成交等事务数据
+    Sqlite,
+    /// 列存储，适合 tick、K 线的分析查询
+    Duckdb,
+}
+
+/// 可迁移的数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum StorageDataKind {
+    Orders,
+    Trades,
+    Ticks,
+    Bars,
+}
+
+impl StorageDataKind {
+    pub const ALL: [StorageDataKind; 4] = [
+        StorageDataKind::Orders,
+        StorageDataKind::Trades,
+        StorageDataKind::Ticks,
+        StorageDataKind::Bars,
+    ];
+}
+
+/// 存储后端选择：事务数据和行情数据可分别使用不同后端，修改后重启生效
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// 报单、成交
+    pub orders_backend: StorageBackendKind,
+    /// tick、K 线
+    pub market_data_backend: StorageBackendKind,
+    pub sqlite_path: PathBuf,
+    pub duckdb_path: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            orders_backend: StorageBackendKind::Sqlite,
+            market_data_backend: StorageBackendKind::Duckdb,
+            sqlite_path: PathBuf::from(DEFAULT_SQLITE_PATH),
+            duckdb_path: PathBuf::from(DEFAULT_DUCKDB_PATH),
+        }
+    }
+}
+
+impl StorageConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("存储配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        self.validate()?;
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("存储配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.sqlite_path.as_os_str().is_empty() || self.duckdb_path.as_os_str().is_empty() {
+            return Err(CtpError::ConfigError("数据库文件路径不能为空".to_string()));
+        }
+        if self.sqlite_path == self.duckdb_path {
+            return Err(CtpError::ConfigError("sqlite 与 DuckDB 不能使用同一个数据库文件".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 后端对应的数据库文件
+    pub fn path_for(&self, kind: StorageBackendKind) -> &Path {
+        match kind {
+            StorageBackendKind::Sqlite => &self.sqlite_path,
+            StorageBackendKind::Duckdb => &self.duckdb_path,
+        }
+    }
+}
+
+/// 一个交易日内单个合约的一组行情（tick 或某一粒度的 K 线）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesKey {
+    pub trading_day: NaiveDate,
+    pub instrument_id: String,
+    pub granularity: StorageGranularity,
+}
+
+/// 存储后端
+///
+/// 报单按 (前置, 会话, 报单引用) 覆盖写入，成交按 [`trade_key`] 去重；
+/// tick 以交易日和合约为单位整体替换，K 线按时间覆盖，与行情归档的语义一致
+pub trait StorageBackend: Send + Sync {
+    fn kind(&self) -> StorageBackendKind;
+
+    fn put_orders(&self, orders: &[OrderStatus]) -> Result<usize, CtpError>;
+
+    /// 按提交时间排序，`instrument_id` 为空时返回全部
+    fn load_orders(&self, instrument_id: Option<&str>) -> Result<Vec<OrderStatus>, CtpError>;
+
+    /// 返回新写入的条数，已存在的成交忽略
+    fn put_trades(&self, trades: &[TradeRecord]) -> Result<usize, CtpError>;
+
+    fn load_trades(&self, instrument_id: Option<&str>) -> Result<Vec<TradeRecord>, CtpError>;
+
+    fn put_ticks(&self, trading_day: NaiveDate, instrument_id: &str, ticks: &[MarketDataTick]) -> Result<usize, CtpError>;
+
+    fn load_ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError>;
+
+    fn put_bars(
+        &self,
+        trading_day: NaiveDate,
+        granularity: StorageGranularity,
+        bars: &[ArchivedBar],
+    ) -> Result<usize, CtpError>;
+
+    fn load_bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+    ) -> Result<Vec<ArchivedBar>, CtpError>;
+
+    /// 已存储的全部行情组，按交易日、合约排序
+    fn series(&self) -> Result<Vec<SeriesKey>, CtpError>;
+
+    /// 通过数据库引擎将当前内容写为 `dest` 处的一致副本，供备份使用
+    fn snapshot(&self, dest: &Path) -> Result<(), CtpError>;
+}
+
+/// 数据库文件加上后缀，如伴随文件和待替换文件
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 恢复备份时数据库文件的写入位置，数据库正在使用，下次打开时才替换
+pub fn pending_restore_path(path: &Path) -> PathBuf {
+    with_suffix(path, PENDING_RESTORE_SUFFIX)
+}
+
+/// 打开前用恢复的数据库文件替换当前文件，旧的伴随文件一并删除
+fn apply_pending_restore(path: &Path) -> Result<(), CtpError> {
+    let pending = pending_restore_path(path);
+    if !pending.exists() {
+        return Ok(());
+    }
+    for suffix in DB_SIDECAR_SUFFIXES {
+        let sidecar = with_suffix(path, suffix);
+        if sidecar.exists() {
+            std::fs::remove_file(&sidecar)?;
+        }
+    }
+    std::fs::rename(&pending, path)?;
+    tracing::info!("已用备份恢复的数据库替换 {}", path.display());
+    Ok(())
+}
+
+/// 打开指定类型的后端
+pub fn open_backend(kind: StorageBackendKind, path: impl AsRef<Path>) -> Result<Arc<dyn StorageBackend>, CtpError> {
+    apply_pending_restore(path.as_ref())?;
+    Ok(match kind {
+        StorageBackendKind::Sqlite => Arc::new(SqliteStore::open(path)?),
+        StorageBackendKind::Duckdb => Arc::new(DuckDbStore::open(path)?),
+    })
+}
+
+/// 按配置打开的存储，事务数据与行情数据各自路由到所选后端
+#[derive(Clone)]
+pub struct Storage {
+    orders: Arc<dyn StorageBackend>,
+    market_data: Arc<dyn StorageBackend>,
+    databases: Vec<(PathBuf, Arc<dyn StorageBackend>)>,
+}
+
+impl Storage {
+    pub fn open(config: &StorageConfig) -> Result<Self, CtpError> {
+        config.validate()?;
+        let orders_path = config.path_for(config.orders_backend).to_path_buf();
+        let orders = open_backend(config.orders_backend, &orders_path)?;
+        let mut databases = vec![(orders_path, orders.clone())];
+        // 两类数据选同一后端时共用一个连接，避免同一文件被打开两次
+        let market_data = if config.market_data_backend == config.orders_backend {
+            orders.clone()
+        } else {
+            let path = config.path_for(config.market_data_backend).to_path_buf();
+            let backend = open_backend(config.market_data_backend, &path)?;
+            databases.push((path, backend.clone()));
+            backend
+        };
+        Ok(Self { orders, market_data, databases })
+    }
+
+    /// 已打开的数据库文件及其后端，备份时据此通过引擎生成快照
+    pub fn databases(&self) -> &[(PathBuf, Arc<dyn StorageBackend>)] {
+        &self.databases
+    }
+
+    /// 报单、成交所在后端
+    pub fn orders(&self) -> &Arc<dyn StorageBackend> {
+        &self.orders
+    }
+
+    /// tick、K 线所在后端
+    pub fn market_data(&self) -> &Arc<dyn StorageBackend> {
+        &self.market_data
+    }
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from: StorageBackendKind,
+    pub to: StorageBackendKind,
+    pub orders: usize,
+    pub trades: usize,
+    pub tick_series: usize,
+    pub ticks: usize,
+    pub bar_series: usize,
+    pub bars: usize,
+}
+
+/// 将数据从一个后端复制到另一个后端，`kinds` 为空表示全部类别
+///
+/// 写入语义是幂等的，中断后可直接重跑；源数据保留不动，确认无误后再切换配置
+pub fn migrate_storage(
+    from: &dyn StorageBackend,
+    to: &dyn StorageBackend,
+    kinds: &[StorageDataKind],
+    mut on_series: impl FnMut(&SeriesKey),
+) -> Result<MigrationReport, CtpError> {
+    let wants = |kind: StorageDataKind| kinds.is_empty() || kinds.contains(&kind);
+    let mut report = MigrationReport {
+        from: from.kind(),
+        to: to.kind(),
+        orders: 0,
+        trades: 0,
+        tick_series: 0,
+        ticks: 0,
+        bar_series: 0,
+        bars: 0,
+    };
+
+    if wants(StorageDataKind::Orders) {
+        report.orders = to.put_orders(&from.load_orders(None)?)?;
+    }
+    if wants(StorageDataKind::Trades) {
+        let trades = from.load_trades(None)?;
+        to.put_trades(&trades)?;
+        report.trades = trades.len();
+    }
+
+    let (ticks, bars) = (wants(StorageDataKind::Ticks), wants(StorageDataKind::Bars));
+    if ticks || bars {
+        for key in from.series()? {
+            if key.granularity == StorageGranularity::Tick {
+                if !ticks {
+                    continue;
+                }
+                on_series(&key);
+                let rows = from.load_ticks(key.trading_day, &key.instrument_id)?;
+                report.ticks += to.put_ticks(key.trading_day, &key.instrument_id, &rows)?;
+                report.tick_series += 1;
+            } else if bars {
+                on_series(&key);
+                let rows = from.load_bars(key.trading_day, &key.instrument_id, key.granularity)?;
+                report.bars += to.put_bars(key.trading_day, key.granularity, &rows)?;
+                report.bar_series += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn db_error(e: impl std::fmt::Display) -> CtpError {
+    CtpError::ConversionError(format!("数据库读写失败: {}", e))
+}
+
+fn to_payload<T: Serialize>(value: &T) -> Result<String, CtpError> {
+    serde_json::to_string(value).map_err(|e| CtpError::ConversionError(format!("序列化存储记录失败: {}", e)))
+}
+
+fn from_payload<T: serde::de::DeserializeOwned>(payload: &str) -> Result<T, CtpError> {
+    serde_json::from_str(payload).map_err(|e| CtpError::ConversionError(format!("解析存储记录失败: {}", e)))
+}
+
+fn order_key(order: &OrderStatus) -> String {
+    format!("{}|{}|{}", order.front_id, order.session_id, order.order_ref.trim())
+}
+
+fn day_key(trading_day: NaiveDate) -> String {
+    trading_day.format("%Y-%m-%d").to_string()
+}
+
+fn parse_series(trading_day: &str, instrument_id: String, granularity: &str) -> Result<SeriesKey, CtpError> {
+    let trading_day = NaiveDate::parse_from_str(trading_day, "%Y-%m-%d")
+        .map_err(|e| CtpError::ConversionError(format!("无效的交易日 {}: {}", trading_day, e)))?;
+    let granularity = GRANULARITIES
+        .into_iter()
+        .find(|g| g.dir_name() == granularity)
+        .ok_or_else(|| CtpError::ConversionError(format!("未知的行情粒度: {}", granularity)))?;
+    Ok(SeriesKey { trading_day, instrument_id, granularity })
+}
+
+/// rusqlite 与 duckdb 的接口一致，两个后端由同一份实现生成
+macro_rules! sql_backend {
+    ($(#[$meta:meta])* $name:ident, $db:ident, $kind:expr) => {
+        $(#[$meta])*
+        pub struct $name {
+            conn: Mutex<$db::Connection>,
+            /// 内存数据库为空
+            path: Option<PathBuf>,
+        }
+
+        impl $name {
+            pub fn open(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+                let path = path.as_ref();
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                Self::init($db::Connection::open(path).map_err(db_error)?, Some(path.to_path_buf()))
+            }
+
+            /// 内存数据库，用于测试
+            pub fn in_memory() -> Result<Self, CtpError> {
+                Self::init($db::Connection::open_in_memory().map_err(db_error)?, None)
+            }
+
+            fn init(conn: $db::Connection, path: Option<PathBuf>) -> Result<Self, CtpError> {
+                conn.execute_batch(SCHEMA).map_err(db_error)?;
+                Ok(Self { conn: Mutex::new(conn), path })
+            }
+
+            fn payloads(&self, sql: &str, instrument_id: Option<&str>) -> Result<Vec<String>, CtpError> {
+                let conn = self.conn.lock().unwrap();
+                let mut stmt = conn.prepare(sql).map_err(db_error)?;
+                let payload = |row: &$db::Row<'_>| row.get::<_, String>(0);
+                let rows = match instrument_id {
+                    Some(id) => stmt.query_map($db::params![id], payload),
+                    None => stmt.query_map([], payload),
+                }
+                .map_err(db_error)?;
+                rows.collect::<Result<Vec<_>, _>>().map_err(db_error)
+            }
+        }
+
+        impl StorageBackend for $name {
+            fn kind(&self) -> StorageBackendKind {
+                $kind
+            }
+
+            fn put_orders(&self, orders: &[OrderStatus]) -> Result<usize, CtpError> {
+                let mut conn = self.conn.lock().unwrap();
+                let tx = conn.transaction().map_err(db_error)?;
+                for order in orders {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO orders (order_key, instrument_id, submit_time, payload) VALUES (?, ?, ?, ?)",
+                        $db::params![order_key(order), order.instrument_id, order.submit_time.to_rfc3339(), to_payload(order)?],
+                    )
+                    .map_err(db_error)?;
+                }
+                tx.commit().map_err(db_error)?;
+                Ok(orders.len())
+            }
+
+            fn load_orders(&self, instrument_id: Option<&str>) -> Result<Vec<OrderStatus>, CtpError> {
+                let sql = match instrument_id {
+                    Some(_) => "SELECT payload FROM orders WHERE instrument_id = ? ORDER BY submit_time, order_key",
+                    None => "SELECT payload FROM orders ORDER BY submit_time, order_key",
+                };
+                self.payloads(sql, instrument_id)?.iter().map(|p| from_payload(p)).collect()
+            }
+
+            fn put_trades(&self, trades: &[TradeRecord]) -> Result<usize, CtpError> {
+                let mut conn = self.conn.lock().unwrap();
+                let tx = conn.transaction().map_err(db_error)?;
+                let mut inserted = 0;
+                for trade in trades {
+                    inserted += tx
+                        .execute(
+                            "INSERT OR IGNORE INTO trades (trade_key, instrument_id, trade_time, payload) VALUES (?, ?, ?, ?)",
+                            $db::params![trade_key(trade), trade.instrument_id, trade.trade_time, to_payload(trade)?],
+                        )
+                        .map_err(db_error)?;
+                }
+                tx.commit().map_err(db_error)?;
+                Ok(inserted)
+            }
+
+            fn load_trades(&self, instrument_id: Option<&str>) -> Result<Vec<TradeRecord>, CtpError> {
+                let sql = match instrument_id {
+                    Some(_) => "SELECT payload FROM trades WHERE instrument_id = ? ORDER BY trade_time, trade_key",
+                    None => "SELECT payload FROM trades ORDER BY trade_time, trade_key",
+                };
+                self.payloads(sql, instrument_id)?.iter().map(|p| from_payload(p)).collect()
+            }
+
+            fn put_ticks(&self, trading_day: NaiveDate, instrument_id: &str, ticks: &[MarketDataTick]) -> Result<usize, CtpError> {
+                let day = day_key(trading_day);
+                let mut conn = self.conn.lock().unwrap();
+                let tx = conn.transaction().map_err(db_error)?;
+                tx.execute("DELETE FROM ticks WHERE trading_day = ? AND instrument_id = ?", $db::params![day, instrument_id])
+                    .map_err(db_error)?;
+                {
+                    let mut stmt = tx
+                        .prepare(&format!(
+                            "INSERT INTO ticks (trading_day, seq, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                            TICK_COLUMNS
+                        ))
+                        .map_err(db_error)?;
+                    for (seq, t) in ticks.iter().enumerate() {
+                        stmt.execute($db::params![
+                            day, seq as i64, instrument_id, t.last_price, t.volume, t.turnover, t.open_interest,
+                            t.bid_price1, t.bid_volume1, t.ask_price1, t.ask_volume1, t.update_time, t.update_millisec,
+                            t.change_percent, t.change_amount, t.open_price, t.highest_price, t.lowest_price, t.pre_close_price,
+                        ])
+                        .map_err(db_error)?;
+                    }
+                }
+                tx.commit().map_err(db_error)?;
+                Ok(ticks.len())
+            }
+
+            fn load_ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+                let conn = self.conn.lock().unwrap();
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT {} FROM ticks WHERE trading_day = ? AND instrument_id = ? ORDER BY seq",
+                        TICK_COLUMNS
+                    ))
+                    .map_err(db_error)?;
+                let rows = stmt
+                    .query_map($db::params![day_key(trading_day), instrument_id], |row| {
+                        Ok(MarketDataTick {
+                            instrument_id: row.get(0)?,
+                            last_price: row.get(1)?,
+                            volume: row.get(2)?,
+                            turnover: row.get(3)?,
+                            open_interest: row.get(4)?,
+                            bid_price1: row.get(5)?,
+                            bid_volume1: row.get(6)?,
+                            ask_price1: row.get(7)?,
+                            ask_volume1: row.get(8)?,
+                            update_time: row.get(9)?,
+                            update_millisec: row.get(10)?,
+                            change_percent: row.get(11)?,
+                            change_amount: row.get(12)?,
+                            open_price: row.get(13)?,
+                            highest_price: row.get(14)?,
+                            lowest_price: row.get(15)?,
+                            pre_close_price: row.get(16)?,
+                            price_limit: None,
+                            trace: None,
+                            source: None,
+                        })
+                    })
+                    .map_err(db_error)?;
+                rows.collect::<Result<Vec<_>, _>>().map_err(db_error)
+            }
+
+            fn put_bars(
+                &self,
+                trading_day: NaiveDate,
+                granularity: StorageGranularity,
+                bars: &[ArchivedBar],
+            ) -> Result<usize, CtpError> {
+                if granularity == StorageGranularity::Tick {
+                    return Err(CtpError::InvalidParameter("K 线不能按 tick 粒度写入".to_string()));
+                }
+                let day = day_key(trading_day);
+                let mut conn = self.conn.lock().unwrap();
+                let tx = conn.transaction().map_err(db_error)?;
+                {
+                    let mut stmt = tx
+                        .prepare(&format!(
+                            "INSERT OR REPLACE INTO bars (trading_day, granularity, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                            BAR_COLUMNS
+                        ))
+                        .map_err(db_error)?;
+                    for b in bars {
+                        stmt.execute($db::params![
+                            day, granularity.dir_name(), b.instrument_id, b.time, b.open, b.high, b.low, b.close,
+                            b.volume, b.turnover, b.open_interest, b.tick_count,
+                        ])
+                        .map_err(db_error)?;
+                    }
+                }
+                tx.commit().map_err(db_error)?;
+                Ok(bars.len())
+            }
+
+            fn load_bars(
+                &self,
+                trading_day: NaiveDate,
+                instrument_id: &str,
+                granularity: StorageGranularity,
+            ) -> Result<Vec<ArchivedBar>, CtpError> {
+                let conn = self.conn.lock().unwrap();
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT {} FROM bars WHERE trading_day = ? AND instrument_id = ? AND granularity = ? ORDER BY time",
+                        BAR_COLUMNS
+                    ))
+                    .map_err(db_error)?;
+                let rows = stmt
+                    .query_map($db::params![day_key(trading_day), instrument_id, granularity.dir_name()], |row| {
+                        Ok(ArchivedBar {
+                            instrument_id: row.get(0)?,
+                            time: row.get(1)?,
+                            open: row.get(2)?,
+                            high: row.get(3)?,
+                            low: row.get(4)?,
+                            close: row.get(5)?,
+                            volume: row.get(6)?,
+                            turnover: row.get(7)?,
+                            open_interest: row.get(8)?,
+                            tick_count: row.get(9)?,
+                        })
+                    })
+                    .map_err(db_error)?;
+                rows.collect::<Result<Vec<_>, _>>().map_err(db_error)
+            }
+
+            fn series(&self) -> Result<Vec<SeriesKey>, CtpError> {
+                let conn = self.conn.lock().unwrap();
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT DISTINCT trading_day, instrument_id, '{}' AS granularity FROM ticks \
+                         UNION SELECT DISTINCT trading_day, instrument_id, granularity FROM bars \
+                         ORDER BY trading_day, instrument_id, granularity",
+                        StorageGranularity::Tick.dir_name()
+                    ))
+                    .map_err(db_error)?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+                    .map_err(db_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(db_error)?;
+                rows.into_iter()
+                    .map(|(day, instrument_id, granularity)| parse_series(&day, instrument_id, &granularity))
+                    .collect()
+            }
+
+            fn snapshot(&self, dest: &Path) -> Result<(), CtpError> {
+                if dest.exists() {
+                    std::fs::remove_file(dest)?;
+                }
+                self.snapshot_to(dest)
+            }
+        }
+    };
+}
+
+sql_backend!(
+    /// sqlite 后端，单文件、事务写入快，默认存放报单与成交
+    SqliteStore,
+    rusqlite,
+    StorageBackendKind::Sqlite
+);
+
+sql_backend!(
+    /// DuckDB 后端，列式存储，默认存放 tick 与 K 线供分析查询
+    DuckDbStore,
+    duckdb,
+    StorageBackendKind::Duckdb
+);
+
+impl SqliteStore {
+    /// VACUUM INTO 在一个读事务内写出完整副本，不受 WAL 和并发写入影响
+    fn snapshot_to(&self, dest: &Path) -> Result<(), CtpError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?", rusqlite::params![dest.to_string_lossy()])
+            .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+impl DuckDbStore {
+    /// 检查点把 WAL 合并进数据库文件，持有连接锁复制期间没有写入
+    fn snapshot_to(&self, dest: &Path) -> Result<(), CtpError> {
+        let conn = self.conn.lock().unwrap();
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| CtpError::StateError("内存数据库不能生成快照".to_string()))?;
+        conn.execute_batch("CHECKPOINT").map_err(db_error)?;
+        std::fs::copy(path, dest)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::models::{OffsetFlag, OrderDirection, OrderStatusType};
+
+    fn order(order_ref: &str, status: OrderStatusType) -> OrderStatus {
+        OrderStatus {
+            order_ref: order_ref.to_string(),
+            order_id: order_ref.to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            limit_price: 3500.0,
+            volume: 2,
+            volume_total_original: 2,
+            volume_traded: 0,
+            volume_left: 2,
+            volume_total: 2,
+            status,
+            submit_time: chrono::Local::now(),
+            insert_time: "09:00:01".to_string(),
+            update_time: chrono::Local::now(),
+            front_id: 1,
+            session_id: 7,
+            order_sys_id: String::new(),
+            status_msg: String::new(),
+            is_local: true,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: Default::default(),
+        }
+    }
+
+    fn trade(trade_id: &str) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: "1".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume: 1,
+            trade_time: "09:00:02".to_string(),
+            exchange_id: "SHFE".to_string(),
+            tags: Default::default(),
+        }
+    }
+
+    fn tick(time: &str, price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: price,
+            volume: 10,
+            turnover: price * 100.0,
+            open_interest: 1000,
+            bid_price1: price - 1.0,
+            bid_volume1: 5,
+            ask_price1: price + 1.0,
+            ask_volume1: 6,
+            update_time: time.to_string(),
+            update_millisec: 500,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 3490.0,
+            highest_price: 3510.0,
+            lowest_price: 3480.0,
+            pre_close_price: 3495.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    fn bar(time: &str, close: f64) -> ArchivedBar {
+        ArchivedBar {
+            instrument_id: "rb2501".to_string(),
+            time: time.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 3,
+            turnover: close * 30.0,
+            open_interest: 1000,
+            tick_count: 2,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_upserts_orders_and_dedups_trades() {
+        let store = SqliteStore::in_memory().unwrap();
+        store.put_orders(&[order("1", OrderStatusType::NoTradeQueueing)]).unwrap();
+        store.put_orders(&[order("1", OrderStatusType::AllTraded), order("2", OrderStatusType::Canceled)]).unwrap();
+        let orders = store.load_orders(None).unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders.iter().find(|o| o.order_ref == "1").unwrap().status, OrderStatusType::AllTraded);
+        assert!(store.load_orders(Some("hc2501")).unwrap().is_empty());
+
+        assert_eq!(store.put_trades(&[trade("T1"), trade("T2")]).unwrap(), 2);
+        assert_eq!(store.put_trades(&[trade("T2"), trade("T3")]).unwrap(), 1);
+        assert_eq!(store.load_trades(Some("rb2501")).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_sqlite_to_duckdb() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let sqlite = SqliteStore::in_memory().unwrap();
+        sqlite.put_orders(&[order("1", OrderStatusType::AllTraded)]).unwrap();
+        sqlite.put_trades(&[trade("T1")]).unwrap();
+        sqlite.put_ticks(day, "rb2501", &[tick("09:00:00", 3500.0), tick("09:00:01", 3501.0)]).unwrap();
+        sqlite.put_bars(day, StorageGranularity::Bar1m, &[bar("09:00:00", 3501.0), bar("09:01:00", 3502.0)]).unwrap();
+        assert!(sqlite.put_bars(day, StorageGranularity::Tick, &[bar("09:00:00", 1.0)]).is_err());
+
+        let duck = DuckDbStore::in_memory().unwrap();
+        let mut seen = Vec::new();
+        let report = migrate_storage(&sqlite, &duck, &[], |key| seen.push(key.granularity)).unwrap();
+        assert_eq!((report.from, report.to), (StorageBackendKind::Sqlite, StorageBackendKind::Duckdb));
+        assert_eq!((report.orders, report.trades, report.ticks, report.bars), (1, 1, 2, 2));
+        assert_eq!(seen, vec![StorageGranularity::Bar1m, StorageGranularity::Tick]);
+
+        let ticks = duck.load_ticks(day, "rb2501").unwrap();
+        assert_eq!(ticks.iter().map(|t| t.update_time.as_str()).collect::<Vec<_>>(), vec!["09:00:00", "09:00:01"]);
+        assert_eq!(duck.load_bars(day, "rb2501", StorageGranularity::Bar1m).unwrap()[1], bar("09:01:00", 3502.0));
+        assert_eq!(duck.load_orders(None).unwrap()[0].order_ref, "1");
+
+        // 重跑不产生重复
+        let report = migrate_storage(&sqlite, &duck, &[StorageDataKind::Ticks], |_| {}).unwrap();
+        assert_eq!((report.orders, report.ticks), (0, 2));
+        assert_eq!(duck.load_ticks(day, "rb2501").unwrap().len(), 2);
+        assert_eq!(duck.series().unwrap(), sqlite.series().unwrap());
+    }
+
+    #[test]
+    fn test_snapshot_and_pending_restore() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("trading.sqlite");
+        let store = SqliteStore::open(&path).unwrap();
+        store.put_trades(&[trade("T1")]).unwrap();
+
+        // 快照是可独立打开的一致副本
+        let snapshot = dir.path().join("snapshot.sqlite");
+        store.snapshot(&snapshot).unwrap();
+        store.put_trades(&[trade("T2")]).unwrap();
+        assert_eq!(SqliteStore::open(&snapshot).unwrap().load_trades(None).unwrap().len(), 1);
+        drop(store);
+
+        // 恢复的数据库在下次打开时替换当前文件
+        std::fs::copy(&snapshot, pending_restore_path(&path)).unwrap();
+        let reopened = open_backend(StorageBackendKind::Sqlite, &path).unwrap();
+        assert_eq!(reopened.load_trades(None).unwrap().len(), 1);
+        assert!(!pending_restore_path(&path).exists());
+    }
+}
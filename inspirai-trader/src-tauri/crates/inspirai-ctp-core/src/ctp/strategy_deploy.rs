@@ -0,0 +1,365 @@
+use crate::ctp::{CtpError, StrategyBudget};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 策略部署记录文件
+pub const DEFAULT_STRATEGY_REGISTRY_FILE: &str = "./data/strategy_deployments.json";
+
+/// 每个策略实例保留的部署历史条数
+const MAX_HISTORY: usize = 100;
+
+/// 策略实现的引用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StrategyEntryPoint {
+    /// 脚本策略
+    Script { path: String, language: String },
+    /// 编译好的插件，`symbol` 为导出的入口
+    Plugin { path: String, symbol: String },
+}
+
+/// 策略部署包
+///
+/// 以 JSON 清单描述，脚本或插件路径相对清单所在目录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyPackage {
+    /// 策略类型名，同一实例升级前后必须一致
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    pub entry: StrategyEntryPoint,
+    /// 策略参数
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+    /// 该版本要求的风险预算，为空时沿用实例当前预算
+    #[serde(default)]
+    pub budget: Option<StrategyBudget>,
+}
+
+impl StrategyPackage {
+    /// 读取部署包清单
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        let content = std::fs::read(path)?;
+        let mut package: StrategyPackage = serde_json::from_slice(&content)
+            .map_err(|e| CtpError::ConversionError(format!("解析策略部署包 {} 失败: {}", path.display(), e)))?;
+        if let Some(dir) = path.parent() {
+            let resolve = |p: &mut String| {
+                if Path::new(p.as_str()).is_relative() {
+                    *p = dir.join(p.as_str()).to_string_lossy().into_owned();
+                }
+            };
+            match &mut package.entry {
+                StrategyEntryPoint::Script { path, .. } | StrategyEntryPoint::Plugin { path, .. } => resolve(path),
+            }
+        }
+        package.validate()?;
+        Ok(package)
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.name.trim().is_empty() {
+            return Err(CtpError::ValidationError("策略名不能为空".to_string()));
+        }
+        if self.version.trim().is_empty() {
+            return Err(CtpError::ValidationError("策略版本不能为空".to_string()));
+        }
+        let path = match &self.entry {
+            StrategyEntryPoint::Script { path, .. } | StrategyEntryPoint::Plugin { path, .. } => path,
+        };
+        if path.trim().is_empty() {
+            return Err(CtpError::ValidationError("策略入口路径不能为空".to_string()));
+        }
+        if let Some(budget) = &self.budget {
+            if budget.max_open_position < 0 || budget.max_loss < 0.0 {
+                return Err(CtpError::ValidationError("风险预算不能为负".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 部署动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentAction {
+    Deploy,
+    Upgrade,
+    Rollback,
+}
+
+/// 一次部署记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentRecord {
+    pub action: DeploymentAction,
+    pub version: String,
+    pub previous_version: Option<String>,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 策略实例的部署状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyInstance {
+    /// 实例 ID，即订单标签中的策略名，持仓和风险预算按此归属
+    pub instance_id: String,
+    pub current_version: String,
+    /// 已部署过的版本，按首次部署先后排列
+    pub packages: Vec<StrategyPackage>,
+    pub history: Vec<DeploymentRecord>,
+}
+
+impl StrategyInstance {
+    pub fn current_package(&self) -> Option<&StrategyPackage> {
+        self.package(&self.current_version)
+    }
+
+    pub fn package(&self, version: &str) -> Option<&StrategyPackage> {
+        self.packages.iter().find(|p| p.version == version)
+    }
+
+    fn switch(&mut self, action: DeploymentAction, version: &str) {
+        self.history.push(DeploymentRecord {
+            action,
+            version: version.to_string(),
+            previous_version: Some(self.current_version.clone()),
+            at: chrono::Utc::now(),
+        });
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.current_version = version.to_string();
+    }
+}
+
+/// 策略部署注册表
+///
+/// 记录每个策略实例部署过的版本，支持升级和回滚。实例 ID 在升级、回滚前后不变，
+/// 因此按策略标签归属的持仓、当日统计和熔断状态都得以保留
+#[derive(Clone)]
+pub struct StrategyRegistry {
+    inner: Arc<Mutex<HashMap<String, StrategyInstance>>>,
+    path: Option<PathBuf>,
+}
+
+impl StrategyRegistry {
+    /// 仅内存的注册表
+    pub fn in_memory() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            path: None,
+        }
+    }
+
+    /// 打开部署记录文件
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref().to_path_buf();
+        let instances: Vec<StrategyInstance> = if path.exists() {
+            serde_json::from_slice(&std::fs::read(&path)?)
+                .map_err(|e| CtpError::ConversionError(format!("解析策略部署记录失败: {}", e)))?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(instances.into_iter().map(|i| (i.instance_id.clone(), i)).collect())),
+            path: Some(path),
+        })
+    }
+
+    /// 部署新实例或将已有实例升级到新版本
+    ///
+    /// 同一版本号的内容不可变更；重新部署历史版本等同回滚
+    pub fn deploy(&self, instance_id: &str, package: StrategyPackage) -> Result<StrategyInstance, CtpError> {
+        package.validate()?;
+        if instance_id.trim().is_empty() {
+            return Err(CtpError::ValidationError("策略实例 ID 不能为空".to_string()));
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let instance = match inner.get_mut(instance_id) {
+            None => {
+                let instance = StrategyInstance {
+                    instance_id: instance_id.to_string(),
+                    current_version: package.version.clone(),
+                    history: vec![DeploymentRecord {
+                        action: DeploymentAction::Deploy,
+                        version: package.version.clone(),
+                        previous_version: None,
+                        at: chrono::Utc::now(),
+                    }],
+                    packages: vec![package],
+                };
+                inner.insert(instance_id.to_string(), instance.clone());
+                instance
+            }
+            Some(instance) => {
+                let current_name = instance.current_package().map(|p| p.name.clone()).unwrap_or_default();
+                if current_name != package.name {
+                    return Err(CtpError::ValidationError(format!(
+                        "策略实例 {} 运行的是 {}，不能升级为 {}",
+                        instance_id, current_name, package.name
+                    )));
+                }
+                if instance.current_version == package.version {
+                    return Err(CtpError::StateError(format!("策略实例 {} 已是版本 {}", instance_id, package.version)));
+                }
+                let action = match instance.package(&package.version) {
+                    Some(existing) if *existing != package => {
+                        return Err(CtpError::ValidationError(format!(
+                            "版本 {} 已部署过且内容不同，请使用新的版本号",
+                            package.version
+                        )));
+                    }
+                    Some(_) => DeploymentAction::Rollback,
+                    None => {
+                        instance.packages.push(package.clone());
+                        DeploymentAction::Upgrade
+                    }
+                };
+                instance.switch(action, &package.version);
+                instance.clone()
+            }
+        };
+        tracing::info!("策略实例 {} 部署版本 {}", instance_id, instance.current_version);
+        self.persist(&inner);
+        Ok(instance)
+    }
+
+    /// 回滚到指定版本，未指定时回到切换到当前版本之前运行的版本
+    pub fn rollback(&self, instance_id: &str, version: Option<&str>) -> Result<StrategyInstance, CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        let instance = inner
+            .get_mut(instance_id)
+            .ok_or_else(|| CtpError::NotFound(format!("策略实例不存在: {}", instance_id)))?;
+        let target = match version {
+            Some(version) => version.to_string(),
+            None => instance
+                .history
+                .iter()
+                .rev()
+                .find(|r| r.version == instance.current_version)
+                .and_then(|r| r.previous_version.clone())
+                .ok_or_else(|| CtpError::StateError(format!("策略实例 {} 没有可回滚的版本", instance_id)))?,
+        };
+        if target == instance.current_version {
+            return Err(CtpError::StateError(format!("策略实例 {} 已是版本 {}", instance_id, target)));
+        }
+        if instance.package(&target).is_none() {
+            return Err(CtpError::NotFound(format!("策略实例 {} 未部署过版本 {}", instance_id, target)));
+        }
+        instance.switch(DeploymentAction::Rollback, &target);
+        let instance = instance.clone();
+        tracing::warn!("策略实例 {} 回滚到版本 {}", instance_id, target);
+        self.persist(&inner);
+        Ok(instance)
+    }
+
+    pub fn get(&self, instance_id: &str) -> Option<StrategyInstance> {
+        self.inner.lock().unwrap().get(instance_id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<StrategyInstance> {
+        let mut instances: Vec<StrategyInstance> = self.inner.lock().unwrap().values().cloned().collect();
+        instances.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+        instances
+    }
+
+    fn persist(&self, instances: &HashMap<String, StrategyInstance>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut list: Vec<&StrategyInstance> = instances.values().collect();
+        list.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+        let result = serde_json::to_vec_pretty(&list)
+            .map_err(|e| CtpError::ConversionError(format!("序列化策略部署记录失败: {}", e)))
+            .and_then(|content| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let tmp = path.with_extension("json.tmp");
+                std::fs::write(&tmp, content)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::warn!("保存策略部署记录失败: {}", e);
+            crate::health::record_storage_error("strategy_registry", &e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn package(version: &str, fast: u64) -> StrategyPackage {
+        let mut parameters = serde_json::Map::new();
+        parameters.insert("fast".to_string(), serde_json::json!(fast));
+        StrategyPackage {
+            name: "ma_cross".to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            author: String::new(),
+            entry: StrategyEntryPoint::Script {
+                path: "ma_cross.py".to_string(),
+                language: "python".to_string(),
+            },
+            parameters,
+            budget: None,
+        }
+    }
+
+    #[test]
+    fn test_upgrade_and_rollback() {
+        let registry = StrategyRegistry::in_memory();
+        registry.deploy("ma_rb", package("1.0.0", 5)).unwrap();
+        let instance = registry.deploy("ma_rb", package("1.1.0", 8)).unwrap();
+        assert_eq!(instance.current_version, "1.1.0");
+        assert_eq!(instance.history[1].action, DeploymentAction::Upgrade);
+
+        // 已有版本号不能换内容，也不能换策略类型
+        assert!(registry.deploy("ma_rb", package("1.0.0", 6)).is_err());
+        let mut other = package("2.0.0", 5);
+        other.name = "grid".to_string();
+        assert!(registry.deploy("ma_rb", other).is_err());
+
+        let instance = registry.rollback("ma_rb", None).unwrap();
+        assert_eq!(instance.current_version, "1.0.0");
+        assert_eq!(instance.current_package().unwrap().parameters["fast"], 5);
+        // 再次回滚回到 1.1.0
+        assert_eq!(registry.rollback("ma_rb", None).unwrap().current_version, "1.1.0");
+        assert!(registry.rollback("ma_rb", Some("9.9.9")).is_err());
+        assert!(registry.rollback("unknown", None).is_err());
+    }
+
+    #[test]
+    fn test_load_manifest_and_persist() {
+        let dir = TempDir::new().unwrap();
+        let manifest = dir.path().join("strategy.json");
+        std::fs::write(
+            &manifest,
+            r#"{"name":"ma_cross","version":"1.0.0","entry":{"type":"plugin","path":"libma.so","symbol":"create"},
+                "parameters":{"fast":5},"budget":{"max_orders_per_day":100,"max_open_position":4,"max_loss":5000.0}}"#,
+        )
+        .unwrap();
+        let package = StrategyPackage::load(&manifest).unwrap();
+        assert_eq!(
+            package.entry,
+            StrategyEntryPoint::Plugin {
+                path: dir.path().join("libma.so").to_string_lossy().into_owned(),
+                symbol: "create".to_string(),
+            }
+        );
+
+        let path = dir.path().join("deployments.json");
+        StrategyRegistry::open(&path).unwrap().deploy("ma_rb", package).unwrap();
+        let restored = StrategyRegistry::open(&path).unwrap();
+        let instance = restored.get("ma_rb").unwrap();
+        assert_eq!(instance.current_version, "1.0.0");
+        assert_eq!(instance.current_package().unwrap().budget.as_ref().unwrap().max_open_position, 4);
+    }
+}
@@ -0,0 +1,874 @@
+use crate::ctp::{
+    data_quality::{DataQualityConfig, DataQualityIssue, DataQualityMonitor, DataQualityTransition},
+    models::MarketDataTick,
+    strategy_warmup::{HistorySource, WarmupConfig, WarmupLoader, WarmupReport, WarmupSeries},
+    tick_compaction::StorageGranularity,
+    trading_session::{BarWindow, SessionConfig, TradingSessions},
+    CtpError,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// 默认策略引擎配置文件
+pub const DEFAULT_STRATEGY_ENGINE_CONFIG_FILE: &str = "./config/strategy_engine.toml";
+
+/// 策略引擎配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyEngineConfig {
+    /// K 线收盘后等待迟到 tick 的毫秒数，超过后收盘并丢弃属于该 K 线的 tick
+    pub grace_ms: u64,
+    pub sessions: SessionConfig,
+    pub data_quality: DataQualityConfig,
+    /// 未单独配置的策略使用的自动暂停策略
+    pub auto_pause: AutoPausePolicy,
+    /// 按策略覆盖自动暂停策略
+    pub strategy_auto_pause: BTreeMap<String, AutoPausePolicy>,
+    /// 策略启动时的历史 K 线预热
+    pub warmup: WarmupConfig,
+}
+
+impl Default for StrategyEngineConfig {
+    fn default() -> Self {
+        Self {
+            grace_ms: 1500,
+            sessions: SessionConfig::default(),
+            data_quality: DataQualityConfig::default(),
+            auto_pause: AutoPausePolicy::default(),
+            strategy_auto_pause: BTreeMap::new(),
+            warmup: WarmupConfig::default(),
+        }
+    }
+}
+
+impl StrategyEngineConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("策略引擎配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        self.validate()?;
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("策略引擎配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.grace_ms > 60_000 {
+            return Err(CtpError::ConfigError("迟到 tick 等待时间不能超过 60 秒".to_string()));
+        }
+        self.data_quality.validate()?;
+        self.warmup.validate()?;
+        self.sessions.validate()
+    }
+
+    pub fn auto_pause_for(&self, strategy_id: &str) -> AutoPausePolicy {
+        self.strategy_auto_pause.get(strategy_id).copied().unwrap_or(self.auto_pause)
+    }
+}
+
+/// 行情异常时的自动暂停策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoPausePolicy {
+    /// 依赖的合约行情异常时暂停策略
+    pub enabled: bool,
+    /// 行情恢复后自动恢复，否则需手动恢复
+    pub auto_resume: bool,
+}
+
+impl Default for AutoPausePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_resume: true,
+        }
+    }
+}
+
+/// 策略因行情质量暂停或恢复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StrategyPauseEvent {
+    /// 调用方应撤销该策略在此合约上的挂单
+    Paused {
+        strategy_id: String,
+        instrument_id: String,
+        issue: DataQualityIssue,
+        at: NaiveDateTime,
+    },
+    /// 所有依赖合约均已恢复，手动恢复时 `at` 为空
+    Resumed {
+        strategy_id: String,
+        at: Option<NaiveDateTime>,
+    },
+}
+
+/// 策略关注的合约与周期
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BarSubscription {
+    pub instrument_id: String,
+    pub granularity: StorageGranularity,
+}
+
+impl BarSubscription {
+    pub fn new(instrument_id: &str, granularity: StorageGranularity) -> Self {
+        Self {
+            instrument_id: instrument_id.to_string(),
+            granularity,
+        }
+    }
+}
+
+/// 已收盘的 K 线
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyBar {
+    pub instrument_id: String,
+    pub granularity: StorageGranularity,
+    pub trading_day: NaiveDate,
+    pub start: NaiveDateTime,
+    /// 收盘时刻
+    pub end: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// 周期内成交量
+    pub volume: i64,
+    /// 周期内成交额
+    pub turnover: f64,
+    pub open_interest: i64,
+    pub tick_count: i32,
+}
+
+/// 策略运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StrategyMode {
+    /// 回放历史 K 线初始化指标，不应下单
+    Replay,
+    /// 接收实盘收盘的 K 线
+    Live,
+}
+
+/// 由引擎驱动的策略
+pub trait Strategy: Send {
+    /// 每根已收盘的 K 线恰好调用一次；同时收盘的多根按合约、周期从短到长依次调用
+    fn on_bar(&mut self, bar: &StrategyBar);
+
+    /// 因行情质量暂停或恢复时调用
+    fn on_pause_change(&mut self, _event: &StrategyPauseEvent) {}
+
+    /// 预热回放开始时以 `Replay` 调用，回放结束转入实盘时以 `Live` 调用
+    fn on_mode_change(&mut self, _mode: StrategyMode) {}
+}
+
+/// 尚未收盘（或处于迟到等待期）的 K 线
+struct OpenBar {
+    bar: StrategyBar,
+    /// 最后计入的 tick 时间，迟到但更早的 tick 不覆盖收盘价
+    last_tick: NaiveDateTime,
+}
+
+#[derive(Default)]
+struct Series {
+    /// 按收盘时刻排序
+    open: Vec<OpenBar>,
+    last_closed: Option<NaiveDateTime>,
+}
+
+impl Series {
+    /// 取出收盘时刻加等待期已到的 K 线
+    fn drain_due(&mut self, watermark: NaiveDateTime, grace: Duration, closed: &mut Vec<StrategyBar>) {
+        while self.open.first().is_some_and(|b| b.bar.end + grace <= watermark) {
+            let bar = self.open.remove(0).bar;
+            self.last_closed = Some(bar.end);
+            closed.push(bar);
+        }
+    }
+}
+
+/// K 线收盘调度
+///
+/// 按品种交易时段切分 K 线，收盘时刻过后再等待 `grace_ms` 接收迟到 tick，
+/// 由该合约后续 tick 的时间或定时器的本地时间推进，每根 K 线只产出一次。
+/// 等待期结束后到达的 tick 计入 `late_ticks` 并丢弃；没有 tick 的周期不生成 K 线
+pub struct BarScheduler {
+    config: StrategyEngineConfig,
+    sessions: HashMap<String, TradingSessions>,
+    series: HashMap<BarSubscription, Series>,
+    /// 各合约最近的累计成交量、成交额及所属交易日
+    cumulative: HashMap<String, (NaiveDate, i64, f64)>,
+    late_ticks: u64,
+}
+
+impl BarScheduler {
+    pub fn new(config: StrategyEngineConfig) -> Self {
+        Self {
+            config,
+            sessions: HashMap::new(),
+            series: HashMap::new(),
+            cumulative: HashMap::new(),
+            late_ticks: 0,
+        }
+    }
+
+    pub fn config(&self) -> &StrategyEngineConfig {
+        &self.config
+    }
+
+    pub fn subscribe(&mut self, subscription: &BarSubscription) -> Result<(), CtpError> {
+        if subscription.granularity == StorageGranularity::Tick {
+            return Err(CtpError::InvalidParameter("K 线订阅不支持 tick 粒度".to_string()));
+        }
+        if subscription.instrument_id.trim().is_empty() {
+            return Err(CtpError::InvalidParameter("订阅的合约代码不能为空".to_string()));
+        }
+        self.series.entry(subscription.clone()).or_default();
+        Ok(())
+    }
+
+    /// 取消订阅，未收盘的 K 线一并丢弃
+    pub fn unsubscribe(&mut self, subscription: &BarSubscription) {
+        self.series.remove(subscription);
+        if !self.series.keys().any(|s| s.instrument_id == subscription.instrument_id) {
+            self.cumulative.remove(&subscription.instrument_id);
+        }
+    }
+
+    pub fn subscriptions(&self) -> Vec<BarSubscription> {
+        self.series.keys().cloned().collect()
+    }
+
+    /// 因等待期已过而丢弃的 tick 数
+    pub fn late_ticks(&self) -> u64 {
+        self.late_ticks
+    }
+
+    fn grace(&self) -> Duration {
+        Duration::milliseconds(self.config.grace_ms as i64)
+    }
+
+    /// 处理一笔 tick，返回因此收盘的 K 线；`now` 为本地时间，用于补全 tick 的日期
+    pub fn on_tick(&mut self, tick: &MarketDataTick, now: NaiveDateTime) -> Vec<StrategyBar> {
+        let mut closed = Vec::new();
+        if !self.series.keys().any(|s| s.instrument_id == tick.instrument_id) {
+            return closed;
+        }
+        let Some(at) = tick_time(tick, now) else {
+            return closed;
+        };
+        let grace = self.grace();
+        let config = &self.config.sessions;
+        let sessions = self
+            .sessions
+            .entry(tick.instrument_id.clone())
+            .or_insert_with(|| config.sessions_for(&tick.instrument_id));
+        // 集合竞价前、休市时段的 tick 不参与 K 线
+        let Some(trading_day) = sessions.trading_day(at) else {
+            return closed;
+        };
+
+        let (volume, turnover) = match self.cumulative.get(&tick.instrument_id) {
+            // 乱序 tick 的累计量更小，不计增量也不回退基准
+            Some(&(day, v, t)) if day == trading_day => {
+                if tick.volume >= v {
+                    self.cumulative.insert(tick.instrument_id.clone(), (day, tick.volume, tick.turnover));
+                    (tick.volume - v, (tick.turnover - t).max(0.0))
+                } else {
+                    (0, 0.0)
+                }
+            }
+            // 换日后累计量从零开始
+            Some(_) => {
+                self.cumulative.insert(tick.instrument_id.clone(), (trading_day, tick.volume, tick.turnover));
+                (tick.volume, tick.turnover)
+            }
+            // 首笔 tick 只作基准
+            None => {
+                self.cumulative.insert(tick.instrument_id.clone(), (trading_day, tick.volume, tick.turnover));
+                (0, 0.0)
+            }
+        };
+
+        let mut late = false;
+        for (subscription, series) in self.series.iter_mut().filter(|(s, _)| s.instrument_id == tick.instrument_id) {
+            let Some(window) = sessions.bar_window(at, subscription.granularity) else {
+                continue;
+            };
+            if series.last_closed.is_some_and(|end| window.end <= end) {
+                late = true;
+                continue;
+            }
+            match series.open.iter_mut().find(|b| b.bar.end == window.end) {
+                Some(open) => open.update(tick, at, volume, turnover),
+                None => {
+                    let position = series.open.partition_point(|b| b.bar.end < window.end);
+                    series.open.insert(position, OpenBar::new(subscription, window, tick, at, volume, turnover));
+                }
+            }
+            series.drain_due(at, grace, &mut closed);
+        }
+        if late {
+            self.late_ticks += 1;
+        }
+        sort_closed(&mut closed);
+        closed
+    }
+
+    /// 按本地时间收盘没有后续 tick 推进的 K 线，如收盘前最后一根、成交稀疏的合约
+    pub fn on_timer(&mut self, now: NaiveDateTime) -> Vec<StrategyBar> {
+        let grace = self.grace();
+        let mut closed = Vec::new();
+        for series in self.series.values_mut() {
+            series.drain_due(now, grace, &mut closed);
+        }
+        sort_closed(&mut closed);
+        closed
+    }
+}
+
+impl OpenBar {
+    fn new(
+        subscription: &BarSubscription,
+        window: BarWindow,
+        tick: &MarketDataTick,
+        at: NaiveDateTime,
+        volume: i64,
+        turnover: f64,
+    ) -> Self {
+        Self {
+            bar: StrategyBar {
+                instrument_id: subscription.instrument_id.clone(),
+                granularity: subscription.granularity,
+                trading_day: window.trading_day,
+                start: window.start,
+                end: window.end,
+                open: tick.last_price,
+                high: tick.last_price,
+                low: tick.last_price,
+                close: tick.last_price,
+                volume,
+                turnover,
+                open_interest: tick.open_interest,
+                tick_count: 1,
+            },
+            last_tick: at,
+        }
+    }
+
+    fn update(&mut self, tick: &MarketDataTick, at: NaiveDateTime, volume: i64, turnover: f64) {
+        let bar = &mut self.bar;
+        bar.high = bar.high.max(tick.last_price);
+        bar.low = bar.low.min(tick.last_price);
+        bar.volume += volume;
+        bar.turnover += turnover;
+        bar.tick_count += 1;
+        if at >= self.last_tick {
+            bar.close = tick.last_price;
+            bar.open_interest = tick.open_interest;
+            self.last_tick = at;
+        }
+    }
+}
+
+/// 按收盘时刻、合约排序，同时收盘的短周期在前
+fn sort_closed(bars: &mut [StrategyBar]) {
+    bars.sort_by(|a, b| {
+        (a.end, &a.instrument_id, std::cmp::Reverse(a.start)).cmp(&(b.end, &b.instrument_id, std::cmp::Reverse(b.start)))
+    });
+}
+
+/// tick 的交易所时间只有时分秒，按本地时间补全日期，跨午夜时取最接近的一天
+fn tick_time(tick: &MarketDataTick, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let time = NaiveTime::parse_from_str(&tick.update_time, "%H:%M:%S").ok()?;
+    let at = now.date().and_time(time) + Duration::milliseconds(tick.update_millisec.max(0) as i64);
+    let diff = at - now;
+    Some(if diff > Duration::hours(12) {
+        at - Duration::days(1)
+    } else if diff < -Duration::hours(12) {
+        at + Duration::days(1)
+    } else {
+        at
+    })
+}
+
+struct RegisteredStrategy {
+    strategy: Box<dyn Strategy>,
+    subscriptions: Vec<BarSubscription>,
+    policy: AutoPausePolicy,
+    /// 导致暂停的异常合约
+    paused_by: BTreeMap<String, DataQualityIssue>,
+    /// 行情已恢复但等待手动恢复
+    awaiting_resume: bool,
+    /// 预热已回放到的收盘时刻，实盘不再重复推送
+    replayed_until: HashMap<BarSubscription, NaiveDateTime>,
+}
+
+impl RegisteredStrategy {
+    fn depends_on(&self, instrument_id: &str) -> bool {
+        self.subscriptions.iter().any(|s| s.instrument_id == instrument_id)
+    }
+
+    fn is_paused(&self) -> bool {
+        !self.paused_by.is_empty() || self.awaiting_resume
+    }
+}
+
+/// 策略引擎：登记策略关注的合约和周期，K 线收盘时回调 `on_bar`
+///
+/// 行情与定时器由调用方送入，引擎本身不持有线程。订阅合约的行情质量异常时，
+/// 按策略的 `AutoPausePolicy` 暂停依赖它的策略，暂停期间收盘的 K 线不再补发；
+/// 暂停与恢复通过 `take_pause_events` 交给调用方撤单和通知
+pub struct StrategyEngine {
+    scheduler: BarScheduler,
+    data_quality: DataQualityMonitor,
+    strategies: BTreeMap<String, RegisteredStrategy>,
+    pause_events: Vec<StrategyPauseEvent>,
+}
+
+impl StrategyEngine {
+    pub fn new(config: StrategyEngineConfig) -> Self {
+        Self {
+            data_quality: DataQualityMonitor::new(config.data_quality.clone(), config.sessions.clone()),
+            scheduler: BarScheduler::new(config),
+            strategies: BTreeMap::new(),
+            pause_events: Vec::new(),
+        }
+    }
+
+    pub fn scheduler(&self) -> &BarScheduler {
+        &self.scheduler
+    }
+
+    /// 登记策略，同一合约和周期被多个策略订阅时共用一组 K 线
+    pub fn register(
+        &mut self,
+        strategy_id: &str,
+        strategy: Box<dyn Strategy>,
+        subscriptions: Vec<BarSubscription>,
+    ) -> Result<(), CtpError> {
+        if self.strategies.contains_key(strategy_id) {
+            return Err(CtpError::InvalidParameter(format!("策略 {} 已登记", strategy_id)));
+        }
+        if subscriptions.is_empty() {
+            return Err(CtpError::InvalidParameter(format!("策略 {} 未订阅任何 K 线", strategy_id)));
+        }
+        if subscriptions.iter().any(|s| s.granularity == StorageGranularity::Tick) {
+            return Err(CtpError::InvalidParameter("K 线订阅不支持 tick 粒度".to_string()));
+        }
+        for subscription in &subscriptions {
+            self.scheduler.subscribe(subscription)?;
+            self.data_quality.watch(&subscription.instrument_id);
+        }
+        let policy = self.scheduler.config().auto_pause_for(strategy_id);
+        self.strategies.insert(
+            strategy_id.to_string(),
+            RegisteredStrategy {
+                strategy,
+                subscriptions,
+                policy,
+                paused_by: BTreeMap::new(),
+                awaiting_resume: false,
+                replayed_until: HashMap::new(),
+            },
+        );
+        // 依赖的合约已处于异常状态时立即暂停
+        let now = chrono::Local::now().naive_local();
+        for (instrument_id, issue) in self.data_quality.degraded() {
+            self.pause(strategy_id, &instrument_id, issue, now);
+        }
+        Ok(())
+    }
+
+    /// 登记策略并用本地历史行情预热
+    ///
+    /// 按 `warmup` 配置取每个订阅在 `now` 之前最近收盘的 K 线，以 `Replay` 模式依次回调 `on_bar`，
+    /// 结束后切换为 `Live`。读取历史失败时不登记；已回放的 K 线在实盘收盘时不再推送
+    pub fn register_with_warmup(
+        &mut self,
+        strategy_id: &str,
+        strategy: Box<dyn Strategy>,
+        subscriptions: Vec<BarSubscription>,
+        source: &dyn HistorySource,
+        now: NaiveDateTime,
+    ) -> Result<WarmupReport, CtpError> {
+        if self.strategies.contains_key(strategy_id) {
+            return Err(CtpError::InvalidParameter(format!("策略 {} 已登记", strategy_id)));
+        }
+        let config = self.scheduler.config();
+        let count = config.warmup.bars_for(strategy_id);
+        let loader = WarmupLoader::new(source, config);
+        let mut bars = Vec::new();
+        let mut series = Vec::new();
+        for subscription in &subscriptions {
+            let loaded = loader.load(subscription, count, now)?;
+            series.push(WarmupSeries {
+                subscription: subscription.clone(),
+                requested: count,
+                loaded: loaded.len(),
+                first_end: loaded.first().map(|b| b.end),
+                last_end: loaded.last().map(|b| b.end),
+            });
+            bars.extend(loaded);
+        }
+        sort_closed(&mut bars);
+
+        self.register(strategy_id, strategy, subscriptions)?;
+        let Some(registered) = self.strategies.get_mut(strategy_id) else {
+            return Err(CtpError::StateError(format!("策略 {} 登记失败", strategy_id)));
+        };
+        registered.strategy.on_mode_change(StrategyMode::Replay);
+        for bar in &bars {
+            registered.strategy.on_bar(bar);
+        }
+        for s in &series {
+            if let Some(end) = s.last_end {
+                registered.replayed_until.insert(s.subscription.clone(), end);
+            }
+        }
+        registered.strategy.on_mode_change(StrategyMode::Live);
+
+        let report = WarmupReport {
+            strategy_id: strategy_id.to_string(),
+            series,
+        };
+        if report.is_complete() {
+            tracing::info!("策略 {} 预热完成，回放 {} 根 K 线", strategy_id, report.total_bars());
+        } else {
+            tracing::warn!("策略 {} 历史行情不足，仅回放 {} 根 K 线", strategy_id, report.total_bars());
+        }
+        Ok(report)
+    }
+
+    /// 注销策略，不再被任何策略订阅的 K 线随之停止
+    pub fn unregister(&mut self, strategy_id: &str) -> bool {
+        let Some(removed) = self.strategies.remove(strategy_id) else {
+            return false;
+        };
+        for subscription in &removed.subscriptions {
+            if !self.strategies.values().any(|s| s.subscriptions.contains(subscription)) {
+                self.scheduler.unsubscribe(subscription);
+            }
+            if !self.strategies.values().any(|s| s.depends_on(&subscription.instrument_id)) {
+                self.data_quality.unwatch(&subscription.instrument_id);
+            }
+        }
+        true
+    }
+
+    pub fn strategy_ids(&self) -> Vec<String> {
+        self.strategies.keys().cloned().collect()
+    }
+
+    pub fn subscriptions(&self, strategy_id: &str) -> Option<&[BarSubscription]> {
+        self.strategies.get(strategy_id).map(|s| s.subscriptions.as_slice())
+    }
+
+    pub fn is_paused(&self, strategy_id: &str) -> bool {
+        self.strategies.get(strategy_id).is_some_and(RegisteredStrategy::is_paused)
+    }
+
+    /// 当前行情异常的合约
+    pub fn degraded_instruments(&self) -> Vec<(String, DataQualityIssue)> {
+        self.data_quality.degraded()
+    }
+
+    /// 取出尚未处理的暂停与恢复事件
+    pub fn take_pause_events(&mut self) -> Vec<StrategyPauseEvent> {
+        std::mem::take(&mut self.pause_events)
+    }
+
+    /// 手动恢复不自动恢复的策略；依赖的合约仍异常时拒绝
+    pub fn resume(&mut self, strategy_id: &str) -> Result<bool, CtpError> {
+        let registered = self
+            .strategies
+            .get_mut(strategy_id)
+            .ok_or_else(|| CtpError::NotFound(format!("策略 {} 未登记", strategy_id)))?;
+        if let Some(instrument_id) = registered.paused_by.keys().next() {
+            return Err(CtpError::StateError(format!(
+                "策略 {} 依赖的合约 {} 行情仍异常",
+                strategy_id, instrument_id
+            )));
+        }
+        if !registered.awaiting_resume {
+            return Ok(false);
+        }
+        registered.awaiting_resume = false;
+        let event = StrategyPauseEvent::Resumed {
+            strategy_id: strategy_id.to_string(),
+            at: None,
+        };
+        registered.strategy.on_pause_change(&event);
+        self.pause_events.push(event);
+        Ok(true)
+    }
+
+    pub fn on_tick(&mut self, tick: &MarketDataTick, now: NaiveDateTime) -> Vec<StrategyBar> {
+        if self.strategies.values().any(|s| s.depends_on(&tick.instrument_id)) {
+            if let Some(transition) = self.data_quality.on_tick(tick, now) {
+                self.apply_data_quality(&transition);
+            }
+        }
+        let bars = self.scheduler.on_tick(tick, now);
+        self.dispatch(&bars);
+        bars
+    }
+
+    /// 收盘到期的 K 线并检查行情停滞与恢复
+    pub fn on_timer(&mut self, now: NaiveDateTime) -> Vec<StrategyBar> {
+        for transition in self.data_quality.on_timer(now) {
+            self.apply_data_quality(&transition);
+        }
+        let bars = self.scheduler.on_timer(now);
+        self.dispatch(&bars);
+        bars
+    }
+
+    fn apply_data_quality(&mut self, transition: &DataQualityTransition) {
+        match transition {
+            DataQualityTransition::Degraded { instrument_id, issue, at } => {
+                let ids: Vec<String> = self.strategies.keys().cloned().collect();
+                for strategy_id in ids {
+                    self.pause(&strategy_id, instrument_id, *issue, *at);
+                }
+            }
+            DataQualityTransition::Recovered { instrument_id, at } => {
+                for (strategy_id, registered) in self.strategies.iter_mut() {
+                    if registered.paused_by.remove(instrument_id).is_none() || !registered.paused_by.is_empty() {
+                        continue;
+                    }
+                    if !registered.policy.auto_resume {
+                        tracing::info!("策略 {} 依赖的行情已恢复，等待手动恢复", strategy_id);
+                        continue;
+                    }
+                    registered.awaiting_resume = false;
+                    let event = StrategyPauseEvent::Resumed {
+                        strategy_id: strategy_id.clone(),
+                        at: Some(*at),
+                    };
+                    tracing::info!("策略 {} 依赖的行情已恢复，自动恢复", strategy_id);
+                    registered.strategy.on_pause_change(&event);
+                    self.pause_events.push(event);
+                }
+            }
+        }
+    }
+
+    fn pause(&mut self, strategy_id: &str, instrument_id: &str, issue: DataQualityIssue, at: NaiveDateTime) {
+        let Some(registered) = self.strategies.get_mut(strategy_id) else {
+            return;
+        };
+        if !registered.policy.enabled || !registered.depends_on(instrument_id) {
+            return;
+        }
+        if registered.paused_by.insert(instrument_id.to_string(), issue).is_some() {
+            return;
+        }
+        registered.awaiting_resume = !registered.policy.auto_resume;
+        tracing::warn!("合约 {} 行情异常（{:?}），暂停策略 {}", instrument_id, issue, strategy_id);
+        let event = StrategyPauseEvent::Paused {
+            strategy_id: strategy_id.to_string(),
+            instrument_id: instrument_id.to_string(),
+            issue,
+            at,
+        };
+        registered.strategy.on_pause_change(&event);
+        self.pause_events.push(event);
+    }
+
+    /// 暂停中的策略不接收 K 线，预热已回放的也不再推送
+    fn dispatch(&mut self, bars: &[StrategyBar]) {
+        for bar in bars {
+            for registered in self.strategies.values_mut().filter(|s| !s.is_paused()) {
+                let Some(subscription) = registered
+                    .subscriptions
+                    .iter()
+                    .find(|s| s.instrument_id == bar.instrument_id && s.granularity == bar.granularity)
+                else {
+                    continue;
+                };
+                if registered.replayed_until.get(subscription).is_some_and(|end| bar.end <= *end) {
+                    continue;
+                }
+                registered.strategy.on_bar(bar);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<StrategyBar>>>);
+
+    impl Strategy for Recorder {
+        fn on_bar(&mut self, bar: &StrategyBar) {
+            self.0.lock().unwrap().push(bar.clone());
+        }
+    }
+
+    fn at(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 3).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    fn tick(time: &str, price: f64, volume: i64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2505".to_string(),
+            last_price: price,
+            volume,
+            turnover: volume as f64 * 10.0,
+            open_interest: 1000,
+            bid_price1: price - 1.0,
+            bid_volume1: 1,
+            ask_price1: price + 1.0,
+            ask_volume1: 1,
+            update_time: time.to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_bar_closes_once_after_grace() {
+        let mut scheduler = BarScheduler::new(StrategyEngineConfig { grace_ms: 2000, ..Default::default() });
+        scheduler.subscribe(&BarSubscription::new("rb2505", StorageGranularity::Bar1m)).unwrap();
+
+        assert!(scheduler.on_tick(&tick("09:00:10", 3500.0, 100), at(9, 0, 10)).is_empty());
+        assert!(scheduler.on_tick(&tick("09:00:50", 3505.0, 110), at(9, 0, 50)).is_empty());
+        // 下一根已开始，但仍在等待期内
+        assert!(scheduler.on_tick(&tick("09:01:01", 3503.0, 115), at(9, 1, 1)).is_empty());
+        // 迟到 tick 仍计入上一根
+        assert!(scheduler.on_tick(&tick("09:00:59", 3508.0, 112), at(9, 1, 1)).is_empty());
+
+        let closed = scheduler.on_tick(&tick("09:01:03", 3502.0, 120), at(9, 1, 3));
+        assert_eq!(closed.len(), 1);
+        let bar = &closed[0];
+        assert_eq!((bar.start, bar.end), (at(9, 0, 0), at(9, 1, 0)));
+        assert_eq!((bar.open, bar.high, bar.low, bar.close), (3500.0, 3508.0, 3500.0, 3508.0));
+        assert_eq!((bar.volume, bar.tick_count), (10, 3));
+
+        // 等待期过后的迟到 tick 丢弃，已收盘的 K 线不再产出
+        assert!(scheduler.on_tick(&tick("09:00:30", 3600.0, 113), at(9, 1, 4)).is_empty());
+        assert_eq!(scheduler.late_ticks(), 1);
+        assert!(scheduler.on_timer(at(9, 1, 10)).is_empty());
+
+        let closed = scheduler.on_timer(at(9, 2, 2));
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].start, closed[0].close, closed[0].volume), (at(9, 1, 0), 3502.0, 10));
+        assert!(scheduler.on_timer(at(9, 5, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_engine_dispatches_session_aligned_bars() {
+        let mut engine = StrategyEngine::new(StrategyEngineConfig::default());
+        let fast = Arc::new(Mutex::new(Vec::new()));
+        let slow = Arc::new(Mutex::new(Vec::new()));
+        engine
+            .register("fast", Box::new(Recorder(fast.clone())), vec![BarSubscription::new("rb2505", StorageGranularity::Bar1m)])
+            .unwrap();
+        engine
+            .register(
+                "slow",
+                Box::new(Recorder(slow.clone())),
+                vec![
+                    BarSubscription::new("rb2505", StorageGranularity::Bar5m),
+                    BarSubscription::new("rb2505", StorageGranularity::Bar1m),
+                ],
+            )
+            .unwrap();
+        assert!(engine.register("fast", Box::new(Recorder(fast.clone())), vec![]).is_err());
+
+        engine.on_tick(&tick("10:14:30", 3500.0, 100), at(10, 14, 30));
+        // 收盘 tick 归入小节休息前最后一根
+        engine.on_tick(&tick("10:15:00", 3510.0, 130), at(10, 15, 0));
+        // 休息时段的 tick 不产生 K 线
+        engine.on_tick(&tick("10:20:00", 3520.0, 130), at(10, 20, 0));
+
+        let closed = engine.on_timer(at(10, 15, 2));
+        assert_eq!(closed.iter().map(|b| b.granularity).collect::<Vec<_>>(), vec![StorageGranularity::Bar1m, StorageGranularity::Bar5m]);
+        assert_eq!(closed[1].start, at(10, 10, 0));
+        assert_eq!(closed[1].end, at(10, 15, 0));
+        assert_eq!(closed[1].close, 3510.0);
+
+        assert_eq!(fast.lock().unwrap().len(), 1);
+        assert_eq!(slow.lock().unwrap().len(), 2);
+
+        // 1 分钟线仍被 slow 订阅，5 分钟线随 slow 注销而停止
+        assert!(engine.unregister("fast"));
+        assert_eq!(engine.scheduler().subscriptions().len(), 2);
+        assert!(engine.unregister("slow"));
+        assert!(engine.scheduler().subscriptions().is_empty());
+        assert!(engine.on_tick(&tick("10:31:00", 3500.0, 140), at(10, 31, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_degraded_data_pauses_and_resumes_strategies() {
+        let mut config = StrategyEngineConfig::default();
+        config.strategy_auto_pause.insert("manual".to_string(), AutoPausePolicy { enabled: true, auto_resume: false });
+        config.strategy_auto_pause.insert("ignore".to_string(), AutoPausePolicy { enabled: false, auto_resume: true });
+        let mut engine = StrategyEngine::new(config);
+        let bars = Arc::new(Mutex::new(Vec::new()));
+        for id in ["auto", "manual", "ignore"] {
+            engine
+                .register(id, Box::new(Recorder(bars.clone())), vec![BarSubscription::new("rb2505", StorageGranularity::Bar1m)])
+                .unwrap();
+        }
+
+        engine.on_tick(&tick("09:00:10", 3500.0, 100), at(9, 0, 10));
+        engine.on_tick(&tick("09:00:20", 4000.0, 110), at(9, 0, 20));
+        let events = engine.take_pause_events();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            StrategyPauseEvent::Paused { strategy_id, instrument_id, issue: DataQualityIssue::PriceJump, .. }
+                if strategy_id == "auto" && instrument_id == "rb2505"
+        ));
+        assert!(engine.is_paused("manual") && !engine.is_paused("ignore"));
+        assert!(engine.resume("manual").is_err());
+
+        // 暂停期间只有未启用自动暂停的策略收到 K 线
+        engine.on_tick(&tick("09:00:30", 4001.0, 120), at(9, 0, 30));
+        engine.on_tick(&tick("09:00:55", 4002.0, 130), at(9, 0, 55));
+        assert_eq!(engine.on_timer(at(9, 1, 2)).len(), 1);
+        assert_eq!(bars.lock().unwrap().len(), 1);
+
+        // 持续正常满 60 秒后恢复，manual 等待手动恢复
+        engine.on_tick(&tick("09:01:20", 4003.0, 140), at(9, 1, 20));
+        engine.on_tick(&tick("09:01:30", 4004.0, 150), at(9, 1, 30));
+        assert_eq!(
+            engine.take_pause_events(),
+            vec![StrategyPauseEvent::Resumed { strategy_id: "auto".to_string(), at: Some(at(9, 1, 30)) }]
+        );
+        assert!(engine.is_paused("manual"));
+        assert!(engine.resume("manual").unwrap());
+        assert!(!engine.resume("auto").unwrap());
+
+        engine.on_tick(&tick("09:01:50", 4005.0, 160), at(9, 1, 50));
+        engine.on_timer(at(9, 2, 2));
+        assert_eq!(bars.lock().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,504 @@
+use crate::ctp::{CtpError, OrderDirection, OrderRequest, OffsetFlag, TradeRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// 订单标签中标识策略的键
+pub const STRATEGY_TAG: &str = "strategy";
+
+/// 单个策略的风险预算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct StrategyBudget {
+    /// 每日最多报单数
+    pub max_orders_per_day: u32,
+    /// 最大持仓手数（各合约净持仓绝对值之和）
+    pub max_open_position: i32,
+    /// 最大亏损（已实现 + 浮动），正数
+    pub max_loss: f64,
+}
+
+impl Default for StrategyBudget {
+    fn default() -> Self {
+        Self {
+            max_orders_per_day: 500,
+            max_open_position: 10,
+            max_loss: 10_000.0,
+        }
+    }
+}
+
+/// 策略熔断状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "state", content = "detail")]
+pub enum BreakerState {
+    /// 正常运行
+    Active,
+    /// 已熔断，需人工复位
+    Tripped {
+        reason: String,
+        tripped_at: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl BreakerState {
+    pub fn is_tripped(&self) -> bool {
+        matches!(self, BreakerState::Tripped { .. })
+    }
+}
+
+/// 策略运行状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct StrategyStatus {
+    pub name: String,
+    pub budget: StrategyBudget,
+    pub breaker: BreakerState,
+    pub trading_day: chrono::NaiveDate,
+    pub orders_today: u32,
+    pub open_position: i32,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+}
+
+/// 单合约净持仓（多为正、空为负）
+#[derive(Debug, Clone, Default)]
+struct InstrumentBook {
+    net: i32,
+    avg_price: f64,
+    last_price: f64,
+}
+
+impl InstrumentBook {
+    /// 计入一笔成交，返回平仓产生的已实现盈亏（价格点数 × 手数）
+    fn apply(&mut self, signed_volume: i32, price: f64) -> f64 {
+        self.last_price = price;
+        if self.net == 0 || self.net.signum() == signed_volume.signum() {
+            let total = self.net.abs() + signed_volume.abs();
+            self.avg_price = (self.avg_price * self.net.abs() as f64 + price * signed_volume.abs() as f64) / total as f64;
+            self.net += signed_volume;
+            return 0.0;
+        }
+
+        let closed = signed_volume.abs().min(self.net.abs());
+        let realized = (price - self.avg_price) * closed as f64 * self.net.signum() as f64;
+        self.net += signed_volume;
+        if self.net != 0 && self.net.signum() == signed_volume.signum() {
+            // 反手后剩余部分按本次成交价开仓
+            self.avg_price = price;
+        } else if self.net == 0 {
+            self.avg_price = 0.0;
+        }
+        realized
+    }
+
+    fn unrealized(&self) -> f64 {
+        (self.last_price - self.avg_price) * self.net as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StrategyEntry {
+    budget: StrategyBudget,
+    breaker: BreakerState,
+    trading_day: chrono::NaiveDate,
+    orders_today: u32,
+    books: HashMap<String, InstrumentBook>,
+    /// 已实现盈亏（价格点数 × 手数 × 合约乘数）
+    realized_pnl: f64,
+}
+
+impl StrategyEntry {
+    fn new(budget: StrategyBudget) -> Self {
+        Self {
+            budget,
+            breaker: BreakerState::Active,
+            trading_day: chrono::Local::now().date_naive(),
+            orders_today: 0,
+            books: HashMap::new(),
+            realized_pnl: 0.0,
+        }
+    }
+
+    /// 跨日后重置报单计数
+    fn roll_day(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        if self.trading_day != today {
+            self.trading_day = today;
+            self.orders_today = 0;
+        }
+    }
+
+    fn open_position(&self) -> i32 {
+        self.books.values().map(|b| b.net.abs()).sum()
+    }
+
+    fn unrealized_pnl(&self, multipliers: &HashMap<String, f64>) -> f64 {
+        self.books
+            .iter()
+            .map(|(id, book)| book.unrealized() * multipliers.get(id).copied().unwrap_or(1.0))
+            .sum()
+    }
+
+    /// 检查持仓与亏损预算，超出时返回熔断原因
+    fn check_limits(&self, multipliers: &HashMap<String, f64>) -> Option<String> {
+        let open_position = self.open_position();
+        if open_position > self.budget.max_open_position {
+            return Some(format!("持仓 {} 手超过上限 {} 手", open_position, self.budget.max_open_position));
+        }
+        let pnl = self.realized_pnl + self.unrealized_pnl(multipliers);
+        if pnl < -self.budget.max_loss {
+            return Some(format!("亏损 {:.2} 超过上限 {:.2}", -pnl, self.budget.max_loss));
+        }
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+struct GuardInner {
+    strategies: HashMap<String, StrategyEntry>,
+    /// 合约乘数，未设置时按 1 计算
+    multipliers: HashMap<String, f64>,
+    /// 新熔断、尚未处理撤单的策略
+    newly_tripped: Vec<String>,
+}
+
+impl GuardInner {
+    fn trip(&mut self, name: &str, reason: String) {
+        if let Some(entry) = self.strategies.get_mut(name) {
+            if entry.breaker.is_tripped() {
+                return;
+            }
+            warn!("策略 {} 触发熔断: {}", name, reason);
+            entry.breaker = BreakerState::Tripped {
+                reason,
+                tripped_at: chrono::Utc::now(),
+            };
+            self.newly_tripped.push(name.to_string());
+        }
+    }
+}
+
+/// 策略沙箱
+///
+/// 按订单的 `strategy` 标签为每个已注册策略记账，超出预算时熔断该策略，
+/// 拒绝其后续报单，直到人工复位；未注册或未打标签的订单不受限制
+#[derive(Debug, Clone, Default)]
+pub struct StrategyGuard {
+    inner: Arc<Mutex<GuardInner>>,
+}
+
+impl StrategyGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订单所属策略
+    pub fn strategy_of(tags: &crate::ctp::OrderTags) -> Option<&str> {
+        tags.get(STRATEGY_TAG).map(String::as_str)
+    }
+
+    /// 注册策略或更新预算（保留已有统计）
+    pub fn register(&self, name: &str, budget: StrategyBudget) {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.strategies.get_mut(name) {
+            Some(entry) => entry.budget = budget,
+            None => {
+                inner.strategies.insert(name.to_string(), StrategyEntry::new(budget));
+            }
+        }
+        info!("注册策略预算: {}", name);
+    }
+
+    /// 注销策略
+    pub fn unregister(&self, name: &str) {
+        self.inner.lock().unwrap().strategies.remove(name);
+    }
+
+    /// 设置合约乘数，用于计算盈亏金额
+    pub fn set_contract_multiplier(&self, instrument_id: &str, multiplier: f64) {
+        self.inner
+            .lock()
+            .unwrap()
+            .multipliers
+            .insert(instrument_id.to_string(), multiplier);
+    }
+
+    /// 报单前检查，不计入当日报单数；报单发出后由 [`Self::record_order`] 计数
+    pub fn check_order(&self, order: &OrderRequest) -> Result<(), CtpError> {
+        let Some(name) = Self::strategy_of(&order.tags) else {
+            return Ok(());
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.strategies.get_mut(name) else {
+            return Ok(());
+        };
+        entry.roll_day();
+
+        if let BreakerState::Tripped { reason, .. } = &entry.breaker {
+            return Err(CtpError::RiskControl(format!("策略 {} 已熔断: {}", name, reason)));
+        }
+
+        if entry.orders_today >= entry.budget.max_orders_per_day {
+            let reason = format!("当日报单数达到上限 {}", entry.budget.max_orders_per_day);
+            inner.trip(name, reason.clone());
+            return Err(CtpError::RiskControl(format!("策略 {} 已熔断: {}", name, reason)));
+        }
+
+        if order.offset_flag == OffsetFlag::Open
+            && entry.open_position() + order.volume as i32 > entry.budget.max_open_position
+        {
+            return Err(CtpError::RiskControl(format!(
+                "策略 {} 开仓后持仓将超过上限 {} 手",
+                name, entry.budget.max_open_position
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 报单已成功发出，计入策略当日报单数；被拒或发送失败的报单不占用预算
+    pub fn record_order(&self, order: &OrderRequest) {
+        let Some(name) = Self::strategy_of(&order.tags) else {
+            return;
+        };
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.strategies.get_mut(name) {
+            entry.roll_day();
+            entry.orders_today += 1;
+        }
+    }
+
+    /// 记录成交（需已带有订单标签），超出预算时熔断
+    pub fn on_trade(&self, trade: &TradeRecord) {
+        let Some(name) = Self::strategy_of(&trade.tags) else {
+            return;
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        let multiplier = inner.multipliers.get(&trade.instrument_id).copied().unwrap_or(1.0);
+        let GuardInner { strategies, multipliers, .. } = &mut *inner;
+        let Some(entry) = strategies.get_mut(name) else {
+            return;
+        };
+
+        let signed_volume = match trade.direction {
+            OrderDirection::Buy => trade.volume,
+            OrderDirection::Sell => -trade.volume,
+        };
+        let realized = entry
+            .books
+            .entry(trade.instrument_id.clone())
+            .or_default()
+            .apply(signed_volume, trade.price);
+        entry.realized_pnl += realized * multiplier;
+
+        if let Some(reason) = entry.check_limits(multipliers) {
+            inner.trip(name, reason);
+        }
+    }
+
+    /// 策略在合约上的净持仓（多为正、空为负）和持仓均价
+    pub fn position(&self, name: &str, instrument_id: &str) -> Option<(i32, f64)> {
+        let inner = self.inner.lock().unwrap();
+        let book = inner.strategies.get(name)?.books.get(instrument_id)?;
+        Some((book.net, book.avg_price))
+    }
+
+    /// 更新最新价，浮动亏损超出预算时熔断
+    pub fn update_price(&self, instrument_id: &str, price: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut to_trip = Vec::new();
+        {
+            let GuardInner { strategies, multipliers, .. } = &mut *inner;
+            for (name, entry) in strategies.iter_mut() {
+                let Some(book) = entry.books.get_mut(instrument_id) else {
+                    continue;
+                };
+                book.last_price = price;
+                if !entry.breaker.is_tripped() {
+                    if let Some(reason) = entry.check_limits(multipliers) {
+                        to_trip.push((name.clone(), reason));
+                    }
+                }
+            }
+        }
+        for (name, reason) in to_trip {
+            inner.trip(&name, reason);
+        }
+    }
+
+    /// 人工熔断
+    pub fn trip(&self, name: &str, reason: &str) -> Result<(), CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.strategies.contains_key(name) {
+            return Err(CtpError::NotFound(format!("策略未注册: {}", name)));
+        }
+        inner.trip(name, reason.to_string());
+        Ok(())
+    }
+
+    /// 人工复位熔断
+    pub fn reset(&self, name: &str) -> Result<(), CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner
+            .strategies
+            .get_mut(name)
+            .ok_or_else(|| CtpError::NotFound(format!("策略未注册: {}", name)))?;
+        entry.breaker = BreakerState::Active;
+        inner.newly_tripped.retain(|n| n != name);
+        info!("策略 {} 熔断已复位", name);
+        Ok(())
+    }
+
+    pub fn is_tripped(&self, name: &str) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .strategies
+            .get(name)
+            .map_or(false, |e| e.breaker.is_tripped())
+    }
+
+    /// 取出新熔断的策略，由调用方撤销其挂单
+    pub fn take_newly_tripped(&self) -> Vec<String> {
+        std::mem::take(&mut self.inner.lock().unwrap().newly_tripped)
+    }
+
+    /// 策略状态
+    pub fn status(&self, name: &str) -> Option<StrategyStatus> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .strategies
+            .get(name)
+            .map(|entry| Self::build_status(name, entry, &inner.multipliers))
+    }
+
+    /// 全部策略状态
+    pub fn all_statuses(&self) -> Vec<StrategyStatus> {
+        let inner = self.inner.lock().unwrap();
+        let mut statuses: Vec<_> = inner
+            .strategies
+            .iter()
+            .map(|(name, entry)| Self::build_status(name, entry, &inner.multipliers))
+            .collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    fn build_status(name: &str, entry: &StrategyEntry, multipliers: &HashMap<String, f64>) -> StrategyStatus {
+        StrategyStatus {
+            name: name.to_string(),
+            budget: entry.budget.clone(),
+            breaker: entry.breaker.clone(),
+            trading_day: entry.trading_day,
+            orders_today: entry.orders_today,
+            open_position: entry.open_position(),
+            realized_pnl: entry.realized_pnl,
+            unrealized_pnl: entry.unrealized_pnl(multipliers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{
+        OrderContingentCondition, OrderForceCloseReason, OrderPriceType, OrderTags, OrderTimeCondition,
+        OrderType, OrderVolumeCondition,
+    };
+
+    fn tags(strategy: &str) -> OrderTags {
+        let mut tags = OrderTags::new();
+        tags.insert(STRATEGY_TAG.to_string(), strategy.to_string());
+        tags
+    }
+
+    fn order(strategy: &str, volume: u32) -> OrderRequest {
+        OrderRequest {
+            instrument_id: "rb2501".to_string(),
+            order_ref: String::new(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume,
+            order_type: OrderType::Limit,
+            price_type: OrderPriceType::Limit,
+            time_condition: OrderTimeCondition::GFD,
+            volume_condition: OrderVolumeCondition::Any,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags: tags(strategy),
+        }
+    }
+
+    fn trade(strategy: &str, direction: OrderDirection, price: f64, volume: i32) -> TradeRecord {
+        TradeRecord {
+            trade_id: uuid::Uuid::new_v4().to_string(),
+            order_id: "1".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction,
+            offset_flag: OffsetFlag::Open,
+            price,
+            volume,
+            trade_time: "09:30:00".to_string(),
+            exchange_id: String::new(),
+            tags: tags(strategy),
+        }
+    }
+
+    #[test]
+    fn test_order_budget_trips_breaker() {
+        let guard = StrategyGuard::new();
+        guard.register("breakout", StrategyBudget { max_orders_per_day: 2, ..StrategyBudget::default() });
+
+        // 只检查未发出的报单不占用预算
+        for _ in 0..3 {
+            assert!(guard.check_order(&order("breakout", 1)).is_ok());
+        }
+        assert_eq!(guard.status("breakout").unwrap().orders_today, 0);
+        for _ in 0..2 {
+            assert!(guard.check_order(&order("breakout", 1)).is_ok());
+            guard.record_order(&order("breakout", 1));
+        }
+        assert!(guard.check_order(&order("breakout", 1)).is_err());
+        assert!(guard.is_tripped("breakout"));
+        assert_eq!(guard.take_newly_tripped(), vec!["breakout".to_string()]);
+
+        // 熔断期间持续拒单，直到人工复位
+        assert!(guard.check_order(&order("breakout", 1)).is_err());
+        guard.reset("breakout").unwrap();
+        assert!(!guard.is_tripped("breakout"));
+
+        // 未注册的策略不受限制
+        assert!(guard.check_order(&order("other", 100)).is_ok());
+    }
+
+    #[test]
+    fn test_loss_budget_trips_breaker() {
+        let guard = StrategyGuard::new();
+        guard.register("mean_revert", StrategyBudget { max_loss: 500.0, ..StrategyBudget::default() });
+        guard.set_contract_multiplier("rb2501", 10.0);
+
+        guard.on_trade(&trade("mean_revert", OrderDirection::Buy, 3500.0, 2));
+        guard.on_trade(&trade("mean_revert", OrderDirection::Sell, 3490.0, 1));
+        let status = guard.status("mean_revert").unwrap();
+        assert_eq!(status.open_position, 1);
+        assert_eq!(status.realized_pnl, -100.0);
+        assert!(!guard.is_tripped("mean_revert"));
+
+        // 浮亏 (3460 - 3500) * 1 * 10 = -400，合计 -500 未超过
+        guard.update_price("rb2501", 3460.0);
+        assert!(!guard.is_tripped("mean_revert"));
+
+        guard.update_price("rb2501", 3450.0);
+        assert!(guard.is_tripped("mean_revert"));
+    }
+}
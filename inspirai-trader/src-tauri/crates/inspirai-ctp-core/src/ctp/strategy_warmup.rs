@@ -0,0 +1,519 @@
+use crate::ctp::{
+    bar_import::{previous_trading_day, trading_day_of},
+    models::MarketDataTick,
+    storage::StorageBackend,
+    strategy_engine::{BarScheduler, BarSubscription, StrategyBar, StrategyEngineConfig},
+    tick_compaction::{ArchivedBar, StorageGranularity, TickCompactor},
+    trading_session::TradingSessions,
+    CtpError,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 由粗到细的 K 线周期，预热时可用更细周期合成
+const BAR_GRANULARITIES: [StorageGranularity; 7] = [
+    StorageGranularity::Bar1d,
+    StorageGranularity::Bar1h,
+    StorageGranularity::Bar30m,
+    StorageGranularity::Bar15m,
+    StorageGranularity::Bar5m,
+    StorageGranularity::Bar1m,
+    StorageGranularity::Bar1s,
+];
+
+/// 策略启动预热配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarmupConfig {
+    /// 每个订阅回放的 K 线根数，0 表示不预热
+    pub bars: usize,
+    /// 最多向前查找的交易日数
+    pub max_lookback_days: usize,
+    /// 按策略覆盖回放根数
+    pub strategy_bars: BTreeMap<String, usize>,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            bars: 100,
+            max_lookback_days: 30,
+            strategy_bars: BTreeMap::new(),
+        }
+    }
+}
+
+impl WarmupConfig {
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.bars.max(self.strategy_bars.values().copied().max().unwrap_or(0)) > 100_000 {
+            return Err(CtpError::ConfigError("预热 K 线根数不能超过 100000".to_string()));
+        }
+        if self.max_lookback_days > 366 {
+            return Err(CtpError::ConfigError("预热回溯天数不能超过 366".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn bars_for(&self, strategy_id: &str) -> usize {
+        self.strategy_bars.get(strategy_id).copied().unwrap_or(self.bars)
+    }
+}
+
+/// 预热用的本地历史行情
+pub trait HistorySource {
+    /// 有历史数据的交易日，升序
+    fn trading_days(&self) -> Result<Vec<NaiveDate>, CtpError>;
+
+    /// 交易日某一粒度的 K 线，没有数据时为空
+    fn bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+    ) -> Result<Vec<ArchivedBar>, CtpError>;
+
+    /// 交易日的 tick，没有数据时为空
+    fn ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError>;
+
+    /// 尚未归档的最近 tick，按接收顺序
+    fn pending_ticks(&self, _instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        Ok(Vec::new())
+    }
+}
+
+/// 列式归档（含导入的 K 线）与当日原始 tick
+impl HistorySource for TickCompactor {
+    fn trading_days(&self) -> Result<Vec<NaiveDate>, CtpError> {
+        self.archived_days()
+    }
+
+    fn bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+    ) -> Result<Vec<ArchivedBar>, CtpError> {
+        self.read_bars(trading_day, instrument_id, granularity)
+    }
+
+    fn ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        self.read_ticks(trading_day, instrument_id)
+    }
+
+    fn pending_ticks(&self, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        self.read_pending_ticks(instrument_id)
+    }
+}
+
+impl HistorySource for Arc<dyn StorageBackend> {
+    fn trading_days(&self) -> Result<Vec<NaiveDate>, CtpError> {
+        let mut days: Vec<NaiveDate> = self.series()?.into_iter().map(|s| s.trading_day).collect();
+        days.dedup();
+        Ok(days)
+    }
+
+    fn bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+    ) -> Result<Vec<ArchivedBar>, CtpError> {
+        self.load_bars(trading_day, instrument_id, granularity)
+    }
+
+    fn ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        self.load_ticks(trading_day, instrument_id)
+    }
+}
+
+/// 单个订阅的预热结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmupSeries {
+    pub subscription: BarSubscription,
+    pub requested: usize,
+    pub loaded: usize,
+    /// 回放的第一根 K 线收盘时刻
+    pub first_end: Option<NaiveDateTime>,
+    /// 回放的最后一根 K 线收盘时刻，实盘从其后开始推送
+    pub last_end: Option<NaiveDateTime>,
+}
+
+/// 策略预热结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmupReport {
+    pub strategy_id: String,
+    pub series: Vec<WarmupSeries>,
+}
+
+impl WarmupReport {
+    pub fn total_bars(&self) -> usize {
+        self.series.iter().map(|s| s.loaded).sum()
+    }
+
+    /// 每个订阅都取到了要求的根数
+    pub fn is_complete(&self) -> bool {
+        self.series.iter().all(|s| s.loaded >= s.requested)
+    }
+}
+
+/// 从本地历史行情生成预热 K 线
+///
+/// 当日先用尚未归档的 tick 重新生成，再按交易日倒序读取归档：优先同周期 K 线（含导入的 K 线），
+/// 其次由更细周期合成，都没有时用归档 tick 重新生成。K 线按交易时段切分，与实盘产出的一致
+pub struct WarmupLoader<'a> {
+    source: &'a dyn HistorySource,
+    config: &'a StrategyEngineConfig,
+}
+
+impl<'a> WarmupLoader<'a> {
+    pub fn new(source: &'a dyn HistorySource, config: &'a StrategyEngineConfig) -> Self {
+        Self { source, config }
+    }
+
+    /// 订阅在 `now` 之前已收盘的最近 `count` 根 K 线，按收盘时刻升序
+    pub fn load(
+        &self,
+        subscription: &BarSubscription,
+        count: usize,
+        now: NaiveDateTime,
+    ) -> Result<Vec<StrategyBar>, CtpError> {
+        if subscription.granularity == StorageGranularity::Tick {
+            return Err(CtpError::InvalidParameter("K 线订阅不支持 tick 粒度".to_string()));
+        }
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let sessions = self.config.sessions.sessions_for(&subscription.instrument_id);
+        let mut bars = BTreeMap::new();
+        let collect = |loaded: Vec<StrategyBar>, bars: &mut BTreeMap<NaiveDateTime, StrategyBar>| {
+            for bar in loaded.into_iter().filter(|b| b.end <= now) {
+                bars.entry(bar.end).or_insert(bar);
+            }
+        };
+
+        let pending = self.source.pending_ticks(&subscription.instrument_id)?;
+        let pending = pending.iter().filter_map(|tick| Some((pending_time(tick, now)?, tick)));
+        collect(self.replay(subscription, pending)?, &mut bars);
+
+        let current_day = trading_day_of(now);
+        let days = self.source.trading_days()?;
+        for &day in days.iter().rev().filter(|d| **d <= current_day).take(self.config.warmup.max_lookback_days) {
+            if bars.len() >= count {
+                break;
+            }
+            collect(self.load_day(subscription, &sessions, day)?, &mut bars);
+        }
+
+        let mut bars: Vec<StrategyBar> = bars.into_values().collect();
+        let skip = bars.len().saturating_sub(count);
+        bars.drain(..skip);
+        Ok(bars)
+    }
+
+    fn load_day(
+        &self,
+        subscription: &BarSubscription,
+        sessions: &TradingSessions,
+        trading_day: NaiveDate,
+    ) -> Result<Vec<StrategyBar>, CtpError> {
+        let instrument_id = &subscription.instrument_id;
+        for granularity in BAR_GRANULARITIES.into_iter().skip_while(|g| *g != subscription.granularity) {
+            let archived = self.source.bars(trading_day, instrument_id, granularity)?;
+            if !archived.is_empty() {
+                return Ok(aggregate(subscription, sessions, trading_day, granularity, &archived));
+            }
+        }
+        let ticks = self.source.ticks(trading_day, instrument_id)?;
+        let ticks = ticks.iter().filter_map(|tick| {
+            let time = NaiveTime::parse_from_str(&tick.update_time, "%H:%M:%S").ok()?;
+            let at = session_time(trading_day, time) + Duration::milliseconds(tick.update_millisec.max(0) as i64);
+            Some((at, tick))
+        });
+        self.replay(subscription, ticks)
+    }
+
+    /// 用独立的调度器按 tick 时间重新生成 K 线，末尾未收盘的一并产出
+    fn replay<'t>(
+        &self,
+        subscription: &BarSubscription,
+        ticks: impl Iterator<Item = (NaiveDateTime, &'t MarketDataTick)>,
+    ) -> Result<Vec<StrategyBar>, CtpError> {
+        let mut ticks: Vec<_> = ticks.collect();
+        if ticks.is_empty() {
+            return Ok(Vec::new());
+        }
+        ticks.sort_by_key(|(at, _)| *at);
+        let mut scheduler = BarScheduler::new(StrategyEngineConfig {
+            grace_ms: 0,
+            ..self.config.clone()
+        });
+        scheduler.subscribe(subscription)?;
+        let mut bars = Vec::new();
+        for (at, tick) in ticks {
+            bars.extend(scheduler.on_tick(tick, at));
+        }
+        bars.extend(scheduler.on_timer(NaiveDateTime::MAX));
+        Ok(bars)
+    }
+}
+
+/// 将同周期或更细周期的归档 K 线按交易时段合成为订阅周期
+fn aggregate(
+    subscription: &BarSubscription,
+    sessions: &TradingSessions,
+    trading_day: NaiveDate,
+    source: StorageGranularity,
+    archived: &[ArchivedBar],
+) -> Vec<StrategyBar> {
+    let mut windows: Vec<_> = archived
+        .iter()
+        .filter_map(|bar| {
+            // 日线没有时间，按日盘收盘时刻归属
+            let start = if source == StorageGranularity::Bar1d {
+                sessions.day_close(trading_day)?
+            } else {
+                session_time(trading_day, NaiveTime::parse_from_str(&bar.time, "%H:%M:%S").ok()?)
+            };
+            let window = sessions.bar_window(start, subscription.granularity)?;
+            Some((start, window, bar))
+        })
+        .collect();
+    // 归档按时间字符串排序，夜盘需排到日盘之前
+    windows.sort_by_key(|(start, _, _)| *start);
+
+    let mut bars: Vec<StrategyBar> = Vec::new();
+    for (_, window, bar) in windows {
+        match bars.last_mut() {
+            Some(last) if last.end == window.end => {
+                last.high = last.high.max(bar.high);
+                last.low = last.low.min(bar.low);
+                last.close = bar.close;
+                last.volume += bar.volume;
+                last.turnover += bar.turnover;
+                last.open_interest = bar.open_interest;
+                last.tick_count += bar.tick_count;
+            }
+            _ => bars.push(StrategyBar {
+                instrument_id: subscription.instrument_id.clone(),
+                granularity: subscription.granularity,
+                trading_day: window.trading_day,
+                start: window.start,
+                end: window.end,
+                open: bar.open,
+                high: bar.high,
+                low: bar.low,
+                close: bar.close,
+                volume: bar.volume,
+                turnover: bar.turnover,
+                open_interest: bar.open_interest,
+                tick_count: bar.tick_count,
+            }),
+        }
+    }
+    bars
+}
+
+/// 交易日内交易所时间对应的本地时间：18 点后属于前一交易日夜盘，凌晨属于夜盘次日
+fn session_time(trading_day: NaiveDate, time: NaiveTime) -> NaiveDateTime {
+    if (6..18).contains(&time.hour()) {
+        return trading_day.and_time(time);
+    }
+    let night = previous_trading_day(trading_day);
+    if time.hour() >= 18 {
+        night.and_time(time)
+    } else {
+        (night + Duration::days(1)).and_time(time)
+    }
+}
+
+/// 未归档 tick 只有时分秒，取不晚于 `now` 的最近一天
+fn pending_time(tick: &MarketDataTick, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let time = NaiveTime::parse_from_str(&tick.update_time, "%H:%M:%S").ok()?;
+    let at = now.date().and_time(time) + Duration::milliseconds(tick.update_millisec.max(0) as i64);
+    // 容忍本地时钟略慢于交易所
+    Some(if at > now + Duration::minutes(1) { at - Duration::days(1) } else { at })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::strategy_engine::{Strategy, StrategyEngine, StrategyMode};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemorySource {
+        bars: HashMap<(NaiveDate, StorageGranularity), Vec<ArchivedBar>>,
+        pending: Vec<MarketDataTick>,
+    }
+
+    impl HistorySource for MemorySource {
+        fn trading_days(&self) -> Result<Vec<NaiveDate>, CtpError> {
+            let mut days: Vec<NaiveDate> = self.bars.keys().map(|(day, _)| *day).collect();
+            days.sort();
+            days.dedup();
+            Ok(days)
+        }
+
+        fn bars(&self, trading_day: NaiveDate, _: &str, granularity: StorageGranularity) -> Result<Vec<ArchivedBar>, CtpError> {
+            Ok(self.bars.get(&(trading_day, granularity)).cloned().unwrap_or_default())
+        }
+
+        fn ticks(&self, _: NaiveDate, _: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+            Ok(Vec::new())
+        }
+
+        fn pending_ticks(&self, _: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+            Ok(self.pending.clone())
+        }
+    }
+
+    fn at(day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+        // 2024-01-03 为周三
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    fn archived(time: &str, close: f64, volume: i64) -> ArchivedBar {
+        ArchivedBar {
+            instrument_id: "rb2505".to_string(),
+            time: time.to_string(),
+            open: close,
+            high: close + 2.0,
+            low: close - 2.0,
+            close,
+            volume,
+            turnover: 0.0,
+            open_interest: 1000,
+            tick_count: 10,
+        }
+    }
+
+    fn tick(time: &str, price: f64, volume: i64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2505".to_string(),
+            last_price: price,
+            volume,
+            turnover: 0.0,
+            open_interest: 1000,
+            bid_price1: price - 1.0,
+            bid_volume1: 1,
+            ask_price1: price + 1.0,
+            ask_volume1: 1,
+            update_time: time.to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    /// 1 月 3 日归档了 1 分钟线（含 1 月 2 日夜盘），1 月 4 日交易日的 tick 尚未归档
+    fn source() -> MemorySource {
+        let mut source = MemorySource::default();
+        source.bars.insert(
+            (NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(), StorageGranularity::Bar1m),
+            vec![
+                archived("09:00:00", 3500.0, 10),
+                archived("09:01:00", 3504.0, 20),
+                archived("14:59:00", 3510.0, 30),
+                archived("21:00:00", 3490.0, 40),
+            ],
+        );
+        source.pending = vec![
+            tick("21:00:10", 3520.0, 100),
+            tick("21:00:40", 3522.0, 110),
+            tick("09:00:05", 3530.0, 120),
+            tick("09:01:10", 3531.0, 130),
+            // 当前尚未收盘的 K 线不回放
+            tick("09:02:10", 3532.0, 140),
+        ];
+        source
+    }
+
+    #[test]
+    fn test_loads_recent_bars_across_archive_and_pending_ticks() {
+        let source = source();
+        let config = StrategyEngineConfig::default();
+        let loader = WarmupLoader::new(&source, &config);
+        let now = at(4, 9, 2, 30);
+
+        let bars = loader.load(&BarSubscription::new("rb2505", StorageGranularity::Bar1m), 5, now).unwrap();
+        let ends: Vec<_> = bars.iter().map(|b| b.end).collect();
+        assert_eq!(ends, vec![at(3, 9, 2, 0), at(3, 15, 0, 0), at(3, 21, 1, 0), at(4, 9, 1, 0), at(4, 9, 2, 0)]);
+        assert_eq!(bars[2].trading_day, NaiveDate::from_ymd_opt(2024, 1, 4).unwrap());
+        assert_eq!((bars[2].open, bars[2].close, bars[2].volume), (3520.0, 3522.0, 10));
+
+        // 5 分钟线由 1 分钟线合成，1 月 2 日夜盘排在 1 月 3 日日盘之前
+        let bars = loader.load(&BarSubscription::new("rb2505", StorageGranularity::Bar5m), 10, now).unwrap();
+        let windows: Vec<_> = bars.iter().map(|b| (b.start, b.end)).collect();
+        assert_eq!(
+            windows,
+            vec![
+                (at(2, 21, 0, 0), at(2, 21, 5, 0)),
+                (at(3, 9, 0, 0), at(3, 9, 5, 0)),
+                (at(3, 14, 55, 0), at(3, 15, 0, 0)),
+                (at(3, 21, 0, 0), at(3, 21, 5, 0)),
+            ]
+        );
+        assert_eq!((bars[1].open, bars[1].close, bars[1].high, bars[1].volume), (3500.0, 3504.0, 3506.0, 30));
+    }
+
+    type Calls = Arc<Mutex<Vec<(Option<StrategyMode>, NaiveDateTime)>>>;
+
+    struct Recorder {
+        mode: Option<StrategyMode>,
+        calls: Calls,
+    }
+
+    impl Strategy for Recorder {
+        fn on_bar(&mut self, bar: &StrategyBar) {
+            self.calls.lock().unwrap().push((self.mode, bar.end));
+        }
+
+        fn on_mode_change(&mut self, mode: StrategyMode) {
+            self.mode = Some(mode);
+        }
+    }
+
+    #[test]
+    fn test_engine_replays_warmup_before_going_live() {
+        let mut config = StrategyEngineConfig::default();
+        config.warmup.strategy_bars.insert("trend".to_string(), 3);
+        let mut engine = StrategyEngine::new(config);
+        let calls = Calls::default();
+        let report = engine
+            .register_with_warmup(
+                "trend",
+                Box::new(Recorder { mode: None, calls: calls.clone() }),
+                vec![BarSubscription::new("rb2505", StorageGranularity::Bar1m)],
+                &source(),
+                at(4, 9, 2, 30),
+            )
+            .unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.series[0].last_end, Some(at(4, 9, 2, 0)));
+
+        // 已回放的 K 线在实盘收盘时不再推送
+        assert_eq!(engine.on_tick(&tick("09:01:59", 3531.0, 150), at(4, 9, 2, 40)).len(), 1);
+        engine.on_tick(&tick("09:02:50", 3533.0, 160), at(4, 9, 2, 50));
+        assert_eq!(engine.on_timer(at(4, 9, 3, 2)).len(), 1);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                (Some(StrategyMode::Replay), at(3, 21, 1, 0)),
+                (Some(StrategyMode::Replay), at(4, 9, 1, 0)),
+                (Some(StrategyMode::Replay), at(4, 9, 2, 0)),
+                (Some(StrategyMode::Live), at(4, 9, 3, 0)),
+            ]
+        );
+        assert!(WarmupConfig { bars: 200_000, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::ctp::{
+    CtpError,
+    models::{AccountInfo, InstrumentInfo, MarginRate, Position, PositionDirection},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 默认追保线（风险度 = 保证金 / 权益）
+pub const DEFAULT_MARGIN_CALL_RATIO: f64 = 1.0;
+
+/// 价格冲击，`product_id` 为空时作用于全部持仓
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceShock {
+    #[serde(default)]
+    pub product_id: Option<String>,
+    /// 价格变动比例，如 -0.05 表示下跌 5%
+    pub change: f64,
+}
+
+/// 压力测试情景，品种冲击优先于整体冲击，未命中的持仓价格不变
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestScenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub shocks: Vec<PriceShock>,
+    /// 风险度达到该值视为追保
+    #[serde(default = "default_margin_call_ratio")]
+    pub margin_call_ratio: f64,
+}
+
+fn default_margin_call_ratio() -> f64 {
+    DEFAULT_MARGIN_CALL_RATIO
+}
+
+impl StressTestScenario {
+    /// 整体价格冲击
+    pub fn portfolio(change: f64) -> Self {
+        Self {
+            name: None,
+            shocks: vec![PriceShock { product_id: None, change }],
+            margin_call_ratio: DEFAULT_MARGIN_CALL_RATIO,
+        }
+    }
+
+    pub fn with_product_shock(mut self, product_id: impl Into<String>, change: f64) -> Self {
+        self.shocks.push(PriceShock { product_id: Some(product_id.into()), change });
+        self
+    }
+
+    pub fn with_margin_call_ratio(mut self, ratio: f64) -> Self {
+        self.margin_call_ratio = ratio;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.shocks.is_empty() {
+            return Err(CtpError::InvalidParameter("压力测试情景至少需要一个价格冲击".to_string()));
+        }
+        if let Some(shock) = self.shocks.iter().find(|s| !s.change.is_finite() || s.change <= -1.0) {
+            return Err(CtpError::InvalidParameter(format!("价格变动比例无效: {}", shock.change)));
+        }
+        if !(self.margin_call_ratio.is_finite() && self.margin_call_ratio > 0.0) {
+            return Err(CtpError::InvalidParameter("追保线必须大于0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 持仓所属品种适用的价格变动
+    fn change_for(&self, product_id: &str) -> f64 {
+        self.shocks
+            .iter()
+            .find(|s| s.product_id.as_deref() == Some(product_id))
+            .or_else(|| self.shocks.iter().find(|s| s.product_id.is_none()))
+            .map_or(0.0, |s| s.change)
+    }
+}
+
+/// 单个持仓的冲击结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressedPosition {
+    pub instrument_id: String,
+    pub product_id: String,
+    pub direction: PositionDirection,
+    pub volume: i32,
+    pub price: f64,
+    pub shocked_price: f64,
+    pub price_change: f64,
+    pub pnl: f64,
+    pub margin: f64,
+    pub stressed_margin: f64,
+}
+
+/// 压力测试结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestResult {
+    pub scenario: StressTestScenario,
+    pub balance: f64,
+    pub pnl: f64,
+    /// 冲击后权益
+    pub equity: f64,
+    pub margin: f64,
+    pub stressed_margin: f64,
+    /// 冲击后风险度，权益不为正时为 None
+    pub risk_ratio: Option<f64>,
+    pub margin_call: bool,
+    /// 恢复到追保线以下需追加的资金
+    pub shortfall: f64,
+    pub positions: Vec<StressedPosition>,
+    pub warnings: Vec<String>,
+}
+
+/// 对当前持仓施加假设的价格冲击，按合约乘数和保证金率重算盈亏与保证金
+///
+/// 价格缺失时以持仓均价估算；保证金率优先使用查询结果，其次合约信息中的比例，
+/// 都没有时按价格变动比例缩放现有保证金
+pub fn run_stress_test(
+    scenario: &StressTestScenario,
+    positions: &[Position],
+    account: &AccountInfo,
+    specs: &HashMap<String, InstrumentInfo>,
+    margin_rates: &HashMap<String, MarginRate>,
+    prices: &HashMap<String, f64>,
+) -> Result<StressTestResult, CtpError> {
+    scenario.validate()?;
+
+    let mut warnings = Vec::new();
+    let mut stressed = Vec::new();
+    for position in positions.iter().filter(|p| p.total_position > 0) {
+        let instrument_id = &position.instrument_id;
+        let spec = specs.get(instrument_id);
+        if spec.is_none() {
+            warnings.push(format!("{} 缺少合约信息，按乘数 1 计算", instrument_id));
+        }
+        let product_id = spec.map(|s| s.product_id.clone()).unwrap_or_default();
+        let multiplier = spec.map_or(1.0, |s| s.volume_multiple.max(1) as f64);
+        let volume = position.total_position as f64;
+
+        let price = match prices.get(instrument_id).copied().filter(|p| *p > 0.0 && *p < f64::MAX) {
+            Some(price) => price,
+            None => {
+                warnings.push(format!("{} 缺少行情，以持仓均价估算", instrument_id));
+                position.position_cost / (volume * multiplier)
+            }
+        };
+        let price_change = scenario.change_for(&product_id);
+        let shocked_price = price * (1.0 + price_change);
+        let sign = match position.direction {
+            PositionDirection::Long => 1.0,
+            PositionDirection::Short => -1.0,
+        };
+        let pnl = (shocked_price - price) * multiplier * volume * sign;
+
+        let (by_money, by_volume) = match (margin_rates.get(instrument_id), position.direction) {
+            (Some(rate), PositionDirection::Long) => (rate.long_margin_ratio_by_money, rate.long_margin_ratio_by_volume),
+            (Some(rate), PositionDirection::Short) => (rate.short_margin_ratio_by_money, rate.short_margin_ratio_by_volume),
+            (None, PositionDirection::Long) => (spec.map_or(0.0, |s| s.long_margin_ratio), 0.0),
+            (None, PositionDirection::Short) => (spec.map_or(0.0, |s| s.short_margin_ratio), 0.0),
+        };
+        let stressed_margin = if by_money > 0.0 || by_volume > 0.0 {
+            (shocked_price * multiplier * by_money + by_volume) * volume
+        } else {
+            warnings.push(format!("{} 缺少保证金率，按现有保证金缩放", instrument_id));
+            position.margin * (1.0 + price_change)
+        };
+
+        stressed.push(StressedPosition {
+            instrument_id: instrument_id.clone(),
+            product_id,
+            direction: position.direction,
+            volume: position.total_position,
+            price,
+            shocked_price,
+            price_change,
+            pnl,
+            margin: position.margin,
+            stressed_margin,
+        });
+    }
+    stressed.sort_by(|a, b| a.instrument_id.cmp(&b.instrument_id));
+
+    // 账户保证金中不属于持仓的部分（如挂单冻结）保持不变
+    let margin = account.curr_margin;
+    let position_margin: f64 = stressed.iter().map(|p| p.margin).sum();
+    let stressed_margin = (margin - position_margin).max(0.0) + stressed.iter().map(|p| p.stressed_margin).sum::<f64>();
+    let pnl: f64 = stressed.iter().map(|p| p.pnl).sum();
+    let equity = account.balance + pnl;
+    let risk_ratio = (equity > 0.0).then(|| stressed_margin / equity);
+    let margin_call = risk_ratio.is_none_or(|r| r >= scenario.margin_call_ratio);
+    let shortfall = (stressed_margin / scenario.margin_call_ratio - equity).max(0.0);
+
+    Ok(StressTestResult {
+        scenario: scenario.clone(),
+        balance: account.balance,
+        pnl,
+        equity,
+        margin,
+        stressed_margin,
+        risk_ratio,
+        margin_call,
+        shortfall,
+        positions: stressed,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(instrument_id: &str, product_id: &str, multiplier: i32) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_id: instrument_id.to_string(),
+            exchange_id: "SHFE".to_string(),
+            instrument_name: String::new(),
+            product_id: product_id.to_string(),
+            product_class: "1".to_string(),
+            delivery_year: 2025,
+            delivery_month: 1,
+            max_market_order_volume: 30,
+            min_market_order_volume: 1,
+            max_limit_order_volume: 500,
+            min_limit_order_volume: 1,
+            volume_multiple: multiplier,
+            price_tick: 1.0,
+            create_date: String::new(),
+            open_date: String::new(),
+            expire_date: String::new(),
+            start_delivery_date: String::new(),
+            end_delivery_date: String::new(),
+            is_trading: true,
+            underlying_instrument: String::new(),
+            strike_price: 0.0,
+            underlying_multiple: 0.0,
+            long_margin_ratio: 0.1,
+            short_margin_ratio: 0.1,
+        }
+    }
+
+    fn position(instrument_id: &str, direction: PositionDirection, volume: i32, margin: f64) -> Position {
+        Position {
+            instrument_id: instrument_id.to_string(),
+            direction,
+            total_position: volume,
+            yesterday_position: volume,
+            today_position: 0,
+            open_cost: 0.0,
+            position_cost: 0.0,
+            margin,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+        }
+    }
+
+    fn account(balance: f64, margin: f64) -> AccountInfo {
+        AccountInfo {
+            account_id: "test".to_string(),
+            currency_id: "CNY".to_string(),
+            available: balance - margin,
+            balance,
+            margin,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            curr_margin: margin,
+            commission: 0.0,
+            close_profit: 0.0,
+            position_profit: 0.0,
+            risk_ratio: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_product_shock_overrides_portfolio_shock() {
+        let specs = HashMap::from([
+            ("rb2501".to_string(), instrument("rb2501", "rb", 10)),
+            ("cu2501".to_string(), instrument("cu2501", "cu", 5)),
+        ]);
+        let prices = HashMap::from([("rb2501".to_string(), 3500.0), ("cu2501".to_string(), 70_000.0)]);
+        // rb 多 10 手保证金 35000，cu 空 2 手保证金 70000
+        let positions = vec![
+            position("rb2501", PositionDirection::Long, 10, 35_000.0),
+            position("cu2501", PositionDirection::Short, 2, 70_000.0),
+        ];
+        let scenario = StressTestScenario::portfolio(-0.05).with_product_shock("cu", 0.10);
+        let result =
+            run_stress_test(&scenario, &positions, &account(200_000.0, 105_000.0), &specs, &HashMap::new(), &prices).unwrap();
+
+        let rb = result.positions.iter().find(|p| p.instrument_id == "rb2501").unwrap();
+        assert_eq!(rb.price_change, -0.05);
+        assert!((rb.pnl + 17_500.0).abs() < 1e-6);
+        let cu = result.positions.iter().find(|p| p.instrument_id == "cu2501").unwrap();
+        assert!((cu.pnl + 70_000.0).abs() < 1e-6);
+        assert!((cu.stressed_margin - 77_000.0).abs() < 1e-6);
+
+        assert!((result.pnl + 87_500.0).abs() < 1e-6);
+        assert!((result.equity - 112_500.0).abs() < 1e-6);
+        assert!((result.stressed_margin - 110_250.0).abs() < 1e-6);
+        assert!(!result.margin_call);
+        assert_eq!(result.shortfall, 0.0);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_margin_call_and_invalid_scenario() {
+        let specs = HashMap::from([("rb2501".to_string(), instrument("rb2501", "rb", 10))]);
+        let prices = HashMap::from([("rb2501".to_string(), 3500.0)]);
+        let positions = vec![position("rb2501", PositionDirection::Long, 20, 70_000.0)];
+        // 下跌 10%：亏损 70000，权益 30000，保证金 63000
+        let rate = MarginRate {
+            instrument_id: "rb2501".to_string(),
+            long_margin_ratio_by_money: 0.1,
+            long_margin_ratio_by_volume: 0.0,
+            short_margin_ratio_by_money: 0.1,
+            short_margin_ratio_by_volume: 0.0,
+        };
+        let rates = HashMap::from([("rb2501".to_string(), rate)]);
+        let result = run_stress_test(
+            &StressTestScenario::portfolio(-0.10),
+            &positions,
+            &account(100_000.0, 70_000.0),
+            &specs,
+            &rates,
+            &prices,
+        )
+        .unwrap();
+        assert!(result.margin_call);
+        assert!((result.shortfall - 33_000.0).abs() < 1e-6);
+
+        assert!(StressTestScenario::portfolio(-1.0).validate().is_err());
+        assert!(StressTestScenario::portfolio(0.05).with_margin_call_ratio(0.0).validate().is_err());
+    }
+}
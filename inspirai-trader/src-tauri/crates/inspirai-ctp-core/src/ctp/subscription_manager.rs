@@ -512,12 +512,13 @@ impl SubscriptionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ctp::{ClientState, Environment, CtpConfig};
+    use crate::ctp::{ClientState, ConnectionMode, Environment, CtpConfig};
     use tokio::sync::mpsc;
 
     fn create_test_config() -> CtpConfig {
         CtpConfig {
             environment: Environment::SimNow,
+            connection_mode: ConnectionMode::Full,
             broker_id: "9999".to_string(),
             investor_id: "test_user".to_string(),
             password: "test_pass".to_string(),
@@ -531,6 +532,8 @@ mod tests {
             timeout_secs: 30,
             reconnect_interval_secs: 5,
             max_reconnect_attempts: 3,
+            market_order_policy: Default::default(),
+            programmatic: Default::default(),
         }
     }
 
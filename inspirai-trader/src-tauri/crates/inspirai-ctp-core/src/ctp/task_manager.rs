@@ -0,0 +1,311 @@
+use crate::ctp::CtpError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 任务进度事件名
+pub const TASK_PROGRESS_EVENT: &str = "task://progress";
+/// 保留的已结束任务数
+const FINISHED_RETENTION: usize = 50;
+/// 进度变化小于该百分比时不推送
+const PROGRESS_STEP: f64 = 1.0;
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 任务信息，同时作为进度事件的负载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: String,
+    /// 任务类型，如 export_market_data
+    pub kind: String,
+    pub label: String,
+    pub state: TaskState,
+    /// 进度百分比 0-100
+    pub progress: f64,
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 取消标记，长任务在处理单元之间检查
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// 已取消时返回错误，便于用 `?` 中止
+    pub fn check(&self) -> Result<(), CtpError> {
+        if self.is_cancelled() {
+            Err(CtpError::StateError("任务已取消".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+type Listener = Box<dyn Fn(&TaskInfo) + Send + Sync>;
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: CancelToken,
+    /// 最近一次推送时的进度
+    reported: f64,
+}
+
+#[derive(Default)]
+struct ManagerInner {
+    running: Vec<TaskEntry>,
+    finished: VecDeque<TaskInfo>,
+}
+
+/// 长任务管理
+///
+/// 导出、压缩等耗时操作登记为任务，统一上报进度百分比、支持取消，
+/// 状态变化通过监听器推送到前端（`task://progress`）
+#[derive(Clone, Default)]
+pub struct TaskManager {
+    inner: Arc<Mutex<ManagerInner>>,
+    listener: Arc<Mutex<Option<Listener>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置进度监听器
+    pub fn set_listener(&self, listener: impl Fn(&TaskInfo) + Send + Sync + 'static) {
+        *self.listener.lock().unwrap() = Some(Box::new(listener));
+    }
+
+    /// 登记新任务
+    pub fn start(&self, kind: &str, label: impl Into<String>) -> TaskHandle {
+        let id = format!("task-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        let info = TaskInfo {
+            id: id.clone(),
+            kind: kind.to_string(),
+            label: label.into(),
+            state: TaskState::Running,
+            progress: 0.0,
+            message: None,
+            error: None,
+            started_at: chrono::Utc::now(),
+            finished_at: None,
+        };
+        let cancel = CancelToken::new();
+        tracing::info!("任务开始 {} [{}]: {}", id, info.kind, info.label);
+        self.inner.lock().unwrap().running.push(TaskEntry {
+            info: info.clone(),
+            cancel: cancel.clone(),
+            reported: 0.0,
+        });
+        self.notify(&info);
+        TaskHandle {
+            id,
+            manager: self.clone(),
+            cancel,
+        }
+    }
+
+    /// 请求取消运行中的任务
+    pub fn cancel(&self, id: &str) -> Result<(), CtpError> {
+        let inner = self.inner.lock().unwrap();
+        let entry = inner
+            .running
+            .iter()
+            .find(|e| e.info.id == id)
+            .ok_or_else(|| CtpError::NotFound(format!("任务 {} 不存在或已结束", id)))?;
+        tracing::info!("请求取消任务 {}", id);
+        entry.cancel.cancel();
+        Ok(())
+    }
+
+    /// 运行中和最近结束的任务，运行中的在前
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .running
+            .iter()
+            .map(|e| e.info.clone())
+            .chain(inner.finished.iter().rev().cloned())
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<TaskInfo> {
+        self.list().into_iter().find(|t| t.id == id)
+    }
+
+    fn update(&self, id: &str, progress: f64, message: Option<String>) {
+        let info = {
+            let mut inner = self.inner.lock().unwrap();
+            let Some(entry) = inner.running.iter_mut().find(|e| e.info.id == id) else {
+                return;
+            };
+            entry.info.progress = progress.clamp(0.0, 100.0);
+            if message.is_some() {
+                entry.info.message = message;
+            }
+            if entry.info.progress - entry.reported < PROGRESS_STEP && entry.info.progress < 100.0 {
+                return;
+            }
+            entry.reported = entry.info.progress;
+            entry.info.clone()
+        };
+        self.notify(&info);
+    }
+
+    fn finish(&self, id: &str, state: TaskState, error: Option<String>) {
+        let info = {
+            let mut inner = self.inner.lock().unwrap();
+            let Some(index) = inner.running.iter().position(|e| e.info.id == id) else {
+                return;
+            };
+            let mut info = inner.running.remove(index).info;
+            info.state = state;
+            info.error = error;
+            info.finished_at = Some(chrono::Utc::now());
+            if state == TaskState::Completed {
+                info.progress = 100.0;
+            }
+            inner.finished.push_back(info.clone());
+            while inner.finished.len() > FINISHED_RETENTION {
+                inner.finished.pop_front();
+            }
+            info
+        };
+        match state {
+            TaskState::Failed => tracing::warn!("任务失败 {}: {}", id, info.error.as_deref().unwrap_or_default()),
+            _ => tracing::info!("任务结束 {}: {:?}", id, state),
+        }
+        self.notify(&info);
+    }
+
+    fn notify(&self, info: &TaskInfo) {
+        if let Some(listener) = self.listener.lock().unwrap().as_ref() {
+            listener(info);
+        }
+    }
+}
+
+/// 运行中任务的句柄，未显式结束即被丢弃时按失败处理
+pub struct TaskHandle {
+    id: String,
+    manager: TaskManager,
+    cancel: CancelToken,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// 上报进度百分比
+    pub fn progress(&self, percent: f64, message: Option<String>) {
+        self.manager.update(&self.id, percent, message);
+    }
+
+    /// 按已完成/总数上报进度
+    pub fn progress_of(&self, completed: usize, total: usize, message: Option<String>) {
+        let percent = if total == 0 { 100.0 } else { completed as f64 * 100.0 / total as f64 };
+        self.progress(percent, message);
+    }
+
+    /// 按执行结果结束任务，已请求取消的失败视为取消
+    pub fn finish<T, E: std::fmt::Display>(self, result: &Result<T, E>) {
+        let (state, error) = match result {
+            Ok(_) => (TaskState::Completed, None),
+            Err(_) if self.cancel.is_cancelled() => (TaskState::Cancelled, None),
+            Err(e) => (TaskState::Failed, Some(e.to_string())),
+        };
+        self.manager.finish(&self.id, state, error);
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        // finish 之后任务已不在运行列表中，此处为空操作
+        self.manager.finish(&self.id, TaskState::Failed, Some("任务异常结束".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_throttled_and_completion_reported() {
+        let manager = TaskManager::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        manager.set_listener(move |info| sink.lock().unwrap().push((info.state, info.progress)));
+
+        let task = manager.start("export_market_data", "导出 rb2501");
+        task.progress(0.5, None);
+        task.progress_of(1, 4, Some("1/4".to_string()));
+        task.progress(25.4, None);
+        assert_eq!(manager.get(task.id()).unwrap().message.as_deref(), Some("1/4"));
+
+        let id = task.id().to_string();
+        task.finish(&Ok::<_, CtpError>(()));
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            vec![(TaskState::Running, 0.0), (TaskState::Running, 25.0), (TaskState::Completed, 100.0)]
+        );
+        let info = manager.get(&id).unwrap();
+        assert_eq!(info.state, TaskState::Completed);
+        assert!(info.finished_at.is_some());
+    }
+
+    #[test]
+    fn test_cancel_and_dropped_handle() {
+        let manager = TaskManager::new();
+        let task = manager.start("compaction", "压缩");
+        let token = task.cancel_token();
+        assert!(token.check().is_ok());
+
+        manager.cancel(task.id()).unwrap();
+        assert!(token.check().is_err());
+        let id = task.id().to_string();
+        task.finish(&token.check());
+        assert_eq!(manager.get(&id).unwrap().state, TaskState::Cancelled);
+        assert!(manager.cancel(&id).is_err());
+
+        let dropped = manager.start("compaction", "压缩");
+        let id = dropped.id().to_string();
+        drop(dropped);
+        let info = manager.get(&id).unwrap();
+        assert_eq!(info.state, TaskState::Failed);
+        assert_eq!(manager.list().len(), 2);
+    }
+}
@@ -9,7 +9,7 @@ mod tests {
         println!("\n=== 测试配置路径加载 ===\n");
         
         // 读取实际的production.toml文件
-        let config_path = PathBuf::from("config/production.toml");
+        let config_path = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/production.toml"));
         println!("配置文件路径: {:?}", config_path);
         println!("配置文件存在: {}", config_path.exists());
         
@@ -4,6 +4,8 @@ mod market_data_subscription_test;
 mod trading_functionality_test;
 // 查询功能测试
 mod query_functionality_test;
+// TTS 一致性测试
+mod tts_conformance_test;
 
 #[cfg(test)]
 mod tests {
@@ -64,6 +66,7 @@ mod tests {
         // 测试 CTP 组件初始化
         // 注意：这个测试可能会失败，因为没有实际的 CTP 库文件
         // 但它可以验证代码结构是否正确
+        let _guard = crate::logging::GLOBAL_SUBSCRIBER_TEST_LOCK.blocking_lock();
         let result = crate::ctp::init();
         
         // 在没有 CTP 库文件的情况下，应该返回库加载错误
@@ -168,6 +168,9 @@ mod tests {
             highest_price: 3520.0,
             lowest_price: 3440.0,
             pre_close_price: 3450.0,
+            price_limit: None,
+            trace: None,
+            source: None,
         };
         
         // 处理行情数据
@@ -288,6 +291,9 @@ mod tests {
             highest_price: 3520.0,
             lowest_price: 3440.0,
             pre_close_price: 3450.0,
+            price_limit: None,
+            trace: None,
+            source: None,
         };
         
         manager.handle_market_data(test_tick);
@@ -381,6 +387,9 @@ mod tests {
             highest_price: 3520.0,
             lowest_price: 3440.0,
             pre_close_price: 3450.0,
+            price_limit: None,
+            trace: None,
+            source: None,
         };
         
         manager.handle_market_data(test_tick);
@@ -192,6 +192,7 @@ mod tests {
     fn create_test_account() -> AccountInfo {
         AccountInfo {
             account_id: "test_account".to_string(),
+            currency_id: "CNY".to_string(),
             available: 100000.0,
             balance: 120000.0,
             frozen_margin: 5000.0,
@@ -229,6 +230,8 @@ mod tests {
             price: 3500.0,
             volume: 5,
             trade_time: "09:30:15".to_string(),
+            exchange_id: String::new(),
+            tags: Default::default(),
         }
     }
 
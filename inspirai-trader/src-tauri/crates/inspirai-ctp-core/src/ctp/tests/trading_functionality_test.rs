@@ -1,6 +1,8 @@
 use crate::ctp::{
-    CtpConfig, Environment,
-    models::{OrderRequest, OrderDirection, OffsetFlag, OrderType, TimeCondition},
+    CtpConfig, CtpError, CtpEvent, Environment, PriceLimitTracker,
+    BarSubscription, OrderRouter, RouterCapabilities, StorageGranularity, Strategy, StrategyBar, StrategyEngine,
+    StrategyEngineConfig, STRATEGY_TAG,
+    models::{MarketDataTick, OrderRequest, OrderStatus, OrderDirection, OffsetFlag, OrderType, RiskParams, TimeCondition},
     trading_service::TradingService,
     utils::DataConverter,
 };
@@ -35,6 +37,64 @@ mod tests {
         TradingService::new(config, client_state, event_sender)
     }
 
+    /// 创建测试行情
+    fn create_test_tick(last_price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price,
+            volume: 0,
+            turnover: 0.0,
+            open_interest: 0,
+            bid_price1: last_price - 1.0,
+            bid_volume1: 10,
+            ask_price1: last_price + 1.0,
+            ask_volume1: 10,
+            update_time: "09:00:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: 0.0,
+            highest_price: 0.0,
+            lowest_price: 0.0,
+            pre_close_price: 0.0,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    /// 只记录撤单的交易通道
+    #[derive(Default)]
+    struct RecordingRouter {
+        cancelled: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl OrderRouter for RecordingRouter {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn capabilities(&self) -> RouterCapabilities {
+            RouterCapabilities::default()
+        }
+
+        fn insert_order(&self, _order: &OrderRequest, _order_ref: &str) -> Result<(), CtpError> {
+            Ok(())
+        }
+
+        fn cancel_order(&self, order: &OrderStatus) -> Result<(), CtpError> {
+            self.cancelled.lock().unwrap().push(order.order_ref.clone());
+            Ok(())
+        }
+    }
+
+    /// 不处理 K 线的策略
+    struct IdleStrategy;
+
+    impl Strategy for IdleStrategy {
+        fn on_bar(&mut self, _bar: &StrategyBar) {}
+    }
+
     /// 创建测试订单
     fn create_test_order() -> OrderRequest {
         OrderRequest {
@@ -261,4 +321,94 @@ mod tests {
         let final_stats = trading_service.get_stats();
         assert_eq!(final_stats.total_orders, 5, "应该有5个订单");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_orders_accepted_again_after_reconnect() {
+        let trading_service = create_test_trading_service();
+
+        // 断线后对账完成前拒绝新订单
+        trading_service.handle_event(CtpEvent::Disconnected).await.unwrap();
+        assert!(trading_service.is_reconciliation_pending());
+        assert!(trading_service.submit_order(create_test_order(), None).await.is_err());
+
+        // 重新登录触发对账；无法发送查询时放弃对账，不再阻止报单
+        trading_service.handle_event(CtpEvent::TraderRecovered).await.unwrap();
+        assert!(!trading_service.is_reconciliation_pending());
+        assert!(trading_service.submit_order(create_test_order(), None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_limit_locked_order_rejected_on_submit() {
+        let price_limits = PriceLimitTracker::new();
+        let trading_service = create_test_trading_service().with_price_limits(price_limits.clone());
+        let mut locked = create_test_tick(3800.0);
+        locked.bid_price1 = 3800.0;
+        locked.bid_volume1 = 900;
+        locked.ask_price1 = 0.0;
+        locked.ask_volume1 = 0;
+        price_limits.update(&locked, 3800.0, 3200.0);
+
+        // 未开启封板拦截时照常报单
+        assert!(trading_service.submit_order(create_test_order(), None).await.is_ok());
+
+        trading_service.set_risk_params(RiskParams {
+            max_position_ratio: 1.0,
+            max_single_loss: 0.0,
+            max_daily_loss: 0.0,
+            max_order_volume: 0,
+            position_limit: Default::default(),
+            forbidden_instruments: vec![],
+            auto_stop_loss: false,
+            stop_loss_ratio: 0.0,
+            auto_take_profit: false,
+            take_profit_ratio: 0.0,
+            block_limit_locked: true,
+        });
+        let result = trading_service.submit_order(create_test_order(), None).await;
+        assert!(matches!(result, Err(CtpError::ValidationError(_))));
+
+        // 封板方向之外的委托不受影响
+        let mut sell = create_test_order();
+        sell.direction = OrderDirection::Sell;
+        assert!(trading_service.submit_order(sell, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_strategy_paused_on_bad_data_cancels_its_orders() {
+        let mut engine = StrategyEngine::new(StrategyEngineConfig::default());
+        engine
+            .register("alpha", Box::new(IdleStrategy), vec![BarSubscription::new("rb2501", StorageGranularity::Bar1m)])
+            .unwrap();
+        let engine = Arc::new(std::sync::Mutex::new(engine));
+        let recording = Arc::new(RecordingRouter::default());
+        let router: Arc<dyn OrderRouter> = recording.clone();
+        let trading_service = create_test_trading_service().with_strategy_engine(engine.clone());
+        trading_service.attach_router(router.clone());
+
+        let mut strategy_order = create_test_order();
+        strategy_order.tags.insert(STRATEGY_TAG.to_string(), "alpha".to_string());
+        let strategy_ref = trading_service.submit_order(strategy_order, Some(router.clone())).await.unwrap();
+        trading_service.submit_order(create_test_order(), Some(router)).await.unwrap();
+
+        trading_service.handle_event(CtpEvent::MarketData(create_test_tick(3500.0))).await.unwrap();
+        assert!(!engine.lock().unwrap().is_paused("alpha"));
+        assert!(recording.cancelled.lock().unwrap().is_empty());
+
+        // 跳价超过阈值，依赖该合约的策略暂停，只撤销该策略的挂单
+        trading_service.handle_event(CtpEvent::MarketData(create_test_tick(3800.0))).await.unwrap();
+        assert!(engine.lock().unwrap().is_paused("alpha"));
+        assert_eq!(*recording.cancelled.lock().unwrap(), vec![strategy_ref]);
+        // 行情异常的合约自动停止交易
+        let disabled = trading_service.get_disabled_trading();
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].source, crate::ctp::SwitchSource::DataQuality);
+        assert!(matches!(
+            trading_service.submit_order(create_test_order(), None).await,
+            Err(CtpError::RiskControl(_))
+        ));
+
+        // 暂停事件只处理一次
+        trading_service.on_timer(chrono::Local::now().naive_local()).await;
+        assert_eq!(recording.cancelled.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,23 @@
+/// OpenCTP TTS 端到端一致性测试
+///
+/// 需要设置 CTP_TTS_CONFORMANCE=1 及 TTS 账户信息，未设置时跳过，CI 中可选运行
+#[cfg(test)]
+#[cfg(feature = "integration_tests")]
+mod integration_tests {
+    use crate::ctp::conformance::{ConformanceConfig, ConformanceRunner};
+
+    #[tokio::test]
+    async fn test_tts_conformance() {
+        let Some(config) = ConformanceConfig::from_env().expect("TTS 一致性测试配置无效") else {
+            println!("未设置 CTP_TTS_CONFORMANCE，跳过 TTS 一致性测试");
+            return;
+        };
+        let report_path = config.report_path.clone();
+
+        let report = ConformanceRunner::new(config).run().await;
+        report.save(&report_path).expect("写出一致性报告失败");
+        println!("一致性报告已写入 {}", report_path.display());
+
+        assert!(report.passed, "{}", report.summary());
+    }
+}
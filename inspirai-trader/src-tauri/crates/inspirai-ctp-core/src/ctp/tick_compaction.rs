@@ -0,0 +1,1059 @@
+use crate::ctp::{CtpError, bar_import::trading_day_of, models::MarketDataTick, retention::{RetentionManager, RetentionReport}, storage::StorageBackend, task_manager::CancelToken};
+use arrow_array::{Array, Float64Array, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 原始 tick 文件压缩前的临时后缀
+const COMPACTING_SUFFIX: &str = "compacting";
+/// 每日索引文件名
+const INDEX_FILE: &str = "index.json";
+/// 默认原始 tick 目录
+pub const DEFAULT_RAW_DIR: &str = "./market_data/raw";
+/// 默认列式归档目录
+pub const DEFAULT_ARCHIVE_DIR: &str = "./market_data/archive";
+
+/// 行情存储压缩任务配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    /// 原始 tick 目录（`JsonLinesSpillStore` 的落盘目录）
+    pub raw_dir: PathBuf,
+    /// 列式归档目录，按交易日分子目录
+    pub archive_dir: PathBuf,
+    /// zstd 压缩级别
+    pub compression_level: i32,
+    /// 超过该天数的 tick 降采样为 1 秒 K 线，None 表示保留全部 tick
+    pub downsample_after_days: Option<u32>,
+    /// 每日收盘后执行的本地时间
+    pub run_after: NaiveTime,
+}
+
+impl CompactionConfig {
+    pub fn new(raw_dir: impl Into<PathBuf>, archive_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            raw_dir: raw_dir.into(),
+            archive_dir: archive_dir.into(),
+            compression_level: 3,
+            downsample_after_days: None,
+            run_after: NaiveTime::from_hms_opt(15, 30, 0).unwrap(),
+        }
+    }
+
+    /// 设置降采样天数
+    pub fn with_downsample_after_days(mut self, days: u32) -> Self {
+        self.downsample_after_days = Some(days);
+        self
+    }
+
+    /// 设置每日执行时间
+    pub fn with_run_after(mut self, run_after: NaiveTime) -> Self {
+        self.run_after = run_after;
+        self
+    }
+
+    /// 该时刻应归档的交易日：日盘收盘后 `run_after` 起至夜盘开始前，归档当天所属交易日
+    ///
+    /// 夜盘时段的 tick 属于下一交易日，待该交易日收盘后再归档，不会被记到当天
+    pub fn due_trading_day(&self, now: NaiveDateTime) -> Option<NaiveDate> {
+        let trading_day = trading_day_of(now);
+        (trading_day == now.date() && now.time() >= self.run_after).then_some(trading_day)
+    }
+}
+
+/// 归档数据粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum StorageGranularity {
+    Tick,
+    /// 1 秒 K 线
+    Bar1s,
+    Bar1m,
+    Bar5m,
+    Bar15m,
+    Bar30m,
+    Bar1h,
+    /// 日线
+    Bar1d,
+}
+
+impl StorageGranularity {
+    /// 交易日目录下的子目录名
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            StorageGranularity::Tick => "ticks",
+            StorageGranularity::Bar1s => "bars_1s",
+            StorageGranularity::Bar1m => "bars_1m",
+            StorageGranularity::Bar5m => "bars_5m",
+            StorageGranularity::Bar15m => "bars_15m",
+            StorageGranularity::Bar30m => "bars_30m",
+            StorageGranularity::Bar1h => "bars_1h",
+            StorageGranularity::Bar1d => "bars_1d",
+        }
+    }
+}
+
+/// 单个合约的索引项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayIndexEntry {
+    pub instrument_id: String,
+    pub granularity: StorageGranularity,
+    /// 相对交易日目录的文件路径
+    pub file: String,
+    pub rows: usize,
+    pub first_time: String,
+    pub last_time: String,
+    pub bytes: u64,
+}
+
+/// 交易日索引
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayIndex {
+    pub trading_day: NaiveDate,
+    pub entries: Vec<DayIndexEntry>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DayIndex {
+    fn new(trading_day: NaiveDate) -> Self {
+        Self {
+            trading_day,
+            entries: Vec::new(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 查找合约的索引项（任意粒度）
+    pub fn entry(&self, instrument_id: &str) -> Option<&DayIndexEntry> {
+        self.entries.iter().find(|e| e.instrument_id == instrument_id)
+    }
+
+    /// 查找合约指定粒度的索引项
+    pub fn entry_for(&self, instrument_id: &str, granularity: StorageGranularity) -> Option<&DayIndexEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.instrument_id == instrument_id && e.granularity == granularity)
+    }
+
+    fn upsert(&mut self, entry: DayIndexEntry) {
+        self.remove(&entry.instrument_id, entry.granularity);
+        self.entries.push(entry);
+        self.entries.sort_by(|a, b| a.instrument_id.cmp(&b.instrument_id));
+    }
+
+    fn remove(&mut self, instrument_id: &str, granularity: StorageGranularity) {
+        self.entries
+            .retain(|e| e.instrument_id != instrument_id || e.granularity != granularity);
+        self.updated_at = chrono::Utc::now();
+    }
+}
+
+/// 归档 K 线（tick 降采样或外部导入）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedBar {
+    pub instrument_id: String,
+    /// K 线起始时间 HH:MM:SS（日线为 00:00:00）
+    pub time: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// 周期内成交量
+    pub volume: i64,
+    /// 周期内成交额
+    pub turnover: f64,
+    pub open_interest: i64,
+    pub tick_count: i32,
+}
+
+/// 压缩任务执行结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompactionReport {
+    pub trading_day: Option<NaiveDate>,
+    pub instruments: usize,
+    pub rows: usize,
+    /// 原始文件大小
+    pub raw_bytes: u64,
+    /// 压缩后文件大小
+    pub compressed_bytes: u64,
+    /// 本次降采样的交易日
+    pub downsampled_days: Vec<NaiveDate>,
+    pub errors: Vec<String>,
+    /// 压缩后执行的数据保留清理
+    #[serde(default)]
+    pub retention: Option<RetentionReport>,
+}
+
+/// 行情存储压缩任务
+///
+/// 每个交易日收盘后将原始 JSON Lines tick 文件转为 zstd 压缩的 parquet，
+/// 生成交易日索引，并可将较早的 tick 降采样为 1 秒 K 线以控制磁盘占用
+pub struct TickCompactor {
+    config: CompactionConfig,
+    cancel: Option<CancelToken>,
+    retention: Option<RetentionManager>,
+    analytics: Option<Arc<dyn StorageBackend>>,
+}
+
+impl TickCompactor {
+    pub fn new(config: CompactionConfig) -> Self {
+        Self {
+            config,
+            cancel: None,
+            retention: None,
+            analytics: None,
+        }
+    }
+
+    /// 压缩完成后按保留策略清理过期数据
+    pub fn with_retention(mut self, retention: RetentionManager) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// 归档的同时写入分析存储后端，供 tick/K 线查询；写入失败不影响归档
+    pub fn with_analytics(mut self, store: Arc<dyn StorageBackend>) -> Self {
+        self.analytics = Some(store);
+        self
+    }
+
+    /// 设置取消标记，压缩在合约之间检查
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn config(&self) -> &CompactionConfig {
+        &self.config
+    }
+
+    /// 交易日归档目录
+    pub fn day_dir(&self, trading_day: NaiveDate) -> PathBuf {
+        self.config.archive_dir.join(trading_day.format("%Y%m%d").to_string())
+    }
+
+    /// 读取交易日索引
+    pub fn load_index(&self, trading_day: NaiveDate) -> Result<Option<DayIndex>, CtpError> {
+        let path = self.day_dir(trading_day).join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| CtpError::ConversionError(format!("解析行情索引失败: {}", e)))
+    }
+
+    /// 执行一次完整任务：压缩当日原始数据，并按配置降采样
+    pub fn run(&self, trading_day: NaiveDate) -> Result<CompactionReport, CtpError> {
+        self.run_with_progress(trading_day, |_, _| {})
+    }
+
+    /// 执行完整任务，每压缩完一个合约回调一次（已完成, 总数）
+    pub fn run_with_progress(
+        &self,
+        trading_day: NaiveDate,
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<CompactionReport, CtpError> {
+        let mut report = self.compact_day_with_progress(trading_day, on_progress)?;
+        if let Some(days) = self.config.downsample_after_days {
+            let cutoff = trading_day - chrono::Duration::days(days as i64);
+            match self.downsample_before(cutoff) {
+                Ok(downsampled) => report.downsampled_days = downsampled,
+                Err(e) => report.errors.push(format!("降采样失败: {}", e)),
+            }
+        }
+        if let Some(retention) = self.retention.as_ref().filter(|r| r.is_enabled()) {
+            let cleanup = retention.enforce(chrono::Utc::now());
+            report.errors.extend(cleanup.errors.iter().map(|e| format!("数据保留清理失败: {}", e)));
+            report.retention = Some(cleanup);
+        }
+        Ok(report)
+    }
+
+    /// 将原始 tick 文件压缩归档到指定交易日
+    pub fn compact_day(&self, trading_day: NaiveDate) -> Result<CompactionReport, CtpError> {
+        self.compact_day_with_progress(trading_day, |_, _| {})
+    }
+
+    fn compact_day_with_progress(
+        &self,
+        trading_day: NaiveDate,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<CompactionReport, CtpError> {
+        let mut report = CompactionReport {
+            trading_day: Some(trading_day),
+            ..CompactionReport::default()
+        };
+        if !self.config.raw_dir.exists() {
+            return Ok(report);
+        }
+
+        let day_dir = self.day_dir(trading_day);
+        let ticks_dir = day_dir.join(StorageGranularity::Tick.dir_name());
+        std::fs::create_dir_all(&ticks_dir)?;
+        let mut index = self.load_index(trading_day)?.unwrap_or_else(|| DayIndex::new(trading_day));
+
+        let raw_files = self.claim_raw_files()?;
+        let total = raw_files.len();
+        for (done, raw_path) in raw_files.into_iter().enumerate() {
+            // 取消后剩余的 .compacting 文件留待下次运行
+            if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                break;
+            }
+            on_progress(done, total);
+            let Some(instrument_id) = instrument_from_raw(&raw_path) else {
+                continue;
+            };
+
+            match self.compact_file(&raw_path, &instrument_id, &ticks_dir) {
+                Ok((entry, raw_bytes)) => {
+                    report.instruments += 1;
+                    report.rows += entry.rows;
+                    report.raw_bytes += raw_bytes;
+                    report.compressed_bytes += entry.bytes;
+                    index.upsert(entry);
+                    std::fs::remove_file(&raw_path)?;
+                    if let Err(e) = self.mirror_ticks(trading_day, &instrument_id) {
+                        warn!("合约 {} 的 tick 写入分析库失败: {}", instrument_id, e);
+                        report.errors.push(format!("{}: 写入分析库失败: {}", instrument_id, e));
+                    }
+                }
+                Err(e) => {
+                    // 保留 .compacting 文件，下次运行时重试
+                    warn!("压缩合约 {} 的行情失败: {}", instrument_id, e);
+                    report.errors.push(format!("{}: {}", instrument_id, e));
+                }
+            }
+        }
+
+        self.save_index(&index)?;
+        if let Some(cancel) = &self.cancel {
+            cancel.check()?;
+        }
+        on_progress(total, total);
+        info!(
+            "交易日 {} 行情压缩完成: {} 个合约，{} 条，{} -> {} 字节",
+            trading_day, report.instruments, report.rows, report.raw_bytes, report.compressed_bytes
+        );
+        Ok(report)
+    }
+
+    /// 将早于 `cutoff` 的交易日 tick 降采样为 1 秒 K 线
+    pub fn downsample_before(&self, cutoff: NaiveDate) -> Result<Vec<NaiveDate>, CtpError> {
+        let mut downsampled = Vec::new();
+        if !self.config.archive_dir.exists() {
+            return Ok(downsampled);
+        }
+
+        for entry in std::fs::read_dir(&self.config.archive_dir)? {
+            let entry = entry?;
+            let Some(day) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%Y%m%d").ok())
+            else {
+                continue;
+            };
+            if day >= cutoff || !entry.path().join(StorageGranularity::Tick.dir_name()).exists() {
+                continue;
+            }
+
+            self.downsample_day(day)?;
+            downsampled.push(day);
+        }
+
+        downsampled.sort();
+        Ok(downsampled)
+    }
+
+    /// 归档中已有的交易日
+    pub fn archived_days(&self) -> Result<Vec<NaiveDate>, CtpError> {
+        let mut days = Vec::new();
+        if !self.config.archive_dir.exists() {
+            return Ok(days);
+        }
+        for entry in std::fs::read_dir(&self.config.archive_dir)? {
+            let name = entry?.file_name();
+            if let Some(day) = name.to_str().and_then(|n| NaiveDate::parse_from_str(n, "%Y%m%d").ok()) {
+                days.push(day);
+            }
+        }
+        days.sort();
+        Ok(days)
+    }
+
+    /// 删除交易日某一粒度的全部归档并更新索引
+    pub fn purge(&self, trading_day: NaiveDate, granularity: StorageGranularity) -> Result<(), CtpError> {
+        let dir = self.day_dir(trading_day).join(granularity.dir_name());
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        if let Some(mut index) = self.load_index(trading_day)? {
+            index.entries.retain(|e| e.granularity != granularity);
+            index.updated_at = chrono::Utc::now();
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+
+    /// 读取归档的 tick 数据
+    pub fn read_ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        let path = self.day_dir(trading_day).join(StorageGranularity::Tick.dir_name()).join(format!("{}.parquet", instrument_id));
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        read_tick_parquet(&path)
+    }
+
+    /// 读取尚未压缩的原始 tick（含压缩中的文件），按落盘顺序
+    pub fn read_pending_ticks(&self, instrument_id: &str) -> Result<Vec<MarketDataTick>, CtpError> {
+        let path = self.config.raw_dir.join(format!("{}.jsonl", instrument_id));
+        let mut ticks = Vec::new();
+        for path in [path.with_extension(format!("jsonl.{}", COMPACTING_SUFFIX)), path] {
+            if path.exists() {
+                ticks.extend(read_raw_ticks(&path)?);
+            }
+        }
+        Ok(ticks)
+    }
+
+    /// 读取归档的 K 线
+    pub fn read_bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+    ) -> Result<Vec<ArchivedBar>, CtpError> {
+        let path = self.bar_path(trading_day, instrument_id, granularity);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        read_bar_parquet(&path)
+    }
+
+    /// 读取交易日的日线；没有日线归档时依次由 1 分钟 K 线、1 秒 K 线或 tick 汇总
+    pub fn read_daily_bar(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<Option<ArchivedBar>, CtpError> {
+        if let Some(bar) = self.read_bars(trading_day, instrument_id, StorageGranularity::Bar1d)?.pop() {
+            return Ok(Some(bar));
+        }
+        for granularity in [StorageGranularity::Bar1m, StorageGranularity::Bar1s] {
+            let bars = self.read_bars(trading_day, instrument_id, granularity)?;
+            if !bars.is_empty() {
+                return Ok(aggregate_bars(&bars));
+            }
+        }
+        Ok(aggregate_bars(&downsample_to_seconds(&self.read_ticks(trading_day, instrument_id)?)))
+    }
+
+    /// 写入 K 线并更新索引，与已有数据按时间合并（同一时间以新数据为准）
+    pub fn write_bars(
+        &self,
+        trading_day: NaiveDate,
+        instrument_id: &str,
+        granularity: StorageGranularity,
+        bars: &[ArchivedBar],
+    ) -> Result<DayIndexEntry, CtpError> {
+        if granularity == StorageGranularity::Tick {
+            return Err(CtpError::InvalidParameter("K 线不能写入 tick 目录".to_string()));
+        }
+
+        let path = self.bar_path(trading_day, instrument_id, granularity);
+        std::fs::create_dir_all(path.parent().unwrap_or(&self.config.archive_dir))?;
+
+        let mut merged: std::collections::BTreeMap<String, ArchivedBar> = if path.exists() {
+            read_bar_parquet(&path)?.into_iter().map(|b| (b.time.clone(), b)).collect()
+        } else {
+            Default::default()
+        };
+        for bar in bars {
+            merged.insert(bar.time.clone(), bar.clone());
+        }
+        let merged: Vec<ArchivedBar> = merged.into_values().collect();
+
+        write_bar_parquet(&path, &merged, self.config.compression_level)?;
+        let entry = index_entry(
+            instrument_id,
+            granularity,
+            format!("{}/{}.parquet", granularity.dir_name(), instrument_id),
+            merged.len(),
+            merged.first().map(|b| b.time.clone()),
+            merged.last().map(|b| b.time.clone()),
+            std::fs::metadata(&path)?.len(),
+        );
+
+        let mut index = self.load_index(trading_day)?.unwrap_or_else(|| DayIndex::new(trading_day));
+        index.upsert(entry.clone());
+        self.save_index(&index)?;
+        if let Some(store) = &self.analytics {
+            if let Err(e) = store.put_bars(trading_day, granularity, &merged) {
+                warn!("合约 {} 的 K 线写入分析库失败: {}", instrument_id, e);
+            }
+        }
+        Ok(entry)
+    }
+
+    /// 将归档后的整日 tick 同步到分析库
+    fn mirror_ticks(&self, trading_day: NaiveDate, instrument_id: &str) -> Result<(), CtpError> {
+        let Some(store) = &self.analytics else {
+            return Ok(());
+        };
+        store.put_ticks(trading_day, instrument_id, &self.read_ticks(trading_day, instrument_id)?)?;
+        Ok(())
+    }
+
+    fn bar_path(&self, trading_day: NaiveDate, instrument_id: &str, granularity: StorageGranularity) -> PathBuf {
+        self.day_dir(trading_day)
+            .join(granularity.dir_name())
+            .join(format!("{}.parquet", instrument_id))
+    }
+
+    /// 启动后台任务，每个交易日 `run_after` 之后执行一次
+    pub fn spawn(self: Arc<Self>, check_interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut last_run: Option<NaiveDate> = None;
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+
+                let Some(trading_day) = self.config.due_trading_day(Local::now().naive_local()) else {
+                    continue;
+                };
+                if last_run == Some(trading_day) {
+                    continue;
+                }
+
+                let compactor = self.clone();
+                match tokio::task::spawn_blocking(move || compactor.run(trading_day)).await {
+                    Ok(Ok(report)) => {
+                        if !report.errors.is_empty() {
+                            warn!("行情压缩存在 {} 个错误: {:?}", report.errors.len(), report.errors);
+                        }
+                    }
+                    Ok(Err(e)) => warn!("行情压缩任务失败: {}", e),
+                    Err(e) => warn!("行情压缩任务异常退出: {}", e),
+                }
+                last_run = Some(trading_day);
+            }
+        })
+    }
+
+    /// 将待压缩的原始文件重命名，之后的落盘写入新文件
+    fn claim_raw_files(&self) -> Result<Vec<PathBuf>, CtpError> {
+        let mut claimed = Vec::new();
+        for entry in std::fs::read_dir(&self.config.raw_dir)? {
+            let path = entry?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("jsonl") => {
+                    let target = path.with_extension(format!("jsonl.{}", COMPACTING_SUFFIX));
+                    std::fs::rename(&path, &target)?;
+                    claimed.push(target);
+                }
+                // 上次未完成的文件
+                Some(COMPACTING_SUFFIX) => claimed.push(path),
+                _ => {}
+            }
+        }
+        claimed.sort();
+        Ok(claimed)
+    }
+
+    fn compact_file(
+        &self,
+        raw_path: &Path,
+        instrument_id: &str,
+        ticks_dir: &Path,
+    ) -> Result<(DayIndexEntry, u64), CtpError> {
+        let raw_bytes = std::fs::metadata(raw_path)?.len();
+        let target = ticks_dir.join(format!("{}.parquet", instrument_id));
+
+        // 同一交易日重复运行时与已有归档合并
+        let mut ticks = if target.exists() {
+            read_tick_parquet(&target)?
+        } else {
+            Vec::new()
+        };
+        ticks.extend(read_raw_ticks(raw_path)?);
+
+        write_tick_parquet(&target, &ticks, self.config.compression_level)?;
+        let entry = index_entry(
+            instrument_id,
+            StorageGranularity::Tick,
+            format!("{}/{}.parquet", StorageGranularity::Tick.dir_name(), instrument_id),
+            ticks.len(),
+            ticks.first().map(|t| t.update_time.clone()),
+            ticks.last().map(|t| t.update_time.clone()),
+            std::fs::metadata(&target)?.len(),
+        );
+        Ok((entry, raw_bytes))
+    }
+
+    fn downsample_day(&self, trading_day: NaiveDate) -> Result<(), CtpError> {
+        let ticks_dir = self.day_dir(trading_day).join(StorageGranularity::Tick.dir_name());
+
+        for entry in std::fs::read_dir(&ticks_dir)? {
+            let path = entry?.path();
+            let Some(instrument_id) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else {
+                continue;
+            };
+
+            let bars = downsample_to_seconds(&read_tick_parquet(&path)?);
+            self.write_bars(trading_day, &instrument_id, StorageGranularity::Bar1s, &bars)?;
+            std::fs::remove_file(&path)?;
+
+            let mut index = self.load_index(trading_day)?.unwrap_or_else(|| DayIndex::new(trading_day));
+            index.remove(&instrument_id, StorageGranularity::Tick);
+            self.save_index(&index)?;
+        }
+
+        std::fs::remove_dir(&ticks_dir)?;
+        info!("交易日 {} 的 tick 已降采样为 1 秒 K 线", trading_day);
+        Ok(())
+    }
+
+    fn save_index(&self, index: &DayIndex) -> Result<(), CtpError> {
+        let day_dir = self.day_dir(index.trading_day);
+        std::fs::create_dir_all(&day_dir)?;
+        let content = serde_json::to_string_pretty(index)
+            .map_err(|e| CtpError::ConversionError(format!("序列化行情索引失败: {}", e)))?;
+        std::fs::write(day_dir.join(INDEX_FILE), content)?;
+        Ok(())
+    }
+}
+
+/// 按 `update_time` 聚合为 1 秒 K 线（输入按时间顺序）
+pub fn downsample_to_seconds(ticks: &[MarketDataTick]) -> Vec<ArchivedBar> {
+    let mut bars: Vec<ArchivedBar> = Vec::new();
+    let mut prev: Option<&MarketDataTick> = None;
+
+    for tick in ticks {
+        // 成交量/成交额为累计值，取相邻 tick 的增量
+        let (volume, turnover) = match prev {
+            Some(p) => ((tick.volume - p.volume).max(0), (tick.turnover - p.turnover).max(0.0)),
+            None => (0, 0.0),
+        };
+        prev = Some(tick);
+
+        match bars.last_mut() {
+            Some(bar) if bar.time == tick.update_time => {
+                bar.high = bar.high.max(tick.last_price);
+                bar.low = bar.low.min(tick.last_price);
+                bar.close = tick.last_price;
+                bar.volume += volume;
+                bar.turnover += turnover;
+                bar.open_interest = tick.open_interest;
+                bar.tick_count += 1;
+            }
+            _ => bars.push(ArchivedBar {
+                instrument_id: tick.instrument_id.clone(),
+                time: tick.update_time.clone(),
+                open: tick.last_price,
+                high: tick.last_price,
+                low: tick.last_price,
+                close: tick.last_price,
+                volume,
+                turnover,
+                open_interest: tick.open_interest,
+                tick_count: 1,
+            }),
+        }
+    }
+
+    bars
+}
+
+/// 将按时间顺序的 K 线汇总为一根（日线起始时间为 00:00:00）
+pub fn aggregate_bars(bars: &[ArchivedBar]) -> Option<ArchivedBar> {
+    let (first, last) = (bars.first()?, bars.last()?);
+    Some(ArchivedBar {
+        instrument_id: first.instrument_id.clone(),
+        time: "00:00:00".to_string(),
+        open: first.open,
+        high: bars.iter().map(|b| b.high).fold(f64::MIN, f64::max),
+        low: bars.iter().map(|b| b.low).fold(f64::MAX, f64::min),
+        close: last.close,
+        volume: bars.iter().map(|b| b.volume).sum(),
+        turnover: bars.iter().map(|b| b.turnover).sum(),
+        open_interest: last.open_interest,
+        tick_count: bars.iter().map(|b| b.tick_count).sum(),
+    })
+}
+
+fn instrument_from_raw(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name
+        .strip_suffix(&format!(".jsonl.{}", COMPACTING_SUFFIX))
+        .or_else(|| name.strip_suffix(".jsonl"))?;
+    Some(stem.to_string())
+}
+
+fn index_entry(
+    instrument_id: &str,
+    granularity: StorageGranularity,
+    file: String,
+    rows: usize,
+    first_time: Option<String>,
+    last_time: Option<String>,
+    bytes: u64,
+) -> DayIndexEntry {
+    DayIndexEntry {
+        instrument_id: instrument_id.to_string(),
+        granularity,
+        file,
+        rows,
+        first_time: first_time.unwrap_or_default(),
+        last_time: last_time.unwrap_or_default(),
+        bytes,
+    }
+}
+
+fn read_raw_ticks(path: &Path) -> Result<Vec<MarketDataTick>, CtpError> {
+    let file = std::fs::File::open(path)?;
+    let mut ticks = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        match serde_json::from_str::<MarketDataTick>(&line?) {
+            Ok(tick) => ticks.push(tick),
+            Err(e) => warn!("跳过无法解析的行情记录: {}", e),
+        }
+    }
+    Ok(ticks)
+}
+
+fn parquet_error(e: impl std::fmt::Display) -> CtpError {
+    CtpError::ConversionError(format!("parquet 读写失败: {}", e))
+}
+
+fn write_batch(path: &Path, batch: RecordBatch, compression_level: i32) -> Result<(), CtpError> {
+    let level = ZstdLevel::try_new(compression_level).map_err(parquet_error)?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(level))
+        .build();
+
+    // 先写临时文件再替换，避免中断时损坏已有归档
+    let tmp = path.with_extension("parquet.tmp");
+    let file = std::fs::File::create(&tmp)?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props)).map_err(parquet_error)?;
+    writer.write(&batch).map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_batches(path: &Path) -> Result<Vec<RecordBatch>, CtpError> {
+    let file = std::fs::File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(parquet_error)?
+        .build()
+        .map_err(parquet_error)?;
+    reader.map(|batch| batch.map_err(parquet_error)).collect()
+}
+
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T, CtpError> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_any().downcast_ref::<T>())
+        .ok_or_else(|| CtpError::ConversionError(format!("parquet 缺少列: {}", name)))
+}
+
+pub(crate) fn tick_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("instrument_id", DataType::Utf8, false),
+        Field::new("last_price", DataType::Float64, false),
+        Field::new("volume", DataType::Int64, false),
+        Field::new("turnover", DataType::Float64, false),
+        Field::new("open_interest", DataType::Int64, false),
+        Field::new("bid_price1", DataType::Float64, false),
+        Field::new("bid_volume1", DataType::Int32, false),
+        Field::new("ask_price1", DataType::Float64, false),
+        Field::new("ask_volume1", DataType::Int32, false),
+        Field::new("update_time", DataType::Utf8, false),
+        Field::new("update_millisec", DataType::Int32, false),
+        Field::new("change_percent", DataType::Float64, false),
+        Field::new("change_amount", DataType::Float64, false),
+        Field::new("open_price", DataType::Float64, false),
+        Field::new("highest_price", DataType::Float64, false),
+        Field::new("lowest_price", DataType::Float64, false),
+        Field::new("pre_close_price", DataType::Float64, false),
+    ]))
+}
+
+fn write_tick_parquet(path: &Path, ticks: &[MarketDataTick], compression_level: i32) -> Result<(), CtpError> {
+    write_batch(path, tick_batch(ticks)?, compression_level)
+}
+
+/// tick 转为列式数据
+pub(crate) fn tick_batch(ticks: &[MarketDataTick]) -> Result<RecordBatch, CtpError> {
+    let f64_col = |f: fn(&MarketDataTick) -> f64| Arc::new(Float64Array::from_iter_values(ticks.iter().map(f)));
+    RecordBatch::try_new(
+        tick_schema(),
+        vec![
+            Arc::new(StringArray::from_iter_values(ticks.iter().map(|t| t.instrument_id.as_str()))),
+            f64_col(|t| t.last_price),
+            Arc::new(Int64Array::from_iter_values(ticks.iter().map(|t| t.volume))),
+            f64_col(|t| t.turnover),
+            Arc::new(Int64Array::from_iter_values(ticks.iter().map(|t| t.open_interest))),
+            f64_col(|t| t.bid_price1),
+            Arc::new(Int32Array::from_iter_values(ticks.iter().map(|t| t.bid_volume1))),
+            f64_col(|t| t.ask_price1),
+            Arc::new(Int32Array::from_iter_values(ticks.iter().map(|t| t.ask_volume1))),
+            Arc::new(StringArray::from_iter_values(ticks.iter().map(|t| t.update_time.as_str()))),
+            Arc::new(Int32Array::from_iter_values(ticks.iter().map(|t| t.update_millisec))),
+            f64_col(|t| t.change_percent),
+            f64_col(|t| t.change_amount),
+            f64_col(|t| t.open_price),
+            f64_col(|t| t.highest_price),
+            f64_col(|t| t.lowest_price),
+            f64_col(|t| t.pre_close_price),
+        ],
+    )
+    .map_err(parquet_error)
+}
+
+fn read_tick_parquet(path: &Path) -> Result<Vec<MarketDataTick>, CtpError> {
+    let mut ticks = Vec::new();
+    for batch in read_batches(path)? {
+        let instrument_id = column::<StringArray>(&batch, "instrument_id")?;
+        let last_price = column::<Float64Array>(&batch, "last_price")?;
+        let volume = column::<Int64Array>(&batch, "volume")?;
+        let turnover = column::<Float64Array>(&batch, "turnover")?;
+        let open_interest = column::<Int64Array>(&batch, "open_interest")?;
+        let bid_price1 = column::<Float64Array>(&batch, "bid_price1")?;
+        let bid_volume1 = column::<Int32Array>(&batch, "bid_volume1")?;
+        let ask_price1 = column::<Float64Array>(&batch, "ask_price1")?;
+        let ask_volume1 = column::<Int32Array>(&batch, "ask_volume1")?;
+        let update_time = column::<StringArray>(&batch, "update_time")?;
+        let update_millisec = column::<Int32Array>(&batch, "update_millisec")?;
+        let change_percent = column::<Float64Array>(&batch, "change_percent")?;
+        let change_amount = column::<Float64Array>(&batch, "change_amount")?;
+        let open_price = column::<Float64Array>(&batch, "open_price")?;
+        let highest_price = column::<Float64Array>(&batch, "highest_price")?;
+        let lowest_price = column::<Float64Array>(&batch, "lowest_price")?;
+        let pre_close_price = column::<Float64Array>(&batch, "pre_close_price")?;
+
+        for i in 0..batch.num_rows() {
+            ticks.push(MarketDataTick {
+                instrument_id: instrument_id.value(i).to_string(),
+                last_price: last_price.value(i),
+                volume: volume.value(i),
+                turnover: turnover.value(i),
+                open_interest: open_interest.value(i),
+                bid_price1: bid_price1.value(i),
+                bid_volume1: bid_volume1.value(i),
+                ask_price1: ask_price1.value(i),
+                ask_volume1: ask_volume1.value(i),
+                update_time: update_time.value(i).to_string(),
+                update_millisec: update_millisec.value(i),
+                change_percent: change_percent.value(i),
+                change_amount: change_amount.value(i),
+                open_price: open_price.value(i),
+                highest_price: highest_price.value(i),
+                lowest_price: lowest_price.value(i),
+                pre_close_price: pre_close_price.value(i),
+                price_limit: None,
+                trace: None,
+                source: None,
+            });
+        }
+    }
+    Ok(ticks)
+}
+
+pub(crate) fn bar_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("instrument_id", DataType::Utf8, false),
+        Field::new("time", DataType::Utf8, false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("volume", DataType::Int64, false),
+        Field::new("turnover", DataType::Float64, false),
+        Field::new("open_interest", DataType::Int64, false),
+        Field::new("tick_count", DataType::Int32, false),
+    ]))
+}
+
+fn write_bar_parquet(path: &Path, bars: &[ArchivedBar], compression_level: i32) -> Result<(), CtpError> {
+    write_batch(path, bar_batch(bars)?, compression_level)
+}
+
+/// K 线转为列式数据
+pub(crate) fn bar_batch(bars: &[ArchivedBar]) -> Result<RecordBatch, CtpError> {
+    let f64_col = |f: fn(&ArchivedBar) -> f64| Arc::new(Float64Array::from_iter_values(bars.iter().map(f)));
+    RecordBatch::try_new(
+        bar_schema(),
+        vec![
+            Arc::new(StringArray::from_iter_values(bars.iter().map(|b| b.instrument_id.as_str()))),
+            Arc::new(StringArray::from_iter_values(bars.iter().map(|b| b.time.as_str()))),
+            f64_col(|b| b.open),
+            f64_col(|b| b.high),
+            f64_col(|b| b.low),
+            f64_col(|b| b.close),
+            Arc::new(Int64Array::from_iter_values(bars.iter().map(|b| b.volume))),
+            f64_col(|b| b.turnover),
+            Arc::new(Int64Array::from_iter_values(bars.iter().map(|b| b.open_interest))),
+            Arc::new(Int32Array::from_iter_values(bars.iter().map(|b| b.tick_count))),
+        ],
+    )
+    .map_err(parquet_error)
+}
+
+fn read_bar_parquet(path: &Path) -> Result<Vec<ArchivedBar>, CtpError> {
+    let mut bars = Vec::new();
+    for batch in read_batches(path)? {
+        let instrument_id = column::<StringArray>(&batch, "instrument_id")?;
+        let time = column::<StringArray>(&batch, "time")?;
+        let open = column::<Float64Array>(&batch, "open")?;
+        let high = column::<Float64Array>(&batch, "high")?;
+        let low = column::<Float64Array>(&batch, "low")?;
+        let close = column::<Float64Array>(&batch, "close")?;
+        let volume = column::<Int64Array>(&batch, "volume")?;
+        let turnover = column::<Float64Array>(&batch, "turnover")?;
+        let open_interest = column::<Int64Array>(&batch, "open_interest")?;
+        let tick_count = column::<Int32Array>(&batch, "tick_count")?;
+
+        for i in 0..batch.num_rows() {
+            bars.push(ArchivedBar {
+                instrument_id: instrument_id.value(i).to_string(),
+                time: time.value(i).to_string(),
+                open: open.value(i),
+                high: high.value(i),
+                low: low.value(i),
+                close: close.value(i),
+                volume: volume.value(i),
+                turnover: turnover.value(i),
+                open_interest: open_interest.value(i),
+                tick_count: tick_count.value(i),
+            });
+        }
+    }
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{JsonLinesSpillStore, TickSpillStore};
+
+    fn tick(time: &str, price: f64, volume: i64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: "rb2501".to_string(),
+            last_price: price,
+            volume,
+            turnover: price * volume as f64,
+            open_interest: 100,
+            bid_price1: price,
+            bid_volume1: 1,
+            ask_price1: price,
+            ask_volume1: 1,
+            update_time: time.to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: price,
+            highest_price: price,
+            lowest_price: price,
+            pre_close_price: price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_downsample_to_seconds() {
+        let bars = downsample_to_seconds(&[
+            tick("09:30:00", 10.0, 1),
+            tick("09:30:00", 12.0, 3),
+            tick("09:30:00", 11.0, 4),
+            tick("09:30:01", 9.0, 10),
+        ]);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].open, bars[0].high, bars[0].low, bars[0].close), (10.0, 12.0, 10.0, 11.0));
+        assert_eq!(bars[0].volume, 3);
+        assert_eq!(bars[0].tick_count, 3);
+        assert_eq!(bars[1].volume, 6);
+    }
+
+    #[test]
+    fn test_due_trading_day_skips_night_session_and_weekend() {
+        let config = CompactionConfig::new("raw", "archive");
+        // 2025-01-03 为周五
+        let at = |day: u32, h: u32, m: u32| NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(h, m, 0).unwrap();
+        let friday = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        assert_eq!(config.due_trading_day(at(3, 15, 0)), None);
+        assert_eq!(config.due_trading_day(at(3, 15, 30)), Some(friday));
+        // 周五夜盘属于下周一
+        assert_eq!(config.due_trading_day(at(3, 21, 30)), None);
+        assert_eq!(config.due_trading_day(at(4, 16, 0)), None);
+    }
+
+    #[test]
+    fn test_compact_and_downsample() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        let raw_dir = root.join("raw");
+        let store = JsonLinesSpillStore::new(&raw_dir).unwrap();
+        store
+            .spill("rb2501", &[tick("09:30:00", 10.0, 1), tick("09:30:01", 11.0, 2)])
+            .unwrap();
+
+        let compactor = TickCompactor::new(CompactionConfig::new(&raw_dir, root.join("archive")));
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+        let report = compactor.compact_day(day).unwrap();
+        assert_eq!(report.instruments, 1);
+        assert_eq!(report.rows, 2);
+        assert!(report.errors.is_empty());
+        assert!(std::fs::read_dir(&raw_dir).unwrap().next().is_none());
+
+        let index = compactor.load_index(day).unwrap().unwrap();
+        let entry = index.entry("rb2501").unwrap();
+        assert_eq!(entry.granularity, StorageGranularity::Tick);
+        assert_eq!(entry.last_time, "09:30:01");
+
+        let ticks = compactor.read_ticks(day, "rb2501").unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[1].last_price, 11.0);
+
+        let downsampled = compactor.downsample_before(day + chrono::Duration::days(1)).unwrap();
+        assert_eq!(downsampled, vec![day]);
+        assert!(compactor.read_ticks(day, "rb2501").unwrap().is_empty());
+        assert_eq!(compactor.read_bars(day, "rb2501", StorageGranularity::Bar1s).unwrap().len(), 2);
+
+        let index = compactor.load_index(day).unwrap().unwrap();
+        assert_eq!(index.entry("rb2501").unwrap().granularity, StorageGranularity::Bar1s);
+        assert!(index.entry_for("rb2501", StorageGranularity::Tick).is_none());
+    }
+
+    #[test]
+    fn test_compaction_mirrors_to_analytics_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        let raw_dir = root.join("raw");
+        JsonLinesSpillStore::new(&raw_dir)
+            .unwrap()
+            .spill("rb2501", &[tick("09:30:00", 10.0, 1), tick("09:30:01", 11.0, 2)])
+            .unwrap();
+
+        let analytics = Arc::new(crate::ctp::storage::SqliteStore::in_memory().unwrap());
+        let compactor = TickCompactor::new(CompactionConfig::new(&raw_dir, root.join("archive")))
+            .with_analytics(analytics.clone());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+        assert!(compactor.compact_day(day).unwrap().errors.is_empty());
+        assert_eq!(analytics.load_ticks(day, "rb2501").unwrap().len(), 2);
+
+        compactor.write_bars(day, "rb2501", StorageGranularity::Bar1m, &downsample_to_seconds(&compactor.read_ticks(day, "rb2501").unwrap())).unwrap();
+        assert_eq!(analytics.load_bars(day, "rb2501", StorageGranularity::Bar1m).unwrap().len(), 2);
+    }
+}
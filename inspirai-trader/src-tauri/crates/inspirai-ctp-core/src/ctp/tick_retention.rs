@@ -0,0 +1,552 @@
+use crate::ctp::{CtpError, models::MarketDataTick};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 行情缓存保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickRetentionConfig {
+    /// 每个合约最多保留的 tick 数
+    pub max_ticks_per_instrument: usize,
+    /// 全部合约的内存预算（字节，估算值）
+    pub max_memory_bytes: usize,
+    /// 淘汰数据攒够该数量后批量落盘
+    pub spill_batch_size: usize,
+}
+
+impl Default for TickRetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_ticks_per_instrument: 1000,
+            max_memory_bytes: 64 * 1024 * 1024,
+            spill_batch_size: 256,
+        }
+    }
+}
+
+/// 缓存保留统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionStats {
+    /// 内存中的 tick 数
+    pub cached_ticks: usize,
+    /// 估算内存占用（字节）
+    pub estimated_bytes: usize,
+    /// 累计淘汰数
+    pub evicted_ticks: u64,
+    /// 累计落盘数
+    pub spilled_ticks: u64,
+    /// 落盘失败次数
+    pub spill_errors: u64,
+}
+
+/// 淘汰数据的落盘存储
+pub trait TickSpillStore {
+    /// 追加写入淘汰的 tick（按时间顺序）
+    fn spill(&self, instrument_id: &str, ticks: &[MarketDataTick]) -> Result<(), CtpError>;
+
+    /// 读取最近的 `limit` 条落盘数据（按时间顺序）
+    fn load(&self, instrument_id: &str, limit: usize) -> Result<Vec<MarketDataTick>, CtpError>;
+
+    /// 删除合约的落盘数据
+    fn remove(&self, instrument_id: &str) -> Result<(), CtpError>;
+
+    /// 删除全部落盘数据
+    fn clear(&self) -> Result<(), CtpError>;
+}
+
+/// 从文件末尾反向读取时每次读入的字节数
+const TAIL_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// 基于 JSON Lines 文件的落盘存储，每个合约一个文件
+pub struct JsonLinesSpillStore {
+    dir: PathBuf,
+}
+
+impl JsonLinesSpillStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, CtpError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn file_path(&self, instrument_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", instrument_id))
+    }
+}
+
+impl TickSpillStore for JsonLinesSpillStore {
+    fn spill(&self, instrument_id: &str, ticks: &[MarketDataTick]) -> Result<(), CtpError> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path(instrument_id))?;
+        let mut writer = std::io::BufWriter::new(file);
+
+        for tick in ticks {
+            let line = serde_json::to_string(tick)
+                .map_err(|e| CtpError::ConversionError(format!("序列化行情失败: {}", e)))?;
+            writeln!(writer, "{}", line)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// 从文件末尾向前分块读取，只解析最后 `limit` 行，不随文件大小占用内存
+    fn load(&self, instrument_id: &str, limit: usize) -> Result<Vec<MarketDataTick>, CtpError> {
+        let path = self.file_path(instrument_id);
+        if limit == 0 || !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let lines = read_tail_lines(&path, limit)?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| serde_json::from_slice::<MarketDataTick>(line).ok())
+            .collect())
+    }
+
+    fn remove(&self, instrument_id: &str) -> Result<(), CtpError> {
+        match std::fs::remove_file(self.file_path(instrument_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn clear(&self) -> Result<(), CtpError> {
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 文件最后 `limit` 个非空行
+fn read_tail_lines(path: &Path, limit: usize) -> Result<Vec<Vec<u8>>, CtpError> {
+    let mut file = std::fs::File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut tail: Vec<u8> = Vec::new();
+
+    // 读够 limit 个完整行（多一个换行作为首行的边界）或到达文件开头
+    while pos > 0 && tail.iter().filter(|&&b| b == b'\n').count() <= limit {
+        let read = TAIL_CHUNK_BYTES.min(pos);
+        pos -= read;
+        let mut chunk = vec![0u8; read as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+
+    let mut lines: Vec<Vec<u8>> = tail
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(<[u8]>::to_vec)
+        .collect();
+    // 未读到文件开头时第一段可能是不完整的行，此时其后已有足够的完整行
+    if pos > 0 {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(limit);
+    Ok(lines.split_off(skip))
+}
+
+/// tick 历史及其落盘存储
+///
+/// 淘汰数据攒够一批后写入落盘存储，查询超出内存部分时从落盘存储补齐；
+/// 行情管理器和应用的行情录制共用
+#[derive(Clone)]
+pub struct TickHistory {
+    buffer: Arc<Mutex<TickHistoryBuffer>>,
+    spill_store: Option<Arc<dyn TickSpillStore + Send + Sync>>,
+}
+
+impl TickHistory {
+    pub fn new(config: TickRetentionConfig) -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(TickHistoryBuffer::new(config))),
+            spill_store: None,
+        }
+    }
+
+    /// 设置淘汰数据的落盘存储，未设置时淘汰数据直接丢弃
+    pub fn with_spill_store(mut self, store: Arc<dyn TickSpillStore + Send + Sync>) -> Self {
+        self.spill_store = Some(store);
+        self
+    }
+
+    pub fn set_config(&self, config: TickRetentionConfig) {
+        self.buffer.lock().unwrap().set_config(config);
+    }
+
+    /// 写入 tick 历史，淘汰数据达到批量阈值时落盘
+    pub fn record(&self, tick: &MarketDataTick) {
+        let mut buffer = self.buffer.lock().unwrap();
+        let mut stored = tick.clone();
+        stored.trace = None;
+        buffer.push(stored);
+
+        if buffer.should_flush() {
+            let pending = buffer.take_pending_spill();
+            drop(buffer);
+            self.spill(pending);
+        }
+    }
+
+    /// 立即落盘所有待写入的淘汰数据
+    pub fn flush(&self) {
+        let pending = self.buffer.lock().unwrap().take_pending_spill();
+        self.spill(pending);
+    }
+
+    /// 内存中的数据连同待写入的淘汰数据全部落盘
+    pub fn spill_all(&self) {
+        let pending = {
+            let mut buffer = self.buffer.lock().unwrap();
+            buffer.evict_all();
+            buffer.take_pending_spill()
+        };
+        self.spill(pending);
+    }
+
+    /// 合约最近的 `limit` 条 tick（按时间顺序），超出内存部分从落盘存储补齐
+    pub fn history(&self, instrument_id: &str, limit: usize) -> Result<Vec<MarketDataTick>, CtpError> {
+        let recent = self.buffer.lock().unwrap().recent(instrument_id, limit);
+        if recent.len() >= limit {
+            return Ok(recent);
+        }
+
+        let Some(store) = &self.spill_store else {
+            return Ok(recent);
+        };
+
+        self.flush();
+        let mut history = store.load(instrument_id, limit - recent.len())?;
+        history.extend(recent);
+        Ok(history)
+    }
+
+    /// 移除合约的内存数据、待落盘数据和落盘文件
+    pub fn remove(&self, instrument_id: &str) {
+        self.buffer.lock().unwrap().remove(instrument_id);
+        if let Some(store) = &self.spill_store {
+            if let Err(e) = store.remove(instrument_id) {
+                tracing::warn!("删除行情落盘数据失败: {} {}", instrument_id, e);
+            }
+        }
+    }
+
+    /// 清空内存数据、待落盘数据和落盘文件
+    pub fn clear(&self) {
+        self.buffer.lock().unwrap().clear();
+        if let Some(store) = &self.spill_store {
+            if let Err(e) = store.clear() {
+                tracing::warn!("清空行情落盘数据失败: {}", e);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> RetentionStats {
+        self.buffer.lock().unwrap().stats()
+    }
+
+    fn spill(&self, pending: HashMap<String, Vec<MarketDataTick>>) {
+        let Some(store) = &self.spill_store else {
+            return;
+        };
+
+        for (instrument_id, ticks) in pending {
+            let result = store.spill(&instrument_id, &ticks);
+            if let Err(e) = &result {
+                tracing::warn!("行情数据落盘失败: {} {}", instrument_id, e);
+            }
+            self.buffer.lock().unwrap().record_spill(ticks.len(), result.is_err());
+        }
+    }
+}
+
+/// 按合约保存的 tick 历史，超出条数或内存预算时淘汰最旧数据
+#[derive(Debug)]
+pub struct TickHistoryBuffer {
+    config: TickRetentionConfig,
+    ticks: HashMap<String, VecDeque<MarketDataTick>>,
+    /// 待落盘的淘汰数据
+    pending_spill: HashMap<String, Vec<MarketDataTick>>,
+    pending_count: usize,
+    stats: RetentionStats,
+}
+
+impl TickHistoryBuffer {
+    pub fn new(config: TickRetentionConfig) -> Self {
+        Self {
+            config,
+            ticks: HashMap::new(),
+            pending_spill: HashMap::new(),
+            pending_count: 0,
+            stats: RetentionStats::default(),
+        }
+    }
+
+    /// 估算单条 tick 的内存占用
+    pub fn estimate_size(tick: &MarketDataTick) -> usize {
+        std::mem::size_of::<MarketDataTick>() + tick.instrument_id.capacity() + tick.update_time.capacity()
+    }
+
+    pub fn config(&self) -> &TickRetentionConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: TickRetentionConfig) {
+        self.config = config;
+        self.enforce_limits(None);
+    }
+
+    /// 写入新 tick 并执行淘汰
+    pub fn push(&mut self, tick: MarketDataTick) {
+        let instrument_id = tick.instrument_id.clone();
+        self.stats.estimated_bytes += Self::estimate_size(&tick);
+        self.stats.cached_ticks += 1;
+        self.ticks.entry(instrument_id.clone()).or_default().push_back(tick);
+
+        self.enforce_limits(Some(&instrument_id));
+    }
+
+    /// 内存中最近的 `limit` 条数据（按时间顺序）
+    pub fn recent(&self, instrument_id: &str, limit: usize) -> Vec<MarketDataTick> {
+        self.ticks
+            .get(instrument_id)
+            .map(|ticks| {
+                let skip = ticks.len().saturating_sub(limit);
+                ticks.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 内存中的数据全部转入待落盘，如收盘后把尾部行情写入落盘存储
+    pub fn evict_all(&mut self) {
+        let instruments: Vec<String> = self.ticks.keys().cloned().collect();
+        for id in instruments {
+            while self.ticks.get(&id).is_some_and(|t| !t.is_empty()) {
+                self.evict_oldest(&id);
+            }
+        }
+    }
+
+    /// 待落盘数据是否已达到批量阈值
+    pub fn should_flush(&self) -> bool {
+        self.pending_count >= self.config.spill_batch_size
+    }
+
+    /// 取出待落盘数据
+    pub fn take_pending_spill(&mut self) -> HashMap<String, Vec<MarketDataTick>> {
+        self.pending_count = 0;
+        std::mem::take(&mut self.pending_spill)
+    }
+
+    /// 记录落盘结果
+    pub fn record_spill(&mut self, spilled: usize, failed: bool) {
+        if failed {
+            self.stats.spill_errors += 1;
+        } else {
+            self.stats.spilled_ticks += spilled as u64;
+        }
+    }
+
+    /// 移除合约的内存数据和待落盘数据
+    pub fn remove(&mut self, instrument_id: &str) {
+        if let Some(ticks) = self.ticks.remove(instrument_id) {
+            self.stats.cached_ticks -= ticks.len();
+            let bytes: usize = ticks.iter().map(Self::estimate_size).sum();
+            self.stats.estimated_bytes = self.stats.estimated_bytes.saturating_sub(bytes);
+        }
+        if let Some(pending) = self.pending_spill.remove(instrument_id) {
+            self.pending_count -= pending.len();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ticks.clear();
+        self.pending_spill.clear();
+        self.pending_count = 0;
+        self.stats.cached_ticks = 0;
+        self.stats.estimated_bytes = 0;
+    }
+
+    pub fn stats(&self) -> RetentionStats {
+        self.stats.clone()
+    }
+
+    fn enforce_limits(&mut self, instrument_id: Option<&str>) {
+        // 条数上限
+        let max_ticks = self.config.max_ticks_per_instrument.max(1);
+        let instruments: Vec<String> = match instrument_id {
+            Some(id) => vec![id.to_string()],
+            None => self.ticks.keys().cloned().collect(),
+        };
+        for id in instruments {
+            while self.ticks.get(&id).map_or(0, |t| t.len()) > max_ticks {
+                self.evict_oldest(&id);
+            }
+        }
+
+        // 内存预算：从数据最多的合约开始淘汰
+        while self.stats.estimated_bytes > self.config.max_memory_bytes && self.stats.cached_ticks > 0 {
+            let Some(largest) = self
+                .ticks
+                .iter()
+                .max_by_key(|(_, ticks)| ticks.len())
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            self.evict_oldest(&largest);
+        }
+    }
+
+    fn evict_oldest(&mut self, instrument_id: &str) {
+        let Some(tick) = self.ticks.get_mut(instrument_id).and_then(|t| t.pop_front()) else {
+            return;
+        };
+
+        self.stats.cached_ticks -= 1;
+        self.stats.estimated_bytes = self.stats.estimated_bytes.saturating_sub(Self::estimate_size(&tick));
+        self.stats.evicted_ticks += 1;
+
+        self.pending_spill.entry(instrument_id.to_string()).or_default().push(tick);
+        self.pending_count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(instrument_id: &str, price: f64) -> MarketDataTick {
+        MarketDataTick {
+            instrument_id: instrument_id.to_string(),
+            last_price: price,
+            volume: 1,
+            turnover: price,
+            open_interest: 0,
+            bid_price1: price,
+            bid_volume1: 1,
+            ask_price1: price,
+            ask_volume1: 1,
+            update_time: "09:30:00".to_string(),
+            update_millisec: 0,
+            change_percent: 0.0,
+            change_amount: 0.0,
+            open_price: price,
+            highest_price: price,
+            lowest_price: price,
+            pre_close_price: price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_per_instrument_cap() {
+        let mut buffer = TickHistoryBuffer::new(TickRetentionConfig {
+            max_ticks_per_instrument: 3,
+            ..TickRetentionConfig::default()
+        });
+
+        for i in 0..5 {
+            buffer.push(tick("rb2501", i as f64));
+        }
+
+        let recent = buffer.recent("rb2501", 10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].last_price, 2.0);
+
+        let stats = buffer.stats();
+        assert_eq!(stats.cached_ticks, 3);
+        assert_eq!(stats.evicted_ticks, 2);
+        assert_eq!(buffer.take_pending_spill()["rb2501"].len(), 2);
+    }
+
+    #[test]
+    fn test_memory_budget_evicts_largest() {
+        let size = TickHistoryBuffer::estimate_size(&tick("rb2501", 0.0));
+        let mut buffer = TickHistoryBuffer::new(TickRetentionConfig {
+            max_ticks_per_instrument: 100,
+            max_memory_bytes: size * 4,
+            spill_batch_size: 2,
+        });
+
+        for i in 0..4 {
+            buffer.push(tick("rb2501", i as f64));
+        }
+        buffer.push(tick("hc2501", 0.0));
+
+        assert_eq!(buffer.recent("rb2501", 10).len(), 3);
+        assert_eq!(buffer.recent("hc2501", 10).len(), 1);
+        assert!(buffer.stats().estimated_bytes <= size * 4);
+        assert!(!buffer.should_flush());
+    }
+
+    #[test]
+    fn test_json_lines_spill_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = JsonLinesSpillStore::new(dir.path()).unwrap();
+
+        store.spill("rb2501", &[tick("rb2501", 1.0), tick("rb2501", 2.0)]).unwrap();
+        store.spill("rb2501", &[tick("rb2501", 3.0)]).unwrap();
+
+        let loaded = store.load("rb2501", 2).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].last_price, 2.0);
+        assert_eq!(store.load("rb2501", 10).unwrap().len(), 3);
+        assert!(store.load("hc2501", 10).unwrap().is_empty());
+
+        // 跨越多个读取块时只取最后几行
+        let many: Vec<MarketDataTick> = (0..1000).map(|i| tick("hc2501", i as f64)).collect();
+        store.spill("hc2501", &many).unwrap();
+        assert!(std::fs::metadata(dir.path().join("hc2501.jsonl")).unwrap().len() > TAIL_CHUNK_BYTES * 2);
+        let loaded = store.load("hc2501", 300).unwrap();
+        assert_eq!(loaded.len(), 300);
+        assert_eq!(loaded[0].last_price, 700.0);
+        assert_eq!(loaded[299].last_price, 999.0);
+
+        store.remove("rb2501").unwrap();
+        assert!(store.load("rb2501", 10).unwrap().is_empty());
+        store.remove("rb2501").unwrap();
+        store.clear().unwrap();
+        assert!(store.load("hc2501", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_spills_and_removes_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = TickHistory::new(TickRetentionConfig {
+            max_ticks_per_instrument: 2,
+            spill_batch_size: 2,
+            ..TickRetentionConfig::default()
+        })
+        .with_spill_store(Arc::new(JsonLinesSpillStore::new(dir.path()).unwrap()));
+
+        for i in 0..5 {
+            history.record(&tick("rb2501", i as f64));
+        }
+        assert_eq!(history.stats().spilled_ticks, 2);
+        let all = history.history("rb2501", 10).unwrap();
+        assert_eq!(all.iter().map(|t| t.last_price).collect::<Vec<_>>(), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        history.spill_all();
+        assert_eq!(history.stats().cached_ticks, 0);
+        assert_eq!(history.history("rb2501", 10).unwrap().len(), 5);
+
+        // 取消订阅后重新订阅不会读到旧的落盘数据
+        history.record(&tick("rb2501", 5.0));
+        history.remove("rb2501");
+        assert!(history.history("rb2501", 10).unwrap().is_empty());
+        assert!(!dir.path().join("rb2501.jsonl").exists());
+    }
+}
@@ -0,0 +1,354 @@
+use crate::ctp::{
+    CtpError,
+    models::{OrderStatus, OrderStatusType, TradeRecord},
+    utils::sanitize_file_component,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认时间线存储目录
+pub const DEFAULT_TIMELINE_DIR: &str = "./data/timeline";
+
+/// 内存中保留的最大条目数
+const DEFAULT_MAX_ENTRIES: usize = 20_000;
+
+/// 时间线条目类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TimelineKind {
+    /// 报单状态变化
+    Order,
+    /// 成交
+    Trade,
+    /// 银期转账
+    Transfer,
+    /// 连接、断开等系统事件
+    Connection,
+    /// 风控触发
+    Risk,
+}
+
+/// 时间线条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// 账户内单调递增的序号，用作分页游标
+    pub seq: u64,
+    pub account_id: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub kind: TimelineKind,
+    pub title: String,
+    pub detail: Option<String>,
+    pub instrument_id: Option<String>,
+    /// 关联标识（报单引用、成交编号、流水号等）
+    pub reference: Option<String>,
+}
+
+/// 时间线分页查询
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimelineQuery {
+    /// 返回序号小于该值的条目，为空时从最新开始
+    #[serde(default)]
+    pub before: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 只返回指定类别，为空时返回全部
+    #[serde(default)]
+    pub kinds: Option<Vec<TimelineKind>>,
+    #[serde(default)]
+    pub instrument_id: Option<String>,
+}
+
+/// 时间线分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelinePage {
+    /// 按时间倒序
+    pub entries: Vec<TimelineEntry>,
+    /// 下一页游标
+    pub next_before: Option<u64>,
+    pub has_more: bool,
+}
+
+struct TimelineInner {
+    account_id: String,
+    entries: VecDeque<TimelineEntry>,
+    next_seq: u64,
+    max_entries: usize,
+    /// 持久化文件，为空时仅保存在内存
+    path: Option<PathBuf>,
+    /// 每笔报单最近一次记录的（状态, 已成交量），过滤重复回报
+    order_states: HashMap<String, (OrderStatusType, u32)>,
+}
+
+/// 账户活动时间线
+///
+/// 将报单、成交、转账、连接事件和风控触发合并为按账户的单一时间序列，
+/// 以 JSON Lines 追加写入本地文件，重启后加载，供活动流界面分页查询
+#[derive(Clone)]
+pub struct Timeline {
+    inner: Arc<Mutex<TimelineInner>>,
+}
+
+impl Timeline {
+    /// 仅内存的时间线
+    pub fn in_memory(account_id: &str) -> Self {
+        Self::build(account_id, None, VecDeque::new())
+    }
+
+    /// 打开账户时间线，加载 `<dir>/<account_id>.jsonl` 中已有的条目
+    pub fn open(dir: impl AsRef<Path>, account_id: &str) -> Result<Self, CtpError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.jsonl", sanitize_file_component(account_id)));
+
+        let mut entries = VecDeque::new();
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                match serde_json::from_str::<TimelineEntry>(&line) {
+                    Ok(entry) => {
+                        if entries.len() >= DEFAULT_MAX_ENTRIES {
+                            entries.pop_front();
+                        }
+                        entries.push_back(entry);
+                    }
+                    Err(e) => tracing::warn!("跳过无法解析的时间线记录: {}", e),
+                }
+            }
+            tracing::info!("加载账户 {} 的时间线 {} 条", account_id, entries.len());
+        }
+
+        Ok(Self::build(account_id, Some(path), entries))
+    }
+
+    fn build(account_id: &str, path: Option<PathBuf>, entries: VecDeque<TimelineEntry>) -> Self {
+        let next_seq = entries.back().map_or(1, |e| e.seq + 1);
+        Self {
+            inner: Arc::new(Mutex::new(TimelineInner {
+                account_id: account_id.to_string(),
+                entries,
+                next_seq,
+                max_entries: DEFAULT_MAX_ENTRIES,
+                path,
+                order_states: HashMap::new(),
+            })),
+        }
+    }
+
+    /// 追加条目并持久化
+    pub fn record(
+        &self,
+        kind: TimelineKind,
+        title: impl Into<String>,
+        detail: Option<String>,
+        instrument_id: Option<String>,
+        reference: Option<String>,
+    ) -> TimelineEntry {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = TimelineEntry {
+            seq: inner.next_seq,
+            account_id: inner.account_id.clone(),
+            timestamp: chrono::Utc::now(),
+            kind,
+            title: title.into(),
+            detail,
+            instrument_id,
+            reference,
+        };
+        inner.next_seq += 1;
+
+        if let Some(path) = &inner.path {
+            if let Err(e) = append_line(path, &entry) {
+                tracing::warn!("写入时间线失败: {}", e);
+                crate::health::record_storage_error("timeline", &e);
+            }
+        }
+        if inner.entries.len() >= inner.max_entries {
+            inner.entries.pop_front();
+        }
+        inner.entries.push_back(entry.clone());
+        entry
+    }
+
+    /// 记录报单回报，状态和成交量均未变化的重复回报忽略
+    pub fn record_order(&self, order: &OrderStatus) {
+        let state = (order.status, order.volume_traded);
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.order_states.get(&order.order_ref) == Some(&state) {
+                return;
+            }
+            inner.order_states.insert(order.order_ref.clone(), state);
+        }
+
+        let title = format!(
+            "{} {} {} {}手 @ {}",
+            order_status_label(order.status),
+            order.direction,
+            order.instrument_id,
+            order.volume_total_original,
+            order.limit_price
+        );
+        let detail = (!order.status_msg.is_empty()).then(|| order.status_msg.clone());
+        self.record(
+            TimelineKind::Order,
+            title,
+            detail,
+            Some(order.instrument_id.clone()),
+            Some(order.order_ref.clone()),
+        );
+    }
+
+    /// 记录报单被拒
+    pub fn record_order_rejected(&self, order: &OrderStatus, reason: &str) {
+        self.record(
+            TimelineKind::Order,
+            format!("报单被拒 {} {} {}手 @ {}", order.direction, order.instrument_id, order.volume, order.limit_price),
+            Some(reason.to_string()),
+            Some(order.instrument_id.clone()),
+            Some(order.order_ref.clone()),
+        );
+    }
+
+    /// 记录成交
+    pub fn record_trade(&self, trade: &TradeRecord) {
+        self.record(
+            TimelineKind::Trade,
+            format!("成交 {} {} {}手 @ {}", trade.direction, trade.instrument_id, trade.volume, trade.price),
+            Some(format!("成交时间 {}", trade.trade_time)),
+            Some(trade.instrument_id.clone()),
+            Some(trade.trade_id.clone()),
+        );
+    }
+
+    /// 记录银期转账，`amount` 为正表示转入期货账户
+    pub fn record_transfer(&self, amount: f64, detail: Option<String>, reference: Option<String>) {
+        let title = if amount >= 0.0 {
+            format!("银行转期货 {:.2}", amount)
+        } else {
+            format!("期货转银行 {:.2}", -amount)
+        };
+        self.record(TimelineKind::Transfer, title, detail, None, reference);
+    }
+
+    /// 记录连接状态变化，`side` 为行情或交易
+    pub fn record_connection(&self, side: &str, connected: bool, detail: Option<String>) {
+        let title = format!("{}前置{}", side, if connected { "已连接" } else { "已断开" });
+        self.record(TimelineKind::Connection, title, detail, None, None);
+    }
+
+    /// 记录风控触发
+    pub fn record_risk(&self, message: impl Into<String>, instrument_id: Option<String>) {
+        self.record(TimelineKind::Risk, message, None, instrument_id, None);
+    }
+
+    /// 分页查询，按时间倒序
+    pub fn page(&self, query: &TimelineQuery) -> TimelinePage {
+        let limit = query.limit.unwrap_or(50).clamp(1, 500);
+        let inner = self.inner.lock().unwrap();
+
+        let mut matched = inner
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| query.before.is_none_or(|before| e.seq < before))
+            .filter(|e| query.kinds.as_ref().is_none_or(|kinds| kinds.contains(&e.kind)))
+            .filter(|e| {
+                query
+                    .instrument_id
+                    .as_ref()
+                    .is_none_or(|id| e.instrument_id.as_ref() == Some(id))
+            });
+
+        let entries: Vec<TimelineEntry> = matched.by_ref().take(limit).cloned().collect();
+        let has_more = matched.next().is_some();
+        TimelinePage {
+            next_before: if has_more { entries.last().map(|e| e.seq) } else { None },
+            entries,
+            has_more,
+        }
+    }
+
+    pub fn account_id(&self) -> String {
+        self.inner.lock().unwrap().account_id.clone()
+    }
+}
+
+fn append_line(path: &Path, entry: &TimelineEntry) -> Result<(), CtpError> {
+    let line = serde_json::to_string(entry).map_err(|e| CtpError::ConversionError(e.to_string()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+fn order_status_label(status: OrderStatusType) -> &'static str {
+    match status {
+        OrderStatusType::AllTraded => "全部成交",
+        OrderStatusType::PartTradedQueueing | OrderStatusType::PartTradedNotQueueing => "部分成交",
+        OrderStatusType::NoTradeQueueing => "已报",
+        OrderStatusType::NoTradeNotQueueing => "未成交",
+        OrderStatusType::Canceled | OrderStatusType::Cancelled => "已撤单",
+        OrderStatusType::Touched => "已触发",
+        OrderStatusType::Unknown => "报单",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagination_and_filters() {
+        let timeline = Timeline::in_memory("000001");
+        timeline.record_connection("交易", true, None);
+        for i in 0..5 {
+            timeline.record(TimelineKind::Trade, format!("成交 {}", i), None, Some("rb2501".to_string()), None);
+        }
+        timeline.record_risk("快捷键操作过于频繁", None);
+
+        let first = timeline.page(&TimelineQuery {
+            limit: Some(3),
+            ..TimelineQuery::default()
+        });
+        assert_eq!(first.entries.len(), 3);
+        assert_eq!(first.entries[0].kind, TimelineKind::Risk);
+        assert!(first.has_more);
+
+        let second = timeline.page(&TimelineQuery {
+            before: first.next_before,
+            limit: Some(10),
+            ..TimelineQuery::default()
+        });
+        assert_eq!(second.entries.len(), 4);
+        assert!(!second.has_more);
+        assert_eq!(second.entries.last().unwrap().kind, TimelineKind::Connection);
+
+        let trades = timeline.page(&TimelineQuery {
+            kinds: Some(vec![TimelineKind::Trade]),
+            instrument_id: Some("rb2501".to_string()),
+            ..TimelineQuery::default()
+        });
+        assert_eq!(trades.entries.len(), 5);
+    }
+
+    #[test]
+    fn test_persisted_and_reloaded() {
+        let dir = std::env::temp_dir().join(format!("timeline_{}", uuid::Uuid::new_v4()));
+        let timeline = Timeline::open(&dir, "000001").unwrap();
+        timeline.record_connection("行情", true, None);
+        timeline.record_transfer(-1000.0, None, Some("T1".to_string()));
+
+        let reloaded = Timeline::open(&dir, "000001").unwrap();
+        let page = reloaded.page(&TimelineQuery::default());
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.entries[0].title, "期货转银行 1000.00");
+
+        // 序号从已有记录之后继续
+        assert_eq!(reloaded.record(TimelineKind::Risk, "test", None, None, None).seq, 3);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::ctp::{rejection_breaker::MANUAL_SOURCE, round_trip::RoundTrip};
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 盈亏热力图默认按交易所时间（北京时间）分时段
+pub const DEFAULT_HEATMAP_UTC_OFFSET_HOURS: i32 = 8;
+
+/// 持仓时长分布的分档（名称, 上限秒数），最后一档无上限
+const HOLDING_BUCKETS: [(&str, Option<i64>); 6] = [
+    ("1分钟内", Some(60)),
+    ("1-5分钟", Some(300)),
+    ("5-30分钟", Some(1800)),
+    ("30分钟-2小时", Some(7200)),
+    ("2小时-1天", Some(86400)),
+    ("1天以上", None),
+];
+
+/// 绩效查询条件，时间范围按平仓时间筛选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsQuery {
+    /// 策略名，`manual` 表示未带策略标签的回合
+    #[serde(default)]
+    pub strategy: Option<String>,
+    #[serde(default)]
+    pub instrument_id: Option<String>,
+    #[serde(default)]
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl AnalyticsQuery {
+    pub fn matches(&self, trip: &RoundTrip) -> bool {
+        self.strategy.as_deref().is_none_or(|s| s == strategy_key(trip))
+            && self.instrument_id.as_deref().is_none_or(|i| i == trip.instrument_id)
+            && self.from.is_none_or(|from| trip.closed_at >= from)
+            && self.to.is_none_or(|to| trip.closed_at < to)
+    }
+}
+
+/// 持仓时长分档
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingBucket {
+    pub label: String,
+    /// 分档上限（秒，不含），最后一档为空
+    pub max_secs: Option<i64>,
+    pub count: usize,
+}
+
+/// 一组回合交易的绩效统计，盈亏均为扣除手续费后的净值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceStats {
+    /// 分组键（策略名或合约），总体统计为 `all`
+    pub key: String,
+    pub round_trips: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub win_rate: f64,
+    pub total_pnl: f64,
+    pub total_fees: f64,
+    pub avg_win: f64,
+    /// 亏损回合的平均盈亏（负数）
+    pub avg_loss: f64,
+    /// 总盈利 / 总亏损，没有亏损时为空
+    pub profit_factor: Option<f64>,
+    pub avg_holding_secs: f64,
+    pub holding_distribution: Vec<HoldingBucket>,
+    pub avg_mae: f64,
+    pub avg_mfe: f64,
+}
+
+/// 交易绩效报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeAnalyticsReport {
+    pub overall: PerformanceStats,
+    pub by_strategy: Vec<PerformanceStats>,
+    pub by_instrument: Vec<PerformanceStats>,
+    /// 符合条件的回合交易，按平仓时间顺序
+    pub round_trips: Vec<RoundTrip>,
+}
+
+/// 盈亏热力图单元格：某星期几某小时内平仓的回合交易
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    /// 0 为周一，6 为周日
+    pub weekday: u32,
+    /// 0-23
+    pub hour: u32,
+    pub round_trips: usize,
+    pub wins: usize,
+    /// 扣除手续费后的净盈亏
+    pub total_pnl: f64,
+    pub avg_pnl: f64,
+}
+
+/// 一组回合交易的盈亏热力图，只包含有成交的单元格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlHeatmap {
+    /// 分组键（策略名或合约），总体为 `all`
+    pub key: String,
+    /// 按星期、小时排序
+    pub cells: Vec<HeatmapCell>,
+    /// 净盈亏最高、最低的单元格
+    pub best: Option<HeatmapCell>,
+    pub worst: Option<HeatmapCell>,
+}
+
+/// 按时段的盈亏热力图报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlHeatmapReport {
+    /// 分时段使用的时区偏移（小时）
+    pub utc_offset_hours: i32,
+    pub overall: PnlHeatmap,
+    pub by_strategy: Vec<PnlHeatmap>,
+    pub by_instrument: Vec<PnlHeatmap>,
+}
+
+/// 按平仓时间的星期几和小时汇总回合交易盈亏，并按策略、合约分组
+pub fn pnl_heatmap(trips: &[RoundTrip], query: &AnalyticsQuery, utc_offset_hours: i32) -> PnlHeatmapReport {
+    let offset = chrono::FixedOffset::east_opt(utc_offset_hours * 3600)
+        .unwrap_or_else(|| chrono::FixedOffset::east_opt(DEFAULT_HEATMAP_UTC_OFFSET_HOURS * 3600).unwrap());
+    let selected: Vec<&RoundTrip> = trips.iter().filter(|t| query.matches(t)).collect();
+
+    let mut by_strategy: BTreeMap<&str, Vec<&RoundTrip>> = BTreeMap::new();
+    let mut by_instrument: BTreeMap<&str, Vec<&RoundTrip>> = BTreeMap::new();
+    for trip in &selected {
+        by_strategy.entry(strategy_key(trip)).or_default().push(trip);
+        by_instrument.entry(trip.instrument_id.as_str()).or_default().push(trip);
+    }
+
+    PnlHeatmapReport {
+        utc_offset_hours: offset.local_minus_utc() / 3600,
+        overall: heatmap("all", &selected, &offset),
+        by_strategy: by_strategy.into_iter().map(|(key, trips)| heatmap(key, &trips, &offset)).collect(),
+        by_instrument: by_instrument.into_iter().map(|(key, trips)| heatmap(key, &trips, &offset)).collect(),
+    }
+}
+
+fn heatmap(key: &str, trips: &[&RoundTrip], offset: &chrono::FixedOffset) -> PnlHeatmap {
+    let mut cells: BTreeMap<(u32, u32), HeatmapCell> = BTreeMap::new();
+    for trip in trips {
+        let closed_at = trip.closed_at.with_timezone(offset);
+        let (weekday, hour) = (closed_at.weekday().num_days_from_monday(), closed_at.hour());
+        let cell = cells.entry((weekday, hour)).or_insert_with(|| HeatmapCell {
+            weekday,
+            hour,
+            round_trips: 0,
+            wins: 0,
+            total_pnl: 0.0,
+            avg_pnl: 0.0,
+        });
+        cell.round_trips += 1;
+        if trip.net_pnl > 0.0 {
+            cell.wins += 1;
+        }
+        cell.total_pnl += trip.net_pnl;
+    }
+
+    let cells: Vec<HeatmapCell> = cells
+        .into_values()
+        .map(|mut cell| {
+            cell.avg_pnl = cell.total_pnl / cell.round_trips as f64;
+            cell
+        })
+        .collect();
+    PnlHeatmap {
+        key: key.to_string(),
+        best: cells.iter().max_by(|a, b| a.total_pnl.total_cmp(&b.total_pnl)).cloned(),
+        worst: cells.iter().min_by(|a, b| a.total_pnl.total_cmp(&b.total_pnl)).cloned(),
+        cells,
+    }
+}
+
+/// 按查询条件统计回合交易，并按策略、合约分组
+pub fn analyze(trips: &[RoundTrip], query: &AnalyticsQuery) -> TradeAnalyticsReport {
+    let selected: Vec<&RoundTrip> = trips.iter().filter(|t| query.matches(t)).collect();
+
+    let mut by_strategy: BTreeMap<&str, Vec<&RoundTrip>> = BTreeMap::new();
+    let mut by_instrument: BTreeMap<&str, Vec<&RoundTrip>> = BTreeMap::new();
+    for trip in &selected {
+        by_strategy.entry(strategy_key(trip)).or_default().push(trip);
+        by_instrument.entry(trip.instrument_id.as_str()).or_default().push(trip);
+    }
+
+    TradeAnalyticsReport {
+        overall: stats("all", &selected),
+        by_strategy: by_strategy.into_iter().map(|(key, trips)| stats(key, &trips)).collect(),
+        by_instrument: by_instrument.into_iter().map(|(key, trips)| stats(key, &trips)).collect(),
+        round_trips: selected.into_iter().cloned().collect(),
+    }
+}
+
+fn strategy_key(trip: &RoundTrip) -> &str {
+    trip.strategy.as_deref().unwrap_or(MANUAL_SOURCE)
+}
+
+fn stats(key: &str, trips: &[&RoundTrip]) -> PerformanceStats {
+    let count = trips.len();
+    let wins: Vec<f64> = trips.iter().map(|t| t.net_pnl).filter(|pnl| *pnl > 0.0).collect();
+    let losses: Vec<f64> = trips.iter().map(|t| t.net_pnl).filter(|pnl| *pnl < 0.0).collect();
+    let gross_win: f64 = wins.iter().sum();
+    let gross_loss: f64 = losses.iter().sum();
+
+    let mut holding_distribution: Vec<HoldingBucket> = HOLDING_BUCKETS
+        .iter()
+        .map(|(label, max_secs)| HoldingBucket {
+            label: label.to_string(),
+            max_secs: *max_secs,
+            count: 0,
+        })
+        .collect();
+    for trip in trips {
+        if let Some(bucket) = holding_distribution
+            .iter_mut()
+            .find(|b| b.max_secs.is_none_or(|max| trip.holding_secs < max))
+        {
+            bucket.count += 1;
+        }
+    }
+
+    let mean = |total: f64, n: usize| if n == 0 { 0.0 } else { total / n as f64 };
+    PerformanceStats {
+        key: key.to_string(),
+        round_trips: count,
+        wins: wins.len(),
+        losses: losses.len(),
+        win_rate: mean(wins.len() as f64, count),
+        total_pnl: trips.iter().map(|t| t.net_pnl).sum(),
+        total_fees: trips.iter().map(|t| t.fees).sum(),
+        avg_win: mean(gross_win, wins.len()),
+        avg_loss: mean(gross_loss, losses.len()),
+        profit_factor: (gross_loss < 0.0).then(|| gross_win / -gross_loss),
+        avg_holding_secs: mean(trips.iter().map(|t| t.holding_secs as f64).sum(), count),
+        holding_distribution,
+        avg_mae: mean(trips.iter().map(|t| t.mae).sum(), count),
+        avg_mfe: mean(trips.iter().map(|t| t.mfe).sum(), count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{OrderTags, PositionDirection};
+
+    fn trip(instrument_id: &str, strategy: Option<&str>, pnl: f64, holding_secs: i64, closed_days_ago: i64) -> RoundTrip {
+        let closed_at = chrono::Utc::now() - chrono::Duration::days(closed_days_ago);
+        RoundTrip {
+            instrument_id: instrument_id.to_string(),
+            direction: PositionDirection::Long,
+            volume: 1,
+            open_price: 3500.0,
+            close_price: 3500.0 + pnl / 10.0,
+            open_trade_id: "1".to_string(),
+            close_trade_id: "2".to_string(),
+            opened_at: closed_at - chrono::Duration::seconds(holding_secs),
+            closed_at,
+            holding_secs,
+            pnl,
+            fees: 0.0,
+            net_pnl: pnl,
+            mae: 50.0,
+            mfe: 150.0,
+            strategy: strategy.map(str::to_string),
+            tags: OrderTags::new(),
+        }
+    }
+
+    #[test]
+    fn test_win_rate_profit_factor_and_groups() {
+        let trips = vec![
+            trip("rb2501", Some("grid"), 300.0, 30, 0),
+            trip("rb2501", Some("grid"), -100.0, 120, 0),
+            trip("hc2501", Some("grid"), 100.0, 4000, 0),
+            trip("hc2501", None, -200.0, 100_000, 0),
+        ];
+        let report = analyze(&trips, &AnalyticsQuery::default());
+
+        let overall = &report.overall;
+        assert_eq!(overall.round_trips, 4);
+        assert_eq!((overall.wins, overall.losses), (2, 2));
+        assert_eq!(overall.win_rate, 0.5);
+        assert_eq!(overall.total_pnl, 100.0);
+        assert_eq!(overall.avg_win, 200.0);
+        assert_eq!(overall.avg_loss, -150.0);
+        assert_eq!(overall.profit_factor, Some(400.0 / 300.0));
+        let counts: Vec<usize> = overall.holding_distribution.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 1, 0, 1, 0, 1]);
+
+        let keys: Vec<&str> = report.by_strategy.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["grid", MANUAL_SOURCE]);
+        assert_eq!(report.by_strategy[0].profit_factor, Some(4.0));
+        assert_eq!(report.by_instrument.len(), 2);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let trips = vec![
+            trip("rb2501", Some("grid"), 300.0, 30, 3),
+            trip("rb2501", None, 100.0, 30, 0),
+            trip("hc2501", Some("grid"), -100.0, 30, 0),
+        ];
+
+        let query = AnalyticsQuery {
+            strategy: Some("grid".to_string()),
+            from: Some(chrono::Utc::now() - chrono::Duration::days(1)),
+            ..AnalyticsQuery::default()
+        };
+        let report = analyze(&trips, &query);
+        assert_eq!(report.overall.round_trips, 1);
+        assert_eq!(report.round_trips[0].instrument_id, "hc2501");
+        assert_eq!(report.overall.profit_factor, Some(0.0));
+
+        let query = AnalyticsQuery {
+            strategy: Some(MANUAL_SOURCE.to_string()),
+            instrument_id: Some("rb2501".to_string()),
+            ..AnalyticsQuery::default()
+        };
+        let report = analyze(&trips, &query);
+        assert_eq!(report.overall.round_trips, 1);
+        assert_eq!(report.overall.profit_factor, None);
+    }
+
+    #[test]
+    fn test_pnl_heatmap_by_weekday_and_hour() {
+        use chrono::TimeZone;
+        let at = |trip: RoundTrip, closed_at: chrono::DateTime<chrono::Utc>| RoundTrip { closed_at, ..trip };
+        // 2024-01-01 为周一，UTC 01:30 即北京时间 09:30
+        let monday = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 1, 30, 0).unwrap();
+        let trips = vec![
+            at(trip("rb2501", Some("grid"), 300.0, 30, 0), monday),
+            at(trip("rb2501", Some("grid"), -100.0, 30, 0), monday + chrono::Duration::minutes(20)),
+            at(trip("hc2501", None, -500.0, 30, 0), monday + chrono::Duration::days(1) + chrono::Duration::hours(13)),
+        ];
+
+        let report = pnl_heatmap(&trips, &AnalyticsQuery::default(), 8);
+        let cells = &report.overall.cells;
+        assert_eq!(cells.len(), 2);
+        assert_eq!((cells[0].weekday, cells[0].hour), (0, 9));
+        assert_eq!((cells[0].round_trips, cells[0].wins), (2, 1));
+        assert_eq!(cells[0].avg_pnl, 100.0);
+        // 周一 14:30 UTC 为北京时间周二 22:30
+        assert_eq!((cells[1].weekday, cells[1].hour), (1, 22));
+        assert_eq!(report.overall.best.as_ref().unwrap().hour, 9);
+        assert_eq!(report.overall.worst.as_ref().unwrap().total_pnl, -500.0);
+        assert_eq!(report.by_strategy.len(), 2);
+        assert_eq!(report.by_instrument[0].key, "hc2501");
+
+        // 按 UTC 分时段
+        let report = pnl_heatmap(&trips, &AnalyticsQuery::default(), 0);
+        assert_eq!((report.overall.cells[0].weekday, report.overall.cells[0].hour), (0, 1));
+    }
+}
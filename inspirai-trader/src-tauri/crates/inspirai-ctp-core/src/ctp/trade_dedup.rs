@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::ctp::{bar_import::trading_day_of, models::TradeRecord, utils::sanitize_file_component, CtpError};
+
+/// 默认已处理成交目录，每个账户、每个交易日一个文件
+pub const DEFAULT_TRADE_DEDUP_DIR: &str = "./data/seen_trades";
+
+/// 成交去重键：交易所 + 合约 + 成交编号
+///
+/// CTP 的成交编号只在交易所、合约内唯一，且带前导空格
+pub fn trade_key(trade: &TradeRecord) -> String {
+    format!("{}|{}|{}", trade.exchange_id.trim(), trade.instrument_id.trim(), trade.trade_id.trim())
+}
+
+struct DedupInner {
+    /// 已加载集合所属交易日
+    trading_day: Option<NaiveDate>,
+    seen: HashSet<String>,
+    duplicates: u64,
+    /// 持久化文件前缀，按交易日追加 `_<YYYYMMDD>.txt`
+    file_prefix: Option<PathBuf>,
+}
+
+impl DedupInner {
+    fn path_for(&self, trading_day: NaiveDate) -> Option<PathBuf> {
+        let prefix = self.file_prefix.as_ref()?;
+        let name = format!(
+            "{}_{}.txt",
+            prefix.file_name()?.to_string_lossy(),
+            trading_day.format("%Y%m%d")
+        );
+        Some(prefix.with_file_name(name))
+    }
+
+    /// 进入新交易日时换用当日的已处理集合
+    fn roll_to(&mut self, trading_day: NaiveDate) -> Result<(), CtpError> {
+        if self.trading_day == Some(trading_day) {
+            return Ok(());
+        }
+        self.seen.clear();
+        self.trading_day = Some(trading_day);
+        if let Some(path) = self.path_for(trading_day).filter(|p| p.exists()) {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if !line.is_empty() {
+                    self.seen.insert(line);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 成交回报去重
+///
+/// 断线重连或重复回调可能把同一笔 OnRtnTrade 送达两次，按交易日记录已处理的成交，
+/// 重复的直接丢弃，保证持仓和盈亏不会重复计入。已处理集合追加写入本地文件，
+/// 重启后续传私有流时同样能识别
+#[derive(Clone)]
+pub struct TradeDeduplicator {
+    inner: Arc<Mutex<DedupInner>>,
+}
+
+impl Default for TradeDeduplicator {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl TradeDeduplicator {
+    /// 仅内存的去重，进程重启后清空
+    pub fn in_memory() -> Self {
+        Self::build(None)
+    }
+
+    /// 打开账户的已处理成交，`scope` 区分各自去重的消费方
+    pub fn open(dir: impl AsRef<Path>, account_id: &str, scope: &str) -> Result<Self, CtpError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let file_name = format!("{}_{}", sanitize_file_component(account_id), sanitize_file_component(scope));
+        let dedup = Self::build(Some(dir.join(file_name)));
+        let count = {
+            let mut inner = dedup.inner.lock().unwrap();
+            inner.roll_to(trading_day_of(chrono::Local::now().naive_local()))?;
+            inner.seen.len()
+        };
+        tracing::info!("加载账户 {} 当日已处理成交 {} 笔 ({})", account_id, count, scope);
+        Ok(dedup)
+    }
+
+    fn build(file_prefix: Option<PathBuf>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DedupInner {
+                trading_day: None,
+                seen: HashSet::new(),
+                duplicates: 0,
+                file_prefix,
+            })),
+        }
+    }
+
+    /// 首次出现的成交返回 true 并记为已处理，重复的返回 false
+    pub fn first_seen(&self, trade: &TradeRecord) -> bool {
+        self.first_seen_at(trade, chrono::Local::now().naive_local())
+    }
+
+    pub fn first_seen_at(&self, trade: &TradeRecord, local_time: NaiveDateTime) -> bool {
+        let key = trade_key(trade);
+        let mut inner = self.inner.lock().unwrap();
+        if let Err(e) = inner.roll_to(trading_day_of(local_time)) {
+            tracing::warn!("加载已处理成交失败: {}", e);
+        }
+        if inner.seen.contains(&key) {
+            inner.duplicates += 1;
+            return false;
+        }
+        if let Some(path) = inner.trading_day.and_then(|day| inner.path_for(day)) {
+            if let Err(e) = append_key(&path, &key) {
+                tracing::warn!("保存已处理成交失败: {}", e);
+                crate::health::record_storage_error("seen_trades", &e);
+            }
+        }
+        inner.seen.insert(key);
+        true
+    }
+
+    /// 是否已处理过，不记录
+    pub fn contains(&self, trade: &TradeRecord) -> bool {
+        self.inner.lock().unwrap().seen.contains(&trade_key(trade))
+    }
+
+    /// 已丢弃的重复成交数
+    pub fn duplicates(&self) -> u64 {
+        self.inner.lock().unwrap().duplicates
+    }
+}
+
+fn append_key(path: &Path, key: &str) -> Result<(), CtpError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", key)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::{OffsetFlag, OrderDirection};
+    use tempfile::TempDir;
+
+    fn trade(trade_id: &str, exchange_id: &str) -> TradeRecord {
+        TradeRecord {
+            trade_id: trade_id.to_string(),
+            order_id: "1".to_string(),
+            instrument_id: "rb2501".to_string(),
+            direction: OrderDirection::Buy,
+            offset_flag: OffsetFlag::Open,
+            price: 3500.0,
+            volume: 1,
+            trade_time: "09:00:00".to_string(),
+            exchange_id: exchange_id.to_string(),
+            tags: Default::default(),
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_trade_dropped_within_trading_day() {
+        let dedup = TradeDeduplicator::in_memory();
+        assert!(dedup.first_seen_at(&trade("      12345", "SHFE"), at(6, 10)));
+        // 前导空格不影响
+        assert!(!dedup.first_seen_at(&trade("12345", "SHFE"), at(6, 10)));
+        // 不同交易所的同号成交是两笔
+        assert!(dedup.first_seen_at(&trade("12345", "DCE"), at(6, 10)));
+        assert_eq!(dedup.duplicates(), 1);
+
+        // 次日换用新的集合
+        assert!(dedup.first_seen_at(&trade("12345", "SHFE"), at(7, 10)));
+    }
+
+    #[test]
+    fn test_seen_set_survives_restart() {
+        let dir = TempDir::new().unwrap();
+        let dedup = TradeDeduplicator::open(dir.path(), "test/user", "positions").unwrap();
+        // 周一夜盘归属周二交易日
+        assert!(dedup.first_seen_at(&trade("1", "SHFE"), at(6, 21)));
+
+        let reopened = TradeDeduplicator::open(dir.path(), "test/user", "positions").unwrap();
+        assert!(!reopened.first_seen_at(&trade("1", "SHFE"), at(7, 9)));
+        assert!(dir.path().join("test_user_positions_20250107.txt").exists());
+
+        // 其他消费方各自去重
+        let orders = TradeDeduplicator::open(dir.path(), "test/user", "orders").unwrap();
+        assert!(orders.first_seen_at(&trade("1", "SHFE"), at(7, 9)));
+    }
+
+    #[test]
+    fn test_friday_night_session_across_midnight() {
+        let dedup = TradeDeduplicator::in_memory();
+        // 周五夜盘 21:00 与周六凌晨 01:00 同属下周一交易日
+        assert!(dedup.first_seen_at(&trade("1", "SHFE"), at(3, 21)));
+        assert!(!dedup.first_seen_at(&trade("1", "SHFE"), at(4, 1)));
+        assert!(dedup.first_seen_at(&trade("2", "SHFE"), at(4, 1)));
+        assert!(!dedup.first_seen_at(&trade("2", "SHFE"), at(6, 10)));
+        assert_eq!(dedup.duplicates(), 2);
+    }
+}
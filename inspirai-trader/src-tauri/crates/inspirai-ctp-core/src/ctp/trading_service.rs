@@ -0,0 +1,1149 @@
+use crate::ctp::{
+    CtpError, CtpEvent, ClientState, TraderSpiImpl, OrderManager,
+    OrderRequest, OrderStatus, OrderAction, TradeRecord, Position, AccountInfo,
+    AccountService, PositionManager, SettlementManager, AccountSummary,
+    Reconciler, ReconciliationSummary, TagAttribution,
+    StrategyGuard, StrategyBudget, StrategyStatus, BreakerState, Timeline, FundsMonitor,
+    BracketBook, BracketOrder, BracketSpec, OrderStatusType, OcoGroup, OcoGroupStatus,
+    TrailingStopManager, TrailingStop, TrailingStopSpec,
+    StrategyRegistry, StrategyPackage, StrategyInstance, DecisionRecorder,
+    OrderRouter, CtpOrderRouter, OrderExpiry,
+    TradingSwitchboard, DisabledTarget, SwitchSource, PriceLimitTracker,
+    models::RiskParams, order_validation::ensure_not_limit_locked,
+    StrategyEngine, StrategyPauseEvent,
+    config::CtpConfig,
+};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn, error, debug};
+
+/// 交易服务
+pub struct TradingService {
+    /// 交易SPI实例
+    trader_spi: Arc<Mutex<TraderSpiImpl>>,
+    /// 订单管理器
+    order_manager: OrderManager,
+    /// 账户服务
+    account_service: AccountService,
+    /// 持仓管理器
+    position_manager: PositionManager,
+    /// 结算管理器
+    settlement_manager: SettlementManager,
+    /// 重连对账
+    reconciler: Reconciler,
+    /// 策略预算与熔断
+    strategy_guard: StrategyGuard,
+    /// 括号单（入场单附带止损止盈）
+    brackets: BracketBook,
+    /// 交易通道（用于熔断后撤单、条件单触发报单）
+    router: Mutex<Option<Arc<dyn OrderRouter>>>,
+    /// CTP 交易 API，重新登录后发起对账查询
+    trader_api: Mutex<Option<Arc<ctp2rs::v1alpha1::TraderApi>>>,
+    /// 事件发送器
+    event_sender: mpsc::UnboundedSender<CtpEvent>,
+    /// 客户端状态
+    client_state: Arc<Mutex<ClientState>>,
+    /// 配置信息
+    config: CtpConfig,
+    /// 服务状态
+    service_state: Arc<Mutex<ServiceState>>,
+    /// 账户活动时间线
+    timeline: Option<Timeline>,
+    /// 资金曲线异常检测，对账结果作为告警上下文
+    funds_monitor: Option<FundsMonitor>,
+    /// 跟踪止损
+    trailing_stops: Option<TrailingStopManager>,
+    /// 策略部署版本
+    strategy_registry: Option<StrategyRegistry>,
+    /// 策略报单时的决策输入快照
+    decisions: DecisionRecorder,
+    /// 按合约、品种的交易开关
+    switchboard: TradingSwitchboard,
+    /// 涨跌停状态，风控开启封板拦截时用于拒单
+    price_limits: Option<PriceLimitTracker>,
+    /// 风控参数
+    risk_params: Mutex<Option<RiskParams>>,
+    /// 策略引擎，行情和定时器驱动 K 线收盘与行情质量暂停
+    strategy_engine: Option<Arc<Mutex<StrategyEngine>>>,
+}
+
+/// 服务状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceState {
+    /// 未初始化
+    Uninitialized,
+    /// 初始化中
+    Initializing,
+    /// 已初始化
+    Initialized,
+    /// 运行中
+    Running,
+    /// 已停止
+    Stopped,
+    /// 错误状态
+    Error(String),
+}
+
+/// 交易服务统计
+#[derive(Debug, Clone, Default)]
+pub struct TradingStats {
+    /// 总订单数
+    pub total_orders: u64,
+    /// 成功订单数
+    pub success_orders: u64,
+    /// 失败订单数
+    pub failed_orders: u64,
+    /// 总成交数
+    pub total_trades: u64,
+    /// 今日成交额
+    pub today_turnover: f64,
+    /// 服务启动时间
+    pub start_time: Option<Instant>,
+    /// 最后活动时间
+    pub last_activity: Option<Instant>,
+}
+
+impl TradingService {
+    /// 创建交易服务
+    pub fn new(
+        config: CtpConfig,
+        client_state: Arc<Mutex<ClientState>>,
+        event_sender: mpsc::UnboundedSender<CtpEvent>,
+    ) -> Self {
+        let trader_spi = Arc::new(Mutex::new(TraderSpiImpl::new(
+            client_state.clone(),
+            event_sender.clone(),
+            config.clone(),
+        )));
+        
+        Self {
+            trader_spi,
+            order_manager: OrderManager::new(),
+            account_service: AccountService::new(config.clone()),
+            position_manager: PositionManager::new(),
+            settlement_manager: SettlementManager::new(),
+            reconciler: Reconciler::new(),
+            strategy_guard: StrategyGuard::new(),
+            brackets: BracketBook::new(),
+            router: Mutex::new(None),
+            trader_api: Mutex::new(None),
+            event_sender,
+            client_state,
+            config,
+            service_state: Arc::new(Mutex::new(ServiceState::Uninitialized)),
+            timeline: None,
+            funds_monitor: None,
+            trailing_stops: None,
+            strategy_registry: None,
+            decisions: DecisionRecorder::new(),
+            switchboard: TradingSwitchboard::in_memory(),
+            price_limits: None,
+            risk_params: Mutex::new(None),
+            strategy_engine: None,
+        }
+    }
+
+    /// 关联账户活动时间线，策略熔断记为风控事件
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// 关联资金曲线异常检测，对账完成后更新其上下文
+    pub fn with_funds_monitor(mut self, funds_monitor: FundsMonitor) -> Self {
+        self.funds_monitor = Some(funds_monitor);
+        self
+    }
+
+    /// 关联跟踪止损，行情更新时检查触发并报出平仓单
+    pub fn with_trailing_stops(mut self, trailing_stops: TrailingStopManager) -> Self {
+        self.trailing_stops = Some(trailing_stops);
+        self.sync_trailing_stop_feed();
+        self
+    }
+
+    /// 关联策略部署注册表，已部署实例的当前版本预算在此注册
+    pub fn with_strategy_registry(mut self, strategy_registry: StrategyRegistry) -> Self {
+        for instance in strategy_registry.list() {
+            self.apply_strategy_budget(&instance);
+        }
+        self.strategy_registry = Some(strategy_registry);
+        self
+    }
+
+    /// 关联持久化的交易开关，替换默认的内存开关
+    pub fn with_switchboard(mut self, switchboard: TradingSwitchboard) -> Self {
+        self.switchboard = switchboard;
+        self
+    }
+
+    /// 关联涨跌停状态，提交订单时按风控参数拦截封板方向的委托
+    pub fn with_price_limits(mut self, price_limits: PriceLimitTracker) -> Self {
+        self.price_limits = Some(price_limits);
+        self
+    }
+
+    /// 关联策略引擎，行情送入引擎，策略因行情异常暂停时撤销其挂单，异常合约自动停止交易；
+    /// ATR 模式跟踪止损的日线也由引擎产出
+    pub fn with_strategy_engine(mut self, strategy_engine: Arc<Mutex<StrategyEngine>>) -> Self {
+        self.strategy_engine = Some(strategy_engine);
+        self.sync_trailing_stop_feed();
+        self
+    }
+
+    /// 更新风控参数
+    pub fn set_risk_params(&self, params: RiskParams) {
+        *self.risk_params.lock().unwrap() = Some(params);
+    }
+
+    /// 初始化服务
+    pub async fn initialize(&self) -> Result<(), CtpError> {
+        info!("初始化交易服务");
+        *self.service_state.lock().unwrap() = ServiceState::Initializing;
+        
+        // 初始化各组件
+        // TODO: 连接到交易前置
+        
+        *self.service_state.lock().unwrap() = ServiceState::Initialized;
+        info!("交易服务初始化完成");
+        
+        Ok(())
+    }
+
+    /// 启动服务
+    pub async fn start(&self) -> Result<(), CtpError> {
+        if *self.service_state.lock().unwrap() != ServiceState::Initialized {
+            return Err(CtpError::StateError("服务未初始化".to_string()));
+        }
+        
+        info!("启动交易服务");
+        *self.service_state.lock().unwrap() = ServiceState::Running;
+        
+        // TODO: 启动后台任务
+        
+        Ok(())
+    }
+
+    /// 停止服务
+    pub async fn stop(&self) -> Result<(), CtpError> {
+        info!("停止交易服务");
+        *self.service_state.lock().unwrap() = ServiceState::Stopped;
+        
+        Ok(())
+    }
+
+    /// 提交订单
+    pub async fn submit_order(&self, mut order: OrderRequest, router: Option<Arc<dyn OrderRouter>>) -> Result<String, CtpError> {
+        // 重连对账完成前不接受新订单
+        if self.reconciler.is_pending() {
+            return Err(CtpError::StateError("重连对账进行中，暂不接受新订单".to_string()));
+        }
+        
+        // 合约或品种已停止交易
+        self.switchboard.check(&order.instrument_id)?;
+        
+        // 验证订单
+        self.order_manager.validate_order(&order)?;
+        if let Some(price_limits) = &self.price_limits {
+            let risk_params = self.risk_params.lock().unwrap().clone();
+            ensure_not_limit_locked(&order, price_limits.status(&order.instrument_id).as_ref(), risk_params.as_ref())?;
+        }
+        if let Some(router) = &router {
+            router.capabilities().check(router.name(), &order)?;
+        }
+        
+        // 策略预算检查
+        if let Err(e) = self.strategy_guard.check_order(&order) {
+            self.enforce_strategy_breakers(router).await;
+            return Err(e);
+        }
+        
+        // 策略报单附带决策输入快照
+        self.decisions.capture(&mut order, &self.strategy_guard);
+        
+        // 生成订单引用
+        let order_ref = {
+            let trader_spi = self.trader_spi.lock().unwrap();
+            let order_ref = trader_spi.next_order_ref();
+            if let Some(client_orders) = trader_spi.client_order_ids() {
+                client_orders.assign(&order_ref, &order.instrument_id, &mut order.tags);
+            }
+            order_ref
+        };
+        
+        info!("提交订单: {} 合约={} 方向={:?} {}手@{}", 
+            order_ref, order.instrument_id, order.direction, order.volume, order.price);
+        
+        // 创建订单状态
+        let order_status = OrderStatus {
+            order_ref: order_ref.clone(),
+            order_id: order_ref.clone(),
+            instrument_id: order.instrument_id.clone(),
+            direction: order.direction,
+            offset_flag: order.offset_flag,
+            price: order.price,
+            limit_price: order.price,
+            volume: order.volume as u32,
+            volume_total_original: order.volume as i32,
+            volume_traded: 0,
+            volume_left: order.volume as u32,
+            volume_total: order.volume as i32,
+            status: crate::ctp::models::OrderStatusType::Unknown,
+            submit_time: chrono::Local::now(),
+            insert_time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            update_time: chrono::Local::now(),
+            front_id: 0,
+            session_id: 0,
+            order_sys_id: String::new(),
+            status_msg: "待提交".to_string(),
+            is_local: true,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: order.tags.clone(),
+        };
+        
+        // 添加到订单管理器
+        self.order_manager.add_order(order_status)?;
+        
+        // 通过交易通道提交订单，发出成功后才计入策略报单数
+        if let Some(router) = router {
+            router.insert_order(&order, &order_ref)?;
+            self.strategy_guard.record_order(&order);
+        } else {
+            warn!("交易通道未提供，订单将仅在本地记录");
+        }
+        
+        Ok(order_ref)
+    }
+
+    /// 提交括号单：入场单成交后按成交手数挂载止损、止盈条件单，两者触发其一即撤销另一个
+    ///
+    /// `exchange_id` 取自合约信息，上期所、能源中心的平仓单报平今
+    pub async fn submit_bracket_order(
+        &self,
+        order: OrderRequest,
+        exchange_id: &str,
+        spec: BracketSpec,
+        router: Option<Arc<dyn OrderRouter>>,
+    ) -> Result<BracketOrder, CtpError> {
+        BracketBook::validate(&order, &spec)?;
+        let entry_ref = self.submit_order(order.clone(), router).await?;
+        let bracket = self.brackets.create(&entry_ref, exchange_id, &order, &spec)?;
+        info!("括号单 {} 已创建，入场单 {}", bracket.id, entry_ref);
+        self.send_bracket_update(bracket.clone());
+        Ok(bracket)
+    }
+
+    /// 取消尚未触发的括号单，入场单仍在队列中时一并撤销
+    pub async fn cancel_bracket(&self, bracket_id: &str, router: Option<Arc<dyn OrderRouter>>) -> Result<BracketOrder, CtpError> {
+        let (bracket, cancel_entry) = self.brackets.cancel(bracket_id)?;
+        if let Some(entry_ref) = cancel_entry {
+            if let Err(e) = self.cancel_order(&entry_ref, router).await {
+                warn!("撤销括号单 {} 的入场单 {} 失败: {}", bracket_id, entry_ref, e);
+            }
+        }
+        self.send_bracket_update(bracket.clone());
+        Ok(bracket)
+    }
+
+    /// 查询括号单
+    pub fn get_brackets(&self) -> Vec<BracketOrder> {
+        self.brackets.list()
+    }
+
+    /// 行情触及括号单触发价时报出平仓单，并撤销入场单的未成交部分
+    async fn trigger_brackets(&self, instrument_id: &str, price: f64) {
+        let triggers = self.brackets.on_price(instrument_id, price);
+        if triggers.is_empty() {
+            return;
+        }
+        let router = self.router.lock().unwrap().clone();
+        for trigger in triggers {
+            if let Some(entry_ref) = &trigger.cancel_entry {
+                if let Err(e) = self.cancel_order(entry_ref, router.clone()).await {
+                    warn!("撤销括号单 {} 的入场单 {} 失败: {}", trigger.bracket_id, entry_ref, e);
+                }
+            }
+            match self.submit_order(trigger.order, router.clone()).await {
+                Ok(order_ref) => {
+                    if let Some(bracket) = self.brackets.attach_exit_order(&trigger.bracket_id, trigger.leg, &order_ref) {
+                        self.send_bracket_update(bracket);
+                    }
+                }
+                Err(e) => {
+                    error!("括号单 {} {}平仓单报单失败: {}", trigger.bracket_id, trigger.leg.label(), e);
+                    if let Some(timeline) = &self.timeline {
+                        timeline.record_risk(
+                            format!("括号单 {} {}平仓单报单失败: {}", trigger.bracket_id, trigger.leg.label(), e),
+                            Some(instrument_id.to_string()),
+                        );
+                    }
+                    if let Some(bracket) = self.brackets.get(&trigger.bracket_id) {
+                        self.send_bracket_update(bracket);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 新增跟踪止损，以持仓最新价作为初始最有利价
+    pub fn add_trailing_stop(&self, spec: TrailingStopSpec) -> Result<TrailingStop, CtpError> {
+        let manager = self.trailing_stops.as_ref()
+            .ok_or_else(|| CtpError::StateError("跟踪止损未启用".to_string()))?;
+        let last_price = self.position_manager
+            .get_position(&spec.instrument_id, spec.position_direction)
+            .map(|detail| detail.last_price)
+            .filter(|price| *price > 0.0);
+        let stop = manager.add(spec, last_price)?;
+        self.sync_trailing_stop_feed();
+        self.send_trailing_stop_update(stop.clone());
+        Ok(stop)
+    }
+
+    /// 取消跟踪止损
+    pub fn cancel_trailing_stop(&self, id: &str) -> Result<TrailingStop, CtpError> {
+        let manager = self.trailing_stops.as_ref()
+            .ok_or_else(|| CtpError::StateError("跟踪止损未启用".to_string()))?;
+        let stop = manager.cancel(id)?;
+        self.sync_trailing_stop_feed();
+        self.send_trailing_stop_update(stop.clone());
+        Ok(stop)
+    }
+
+    /// 查询跟踪止损及当前触发价
+    pub fn get_trailing_stops(&self) -> Vec<TrailingStop> {
+        self.trailing_stops.as_ref().map(|m| m.list()).unwrap_or_default()
+    }
+
+    /// 行情触及跟踪止损触发价时报出平仓单
+    async fn trigger_trailing_stops(&self, instrument_id: &str, price: f64) {
+        let Some(manager) = self.trailing_stops.clone() else {
+            return;
+        };
+        let triggers = manager.on_tick(instrument_id, price);
+        if triggers.is_empty() {
+            return;
+        }
+        self.sync_trailing_stop_feed();
+        let router = self.router.lock().unwrap().clone();
+        for trigger in triggers {
+            let id = trigger.stop.id.clone();
+            match self.submit_order(trigger.order, router.clone()).await {
+                Ok(order_ref) => {
+                    if let Some(stop) = manager.attach_exit_order(&id, &order_ref) {
+                        self.send_trailing_stop_update(stop);
+                    }
+                }
+                Err(e) => {
+                    error!("跟踪止损 {} 平仓单报单失败: {}", id, e);
+                    if let Some(timeline) = &self.timeline {
+                        timeline.record_risk(
+                            format!("跟踪止损 {} 平仓单报单失败: {}", id, e),
+                            Some(instrument_id.to_string()),
+                        );
+                    }
+                    self.send_trailing_stop_update(trigger.stop);
+                }
+            }
+        }
+    }
+
+    /// 按活动中的 ATR 模式跟踪止损更新策略引擎中的日线订阅
+    fn sync_trailing_stop_feed(&self) {
+        let (Some(manager), Some(engine)) = (&self.trailing_stops, &self.strategy_engine) else {
+            return;
+        };
+        if let Err(e) = manager.sync_atr_feed(&mut engine.lock().unwrap(), None) {
+            warn!("跟踪止损日线订阅更新失败: {}", e);
+        }
+    }
+
+    fn send_trailing_stop_update(&self, stop: TrailingStop) {
+        if let Err(e) = self.event_sender.send(CtpEvent::TrailingStopUpdate(stop)) {
+            warn!("发送跟踪止损事件失败: {}", e);
+        }
+    }
+
+    /// 为挂单设置到期时间，到期后自动撤销
+    pub fn set_order_expiry(
+        &self,
+        order_id: &str,
+        expire_at: chrono::DateTime<chrono::Utc>,
+        reason: Option<String>,
+    ) -> Result<OrderExpiry, CtpError> {
+        self.order_manager.set_order_expiry(order_id, expire_at, reason)
+    }
+
+    /// 为带指定标签的全部挂单设置到期时间
+    pub fn set_expiry_by_tag(
+        &self,
+        key: &str,
+        value: &str,
+        expire_at: chrono::DateTime<chrono::Utc>,
+        reason: Option<String>,
+    ) -> Vec<OrderExpiry> {
+        self.order_manager.set_expiry_by_tag(key, value, expire_at, reason)
+    }
+
+    /// 取消挂单的到期计划
+    pub fn clear_order_expiry(&self, order_id: &str) -> Option<OrderExpiry> {
+        self.order_manager.clear_order_expiry(order_id)
+    }
+
+    /// 获取全部到期计划
+    pub fn get_order_expiries(&self) -> Vec<OrderExpiry> {
+        self.order_manager.get_order_expiries()
+    }
+
+    /// 为已到期的挂单发出撤单，由 `on_timer` 驱动；撤单回报确认后才发布到期事件
+    pub async fn cancel_expired_orders(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<OrderExpiry> {
+        let due = self.order_manager.due_expiries(now);
+        if due.is_empty() {
+            return due;
+        }
+        let router = self.router.lock().unwrap().clone();
+        let mut sent = Vec::new();
+        for expiry in due {
+            match self.cancel_order(&expiry.order_id, router.clone()).await {
+                Ok(()) => {
+                    info!("订单 {} 到期，已发出撤单: {}", expiry.order_id, expiry.reason);
+                    sent.push(expiry);
+                }
+                Err(e @ (CtpError::NotFound(_) | CtpError::StateError(_))) => {
+                    warn!("到期订单 {} 已不可撤，放弃到期计划: {}", expiry.order_id, e);
+                    self.order_manager.expiry_cancel_failed(&expiry.order_id, true);
+                }
+                Err(e) => {
+                    error!("撤销到期订单 {} 失败，稍后重试: {}", expiry.order_id, e);
+                    self.order_manager.expiry_cancel_failed(&expiry.order_id, false);
+                }
+            }
+        }
+        sent
+    }
+
+    /// 发布撤单已确认的到期订单
+    fn publish_order_expiries(&self) {
+        for expiry in self.order_manager.take_expired_orders() {
+            info!("订单 {} 到期撤单完成: {}", expiry.order_id, expiry.reason);
+            if let Err(e) = self.event_sender.send(CtpEvent::OrderExpired(expiry)) {
+                warn!("发送订单到期事件失败: {}", e);
+            }
+        }
+    }
+
+    /// 将两个以上的活动订单组成 OCO 组，任一成员成交达到 `fill_threshold` 比例后撤销其余成员
+    pub fn create_oco_group(&self, order_ids: Vec<String>, fill_threshold: Option<f64>) -> Result<OcoGroup, CtpError> {
+        let group = self.order_manager.create_oco_group(order_ids, fill_threshold)?;
+        self.publish_oco_updates();
+        Ok(group)
+    }
+
+    /// 解散 OCO 组，成员订单不受影响
+    pub fn dissolve_oco_group(&self, group_id: &str) -> Result<OcoGroup, CtpError> {
+        let group = self.order_manager.dissolve_oco_group(group_id)?;
+        self.publish_oco_updates();
+        Ok(group)
+    }
+
+    /// 查询 OCO 组
+    pub fn get_oco_groups(&self) -> Vec<OcoGroup> {
+        self.order_manager.get_oco_groups()
+    }
+
+    /// 撤销已触发 OCO 组的其余成员并发布组状态变化
+    async fn enforce_oco_groups(&self, router: Option<Arc<dyn OrderRouter>>) {
+        for group in self.order_manager.take_oco_updates() {
+            if group.status == OcoGroupStatus::Triggered {
+                for order_id in &group.cancel_order_ids {
+                    if let Err(e) = self.cancel_order(order_id, router.clone()).await {
+                        error!("撤销 OCO 组 {} 的订单 {} 失败: {}", group.id, order_id, e);
+                    }
+                }
+            }
+            if let Err(e) = self.event_sender.send(CtpEvent::OcoGroupUpdate(group)) {
+                warn!("发送 OCO 组事件失败: {}", e);
+            }
+        }
+    }
+
+    fn publish_oco_updates(&self) {
+        for group in self.order_manager.take_oco_updates() {
+            if let Err(e) = self.event_sender.send(CtpEvent::OcoGroupUpdate(group)) {
+                warn!("发送 OCO 组事件失败: {}", e);
+            }
+        }
+    }
+
+    fn send_bracket_update(&self, bracket: BracketOrder) {
+        if let Err(e) = self.event_sender.send(CtpEvent::BracketUpdate(bracket)) {
+            warn!("发送括号单事件失败: {}", e);
+        }
+    }
+
+    /// 撤销订单
+    pub async fn cancel_order(&self, order_id: &str, router: Option<Arc<dyn OrderRouter>>) -> Result<(), CtpError> {
+        info!("撤销订单: {}", order_id);
+        
+        // 获取订单信息
+        let order_info = self.order_manager.get_order(order_id)
+            .ok_or_else(|| CtpError::NotFound(format!("订单不存在: {}", order_id)))?;
+        
+        // 检查订单状态
+        if !self.can_cancel(&order_info.status) {
+            return Err(CtpError::StateError(
+                format!("订单状态不允许撤销: {:?}", order_info.status.status)
+            ));
+        }
+        
+        // 通过交易通道撤销订单
+        if let Some(router) = router {
+            router.cancel_order(&order_info.status)?;
+        } else {
+            warn!("交易通道未提供，撤单将仅在本地记录");
+        }
+        
+        Ok(())
+    }
+
+    /// 查询订单
+    pub async fn query_order(&self, order_id: &str) -> Result<OrderStatus, CtpError> {
+        self.order_manager.get_order(order_id)
+            .map(|info| info.status)
+            .ok_or_else(|| CtpError::NotFound(format!("订单不存在: {}", order_id)))
+    }
+
+    /// 查询所有活动订单
+    pub async fn query_active_orders(&self) -> Result<Vec<OrderStatus>, CtpError> {
+        Ok(self.order_manager.get_active_orders())
+    }
+
+    /// 查询成交记录
+    pub async fn query_trades(&self, order_id: Option<&str>, trader_api: Option<Arc<ctp2rs::v1alpha1::TraderApi>>) -> Result<Vec<TradeRecord>, CtpError> {
+        // 使用真实的 CTP API 查询成交记录
+        if let Some(api) = trader_api {
+            // 创建成交查询请求
+            let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryTradeField::default();
+            
+            // 使用 ctp2rs 提供的字符串赋值工具
+            use ctp2rs::ffi::AssignFromString;
+            qry_req.BrokerID.assign_from_str(&self.config.broker_id);
+            qry_req.InvestorID.assign_from_str(&self.config.investor_id);
+            
+            // 如果指定了订单ID，则查询特定订单的成交
+            // 注意：CThostFtdcQryTradeField 没有 OrderRef 字段，需要使用其他字段
+            // 这里我们暂时跳过特定订单的过滤，在回调中处理
+            if let Some(_id) = order_id {
+                // TODO: 根据 CTP API 文档确定正确的字段来过滤特定订单的成交
+                // qry_req.TradeID.assign_from_str(id);
+            }
+            
+            let request_id = chrono::Utc::now().timestamp_millis() as i32 % 1000000;
+            
+            info!("发送成交查询请求，请求ID: {}", request_id);
+            
+            // 调用 ctp2rs TraderApi 查询成交
+            let result = api.req_qry_trade(&mut qry_req, request_id);
+            
+            if result != 0 {
+                return Err(CtpError::CtpApiError {
+                    code: result,
+                    message: "成交查询请求发送失败".to_string(),
+                });
+            }
+            
+            info!("成交查询请求已发送");
+        }
+        
+        // 返回本地缓存的成交记录
+        if let Some(id) = order_id {
+            Ok(self.order_manager.get_order_trades(id))
+        } else {
+            Ok(self.order_manager.get_today_trades())
+        }
+    }
+
+    /// 查询持仓
+    pub async fn query_positions(&self, trader_api: Option<Arc<ctp2rs::v1alpha1::TraderApi>>) -> Result<Vec<Position>, CtpError> {
+        // 使用真实的 CTP API 查询持仓信息
+        if let Some(api) = trader_api {
+            // 创建投资者持仓查询请求
+            let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryInvestorPositionField::default();
+            
+            // 使用 ctp2rs 提供的字符串赋值工具
+            use ctp2rs::ffi::AssignFromString;
+            qry_req.BrokerID.assign_from_str(&self.config.broker_id);
+            qry_req.InvestorID.assign_from_str(&self.config.investor_id);
+            // InstrumentID 留空表示查询所有合约的持仓
+            
+            let request_id = chrono::Utc::now().timestamp_millis() as i32 % 1000000;
+            
+            info!("发送投资者持仓查询请求，请求ID: {}", request_id);
+            
+            // 调用 ctp2rs TraderApi 查询投资者持仓
+            let result = api.req_qry_investor_position(&mut qry_req, request_id);
+            
+            if result != 0 {
+                return Err(CtpError::CtpApiError {
+                    code: result,
+                    message: "投资者持仓查询请求发送失败".to_string(),
+                });
+            }
+            
+            info!("投资者持仓查询请求已发送");
+        }
+        
+        // 返回本地缓存的持仓信息
+        Ok(self.trader_spi.lock().unwrap().get_all_positions())
+    }
+
+    /// 查询账户信息
+    pub async fn query_account(&self, trader_api: Option<Arc<ctp2rs::v1alpha1::TraderApi>>) -> Result<AccountInfo, CtpError> {
+        // 使用真实的 CTP API 查询账户信息
+        if let Some(api) = trader_api {
+            // 创建资金账户查询请求
+            let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryTradingAccountField::default();
+            
+            // 使用 ctp2rs 提供的字符串赋值工具
+            use ctp2rs::ffi::AssignFromString;
+            qry_req.BrokerID.assign_from_str(&self.config.broker_id);
+            qry_req.InvestorID.assign_from_str(&self.config.investor_id);
+            
+            let request_id = chrono::Utc::now().timestamp_millis() as i32 % 1000000;
+            
+            info!("发送资金账户查询请求，请求ID: {}", request_id);
+            
+            // 调用 ctp2rs TraderApi 查询资金账户
+            let result = api.req_qry_trading_account(&mut qry_req, request_id);
+            
+            if result != 0 {
+                return Err(CtpError::CtpApiError {
+                    code: result,
+                    message: "资金账户查询请求发送失败".to_string(),
+                });
+            }
+            
+            info!("资金账户查询请求已发送");
+        }
+        
+        // 尝试从本地缓存获取账户信息
+        self.account_service.get_account()
+            .ok_or_else(|| CtpError::NotFound("账户信息未初始化".to_string()))
+    }
+    
+    /// 获取账户摘要
+    pub async fn get_account_summary(&self) -> AccountSummary {
+        self.account_service.get_summary()
+    }
+    
+    /// 计算可开仓手数
+    pub async fn calculate_available_volume(
+        &self,
+        instrument_id: &str,
+        price: f64,
+        margin_ratio: f64,
+    ) -> Result<i32, CtpError> {
+        self.account_service.calculate_available_volume(instrument_id, price, margin_ratio)
+    }
+    
+    /// 获取可平仓数量
+    pub async fn get_closeable_volume(
+        &self,
+        instrument_id: &str,
+        direction: crate::ctp::OrderDirection,
+        offset_flag: crate::ctp::OffsetFlag,
+    ) -> Result<i32, CtpError> {
+        self.position_manager.get_closeable_volume(instrument_id, direction, offset_flag)
+    }
+    
+    /// 重新登录后发起对账：查询报单、成交、持仓，结果经 `handle_event` 汇总
+    ///
+    /// 查询发送失败时放弃本次对账，不再阻止新订单
+    pub async fn reconcile_after_reconnect(&self, trader_api: Option<Arc<ctp2rs::v1alpha1::TraderApi>>) -> Result<(), CtpError> {
+        let result = self.send_reconciliation_queries(trader_api).await;
+        if let Err(e) = &result {
+            self.reconciler.abort(&e.to_string());
+        }
+        result
+    }
+
+    async fn send_reconciliation_queries(&self, trader_api: Option<Arc<ctp2rs::v1alpha1::TraderApi>>) -> Result<(), CtpError> {
+        let api = trader_api
+            .ok_or_else(|| CtpError::StateError("交易 API 未初始化".to_string()))?;
+        
+        self.reconciler.begin();
+        
+        let mut qry_req = ctp2rs::v1alpha1::CThostFtdcQryOrderField::default();
+        
+        use ctp2rs::ffi::AssignFromString;
+        qry_req.BrokerID.assign_from_str(&self.config.broker_id);
+        qry_req.InvestorID.assign_from_str(&self.config.investor_id);
+        
+        let request_id = chrono::Utc::now().timestamp_millis() as i32 % 1000000;
+        
+        info!("对账：发送报单查询请求，请求ID: {}", request_id);
+        
+        let result = api.req_qry_order(&mut qry_req, request_id);
+        if result != 0 {
+            return Err(CtpError::CtpApiError {
+                code: result,
+                message: "报单查询请求发送失败".to_string(),
+            });
+        }
+        
+        // CTP 查询有流控，间隔发送
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.query_trades(None, Some(api.clone())).await?;
+        
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.query_positions(Some(api)).await?;
+        
+        Ok(())
+    }
+    
+    /// 是否正在等待重连对账
+    pub fn is_reconciliation_pending(&self) -> bool {
+        self.reconciler.is_pending()
+    }
+    
+    /// 最近一次对账结果
+    pub fn last_reconciliation(&self) -> Option<ReconciliationSummary> {
+        self.reconciler.last_summary()
+    }
+    
+    /// 查询结算单
+    pub async fn query_settlement(&self, trading_day: Option<String>) -> Result<String, CtpError> {
+        let date = if let Some(day) = trading_day {
+            Some(chrono::NaiveDate::parse_from_str(&day, "%Y%m%d")
+                .map_err(|e| CtpError::ConversionError(format!("日期格式错误: {}", e)))?)
+        } else {
+            None
+        };
+        
+        let settlement = self.settlement_manager.get_settlement(date)?;
+        Ok(settlement.content)
+    }
+    
+    /// 确认结算单
+    pub async fn confirm_settlement(&self, trading_day: Option<String>) -> Result<(), CtpError> {
+        let date = if let Some(day) = trading_day {
+            Some(chrono::NaiveDate::parse_from_str(&day, "%Y%m%d")
+                .map_err(|e| CtpError::ConversionError(format!("日期格式错误: {}", e)))?)
+        } else {
+            None
+        };
+        
+        self.settlement_manager.confirm_settlement(date)
+    }
+
+    /// 关联 CTP 交易 API，熔断触发时用于撤销策略挂单，重新登录后用于对账查询
+    pub fn attach_trader_api(&self, trader_api: Arc<ctp2rs::v1alpha1::TraderApi>) {
+        *self.trader_api.lock().unwrap() = Some(trader_api.clone());
+        self.attach_router(Arc::new(CtpOrderRouter::new(
+            trader_api,
+            &self.config.broker_id,
+            &self.config.investor_id,
+        )));
+    }
+
+    /// 关联交易通道，用于熔断撤单和条件单触发报单
+    pub fn attach_router(&self, router: Arc<dyn OrderRouter>) {
+        info!("关联交易通道: {}", router.name());
+        *self.router.lock().unwrap() = Some(router);
+    }
+    
+    /// 注册策略预算
+    pub fn register_strategy(&self, name: &str, budget: StrategyBudget) {
+        self.strategy_guard.register(name, budget);
+    }
+    
+    /// 人工复位策略熔断
+    pub fn reset_strategy(&self, name: &str) -> Result<(), CtpError> {
+        self.strategy_guard.reset(name)
+    }
+    
+    /// 获取全部策略状态
+    pub fn get_strategy_statuses(&self) -> Vec<StrategyStatus> {
+        self.strategy_guard.all_statuses()
+    }
+    
+    /// 部署或升级策略实例
+    ///
+    /// 实例 ID 即策略标签，升级后持仓、当日统计和熔断状态沿用；新版本带预算时更新预算上限
+    pub fn deploy_strategy(&self, instance_id: &str, package: StrategyPackage) -> Result<StrategyInstance, CtpError> {
+        self.config.programmatic.ensure_algo_allowed(self.config.environment)?;
+        let registry = self.strategy_registry.as_ref()
+            .ok_or_else(|| CtpError::StateError("策略部署注册表未启用".to_string()))?;
+        let instance = registry.deploy(instance_id, package)?;
+        self.apply_strategy_budget(&instance);
+        Ok(instance)
+    }
+
+    /// 回滚策略实例，未指定版本时回到上一个运行的版本
+    pub fn rollback_strategy(&self, instance_id: &str, version: Option<&str>) -> Result<StrategyInstance, CtpError> {
+        let registry = self.strategy_registry.as_ref()
+            .ok_or_else(|| CtpError::StateError("策略部署注册表未启用".to_string()))?;
+        let instance = registry.rollback(instance_id, version)?;
+        self.apply_strategy_budget(&instance);
+        Ok(instance)
+    }
+
+    /// 获取策略实例部署状态
+    pub fn get_strategy_deployments(&self) -> Vec<StrategyInstance> {
+        self.strategy_registry.as_ref().map(|r| r.list()).unwrap_or_default()
+    }
+
+    fn apply_strategy_budget(&self, instance: &StrategyInstance) {
+        if let Some(budget) = instance.current_package().and_then(|p| p.budget.clone()) {
+            self.strategy_guard.register(&instance.instance_id, budget);
+        }
+    }
+
+    /// 停止合约交易，人工操作或风控触发（如行情数据质量异常）
+    pub fn disable_instrument_trading(&self, instrument_id: &str, source: SwitchSource, reason: &str) -> Result<DisabledTarget, CtpError> {
+        self.switchboard.disable_instrument(instrument_id, source, reason)
+    }
+
+    /// 停止品种交易
+    pub fn disable_product_trading(&self, product_id: &str, source: SwitchSource, reason: &str) -> Result<DisabledTarget, CtpError> {
+        self.switchboard.disable_product(product_id, source, reason)
+    }
+
+    /// 恢复合约交易
+    pub fn enable_instrument_trading(&self, instrument_id: &str) -> Option<DisabledTarget> {
+        self.switchboard.enable_instrument(instrument_id)
+    }
+
+    /// 恢复品种交易
+    pub fn enable_product_trading(&self, product_id: &str) -> Option<DisabledTarget> {
+        self.switchboard.enable_product(product_id)
+    }
+
+    /// 获取全部停止交易的合约和品种
+    pub fn get_disabled_trading(&self) -> Vec<DisabledTarget> {
+        self.switchboard.list()
+    }
+
+    /// 获取策略沙箱
+    pub fn strategy_guard(&self) -> &StrategyGuard {
+        &self.strategy_guard
+    }
+    
+    /// 撤销新熔断策略的挂单并通知上层
+    async fn enforce_strategy_breakers(&self, router: Option<Arc<dyn OrderRouter>>) {
+        for name in self.strategy_guard.take_newly_tripped() {
+            let resting: Vec<String> = self.order_manager
+                .get_active_orders()
+                .into_iter()
+                .filter(|order| StrategyGuard::strategy_of(&order.tags) == Some(name.as_str()))
+                .map(|order| order.order_id)
+                .collect();
+            
+            warn!("策略 {} 已熔断，撤销 {} 笔挂单", name, resting.len());
+            for order_id in resting {
+                if let Err(e) = self.cancel_order(&order_id, router.clone()).await {
+                    error!("撤销熔断策略 {} 的挂单 {} 失败: {}", name, order_id, e);
+                }
+            }
+            
+            if let Some(status) = self.strategy_guard.status(&name) {
+                if let (Some(timeline), BreakerState::Tripped { reason, .. }) = (&self.timeline, &status.breaker) {
+                    timeline.record_risk(format!("策略 {} 熔断: {}", name, reason), None);
+                }
+                if let Err(e) = self.event_sender.send(CtpEvent::StrategyCircuitBreakerTripped(status)) {
+                    warn!("发送策略熔断事件失败: {}", e);
+                }
+            }
+        }
+    }
+
+    /// 定时驱动：撤销到期挂单，收盘没有后续行情推进的 K 线，检查行情停滞并处理策略暂停
+    ///
+    /// 由调用方按秒级间隔调用，`now` 为本地时间
+    pub async fn on_timer(&self, now: chrono::NaiveDateTime) {
+        if let Some(local) = now.and_local_timezone(chrono::Local).earliest() {
+            self.cancel_expired_orders(local.with_timezone(&chrono::Utc)).await;
+        }
+        if let Some(engine) = &self.strategy_engine {
+            let mut engine = engine.lock().unwrap();
+            engine.on_timer(now);
+            self.switchboard.sync_data_quality(&engine.degraded_instruments());
+        }
+        let router = self.router.lock().unwrap().clone();
+        self.handle_strategy_pauses(router).await;
+    }
+
+    /// 策略因行情异常暂停后撤销它在该合约上的挂单
+    async fn handle_strategy_pauses(&self, router: Option<Arc<dyn OrderRouter>>) {
+        let Some(engine) = &self.strategy_engine else {
+            return;
+        };
+        let events = engine.lock().unwrap().take_pause_events();
+        for event in events {
+            let StrategyPauseEvent::Paused { strategy_id, instrument_id, issue, .. } = event else {
+                continue;
+            };
+            let resting: Vec<String> = self.order_manager
+                .get_active_orders()
+                .into_iter()
+                .filter(|order| {
+                    order.instrument_id == instrument_id
+                        && StrategyGuard::strategy_of(&order.tags) == Some(strategy_id.as_str())
+                })
+                .map(|order| order.order_id)
+                .collect();
+            
+            warn!("策略 {} 因 {} 行情异常（{:?}）暂停，撤销 {} 笔挂单", strategy_id, instrument_id, issue, resting.len());
+            for order_id in &resting {
+                if let Err(e) = self.cancel_order(order_id, router.clone()).await {
+                    error!("撤销暂停策略 {} 的挂单 {} 失败: {}", strategy_id, order_id, e);
+                }
+            }
+            if let Some(timeline) = &self.timeline {
+                timeline.record_risk(
+                    format!("策略 {} 因 {} 行情异常暂停，撤销挂单 {} 笔", strategy_id, instrument_id, resting.len()),
+                    Some(instrument_id.clone()),
+                );
+            }
+        }
+    }
+
+    /// 获取服务状态
+    pub fn get_state(&self) -> ServiceState {
+        self.service_state.lock().unwrap().clone()
+    }
+
+    /// 按标签键归集成交（如按策略名分组）
+    pub fn get_attribution_by_tag(&self, key: &str) -> std::collections::HashMap<String, TagAttribution> {
+        self.order_manager.get_attribution_by_tag(key, &self.position_manager.round_trip_book())
+    }
+
+    /// 获取交易统计
+    pub fn get_stats(&self) -> TradingStats {
+        let order_stats = self.order_manager.get_stats();
+        
+        TradingStats {
+            total_orders: order_stats.total_orders,
+            success_orders: order_stats.success_orders,
+            failed_orders: order_stats.failed_orders,
+            total_trades: order_stats.total_trades,
+            today_turnover: order_stats.today_turnover,
+            start_time: None,
+            last_activity: Some(Instant::now()),
+        }
+    }
+
+    /// 处理交易事件
+    pub async fn handle_event(&self, event: CtpEvent) -> Result<(), CtpError> {
+        match event {
+            CtpEvent::OrderUpdate(order) => {
+                // 全部成交由成交回报挂载子单，这里只处理撤单、拒单
+                let finished = matches!(
+                    order.status,
+                    OrderStatusType::Canceled
+                        | OrderStatusType::Cancelled
+                        | OrderStatusType::PartTradedNotQueueing
+                        | OrderStatusType::NoTradeNotQueueing
+                );
+                let (order_ref, volume_traded) = (order.order_ref.clone(), order.volume_traded);
+                self.order_manager.update_order(order)?;
+                if finished {
+                    if let Some(bracket) = self.brackets.on_entry_finished(&order_ref, volume_traded) {
+                        self.send_bracket_update(bracket);
+                    }
+                }
+                self.publish_order_expiries();
+                let router = self.router.lock().unwrap().clone();
+                self.enforce_oco_groups(router).await;
+            }
+            CtpEvent::TradeUpdate(mut trade) => {
+                if self.order_manager.is_duplicate_trade(&trade) {
+                    warn!("忽略重复成交事件: {} 合约={}", trade.trade_id, trade.instrument_id);
+                    return Ok(());
+                }
+                if trade.tags.is_empty() {
+                    if let Some(tags) = self.order_manager.tags_for_order(&trade.order_id) {
+                        trade.tags = tags;
+                    }
+                }
+                self.strategy_guard.on_trade(&trade);
+                self.position_manager.record_fill(&trade, chrono::Utc::now());
+                if let Some(bracket) = self.brackets.on_trade(&trade.order_id, trade.volume.max(0) as u32) {
+                    self.send_bracket_update(bracket);
+                }
+                self.order_manager.add_trade(trade)?;
+                let router = self.router.lock().unwrap().clone();
+                self.enforce_oco_groups(router.clone()).await;
+                self.enforce_strategy_breakers(router).await;
+            }
+            CtpEvent::MarketData(tick) => {
+                self.decisions.on_tick(&tick);
+                self.strategy_guard.update_price(&tick.instrument_id, tick.last_price);
+                self.position_manager.update_last_price(&tick.instrument_id, tick.last_price);
+                self.trigger_brackets(&tick.instrument_id, tick.last_price).await;
+                self.trigger_trailing_stops(&tick.instrument_id, tick.last_price).await;
+                if let Some(engine) = &self.strategy_engine {
+                    let mut engine = engine.lock().unwrap();
+                    engine.on_tick(&tick, chrono::Local::now().naive_local());
+                    self.switchboard.sync_data_quality(&engine.degraded_instruments());
+                }
+                let router = self.router.lock().unwrap().clone();
+                self.enforce_strategy_breakers(router.clone()).await;
+                self.handle_strategy_pauses(router).await;
+            }
+            CtpEvent::PositionUpdate(positions) => {
+                // 更新持仓管理器
+                for position in positions {
+                    self.position_manager.update_position(position.clone())?;
+                    self.account_service.update_position(position)?;
+                }
+            }
+            CtpEvent::AccountUpdate(account) => {
+                // 更新账户服务
+                self.account_service.update_account(account)?;
+            }
+            CtpEvent::Disconnected => {
+                // 断线期间的状态变化未知，重连后需要对账
+                self.reconciler.require();
+            }
+            CtpEvent::LoginSuccess(_) | CtpEvent::TraderRecovered => {
+                // 断线后重新登录，对账完成前新订单仍被拒绝
+                if self.reconciler.needs_start() {
+                    let trader_api = self.trader_api.lock().unwrap().clone();
+                    if let Err(e) = self.reconcile_after_reconnect(trader_api).await {
+                        warn!("重连对账未能发起: {}", e);
+                    }
+                }
+            }
+            CtpEvent::QueryOrdersResult(_)
+            | CtpEvent::QueryTradesResult(_)
+            | CtpEvent::QueryPositionsResult(_) => {
+                if self.reconciler.feed(&event) {
+                    if let Some(summary) = self.reconciler.complete(&self.order_manager, &self.position_manager) {
+                        if let Some(monitor) = &self.funds_monitor {
+                            monitor.record_reconciliation(summary.clone());
+                        }
+                        if let Err(e) = self.event_sender.send(CtpEvent::ReconciliationCompleted(summary)) {
+                            warn!("发送对账结果失败: {}", e);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        
+        Ok(())
+    }
+
+    /// 判断订单是否可以撤销
+    pub fn can_cancel(&self, order: &OrderStatus) -> bool {
+        matches!(
+            order.status,
+            crate::ctp::models::OrderStatusType::Unknown
+                | crate::ctp::models::OrderStatusType::PartTradedQueueing
+                | crate::ctp::models::OrderStatusType::NoTradeQueueing
+                | crate::ctp::models::OrderStatusType::Touched
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,274 @@
+use crate::ctp::{bar_import::trading_day_of, tick_compaction::StorageGranularity, CtpError};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 连续交易时段，结束早于开始表示跨午夜（如夜盘 21:00-01:00）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRange {
+    /// HH:MM
+    pub start: String,
+    /// HH:MM
+    pub end: String,
+}
+
+impl SessionRange {
+    pub fn new(start: &str, end: &str) -> Self {
+        Self {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn parse(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let parse = |v: &str| NaiveTime::parse_from_str(v, "%H:%M").ok();
+        Some((parse(&self.start)?, parse(&self.end)?))
+    }
+}
+
+/// 各品种的交易时段
+///
+/// 未单独配置的品种使用 `default_sessions`（商品期货日盘）。节假日前无夜盘等临时调整不在此处理，
+/// 没有 tick 的时段不会生成 K 线
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub default_sessions: Vec<SessionRange>,
+    /// 按品种覆盖，键为品种代码（rb、IF 等），不区分大小写
+    pub products: BTreeMap<String, Vec<SessionRange>>,
+    /// 开盘前集合竞价的 tick 在此秒数内归入第一根 K 线
+    pub auction_lead_secs: u32,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        let day = || {
+            vec![
+                SessionRange::new("09:00", "10:15"),
+                SessionRange::new("10:30", "11:30"),
+                SessionRange::new("13:30", "15:00"),
+            ]
+        };
+        let with_night = |end: &str| {
+            let mut sessions = vec![SessionRange::new("21:00", end)];
+            sessions.extend(day());
+            sessions
+        };
+        let mut products = BTreeMap::new();
+        let groups: [(&[&str], Vec<SessionRange>); 5] = [
+            (
+                &[
+                    "rb", "hc", "bu", "ru", "fu", "sp", "br", "FG", "SA", "MA", "TA", "SR", "CF", "RM", "OI", "PF",
+                    "m", "y", "p", "c", "cs", "a", "b", "i", "j", "jm", "l", "v", "pp", "eg", "eb", "pg",
+                ],
+                with_night("23:00"),
+            ),
+            (&["cu", "al", "zn", "pb", "ni", "sn", "ss", "bc", "ao"], with_night("01:00")),
+            (&["au", "ag", "sc"], with_night("02:30")),
+            (
+                &["IF", "IH", "IC", "IM"],
+                vec![SessionRange::new("09:30", "11:30"), SessionRange::new("13:00", "15:00")],
+            ),
+            (
+                &["T", "TF", "TS", "TL"],
+                vec![SessionRange::new("09:30", "11:30"), SessionRange::new("13:00", "15:15")],
+            ),
+        ];
+        for (codes, sessions) in groups {
+            for code in codes {
+                products.insert(code.to_string(), sessions.clone());
+            }
+        }
+        Self {
+            default_sessions: day(),
+            products,
+            auction_lead_secs: 60,
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.default_sessions.is_empty() {
+            return Err(CtpError::ConfigError("默认交易时段不能为空".to_string()));
+        }
+        for (name, sessions) in std::iter::once(("默认", &self.default_sessions))
+            .chain(self.products.iter().map(|(k, v)| (k.as_str(), v)))
+        {
+            for range in sessions {
+                match range.parse() {
+                    Some((start, end)) if start != end => {}
+                    _ => {
+                        return Err(CtpError::ConfigError(format!(
+                            "{} 的交易时段 {}-{} 无效",
+                            name, range.start, range.end
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 合约所属品种的交易时段
+    pub fn sessions_for(&self, instrument_id: &str) -> TradingSessions {
+        let product = product_of(instrument_id);
+        let ranges = self
+            .products
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(product))
+            .map(|(_, sessions)| sessions)
+            .unwrap_or(&self.default_sessions);
+        let mut sessions: Vec<(NaiveTime, NaiveTime)> = ranges.iter().filter_map(SessionRange::parse).collect();
+        sessions.sort();
+        TradingSessions {
+            sessions,
+            auction_lead: Duration::seconds(self.auction_lead_secs as i64),
+        }
+    }
+}
+
+/// 合约代码的字母前缀即品种代码
+pub fn product_of(instrument_id: &str) -> &str {
+    let end = instrument_id
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(instrument_id.len());
+    &instrument_id[..end]
+}
+
+/// K 线的起止时间，`end` 即收盘时刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarWindow {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub trading_day: NaiveDate,
+}
+
+/// 单个品种的交易时段，按开始时间排序
+#[derive(Debug, Clone)]
+pub struct TradingSessions {
+    sessions: Vec<(NaiveTime, NaiveTime)>,
+    auction_lead: Duration,
+}
+
+impl TradingSessions {
+    /// 包含该时刻的时段起止，收盘时刻本身仍属于该时段；集合竞价 tick 视为开盘时刻
+    pub fn locate(&self, at: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        for &(start, end) in &self.sessions {
+            // 跨午夜时段的后半段属于前一天开始的时段
+            let mut candidates = vec![at.date()];
+            if end < start {
+                candidates.push(at.date().pred_opt()?);
+            }
+            for date in candidates {
+                let open = date.and_time(start);
+                let close = if end < start { (date + Duration::days(1)).and_time(end) } else { date.and_time(end) };
+                if at >= open - self.auction_lead && at <= close {
+                    return Some((open, close));
+                }
+            }
+        }
+        None
+    }
+
+    /// 该时刻所属交易日，不在交易时段内时为空
+    pub fn trading_day(&self, at: NaiveDateTime) -> Option<NaiveDate> {
+        self.locate(at).map(|(open, _)| trading_day_of(open))
+    }
+
+    /// 交易日最后一个日盘时段的收盘时刻
+    pub fn day_close(&self, trading_day: NaiveDate) -> Option<NaiveDateTime> {
+        self.sessions
+            .iter()
+            .filter(|(start, end)| start < end && start.hour() < 18)
+            .map(|&(_, end)| trading_day.and_time(end))
+            .max()
+    }
+
+    /// 该时刻所属的 K 线
+    ///
+    /// 日内周期按自然时钟整点对齐，遇到时段收盘则截断，K 线不跨越休市；
+    /// 日线覆盖整个交易日，在最后一个日盘时段收盘时结束
+    pub fn bar_window(&self, at: NaiveDateTime, granularity: StorageGranularity) -> Option<BarWindow> {
+        let (open, close) = self.locate(at)?;
+        let trading_day = trading_day_of(open);
+        let at = at.max(open);
+        let period = match granularity {
+            StorageGranularity::Tick => return None,
+            StorageGranularity::Bar1d => {
+                return Some(BarWindow {
+                    start: open,
+                    end: self.day_close(trading_day).unwrap_or(close),
+                    trading_day,
+                })
+            }
+            StorageGranularity::Bar1s => 1,
+            StorageGranularity::Bar1m => 60,
+            StorageGranularity::Bar5m => 300,
+            StorageGranularity::Bar15m => 900,
+            StorageGranularity::Bar30m => 1800,
+            StorageGranularity::Bar1h => 3600,
+        };
+        // 收盘时刻的 tick 归入最后一根
+        let probe = if at == close { at - Duration::seconds(1) } else { at };
+        let midnight = open.date().and_time(NaiveTime::MIN);
+        let offset = (probe - midnight).num_seconds();
+        let aligned = midnight + Duration::seconds(offset - offset.rem_euclid(period));
+        Some(BarWindow {
+            start: aligned.max(open),
+            end: (aligned + Duration::seconds(period)).min(close),
+            trading_day,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+        // 2024-01-05 为周五
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    #[test]
+    fn test_bar_windows_align_to_sessions() {
+        let config = SessionConfig::default();
+        let rb = config.sessions_for("rb2505");
+
+        let w = rb.bar_window(at(5, 10, 7, 3), StorageGranularity::Bar5m).unwrap();
+        assert_eq!((w.start, w.end), (at(5, 10, 5, 0), at(5, 10, 10, 0)));
+        // 小节休息前的整点小时线在 10:15 截断
+        let w = rb.bar_window(at(5, 10, 14, 59), StorageGranularity::Bar1h).unwrap();
+        assert_eq!((w.start, w.end), (at(5, 10, 0, 0), at(5, 10, 15, 0)));
+        let w = rb.bar_window(at(5, 10, 31, 0), StorageGranularity::Bar1h).unwrap();
+        assert_eq!((w.start, w.end), (at(5, 10, 30, 0), at(5, 11, 0, 0)));
+        // 收盘 tick 与集合竞价 tick
+        let w = rb.bar_window(at(5, 15, 0, 0), StorageGranularity::Bar1m).unwrap();
+        assert_eq!((w.start, w.end), (at(5, 14, 59, 0), at(5, 15, 0, 0)));
+        let w = rb.bar_window(at(5, 20, 59, 0), StorageGranularity::Bar1m).unwrap();
+        assert_eq!((w.start, w.end), (at(5, 21, 0, 0), at(5, 21, 1, 0)));
+        assert!(rb.bar_window(at(5, 12, 0, 0), StorageGranularity::Bar1m).is_none());
+
+        // 周五夜盘属于下周一交易日，日线在周一 15:00 收盘
+        let w = rb.bar_window(at(5, 21, 30, 0), StorageGranularity::Bar1d).unwrap();
+        assert_eq!(w.trading_day, NaiveDate::from_ymd_opt(2024, 1, 8).unwrap());
+        assert_eq!(w.end, at(8, 15, 0, 0));
+    }
+
+    #[test]
+    fn test_night_session_across_midnight() {
+        let config = SessionConfig::default();
+        let au = config.sessions_for("au2506");
+        let w = au.bar_window(at(6, 0, 45, 10), StorageGranularity::Bar1h).unwrap();
+        assert_eq!((w.start, w.end), (at(6, 0, 0, 0), at(6, 1, 0, 0)));
+        assert_eq!(w.trading_day, NaiveDate::from_ymd_opt(2024, 1, 8).unwrap());
+        let w = au.bar_window(at(6, 2, 29, 0), StorageGranularity::Bar1h).unwrap();
+        assert_eq!(w.end, at(6, 2, 30, 0));
+
+        assert_eq!(product_of("SR505"), "SR");
+        assert_eq!(config.sessions_for("if2503").day_close(NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()), Some(at(8, 15, 0, 0)));
+        assert_eq!(config.sessions_for("T2503").day_close(NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()), Some(at(8, 15, 15, 0)));
+        assert!(SessionConfig { default_sessions: vec![SessionRange::new("9:00", "x")], ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::ctp::{data_quality::DataQualityIssue, CtpError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 默认交易开关状态文件
+pub const DEFAULT_SWITCHBOARD_FILE: &str = "./data/trading_switchboard.json";
+
+/// 停止交易的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchScope {
+    /// 单个合约
+    Instrument,
+    /// 整个品种（如 rb 下全部合约）
+    Product,
+}
+
+/// 停止交易的发起方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchSource {
+    /// 人工操作
+    Manual,
+    /// 风控触发
+    Risk,
+    /// 行情数据质量异常，恢复后自动解除
+    DataQuality,
+}
+
+/// 停止交易记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisabledTarget {
+    /// 合约代码或品种代码
+    pub target: String,
+    pub scope: SwitchScope,
+    pub source: SwitchSource,
+    pub reason: String,
+    pub disabled_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SwitchboardState {
+    #[serde(default)]
+    instruments: BTreeMap<String, DisabledTarget>,
+    /// 键为小写品种代码
+    #[serde(default)]
+    products: BTreeMap<String, DisabledTarget>,
+}
+
+/// 按合约、品种的交易开关
+///
+/// 被停止交易的合约拒绝一切报单；状态写入文件，重启后保持
+#[derive(Clone)]
+pub struct TradingSwitchboard {
+    inner: Arc<Mutex<SwitchboardState>>,
+    path: Option<PathBuf>,
+}
+
+impl Default for TradingSwitchboard {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl TradingSwitchboard {
+    /// 仅内存的开关
+    pub fn in_memory() -> Self {
+        Self {
+            inner: Arc::default(),
+            path: None,
+        }
+    }
+
+    /// 打开开关状态文件
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            serde_json::from_slice(&std::fs::read(&path)?)
+                .map_err(|e| CtpError::ConversionError(format!("解析交易开关状态失败: {}", e)))?
+        } else {
+            SwitchboardState::default()
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(state)),
+            path: Some(path),
+        })
+    }
+
+    /// 停止合约交易，重复停止时更新原因
+    pub fn disable_instrument(&self, instrument_id: &str, source: SwitchSource, reason: &str) -> Result<DisabledTarget, CtpError> {
+        self.disable(SwitchScope::Instrument, instrument_id, source, reason)
+    }
+
+    /// 停止品种交易，作用于该品种全部合约
+    pub fn disable_product(&self, product_id: &str, source: SwitchSource, reason: &str) -> Result<DisabledTarget, CtpError> {
+        self.disable(SwitchScope::Product, product_id, source, reason)
+    }
+
+    /// 恢复合约交易，返回被移除的记录
+    pub fn enable_instrument(&self, instrument_id: &str) -> Option<DisabledTarget> {
+        let mut state = self.inner.lock().unwrap();
+        let removed = state.instruments.remove(instrument_id);
+        if removed.is_some() {
+            tracing::info!("合约 {} 恢复交易", instrument_id);
+            self.persist(&state);
+        }
+        removed
+    }
+
+    /// 恢复品种交易
+    pub fn enable_product(&self, product_id: &str) -> Option<DisabledTarget> {
+        let mut state = self.inner.lock().unwrap();
+        let removed = state.products.remove(&product_id.to_ascii_lowercase());
+        if removed.is_some() {
+            tracing::info!("品种 {} 恢复交易", product_id);
+            self.persist(&state);
+        }
+        removed
+    }
+
+    /// 合约被停止交易时返回对应记录，合约级优先于品种级
+    pub fn disabled_reason(&self, instrument_id: &str) -> Option<DisabledTarget> {
+        let state = self.inner.lock().unwrap();
+        state
+            .instruments
+            .get(instrument_id)
+            .or_else(|| state.products.get(&product_of(instrument_id)))
+            .cloned()
+    }
+
+    /// 报单前检查，被停止交易的合约返回风控错误
+    pub fn check(&self, instrument_id: &str) -> Result<(), CtpError> {
+        match self.disabled_reason(instrument_id) {
+            None => Ok(()),
+            Some(entry) => {
+                let scope = match entry.scope {
+                    SwitchScope::Instrument => "合约",
+                    SwitchScope::Product => "品种",
+                };
+                Err(CtpError::RiskControl(format!(
+                    "{} {} 已停止交易 ({}): {}",
+                    scope, entry.target, entry.disabled_at.with_timezone(&chrono::Local).format("%H:%M:%S"), entry.reason
+                )))
+            }
+        }
+    }
+
+    /// 按当前行情异常的合约同步自动停止：新出现异常的合约停止交易，已恢复的解除
+    ///
+    /// 只解除 `DataQuality` 来源的记录，人工或其他风控停止的合约不受影响。返回是否有变化
+    pub fn sync_data_quality(&self, degraded: &[(String, DataQualityIssue)]) -> bool {
+        let mut state = self.inner.lock().unwrap();
+        let mut changed = false;
+        for (instrument_id, issue) in degraded {
+            if state.instruments.contains_key(instrument_id) {
+                continue;
+            }
+            let entry = DisabledTarget {
+                target: instrument_id.clone(),
+                scope: SwitchScope::Instrument,
+                source: SwitchSource::DataQuality,
+                reason: format!("行情数据异常: {:?}", issue),
+                disabled_at: chrono::Utc::now(),
+            };
+            tracing::warn!("合约 {} 行情异常（{:?}），自动停止交易", instrument_id, issue);
+            state.instruments.insert(instrument_id.clone(), entry);
+            changed = true;
+        }
+        let recovered: Vec<String> = state
+            .instruments
+            .values()
+            .filter(|e| e.source == SwitchSource::DataQuality && !degraded.iter().any(|(id, _)| *id == e.target))
+            .map(|e| e.target.clone())
+            .collect();
+        for instrument_id in recovered {
+            tracing::info!("合约 {} 行情恢复，解除自动停止交易", instrument_id);
+            state.instruments.remove(&instrument_id);
+            changed = true;
+        }
+        if changed {
+            self.persist(&state);
+        }
+        changed
+    }
+
+    /// 全部停止交易记录，品种在前
+    pub fn list(&self) -> Vec<DisabledTarget> {
+        let state = self.inner.lock().unwrap();
+        state.products.values().chain(state.instruments.values()).cloned().collect()
+    }
+
+    fn disable(&self, scope: SwitchScope, target: &str, source: SwitchSource, reason: &str) -> Result<DisabledTarget, CtpError> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(CtpError::ValidationError("合约或品种代码不能为空".to_string()));
+        }
+        let entry = DisabledTarget {
+            target: target.to_string(),
+            scope,
+            source,
+            reason: reason.to_string(),
+            disabled_at: chrono::Utc::now(),
+        };
+        let mut state = self.inner.lock().unwrap();
+        match scope {
+            SwitchScope::Instrument => state.instruments.insert(target.to_string(), entry.clone()),
+            SwitchScope::Product => state.products.insert(target.to_ascii_lowercase(), entry.clone()),
+        };
+        tracing::warn!("{:?} {} 停止交易 ({:?}): {}", scope, target, source, reason);
+        self.persist(&state);
+        Ok(entry)
+    }
+
+    fn persist(&self, state: &SwitchboardState) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(state)
+            .map_err(|e| CtpError::ConversionError(format!("序列化交易开关状态失败: {}", e)))
+            .and_then(|content| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let tmp = path.with_extension("json.tmp");
+                std::fs::write(&tmp, content)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::warn!("保存交易开关状态失败: {}", e);
+            crate::health::record_storage_error("trading_switchboard", &e);
+        }
+    }
+}
+
+/// 合约代码开头的字母部分即品种代码（小写）
+fn product_of(instrument_id: &str) -> String {
+    instrument_id
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_instrument_and_product_switches() {
+        let board = TradingSwitchboard::in_memory();
+        assert!(board.check("rb2501").is_ok());
+
+        board.disable_product("RB", SwitchSource::Risk, "行情数据异常").unwrap();
+        let err = board.check("rb2505").unwrap_err();
+        assert!(matches!(err, CtpError::RiskControl(ref msg) if msg.contains("行情数据异常")));
+        assert!(board.check("i2505").is_ok());
+
+        board.disable_instrument("i2505", SwitchSource::Manual, "交割月").unwrap();
+        assert!(board.check("i2505").is_err());
+        assert!(board.check("i2509").is_ok());
+        assert!(board.disable_instrument(" ", SwitchSource::Manual, "").is_err());
+
+        assert_eq!(board.list().len(), 2);
+        assert_eq!(board.enable_product("rb").unwrap().scope, SwitchScope::Product);
+        assert!(board.check("rb2505").is_ok());
+        assert!(board.enable_product("rb").is_none());
+    }
+
+    #[test]
+    fn test_state_persists_across_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("switchboard.json");
+        let board = TradingSwitchboard::open(&path).unwrap();
+        board.disable_instrument("IF2503", SwitchSource::Manual, "暂停交易").unwrap();
+        board.disable_product("sc", SwitchSource::Risk, "行情中断").unwrap();
+
+        let reopened = TradingSwitchboard::open(&path).unwrap();
+        assert_eq!(reopened.list(), board.list());
+        assert!(reopened.check("sc2506").is_err());
+        reopened.enable_instrument("IF2503");
+        assert!(TradingSwitchboard::open(&path).unwrap().check("IF2503").is_ok());
+    }
+
+    #[test]
+    fn test_data_quality_switches_follow_degraded_instruments() {
+        let board = TradingSwitchboard::in_memory();
+        board.disable_instrument("i2505", SwitchSource::Manual, "交割月").unwrap();
+
+        let degraded = vec![
+            ("rb2505".to_string(), DataQualityIssue::Stale),
+            ("i2505".to_string(), DataQualityIssue::PriceJump),
+        ];
+        assert!(board.sync_data_quality(&degraded));
+        assert!(!board.sync_data_quality(&degraded));
+        let err = board.check("rb2505").unwrap_err();
+        assert!(matches!(err, CtpError::RiskControl(ref msg) if msg.contains("Stale")));
+        // 人工停止的记录不被覆盖
+        assert_eq!(board.disabled_reason("i2505").unwrap().source, SwitchSource::Manual);
+
+        // 恢复后只解除自动停止的合约
+        assert!(board.sync_data_quality(&[]));
+        assert!(board.check("rb2505").is_ok());
+        assert!(board.check("i2505").is_err());
+    }
+}
@@ -0,0 +1,728 @@
+use crate::ctp::{
+    risk_report::average_true_range,
+    strategy_engine::{BarSubscription, Strategy, StrategyBar, StrategyEngine},
+    strategy_warmup::HistorySource,
+    tick_compaction::{ArchivedBar, StorageGranularity},
+    CtpError, OffsetFlag, OrderContingentCondition, OrderDirection, OrderForceCloseReason, OrderInput, OrderPriceType,
+    OrderRequest, OrderTimeCondition, OrderType, OrderVolumeCondition, PositionDirection,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 跟踪止损持久化文件
+pub const DEFAULT_TRAILING_STOP_FILE: &str = "./data/trailing_stops.json";
+
+/// 跟踪止损平仓单的标签键，值为止损 ID
+pub const TRAILING_STOP_TAG: &str = "trailing_stop";
+
+/// 向策略引擎登记日线订阅时使用的名称前缀
+pub const TRAILING_STOP_ATR_FEED: &str = "trailing_stop_atr";
+
+/// 合约日线在策略引擎中的登记名
+pub fn atr_feed_id(instrument_id: &str) -> String {
+    format!("{}:{}", TRAILING_STOP_ATR_FEED, instrument_id)
+}
+
+/// 每个合约保留的日线数，ATR 周期不超过该值减一
+const MAX_DAILY_BARS: usize = 64;
+
+/// 触发价重算的最小间隔
+const DEFAULT_RECALC_INTERVAL: Duration = Duration::from_millis(200);
+/// 触发价变化后持久化的最小间隔
+const DEFAULT_PERSIST_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 跟踪方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrailMode {
+    /// 距最有利价固定跳数
+    Ticks { ticks: u32 },
+    /// 距最有利价固定百分比
+    Percent { percent: f64 },
+    /// 距最有利价若干倍 ATR（策略引擎收盘的日线）
+    Atr { multiplier: f64, period: usize },
+}
+
+/// 跟踪止损定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TrailingStopSpec {
+    pub instrument_id: String,
+    /// 被保护持仓的方向
+    pub position_direction: PositionDirection,
+    pub volume: u32,
+    pub mode: TrailMode,
+    /// 最小变动价位，用于跳数计算和触发价取整
+    pub price_tick: f64,
+    /// 触发后平仓委托价相对触发价的让价跳数
+    #[serde(default)]
+    pub slippage_ticks: u32,
+    #[serde(default = "default_offset_flag")]
+    pub offset_flag: OffsetFlag,
+}
+
+fn default_offset_flag() -> OffsetFlag {
+    OffsetFlag::Close
+}
+
+impl TrailingStopSpec {
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.instrument_id.is_empty() {
+            return Err(CtpError::ValidationError("合约代码不能为空".to_string()));
+        }
+        if self.volume == 0 {
+            return Err(CtpError::ValidationError("跟踪止损数量必须大于0".to_string()));
+        }
+        if self.price_tick <= 0.0 {
+            return Err(CtpError::ValidationError("最小变动价位必须大于0".to_string()));
+        }
+        if self.offset_flag == OffsetFlag::Open {
+            return Err(CtpError::ValidationError("跟踪止损只能平仓".to_string()));
+        }
+        let valid = match &self.mode {
+            TrailMode::Ticks { ticks } => *ticks > 0,
+            TrailMode::Percent { percent } => *percent > 0.0 && *percent < 100.0,
+            TrailMode::Atr { multiplier, period } => *multiplier > 0.0 && *period > 0 && *period < MAX_DAILY_BARS,
+        };
+        if !valid {
+            return Err(CtpError::ValidationError(format!("跟踪参数无效: {:?}", self.mode)));
+        }
+        Ok(())
+    }
+}
+
+/// 跟踪止损状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingStopStatus {
+    Active,
+    Triggered,
+    Cancelled,
+}
+
+/// 跟踪止损及其当前触发价
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TrailingStop {
+    pub id: String,
+    pub spec: TrailingStopSpec,
+    pub status: TrailingStopStatus,
+    /// 创建以来的最有利价（多头最高价、空头最低价）
+    pub extreme_price: Option<f64>,
+    /// 当前触发价，ATR 模式下尚无 ATR 时为空
+    pub trigger_price: Option<f64>,
+    pub last_price: Option<f64>,
+    /// ATR 模式使用的 ATR
+    pub atr: Option<f64>,
+    /// 触发后报出的平仓单引用
+    pub order_ref: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub triggered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TrailingStop {
+    fn is_long(&self) -> bool {
+        self.spec.position_direction == PositionDirection::Long
+    }
+
+    /// 按最有利价计算触发价，只向有利方向移动
+    fn recalc(&mut self) -> bool {
+        let Some(extreme) = self.extreme_price else {
+            return false;
+        };
+        let distance = match &self.spec.mode {
+            TrailMode::Ticks { ticks } => *ticks as f64 * self.spec.price_tick,
+            TrailMode::Percent { percent } => extreme * percent / 100.0,
+            TrailMode::Atr { multiplier, .. } => match self.atr {
+                Some(atr) => atr * multiplier,
+                None => return false,
+            },
+        };
+        let tick = self.spec.price_tick;
+        let candidate = if self.is_long() {
+            ((extreme - distance) / tick + 1e-9).floor() * tick
+        } else {
+            ((extreme + distance) / tick - 1e-9).ceil() * tick
+        };
+        let tightened = match self.trigger_price {
+            None => true,
+            Some(current) if self.is_long() => candidate > current + 1e-9,
+            Some(current) => candidate < current - 1e-9,
+        };
+        if tightened {
+            self.trigger_price = Some(candidate);
+        }
+        tightened
+    }
+
+    fn exit_order(&self) -> OrderRequest {
+        let long = self.is_long();
+        let trigger = self.trigger_price.unwrap_or_default();
+        let slippage = self.spec.slippage_ticks as f64 * self.spec.price_tick;
+        let mut tags = crate::ctp::OrderTags::new();
+        tags.insert(TRAILING_STOP_TAG.to_string(), self.id.clone());
+        OrderRequest {
+            instrument_id: self.spec.instrument_id.clone(),
+            order_ref: String::new(),
+            direction: if long { OrderDirection::Sell } else { OrderDirection::Buy },
+            offset_flag: self.spec.offset_flag,
+            price: if long { trigger - slippage } else { trigger + slippage },
+            volume: self.spec.volume,
+            order_type: OrderType::Limit,
+            price_type: OrderPriceType::Limit,
+            time_condition: OrderTimeCondition::GFD,
+            volume_condition: OrderVolumeCondition::Any,
+            min_volume: 1,
+            contingent_condition: OrderContingentCondition::Immediately,
+            stop_price: 0.0,
+            force_close_reason: OrderForceCloseReason::NotForceClose,
+            is_auto_suspend: false,
+            time_in_force: None,
+            tags,
+        }
+    }
+}
+
+/// 触发的跟踪止损及其平仓单
+#[derive(Debug, Clone)]
+pub struct TrailingStopTrigger {
+    pub stop: TrailingStop,
+    pub order: OrderRequest,
+}
+
+impl TrailingStopTrigger {
+    /// 平仓单的前端报单格式，经客户端下单流程报出
+    pub fn order_input(&self) -> OrderInput {
+        let order = &self.order;
+        OrderInput {
+            instrument_id: order.instrument_id.clone(),
+            direction: format!("{:?}", order.direction),
+            offset: format!("{:?}", order.offset_flag),
+            price: order.price,
+            volume: order.volume,
+            order_type: "Limit".to_string(),
+            time_condition: "GFD".to_string(),
+            volume_condition: "Any".to_string(),
+            min_volume: 1,
+            contingent_condition: "Immediately".to_string(),
+            stop_price: 0.0,
+            force_close_reason: "NotForceClose".to_string(),
+            is_auto_suspend: false,
+            tags: order.tags.clone(),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct StoredStops {
+    next_id: u64,
+    stops: Vec<TrailingStop>,
+}
+
+struct ManagerInner {
+    next_id: u64,
+    stops: HashMap<String, TrailingStop>,
+    /// 各止损上次重算触发价的时间
+    last_recalc: HashMap<String, std::time::Instant>,
+    /// 最有利价已更新但因节流尚未重算的止损
+    stale: HashSet<String>,
+    /// 各合约最近的日线，按交易日排序
+    daily_bars: HashMap<String, BTreeMap<NaiveDate, ArchivedBar>>,
+    path: Option<PathBuf>,
+    dirty: bool,
+    last_persist: Option<std::time::Instant>,
+    recalc_interval: Duration,
+    persist_interval: Duration,
+}
+
+/// 跟踪止损条件单
+///
+/// 每个行情更新最有利价并检查是否触及触发价；触发价按节流间隔重算且只向有利方向
+/// 移动，节流期间跳过的重算在下一个行情补上。ATR 模式的日线来自策略引擎，
+/// 见 `sync_atr_feed`。活动中的跟踪止损写入本地 JSON，重启后恢复
+#[derive(Clone)]
+pub struct TrailingStopManager {
+    inner: Arc<Mutex<ManagerInner>>,
+}
+
+impl TrailingStopManager {
+    /// 仅内存的跟踪止损
+    pub fn in_memory() -> Self {
+        Self::build(None, StoredStops::default())
+    }
+
+    /// 打开持久化文件，恢复其中的活动止损
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref().to_path_buf();
+        let stored = if path.exists() {
+            let content = std::fs::read(&path)?;
+            serde_json::from_slice::<StoredStops>(&content)
+                .map_err(|e| CtpError::ConversionError(format!("解析跟踪止损文件失败: {}", e)))?
+        } else {
+            StoredStops::default()
+        };
+        tracing::info!("恢复跟踪止损 {} 个", stored.stops.len());
+        Ok(Self::build(Some(path), stored))
+    }
+
+    fn build(path: Option<PathBuf>, stored: StoredStops) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ManagerInner {
+                next_id: stored.next_id,
+                stops: stored.stops.into_iter().map(|s| (s.id.clone(), s)).collect(),
+                last_recalc: HashMap::new(),
+                stale: HashSet::new(),
+                daily_bars: HashMap::new(),
+                path,
+                dirty: false,
+                last_persist: None,
+                recalc_interval: DEFAULT_RECALC_INTERVAL,
+                persist_interval: DEFAULT_PERSIST_INTERVAL,
+            })),
+        }
+    }
+
+    /// 设置触发价重算间隔
+    pub fn with_recalc_interval(self, interval: Duration) -> Self {
+        self.inner.lock().unwrap().recalc_interval = interval;
+        self
+    }
+
+    /// 新增跟踪止损，`last_price` 为当前价，作为初始最有利价
+    pub fn add(&self, spec: TrailingStopSpec, last_price: Option<f64>) -> Result<TrailingStop, CtpError> {
+        spec.validate()?;
+        let now = chrono::Utc::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let mut stop = TrailingStop {
+            id: format!("TS{}", inner.next_id),
+            spec,
+            status: TrailingStopStatus::Active,
+            extreme_price: last_price,
+            trigger_price: None,
+            last_price,
+            atr: None,
+            order_ref: None,
+            created_at: now,
+            updated_at: now,
+            triggered_at: None,
+        };
+        if let TrailMode::Atr { period, .. } = stop.spec.mode {
+            stop.atr = inner.atr_of(&stop.spec.instrument_id, period);
+        }
+        stop.recalc();
+        inner.stops.insert(stop.id.clone(), stop.clone());
+        inner.persist_now();
+        tracing::info!("新增跟踪止损 {} {} {:?}", stop.id, stop.spec.instrument_id, stop.spec.mode);
+        Ok(stop)
+    }
+
+    /// 取消跟踪止损
+    pub fn cancel(&self, id: &str) -> Result<TrailingStop, CtpError> {
+        let mut inner = self.inner.lock().unwrap();
+        let stop = inner
+            .stops
+            .get_mut(id)
+            .ok_or_else(|| CtpError::NotFound(format!("跟踪止损不存在: {}", id)))?;
+        if stop.status != TrailingStopStatus::Active {
+            return Err(CtpError::StateError(format!("跟踪止损 {} 已触发或取消", id)));
+        }
+        stop.status = TrailingStopStatus::Cancelled;
+        stop.updated_at = chrono::Utc::now();
+        let stop = stop.clone();
+        inner.persist_now();
+        Ok(stop)
+    }
+
+    /// 日线收盘，更新该合约各 ATR 模式止损的 ATR；非日线忽略
+    pub fn on_bar(&self, bar: &StrategyBar) {
+        if bar.granularity != StorageGranularity::Bar1d {
+            return;
+        }
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let history = inner.daily_bars.entry(bar.instrument_id.clone()).or_default();
+        history.insert(
+            bar.trading_day,
+            ArchivedBar {
+                instrument_id: bar.instrument_id.clone(),
+                time: "00:00:00".to_string(),
+                open: bar.open,
+                high: bar.high,
+                low: bar.low,
+                close: bar.close,
+                volume: bar.volume,
+                turnover: bar.turnover,
+                open_interest: bar.open_interest,
+                tick_count: bar.tick_count,
+            },
+        );
+        while history.len() > MAX_DAILY_BARS {
+            history.pop_first();
+        }
+        let bars: Vec<ArchivedBar> = history.values().cloned().collect();
+
+        let mut changed = false;
+        for stop in inner
+            .stops
+            .values_mut()
+            .filter(|s| s.status == TrailingStopStatus::Active && s.spec.instrument_id == bar.instrument_id)
+        {
+            let TrailMode::Atr { period, .. } = stop.spec.mode else {
+                continue;
+            };
+            let Some(atr) = average_true_range(&bars, period) else {
+                continue;
+            };
+            stop.atr = Some(atr);
+            changed |= stop.recalc();
+        }
+        inner.dirty |= changed;
+    }
+
+    /// 活动中的 ATR 模式止损涉及的合约
+    fn atr_instruments(&self) -> BTreeSet<String> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .stops
+            .values()
+            .filter(|s| s.status == TrailingStopStatus::Active && matches!(s.spec.mode, TrailMode::Atr { .. }))
+            .map(|s| s.spec.instrument_id.clone())
+            .collect()
+    }
+
+    /// 按活动中的 ATR 模式止损向策略引擎登记或注销各合约的日线订阅
+    ///
+    /// 每个合约单独登记为 `atr_feed_id(合约)`，已登记的合约不受影响。给出 `history` 时
+    /// 新登记的合约先用本地历史日线预热。新增或取消 ATR 模式止损后调用
+    pub fn sync_atr_feed(&self, engine: &mut StrategyEngine, history: Option<&dyn HistorySource>) -> Result<(), CtpError> {
+        let instruments = self.atr_instruments();
+        let prefix = format!("{}:", TRAILING_STOP_ATR_FEED);
+        for id in engine.strategy_ids() {
+            if id.strip_prefix(&prefix).is_some_and(|instrument| !instruments.contains(instrument)) {
+                engine.unregister(&id);
+            }
+        }
+        for instrument_id in &instruments {
+            let feed_id = atr_feed_id(instrument_id);
+            if engine.subscriptions(&feed_id).is_some() {
+                continue;
+            }
+            let subscriptions = vec![BarSubscription::new(instrument_id, StorageGranularity::Bar1d)];
+            let feed = Box::new(AtrFeed(self.clone()));
+            let Some(source) = history else {
+                engine.register(&feed_id, feed, subscriptions)?;
+                continue;
+            };
+            let now = chrono::Local::now().naive_local();
+            if let Err(e) = engine.register_with_warmup(&feed_id, feed, subscriptions.clone(), source, now) {
+                tracing::warn!("{} 的历史日线读取失败，ATR 待日线收盘后计算: {}", instrument_id, e);
+                engine.register(&feed_id, Box::new(AtrFeed(self.clone())), subscriptions)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 行情更新，返回本次触发的止损及平仓单
+    pub fn on_tick(&self, instrument_id: &str, price: f64) -> Vec<TrailingStopTrigger> {
+        if price <= 0.0 || !price.is_finite() {
+            return Vec::new();
+        }
+        let now = std::time::Instant::now();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let mut triggers = Vec::new();
+        for stop in inner
+            .stops
+            .values_mut()
+            .filter(|s| s.status == TrailingStopStatus::Active && s.spec.instrument_id == instrument_id)
+        {
+            stop.last_price = Some(price);
+            let long = stop.is_long();
+            let improved = match stop.extreme_price {
+                None => true,
+                Some(extreme) => (long && price > extreme) || (!long && price < extreme),
+            };
+            if improved {
+                stop.extreme_price = Some(price);
+            }
+            let due = inner
+                .last_recalc
+                .get(&stop.id)
+                .is_none_or(|last| now.duration_since(*last) >= inner.recalc_interval);
+            let pending = improved || stop.trigger_price.is_none() || inner.stale.contains(&stop.id);
+            // 节流只合并连续创新高（低）的行情；回撤的行情先补上跳过的重算再判断触发
+            if pending && (due || !improved) {
+                inner.last_recalc.insert(stop.id.clone(), now);
+                inner.stale.remove(&stop.id);
+                inner.dirty |= stop.recalc();
+            } else if pending {
+                inner.stale.insert(stop.id.clone());
+            }
+
+            let hit = stop
+                .trigger_price
+                .is_some_and(|trigger| if long { price <= trigger } else { price >= trigger });
+            if hit {
+                stop.status = TrailingStopStatus::Triggered;
+                stop.triggered_at = Some(chrono::Utc::now());
+                stop.updated_at = chrono::Utc::now();
+                tracing::info!(
+                    "跟踪止损 {} 触发: {} 最新价 {} 触发价 {:?}",
+                    stop.id,
+                    stop.spec.instrument_id,
+                    price,
+                    stop.trigger_price
+                );
+                triggers.push(TrailingStopTrigger {
+                    stop: stop.clone(),
+                    order: stop.exit_order(),
+                });
+            }
+        }
+
+        let persist_due = inner.dirty && inner.last_persist.is_none_or(|last| now.duration_since(last) >= inner.persist_interval);
+        if !triggers.is_empty() || persist_due {
+            inner.persist_now();
+        }
+        triggers
+    }
+
+    /// 记录触发后报出的平仓单引用
+    pub fn attach_exit_order(&self, id: &str, order_ref: &str) -> Option<TrailingStop> {
+        let mut inner = self.inner.lock().unwrap();
+        let stop = inner.stops.get_mut(id)?;
+        stop.order_ref = Some(order_ref.to_string());
+        let stop = stop.clone();
+        inner.persist_now();
+        Some(stop)
+    }
+
+    pub fn get(&self, id: &str) -> Option<TrailingStop> {
+        self.inner.lock().unwrap().stops.get(id).cloned()
+    }
+
+    /// 所有跟踪止损及当前触发价，按创建时间排序
+    pub fn list(&self) -> Vec<TrailingStop> {
+        let mut stops: Vec<TrailingStop> = self.inner.lock().unwrap().stops.values().cloned().collect();
+        stops.sort_by_key(|s| s.created_at);
+        stops
+    }
+
+    /// 立即写入未保存的触发价
+    pub fn flush(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.dirty {
+            inner.persist_now();
+        }
+    }
+}
+
+impl ManagerInner {
+    fn atr_of(&self, instrument_id: &str, period: usize) -> Option<f64> {
+        let bars: Vec<ArchivedBar> = self.daily_bars.get(instrument_id)?.values().cloned().collect();
+        average_true_range(&bars, period)
+    }
+
+    /// 只保存活动中的止损，已触发或取消的保留在内存供查询
+    fn persist_now(&mut self) {
+        self.last_persist = Some(std::time::Instant::now());
+        self.dirty = false;
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut stops: Vec<TrailingStop> = self
+            .stops
+            .values()
+            .filter(|s| s.status == TrailingStopStatus::Active)
+            .cloned()
+            .collect();
+        stops.sort_by_key(|s| s.created_at);
+        let stored = StoredStops {
+            next_id: self.next_id,
+            stops,
+        };
+        if let Err(e) = write_json(path, &stored) {
+            tracing::warn!("保存跟踪止损失败: {}", e);
+            crate::health::record_storage_error("trailing_stops", &e);
+        }
+    }
+}
+
+/// 以策略身份登记到策略引擎，接收日线计算 ATR
+struct AtrFeed(TrailingStopManager);
+
+impl Strategy for AtrFeed {
+    fn on_bar(&mut self, bar: &StrategyBar) {
+        self.0.on_bar(bar);
+    }
+}
+
+fn write_json(path: &Path, stored: &StoredStops) -> Result<(), CtpError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_vec_pretty(stored)
+        .map_err(|e| CtpError::ConversionError(format!("序列化跟踪止损失败: {}", e)))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn spec(direction: PositionDirection, mode: TrailMode) -> TrailingStopSpec {
+        TrailingStopSpec {
+            instrument_id: "rb2501".to_string(),
+            position_direction: direction,
+            volume: 2,
+            mode,
+            price_tick: 1.0,
+            slippage_ticks: 2,
+            offset_flag: OffsetFlag::Close,
+        }
+    }
+
+    #[test]
+    fn test_tick_and_percent_trails_ratchet_and_trigger() {
+        let manager = TrailingStopManager::in_memory().with_recalc_interval(Duration::ZERO);
+        let long = manager.add(spec(PositionDirection::Long, TrailMode::Ticks { ticks: 10 }), Some(3500.0)).unwrap();
+        assert_eq!(long.trigger_price, Some(3490.0));
+        let short = manager
+            .add(spec(PositionDirection::Short, TrailMode::Percent { percent: 1.0 }), Some(3500.0))
+            .unwrap();
+        assert_eq!(short.trigger_price, Some(3535.0));
+
+        assert!(manager.on_tick("rb2501", 3520.0).is_empty());
+        // 回落不放宽触发价
+        assert!(manager.on_tick("rb2501", 3515.0).is_empty());
+        assert_eq!(manager.get(&long.id).unwrap().trigger_price, Some(3510.0));
+        assert_eq!(manager.get(&short.id).unwrap().trigger_price, Some(3535.0));
+        assert_eq!(manager.get(&short.id).unwrap().extreme_price, Some(3500.0));
+
+        let triggers = manager.on_tick("rb2501", 3509.0);
+        assert_eq!(triggers.len(), 1);
+        let long_exit = &triggers[0];
+        assert_eq!(long_exit.stop.id, long.id);
+        assert_eq!(long_exit.order.direction, OrderDirection::Sell);
+        assert_eq!((long_exit.order.price, long_exit.order.volume), (3508.0, 2));
+        assert_eq!(long_exit.order.tags.get(TRAILING_STOP_TAG), Some(&long.id));
+        assert_eq!(manager.get(&long.id).unwrap().status, TrailingStopStatus::Triggered);
+        // 空头止损：价格下行后触发价随之下移
+        assert!(manager.on_tick("rb2501", 3400.0).is_empty());
+        assert_eq!(manager.get(&short.id).unwrap().trigger_price, Some(3434.0));
+        let triggers = manager.on_tick("rb2501", 3434.0);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].order.direction, OrderDirection::Buy);
+        assert_eq!(triggers[0].order.price, 3436.0);
+        // 已触发的不再重复触发
+        assert!(manager.on_tick("rb2501", 3300.0).is_empty());
+    }
+
+    #[test]
+    fn test_atr_trail_and_persistence() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trailing_stops.json");
+        let manager = TrailingStopManager::open(&path).unwrap().with_recalc_interval(Duration::ZERO);
+        let stop = manager
+            .add(spec(PositionDirection::Long, TrailMode::Atr { multiplier: 2.0, period: 2 }), Some(3500.0))
+            .unwrap();
+        // 尚无 ATR 时不挂载触发价
+        assert_eq!(stop.trigger_price, None);
+        assert!(manager.on_tick("rb2501", 3000.0).is_empty());
+
+        let bar = |day: u32, high: f64, low: f64, close: f64| {
+            let trading_day = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+            StrategyBar {
+                instrument_id: "rb2501".to_string(),
+                granularity: StorageGranularity::Bar1d,
+                trading_day,
+                start: trading_day.and_hms_opt(9, 0, 0).unwrap(),
+                end: trading_day.and_hms_opt(15, 0, 0).unwrap(),
+                open: close,
+                high,
+                low,
+                close,
+                volume: 0,
+                turnover: 0.0,
+                open_interest: 0,
+                tick_count: 0,
+            }
+        };
+        manager.on_bar(&bar(2, 3510.0, 3490.0, 3500.0));
+        manager.on_bar(&bar(3, 3520.0, 3500.0, 3510.0));
+        assert_eq!(manager.get(&stop.id).unwrap().trigger_price, None);
+        // 非日线不计入
+        manager.on_bar(&StrategyBar { granularity: StorageGranularity::Bar1m, ..bar(4, 9999.0, 0.0, 3520.0) });
+        manager.on_bar(&bar(4, 3530.0, 3490.0, 3520.0));
+        // ATR = (20 + 40) / 2 = 30，最有利价 3500
+        assert_eq!(manager.get(&stop.id).unwrap().trigger_price, Some(3440.0));
+        manager.on_tick("rb2501", 3560.0);
+        manager.flush();
+
+        let restored = TrailingStopManager::open(&path).unwrap();
+        let stops = restored.list();
+        assert_eq!(stops.len(), 1);
+        assert_eq!(stops[0].trigger_price, Some(3500.0));
+        assert_eq!(stops[0].atr, Some(30.0));
+        restored.cancel(&stop.id).unwrap();
+        assert!(TrailingStopManager::open(&path).unwrap().list().is_empty());
+        // 重启后编号继续递增
+        let next = restored.add(spec(PositionDirection::Long, TrailMode::Ticks { ticks: 5 }), None).unwrap();
+        assert_ne!(next.id, stop.id);
+    }
+
+    #[test]
+    fn test_throttled_recalc_is_applied_on_pullback() {
+        let manager = TrailingStopManager::in_memory();
+        let stop = manager.add(spec(PositionDirection::Long, TrailMode::Ticks { ticks: 10 }), Some(3500.0)).unwrap();
+        assert!(manager.on_tick("rb2501", 3520.0).is_empty());
+        assert_eq!(manager.get(&stop.id).unwrap().trigger_price, Some(3510.0));
+        // 节流间隔内的新高只更新最有利价
+        assert!(manager.on_tick("rb2501", 3530.0).is_empty());
+        assert_eq!(manager.get(&stop.id).unwrap().trigger_price, Some(3510.0));
+        // 随后回落的行情补上重算
+        assert!(manager.on_tick("rb2501", 3525.0).is_empty());
+        assert_eq!(manager.get(&stop.id).unwrap().trigger_price, Some(3520.0));
+        assert_eq!(manager.on_tick("rb2501", 3519.0).len(), 1);
+    }
+
+    #[test]
+    fn test_atr_feed_registers_daily_subscriptions() {
+        let manager = TrailingStopManager::in_memory();
+        let mut engine = StrategyEngine::new(crate::ctp::StrategyEngineConfig::default());
+        manager.sync_atr_feed(&mut engine, None).unwrap();
+        assert!(engine.strategy_ids().is_empty());
+
+        let atr = TrailMode::Atr { multiplier: 2.0, period: 2 };
+        let stop = manager.add(spec(PositionDirection::Long, atr.clone()), Some(3500.0)).unwrap();
+        manager.add(spec(PositionDirection::Short, atr), Some(3500.0)).unwrap();
+        manager.add(spec(PositionDirection::Long, TrailMode::Ticks { ticks: 5 }), Some(3500.0)).unwrap();
+        manager.sync_atr_feed(&mut engine, None).unwrap();
+        assert_eq!(
+            engine.subscriptions(&atr_feed_id("rb2501")),
+            Some([BarSubscription::new("rb2501", StorageGranularity::Bar1d)].as_slice())
+        );
+        assert_eq!(engine.strategy_ids().len(), 1);
+
+        manager.cancel(&stop.id).unwrap();
+        manager.sync_atr_feed(&mut engine, None).unwrap();
+        assert_eq!(engine.strategy_ids().len(), 1);
+        for stop in manager.list().into_iter().filter(|s| s.status == TrailingStopStatus::Active) {
+            manager.cancel(&stop.id).unwrap();
+        }
+        manager.sync_atr_feed(&mut engine, None).unwrap();
+        assert!(engine.strategy_ids().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use crate::ctp::{ClientState, ConnectionMode, CtpEvent, Environment, OrderInput, OrderRequest};
+use std::path::Path;
+use ts_rs::{ExportError, TS};
+
+/// 前端生成类型目录
+pub const DEFAULT_TS_OUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../src/types/generated");
+
+/// 导出事件、订单模型及其依赖类型的 TypeScript 定义，每个类型一个文件
+///
+/// 字段名、枚举标签均按 serde 序列化结果生成，Rust 类型变更后重新生成即可与前端保持一致
+pub fn export_bindings(out_dir: impl AsRef<Path>) -> Result<(), ExportError> {
+    let out_dir = out_dir.as_ref();
+    CtpEvent::export_all_to(out_dir)?;
+    OrderRequest::export_all_to(out_dir)?;
+    OrderInput::export_all_to(out_dir)?;
+    ClientState::export_all_to(out_dir)?;
+    Environment::export_all_to(out_dir)?;
+    ConnectionMode::export_all_to(out_dir)?;
+    write_index(out_dir)
+}
+
+/// 重写 index.ts，汇总目录下全部生成类型
+pub fn write_index(out_dir: impl AsRef<Path>) -> Result<(), ExportError> {
+    let out_dir = out_dir.as_ref();
+    let mut modules: Vec<String> = std::fs::read_dir(out_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".ts").map(str::to_string))
+        .filter(|name| name != "index")
+        .collect();
+    modules.sort();
+
+    let mut content = String::from("// 由 Rust 类型生成，请勿手动修改：bun run gen:types\n");
+    for module in modules {
+        content.push_str(&format!("export type * from './{}';\n", module));
+    }
+    std::fs::write(out_dir.join("index.ts"), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_event_union_matches_serde() {
+        let dir = TempDir::new().unwrap();
+        export_bindings(dir.path()).unwrap();
+
+        let event = std::fs::read_to_string(dir.path().join("CtpEvent.ts")).unwrap();
+        assert!(event.contains(r#""type": "OrderUpdate", "data": OrderStatus"#));
+        assert!(event.contains(r#"{ "type": "Connected" }"#));
+        assert!(dir.path().join("MarketDataTick.ts").exists());
+
+        let index = std::fs::read_to_string(dir.path().join("index.ts")).unwrap();
+        assert!(index.contains("export type * from './CtpEvent';"));
+        assert!(!index.contains("'./index'"));
+    }
+
+    /// 前端提交的类型须与 Rust 类型一致，不一致时重新生成
+    #[test]
+    fn test_committed_bindings_are_current() {
+        let dir = TempDir::new().unwrap();
+        export_bindings(dir.path()).unwrap();
+
+        let mut stale = Vec::new();
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            // index.ts 还汇总了应用层的类型，只比较各类型文件
+            if name == "index.ts" {
+                continue;
+            }
+            let committed = std::fs::read_to_string(Path::new(DEFAULT_TS_OUT_DIR).join(&name)).ok();
+            if committed.as_deref() != Some(std::fs::read_to_string(&path).unwrap().as_str()) {
+                stale.push(name);
+            }
+        }
+        stale.sort();
+        assert!(stale.is_empty(), "前端类型与 Rust 类型不一致，请运行 bun run gen:types: {:?}", stale);
+    }
+
+    /// 重新生成前端类型：cargo test --features ts export_typescript_bindings
+    #[test]
+    fn export_typescript_bindings() {
+        export_bindings(DEFAULT_TS_OUT_DIR).unwrap();
+    }
+}
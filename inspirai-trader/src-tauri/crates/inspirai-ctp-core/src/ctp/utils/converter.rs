@@ -0,0 +1,683 @@
+use crate::ctp::{
+    models::*,
+    orderbook_heatmap::{DepthLevel, DepthSnapshot},
+    CtpError,
+};
+
+// 使用 ctp2rs 提供的官方数据结构和转换工具
+use ctp2rs::v1alpha1::{
+    CThostFtdcDepthMarketDataField,
+    CThostFtdcInputOrderField,
+    CThostFtdcInstrumentCommissionRateField,
+    CThostFtdcOrderField,
+    CThostFtdcTradeField,
+    CThostFtdcInvestorPositionField,
+    CThostFtdcTradingAccountField,
+};
+use ctp2rs::ffi::{gb18030_cstr_i8_to_str, WrapToString};
+
+use super::encoding::string_to_ctp_string;
+use super::pool::{ObjectPool, Pooled};
+
+/// 行情快照中参与 tick 转换的字段
+///
+/// 字符串字段保持 CTP 的 GB18030 字符数组，转换逻辑与 CTP 结构解耦，
+/// 基准测试不构造 CTP 结构也能测量转换开销
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DepthQuote<'a> {
+    pub instrument_id: &'a [i8],
+    pub update_time: &'a [i8],
+    pub update_millisec: i32,
+    pub last_price: f64,
+    pub pre_close_price: f64,
+    pub open_price: f64,
+    pub highest_price: f64,
+    pub lowest_price: f64,
+    pub volume: i32,
+    pub turnover: f64,
+    pub open_interest: f64,
+    pub bid_price1: f64,
+    pub bid_volume1: i32,
+    pub ask_price1: f64,
+    pub ask_volume1: i32,
+}
+
+impl<'a> From<&'a CThostFtdcDepthMarketDataField> for DepthQuote<'a> {
+    fn from(ctp_data: &'a CThostFtdcDepthMarketDataField) -> Self {
+        Self {
+            instrument_id: &ctp_data.InstrumentID,
+            update_time: &ctp_data.UpdateTime,
+            update_millisec: ctp_data.UpdateMillisec,
+            last_price: ctp_data.LastPrice,
+            pre_close_price: ctp_data.PreClosePrice,
+            open_price: ctp_data.OpenPrice,
+            highest_price: ctp_data.HighestPrice,
+            lowest_price: ctp_data.LowestPrice,
+            volume: ctp_data.Volume,
+            turnover: ctp_data.Turnover,
+            open_interest: ctp_data.OpenInterest,
+            bid_price1: ctp_data.BidPrice1,
+            bid_volume1: ctp_data.BidVolume1,
+            ask_price1: ctp_data.AskPrice1,
+            ask_volume1: ctp_data.AskVolume1,
+        }
+    }
+}
+
+/// 数据转换工具
+/// 
+/// 负责在 CTP 原生数据结构和业务模型之间进行转换
+/// 严格使用 ctp2rs 提供的官方工具，禁止自定义实现
+pub struct DataConverter;
+
+impl DataConverter {
+    /// 将 CTP 行情数据转换为业务模型
+    /// 使用 ctp2rs 官方数据结构和转换工具
+    pub fn convert_market_data(ctp_data: &CThostFtdcDepthMarketDataField) -> Result<MarketDataTick, CtpError> {
+        Self::convert_depth_quote(&DepthQuote::from(ctp_data))
+    }
+
+    /// 由行情快照字段生成 tick，计算涨跌幅和涨跌额
+    pub fn convert_depth_quote(quote: &DepthQuote<'_>) -> Result<MarketDataTick, CtpError> {
+        // 使用 ctp2rs 官方字符串转换工具
+        let instrument_id = gb18030_cstr_i8_to_str(quote.instrument_id)
+            .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string();
+        let update_time = gb18030_cstr_i8_to_str(quote.update_time)
+            .map_err(|e| CtpError::ConversionError(format!("更新时间转换失败: {}", e)))?.to_string();
+        
+        // 计算涨跌幅和涨跌额
+        let change_amount = if quote.pre_close_price > 0.0 {
+            quote.last_price - quote.pre_close_price
+        } else {
+            0.0
+        };
+        
+        let change_percent = if quote.pre_close_price > 0.0 {
+            (change_amount / quote.pre_close_price) * 100.0
+        } else {
+            0.0
+        };
+        
+        Ok(MarketDataTick {
+            instrument_id,
+            last_price: quote.last_price,
+            volume: quote.volume as i64,
+            turnover: quote.turnover,
+            open_interest: quote.open_interest as i64,
+            bid_price1: quote.bid_price1,
+            bid_volume1: quote.bid_volume1,
+            ask_price1: quote.ask_price1,
+            ask_volume1: quote.ask_volume1,
+            update_time,
+            update_millisec: quote.update_millisec,
+            change_percent,
+            change_amount,
+            open_price: quote.open_price,
+            highest_price: quote.highest_price,
+            lowest_price: quote.lowest_price,
+            pre_close_price: quote.pre_close_price,
+            price_limit: None,
+            trace: None,
+            source: None,
+        })
+    }
+
+    /// 提取五档盘口，无效档位（价格为 0 或 DBL_MAX、数量为 0）跳过
+    pub fn convert_depth_snapshot(ctp_data: &CThostFtdcDepthMarketDataField) -> Result<DepthSnapshot, CtpError> {
+        let instrument_id = gb18030_cstr_i8_to_str(&ctp_data.InstrumentID)
+            .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string();
+        let update_time = gb18030_cstr_i8_to_str(&ctp_data.UpdateTime)
+            .map_err(|e| CtpError::ConversionError(format!("更新时间转换失败: {}", e)))?.to_string();
+
+        let levels = |quotes: [(f64, i32); 5]| -> Vec<DepthLevel> {
+            quotes
+                .into_iter()
+                .take_while(|(price, volume)| *price > 0.0 && *price < f64::MAX && *volume > 0)
+                .map(|(price, volume)| DepthLevel { price, volume })
+                .collect()
+        };
+
+        Ok(DepthSnapshot {
+            instrument_id,
+            last_price: ctp_data.LastPrice,
+            update_time,
+            update_millisec: ctp_data.UpdateMillisec,
+            bids: levels([
+                (ctp_data.BidPrice1, ctp_data.BidVolume1),
+                (ctp_data.BidPrice2, ctp_data.BidVolume2),
+                (ctp_data.BidPrice3, ctp_data.BidVolume3),
+                (ctp_data.BidPrice4, ctp_data.BidVolume4),
+                (ctp_data.BidPrice5, ctp_data.BidVolume5),
+            ]),
+            asks: levels([
+                (ctp_data.AskPrice1, ctp_data.AskVolume1),
+                (ctp_data.AskPrice2, ctp_data.AskVolume2),
+                (ctp_data.AskPrice3, ctp_data.AskVolume3),
+                (ctp_data.AskPrice4, ctp_data.AskVolume4),
+                (ctp_data.AskPrice5, ctp_data.AskVolume5),
+            ]),
+        })
+    }
+
+    /// 将业务订单请求转换为 CTP 结构体
+    /// 使用 ctp2rs 官方数据结构和字符串赋值工具
+    pub fn convert_order_request(
+        order: &OrderRequest,
+        broker_id: &str,
+        investor_id: &str,
+        order_ref: &str,
+    ) -> Result<CThostFtdcInputOrderField, CtpError> {
+        let mut ctp_order = CThostFtdcInputOrderField::default();
+        Self::fill_order_request(&mut ctp_order, order, broker_id, investor_id, order_ref)?;
+        Ok(ctp_order)
+    }
+
+    /// 从对象池取报单结构体并填写，报单发出后随守卫释放归还
+    pub fn convert_order_request_pooled<'a>(
+        pool: &'a ObjectPool<CThostFtdcInputOrderField>,
+        order: &OrderRequest,
+        broker_id: &str,
+        investor_id: &str,
+        order_ref: &str,
+    ) -> Result<Pooled<'a, CThostFtdcInputOrderField>, CtpError> {
+        let mut ctp_order = pool.acquire();
+        Self::fill_order_request(&mut ctp_order, order, broker_id, investor_id, order_ref)?;
+        Ok(ctp_order)
+    }
+
+    /// 填写已清零的报单结构体
+    fn fill_order_request(
+        ctp_order: &mut CThostFtdcInputOrderField,
+        order: &OrderRequest,
+        broker_id: &str,
+        investor_id: &str,
+        order_ref: &str,
+    ) -> Result<(), CtpError> {
+        // assign_from_str 按 UTF-8 字节静默截断，这里先编码为 GB18030，超长直接报错
+        Self::assign_field(&mut ctp_order.BrokerID, broker_id, "经纪商代码")?;
+        Self::assign_field(&mut ctp_order.InvestorID, investor_id, "投资者代码")?;
+        Self::assign_field(&mut ctp_order.InstrumentID, &order.instrument_id, "合约代码")?;
+        Self::assign_field(&mut ctp_order.OrderRef, order_ref, "报单引用")?;
+        
+        // 订单参数
+        ctp_order.Direction = Self::direction_to_ctp_char(order.direction);
+        ctp_order.CombOffsetFlag[0] = Self::offset_flag_to_ctp_char(order.offset_flag);
+        ctp_order.LimitPrice = order.price;
+        ctp_order.VolumeTotalOriginal = i32::try_from(order.volume)
+            .map_err(|_| CtpError::ConversionError(format!("报单数量超出范围: {}", order.volume)))?;
+        ctp_order.OrderPriceType = Self::order_type_to_ctp_char(order.order_type);
+        // 报单有效期：FAK、FOK 为 IOC 加成交量条件，未显式设置时沿用订单的时间条件
+        let (time_condition, volume_condition) = match order.time_in_force {
+            Some(TimeInForce::FAK) if order.volume_condition == OrderVolumeCondition::Min => {
+                (OrderTimeCondition::IOC, OrderVolumeCondition::Min)
+            }
+            Some(time_in_force) => time_in_force.conditions(),
+            None => (order.time_condition, order.volume_condition),
+        };
+        ctp_order.TimeCondition = match time_condition {
+            OrderTimeCondition::IOC => '1' as i8,
+            OrderTimeCondition::GFD => '3' as i8,
+            _ => '3' as i8, // 默认为当日有效
+        };
+        ctp_order.VolumeCondition = Self::volume_condition_to_ctp_char(volume_condition);
+        ctp_order.MinVolume = match volume_condition {
+            OrderVolumeCondition::Min => order.min_volume.clamp(1, order.volume.max(1)) as i32,
+            _ => 1,
+        };
+        
+        // 其他必要字段
+        ctp_order.CombHedgeFlag[0] = '1' as i8; // 投机
+        ctp_order.ContingentCondition = '1' as i8; // 立即
+        ctp_order.ForceCloseReason = '0' as i8; // 非强平
+        ctp_order.IsAutoSuspend = 0; // 不自动挂起
+        ctp_order.UserForceClose = 0; // 非用户强平
+        
+        Ok(())
+    }
+
+    /// 写入 CTP 字符串字段，GB18030 编码后超出字段长度时报错而不是截断
+    fn assign_field(field: &mut [i8], value: &str, name: &str) -> Result<(), CtpError> {
+        string_to_ctp_string(value, field)
+            .map_err(|e| CtpError::ConversionError(format!("{}写入失败: {}", name, e)))
+    }
+
+    /// 将 CTP 订单转换为订单状态（简化版本，用于 TraderSpi）
+    pub fn convert_order(ctp_order: &CThostFtdcOrderField) -> Result<OrderStatus, CtpError> {
+        Self::convert_order_status(ctp_order)
+    }
+
+    /// 将 CTP 成交转换为成交记录（简化版本，用于 TraderSpi）
+    pub fn convert_trade(ctp_trade: &CThostFtdcTradeField) -> Result<TradeRecord, CtpError> {
+        Self::convert_trade_record(ctp_trade)
+    }
+
+    /// 将 CTP 持仓转换为持仓信息（简化版本，用于 TraderSpi）
+    pub fn convert_position(ctp_position: &CThostFtdcInvestorPositionField) -> Result<Position, CtpError> {
+        Self::convert_position_info(ctp_position)
+    }
+
+    /// 将 CTP 账户转换为账户信息（简化版本，用于 TraderSpi）
+    pub fn convert_account(ctp_account: &CThostFtdcTradingAccountField) -> Result<AccountInfo, CtpError> {
+        Self::convert_account_info(ctp_account)
+    }
+
+    /// 将 CTP 订单状态转换为业务模型
+    /// 使用 ctp2rs 官方字符串转换工具
+    pub fn convert_order_status(ctp_order: &CThostFtdcOrderField) -> Result<OrderStatus, CtpError> {
+        let order_ref = gb18030_cstr_i8_to_str(&ctp_order.OrderRef)
+            .map_err(|e| CtpError::ConversionError(format!("订单号转换失败: {}", e)))?.to_string();
+        let order_sys_id = gb18030_cstr_i8_to_str(&ctp_order.OrderSysID)
+            .unwrap_or_default().to_string();
+        
+        Ok(OrderStatus {
+            order_ref: order_ref.clone(),
+            order_id: if order_sys_id.is_empty() { order_ref } else { order_sys_id.clone() },
+            instrument_id: gb18030_cstr_i8_to_str(&ctp_order.InstrumentID)
+                .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string(),
+            direction: Self::ctp_char_to_direction(ctp_order.Direction)?,
+            offset_flag: Self::ctp_char_to_offset_flag(ctp_order.CombOffsetFlag[0])?,
+            price: ctp_order.LimitPrice,
+            limit_price: ctp_order.LimitPrice,
+            volume: ctp_order.VolumeTotalOriginal as u32,
+            volume_total_original: ctp_order.VolumeTotalOriginal,
+            volume_traded: ctp_order.VolumeTraded as u32,
+            volume_left: ctp_order.VolumeTotal as u32,
+            volume_total: ctp_order.VolumeTotal,
+            status: Self::ctp_char_to_order_status(ctp_order.OrderStatus)?,
+            submit_time: chrono::Local::now(), // CTP不提供提交时间，使用当前时间
+            insert_time: gb18030_cstr_i8_to_str(&ctp_order.InsertTime)
+                .map_err(|e| CtpError::ConversionError(format!("插入时间转换失败: {}", e)))?.to_string(),
+            update_time: chrono::Local::now(), // 使用当前时间作为更新时间
+            front_id: ctp_order.FrontID,
+            session_id: ctp_order.SessionID,
+            order_sys_id,
+            status_msg: gb18030_cstr_i8_to_str(&ctp_order.StatusMsg)
+                .ok()
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_default(),
+            is_local: false,
+            frozen_margin: 0.0,
+            frozen_commission: 0.0,
+            tags: Default::default(),
+        })
+    }
+
+    /// 将 CTP 成交记录转换为业务模型
+    /// 使用 ctp2rs 官方字符串转换工具
+    pub fn convert_trade_record(ctp_trade: &CThostFtdcTradeField) -> Result<TradeRecord, CtpError> {
+        Ok(TradeRecord {
+            trade_id: gb18030_cstr_i8_to_str(&ctp_trade.TradeID)
+                .map_err(|e| CtpError::ConversionError(format!("成交编号转换失败: {}", e)))?.to_string(),
+            order_id: gb18030_cstr_i8_to_str(&ctp_trade.OrderRef)
+                .map_err(|e| CtpError::ConversionError(format!("订单号转换失败: {}", e)))?.to_string(),
+            instrument_id: gb18030_cstr_i8_to_str(&ctp_trade.InstrumentID)
+                .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string(),
+            direction: Self::ctp_char_to_direction(ctp_trade.Direction)?,
+            offset_flag: Self::ctp_char_to_offset_flag(ctp_trade.OffsetFlag)?,
+            price: ctp_trade.Price,
+            volume: ctp_trade.Volume,
+            trade_time: gb18030_cstr_i8_to_str(&ctp_trade.TradeTime)
+                .map_err(|e| CtpError::ConversionError(format!("成交时间转换失败: {}", e)))?.to_string(),
+            exchange_id: gb18030_cstr_i8_to_str(&ctp_trade.ExchangeID).unwrap_or_default().to_string(),
+            tags: Default::default(),
+        })
+    }
+
+    /// 将 CTP 持仓信息转换为业务模型
+    /// 使用 ctp2rs 官方字符串转换工具
+    pub fn convert_position_info(ctp_position: &CThostFtdcInvestorPositionField) -> Result<Position, CtpError> {
+        let direction = if ctp_position.PosiDirection == '2' as i8 {
+            PositionDirection::Long
+        } else {
+            PositionDirection::Short
+        };
+
+        Ok(Position {
+            instrument_id: gb18030_cstr_i8_to_str(&ctp_position.InstrumentID)
+                .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string(),
+            direction,
+            total_position: ctp_position.Position,
+            yesterday_position: ctp_position.YdPosition,
+            today_position: ctp_position.TodayPosition,
+            open_cost: ctp_position.OpenCost,
+            position_cost: ctp_position.PositionCost,
+            margin: ctp_position.UseMargin,
+            unrealized_pnl: ctp_position.PositionProfit,
+            realized_pnl: ctp_position.CloseProfit,
+        })
+    }
+
+    /// 将 CTP 账户信息转换为业务模型
+    /// 使用 ctp2rs 官方字符串转换工具
+    pub fn convert_account_info(ctp_account: &CThostFtdcTradingAccountField) -> Result<AccountInfo, CtpError> {
+        Ok(AccountInfo {
+            account_id: gb18030_cstr_i8_to_str(&ctp_account.AccountID)
+                .map_err(|e| CtpError::ConversionError(format!("账户ID转换失败: {}", e)))?.to_string(),
+            currency_id: crate::ctp::currency::normalize_currency(
+                &gb18030_cstr_i8_to_str(&ctp_account.CurrencyID).unwrap_or_default(),
+            ),
+            available: ctp_account.Available,
+            balance: ctp_account.Balance,
+            margin: ctp_account.CurrMargin,
+            frozen_margin: ctp_account.FrozenMargin,
+            frozen_commission: ctp_account.FrozenCommission,
+            curr_margin: ctp_account.CurrMargin,
+            commission: ctp_account.Commission,
+            close_profit: ctp_account.CloseProfit,
+            position_profit: ctp_account.PositionProfit,
+            risk_ratio: if ctp_account.Balance > 0.0 {
+                ctp_account.CurrMargin / ctp_account.Balance * 100.0
+            } else {
+                0.0
+            },
+        })
+    }
+
+    /// 将 CTP 手续费率转换为业务模型，合约代码可能是品种代码（按品种设置的费率）
+    pub fn convert_commission_rate(
+        ctp_rate: &CThostFtdcInstrumentCommissionRateField,
+    ) -> Result<CommissionRate, CtpError> {
+        Ok(CommissionRate {
+            instrument_id: gb18030_cstr_i8_to_str(&ctp_rate.InstrumentID)
+                .map_err(|e| CtpError::ConversionError(format!("合约代码转换失败: {}", e)))?.to_string(),
+            open_ratio_by_money: ctp_rate.OpenRatioByMoney,
+            open_ratio_by_volume: ctp_rate.OpenRatioByVolume,
+            close_ratio_by_money: ctp_rate.CloseRatioByMoney,
+            close_ratio_by_volume: ctp_rate.CloseRatioByVolume,
+            close_today_ratio_by_money: ctp_rate.CloseTodayRatioByMoney,
+            close_today_ratio_by_volume: ctp_rate.CloseTodayRatioByVolume,
+        })
+    }
+
+    // 辅助转换方法 - 使用 ctp2rs 官方工具，禁止自定义实现
+
+    /// 买卖方向转换
+    pub fn direction_to_ctp_char(direction: OrderDirection) -> i8 {
+        match direction {
+            OrderDirection::Buy => '0' as i8,
+            OrderDirection::Sell => '1' as i8,
+        }
+    }
+
+    pub fn ctp_char_to_direction(ctp_char: i8) -> Result<OrderDirection, CtpError> {
+        match ctp_char as u8 as char {
+            '0' => Ok(OrderDirection::Buy),
+            '1' => Ok(OrderDirection::Sell),
+            _ => Err(CtpError::ConversionError(
+                format!("未知的买卖方向: {}", ctp_char)
+            )),
+        }
+    }
+
+    /// 开平仓标志转换
+    pub fn offset_flag_to_ctp_char(offset_flag: OffsetFlag) -> i8 {
+        match offset_flag {
+            OffsetFlag::Open => '0' as i8,
+            OffsetFlag::Close => '1' as i8,
+            OffsetFlag::CloseToday => '3' as i8,
+            OffsetFlag::CloseYesterday => '4' as i8,
+        }
+    }
+
+    pub fn ctp_char_to_offset_flag(ctp_char: i8) -> Result<OffsetFlag, CtpError> {
+        match ctp_char as u8 as char {
+            '0' => Ok(OffsetFlag::Open),
+            '1' => Ok(OffsetFlag::Close),
+            '3' => Ok(OffsetFlag::CloseToday),
+            '4' => Ok(OffsetFlag::CloseYesterday),
+            _ => Err(CtpError::ConversionError(
+                format!("未知的开平仓标志: {}", ctp_char)
+            )),
+        }
+    }
+
+    /// 订单类型转换
+    fn order_type_to_ctp_char(order_type: OrderType) -> i8 {
+        match order_type {
+            OrderType::Limit => '2' as i8,
+            OrderType::Market => '1' as i8,
+            OrderType::Conditional => '9' as i8,
+        }
+    }
+
+    /// 时间条件转换
+    fn time_condition_to_ctp_char(time_condition: TimeCondition) -> i8 {
+        match time_condition {
+            TimeCondition::IOC => '1' as i8,
+            TimeCondition::FOK => '4' as i8,
+            TimeCondition::GFD => '3' as i8,
+        }
+    }
+
+    /// 成交量条件转换
+    fn volume_condition_to_ctp_char(volume_condition: OrderVolumeCondition) -> i8 {
+        match volume_condition {
+            OrderVolumeCondition::Any => '1' as i8,
+            OrderVolumeCondition::Min => '2' as i8,
+            OrderVolumeCondition::All => '3' as i8,
+        }
+    }
+
+    /// 订单状态转换
+    fn ctp_char_to_order_status(ctp_char: i8) -> Result<OrderStatusType, CtpError> {
+        match ctp_char as u8 as char {
+            '0' => Ok(OrderStatusType::AllTraded),
+            '1' => Ok(OrderStatusType::PartTradedQueueing),
+            '2' => Ok(OrderStatusType::PartTradedNotQueueing),
+            '3' => Ok(OrderStatusType::NoTradeQueueing),
+            '4' => Ok(OrderStatusType::NoTradeNotQueueing),
+            '5' => Ok(OrderStatusType::Canceled),
+            'a' => Ok(OrderStatusType::Unknown),
+            _ => Ok(OrderStatusType::Unknown),
+        }
+    }
+}
+
+// 所有 CTP 数据结构都使用 ctp2rs 提供的官方定义
+// 严禁自定义 CTP 结构体，必须使用 ctp2rs::v1alpha1 中的官方结构体
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_conversion() {
+        assert_eq!(DataConverter::direction_to_ctp_char(OrderDirection::Buy), '0' as i8);
+        assert_eq!(DataConverter::direction_to_ctp_char(OrderDirection::Sell), '1' as i8);
+        
+        assert_eq!(DataConverter::ctp_char_to_direction('0' as i8).unwrap(), OrderDirection::Buy);
+        assert_eq!(DataConverter::ctp_char_to_direction('1' as i8).unwrap(), OrderDirection::Sell);
+    }
+
+    #[test]
+    fn test_offset_flag_conversion() {
+        assert_eq!(DataConverter::offset_flag_to_ctp_char(OffsetFlag::Open), '0' as i8);
+        assert_eq!(DataConverter::offset_flag_to_ctp_char(OffsetFlag::Close), '1' as i8);
+        
+        assert_eq!(DataConverter::ctp_char_to_offset_flag('0' as i8).unwrap(), OffsetFlag::Open);
+        assert_eq!(DataConverter::ctp_char_to_offset_flag('1' as i8).unwrap(), OffsetFlag::Close);
+    }
+
+    #[test]
+    fn test_order_type_conversion() {
+        assert_eq!(DataConverter::order_type_to_ctp_char(OrderType::Limit), '2' as i8);
+        assert_eq!(DataConverter::order_type_to_ctp_char(OrderType::Market), '1' as i8);
+    }
+
+    mod round_trip {
+        use super::*;
+        use crate::ctp::utils::utf8_to_gb18030;
+        use ctp2rs::ffi::AssignFromString;
+        use proptest::prelude::*;
+
+        fn order_request(
+            instrument_id: String,
+            direction: OrderDirection,
+            offset_flag: OffsetFlag,
+            price: f64,
+            volume: u32,
+        ) -> OrderRequest {
+            OrderRequest {
+                instrument_id,
+                order_ref: String::new(),
+                direction,
+                offset_flag,
+                price,
+                volume,
+                order_type: OrderType::Limit,
+                price_type: OrderPriceType::Limit,
+                time_condition: OrderTimeCondition::GFD,
+                volume_condition: OrderVolumeCondition::Any,
+                min_volume: 1,
+                contingent_condition: OrderContingentCondition::Immediately,
+                stop_price: 0.0,
+                force_close_reason: OrderForceCloseReason::NotForceClose,
+                is_auto_suspend: false,
+                time_in_force: None,
+                tags: Default::default(),
+            }
+        }
+
+        /// 按柜台回报的方式把报单录入结构回填为报单结构
+        fn echo_order(input: &CThostFtdcInputOrderField) -> CThostFtdcOrderField {
+            let mut order = CThostFtdcOrderField::default();
+            order.InstrumentID = input.InstrumentID;
+            order.OrderRef = input.OrderRef;
+            order.Direction = input.Direction;
+            order.CombOffsetFlag = input.CombOffsetFlag;
+            order.LimitPrice = input.LimitPrice;
+            order.VolumeTotalOriginal = input.VolumeTotalOriginal;
+            order.VolumeTotal = input.VolumeTotalOriginal;
+            order.OrderStatus = '3' as i8;
+            order.InsertTime.assign_from_str("09:30:00");
+            order
+        }
+
+        fn direction() -> impl Strategy<Value = OrderDirection> {
+            prop_oneof![Just(OrderDirection::Buy), Just(OrderDirection::Sell)]
+        }
+
+        fn offset_flag() -> impl Strategy<Value = OffsetFlag> {
+            prop_oneof![
+                Just(OffsetFlag::Open),
+                Just(OffsetFlag::Close),
+                Just(OffsetFlag::CloseToday),
+                Just(OffsetFlag::CloseYesterday),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn order_request_round_trip(
+                instrument_id in "[a-zA-Z]{1,2}[0-9]{3,4}",
+                order_ref in "[0-9]{1,12}",
+                direction in direction(),
+                offset_flag in offset_flag(),
+                price in 0.0f64..1.0e7,
+                volume in 1u32..=i32::MAX as u32,
+            ) {
+                let request = order_request(instrument_id.clone(), direction, offset_flag, price, volume);
+                let input = DataConverter::convert_order_request(&request, "9999", "081234", &order_ref).unwrap();
+                let status = DataConverter::convert_order_status(&echo_order(&input)).unwrap();
+
+                prop_assert_eq!(status.instrument_id, instrument_id);
+                prop_assert_eq!(status.order_ref, order_ref);
+                prop_assert_eq!(status.direction, direction);
+                prop_assert_eq!(status.offset_flag, offset_flag);
+                prop_assert_eq!(status.limit_price.to_bits(), price.to_bits());
+                prop_assert_eq!(status.volume, volume);
+            }
+
+            #[test]
+            fn string_fields_never_truncate_silently(value in "[a-zA-Z0-9\\p{Han}，。（）]{0,60}") {
+                let request = order_request(value.clone(), OrderDirection::Buy, OffsetFlag::Open, 1.0, 1);
+                let capacity = CThostFtdcInputOrderField::default().InstrumentID.len();
+                // 需要保留结尾的 NUL
+                let fits = utf8_to_gb18030(&value).unwrap().len() < capacity;
+                match DataConverter::convert_order_request(&request, "9999", "081234", "1") {
+                    Ok(input) => {
+                        prop_assert!(fits);
+                        let decoded = gb18030_cstr_i8_to_str(&input.InstrumentID).unwrap();
+                        prop_assert_eq!(decoded.as_ref(), value.as_str());
+                    }
+                    Err(_) => prop_assert!(!fits),
+                }
+            }
+
+            #[test]
+            fn market_data_round_trip(
+                instrument_id in "[a-zA-Z]{1,2}[0-9]{3,4}",
+                last_price in 0.0f64..1.0e7,
+                pre_close_price in 0.0f64..1.0e7,
+                volume in 0i32..=i32::MAX,
+                open_interest in 0.0f64..1.0e9,
+                bid_volume1 in 0i32..100_000,
+                update_millisec in 0i32..1000,
+            ) {
+                let mut field = CThostFtdcDepthMarketDataField::default();
+                field.InstrumentID.assign_from_str(&instrument_id);
+                field.UpdateTime.assign_from_str("21:00:01");
+                field.UpdateMillisec = update_millisec;
+                field.LastPrice = last_price;
+                field.PreClosePrice = pre_close_price;
+                field.Volume = volume;
+                field.OpenInterest = open_interest.trunc();
+                field.BidPrice1 = last_price;
+                field.BidVolume1 = bid_volume1;
+
+                let tick = DataConverter::convert_market_data(&field).unwrap();
+                prop_assert_eq!(tick.instrument_id, instrument_id);
+                prop_assert_eq!(tick.update_time.as_str(), "21:00:01");
+                prop_assert_eq!(tick.update_millisec, update_millisec);
+                prop_assert_eq!(tick.last_price.to_bits(), last_price.to_bits());
+                prop_assert_eq!(tick.volume, volume as i64);
+                prop_assert_eq!(tick.open_interest as f64, open_interest.trunc());
+                prop_assert_eq!(tick.bid_volume1, bid_volume1);
+                if pre_close_price > 0.0 {
+                    prop_assert_eq!(tick.change_amount, last_price - pre_close_price);
+                } else {
+                    prop_assert_eq!(tick.change_amount, 0.0);
+                }
+            }
+        }
+
+        #[test]
+        fn test_out_of_range_volume_rejected() {
+            let request = order_request("rb2501".to_string(), OrderDirection::Buy, OffsetFlag::Open, 3500.0, u32::MAX);
+            assert!(DataConverter::convert_order_request(&request, "9999", "081234", "1").is_err());
+        }
+
+        #[test]
+        fn test_time_in_force_mapping() {
+            let base = order_request("rb2501".to_string(), OrderDirection::Buy, OffsetFlag::Open, 3500.0, 5);
+            let cases = [
+                (TimeInForce::GFD, '3', '1'),
+                (TimeInForce::FAK, '1', '1'),
+                (TimeInForce::FOK, '1', '3'),
+            ];
+            for (time_in_force, time_condition, volume_condition) in cases {
+                let request = base.clone().with_time_in_force(time_in_force);
+                assert_eq!(request.time_in_force(), time_in_force);
+                let input = DataConverter::convert_order_request(&request, "9999", "081234", "1").unwrap();
+                assert_eq!(input.TimeCondition, time_condition as i8, "{:?}", time_in_force);
+                assert_eq!(input.VolumeCondition, volume_condition as i8, "{:?}", time_in_force);
+                assert_eq!(input.MinVolume, 1);
+            }
+
+            // 显式有效期覆盖时间条件，FAK 保留最小成交量
+            let mut request = base.clone();
+            request.volume_condition = OrderVolumeCondition::Min;
+            request.min_volume = 3;
+            request.time_in_force = Some(TimeInForce::FAK);
+            let input = DataConverter::convert_order_request(&request, "9999", "081234", "1").unwrap();
+            assert_eq!((input.TimeCondition, input.VolumeCondition, input.MinVolume), ('1' as i8, '2' as i8, 3));
+
+            // 未设置时由时间条件推断
+            let mut request = base;
+            request.time_condition = OrderTimeCondition::IOC;
+            request.volume_condition = OrderVolumeCondition::All;
+            assert_eq!(request.time_in_force(), TimeInForce::FOK);
+        }
+    }
+}
\ No newline at end of file
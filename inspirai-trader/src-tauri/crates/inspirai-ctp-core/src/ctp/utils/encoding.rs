@@ -1,3 +1,5 @@
+use encoding_rs::GB18030;
+
 use crate::ctp::CtpError;
 
 /// 将 GB18030 编码的字节数组转换为 UTF-8 字符串
@@ -5,28 +7,18 @@ use crate::ctp::CtpError;
 /// CTP API 使用 GB18030 编码，需要转换为 Rust 的 UTF-8 字符串
 pub fn gb18030_to_utf8(gb18030_bytes: &[u8]) -> Result<String, CtpError> {
     // 移除尾部的空字节
-    let trimmed_bytes: Vec<u8> = gb18030_bytes.iter()
-        .take_while(|&&b| b != 0)
-        .copied()
-        .collect();
+    let len = gb18030_bytes.iter().position(|&b| b == 0).unwrap_or(gb18030_bytes.len());
+    let trimmed_bytes = &gb18030_bytes[..len];
     
-    if trimmed_bytes.is_empty() {
-        return Ok(String::new());
+    if trimmed_bytes.is_ascii() {
+        return Ok(String::from_utf8_lossy(trimmed_bytes).into_owned());
     }
     
-    // 尝试直接解析为 UTF-8（对于纯 ASCII 字符）
-    match String::from_utf8(trimmed_bytes.clone()) {
-        Ok(s) => Ok(s),
-        Err(_) => {
-            // 如果不是有效的 UTF-8，尝试 GB18030 解码
-            // 这里使用简化的处理方式，在实际项目中可能需要使用专门的编码库
-            // 如 encoding_rs 或 iconv
-            
-            // 对于大多数情况，CTP 返回的字符串都是 ASCII 或简单的中文
-            // 这里使用 lossy 转换作为后备方案
-            Ok(String::from_utf8_lossy(&trimmed_bytes).to_string())
-        }
+    let (decoded, had_errors) = GB18030.decode_without_bom_handling(trimmed_bytes);
+    if had_errors {
+        return Err(CtpError::ConversionError(format!("无效的 GB18030 字节序列 (长度: {})", len)));
     }
+    Ok(decoded.into_owned())
 }
 
 /// 将 UTF-8 字符串转换为 GB18030 编码的字节数组
@@ -35,14 +27,14 @@ pub fn gb18030_to_utf8(gb18030_bytes: &[u8]) -> Result<String, CtpError> {
 pub fn utf8_to_gb18030(utf8_str: &str) -> Result<Vec<u8>, CtpError> {
     // 对于纯 ASCII 字符，UTF-8 和 GB18030 编码相同
     if utf8_str.is_ascii() {
-        Ok(utf8_str.as_bytes().to_vec())
-    } else {
-        // 对于包含中文的字符串，这里需要实际的 GB18030 编码
-        // 在实际项目中，应该使用专门的编码库
-        // 这里暂时使用 UTF-8 字节作为后备方案
-        tracing::warn!("字符串包含非 ASCII 字符，可能需要 GB18030 编码: {}", utf8_str);
-        Ok(utf8_str.as_bytes().to_vec())
+        return Ok(utf8_str.as_bytes().to_vec());
+    }
+    
+    let (encoded, _, had_errors) = GB18030.encode(utf8_str);
+    if had_errors {
+        return Err(CtpError::ConversionError(format!("字符串无法编码为 GB18030: {}", utf8_str)));
     }
+    Ok(encoded.into_owned())
 }
 
 /// 将 CTP 字符数组转换为 Rust 字符串的便捷函数
@@ -57,7 +49,12 @@ pub fn ctp_string_to_string(ctp_str: &[i8]) -> Result<String, CtpError> {
 
 /// 将 Rust 字符串复制到 CTP 字符数组的便捷函数
 pub fn string_to_ctp_string(rust_str: &str, ctp_field: &mut [i8]) -> Result<(), CtpError> {
-    let gb18030_bytes = utf8_to_gb18030(rust_str)?;
+    // 报单路径上的字段基本都是 ASCII，直接借用原字节，不分配中间缓冲
+    let gb18030_bytes = if rust_str.is_ascii() {
+        std::borrow::Cow::Borrowed(rust_str.as_bytes())
+    } else {
+        std::borrow::Cow::Owned(utf8_to_gb18030(rust_str)?)
+    };
     
     if gb18030_bytes.len() >= ctp_field.len() {
         return Err(CtpError::ConversionError(
@@ -127,4 +124,14 @@ mod tests {
         let result = string_to_ctp_string(long_str, &mut ctp_field);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_chinese_round_trip() {
+        let text = "平仓报单被拒绝";
+        let gb18030_bytes = utf8_to_gb18030(text).unwrap();
+        // GB18030 中汉字占 2 字节
+        assert_eq!(gb18030_bytes.len(), text.chars().count() * 2);
+        assert_eq!(gb18030_to_utf8(&gb18030_bytes).unwrap(), text);
+        assert!(gb18030_to_utf8(&[0xB2, 0x00]).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,7 @@
+/// 账户号等标识作为文件名时去掉路径字符
+pub(crate) fn sanitize_file_component(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
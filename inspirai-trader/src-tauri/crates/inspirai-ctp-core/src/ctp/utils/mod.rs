@@ -0,0 +1,12 @@
+// 工具模块
+// 包含数据转换、编码处理等辅助功能
+
+pub mod converter;
+pub mod encoding;
+pub mod file_name;
+pub mod pool;
+
+pub use converter::{DataConverter, DepthQuote};
+pub use encoding::{gb18030_to_utf8, utf8_to_gb18030};
+pub(crate) use file_name::sanitize_file_component;
+pub use pool::{ConversionPools, ObjectPool, PoolStats, Pooled, SymbolCache};
\ No newline at end of file
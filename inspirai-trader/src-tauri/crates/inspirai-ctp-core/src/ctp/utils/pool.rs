@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use ctp2rs::ffi::gb18030_cstr_i8_to_str;
+use ctp2rs::v1alpha1::CThostFtdcInputOrderField;
+use serde::{Deserialize, Serialize};
+
+use crate::ctp::CtpError;
+
+/// 报单结构体池默认容量
+pub const DEFAULT_ORDER_POOL_CAPACITY: usize = 64;
+/// 合约、交易所代码缓存默认容量
+pub const DEFAULT_SYMBOL_CACHE_CAPACITY: usize = 4096;
+
+/// 对象池命中统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolStats {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// 命中率，无请求时为 0
+    pub hit_rate: f64,
+    /// 池中空闲或已缓存的对象数
+    pub idle: usize,
+    pub capacity: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Counters {
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self, name: &str, idle: usize, capacity: usize) -> PoolStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        PoolStats {
+            name: name.to_string(),
+            hits,
+            misses,
+            hit_rate: if total == 0 { 0.0 } else { hits as f64 / total as f64 },
+            idle,
+            capacity,
+        }
+    }
+}
+
+/// 定长对象池
+///
+/// 取出的对象已重置为默认值，归还时超出容量的直接释放
+pub struct ObjectPool<T: Default> {
+    name: &'static str,
+    capacity: usize,
+    idle: Mutex<Vec<Box<T>>>,
+    counters: Counters,
+}
+
+impl<T: Default> ObjectPool<T> {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            capacity,
+            idle: Mutex::new(Vec::with_capacity(capacity)),
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn acquire(&self) -> Pooled<'_, T> {
+        let reused = self.idle.lock().unwrap().pop();
+        self.counters.record(reused.is_some());
+        let item = match reused {
+            Some(mut item) => {
+                *item = T::default();
+                item
+            }
+            None => Box::default(),
+        };
+        Pooled { pool: self, item: Some(item) }
+    }
+
+    fn release(&self, item: Box<T>) {
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < self.capacity {
+            idle.push(item);
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.counters.stats(self.name, self.idle.lock().unwrap().len(), self.capacity)
+    }
+}
+
+/// 池中取出的对象，释放时归还
+pub struct Pooled<'a, T: Default> {
+    pool: &'a ObjectPool<T>,
+    item: Option<Box<T>>,
+}
+
+impl<T: Default> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_deref().expect("对象已归还")
+    }
+}
+
+impl<T: Default> DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_deref_mut().expect("对象已归还")
+    }
+}
+
+impl<T: Default> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.pool.release(item);
+        }
+    }
+}
+
+/// 合约、交易所等短代码的解码缓存
+///
+/// 以 CTP 原始字节为键，命中时只增加引用计数，不再解码和分配字符串；
+/// 缓存满时整体清空，代码数量有限，很快重新填满
+pub struct SymbolCache {
+    name: &'static str,
+    capacity: usize,
+    symbols: Mutex<HashMap<Vec<i8>, Arc<str>>>,
+    counters: Counters,
+}
+
+impl SymbolCache {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            capacity,
+            symbols: Mutex::new(HashMap::new()),
+            counters: Counters::default(),
+        }
+    }
+
+    /// 解码 CTP 字符串字段，结尾的空字节不参与缓存键
+    pub fn get(&self, field: &[i8]) -> Result<Arc<str>, CtpError> {
+        let len = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+        let key = &field[..len];
+        let mut symbols = self.symbols.lock().unwrap();
+        if let Some(symbol) = symbols.get(key) {
+            self.counters.record(true);
+            return Ok(symbol.clone());
+        }
+        self.counters.record(false);
+        let symbol: Arc<str> = gb18030_cstr_i8_to_str(key)
+            .map_err(|e| CtpError::ConversionError(format!("代码转换失败: {}", e)))?
+            .as_ref()
+            .into();
+        if symbols.len() >= self.capacity {
+            symbols.clear();
+        }
+        symbols.insert(key.to_vec(), symbol.clone());
+        Ok(symbol)
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.counters.stats(self.name, self.symbols.lock().unwrap().len(), self.capacity)
+    }
+}
+
+/// 报单和行情转换路径共用的对象池
+#[derive(Clone)]
+pub struct ConversionPools {
+    orders: Arc<ObjectPool<CThostFtdcInputOrderField>>,
+    symbols: Arc<SymbolCache>,
+}
+
+impl Default for ConversionPools {
+    fn default() -> Self {
+        Self::new(DEFAULT_ORDER_POOL_CAPACITY, DEFAULT_SYMBOL_CACHE_CAPACITY)
+    }
+}
+
+impl ConversionPools {
+    pub fn new(order_capacity: usize, symbol_capacity: usize) -> Self {
+        Self {
+            orders: Arc::new(ObjectPool::new("input_order", order_capacity)),
+            symbols: Arc::new(SymbolCache::new("symbol", symbol_capacity)),
+        }
+    }
+
+    pub fn orders(&self) -> &ObjectPool<CThostFtdcInputOrderField> {
+        &self.orders
+    }
+
+    pub fn symbols(&self) -> &SymbolCache {
+        &self.symbols
+    }
+
+    pub fn stats(&self) -> Vec<PoolStats> {
+        vec![self.orders.stats(), self.symbols.stats()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_pool_reuses_and_resets() {
+        let pool: ObjectPool<Vec<u8>> = ObjectPool::new("buf", 1);
+        {
+            let mut first = pool.acquire();
+            first.push(1);
+            let _second = pool.acquire();
+        }
+        // 容量为 1，只保留一个
+        assert_eq!(pool.stats().idle, 1);
+        let reused = pool.acquire();
+        assert!(reused.is_empty());
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_symbol_cache_hits_and_bounds() {
+        let cache = SymbolCache::new("symbol", 2);
+        let field = |s: &str| {
+            let mut bytes = [0i8; 31];
+            for (i, b) in s.bytes().enumerate() {
+                bytes[i] = b as i8;
+            }
+            bytes
+        };
+        let rb = field("rb2501");
+        assert_eq!(&*cache.get(&rb).unwrap(), "rb2501");
+        let again = cache.get(&rb).unwrap();
+        assert_eq!(&*again, "rb2501");
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+
+        cache.get(&field("cu2502")).unwrap();
+        cache.get(&field("au2506")).unwrap();
+        // 超出容量后清空重建
+        assert_eq!(cache.stats().idle, 1);
+    }
+}
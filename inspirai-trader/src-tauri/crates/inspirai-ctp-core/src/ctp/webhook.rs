@@ -0,0 +1,550 @@
+use crate::ctp::{CtpError, CtpEvent, DailyRiskReport, DeadManReport};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 默认 Webhook 配置文件
+pub const DEFAULT_WEBHOOK_CONFIG_FILE: &str = "./config/webhooks.toml";
+/// 保留的投递记录条数
+pub const WEBHOOK_DELIVERY_LOG_CAPACITY: usize = 200;
+/// 签名请求头，值为 `sha256=<hex>`，对 `{timestamp}.{body}` 做 HMAC-SHA256
+pub const SIGNATURE_HEADER: &str = "X-Inspirai-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Inspirai-Timestamp";
+pub const EVENT_HEADER: &str = "X-Inspirai-Event";
+pub const DELIVERY_HEADER: &str = "X-Inspirai-Delivery";
+
+/// 触发 Webhook 的事件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// 前置断开（含交易端断开进入降级模式）
+    Disconnected,
+    LoginFailed,
+    /// 策略熔断或拒单熔断暂停报单
+    RiskHalt,
+    /// 死人开关触发后的撤单/平仓处置
+    KillSwitch,
+    /// 日终风险报告生成完成
+    DailyReport,
+    /// 资金曲线异常
+    FundsAnomaly,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disconnected => "disconnected",
+            Self::LoginFailed => "login_failed",
+            Self::RiskHalt => "risk_halt",
+            Self::KillSwitch => "kill_switch",
+            Self::DailyReport => "daily_report",
+            Self::FundsAnomaly => "funds_anomaly",
+        }
+    }
+}
+
+/// 投递目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    /// 签名密钥，为空时不签名
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// 订阅的事件，为空表示全部
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookEndpoint {
+    pub fn accepts(&self, kind: WebhookEventKind) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&kind))
+    }
+}
+
+/// Webhook 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// 每个事件的最多尝试次数（含首次）
+    pub max_attempts: u32,
+    /// 首次重试间隔，之后逐次翻倍
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// 从 TOML 文件加载，文件不存在时返回默认配置
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("Webhook 配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("Webhook 配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.max_attempts == 0 {
+            return Err(CtpError::ConfigError("Webhook 最多尝试次数必须大于0".to_string()));
+        }
+        for endpoint in &self.endpoints {
+            if endpoint.id.trim().is_empty() {
+                return Err(CtpError::ConfigError("Webhook 目标 ID 不能为空".to_string()));
+            }
+            if !endpoint.url.starts_with("http://") && !endpoint.url.starts_with("https://") {
+                return Err(CtpError::ConfigError(format!("Webhook 地址无效: {}", endpoint.url)));
+            }
+        }
+        let mut ids: Vec<&str> = self.endpoints.iter().map(|e| e.id.as_str()).collect();
+        ids.sort_unstable();
+        if ids.windows(2).any(|w| w[0] == w[1]) {
+            return Err(CtpError::ConfigError("Webhook 目标 ID 重复".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 第 attempt 次失败后的等待时间
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(20);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// 推送内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub id: String,
+    pub kind: WebhookEventKind,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    /// 一行说明，便于直接转发到群机器人
+    pub summary: String,
+    pub data: serde_json::Value,
+}
+
+impl WebhookPayload {
+    pub fn new(kind: WebhookEventKind, summary: impl Into<String>, data: serde_json::Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            occurred_at: chrono::Utc::now(),
+            summary: summary.into(),
+            data,
+        }
+    }
+
+    /// 客户端事件中需要对外通知的部分
+    pub fn from_event(event: &CtpEvent) -> Option<Self> {
+        match event {
+            CtpEvent::Disconnected => Some(Self::new(WebhookEventKind::Disconnected, "CTP 前置连接断开", serde_json::Value::Null)),
+            CtpEvent::DegradedModeEntered(reason) => Some(Self::new(
+                WebhookEventKind::Disconnected,
+                format!("交易端不可用，进入仅行情模式: {}", reason),
+                serde_json::json!({ "reason": reason, "degraded": true }),
+            )),
+            CtpEvent::LoginFailed(reason) => Some(Self::new(
+                WebhookEventKind::LoginFailed,
+                format!("CTP 登录失败: {}", reason),
+                serde_json::json!({ "reason": reason }),
+            )),
+            CtpEvent::StrategyCircuitBreakerTripped(status) => Some(Self::new(
+                WebhookEventKind::RiskHalt,
+                format!("策略 {} 超出预算被熔断，挂单已撤销", status.name),
+                serde_json::to_value(status).unwrap_or_default(),
+            )),
+            CtpEvent::OrderRejectionBreakerTripped(alert) => Some(Self::new(
+                WebhookEventKind::RiskHalt,
+                format!("来源 {} 在 {} 秒内被拒 {} 次，已暂停报单", alert.source, alert.window_secs, alert.rejections),
+                serde_json::to_value(alert).unwrap_or_default(),
+            )),
+            CtpEvent::FundsAnomalyDetected(anomaly) => Some(Self::new(
+                WebhookEventKind::FundsAnomaly,
+                anomaly.message.clone(),
+                serde_json::to_value(anomaly).unwrap_or_default(),
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn kill_switch(report: &DeadManReport) -> Self {
+        Self::new(
+            WebhookEventKind::KillSwitch,
+            format!(
+                "死人开关触发：撤单 {} 笔，平仓 {} 笔，失败 {} 项",
+                report.cancelled.len(),
+                report.flatten_orders.len(),
+                report.errors.len()
+            ),
+            serde_json::to_value(report).unwrap_or_default(),
+        )
+    }
+
+    pub fn daily_report(report: &DailyRiskReport) -> Self {
+        Self::new(
+            WebhookEventKind::DailyReport,
+            format!(
+                "{} 风险报告已生成：权益 {:.2}，保证金 {:.2}，VaR {:.2}，警告 {} 条",
+                report.trading_day,
+                report.balance,
+                report.margin,
+                report.total_var,
+                report.warnings.len()
+            ),
+            serde_json::json!({
+                "trading_day": report.trading_day,
+                "balance": report.balance,
+                "margin": report.margin,
+                "total_var": report.total_var,
+                "warnings": report.warnings,
+            }),
+        )
+    }
+}
+
+/// 投递结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Delivered,
+    /// 本次失败，稍后重试
+    Retrying,
+    /// 重试用尽或目标拒绝，放弃
+    Failed,
+}
+
+/// 单次投递尝试的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub payload_id: String,
+    pub endpoint_id: String,
+    pub kind: WebhookEventKind,
+    pub attempt: u32,
+    pub status: DeliveryStatus,
+    pub http_status: Option<u16>,
+    pub error: Option<String>,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+struct DispatcherInner {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    deliveries: VecDeque<WebhookDelivery>,
+}
+
+/// Webhook 分发器
+///
+/// 每个事件按订阅分发到各目标，独立的异步任务负责投递：网络错误、5xx 和 429
+/// 按指数退避重试，其他 4xx 视为目标拒绝不再重试。每次尝试都记入投递记录
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    inner: Arc<Mutex<DispatcherInner>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: WebhookConfig) -> Self {
+        let client = build_client(&config);
+        Self {
+            inner: Arc::new(Mutex::new(DispatcherInner {
+                config,
+                client,
+                deliveries: VecDeque::new(),
+            })),
+        }
+    }
+
+    pub fn config(&self) -> WebhookConfig {
+        self.inner.lock().unwrap().config.clone()
+    }
+
+    /// 替换配置，已在途的投递按旧配置完成
+    pub fn update_config(&self, config: WebhookConfig) -> Result<(), CtpError> {
+        config.validate()?;
+        let client = build_client(&config);
+        let mut inner = self.inner.lock().unwrap();
+        inner.config = config;
+        inner.client = client;
+        Ok(())
+    }
+
+    /// 最近的投递记录，按时间倒序
+    pub fn deliveries(&self, limit: usize) -> Vec<WebhookDelivery> {
+        self.inner.lock().unwrap().deliveries.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// 客户端事件中需要通知的部分转为推送，返回投递的目标数
+    pub fn notify_event(&self, event: &CtpEvent) -> usize {
+        WebhookPayload::from_event(event).map_or(0, |payload| self.notify(payload))
+    }
+
+    /// 向订阅该类别的目标投递，需在 tokio 运行时中调用；返回投递的目标数
+    pub fn notify(&self, payload: WebhookPayload) -> usize {
+        let (config, client, endpoints) = {
+            let inner = self.inner.lock().unwrap();
+            let endpoints: Vec<WebhookEndpoint> = inner
+                .config
+                .endpoints
+                .iter()
+                .filter(|e| e.accepts(payload.kind))
+                .cloned()
+                .collect();
+            (inner.config.clone(), inner.client.clone(), endpoints)
+        };
+        if endpoints.is_empty() {
+            return 0;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("不在异步运行时中，丢弃 Webhook 推送: {}", payload.summary);
+            return 0;
+        };
+
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => Arc::new(body),
+            Err(e) => {
+                tracing::error!("Webhook 推送序列化失败: {}", e);
+                return 0;
+            }
+        };
+        let count = endpoints.len();
+        for endpoint in endpoints {
+            let dispatcher = self.clone();
+            let job = DeliveryJob {
+                payload_id: payload.id.clone(),
+                kind: payload.kind,
+                body: body.clone(),
+                endpoint,
+            };
+            runtime.spawn(dispatcher.deliver(client.clone(), config.clone(), job));
+        }
+        count
+    }
+
+    async fn deliver(self, client: reqwest::Client, config: WebhookConfig, job: DeliveryJob) {
+        for attempt in 1..=config.max_attempts {
+            let timestamp = chrono::Utc::now().timestamp().to_string();
+            let mut request = client
+                .post(&job.endpoint.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, job.kind.as_str())
+                .header(DELIVERY_HEADER, &job.payload_id)
+                .header(TIMESTAMP_HEADER, &timestamp)
+                .body(job.body.as_ref().clone());
+            if let Some(secret) = job.endpoint.secret.as_deref().filter(|s| !s.is_empty()) {
+                request = request.header(SIGNATURE_HEADER, sign(secret, &timestamp, &job.body));
+            }
+
+            let (http_status, error, retryable) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    tracing::info!("Webhook 已投递: {} -> {}", job.kind.as_str(), job.endpoint.id);
+                    self.record(&job, attempt, DeliveryStatus::Delivered, Some(response.status().as_u16()), None);
+                    return;
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                    (Some(status.as_u16()), format!("HTTP {}", status), retryable)
+                }
+                Err(e) => (None, e.to_string(), true),
+            };
+
+            if !retryable || attempt == config.max_attempts {
+                tracing::warn!(
+                    "Webhook 投递失败: {} -> {}，第 {} 次: {}",
+                    job.kind.as_str(), job.endpoint.id, attempt, error
+                );
+                self.record(&job, attempt, DeliveryStatus::Failed, http_status, Some(error));
+                return;
+            }
+            let delay = config.backoff(attempt);
+            tracing::debug!("Webhook 投递失败，{}ms 后重试: {} ({})", delay.as_millis(), job.endpoint.id, error);
+            self.record(&job, attempt, DeliveryStatus::Retrying, http_status, Some(error));
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn record(&self, job: &DeliveryJob, attempt: u32, status: DeliveryStatus, http_status: Option<u16>, error: Option<String>) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.deliveries.len() >= WEBHOOK_DELIVERY_LOG_CAPACITY {
+            inner.deliveries.pop_front();
+        }
+        inner.deliveries.push_back(WebhookDelivery {
+            payload_id: job.payload_id.clone(),
+            endpoint_id: job.endpoint.id.clone(),
+            kind: job.kind,
+            attempt,
+            status,
+            http_status,
+            error,
+            at: chrono::Utc::now(),
+        });
+    }
+}
+
+struct DeliveryJob {
+    payload_id: String,
+    kind: WebhookEventKind,
+    body: Arc<Vec<u8>>,
+    endpoint: WebhookEndpoint,
+}
+
+fn build_client(config: &WebhookConfig) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::warn!("创建 Webhook HTTP 客户端失败，使用默认配置: {}", e);
+            reqwest::Client::new()
+        })
+}
+
+/// 签名头的值，接收方用同一密钥对 `{timestamp}.{body}` 计算后比对
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    format!("sha256={}", hmac_sha256_hex(secret.as_bytes(), &message))
+}
+
+fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度密钥");
+    mac.update(message);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_signature_backoff_and_event_mapping() {
+        // RFC 4231 测试用例 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert!(sign("secret", "1700000000", b"{}").starts_with("sha256="));
+
+        let config = WebhookConfig {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 3000,
+            ..WebhookConfig::default()
+        };
+        let delays: Vec<u128> = (1..=5).map(|a| config.backoff(a).as_millis()).collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 3000, 3000]);
+
+        assert_eq!(WebhookPayload::from_event(&CtpEvent::Disconnected).unwrap().kind, WebhookEventKind::Disconnected);
+        assert_eq!(
+            WebhookPayload::from_event(&CtpEvent::LoginFailed("不合法的登录".to_string())).unwrap().kind,
+            WebhookEventKind::LoginFailed
+        );
+        assert!(WebhookPayload::from_event(&CtpEvent::Connected).is_none());
+
+        let endpoint = WebhookEndpoint {
+            id: "ops".to_string(),
+            url: "https://example.com/hook".to_string(),
+            secret: None,
+            events: vec![WebhookEventKind::RiskHalt],
+            enabled: true,
+        };
+        assert!(endpoint.accepts(WebhookEventKind::RiskHalt));
+        assert!(!endpoint.accepts(WebhookEventKind::Disconnected));
+
+        let duplicated = WebhookConfig {
+            endpoints: vec![endpoint.clone(), endpoint],
+            ..WebhookConfig::default()
+        };
+        assert!(duplicated.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_delivered_with_signature() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        // 第一次返回 503，第二次返回 200，并回传收到的请求
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = socket.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_string());
+                let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let dispatcher = WebhookDispatcher::new(WebhookConfig {
+            endpoints: vec![WebhookEndpoint {
+                id: "local".to_string(),
+                url,
+                secret: Some("s3cret".to_string()),
+                events: Vec::new(),
+                enabled: true,
+            }],
+            initial_backoff_ms: 10,
+            ..WebhookConfig::default()
+        });
+        assert_eq!(dispatcher.notify_event(&CtpEvent::LoginFailed("密码错误".to_string())), 1);
+
+        let requests = tokio::time::timeout(Duration::from_secs(10), server).await.unwrap().unwrap();
+        let request = requests[1].to_lowercase();
+        assert!(request.contains("x-inspirai-event: login_failed"));
+        assert!(request.contains("x-inspirai-signature: sha256="));
+
+        for _ in 0..100 {
+            if dispatcher.deliveries(10).first().map(|d| d.status) == Some(DeliveryStatus::Delivered) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let deliveries = dispatcher.deliveries(10);
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempt, 2);
+        assert_eq!(deliveries[1].status, DeliveryStatus::Retrying);
+        assert_eq!(deliveries[1].http_status, Some(503));
+    }
+}
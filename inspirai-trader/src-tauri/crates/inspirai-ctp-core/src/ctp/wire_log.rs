@@ -0,0 +1,702 @@
+use crate::ctp::{utils::encoding::ctp_string_to_string, CtpError};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Instant;
+
+/// 默认报文日志配置文件
+pub const DEFAULT_WIRE_LOG_CONFIG_FILE: &str = "./config/wire_log.toml";
+
+/// 默认报文日志目录，按自然日写 `wire_YYYYMMDD.jsonl`
+pub const DEFAULT_WIRE_LOG_DIR: &str = "./logs/ctp_wire";
+
+/// `Debug` 输出无法解析时的载荷，原文可能含未脱敏的密码等字段，不落盘
+pub const UNPARSED_PAYLOAD: &str = "<unparsed>";
+
+/// 完全遮盖的字段，按字段名包含匹配（不区分大小写）
+const SECRET_FIELDS: &[&str] = &["password", "authcode"];
+
+/// 只保留首尾两位的账号类字段，按字段名精确匹配（不区分大小写）
+const ACCOUNT_FIELDS: &[&str] = &[
+    "investorid",
+    "userid",
+    "accountid",
+    "bankaccount",
+    "identifiedcardno",
+    "newbankaccount",
+];
+
+/// CTP 报文日志配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WireLogConfig {
+    /// 关闭时不格式化报文，对请求与回调路径只有一次判断的开销
+    pub enabled: bool,
+    /// 内存中保留的最近报文条数
+    pub capacity: usize,
+    /// 同时追加写入 `dir` 下的 JSON Lines 文件
+    pub write_file: bool,
+    pub dir: PathBuf,
+    /// 每秒报文超过该条数视为高负载，此时 `sampled_callbacks` 中的回调按 `sample_every` 采样
+    pub sample_above_per_second: u32,
+    pub sample_every: u32,
+    /// 高负载时允许采样的回调，请求和报单、成交、错误等回调始终完整记录
+    pub sampled_callbacks: Vec<String>,
+    /// 额外完全遮盖的字段名
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for WireLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 5_000,
+            write_file: true,
+            dir: PathBuf::from(DEFAULT_WIRE_LOG_DIR),
+            sample_above_per_second: 200,
+            sample_every: 20,
+            sampled_callbacks: vec!["OnRtnDepthMarketData".to_string()],
+            redact_fields: Vec::new(),
+        }
+    }
+}
+
+impl WireLogConfig {
+    /// 读取配置，文件不存在时使用默认值（不启用）
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CtpError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CtpError::ConfigError(format!("报文日志配置解析失败: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CtpError> {
+        self.validate()?;
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CtpError::ConfigError(format!("报文日志配置序列化失败: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), CtpError> {
+        if self.capacity == 0 {
+            return Err(CtpError::ConfigError("报文日志保留条数必须大于 0".to_string()));
+        }
+        if self.sample_every == 0 || self.sample_above_per_second == 0 {
+            return Err(CtpError::ConfigError("报文日志采样参数必须大于 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 报文方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireDirection {
+    /// 发往前置的请求
+    Request,
+    /// 前置推送的回调
+    Callback,
+}
+
+/// 一条报文，`payload` 为脱敏后的结构体字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireRecord {
+    pub seq: u64,
+    pub timestamp: DateTime<Local>,
+    pub direction: WireDirection,
+    /// CTP 接口名，如 `ReqOrderInsert`、`OnRtnOrder`
+    pub name: String,
+    pub request_id: Option<i32>,
+    pub payload: Value,
+}
+
+/// 报文日志统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WireLogStats {
+    pub recorded: u64,
+    /// 高负载采样丢弃的回调数
+    pub sampled_out: u64,
+    /// 写文件失败次数
+    pub write_errors: u64,
+    pub buffered: usize,
+}
+
+struct WireLogState {
+    config: WireLogConfig,
+    /// 与 `config.redact_fields` 相同，在锁外脱敏时共享
+    redact_fields: Arc<Vec<String>>,
+    records: VecDeque<WireRecord>,
+    seq: u64,
+    window_start: Instant,
+    window_count: u32,
+    sample_counter: u64,
+    stats: WireLogStats,
+    writer: Option<mpsc::Sender<WriterMessage>>,
+}
+
+enum WriterMessage {
+    Record(Box<WireRecord>),
+    /// 之前的报文写完后回复
+    Flush(mpsc::Sender<()>),
+}
+
+/// CTP 请求与回调的报文日志
+///
+/// 独立于常规日志，按接口名记录每个请求结构体和回调结构体的全部字段，
+/// 用于与期货公司核对报单争议；密码类字段完全遮盖，账号类字段只保留首尾两位。
+/// 回调线程上只做格式化和脱敏，写文件由后台线程完成
+#[derive(Clone)]
+pub struct WireLogger {
+    state: Arc<Mutex<WireLogState>>,
+    write_errors: Arc<AtomicU64>,
+}
+
+impl Default for WireLogger {
+    fn default() -> Self {
+        Self::new(WireLogConfig::default())
+    }
+}
+
+impl WireLogger {
+    pub fn new(config: WireLogConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(WireLogState {
+                records: VecDeque::with_capacity(config.capacity.min(1024)),
+                redact_fields: Arc::new(config.redact_fields.clone()),
+                config,
+                seq: 0,
+                window_start: Instant::now(),
+                window_count: 0,
+                sample_counter: 0,
+                stats: WireLogStats::default(),
+                writer: None,
+            })),
+            write_errors: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn config(&self) -> WireLogConfig {
+        self.state.lock().unwrap().config.clone()
+    }
+
+    /// 替换配置，缓冲区超出新容量的旧报文被丢弃
+    pub fn update_config(&self, config: WireLogConfig) -> Result<(), CtpError> {
+        config.validate()?;
+        let mut state = self.state.lock().unwrap();
+        while state.records.len() > config.capacity {
+            state.records.pop_front();
+        }
+        // 旧写线程写完已排队的报文后退出，按新目录重新启动
+        state.writer = None;
+        state.redact_fields = Arc::new(config.redact_fields.clone());
+        state.config = config;
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().unwrap().config.enabled
+    }
+
+    /// 记录发往前置的请求
+    pub fn request<T: Debug>(&self, name: &str, request_id: i32, request: &T) {
+        self.record(WireDirection::Request, name, Some(request_id), || debug_to_json(request));
+    }
+
+    /// 记录回调，数据与响应信息任一为空时记为 null
+    pub fn callback<T: Debug>(
+        &self,
+        name: &str,
+        request_id: Option<i32>,
+        data: Option<&T>,
+        rsp_info: Option<&dyn Debug>,
+    ) {
+        self.record(WireDirection::Callback, name, request_id, || {
+            let mut payload = Map::new();
+            payload.insert("data".to_string(), data.map_or(Value::Null, |d| debug_to_json(d)));
+            if let Some(info) = rsp_info {
+                payload.insert("rsp_info".to_string(), debug_to_json(info));
+            }
+            Value::Object(payload)
+        });
+    }
+
+    fn record(&self, direction: WireDirection, name: &str, request_id: Option<i32>, payload: impl FnOnce() -> Value) {
+        let redact_fields = {
+            let mut state = self.state.lock().unwrap();
+            if !state.config.enabled {
+                return;
+            }
+            let now = Instant::now();
+            if now.duration_since(state.window_start).as_secs() >= 1 {
+                state.window_start = now;
+                state.window_count = 0;
+            }
+            state.window_count = state.window_count.saturating_add(1);
+            let overloaded = state.window_count > state.config.sample_above_per_second;
+            if overloaded && direction == WireDirection::Callback && state.config.sampled_callbacks.iter().any(|c| c == name) {
+                state.sample_counter += 1;
+                if state.sample_counter % state.config.sample_every as u64 != 0 {
+                    state.stats.sampled_out += 1;
+                    return;
+                }
+            }
+            state.redact_fields.clone()
+        };
+
+        // 格式化与脱敏不占用锁
+        let mut payload = payload();
+        redact(&mut payload, &redact_fields);
+        let timestamp = Local::now();
+
+        let mut state = self.state.lock().unwrap();
+        state.seq += 1;
+        let record = WireRecord {
+            seq: state.seq,
+            timestamp,
+            direction,
+            name: name.to_string(),
+            request_id,
+            payload,
+        };
+        if state.config.write_file {
+            let sent = state
+                .writer(&self.write_errors)
+                .is_some_and(|writer| writer.send(WriterMessage::Record(Box::new(record.clone()))).is_ok());
+            if !sent {
+                state.writer = None;
+                self.write_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        if state.records.len() >= state.config.capacity {
+            state.records.pop_front();
+        }
+        state.records.push_back(record);
+        state.stats.recorded += 1;
+    }
+
+    /// 等待已记录的报文写入文件
+    pub fn flush(&self) {
+        let writer = self.state.lock().unwrap().writer.clone();
+        let Some(writer) = writer else {
+            return;
+        };
+        let (done_tx, done_rx) = mpsc::channel();
+        if writer.send(WriterMessage::Flush(done_tx)).is_ok() {
+            let _ = done_rx.recv();
+        }
+    }
+
+    /// 最近的报文，按时间顺序，`limit` 为空时返回全部
+    pub fn snapshot(&self, limit: Option<usize>) -> Vec<WireRecord> {
+        let state = self.state.lock().unwrap();
+        let skip = limit.map_or(0, |n| state.records.len().saturating_sub(n));
+        state.records.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn stats(&self) -> WireLogStats {
+        let state = self.state.lock().unwrap();
+        WireLogStats {
+            buffered: state.records.len(),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+            ..state.stats.clone()
+        }
+    }
+}
+
+impl WireLogState {
+    /// 写线程，首次写文件时启动
+    fn writer(&mut self, write_errors: &Arc<AtomicU64>) -> Option<&mpsc::Sender<WriterMessage>> {
+        if self.writer.is_none() {
+            let (tx, rx) = mpsc::channel();
+            let dir = self.config.dir.clone();
+            let write_errors = write_errors.clone();
+            let spawned = std::thread::Builder::new()
+                .name("ctp-wire-log".to_string())
+                .spawn(move || run_writer(dir, rx, write_errors));
+            match spawned {
+                Ok(_) => self.writer = Some(tx),
+                Err(e) => tracing::warn!("启动报文日志写线程失败: {}", e),
+            }
+        }
+        self.writer.as_ref()
+    }
+}
+
+/// 按自然日追加写入 `wire_YYYYMMDD.jsonl`，发送端全部释放后退出
+fn run_writer(dir: PathBuf, rx: mpsc::Receiver<WriterMessage>, write_errors: Arc<AtomicU64>) {
+    let mut file: Option<(NaiveDate, std::fs::File)> = None;
+    for message in rx {
+        match message {
+            WriterMessage::Record(record) => {
+                if let Err(e) = append(&dir, &mut file, &record) {
+                    write_errors.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("写入报文日志失败: {}", e);
+                }
+            }
+            WriterMessage::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+fn append(dir: &Path, file: &mut Option<(NaiveDate, std::fs::File)>, record: &WireRecord) -> Result<(), CtpError> {
+    let day = record.timestamp.date_naive();
+    if file.as_ref().is_none_or(|(d, _)| *d != day) {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("wire_{}.jsonl", day.format("%Y%m%d")));
+        *file = Some((day, std::fs::OpenOptions::new().create(true).append(true).open(path)?));
+    }
+    let line = serde_json::to_string(record).map_err(|e| CtpError::ConversionError(format!("序列化报文失败: {}", e)))?;
+    if let Some((_, file)) = file.as_mut() {
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// 按字段名遮盖敏感字段，递归处理嵌套结构
+fn redact(value: &mut Value, extra: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let lower = key.to_ascii_lowercase();
+                if SECRET_FIELDS.iter().any(|s| lower.contains(s)) || extra.iter().any(|e| e.eq_ignore_ascii_case(key)) {
+                    if !field.is_null() {
+                        *field = Value::String("***".to_string());
+                    }
+                } else if ACCOUNT_FIELDS.contains(&lower.as_str()) {
+                    if let Value::String(s) = field {
+                        *s = mask_account(s);
+                    }
+                } else {
+                    redact(field, extra);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, extra)),
+        _ => {}
+    }
+}
+
+fn mask_account(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..2].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(chars.len() - 4), tail)
+}
+
+/// 将 CTP 结构体的 `Debug` 输出转为 JSON
+///
+/// 生成的结构体只实现了 `Debug`，字符数组按 GB18030 解码为字符串，
+/// 非有限浮点数记为 null；无法解析时记为 [`UNPARSED_PAYLOAD`]，原文不保留，
+/// 以免字段名无法识别的密码随原文写出
+pub fn debug_to_json<T: Debug + ?Sized>(value: &T) -> Value {
+    let text = format!("{:?}", value);
+    let mut parser = DebugParser { input: text.as_bytes(), pos: 0 };
+    match parser.value() {
+        Some(parsed) if parser.at_end() => parsed,
+        _ => {
+            tracing::debug!("报文结构无法解析，载荷不记录");
+            Value::String(UNPARSED_PAYLOAD.to_string())
+        }
+    }
+}
+
+struct DebugParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl DebugParser<'_> {
+    fn at_end(&mut self) -> bool {
+        self.skip_ws();
+        self.pos >= self.input.len()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.input.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            b'[' => self.array(),
+            b'"' => self.string().map(Value::String),
+            b'\'' => self.char_literal(),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => {
+                let ident = self.ident()?;
+                match self.peek() {
+                    Some(b'{') => self.fields(),
+                    Some(b'(') => {
+                        // Some(x) 等元组形式只取内部值
+                        self.pos += 1;
+                        let inner = self.value()?;
+                        self.eat(b')').then_some(inner)
+                    }
+                    _ => Some(match ident.as_str() {
+                        "true" => Value::Bool(true),
+                        "false" => Value::Bool(false),
+                        "None" | "inf" | "NaN" => Value::Null,
+                        _ => Value::String(ident),
+                    }),
+                }
+            }
+        }
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        self.skip_ws();
+        let start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| String::from_utf8_lossy(&self.input[start..self.pos]).into_owned())
+    }
+
+    fn fields(&mut self) -> Option<Value> {
+        self.eat(b'{');
+        let mut map = Map::new();
+        loop {
+            if self.eat(b'}') {
+                return Some(Value::Object(map));
+            }
+            let key = self.ident()?;
+            if !self.eat(b':') {
+                return None;
+            }
+            map.insert(key, self.value()?);
+            if !self.eat(b',') && self.peek() != Some(b'}') {
+                return None;
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.eat(b'[');
+        let mut items = Vec::new();
+        loop {
+            if self.eat(b']') {
+                break;
+            }
+            items.push(self.value()?);
+            if !self.eat(b',') && self.peek() != Some(b']') {
+                return None;
+            }
+        }
+        // 字符数组（TThostFtdc*Type）转为字符串
+        let bytes: Option<Vec<i8>> = items
+            .iter()
+            .map(|v| v.as_i64().and_then(|n| i8::try_from(n).ok()))
+            .collect();
+        match bytes {
+            Some(bytes) if !bytes.is_empty() => Some(Value::String(ctp_string_to_string(&bytes).unwrap_or_else(|_| {
+                let raw: Vec<u8> = bytes.iter().take_while(|&&b| b != 0).map(|&b| b as u8).collect();
+                String::from_utf8_lossy(&raw).into_owned()
+            }))),
+            _ => Some(Value::Array(items)),
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.eat(b'"');
+        let start = self.pos;
+        let mut escaped = false;
+        while let Some(&b) = self.input.get(self.pos) {
+            self.pos += 1;
+            match b {
+                b'\\' if !escaped => escaped = true,
+                b'"' if !escaped => {
+                    let raw = String::from_utf8_lossy(&self.input[start..self.pos - 1]).into_owned();
+                    return Some(serde_json::from_str(&format!("\"{}\"", raw)).unwrap_or(raw));
+                }
+                _ => escaped = false,
+            }
+        }
+        None
+    }
+
+    fn char_literal(&mut self) -> Option<Value> {
+        self.eat(b'\'');
+        let start = self.pos;
+        while self.input.get(self.pos).is_some_and(|&b| b != b'\'') {
+            self.pos += if self.input[self.pos] == b'\\' { 2 } else { 1 };
+        }
+        let raw = String::from_utf8_lossy(self.input.get(start..self.pos)?).into_owned();
+        self.pos += 1;
+        Some(Value::String(raw))
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        self.skip_ws();
+        let start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.'))
+        {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos]).ok()?;
+        if let Ok(n) = text.parse::<i64>() {
+            return Some(Value::from(n));
+        }
+        let f: f64 = text.parse().ok()?;
+        Some(serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(non_snake_case)]
+    #[derive(Debug)]
+    struct ReqUserLogin {
+        BrokerID: [i8; 11],
+        UserID: [i8; 16],
+        Password: [i8; 41],
+        LimitPrice: f64,
+        VolumeTotalOriginal: i32,
+    }
+
+    fn chars<const N: usize>(value: &str) -> [i8; N] {
+        let mut out = [0i8; N];
+        for (dst, src) in out.iter_mut().zip(value.bytes()) {
+            *dst = src as i8;
+        }
+        out
+    }
+
+    fn login() -> ReqUserLogin {
+        ReqUserLogin {
+            BrokerID: chars("9999"),
+            UserID: chars("20240518"),
+            Password: chars("secret!"),
+            LimitPrice: f64::MAX,
+            VolumeTotalOriginal: 3,
+        }
+    }
+
+    #[test]
+    fn test_requests_are_masked_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = WireLogger::new(WireLogConfig {
+            enabled: true,
+            dir: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        logger.request("ReqUserLogin", 7, &login());
+        logger.callback::<ReqUserLogin>("OnFrontDisconnected", None, None, None);
+        logger.flush();
+
+        let records = logger.snapshot(None);
+        assert_eq!(records.len(), 2);
+        let payload = &records[0].payload;
+        assert_eq!(payload["BrokerID"], "9999");
+        assert_eq!(payload["UserID"], "20****18");
+        assert_eq!(payload["Password"], "***");
+        assert_eq!(payload["VolumeTotalOriginal"], 3);
+        assert!(payload["LimitPrice"].is_number());
+        assert_eq!(records[0].request_id, Some(7));
+        assert!(records[1].payload["data"].is_null());
+
+        let file = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+        let content = std::fs::read_to_string(file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("secret!"));
+    }
+
+    /// Debug 输出形如 `Login { Password: secret!; .. }`，解析器无法处理
+    struct Unparseable;
+
+    impl Debug for Unparseable {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Login {{ Password: [115, 101, 99]; UserID: 20240518 }}")
+        }
+    }
+
+    #[test]
+    fn test_unparseable_payload_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = WireLogger::new(WireLogConfig {
+            enabled: true,
+            dir: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        logger.request("ReqUserLogin", 1, &Unparseable);
+        logger.callback("OnRspUserLogin", Some(1), Some(&Unparseable), None);
+        logger.flush();
+
+        let records = logger.snapshot(None);
+        assert_eq!(records[0].payload, UNPARSED_PAYLOAD);
+        assert_eq!(records[1].payload["data"], UNPARSED_PAYLOAD);
+        let file = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+        let content = std::fs::read_to_string(file).unwrap();
+        assert!(!content.contains("115, 101, 99"));
+        assert!(!content.contains("20240518"));
+    }
+
+    #[test]
+    fn test_market_data_sampled_under_load() {
+        let logger = WireLogger::new(WireLogConfig {
+            enabled: true,
+            write_file: false,
+            sample_above_per_second: 10,
+            sample_every: 5,
+            ..Default::default()
+        });
+        for _ in 0..60 {
+            logger.callback("OnRtnDepthMarketData", None, Some(&login()), None);
+        }
+        logger.callback("OnRtnOrder", None, Some(&login()), None);
+        logger.request("ReqOrderInsert", 8, &login());
+
+        let stats = logger.stats();
+        assert_eq!(stats.recorded, 10 + 10 + 2);
+        assert_eq!(stats.sampled_out, 40);
+        let names: Vec<String> = logger.snapshot(Some(2)).into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["OnRtnOrder", "ReqOrderInsert"]);
+
+        let disabled = WireLogger::default();
+        disabled.request("ReqOrderInsert", 1, &login());
+        assert_eq!(disabled.stats().recorded, 0);
+    }
+}
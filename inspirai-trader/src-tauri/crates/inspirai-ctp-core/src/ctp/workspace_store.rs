@@ -0,0 +1,290 @@
+use crate::ctp::CtpError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 默认工作区快照目录
+pub const DEFAULT_WORKSPACE_DIR: &str = "./data/workspaces";
+/// 每个快照默认保留的版本数
+pub const DEFAULT_WORKSPACE_VERSIONS: usize = 20;
+/// 未指定配置档时使用的配置档
+pub const DEFAULT_WORKSPACE_PROFILE: &str = "default";
+
+/// 工作区快照，`state` 为前端自定义的 JSON，后端不解析
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    pub profile: String,
+    pub name: String,
+    pub version: u32,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub state: serde_json::Value,
+}
+
+/// 快照版本信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceVersion {
+    pub version: u32,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub size_bytes: u64,
+}
+
+/// 快照概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSummary {
+    pub name: String,
+    pub latest_version: u32,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub version_count: usize,
+}
+
+/// 界面工作区快照存储
+///
+/// 按 `配置档/快照名/v版本号.json` 保存前端布局、自选、图表等状态，
+/// 每次保存生成新版本并只保留最近若干个；保存时可携带期望的当前版本，
+/// 不一致时拒绝写入，避免多个窗口互相覆盖
+pub struct WorkspaceStore {
+    dir: PathBuf,
+    max_versions: usize,
+}
+
+impl WorkspaceStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_versions: DEFAULT_WORKSPACE_VERSIONS,
+        }
+    }
+
+    pub fn with_max_versions(mut self, max_versions: usize) -> Self {
+        self.max_versions = max_versions.max(1);
+        self
+    }
+
+    /// 保存新版本；`expected_version` 为 Some 时须与当前最新版本一致（0 表示尚不存在）
+    pub fn save(
+        &self,
+        profile: &str,
+        name: &str,
+        state: serde_json::Value,
+        expected_version: Option<u32>,
+    ) -> Result<WorkspaceSnapshot, CtpError> {
+        let dir = self.snapshot_dir(profile, name)?;
+        let versions = self.version_numbers(&dir)?;
+        let latest = versions.last().copied().unwrap_or(0);
+        if let Some(expected) = expected_version {
+            if expected != latest {
+                return Err(CtpError::StateError(format!(
+                    "工作区 {} 已被更新到版本 {}，期望版本 {}",
+                    name, latest, expected
+                )));
+            }
+        }
+
+        let snapshot = WorkspaceSnapshot {
+            profile: profile.to_string(),
+            name: name.to_string(),
+            version: latest + 1,
+            saved_at: chrono::Utc::now(),
+            state,
+        };
+        std::fs::create_dir_all(&dir)?;
+        let content = serde_json::to_vec_pretty(&snapshot)
+            .map_err(|e| CtpError::ConversionError(format!("序列化工作区快照失败: {}", e)))?;
+        let path = version_path(&dir, snapshot.version);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)?;
+
+        let excess = (versions.len() + 1).saturating_sub(self.max_versions);
+        for version in versions.iter().take(excess) {
+            if let Err(e) = std::fs::remove_file(version_path(&dir, *version)) {
+                tracing::warn!("清理工作区 {} 旧版本 {} 失败: {}", name, version, e);
+            }
+        }
+        tracing::debug!("工作区快照已保存: {}/{} v{}", profile, name, snapshot.version);
+        Ok(snapshot)
+    }
+
+    /// 读取指定版本，未指定时读取最新版本
+    pub fn load(&self, profile: &str, name: &str, version: Option<u32>) -> Result<Option<WorkspaceSnapshot>, CtpError> {
+        let dir = self.snapshot_dir(profile, name)?;
+        let version = match version {
+            Some(version) => version,
+            None => match self.version_numbers(&dir)?.last() {
+                Some(latest) => *latest,
+                None => return Ok(None),
+            },
+        };
+        let path = version_path(&dir, version);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read(path)?;
+        serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|e| CtpError::ConversionError(format!("解析工作区快照失败: {}", e)))
+    }
+
+    /// 配置档下的所有快照，按名称排序
+    pub fn list(&self, profile: &str) -> Result<Vec<WorkspaceSummary>, CtpError> {
+        let profile_dir = self.dir.join(validate_segment(profile, "配置档")?);
+        if !profile_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut summaries = Vec::new();
+        for entry in std::fs::read_dir(&profile_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let versions = self.versions(profile, &name)?;
+            if let Some(latest) = versions.last() {
+                summaries.push(WorkspaceSummary {
+                    name,
+                    latest_version: latest.version,
+                    saved_at: latest.saved_at,
+                    version_count: versions.len(),
+                });
+            }
+        }
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(summaries)
+    }
+
+    /// 快照的所有保留版本，按版本号升序
+    pub fn versions(&self, profile: &str, name: &str) -> Result<Vec<WorkspaceVersion>, CtpError> {
+        let dir = self.snapshot_dir(profile, name)?;
+        let mut versions = Vec::new();
+        for version in self.version_numbers(&dir)? {
+            let path = version_path(&dir, version);
+            let metadata = std::fs::metadata(&path)?;
+            let saved_at = metadata
+                .modified()
+                .map(chrono::DateTime::<chrono::Utc>::from)
+                .unwrap_or_else(|_| chrono::Utc::now());
+            versions.push(WorkspaceVersion {
+                version,
+                saved_at,
+                size_bytes: metadata.len(),
+            });
+        }
+        Ok(versions)
+    }
+
+    /// 删除快照的所有版本，返回是否存在
+    pub fn delete(&self, profile: &str, name: &str) -> Result<bool, CtpError> {
+        let dir = self.snapshot_dir(profile, name)?;
+        if !dir.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(&dir)?;
+        Ok(true)
+    }
+
+    /// 已有快照的配置档
+    pub fn profiles(&self) -> Result<Vec<String>, CtpError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut profiles = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                profiles.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        profiles.sort();
+        Ok(profiles)
+    }
+
+    fn snapshot_dir(&self, profile: &str, name: &str) -> Result<PathBuf, CtpError> {
+        Ok(self
+            .dir
+            .join(validate_segment(profile, "配置档")?)
+            .join(validate_segment(name, "快照名")?))
+    }
+
+    fn version_numbers(&self, dir: &Path) -> Result<Vec<u32>, CtpError> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut versions: Vec<u32> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.strip_prefix('v')?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        versions.sort_unstable();
+        Ok(versions)
+    }
+}
+
+fn version_path(dir: &Path, version: u32) -> PathBuf {
+    dir.join(format!("v{:06}.json", version))
+}
+
+/// 配置档和快照名直接作为目录名，不允许路径分隔符和以点开头
+fn validate_segment<'a>(value: &'a str, label: &str) -> Result<&'a str, CtpError> {
+    let valid = !value.trim().is_empty()
+        && value.len() <= 64
+        && !value.starts_with('.')
+        && value.chars().all(|c| !c.is_control() && !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'));
+    if valid {
+        Ok(value)
+    } else {
+        Err(CtpError::InvalidParameter(format!("{}无效: {:?}", label, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_versioning_and_conflict() {
+        let dir = TempDir::new().unwrap();
+        let store = WorkspaceStore::new(dir.path()).with_max_versions(2);
+
+        let first = store.save("alice", "盯盘", serde_json::json!({ "charts": ["rb2501"] }), Some(0)).unwrap();
+        assert_eq!(first.version, 1);
+        // 基于过期版本保存被拒绝
+        assert!(store.save("alice", "盯盘", serde_json::json!({}), Some(0)).is_err());
+        store.save("alice", "盯盘", serde_json::json!({ "charts": ["rb2501", "cu2501"] }), Some(1)).unwrap();
+        store.save("alice", "盯盘", serde_json::json!({ "charts": [] }), None).unwrap();
+
+        // 只保留最近两个版本
+        let versions: Vec<u32> = store.versions("alice", "盯盘").unwrap().iter().map(|v| v.version).collect();
+        assert_eq!(versions, vec![2, 3]);
+        assert!(store.load("alice", "盯盘", Some(1)).unwrap().is_none());
+        let latest = store.load("alice", "盯盘", None).unwrap().unwrap();
+        assert_eq!(latest.version, 3);
+        assert_eq!(latest.state, serde_json::json!({ "charts": [] }));
+        let previous = store.load("alice", "盯盘", Some(2)).unwrap().unwrap();
+        assert_eq!(previous.state["charts"][1], "cu2501");
+    }
+
+    #[test]
+    fn test_profile_scoping_and_invalid_names() {
+        let dir = TempDir::new().unwrap();
+        let store = WorkspaceStore::new(dir.path());
+        store.save("alice", "layout", serde_json::json!(1), None).unwrap();
+        store.save("bob", "layout", serde_json::json!(2), None).unwrap();
+        store.save("bob", "night", serde_json::json!(3), None).unwrap();
+
+        assert_eq!(store.profiles().unwrap(), vec!["alice", "bob"]);
+        assert_eq!(store.list("alice").unwrap().len(), 1);
+        let bob: Vec<String> = store.list("bob").unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(bob, vec!["layout", "night"]);
+        assert_eq!(store.load("alice", "layout", None).unwrap().unwrap().state, serde_json::json!(1));
+        assert!(store.list("carol").unwrap().is_empty());
+
+        assert!(store.delete("bob", "night").unwrap());
+        assert!(!store.delete("bob", "night").unwrap());
+
+        assert!(store.save("../etc", "x", serde_json::json!(null), None).is_err());
+        assert!(store.save("alice", "a/b", serde_json::json!(null), None).is_err());
+        assert!(store.load("alice", "..", None).is_err());
+    }
+}
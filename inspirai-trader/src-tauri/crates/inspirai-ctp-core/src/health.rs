@@ -0,0 +1,609 @@
+// 应用健康检查
+// 汇总 CTP 连接、事件通道、日志、存储写入和后台任务状态，按可配置阈值给出整体状态
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ctp::{ClientState, EventLag, SessionHealth};
+
+/// 健康等级，按严重程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HealthLevel {
+    Healthy,
+    /// 可用但需要关注
+    Degraded,
+    Unhealthy,
+}
+
+/// 两级阈值：达到 `degraded` 为降级，达到 `unhealthy` 为异常
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Threshold {
+    pub degraded: u64,
+    pub unhealthy: u64,
+}
+
+impl Threshold {
+    pub const fn new(degraded: u64, unhealthy: u64) -> Self {
+        Self { degraded, unhealthy }
+    }
+
+    pub fn level(&self, value: u64) -> HealthLevel {
+        if value >= self.unhealthy {
+            HealthLevel::Unhealthy
+        } else if value >= self.degraded {
+            HealthLevel::Degraded
+        } else {
+            HealthLevel::Healthy
+        }
+    }
+}
+
+/// 健康检查阈值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthThresholds {
+    /// 事件通道积压条数
+    pub event_backlog: Threshold,
+    /// 日志写入队列长度
+    pub log_queue: Threshold,
+    /// 日志目录占用字节数
+    pub log_disk_bytes: Threshold,
+    /// 日志写入错误数
+    pub log_errors: Threshold,
+    /// 各存储累计写入错误数
+    pub storage_errors: Threshold,
+    /// 周期任务超过期望间隔多少倍未心跳视为停滞
+    pub task_stale_factor: u32,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        Self {
+            event_backlog: Threshold::new(1_000, 10_000),
+            log_queue: Threshold::new(1_000, 10_000),
+            log_disk_bytes: Threshold::new(2 * GIB, 10 * GIB),
+            log_errors: Threshold::new(10, 100),
+            storage_errors: Threshold::new(1, 10),
+            task_stale_factor: 3,
+        }
+    }
+}
+
+impl HealthThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        let thresholds = [
+            ("event_backlog", self.event_backlog),
+            ("log_queue", self.log_queue),
+            ("log_disk_bytes", self.log_disk_bytes),
+            ("log_errors", self.log_errors),
+            ("storage_errors", self.storage_errors),
+        ];
+        for (name, threshold) in thresholds {
+            if threshold.degraded == 0 || threshold.degraded > threshold.unhealthy {
+                return Err(format!("{} 阈值无效：要求 0 < degraded <= unhealthy", name));
+            }
+        }
+        if self.task_stale_factor == 0 {
+            return Err("task_stale_factor 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 单个存储的写入错误统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageErrorStats {
+    pub store: String,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+static STORAGE_ERRORS: OnceLock<Mutex<HashMap<String, StorageErrorStats>>> = OnceLock::new();
+
+/// 记录一次存储写入失败（时间线、回合交易、操作录制等），计入健康检查
+pub fn record_storage_error(store: &str, error: impl std::fmt::Display) {
+    let mut errors = STORAGE_ERRORS.get_or_init(Default::default).lock().unwrap();
+    let stats = errors.entry(store.to_string()).or_insert_with(|| StorageErrorStats {
+        store: store.to_string(),
+        errors: 0,
+        last_error: None,
+        last_error_at: None,
+    });
+    stats.errors += 1;
+    stats.last_error = Some(error.to_string());
+    stats.last_error_at = Some(Utc::now());
+}
+
+/// 各存储的写入错误统计，按存储名排序
+pub fn storage_errors() -> Vec<StorageErrorStats> {
+    let Some(errors) = STORAGE_ERRORS.get() else {
+        return Vec::new();
+    };
+    let mut stats: Vec<_> = errors.lock().unwrap().values().cloned().collect();
+    stats.sort_by(|a, b| a.store.cmp(&b.store));
+    stats
+}
+
+/// 后台任务运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskLifecycle {
+    Running,
+    /// 正常结束
+    Finished,
+    /// 未正常结束（panic 或提前退出）
+    Died,
+}
+
+/// 后台任务存活信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskLivenessInfo {
+    pub name: String,
+    pub state: TaskLifecycle,
+    pub started_at: DateTime<Utc>,
+    pub last_beat: DateTime<Utc>,
+    /// 周期任务的期望心跳间隔，事件驱动的任务为空
+    pub expected_interval_ms: Option<u64>,
+    #[serde(skip)]
+    generation: u64,
+}
+
+#[derive(Debug, Default)]
+struct LivenessInner {
+    tasks: HashMap<String, TaskLivenessInfo>,
+    next_generation: u64,
+}
+
+/// 后台任务存活登记
+///
+/// 事件桥、实例心跳、死人开关等常驻任务启动时登记，循环中心跳；
+/// 任务未调用 [`TaskBeat::finish`] 就退出时标记为 `Died`
+#[derive(Debug, Clone, Default)]
+pub struct TaskLiveness {
+    inner: Arc<Mutex<LivenessInner>>,
+}
+
+impl TaskLiveness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记任务，同名任务（如重连后新的事件桥）替换旧记录
+    pub fn register(&self, name: &str, expected_interval: Option<Duration>) -> TaskBeat {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_generation += 1;
+        let generation = inner.next_generation;
+        let now = Utc::now();
+        inner.tasks.insert(
+            name.to_string(),
+            TaskLivenessInfo {
+                name: name.to_string(),
+                state: TaskLifecycle::Running,
+                started_at: now,
+                last_beat: now,
+                expected_interval_ms: expected_interval.map(|d| d.as_millis() as u64),
+                generation,
+            },
+        );
+        TaskBeat {
+            liveness: self.clone(),
+            name: name.to_string(),
+            generation,
+            finished: false,
+        }
+    }
+
+    pub fn list(&self) -> Vec<TaskLivenessInfo> {
+        let mut tasks: Vec<_> = self.inner.lock().unwrap().tasks.values().cloned().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        tasks
+    }
+
+    fn update(&self, name: &str, generation: u64, update: impl FnOnce(&mut TaskLivenessInfo)) {
+        let mut inner = self.inner.lock().unwrap();
+        // 已被同名新任务替换的旧任务不再更新
+        if let Some(task) = inner.tasks.get_mut(name).filter(|t| t.generation == generation) {
+            update(task);
+        }
+    }
+}
+
+/// 任务心跳句柄，drop 时若未正常结束则标记为 `Died`
+pub struct TaskBeat {
+    liveness: TaskLiveness,
+    name: String,
+    generation: u64,
+    finished: bool,
+}
+
+impl TaskBeat {
+    pub fn beat(&self) {
+        self.liveness.update(&self.name, self.generation, |task| task.last_beat = Utc::now());
+    }
+
+    /// 任务正常结束
+    pub fn finish(mut self) {
+        self.finished = true;
+        self.liveness.update(&self.name, self.generation, |task| task.state = TaskLifecycle::Finished);
+    }
+}
+
+impl Drop for TaskBeat {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.liveness.update(&self.name, self.generation, |task| task.state = TaskLifecycle::Died);
+        }
+    }
+}
+
+/// 单个 CTP 账户的连接快照
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    pub account_id: String,
+    pub broker_id: String,
+    pub state: ClientState,
+    pub session: SessionHealth,
+}
+
+/// 日志系统快照，未初始化时不提供
+#[derive(Debug, Clone, Default)]
+pub struct LoggingSnapshot {
+    pub queue_size: usize,
+    pub disk_usage_bytes: u64,
+    pub dropped_total: u64,
+    pub error_count: u64,
+}
+
+/// 健康检查的输入
+#[derive(Debug, Clone, Default)]
+pub struct HealthInputs {
+    pub accounts: Vec<AccountSnapshot>,
+    pub event_lag: Option<EventLag>,
+    pub logging: Option<LoggingSnapshot>,
+    pub storage: Vec<StorageErrorStats>,
+    pub tasks: Vec<TaskLivenessInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtpAccountHealth {
+    pub account_id: String,
+    pub broker_id: String,
+    pub state: ClientState,
+    pub session: SessionHealth,
+    pub level: HealthLevel,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventChannelHealth {
+    /// 未建立连接时为空
+    pub lag: Option<EventLag>,
+    pub level: HealthLevel,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingHealth {
+    pub initialized: bool,
+    pub queue_size: usize,
+    pub disk_usage_bytes: u64,
+    pub dropped_total: u64,
+    pub error_count: u64,
+    pub level: HealthLevel,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageHealth {
+    pub stores: Vec<StorageErrorStats>,
+    pub total_errors: u64,
+    pub level: HealthLevel,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHealth {
+    #[serde(flatten)]
+    pub task: TaskLivenessInfo,
+    pub level: HealthLevel,
+    pub issues: Vec<String>,
+}
+
+/// 应用整体健康文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppHealth {
+    /// 各组件中最差的等级
+    pub status: HealthLevel,
+    pub checked_at: DateTime<Utc>,
+    pub ctp: Vec<CtpAccountHealth>,
+    pub events: EventChannelHealth,
+    pub logging: LoggingHealth,
+    pub storage: StorageHealth,
+    pub tasks: Vec<TaskHealth>,
+    pub thresholds: HealthThresholds,
+}
+
+impl AppHealth {
+    /// 按阈值评估各组件并汇总整体状态
+    pub fn assess(inputs: HealthInputs, thresholds: &HealthThresholds) -> Self {
+        let ctp: Vec<_> = inputs.accounts.into_iter().map(assess_account).collect();
+        let events = assess_events(inputs.event_lag, thresholds);
+        let logging = assess_logging(inputs.logging, thresholds);
+        let storage = assess_storage(inputs.storage, thresholds);
+        let now = Utc::now();
+        let tasks: Vec<_> = inputs
+            .tasks
+            .into_iter()
+            .map(|task| assess_task(task, thresholds, now))
+            .collect();
+
+        let status = ctp
+            .iter()
+            .map(|a| a.level)
+            .chain([events.level, logging.level, storage.level])
+            .chain(tasks.iter().map(|t| t.level))
+            .max()
+            .unwrap_or(HealthLevel::Healthy);
+
+        Self {
+            status,
+            checked_at: now,
+            ctp,
+            events,
+            logging,
+            storage,
+            tasks,
+            thresholds: thresholds.clone(),
+        }
+    }
+}
+
+fn assess_account(account: AccountSnapshot) -> CtpAccountHealth {
+    let mut issues = Vec::new();
+    let level = match &account.state {
+        ClientState::Error(message) => {
+            issues.push(format!("客户端错误: {}", message));
+            HealthLevel::Unhealthy
+        }
+        ClientState::Disconnected => {
+            issues.push("连接已断开".to_string());
+            HealthLevel::Unhealthy
+        }
+        ClientState::Connecting | ClientState::Connected | ClientState::LoggingIn => {
+            issues.push("尚未完成登录".to_string());
+            HealthLevel::Degraded
+        }
+        ClientState::LoggedIn if account.session.is_degraded() => {
+            issues.push(match &account.session.last_trader_error {
+                Some(error) => format!("交易端不可用，仅行情运行: {}", error),
+                None => "交易端不可用，仅行情运行".to_string(),
+            });
+            HealthLevel::Degraded
+        }
+        ClientState::LoggedIn => HealthLevel::Healthy,
+    };
+    CtpAccountHealth {
+        account_id: account.account_id,
+        broker_id: account.broker_id,
+        state: account.state,
+        session: account.session,
+        level,
+        issues,
+    }
+}
+
+fn assess_events(lag: Option<EventLag>, thresholds: &HealthThresholds) -> EventChannelHealth {
+    let mut issues = Vec::new();
+    let level = match &lag {
+        Some(lag) => {
+            let level = thresholds.event_backlog.level(lag.backlog as u64);
+            if level != HealthLevel::Healthy {
+                issues.push(format!("事件通道积压 {} 条", lag.backlog));
+            }
+            level
+        }
+        None => HealthLevel::Healthy,
+    };
+    EventChannelHealth { lag, level, issues }
+}
+
+fn assess_logging(logging: Option<LoggingSnapshot>, thresholds: &HealthThresholds) -> LoggingHealth {
+    let Some(snapshot) = logging else {
+        return LoggingHealth {
+            initialized: false,
+            queue_size: 0,
+            disk_usage_bytes: 0,
+            dropped_total: 0,
+            error_count: 0,
+            level: HealthLevel::Degraded,
+            issues: vec!["日志系统未初始化，仅输出到控制台".to_string()],
+        };
+    };
+
+    let checks = [
+        (thresholds.log_queue.level(snapshot.queue_size as u64), format!("日志队列积压 {} 条", snapshot.queue_size)),
+        (
+            thresholds.log_disk_bytes.level(snapshot.disk_usage_bytes),
+            format!("日志占用磁盘 {} MB", snapshot.disk_usage_bytes / (1024 * 1024)),
+        ),
+        (thresholds.log_errors.level(snapshot.error_count), format!("日志写入错误 {} 次", snapshot.error_count)),
+    ];
+    let mut level = HealthLevel::Healthy;
+    let mut issues = Vec::new();
+    for (check_level, issue) in checks {
+        if check_level != HealthLevel::Healthy {
+            level = level.max(check_level);
+            issues.push(issue);
+        }
+    }
+    if snapshot.dropped_total > 0 {
+        issues.push(format!("已丢弃 {} 条日志", snapshot.dropped_total));
+    }
+
+    LoggingHealth {
+        initialized: true,
+        queue_size: snapshot.queue_size,
+        disk_usage_bytes: snapshot.disk_usage_bytes,
+        dropped_total: snapshot.dropped_total,
+        error_count: snapshot.error_count,
+        level,
+        issues,
+    }
+}
+
+fn assess_storage(stores: Vec<StorageErrorStats>, thresholds: &HealthThresholds) -> StorageHealth {
+    let mut level = HealthLevel::Healthy;
+    let mut issues = Vec::new();
+    for store in &stores {
+        let store_level = thresholds.storage_errors.level(store.errors);
+        if store_level != HealthLevel::Healthy {
+            level = level.max(store_level);
+            issues.push(format!(
+                "{} 写入失败 {} 次，最近一次: {}",
+                store.store,
+                store.errors,
+                store.last_error.as_deref().unwrap_or("-")
+            ));
+        }
+    }
+    StorageHealth {
+        total_errors: stores.iter().map(|s| s.errors).sum(),
+        stores,
+        level,
+        issues,
+    }
+}
+
+fn assess_task(task: TaskLivenessInfo, thresholds: &HealthThresholds, now: DateTime<Utc>) -> TaskHealth {
+    let mut issues = Vec::new();
+    let level = match task.state {
+        TaskLifecycle::Died => {
+            issues.push("任务异常退出".to_string());
+            HealthLevel::Unhealthy
+        }
+        TaskLifecycle::Finished => HealthLevel::Healthy,
+        TaskLifecycle::Running => {
+            let silent_ms = (now - task.last_beat).num_milliseconds().max(0) as u64;
+            match task.expected_interval_ms {
+                Some(interval) if silent_ms > interval * thresholds.task_stale_factor as u64 => {
+                    issues.push(format!("已 {} 秒未心跳", silent_ms / 1000));
+                    HealthLevel::Degraded
+                }
+                _ => HealthLevel::Healthy,
+            }
+        }
+    };
+    TaskHealth { task, level, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctp::SideStatus;
+
+    fn logged_in(account_id: &str, td: SideStatus) -> AccountSnapshot {
+        let mut session = SessionHealth::new();
+        session.md = SideStatus::Ready;
+        session.td = td;
+        AccountSnapshot {
+            account_id: account_id.to_string(),
+            broker_id: "9999".to_string(),
+            state: ClientState::LoggedIn,
+            session,
+        }
+    }
+
+    #[test]
+    fn test_overall_status_is_worst_component() {
+        let thresholds = HealthThresholds::default();
+        let healthy = HealthInputs {
+            accounts: vec![logged_in("100001", SideStatus::Ready)],
+            event_lag: Some(EventLag::default()),
+            logging: Some(LoggingSnapshot::default()),
+            ..HealthInputs::default()
+        };
+        assert_eq!(AppHealth::assess(healthy.clone(), &thresholds).status, HealthLevel::Healthy);
+
+        let mut degraded = healthy.clone();
+        degraded.accounts.push(logged_in("100002", SideStatus::Failed("认证失败".to_string())));
+        let health = AppHealth::assess(degraded, &thresholds);
+        assert_eq!(health.status, HealthLevel::Degraded);
+        assert_eq!(health.ctp[1].level, HealthLevel::Degraded);
+
+        let mut backlog = healthy;
+        backlog.event_lag = Some(EventLag {
+            backlog: 20_000,
+            ..EventLag::default()
+        });
+        backlog.storage = vec![StorageErrorStats {
+            store: "timeline".to_string(),
+            errors: 2,
+            last_error: Some("磁盘已满".to_string()),
+            last_error_at: Some(Utc::now()),
+        }];
+        let health = AppHealth::assess(backlog, &thresholds);
+        assert_eq!(health.status, HealthLevel::Unhealthy);
+        assert_eq!(health.events.level, HealthLevel::Unhealthy);
+        assert_eq!(health.storage.level, HealthLevel::Degraded);
+        assert!(health.storage.issues[0].contains("磁盘已满"));
+
+        // 调高阈值后不再告警
+        let relaxed = HealthThresholds {
+            storage_errors: Threshold::new(5, 50),
+            ..thresholds
+        };
+        relaxed.validate().unwrap();
+        assert!(HealthThresholds {
+            log_queue: Threshold::new(10, 5),
+            ..HealthThresholds::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_task_liveness() {
+        let liveness = TaskLiveness::new();
+        let heartbeat = liveness.register("instance_heartbeat", Some(Duration::from_millis(1)));
+        let bridge = liveness.register("event_bridge", None);
+        heartbeat.beat();
+        bridge.finish();
+
+        // 同名重新登记后，旧句柄的退出不影响新记录
+        let watchdog = liveness.register("dead_man_watchdog", None);
+        let replacement = liveness.register("dead_man_watchdog", None);
+        drop(watchdog);
+        drop(heartbeat);
+
+        let tasks = liveness.list();
+        let states: Vec<_> = tasks.iter().map(|t| (t.name.as_str(), t.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                ("dead_man_watchdog", TaskLifecycle::Running),
+                ("event_bridge", TaskLifecycle::Finished),
+                ("instance_heartbeat", TaskLifecycle::Died),
+            ]
+        );
+
+        let health = AppHealth::assess(
+            HealthInputs {
+                tasks,
+                logging: Some(LoggingSnapshot::default()),
+                ..HealthInputs::default()
+            },
+            &HealthThresholds::default(),
+        );
+        assert_eq!(health.status, HealthLevel::Unhealthy);
+        assert_eq!(health.tasks[2].level, HealthLevel::Unhealthy);
+        drop(replacement);
+
+        record_storage_error("health_test_store", "写入失败");
+        assert!(storage_errors().iter().any(|s| s.store == "health_test_store" && s.errors == 1));
+    }
+}
@@ -0,0 +1,11 @@
+//! 交易核心库
+//!
+//! CTP 客户端、交易服务、行情存储和日志系统，不依赖 Tauri，
+//! 桌面应用、命令行工具和回测等无界面程序共用同一套实现
+
+// CTP 交易组件模块
+pub mod ctp;
+// 新的高级日志系统模块
+pub mod logging;
+// 应用健康检查
+pub mod health;
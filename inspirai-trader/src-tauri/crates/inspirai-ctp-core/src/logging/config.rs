@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
 use crate::ctp::config::Environment;
 use super::error::LogError;
+use super::router::RouteRule;
+use super::sampling::SamplingRule;
 
 /// 日志级别枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -122,6 +125,58 @@ impl std::fmt::Display for LogType {
     }
 }
 
+/// 单个日志类型的轮转与保留策略，未设置的项沿用全局配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogTypePolicy {
+    /// 最大文件大小 (字节)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// 最大文件数量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+    /// 保留天数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+    /// 是否启用压缩
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_enabled: Option<bool>,
+}
+
+impl LogTypePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+    
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+    
+    pub fn with_retention_days(mut self, retention_days: u32) -> Self {
+        self.retention_days = Some(retention_days);
+        self
+    }
+    
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression_enabled = Some(enabled);
+        self
+    }
+}
+
+/// 合并全局配置后某个日志类型实际生效的策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedLogPolicy {
+    pub max_file_size: u64,
+    pub max_files: usize,
+    pub retention_days: u32,
+    pub compression_enabled: bool,
+}
+
 /// 日志配置结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogConfig {
@@ -147,6 +202,15 @@ pub struct LogConfig {
     pub batch_size: usize,
     /// 刷新间隔
     pub flush_interval: Duration,
+    /// 按日志类型覆盖的轮转与保留策略
+    #[serde(default)]
+    pub type_policies: HashMap<LogType, LogTypePolicy>,
+    /// 高频调用点的采样与限流规则
+    #[serde(default)]
+    pub sampling_rules: Vec<SamplingRule>,
+    /// 声明式路由规则，按顺序匹配
+    #[serde(default)]
+    pub routing_rules: Vec<RouteRule>,
 }
 
 impl Default for LogConfig {
@@ -163,6 +227,9 @@ impl Default for LogConfig {
             async_buffer_size: 64 * 1024, // 64KB
             batch_size: 1000,
             flush_interval: Duration::from_millis(100),
+            type_policies: Self::production_type_policies(),
+            sampling_rules: Vec::new(),
+            routing_rules: Vec::new(),
         }
     }
 }
@@ -182,6 +249,9 @@ impl LogConfig {
             async_buffer_size: 32 * 1024, // 32KB
             batch_size: 500,
             flush_interval: Duration::from_millis(50), // 更快刷新用于调试
+            type_policies: Self::development_type_policies(),
+            sampling_rules: Vec::new(),
+            routing_rules: Vec::new(),
         }
     }
     
@@ -201,9 +271,51 @@ impl LogConfig {
             async_buffer_size: 64 * 1024, // 64KB
             batch_size: 1000,
             flush_interval: Duration::from_millis(100),
+            type_policies: Self::production_type_policies(),
+            sampling_rules: Vec::new(),
+            routing_rules: Vec::new(),
         })
     }
     
+    /// 开发环境的类型策略：交易和错误日志保留更久，便于回查
+    fn development_type_policies() -> HashMap<LogType, LogTypePolicy> {
+        HashMap::from([
+            (LogType::Trading, LogTypePolicy::new().with_retention_days(30)),
+            (LogType::Error, LogTypePolicy::new().with_retention_days(30)),
+        ])
+    }
+    
+    /// 生产环境的类型策略：交易日志留存一年供审计，行情和性能日志量大、保留较短
+    fn production_type_policies() -> HashMap<LogType, LogTypePolicy> {
+        HashMap::from([
+            (LogType::Trading, LogTypePolicy::new().with_retention_days(365).with_max_files(400)),
+            (LogType::Error, LogTypePolicy::new().with_retention_days(180)),
+            (LogType::App, LogTypePolicy::new().with_retention_days(30)),
+            (
+                LogType::MarketData,
+                LogTypePolicy::new().with_max_file_size(200 * 1024 * 1024).with_retention_days(14),
+            ),
+            (LogType::Performance, LogTypePolicy::new().with_retention_days(14)),
+        ])
+    }
+    
+    /// 设置某个日志类型的策略
+    pub fn with_type_policy(mut self, log_type: LogType, policy: LogTypePolicy) -> Self {
+        self.type_policies.insert(log_type, policy);
+        self
+    }
+    
+    /// 获取日志类型实际生效的策略
+    pub fn policy_for(&self, log_type: LogType) -> ResolvedLogPolicy {
+        let policy = self.type_policies.get(&log_type);
+        ResolvedLogPolicy {
+            max_file_size: policy.and_then(|p| p.max_file_size).unwrap_or(self.max_file_size),
+            max_files: policy.and_then(|p| p.max_files).unwrap_or(self.max_files),
+            retention_days: policy.and_then(|p| p.retention_days).unwrap_or(self.retention_days),
+            compression_enabled: policy.and_then(|p| p.compression_enabled).unwrap_or(self.compression_enabled),
+        }
+    }
+    
     /// 根据环境创建配置
     pub fn for_environment(env: Environment) -> Result<Self, LogError> {
         match env {
@@ -278,6 +390,33 @@ impl LogConfig {
             });
         }
         
+        for rule in &self.sampling_rules {
+            rule.validate()?;
+        }
+        
+        for rule in &self.routing_rules {
+            rule.validate()?;
+        }
+        
+        // 验证各类型的策略覆盖
+        for (log_type, policy) in &self.type_policies {
+            if policy.max_file_size.is_some_and(|size| size < 1024 * 1024) {
+                return Err(LogError::InvalidConfig {
+                    field: format!("{} 的 max_file_size 不能小于 1MB", log_type),
+                });
+            }
+            if policy.max_files == Some(0) {
+                return Err(LogError::InvalidConfig {
+                    field: format!("{} 的 max_files 必须大于 0", log_type),
+                });
+            }
+            if policy.retention_days == Some(0) {
+                return Err(LogError::InvalidConfig {
+                    field: format!("{} 的 retention_days 必须大于 0", log_type),
+                });
+            }
+        }
+        
         Ok(())
     }
     
@@ -427,6 +566,35 @@ mod tests {
         assert!(config.validate().is_err());
     }
     
+    #[test]
+    fn test_type_policy_overrides() {
+        let config = LogConfig::development()
+            .with_type_policy(LogType::MarketData, LogTypePolicy::new().with_compression(true).with_max_files(3));
+        
+        // 未覆盖的类型沿用全局配置
+        let app = config.policy_for(LogType::App);
+        assert_eq!(app.retention_days, 7);
+        assert!(!app.compression_enabled);
+        
+        assert_eq!(config.policy_for(LogType::Trading).retention_days, 30);
+        let market = config.policy_for(LogType::MarketData);
+        assert!(market.compression_enabled);
+        assert_eq!(market.max_files, 3);
+        assert_eq!(market.max_file_size, config.max_file_size);
+        
+        let production = LogConfig::default();
+        assert!(production.policy_for(LogType::Trading).retention_days > production.policy_for(LogType::App).retention_days);
+        
+        let invalid = config.with_type_policy(LogType::Error, LogTypePolicy::new().with_retention_days(0));
+        assert!(invalid.validate().is_err());
+        
+        // 旧配置文件没有 type_policies 字段
+        let mut value = serde_json::to_value(LogConfig::development()).unwrap();
+        value.as_object_mut().unwrap().remove("type_policies");
+        let parsed: LogConfig = serde_json::from_value(value).unwrap();
+        assert!(parsed.type_policies.is_empty());
+    }
+    
     #[test]
     fn test_log_config_env_overrides() {
         std::env::set_var("LOG_LEVEL", "ERROR");
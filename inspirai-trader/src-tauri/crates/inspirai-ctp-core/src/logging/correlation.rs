@@ -0,0 +1,91 @@
+//! 关联 ID
+//!
+//! 前端发起一次操作时生成关联 ID，后端命令在其作用域内执行，期间产生的日志
+//! 自动带上 `correlation_id` 字段。异步命令用 [`with_correlation`]，
+//! SPI 回调等同步代码用 [`enter_correlation`]。
+use std::cell::RefCell;
+use std::future::Future;
+
+/// 日志字段名
+pub const CORRELATION_FIELD: &str = "correlation_id";
+
+tokio::task_local! {
+    static TASK_CORRELATION: String;
+}
+
+thread_local! {
+    static THREAD_CORRELATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 生成新的关联 ID
+pub fn new_correlation_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// 当前任务或线程的关联 ID
+pub fn current_correlation_id() -> Option<String> {
+    TASK_CORRELATION
+        .try_with(Clone::clone)
+        .ok()
+        .or_else(|| THREAD_CORRELATION.with(|current| current.borrow().clone()))
+}
+
+/// 在关联 ID 作用域内执行异步操作，未提供时生成新的
+pub async fn with_correlation<F: Future>(correlation_id: Option<String>, future: F) -> F::Output {
+    let correlation_id = correlation_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(new_correlation_id);
+    TASK_CORRELATION.scope(correlation_id, future).await
+}
+
+/// 同步作用域守卫，drop 时恢复之前的关联 ID
+pub struct CorrelationGuard {
+    previous: Option<String>,
+}
+
+impl Drop for CorrelationGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        THREAD_CORRELATION.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// 在当前线程设置关联 ID，直到返回的守卫被 drop
+pub fn enter_correlation(correlation_id: String) -> CorrelationGuard {
+    let previous = THREAD_CORRELATION.with(|current| current.borrow_mut().replace(correlation_id));
+    CorrelationGuard { previous }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_task_scope() {
+        assert_eq!(current_correlation_id(), None);
+
+        let id = with_correlation(Some("ui-1".to_string()), async {
+            tokio::task::yield_now().await;
+            current_correlation_id()
+        })
+        .await;
+        assert_eq!(id.as_deref(), Some("ui-1"));
+
+        // 未提供时生成新的
+        let generated = with_correlation(None, async { current_correlation_id() }).await.unwrap();
+        assert_eq!(generated.len(), 32);
+        assert_eq!(current_correlation_id(), None);
+    }
+
+    #[test]
+    fn test_thread_guard_restores_previous() {
+        let outer = enter_correlation("outer".to_string());
+        {
+            let _inner = enter_correlation("inner".to_string());
+            assert_eq!(current_correlation_id().as_deref(), Some("inner"));
+        }
+        assert_eq!(current_correlation_id().as_deref(), Some("outer"));
+        drop(outer);
+        assert_eq!(current_correlation_id(), None);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::LogEntry;
+
+/// 默认保留的最近事件数
+pub const DEFAULT_FLIGHT_RECORDER_CAPACITY: usize = 2_000;
+
+/// 飞行记录器
+///
+/// 在内存中保留最近的日志事件，出问题时连同日志文件一起导出，
+/// 不受文件轮转和清理的影响
+#[derive(Debug, Clone)]
+pub struct FlightRecorder {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    capacity: usize,
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_FLIGHT_RECORDER_CAPACITY)
+    }
+}
+
+impl FlightRecorder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity.min(1024)))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn record(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// 当前保留的事件，按时间顺序
+    pub fn snapshot(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// 飞行记录层，置于采样层之后
+pub struct FlightRecorderLayer {
+    recorder: FlightRecorder,
+}
+
+impl FlightRecorderLayer {
+    pub fn new(recorder: FlightRecorder) -> Self {
+        Self { recorder }
+    }
+}
+
+impl<S> Layer<S> for FlightRecorderLayer
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        self.recorder.record(LogEntry::from_tracing_event(event, &ctx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_keeps_most_recent_events() {
+        let recorder = FlightRecorder::new(3);
+        let subscriber = tracing_subscriber::registry().with(FlightRecorderLayer::new(recorder.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::debug!(seq = i, "event {}", i);
+            }
+        });
+
+        let entries = recorder.snapshot();
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+        assert_eq!(entries[2].fields.get("seq"), Some(&4.into()));
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{config::LogLevel, context::LogContext, error::LogError, LogEntry};
+
+/// 前端日志的模块前缀，组件名拼在其后，如 `frontend::OrderPanel`
+pub const FRONTEND_MODULE: &str = "frontend";
+/// 消息最大长度（字符）
+pub const MAX_FRONTEND_MESSAGE_LEN: usize = 4 * 1024;
+/// 堆栈最大长度（字符）
+pub const MAX_FRONTEND_STACK_LEN: usize = 16 * 1024;
+
+/// 前端上报的日志事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendLogEvent {
+    pub level: LogLevel,
+    pub message: String,
+    /// 出错的组件或页面
+    #[serde(default)]
+    pub component: Option<String>,
+    /// JS 错误堆栈或 React 组件堆栈
+    #[serde(default)]
+    pub stack: Option<String>,
+    /// 前端会话 ID，每次加载页面生成
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// 前端记录的时间，缺省为接收时间
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// 其他附加字段
+    #[serde(default)]
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+impl FrontendLogEvent {
+    pub fn validate(&self) -> Result<(), LogError> {
+        if self.message.trim().is_empty() {
+            return Err(LogError::InvalidConfig {
+                field: "前端日志的 message 不能为空".to_string(),
+            });
+        }
+        if let Some(component) = &self.component {
+            // 组件名拼入模块路径，不能冒充后端模块
+            if component.contains("::") {
+                return Err(LogError::InvalidConfig {
+                    field: format!("无效的组件名: {}", component),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换为日志条目，超长的消息和堆栈会被截断
+    pub fn into_entry(self) -> LogEntry {
+        let module = match &self.component {
+            Some(component) if !component.is_empty() => format!("{}::{}", FRONTEND_MODULE, component),
+            _ => FRONTEND_MODULE.to_string(),
+        };
+
+        let mut fields = self.fields;
+        fields.insert("source".to_string(), FRONTEND_MODULE.into());
+        if let Some(component) = self.component {
+            fields.insert("component".to_string(), component.into());
+        }
+        if let Some(stack) = self.stack {
+            fields.insert("stack".to_string(), truncate(stack, MAX_FRONTEND_STACK_LEN).into());
+        }
+
+        LogEntry {
+            timestamp: self.timestamp.unwrap_or_else(Utc::now),
+            level: self.level,
+            context: LogContext::new(self.level, &module),
+            module,
+            thread_id: "webview".to_string(),
+            message: truncate(self.message, MAX_FRONTEND_MESSAGE_LEN),
+            request_id: None,
+            session_id: self.session_id,
+            fields,
+        }
+    }
+}
+
+fn truncate(mut text: String, max_chars: usize) -> String {
+    if let Some((index, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(index);
+        text.push_str("…(已截断)");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontend_event_into_entry() {
+        let event: FrontendLogEvent = serde_json::from_str(
+            r#"{"level":"Error","message":"Cannot read properties of undefined","component":"OrderPanel",
+                "stack":"TypeError: x\n    at OrderPanel.tsx:42","session_id":"s-1","fields":{"url":"/trading"}}"#,
+        )
+        .unwrap();
+        event.validate().unwrap();
+
+        let entry = event.into_entry();
+        assert_eq!(entry.module, "frontend::OrderPanel");
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.session_id.as_deref(), Some("s-1"));
+        assert_eq!(entry.fields.get("source"), Some(&"frontend".into()));
+        assert_eq!(entry.fields.get("component"), Some(&"OrderPanel".into()));
+        assert_eq!(entry.fields.get("url"), Some(&"/trading".into()));
+        assert!(entry.fields.get("stack").unwrap().as_str().unwrap().contains("OrderPanel.tsx:42"));
+    }
+
+    #[test]
+    fn test_validation_and_truncation() {
+        let mut event = FrontendLogEvent {
+            level: LogLevel::Warn,
+            message: "  ".to_string(),
+            component: None,
+            stack: None,
+            session_id: None,
+            timestamp: None,
+            fields: HashMap::new(),
+        };
+        assert!(event.validate().is_err());
+
+        event.message = "告".repeat(MAX_FRONTEND_MESSAGE_LEN + 10);
+        event.component = Some("ctp::client".to_string());
+        assert!(event.validate().is_err());
+
+        event.component = None;
+        event.validate().unwrap();
+        let entry = event.into_entry();
+        assert_eq!(entry.module, FRONTEND_MODULE);
+        assert!(entry.message.ends_with("…(已截断)"));
+        assert_eq!(entry.message.chars().count(), MAX_FRONTEND_MESSAGE_LEN + "…(已截断)".chars().count());
+    }
+}
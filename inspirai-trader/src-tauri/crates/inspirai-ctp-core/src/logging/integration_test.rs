@@ -24,6 +24,9 @@ mod integration_tests {
             async_buffer_size: 1024,
             batch_size: 100,
             flush_interval: Duration::from_millis(100),
+            type_policies: Default::default(),
+            sampling_rules: Vec::new(),
+            routing_rules: Vec::new(),
         };
         (config, temp_dir)
     }
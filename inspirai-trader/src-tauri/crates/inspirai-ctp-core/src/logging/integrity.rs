@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{
+    config::{LogConfig, LogType},
+    error::LogError,
+    query::{LogIndex, TimeRange},
+    seek_index,
+};
+
+/// 单个文件的校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrityStatus {
+    /// 校验和与清单一致
+    Verified,
+    /// 清单中有记录但文件不存在
+    Missing,
+    /// 校验和与清单不一致
+    Modified,
+    /// 归档文件未在清单中登记
+    Untracked,
+}
+
+/// 文件校验明细
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIntegrity {
+    pub path: PathBuf,
+    pub status: IntegrityStatus,
+    pub expected_checksum: Option<String>,
+    pub actual_checksum: Option<String>,
+}
+
+/// 日志完整性校验报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub range: Option<TimeRange>,
+    pub files_checked: usize,
+    pub verified: usize,
+    /// 缺失、被修改或未登记的文件
+    pub issues: Vec<FileIntegrity>,
+}
+
+impl IntegrityReport {
+    /// 没有缺失或被修改的文件
+    pub fn is_intact(&self) -> bool {
+        !self
+            .issues
+            .iter()
+            .any(|f| matches!(f.status, IntegrityStatus::Missing | IntegrityStatus::Modified))
+    }
+}
+
+/// 计算文件 SHA256，流式读取避免大文件整体载入内存
+pub fn file_checksum(path: &Path) -> Result<String, LogError> {
+    let mut file = fs::File::open(path).map_err(LogError::WriteError)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(LogError::WriteError)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 是否为正在写入的日志文件，这类文件持续变化，不参与校验
+pub fn is_active_log(config: &LogConfig, path: &Path) -> bool {
+    LogType::all().into_iter().any(|t| config.get_log_file_path(t) == path)
+}
+
+/// 按清单重新计算已轮转/归档文件的校验和
+///
+/// 清单只记录轮转时的归档文件；正在写入的日志和寻址索引不参与校验。
+/// 指定时间范围时只校验修改时间落在范围内的文件
+pub fn verify_archives(
+    config: &LogConfig,
+    manifest: &BTreeMap<String, LogIndex>,
+    range: Option<&TimeRange>,
+) -> Result<IntegrityReport, LogError> {
+    let in_range = |start: DateTime<Utc>, end: DateTime<Utc>| range.is_none_or(|r| end >= r.start && start <= r.end);
+
+    let mut files_checked = 0;
+    let mut verified = 0;
+    let mut issues = Vec::new();
+    let mut tracked = HashSet::new();
+
+    for index in manifest.values() {
+        if is_active_log(config, &index.file_path) || seek_index::is_seek_index(&index.file_path) {
+            continue;
+        }
+        tracked.insert(index.file_path.clone());
+        if !in_range(index.start_time, index.end_time) {
+            continue;
+        }
+        files_checked += 1;
+
+        if !index.file_path.exists() {
+            tracing::warn!(file = %index.file_path.display(), "归档日志缺失");
+            issues.push(FileIntegrity {
+                path: index.file_path.clone(),
+                status: IntegrityStatus::Missing,
+                expected_checksum: Some(index.checksum.clone()),
+                actual_checksum: None,
+            });
+            continue;
+        }
+        let actual = file_checksum(&index.file_path)?;
+        if actual == index.checksum {
+            verified += 1;
+        } else {
+            tracing::warn!(file = %index.file_path.display(), "归档日志校验和不一致");
+            issues.push(FileIntegrity {
+                path: index.file_path.clone(),
+                status: IntegrityStatus::Modified,
+                expected_checksum: Some(index.checksum.clone()),
+                actual_checksum: Some(actual),
+            });
+        }
+    }
+
+    // 目录中存在但清单未登记的归档文件
+    for log_type in LogType::all() {
+        let log_dir = config.output_dir.join(log_type.as_str());
+        let Ok(entries) = fs::read_dir(&log_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file()
+                || tracked.contains(&path)
+                || is_active_log(config, &path)
+                || seek_index::is_seek_index(&path)
+            {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .map_err(LogError::WriteError)?;
+            if !in_range(modified, modified) {
+                continue;
+            }
+            files_checked += 1;
+            issues.push(FileIntegrity {
+                path,
+                status: IntegrityStatus::Untracked,
+                expected_checksum: None,
+                actual_checksum: None,
+            });
+        }
+    }
+
+    issues.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(IntegrityReport {
+        checked_at: Utc::now(),
+        range: range.cloned(),
+        files_checked,
+        verified,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::query::LogIndexManager;
+    use tempfile::TempDir;
+
+    fn setup() -> (LogConfig, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let config = LogConfig {
+            output_dir: temp_dir.path().to_path_buf(),
+            ..LogConfig::development()
+        };
+        config.ensure_directories().unwrap();
+        (config, temp_dir)
+    }
+
+    #[test]
+    fn test_detects_missing_modified_and_untracked() {
+        let (config, _temp_dir) = setup();
+        let app_dir = config.output_dir.join("app");
+        let kept = app_dir.join("app.20240115_000000.log.gz");
+        let tampered = app_dir.join("app.20240116_000000.log.gz");
+        let deleted = app_dir.join("app.20240117_000000.log.gz");
+        for path in [&kept, &tampered, &deleted] {
+            fs::write(path, b"archived").unwrap();
+        }
+        fs::write(config.get_log_file_path(LogType::App), b"active").unwrap();
+
+        let mut manager = LogIndexManager::new(&config).unwrap();
+        for path in [&kept, &tampered, &deleted] {
+            manager.record_file(&config, path).unwrap();
+        }
+        fs::write(&tampered, b"edited").unwrap();
+        fs::remove_file(&deleted).unwrap();
+        fs::write(app_dir.join("app.20240118_000000.log"), b"unknown").unwrap();
+
+        // 重新加载清单，确认已持久化
+        let manager = LogIndexManager::new(&config).unwrap();
+        let report = verify_archives(&config, manager.indices(), None).unwrap();
+        assert_eq!(report.files_checked, 4);
+        assert_eq!(report.verified, 1);
+        let statuses: Vec<IntegrityStatus> = report.issues.iter().map(|f| f.status).collect();
+        assert_eq!(
+            statuses,
+            vec![IntegrityStatus::Modified, IntegrityStatus::Missing, IntegrityStatus::Untracked]
+        );
+        assert!(!report.is_intact());
+    }
+
+    #[test]
+    fn test_range_filter_and_forget() {
+        let (config, _temp_dir) = setup();
+        let archived = config.output_dir.join("trading").join("trading.20240115_000000.log.gz");
+        fs::write(&archived, b"archived").unwrap();
+
+        let mut manager = LogIndexManager::new(&config).unwrap();
+        manager.record_file(&config, &archived).unwrap();
+
+        let past = TimeRange {
+            start: "2020-01-01T00:00:00Z".parse().unwrap(),
+            end: "2020-01-02T00:00:00Z".parse().unwrap(),
+        };
+        let report = verify_archives(&config, manager.indices(), Some(&past)).unwrap();
+        assert_eq!(report.files_checked, 0);
+
+        let report = verify_archives(&config, manager.indices(), Some(&TimeRange::last_hours(1))).unwrap();
+        assert_eq!(report.verified, 1);
+        assert!(report.is_intact());
+
+        // 正常清理后从清单移除，不再报告缺失
+        fs::remove_file(&archived).unwrap();
+        manager.forget_files(&config, &[archived]).unwrap();
+        let report = verify_archives(&config, manager.indices(), None).unwrap();
+        assert!(report.issues.is_empty());
+    }
+}
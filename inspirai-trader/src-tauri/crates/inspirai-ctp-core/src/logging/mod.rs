@@ -16,10 +16,19 @@ pub mod writer;
 pub mod formatter;
 pub mod rotator;
 pub mod query;
+pub mod query_dsl;
+pub mod seek_index;
+pub mod integrity;
+pub mod frontend;
+pub mod sampling;
+pub mod startup;
 pub mod security;
 pub mod error;
 pub mod metrics;
 pub mod context;
+pub mod correlation;
+pub mod flight_recorder;
+pub mod support_bundle;
 
 // #[cfg(test)]
 // mod integration_test;
@@ -36,10 +45,23 @@ pub use writer::*;
 pub use formatter::*;
 pub use rotator::*;
 pub use query::*;
+pub use query_dsl::*;
+pub use seek_index::*;
+pub use integrity::*;
+pub use frontend::*;
+pub use sampling::*;
+pub use startup::*;
 pub use security::*;
 pub use error::*;
 pub use metrics::*;
 pub use context::*;
+pub use correlation::*;
+pub use flight_recorder::*;
+pub use support_bundle::*;
+
+/// 会安装全局 subscriber 的测试互相串行，先后顺序不影响结果
+#[cfg(test)]
+pub(crate) static GLOBAL_SUBSCRIBER_TEST_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 /// 全局日志系统实例
 static LOGGER: OnceLock<Arc<LoggingSystem>> = OnceLock::new();
@@ -52,23 +74,18 @@ pub struct LoggingSystem {
     writer: Arc<AsyncWriter>,
     rotator: Arc<AsyncMutex<LogRotator>>,
     metrics: Arc<AsyncMutex<LogMetrics>>,
+    sampler: LogSampler,
+    masker: DataMasker,
+    flight_recorder: FlightRecorder,
 }
 
 impl LoggingSystem {
     /// 初始化日志系统
+    ///
+    /// 未安装启动缓冲且已存在其他全局 tracing subscriber 时返回错误，事件不会进入文件通道；
+    /// 需要沿用现有 subscriber 时可通过 [`LoggingSystem::layers`] 把文件输出层组合进去
     pub async fn init(config: LogConfig) -> Result<(), LogError> {
-        let router = Arc::new(LogRouter::new(&config)?);
-        let writer = Arc::new(AsyncWriter::new(&config).await?);
-        let rotator = Arc::new(AsyncMutex::new(LogRotator::new(&config)?));
-        let metrics = Arc::new(AsyncMutex::new(LogMetrics::new()));
-
-        let system = Arc::new(Self {
-            config,
-            router,
-            writer,
-            rotator,
-            metrics,
-        });
+        let system = Self::build(config).await?;
 
         // 设置全局实例
         LOGGER.set(system.clone()).map_err(|_| {
@@ -85,6 +102,36 @@ impl LoggingSystem {
         Ok(())
     }
 
+    /// 以作用域方式初始化，不设置全局实例和全局 subscriber
+    ///
+    /// 返回的 guard 存活期间当前线程的事件写入该实例，主要用于测试，可重复调用
+    pub async fn init_scoped(config: LogConfig) -> Result<(Arc<Self>, tracing::dispatcher::DefaultGuard), LogError> {
+        let system = Self::build(config).await?;
+        let guard = tracing::dispatcher::set_default(&system.dispatch());
+        system.start_background_tasks().await?;
+        Ok((system, guard))
+    }
+
+    /// 创建日志系统各组件
+    async fn build(config: LogConfig) -> Result<Arc<Self>, LogError> {
+        let router = Arc::new(LogRouter::new(&config)?);
+        let writer = Arc::new(AsyncWriter::new(&config).await?);
+        let rotator = Arc::new(AsyncMutex::new(LogRotator::new(&config)?));
+        let metrics = Arc::new(AsyncMutex::new(LogMetrics::new()));
+        let sampler = LogSampler::new(config.sampling_rules.clone())?;
+
+        Ok(Arc::new(Self {
+            config,
+            router,
+            writer,
+            rotator,
+            metrics,
+            sampler,
+            masker: DataMasker::new(),
+            flight_recorder: FlightRecorder::default(),
+        }))
+    }
+
     /// 获取全局日志系统实例
     pub fn instance() -> Result<Arc<Self>, LogError> {
         LOGGER.get().cloned().ok_or_else(|| {
@@ -93,11 +140,75 @@ impl LoggingSystem {
     }
 
     /// 初始化 tracing subscriber
+    ///
+    /// 已安装启动缓冲时替换其输出层并回放缓冲的事件，否则尝试安装全局 subscriber
     async fn init_tracing(&self) -> Result<(), LogError> {
-        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+        let file_layer = self.file_layer();
+        let env_filter = self.env_filter();
+
+        if let Some(startup) = StartupHandle::get() {
+            let replay = file_layer.clone();
+            let (entries, dropped) = startup.activate(env_filter, self.build_layers(file_layer))?;
+            // 缓冲时尚未应用配置的级别，由路由器的级别过滤处理
+            let replayed = entries.len();
+            for entry in entries {
+                replay.write_entry(entry);
+            }
+            tracing::info!(replayed, dropped, "已回放启动阶段缓冲的日志");
+            return Ok(());
+        }
+
+        if !install_global(self.dispatch()) {
+            return Err(LogError::InitError(
+                "已存在全局 tracing subscriber，日志不会写入文件；请在启动时调用 install_startup_buffer".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 由本实例输出层组成的 subscriber，可用于 `tracing::dispatcher::with_default`
+    pub fn dispatch(&self) -> tracing::Dispatch {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let subscriber = tracing_subscriber::registry()
+            .with(self.env_filter())
+            .with(self.build_layers(self.file_layer()));
+        tracing::Dispatch::new(subscriber)
+    }
+
+    /// 采样、控制台和文件输出层，供组合进调用方自己的 subscriber
+    pub fn layers<S>(&self) -> Vec<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        self.build_layers(self.file_layer())
+    }
+
+    fn env_filter(&self) -> tracing_subscriber::EnvFilter {
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(self.config.level.to_string()))
+    }
+
+    /// 自定义文件输出层 - 使用独立的 metrics 实例以避免异步问题
+    fn file_layer(&self) -> CustomFileLayer {
+        CustomFileLayer::new(
+            self.router.clone(),
+            self.writer.clone(),
+            Arc::new(Mutex::new(LogMetrics::new())),
+        )
+    }
 
+    /// 构建输出层
+    fn build_layers<S>(&self, file_layer: CustomFileLayer) -> Vec<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
         let mut layers = Vec::new();
 
+        // 采样层放在最前，被丢弃的事件不再路由和输出
+        layers.push(SamplingLayer::new(self.sampler.clone()).boxed());
+        layers.push(FlightRecorderLayer::new(self.flight_recorder.clone()).boxed());
+
         // 控制台输出层
         if self.config.console_output {
             let console_layer = tracing_subscriber::fmt::layer()
@@ -110,28 +221,8 @@ impl LoggingSystem {
             layers.push(console_layer.boxed());
         }
 
-        // 自定义文件输出层 - 使用独立的 metrics 实例以避免异步问题
-        let layer_metrics = Arc::new(Mutex::new(LogMetrics::new()));
-        let file_layer = CustomFileLayer::new(
-            self.router.clone(),
-            self.writer.clone(),
-            layer_metrics,
-        );
         layers.push(file_layer.boxed());
-
-        // 创建并初始化 subscriber
-        let subscriber = tracing_subscriber::registry()
-            .with(
-                tracing_subscriber::EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&self.config.level.to_string()))
-            )
-            .with(layers);
-
-        subscriber.try_init().map_err(|e| {
-            LogError::InitError(format!("初始化 tracing subscriber 失败: {}", e))
-        })?;
-
-        Ok(())
+        layers
     }
 
     /// 启动后台任务
@@ -181,9 +272,68 @@ impl LoggingSystem {
     pub fn get_metrics(&self) -> Arc<AsyncMutex<LogMetrics>> {
         self.metrics.clone()
     }
+
+    /// 获取日志采样器，可在运行时调整规则
+    pub fn sampler(&self) -> &LogSampler {
+        &self.sampler
+    }
+
+    /// 获取日志路由器
+    pub fn router(&self) -> &LogRouter {
+        &self.router
+    }
+
+    /// 获取飞行记录器
+    pub fn flight_recorder(&self) -> &FlightRecorder {
+        &self.flight_recorder
+    }
+
+    /// 获取日志配置
+    pub fn config(&self) -> &LogConfig {
+        &self.config
+    }
+
+    /// 获取脱敏器
+    pub fn masker(&self) -> &DataMasker {
+        &self.masker
+    }
+
+    /// 重新统计日志目录占用的磁盘空间并更新指标
+    pub async fn refresh_disk_usage(&self) -> Result<u64, LogError> {
+        let output_dir = self.config.output_dir.clone();
+        let bytes = tokio::task::spawn_blocking(move || dir_size(&output_dir))
+            .await
+            .map_err(|e| LogError::InitError(format!("统计日志目录失败: {}", e)))??;
+        self.metrics.lock().await.update_disk_usage(bytes);
+        Ok(bytes)
+    }
+
+    /// 写入来自 tracing 之外的日志条目（如前端上报），脱敏后按路由规则写入
+    pub fn ingest(&self, mut entry: LogEntry) -> Result<(), LogError> {
+        self.masker.mask_log_entry(&mut entry)?;
+        self.file_layer().write_entry(entry);
+        Ok(())
+    }
+}
+
+/// 目录下所有文件的总字节数，目录不存在时为 0
+fn dir_size(dir: &std::path::Path) -> Result<u64, LogError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let mut total = 0;
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() { dir_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(total)
 }
 
 /// 自定义文件输出层
+#[derive(Clone)]
 pub struct CustomFileLayer {
     router: Arc<LogRouter>,
     writer: Arc<AsyncWriter>,
@@ -202,6 +352,32 @@ impl CustomFileLayer {
             metrics,
         }
     }
+
+    /// 路由并写入日志条目，规则可能要求同时写入多个类型
+    pub fn write_entry(&self, entry: LogEntry) {
+        let log_types = self.router.route_all(&entry);
+        let Some((&last, rest)) = log_types.split_last() else {
+            return;
+        };
+        for &log_type in rest {
+            self.write_to(log_type, entry.clone());
+        }
+        self.write_to(last, entry);
+    }
+
+    fn write_to(&self, log_type: LogType, entry: LogEntry) {
+        // 异步写入
+        if let Err(e) = self.writer.write_async(log_type, entry) {
+            eprintln!("日志写入失败: {}", e);
+            // 更新错误指标
+            let mut metrics = self.metrics.lock().unwrap();
+            metrics.error_count += 1;
+        } else {
+            // 更新成功指标
+            let mut metrics = self.metrics.lock().unwrap();
+            metrics.logs_written_total += 1;
+        }
+    }
 }
 
 impl<S> Layer<S> for CustomFileLayer
@@ -212,20 +388,7 @@ where
         // 创建结构化日志条目
         let entry = LogEntry::from_tracing_event(event, &ctx);
         
-        // 路由到适当的日志文件
-        if let Some(log_type) = self.router.route(&entry) {
-            // 异步写入
-            if let Err(e) = self.writer.write_async(log_type, entry) {
-                eprintln!("日志写入失败: {}", e);
-                // 更新错误指标
-                let mut metrics = self.metrics.lock().unwrap();
-                metrics.error_count += 1;
-            } else {
-                // 更新成功指标
-                let mut metrics = self.metrics.lock().unwrap();
-                metrics.logs_written_total += 1;
-            }
-        }
+        self.write_entry(entry);
     }
 }
 
@@ -305,6 +468,13 @@ impl LogEntry {
         
         event.record(&mut visitor);
         
+        // 补充当前操作的关联 ID
+        if !visitor.fields.contains_key(CORRELATION_FIELD) {
+            if let Some(correlation_id) = current_correlation_id() {
+                visitor.fields.insert(CORRELATION_FIELD.to_string(), correlation_id.into());
+            }
+        }
+        
         // 转换日志级别
         let level = match *event.metadata().level() {
             Level::TRACE => LogLevel::Trace,
@@ -480,9 +650,19 @@ mod tests {
             async_buffer_size: 1024,
             batch_size: 100,
             flush_interval: std::time::Duration::from_millis(100),
+            type_policies: Default::default(),
+            sampling_rules: Vec::new(),
+            routing_rules: Vec::new(),
         };
 
+        let _guard = GLOBAL_SUBSCRIBER_TEST_LOCK.lock().await;
+        let preinstalled = tracing::dispatcher::has_been_set();
         let result = LoggingSystem::init(config).await;
+        if preinstalled {
+            // 其他测试已安装全局 subscriber，文件输出无法生效时须报错
+            assert!(matches!(result, Err(LogError::InitError(_))), "{:?}", result);
+            return;
+        }
         assert!(result.is_ok(), "日志系统初始化失败: {:?}", result);
 
         // 测试日志记录
@@ -496,4 +676,24 @@ mod tests {
         let shutdown = system.unwrap().shutdown().await;
         assert!(shutdown.is_ok(), "日志系统关闭失败");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_scoped_init_is_repeatable() {
+        // 作用域初始化不占用全局状态，可多次调用
+        for round in 0..2 {
+            let temp_dir = TempDir::new().unwrap();
+            let config = LogConfig {
+                output_dir: temp_dir.path().to_path_buf(),
+                console_output: false,
+                ..LogConfig::development()
+            };
+            let (system, guard) = LoggingSystem::init_scoped(config.clone()).await.unwrap();
+            tracing::info!(account_id = "12345", round, "作用域内的交易日志");
+            drop(guard);
+            system.writer.flush().await.unwrap();
+
+            let content = std::fs::read_to_string(config.get_log_file_path(LogType::Trading)).unwrap();
+            assert!(content.contains("作用域内的交易日志"), "第 {} 轮未写入: {}", round, content);
+        }
+    }
+}
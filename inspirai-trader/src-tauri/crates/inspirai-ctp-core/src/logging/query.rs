@@ -1,22 +1,40 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, BTreeMap};
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::{BufRead, BufReader};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, OnceLock};
+use aho_corasick::AhoCorasick;
 use chrono::{DateTime, Utc};
+use memchr::memmem;
 use serde::{Serialize, Deserialize};
 use regex::Regex;
 
 use super::{
     config::{LogConfig, LogType, LogLevel},
     error::LogError,
+    integrity::{self, IntegrityReport},
+    seek_index::{self, SeekIndex},
     LogEntry,
 };
 
+/// 默认并行搜索的文件数上限
+const DEFAULT_SEARCH_CONCURRENCY: usize = 4;
+/// 扫描过程中检查取消标记的间隔行数
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+/// 分页偏移上限，合并时需保留 offset + limit 条
+const MAX_QUERY_OFFSET: usize = 100_000;
+/// 超过该长度的行不解析，避免异常日志拖慢检索
+pub const MAX_PARSE_LINE_BYTES: usize = 1024 * 1024;
+
 /// 日志查询接口
 #[derive(Debug)]
 pub struct LogQueryEngine {
     config: LogConfig,
     index_manager: LogIndexManager,
+    /// 同时搜索的文件数
+    concurrency: usize,
 }
 
 impl LogQueryEngine {
@@ -24,56 +42,119 @@ impl LogQueryEngine {
     pub fn new(config: LogConfig) -> Result<Self, LogError> {
         let index_manager = LogIndexManager::new(&config)?;
         
+        let concurrency = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(DEFAULT_SEARCH_CONCURRENCY);
+        
         Ok(Self {
             config,
             index_manager,
+            concurrency,
         })
     }
     
+    /// 设置同时搜索的文件数
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+    
     /// 执行日志查询
     pub async fn query(&self, query: LogQuery) -> Result<QueryResult, LogError> {
+        let started = std::time::Instant::now();
+        
         // 验证查询参数
         query.validate()?;
         
         // 根据时间范围和日志类型确定需要搜索的文件
         let candidate_files = self.get_candidate_files(&query).await?;
-        let files_searched = candidate_files.len();
-        
-        // 执行搜索
-        let mut results = Vec::new();
-        let mut total_scanned = 0;
-        
-        for file_info in candidate_files {
-            match self.search_file(&file_info.path, &query).await {
-                Ok(mut file_results) => {
-                    total_scanned += file_results.len();
-                    results.append(&mut file_results);
+        let mut skipped_files = 0;
+        
+        // 并行搜索，每个文件最多保留 offset + limit 条，合并后统一分页
+        let needed = query.offset + query.limit;
+        let shared_query = Arc::new(query.clone());
+        let mut pending = candidate_files.into_iter();
+        let mut workers = tokio::task::JoinSet::new();
+        let mut in_flight: HashMap<tokio::task::Id, (DateTime<Utc>, Arc<AtomicBool>)> = HashMap::new();
+        let mut results: Vec<LogEntry> = Vec::new();
+        let mut total_found = 0;
+        let mut files_searched = 0;
+        let mut is_estimate = false;
+        
+        loop {
+            // 补充工作任务；文件按修改时间倒序，一旦某个文件不可能进入结果，后续文件也不可能
+            while workers.len() < self.concurrency {
+                let Some(file_info) = pending.next() else {
+                    break;
+                };
+                if Self::cannot_contribute(&results, needed, &query, file_info.modified_time) {
+                    skipped_files = 1 + pending.len();
+                    pending = Vec::new().into_iter();
+                    break;
+                }
+                let cancel = Arc::new(AtomicBool::new(false));
+                let worker_query = shared_query.clone();
+                let worker_cancel = cancel.clone();
+                let path = file_info.path.clone();
+                let handle = workers.spawn_blocking(move || {
+                    let result = Self::search_file_sync(&path, &worker_query, &worker_cancel);
+                    (path, result)
+                });
+                in_flight.insert(handle.id(), (file_info.modified_time, cancel));
+            }
+            
+            let Some(joined) = workers.join_next_with_id().await else {
+                break;
+            };
+            let (path, file_result) = match joined {
+                Ok((id, output)) => {
+                    in_flight.remove(&id);
+                    output
+                }
+                Err(e) => {
+                    in_flight.remove(&e.id());
+                    tracing::warn!(error = %e, "日志搜索任务异常退出");
+                    continue;
+                }
+            };
+            match file_result {
+                Ok(mut scan) => {
+                    files_searched += 1;
+                    total_found += scan.matched;
+                    is_estimate |= !scan.complete;
+                    results.append(&mut scan.entries);
+                    Self::retain_best(&mut results, needed, &query);
                     
-                    // 检查结果数量限制
-                    if results.len() >= query.limit {
-                        results.truncate(query.limit);
-                        break;
+                    // 结果已足够时取消不可能再贡献结果的文件
+                    for (modified_time, cancel) in in_flight.values() {
+                        if Self::cannot_contribute(&results, needed, &query, *modified_time) {
+                            cancel.store(true, AtomicOrdering::Relaxed);
+                        }
                     }
                 }
                 Err(e) => {
                     tracing::warn!(
-                        file = %file_info.path.display(),
+                        file = %path.display(),
                         error = %e,
                         "搜索文件时出错"
                     );
-                    continue;
                 }
             }
         }
         
-        // 排序结果
-        self.sort_results(&mut results, &query);
+        // 分页
+        let results: Vec<LogEntry> = results.into_iter().skip(query.offset).take(query.limit).collect();
+        
+        // 跳过或中途取消的文件未完整计数，总数为下限
+        is_estimate |= skipped_files > 0;
         
         Ok(QueryResult {
             entries: results,
-            total_found: total_scanned,
+            total_found,
+            is_estimate,
             query: query.clone(),
-            execution_time_ms: 0, // TODO: 实际测量执行时间
+            execution_time_ms: started.elapsed().as_millis() as u64,
             files_searched,
         })
     }
@@ -118,15 +199,15 @@ impl LogQueryEngine {
             let entry = entry.map_err(LogError::WriteError)?;
             let path = entry.path();
             
-            if path.is_file() {
+            if path.is_file() && !seek_index::is_seek_index(&path) {
                 let metadata = entry.metadata().map_err(LogError::WriteError)?;
                 let modified_time = DateTime::<Utc>::from(
                     metadata.modified().map_err(LogError::WriteError)?
                 );
                 
-                // 检查时间范围过滤
+                // 检查时间范围过滤：文件内日志都早于修改时间，修改时间早于范围起点的文件可跳过
                 if let Some(range) = time_range {
-                    if !range.contains(modified_time) {
+                    if modified_time < range.start {
                         continue;
                     }
                 }
@@ -148,77 +229,103 @@ impl LogQueryEngine {
         Ok(files)
     }
     
-    /// 搜索单个文件
-    async fn search_file(&self, file_path: &Path, query: &LogQuery) -> Result<Vec<LogEntry>, LogError> {
-        let file_path_owned = file_path.to_owned();
-        let query_owned = query.clone();
-        
-        // 在后台线程中执行文件搜索
-        let results = tokio::task::spawn_blocking(move || {
-            Self::search_file_sync(&file_path_owned, &query_owned)
-        }).await
-        .map_err(|e| LogError::QueryError {
-            query: format!("搜索文件 {:?}", file_path),
-        })?;
-        
-        results
+    /// 按排序保留前 `needed` 条
+    fn retain_best(results: &mut Vec<LogEntry>, needed: usize, query: &LogQuery) {
+        results.sort_by(|a, b| compare_entries(a, b, query));
+        results.truncate(needed);
+    }
+    
+    /// 按时间倒序且已有足够结果时，修改时间早于第 `needed` 条的文件不会再贡献结果
+    fn cannot_contribute(results: &[LogEntry], needed: usize, query: &LogQuery, modified_time: DateTime<Utc>) -> bool {
+        query.sort_by == SortBy::Timestamp
+            && query.sort_order == SortOrder::Descending
+            && needed > 0
+            && results.get(needed - 1).is_some_and(|last| modified_time < last.timestamp)
     }
     
     /// 同步搜索文件
-    fn search_file_sync(file_path: &Path, query: &LogQuery) -> Result<Vec<LogEntry>, LogError> {
-        let mut results = Vec::new();
-        
+    fn search_file_sync(file_path: &Path, query: &LogQuery, cancel: &AtomicBool) -> Result<FileScan, LogError> {
         // 判断是否为压缩文件
         let is_compressed = file_path.extension()
             .and_then(|s| s.to_str())
             .map(|s| s == "gz")
             .unwrap_or(false);
         
+        let matcher = CompiledQuery::new(query);
         if is_compressed {
-            // 处理压缩文件
-            use flate2::read::GzDecoder;
-            let file = fs::File::open(file_path).map_err(LogError::WriteError)?;
-            let decoder = GzDecoder::new(file);
-            let reader = BufReader::new(decoder);
-            
-            for (line_number, line_result) in reader.lines().enumerate() {
-                let line = line_result.map_err(LogError::WriteError)?;
-                
-                if let Some(entry) = Self::parse_log_line(&line, line_number + 1)? {
-                    if Self::matches_query(&entry, query) {
-                        results.push(entry);
-                        
-                        if results.len() >= query.limit {
-                            break;
-                        }
-                    }
-                }
+            // 有寻址索引时只解压时间范围内的块，压缩文件可能由多个 gzip 成员组成
+            let indexed = query.time_range.as_ref()
+                .and_then(|range| Some(SeekIndex::load(file_path)?.ranges_for(range)));
+            if let Some(ranges) = indexed {
+                let reader = seek_index::open_ranges(file_path, &ranges)?;
+                return Self::scan_lines(BufReader::new(reader), &matcher, cancel);
             }
+            
+            use flate2::read::MultiGzDecoder;
+            let file = fs::File::open(file_path).map_err(LogError::WriteError)?;
+            Self::scan_lines(BufReader::new(MultiGzDecoder::new(file)), &matcher, cancel)
         } else {
-            // 处理普通文件
             let file = fs::File::open(file_path).map_err(LogError::WriteError)?;
-            let reader = BufReader::new(file);
+            Self::scan_lines(BufReader::new(file), &matcher, cancel)
+        }
+    }
+    
+    /// 逐行扫描，先在原始字节上预筛选，命中后才解析
+    ///
+    /// 按查询排序只保留前 offset + limit 条并统计全部命中数，取消后返回已找到的部分
+    fn scan_lines<R: BufRead>(mut reader: R, matcher: &CompiledQuery, cancel: &AtomicBool) -> Result<FileScan, LogError> {
+        let needed = matcher.query.offset + matcher.query.limit;
+        let mut results = Vec::new();
+        let mut matched = 0;
+        let mut complete = true;
+        let mut buffer = Vec::with_capacity(1024);
+        let mut line_number = 0;
+        
+        loop {
+            buffer.clear();
+            if reader.read_until(b'\n', &mut buffer).map_err(LogError::WriteError)? == 0 {
+                break;
+            }
+            line_number += 1;
+            if line_number % CANCEL_CHECK_INTERVAL == 0 && cancel.load(AtomicOrdering::Relaxed) {
+                complete = false;
+                break;
+            }
+            if !matcher.may_match(&buffer) {
+                continue;
+            }
+            let Ok(line) = std::str::from_utf8(&buffer) else {
+                continue;
+            };
+            let line = line.trim_end_matches(['\n', '\r']);
             
-            for (line_number, line_result) in reader.lines().enumerate() {
-                let line = line_result.map_err(LogError::WriteError)?;
-                
-                if let Some(entry) = Self::parse_log_line(&line, line_number + 1)? {
-                    if Self::matches_query(&entry, query) {
-                        results.push(entry);
-                        
-                        if results.len() >= query.limit {
-                            break;
-                        }
+            if let Some(entry) = Self::parse_log_line(line, line_number)? {
+                if matcher.matches(&entry) {
+                    matched += 1;
+                    results.push(entry);
+                    
+                    // 超出两倍时整理一次，控制内存
+                    if results.len() >= needed.saturating_mul(2).max(64) {
+                        Self::retain_best(&mut results, needed, matcher.query);
                     }
                 }
             }
         }
         
-        Ok(results)
+        Self::retain_best(&mut results, needed, matcher.query);
+        Ok(FileScan {
+            entries: results,
+            matched,
+            complete,
+        })
     }
     
-    /// 解析日志行
-    fn parse_log_line(line: &str, line_number: usize) -> Result<Option<LogEntry>, LogError> {
+    /// 解析日志行，无法识别或过长的行返回 None
+    pub fn parse_log_line(line: &str, line_number: usize) -> Result<Option<LogEntry>, LogError> {
+        if line.len() > MAX_PARSE_LINE_BYTES {
+            return Ok(None);
+        }
+        
         // 尝试解析 JSON 格式
         if line.trim().starts_with('{') {
             match serde_json::from_str::<serde_json::Value>(line) {
@@ -233,7 +340,7 @@ impl LogQueryEngine {
         }
         
         // 尝试解析人类可读格式
-        if let Some(entry) = Self::parse_human_readable_log(&line, line_number)? {
+        if let Some(entry) = Self::parse_human_readable_log(line, line_number)? {
             return Ok(Some(entry));
         }
         
@@ -241,8 +348,8 @@ impl LogQueryEngine {
         Ok(None)
     }
     
-    /// 从 JSON 解析日志条目
-    fn parse_json_log_entry(json: &serde_json::Value) -> Result<LogEntry, LogError> {
+    /// 从 JSON 解析日志条目，缺失或类型不符的字段取默认值
+    pub fn parse_json_log_entry(json: &serde_json::Value) -> Result<LogEntry, LogError> {
         let timestamp = json.get("timestamp")
             .and_then(|v| v.as_str())
             .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
@@ -316,9 +423,7 @@ impl LogQueryEngine {
         // 这是一个简化的实现，实际应该根据具体的日志格式来解析
         // 例如：2024-01-15 18:30:45.123 [INFO ] [trading_service] 订单提交成功 ...
         
-        let re = Regex::new(r"(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{3}) \[(\w+)\s*\] \[([^\]]+)\] (.*)").unwrap();
-        
-        if let Some(captures) = re.captures(line) {
+        if let Some(captures) = human_log_regex().captures(line) {
             let timestamp_str = captures.get(1).unwrap().as_str();
             let level_str = captures.get(2).unwrap().as_str();
             let module_str = captures.get(3).unwrap().as_str();
@@ -357,89 +462,6 @@ impl LogQueryEngine {
         Ok(None)
     }
     
-    /// 检查日志条目是否匹配查询条件
-    fn matches_query(entry: &LogEntry, query: &LogQuery) -> bool {
-        // 检查日志级别
-        if !query.levels.is_empty() && !query.levels.contains(&entry.level) {
-            return false;
-        }
-        
-        // 检查模块过滤
-        if !query.modules.is_empty() {
-            let matches_module = query.modules.iter().any(|module| {
-                entry.module.contains(module)
-            });
-            if !matches_module {
-                return false;
-            }
-        }
-        
-        // 检查关键字搜索
-        if !query.keywords.is_empty() {
-            let text_to_search = format!("{} {}", entry.message, 
-                entry.fields.values()
-                    .map(|v| v.to_string())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            ).to_lowercase();
-            
-            let matches_keywords = query.keywords.iter().all(|keyword| {
-                text_to_search.contains(&keyword.to_lowercase())
-            });
-            if !matches_keywords {
-                return false;
-            }
-        }
-        
-        // 检查字段过滤
-        for (field, expected_value) in &query.field_filters {
-            match entry.fields.get(field) {
-                Some(actual_value) => {
-                    if actual_value.to_string() != *expected_value {
-                        return false;
-                    }
-                }
-                None => return false,
-            }
-        }
-        
-        // 检查时间范围
-        if let Some(time_range) = &query.time_range {
-            if !time_range.contains(entry.timestamp) {
-                return false;
-            }
-        }
-        
-        true
-    }
-    
-    /// 排序查询结果
-    fn sort_results(&self, results: &mut Vec<LogEntry>, query: &LogQuery) {
-        match query.sort_by {
-            SortBy::Timestamp => {
-                if query.sort_order == SortOrder::Descending {
-                    results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-                } else {
-                    results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-                }
-            }
-            SortBy::Level => {
-                if query.sort_order == SortOrder::Descending {
-                    results.sort_by(|a, b| b.level.cmp(&a.level));
-                } else {
-                    results.sort_by(|a, b| a.level.cmp(&b.level));
-                }
-            }
-            SortBy::Module => {
-                if query.sort_order == SortOrder::Descending {
-                    results.sort_by(|a, b| b.module.cmp(&a.module));
-                } else {
-                    results.sort_by(|a, b| a.module.cmp(&b.module));
-                }
-            }
-        }
-    }
-    
     /// 重建索引
     pub async fn rebuild_index(&mut self) -> Result<(), LogError> {
         self.index_manager.rebuild(&self.config).await
@@ -449,6 +471,18 @@ impl LogQueryEngine {
     pub fn get_query_stats(&self) -> QueryStats {
         self.index_manager.get_stats()
     }
+    
+    /// 按索引清单校验已轮转/归档日志的完整性
+    pub async fn verify_integrity(&self, range: Option<TimeRange>) -> Result<IntegrityReport, LogError> {
+        let config = self.config.clone();
+        let manifest = self.index_manager.indices().clone();
+        
+        tokio::task::spawn_blocking(move || integrity::verify_archives(&config, &manifest, range.as_ref()))
+            .await
+            .map_err(|e| LogError::QueryError {
+                query: format!("完整性校验任务异常: {}", e),
+            })?
+    }
 }
 
 /// 日志查询条件
@@ -562,6 +596,12 @@ impl LogQuery {
             });
         }
         
+        if self.offset > MAX_QUERY_OFFSET {
+            return Err(LogError::QueryError {
+                query: format!("offset 不能超过 {}", MAX_QUERY_OFFSET),
+            });
+        }
+        
         if let Some(time_range) = &self.time_range {
             if time_range.start >= time_range.end {
                 return Err(LogError::QueryError {
@@ -633,16 +673,186 @@ pub enum SortOrder {
     Descending,
 }
 
+/// 按查询的排序方式比较两条日志
+fn compare_entries(a: &LogEntry, b: &LogEntry, query: &LogQuery) -> Ordering {
+    let ordering = match query.sort_by {
+        SortBy::Timestamp => a.timestamp.cmp(&b.timestamp),
+        SortBy::Level => a.level.cmp(&b.level),
+        SortBy::Module => a.module.cmp(&b.module),
+    };
+    match query.sort_order {
+        SortOrder::Ascending => ordering,
+        SortOrder::Descending => ordering.reverse(),
+    }
+}
+
+/// 人类可读日志行格式，例如：
+/// 2024-01-15 18:30:45.123 [INFO ] [trading_service] 订单提交成功
+fn human_log_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{3}) \[(\w+)\s*\] \[([^\]]+)\] (.*)").unwrap()
+    })
+}
+
+/// 预编译的查询条件
+///
+/// 每个文件只构建一次：关键字预先转小写，并在原始行上用 aho-corasick / memmem
+/// 预筛选关键字和模块，未命中的行跳过 JSON 解析
+struct CompiledQuery<'a> {
+    query: &'a LogQuery,
+    /// 小写关键字
+    keywords: Vec<String>,
+    /// 可在原始行上预筛选的关键字（全部需命中）
+    keyword_prefilter: Option<AhoCorasick>,
+    /// 模块预筛选（任一命中），模块含需转义字符时为空
+    module_finders: Option<Vec<memmem::Finder<'a>>>,
+}
+
+impl<'a> CompiledQuery<'a> {
+    fn new(query: &'a LogQuery) -> Self {
+        // JSON 中需转义或大小写不只是 ASCII 的关键字无法在原始行上可靠匹配
+        let raw_safe = |s: &str| !s.is_empty() && !s.chars().any(|c| c == '"' || c == '\\' || c.is_control());
+        let caseless = |s: &str| s.chars().all(|c| c.is_ascii() || (!c.is_lowercase() && !c.is_uppercase()));
+        
+        let prefilter_keywords: Vec<&String> = query.keywords
+            .iter()
+            .filter(|k| raw_safe(k) && caseless(k))
+            .collect();
+        let keyword_prefilter = if prefilter_keywords.is_empty() {
+            None
+        } else {
+            AhoCorasick::builder()
+                .ascii_case_insensitive(true)
+                .build(&prefilter_keywords)
+                .ok()
+        };
+        
+        let module_finders = (!query.modules.is_empty() && query.modules.iter().all(|m| raw_safe(m)))
+            .then(|| query.modules.iter().map(|m| memmem::Finder::new(m.as_bytes())).collect());
+        
+        Self {
+            query,
+            keywords: query.keywords.iter().map(|k| k.to_lowercase()).collect(),
+            keyword_prefilter,
+            module_finders,
+        }
+    }
+    
+    /// 原始行是否可能匹配，返回 false 的行一定不匹配
+    fn may_match(&self, line: &[u8]) -> bool {
+        if let Some(finders) = &self.module_finders {
+            if !finders.iter().any(|f| f.find(line).is_some()) {
+                return false;
+            }
+        }
+        if let Some(prefilter) = &self.keyword_prefilter {
+            let patterns = prefilter.patterns_len();
+            let mut seen = vec![false; patterns];
+            let mut remaining = patterns;
+            for found in prefilter.find_overlapping_iter(line) {
+                let id = found.pattern().as_usize();
+                if !seen[id] {
+                    seen[id] = true;
+                    remaining -= 1;
+                    if remaining == 0 {
+                        break;
+                    }
+                }
+            }
+            if remaining > 0 {
+                return false;
+            }
+        }
+        true
+    }
+    
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let query = self.query;
+        
+        // 检查日志级别
+        if !query.levels.is_empty() && !query.levels.contains(&entry.level) {
+            return false;
+        }
+        
+        // 检查时间范围
+        if let Some(time_range) = &query.time_range {
+            if !time_range.contains(entry.timestamp) {
+                return false;
+            }
+        }
+        
+        // 检查模块过滤
+        let entry_module = super::router::routable_module(&entry.module);
+        if !query.modules.is_empty() && !query.modules.iter().any(|module| entry_module.contains(module)) {
+            return false;
+        }
+        
+        // 检查字段过滤，字符串字段按原值比较，其他类型按 JSON 文本比较
+        for (field, expected_value) in &query.field_filters {
+            let matches = match entry.fields.get(field) {
+                Some(actual_value) => match actual_value.as_str() {
+                    Some(actual) => actual == expected_value,
+                    None => actual_value.to_string().as_str() == expected_value.as_str(),
+                },
+                None => false,
+            };
+            if !matches {
+                return false;
+            }
+        }
+        
+        // 检查关键字搜索，消息中未找到的关键字再到字段中查找
+        if !self.keywords.is_empty() {
+            let message = entry.message.to_lowercase();
+            let mut fields_text: Option<String> = None;
+            for keyword in &self.keywords {
+                if message.contains(keyword.as_str()) {
+                    continue;
+                }
+                let fields_text = fields_text.get_or_insert_with(|| {
+                    entry.fields.values()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .to_lowercase()
+                });
+                if !fields_text.contains(keyword.as_str()) {
+                    return false;
+                }
+            }
+        }
+        
+        true
+    }
+}
+
 /// 查询结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
+    /// 当前页的日志
     pub entries: Vec<LogEntry>,
+    /// 匹配的总条数（不受分页影响）
     pub total_found: usize,
+    /// 部分文件因提前结束未完整扫描时为 true，此时总数为下限
+    #[serde(default)]
+    pub is_estimate: bool,
     pub query: LogQuery,
     pub execution_time_ms: u64,
     pub files_searched: usize,
 }
 
+/// 单个文件的扫描结果
+#[derive(Debug)]
+struct FileScan {
+    /// 按排序保留的前 offset + limit 条
+    entries: Vec<LogEntry>,
+    /// 全部命中条数
+    matched: usize,
+    /// 是否扫描到文件末尾
+    complete: bool,
+}
+
 /// 文件信息
 #[derive(Debug, Clone)]
 struct FileInfo {
@@ -736,38 +946,56 @@ impl LogIndexManager {
     
     /// 索引单个文件
     async fn index_file(&mut self, file_path: &Path) -> Result<(), LogError> {
+        let index = Self::build_index(file_path)?;
+        let key = file_path.to_string_lossy().to_string();
+        self.indices.insert(key, index);
+        
+        Ok(())
+    }
+    
+    /// 登记轮转或归档后的文件并保存清单，供之后完整性校验
+    pub fn record_file(&mut self, config: &LogConfig, file_path: &Path) -> Result<(), LogError> {
+        let index = Self::build_index(file_path)?;
+        self.indices.insert(file_path.to_string_lossy().to_string(), index);
+        self.stats.total_indices = self.indices.len();
+        self.save_indices(config)
+    }
+    
+    /// 从清单中移除已按策略清理的文件
+    pub fn forget_files(&mut self, config: &LogConfig, file_paths: &[PathBuf]) -> Result<(), LogError> {
+        let before = self.indices.len();
+        for path in file_paths {
+            self.indices.remove(path.to_string_lossy().as_ref());
+        }
+        if self.indices.len() == before {
+            return Ok(());
+        }
+        self.stats.total_indices = self.indices.len();
+        self.save_indices(config)
+    }
+    
+    /// 当前清单
+    pub fn indices(&self) -> &BTreeMap<String, LogIndex> {
+        &self.indices
+    }
+    
+    fn build_index(file_path: &Path) -> Result<LogIndex, LogError> {
         let metadata = fs::metadata(file_path).map_err(LogError::WriteError)?;
         let modified_time = DateTime::<Utc>::from(
             metadata.modified().map_err(LogError::WriteError)?
         );
         
         // 计算文件校验和
-        let checksum = self.calculate_file_checksum(file_path)?;
+        let checksum = integrity::file_checksum(file_path)?;
         
-        let index = LogIndex {
+        Ok(LogIndex {
             file_path: file_path.to_path_buf(),
             start_time: modified_time, // 简化实现，实际应该读取文件内容获取
             end_time: modified_time,
             log_count: 0, // 简化实现
             size_bytes: metadata.len(),
             checksum,
-        };
-        
-        let key = file_path.to_string_lossy().to_string();
-        self.indices.insert(key, index);
-        
-        Ok(())
-    }
-    
-    /// 计算文件校验和
-    fn calculate_file_checksum(&self, file_path: &Path) -> Result<String, LogError> {
-        use sha2::{Sha256, Digest};
-        
-        let content = fs::read(file_path).map_err(LogError::WriteError)?;
-        let mut hasher = Sha256::new();
-        hasher.update(&content);
-        
-        Ok(format!("{:x}", hasher.finalize()))
+        })
     }
     
     /// 获取统计信息
@@ -847,6 +1075,20 @@ mod tests {
         assert_eq!(entry.request_id, Some("req_123".to_string()));
     }
     
+    #[test]
+    fn test_malformed_lines_are_skipped() {
+        let truncated = r#"{"timestamp":"2024-01-15T10:30:45.123Z","level":"INFO","mess"#;
+        assert!(LogQueryEngine::parse_log_line(truncated, 1).unwrap().is_none());
+        
+        let oversized = format!("2024-01-15 18:30:45.123 [INFO ] [m] {}", "x".repeat(MAX_PARSE_LINE_BYTES));
+        assert!(LogQueryEngine::parse_log_line(&oversized, 1).unwrap().is_none());
+        
+        // 字段类型不符时取默认值
+        let entry = LogQueryEngine::parse_json_log_entry(&serde_json::json!({"level": 3, "message": ["x"]})).unwrap();
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.message, "");
+    }
+    
     #[tokio::test]
     async fn test_human_readable_log_parsing() {
         let log_line = "2024-01-15 18:30:45.123 [INFO ] [trading_service] 订单提交成功";
@@ -940,6 +1182,164 @@ mod tests {
         assert_eq!(result.entries[0].message, "正常消息");
     }
     
+    #[test]
+    fn test_compiled_query_prefilter() {
+        let query = LogQuery::new().with_keyword("Order").with_keyword("rb2501").with_module("ctp");
+        let matcher = CompiledQuery::new(&query);
+        
+        let line = r#"{"timestamp":"2024-01-15T10:30:45.123Z","level":"INFO","module":"ctp::trader","message":"ORDER accepted","instrument":"rb2501"}"#;
+        assert!(matcher.may_match(line.as_bytes()));
+        let entry = LogQueryEngine::parse_log_line(line, 1).unwrap().unwrap();
+        assert!(matcher.matches(&entry));
+        
+        // 缺少任一关键字或模块的行在解析前被跳过
+        assert!(!matcher.may_match(br#"{"module":"ctp","message":"order accepted"}"#));
+        assert!(!matcher.may_match(br#"{"module":"md","message":"order rb2501"}"#));
+        
+        // 非 ASCII 大小写关键字不做预筛选，由解析后的匹配处理
+        let query = LogQuery::new().with_keyword("ÉCHEC");
+        let matcher = CompiledQuery::new(&query);
+        assert!(matcher.may_match(b"anything"));
+    }
+    
+    #[tokio::test]
+    async fn test_search_gz_and_human_readable_lines() {
+        use flate2::{write::GzEncoder, Compression};
+        
+        let (_config, temp_dir) = create_test_config();
+        let path = temp_dir.path().join("app.log.gz");
+        let mut encoder = GzEncoder::new(fs::File::create(&path).unwrap(), Compression::default());
+        writeln!(encoder, "2024-01-15 18:30:45.123 [INFO ] [trading_service] 订单提交成功").unwrap();
+        writeln!(encoder, "2024-01-15 18:30:46.123 [ERROR] [trading_service] 订单提交失败\r").unwrap();
+        encoder.finish().unwrap();
+        
+        let query = LogQuery::new().with_keyword("失败");
+        let results = LogQueryEngine::search_file_sync(&path, &query, &AtomicBool::new(false)).unwrap().entries;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].level, LogLevel::Error);
+        assert_eq!(results[0].message, "订单提交失败");
+    }
+    
+    fn json_line(timestamp: &str, message: &str) -> String {
+        format!(r#"{{"timestamp":"{}","level":"INFO","module":"test_module","message":"{}"}}"#, timestamp, message)
+    }
+    
+    #[tokio::test]
+    async fn test_parallel_search_merges_pages_across_files() {
+        let (config, _temp_dir) = create_test_config();
+        config.ensure_directories().unwrap();
+        let app_dir = config.output_dir.join(LogType::App.as_str());
+        for (file, hours) in [("a.log", [1, 4]), ("b.log", [2, 5]), ("c.log", [3, 6])] {
+            let lines: Vec<String> = hours
+                .iter()
+                .map(|h| json_line(&format!("2024-01-15T0{}:00:00Z", h), &format!("m{}", h)))
+                .collect();
+            let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+            create_test_log_file(&app_dir.join(file), &lines).unwrap();
+        }
+        
+        let engine = LogQueryEngine::new(config).unwrap().with_concurrency(2);
+        let mut query = LogQuery::new().with_log_type(LogType::App).with_limit(2);
+        query.offset = 1;
+        let result = engine.query(query.clone()).await.unwrap();
+        let messages: Vec<&str> = result.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["m5", "m4"]);
+        assert_eq!(result.total_found, 6);
+        assert!(!result.is_estimate);
+        assert_eq!(result.files_searched, 3);
+        
+        query.sort_order = SortOrder::Ascending;
+        let result = engine.query(query).await.unwrap();
+        let messages: Vec<&str> = result.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["m2", "m3"]);
+    }
+    
+    #[tokio::test]
+    async fn test_offset_pages_and_estimated_total() {
+        let (config, _temp_dir) = create_test_config();
+        config.ensure_directories().unwrap();
+        let app_dir = config.output_dir.join(LogType::App.as_str());
+        let lines: Vec<String> = (0..5)
+            .map(|i| json_line(&format!("2024-01-15T0{}:00:00Z", i), &format!("m{}", i)))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+        create_test_log_file(&app_dir.join("app.log"), &lines).unwrap();
+        
+        let engine = LogQueryEngine::new(config).unwrap();
+        let mut query = LogQuery::new().with_log_type(LogType::App).with_limit(2);
+        query.offset = 4;
+        let result = engine.query(query.clone()).await.unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].message, "m0");
+        assert_eq!(result.total_found, 5);
+        
+        query.offset = 10;
+        let result = engine.query(query.clone()).await.unwrap();
+        assert!(result.entries.is_empty());
+        assert_eq!(result.total_found, 5);
+        
+        query.offset = MAX_QUERY_OFFSET + 1;
+        assert!(query.validate().is_err());
+        
+        // 取消的扫描标记为不完整
+        let cancelled = AtomicBool::new(true);
+        let many: Vec<String> = (0..CANCEL_CHECK_INTERVAL + 1).map(|_| json_line("2024-01-15T00:00:00Z", "m")).collect();
+        let many: Vec<&str> = many.iter().map(|l| l.as_str()).collect();
+        let path = app_dir.join("big.log");
+        create_test_log_file(&path, &many).unwrap();
+        let scan = LogQueryEngine::search_file_sync(&path, &LogQuery::new(), &cancelled).unwrap();
+        assert!(!scan.complete);
+        assert!(scan.matched < many.len());
+    }
+    
+    #[tokio::test]
+    async fn test_time_range_search_uses_seek_index() {
+        let (_config, temp_dir) = create_test_config();
+        let input = temp_dir.path().join("app.log");
+        let lines: Vec<String> = (0..24)
+            .map(|h| json_line(&format!("2024-01-15T{:02}:00:00Z", h), &format!("h{}", h)))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+        create_test_log_file(&input, &lines).unwrap();
+        let output = temp_dir.path().join("app.log.gz");
+        let index = seek_index::compress_with_index(&input, &output, 200).unwrap();
+        index.save(&output).unwrap();
+        
+        let start = "2024-01-15T10:00:00Z".parse().unwrap();
+        let end = "2024-01-15T12:00:00Z".parse().unwrap();
+        let query = LogQuery::new().with_time_range(start, end);
+        let scan = LogQueryEngine::search_file_sync(&output, &query, &AtomicBool::new(false)).unwrap();
+        assert_eq!(scan.matched, 3);
+        
+        // 无索引时完整解压所有 gzip 成员
+        fs::remove_file(seek_index::seek_index_path(&output)).unwrap();
+        let scan = LogQueryEngine::search_file_sync(&output, &LogQuery::new(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(scan.matched, 24);
+    }
+    
+    #[test]
+    fn test_files_older_than_page_cannot_contribute() {
+        let entry = |hour: u32| {
+            let line = json_line(&format!("2024-01-15T0{}:00:00Z", hour), "m");
+            LogQueryEngine::parse_log_line(&line, 1).unwrap().unwrap()
+        };
+        let mut results = vec![entry(1), entry(5), entry(3)];
+        let query = LogQuery::new().with_limit(2);
+        LogQueryEngine::retain_best(&mut results, 2, &query);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].timestamp.to_rfc3339(), "2024-01-15T03:00:00+00:00");
+        
+        let older = "2024-01-15T02:00:00Z".parse().unwrap();
+        let newer = "2024-01-15T04:00:00Z".parse().unwrap();
+        assert!(LogQueryEngine::cannot_contribute(&results, 2, &query, older));
+        assert!(!LogQueryEngine::cannot_contribute(&results, 2, &query, newer));
+        assert!(!LogQueryEngine::cannot_contribute(&results, 3, &query, older));
+        
+        let mut ascending = query.clone();
+        ascending.sort_order = SortOrder::Ascending;
+        assert!(!LogQueryEngine::cannot_contribute(&results, 2, &ascending, older));
+    }
+    
     #[tokio::test]
     async fn test_index_manager() {
         let (config, _temp_dir) = create_test_config();
@@ -0,0 +1,274 @@
+//! 日志查询语言
+//!
+//! 将 `level>=warn AND module:ctp AND instrument=rb2405 AND last 2h` 这类查询串
+//! 编译为 `LogQuery`。条件之间为与关系，`AND` 可省略：
+//! - `level=info` / `level>=warn` / `level<error`：日志级别（比较运算按严重程度）
+//! - `module:ctp`、`type:trading`：模块（包含匹配）与日志类型
+//! - `last 30m` / `last 2h` / `last 7d`、`today`、`since:2024-01-15`、`until:...`：时间范围
+//! - `limit=100`、`offset=200`、`sort:asc`：分页与排序
+//! - 其他 `key=value` / `key:value`：字段过滤，如 `instrument=rb2405`
+//! - 其余词或引号内短语：关键字
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{
+    config::{LogLevel, LogType},
+    error::LogError,
+    query::{LogQuery, SortBy, SortOrder, TimeRange},
+};
+
+const ALL_LEVELS: [LogLevel; 5] = [LogLevel::Trace, LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error];
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+/// 解析查询串为 `LogQuery`
+pub fn parse_log_query(input: &str) -> Result<LogQuery, LogError> {
+    let mut query = LogQuery::new();
+    let mut tokens = tokenize(input)?.into_iter();
+
+    while let Some((token, quoted)) = tokens.next() {
+        if quoted {
+            query.keywords.push(token);
+            continue;
+        }
+        match token.to_lowercase().as_str() {
+            "and" => continue,
+            "or" | "not" => return Err(dsl_error(format!("不支持 {} 运算，条件之间均为 AND", token))),
+            "last" => {
+                let (amount, _) = tokens.next().ok_or_else(|| dsl_error("last 后缺少时长，如 last 2h"))?;
+                let end = Utc::now();
+                set_time_range(&mut query, Some(end - parse_duration(&amount)?), Some(end));
+                continue;
+            }
+            "today" => {
+                let today = TimeRange::today();
+                set_time_range(&mut query, Some(today.start), Some(today.end));
+                continue;
+            }
+            _ => {}
+        }
+
+        match split_condition(&token) {
+            Some((key, op, value)) => apply_condition(&mut query, &key, op, value)?,
+            None => query.keywords.push(token),
+        }
+    }
+
+    query.validate()?;
+    Ok(query)
+}
+
+impl LogQuery {
+    /// 由查询语言构建，见 [`parse_log_query`]
+    pub fn parse(input: &str) -> Result<Self, LogError> {
+        parse_log_query(input)
+    }
+}
+
+fn dsl_error(message: impl Into<String>) -> LogError {
+    LogError::QueryError { query: message.into() }
+}
+
+/// 按空白切分，引号内整体作为一个词，返回（词, 是否带引号）
+fn tokenize(input: &str) -> Result<Vec<(String, bool)>, LogError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() || quoted {
+                    // 仅整个词被引号包住时视为关键字短语，key="a b" 仍按条件解析
+                    let whole = quoted && !current.contains(['=', ':', '<', '>']);
+                    tokens.push((std::mem::take(&mut current), whole));
+                }
+                quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(dsl_error("引号未闭合"));
+    }
+    if !current.is_empty() || quoted {
+        let whole = quoted && !current.contains(['=', ':', '<', '>']);
+        tokens.push((current, whole));
+    }
+    Ok(tokens)
+}
+
+/// 拆分 `key<op>value`，`:` 等同于 `=`
+fn split_condition(token: &str) -> Option<(String, Op, String)> {
+    let index = token.find(['=', ':', '<', '>'])?;
+    let (key, rest) = token.split_at(index);
+    if key.is_empty() {
+        return None;
+    }
+    let (op, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Op::Ge, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Op::Le, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Op::Gt, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Op::Lt, value)
+    } else {
+        (Op::Eq, &rest[1..])
+    };
+    Some((key.to_lowercase(), op, value.to_string()))
+}
+
+fn apply_condition(query: &mut LogQuery, key: &str, op: Op, value: String) -> Result<(), LogError> {
+    if value.is_empty() {
+        return Err(dsl_error(format!("条件 {} 缺少取值", key)));
+    }
+    if op != Op::Eq && key != "level" {
+        return Err(dsl_error(format!("{} 只支持 = 或 :", key)));
+    }
+
+    match key {
+        "level" => {
+            let level = LogLevel::from_str(&value).map_err(|_| dsl_error(format!("未知的日志级别: {}", value)))?;
+            let selected = ALL_LEVELS.into_iter().filter(|l| match op {
+                Op::Eq => *l == level,
+                Op::Ge => *l >= level,
+                Op::Gt => *l > level,
+                Op::Le => *l <= level,
+                Op::Lt => *l < level,
+            });
+            // 多个级别条件取交集
+            let selected: Vec<LogLevel> = selected
+                .filter(|l| query.levels.is_empty() || query.levels.contains(l))
+                .collect();
+            if selected.is_empty() {
+                return Err(dsl_error("级别条件没有交集"));
+            }
+            query.levels = selected;
+        }
+        "module" => query.modules.push(value),
+        "type" | "log_type" => {
+            let log_type = LogType::all()
+                .into_iter()
+                .find(|t| t.as_str().eq_ignore_ascii_case(&value))
+                .ok_or_else(|| dsl_error(format!("未知的日志类型: {}", value)))?;
+            query.log_types.push(log_type);
+        }
+        "keyword" | "text" => query.keywords.push(value),
+        "since" => set_time_range(query, Some(parse_time(&value)?), None),
+        "until" => set_time_range(query, None, Some(parse_time(&value)?)),
+        "limit" => query.limit = parse_number(key, &value)?,
+        "offset" => query.offset = parse_number(key, &value)?,
+        "sort" => {
+            let (sort_by, sort_order) = match value.to_lowercase().as_str() {
+                "asc" => (SortBy::Timestamp, SortOrder::Ascending),
+                "desc" => (SortBy::Timestamp, SortOrder::Descending),
+                "level" => (SortBy::Level, SortOrder::Descending),
+                "module" => (SortBy::Module, SortOrder::Ascending),
+                _ => return Err(dsl_error(format!("未知的排序方式: {}", value))),
+            };
+            query.sort_by = sort_by;
+            query.sort_order = sort_order;
+        }
+        _ => {
+            query.field_filters.insert(key.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+/// 合并时间范围，未给出的一端保留已有值或不限
+fn set_time_range(query: &mut LogQuery, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
+    let existing = query.time_range.take();
+    query.time_range = Some(TimeRange {
+        start: start
+            .or(existing.as_ref().map(|r| r.start))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        end: end.or(existing.map(|r| r.end)).unwrap_or_else(Utc::now),
+    });
+}
+
+/// 解析 `30s` / `15m` / `2h` / `7d`
+fn parse_duration(value: &str) -> Result<chrono::Duration, LogError> {
+    let value = value.trim().to_lowercase();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| dsl_error(format!("无效的时长: {}", value)))?;
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" | "min" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        _ => Err(dsl_error(format!("无效的时长单位: {}，可用 s/m/h/d", value))),
+    }
+}
+
+/// 解析 RFC3339 时间或 `YYYY-MM-DD`（按 UTC 零点）
+fn parse_time(value: &str) -> Result<DateTime<Utc>, LogError> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
+        .ok_or_else(|| dsl_error(format!("无效的时间: {}", value)))
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, LogError> {
+    value.parse().map_err(|_| dsl_error(format!("{} 必须是非负整数: {}", key, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_query() {
+        let query =
+            parse_log_query(r#"level>=warn AND module:ctp AND instrument=rb2405 AND last 2h "order rejected" limit=50"#)
+                .unwrap();
+
+        assert_eq!(query.levels, vec![LogLevel::Warn, LogLevel::Error]);
+        assert_eq!(query.modules, vec!["ctp".to_string()]);
+        assert_eq!(query.field_filters.get("instrument"), Some(&"rb2405".to_string()));
+        assert_eq!(query.keywords, vec!["order rejected".to_string()]);
+        assert_eq!(query.limit, 50);
+
+        let range = query.time_range.unwrap();
+        let span = range.end - range.start;
+        assert_eq!(span, chrono::Duration::hours(2));
+    }
+
+    #[test]
+    fn test_parse_misc_and_errors() {
+        let query = parse_log_query("type:trading level<info since:2024-01-15 sort:asc 超时").unwrap();
+        assert_eq!(query.log_types, vec![LogType::Trading]);
+        assert_eq!(query.levels, vec![LogLevel::Trace, LogLevel::Debug]);
+        assert_eq!(query.sort_order, SortOrder::Ascending);
+        assert_eq!(query.keywords, vec!["超时".to_string()]);
+        assert_eq!(query.time_range.unwrap().start.date_naive(), NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+
+        // 级别条件取交集
+        let query = parse_log_query("level>=info level<=warn").unwrap();
+        assert_eq!(query.levels, vec![LogLevel::Info, LogLevel::Warn]);
+
+        assert!(parse_log_query("level>=fatal").is_err());
+        assert!(parse_log_query("module:ctp OR module:trading").is_err());
+        assert!(parse_log_query("last 2y").is_err());
+        assert!(parse_log_query("module>ctp").is_err());
+        assert!(parse_log_query("\"unclosed").is_err());
+    }
+}
@@ -1,15 +1,15 @@
 use std::path::{Path, PathBuf};
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::time::{SystemTime, UNIX_EPOCH};
 use chrono::{DateTime, Utc, TimeZone};
-use flate2::write::GzEncoder;
-use flate2::Compression;
 use sha2::{Sha256, Digest};
 
 use super::{
     config::{LogConfig, LogType}, 
-    error::LogError
+    error::LogError,
+    query::LogIndexManager,
+    seek_index::{self, DEFAULT_SEEK_BLOCK_SIZE},
 };
 
 /// 日志轮转器 - 负责日志文件的轮转、压缩和清理
@@ -69,7 +69,7 @@ impl LogRotator {
         let metadata = fs::metadata(&log_file_path)
             .map_err(LogError::WriteError)?;
         
-        if metadata.len() >= config.max_file_size {
+        if metadata.len() >= config.policy_for(log_type).max_file_size {
             self.rotate_log_file(&log_file_path, log_type, config).await?;
         }
         
@@ -107,7 +107,8 @@ impl LogRotator {
             })?;
         
         // 如果启用压缩，压缩轮转的文件
-        if config.compression_enabled {
+        let mut archived_path = rotated_file_path.clone();
+        if config.policy_for(log_type).compression_enabled {
             let compressed_path = self.compress_log_file(&rotated_file_path).await?;
             
             // 删除原始轮转文件
@@ -115,6 +116,16 @@ impl LogRotator {
                 fs::remove_file(&rotated_file_path)
                     .map_err(LogError::WriteError)?;
             }
+            archived_path = compressed_path;
+        }
+        
+        // 登记归档文件校验和，供完整性校验使用
+        if let Err(e) = LogIndexManager::new(config).and_then(|mut m| m.record_file(config, &archived_path)) {
+            tracing::warn!(
+                file = %archived_path.display(),
+                error = %e,
+                "登记归档日志校验和失败"
+            );
         }
         
         // 更新统计信息
@@ -188,45 +199,35 @@ impl LogRotator {
     }
     
     /// 同步压缩文件（在 spawn_blocking 中调用）
+    ///
+    /// 分块压缩并写入寻址索引，索引写入失败不影响压缩结果
     fn compress_file_sync(
         input_path: &Path, 
         output_path: &Path
     ) -> Result<(), LogError> {
-        let mut input_file = fs::File::open(input_path)
-            .map_err(LogError::WriteError)?;
+        let index = seek_index::compress_with_index(input_path, output_path, DEFAULT_SEEK_BLOCK_SIZE)?;
         
-        let output_file = fs::File::create(output_path)
-            .map_err(LogError::WriteError)?;
-        
-        let mut encoder = GzEncoder::new(output_file, Compression::default());
-        let mut buffer = [0; 8192]; // 8KB 缓冲区
-        
-        loop {
-            let bytes_read = input_file.read(&mut buffer)
-                .map_err(LogError::WriteError)?;
-            
-            if bytes_read == 0 {
-                break;
-            }
-            
-            encoder.write_all(&buffer[..bytes_read])
-                .map_err(LogError::WriteError)?;
+        if let Err(e) = index.save(output_path) {
+            tracing::warn!(
+                file = %output_path.display(),
+                error = %e,
+                "写入压缩日志索引失败"
+            );
         }
         
-        encoder.finish()
-            .map_err(LogError::WriteError)?;
-        
         Ok(())
     }
     
     /// 清理过期的日志文件
     async fn cleanup_old_logs(&mut self, config: &LogConfig) -> Result<(), LogError> {
-        let retention_duration = chrono::Duration::days(config.retention_days as i64);
-        let cutoff_time = Utc::now() - retention_duration;
-        
+        let mut deleted = Vec::new();
         for log_type in LogType::all() {
-            self.cleanup_log_type_files(log_type, config, cutoff_time).await?;
+            // 各类型按自己的保留天数计算截止时间
+            let retention_duration = chrono::Duration::days(config.policy_for(log_type).retention_days as i64);
+            let cutoff_time = Utc::now() - retention_duration;
+            deleted.extend(self.cleanup_log_type_files(log_type, config, cutoff_time).await?);
         }
+        Self::forget_deleted(config, &deleted);
         
         self.rotation_stats.last_cleanup_time = Some(Utc::now());
         
@@ -239,11 +240,11 @@ impl LogRotator {
         log_type: LogType,
         config: &LogConfig,
         cutoff_time: DateTime<Utc>,
-    ) -> Result<(), LogError> {
+    ) -> Result<Vec<PathBuf>, LogError> {
         let log_dir = config.output_dir.join(log_type.as_str());
         
         if !log_dir.exists() {
-            return Ok(());
+            return Ok(Vec::new());
         }
         
         let entries = fs::read_dir(&log_dir)
@@ -256,7 +257,8 @@ impl LogRotator {
             let entry = entry.map_err(LogError::WriteError)?;
             let path = entry.path();
             
-            if path.is_file() {
+            // 索引文件随对应的压缩文件一起删除
+            if path.is_file() && !seek_index::is_seek_index(&path) {
                 let metadata = entry.metadata()
                     .map_err(LogError::WriteError)?;
                 
@@ -273,7 +275,8 @@ impl LogRotator {
         }
         
         // 检查文件数量限制
-        if files_to_keep.len() > config.max_files {
+        let max_files = config.policy_for(log_type).max_files;
+        if files_to_keep.len() > max_files {
             // 按修改时间排序，删除最旧的文件
             files_to_keep.sort_by_key(|path| {
                 fs::metadata(path)
@@ -281,7 +284,7 @@ impl LogRotator {
                     .unwrap_or(SystemTime::UNIX_EPOCH)
             });
             
-            let excess_count = files_to_keep.len() - config.max_files;
+            let excess_count = files_to_keep.len() - max_files;
             for path in files_to_keep.drain(..excess_count) {
                 let size = fs::metadata(&path)
                     .map(|m| m.len())
@@ -291,9 +294,12 @@ impl LogRotator {
         }
         
         // 删除标记的文件
+        let mut deleted = Vec::new();
         for (file_path, file_size) in files_to_delete {
             match fs::remove_file(&file_path) {
                 Ok(_) => {
+                    let _ = fs::remove_file(seek_index::seek_index_path(&file_path));
+                    deleted.push(file_path.clone());
                     self.rotation_stats.total_deletions += 1;
                     self.rotation_stats.bytes_deleted += file_size;
                     
@@ -313,7 +319,17 @@ impl LogRotator {
             }
         }
         
-        Ok(())
+        Ok(deleted)
+    }
+    
+    /// 将已清理的文件从校验清单中移除，避免之后被报告为缺失
+    fn forget_deleted(config: &LogConfig, deleted: &[PathBuf]) {
+        if deleted.is_empty() {
+            return;
+        }
+        if let Err(e) = LogIndexManager::new(config).and_then(|mut m| m.forget_files(config, deleted)) {
+            tracing::warn!(error = %e, "更新日志校验清单失败");
+        }
     }
     
     /// 手动轮转指定的日志文件
@@ -537,6 +553,7 @@ impl LogRotator {
         // 删除最旧的文件，直到释放足够空间
         let target_cleanup_size = 500 * 1024 * 1024; // 500MB
         let mut cleaned_size = 0u64;
+        let mut deleted = Vec::new();
         
         for (path, _, size) in compressed_files {
             if cleaned_size >= target_cleanup_size {
@@ -545,6 +562,8 @@ impl LogRotator {
             
             match fs::remove_file(&path) {
                 Ok(_) => {
+                    let _ = fs::remove_file(seek_index::seek_index_path(&path));
+                    deleted.push(path.clone());
                     cleaned_size += size;
                     self.rotation_stats.total_deletions += 1;
                     self.rotation_stats.bytes_deleted += size;
@@ -565,6 +584,8 @@ impl LogRotator {
             }
         }
         
+        Self::forget_deleted(&self.config, &deleted);
+        
         tracing::info!(
             cleaned_mb = cleaned_size / (1024 * 1024),
             "紧急清理完成"
@@ -607,6 +628,7 @@ impl DiskUsage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::config::LogTypePolicy;
     use tempfile::TempDir;
     use std::fs::OpenOptions;
     use std::io::Write as IoWrite;
@@ -725,6 +747,39 @@ mod tests {
         assert!(stats.total_deletions > 0);
     }
     
+    #[tokio::test]
+    async fn test_rotation_follows_type_policy() {
+        let (config, _temp_dir) = create_test_config();
+        let config = config
+            .with_type_policy(LogType::Trading, LogTypePolicy::new().with_compression(false).with_retention_days(365))
+            .with_type_policy(LogType::Ctp, LogTypePolicy::new().with_max_file_size(1024 * 1024));
+        config.ensure_directories().unwrap();
+        
+        for log_type in [LogType::App, LogType::Trading, LogType::Ctp] {
+            create_test_log_file(&config.get_log_file_path(log_type), 2048).unwrap();
+        }
+        // 交易日志的旧文件仍在保留期内
+        let old_trading = config.output_dir.join("trading").join("old.log");
+        create_test_log_file(&old_trading, 128).unwrap();
+        let old_time = SystemTime::now() - std::time::Duration::from_secs(86400 * 2);
+        filetime::set_file_mtime(&old_trading, filetime::FileTime::from_system_time(old_time)).unwrap();
+        
+        let mut rotator = LogRotator::new(&config).unwrap();
+        rotator.check_and_rotate(&config).await.unwrap();
+        
+        let names = |log_type: LogType| -> Vec<String> {
+            fs::read_dir(config.output_dir.join(log_type.as_str())).unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect()
+        };
+        assert!(names(LogType::App).iter().any(|n| n.ends_with(".gz")));
+        assert!(!names(LogType::Trading).iter().any(|n| n.ends_with(".gz")));
+        assert!(old_trading.exists());
+        // ctp 日志未达到自己的大小上限，不轮转
+        assert!(config.get_log_file_path(LogType::Ctp).exists());
+        assert_eq!(rotator.get_stats().total_rotations, 2);
+    }
+    
     #[tokio::test]
     async fn test_disk_usage_calculation() {
         let (config, _temp_dir) = create_test_config();
@@ -1,9 +1,143 @@
 use std::collections::HashMap;
-use super::{config::{LogConfig, LogType, LogLevel}, error::LogError, LogEntry};
+use serde::{Deserialize, Serialize};
+use super::{config::{LogConfig, LogType, LogLevel}, context::LogContext, error::LogError, LogEntry};
+
+/// 声明式路由规则
+///
+/// 所有给出的条件同时满足时命中，日志写入 `targets` 中的每个类型。
+/// 规则按配置顺序匹配，第一条命中的规则生效；都未命中时回退到内置的模块/字段推断
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRule {
+    /// 规则名，用于路由测试结果
+    pub name: String,
+    /// 匹配 `log_type` 字段的取值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_type: Option<String>,
+    /// 模块前缀，如 `inspirai_trader::ctp`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_prefix: Option<String>,
+    /// 最低级别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<LogLevel>,
+    /// 字段取值，`*` 表示字段存在即可
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, String>,
+    /// 目标日志类型，多个时同时写入
+    pub targets: Vec<LogType>,
+}
+
+impl RouteRule {
+    pub fn new(name: &str, targets: Vec<LogType>) -> Self {
+        Self {
+            name: name.to_string(),
+            log_type: None,
+            module_prefix: None,
+            min_level: None,
+            fields: HashMap::new(),
+            targets,
+        }
+    }
+
+    pub fn with_log_type(mut self, log_type: &str) -> Self {
+        self.log_type = Some(log_type.to_string());
+        self
+    }
+
+    pub fn with_module_prefix(mut self, prefix: &str) -> Self {
+        self.module_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn with_min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    pub fn with_field(mut self, field: &str, value: &str) -> Self {
+        self.fields.insert(field.to_string(), value.to_string());
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), LogError> {
+        if self.targets.is_empty() {
+            return Err(LogError::InvalidConfig {
+                field: format!("路由规则 {} 没有目标日志类型", self.name),
+            });
+        }
+        if self.log_type.is_none() && self.module_prefix.is_none() && self.min_level.is_none() && self.fields.is_empty() {
+            return Err(LogError::InvalidConfig {
+                field: format!("路由规则 {} 至少需要一个匹配条件", self.name),
+            });
+        }
+        Ok(())
+    }
+
+    /// 是否命中该日志条目
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.min_level.is_none_or(|level| entry.level >= level)
+            && self.module_prefix.as_deref().is_none_or(|prefix| entry.module.starts_with(prefix))
+            && self.log_type.as_deref().is_none_or(|expected| field_equals(entry, "log_type", expected))
+            && self.fields.iter().all(|(field, expected)| field_equals(entry, field, expected))
+    }
+}
+
+/// 去掉核心库自身的 crate 名后的模块路径
+///
+/// crate 名 `inspirai_ctp_core` 含 "ctp"，不去掉的话核心库内所有日志都会命中 ctp 模块规则
+pub fn routable_module(module: &str) -> &str {
+    module
+        .strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::"))
+        .unwrap_or(module)
+}
+
+fn field_equals(entry: &LogEntry, field: &str, expected: &str) -> bool {
+    match entry.fields.get(field) {
+        Some(_) if expected == "*" => true,
+        Some(serde_json::Value::String(value)) => value == expected,
+        Some(value) => value.to_string().as_str() == expected,
+        None => false,
+    }
+}
+
+/// 路由测试用的样例事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteSample {
+    pub module: String,
+    pub level: LogLevel,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+impl RouteSample {
+    pub fn into_entry(self) -> LogEntry {
+        LogEntry {
+            timestamp: chrono::Utc::now(),
+            level: self.level,
+            context: LogContext::new(self.level, &self.module),
+            module: self.module,
+            thread_id: "route_test".to_string(),
+            message: self.message,
+            request_id: None,
+            session_id: None,
+            fields: self.fields,
+        }
+    }
+}
+
+/// 路由结果及命中的规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteDecision {
+    /// 命中的声明式规则名，`None` 表示使用内置推断
+    pub matched_rule: Option<String>,
+    pub targets: Vec<LogType>,
+}
 
 /// 日志路由器，负责根据日志内容将日志分发到不同的输出目标
 #[derive(Debug)]
 pub struct LogRouter {
+    route_rules: Vec<RouteRule>,
     routing_rules: HashMap<String, LogType>,
     level_filters: HashMap<LogType, LogLevel>,
     error_always_duplicate: bool,
@@ -13,6 +147,7 @@ impl LogRouter {
     /// 创建新的日志路由器
     pub fn new(config: &LogConfig) -> Result<Self, LogError> {
         let mut router = Self {
+            route_rules: Vec::new(),
             routing_rules: HashMap::new(),
             level_filters: HashMap::new(),
             error_always_duplicate: true,
@@ -26,6 +161,12 @@ impl LogRouter {
     
     /// 初始化路由规则
     fn init_routing_rules(&mut self, config: &LogConfig) -> Result<(), LogError> {
+        // 配置中的声明式规则优先
+        for rule in &config.routing_rules {
+            rule.validate()?;
+        }
+        self.route_rules = config.routing_rules.clone();
+        
         // 基于模块名的路由规则
         self.routing_rules.insert("ctp".to_string(), LogType::Ctp);
         self.routing_rules.insert("trading".to_string(), LogType::Trading);
@@ -51,44 +192,51 @@ impl LogRouter {
     
     /// 路由日志条目到适当的日志类型
     pub fn route(&self, entry: &LogEntry) -> Option<LogType> {
-        // 首先检查级别过滤
-        let primary_type = self.determine_primary_type(entry);
+        self.route_all(entry).first().copied()
+    }
+    
+    /// 获取需要写入的所有日志类型（包括重复写入）
+    pub fn route_all(&self, entry: &LogEntry) -> Vec<LogType> {
+        self.explain(entry).targets
+    }
+    
+    /// 给出路由结果及命中的规则，用于校验路由配置
+    pub fn explain(&self, entry: &LogEntry) -> RouteDecision {
+        let (matched_rule, candidates) = match self.route_rules.iter().find(|rule| rule.matches(entry)) {
+            Some(rule) => (Some(rule.name.clone()), rule.targets.clone()),
+            None => (None, self.determine_primary_type(entry).into_iter().collect()),
+        };
         
-        if let Some(log_type) = primary_type {
+        // 级别过滤按目标分别判断
+        let mut log_types: Vec<LogType> = Vec::new();
+        for log_type in candidates {
+            if log_types.contains(&log_type) {
+                continue;
+            }
             if let Some(&min_level) = self.level_filters.get(&log_type) {
                 if entry.level < min_level {
-                    return None; // 级别不够，过滤掉
+                    continue; // 级别不够，过滤掉
                 }
             }
-            
-            Some(log_type)
-        } else {
-            None
+            log_types.push(log_type);
         }
-    }
-    
-    /// 获取需要写入的所有日志类型（包括重复写入）
-    pub fn route_all(&self, entry: &LogEntry) -> Vec<LogType> {
-        let mut log_types = Vec::new();
         
-        // 获取主要日志类型
-        if let Some(primary_type) = self.route(entry) {
-            log_types.push(primary_type);
-            
+        if let Some(&primary_type) = log_types.first() {
             // 错误级别的日志同时写入错误日志
-            if self.error_always_duplicate && entry.level >= LogLevel::Error {
-                if primary_type != LogType::Error {
-                    log_types.push(LogType::Error);
-                }
+            if self.error_always_duplicate && entry.level >= LogLevel::Error && !log_types.contains(&LogType::Error) {
+                log_types.push(LogType::Error);
             }
             
-            // 性能相关的日志可能也需要写入性能日志
-            if self.is_performance_related(entry) && primary_type != LogType::Performance {
+            // 内置推断下，性能相关的日志也写入性能日志
+            if matched_rule.is_none() && self.is_performance_related(entry) && primary_type != LogType::Performance {
                 log_types.push(LogType::Performance);
             }
         }
         
-        log_types
+        RouteDecision {
+            matched_rule,
+            targets: log_types,
+        }
     }
     
     /// 确定主要的日志类型
@@ -104,12 +252,13 @@ impl LogRouter {
         }
         
         // 2. 基于模块名匹配
+        let module = routable_module(&entry.module);
         for (rule_pattern, &log_type) in &self.routing_rules {
             if rule_pattern.starts_with("log_type:") {
                 continue; // 跳过已处理的显式类型
             }
             
-            if entry.module.contains(rule_pattern) {
+            if module.contains(rule_pattern) {
                 return Some(log_type);
             }
         }
@@ -213,6 +362,20 @@ impl LogRouter {
         self.error_always_duplicate = enabled;
     }
     
+    /// 替换声明式路由规则
+    pub fn set_route_rules(&mut self, rules: Vec<RouteRule>) -> Result<(), LogError> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        self.route_rules = rules;
+        Ok(())
+    }
+    
+    /// 获取声明式路由规则
+    pub fn get_route_rules(&self) -> &[RouteRule] {
+        &self.route_rules
+    }
+    
     /// 获取所有路由规则
     pub fn get_routing_rules(&self) -> &HashMap<String, LogType> {
         &self.routing_rules
@@ -226,7 +389,7 @@ impl LogRouter {
     /// 获取路由统计信息
     pub fn get_routing_stats(&self) -> RoutingStats {
         RoutingStats {
-            total_rules: self.routing_rules.len(),
+            total_rules: self.routing_rules.len() + self.route_rules.len(),
             level_filters_count: self.level_filters.len(),
             error_duplication_enabled: self.error_always_duplicate,
             supported_log_types: LogType::all(),
@@ -491,7 +654,21 @@ mod tests {
         let routed_type = router.route(&entry);
         assert_eq!(routed_type, Some(LogType::Trading));
     }
-    
+
+    #[test]
+    fn test_core_crate_name_not_routed_as_ctp() {
+        let config = create_test_config();
+        let router = LogRouter::new(&config).unwrap();
+
+        let mut entry = create_test_entry("inspirai_ctp_core::logging::writer", LogLevel::Info);
+        assert_eq!(router.route(&entry), Some(LogType::App));
+        entry.fields.insert("account_id".to_string(), "12345".into());
+        assert_eq!(router.route(&entry), Some(LogType::Trading));
+
+        let ctp_entry = create_test_entry("inspirai_ctp_core::ctp::client", LogLevel::Info);
+        assert_eq!(router.route(&ctp_entry), Some(LogType::Ctp));
+    }
+
     #[test]
     fn test_explicit_log_type_routing() {
         let config = create_test_config();
@@ -603,4 +780,62 @@ mod tests {
         assert_eq!(stats.level_filters_count, LogType::all().len());
         assert_eq!(stats.supported_log_types, LogType::all());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_declarative_rules_fan_out() {
+        let mut config = create_test_config();
+        config.routing_rules = vec![
+            RouteRule::new("risk", vec![LogType::Trading, LogType::Performance])
+                .with_module_prefix("inspirai_trader::risk")
+                .with_field("check", "*"),
+            RouteRule::new("audit", vec![LogType::Trading]).with_log_type("audit"),
+        ];
+        let router = LogRouter::new(&config).unwrap();
+        
+        let mut entry = create_test_entry("inspirai_trader::risk::engine", LogLevel::Info);
+        entry.fields.insert("check".to_string(), "margin".into());
+        let decision = router.explain(&entry);
+        assert_eq!(decision.matched_rule.as_deref(), Some("risk"));
+        assert_eq!(decision.targets, vec![LogType::Trading, LogType::Performance]);
+        
+        // 规则目标之外仍追加错误日志
+        entry.level = LogLevel::Error;
+        assert_eq!(
+            router.route_all(&entry),
+            vec![LogType::Trading, LogType::Performance, LogType::Error]
+        );
+        
+        let mut audit = create_test_entry("ctp::client", LogLevel::Info);
+        audit.fields.insert("log_type".to_string(), "audit".into());
+        assert_eq!(router.route(&audit), Some(LogType::Trading));
+        
+        // 未命中规则时回退到内置推断
+        let decision = router.explain(&create_test_entry("ctp::client", LogLevel::Info));
+        assert_eq!(decision.matched_rule, None);
+        assert_eq!(decision.targets, vec![LogType::Ctp]);
+    }
+    
+    #[test]
+    fn test_route_rule_validation_and_sample() {
+        let mut config = create_test_config();
+        config.routing_rules = vec![RouteRule::new("empty", vec![LogType::App])];
+        assert!(LogRouter::new(&config).is_err());
+        assert!(config.validate().is_err());
+        
+        config.routing_rules = vec![RouteRule::new("no_target", Vec::new()).with_min_level(LogLevel::Warn)];
+        assert!(LogRouter::new(&config).is_err());
+        
+        let rules: Vec<RouteRule> = serde_json::from_str(
+            r#"[{"name":"md_warn","module_prefix":"md","min_level":"Warn","targets":["MarketData","Error"]}]"#,
+        )
+        .unwrap();
+        let mut router = LogRouter::new(&create_test_config()).unwrap();
+        router.set_route_rules(rules).unwrap();
+        
+        let sample: RouteSample =
+            serde_json::from_str(r#"{"module":"md::spi","level":"Warn","fields":{"instrument_id":"rb2405"}}"#).unwrap();
+        let decision = router.explain(&sample.into_entry());
+        assert_eq!(decision.matched_rule.as_deref(), Some("md_warn"));
+        assert_eq!(decision.targets, vec![LogType::MarketData, LogType::Error]);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::{config::LogLevel, error::LogError};
+
+/// 采样/限流规则
+///
+/// 只作用于 `max_level` 及以下的日志，WARN 及以上级别始终保留
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingRule {
+    /// 模块前缀，如 `inspirai_trader::ctp::md_spi`
+    pub module: String,
+    /// 限定调用点，匹配 `文件:行号` 片段，如 `md_spi.rs:212`；为空时作用于模块内所有调用点
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callsite: Option<String>,
+    /// 每 N 条保留 1 条，1 表示不采样
+    #[serde(default = "default_sample_every")]
+    pub sample_every: u32,
+    /// 每个调用点每秒最多保留的条数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_second: Option<u32>,
+    /// 规则适用的最高级别
+    #[serde(default = "default_max_level")]
+    pub max_level: LogLevel,
+}
+
+fn default_sample_every() -> u32 {
+    1
+}
+
+fn default_max_level() -> LogLevel {
+    LogLevel::Debug
+}
+
+impl SamplingRule {
+    /// 模块内每 N 条保留 1 条
+    pub fn sample(module: &str, every: u32) -> Self {
+        Self {
+            module: module.to_string(),
+            callsite: None,
+            sample_every: every,
+            max_per_second: None,
+            max_level: default_max_level(),
+        }
+    }
+
+    /// 模块内每个调用点每秒最多保留 N 条
+    pub fn rate_limit(module: &str, max_per_second: u32) -> Self {
+        Self {
+            max_per_second: Some(max_per_second),
+            ..Self::sample(module, 1)
+        }
+    }
+
+    pub fn with_callsite(mut self, callsite: &str) -> Self {
+        self.callsite = Some(callsite.to_string());
+        self
+    }
+
+    pub fn with_max_level(mut self, level: LogLevel) -> Self {
+        self.max_level = level;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), LogError> {
+        if self.module.is_empty() {
+            return Err(LogError::InvalidConfig {
+                field: "采样规则的 module 不能为空".to_string(),
+            });
+        }
+        if self.sample_every == 0 || self.max_per_second == Some(0) {
+            return Err(LogError::InvalidConfig {
+                field: format!("{} 的 sample_every / max_per_second 必须大于 0", self.module),
+            });
+        }
+        if self.max_level >= LogLevel::Warn {
+            return Err(LogError::InvalidConfig {
+                field: format!("{} 的采样级别不能包含 WARN 及以上", self.module),
+            });
+        }
+        Ok(())
+    }
+
+    fn matches(&self, target: &str, callsite: &str, level: LogLevel) -> bool {
+        level <= self.max_level
+            && target.starts_with(&self.module)
+            && self.callsite.as_deref().is_none_or(|c| callsite.contains(c))
+    }
+}
+
+/// 采样统计，按规则汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingStats {
+    pub rule: SamplingRule,
+    pub seen: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug)]
+struct CallsiteState {
+    rule: usize,
+    seen: u64,
+    window_start: Instant,
+    window_count: u32,
+}
+
+#[derive(Debug, Default)]
+struct SamplerInner {
+    rules: Vec<SamplingRule>,
+    /// 调用点 -> 命中的规则与计数，`None` 表示不受任何规则约束
+    callsites: HashMap<&'static str, Option<CallsiteState>>,
+    /// 各规则的 (命中数, 丢弃数)
+    totals: Vec<(u64, u64)>,
+}
+
+/// 日志采样器
+///
+/// 对高频调用点按规则做 1/N 采样和每秒限流，规则可在运行时替换。
+/// 调用点按 tracing 元数据名（`event 文件:行号`）区分
+#[derive(Debug, Clone, Default)]
+pub struct LogSampler {
+    inner: Arc<Mutex<SamplerInner>>,
+}
+
+impl LogSampler {
+    pub fn new(rules: Vec<SamplingRule>) -> Result<Self, LogError> {
+        let sampler = Self::default();
+        sampler.set_rules(rules)?;
+        Ok(sampler)
+    }
+
+    /// 替换全部规则并清空计数
+    pub fn set_rules(&self, rules: Vec<SamplingRule>) -> Result<(), LogError> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.totals = vec![(0, 0); rules.len()];
+        inner.rules = rules;
+        inner.callsites.clear();
+        Ok(())
+    }
+
+    pub fn rules(&self) -> Vec<SamplingRule> {
+        self.inner.lock().unwrap().rules.clone()
+    }
+
+    pub fn stats(&self) -> Vec<SamplingStats> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .rules
+            .iter()
+            .zip(&inner.totals)
+            .map(|(rule, (seen, dropped))| SamplingStats {
+                rule: rule.clone(),
+                seen: *seen,
+                dropped: *dropped,
+            })
+            .collect()
+    }
+
+    /// 判断该条日志是否保留
+    pub fn should_log(&self, target: &str, callsite: &'static str, level: LogLevel) -> bool {
+        if level >= LogLevel::Warn {
+            return true;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.rules.is_empty() {
+            return true;
+        }
+        let inner = &mut *inner;
+
+        let state = inner.callsites.entry(callsite).or_insert_with(|| {
+            inner
+                .rules
+                .iter()
+                .position(|r| r.matches(target, callsite, level))
+                .map(|rule| CallsiteState {
+                    rule,
+                    seen: 0,
+                    window_start: Instant::now(),
+                    window_count: 0,
+                })
+        });
+        let Some(state) = state else {
+            return true;
+        };
+        let rule = &inner.rules[state.rule];
+
+        state.seen += 1;
+        let mut keep = (state.seen - 1) % rule.sample_every as u64 == 0;
+        if keep {
+            if let Some(max) = rule.max_per_second {
+                let now = Instant::now();
+                if now.duration_since(state.window_start) >= Duration::from_secs(1) {
+                    state.window_start = now;
+                    state.window_count = 0;
+                }
+                keep = state.window_count < max;
+                if keep {
+                    state.window_count += 1;
+                }
+            }
+        }
+
+        let totals = &mut inner.totals[state.rule];
+        totals.0 += 1;
+        if !keep {
+            totals.1 += 1;
+        }
+        keep
+    }
+}
+
+/// 采样层，置于路由和输出层之前，被丢弃的事件不会到达任何输出
+pub struct SamplingLayer {
+    sampler: LogSampler,
+}
+
+impl SamplingLayer {
+    pub fn new(sampler: LogSampler) -> Self {
+        Self { sampler }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SamplingLayer {
+    fn event_enabled(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let metadata = event.metadata();
+        self.sampler
+            .should_log(metadata.target(), metadata.name(), LogLevel::from(*metadata.level()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK_SITE: &str = "event src/ctp/md_spi.rs:212";
+    const OTHER_SITE: &str = "event src/ctp/md_spi.rs:300";
+
+    #[test]
+    fn test_sampling_keeps_one_in_n_and_never_warn() {
+        let sampler = LogSampler::new(vec![
+            SamplingRule::sample("inspirai_trader::ctp::md_spi", 100).with_callsite("md_spi.rs:212")
+        ])
+        .unwrap();
+        let target = "inspirai_trader::ctp::md_spi";
+
+        let kept = (0..1000).filter(|_| sampler.should_log(target, TICK_SITE, LogLevel::Debug)).count();
+        assert_eq!(kept, 10);
+        // 其他调用点、INFO 级别和 WARN 以上不受影响
+        assert!((0..10).all(|_| sampler.should_log(target, OTHER_SITE, LogLevel::Debug)));
+        assert!((0..10).all(|_| sampler.should_log(target, "event src/ctp/md_spi.rs:1", LogLevel::Info)));
+        assert!((0..10).all(|_| sampler.should_log(target, TICK_SITE, LogLevel::Warn)));
+
+        let stats = sampler.stats();
+        assert_eq!(stats[0].seen, 1000);
+        assert_eq!(stats[0].dropped, 990);
+    }
+
+    #[test]
+    fn test_rate_limit_and_runtime_rules() {
+        let sampler = LogSampler::default();
+        let target = "inspirai_trader::ctp::md_spi";
+        assert!((0..100).all(|_| sampler.should_log(target, TICK_SITE, LogLevel::Trace)));
+
+        sampler.set_rules(vec![SamplingRule::rate_limit("inspirai_trader::ctp", 5)]).unwrap();
+        let kept = (0..100).filter(|_| sampler.should_log(target, TICK_SITE, LogLevel::Debug)).count();
+        assert_eq!(kept, 5);
+        // 限流按调用点计算
+        assert!(sampler.should_log(target, OTHER_SITE, LogLevel::Debug));
+
+        assert!(sampler.set_rules(vec![SamplingRule::sample("x", 0)]).is_err());
+        assert!(sampler
+            .set_rules(vec![SamplingRule::sample("x", 10).with_max_level(LogLevel::Warn)])
+            .is_err());
+        assert_eq!(sampler.rules().len(), 1);
+    }
+}
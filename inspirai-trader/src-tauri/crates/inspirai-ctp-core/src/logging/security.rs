@@ -163,7 +163,7 @@ impl DataMasker {
     }
     
     /// 脱敏文本内容
-    fn mask_text(&self, text: &str) -> String {
+    pub fn mask_text(&self, text: &str) -> String {
         let mut result = text.to_string();
         
         for pattern in &self.patterns {
@@ -345,11 +345,12 @@ impl AccessController {
     /// 确定日志条目的类型
     fn determine_log_type(&self, entry: &LogEntry) -> super::config::LogType {
         // 简化的日志类型确定逻辑
-        if entry.module.contains("ctp") {
+        let module = super::router::routable_module(&entry.module);
+        if module.contains("ctp") {
             super::config::LogType::Ctp
-        } else if entry.module.contains("trading") {
+        } else if module.contains("trading") {
             super::config::LogType::Trading
-        } else if entry.module.contains("market_data") {
+        } else if module.contains("market_data") {
             super::config::LogType::MarketData
         } else if entry.level >= super::config::LogLevel::Error {
             super::config::LogType::Error
@@ -0,0 +1,233 @@
+//! 压缩日志的分块寻址索引
+//!
+//! 轮转压缩时把日志按行切成约 1MB 的块，每块写成独立的 gzip 成员（整体仍是合法的
+//! gzip 文件），并在旁边写入 `<文件>.gz.idx`，记录每块的压缩偏移、长度和首条时间戳。
+//! 带时间范围的查询据此只解压相关的块。
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use super::{error::LogError, query::TimeRange};
+
+/// 索引文件扩展名
+pub const SEEK_INDEX_EXTENSION: &str = "idx";
+/// 默认块大小（未压缩字节数）
+pub const DEFAULT_SEEK_BLOCK_SIZE: usize = 1024 * 1024;
+const SEEK_INDEX_VERSION: u32 = 1;
+
+/// 一个压缩块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeekBlock {
+    /// 在 .gz 文件中的字节偏移
+    pub offset: u64,
+    /// 压缩后长度
+    pub compressed_len: u64,
+    /// 块内第一条可解析时间戳
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub line_count: usize,
+}
+
+/// 压缩文件的寻址索引
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeekIndex {
+    pub version: u32,
+    pub block_size: usize,
+    pub blocks: Vec<SeekBlock>,
+}
+
+impl SeekIndex {
+    /// 读取压缩文件旁的索引，不存在或损坏时返回空
+    pub fn load(gz_path: &Path) -> Option<Self> {
+        let content = fs::read(seek_index_path(gz_path)).ok()?;
+        let index: SeekIndex = serde_json::from_slice(&content).ok()?;
+        (index.version == SEEK_INDEX_VERSION).then_some(index)
+    }
+
+    /// 写入压缩文件旁的索引
+    pub fn save(&self, gz_path: &Path) -> Result<(), LogError> {
+        let content = serde_json::to_vec(self)?;
+        fs::write(seek_index_path(gz_path), content).map_err(LogError::WriteError)
+    }
+
+    /// 可能包含时间范围内日志的块，相邻块合并为 (偏移, 长度)
+    ///
+    /// 块覆盖从其首条时间到下一块首条时间，缺少时间戳的块总是保留
+    pub fn ranges_for(&self, range: &TimeRange) -> Vec<(u64, u64)> {
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for (i, block) in self.blocks.iter().enumerate() {
+            let starts_after = block.first_timestamp.is_some_and(|t| t > range.end);
+            let next_start = self.blocks.get(i + 1).and_then(|b| b.first_timestamp);
+            let ends_before = next_start.is_some_and(|t| t < range.start);
+            if starts_after || ends_before {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some((offset, len)) if *offset + *len == block.offset => *len += block.compressed_len,
+                _ => ranges.push((block.offset, block.compressed_len)),
+            }
+        }
+        ranges
+    }
+}
+
+/// 索引文件路径：`app.log.gz` -> `app.log.gz.idx`
+pub fn seek_index_path(gz_path: &Path) -> PathBuf {
+    let mut name = gz_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(SEEK_INDEX_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// 是否为索引文件
+pub fn is_seek_index(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some(SEEK_INDEX_EXTENSION)
+}
+
+/// 分块压缩并生成索引
+pub fn compress_with_index(input_path: &Path, output_path: &Path, block_size: usize) -> Result<SeekIndex, LogError> {
+    let mut reader = BufReader::new(fs::File::open(input_path).map_err(LogError::WriteError)?);
+    let mut output = fs::File::create(output_path).map_err(LogError::WriteError)?;
+
+    let mut blocks = Vec::new();
+    let mut block = Vec::with_capacity(block_size);
+    let mut first_timestamp = None;
+    let mut line_count = 0;
+    let mut offset = 0u64;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line).map_err(LogError::WriteError)?;
+        if read > 0 {
+            if first_timestamp.is_none() {
+                first_timestamp = std::str::from_utf8(&line).ok().and_then(line_timestamp);
+            }
+            block.extend_from_slice(&line);
+            line_count += 1;
+        }
+        if (read == 0 && !block.is_empty()) || block.len() >= block_size {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&block).map_err(LogError::WriteError)?;
+            let compressed = encoder.finish().map_err(LogError::WriteError)?;
+            output.write_all(&compressed).map_err(LogError::WriteError)?;
+
+            blocks.push(SeekBlock {
+                offset,
+                compressed_len: compressed.len() as u64,
+                first_timestamp: first_timestamp.take(),
+                line_count,
+            });
+            offset += compressed.len() as u64;
+            block.clear();
+            line_count = 0;
+        }
+        if read == 0 {
+            break;
+        }
+    }
+    output.flush().map_err(LogError::WriteError)?;
+
+    Ok(SeekIndex {
+        version: SEEK_INDEX_VERSION,
+        block_size,
+        blocks,
+    })
+}
+
+/// 只解压指定字节范围的读取器
+pub fn open_ranges(gz_path: &Path, ranges: &[(u64, u64)]) -> Result<Box<dyn Read + Send>, LogError> {
+    let mut reader: Box<dyn Read + Send> = Box::new(std::io::empty());
+    for &(offset, len) in ranges {
+        let mut file = fs::File::open(gz_path).map_err(LogError::WriteError)?;
+        file.seek(SeekFrom::Start(offset)).map_err(LogError::WriteError)?;
+        reader = Box::new(reader.chain(MultiGzDecoder::new(file.take(len))));
+    }
+    Ok(reader)
+}
+
+/// 提取日志行时间戳，支持 JSON 的 `timestamp` 字段和文本格式行首时间
+fn line_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(line).ok()?;
+        let timestamp = json.get("timestamp")?.as_str()?;
+        return DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc));
+    }
+    let prefix = line.get(..23)?;
+    NaiveDateTime::parse_from_str(prefix, "%Y-%m-%d %H:%M:%S%.3f")
+        .ok()
+        .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_log(path: &Path, hours: std::ops::Range<u32>) {
+        let mut file = fs::File::create(path).unwrap();
+        for hour in hours {
+            writeln!(
+                file,
+                r#"{{"timestamp":"2024-01-15T{:02}:00:00Z","level":"INFO","module":"m","message":"h{}"}}"#,
+                hour, hour
+            )
+            .unwrap();
+        }
+    }
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        format!("2024-01-15T{:02}:00:00Z", hour).parse().unwrap()
+    }
+
+    #[test]
+    fn test_blocks_are_valid_gzip_and_indexed() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("app.log");
+        let output = dir.path().join("app.log.gz");
+        write_log(&input, 0..24);
+
+        // 块足够小，每块约两行
+        let index = compress_with_index(&input, &output, 150).unwrap();
+        assert!(index.blocks.len() > 5);
+        assert_eq!(index.blocks.iter().map(|b| b.line_count).sum::<usize>(), 24);
+        assert_eq!(index.blocks[0].first_timestamp, Some(time(0)));
+
+        // 整个文件仍可按普通 gzip 读取
+        let mut content = String::new();
+        MultiGzDecoder::new(fs::File::open(&output).unwrap()).read_to_string(&mut content).unwrap();
+        assert_eq!(content, fs::read_to_string(&input).unwrap());
+
+        index.save(&output).unwrap();
+        assert_eq!(SeekIndex::load(&output), Some(index));
+        assert!(is_seek_index(&seek_index_path(&output)));
+    }
+
+    #[test]
+    fn test_range_reads_only_relevant_blocks() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("app.log");
+        let output = dir.path().join("app.log.gz");
+        write_log(&input, 0..24);
+        let index = compress_with_index(&input, &output, 150).unwrap();
+
+        let ranges = index.ranges_for(&TimeRange { start: time(10), end: time(12) });
+        assert_eq!(ranges.len(), 1);
+        let total: u64 = index.blocks.iter().map(|b| b.compressed_len).sum();
+        assert!(ranges[0].1 < total);
+
+        let mut content = String::new();
+        open_ranges(&output, &ranges).unwrap().read_to_string(&mut content).unwrap();
+        for hour in 10..=12 {
+            assert!(content.contains(&format!("\"h{}\"", hour)));
+        }
+        assert!(!content.contains("\"h0\""));
+        assert!(!content.contains("\"h23\""));
+    }
+}
@@ -0,0 +1,242 @@
+//! 启动阶段日志缓冲
+//!
+//! 进程启动时先安装全局 subscriber：可重载的过滤器和输出层（初始为空）加上缓冲层。
+//! `LoggingSystem::init` 完成前产生的事件暂存在有界缓冲区中，初始化后
+//! 换入真正的输出层并把缓冲的事件回放到文件通道；初始化失败时回退到控制台输出，
+//! 缓冲的事件打印到 stderr，不会丢失。
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, Layered};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use super::{
+    error::LogError,
+    formatter::{HumanReadableFormatter, LogFormatter},
+    LogEntry,
+};
+
+/// 默认缓冲条数
+pub const DEFAULT_STARTUP_BUFFER_CAPACITY: usize = 10_000;
+
+/// 带可重载过滤器的 registry，输出层挂在其上
+pub type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// 可在初始化后替换的输出层
+pub type StartupLayers = Vec<Box<dyn Layer<FilteredRegistry> + Send + Sync>>;
+
+static STARTUP: OnceLock<StartupHandle> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct BufferInner {
+    entries: VecDeque<LogEntry>,
+    dropped: u64,
+}
+
+/// 有界的启动日志缓冲区，满后丢弃最早的条目
+#[derive(Debug, Clone)]
+pub struct StartupBuffer {
+    inner: Arc<Mutex<BufferInner>>,
+    closed: Arc<AtomicBool>,
+    capacity: usize,
+}
+
+impl StartupBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BufferInner::default())),
+            closed: Arc::new(AtomicBool::new(false)),
+            capacity,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub fn push(&self, entry: LogEntry) {
+        let mut inner = self.inner.lock().unwrap();
+        if self.is_closed() {
+            return;
+        }
+        if inner.entries.len() >= self.capacity {
+            inner.entries.pop_front();
+            inner.dropped += 1;
+        }
+        inner.entries.push_back(entry);
+    }
+
+    /// 停止缓冲并取出全部条目，返回（条目, 因容量丢弃的条数）
+    pub fn close(&self) -> (Vec<LogEntry>, u64) {
+        let mut inner = self.inner.lock().unwrap();
+        self.closed.store(true, Ordering::Release);
+        let dropped = std::mem::take(&mut inner.dropped);
+        (inner.entries.drain(..).collect(), dropped)
+    }
+}
+
+/// 缓冲层，关闭后不再处理事件
+pub struct StartupBufferLayer {
+    buffer: StartupBuffer,
+}
+
+impl StartupBufferLayer {
+    pub fn new(buffer: StartupBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S> Layer<S> for StartupBufferLayer
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        if self.buffer.is_closed() {
+            return;
+        }
+        self.buffer.push(LogEntry::from_tracing_event(event, &ctx));
+    }
+}
+
+/// 已安装的启动缓冲及输出层重载句柄
+pub struct StartupHandle {
+    buffer: StartupBuffer,
+    filter: reload::Handle<EnvFilter, Registry>,
+    layers: reload::Handle<Option<StartupLayers>, FilteredRegistry>,
+}
+
+impl StartupHandle {
+    /// 已安装时返回全局句柄
+    pub fn get() -> Option<&'static StartupHandle> {
+        STARTUP.get()
+    }
+
+    /// 换入真正的过滤器和输出层，返回缓冲期间的事件供回放
+    pub fn activate(&self, filter: EnvFilter, layers: StartupLayers) -> Result<(Vec<LogEntry>, u64), LogError> {
+        // 先关闭缓冲再换层，避免同一事件既被缓冲又被新层输出
+        let buffered = self.buffer.close();
+        self.filter
+            .reload(filter)
+            .and_then(|_| self.layers.reload(Some(layers)))
+            .map_err(|e| LogError::InitError(format!("替换日志输出层失败: {}", e)))?;
+        Ok(buffered)
+    }
+}
+
+/// 安装带启动缓冲的全局 subscriber，应在进程启动后尽早调用
+pub fn install_startup_buffer(capacity: usize) -> Result<(), LogError> {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+    let buffer = StartupBuffer::new(capacity);
+    let (filter, filter_handle) =
+        reload::Layer::new(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug")));
+    // 输出层为 None 时不影响事件是否启用，缓冲层照常收到事件
+    let (layers, layers_handle) = reload::Layer::new(None::<StartupLayers>);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(layers)
+        .with(StartupBufferLayer::new(buffer.clone()))
+        .try_init()
+        .map_err(|e| LogError::InitError(format!("安装启动日志缓冲失败: {}", e)))?;
+
+    STARTUP
+        .set(StartupHandle {
+            buffer,
+            filter: filter_handle,
+            layers: layers_handle,
+        })
+        .map_err(|_| LogError::InitError("启动日志缓冲已经安装".to_string()))
+}
+
+/// 尝试安装全局 subscriber
+///
+/// 已存在全局 subscriber 时不覆盖也不报错，沿用现有的并返回 false
+pub fn install_global(dispatch: impl Into<tracing::Dispatch>) -> bool {
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    match dispatch.into().try_init() {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, "已存在全局 tracing subscriber，沿用现有配置");
+            false
+        }
+    }
+}
+
+/// 日志系统初始化失败时回退到控制台输出，并把缓冲的事件打印到 stderr
+pub fn fallback_to_console() {
+    let Some(startup) = StartupHandle::get() else {
+        install_global(tracing_subscriber::fmt().finish());
+        return;
+    };
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    match startup.activate(filter, vec![tracing_subscriber::fmt::layer().boxed()]) {
+        Ok((entries, dropped)) => {
+            let formatter = HumanReadableFormatter::new();
+            if dropped > 0 {
+                eprintln!("启动日志缓冲已满，丢弃了最早的 {} 条", dropped);
+            }
+            for entry in &entries {
+                if let Ok(line) = formatter.format(entry) {
+                    eprintln!("{}", line);
+                }
+            }
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::config::LogLevel;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_buffers_events_until_closed() {
+        let buffer = StartupBuffer::new(100);
+        let subscriber = tracing_subscriber::registry().with(StartupBufferLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(account_id = "12345", "启动前的日志");
+            tracing::warn!("配置缺失");
+            let (entries, dropped) = buffer.close();
+            assert_eq!(dropped, 0);
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].message, "启动前的日志");
+            assert_eq!(entries[0].fields.get("account_id"), Some(&"12345".into()));
+            assert_eq!(entries[1].level, LogLevel::Warn);
+
+            // 关闭后不再缓冲
+            tracing::info!("初始化之后");
+            assert!(buffer.close().0.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let buffer = StartupBuffer::new(3);
+        let subscriber = tracing_subscriber::registry().with(StartupBufferLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::info!("event {}", i);
+            }
+        });
+
+        let (entries, dropped) = buffer.close();
+        assert_eq!(dropped, 2);
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+    }
+
+    #[test]
+    fn test_install_global_does_not_fail_when_present() {
+        let _guard = crate::logging::GLOBAL_SUBSCRIBER_TEST_LOCK.blocking_lock();
+        install_global(tracing_subscriber::registry());
+        assert!(!install_global(tracing_subscriber::registry()));
+    }
+}
@@ -0,0 +1,359 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    config::{LogConfig, LogType},
+    error::LogError,
+    security::DataMasker,
+    seek_index, LogEntry,
+};
+
+/// 支持包默认输出目录
+pub const DEFAULT_SUPPORT_BUNDLE_DIR: &str = "support";
+/// 支持包默认大小上限
+pub const DEFAULT_SUPPORT_BUNDLE_MAX_BYTES: u64 = 50 * 1024 * 1024;
+/// 为清单预留的空间
+const MANIFEST_RESERVE: u64 = 64 * 1024;
+/// 每个文件在 zip 中的头部等额外开销估算
+const ZIP_ENTRY_OVERHEAD: u64 = 256;
+/// 配置中按键名剔除的敏感字段
+const SECRET_KEYS: &[&str] = &["password", "auth_code", "secret", "token", "api_key", "private_key"];
+
+/// 支持包选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupportBundleOptions {
+    /// 支持包大小上限（字节），超出时截断或跳过较早的日志
+    pub max_bytes: u64,
+    /// 收集最近多少小时内修改过的日志文件
+    pub log_hours: u32,
+    pub output_dir: PathBuf,
+}
+
+impl Default for SupportBundleOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_SUPPORT_BUNDLE_MAX_BYTES,
+            log_hours: 24,
+            output_dir: PathBuf::from(DEFAULT_SUPPORT_BUNDLE_DIR),
+        }
+    }
+}
+
+/// 支持包中的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub path: String,
+    pub bytes: u64,
+    /// 超出大小上限，只保留了末尾部分
+    pub truncated: bool,
+}
+
+/// 支持包清单，写入 `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub created_at: DateTime<Utc>,
+    pub app_version: String,
+    pub max_bytes: u64,
+    /// 不含清单的内容总字节数（未压缩）
+    pub total_bytes: u64,
+    pub files: Vec<BundleFile>,
+    /// 因大小上限或读取失败未收入的内容及原因
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupportBundleSummary {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub manifest: BundleManifest,
+}
+
+/// 问题反馈用的支持包
+///
+/// 诊断、指标、配置等小文件优先收入，剩余空间按从新到旧收入脱敏后的日志，
+/// 放不下的日志只保留末尾。大小按未压缩内容计算，生成的 zip 不会超过上限
+pub struct SupportBundle {
+    options: SupportBundleOptions,
+    sections: Vec<(String, Vec<u8>)>,
+    logs: Vec<(String, PathBuf)>,
+    skipped: Vec<String>,
+}
+
+impl SupportBundle {
+    pub fn new(options: SupportBundleOptions) -> Self {
+        Self {
+            options,
+            sections: Vec::new(),
+            logs: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// 以 JSON 收入一段内容，按键名剔除密码等敏感字段
+    pub fn add_json(&mut self, name: &str, value: &impl Serialize) -> Result<(), LogError> {
+        let mut value = serde_json::to_value(value)?;
+        strip_secrets(&mut value);
+        self.sections.push((name.to_string(), serde_json::to_vec_pretty(&value)?));
+        Ok(())
+    }
+
+    /// 收入飞行记录器中的事件，逐条脱敏后按 JSON Lines 写入
+    pub fn add_flight_recorder(&mut self, entries: Vec<LogEntry>, masker: &DataMasker) -> Result<(), LogError> {
+        let mut content = Vec::new();
+        for mut entry in entries {
+            masker.mask_log_entry(&mut entry)?;
+            serde_json::to_writer(&mut content, &entry)?;
+            content.push(b'\n');
+        }
+        self.sections.push(("flight_recorder.jsonl".to_string(), content));
+        Ok(())
+    }
+
+    /// 登记最近修改过的日志文件（含已轮转的压缩文件），从新到旧
+    pub fn collect_logs(&mut self, config: &LogConfig) -> Result<(), LogError> {
+        let since = std::time::SystemTime::now() - std::time::Duration::from_secs(self.options.log_hours as u64 * 3600);
+        let mut logs = Vec::new();
+        for log_type in LogType::all() {
+            let Ok(entries) = fs::read_dir(config.output_dir.join(log_type.as_str())) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !path.is_file() || !name.contains(".log") || seek_index::is_seek_index(&path) {
+                    continue;
+                }
+                let modified = entry.metadata()?.modified()?;
+                if modified >= since {
+                    let name = name.trim_end_matches(".gz").to_string();
+                    logs.push((modified, format!("logs/{}/{}", log_type.as_str(), name), path));
+                }
+            }
+        }
+        logs.sort_by_key(|(modified, _, _)| std::cmp::Reverse(*modified));
+        self.logs.extend(logs.into_iter().map(|(_, name, path)| (name, path)));
+        Ok(())
+    }
+
+    /// 写出 zip 文件
+    pub fn write(self, masker: &DataMasker) -> Result<SupportBundleSummary, LogError> {
+        let Self {
+            options,
+            sections,
+            logs,
+            mut skipped,
+        } = self;
+        fs::create_dir_all(&options.output_dir)?;
+        let created_at = Utc::now();
+        let path = options
+            .output_dir
+            .join(format!("support-{}.zip", created_at.format("%Y%m%d_%H%M%S")));
+
+        let file = fs::File::create(&path)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let zip_options =
+            zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut add = |name: &str, content: &[u8]| -> Result<(), LogError> {
+            zip.start_file(name, zip_options).map_err(zip_error)?;
+            zip.write_all(content)?;
+            Ok(())
+        };
+
+        let mut remaining = options.max_bytes.saturating_sub(MANIFEST_RESERVE + ZIP_ENTRY_OVERHEAD);
+        let mut files = Vec::new();
+
+        for (name, content) in sections {
+            let size = content.len() as u64 + ZIP_ENTRY_OVERHEAD;
+            if size > remaining {
+                skipped.push(format!("{}: 超出大小上限", name));
+                continue;
+            }
+            add(&name, &content)?;
+            remaining -= size;
+            files.push(BundleFile {
+                path: name,
+                bytes: content.len() as u64,
+                truncated: false,
+            });
+        }
+
+        for (name, log_path) in logs {
+            if remaining <= ZIP_ENTRY_OVERHEAD {
+                skipped.push(format!("{}: 超出大小上限", name));
+                continue;
+            }
+            let content = match read_masked_log(&log_path, masker) {
+                Ok(content) => content,
+                Err(e) => {
+                    skipped.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            let (content, truncated) = keep_tail(content, (remaining - ZIP_ENTRY_OVERHEAD) as usize);
+            if content.is_empty() {
+                skipped.push(format!("{}: 超出大小上限", name));
+                continue;
+            }
+            add(&name, &content)?;
+            remaining -= content.len() as u64 + ZIP_ENTRY_OVERHEAD;
+            files.push(BundleFile {
+                path: name,
+                bytes: content.len() as u64,
+                truncated,
+            });
+        }
+
+        let manifest = BundleManifest {
+            created_at,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            max_bytes: options.max_bytes,
+            total_bytes: files.iter().map(|f| f.bytes).sum(),
+            files,
+            skipped,
+        };
+        add("manifest.json", &serde_json::to_vec_pretty(&manifest)?)?;
+        zip.finish().map_err(zip_error)?;
+
+        let size_bytes = fs::metadata(&path)?.len();
+        tracing::info!(path = %path.display(), size_bytes, files = manifest.files.len(), "已生成支持包");
+        Ok(SupportBundleSummary {
+            path,
+            size_bytes,
+            manifest,
+        })
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> LogError {
+    LogError::WriteError(std::io::Error::other(e))
+}
+
+/// 按键名把敏感字段替换为 `***`
+pub fn strip_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                    if !value.is_null() {
+                        *value = serde_json::Value::String("***".to_string());
+                    }
+                } else {
+                    strip_secrets(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// 读取日志文件（压缩文件先解压），逐行脱敏
+fn read_masked_log(path: &Path, masker: &DataMasker) -> Result<Vec<u8>, LogError> {
+    let file = fs::File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
+        Box::new(flate2::read::MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut content = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        // JSON 格式的日志按字段规则脱敏，其他格式按文本脱敏
+        let masked = match serde_json::from_str::<LogEntry>(&line) {
+            Ok(mut entry) => {
+                masker.mask_log_entry(&mut entry)?;
+                serde_json::to_string(&entry)?
+            }
+            Err(_) => masker.mask_text(&line),
+        };
+        content.extend_from_slice(masked.as_bytes());
+        content.push(b'\n');
+    }
+    Ok(content)
+}
+
+/// 超出上限时只保留末尾的完整行
+fn keep_tail(content: Vec<u8>, max_len: usize) -> (Vec<u8>, bool) {
+    if content.len() <= max_len {
+        return (content, false);
+    }
+    let start = content.len() - max_len;
+    let start = match content[start..].iter().position(|&b| b == b'\n') {
+        Some(offset) => start + offset + 1,
+        None => content.len(),
+    };
+    (content[start..].to_vec(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_strip_secrets() {
+        let mut value = serde_json::json!({
+            "broker_id": "9999",
+            "password": "123456",
+            "auth_code": "0000000000000000",
+            "accounts": [{ "investor_id": "100001", "Password": "abc" }],
+            "api_token": null,
+        });
+        strip_secrets(&mut value);
+        assert_eq!(value["broker_id"], "9999");
+        assert_eq!(value["password"], "***");
+        assert_eq!(value["auth_code"], "***");
+        assert_eq!(value["accounts"][0]["investor_id"], "100001");
+        assert_eq!(value["accounts"][0]["Password"], "***");
+        assert!(value["api_token"].is_null());
+    }
+
+    #[test]
+    fn test_bundle_respects_size_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = LogConfig {
+            output_dir: temp_dir.path().join("logs"),
+            ..LogConfig::development()
+        };
+        config.ensure_directories().unwrap();
+        let lines: String = (0..2000).map(|i| format!("2024-01-15 09:30:00 INFO line {}\n", i)).collect();
+        fs::write(config.get_log_file_path(LogType::App), &lines).unwrap();
+        fs::write(config.get_log_file_path(LogType::Trading), "2024-01-15 09:30:00 INFO 下单\n").unwrap();
+
+        let options = SupportBundleOptions {
+            max_bytes: MANIFEST_RESERVE + 20 * 1024,
+            output_dir: temp_dir.path().join("support"),
+            ..SupportBundleOptions::default()
+        };
+        let masker = DataMasker::new();
+        let mut bundle = SupportBundle::new(options);
+        bundle
+            .add_json("config.json", &serde_json::json!({ "broker_id": "9999", "password": "secret" }))
+            .unwrap();
+        bundle.add_flight_recorder(Vec::new(), &masker).unwrap();
+        bundle.collect_logs(&config).unwrap();
+        let summary = bundle.write(&masker).unwrap();
+
+        assert!(summary.size_bytes <= summary.manifest.max_bytes);
+        let app = summary.manifest.files.iter().find(|f| f.path == "logs/app/app.log").unwrap();
+        assert!(app.truncated);
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&summary.path).unwrap()).unwrap();
+        let mut config_json = String::new();
+        archive.by_name("config.json").unwrap().read_to_string(&mut config_json).unwrap();
+        assert!(!config_json.contains("secret"));
+        let mut app_log = String::new();
+        archive.by_name("logs/app/app.log").unwrap().read_to_string(&mut app_log).unwrap();
+        // 截断后保留最新的完整行
+        assert!(app_log.starts_with("2024-01-15"));
+        assert!(app_log.ends_with("line 1999\n"));
+        assert!(archive.by_name("manifest.json").is_ok());
+    }
+}
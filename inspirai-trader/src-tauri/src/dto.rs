@@ -0,0 +1,191 @@
+// Tauri 命令的响应数据结构
+// 前端直接消费结构化数据，不再解析文本消息
+
+use crate::ctp::{ClientState, ConnectionMode, Environment};
+use serde::{Deserialize, Serialize};
+
+/// 通用操作结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ActionResult {
+    pub success: bool,
+    /// 供界面展示的提示文本
+    pub message: String,
+}
+
+impl ActionResult {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            message: message.into(),
+        }
+    }
+
+    pub fn noop(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// 连接结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ConnectResult {
+    pub environment: Environment,
+    pub connection_mode: ConnectionMode,
+    pub md_front_addr: String,
+    pub trader_front_addr: String,
+    pub state: ClientState,
+    pub message: String,
+}
+
+/// 登录结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct LoginResult {
+    pub user_id: String,
+    /// 交易端不可用，仅行情运行
+    pub degraded: bool,
+    /// 是否已自动确认结算单
+    pub settlement_confirmed: bool,
+    pub message: String,
+}
+
+/// 客户端状态信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct StatusInfo {
+    pub state: ClientState,
+    pub connected: bool,
+    pub logged_in: bool,
+    pub degraded: bool,
+    /// 未创建客户端时为 None
+    pub connection_mode: Option<ConnectionMode>,
+    pub subscribed_count: usize,
+}
+
+impl StatusInfo {
+    /// 未创建客户端时的状态
+    pub fn disconnected() -> Self {
+        Self {
+            state: ClientState::Disconnected,
+            connected: false,
+            logged_in: false,
+            degraded: false,
+            connection_mode: None,
+            subscribed_count: 0,
+        }
+    }
+}
+
+/// 单个合约的订阅结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub enum SubscriptionOutcome {
+    /// 已发送订阅请求
+    Subscribed,
+    /// 之前已订阅
+    AlreadySubscribed,
+    /// 已发送取消订阅请求
+    Unsubscribed,
+    /// 之前未订阅
+    NotSubscribed,
+    /// 请求失败
+    Failed,
+}
+
+/// 合约订阅状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct InstrumentSubscriptionStatus {
+    pub instrument_id: String,
+    pub outcome: SubscriptionOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 订阅/取消订阅结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct SubscribeResult {
+    pub requested: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub instruments: Vec<InstrumentSubscriptionStatus>,
+}
+
+impl SubscribeResult {
+    /// 记录一个合约的结果
+    pub fn push(&mut self, instrument_id: &str, outcome: SubscriptionOutcome, error: Option<String>) {
+        self.requested += 1;
+        if outcome == SubscriptionOutcome::Failed {
+            self.failed += 1;
+        } else {
+            self.succeeded += 1;
+        }
+        self.instruments.push(InstrumentSubscriptionStatus {
+            instrument_id: instrument_id.to_string(),
+            outcome,
+            error,
+        });
+    }
+}
+
+/// 撤单结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct CancelOrderResult {
+    pub order_ref: String,
+    pub instrument_id: String,
+    pub message: String,
+}
+
+/// 交易端重试结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TraderRetryResult {
+    /// 第几次重试
+    pub attempt: u32,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribe_result_counts() {
+        let mut result = SubscribeResult::default();
+        result.push("rb2501", SubscriptionOutcome::Subscribed, None);
+        result.push("hc2501", SubscriptionOutcome::AlreadySubscribed, None);
+        result.push("i2501", SubscriptionOutcome::Failed, Some("网络错误".to_string()));
+
+        assert_eq!(result.requested, 3);
+        assert_eq!(result.succeeded, 2);
+        assert_eq!(result.failed, 1);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["instruments"][0]["outcome"], "Subscribed");
+        assert!(json["instruments"][0].get("error").is_none());
+    }
+
+    /// 重新生成前端类型：cargo test --features ts export_typescript_bindings
+    #[cfg(feature = "ts")]
+    #[test]
+    fn export_typescript_bindings() {
+        use crate::ctp::ts_bindings::{export_bindings, write_index, DEFAULT_TS_OUT_DIR};
+        use ts_rs::TS;
+
+        export_bindings(DEFAULT_TS_OUT_DIR).unwrap();
+        ActionResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        ConnectResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        LoginResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        StatusInfo::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        SubscribeResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        CancelOrderResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        TraderRetryResult::export_all_to(DEFAULT_TS_OUT_DIR).unwrap();
+        write_index(DEFAULT_TS_OUT_DIR).unwrap();
+    }
+}
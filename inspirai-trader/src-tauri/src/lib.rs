@@ -1,10 +1,11 @@
-// CTP 交易组件模块
-pub mod ctp;
-// 新的高级日志系统模块
-pub mod logging;
+// CTP 交易组件、日志系统和健康检查来自 inspirai-ctp-core，原路径保持不变
+pub use inspirai_ctp_core::{ctp, health, logging};
+pub use inspirai_ctp_core::{log_ctp, log_market_data, log_performance, log_trading};
+// Tauri 命令响应结构
+pub mod dto;
 
 use std::sync::Arc;
-use tauri::State;
+use tauri::{Manager, State};
 use tokio::sync::{mpsc, Mutex};
 
 // 应用状态
@@ -12,8 +13,160 @@ struct AppState {
     ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
     market_data_service: Arc<Mutex<Option<ctp::MarketDataService>>>,
     event_receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<ctp::CtpEvent>>>>,
+    // 快捷键开关与限速跨连接保留
+    hotkeys: Arc<ctp::HotkeyController>,
+    // 多窗口事件分发与快照
+    event_bridge: ctp::EventBridge,
+    // 命令调用录制，默认关闭
+    action_recorder: ctp::ActionRecorder,
+    // 交易命令幂等键，跨重连保留
+    idempotency: ctp::IdempotencyStore,
+    // 主备实例协调，未启用时为空
+    instance: Option<ctp::InstanceCoordinator>,
+    // 界面心跳死人开关，默认未布防
+    dead_man: ctp::DeadManSwitch,
+    // 导出、压缩等长任务的进度与取消
+    tasks: ctp::TaskManager,
+    // 常驻后台任务的存活登记
+    liveness: health::TaskLiveness,
+    // 健康检查阈值，可在运行时调整
+    health_thresholds: std::sync::Mutex<health::HealthThresholds>,
+    // 按窗口订阅的指标推送
+    metrics_stream: ctp::MetricsStream,
+    // 关键事件的外部 Webhook 通知
+    webhooks: ctp::WebhookDispatcher,
+    // 钉钉、飞书、Telegram 群机器人通知
+    notifier: ctp::Notifier,
+    // 前端工作区快照（布局、自选、图表）
+    workspaces: ctp::WorkspaceStore,
+    // 配置、数据目录和操作日志的定时备份
+    backups: ctp::BackupManager,
+    // 日志、行情、订单历史和日志型记录的保留策略
+    retention: ctp::RetentionManager,
+    // 命名风控预设与按时段切换
+    risk_presets: ctp::RiskPresetManager,
+    // 报单成交与行情分析的存储后端，打开失败时不落库
+    storage: Option<ctp::Storage>,
+    // 最近一次启动自检报告
+    self_test: Arc<std::sync::Mutex<Option<ctp::SelfTestReport>>>,
+    // 行情热路径调优配置
+    runtime_tuning: ctp::RuntimeTuning,
+    // 行情热路径专用运行时，未开启时事件总线运行在界面运行时上
+    hot_path: Option<Arc<ctp::HotPathRuntime>>,
+    // 界面运行时的调度延迟探测
+    ui_latency: std::sync::OnceLock<ctp::LatencyProbe>,
+    // 策略引擎，跨连接保留已登记的策略
+    strategy_engine: Arc<std::sync::Mutex<ctp::StrategyEngine>>,
+    // 跟踪止损条件单，由策略引擎任务按行情检查触发
+    trailing_stops: ctp::TrailingStopManager,
+    // 按合约、品种的交易开关，跨连接保留，行情异常的合约自动停止
+    switchboard: ctp::TradingSwitchboard,
 }
 
+// 观察模式变化推送给对应窗口，界面据此显示或隐藏只读横幅
+const OBSERVER_MODE_EVENT: &str = "observer-mode-changed";
+
+// 风控预设切换（手动或定时）推送给所有窗口，附参数变化
+const RISK_PRESET_EVENT: &str = "risk-preset-switched";
+
+// 观察窗口可以调用的命令：行情、查询和窗口自身的订阅，其余命令（下单撤单、会话控制、风控和配置修改等）
+// 在后端直接拒绝，新增命令默认对观察窗口不可用
+const OBSERVER_COMMANDS: &[&str] = &[
+    "greet",
+    "ctp_create_config",
+    "ctp_subscribe",
+    "ctp_batch_subscribe",
+    "ctp_get_status",
+    "ctp_get_session_health",
+    "ctp_get_connection_quality",
+    "ctp_get_diagnostics",
+    "ctp_get_event_bus_lag",
+    "ctp_get_self_test_report",
+    "ctp_validate_order",
+    "ctp_preview_order",
+    "ctp_max_open_volume",
+    "ctp_stress_test",
+    "ctp_get_hedge_suggestions",
+    "ctp_plan_rollovers",
+    "ctp_get_rollovers",
+    "ctp_get_trailing_stops",
+    "ctp_get_disabled_trading",
+    "ctp_hotkey_status",
+    "ctp_get_timeline",
+    "ctp_register_window",
+    "ctp_unregister_window",
+    // 由 EventBridge::set_observer 校验调用窗口，观察窗口不能解除自身的观察模式
+    "ctp_set_observer_mode",
+    "ctp_get_observers",
+    "ctp_get_rejection_breakers",
+    "ctp_get_api_usage",
+    "ctp_get_compliance_status",
+    "ctp_get_scheduler_latency",
+    "ctp_get_conversion_pool_stats",
+    "ctp_get_wire_log",
+    "ctp_get_wire_log_stats",
+    "get_wire_log_config",
+    "ctp_get_client_order",
+    "ctp_get_funds_anomalies",
+    "ctp_get_funds_monitor_config",
+    "ctp_get_instance_status",
+    "ctp_query_account",
+    "ctp_get_aggregated_equity",
+    "ctp_query_positions",
+    "ctp_query_orders",
+    "ctp_query_trades",
+    "ctp_query_instruments",
+    "ctp_query_commission_rate",
+    "ctp_query_margin_rate",
+    "ctp_get_market_data",
+    "ctp_get_all_market_data",
+    "ctp_get_risk_presets",
+    "ctp_diff_risk_preset",
+    "ctp_ack_tick_trace",
+    "ctp_get_pipeline_trace_stats",
+    "ctp_start_heatmap_stream",
+    "ctp_get_heatmap_history",
+    "ctp_start_order_flow_stream",
+    "ctp_get_order_flow",
+    "ctp_get_market_overview",
+    "ctp_get_price_limits",
+    "explain_error",
+    "ctp_get_dead_man_status",
+    "ctp_get_trade_analytics",
+    "ctp_get_pnl_heatmap",
+    "ctp_get_round_trips",
+    "ctp_get_settlement_prices",
+    "ctp_get_risk_report",
+    "get_webhook_config",
+    "get_webhook_deliveries",
+    "get_notifier_config",
+    "get_notification_records",
+    "load_workspace",
+    "list_workspaces",
+    "list_workspace_versions",
+    "list_workspace_profiles",
+    "get_backup_config",
+    "list_backups",
+    "get_retention_policy",
+    "preview_retention",
+    "get_storage_config",
+    "list_tasks",
+    "ctp_get_continuous_kline",
+    "query_logs",
+    "query_logs_dsl",
+    "verify_log_integrity",
+    "get_log_metrics",
+    "get_log_sampling",
+    "test_log_routing",
+    "log_frontend_event",
+    "get_log_system_status",
+    "get_app_health",
+    "ctp_get_action_recording",
+    "subscribe_metrics",
+    "unsubscribe_metrics",
+    "get_metrics_subscriptions",
+];
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -37,9 +190,10 @@ async fn ctp_create_config() -> Result<ctp::CtpConfig, String> {
 // 连接 CTP 服务器
 #[tauri::command]
 async fn ctp_connect(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     mut config: ctp::CtpConfig,
-) -> Result<String, String> {
+) -> Result<dto::ConnectResult, String> {
     // 自动检测并设置动态库路径（如果未设置）
     if config.md_dynlib_path.is_none() || config.td_dynlib_path.is_none() {
         tracing::info!("自动检测 CTP 动态库路径...");
@@ -64,45 +218,396 @@ async fn ctp_connect(
         }
     }
     
+    // 备用实例只记录行情，交易会话由主实例持有
+    if let Some(instance) = state.instance.as_ref().filter(|i| !i.is_active()) {
+        match config.connection_mode {
+            ctp::ConnectionMode::TdOnly => {
+                return Err(format!("当前为备用实例 {}，不能建立交易会话", instance.instance_id()));
+            }
+            ctp::ConnectionMode::Full => {
+                tracing::info!("当前为备用实例，以仅行情模式连接");
+                config.connection_mode = ctp::ConnectionMode::MdOnly;
+            }
+            ctp::ConnectionMode::MdOnly => {}
+        }
+    }
+    
     // 创建新的客户端
     match ctp::CtpClient::new(config.clone()).await {
         Ok(mut new_client) => {
+            new_client.set_md_callback_core(state.runtime_tuning.md_callback_core);
+            new_client.set_switchboard(state.switchboard.clone());
             // 连接到服务器
             if let Err(e) = new_client.connect().await {
                 return Err(format!("连接失败: {}", e));
             }
             
+            let result = dto::ConnectResult {
+                environment: config.environment,
+                connection_mode: config.connection_mode,
+                md_front_addr: config.md_front_addr.clone(),
+                trader_front_addr: config.trader_front_addr.clone(),
+                state: new_client.get_state(),
+                message: "CTP 服务器连接成功".to_string(),
+            };
+            
+            // 新连接的事件进入事件总线，事件桥作为订阅者转发给各窗口
+            state.event_bridge.reset();
+            let bus_started = match state.hot_path.as_ref() {
+                Some(hot_path) => new_client.start_event_bus_on(&hot_path.handle()),
+                None => new_client.start_event_bus(),
+            };
+            if bus_started {
+                let subscriber = new_client.subscribe_events("ui_bridge", &[], ctp::DEFAULT_SUBSCRIBER_CAPACITY);
+                spawn_event_bridge(app, state.event_bridge.clone(), subscriber, &state.liveness);
+                let subscriber = new_client.subscribe_events(
+                    "alerts",
+                    &[ctp::BusTopic::Orders, ctp::BusTopic::Account, ctp::BusTopic::System],
+                    ctp::DEFAULT_SUBSCRIBER_CAPACITY,
+                );
+                spawn_alert_dispatcher(state.webhooks.clone(), state.notifier.clone(), subscriber, &state.liveness);
+                if let Some(storage) = state.storage.clone() {
+                    let subscriber = new_client.subscribe_events("storage", &[ctp::BusTopic::Orders], ctp::DEFAULT_SUBSCRIBER_CAPACITY);
+                    spawn_storage_writer(storage, subscriber, &state.liveness);
+                }
+                let subscriber = new_client.subscribe_events("strategy_engine", &[ctp::BusTopic::Ticks], ctp::DEFAULT_SUBSCRIBER_CAPACITY);
+                spawn_strategy_engine(
+                    state.strategy_engine.clone(),
+                    state.trailing_stops.clone(),
+                    state.switchboard.clone(),
+                    state.ctp_client.clone(),
+                    subscriber,
+                    &state.liveness,
+                );
+                let subscriber = new_client.subscribe_events("tick_recorder", &[ctp::BusTopic::Ticks], ctp::DEFAULT_SUBSCRIBER_CAPACITY);
+                spawn_tick_recorder(subscriber, &state.liveness);
+            }
+            
             // 设置客户端到状态
             {
                 let mut client = state.ctp_client.lock().await;
                 *client = Some(new_client);
             }
             
-            Ok("CTP 服务器连接成功".to_string())
+            Ok(result)
         }
         Err(e) => Err(format!("创建客户端失败: {}", e)),
     }
 }
 
+// 将客户端事件按窗口订阅转发，通过 ctp-event 事件发送给目标窗口
+fn spawn_event_bridge(
+    app: tauri::AppHandle,
+    bridge: ctp::EventBridge,
+    subscriber: ctp::BusSubscriber,
+    liveness: &health::TaskLiveness,
+) {
+    use tauri::Emitter;
+
+    let beat = liveness.register("event_bridge", None);
+    tauri::async_runtime::spawn(async move {
+        tracing::info!("事件桥已启动");
+        while let Some(event) = subscriber.recv().await {
+            bridge.record_backlog(subscriber.pending());
+            beat.beat();
+            for label in bridge.dispatch(&event) {
+                if let Err(e) = app.emit_to(label.as_str(), "ctp-event", &event) {
+                    tracing::warn!("向窗口 {} 推送事件失败: {}", label, e);
+                }
+            }
+        }
+        if subscriber.is_disconnected() {
+            // 不调用 finish，健康检查中显示为异常退出
+            tracing::error!("事件桥积压过多已断开，界面需重新连接");
+            return;
+        }
+        tracing::info!("事件桥已停止");
+        beat.finish();
+    });
+}
+
+// 告警推送：断线、登录失败和熔断推送 Webhook，成交、盈亏阈值和断线发送群机器人通知。
+// 单独订阅，不受界面事件桥积压影响
+fn spawn_alert_dispatcher(
+    webhooks: ctp::WebhookDispatcher,
+    notifier: ctp::Notifier,
+    subscriber: ctp::BusSubscriber,
+    liveness: &health::TaskLiveness,
+) {
+    let beat = liveness.register("alert_dispatcher", None);
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = subscriber.recv().await {
+            beat.beat();
+            webhooks.notify_event(&event);
+            notifier.notify_event(&event);
+        }
+        if subscriber.is_disconnected() {
+            tracing::error!("告警推送积压过多已断开，之后的告警不再推送");
+            return;
+        }
+        beat.finish();
+    });
+}
+
+// 将报单、成交回报及查询结果写入事务存储后端
+fn spawn_storage_writer(storage: ctp::Storage, subscriber: ctp::BusSubscriber, liveness: &health::TaskLiveness) {
+    let beat = liveness.register("storage_writer", None);
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = subscriber.recv().await {
+            beat.beat();
+            let (orders, trades) = match event {
+                ctp::CtpEvent::OrderUpdate(order) => (vec![order], Vec::new()),
+                ctp::CtpEvent::QueryOrdersResult(orders) => (orders, Vec::new()),
+                ctp::CtpEvent::TradeUpdate(trade) => (Vec::new(), vec![trade]),
+                ctp::CtpEvent::QueryTradesResult(trades) => (Vec::new(), trades),
+                _ => continue,
+            };
+            let backend = storage.orders().clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                backend.put_orders(&orders)?;
+                backend.put_trades(&trades)
+            })
+            .await;
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => {
+                    tracing::warn!("写入报单成交存储失败: {}", e);
+                    health::record_storage_error("storage", &e);
+                }
+                Err(e) => {
+                    tracing::warn!("存储写入任务异常: {}", e);
+                    health::record_storage_error("storage", &e);
+                }
+            }
+        }
+        if subscriber.is_disconnected() {
+            tracing::error!("报单成交存储积压过多已断开，重连后由查询结果补齐");
+            return;
+        }
+        beat.finish();
+    });
+}
+
+// 行情写入受内存预算限制的 tick 历史，淘汰数据落盘到原始 tick 目录，供收盘后压缩归档。
+// 定时落盘未攒满一批的淘汰数据；行情停止一段时间（休市）后内存中的尾部行情也全部落盘，
+// 避免压缩时缺少收盘前的数据
+fn spawn_tick_recorder(subscriber: ctp::BusSubscriber, liveness: &health::TaskLiveness) {
+    let store = match ctp::JsonLinesSpillStore::new(ctp::DEFAULT_RAW_DIR) {
+        Ok(store) => store,
+        Err(e) => {
+            tracing::error!("行情落盘目录不可用，tick 不再落盘: {}", e);
+            return;
+        }
+    };
+    let history = ctp::TickHistory::new(ctp::TickRetentionConfig::default()).with_spill_store(Arc::new(store));
+    const IDLE_SPILL_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+    let beat = liveness.register("tick_recorder", Some(std::time::Duration::from_secs(5)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        let mut last_tick = std::time::Instant::now();
+        loop {
+            tokio::select! {
+                event = subscriber.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+                    if let ctp::CtpEvent::MarketData(tick) = event {
+                        history.record(&tick);
+                        last_tick = std::time::Instant::now();
+                    }
+                }
+                _ = interval.tick() => {
+                    beat.beat();
+                    if last_tick.elapsed() >= IDLE_SPILL_AFTER {
+                        history.spill_all();
+                    } else {
+                        history.flush();
+                    }
+                }
+            }
+        }
+        history.spill_all();
+        beat.finish();
+    });
+}
+
+// 行情送入策略引擎推进 K 线收盘和行情质量检查，定时器收盘没有后续行情的 K 线；
+// 策略因行情异常暂停时撤销它在该合约上的挂单
+fn spawn_strategy_engine(
+    engine: Arc<std::sync::Mutex<ctp::StrategyEngine>>,
+    trailing_stops: ctp::TrailingStopManager,
+    switchboard: ctp::TradingSwitchboard,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    subscriber: ctp::BusSubscriber,
+    liveness: &health::TaskLiveness,
+) {
+    let beat = liveness.register("strategy_engine", Some(std::time::Duration::from_secs(1)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            let mut triggers = Vec::new();
+            tokio::select! {
+                event = subscriber.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+                    if let ctp::CtpEvent::MarketData(tick) = event {
+                        engine.lock().unwrap().on_tick(&tick, chrono::Local::now().naive_local());
+                        triggers = trailing_stops.on_tick(&tick.instrument_id, tick.last_price);
+                    }
+                }
+                _ = interval.tick() => {
+                    beat.beat();
+                    engine.lock().unwrap().on_timer(chrono::Local::now().naive_local());
+                }
+            }
+            let degraded = engine.lock().unwrap().degraded_instruments();
+            switchboard.sync_data_quality(&degraded);
+            if !triggers.is_empty() {
+                submit_trailing_stop_exits(&trailing_stops, &ctp_client, triggers).await;
+                if let Err(e) = trailing_stops.sync_atr_feed(&mut engine.lock().unwrap(), None) {
+                    tracing::warn!("跟踪止损日线订阅更新失败: {}", e);
+                }
+            }
+            let pause_events = engine.lock().unwrap().take_pause_events();
+            for event in pause_events {
+                let ctp::StrategyPauseEvent::Paused { strategy_id, instrument_id, .. } = event else {
+                    continue;
+                };
+                let mut client_guard = ctp_client.lock().await;
+                if let Some(client) = client_guard.as_mut() {
+                    if let Err(e) = client.cancel_strategy_orders(&strategy_id, &instrument_id).await {
+                        tracing::warn!("撤销暂停策略 {} 的挂单失败: {}", strategy_id, e);
+                    }
+                }
+            }
+        }
+        beat.finish();
+    });
+}
+
+// 报出触发的跟踪止损平仓单，经客户端下单流程（开关、风控、预校验）
+async fn submit_trailing_stop_exits(
+    trailing_stops: &ctp::TrailingStopManager,
+    ctp_client: &Arc<Mutex<Option<ctp::CtpClient>>>,
+    triggers: Vec<ctp::TrailingStopTrigger>,
+) {
+    let mut client_guard = ctp_client.lock().await;
+    let Some(client) = client_guard.as_mut() else {
+        return;
+    };
+    let sender = client.event_sender();
+    for trigger in triggers {
+        let id = trigger.stop.id.clone();
+        let stop = match client.place_order(trigger.order_input()).await {
+            Ok(order_ref) => trailing_stops.attach_exit_order(&id, &order_ref.order_ref).unwrap_or(trigger.stop),
+            Err(e) => {
+                tracing::error!("跟踪止损 {} 平仓单报单失败: {}", id, e);
+                trigger.stop
+            }
+        };
+        if let Err(e) = sender.send(ctp::CtpEvent::TrailingStopUpdate(stop)) {
+            tracing::warn!("发送跟踪止损事件失败: {}", e);
+        }
+    }
+}
+
+// 窗口注册事件订阅，返回最新快照用于初始化（不发起 CTP 查询）
+#[tauri::command]
+async fn ctp_register_window(
+    window: tauri::Window,
+    state: State<'_, AppState>,
+    subscription: Option<ctp::WindowSubscription>,
+) -> Result<ctp::BridgeSnapshot, String> {
+    let (client_state, subscriptions) = {
+        let client_guard = state.ctp_client.lock().await;
+        match client_guard.as_ref() {
+            Some(client) => (Some(client.get_state()), client.get_subscribed_instruments()),
+            None => (None, Vec::new()),
+        }
+    };
+    Ok(state.event_bridge.register_window(
+        window.label(),
+        subscription.unwrap_or_default(),
+        client_state,
+        subscriptions,
+    ))
+}
+
+// 设置窗口的观察模式：观察窗口接收全部数据但交易命令被拒绝，只能由非观察窗口设置或解除
+#[tauri::command]
+async fn ctp_set_observer_mode(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    state: State<'_, AppState>,
+    label: String,
+    enabled: bool,
+) -> Result<bool, String> {
+    use tauri::Emitter;
+
+    let changed = state
+        .event_bridge
+        .set_observer(window.label(), &label, enabled)
+        .map_err(|e| e.to_string())?;
+    if changed {
+        let payload = serde_json::json!({ "label": label, "observer": enabled });
+        if let Err(e) = app.emit_to(label.as_str(), OBSERVER_MODE_EVENT, payload) {
+            tracing::warn!("推送观察模式变化失败: {}", e);
+        }
+    }
+    Ok(changed)
+}
+
+// 处于观察模式的窗口
+#[tauri::command]
+async fn ctp_get_observers(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.event_bridge.observers())
+}
+
+// 窗口注销事件订阅
+#[tauri::command]
+async fn ctp_unregister_window(window: tauri::Window, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.event_bridge.unregister_window(window.label()))
+}
+
 // 登录 CTP
 #[tauri::command]
 async fn ctp_login(
     state: State<'_, AppState>,
     credentials: ctp::LoginCredentials,
-) -> Result<String, String> {
+) -> Result<dto::LoginResult, String> {
     let user_id = credentials.user_id.clone();
     
     // 获取客户端并执行登录
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
         match client.login(credentials).await {
+            Ok(_) if client.is_degraded() => {
+                // 交易端不可用，仅行情运行，后台独立重试交易端
+                spawn_trader_recovery(state.ctp_client.clone(), client.reconnect_policy(), &state.liveness);
+                Ok(dto::LoginResult {
+                    message: format!("用户 {} 登录成功（降级模式：交易端不可用，仅行情可用）", user_id),
+                    user_id,
+                    degraded: true,
+                    settlement_confirmed: false,
+                })
+            },
             Ok(_) => {
-                // 登录成功后自动确认结算单
-                if let Err(e) = client.confirm_settlement_info().await {
-                    tracing::warn!("自动确认结算单失败: {}", e);
-                    // 不影响登录成功的返回
+                // 登录成功后自动确认结算单（仅行情模式无需确认）
+                let mut settlement_confirmed = false;
+                if client.connection_mode().uses_td() {
+                    match client.confirm_settlement_info().await {
+                        Ok(_) => settlement_confirmed = true,
+                        // 不影响登录成功的返回
+                        Err(e) => tracing::warn!("自动确认结算单失败: {}", e),
+                    }
                 }
-                Ok(format!("用户 {} 登录成功", user_id))
+                Ok(dto::LoginResult {
+                    message: format!("用户 {} 登录成功", user_id),
+                    user_id,
+                    degraded: false,
+                    settlement_confirmed,
+                })
             },
             Err(e) => Err(format!("登录失败: {}", e)),
         }
@@ -111,16 +616,158 @@ async fn ctp_login(
     }
 }
 
+// 降级模式下独立重试交易端，行情端不受影响
+fn spawn_trader_recovery(
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    (interval, max_attempts): (std::time::Duration, u32),
+    liveness: &health::TaskLiveness,
+) {
+    let beat = liveness.register("trader_recovery", Some(interval));
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            beat.beat();
+            
+            let mut client_guard = ctp_client.lock().await;
+            let Some(client) = client_guard.as_mut() else {
+                break;
+            };
+            
+            if !client.is_degraded() {
+                tracing::info!("交易端已恢复，停止后台重试");
+                // 断线期间的报单状态未知，对账完成前不接受新订单
+                if client.needs_reconciliation() {
+                    if let Err(e) = client.reconcile_after_reconnect().await {
+                        tracing::warn!("重连对账未能发起: {}", e);
+                    }
+                }
+                break;
+            }
+            
+            let health = client.get_session_health();
+            if health.trader_retry_count >= max_attempts {
+                tracing::warn!("交易端重试已达上限 {} 次，保持降级模式", max_attempts);
+                break;
+            }
+            
+            if let Err(e) = client.retry_trader_login() {
+                tracing::warn!("交易端重试失败: {}", e);
+            }
+        }
+        beat.finish();
+    });
+}
+
+// 获取行情/交易两侧会话健康状态
+#[tauri::command]
+async fn ctp_get_session_health(
+    state: State<'_, AppState>,
+) -> Result<ctp::SessionHealth, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        Ok(client.get_session_health())
+    } else {
+        Ok(ctp::SessionHealth::new())
+    }
+}
+
+// 获取行情/交易两侧连接质量（心跳、断线、往返时延）
+#[tauri::command]
+async fn ctp_get_connection_quality(
+    state: State<'_, AppState>,
+) -> Result<ctp::ConnectionQualityReport, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        Ok(client.get_connection_quality())
+    } else {
+        Ok(ctp::ConnectionQuality::new().report())
+    }
+}
+
+// 手动重试交易端登录
+#[tauri::command]
+async fn ctp_retry_trader_login(
+    state: State<'_, AppState>,
+) -> Result<dto::TraderRetryResult, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        match client.retry_trader_login() {
+            Ok(attempt) => Ok(dto::TraderRetryResult {
+                attempt,
+                message: format!("已发送交易端重试请求（第 {} 次）", attempt),
+            }),
+            Err(e) => Err(format!("交易端重试失败: {}", e)),
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取最近的诊断事件（问题面板）
+#[tauri::command]
+async fn ctp_get_diagnostics(
+    limit: Option<usize>,
+    min_severity: Option<ctp::DiagnosticSeverity>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::DiagnosticEvent>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        Ok(client.diagnostics().recent(limit.unwrap_or(100), min_severity))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// 清空诊断事件
+#[tauri::command]
+async fn ctp_clear_diagnostics(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        client.diagnostics().clear();
+    }
+    Ok(())
+}
+
+// 获取事件总线各订阅者的积压与丢弃情况
+#[tauri::command]
+async fn ctp_get_event_bus_lag(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::SubscriberLag>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    Ok(client_guard.as_ref().map(|client| client.event_bus_lag()).unwrap_or_default())
+}
+
+// 运行自检：合成行情与模拟订单走一遍行情、订单、事件总线、日志和存储
+#[tauri::command]
+async fn ctp_run_self_test(
+    state: State<'_, AppState>,
+) -> Result<ctp::SelfTestReport, String> {
+    let report = ctp::run_self_test(self_test_options()).await;
+    tracing::info!("{}", report.summary());
+    *state.self_test.lock().unwrap() = Some(report.clone());
+    Ok(report)
+}
+
+// 获取最近一次自检报告，未运行过时为空
+#[tauri::command]
+async fn ctp_get_self_test_report(
+    state: State<'_, AppState>,
+) -> Result<Option<ctp::SelfTestReport>, String> {
+    Ok(state.self_test.lock().unwrap().clone())
+}
+
 // 确认结算单
 #[tauri::command]
 async fn ctp_confirm_settlement(
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<dto::ActionResult, String> {
     // 获取客户端并确认结算单
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
         match client.confirm_settlement_info().await {
-            Ok(_) => Ok("结算单确认成功".to_string()),
+            Ok(_) => Ok(dto::ActionResult::ok("结算单确认成功")),
             Err(e) => Err(format!("结算单确认失败: {}", e)),
         }
     } else {
@@ -133,36 +780,72 @@ async fn ctp_confirm_settlement(
 async fn ctp_subscribe(
     state: State<'_, AppState>,
     instrument_ids: Vec<String>,
-) -> Result<String, String> {
-    let count = instrument_ids.len();
-    
+) -> Result<dto::SubscribeResult, String> {
     // 获取客户端并执行订阅
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.subscribe_market_data(&instrument_ids).await {
-            Ok(_) => Ok(format!("已订阅 {} 个合约", count)),
-            Err(e) => Err(format!("订阅失败: {}", e)),
-        }
+        let mut result = dto::SubscribeResult::default();
+        subscribe_instruments(client, &instrument_ids, &mut result).await;
+        Ok(result)
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
+// 订阅一组合约并逐个记录结果，已订阅的合约跳过
+async fn subscribe_instruments(
+    client: &mut ctp::CtpClient,
+    instrument_ids: &[String],
+    result: &mut dto::SubscribeResult,
+) {
+    let (skipped, pending): (Vec<String>, Vec<String>) = instrument_ids
+        .iter()
+        .cloned()
+        .partition(|id| client.is_instrument_subscribed(id));
+    
+    for id in &skipped {
+        result.push(id, dto::SubscriptionOutcome::AlreadySubscribed, None);
+    }
+    if pending.is_empty() {
+        return;
+    }
+    
+    let error = client.subscribe_market_data(&pending).await.err().map(|e| e.to_string());
+    for id in &pending {
+        match &error {
+            None => result.push(id, dto::SubscriptionOutcome::Subscribed, None),
+            Some(e) => result.push(id, dto::SubscriptionOutcome::Failed, Some(e.clone())),
+        }
+    }
+}
+
 // 取消订阅行情
 #[tauri::command]
 async fn ctp_unsubscribe(
     state: State<'_, AppState>,
     instrument_ids: Vec<String>,
-) -> Result<String, String> {
-    let count = instrument_ids.len();
-    
+) -> Result<dto::SubscribeResult, String> {
     // 获取客户端并执行取消订阅
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.unsubscribe_market_data(&instrument_ids).await {
-            Ok(_) => Ok(format!("已取消订阅 {} 个合约", count)),
-            Err(e) => Err(format!("取消订阅失败: {}", e)),
+        let mut result = dto::SubscribeResult::default();
+        let (pending, skipped): (Vec<String>, Vec<String>) = instrument_ids
+            .into_iter()
+            .partition(|id| client.is_instrument_subscribed(id));
+        
+        for id in &skipped {
+            result.push(id, dto::SubscriptionOutcome::NotSubscribed, None);
+        }
+        if !pending.is_empty() {
+            let error = client.unsubscribe_market_data(&pending).await.err().map(|e| e.to_string());
+            for id in &pending {
+                match &error {
+                    None => result.push(id, dto::SubscriptionOutcome::Unsubscribed, None),
+                    Some(e) => result.push(id, dto::SubscriptionOutcome::Failed, Some(e.clone())),
+                }
+            }
         }
+        Ok(result)
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
@@ -170,242 +853,1280 @@ async fn ctp_unsubscribe(
 
 // 获取客户端状态
 #[tauri::command]
-async fn ctp_get_status(state: State<'_, AppState>) -> Result<String, String> {
+async fn ctp_get_status(state: State<'_, AppState>) -> Result<dto::StatusInfo, String> {
     let client = state.ctp_client.lock().await;
     
     if let Some(ref client) = *client {
-        let client_state = client.get_state();
-        Ok(format!("{:?}", client_state))
+        Ok(dto::StatusInfo {
+            state: client.get_state(),
+            connected: client.is_connected(),
+            logged_in: client.is_logged_in(),
+            degraded: client.is_degraded(),
+            connection_mode: Some(client.connection_mode()),
+            subscribed_count: client.get_subscribed_instruments().len(),
+        })
     } else {
-        Ok("Disconnected".to_string())
+        Ok(dto::StatusInfo::disconnected())
     }
 }
 
 // 断开连接
 #[tauri::command]
-async fn ctp_disconnect(state: State<'_, AppState>) -> Result<String, String> {
+async fn ctp_disconnect(state: State<'_, AppState>) -> Result<dto::ActionResult, String> {
     let mut client = state.ctp_client.lock().await;
     
     if client.is_some() {
         *client = None;
-        Ok("已断开 CTP 连接".to_string())
+        Ok(dto::ActionResult::ok("已断开 CTP 连接"))
     } else {
-        Ok("未连接".to_string())
+        Ok(dto::ActionResult::noop("未连接"))
     }
 }
 
-// 下单
+// 下单，携带幂等键重试时返回首次下单结果；关联 ID 贯穿请求、回报和日志
 #[tauri::command]
 async fn ctp_place_order(
     state: State<'_, AppState>,
-    order: ctp::OrderInput,
+    mut order: ctp::OrderInput,
+    idempotency_key: Option<String>,
+    correlation_id: Option<String>,
 ) -> Result<ctp::OrderRef, String> {
+    let request = ctp::IdempotentRequest::new(idempotency_key, "ctp_place_order", &order);
+    // 未指定来源时标记为界面下单
+    order.tags.entry("source".to_string()).or_insert_with(|| "ui".to_string());
+    
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.place_order(order).await {
-            Ok(order_ref) => Ok(order_ref),
-            Err(e) => Err(format!("下单失败: {}", e))
+        if let Some(result) = state.idempotency.replay(&request).map_err(|e| e.to_string())? {
+            return result;
         }
+        let result = logging::with_correlation(correlation_id, client.place_order(order)).await;
+        state.idempotency.complete(&request, result, "下单失败")
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 撤单
+// 报单预校验：执行完整的下单前检查但不提交，返回规范化订单和全部问题
 #[tauri::command]
-async fn ctp_cancel_order(
+async fn ctp_validate_order(
     state: State<'_, AppState>,
-    order_ref: String,
-    instrument_id: String,
-) -> Result<String, String> {
+    order: ctp::OrderInput,
+) -> Result<ctp::OrderValidationResult, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.cancel_order(&order_ref).await {
-            Ok(_) => Ok(format!("撤单请求已发送: {}", order_ref)),
-            Err(e) => Err(format!("撤单失败: {}", e))
-        }
+        client.validate_order(&order).await
+            .map_err(|e| format!("校验订单失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询账户资金
+// 下单预览：所需保证金、预估手续费、成交后可用资金和持仓，下单面板每次修改时调用
 #[tauri::command]
-async fn ctp_query_account(
+async fn ctp_preview_order(
     state: State<'_, AppState>,
-) -> Result<ctp::AccountInfo, String> {
+    order: ctp::OrderInput,
+) -> Result<ctp::OrderPreview, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_account().await {
-            Ok(account_info) => Ok(account_info),
-            Err(e) => Err(format!("查询账户失败: {}", e))
-        }
+        client.preview_order(&order).await
+            .map_err(|e| format!("下单预览失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询持仓
+// 计算最大可开手数，供下单面板“最大”按钮使用
 #[tauri::command]
-async fn ctp_query_positions(
+async fn ctp_max_open_volume(
     state: State<'_, AppState>,
-) -> Result<Vec<ctp::Position>, String> {
+    instrument: String,
+    direction: ctp::OrderDirection,
+    price: Option<f64>,
+    utilization_cap: Option<f64>,
+) -> Result<ctp::MaxOpenVolume, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_positions().await {
-            Ok(positions) => Ok(positions),
-            Err(e) => Err(format!("查询持仓失败: {}", e))
-        }
+        client.max_open_volume(&instrument, direction, price, utilization_cap).await
+            .map_err(|e| format!("计算最大可开手数失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询订单
+// 保证金压力测试：对当前持仓施加假设的价格冲击，返回盈亏、保证金和是否追保
 #[tauri::command]
-async fn ctp_query_orders(
+async fn ctp_stress_test(
     state: State<'_, AppState>,
-) -> Result<Vec<ctp::OrderStatus>, String> {
+    scenario: ctp::StressTestScenario,
+) -> Result<ctp::StressTestResult, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_orders(None).await {
-            Ok(orders) => Ok(orders),
-            Err(e) => Err(format!("查询订单失败: {}", e))
-        }
+        client.stress_test(&scenario).await
+            .map_err(|e| format!("压力测试失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询成交记录
+// 按净敞口上限生成对冲建议，未传入配置时读取配置文件；只返回建议，不会下单
 #[tauri::command]
-async fn ctp_query_trades(
+async fn ctp_get_hedge_suggestions(
     state: State<'_, AppState>,
-) -> Result<Vec<ctp::Trade>, String> {
+    config: Option<ctp::HedgeConfig>,
+) -> Result<ctp::HedgeReport, String> {
+    let config = match config {
+        Some(config) => config,
+        None => ctp::HedgeConfig::load(ctp::DEFAULT_HEDGE_CONFIG_FILE)
+            .map_err(|e| format!("加载敞口上限配置失败: {}", e))?,
+    };
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_trades(None).await {
-            Ok(trades) => Ok(trades),
-            Err(e) => Err(format!("查询成交失败: {}", e))
-        }
+        client.hedge_suggestions(&config).await
+            .map_err(|e| format!("生成对冲建议失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询合约信息
+// 为临近到期的持仓生成移仓计划，未传入配置时使用 config/rollover.toml
 #[tauri::command]
-async fn ctp_query_instruments(
+async fn ctp_plan_rollovers(
     state: State<'_, AppState>,
-) -> Result<Vec<ctp::InstrumentInfo>, String> {
+    config: Option<ctp::RolloverConfig>,
+) -> Result<ctp::RolloverReport, String> {
+    let config = match config {
+        Some(config) => config,
+        None => ctp::RolloverConfig::load(ctp::DEFAULT_ROLLOVER_CONFIG_FILE)
+            .map_err(|e| format!("加载移仓配置失败: {}", e))?,
+    };
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_instruments().await {
-            Ok(instruments) => Ok(instruments),
-            Err(e) => Err(format!("查询合约失败: {}", e))
-        }
+        client.plan_rollovers(&config).await
+            .map_err(|e| format!("生成移仓计划失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询手续费率
+// 执行移仓计划：先报平仓腿，开仓腿随平仓成交由追单任务报出，进度通过 RolloverUpdate 事件推送
 #[tauri::command]
-async fn ctp_query_commission_rate(
+async fn ctp_execute_rollover(
     state: State<'_, AppState>,
-    instrument_id: String,
-) -> Result<ctp::CommissionRate, String> {
+    plan: ctp::RolloverPlan,
+) -> Result<ctp::RolloverExecution, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_commission_rate(&instrument_id).await {
-            Ok(rate) => Ok(rate),
-            Err(e) => Err(format!("查询手续费率失败: {}", e))
-        }
+        client.execute_rollover(plan).await
+            .map_err(|e| format!("执行移仓失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 查询保证金率
+// 取消移仓，撤销在途的腿报单，已成交部分保留
 #[tauri::command]
-async fn ctp_query_margin_rate(
+async fn ctp_cancel_rollover(
     state: State<'_, AppState>,
-    instrument_id: String,
-) -> Result<ctp::MarginRate, String> {
+    rollover_id: String,
+) -> Result<ctp::RolloverExecution, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
-        match client.query_margin_rate(&instrument_id).await {
-            Ok(rate) => Ok(rate),
-            Err(e) => Err(format!("查询保证金率失败: {}", e))
-        }
+        client.cancel_rollover(&rollover_id).await
+            .map_err(|e| format!("取消移仓失败: {}", e))
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 批量订阅行情
+// 查询移仓：进行中的在前，之后是最近结束的移仓记录
 #[tauri::command]
-async fn ctp_batch_subscribe(
+async fn ctp_get_rollovers(
     state: State<'_, AppState>,
-    subscriptions: Vec<ctp::MarketDataSubscription>,
-) -> Result<String, String> {
-    let count = subscriptions.len();
-    let mut client_guard = state.ctp_client.lock().await;
-    if let Some(ref mut client) = client_guard.as_mut() {
-        for sub in subscriptions {
-            if let Err(e) = client.subscribe_market_data(&sub.instruments).await {
-                return Err(format!("批量订阅部分失败: {}", e));
-            }
-        }
-        Ok(format!("成功订阅 {} 组合约", count))
+    limit: Option<usize>,
+) -> Result<Vec<ctp::RolloverExecution>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        let rollovers = client.rollovers();
+        let mut list = rollovers.active();
+        list.extend(rollovers.history(limit.unwrap_or(50)));
+        Ok(list)
     } else {
         Err("请先连接并登录 CTP".to_string())
     }
 }
 
-// 获取实时行情
+// 人工停止合约或品种交易，被停止的合约拒绝一切报单
 #[tauri::command]
-async fn ctp_get_market_data(
+async fn ctp_disable_trading(
     state: State<'_, AppState>,
-    instrument_id: String,
-) -> Result<ctp::MarketData, String> {
-    let mut client_guard = state.ctp_client.lock().await;
-    if let Some(ref mut client) = client_guard.as_mut() {
-        match client.get_market_data(&instrument_id).await {
-            Ok(data) => Ok(data),
-            Err(e) => Err(format!("获取行情失败: {}", e))
-        }
-    } else {
-        Err("请先连接并登录 CTP".to_string())
-    }
+    scope: ctp::SwitchScope,
+    target: String,
+    reason: String,
+) -> Result<ctp::DisabledTarget, String> {
+    let result = match scope {
+        ctp::SwitchScope::Instrument => state.switchboard.disable_instrument(&target, ctp::SwitchSource::Manual, &reason),
+        ctp::SwitchScope::Product => state.switchboard.disable_product(&target, ctp::SwitchSource::Manual, &reason),
+    };
+    result.map_err(|e| format!("停止交易失败: {}", e))
 }
 
-// 获取所有订阅的行情
+// 恢复合约或品种交易，返回被移除的记录
 #[tauri::command]
-async fn ctp_get_all_market_data(
+async fn ctp_enable_trading(
     state: State<'_, AppState>,
-) -> Result<Vec<ctp::MarketData>, String> {
-    let mut client_guard = state.ctp_client.lock().await;
-    if let Some(ref mut client) = client_guard.as_mut() {
-        match client.get_all_market_data().await {
-            Ok(data) => Ok(data),
-            Err(e) => Err(format!("获取所有行情失败: {}", e))
-        }
-    } else {
-        Err("请先连接并登录 CTP".to_string())
-    }
+    scope: ctp::SwitchScope,
+    target: String,
+) -> Result<Option<ctp::DisabledTarget>, String> {
+    Ok(match scope {
+        ctp::SwitchScope::Instrument => state.switchboard.enable_instrument(&target),
+        ctp::SwitchScope::Product => state.switchboard.enable_product(&target),
+    })
 }
 
-// 设置风险控制参数
+// 查询停止交易的合约和品种
 #[tauri::command]
-async fn ctp_set_risk_params(
-    state: State<'_, AppState>,
-    params: ctp::RiskParams,
-) -> Result<String, String> {
+async fn ctp_get_disabled_trading(state: State<'_, AppState>) -> Result<Vec<ctp::DisabledTarget>, String> {
+    Ok(state.switchboard.list())
+}
+
+// 新增跟踪止损，已连接时以最新价作为初始最有利价
+#[tauri::command]
+async fn ctp_add_trailing_stop(
+    state: State<'_, AppState>,
+    spec: ctp::TrailingStopSpec,
+) -> Result<ctp::TrailingStop, String> {
+    let last_price = {
+        let mut client_guard = state.ctp_client.lock().await;
+        match client_guard.as_mut() {
+            Some(client) => client.get_market_data(&spec.instrument_id).await.ok().map(|m| m.last_price),
+            None => None,
+        }
+    };
+    let stop = state
+        .trailing_stops
+        .add(spec, last_price.filter(|p| *p > 0.0))
+        .map_err(|e| format!("新增跟踪止损失败: {}", e))?;
+    let history = state.storage.as_ref().map(|s| s.market_data() as &dyn ctp::HistorySource);
+    if let Err(e) = state.trailing_stops.sync_atr_feed(&mut state.strategy_engine.lock().unwrap(), history) {
+        tracing::warn!("跟踪止损日线订阅更新失败: {}", e);
+    }
+    Ok(stop)
+}
+
+// 取消跟踪止损
+#[tauri::command]
+async fn ctp_cancel_trailing_stop(state: State<'_, AppState>, id: String) -> Result<ctp::TrailingStop, String> {
+    let stop = state
+        .trailing_stops
+        .cancel(&id)
+        .map_err(|e| format!("取消跟踪止损失败: {}", e))?;
+    if let Err(e) = state.trailing_stops.sync_atr_feed(&mut state.strategy_engine.lock().unwrap(), None) {
+        tracing::warn!("跟踪止损日线订阅更新失败: {}", e);
+    }
+    Ok(stop)
+}
+
+// 查询跟踪止损及当前触发价
+#[tauri::command]
+async fn ctp_get_trailing_stops(state: State<'_, AppState>) -> Result<Vec<ctp::TrailingStop>, String> {
+    Ok(state.trailing_stops.list())
+}
+
+// 执行快捷键动作：价格、开平由后端解析，经开关、限速和预校验后提交
+#[tauri::command]
+async fn ctp_hotkey_execute(
+    state: State<'_, AppState>,
+    action: ctp::HotkeyAction,
+    idempotency_key: Option<String>,
+    correlation_id: Option<String>,
+) -> Result<ctp::HotkeyOutcome, String> {
+    let request = ctp::IdempotentRequest::new(idempotency_key, "ctp_hotkey_execute", &action);
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        if let Some(result) = state.idempotency.replay(&request).map_err(|e| e.to_string())? {
+            return result;
+        }
+        let result = logging::with_correlation(correlation_id, client.execute_hotkey(&state.hotkeys, action)).await;
+        state.idempotency.complete(&request, result, "快捷键执行失败")
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 启用或关闭快捷键交易
+#[tauri::command]
+async fn ctp_hotkey_set_enabled(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<ctp::HotkeyStatus, String> {
+    state.hotkeys.set_enabled(enabled);
+    Ok(state.hotkeys.status())
+}
+
+// 获取快捷键状态
+#[tauri::command]
+async fn ctp_hotkey_status(state: State<'_, AppState>) -> Result<ctp::HotkeyStatus, String> {
+    Ok(state.hotkeys.status())
+}
+
+// 分页查询账户活动时间线（报单、成交、转账、连接和风控事件）
+#[tauri::command]
+async fn ctp_get_timeline(
+    state: State<'_, AppState>,
+    query: Option<ctp::TimelineQuery>,
+) -> Result<ctp::TimelinePage, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.timeline().page(&query.unwrap_or_default()))
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取各报单来源的拒单熔断状态
+#[tauri::command]
+async fn ctp_get_rejection_breakers(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::SourceBreakerStatus>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.rejection_breaker().statuses())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// 确认拒单熔断告警，恢复该来源报单
+#[tauri::command]
+async fn ctp_acknowledge_rejection_breaker(
+    state: State<'_, AppState>,
+    source: String,
+) -> Result<bool, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.rejection_breaker().acknowledge(&source))
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取当日报单、撤单、查询次数及阈值预警
+#[tauri::command]
+async fn ctp_get_api_usage(
+    state: State<'_, AppState>,
+) -> Result<ctp::ApiUsageSnapshot, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.api_usage())
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取账户及各策略的报单成交比、撤单比合规状态
+#[tauri::command]
+async fn ctp_get_compliance_status(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::ComplianceStatus>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    Ok(client_guard.as_ref().map(|client| client.compliance_statuses()).unwrap_or_default())
+}
+
+// 按客户端订单号查询对应的会话、报单引用和交易所报单编号
+#[tauri::command]
+async fn ctp_get_client_order(
+    state: State<'_, AppState>,
+    client_order_id: String,
+) -> Result<ctp::ClientOrderRecord, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let client = client_guard.as_ref().ok_or_else(|| "请先连接并登录 CTP".to_string())?;
+    client
+        .client_order(&client_order_id)
+        .ok_or_else(|| format!("客户端订单号 {} 不存在", client_order_id))
+}
+
+// 获取报单、行情转换对象池的命中率
+#[tauri::command]
+async fn ctp_get_conversion_pool_stats(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::PoolStats>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    Ok(client_guard.as_ref().map(|client| client.conversion_pool_stats()).unwrap_or_default())
+}
+
+// 获取最近的 CTP 请求与回调报文（已脱敏），用于核对报单争议
+#[tauri::command]
+async fn ctp_get_wire_log(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<ctp::WireRecord>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    Ok(client_guard.as_ref().map(|client| client.wire_log().snapshot(limit)).unwrap_or_default())
+}
+
+// 获取报文日志的记录、采样丢弃统计
+#[tauri::command]
+async fn ctp_get_wire_log_stats(state: State<'_, AppState>) -> Result<Option<ctp::WireLogStats>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    Ok(client_guard.as_ref().map(|client| client.wire_log().stats()))
+}
+
+// 读取报文日志配置
+#[tauri::command]
+async fn get_wire_log_config() -> Result<ctp::WireLogConfig, String> {
+    ctp::WireLogConfig::load(ctp::DEFAULT_WIRE_LOG_CONFIG_FILE).map_err(|e| format!("读取报文日志配置失败: {}", e))
+}
+
+// 保存报文日志配置，已连接的客户端立即生效
+#[tauri::command]
+async fn set_wire_log_config(state: State<'_, AppState>, config: ctp::WireLogConfig) -> Result<(), String> {
+    config
+        .save(ctp::DEFAULT_WIRE_LOG_CONFIG_FILE)
+        .map_err(|e| format!("保存报文日志配置失败: {}", e))?;
+    if let Some(client) = state.ctp_client.lock().await.as_ref() {
+        client.wire_log().update_config(config).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 获取行情热路径与界面运行时的调度延迟
+#[tauri::command]
+fn ctp_get_scheduler_latency(state: State<'_, AppState>) -> Result<Vec<ctp::SchedulerLatencyStats>, String> {
+    let mut stats = Vec::new();
+    if let Some(probe) = state.ui_latency.get() {
+        stats.push(probe.stats());
+    }
+    if let Some(latency) = state.hot_path.as_ref().and_then(|hot_path| hot_path.latency()) {
+        stats.push(latency);
+    }
+    Ok(stats)
+}
+
+// 获取最近的资金曲线异常告警
+#[tauri::command]
+async fn ctp_get_funds_anomalies(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::FundsAnomaly>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.funds_monitor().recent())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// 获取资金曲线异常检测阈值
+#[tauri::command]
+async fn ctp_get_funds_monitor_config(
+    state: State<'_, AppState>,
+) -> Result<ctp::FundsMonitorConfig, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        Ok(client.funds_monitor().config())
+    } else {
+        Ok(ctp::FundsMonitorConfig::default())
+    }
+}
+
+// 设置资金曲线异常检测阈值
+#[tauri::command]
+async fn ctp_set_funds_monitor_config(
+    state: State<'_, AppState>,
+    config: ctp::FundsMonitorConfig,
+) -> Result<(), String> {
+    if config.min_unexplained_drop < 0.0 || !(0.0..1.0).contains(&config.unexplained_drop_ratio) {
+        return Err("资金异常阈值无效".to_string());
+    }
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        client.funds_monitor().set_config(config);
+        Ok(())
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 设置交易命令幂等键保留时长（秒）
+#[tauri::command]
+async fn ctp_set_idempotency_retention(
+    state: State<'_, AppState>,
+    seconds: u64,
+) -> Result<dto::ActionResult, String> {
+    state.idempotency.set_retention(std::time::Duration::from_secs(seconds));
+    Ok(dto::ActionResult::ok(format!("幂等键保留 {} 秒", seconds)))
+}
+
+// 获取主备实例协调状态，未启用协调时返回空
+#[tauri::command]
+async fn ctp_get_instance_status(state: State<'_, AppState>) -> Result<Option<ctp::InstanceStatus>, String> {
+    Ok(state.instance.as_ref().map(|instance| instance.status()))
+}
+
+// 将主角色移交给备用实例：先断开本实例的交易端再释放锁，避免重复登录；行情会话保留
+#[tauri::command]
+async fn ctp_handover_active(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    target: Option<String>,
+) -> Result<ctp::InstanceStatus, String> {
+    use tauri::Emitter;
+
+    let instance = state.instance.as_ref().ok_or("未启用主备实例协调")?;
+    if !instance.is_active() {
+        return Err("当前实例不是主实例".to_string());
+    }
+
+    // 只断开交易端，行情订阅与录制继续
+    if let Some(client) = state.ctp_client.lock().await.as_mut() {
+        client.stop_trader_session();
+    }
+    let status = instance.hand_over(target).map_err(|e| format!("移交主角色失败: {}", e))?;
+    if let Err(e) = app.emit("instance-role-changed", &status) {
+        tracing::warn!("推送实例角色变化失败: {}", e);
+    }
+    Ok(status)
+}
+
+// 开启或关闭操作录制，开启时写入新的会话文件
+#[tauri::command]
+async fn ctp_set_action_recording(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<ctp::ActionRecordingStatus, String> {
+    if enabled {
+        state.action_recorder.start().map_err(|e| format!("开启操作录制失败: {}", e))
+    } else {
+        Ok(state.action_recorder.stop())
+    }
+}
+
+// 获取操作录制状态
+#[tauri::command]
+async fn ctp_get_action_recording(state: State<'_, AppState>) -> Result<ctp::ActionRecordingStatus, String> {
+    Ok(state.action_recorder.status())
+}
+
+// Webhook 配置读取失败时不推送，不影响启动
+fn webhook_dispatcher() -> ctp::WebhookDispatcher {
+    let config = ctp::WebhookConfig::load(ctp::DEFAULT_WEBHOOK_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载 Webhook 配置失败: {}", e);
+        ctp::WebhookConfig::default()
+    });
+    if !config.endpoints.is_empty() {
+        tracing::info!("已配置 {} 个 Webhook 目标", config.endpoints.len());
+    }
+    ctp::WebhookDispatcher::new(config)
+}
+
+// 运行时调优配置读取失败时使用默认值，热路径运行时创建失败时回退到界面运行时
+fn runtime_tuning() -> (ctp::RuntimeTuning, Option<Arc<ctp::HotPathRuntime>>) {
+    let tuning = ctp::RuntimeTuning::load(ctp::DEFAULT_RUNTIME_TUNING_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载运行时调优配置失败: {}", e);
+        ctp::RuntimeTuning::default()
+    });
+    if !tuning.dedicated_md_runtime {
+        return (tuning, None);
+    }
+    match ctp::HotPathRuntime::start(&tuning) {
+        Ok(runtime) => (tuning, Some(Arc::new(runtime))),
+        Err(e) => {
+            tracing::warn!("{}，事件总线使用默认运行时", e);
+            (tuning, None)
+        }
+    }
+}
+
+// 通知渠道配置读取失败时不发送，不影响启动
+fn notifier() -> ctp::Notifier {
+    let config = ctp::NotifierConfig::load(ctp::DEFAULT_NOTIFIER_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载通知渠道配置失败: {}", e);
+        ctp::NotifierConfig::default()
+    });
+    if !config.channels.is_empty() {
+        tracing::info!("已配置 {} 个通知渠道", config.channels.len());
+    }
+    ctp::Notifier::new(config)
+}
+
+// 备份配置读取失败时使用默认配置；已打开的数据库通过引擎快照备份，恢复时重启后替换
+fn backup_manager(storage: Option<ctp::Storage>) -> ctp::BackupManager {
+    let config = ctp::BackupConfig::load(ctp::DEFAULT_BACKUP_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载备份配置失败: {}", e);
+        ctp::BackupConfig::default()
+    });
+    let manager = ctp::BackupManager::new(config);
+    match storage {
+        Some(storage) => manager.with_storage(storage),
+        None => manager,
+    }
+}
+
+// 保留策略读取失败时使用默认策略
+fn retention_manager() -> ctp::RetentionManager {
+    let policy = ctp::RetentionPolicy::load(ctp::DEFAULT_RETENTION_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载数据保留策略失败: {}", e);
+        ctp::RetentionPolicy::default()
+    });
+    ctp::RetentionManager::new(policy)
+}
+
+// 存储配置读取或数据库打开失败时不落库，不影响交易
+fn storage() -> Option<ctp::Storage> {
+    let config = ctp::StorageConfig::load(ctp::DEFAULT_STORAGE_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载存储配置失败: {}", e);
+        ctp::StorageConfig::default()
+    });
+    match ctp::Storage::open(&config) {
+        Ok(storage) => Some(storage),
+        Err(e) => {
+            tracing::warn!("打开存储后端失败: {}", e);
+            None
+        }
+    }
+}
+
+// 策略引擎配置读取失败时使用默认配置
+fn strategy_engine() -> ctp::StrategyEngine {
+    let config = ctp::StrategyEngineConfig::load(ctp::DEFAULT_STRATEGY_ENGINE_CONFIG_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载策略引擎配置失败: {}", e);
+        ctp::StrategyEngineConfig::default()
+    });
+    ctp::StrategyEngine::new(config)
+}
+
+// 交易开关状态读取失败时不恢复，之后的开关只保存在内存中
+fn switchboard() -> ctp::TradingSwitchboard {
+    ctp::TradingSwitchboard::open(ctp::DEFAULT_SWITCHBOARD_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载交易开关状态失败: {}", e);
+        ctp::TradingSwitchboard::in_memory()
+    })
+}
+
+// 跟踪止损文件读取失败时不恢复，新增的止损只保存在内存中
+fn trailing_stop_manager() -> ctp::TrailingStopManager {
+    ctp::TrailingStopManager::open(ctp::DEFAULT_TRAILING_STOP_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载跟踪止损失败: {}", e);
+        ctp::TrailingStopManager::in_memory()
+    })
+}
+
+// 风控预设读取失败时不启用预设
+fn risk_preset_manager() -> ctp::RiskPresetManager {
+    let config = ctp::RiskPresetConfig::load(ctp::DEFAULT_RISK_PRESETS_FILE).unwrap_or_else(|e| {
+        tracing::warn!("加载风控预设失败: {}", e);
+        ctp::RiskPresetConfig::default()
+    });
+    ctp::RiskPresetManager::new(config)
+}
+
+// 自检使用当前环境的默认配置，不发起连接
+fn self_test_options() -> ctp::SelfTestOptions {
+    let env = std::env::var("CTP_ENV")
+        .ok()
+        .and_then(|v| v.parse::<ctp::config::Environment>().ok())
+        .unwrap_or(ctp::config::Environment::SimNow);
+    ctp::SelfTestOptions::new(ctp::CtpConfig::for_environment(env, String::new(), String::new()))
+}
+
+// 设置 CTP_SELF_TEST=1 时启动后先自检，结果推送到前端
+fn spawn_startup_self_test(app: tauri::AppHandle, report_slot: Arc<std::sync::Mutex<Option<ctp::SelfTestReport>>>) {
+    tauri::async_runtime::spawn(async move {
+        use tauri::Emitter;
+        let report = ctp::run_self_test(self_test_options()).await;
+        if report.ready {
+            tracing::info!("{}", report.summary());
+        } else {
+            tracing::warn!("{}", report.summary());
+        }
+        *report_slot.lock().unwrap() = Some(report.clone());
+        if let Err(e) = app.emit("self-test-report", report) {
+            tracing::warn!("推送自检报告失败: {}", e);
+        }
+    });
+}
+
+// 设置 CTP_INSTANCE_DIR 时启用主备实例协调，主备实例须指向同一目录
+fn instance_coordinator() -> Option<ctp::InstanceCoordinator> {
+    let dir = std::env::var("CTP_INSTANCE_DIR").ok().filter(|d| !d.is_empty())?;
+    let config = ctp::CoordinatorConfig {
+        dir: dir.into(),
+        ..ctp::CoordinatorConfig::default()
+    };
+    match ctp::InstanceCoordinator::new(config) {
+        Ok(instance) => {
+            // 启动时即确定角色，前端连接前可据此选择连接模式
+            match instance.tick() {
+                Ok(role) => tracing::info!("实例 {} 启动角色: {:?}", instance.instance_id(), role),
+                Err(e) => tracing::warn!("实例协调心跳失败: {}", e),
+            }
+            Some(instance)
+        }
+        Err(e) => {
+            tracing::warn!("启用主备实例协调失败: {}", e);
+            None
+        }
+    }
+}
+
+// 主备实例心跳：主实例刷新租约，备用实例在移交或主实例退出后接管
+fn spawn_instance_heartbeat(
+    app: tauri::AppHandle,
+    instance: ctp::InstanceCoordinator,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    liveness: &health::TaskLiveness,
+) {
+    use tauri::Emitter;
+
+    let beat = liveness.register("instance_heartbeat", Some(instance.config().heartbeat_interval));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(instance.config().heartbeat_interval);
+        let mut last_role = instance.role();
+        loop {
+            interval.tick().await;
+            beat.beat();
+            let role = match instance.tick() {
+                Ok(role) => role,
+                Err(e) => {
+                    tracing::warn!("实例协调心跳失败: {}", e);
+                    continue;
+                }
+            };
+            if role == last_role {
+                continue;
+            }
+
+            if let Some(client) = ctp_client.lock().await.as_mut() {
+                if role == ctp::InstanceRole::Standby {
+                    // 失去主角色时立即断开交易端，保证只有一个实例持有交易会话；行情继续录制
+                    client.stop_trader_session();
+                } else if client.is_logged_in() {
+                    // 升为主实例后在现有行情会话上建立交易会话
+                    match client.start_trader_session().await {
+                        Ok(()) => tracing::info!("已接管交易会话"),
+                        Err(e) => tracing::error!("接管交易会话失败: {}", e),
+                    }
+                }
+            }
+            tracing::info!("实例角色变化: {:?} -> {:?}", last_role, role);
+            if let Err(e) = app.emit("instance-role-changed", instance.status()) {
+                tracing::warn!("推送实例角色变化失败: {}", e);
+            }
+            last_role = role;
+        }
+    });
+}
+
+// 死人开关：策略运行期间布防，前端或远程控制端超时未发送心跳时撤销全部挂单并可选平仓
+#[tauri::command]
+async fn ctp_arm_dead_man(
+    state: State<'_, AppState>,
+    timeout_secs: u64,
+    flatten: bool,
+    source: Option<String>,
+) -> Result<ctp::DeadManStatus, String> {
+    let config = ctp::DeadManConfig {
+        timeout: std::time::Duration::from_secs(timeout_secs),
+        flatten,
+    };
+    state.dead_man.arm(config, source).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn ctp_disarm_dead_man(state: State<'_, AppState>) -> Result<ctp::DeadManStatus, String> {
+    Ok(state.dead_man.disarm())
+}
+
+#[tauri::command]
+async fn ctp_dead_man_heartbeat(
+    state: State<'_, AppState>,
+    source: Option<String>,
+) -> Result<ctp::DeadManStatus, String> {
+    Ok(state.dead_man.heartbeat(source))
+}
+
+#[tauri::command]
+async fn ctp_get_dead_man_status(state: State<'_, AppState>) -> Result<ctp::DeadManStatus, String> {
+    Ok(state.dead_man.status())
+}
+
+/// 每秒检查死人开关，触发后撤单（并可选平仓），处置结果推送到前端
+fn spawn_dead_man_watchdog(
+    app: tauri::AppHandle,
+    dead_man: ctp::DeadManSwitch,
+    webhooks: ctp::WebhookDispatcher,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    liveness: &health::TaskLiveness,
+) {
+    use tauri::Emitter;
+
+    let beat = liveness.register("dead_man_watchdog", Some(std::time::Duration::from_secs(1)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            beat.beat();
+            let Some(trigger) = dead_man.check() else {
+                continue;
+            };
+            let mut client_guard = ctp_client.lock().await;
+            let Some(client) = client_guard.as_mut() else {
+                tracing::warn!("死人开关触发时未连接交易，无需处置");
+                continue;
+            };
+            let report = client.execute_dead_man(trigger).await;
+            drop(client_guard);
+            webhooks.notify(ctp::WebhookPayload::kill_switch(&report));
+            if let Err(e) = app.emit("dead-man-triggered", report) {
+                tracing::warn!("推送死人开关处置结果失败: {}", e);
+            }
+        }
+    });
+}
+
+// 市价单模拟的追单：FAK 未成交部分按最新对手价重新报出；移仓平仓成交后的开仓腿也在此报出
+fn spawn_market_order_chaser(ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>, liveness: &health::TaskLiveness) {
+    let beat = liveness.register("market_order_chaser", Some(std::time::Duration::from_millis(500)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(500));
+        loop {
+            interval.tick().await;
+            beat.beat();
+            let mut client_guard = ctp_client.lock().await;
+            if let Some(client) = client_guard.as_mut() {
+                if client.has_pending_market_chases() {
+                    client.chase_market_orders().await;
+                }
+                if client.has_pending_rollovers() {
+                    client.advance_rollovers().await;
+                }
+            }
+        }
+    });
+}
+
+// 每分钟检查一次，距最近备份超过配置的间隔时在阻塞线程中打包
+fn spawn_backup_scheduler(backups: ctp::BackupManager, liveness: &health::TaskLiveness) {
+    let beat = liveness.register("backup_scheduler", Some(std::time::Duration::from_secs(60)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            beat.beat();
+            match backups.is_due(chrono::Utc::now()) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("检查备份状态失败: {}", e);
+                    continue;
+                }
+            }
+            let manager = backups.clone();
+            match tauri::async_runtime::spawn_blocking(move || manager.create_backup()).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::error!("定时备份失败: {}", e),
+                Err(e) => tracing::error!("定时备份任务异常: {}", e),
+            }
+        }
+    });
+}
+
+// 每 30 秒按时段检查应生效的风控预设，只在登录后下发；时段未变化时不覆盖手动切换
+fn spawn_risk_preset_scheduler(
+    app: tauri::AppHandle,
+    presets: ctp::RiskPresetManager,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    liveness: &health::TaskLiveness,
+) {
+    use tauri::Emitter;
+
+    let beat = liveness.register("risk_preset_scheduler", Some(std::time::Duration::from_secs(30)));
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+        loop {
+            interval.tick().await;
+            beat.beat();
+            let mut client_guard = ctp_client.lock().await;
+            let Some(client) = client_guard.as_mut().filter(|c| c.is_logged_in()) else {
+                continue;
+            };
+            let Some(name) = presets.due_switch(chrono::Local::now().naive_local()) else {
+                continue;
+            };
+            match client.apply_risk_preset(&presets, &name, ctp::PresetSwitchSource::Schedule).await {
+                Ok(switch) => {
+                    tracing::info!("定时切换风控预设: {}", name);
+                    if let Err(e) = app.emit(RISK_PRESET_EVENT, switch) {
+                        tracing::warn!("推送风控预设切换失败: {}", e);
+                    }
+                }
+                Err(e) => tracing::error!("定时切换风控预设 {} 失败: {}", name, e),
+            }
+        }
+    });
+}
+
+// 每分钟检查一次，交易日收盘后（`run_after`）自动生成当日风险报告，未登录时等待下次检查
+fn spawn_risk_report_scheduler(
+    webhooks: ctp::WebhookDispatcher,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    liveness: &health::TaskLiveness,
+) {
+    let beat = liveness.register("risk_report_scheduler", Some(std::time::Duration::from_secs(60)));
+    tauri::async_runtime::spawn(async move {
+        let generator = ctp::RiskReportGenerator::new(ctp::RiskReportConfig::default());
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            beat.beat();
+            let Some(trading_day) = generator.due_report_day(chrono::Local::now().naive_local()) else {
+                continue;
+            };
+            if !ctp_client.lock().await.as_ref().is_some_and(|c| c.is_logged_in()) {
+                continue;
+            }
+            match generate_risk_report(&ctp_client, &webhooks, trading_day, None).await {
+                Ok(_) => tracing::info!("交易日 {} 风险报告已定时生成", trading_day),
+                Err(e) => tracing::error!("定时生成交易日 {} 风险报告失败: {}", trading_day, e),
+            }
+        }
+    });
+}
+
+// 开发命令：在模拟环境中按顺序重新执行录制的操作，用于复现问题
+#[tauri::command]
+async fn replay_actions(
+    state: State<'_, AppState>,
+    file: String,
+    respect_timing: Option<bool>,
+) -> Result<ctp::ReplayReport, String> {
+    if !cfg!(debug_assertions) {
+        return Err("操作回放仅在开发构建中可用".to_string());
+    }
+    {
+        let client_guard = state.ctp_client.lock().await;
+        match client_guard.as_ref() {
+            Some(client) if client.get_config_info().environment == ctp::Environment::Production => {
+                return Err("操作回放只能在模拟环境中执行".to_string());
+            }
+            Some(client) if client.is_logged_in() => {}
+            _ => return Err("请先连接并登录模拟环境".to_string()),
+        }
+    }
+
+    let actions = ctp::load_actions(&file).map_err(|e| format!("读取录制文件失败: {}", e))?;
+    tracing::info!("开始回放 {} 中的 {} 条操作", file, actions.len());
+
+    let mut report = ctp::ReplayReport::new(&file);
+    let mut last_offset = actions.first().map_or(0, |a| a.offset_ms);
+    for action in &actions {
+        if respect_timing.unwrap_or(false) {
+            // 还原操作间隔，单次最多等待 10 秒
+            let wait = action.offset_ms.saturating_sub(last_offset).min(10_000);
+            tokio::time::sleep(std::time::Duration::from_millis(wait)).await;
+        }
+        last_offset = action.offset_ms;
+
+        let mut client_guard = state.ctp_client.lock().await;
+        let Some(client) = client_guard.as_mut() else {
+            report.push(action, ctp::ReplayStepStatus::Failed, Some("客户端已断开".to_string()));
+            break;
+        };
+        match replay_action(client, &state.hotkeys, action).await {
+            Ok(true) => report.push(action, ctp::ReplayStepStatus::Executed, None),
+            Ok(false) => report.push(action, ctp::ReplayStepStatus::Skipped, Some("该命令不支持回放".to_string())),
+            Err(e) => report.push(action, ctp::ReplayStepStatus::Failed, Some(e.to_string())),
+        }
+    }
+
+    tracing::info!(
+        "回放完成: 执行 {}，跳过 {}，失败 {}",
+        report.executed,
+        report.skipped,
+        report.failed
+    );
+    Ok(report)
+}
+
+// 重新执行一条录制的操作，不支持回放的命令返回 false
+//
+// 连接、登录等命令的参数已脱敏无法重放；撤单使用录制时的报单引用
+async fn replay_action(
+    client: &mut ctp::CtpClient,
+    hotkeys: &ctp::HotkeyController,
+    action: &ctp::RecordedAction,
+) -> Result<bool, ctp::CtpError> {
+    match action.command.as_str() {
+        "ctp_place_order" => {
+            let mut order: ctp::OrderInput = action.arg("order")?;
+            order.tags.insert("source".to_string(), "replay".to_string());
+            client.place_order(order).await?;
+        }
+        "ctp_validate_order" => {
+            client.validate_order(&action.arg("order")?).await?;
+        }
+        "ctp_cancel_order" => {
+            let order_ref: String = action.arg("orderRef")?;
+            client.cancel_order(&order_ref).await?;
+        }
+        "ctp_hotkey_execute" => {
+            client.execute_hotkey(hotkeys, action.arg("action")?).await?;
+        }
+        "ctp_subscribe" => {
+            let instrument_ids: Vec<String> = action.arg("instrumentIds")?;
+            client.subscribe_market_data(&instrument_ids).await?;
+        }
+        "ctp_unsubscribe" => {
+            let instrument_ids: Vec<String> = action.arg("instrumentIds")?;
+            client.unsubscribe_market_data(&instrument_ids).await?;
+        }
+        "ctp_set_risk_params" => {
+            client.set_risk_params(action.arg("params")?).await?;
+        }
+        "ctp_query_account" => {
+            client.query_account().await?;
+        }
+        "ctp_query_positions" => {
+            client.query_positions().await?;
+        }
+        "ctp_query_orders" => {
+            client.query_orders(None).await?;
+        }
+        "ctp_query_trades" => {
+            client.query_trades(None).await?;
+        }
+        "ctp_query_commission_rate" => {
+            let instrument_id: String = action.arg("instrumentId")?;
+            client.query_commission_rate(&instrument_id).await?;
+        }
+        "ctp_query_margin_rate" => {
+            let instrument_id: String = action.arg("instrumentId")?;
+            client.query_margin_rate(&instrument_id).await?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+// 撤单，携带幂等键重试时返回首次撤单结果
+#[tauri::command]
+async fn ctp_cancel_order(
+    state: State<'_, AppState>,
+    order_ref: String,
+    instrument_id: String,
+    idempotency_key: Option<String>,
+    correlation_id: Option<String>,
+) -> Result<dto::CancelOrderResult, String> {
+    let request = ctp::IdempotentRequest::new(idempotency_key, "ctp_cancel_order", &(&order_ref, &instrument_id));
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        if let Some(result) = state.idempotency.replay(&request).map_err(|e| e.to_string())? {
+            return result;
+        }
+        let result = logging::with_correlation(correlation_id, client.cancel_order(&order_ref)).await
+            .map(|_| dto::CancelOrderResult {
+                message: format!("撤单请求已发送: {}", order_ref),
+                order_ref,
+                instrument_id,
+            });
+        state.idempotency.complete(&request, result, "撤单失败")
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询账户资金
+#[tauri::command]
+async fn ctp_query_account(
+    state: State<'_, AppState>,
+) -> Result<ctp::AccountInfo, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_account().await {
+            Ok(account_info) => Ok(account_info),
+            Err(e) => Err(format!("查询账户失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 各币种资金按汇率配置折算后的总权益，缺少汇率时不汇总
+#[tauri::command]
+async fn ctp_get_aggregated_equity(
+    state: State<'_, AppState>,
+    display_currency: Option<String>,
+) -> Result<ctp::AggregatedEquity, String> {
+    let fx = ctp::FxRateTable::load(ctp::DEFAULT_FX_RATE_FILE).map_err(|e| e.to_string())?;
+    let display_currency = display_currency
+        .map(|c| ctp::normalize_currency(&c))
+        .unwrap_or_else(|| fx.base_currency.clone());
+    let client_guard = state.ctp_client.lock().await;
+    let client = client_guard.as_ref().ok_or_else(|| "请先连接并登录 CTP".to_string())?;
+    Ok(client.aggregated_equity(&display_currency, &fx))
+}
+
+// 查询持仓
+#[tauri::command]
+async fn ctp_query_positions(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::Position>, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_positions().await {
+            Ok(positions) => Ok(positions),
+            Err(e) => Err(format!("查询持仓失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询订单
+#[tauri::command]
+async fn ctp_query_orders(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::OrderStatus>, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_orders(None).await {
+            Ok(orders) => Ok(orders),
+            Err(e) => Err(format!("查询订单失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询成交记录
+#[tauri::command]
+async fn ctp_query_trades(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::Trade>, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_trades(None).await {
+            Ok(trades) => Ok(trades),
+            Err(e) => Err(format!("查询成交失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询合约信息
+#[tauri::command]
+async fn ctp_query_instruments(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::InstrumentInfo>, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_instruments().await {
+            Ok(instruments) => Ok(instruments),
+            Err(e) => Err(format!("查询合约失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询手续费率
+#[tauri::command]
+async fn ctp_query_commission_rate(
+    state: State<'_, AppState>,
+    instrument_id: String,
+) -> Result<ctp::CommissionRate, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_commission_rate(&instrument_id).await {
+            Ok(rate) => Ok(rate),
+            Err(e) => Err(format!("查询手续费率失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 查询保证金率
+#[tauri::command]
+async fn ctp_query_margin_rate(
+    state: State<'_, AppState>,
+    instrument_id: String,
+) -> Result<ctp::MarginRate, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.query_margin_rate(&instrument_id).await {
+            Ok(rate) => Ok(rate),
+            Err(e) => Err(format!("查询保证金率失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 批量订阅行情
+#[tauri::command]
+async fn ctp_batch_subscribe(
+    state: State<'_, AppState>,
+    subscriptions: Vec<ctp::MarketDataSubscription>,
+) -> Result<dto::SubscribeResult, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        // 各组独立订阅，单组失败不影响其他组
+        let mut result = dto::SubscribeResult::default();
+        for sub in subscriptions {
+            subscribe_instruments(client, &sub.instruments, &mut result).await;
+        }
+        Ok(result)
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取实时行情
+#[tauri::command]
+async fn ctp_get_market_data(
+    state: State<'_, AppState>,
+    instrument_id: String,
+) -> Result<ctp::MarketData, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.get_market_data(&instrument_id).await {
+            Ok(data) => Ok(data),
+            Err(e) => Err(format!("获取行情失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 获取所有订阅的行情
+#[tauri::command]
+async fn ctp_get_all_market_data(
+    state: State<'_, AppState>,
+) -> Result<Vec<ctp::MarketData>, String> {
+    let mut client_guard = state.ctp_client.lock().await;
+    if let Some(ref mut client) = client_guard.as_mut() {
+        match client.get_all_market_data().await {
+            Ok(data) => Ok(data),
+            Err(e) => Err(format!("获取所有行情失败: {}", e))
+        }
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 设置风险控制参数
+#[tauri::command]
+async fn ctp_set_risk_params(
+    state: State<'_, AppState>,
+    params: ctp::RiskParams,
+) -> Result<dto::ActionResult, String> {
     let mut client_guard = state.ctp_client.lock().await;
     if let Some(ref mut client) = client_guard.as_mut() {
         match client.set_risk_params(params).await {
-            Ok(_) => Ok("风险参数设置成功".to_string()),
+            Ok(_) => Ok(dto::ActionResult::ok("风险参数设置成功")),
             Err(e) => Err(format!("设置风险参数失败: {}", e))
         }
     } else {
@@ -413,6 +2134,683 @@ async fn ctp_set_risk_params(
     }
 }
 
+// 读取风控预设配置和当前生效的预设
+#[tauri::command]
+async fn ctp_get_risk_presets(
+    state: State<'_, AppState>,
+) -> Result<(ctp::RiskPresetConfig, Option<String>), String> {
+    Ok((state.risk_presets.config(), state.risk_presets.active()))
+}
+
+// 更新风控预设和定时规则并保存到配置文件
+#[tauri::command]
+async fn ctp_set_risk_presets(state: State<'_, AppState>, config: ctp::RiskPresetConfig) -> Result<(), String> {
+    state.risk_presets.update_config(config.clone()).map_err(|e| format!("风控预设无效: {}", e))?;
+    config
+        .save(ctp::DEFAULT_RISK_PRESETS_FILE)
+        .map_err(|e| format!("保存风控预设失败: {}", e))
+}
+
+// 查看切换到预设后相对当前风险参数的变化
+#[tauri::command]
+async fn ctp_diff_risk_preset(state: State<'_, AppState>, name: String) -> Result<Vec<ctp::RiskParamChange>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let current = client_guard.as_ref().and_then(|c| c.risk_params());
+    state.risk_presets.diff(&name, current.as_ref()).map_err(|e| e.to_string())
+}
+
+// 手动切换风控预设，保持到下一个定时时段
+#[tauri::command]
+async fn ctp_activate_risk_preset(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<ctp::PresetSwitch, String> {
+    use tauri::Emitter;
+
+    let mut client_guard = state.ctp_client.lock().await;
+    let Some(client) = client_guard.as_mut() else {
+        return Err("请先连接并登录 CTP".to_string());
+    };
+    let switch = client
+        .apply_risk_preset(&state.risk_presets, &name, ctp::PresetSwitchSource::Manual)
+        .await
+        .map_err(|e| format!("切换风控预设失败: {}", e))?;
+    if let Err(e) = app.emit(RISK_PRESET_EVENT, switch.clone()) {
+        tracing::warn!("推送风控预设切换失败: {}", e);
+    }
+    Ok(switch)
+}
+
+// 设置行情链路追踪开关
+#[tauri::command]
+async fn ctp_set_pipeline_tracing(enabled: bool) -> Result<dto::ActionResult, String> {
+    ctp::PipelineTracer::global().set_enabled(enabled);
+    Ok(dto::ActionResult::ok(format!("行情链路追踪已{}", if enabled { "开启" } else { "关闭" })))
+}
+
+// 前端确认收到行情，用于统计前端阶段耗时
+#[tauri::command]
+async fn ctp_ack_tick_trace(trace: ctp::TickTrace) -> Result<(), String> {
+    ctp::PipelineTracer::global().record_ack(&trace);
+    Ok(())
+}
+
+// 获取行情链路各阶段耗时统计
+#[tauri::command]
+async fn ctp_get_pipeline_trace_stats(reset: Option<bool>) -> Result<ctp::PipelineTraceStats, String> {
+    let tracer = ctp::PipelineTracer::global();
+    let stats = tracer.get_stats();
+    if reset.unwrap_or(false) {
+        tracer.reset();
+    }
+    Ok(stats)
+}
+
+// 启动盘口热力图推送，采样结果通过 orderbook-heatmap 事件发送给前端
+#[tauri::command]
+async fn ctp_start_heatmap_stream(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<dto::ActionResult, String> {
+    use tauri::Emitter;
+
+    let heatmap = {
+        let client_guard = state.ctp_client.lock().await;
+        match *client_guard {
+            Some(ref client) => client.order_book_heatmap(),
+            None => return Err("请先连接并登录 CTP".to_string()),
+        }
+    };
+
+    if !heatmap.start_sampler() {
+        return Ok(dto::ActionResult::ok("盘口热力图推送已在运行"));
+    }
+
+    let mut receiver = heatmap.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(column) => {
+                    if let Err(e) = app.emit("orderbook-heatmap", &column) {
+                        tracing::warn!("推送盘口热力图失败: {}", e);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("盘口热力图推送滞后，丢弃 {} 列", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(dto::ActionResult::ok("盘口热力图推送已启动"))
+}
+
+// 获取合约的盘口热力图历史
+#[tauri::command]
+async fn ctp_get_heatmap_history(
+    state: State<'_, AppState>,
+    instrument_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<ctp::HeatmapColumn>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        Ok(client.order_book_heatmap().history(&instrument_id, limit.unwrap_or(usize::MAX)))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// 启动订单流指标推送，逐笔主动买卖、失衡度和大单通过 order-flow 事件发送给前端
+#[tauri::command]
+async fn ctp_start_order_flow_stream(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<dto::ActionResult, String> {
+    use tauri::Emitter;
+
+    let order_flow = {
+        let client_guard = state.ctp_client.lock().await;
+        match *client_guard {
+            Some(ref client) => client.order_flow(),
+            None => return Err("请先连接并登录 CTP".to_string()),
+        }
+    };
+
+    if !order_flow.start_stream() {
+        return Ok(dto::ActionResult::ok("订单流推送已在运行"));
+    }
+
+    let mut receiver = order_flow.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(flow) => {
+                    if let Err(e) = app.emit("order-flow", &flow) {
+                        tracing::warn!("推送订单流指标失败: {}", e);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("订单流推送滞后，丢弃 {} 条", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(dto::ActionResult::ok("订单流推送已启动"))
+}
+
+// 获取合约的订单流快照（当日主动买卖量与最近大单）
+#[tauri::command]
+async fn ctp_get_order_flow(
+    state: State<'_, AppState>,
+    instrument_id: String,
+) -> Result<Option<ctp::OrderFlowSnapshot>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        Ok(client.order_flow().snapshot(&instrument_id))
+    } else {
+        Ok(None)
+    }
+}
+
+// 获取各交易所市场概览（成交量、成交额、涨跌家数、涨跌幅榜），首次调用时启动定时统计
+#[tauri::command]
+async fn ctp_get_market_overview(state: State<'_, AppState>) -> Result<ctp::MarketOverviewSnapshot, String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        let overview = client.market_overview();
+        overview.start_refresh();
+        Ok(overview.snapshot())
+    } else {
+        Ok(ctp::MarketOverviewSnapshot::default())
+    }
+}
+
+// 获取各合约涨跌停价与贴板/封板状态
+#[tauri::command]
+async fn ctp_get_price_limits(
+    state: State<'_, AppState>,
+) -> Result<std::collections::BTreeMap<String, ctp::LimitStatus>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    match client_guard.as_ref() {
+        Some(client) => Ok(client.price_limits().all()),
+        None => Ok(Default::default()),
+    }
+}
+
+// 解释 CTP 错误码，返回原因说明和处理建议；已连接时附加当前经纪商的补充说明
+#[tauri::command]
+async fn explain_error(
+    state: State<'_, AppState>,
+    code: i32,
+    message: Option<String>,
+) -> Result<ctp::ErrorExplanation, String> {
+    let message = message.unwrap_or_default();
+    let client_guard = state.ctp_client.lock().await;
+    let explanation = match *client_guard {
+        Some(ref client) => client.error_explainer().explain(code, &message),
+        None => ctp::ErrorExplainer::default().explain(code, &message),
+    };
+    Ok(explanation)
+}
+
+// 交易绩效统计（胜率、盈亏比、持仓时长分布、MAE/MFE），可按策略、合约和平仓日期筛选
+#[tauri::command]
+async fn ctp_get_trade_analytics(
+    state: State<'_, AppState>,
+    query: Option<ctp::AnalyticsQuery>,
+) -> Result<ctp::TradeAnalyticsReport, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let round_trips = match *client_guard {
+        Some(ref client) => client.position_manager().round_trips(),
+        None => Vec::new(),
+    };
+    Ok(ctp::analyze_trades(&round_trips, &query.unwrap_or_default()))
+}
+
+// 按星期几和小时汇总回合交易盈亏热力图，可按策略、合约和平仓日期筛选；时区偏移默认北京时间
+#[tauri::command]
+async fn ctp_get_pnl_heatmap(
+    state: State<'_, AppState>,
+    query: Option<ctp::AnalyticsQuery>,
+    utc_offset_hours: Option<i32>,
+) -> Result<ctp::PnlHeatmapReport, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let round_trips = match *client_guard {
+        Some(ref client) => client.position_manager().round_trips(),
+        None => Vec::new(),
+    };
+    Ok(ctp::pnl_heatmap(
+        &round_trips,
+        &query.unwrap_or_default(),
+        utc_offset_hours.unwrap_or(ctp::DEFAULT_HEATMAP_UTC_OFFSET_HOURS),
+    ))
+}
+
+// 查询已完成的回合交易（按平仓时间顺序），供交易复盘和绩效界面使用
+#[tauri::command]
+async fn ctp_get_round_trips(
+    state: State<'_, AppState>,
+    query: Option<ctp::AnalyticsQuery>,
+) -> Result<Vec<ctp::RoundTrip>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let Some(ref client) = *client_guard else {
+        return Ok(Vec::new());
+    };
+    let query = query.unwrap_or_default();
+    Ok(client
+        .position_manager()
+        .round_trips()
+        .into_iter()
+        .filter(|trip| query.matches(trip))
+        .collect())
+}
+
+// 设置开平仓配对方式（先进先出 / 后进先出），只影响之后的平仓成交
+#[tauri::command]
+async fn ctp_set_pairing_method(
+    state: State<'_, AppState>,
+    method: ctp::PairingMethod,
+) -> Result<(), String> {
+    let client_guard = state.ctp_client.lock().await;
+    if let Some(ref client) = *client_guard {
+        client.position_manager().round_trip_book().set_method(method);
+        Ok(())
+    } else {
+        Err("请先连接并登录 CTP".to_string())
+    }
+}
+
+// 生成当日风险报告（ATR 近似 VaR、集中度、保证金压力），保存到报告目录
+#[tauri::command]
+async fn ctp_generate_risk_report(
+    state: State<'_, AppState>,
+    trading_day: Option<chrono::NaiveDate>,
+    archive_dir: Option<String>,
+) -> Result<ctp::DailyRiskReport, String> {
+    let trading_day = trading_day.unwrap_or_else(|| chrono::Local::now().date_naive());
+    generate_risk_report(&state.ctp_client, &state.webhooks, trading_day, archive_dir).await
+}
+
+// 查询持仓、资金和合约后在阻塞线程中生成并保存风险报告，手动命令与每日定时任务共用
+async fn generate_risk_report(
+    ctp_client: &Arc<Mutex<Option<ctp::CtpClient>>>,
+    webhooks: &ctp::WebhookDispatcher,
+    trading_day: chrono::NaiveDate,
+    archive_dir: Option<String>,
+) -> Result<ctp::DailyRiskReport, String> {
+    let (positions, account, instruments, settlement_prices) = {
+        let mut client_guard = ctp_client.lock().await;
+        let Some(client) = client_guard.as_mut() else {
+            return Err("请先连接并登录 CTP".to_string());
+        };
+        let positions = client.query_positions().await.map_err(|e| format!("查询持仓失败: {}", e))?;
+        let account = client.query_account().await.map_err(|e| format!("查询账户失败: {}", e))?;
+        let instruments = client.query_instruments().await.map_err(|e| format!("查询合约失败: {}", e))?;
+        let settlement_prices = client.settlement_prices().settlement_prices(trading_day);
+        (positions, account, instruments, settlement_prices)
+    };
+
+    let archive_dir = archive_dir.unwrap_or_else(|| ctp::DEFAULT_ARCHIVE_DIR.to_string());
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let generator = ctp::RiskReportGenerator::new(ctp::RiskReportConfig::default())
+            .with_settlement_prices(settlement_prices);
+        let store = ctp::TickCompactor::new(ctp::CompactionConfig::new(ctp::DEFAULT_RAW_DIR, archive_dir));
+
+        let mut held: Vec<String> = positions.iter().map(|p| p.instrument_id.clone()).collect();
+        held.sort();
+        held.dedup();
+        let bars = generator.load_daily_bars(&store, &held, trading_day)?;
+        let specs = instruments
+            .into_iter()
+            .filter(|i| held.contains(&i.instrument_id))
+            .map(|i| (i.instrument_id.clone(), i))
+            .collect();
+
+        let report = generator.generate(trading_day, &positions, Some(&account), &specs, &bars);
+        generator.save(&report)?;
+        Ok::<_, ctp::CtpError>(report)
+    })
+    .await
+    .map_err(|e| format!("风险报告任务异常: {}", e))?
+    .map_err(|e| format!("生成风险报告失败: {}", e))?;
+    webhooks.notify(ctp::WebhookPayload::daily_report(&report));
+    Ok(report)
+}
+
+// 查询已采集的昨结算、今结算价
+#[tauri::command]
+async fn ctp_get_settlement_prices(state: State<'_, AppState>) -> Result<Vec<ctp::SettlementPrice>, String> {
+    let client_guard = state.ctp_client.lock().await;
+    let client = client_guard.as_ref().ok_or_else(|| "请先连接并登录 CTP".to_string())?;
+    Ok(client.settlement_prices().all())
+}
+
+// 读取 Webhook 配置（含密钥，仅本机界面使用）
+#[tauri::command]
+async fn get_webhook_config(state: State<'_, AppState>) -> Result<ctp::WebhookConfig, String> {
+    Ok(state.webhooks.config())
+}
+
+// 更新 Webhook 配置并保存到配置文件
+#[tauri::command]
+async fn set_webhook_config(state: State<'_, AppState>, config: ctp::WebhookConfig) -> Result<(), String> {
+    state.webhooks.update_config(config.clone()).map_err(|e| format!("Webhook 配置无效: {}", e))?;
+    config
+        .save(ctp::DEFAULT_WEBHOOK_CONFIG_FILE)
+        .map_err(|e| format!("保存 Webhook 配置失败: {}", e))
+}
+
+// 最近的 Webhook 投递记录，按时间倒序
+#[tauri::command]
+async fn get_webhook_deliveries(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<ctp::WebhookDelivery>, String> {
+    Ok(state.webhooks.deliveries(limit.unwrap_or(50)))
+}
+
+// 读取群机器人通知配置（含密钥，仅本机界面使用）
+#[tauri::command]
+async fn get_notifier_config(state: State<'_, AppState>) -> Result<ctp::NotifierConfig, String> {
+    Ok(state.notifier.config())
+}
+
+// 更新群机器人通知配置并保存到配置文件
+#[tauri::command]
+async fn set_notifier_config(state: State<'_, AppState>, config: ctp::NotifierConfig) -> Result<(), String> {
+    state.notifier.update_config(config.clone()).map_err(|e| format!("通知渠道配置无效: {}", e))?;
+    config
+        .save(ctp::DEFAULT_NOTIFIER_CONFIG_FILE)
+        .map_err(|e| format!("保存通知渠道配置失败: {}", e))
+}
+
+// 向指定渠道发送测试消息
+#[tauri::command]
+async fn test_notification_channel(state: State<'_, AppState>, channel_id: String) -> Result<(), String> {
+    state
+        .notifier
+        .test_send(&channel_id)
+        .await
+        .map_err(|e| format!("测试发送失败: {}", e))
+}
+
+// 最近的通知发送记录，按时间倒序
+#[tauri::command]
+async fn get_notification_records(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<ctp::NotificationRecord>, String> {
+    Ok(state.notifier.records(limit.unwrap_or(50)))
+}
+
+// 保存工作区快照，生成新版本；expected_version 与当前版本不一致时拒绝，未指定配置档时使用 default
+#[tauri::command]
+async fn save_workspace(
+    state: State<'_, AppState>,
+    profile: Option<String>,
+    name: String,
+    snapshot: serde_json::Value,
+    expected_version: Option<u32>,
+) -> Result<ctp::WorkspaceSnapshot, String> {
+    let profile = profile.unwrap_or_else(|| ctp::DEFAULT_WORKSPACE_PROFILE.to_string());
+    state
+        .workspaces
+        .save(&profile, &name, snapshot, expected_version)
+        .map_err(|e| format!("保存工作区失败: {}", e))
+}
+
+// 读取工作区快照，未指定版本时读取最新版本，不存在时返回 null
+#[tauri::command]
+async fn load_workspace(
+    state: State<'_, AppState>,
+    profile: Option<String>,
+    name: String,
+    version: Option<u32>,
+) -> Result<Option<ctp::WorkspaceSnapshot>, String> {
+    let profile = profile.unwrap_or_else(|| ctp::DEFAULT_WORKSPACE_PROFILE.to_string());
+    state
+        .workspaces
+        .load(&profile, &name, version)
+        .map_err(|e| format!("读取工作区失败: {}", e))
+}
+
+// 列出配置档下的工作区快照
+#[tauri::command]
+async fn list_workspaces(
+    state: State<'_, AppState>,
+    profile: Option<String>,
+) -> Result<Vec<ctp::WorkspaceSummary>, String> {
+    let profile = profile.unwrap_or_else(|| ctp::DEFAULT_WORKSPACE_PROFILE.to_string());
+    state.workspaces.list(&profile).map_err(|e| format!("列出工作区失败: {}", e))
+}
+
+// 列出工作区快照保留的版本
+#[tauri::command]
+async fn list_workspace_versions(
+    state: State<'_, AppState>,
+    profile: Option<String>,
+    name: String,
+) -> Result<Vec<ctp::WorkspaceVersion>, String> {
+    let profile = profile.unwrap_or_else(|| ctp::DEFAULT_WORKSPACE_PROFILE.to_string());
+    state
+        .workspaces
+        .versions(&profile, &name)
+        .map_err(|e| format!("列出工作区版本失败: {}", e))
+}
+
+// 删除工作区快照的所有版本
+#[tauri::command]
+async fn delete_workspace(
+    state: State<'_, AppState>,
+    profile: Option<String>,
+    name: String,
+) -> Result<bool, String> {
+    let profile = profile.unwrap_or_else(|| ctp::DEFAULT_WORKSPACE_PROFILE.to_string());
+    state
+        .workspaces
+        .delete(&profile, &name)
+        .map_err(|e| format!("删除工作区失败: {}", e))
+}
+
+// 列出已有工作区快照的配置档
+#[tauri::command]
+async fn list_workspace_profiles(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    state.workspaces.profiles().map_err(|e| format!("列出配置档失败: {}", e))
+}
+
+// 读取备份配置
+#[tauri::command]
+async fn get_backup_config(state: State<'_, AppState>) -> Result<ctp::BackupConfig, String> {
+    Ok(state.backups.config())
+}
+
+// 更新备份配置（备份目录、间隔、保留数量）并保存到配置文件
+#[tauri::command]
+async fn set_backup_config(state: State<'_, AppState>, config: ctp::BackupConfig) -> Result<(), String> {
+    state.backups.update_config(config.clone()).map_err(|e| format!("备份配置无效: {}", e))?;
+    config
+        .save(ctp::DEFAULT_BACKUP_CONFIG_FILE)
+        .map_err(|e| format!("保存备份配置失败: {}", e))
+}
+
+// 立即备份
+#[tauri::command]
+async fn create_backup(state: State<'_, AppState>) -> Result<ctp::BackupInfo, String> {
+    let backups = state.backups.clone();
+    tauri::async_runtime::spawn_blocking(move || backups.create_backup())
+        .await
+        .map_err(|e| format!("备份任务异常: {}", e))?
+        .map_err(|e| format!("备份失败: {}", e))
+}
+
+// 列出备份目录中的备份包，按时间倒序
+#[tauri::command]
+async fn list_backups(state: State<'_, AppState>) -> Result<Vec<ctp::BackupInfo>, String> {
+    state.backups.list_backups().map_err(|e| format!("列出备份失败: {}", e))
+}
+
+// 校验并恢复备份，绝对路径的备份源写回原位置，其余相对工作目录；恢复前自动备份当前数据，
+// 该备份失败时中止恢复，除非显式 force；恢复的配置和数据库在重启后生效
+#[tauri::command]
+async fn restore_backup(
+    state: State<'_, AppState>,
+    path: String,
+    force: Option<bool>,
+) -> Result<ctp::RestoreReport, String> {
+    let backups = state.backups.clone();
+    let force = force.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || backups.restore_backup(&path, ".", force))
+        .await
+        .map_err(|e| format!("恢复任务异常: {}", e))?
+        .map_err(|e| format!("恢复备份失败: {}", e))
+}
+
+// 读取数据保留策略
+#[tauri::command]
+async fn get_retention_policy(state: State<'_, AppState>) -> Result<ctp::RetentionPolicy, String> {
+    Ok(state.retention.policy())
+}
+
+// 更新数据保留策略并保存到配置文件；日志通道的天数在重启后同步到日志轮转
+#[tauri::command]
+async fn set_retention_policy(state: State<'_, AppState>, policy: ctp::RetentionPolicy) -> Result<(), String> {
+    state.retention.update_policy(policy.clone()).map_err(|e| format!("数据保留策略无效: {}", e))?;
+    policy
+        .save(ctp::DEFAULT_RETENTION_CONFIG_FILE)
+        .map_err(|e| format!("保存数据保留策略失败: {}", e))
+}
+
+// 试运行：列出按当前策略将删除的数据，不做修改
+#[tauri::command]
+async fn preview_retention(state: State<'_, AppState>) -> Result<ctp::RetentionReport, String> {
+    let retention = state.retention.clone();
+    tauri::async_runtime::spawn_blocking(move || retention.preview(chrono::Utc::now()))
+        .await
+        .map_err(|e| format!("保留策略试运行异常: {}", e))
+}
+
+// 立即按策略清理过期数据
+#[tauri::command]
+async fn enforce_retention(state: State<'_, AppState>) -> Result<ctp::RetentionReport, String> {
+    let retention = state.retention.clone();
+    tauri::async_runtime::spawn_blocking(move || retention.enforce(chrono::Utc::now()))
+        .await
+        .map_err(|e| format!("数据保留清理异常: {}", e))
+}
+
+// 读取存储后端配置
+#[tauri::command]
+async fn get_storage_config() -> Result<ctp::StorageConfig, String> {
+    ctp::StorageConfig::load(ctp::DEFAULT_STORAGE_CONFIG_FILE).map_err(|e| format!("读取存储配置失败: {}", e))
+}
+
+// 保存存储后端配置，重启后生效；切换后端前先用 inspirai-cli migrate-storage 迁移已有数据
+#[tauri::command]
+async fn set_storage_config(config: ctp::StorageConfig) -> Result<(), String> {
+    config
+        .save(ctp::DEFAULT_STORAGE_CONFIG_FILE)
+        .map_err(|e| format!("保存存储配置失败: {}", e))
+}
+
+// 读取已保存的风险报告
+#[tauri::command]
+async fn ctp_get_risk_report(trading_day: chrono::NaiveDate) -> Result<Option<ctp::DailyRiskReport>, String> {
+    ctp::RiskReportGenerator::new(ctp::RiskReportConfig::default())
+        .load(trading_day)
+        .map_err(|e| format!("读取风险报告失败: {}", e))
+}
+
+// 导出本地归档行情（tick/K 线）为 CSV 或 parquet，进度通过 market-data-export-progress 和 task://progress 事件推送
+#[tauri::command]
+async fn export_market_data(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    request: ctp::MarketDataExportRequest,
+    archive_dir: Option<String>,
+) -> Result<ctp::ExportSummary, String> {
+    use tauri::Emitter;
+
+    let archive_dir = archive_dir.unwrap_or_else(|| ctp::DEFAULT_ARCHIVE_DIR.to_string());
+    let task = state.tasks.start("export_market_data", format!("导出行情到 {}", request.path.display()));
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = ctp::TickCompactor::new(ctp::CompactionConfig::new(ctp::DEFAULT_RAW_DIR, archive_dir));
+        let result = ctp::MarketDataExporter::new(&store)
+            .with_cancel(task.cancel_token())
+            .export(&request, |progress| {
+                task.progress_of(
+                    progress.completed,
+                    progress.total,
+                    Some(format!("{} {}", progress.instrument_id, progress.trading_day)),
+                );
+                if let Err(e) = app.emit("market-data-export-progress", progress) {
+                    tracing::warn!("推送导出进度失败: {}", e);
+                }
+            });
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| format!("导出任务异常: {}", e))?
+    .map_err(|e| format!("导出行情失败: {}", e))
+}
+
+// 手动压缩指定交易日的原始行情，完成后按保留策略清理过期数据，作为长任务上报进度并可取消
+#[tauri::command]
+async fn compact_market_data(
+    state: State<'_, AppState>,
+    trading_day: chrono::NaiveDate,
+    archive_dir: Option<String>,
+) -> Result<ctp::CompactionReport, String> {
+    let archive_dir = archive_dir.unwrap_or_else(|| ctp::DEFAULT_ARCHIVE_DIR.to_string());
+    let task = state.tasks.start("compact_market_data", format!("压缩 {} 行情", trading_day));
+    let retention = state.retention.clone();
+    let analytics = state.storage.as_ref().map(|s| s.market_data().clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut store = ctp::TickCompactor::new(ctp::CompactionConfig::new(ctp::DEFAULT_RAW_DIR, archive_dir))
+            .with_cancel(task.cancel_token())
+            .with_retention(retention);
+        if let Some(analytics) = analytics {
+            store = store.with_analytics(analytics);
+        }
+        let result = store.run_with_progress(trading_day, |done, total| task.progress_of(done, total, None));
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| format!("压缩任务异常: {}", e))?
+    .map_err(|e| format!("压缩行情失败: {}", e))
+}
+
+// 运行中和最近结束的长任务
+#[tauri::command]
+async fn list_tasks(state: State<'_, AppState>) -> Result<Vec<ctp::TaskInfo>, String> {
+    Ok(state.tasks.list())
+}
+
+// 请求取消长任务，任务在下一个处理单元前停止
+#[tauri::command]
+async fn cancel_task(state: State<'_, AppState>, task_id: String) -> Result<(), String> {
+    state.tasks.cancel(&task_id).map_err(|e| e.to_string())
+}
+
+// 由本地归档的相继合约 K 线合成连续合约序列，按请求选择复权方式
+#[tauri::command]
+async fn ctp_get_continuous_kline(
+    request: ctp::ContinuousKlineRequest,
+    archive_dir: Option<String>,
+) -> Result<ctp::ContinuousKline, String> {
+    let archive_dir = archive_dir.unwrap_or_else(|| ctp::DEFAULT_ARCHIVE_DIR.to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = ctp::TickCompactor::new(ctp::CompactionConfig::new(ctp::DEFAULT_RAW_DIR, archive_dir));
+        ctp::ContinuousKlineBuilder::new(&store).build(&request)
+    })
+    .await
+    .map_err(|e| format!("连续合约任务异常: {}", e))?
+    .map_err(|e| format!("生成连续合约K线失败: {}", e))
+}
+
 // 日志系统相关命令
 
 /// 查询日志
@@ -432,6 +2830,33 @@ async fn query_logs(
         .map_err(|e| format!("查询日志失败: {}", e))
 }
 
+/// 按查询语言查询日志，如 `level>=warn AND module:ctp AND last 2h`
+#[tauri::command]
+async fn query_logs_dsl(
+    query: String,
+) -> Result<logging::QueryResult, String> {
+    let query = logging::LogQuery::parse(&query)
+        .map_err(|e| format!("解析查询失败: {}", e))?;
+    query_logs(query).await
+}
+
+/// 按校验清单检查已轮转/归档日志是否缺失或被修改
+#[tauri::command]
+async fn verify_log_integrity(
+    range: Option<logging::TimeRange>,
+) -> Result<logging::IntegrityReport, String> {
+    // 校验运行中日志系统的输出目录，日志系统未初始化时才退回开发配置
+    let config = match logging::LoggingSystem::instance() {
+        Ok(system) => system.config().clone(),
+        Err(_) => logging::LogConfig::development(),
+    };
+    let query_engine = logging::LogQueryEngine::new(config)
+        .map_err(|e| format!("创建查询引擎失败: {}", e))?;
+    
+    query_engine.verify_integrity(range).await
+        .map_err(|e| format!("校验日志完整性失败: {}", e))
+}
+
 /// 获取日志系统指标
 #[tauri::command]
 async fn get_log_metrics() -> Result<logging::MetricsSnapshot, String> {
@@ -443,6 +2868,60 @@ async fn get_log_metrics() -> Result<logging::MetricsSnapshot, String> {
     Ok(snapshot)
 }
 
+/// 获取日志采样规则及丢弃统计
+#[tauri::command]
+async fn get_log_sampling() -> Result<Vec<logging::SamplingStats>, String> {
+    let system = logging::LoggingSystem::instance()
+        .map_err(|e| format!("获取日志系统失败: {}", e))?;
+    Ok(system.sampler().stats())
+}
+
+/// 运行时替换日志采样规则
+#[tauri::command]
+async fn set_log_sampling_rules(
+    rules: Vec<logging::SamplingRule>,
+) -> Result<(), String> {
+    let system = logging::LoggingSystem::instance()
+        .map_err(|e| format!("获取日志系统失败: {}", e))?;
+    system.sampler().set_rules(rules)
+        .map_err(|e| format!("设置采样规则失败: {}", e))
+}
+
+/// 接收前端上报的错误/警告，写入统一日志
+#[tauri::command]
+async fn log_frontend_event(event: logging::FrontendLogEvent) -> Result<(), String> {
+    event.validate().map_err(|e| format!("前端日志无效: {}", e))?;
+    let system = logging::LoggingSystem::instance()
+        .map_err(|e| format!("获取日志系统失败: {}", e))?;
+    system.ingest(event.into_entry())
+        .map_err(|e| format!("写入前端日志失败: {}", e))
+}
+
+/// 用样例事件测试日志路由规则
+///
+/// 给出 `rules` 时测试这组规则，否则使用当前日志系统的路由配置
+#[tauri::command]
+async fn test_log_routing(
+    sample: logging::RouteSample,
+    rules: Option<Vec<logging::RouteRule>>,
+) -> Result<logging::RouteDecision, String> {
+    let entry = sample.into_entry();
+    match rules {
+        Some(rules) => {
+            let mut config = logging::LogConfig::development(); // TODO: 从配置获取
+            config.routing_rules = rules;
+            let router = logging::LogRouter::new(&config)
+                .map_err(|e| format!("路由规则无效: {}", e))?;
+            Ok(router.explain(&entry))
+        }
+        None => {
+            let system = logging::LoggingSystem::instance()
+                .map_err(|e| format!("获取日志系统失败: {}", e))?;
+            Ok(system.router().explain(&entry))
+        }
+    }
+}
+
 /// 获取日志系统状态
 #[tauri::command]
 async fn get_log_system_status() -> Result<serde_json::Value, String> {
@@ -467,8 +2946,203 @@ async fn get_log_system_status() -> Result<serde_json::Value, String> {
     }
 }
 
+/// 应用整体健康状况：CTP 连接、事件通道积压、日志队列与磁盘、存储写入错误和后台任务存活
+#[tauri::command]
+async fn get_app_health(state: State<'_, AppState>) -> Result<health::AppHealth, String> {
+    Ok(collect_app_health(&state).await)
+}
+
+async fn collect_app_health(state: &AppState) -> health::AppHealth {
+    let mut inputs = health::HealthInputs::default();
+    {
+        let client_guard = state.ctp_client.lock().await;
+        if let Some(ref client) = *client_guard {
+            let config = client.get_config_info();
+            inputs.accounts.push(health::AccountSnapshot {
+                account_id: config.user_id,
+                broker_id: config.broker_id,
+                state: client.get_state(),
+                session: client.get_session_health(),
+            });
+            inputs.event_lag = Some(state.event_bridge.lag());
+        }
+    }
+
+    if let Ok(system) = logging::LoggingSystem::instance() {
+        if let Err(e) = system.refresh_disk_usage().await {
+            tracing::warn!("统计日志磁盘占用失败: {}", e);
+        }
+        let metrics = system.get_metrics();
+        let metrics = metrics.lock().await;
+        inputs.logging = Some(health::LoggingSnapshot {
+            queue_size: metrics.queue_size,
+            disk_usage_bytes: metrics.disk_usage_bytes,
+            dropped_total: metrics.logs_dropped_total,
+            error_count: metrics.error_count,
+        });
+    }
+    inputs.storage = health::storage_errors();
+    inputs.tasks = state.liveness.list();
+
+    let thresholds = state.health_thresholds.lock().unwrap().clone();
+    health::AppHealth::assess(inputs, &thresholds)
+}
+
+/// 采集一次日志与交易指标
+async fn collect_metrics(
+    ctp_client: &Arc<Mutex<Option<ctp::CtpClient>>>,
+    bridge: &ctp::EventBridge,
+) -> (Option<ctp::LoggingMetrics>, Option<ctp::TradingMetrics>) {
+    let trading = {
+        let client_guard = ctp_client.lock().await;
+        client_guard.as_ref().map(|client| {
+            let quality = client.get_connection_quality();
+            let lag = bridge.lag();
+            ctp::TradingMetrics {
+                state: client.get_state(),
+                degraded: client.is_degraded(),
+                subscribed_instruments: client.get_subscribed_instruments().len(),
+                connection_quality: quality.overall,
+                td_rtt_avg_ms: quality.td.rtt.avg_ms,
+                event_backlog: lag.backlog,
+                events_dispatched: lag.dispatched,
+            }
+        })
+    };
+
+    let logging = match logging::LoggingSystem::instance() {
+        Ok(system) => {
+            let metrics = system.get_metrics();
+            let snapshot = metrics.lock().await.snapshot();
+            Some(ctp::LoggingMetrics::from(&snapshot))
+        }
+        Err(_) => None,
+    };
+    (logging, trading)
+}
+
+/// 指标采集任务：按最短的订阅间隔采集并写入指标流，无订阅时不采集
+fn spawn_metrics_collector(
+    stream: ctp::MetricsStream,
+    ctp_client: Arc<Mutex<Option<ctp::CtpClient>>>,
+    bridge: ctp::EventBridge,
+    liveness: &health::TaskLiveness,
+) {
+    let beat = liveness.register("metrics_collector", Some(ctp::DEFAULT_METRICS_INTERVAL));
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = stream
+                .collect_interval()
+                .map_or(ctp::DEFAULT_METRICS_INTERVAL, |i| i.min(ctp::DEFAULT_METRICS_INTERVAL));
+            tokio::time::sleep(interval).await;
+            beat.beat();
+            if stream.collect_interval().is_none() {
+                continue;
+            }
+            let (logging, trading) = collect_metrics(&ctp_client, &bridge).await;
+            stream.publish(logging, trading);
+        }
+    });
+}
+
+/// 当前窗口订阅指标推送，返回当前完整指标帧；之后按间隔推送变化部分
+#[tauri::command]
+async fn subscribe_metrics(
+    window: tauri::Window,
+    state: State<'_, AppState>,
+    interval_ms: Option<u64>,
+) -> Result<ctp::MetricsFrame, String> {
+    use tauri::Emitter;
+
+    let interval = interval_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(ctp::DEFAULT_METRICS_INTERVAL);
+    let (logging, trading) = collect_metrics(&state.ctp_client, &state.event_bridge).await;
+    state.metrics_stream.publish(logging, trading);
+
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    Ok(state.metrics_stream.subscribe(window.label(), interval, move |frame| {
+        app.emit_to(label.as_str(), ctp::METRICS_EVENT, frame).is_ok()
+    }))
+}
+
+/// 当前窗口取消指标推送
+#[tauri::command]
+async fn unsubscribe_metrics(window: tauri::Window, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.metrics_stream.unsubscribe(window.label()))
+}
+
+/// 列出各窗口的指标订阅
+#[tauri::command]
+async fn get_metrics_subscriptions(state: State<'_, AppState>) -> Result<Vec<ctp::MetricsSubscription>, String> {
+    Ok(state.metrics_stream.subscriptions())
+}
+
+/// 调整健康检查阈值
+#[tauri::command]
+async fn set_health_thresholds(
+    state: State<'_, AppState>,
+    thresholds: health::HealthThresholds,
+) -> Result<(), String> {
+    thresholds.validate()?;
+    *state.health_thresholds.lock().unwrap() = thresholds;
+    Ok(())
+}
+
+/// 生成问题反馈支持包：脱敏的近期日志、去除密钥的配置、诊断事件、指标、健康状况和飞行记录
+#[tauri::command]
+async fn generate_support_bundle(
+    state: State<'_, AppState>,
+    options: Option<logging::SupportBundleOptions>,
+) -> Result<logging::SupportBundleSummary, String> {
+    let bundle_error = |e: logging::LogError| format!("生成支持包失败: {}", e);
+    let mut bundle = logging::SupportBundle::new(options.unwrap_or_default());
+
+    bundle.add_json("health.json", &collect_app_health(&state).await).map_err(bundle_error)?;
+    {
+        let client_guard = state.ctp_client.lock().await;
+        if let Some(ref client) = *client_guard {
+            bundle.add_json("config/ctp.json", &client.get_config_info()).map_err(bundle_error)?;
+            bundle.add_json("diagnostics.json", &client.diagnostics().recent(1000, None)).map_err(bundle_error)?;
+        }
+    }
+
+    let system = logging::LoggingSystem::instance().ok();
+    match &system {
+        Some(system) => {
+            let metrics = system.get_metrics();
+            let snapshot = metrics.lock().await.snapshot();
+            bundle.add_json("config/logging.json", system.config()).map_err(bundle_error)?;
+            bundle.add_json("metrics.json", &snapshot).map_err(bundle_error)?;
+            bundle.add_flight_recorder(system.flight_recorder().snapshot(), system.masker()).map_err(bundle_error)?;
+            bundle.collect_logs(system.config()).map_err(bundle_error)?;
+        }
+        None => {
+            let config = logging::LogConfig::development(); // TODO: 从配置获取
+            bundle.collect_logs(&config).map_err(bundle_error)?;
+        }
+    }
+
+    tauri::async_runtime::spawn_blocking(move || match &system {
+        Some(system) => bundle.write(system.masker()),
+        None => bundle.write(&logging::DataMasker::new()),
+    })
+    .await
+    .map_err(|e| format!("支持包任务异常: {}", e))?
+    .map_err(bundle_error)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 先缓冲启动阶段的日志，日志系统就绪后回放到文件
+    if let Err(e) = logging::install_startup_buffer(logging::DEFAULT_STARTUP_BUFFER_CAPACITY) {
+        eprintln!("{}", e);
+    }
+    
+    // 日志轮转按保留策略中各通道的天数清理
+    let retention = retention_manager();
+    
     // 初始化新的高级日志系统
     let rt = tokio::runtime::Runtime::new().expect("创建 tokio 运行时失败");
     rt.block_on(async {
@@ -478,57 +3152,281 @@ pub fn run() {
             .parse::<ctp::config::Environment>()
             .unwrap_or(ctp::config::Environment::SimNow);
             
-        if let Err(e) = logging::init_logging(env).await {
+        let result = match logging::LogConfig::for_environment(env) {
+            Ok(config) => logging::LoggingSystem::init(retention.policy().apply_to_log_config(config)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
             eprintln!("日志系统初始化失败: {}", e);
-            // 回退到简单的日志系统
-            tracing_subscriber::fmt::init();
+            // 回退到简单的日志系统，缓冲的日志输出到控制台
+            logging::fallback_to_console();
         } else {
             tracing::info!("高级日志系统初始化成功");
         }
     });
     
+    // 操作录制默认关闭，设置 CTP_RECORD_ACTIONS=1 时启动即开启
+    let action_recorder = ctp::ActionRecorder::default();
+    if std::env::var("CTP_RECORD_ACTIONS").is_ok_and(|v| v == "1") {
+        if let Err(e) = action_recorder.start() {
+            tracing::warn!("开启操作录制失败: {}", e);
+        }
+    }
+    
+    // 休市日参与交易日推算（夜盘归属、按交易日滚动的文件）
+    match ctp::bar_import::load_holidays(ctp::bar_import::DEFAULT_HOLIDAYS_FILE) {
+        Ok(count) => tracing::info!("已加载 {} 个休市日", count),
+        Err(e) => tracing::warn!("加载休市日配置失败: {}", e),
+    }
+    
+    let (tuning, hot_path) = runtime_tuning();
+    
+    // 事务和行情存储，备份时同一组连接生成数据库快照
+    let storage = storage();
+
     // 创建应用状态
     let app_state = AppState {
         ctp_client: Arc::new(Mutex::new(None)),
         market_data_service: Arc::new(Mutex::new(None)),
         event_receiver: Arc::new(Mutex::new(None)),
+        hotkeys: Arc::new(ctp::HotkeyController::new()),
+        event_bridge: ctp::EventBridge::new(),
+        action_recorder: action_recorder.clone(),
+        idempotency: ctp::IdempotencyStore::new(),
+        instance: instance_coordinator(),
+        dead_man: ctp::DeadManSwitch::new(),
+        tasks: ctp::TaskManager::new(),
+        liveness: health::TaskLiveness::new(),
+        health_thresholds: std::sync::Mutex::new(health::HealthThresholds::default()),
+        metrics_stream: ctp::MetricsStream::new(),
+        webhooks: webhook_dispatcher(),
+        notifier: notifier(),
+        workspaces: ctp::WorkspaceStore::new(ctp::DEFAULT_WORKSPACE_DIR),
+        backups: backup_manager(storage.clone()),
+        retention: retention.clone(),
+        risk_presets: risk_preset_manager(),
+        storage,
+        self_test: Arc::new(std::sync::Mutex::new(None)),
+        runtime_tuning: tuning,
+        hot_path,
+        ui_latency: std::sync::OnceLock::new(),
+        strategy_engine: Arc::new(std::sync::Mutex::new(strategy_engine())),
+        trailing_stops: trailing_stop_manager(),
+        switchboard: switchboard(),
     };
+    // 恢复的 ATR 模式跟踪止损用本地日线预热
+    {
+        let history = app_state.storage.as_ref().map(|s| s.market_data() as &dyn ctp::HistorySource);
+        let mut engine = app_state.strategy_engine.lock().unwrap();
+        if let Err(e) = app_state.trailing_stops.sync_atr_feed(&mut engine, history) {
+            tracing::warn!("跟踪止损日线订阅失败: {}", e);
+        }
+    }
+    
+    let handler = tauri::generate_handler![
+        greet,
+        ctp_init,
+        ctp_create_config,
+        ctp_connect,
+        ctp_login,
+        ctp_confirm_settlement,
+        ctp_subscribe,
+        ctp_unsubscribe,
+        ctp_get_status,
+        ctp_get_session_health,
+        ctp_get_connection_quality,
+        ctp_retry_trader_login,
+        ctp_get_diagnostics,
+        ctp_get_event_bus_lag,
+        ctp_run_self_test,
+        ctp_get_self_test_report,
+        ctp_clear_diagnostics,
+        ctp_disconnect,
+        ctp_place_order,
+        ctp_validate_order,
+        ctp_preview_order,
+        ctp_max_open_volume,
+        ctp_stress_test,
+        ctp_get_hedge_suggestions,
+        ctp_plan_rollovers,
+        ctp_execute_rollover,
+        ctp_cancel_rollover,
+        ctp_get_rollovers,
+        ctp_add_trailing_stop,
+        ctp_cancel_trailing_stop,
+        ctp_get_trailing_stops,
+        ctp_disable_trading,
+        ctp_enable_trading,
+        ctp_get_disabled_trading,
+        ctp_hotkey_execute,
+        ctp_hotkey_set_enabled,
+        ctp_hotkey_status,
+        ctp_get_timeline,
+        ctp_register_window,
+        ctp_unregister_window,
+        ctp_set_observer_mode,
+        ctp_get_observers,
+        ctp_get_rejection_breakers,
+        ctp_get_api_usage,
+        ctp_get_compliance_status,
+        ctp_get_scheduler_latency,
+        ctp_get_conversion_pool_stats,
+        ctp_get_wire_log,
+        ctp_get_wire_log_stats,
+        get_wire_log_config,
+        set_wire_log_config,
+        ctp_get_client_order,
+        ctp_acknowledge_rejection_breaker,
+        ctp_get_funds_anomalies,
+        ctp_get_funds_monitor_config,
+        ctp_set_funds_monitor_config,
+        ctp_cancel_order,
+        ctp_set_idempotency_retention,
+        ctp_get_instance_status,
+        ctp_handover_active,
+        ctp_query_account,
+        ctp_get_aggregated_equity,
+        ctp_query_positions,
+        ctp_query_orders,
+        ctp_query_trades,
+        ctp_query_instruments,
+        ctp_query_commission_rate,
+        ctp_query_margin_rate,
+        ctp_batch_subscribe,
+        ctp_get_market_data,
+        ctp_get_all_market_data,
+        ctp_set_risk_params,
+        ctp_get_risk_presets,
+        ctp_set_risk_presets,
+        ctp_diff_risk_preset,
+        ctp_activate_risk_preset,
+        ctp_set_pipeline_tracing,
+        ctp_ack_tick_trace,
+        ctp_get_pipeline_trace_stats,
+        ctp_start_heatmap_stream,
+        ctp_get_heatmap_history,
+        ctp_start_order_flow_stream,
+        ctp_get_order_flow,
+        ctp_get_market_overview,
+        ctp_get_price_limits,
+        explain_error,
+        ctp_arm_dead_man,
+        ctp_disarm_dead_man,
+        ctp_dead_man_heartbeat,
+        ctp_get_dead_man_status,
+        ctp_get_trade_analytics,
+        ctp_get_pnl_heatmap,
+        ctp_get_round_trips,
+        ctp_set_pairing_method,
+        ctp_generate_risk_report,
+        ctp_get_settlement_prices,
+        ctp_get_risk_report,
+        get_webhook_config,
+        set_webhook_config,
+        get_webhook_deliveries,
+        get_notifier_config,
+        set_notifier_config,
+        test_notification_channel,
+        get_notification_records,
+        save_workspace,
+        load_workspace,
+        list_workspaces,
+        list_workspace_versions,
+        delete_workspace,
+        list_workspace_profiles,
+        get_backup_config,
+        set_backup_config,
+        create_backup,
+        list_backups,
+        restore_backup,
+        get_retention_policy,
+        set_retention_policy,
+        preview_retention,
+        enforce_retention,
+        get_storage_config,
+        set_storage_config,
+        export_market_data,
+        compact_market_data,
+        list_tasks,
+        cancel_task,
+        ctp_get_continuous_kline,
+        query_logs,
+        query_logs_dsl,
+        verify_log_integrity,
+        get_log_metrics,
+        get_log_sampling,
+        set_log_sampling_rules,
+        test_log_routing,
+        log_frontend_event,
+        get_log_system_status,
+        get_app_health,
+        set_health_thresholds,
+        generate_support_bundle,
+        ctp_set_action_recording,
+        ctp_get_action_recording,
+        replay_actions,
+        subscribe_metrics,
+        unsubscribe_metrics,
+        get_metrics_subscriptions
+    ];
+    
+    let observer_bridge = app_state.event_bridge.clone();
     
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(app_state)
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            ctp_init,
-            ctp_create_config,
-            ctp_connect,
-            ctp_login,
-            ctp_confirm_settlement,
-            ctp_subscribe,
-            ctp_unsubscribe,
-            ctp_get_status,
-            ctp_disconnect,
-            ctp_place_order,
-            ctp_cancel_order,
-            ctp_query_account,
-            ctp_query_positions,
-            ctp_query_orders,
-            ctp_query_trades,
-            ctp_query_instruments,
-            ctp_query_commission_rate,
-            ctp_query_margin_rate,
-            ctp_batch_subscribe,
-            ctp_get_market_data,
-            ctp_get_all_market_data,
-            ctp_set_risk_params,
-            query_logs,
-            get_log_metrics,
-            get_log_system_status
-        ])
-        .setup(|_app| {
+        .invoke_handler(move |invoke| {
+            // 观察窗口只能调用白名单内的只读命令，其余命令不进入处理函数
+            if !OBSERVER_COMMANDS.contains(&invoke.message.command()) {
+                let window = invoke.message.webview_ref().window();
+                if let Err(e) = observer_bridge.ensure_can_trade(window.label()) {
+                    invoke.resolver.reject(e.to_string());
+                    return true;
+                }
+            }
+            // 录制开启时记录命令名和脱敏参数
+            if let tauri::ipc::InvokeBody::Json(args) = invoke.message.payload() {
+                action_recorder.record(invoke.message.command(), args);
+            }
+            handler(invoke)
+        })
+        .on_window_event(|window, event| {
+            // 关闭的窗口不再接收事件
+            if let tauri::WindowEvent::Destroyed = event {
+                let state = window.state::<AppState>();
+                state.event_bridge.unregister_window(window.label());
+                state.metrics_stream.unsubscribe(window.label());
+            }
+        })
+        .setup(|app| {
             // 应用启动时初始化 CTP 组件
             tracing::info!("启动 Inspirai Trader 应用");
             
+            let state = app.state::<AppState>();
+            if let Some(instance) = state.instance.clone() {
+                spawn_instance_heartbeat(app.handle().clone(), instance, state.ctp_client.clone(), &state.liveness);
+            }
+            spawn_dead_man_watchdog(app.handle().clone(), state.dead_man.clone(), state.webhooks.clone(), state.ctp_client.clone(), &state.liveness);
+            spawn_market_order_chaser(state.ctp_client.clone(), &state.liveness);
+            spawn_backup_scheduler(state.backups.clone(), &state.liveness);
+            spawn_risk_preset_scheduler(app.handle().clone(), state.risk_presets.clone(), state.ctp_client.clone(), &state.liveness);
+            spawn_risk_report_scheduler(state.webhooks.clone(), state.ctp_client.clone(), &state.liveness);
+            spawn_metrics_collector(state.metrics_stream.clone(), state.ctp_client.clone(), state.event_bridge.clone(), &state.liveness);
+            if let Some(interval) = state.runtime_tuning.probe_interval() {
+                let probe = ctp::LatencyProbe::spawn(tauri::async_runtime::handle().inner(), "ui", interval);
+                let _ = state.ui_latency.set(probe);
+            }
+            if std::env::var("CTP_SELF_TEST").is_ok_and(|v| v == "1") {
+                spawn_startup_self_test(app.handle().clone(), state.self_test.clone());
+            }
+            let handle = app.handle().clone();
+            state.tasks.set_listener(move |info| {
+                use tauri::Emitter;
+                if let Err(e) = handle.emit(ctp::TASK_PROGRESS_EVENT, info) {
+                    tracing::warn!("推送任务进度失败: {}", e);
+                }
+            });
+            
             // 记录应用启动日志
             crate::log_performance!("app_startup_time", 0.0, "ms");
             
@@ -1,6 +1,7 @@
 import React, { Component, ErrorInfo, ReactNode } from 'react';
 import { Result, Button, Typography, Card } from 'antd';
 import { ReloadOutlined, BugOutlined } from '@ant-design/icons';
+import { getCtpService } from '@/services/ctpService';
 
 const { Paragraph, Text } = Typography;
 
@@ -69,6 +70,17 @@ export class ErrorBoundary extends Component<Props, State> {
     // 记录错误到本地存储（用于调试）
     this.logErrorToStorage(error, errorInfo);
 
+    // 上报到统一日志
+    getCtpService()
+      .logFrontendEvent({
+        level: 'Error',
+        message: error.message,
+        component: 'ErrorBoundary',
+        stack: [error.stack, errorInfo.componentStack].filter(Boolean).join('\n'),
+        fields: { error_id: this.state.errorId, url: window.location.href },
+      })
+      .catch(() => {});
+
     // 在开发环境下显示详细错误信息
     if (process.env.NODE_ENV === 'development') {
       console.group('🐛 错误边界详细信息');
@@ -1,4 +1,4 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { Button, Badge, Tooltip, Space, Typography } from 'antd';
 import { 
   LinkOutlined, 
@@ -7,13 +7,37 @@ import {
   ReloadOutlined 
 } from '@ant-design/icons';
 import { useConnectionStore } from '@/stores/connectionStore';
+import { getCtpService } from '@/services/ctpService';
+import { ConnectionQualityReport, LinkQuality, QualityLevel } from '@/types/ctp';
 import ConnectionDialog from '@/components/connection/ConnectionDialog';
 import './ConnectionStatus.css';
 
 const { Text } = Typography;
 
+const QUALITY_POLL_INTERVAL = 5000;
+
+const qualityColor: Record<QualityLevel, string> = {
+  Good: '#52c41a',
+  Degraded: '#faad14',
+  Poor: '#ff4d4f',
+  Offline: '#8c8c8c',
+};
+
+const qualityText: Record<QualityLevel, string> = {
+  Good: '良好',
+  Degraded: '一般',
+  Poor: '较差',
+  Offline: '离线',
+};
+
+const describeLink = (name: string, level: QualityLevel, link: LinkQuality) =>
+  `${name}: ${qualityText[level]}，往返 ${link.rtt.avg_ms.toFixed(0)}ms，` +
+  `重连 ${link.reconnect_count} 次，心跳警告 ${link.heartbeat_warnings} 次，` +
+  `最长静默 ${(link.longest_silence_ms / 1000).toFixed(1)}s`;
+
 export const ConnectionStatus: React.FC = () => {
   const [showDialog, setShowDialog] = useState(false);
+  const [quality, setQuality] = useState<ConnectionQualityReport | null>(null);
   
   const { 
     connectionState, 
@@ -24,6 +48,23 @@ export const ConnectionStatus: React.FC = () => {
     connect
   } = useConnectionStore();
 
+  useEffect(() => {
+    if (!isConnected) {
+      setQuality(null);
+      return;
+    }
+
+    const service = getCtpService();
+    const poll = () => {
+      service.getConnectionQuality()
+        .then(setQuality)
+        .catch((error) => console.error('Failed to get connection quality:', error));
+    };
+    poll();
+    const timer = setInterval(poll, QUALITY_POLL_INTERVAL);
+    return () => clearInterval(timer);
+  }, [isConnected]);
+
   const getStatusColor = () => {
     switch (connectionState) {
       case 'LoggedIn': return 'success';
@@ -59,6 +100,22 @@ export const ConnectionStatus: React.FC = () => {
         <Space>
           <Badge status={getStatusColor() as any} />
           <Text className="status-text">{getStatusText()}</Text>
+
+          {quality && (
+            <Tooltip
+              title={
+                <div>
+                  <div>{describeLink('行情', quality.md_level, quality.md)}</div>
+                  <div>{describeLink('交易', quality.td_level, quality.td)}</div>
+                </div>
+              }
+            >
+              <Badge
+                color={qualityColor[quality.overall]}
+                text={<Text className="quality-text">连接质量: {qualityText[quality.overall]}</Text>}
+              />
+            </Tooltip>
+          )}
           
           {isLoggedIn && userId && (
             <Space>
@@ -1,4 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { 
   MarketData, 
   OrderInput, 
@@ -11,11 +13,94 @@ import {
   CommissionRate,
   MarginRate,
   RiskParams,
+  RiskPresetConfig,
+  RiskParamChange,
+  PresetSwitch,
   LoginCredentials,
   CtpConfig,
-  MarketDataSubscription
+  MarketDataSubscription,
+  ActionResult,
+  ConnectResult,
+  LoginResult,
+  StatusInfo,
+  SubscribeResult,
+  CancelOrderResult,
+  ConnectionQualityReport,
+  MarketDataExportRequest,
+  ExportProgress,
+  ExportSummary,
+  HeatmapColumn,
+  OrderFlowTick,
+  OrderFlowSnapshot,
+  DailyRiskReport,
+  OrderValidationResult,
+  HotkeyAction,
+  HotkeyOutcome,
+  HotkeyStatus,
+  TimelineQuery,
+  TimelinePage,
+  SourceBreakerStatus,
+  WindowSubscription,
+  BridgeSnapshot,
+  ObserverModeChange,
+  BridgeEvent,
+  ActionRecordingStatus,
+  ReplayReport,
+  MarketOverviewSnapshot,
+  InstanceStatus,
+  AnalyticsQuery,
+  TradeAnalyticsReport,
+  PnlHeatmapReport,
+  RoundTrip,
+  PairingMethod,
+  ErrorExplanation,
+  DeadManStatus,
+  DeadManReport,
+  LimitStatus,
+  MaxOpenVolume,
+  OrderPreview,
+  StressTestScenario,
+  StressTestResult,
+  HedgeConfig,
+  HedgeReport,
+  RolloverConfig,
+  RolloverReport,
+  RolloverPlan,
+  RolloverExecution,
+  ContinuousKlineRequest,
+  ContinuousKline,
+  CompactionReport,
+  TaskInfo,
+  FrontendLogEvent,
+  AppHealth,
+  HealthThresholds,
+  SupportBundleOptions,
+  SupportBundleSummary,
+  MetricsFrame,
+  MetricsSubscription,
+  WebhookConfig,
+  WebhookDelivery,
+  NotifierConfig,
+  NotificationRecord,
+  WorkspaceSnapshot,
+  WorkspaceSummary,
+  WorkspaceVersion,
+  BackupConfig,
+  BackupInfo,
+  RestoreReport,
+  RetentionPolicy,
+  RetentionReport,
+  StorageConfig,
+  WireLogConfig,
+  WireLogStats,
+  WireRecord,
+  FundsAnomaly,
+  FundsMonitorConfig
 } from '@/types/ctp';
 
+// 每次加载页面生成的前端会话 ID，随前端日志上报
+export const FRONTEND_SESSION_ID = crypto.randomUUID();
+
 /**
  * CTP Trading Service
  * Provides comprehensive interface to CTP trading system
@@ -30,36 +115,40 @@ export class CtpService {
     return invoke('ctp_create_config');
   }
 
-  async connect(config: CtpConfig): Promise<string> {
+  async connect(config: CtpConfig): Promise<ConnectResult> {
     return invoke('ctp_connect', { config });
   }
 
-  async login(credentials: LoginCredentials): Promise<string> {
+  async login(credentials: LoginCredentials): Promise<LoginResult> {
     return invoke('ctp_login', { credentials });
   }
 
-  async confirmSettlement(): Promise<string> {
+  async confirmSettlement(): Promise<ActionResult> {
     return invoke('ctp_confirm_settlement');
   }
 
-  async getStatus(): Promise<string> {
+  async getStatus(): Promise<StatusInfo> {
     return invoke('ctp_get_status');
   }
 
-  async disconnect(): Promise<string> {
+  async getConnectionQuality(): Promise<ConnectionQualityReport> {
+    return invoke('ctp_get_connection_quality');
+  }
+
+  async disconnect(): Promise<ActionResult> {
     return invoke('ctp_disconnect');
   }
 
   // Market Data
-  async subscribe(instrumentIds: string[]): Promise<string> {
+  async subscribe(instrumentIds: string[]): Promise<SubscribeResult> {
     return invoke('ctp_subscribe', { instrumentIds });
   }
 
-  async unsubscribe(instrumentIds: string[]): Promise<string> {
+  async unsubscribe(instrumentIds: string[]): Promise<SubscribeResult> {
     return invoke('ctp_unsubscribe', { instrumentIds });
   }
 
-  async batchSubscribe(subscriptions: MarketDataSubscription[]): Promise<string> {
+  async batchSubscribe(subscriptions: MarketDataSubscription[]): Promise<SubscribeResult> {
     return invoke('ctp_batch_subscribe', { subscriptions });
   }
 
@@ -72,12 +161,168 @@ export class CtpService {
   }
 
   // Trading Operations
-  async placeOrder(order: OrderInput): Promise<OrderRef> {
-    return invoke('ctp_place_order', { order });
+  // 交易命令携带幂等键，IPC 超时重试时传入同一个键，后端返回首次执行结果
+  // 关联 ID 写入该操作产生的后端日志和订单/成交回报的 tags.correlation_id
+  async placeOrder(
+    order: OrderInput,
+    idempotencyKey: string = crypto.randomUUID(),
+    correlationId: string = crypto.randomUUID()
+  ): Promise<OrderRef> {
+    return invoke('ctp_place_order', { order, idempotencyKey, correlationId });
+  }
+
+  async validateOrder(order: OrderInput): Promise<OrderValidationResult> {
+    return invoke('ctp_validate_order', { order });
+  }
+
+  // 下单面板每次修改时重新计算，费率走后端缓存
+  async previewOrder(order: OrderInput): Promise<OrderPreview> {
+    return invoke('ctp_preview_order', { order });
+  }
+
+  async maxOpenVolume(
+    instrument: string,
+    direction: 'Buy' | 'Sell',
+    price?: number,
+    utilizationCap?: number
+  ): Promise<MaxOpenVolume> {
+    return invoke('ctp_max_open_volume', { instrument, direction, price, utilizationCap });
+  }
+
+  async stressTest(scenario: StressTestScenario): Promise<StressTestResult> {
+    return invoke('ctp_stress_test', { scenario });
+  }
+
+  // 未传入配置时使用 config/hedge_caps.toml
+  async getHedgeSuggestions(config?: HedgeConfig): Promise<HedgeReport> {
+    return invoke('ctp_get_hedge_suggestions', { config });
+  }
+
+  // 未传入配置时使用 config/rollover.toml
+  async planRollovers(config?: RolloverConfig): Promise<RolloverReport> {
+    return invoke('ctp_plan_rollovers', { config });
+  }
+
+  // 进度通过 RolloverUpdate 事件推送
+  async executeRollover(plan: RolloverPlan): Promise<RolloverExecution> {
+    return invoke('ctp_execute_rollover', { plan });
+  }
+
+  async cancelRollover(rolloverId: string): Promise<RolloverExecution> {
+    return invoke('ctp_cancel_rollover', { rolloverId });
+  }
+
+  async getRollovers(limit?: number): Promise<RolloverExecution[]> {
+    return invoke('ctp_get_rollovers', { limit });
+  }
+
+  async executeHotkey(
+    action: HotkeyAction,
+    idempotencyKey: string = crypto.randomUUID(),
+    correlationId: string = crypto.randomUUID()
+  ): Promise<HotkeyOutcome> {
+    return invoke('ctp_hotkey_execute', { action, idempotencyKey, correlationId });
+  }
+
+  async setHotkeysEnabled(enabled: boolean): Promise<HotkeyStatus> {
+    return invoke('ctp_hotkey_set_enabled', { enabled });
+  }
+
+  async getHotkeyStatus(): Promise<HotkeyStatus> {
+    return invoke('ctp_hotkey_status');
+  }
+
+  async getTimeline(query?: TimelineQuery): Promise<TimelinePage> {
+    return invoke('ctp_get_timeline', { query });
+  }
+
+  async getRejectionBreakers(): Promise<SourceBreakerStatus[]> {
+    return invoke('ctp_get_rejection_breakers');
+  }
+
+  async acknowledgeRejectionBreaker(source: string): Promise<boolean> {
+    return invoke('ctp_acknowledge_rejection_breaker', { source });
+  }
+
+  async getFundsAnomalies(): Promise<FundsAnomaly[]> {
+    return invoke('ctp_get_funds_anomalies');
+  }
+
+  async getFundsMonitorConfig(): Promise<FundsMonitorConfig> {
+    return invoke('ctp_get_funds_monitor_config');
+  }
+
+  async setFundsMonitorConfig(config: FundsMonitorConfig): Promise<void> {
+    return invoke('ctp_set_funds_monitor_config', { config });
+  }
+
+  async getInstanceStatus(): Promise<InstanceStatus | null> {
+    return invoke('ctp_get_instance_status');
+  }
+
+  // 移交主角色后本实例交易会话断开，目标实例收到 instance-role-changed 后重新连接登录
+  async handoverActive(target?: string): Promise<InstanceStatus> {
+    return invoke('ctp_handover_active', { target });
+  }
+
+  async onInstanceRoleChanged(callback: (status: InstanceStatus) => void): Promise<UnlistenFn> {
+    return listen<InstanceStatus>('instance-role-changed', (event) => callback(event.payload));
+  }
+
+  async armDeadMan(timeoutSecs: number, flatten: boolean, source = 'main'): Promise<DeadManStatus> {
+    return invoke('ctp_arm_dead_man', { timeoutSecs, flatten, source });
+  }
+
+  async disarmDeadMan(): Promise<DeadManStatus> {
+    return invoke('ctp_disarm_dead_man');
+  }
+
+  async deadManHeartbeat(source = 'main'): Promise<DeadManStatus> {
+    return invoke('ctp_dead_man_heartbeat', { source });
+  }
+
+  async getDeadManStatus(): Promise<DeadManStatus> {
+    return invoke('ctp_get_dead_man_status');
+  }
+
+  /**
+   * 按固定间隔发送死人开关心跳，返回停止函数
+   */
+  startDeadManHeartbeat(intervalMs: number, source = 'main'): () => void {
+    const timer = setInterval(() => {
+      this.deadManHeartbeat(source).catch((error) => console.warn('死人开关心跳失败', error));
+    }, intervalMs);
+    return () => clearInterval(timer);
+  }
+
+  async onDeadManTriggered(callback: (report: DeadManReport) => void): Promise<UnlistenFn> {
+    return listen<DeadManReport>('dead-man-triggered', (event) => callback(event.payload));
+  }
+
+  async setActionRecording(enabled: boolean): Promise<ActionRecordingStatus> {
+    return invoke('ctp_set_action_recording', { enabled });
+  }
+
+  async getActionRecording(): Promise<ActionRecordingStatus> {
+    return invoke('ctp_get_action_recording');
+  }
+
+  // 开发用：在模拟环境中回放录制文件
+  async replayActions(file: string, respectTiming = false): Promise<ReplayReport> {
+    return invoke('replay_actions', { file, respectTiming });
   }
 
-  async cancelOrder(orderRef: string, instrumentId: string): Promise<string> {
-    return invoke('ctp_cancel_order', { orderRef, instrumentId });
+  async cancelOrder(
+    orderRef: string,
+    instrumentId: string,
+    idempotencyKey: string = crypto.randomUUID(),
+    correlationId: string = crypto.randomUUID()
+  ): Promise<CancelOrderResult> {
+    return invoke('ctp_cancel_order', { orderRef, instrumentId, idempotencyKey, correlationId });
+  }
+
+  async setIdempotencyRetention(seconds: number): Promise<ActionResult> {
+    return invoke('ctp_set_idempotency_retention', { seconds });
   }
 
   // Query Operations
@@ -110,9 +355,350 @@ export class CtpService {
   }
 
   // Risk Management
-  async setRiskParams(params: RiskParams): Promise<string> {
+  async setRiskParams(params: RiskParams): Promise<ActionResult> {
     return invoke('ctp_set_risk_params', { params });
   }
+
+  // 返回预设配置和当前生效的预设名
+  async getRiskPresets(): Promise<[RiskPresetConfig, string | null]> {
+    return invoke('ctp_get_risk_presets');
+  }
+
+  async setRiskPresets(config: RiskPresetConfig): Promise<void> {
+    return invoke('ctp_set_risk_presets', { config });
+  }
+
+  async diffRiskPreset(name: string): Promise<RiskParamChange[]> {
+    return invoke('ctp_diff_risk_preset', { name });
+  }
+
+  async activateRiskPreset(name: string): Promise<PresetSwitch> {
+    return invoke('ctp_activate_risk_preset', { name });
+  }
+
+  /** 风控预设切换（手动或定时） */
+  async onRiskPresetSwitched(callback: (change: PresetSwitch) => void): Promise<UnlistenFn> {
+    return listen<PresetSwitch>('risk-preset-switched', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  async generateRiskReport(tradingDay?: string, archiveDir?: string): Promise<DailyRiskReport> {
+    return invoke('ctp_generate_risk_report', { tradingDay, archiveDir });
+  }
+
+  async getRiskReport(tradingDay: string): Promise<DailyRiskReport | null> {
+    return invoke('ctp_get_risk_report', { tradingDay });
+  }
+
+  // 外部 Webhook：断线、登录失败、熔断、死人开关和日终报告时推送
+  async getWebhookConfig(): Promise<WebhookConfig> {
+    return invoke('get_webhook_config');
+  }
+
+  async setWebhookConfig(config: WebhookConfig): Promise<void> {
+    return invoke('set_webhook_config', { config });
+  }
+
+  async getWebhookDeliveries(limit?: number): Promise<WebhookDelivery[]> {
+    return invoke('get_webhook_deliveries', { limit });
+  }
+
+  // 群机器人通知：钉钉、飞书、Telegram，按渠道限速
+  async getNotifierConfig(): Promise<NotifierConfig> {
+    return invoke('get_notifier_config');
+  }
+
+  async setNotifierConfig(config: NotifierConfig): Promise<void> {
+    return invoke('set_notifier_config', { config });
+  }
+
+  async testNotificationChannel(channelId: string): Promise<void> {
+    return invoke('test_notification_channel', { channelId });
+  }
+
+  async getNotificationRecords(limit?: number): Promise<NotificationRecord[]> {
+    return invoke('get_notification_records', { limit });
+  }
+
+  // 工作区快照：替代 localStorage 保存布局、自选和图表，未指定 profile 时使用 default
+  /**
+   * 保存为新版本。传入 expectedVersion（首次保存为 0）时，
+   * 若其他窗口已保存过更新的版本则拒绝写入，需重新加载后再保存
+   */
+  async saveWorkspace<T>(
+    name: string,
+    snapshot: T,
+    options: { profile?: string; expectedVersion?: number } = {}
+  ): Promise<WorkspaceSnapshot<T>> {
+    return invoke('save_workspace', {
+      profile: options.profile,
+      name,
+      snapshot,
+      expectedVersion: options.expectedVersion
+    });
+  }
+
+  /** 读取指定版本，未指定时为最新版本；不存在时返回 null */
+  async loadWorkspace<T>(name: string, profile?: string, version?: number): Promise<WorkspaceSnapshot<T> | null> {
+    return invoke('load_workspace', { profile, name, version });
+  }
+
+  async listWorkspaces(profile?: string): Promise<WorkspaceSummary[]> {
+    return invoke('list_workspaces', { profile });
+  }
+
+  async listWorkspaceVersions(name: string, profile?: string): Promise<WorkspaceVersion[]> {
+    return invoke('list_workspace_versions', { profile, name });
+  }
+
+  async deleteWorkspace(name: string, profile?: string): Promise<boolean> {
+    return invoke('delete_workspace', { profile, name });
+  }
+
+  async listWorkspaceProfiles(): Promise<string[]> {
+    return invoke('list_workspace_profiles');
+  }
+
+  // 本地备份：按配置的间隔自动备份，也可手动备份或从备份包恢复
+  async getBackupConfig(): Promise<BackupConfig> {
+    return invoke('get_backup_config');
+  }
+
+  async setBackupConfig(config: BackupConfig): Promise<void> {
+    return invoke('set_backup_config', { config });
+  }
+
+  async createBackup(): Promise<BackupInfo> {
+    return invoke('create_backup');
+  }
+
+  async listBackups(): Promise<BackupInfo[]> {
+    return invoke('list_backups');
+  }
+
+  /** 校验通过才会恢复，恢复前自动备份当前数据，该备份失败时除非 force 否则中止；恢复的配置需重启应用后生效 */
+  async restoreBackup(path: string, force = false): Promise<RestoreReport> {
+    return invoke('restore_backup', { path, force });
+  }
+
+  // 数据保留策略：试运行列出将删除的数据，清理也随行情压缩任务执行
+  async getRetentionPolicy(): Promise<RetentionPolicy> {
+    return invoke('get_retention_policy');
+  }
+
+  async setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+    return invoke('set_retention_policy', { policy });
+  }
+
+  async previewRetention(): Promise<RetentionReport> {
+    return invoke('preview_retention');
+  }
+
+  async enforceRetention(): Promise<RetentionReport> {
+    return invoke('enforce_retention');
+  }
+
+  // 存储后端选择，切换前先用 inspirai-cli migrate-storage 迁移已有数据
+  async getStorageConfig(): Promise<StorageConfig> {
+    return invoke('get_storage_config');
+  }
+
+  async setStorageConfig(config: StorageConfig): Promise<void> {
+    return invoke('set_storage_config', { config });
+  }
+
+  // CTP 报文日志，用于核对报单争议
+  async getWireLog(limit?: number): Promise<WireRecord[]> {
+    return invoke('ctp_get_wire_log', { limit: limit ?? null });
+  }
+
+  async getWireLogStats(): Promise<WireLogStats | null> {
+    return invoke('ctp_get_wire_log_stats');
+  }
+
+  async getWireLogConfig(): Promise<WireLogConfig> {
+    return invoke('get_wire_log_config');
+  }
+
+  async setWireLogConfig(config: WireLogConfig): Promise<void> {
+    return invoke('set_wire_log_config', { config });
+  }
+
+  // Multi-window Event Bridge
+  /**
+   * 注册当前窗口的事件订阅，返回最新快照用于初始化，
+   * 之后匹配订阅的事件通过 ctp-event 推送到本窗口
+   */
+  async registerWindow(
+    subscription: WindowSubscription,
+    onEvent: (event: BridgeEvent) => void
+  ): Promise<{ snapshot: BridgeSnapshot; unlisten: UnlistenFn }> {
+    const unlistenEvents = await getCurrentWebviewWindow().listen<BridgeEvent>('ctp-event', (event) => {
+      onEvent(event.payload);
+    });
+    const snapshot = await invoke<BridgeSnapshot>('ctp_register_window', { subscription });
+    const unlisten = () => {
+      unlistenEvents();
+      invoke('ctp_unregister_window').catch(() => undefined);
+    };
+    return { snapshot, unlisten };
+  }
+
+  /** 设置指定窗口的观察模式；观察窗口调用时被拒绝 */
+  async setObserverMode(label: string, enabled: boolean): Promise<boolean> {
+    return invoke('ctp_set_observer_mode', { label, enabled });
+  }
+
+  async getObservers(): Promise<string[]> {
+    return invoke('ctp_get_observers');
+  }
+
+  /** 当前窗口观察模式变化，用于切换只读横幅 */
+  async onObserverModeChanged(callback: (change: ObserverModeChange) => void): Promise<UnlistenFn> {
+    return getCurrentWebviewWindow().listen<ObserverModeChange>('observer-mode-changed', (event) => {
+      callback(event.payload);
+    });
+  }
+
+  // Order Book Heatmap
+  async startHeatmapStream(
+    onColumn: (column: HeatmapColumn) => void
+  ): Promise<UnlistenFn> {
+    const unlisten = await listen<HeatmapColumn>('orderbook-heatmap', (event) => {
+      onColumn(event.payload);
+    });
+    await invoke('ctp_start_heatmap_stream');
+    return unlisten;
+  }
+
+  async getHeatmapHistory(instrumentId: string, limit?: number): Promise<HeatmapColumn[]> {
+    return invoke('ctp_get_heatmap_history', { instrumentId, limit });
+  }
+
+  // Order Flow
+  async startOrderFlowStream(
+    onFlow: (flow: OrderFlowTick) => void
+  ): Promise<UnlistenFn> {
+    const unlisten = await listen<OrderFlowTick>('order-flow', (event) => {
+      onFlow(event.payload);
+    });
+    await invoke('ctp_start_order_flow_stream');
+    return unlisten;
+  }
+
+  async getOrderFlow(instrumentId: string): Promise<OrderFlowSnapshot | null> {
+    return invoke('ctp_get_order_flow', { instrumentId });
+  }
+
+  async getMarketOverview(): Promise<MarketOverviewSnapshot> {
+    return invoke('ctp_get_market_overview');
+  }
+
+  async getPriceLimits(): Promise<Record<string, LimitStatus>> {
+    return invoke('ctp_get_price_limits');
+  }
+
+  async explainError(code: number, message?: string): Promise<ErrorExplanation> {
+    return invoke('explain_error', { code, message });
+  }
+
+  async getTradeAnalytics(query?: AnalyticsQuery): Promise<TradeAnalyticsReport> {
+    return invoke('ctp_get_trade_analytics', { query });
+  }
+
+  async getPnlHeatmap(query?: AnalyticsQuery, utcOffsetHours?: number): Promise<PnlHeatmapReport> {
+    return invoke('ctp_get_pnl_heatmap', { query, utcOffsetHours });
+  }
+
+  async getRoundTrips(query?: AnalyticsQuery): Promise<RoundTrip[]> {
+    return invoke('ctp_get_round_trips', { query });
+  }
+
+  async setPairingMethod(method: PairingMethod): Promise<void> {
+    return invoke('ctp_set_pairing_method', { method });
+  }
+
+  // Research Data Export
+  async exportMarketData(
+    request: MarketDataExportRequest,
+    onProgress?: (progress: ExportProgress) => void,
+    archiveDir?: string
+  ): Promise<ExportSummary> {
+    let unlisten: UnlistenFn | undefined;
+    if (onProgress) {
+      unlisten = await listen<ExportProgress>('market-data-export-progress', (event) => {
+        onProgress(event.payload);
+      });
+    }
+    try {
+      return await invoke('export_market_data', { request, archiveDir });
+    } finally {
+      unlisten?.();
+    }
+  }
+
+  async compactMarketData(tradingDay: string, archiveDir?: string): Promise<CompactionReport> {
+    return invoke('compact_market_data', { tradingDay, archiveDir });
+  }
+
+  // Long-running Tasks
+  async listTasks(): Promise<TaskInfo[]> {
+    return invoke('list_tasks');
+  }
+
+  async cancelTask(taskId: string): Promise<void> {
+    return invoke('cancel_task', { taskId });
+  }
+
+  async onTaskProgress(callback: (task: TaskInfo) => void): Promise<UnlistenFn> {
+    return listen<TaskInfo>('task://progress', (event) => callback(event.payload));
+  }
+
+  async getContinuousKline(request: ContinuousKlineRequest, archiveDir?: string): Promise<ContinuousKline> {
+    return invoke('ctp_get_continuous_kline', { request, archiveDir });
+  }
+
+  // Unified Logging
+  // 应用健康检查
+  async getAppHealth(): Promise<AppHealth> {
+    return invoke('get_app_health');
+  }
+
+  async setHealthThresholds(thresholds: HealthThresholds): Promise<void> {
+    return invoke('set_health_thresholds', { thresholds });
+  }
+
+  // 生成问题反馈支持包（zip），返回路径和清单
+  async generateSupportBundle(options?: SupportBundleOptions): Promise<SupportBundleSummary> {
+    return invoke('generate_support_bundle', { options });
+  }
+
+  // 指标推送：返回完整帧，之后只推送变化的部分，由调用方合并
+  async subscribeMetrics(
+    onFrame: (frame: MetricsFrame) => void,
+    intervalMs?: number
+  ): Promise<{ frame: MetricsFrame; unlisten: UnlistenFn }> {
+    const unlistenFrames = await getCurrentWebviewWindow().listen<MetricsFrame>('metrics-update', (event) => {
+      onFrame(event.payload);
+    });
+    const frame = await invoke<MetricsFrame>('subscribe_metrics', { intervalMs });
+    const unlisten = () => {
+      unlistenFrames();
+      invoke('unsubscribe_metrics').catch(() => undefined);
+    };
+    return { frame, unlisten };
+  }
+
+  async getMetricsSubscriptions(): Promise<MetricsSubscription[]> {
+    return invoke('get_metrics_subscriptions');
+  }
+
+  async logFrontendEvent(event: FrontendLogEvent): Promise<void> {
+    return invoke('log_frontend_event', {
+      event: { session_id: FRONTEND_SESSION_ID, timestamp: new Date().toISOString(), ...event },
+    });
+  }
 }
 
 // Singleton instance
@@ -67,7 +67,7 @@ export const useConnectionStore = create<ConnectionStore>((set, get) => ({
         reconnectAttempts: 0
       });
       
-      message.success(result);
+      message.success(result.message);
       
       // Auto-login if credentials are saved
       const { credentials } = get();
@@ -111,11 +111,13 @@ export const useConnectionStore = create<ConnectionStore>((set, get) => ({
       const service = getCtpService();
       const result = await service.login(credentials);
       
-      // Auto-confirm settlement
-      try {
-        await service.confirmSettlement();
-      } catch (error) {
-        console.warn('Settlement confirmation failed:', error);
+      // Auto-confirm settlement (skipped when the backend already confirmed it)
+      if (!result.settlement_confirmed && !result.degraded) {
+        try {
+          await service.confirmSettlement();
+        } catch (error) {
+          console.warn('Settlement confirmation failed:', error);
+        }
       }
       
       set({ 
@@ -125,7 +127,7 @@ export const useConnectionStore = create<ConnectionStore>((set, get) => ({
         userId: credentials.user_id
       });
       
-      message.success(result);
+      message.success(result.message);
     } catch (error: any) {
       set({ 
         connectionState: ConnectionState.Connected,
@@ -157,7 +159,7 @@ export const useConnectionStore = create<ConnectionStore>((set, get) => ({
         reconnectAttempts: 0
       });
       
-      message.info(result);
+      message.info(result.message);
     } catch (error: any) {
       message.error(`Disconnect failed: ${error.message}`);
       throw error;
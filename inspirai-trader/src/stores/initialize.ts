@@ -4,6 +4,7 @@ import { useTradingStore } from './trading';
 import { stateManager } from './stateManager';
 import { eventBus } from './eventBus';
 import { AppEventType } from '../types';
+import { getCtpService } from '../services/ctpService';
 
 /**
  * 初始化状态管理系统
@@ -53,6 +54,14 @@ const setupGlobalErrorHandling = (): void => {
   // 监听未捕获的Promise错误
   window.addEventListener('unhandledrejection', (event) => {
     console.error('未捕获的Promise错误:', event.reason);
+
+    getCtpService()
+      .logFrontendEvent({
+        level: 'Error',
+        message: `未捕获的Promise错误: ${event.reason}`,
+        stack: event.reason instanceof Error ? event.reason.stack : undefined,
+      })
+      .catch(() => {});
     
     eventBus.emit(AppEventType.ERROR_OCCURRED, {
       message: `未捕获的错误: ${event.reason}`,
@@ -66,6 +75,15 @@ const setupGlobalErrorHandling = (): void => {
   // 监听全局JavaScript错误
   window.addEventListener('error', (event) => {
     console.error('全局JavaScript错误:', event.error);
+
+    getCtpService()
+      .logFrontendEvent({
+        level: 'Error',
+        message: `JavaScript错误: ${event.message}`,
+        stack: event.error instanceof Error ? event.error.stack : undefined,
+        fields: { filename: event.filename, lineno: event.lineno, colno: event.colno },
+      })
+      .catch(() => {});
     
     eventBus.emit(AppEventType.ERROR_OCCURRED, {
       message: `JavaScript错误: ${event.message}`,
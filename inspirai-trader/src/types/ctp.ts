@@ -38,7 +38,7 @@ export interface OrderInput {
   price: number;
   volume: number;
   order_type: 'Limit' | 'Market' | 'Stop' | 'StopLimit';
-  time_condition: 'IOC' | 'GFS' | 'GFD' | 'GTD' | 'GTC' | 'GFA';
+  time_condition: 'IOC' | 'FAK' | 'FOK' | 'GFS' | 'GFD' | 'GTD' | 'GTC' | 'GFA';
   volume_condition: 'Any' | 'Min' | 'All';
   min_volume: number;
   contingent_condition: 'Immediately' | 'Touch' | 'TouchProfit';
@@ -112,6 +112,7 @@ export interface Position {
 // Account Types
 export interface AccountInfo {
   account_id: string;
+  currency_id: string;
   available: number;
   balance: number;
   margin: number;
@@ -210,6 +211,44 @@ export interface RiskParams {
   stop_loss_ratio: number;
   auto_take_profit: boolean;
   take_profit_ratio: number;
+  block_limit_locked?: boolean;
+}
+
+// 命名风控预设，end 早于 start 表示跨午夜
+export interface RiskPreset {
+  name: string;
+  description?: string;
+  params: RiskParams;
+}
+
+export interface PresetScheduleRule {
+  preset: string;
+  start: string;
+  end: string;
+  weekdays?: number[];
+  dates?: string[];
+}
+
+export interface RiskPresetConfig {
+  schedule_enabled: boolean;
+  default_preset: string | null;
+  schedule: PresetScheduleRule[];
+  presets: RiskPreset[];
+}
+
+// 参数值为 JSON 文本，持仓上限按 position_limit.<合约> 逐项列出
+export interface RiskParamChange {
+  field: string;
+  before: string | null;
+  after: string | null;
+}
+
+export interface PresetSwitch {
+  preset: string;
+  previous: string | null;
+  source: 'Manual' | 'Schedule';
+  switched_at: string;
+  changes: RiskParamChange[];
 }
 
 // Subscription Types
@@ -226,6 +265,1158 @@ export interface MarketDataFilter {
   product_classes?: string[];
 }
 
+// Command Response Types
+export interface ActionResult {
+  success: boolean;
+  message: string;
+}
+
+export interface ConnectResult {
+  environment: string;
+  connection_mode: 'full' | 'md_only' | 'td_only';
+  md_front_addr: string;
+  trader_front_addr: string;
+  state: unknown;
+  message: string;
+}
+
+export interface LoginResult {
+  user_id: string;
+  degraded: boolean;
+  settlement_confirmed: boolean;
+  message: string;
+}
+
+export interface StatusInfo {
+  state: unknown;
+  connected: boolean;
+  logged_in: boolean;
+  degraded: boolean;
+  connection_mode: 'full' | 'md_only' | 'td_only' | null;
+  subscribed_count: number;
+}
+
+export type SubscriptionOutcome =
+  | 'Subscribed'
+  | 'AlreadySubscribed'
+  | 'Unsubscribed'
+  | 'NotSubscribed'
+  | 'Failed';
+
+export interface InstrumentSubscriptionStatus {
+  instrument_id: string;
+  outcome: SubscriptionOutcome;
+  error?: string;
+}
+
+export interface SubscribeResult {
+  requested: number;
+  succeeded: number;
+  failed: number;
+  instruments: InstrumentSubscriptionStatus[];
+}
+
+export interface CancelOrderResult {
+  order_ref: string;
+  instrument_id: string;
+  message: string;
+}
+
+// Connection Quality
+export type QualityLevel = 'Good' | 'Degraded' | 'Poor' | 'Offline';
+
+export interface DisconnectRecord {
+  timestamp: string;
+  reason_code: number;
+  reason: string;
+}
+
+export interface RttStats {
+  samples: number;
+  last_ms: number;
+  min_ms: number;
+  max_ms: number;
+  avg_ms: number;
+}
+
+export interface LinkQuality {
+  connected: boolean;
+  connect_count: number;
+  reconnect_count: number;
+  disconnect_count: number;
+  recent_disconnects: DisconnectRecord[];
+  disconnect_reasons: Record<string, number>;
+  heartbeat_warnings: number;
+  last_heartbeat_lapse_secs: number | null;
+  rtt: RttStats;
+  longest_silence_ms: number;
+  current_silence_ms: number;
+}
+
+export interface ConnectionQualityReport {
+  overall: QualityLevel;
+  md_level: QualityLevel;
+  td_level: QualityLevel;
+  md: LinkQuality;
+  td: LinkQuality;
+  generated_at: string;
+}
+
+// 报单预校验
+export type IssueSeverity = 'Warning' | 'Error';
+
+export interface ValidationIssue {
+  severity: IssueSeverity;
+  field: string;
+  message: string;
+}
+
+export interface OrderValidationResult {
+  valid: boolean;
+  normalized: OrderInput;
+  estimated_notional: number | null;
+  estimated_margin: number | null;
+  issues: ValidationIssue[];
+}
+
+// 最大可开手数
+export interface MaxOpenVolume {
+  instrument_id: string;
+  direction: 'Buy' | 'Sell';
+  price: number;
+  margin_per_lot: number;
+  utilization_cap: number;
+  margin_budget: number;
+  max_volume: number;
+  capped_by: string | null;
+}
+
+// 下单预览，released_margin 为平仓释放的保证金，available_after 不含平仓盈亏
+export interface PositionSides {
+  long: number;
+  short: number;
+}
+
+export interface OrderPreview {
+  instrument_id: string;
+  direction: 'Buy' | 'Sell';
+  offset: 'Open' | 'Close' | 'CloseToday' | 'CloseYesterday';
+  volume: number;
+  price: number;
+  notional: number;
+  required_margin: number;
+  released_margin: number;
+  estimated_fee: number | null;
+  available_before: number;
+  available_after: number;
+  position_before: PositionSides;
+  position_after: PositionSides;
+  issues: ValidationIssue[];
+}
+
+// 保证金压力测试，product_id 为空表示整体冲击
+export interface PriceShock {
+  product_id?: string | null;
+  change: number;
+}
+
+export interface StressTestScenario {
+  name?: string | null;
+  shocks: PriceShock[];
+  margin_call_ratio?: number;
+}
+
+export interface StressedPosition {
+  instrument_id: string;
+  product_id: string;
+  direction: 'Long' | 'Short';
+  volume: number;
+  price: number;
+  shocked_price: number;
+  price_change: number;
+  pnl: number;
+  margin: number;
+  stressed_margin: number;
+}
+
+export interface StressTestResult {
+  scenario: StressTestScenario;
+  balance: number;
+  pnl: number;
+  equity: number;
+  margin: number;
+  stressed_margin: number;
+  risk_ratio: number | null;
+  margin_call: boolean;
+  shortfall: number;
+  positions: StressedPosition[];
+  warnings: string[];
+}
+
+// 对冲建议，仅供参考，不会自动下单
+export interface ExposureCap {
+  key: string;
+  max_net_notional?: number | null;
+  max_net_volume?: number | null;
+  hedge_instrument?: string | null;
+}
+
+export interface HedgeConfig {
+  caps: ExposureCap[];
+  default_max_net_notional?: number | null;
+}
+
+export interface NetExposure {
+  key: string;
+  net_volume: number;
+  net_notional: number;
+  gross_notional: number;
+  max_net_notional: number | null;
+  max_net_volume: number | null;
+  over_cap: boolean;
+}
+
+export interface HedgeSuggestion {
+  key: string;
+  instrument_id: string;
+  direction: 'Buy' | 'Sell';
+  offset_flag: 'Open' | 'Close' | 'CloseToday' | 'CloseYesterday';
+  volume: number;
+  price: number;
+  resulting_net_notional: number;
+  reason: string;
+}
+
+export interface HedgeReport {
+  generated_at: string;
+  exposures: NetExposure[];
+  suggestions: HedgeSuggestion[];
+  warnings: string[];
+}
+
+// 临近到期持仓的移仓
+export interface RolloverConfig {
+  days_before_expiry: number;
+  price_ticks: number;
+}
+
+export interface RolloverPlan {
+  product_id: string;
+  direction: 'Long' | 'Short';
+  from_instrument: string;
+  to_instrument: string;
+  volume: number;
+  expire_date: string;
+  days_to_expiry: number;
+  close_orders: OrderInput[];
+  open_order: OrderInput;
+  estimated_spread: number;
+  price_ticks: number;
+}
+
+export interface RolloverReport {
+  plans: RolloverPlan[];
+  warnings: string[];
+}
+
+export type RolloverStatus = 'Closing' | 'Opening' | 'Completed' | 'Failed' | 'Cancelled';
+
+// 结束后作为关联的平仓、开仓成交对保存
+export interface RolloverExecution {
+  id: string;
+  plan: RolloverPlan;
+  status: RolloverStatus;
+  started_at: string;
+  finished_at: string | null;
+  close_order_refs: string[];
+  open_order_refs: string[];
+  closed_volume: number;
+  opened_volume: number;
+  close_avg_price: number;
+  open_avg_price: number;
+  close_trade_ids: string[];
+  open_trade_ids: string[];
+  message: string | null;
+}
+
+// 快捷键交易
+export type HotkeyAction =
+  | { action: 'buyAtCounterparty'; instrument_id: string; volume: number }
+  | { action: 'sellAtCounterparty'; instrument_id: string; volume: number }
+  | { action: 'closeLastPosition'; instrument_id: string | null }
+  | { action: 'cancelNearestOrder'; instrument_id: string };
+
+export interface HotkeyOutcome {
+  action: HotkeyAction;
+  order_refs: string[];
+  cancelled: string[];
+  message: string;
+}
+
+export interface HotkeyStatus {
+  enabled: boolean;
+  max_actions: number;
+  window_ms: number;
+  max_volume: number;
+  recent_actions: number;
+  last_opened: string | null;
+}
+
+// 账户活动时间线
+export type TimelineKind = 'Order' | 'Trade' | 'Transfer' | 'Connection' | 'Risk';
+
+export interface TimelineEntry {
+  seq: number;
+  account_id: string;
+  timestamp: string;
+  kind: TimelineKind;
+  title: string;
+  detail: string | null;
+  instrument_id: string | null;
+  reference: string | null;
+}
+
+export interface TimelineQuery {
+  before?: number;
+  limit?: number;
+  kinds?: TimelineKind[];
+  instrument_id?: string;
+}
+
+export interface TimelinePage {
+  entries: TimelineEntry[];
+  next_before: number | null;
+  has_more: boolean;
+}
+
+// 拒单熔断
+export interface RejectionReason {
+  reason: string;
+  count: number;
+}
+
+export interface RejectionAlert {
+  source: string;
+  rejections: number;
+  window_secs: number;
+  reasons: RejectionReason[];
+  instruments: string[];
+  tripped_at: string;
+}
+
+export interface SourceBreakerStatus {
+  source: string;
+  paused: boolean;
+  recent_rejections: number;
+  alert: RejectionAlert | null;
+}
+
+// 多窗口事件桥
+export type EventTopic = 'Session' | 'MarketData' | 'Orders' | 'Trades' | 'Account' | 'Positions' | 'System';
+
+export interface WindowSubscription {
+  topics?: EventTopic[];
+  instruments?: string[] | null;
+  /** 以观察模式注册：接收全部数据，交易命令在后端被拒绝 */
+  observer?: boolean;
+}
+
+export interface BridgeSnapshot {
+  state: string | { Error: string } | null;
+  subscriptions: string[];
+  account: AccountInfo | null;
+  positions: Position[];
+  orders: OrderStatus[];
+  trades: Trade[];
+  last_ticks: MarketData[];
+  /** 当前窗口处于观察模式，需显示只读横幅 */
+  observer: boolean;
+}
+
+// 观察模式变化，推送给对应窗口
+export interface ObserverModeChange {
+  label: string;
+  observer: boolean;
+}
+
+// 事件桥推送的原始事件（serde tag/content 格式）
+export interface BridgeEvent<T = unknown> {
+  type: string;
+  data?: T;
+}
+
+// 操作录制与回放
+export interface ActionRecordingStatus {
+  enabled: boolean;
+  file: string | null;
+  recorded: number;
+}
+
+export type ReplayStepStatus = 'Executed' | 'Skipped' | 'Failed';
+
+export interface ReplayStep {
+  seq: number;
+  command: string;
+  status: ReplayStepStatus;
+  message: string | null;
+}
+
+export interface ReplayReport {
+  file: string;
+  executed: number;
+  skipped: number;
+  failed: number;
+  steps: ReplayStep[];
+}
+
+// 主备实例协调
+export type InstanceRole = 'Active' | 'Standby';
+
+export interface LeaseInfo {
+  instance_id: string;
+  pid: number;
+  acquired_at: string;
+  heartbeat_at: string;
+  handover_to: string | null;
+  handover_at: string | null;
+}
+
+export interface InstanceStatus {
+  instance_id: string;
+  role: InstanceRole;
+  lease: LeaseInfo | null;
+}
+
+// 每日风险报告
+export interface InstrumentRisk {
+  instrument_id: string;
+  product_id: string;
+  exchange_id: string;
+  net_volume: number;
+  gross_volume: number;
+  last_price: number;
+  mark_source: 'settlement' | 'daily_close' | 'average_cost';
+  settlement_pnl: number | null;
+  net_notional: number;
+  gross_notional: number;
+  margin: number;
+  atr: number | null;
+  var: number | null;
+}
+
+export interface ConcentrationEntry {
+  key: string;
+  gross_notional: number;
+  net_notional: number;
+  share: number;
+}
+
+export interface StressScenario {
+  price_move: number;
+  pnl: number;
+  equity: number;
+  margin: number;
+  risk_ratio: number | null;
+}
+
+export interface DailyRiskReport {
+  trading_day: string;
+  generated_at: string;
+  balance: number;
+  margin: number;
+  gross_notional: number;
+  net_notional: number;
+  total_var: number;
+  instruments: InstrumentRisk[];
+  by_product: ConcentrationEntry[];
+  by_exchange: ConcentrationEntry[];
+  stress: StressScenario[];
+  warnings: string[];
+}
+
+// 盘口热力图列：sizes[i] 对应 base_price + i * price_tick，买盘为正、卖盘为负
+export interface HeatmapColumn {
+  instrument_id: string;
+  timestamp_ms: number;
+  update_time: string;
+  last_price: number;
+  base_price: number;
+  price_tick: number;
+  sizes: number[];
+}
+
+// 订单流：按此前盘口推断主动成交方向，imbalance 为窗口内 (买 - 卖) / (买 + 卖)
+export type AggressorSide = 'Buy' | 'Sell' | 'Neutral';
+
+export interface OrderFlowTick {
+  instrument_id: string;
+  timestamp_ms: number;
+  update_time: string;
+  update_millisec: number;
+  price: number;
+  volume: number;
+  side: AggressorSide;
+  window_buy_volume: number;
+  window_sell_volume: number;
+  imbalance: number;
+  cumulative_delta: number;
+  large_order: boolean;
+}
+
+export interface OrderFlowSnapshot {
+  instrument_id: string;
+  last: OrderFlowTick | null;
+  session_buy_volume: number;
+  session_sell_volume: number;
+  session_neutral_volume: number;
+  large_orders: OrderFlowTick[];
+}
+
+// 市场概览
+export interface InstrumentMove {
+  instrument_id: string;
+  last_price: number;
+  change_percent: number;
+  volume: number;
+  turnover: number;
+}
+
+export interface ExchangeSummary {
+  exchange_id: string;
+  instrument_count: number;
+  total_volume: number;
+  total_turnover: number;
+  up_count: number;
+  down_count: number;
+  flat_count: number;
+  top_gainers: InstrumentMove[];
+  top_losers: InstrumentMove[];
+}
+
+export interface MarketOverviewSnapshot {
+  exchanges: ExchangeSummary[];
+  instrument_count: number;
+  computed_at: string | null;
+}
+
+// 死人开关
+export interface DeadManConfig {
+  timeout: { secs: number; nanos: number };
+  flatten: boolean;
+}
+
+export interface DeadManStatus {
+  armed: boolean;
+  config: DeadManConfig;
+  last_source: string | null;
+  since_heartbeat_ms: number | null;
+  triggered_at: string | null;
+}
+
+export interface DeadManTrigger {
+  triggered_at: string;
+  silent_ms: number;
+  last_source: string | null;
+  flatten: boolean;
+}
+
+export interface DeadManReport {
+  trigger: DeadManTrigger;
+  cancelled: string[];
+  flatten_orders: string[];
+  errors: string[];
+}
+
+// 涨跌停状态
+export type LimitState =
+  | 'Normal'
+  | 'AtUpperLimit'
+  | 'LimitUpLocked'
+  | 'AtLowerLimit'
+  | 'LimitDownLocked';
+
+export interface LimitStatus {
+  upper_limit_price: number;
+  lower_limit_price: number;
+  state: LimitState;
+  pinned_since: string | null;
+}
+
+// 拒单说明
+export type ErrorCategory =
+  | 'InsufficientFunds'
+  | 'InsufficientPosition'
+  | 'PriceLimit'
+  | 'MarketClosed'
+  | 'SelfTrade'
+  | 'Instrument'
+  | 'Permission'
+  | 'OrderField'
+  | 'Session'
+  | 'Throttled'
+  | 'Other';
+
+export interface ErrorExplanation {
+  code: number;
+  category: ErrorCategory;
+  title: string;
+  explanation: string;
+  suggestions: string[];
+  broker_hint: string | null;
+}
+
+// 交易绩效统计
+export type PairingMethod = 'Fifo' | 'Lifo';
+
+export interface RoundTrip {
+  instrument_id: string;
+  direction: 'Long' | 'Short';
+  volume: number;
+  open_price: number;
+  close_price: number;
+  open_trade_id: string;
+  close_trade_id: string;
+  opened_at: string;
+  closed_at: string;
+  holding_secs: number;
+  pnl: number;
+  fees: number;
+  net_pnl: number;
+  mae: number;
+  mfe: number;
+  strategy: string | null;
+  tags: Record<string, string>;
+}
+
+export interface AnalyticsQuery {
+  strategy?: string;
+  instrument_id?: string;
+  from?: string;
+  to?: string;
+}
+
+export interface HoldingBucket {
+  label: string;
+  max_secs: number | null;
+  count: number;
+}
+
+export interface PerformanceStats {
+  key: string;
+  round_trips: number;
+  wins: number;
+  losses: number;
+  win_rate: number;
+  total_pnl: number;
+  total_fees: number;
+  avg_win: number;
+  avg_loss: number;
+  profit_factor: number | null;
+  avg_holding_secs: number;
+  holding_distribution: HoldingBucket[];
+  avg_mae: number;
+  avg_mfe: number;
+}
+
+export interface TradeAnalyticsReport {
+  overall: PerformanceStats;
+  by_strategy: PerformanceStats[];
+  by_instrument: PerformanceStats[];
+  round_trips: RoundTrip[];
+}
+
+// 盈亏热力图：按平仓时间的星期几（0 为周一）和小时汇总
+export interface HeatmapCell {
+  weekday: number;
+  hour: number;
+  round_trips: number;
+  wins: number;
+  total_pnl: number;
+  avg_pnl: number;
+}
+
+export interface PnlHeatmap {
+  key: string;
+  cells: HeatmapCell[];
+  best: HeatmapCell | null;
+  worst: HeatmapCell | null;
+}
+
+export interface PnlHeatmapReport {
+  utc_offset_hours: number;
+  overall: PnlHeatmap;
+  by_strategy: PnlHeatmap[];
+  by_instrument: PnlHeatmap[];
+}
+
+// 研究数据导出
+export type StorageGranularity =
+  | 'Tick' | 'Bar1s' | 'Bar1m' | 'Bar5m' | 'Bar15m' | 'Bar30m' | 'Bar1h' | 'Bar1d';
+
+export type ExportFormat = 'Csv' | 'Parquet';
+
+export interface MarketDataExportRequest {
+  instruments: string[];
+  start_date: string; // YYYY-MM-DD
+  end_date: string;
+  granularity: StorageGranularity;
+  format: ExportFormat;
+  path: string;
+  start_time?: string; // HH:MM:SS
+  end_time?: string;
+}
+
+export interface ExportProgress {
+  instrument_id: string;
+  trading_day: string;
+  completed: number;
+  total: number;
+  rows_written: number;
+}
+
+export interface ExportSummary {
+  path: string;
+  rows: number;
+  trading_days: number;
+  missing_instruments: string[];
+}
+
+export interface CompactionReport {
+  trading_day: string | null;
+  instruments: number;
+  rows: number;
+  raw_bytes: number;
+  compressed_bytes: number;
+  downsampled_days: string[];
+  errors: string[];
+  /** 压缩后执行的数据保留清理 */
+  retention: RetentionReport | null;
+}
+
+// 长任务
+export type TaskState = 'Running' | 'Completed' | 'Failed' | 'Cancelled';
+
+export interface TaskInfo {
+  id: string;
+  kind: string;
+  label: string;
+  state: TaskState;
+  progress: number;
+  message: string | null;
+  error: string | null;
+  started_at: string;
+  finished_at: string | null;
+}
+
+// 前端日志上报
+export type LogLevel = 'Trace' | 'Debug' | 'Info' | 'Warn' | 'Error';
+
+export interface FrontendLogEvent {
+  level: LogLevel;
+  message: string;
+  component?: string;
+  stack?: string;
+  session_id?: string;
+  timestamp?: string;
+  fields?: Record<string, unknown>;
+}
+
+// 应用健康检查
+export type HealthLevel = 'Healthy' | 'Degraded' | 'Unhealthy';
+
+export interface Threshold {
+  degraded: number;
+  unhealthy: number;
+}
+
+export interface HealthThresholds {
+  event_backlog: Threshold;
+  log_queue: Threshold;
+  log_disk_bytes: Threshold;
+  log_errors: Threshold;
+  storage_errors: Threshold;
+  task_stale_factor: number;
+}
+
+export interface CtpAccountHealth {
+  account_id: string;
+  broker_id: string;
+  state: string | { Error: string };
+  session: unknown;
+  level: HealthLevel;
+  issues: string[];
+}
+
+export interface EventLag {
+  backlog: number;
+  max_backlog: number;
+  dispatched: number;
+  last_dispatch_at: string | null;
+}
+
+export interface StorageErrorStats {
+  store: string;
+  errors: number;
+  last_error: string | null;
+  last_error_at: string | null;
+}
+
+export interface TaskHealth {
+  name: string;
+  state: 'Running' | 'Finished' | 'Died';
+  started_at: string;
+  last_beat: string;
+  expected_interval_ms: number | null;
+  level: HealthLevel;
+  issues: string[];
+}
+
+export interface AppHealth {
+  status: HealthLevel;
+  checked_at: string;
+  ctp: CtpAccountHealth[];
+  events: { lag: EventLag | null; level: HealthLevel; issues: string[] };
+  logging: {
+    initialized: boolean;
+    queue_size: number;
+    disk_usage_bytes: number;
+    dropped_total: number;
+    error_count: number;
+    level: HealthLevel;
+    issues: string[];
+  };
+  storage: { stores: StorageErrorStats[]; total_errors: number; level: HealthLevel; issues: string[] };
+  tasks: TaskHealth[];
+  thresholds: HealthThresholds;
+}
+
+// 问题反馈支持包
+export interface SupportBundleOptions {
+  max_bytes?: number;
+  log_hours?: number;
+  output_dir?: string;
+}
+
+export interface BundleFile {
+  path: string;
+  bytes: number;
+  truncated: boolean;
+}
+
+export interface SupportBundleSummary {
+  path: string;
+  size_bytes: number;
+  manifest: {
+    created_at: string;
+    app_version: string;
+    max_bytes: number;
+    total_bytes: number;
+    files: BundleFile[];
+    skipped: string[];
+  };
+}
+
+// 指标推送：字段为空表示相对上一帧未变化
+export interface LoggingMetrics {
+  logs_written_total: number;
+  logs_dropped_total: number;
+  success_rate: number;
+  average_latency_ms: number;
+  p95_latency_ms: number;
+  queue_size: number;
+  disk_usage_bytes: number;
+  error_count: number;
+}
+
+export interface TradingMetrics {
+  state: string | { Error: string };
+  degraded: boolean;
+  subscribed_instruments: number;
+  connection_quality: QualityLevel;
+  td_rtt_avg_ms: number;
+  event_backlog: number;
+  events_dispatched: number;
+}
+
+export interface MetricsFrame {
+  seq: number;
+  timestamp: string | null;
+  logging: LoggingMetrics | null;
+  trading: TradingMetrics | null;
+}
+
+export interface MetricsSubscription {
+  window: string;
+  interval_ms: number;
+}
+
+// 外部 Webhook 通知
+export type WebhookEventKind = 'disconnected' | 'login_failed' | 'risk_halt' | 'kill_switch' | 'daily_report' | 'funds_anomaly';
+
+export interface WebhookEndpoint {
+  id: string;
+  url: string;
+  /** 签名密钥，为空时不签名 */
+  secret: string | null;
+  /** 订阅的事件，为空表示全部 */
+  events: WebhookEventKind[];
+  enabled: boolean;
+}
+
+export interface WebhookConfig {
+  endpoints: WebhookEndpoint[];
+  max_attempts: number;
+  initial_backoff_ms: number;
+  max_backoff_ms: number;
+  timeout_secs: number;
+}
+
+export interface WebhookDelivery {
+  payload_id: string;
+  endpoint_id: string;
+  kind: WebhookEventKind;
+  attempt: number;
+  status: 'Delivered' | 'Retrying' | 'Failed';
+  http_status: number | null;
+  error: string | null;
+  at: string;
+}
+
+// 群机器人通知
+export type NotificationKind = 'order_filled' | 'pnl_threshold' | 'connection_lost' | 'test';
+
+export type ChannelTarget =
+  | { type: 'dingtalk'; webhook_url: string; secret?: string | null }
+  | { type: 'feishu'; webhook_url: string; secret?: string | null }
+  | { type: 'telegram'; bot_token: string; chat_id: string; api_base?: string | null };
+
+export interface NotificationChannel {
+  id: string;
+  target: ChannelTarget;
+  events: NotificationKind[];
+  rate_limit_per_minute: number;
+  enabled: boolean;
+}
+
+export interface NotificationTemplates {
+  order_filled: string;
+  pnl_threshold: string;
+  connection_lost: string;
+}
+
+export interface NotifierConfig {
+  channels: NotificationChannel[];
+  templates: NotificationTemplates;
+  pnl_thresholds: number[];
+}
+
+export interface NotificationRecord {
+  channel_id: string;
+  kind: NotificationKind;
+  status: 'Sent' | 'RateLimited' | 'Failed';
+  text: string;
+  error: string | null;
+  at: string;
+}
+
+// 工作区快照：state 为前端自定义 JSON，后端按配置档和名称保存多个版本
+export interface WorkspaceSnapshot<T = unknown> {
+  profile: string;
+  name: string;
+  version: number;
+  saved_at: string;
+  state: T;
+}
+
+export interface WorkspaceVersion {
+  version: number;
+  saved_at: string;
+  size_bytes: number;
+}
+
+export interface WorkspaceSummary {
+  name: string;
+  latest_version: number;
+  saved_at: string;
+  version_count: number;
+}
+
+// 本地备份：配置、数据目录和操作日志打包为 zip，清单含各文件 SHA-256
+export interface BackupConfig {
+  enabled: boolean;
+  target_dir: string;
+  sources: string[];
+  exclude: string[];
+  interval_hours: number;
+  keep: number;
+}
+
+export interface BackupInfo {
+  path: string;
+  created_at: string;
+  size_bytes: number;
+  file_count: number | null;
+}
+
+export interface RestoreReport {
+  backup: string;
+  restored_files: number;
+  pre_restore_backup: string | null;
+  // 正在使用的数据库，恢复内容重启后替换
+  deferred: string[];
+}
+
+// 数据保留策略，天数为 null 的类别不清理
+export type RetentionCategory = 'Logs' | 'Ticks' | 'Bars' | 'OrderHistory' | 'Journals';
+
+export interface RetentionPolicy {
+  enabled: boolean;
+  /** 各日志通道（app、ctp、trading 等）的保留天数 */
+  log_days: Record<string, number>;
+  tick_days: number | null;
+  bar_days: number | null;
+  order_history_days: number | null;
+  journal_days: number | null;
+  log_dir: string;
+  archive_dir: string;
+  order_history_dirs: string[];
+  journal_dirs: string[];
+}
+
+export interface RetentionItem {
+  category: RetentionCategory;
+  path: string;
+  trading_day: string | null;
+  bytes: number;
+  /** 按行裁剪的记录数，整文件删除时为 null */
+  records: number | null;
+}
+
+export interface RetentionReport {
+  dry_run: boolean;
+  generated_at: string;
+  items: RetentionItem[];
+  total_bytes: number;
+  errors: string[];
+}
+
+// 存储后端：sqlite 存报单成交，DuckDB 存 tick/K 线供分析查询，修改后重启生效
+export type StorageBackendKind = 'sqlite' | 'duckdb';
+
+export interface StorageConfig {
+  orders_backend: StorageBackendKind;
+  market_data_backend: StorageBackendKind;
+  sqlite_path: string;
+  duckdb_path: string;
+}
+
+// CTP 请求与回调报文日志，密码类字段完全遮盖，账号类字段只保留首尾两位
+export interface WireLogConfig {
+  enabled: boolean;
+  capacity: number;
+  write_file: boolean;
+  dir: string;
+  sample_above_per_second: number;
+  sample_every: number;
+  sampled_callbacks: string[];
+  redact_fields: string[];
+}
+
+export type WireDirection = 'Request' | 'Callback';
+
+export interface WireRecord {
+  seq: number;
+  timestamp: string;
+  direction: WireDirection;
+  name: string;
+  request_id: number | null;
+  payload: unknown;
+}
+
+export interface WireLogStats {
+  recorded: number;
+  sampled_out: number;
+  write_errors: number;
+  buffered: number;
+}
+
+// 资金曲线异常：无法解释的权益下降、可用资金为负
+export interface FundsMonitorConfig {
+  min_unexplained_drop: number;
+  unexplained_drop_ratio: number;
+}
+
+export type FundsAnomalyKind = 'unexplained_drop' | 'negative_available';
+
+export interface ReconciliationSummary {
+  started_at: string;
+  finished_at: string;
+  orders_checked: number;
+  orders_updated: string[];
+  orders_adopted: string[];
+  orders_marked_unknown: string[];
+  trades_added: number;
+  position_adjustments: {
+    instrument_id: string;
+    direction: 'Long' | 'Short';
+    local_volume: number;
+    remote_volume: number;
+  }[];
+}
+
+export interface FundsAnomaly {
+  kind: FundsAnomalyKind;
+  account_id: string;
+  detected_at: string;
+  previous_balance: number;
+  balance: number;
+  explained_change: number;
+  unexplained_change: number;
+  available: number;
+  fills_since_last: number;
+  transfers_since_last: number;
+  message: string;
+  reconciliation: ReconciliationSummary | null;
+}
+
+// 连续合约 K 线
+export type AdjustmentMethod = 'None' | 'BackAdjusted' | 'RatioAdjusted';
+
+export interface ContinuousKlineRequest {
+  contracts: string[];
+  start_date: string; // YYYY-MM-DD
+  end_date: string;
+  granularity: StorageGranularity;
+  adjustment?: AdjustmentMethod;
+}
+
+export interface ContinuousBar {
+  trading_day: string;
+  time: string;
+  instrument_id: string;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
+  turnover: number;
+  open_interest: number;
+}
+
+export interface RollEvent {
+  trading_day: string;
+  from: string;
+  to: string;
+  from_close: number;
+  to_close: number;
+}
+
+export interface ContinuousKline {
+  adjustment: AdjustmentMethod;
+  bars: ContinuousBar[];
+  rolls: RollEvent[];
+}
+
 // Event Types
 export type CtpEvent = 
   | { type: 'Connected' }
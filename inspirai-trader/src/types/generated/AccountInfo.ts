@@ -0,0 +1,54 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 账户信息
+ */
+export type AccountInfo = { 
+/**
+ * 账户代码
+ */
+account_id: string, 
+/**
+ * 币种，不同币种的资金不能直接相加
+ */
+currency_id: string, 
+/**
+ * 可用资金
+ */
+available: number, 
+/**
+ * 账户余额
+ */
+balance: number, 
+/**
+ * 保证金
+ */
+margin: number, 
+/**
+ * 冻结资金
+ */
+frozen_margin: number, 
+/**
+ * 冻结手续费
+ */
+frozen_commission: number, 
+/**
+ * 当前保证金总额
+ */
+curr_margin: number, 
+/**
+ * 手续费
+ */
+commission: number, 
+/**
+ * 平仓盈亏
+ */
+close_profit: number, 
+/**
+ * 持仓盈亏
+ */
+position_profit: number, 
+/**
+ * 风险度
+ */
+risk_ratio: number, };
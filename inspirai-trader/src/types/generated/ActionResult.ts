@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 通用操作结果
+ */
+export type ActionResult = { success: boolean, 
+/**
+ * 供界面展示的提示文本
+ */
+message: string, };
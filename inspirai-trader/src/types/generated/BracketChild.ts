@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BracketChildStatus } from "./BracketChildStatus";
+import type { BracketLeg } from "./BracketLeg";
+
+/**
+ * 止损或止盈子单
+ */
+export type BracketChild = { leg: BracketLeg, trigger_price: number, 
+/**
+ * 挂载数量，随入场成交增加
+ */
+volume: number, status: BracketChildStatus, 
+/**
+ * 触发后报出的平仓单引用
+ */
+order_ref: string | null, filled_volume: number, };
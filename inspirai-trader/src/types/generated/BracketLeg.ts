@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 括号单的止损、止盈腿
+ */
+export type BracketLeg = "stop_loss" | "take_profit";
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BracketChild } from "./BracketChild";
+import type { BracketStatus } from "./BracketStatus";
+import type { OrderDirection } from "./OrderDirection";
+
+/**
+ * 括号单：入场单加附带的止损、止盈条件单，两条腿互斥（OCO）
+ */
+export type BracketOrder = { id: string, instrument_id: string, direction: OrderDirection, entry_order_ref: string, entry_volume: number, entry_filled: number, 
+/**
+ * 入场单是否已结束（全部成交、撤单或拒单）
+ */
+entry_done: boolean, children: Array<BracketChild>, status: BracketStatus, created_at: string, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 括号单状态
+ */
+export type BracketStatus = "pending_entry" | "armed" | "exiting" | "closed" | "cancelled";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 策略熔断状态
+ */
+export type BreakerState = { "state": "Active" } | { "state": "Tripped", "detail": { reason: string, tripped_at: string, } };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 撤单结果
+ */
+export type CancelOrderResult = { order_ref: string, instrument_id: string, message: string, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClientState } from "./ClientState";
+import type { ConnectionMode } from "./ConnectionMode";
+import type { Environment } from "./Environment";
+
+/**
+ * 连接结果
+ */
+export type ConnectResult = { environment: Environment, connection_mode: ConnectionMode, md_front_addr: string, trader_front_addr: string, state: ClientState, message: string, };
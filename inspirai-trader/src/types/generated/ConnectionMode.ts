@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 连接模式
+ */
+export type ConnectionMode = "full" | "md_only" | "td_only";
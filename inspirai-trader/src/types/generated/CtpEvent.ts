@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountInfo } from "./AccountInfo";
+import type { BracketOrder } from "./BracketOrder";
+import type { FundsAnomaly } from "./FundsAnomaly";
+import type { LoginResponse } from "./LoginResponse";
+import type { MarketDataTick } from "./MarketDataTick";
+import type { OcoGroup } from "./OcoGroup";
+import type { OrderExpiry } from "./OrderExpiry";
+import type { OrderStatus } from "./OrderStatus";
+import type { Position } from "./Position";
+import type { ReconciliationSummary } from "./ReconciliationSummary";
+import type { RejectionAlert } from "./RejectionAlert";
+import type { RolloverExecution } from "./RolloverExecution";
+import type { StrategyStatus } from "./StrategyStatus";
+import type { TradeRecord } from "./TradeRecord";
+import type { TrailingStop } from "./TrailingStop";
+
+/**
+ * CTP 事件类型
+ */
+export type CtpEvent = { "type": "Connected" } | { "type": "Disconnected" } | { "type": "LoginRequired" } | { "type": "LoginSuccess", "data": LoginResponse } | { "type": "LoginFailed", "data": string } | { "type": "MarketData", "data": MarketDataTick } | { "type": "OrderUpdate", "data": OrderStatus } | { "type": "TradeUpdate", "data": TradeRecord } | { "type": "AccountUpdate", "data": AccountInfo } | { "type": "PositionUpdate", "data": Array<Position> } | { "type": "QueryAccountResult", "data": AccountInfo } | { "type": "QueryPositionsResult", "data": Array<Position> } | { "type": "QueryTradesResult", "data": Array<TradeRecord> } | { "type": "QueryOrdersResult", "data": Array<OrderStatus> } | { "type": "QuerySettlementResult", "data": string } | { "type": "SettlementRequired" } | { "type": "SettlementConfirmed" } | { "type": "DegradedModeEntered", "data": string } | { "type": "TraderRecovered" } | { "type": "ReconciliationCompleted", "data": ReconciliationSummary } | { "type": "StrategyCircuitBreakerTripped", "data": StrategyStatus } | { "type": "OrderRejectionBreakerTripped", "data": RejectionAlert } | { "type": "FundsAnomalyDetected", "data": FundsAnomaly } | { "type": "BracketUpdate", "data": BracketOrder } | { "type": "OcoGroupUpdate", "data": OcoGroup } | { "type": "TrailingStopUpdate", "data": TrailingStop } | { "type": "OrderExpired", "data": OrderExpiry } | { "type": "RolloverUpdate", "data": RolloverExecution } | { "type": "Error", "data": string };
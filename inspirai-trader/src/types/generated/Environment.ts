@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 环境类型枚举
+ */
+export type Environment = "simnow" | "tts" | "production";
@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FundsAnomalyKind } from "./FundsAnomalyKind";
+import type { ReconciliationSummary } from "./ReconciliationSummary";
+
+/**
+ * 资金异常告警
+ */
+export type FundsAnomaly = { kind: FundsAnomalyKind, account_id: string, detected_at: string, previous_balance: number, balance: number, 
+/**
+ * 可由平仓盈亏、持仓盈亏、手续费和出入金解释的变动
+ */
+explained_change: number, 
+/**
+ * 无法解释的变动，负数为下降
+ */
+unexplained_change: number, available: number, 
+/**
+ * 两次快照之间记录的成交笔数
+ */
+fills_since_last: number, 
+/**
+ * 两次快照之间记录的净出入金
+ */
+transfers_since_last: number, message: string, 
+/**
+ * 最近一次对账结果，便于排查是否有漏记成交
+ */
+reconciliation: ReconciliationSummary | null, };
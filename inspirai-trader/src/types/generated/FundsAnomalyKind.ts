@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 异常类型
+ */
+export type FundsAnomalyKind = "unexplained_drop" | "negative_available";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SubscriptionOutcome } from "./SubscriptionOutcome";
+
+/**
+ * 合约订阅状态
+ */
+export type InstrumentSubscriptionStatus = { instrument_id: string, outcome: SubscriptionOutcome, error: string | null, };
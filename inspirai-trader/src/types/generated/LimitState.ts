@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 合约涨跌停状态
+ */
+export type LimitState = "Normal" | "AtUpperLimit" | "LimitUpLocked" | "AtLowerLimit" | "LimitDownLocked";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LimitState } from "./LimitState";
+
+/**
+ * 合约涨跌停价与当前状态
+ */
+export type LimitStatus = { upper_limit_price: number, lower_limit_price: number, state: LimitState, 
+/**
+ * 进入当前非正常状态的时间
+ */
+pinned_since: string | null, };
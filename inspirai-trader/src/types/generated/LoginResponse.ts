@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 登录响应
+ */
+export type LoginResponse = { tradingDay: string, loginTime: string, brokerId: string, userId: string, systemName: string, frontId: number, sessionId: number, maxOrderRef: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 登录结果
+ */
+export type LoginResult = { user_id: string, 
+/**
+ * 交易端不可用，仅行情运行
+ */
+degraded: boolean, 
+/**
+ * 是否已自动确认结算单
+ */
+settlement_confirmed: boolean, message: string, };
@@ -0,0 +1,88 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LimitStatus } from "./LimitStatus";
+import type { TickTrace } from "./TickTrace";
+
+/**
+ * 行情数据
+ */
+export type MarketDataTick = { 
+/**
+ * 合约代码
+ */
+instrument_id: string, 
+/**
+ * 最新价
+ */
+last_price: number, 
+/**
+ * 成交量
+ */
+volume: number, 
+/**
+ * 成交额
+ */
+turnover: number, 
+/**
+ * 持仓量
+ */
+open_interest: number, 
+/**
+ * 买一价
+ */
+bid_price1: number, 
+/**
+ * 买一量
+ */
+bid_volume1: number, 
+/**
+ * 卖一价
+ */
+ask_price1: number, 
+/**
+ * 卖一量
+ */
+ask_volume1: number, 
+/**
+ * 更新时间
+ */
+update_time: string, 
+/**
+ * 更新毫秒
+ */
+update_millisec: number, 
+/**
+ * 涨跌幅
+ */
+change_percent: number, 
+/**
+ * 涨跌额
+ */
+change_amount: number, 
+/**
+ * 今开盘
+ */
+open_price: number, 
+/**
+ * 最高价
+ */
+highest_price: number, 
+/**
+ * 最低价
+ */
+lowest_price: number, 
+/**
+ * 昨收盘
+ */
+pre_close_price: number, 
+/**
+ * 涨跌停价与贴板/封板状态
+ */
+price_limit?: LimitStatus | null, 
+/**
+ * 链路追踪时间戳（仅在开启追踪时存在）
+ */
+trace?: TickTrace | null, 
+/**
+ * 行情来源，为空表示 CTP 行情
+ */
+source?: string | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OcoGroupStatus } from "./OcoGroupStatus";
+
+/**
+ * OCO（一撤其余）订单组：任一成员成交达到阈值后撤销其余成员
+ */
+export type OcoGroup = { id: string, order_ids: Array<string>, 
+/**
+ * 成员成交比例达到该值即触发，1.0 表示全部成交
+ */
+fill_threshold: number, status: OcoGroupStatus, 
+/**
+ * 触发的成员
+ */
+triggered_by: string | null, 
+/**
+ * 触发时仍在队列中、需撤销的成员
+ */
+cancel_order_ids: Array<string>, created_at: string, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * OCO 订单组状态
+ */
+export type OcoGroupStatus = "active" | "triggered" | "completed" | "expired" | "dissolved";
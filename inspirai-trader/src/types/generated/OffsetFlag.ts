@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 开平仓标志
+ */
+export type OffsetFlag = "Open" | "Close" | "CloseToday" | "CloseYesterday";
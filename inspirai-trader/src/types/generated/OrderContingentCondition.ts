@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 订单触发条件
+ */
+export type OrderContingentCondition = "Immediately" | "Touch" | "TouchProfit" | "ParkedOrder";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 订单到期撤单计划
+ */
+export type OrderExpiry = { order_id: string, instrument_id: string, expire_at: string, 
+/**
+ * 撤单原因，随撤单事件发布
+ */
+reason: string, };
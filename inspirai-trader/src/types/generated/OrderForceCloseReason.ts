@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 强平原因
+ */
+export type OrderForceCloseReason = "NotForceClose" | "LackDeposit" | "ClientOverPositionLimit" | "MemberOverPositionLimit" | "NotMultiple" | "Violation" | "Other";
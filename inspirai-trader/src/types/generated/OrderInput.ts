@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OrderInput = { instrument_id: string, direction: string, offset: string, price: number, volume: number, order_type: string, time_condition: string, volume_condition: string, min_volume: number, contingent_condition: string, stop_price: number, force_close_reason: string, is_auto_suspend: boolean, 
+/**
+ * 自定义标签（策略名、信号ID、下单来源等）
+ */
+tags: { [key in string]?: string }, };
@@ -0,0 +1,83 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OffsetFlag } from "./OffsetFlag";
+import type { OrderContingentCondition } from "./OrderContingentCondition";
+import type { OrderDirection } from "./OrderDirection";
+import type { OrderForceCloseReason } from "./OrderForceCloseReason";
+import type { OrderPriceType } from "./OrderPriceType";
+import type { OrderTimeCondition } from "./OrderTimeCondition";
+import type { OrderType } from "./OrderType";
+import type { OrderVolumeCondition } from "./OrderVolumeCondition";
+import type { TimeInForce } from "./TimeInForce";
+
+/**
+ * 订单请求
+ */
+export type OrderRequest = { 
+/**
+ * 合约代码
+ */
+instrument_id: string, 
+/**
+ * 订单引用
+ */
+order_ref: string, 
+/**
+ * 买卖方向
+ */
+direction: OrderDirection, 
+/**
+ * 开平仓标志
+ */
+offset_flag: OffsetFlag, 
+/**
+ * 价格
+ */
+price: number, 
+/**
+ * 数量
+ */
+volume: number, 
+/**
+ * 订单类型
+ */
+order_type: OrderType, 
+/**
+ * 价格类型
+ */
+price_type: OrderPriceType, 
+/**
+ * 时间条件
+ */
+time_condition: OrderTimeCondition, 
+/**
+ * 成交量条件
+ */
+volume_condition: OrderVolumeCondition, 
+/**
+ * 最小成交量
+ */
+min_volume: number, 
+/**
+ * 触发条件
+ */
+contingent_condition: OrderContingentCondition, 
+/**
+ * 止损价
+ */
+stop_price: number, 
+/**
+ * 强平原因
+ */
+force_close_reason: OrderForceCloseReason, 
+/**
+ * 自动挂起标志
+ */
+is_auto_suspend: boolean, 
+/**
+ * 报单有效期，设置后覆盖时间条件和成交量条件
+ */
+time_in_force?: TimeInForce | null, 
+/**
+ * 自定义标签，随成交传递
+ */
+tags?: { [key in string]?: string }, };
@@ -0,0 +1,105 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OffsetFlag } from "./OffsetFlag";
+import type { OrderDirection } from "./OrderDirection";
+import type { OrderStatusType } from "./OrderStatusType";
+
+/**
+ * 订单状态
+ */
+export type OrderStatus = { 
+/**
+ * 订单引用
+ */
+order_ref: string, 
+/**
+ * 订单号
+ */
+order_id: string, 
+/**
+ * 合约代码
+ */
+instrument_id: string, 
+/**
+ * 买卖方向
+ */
+direction: OrderDirection, 
+/**
+ * 开平仓标志
+ */
+offset_flag: OffsetFlag, 
+/**
+ * 价格
+ */
+price: number, 
+/**
+ * 委托价格
+ */
+limit_price: number, 
+/**
+ * 数量
+ */
+volume: number, 
+/**
+ * 委托数量
+ */
+volume_total_original: number, 
+/**
+ * 成交数量
+ */
+volume_traded: number, 
+/**
+ * 剩余数量
+ */
+volume_left: number, 
+/**
+ * 剩余数量（兼容旧字段）
+ */
+volume_total: number, 
+/**
+ * 订单状态
+ */
+status: OrderStatusType, 
+/**
+ * 提交时间
+ */
+submit_time: string, 
+/**
+ * 委托时间
+ */
+insert_time: string, 
+/**
+ * 更新时间
+ */
+update_time: string, 
+/**
+ * 前置编号
+ */
+front_id: number, 
+/**
+ * 会话编号
+ */
+session_id: number, 
+/**
+ * 系统订单号
+ */
+order_sys_id: string, 
+/**
+ * 状态信息
+ */
+status_msg: string, 
+/**
+ * 是否本地订单
+ */
+is_local: boolean, 
+/**
+ * 冻结保证金
+ */
+frozen_margin: number, 
+/**
+ * 冻结手续费
+ */
+frozen_commission: number, 
+/**
+ * 下单时附带的标签
+ */
+tags?: { [key in string]?: string }, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 订单状态
+ */
+export type OrderStatusType = "Unknown" | "AllTraded" | "PartTradedQueueing" | "PartTradedNotQueueing" | "NoTradeQueueing" | "NoTradeNotQueueing" | "Canceled" | "Cancelled" | "Touched";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 订单成交量条件
+ */
+export type OrderVolumeCondition = "Any" | "Min" | "All";
@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PositionDirection } from "./PositionDirection";
+
+/**
+ * 持仓信息
+ */
+export type Position = { 
+/**
+ * 合约代码
+ */
+instrument_id: string, 
+/**
+ * 持仓方向
+ */
+direction: PositionDirection, 
+/**
+ * 总持仓
+ */
+total_position: number, 
+/**
+ * 昨持仓
+ */
+yesterday_position: number, 
+/**
+ * 今持仓
+ */
+today_position: number, 
+/**
+ * 开仓成本
+ */
+open_cost: number, 
+/**
+ * 持仓成本
+ */
+position_cost: number, 
+/**
+ * 占用保证金
+ */
+margin: number, 
+/**
+ * 浮动盈亏
+ */
+unrealized_pnl: number, 
+/**
+ * 平仓盈亏
+ */
+realized_pnl: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PositionDirection } from "./PositionDirection";
+
+/**
+ * 持仓调整记录
+ */
+export type PositionAdjustment = { instrument_id: string, direction: PositionDirection, 
+/**
+ * 本地持仓
+ */
+local_volume: number, 
+/**
+ * 柜台持仓
+ */
+remote_volume: number, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PositionAdjustment } from "./PositionAdjustment";
+
+/**
+ * 对账结果摘要
+ */
+export type ReconciliationSummary = { started_at: string, finished_at: string, 
+/**
+ * 柜台返回的报单数
+ */
+orders_checked: number, 
+/**
+ * 状态被柜台数据更新的报单
+ */
+orders_updated: Array<string>, 
+/**
+ * 本地不存在、从柜台补录的报单
+ */
+orders_adopted: Array<string>, 
+/**
+ * 本地活动但柜台不存在、标记为未知的报单
+ */
+orders_marked_unknown: Array<string>, 
+/**
+ * 补录的成交数
+ */
+trades_added: number, 
+/**
+ * 持仓调整
+ */
+position_adjustments: Array<PositionAdjustment>, };
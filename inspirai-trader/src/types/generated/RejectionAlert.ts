@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RejectionReason } from "./RejectionReason";
+
+/**
+ * 熔断告警
+ */
+export type RejectionAlert = { source: string, rejections: number, window_secs: number, 
+/**
+ * 按次数降序
+ */
+reasons: Array<RejectionReason>, instruments: Array<string>, tripped_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 拒单原因汇总
+ */
+export type RejectionReason = { reason: string, count: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RolloverPlan } from "./RolloverPlan";
+import type { RolloverStatus } from "./RolloverStatus";
+
+/**
+ * 一次移仓的执行进度，结束后作为关联的平仓、开仓成交对写入移仓记录
+ */
+export type RolloverExecution = { id: string, plan: RolloverPlan, status: RolloverStatus, started_at: string, finished_at: string | null, close_order_refs: Array<string>, open_order_refs: Array<string>, closed_volume: number, opened_volume: number, close_avg_price: number, open_avg_price: number, close_trade_ids: Array<string>, open_trade_ids: Array<string>, message: string | null, };
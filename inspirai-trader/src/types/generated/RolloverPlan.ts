@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OrderInput } from "./OrderInput";
+import type { PositionDirection } from "./PositionDirection";
+
+/**
+ * 移仓计划：平掉临近到期的合约，在主力合约开出同方向同手数的持仓
+ */
+export type RolloverPlan = { product_id: string, direction: PositionDirection, from_instrument: string, to_instrument: string, volume: number, expire_date: string, days_to_expiry: number, 
+/**
+ * 平仓腿，上期所、能源中心按今昨仓拆分
+ */
+close_orders: Array<OrderInput>, open_order: OrderInput, 
+/**
+ * 新合约与旧合约的价差（新 - 旧），按两条腿的报单价计算
+ */
+estimated_spread: number, 
+/**
+ * 报单时按最新对手价加该跳数重新定价
+ */
+price_ticks: number, };
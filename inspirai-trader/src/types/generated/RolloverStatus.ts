@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 移仓执行状态
+ */
+export type RolloverStatus = "Closing" | "Opening" | "Completed" | "Failed" | "Cancelled";
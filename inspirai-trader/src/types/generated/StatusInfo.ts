@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClientState } from "./ClientState";
+import type { ConnectionMode } from "./ConnectionMode";
+
+/**
+ * 客户端状态信息
+ */
+export type StatusInfo = { state: ClientState, connected: boolean, logged_in: boolean, degraded: boolean, 
+/**
+ * 未创建客户端时为 None
+ */
+connection_mode: ConnectionMode | null, subscribed_count: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个策略的风险预算
+ */
+export type StrategyBudget = { 
+/**
+ * 每日最多报单数
+ */
+max_orders_per_day: number, 
+/**
+ * 最大持仓手数（各合约净持仓绝对值之和）
+ */
+max_open_position: number, 
+/**
+ * 最大亏损（已实现 + 浮动），正数
+ */
+max_loss: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BreakerState } from "./BreakerState";
+import type { StrategyBudget } from "./StrategyBudget";
+
+/**
+ * 策略运行状态
+ */
+export type StrategyStatus = { name: string, budget: StrategyBudget, breaker: BreakerState, trading_day: string, orders_today: number, open_position: number, realized_pnl: number, unrealized_pnl: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstrumentSubscriptionStatus } from "./InstrumentSubscriptionStatus";
+
+/**
+ * 订阅/取消订阅结果
+ */
+export type SubscribeResult = { requested: number, succeeded: number, failed: number, instruments: Array<InstrumentSubscriptionStatus>, };
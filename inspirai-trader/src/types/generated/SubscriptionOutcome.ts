@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个合约的订阅结果
+ */
+export type SubscriptionOutcome = "Subscribed" | "AlreadySubscribed" | "Unsubscribed" | "NotSubscribed" | "Failed";
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单笔行情的链路时间戳（纳秒，0 表示未经过该阶段）
+ */
+export type TickTrace = { 
+/**
+ * SPI 回调进入时间
+ */
+spi_ns: number, 
+/**
+ * 转换完成时间
+ */
+converted_ns: number, 
+/**
+ * 管理器更新完成时间
+ */
+managed_ns: number, 
+/**
+ * 事件发出时间
+ */
+emitted_ns: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 报单有效期
+ *
+ * CTP 没有独立的有效期字段，FAK、FOK 由时间条件 IOC 与成交量条件组合表示
+ */
+export type TimeInForce = "GFD" | "FAK" | "FOK";
@@ -0,0 +1,48 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OffsetFlag } from "./OffsetFlag";
+import type { OrderDirection } from "./OrderDirection";
+
+/**
+ * 成交记录
+ */
+export type TradeRecord = { 
+/**
+ * 成交编号
+ */
+trade_id: string, 
+/**
+ * 订单号
+ */
+order_id: string, 
+/**
+ * 合约代码
+ */
+instrument_id: string, 
+/**
+ * 买卖方向
+ */
+direction: OrderDirection, 
+/**
+ * 开平仓标志
+ */
+offset_flag: OffsetFlag, 
+/**
+ * 成交价格
+ */
+price: number, 
+/**
+ * 成交数量
+ */
+volume: number, 
+/**
+ * 成交时间
+ */
+trade_time: string, 
+/**
+ * 交易所代码
+ */
+exchange_id: string, 
+/**
+ * 所属订单的标签
+ */
+tags?: { [key in string]?: string }, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 交易端重试结果
+ */
+export type TraderRetryResult = { 
+/**
+ * 第几次重试
+ */
+attempt: number, message: string, };
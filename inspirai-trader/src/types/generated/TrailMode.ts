@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 跟踪方式
+ */
+export type TrailMode = { "type": "ticks", ticks: number, } | { "type": "percent", percent: number, } | { "type": "atr", multiplier: number, period: number, };
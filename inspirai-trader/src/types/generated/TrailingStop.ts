@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrailingStopSpec } from "./TrailingStopSpec";
+import type { TrailingStopStatus } from "./TrailingStopStatus";
+
+/**
+ * 跟踪止损及其当前触发价
+ */
+export type TrailingStop = { id: string, spec: TrailingStopSpec, status: TrailingStopStatus, 
+/**
+ * 创建以来的最有利价（多头最高价、空头最低价）
+ */
+extreme_price: number | null, 
+/**
+ * 当前触发价，ATR 模式下尚无 ATR 时为空
+ */
+trigger_price: number | null, last_price: number | null, 
+/**
+ * ATR 模式使用的 ATR
+ */
+atr: number | null, 
+/**
+ * 触发后报出的平仓单引用
+ */
+order_ref: string | null, created_at: string, updated_at: string, triggered_at: string | null, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OffsetFlag } from "./OffsetFlag";
+import type { PositionDirection } from "./PositionDirection";
+import type { TrailMode } from "./TrailMode";
+
+/**
+ * 跟踪止损定义
+ */
+export type TrailingStopSpec = { instrument_id: string, 
+/**
+ * 被保护持仓的方向
+ */
+position_direction: PositionDirection, volume: number, mode: TrailMode, 
+/**
+ * 最小变动价位，用于跳数计算和触发价取整
+ */
+price_tick: number, 
+/**
+ * 触发后平仓委托价相对触发价的让价跳数
+ */
+slippage_ticks: number, offset_flag: OffsetFlag, };
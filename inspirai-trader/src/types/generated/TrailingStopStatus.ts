@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 跟踪止损状态
+ */
+export type TrailingStopStatus = "active" | "triggered" | "cancelled";
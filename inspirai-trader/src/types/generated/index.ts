@@ -0,0 +1,60 @@
+// 由 Rust 类型生成，请勿手动修改：bun run gen:types
+export type * from './AccountInfo';
+export type * from './ActionResult';
+export type * from './BracketChild';
+export type * from './BracketChildStatus';
+export type * from './BracketLeg';
+export type * from './BracketOrder';
+export type * from './BracketStatus';
+export type * from './BreakerState';
+export type * from './CancelOrderResult';
+export type * from './ClientState';
+export type * from './ConnectResult';
+export type * from './ConnectionMode';
+export type * from './CtpEvent';
+export type * from './Environment';
+export type * from './FundsAnomaly';
+export type * from './FundsAnomalyKind';
+export type * from './InstrumentSubscriptionStatus';
+export type * from './LimitState';
+export type * from './LimitStatus';
+export type * from './LoginResponse';
+export type * from './LoginResult';
+export type * from './MarketDataTick';
+export type * from './OcoGroup';
+export type * from './OcoGroupStatus';
+export type * from './OffsetFlag';
+export type * from './OrderContingentCondition';
+export type * from './OrderDirection';
+export type * from './OrderExpiry';
+export type * from './OrderForceCloseReason';
+export type * from './OrderInput';
+export type * from './OrderPriceType';
+export type * from './OrderRequest';
+export type * from './OrderStatus';
+export type * from './OrderStatusType';
+export type * from './OrderTimeCondition';
+export type * from './OrderType';
+export type * from './OrderVolumeCondition';
+export type * from './Position';
+export type * from './PositionAdjustment';
+export type * from './PositionDirection';
+export type * from './ReconciliationSummary';
+export type * from './RejectionAlert';
+export type * from './RejectionReason';
+export type * from './RolloverExecution';
+export type * from './RolloverPlan';
+export type * from './RolloverStatus';
+export type * from './StatusInfo';
+export type * from './StrategyBudget';
+export type * from './StrategyStatus';
+export type * from './SubscribeResult';
+export type * from './SubscriptionOutcome';
+export type * from './TickTrace';
+export type * from './TimeInForce';
+export type * from './TradeRecord';
+export type * from './TraderRetryResult';
+export type * from './TrailMode';
+export type * from './TrailingStop';
+export type * from './TrailingStopSpec';
+export type * from './TrailingStopStatus';
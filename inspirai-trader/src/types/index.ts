@@ -149,8 +149,18 @@ export interface CtpConfig {
   reconnect_interval_secs: number;
   /** 最大重连次数 */
   max_reconnect_attempts: number;
+  /** 市价单处理策略，未设置时交易所不支持市价单则按涨跌停价报单 */
+  market_order_policy?: MarketOrderPolicy;
 }
 
+/**
+ * 市价单处理策略
+ */
+export type MarketOrderPolicy =
+  | { type: 'Native' }
+  | { type: 'LimitPrice' }
+  | { type: 'Counterparty'; ticks: number; max_chases: number };
+
 // ============================================================================
 // 市场数据类型
 // ============================================================================